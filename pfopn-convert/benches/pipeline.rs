@@ -0,0 +1,136 @@
+//! Diff, merge, and transform timings on synthetic configs.
+//!
+//! Run with `cargo bench -p pfopn-convert`; compare against an earlier run
+//! with `-- --save-baseline before` and `-- --baseline before`. Each group
+//! runs at the sizes in [`SIZES`], generated by
+//! [`pfopn_convert::synthetic::SyntheticConfig`].
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use pfopn_convert::pipeline::ConversionPipeline;
+use pfopn_convert::profile::{load_baseline, seed_baseline_interfaces};
+use pfopn_convert::section::default_key_fields;
+use pfopn_convert::synthetic::SyntheticConfig;
+use pfopn_convert::verify::build_verify_report;
+use xml_diff_core::{diff_with_options, parse, write, DiffOptions, XmlNode};
+
+/// Small and large configs; rules dominate, the rest scale along.
+const SIZES: &[SyntheticConfig] = &[
+    SyntheticConfig {
+        rules: 1_000,
+        aliases: 100,
+        static_maps: 100,
+        certs: 10,
+    },
+    SyntheticConfig {
+        rules: 10_000,
+        aliases: 1_000,
+        static_maps: 1_000,
+        certs: 200,
+    },
+];
+
+fn label(size: &SyntheticConfig) -> String {
+    format!("{}r-{}a", size.rules, size.aliases)
+}
+
+fn opnsense_baseline(source: &XmlNode) -> XmlNode {
+    let (mut baseline, _) = load_baseline("opnsense", "26.1").expect("embedded baseline");
+    seed_baseline_interfaces(&mut baseline, source, &BTreeMap::new());
+    baseline
+}
+
+/// Copy of `config` with every 10th rule description changed.
+fn edited(config: &XmlNode) -> XmlNode {
+    let mut out = config.clone();
+    if let Some(filter) = out.children.iter_mut().find(|c| c.tag == "filter") {
+        for rule in filter.children.iter_mut().step_by(10) {
+            if let Some(descr) = rule.children.iter_mut().find(|c| c.tag == "descr") {
+                descr.text = Some("edited".to_string());
+            }
+        }
+    }
+    out
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for size in SIZES {
+        let xml = write(&size.pfsense()).expect("write");
+        group.throughput(Throughput::Bytes(xml.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(label(size)), &xml, |b, xml| {
+            b.iter(|| parse(black_box(xml)).expect("parse"))
+        });
+    }
+    group.finish();
+}
+
+fn bench_diff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diff");
+    let opts = DiffOptions {
+        key_fields: default_key_fields(),
+        ..DiffOptions::default()
+    };
+    for size in SIZES {
+        let left = size.pfsense();
+        let right = edited(&left);
+        group.bench_with_input(
+            BenchmarkId::new("keyed", label(size)),
+            &(left, right),
+            |b, (left, right)| b.iter(|| diff_with_options(black_box(left), right, &opts)),
+        );
+    }
+    group.finish();
+}
+
+fn bench_convert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convert");
+    group.sample_size(10);
+    for size in SIZES {
+        let source = size.pfsense();
+        let baseline = opnsense_baseline(&source);
+        let pipeline = ConversionPipeline::new("opnsense");
+        group.bench_with_input(
+            BenchmarkId::new("pfsense-to-opnsense", label(size)),
+            &(source, baseline),
+            |b, (source, baseline)| {
+                b.iter(|| pipeline.run(black_box(source), baseline).expect("convert"))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify");
+    group.sample_size(10);
+    for size in SIZES {
+        let source = size.pfsense();
+        let output = ConversionPipeline::new("opnsense")
+            .run(&source, &opnsense_baseline(&source))
+            .expect("convert")
+            .output;
+        group.bench_with_input(
+            BenchmarkId::new("pfsense", label(size)),
+            &source,
+            |b, root| b.iter(|| build_verify_report(black_box(root), None)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("opnsense", label(size)),
+            &output,
+            |b, root| b.iter(|| build_verify_report(black_box(root), None)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parse,
+    bench_diff,
+    bench_convert,
+    bench_verify
+);
+criterion_main!(benches);
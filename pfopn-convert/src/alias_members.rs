@@ -0,0 +1,392 @@
+//! Set semantics for alias member lists.
+//!
+//! Alias members are stored as one text field: pfSense `<address>` is
+//! space-separated with per-member descriptions in `<detail>` (`||`-separated,
+//! aligned with `<address>`), and OPNsense `<content>` is newline-separated.
+//! Compared as text, a reordered alias is a modification and a merged alias
+//! can list a member twice. This module compares and merges them as sets:
+//!
+//! - [`alias_member_changes`] lists added, removed, and re-described members
+//!   per alias found on both sides
+//! - [`collapse_alias_list_entries`] replaces the text-level `Modified`
+//!   entries for those fields with that list
+//! - [`union_alias_entries`] appends missing members without duplicating
+//!   existing ones
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use xml_diff_core::{DiffEntry, XmlNode};
+
+/// Alias containers, with whether members are in OPNsense `<content>`.
+const ALIAS_CONTAINERS: &[(&[&str], bool)] = &[
+    (&["aliases"], false),
+    (&["OPNsense", "Firewall", "Alias", "aliases"], true),
+];
+
+/// Alias fields that hold a member list.
+const LIST_FIELDS: &[&str] = &["address", "detail", "content"];
+
+/// Member differences of one alias present on both sides.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AliasMemberChange {
+    pub alias: String,
+    /// Members only on the right, in right-side order.
+    pub added: Vec<String>,
+    /// Members only on the left, in left-side order.
+    pub removed: Vec<String>,
+    /// Members on both sides whose description differs.
+    pub detail_changed: Vec<String>,
+}
+
+/// Member differences for every alias present in both trees, by name.
+pub fn alias_member_changes(left: &XmlNode, right: &XmlNode) -> Vec<AliasMemberChange> {
+    let right_aliases = aliases_by_name(right);
+    let mut out = Vec::new();
+    for (name, left_alias) in aliases_by_name(left) {
+        let Some(right_alias) = right_aliases.get(&name) else {
+            continue;
+        };
+        let left_members = members(left_alias);
+        let right_members = members(right_alias);
+        let left_map: BTreeMap<&str, &str> = left_members
+            .iter()
+            .map(|(m, d)| (m.as_str(), d.as_str()))
+            .collect();
+        let right_map: BTreeMap<&str, &str> = right_members
+            .iter()
+            .map(|(m, d)| (m.as_str(), d.as_str()))
+            .collect();
+        let change = AliasMemberChange {
+            alias: name,
+            added: right_members
+                .iter()
+                .filter(|(m, _)| !left_map.contains_key(m.as_str()))
+                .map(|(m, _)| m.clone())
+                .collect(),
+            removed: left_members
+                .iter()
+                .filter(|(m, _)| !right_map.contains_key(m.as_str()))
+                .map(|(m, _)| m.clone())
+                .collect(),
+            detail_changed: left_members
+                .iter()
+                .filter(|(m, d)| {
+                    right_map
+                        .get(m.as_str())
+                        .is_some_and(|right| right.trim() != d.trim())
+                })
+                .map(|(m, _)| m.clone())
+                .collect(),
+        };
+        if !change.added.is_empty()
+            || !change.removed.is_empty()
+            || !change.detail_changed.is_empty()
+        {
+            out.push(change);
+        }
+    }
+    out
+}
+
+/// Drop `Modified` entries for alias member fields from `entries` and
+/// return the set-level changes that replace them.
+///
+/// Reordered members no longer show up at all; real changes are listed once
+/// per alias instead of as a whole-field text change.
+pub fn collapse_alias_list_entries(
+    entries: &mut Vec<DiffEntry>,
+    left: &XmlNode,
+    right: &XmlNode,
+) -> Vec<AliasMemberChange> {
+    entries.retain(|entry| match entry {
+        DiffEntry::Modified { path, .. } => !is_alias_list_path(path),
+        _ => true,
+    });
+    alias_member_changes(left, right)
+}
+
+/// Append members of `incoming` missing from `existing`; true if any were
+/// added.
+///
+/// `platform` selects the member field: OPNsense `<content>` or pfSense
+/// `<address>`, whose `<detail>` descriptions are carried along.
+pub fn union_alias_entries(existing: &mut XmlNode, incoming: &XmlNode, platform: &str) -> bool {
+    if platform == "opnsense" {
+        let mut content: Vec<String> = lines(&text(existing, "content"));
+        let before = content.len();
+        for entry in lines(&text(incoming, "content")) {
+            if !content.contains(&entry) {
+                content.push(entry);
+            }
+        }
+        if content.len() == before {
+            return false;
+        }
+        set_text(existing, "content", &content.join("\n"));
+        return true;
+    }
+
+    let mut addresses: Vec<String> = text(existing, "address")
+        .split_whitespace()
+        .map(ToString::to_string)
+        .collect();
+    let mut details = split_details(&text(existing, "detail"), addresses.len());
+    let before = addresses.len();
+    let incoming_addresses: Vec<_> = text(incoming, "address")
+        .split_whitespace()
+        .map(ToString::to_string)
+        .collect();
+    let incoming_details = split_details(&text(incoming, "detail"), incoming_addresses.len());
+    for (address, detail) in incoming_addresses.into_iter().zip(incoming_details) {
+        if !addresses.contains(&address) {
+            addresses.push(address);
+            details.push(detail);
+        }
+    }
+    if addresses.len() == before {
+        return false;
+    }
+    set_text(existing, "address", &addresses.join(" "));
+    set_text(existing, "detail", &details.join("||"));
+    true
+}
+
+/// Union members of same-named aliases in `from` into `out`, container by
+/// container; returns the names of the aliases that gained members.
+pub fn union_matching_aliases(out: &mut XmlNode, from: &XmlNode) -> Vec<String> {
+    let mut merged = Vec::new();
+    for (path, opnsense) in ALIAS_CONTAINERS {
+        let Some(incoming) = find_path(from, path) else {
+            continue;
+        };
+        let Some(container) = find_path_mut(out, path) else {
+            continue;
+        };
+        let platform = if *opnsense { "opnsense" } else { "pfsense" };
+        for alias in container.children.iter_mut().filter(|c| c.tag == "alias") {
+            let name = text(alias, "name");
+            let Some(other) = incoming
+                .children
+                .iter()
+                .find(|c| c.tag == "alias" && text(c, "name") == name)
+            else {
+                continue;
+            };
+            if union_alias_entries(alias, other, platform) {
+                merged.push(name);
+            }
+        }
+    }
+    merged
+}
+
+/// Render alias member changes for terminal output.
+pub fn render_alias_changes(changes: &[AliasMemberChange]) -> String {
+    let mut lines = Vec::new();
+    for change in changes {
+        let mut parts: Vec<String> = change.added.iter().map(|m| format!("+{m}")).collect();
+        parts.extend(change.removed.iter().map(|m| format!("-{m}")));
+        if !change.detail_changed.is_empty() {
+            parts.push(format!(
+                "(description changed: {})",
+                change.detail_changed.join(", ")
+            ));
+        }
+        lines.push(format!("~ alias {}: {}", change.alias, parts.join(" ")));
+    }
+    lines.join("\n")
+}
+
+/// Whether `path` is a member field of a keyed `<alias>`
+/// (`...alias[name].address[1]`).
+fn is_alias_list_path(path: &str) -> bool {
+    let Some((parent, last)) = path.rsplit_once('.') else {
+        return false;
+    };
+    let field = last.split('[').next().unwrap_or(last);
+    LIST_FIELDS.contains(&field)
+        && parent
+            .rsplit_once('.')
+            .map_or(parent, |(_, segment)| segment)
+            .starts_with("alias[")
+}
+
+fn aliases_by_name(root: &XmlNode) -> BTreeMap<String, &XmlNode> {
+    let mut out = BTreeMap::new();
+    for (path, _) in ALIAS_CONTAINERS {
+        let Some(container) = find_path(root, path) else {
+            continue;
+        };
+        for alias in container.get_children("alias") {
+            let name = text(alias, "name");
+            if !name.is_empty() {
+                out.entry(name).or_insert(alias);
+            }
+        }
+    }
+    out
+}
+
+/// Members of an alias with their descriptions.
+fn members(alias: &XmlNode) -> Vec<(String, String)> {
+    if alias.get_child("content").is_some() {
+        return lines(&text(alias, "content"))
+            .into_iter()
+            .map(|m| (m, String::new()))
+            .collect();
+    }
+    let addresses: Vec<String> = text(alias, "address")
+        .split_whitespace()
+        .map(ToString::to_string)
+        .collect();
+    let details = split_details(&text(alias, "detail"), addresses.len());
+    addresses.into_iter().zip(details).collect()
+}
+
+fn find_path<'a>(root: &'a XmlNode, path: &[&str]) -> Option<&'a XmlNode> {
+    path.iter().try_fold(root, |node, tag| node.get_child(tag))
+}
+
+fn find_path_mut<'a>(root: &'a mut XmlNode, path: &[&str]) -> Option<&'a mut XmlNode> {
+    let mut node = root;
+    for tag in path {
+        node = node.children.iter_mut().find(|c| c.tag == *tag)?;
+    }
+    Some(node)
+}
+
+fn text(node: &XmlNode, tag: &str) -> String {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .unwrap_or("")
+        .to_string()
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+fn lines(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn split_details(value: &str, len: usize) -> Vec<String> {
+    let mut details: Vec<String> = if value.is_empty() {
+        Vec::new()
+    } else {
+        value.split("||").map(ToString::to_string).collect()
+    };
+    details.resize(len, String::new());
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::{diff_with_options, parse, DiffEntry, DiffOptions};
+
+    use super::{
+        alias_member_changes, collapse_alias_list_entries, union_matching_aliases,
+        AliasMemberChange,
+    };
+    use crate::section::default_key_fields;
+
+    fn diff(left: &[u8], right: &[u8]) -> (Vec<DiffEntry>, Vec<AliasMemberChange>) {
+        let left = parse(left).expect("left");
+        let right = parse(right).expect("right");
+        let opts = DiffOptions {
+            key_fields: default_key_fields(),
+            ..DiffOptions::default()
+        };
+        let mut entries = diff_with_options(&left, &right, &opts);
+        let changes = collapse_alias_list_entries(&mut entries, &left, &right);
+        (entries, changes)
+    }
+
+    #[test]
+    fn reordered_members_are_not_a_change() {
+        let (entries, changes) = diff(
+            br#"<pfsense><aliases><alias><name>web</name><address>10.0.0.1 10.0.0.2</address><detail>a||b</detail></alias></aliases></pfsense>"#,
+            br#"<pfsense><aliases><alias><name>web</name><address>10.0.0.2 10.0.0.1</address><detail>b||a</detail></alias></aliases></pfsense>"#,
+        );
+        assert!(entries.is_empty(), "{entries:?}");
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn lists_added_removed_and_redescribed_members() {
+        let (entries, changes) = diff(
+            br#"<pfsense><aliases><alias><name>web</name><address>10.0.0.1 10.0.0.2</address><detail>a||b</detail><descr>old</descr></alias></aliases></pfsense>"#,
+            br#"<pfsense><aliases><alias><name>web</name><address>10.0.0.3 10.0.0.1</address><detail>c||renamed</detail><descr>new</descr></alias></aliases></pfsense>"#,
+        );
+        let paths: Vec<String> = entries
+            .iter()
+            .map(|e| match e {
+                DiffEntry::Modified { path, .. } => path.clone(),
+                other => format!("{other:?}"),
+            })
+            .collect();
+        assert_eq!(paths, vec!["pfsense.aliases[1].alias[web].descr[1]"]);
+        assert_eq!(
+            changes,
+            vec![AliasMemberChange {
+                alias: "web".to_string(),
+                added: vec!["10.0.0.3".to_string()],
+                removed: vec!["10.0.0.2".to_string()],
+                detail_changed: vec!["10.0.0.1".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn compares_opnsense_content_lines() {
+        let left = parse(
+            br#"<opnsense><OPNsense><Firewall><Alias><aliases><alias uuid="1"><name>dns</name><content>1.1.1.1
+9.9.9.9</content></alias></aliases></Alias></Firewall></OPNsense></opnsense>"#,
+        )
+        .expect("left");
+        let right = parse(
+            br#"<opnsense><OPNsense><Firewall><Alias><aliases><alias uuid="1"><name>dns</name><content>9.9.9.9
+8.8.8.8
+1.1.1.1</content></alias></aliases></Alias></Firewall></OPNsense></opnsense>"#,
+        )
+        .expect("right");
+        let changes = alias_member_changes(&left, &right);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].added, vec!["8.8.8.8"]);
+        assert!(changes[0].removed.is_empty());
+    }
+
+    #[test]
+    fn union_adds_missing_members_once() {
+        let mut out = parse(
+            br#"<pfsense><aliases><alias><name>web</name><address>10.0.0.1 10.0.0.2</address><detail>a||b</detail></alias></aliases></pfsense>"#,
+        )
+        .expect("out");
+        let from = parse(
+            br#"<pfsense><aliases><alias><name>web</name><address>10.0.0.2 10.0.0.3</address><detail>b||c</detail></alias><alias><name>other</name><address>1.2.3.4</address></alias></aliases></pfsense>"#,
+        )
+        .expect("from");
+        assert_eq!(union_matching_aliases(&mut out, &from), vec!["web"]);
+        assert_eq!(
+            out.get_text(&["aliases", "alias", "address"]),
+            Some("10.0.0.1 10.0.0.2 10.0.0.3")
+        );
+        assert_eq!(
+            out.get_text(&["aliases", "alias", "detail"]),
+            Some("a||b||c")
+        );
+        assert!(union_matching_aliases(&mut out, &from).is_empty());
+    }
+}
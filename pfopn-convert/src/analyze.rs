@@ -1,5 +1,5 @@
 use serde::Serialize;
-use xml_diff_core::DiffEntry;
+use xml_diff_core::{DiffEntry, XmlNode};
 
 /// Recommended action for a diff entry.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -15,6 +15,78 @@ pub enum RecommendedAction {
     Noop,
 }
 
+/// A heuristic that shaped an entry's action or confidence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Heuristic {
+    /// Both sides are the same.
+    Identical,
+    /// Node exists on the left only.
+    MissingOnRight,
+    /// Node exists on the right only.
+    MissingOnLeft,
+    /// Element text differs.
+    ValueDiffers,
+    /// Attribute differs or exists on one side only.
+    AttributeDiffers,
+    /// Tags differ at the same position.
+    TagMismatch,
+    /// Ordered element changed position.
+    OrderChanged,
+    /// Unchanged node moved under another parent.
+    ContentIdentical,
+    /// Inserted or removed node has no content.
+    EmptyElement,
+    /// One side has no text; usually a default filled in or cleared.
+    EmptyOnOneSide,
+    /// Values differ only in case or whitespace.
+    CaseOrWhitespaceOnly,
+    /// Description field; nothing functional depends on it.
+    DescriptiveField,
+    /// Timestamp or revision bookkeeping.
+    TimestampField,
+    /// `uuid` attribute; regenerated on import.
+    GeneratedIdentifier,
+    /// OPNsense model `version` attribute; bumped by firmware updates.
+    ModelVersion,
+}
+
+impl Heuristic {
+    /// Snake-case name, as serialized.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Identical => "identical",
+            Self::MissingOnRight => "missing_on_right",
+            Self::MissingOnLeft => "missing_on_left",
+            Self::ValueDiffers => "value_differs",
+            Self::AttributeDiffers => "attribute_differs",
+            Self::TagMismatch => "tag_mismatch",
+            Self::OrderChanged => "order_changed",
+            Self::ContentIdentical => "content_identical",
+            Self::EmptyElement => "empty_element",
+            Self::EmptyOnOneSide => "empty_on_one_side",
+            Self::CaseOrWhitespaceOnly => "case_or_whitespace_only",
+            Self::DescriptiveField => "descriptive_field",
+            Self::TimestampField => "timestamp_field",
+            Self::GeneratedIdentifier => "generated_identifier",
+            Self::ModelVersion => "model_version",
+        }
+    }
+
+    /// Confidence a downgrading heuristic caps the entry at.
+    fn cap(self) -> u8 {
+        match self {
+            Self::TimestampField => 20,
+            Self::CaseOrWhitespaceOnly => 25,
+            Self::DescriptiveField | Self::GeneratedIdentifier => 30,
+            Self::ModelVersion => 40,
+            Self::EmptyOnOneSide => 50,
+            Self::EmptyElement => 60,
+            _ => 100,
+        }
+    }
+}
+
 /// Action-oriented analysis record for one path.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct AnalysisEntry {
@@ -22,45 +94,157 @@ pub struct AnalysisEntry {
     pub action: RecommendedAction,
     pub safe: bool,
     pub reason: String,
+    /// Confidence (0-100) that the action is right; for manual conflicts,
+    /// that the difference matters.
+    pub confidence: u8,
+    /// Heuristics that fired, the base classification first.
+    pub rationale: Vec<Heuristic>,
 }
 
+/// Tags holding free-form descriptions.
+const DESCRIPTIVE_TAGS: &[&str] = &["descr", "description", "detail"];
+
+/// Tags holding timestamps and revision bookkeeping.
+const TIMESTAMP_TAGS: &[&str] = &["time", "lastchange", "created", "updated", "revision"];
+
 /// Build an actionable analysis from generic diff entries.
 pub fn analyze(entries: &[DiffEntry]) -> Vec<AnalysisEntry> {
-    entries
-        .iter()
-        .map(|entry| match entry {
-            DiffEntry::Identical { path } => AnalysisEntry {
-                path: path.clone(),
-                action: RecommendedAction::Noop,
-                safe: true,
-                reason: "identical".to_string(),
-            },
-            DiffEntry::OnlyLeft { path, .. } => AnalysisEntry {
-                path: path.clone(),
-                action: RecommendedAction::InsertLeftToRight,
-                safe: true,
-                reason: "missing on right".to_string(),
-            },
-            DiffEntry::OnlyRight { path, .. } => AnalysisEntry {
-                path: path.clone(),
-                action: RecommendedAction::InsertRightToLeft,
-                safe: true,
-                reason: "missing on left".to_string(),
-            },
-            DiffEntry::Modified { path, .. } => AnalysisEntry {
-                path: path.clone(),
-                action: RecommendedAction::ConflictManual,
-                safe: false,
-                reason: "value differs on both sides".to_string(),
-            },
-            DiffEntry::Structural { path, description } => AnalysisEntry {
-                path: path.clone(),
-                action: RecommendedAction::ConflictManual,
-                safe: false,
-                reason: format!("structural mismatch: {description}"),
+    entries.iter().map(analyze_entry).collect()
+}
+
+fn analyze_entry(entry: &DiffEntry) -> AnalysisEntry {
+    let (path, action, reason, base, modifiers) = match entry {
+        DiffEntry::Identical { path } => (
+            path,
+            RecommendedAction::Noop,
+            "identical".to_string(),
+            (Heuristic::Identical, 100),
+            vec![],
+        ),
+        DiffEntry::OnlyLeft { path, node } => (
+            path,
+            RecommendedAction::InsertLeftToRight,
+            "missing on right".to_string(),
+            (Heuristic::MissingOnRight, 90),
+            empty_element(node),
+        ),
+        DiffEntry::OnlyRight { path, node } => (
+            path,
+            RecommendedAction::InsertRightToLeft,
+            "missing on left".to_string(),
+            (Heuristic::MissingOnLeft, 90),
+            empty_element(node),
+        ),
+        DiffEntry::Modified {
+            path,
+            left_text,
+            right_text,
+            ..
+        } => {
+            let mut modifiers = field_kind(path);
+            match (left_text, right_text) {
+                (Some(left), Some(right)) if left != right && fold(left) == fold(right) => {
+                    modifiers.push(Heuristic::CaseOrWhitespaceOnly);
+                }
+                (Some(_), None) | (None, Some(_)) => modifiers.push(Heuristic::EmptyOnOneSide),
+                _ => {}
+            }
+            (
+                path,
+                RecommendedAction::ConflictManual,
+                "value differs on both sides".to_string(),
+                (Heuristic::ValueDiffers, 80),
+                modifiers,
+            )
+        }
+        DiffEntry::Structural { path, description } => (
+            path,
+            RecommendedAction::ConflictManual,
+            format!("structural mismatch: {description}"),
+            (Heuristic::TagMismatch, 90),
+            vec![],
+        ),
+        DiffEntry::AttributeChanged {
+            path,
+            name,
+            left,
+            right,
+        } => (
+            path,
+            RecommendedAction::ConflictManual,
+            format!(
+                "attribute differs: {} -> {}",
+                left.as_deref().unwrap_or("(absent)"),
+                right.as_deref().unwrap_or("(absent)")
+            ),
+            (Heuristic::AttributeDiffers, 70),
+            match name.as_str() {
+                "uuid" => vec![Heuristic::GeneratedIdentifier],
+                "version" => vec![Heuristic::ModelVersion],
+                _ => vec![],
             },
-        })
-        .collect()
+        ),
+        DiffEntry::Moved { path, from, to } => (
+            path,
+            RecommendedAction::ConflictManual,
+            format!("order changed: position {from} -> {to}"),
+            (Heuristic::OrderChanged, 85),
+            vec![],
+        ),
+        DiffEntry::Relocated { from, to } => (
+            to,
+            RecommendedAction::Noop,
+            format!("relocated unchanged from {from}"),
+            (Heuristic::ContentIdentical, 95),
+            vec![],
+        ),
+    };
+
+    let (base, score) = base;
+    let confidence = modifiers.iter().map(|h| h.cap()).fold(score, u8::min);
+    let mut rationale = vec![base];
+    rationale.extend(modifiers);
+    AnalysisEntry {
+        path: path.clone(),
+        safe: action != RecommendedAction::ConflictManual,
+        action,
+        reason,
+        confidence,
+        rationale,
+    }
+}
+
+fn empty_element(node: &XmlNode) -> Vec<Heuristic> {
+    let empty = node.children.is_empty()
+        && node.attributes.is_empty()
+        && node.text.as_deref().is_none_or(|t| t.trim().is_empty());
+    if empty {
+        vec![Heuristic::EmptyElement]
+    } else {
+        vec![]
+    }
+}
+
+/// Descriptive or timestamp heuristics from the last path segment.
+fn field_kind(path: &str) -> Vec<Heuristic> {
+    let leaf = path.rsplit('.').next().unwrap_or(path);
+    let tag = leaf.split('[').next().unwrap_or(leaf);
+    if DESCRIPTIVE_TAGS.contains(&tag) {
+        vec![Heuristic::DescriptiveField]
+    } else if TIMESTAMP_TAGS.contains(&tag) {
+        vec![Heuristic::TimestampField]
+    } else {
+        vec![]
+    }
+}
+
+/// Lowercase with whitespace runs collapsed to one space.
+fn fold(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
 }
 
 /// Count analysis outcomes by action type.
@@ -86,7 +270,7 @@ pub fn summarize_analysis(entries: &[AnalysisEntry]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{analyze, RecommendedAction};
+    use super::{analyze, Heuristic, RecommendedAction};
     use xml_diff_core::{DiffEntry, XmlNode};
 
     #[test]
@@ -104,6 +288,8 @@ mod tests {
                 path: "root.value[1]".to_string(),
                 left: "a".to_string(),
                 right: "b".to_string(),
+                left_text: Some("a".to_string()),
+                right_text: Some("b".to_string()),
             },
         ];
 
@@ -112,4 +298,70 @@ mod tests {
         assert_eq!(actions[1].action, RecommendedAction::InsertRightToLeft);
         assert_eq!(actions[2].action, RecommendedAction::ConflictManual);
     }
+
+    #[test]
+    fn scores_conflicts_by_heuristics() {
+        let modified = |path: &str, left: Option<&str>, right: Option<&str>| DiffEntry::Modified {
+            path: path.to_string(),
+            left: format!("{left:?}"),
+            right: format!("{right:?}"),
+            left_text: left.map(ToString::to_string),
+            right_text: right.map(ToString::to_string),
+        };
+        let entries = vec![
+            modified(
+                "pfsense.system[1].dnsserver[1]",
+                Some("1.1.1.1"),
+                Some("9.9.9.9"),
+            ),
+            modified("pfsense.filter[1].rule[1].descr[1]", Some("a"), Some("b")),
+            modified("pfsense.system[1].hostname[1]", Some("FW  1"), Some("fw 1")),
+            modified("pfsense.system[1].domain[1]", Some("a b"), Some("ab")),
+            modified("pfsense.system[1].timezone[1]", None, Some("UTC")),
+            DiffEntry::AttributeChanged {
+                path: "opnsense.OPNsense[1].Kea[1]@version".to_string(),
+                name: "version".to_string(),
+                left: Some("1.0.0".to_string()),
+                right: Some("1.0.1".to_string()),
+            },
+            DiffEntry::OnlyLeft {
+                path: "pfsense.system[1].disablebeep[1]".to_string(),
+                node: XmlNode::new("disablebeep"),
+            },
+        ];
+
+        let actions = analyze(&entries);
+        let scored: Vec<(u8, &[Heuristic])> = actions
+            .iter()
+            .map(|a| (a.confidence, a.rationale.as_slice()))
+            .collect();
+        assert_eq!(
+            scored,
+            [
+                (80, &[Heuristic::ValueDiffers][..]),
+                (
+                    30,
+                    &[Heuristic::ValueDiffers, Heuristic::DescriptiveField][..]
+                ),
+                (
+                    25,
+                    &[Heuristic::ValueDiffers, Heuristic::CaseOrWhitespaceOnly][..]
+                ),
+                (80, &[Heuristic::ValueDiffers][..]),
+                (
+                    50,
+                    &[Heuristic::ValueDiffers, Heuristic::EmptyOnOneSide][..]
+                ),
+                (
+                    40,
+                    &[Heuristic::AttributeDiffers, Heuristic::ModelVersion][..]
+                ),
+                (
+                    60,
+                    &[Heuristic::MissingOnRight, Heuristic::EmptyElement][..]
+                ),
+            ]
+        );
+        assert!(actions[6].safe);
+    }
 }
@@ -1,6 +1,8 @@
 use serde::Serialize;
 use xml_diff_core::XmlNode;
 
+use crate::transform::booleans::is_truthy;
+
 /// Best-effort DHCP backend identification.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct BackendDetection {
@@ -135,13 +137,6 @@ fn is_opnsense_kea_enabled(root: &XmlNode, evidence: &mut Vec<String>) -> bool {
     enabled_any
 }
 
-fn is_truthy(value: &str) -> bool {
-    matches!(
-        value.trim().to_ascii_lowercase().as_str(),
-        "1" | "on" | "true" | "enabled" | "yes"
-    )
-}
-
 fn has_legacy_dhcp_sections(root: &XmlNode) -> bool {
     root.get_child("dhcpd").is_some()
         || root.get_child("dhcpdv6").is_some()
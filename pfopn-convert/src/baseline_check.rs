@@ -0,0 +1,531 @@
+//! Target baseline validation.
+//!
+//! A conversion merges the source into a destination baseline, so a baseline
+//! of the wrong platform, without the skeleton the chosen DHCP backend needs,
+//! or with conflicting data only fails (or silently degrades) halfway through
+//! `convert`. [`validate_baseline`] runs those checks up front and reports
+//! them as [`VerifyIssue`]s:
+//!
+//! - **Platform** — root tag matches the requested target and differs from the source
+//! - **Skeleton** — profile-required sections, at least one interface, and a
+//!   detectable version
+//! - **DHCP backend** — the structure and plugins the effective backend needs
+//! - **Conflicts** — duplicate interfaces, one device assigned twice, duplicate
+//!   users, groups, certificates, aliases, or gateways
+//! - **Source features** (when a source is given) — every physical source
+//!   interface has a destination, and OPNsense baselines carry the MVC
+//!   containers for the features the source uses
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::backend_detect::detect_dhcp_backend;
+use crate::detect::{detect_config, detect_version_info, ConfigFlavor};
+use crate::interface_guard::{collect_interfaces, format_missing, unmatched_interfaces};
+use crate::profile::load_profile;
+use crate::transform::dhcp::{
+    ensure_backend_readiness, has_legacy_dhcp_data, resolve_effective_backend,
+    EffectiveDhcpBackend, RequestedDhcpBackend,
+};
+use crate::verify::{VerifyIssue, VerifySeverity};
+use crate::verify_codes::FindingCode;
+
+/// OPNsense MVC containers a source feature is converted into.
+const OPNSENSE_CONTAINERS: &[(&str, &[&str])] = &[
+    ("aliases", &["Firewall", "Alias"]),
+    ("gateways", &["Gateways"]),
+    ("OpenVPN", &["OpenVPN"]),
+    ("IPsec", &["Swanctl"]),
+    ("WireGuard", &["wireguard"]),
+];
+
+/// Result of [`validate_baseline`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BaselineReport {
+    /// Baseline platform detected from the root tag.
+    pub platform: String,
+    /// Baseline version, or `unknown`.
+    pub version: String,
+    /// Source platform, when a source was given.
+    pub source_platform: Option<String>,
+    /// DHCP backend a conversion onto this baseline would use.
+    pub dhcp_backend: Option<String>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<VerifyIssue>,
+}
+
+/// Check whether `target` can serve as the baseline for converting `source`.
+///
+/// `to` is the requested target platform; `backend` and `interface_map` are
+/// the options the conversion will run with.
+pub fn validate_baseline(
+    target: &XmlNode,
+    to: Option<&str>,
+    source: Option<&XmlNode>,
+    backend: RequestedDhcpBackend,
+    interface_map: &BTreeMap<String, String>,
+) -> BaselineReport {
+    let mut issues = Vec::new();
+    let platform = flavor_name(detect_config(target));
+    let version = detect_version_info(target).value;
+    let source_platform = source.map(|s| flavor_name(detect_config(s)));
+
+    let report = |issues: Vec<VerifyIssue>, dhcp_backend: Option<String>| {
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == VerifySeverity::Error)
+            .count();
+        BaselineReport {
+            platform: platform.to_string(),
+            version: version.clone(),
+            source_platform: source_platform.map(ToOwned::to_owned),
+            dhcp_backend,
+            errors,
+            warnings: issues.len() - errors,
+            issues,
+        }
+    };
+
+    if platform == "unknown" {
+        issues.push(err(
+            FindingCode::UnknownPlatform,
+            "baseline root tag is not recognized as pfsense/opnsense",
+        ));
+        return report(issues, None);
+    }
+    if let Some(to) = to.filter(|to| *to != platform) {
+        issues.push(err(
+            FindingCode::PlatformMismatch,
+            &format!("baseline is a {platform} config but the target platform is {to}"),
+        ));
+    }
+    if source_platform == Some(platform) {
+        issues.push(err(
+            FindingCode::SamePlatform,
+            &format!(
+                "source and baseline are both {platform}; conversion requires different platforms"
+            ),
+        ));
+    }
+
+    issues.extend(skeleton_issues(target, platform, &version));
+    issues.extend(conflict_issues(target));
+
+    let empty_source = XmlNode::new(if platform == "opnsense" {
+        "pfsense"
+    } else {
+        "opnsense"
+    });
+    let effective =
+        resolve_effective_backend(backend, source.unwrap_or(&empty_source), target, platform);
+    if let Err(error) = ensure_backend_readiness(target, backend, effective) {
+        issues.push(err(FindingCode::BackendNotReady, &error.to_string()));
+    }
+
+    if let Some(source) = source {
+        if effective == EffectiveDhcpBackend::Isc
+            && detect_dhcp_backend(source).mode == "kea"
+            && !has_legacy_dhcp_data(source)
+        {
+            issues.push(err(
+                FindingCode::BackendSourceConflict,
+                "source is Kea-only but the conversion would use ISC DHCP; use --backend kea",
+            ));
+        }
+        issues.extend(interface_issues(source, target, interface_map));
+        if platform == "opnsense" {
+            issues.extend(container_issues(source, target, effective));
+        }
+    }
+
+    let dhcp_backend = match effective {
+        EffectiveDhcpBackend::Kea => "kea",
+        EffectiveDhcpBackend::Isc => "isc",
+    };
+    report(issues, Some(dhcp_backend.to_string()))
+}
+
+/// Render a [`BaselineReport`] for the terminal.
+pub fn render_baseline_text(report: &BaselineReport) -> String {
+    let mut out = vec![format!(
+        "validate-baseline platform={} version={} source={} dhcp_backend={}",
+        report.platform,
+        report.version,
+        report.source_platform.as_deref().unwrap_or("none"),
+        report.dhcp_backend.as_deref().unwrap_or("unknown")
+    )];
+    out.push(format!(
+        "result errors={} warnings={}",
+        report.errors, report.warnings
+    ));
+    out.push("issues".to_string());
+    if report.issues.is_empty() {
+        out.push("- none".to_string());
+    }
+    for issue in &report.issues {
+        let sev = match issue.severity {
+            VerifySeverity::Error => "error",
+            VerifySeverity::Warning => "warning",
+            VerifySeverity::Info => "info",
+        };
+        out.push(format!("- [{sev}] {}: {}", issue.code, issue.message));
+    }
+    out.join("\n")
+}
+
+fn skeleton_issues(target: &XmlNode, platform: &str, version: &str) -> Vec<VerifyIssue> {
+    let mut out = Vec::new();
+    if version == "unknown" {
+        out.push(warn(
+            FindingCode::UnknownVersion,
+            "baseline version not found; version-dependent defaults (Kea on OPNsense 26+) cannot be applied",
+        ));
+    }
+    let required = load_profile(platform, version)
+        .map(|profile| profile.required_sections)
+        .unwrap_or_default();
+    for section in required {
+        if target.get_child(&section).is_none() {
+            out.push(err(
+                FindingCode::MissingRequiredSection,
+                &format!("required section '{section}' is missing"),
+            ));
+        }
+    }
+    if target
+        .get_child("interfaces")
+        .is_some_and(|i| i.children.is_empty())
+    {
+        out.push(err(
+            FindingCode::NoInterfaces,
+            "baseline has no interface assignments; export it after assigning at least WAN and LAN",
+        ));
+    }
+    out
+}
+
+fn conflict_issues(target: &XmlNode) -> Vec<VerifyIssue> {
+    let mut out = Vec::new();
+    if let Some(interfaces) = target.get_child("interfaces") {
+        let mut seen = BTreeSet::new();
+        let mut devices: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for iface in &interfaces.children {
+            if !seen.insert(iface.tag.as_str()) {
+                out.push(err(
+                    FindingCode::DuplicateInterface,
+                    &format!("interface '{}' is defined more than once", iface.tag),
+                ));
+            }
+            if let Some(device) = iface.get_text(&["if"]).map(str::trim) {
+                if !device.is_empty() {
+                    devices.entry(device).or_default().push(&iface.tag);
+                }
+            }
+        }
+        for (device, names) in devices.into_iter().filter(|(_, n)| n.len() > 1) {
+            out.push(err(
+                FindingCode::SharedDevice,
+                &format!(
+                    "device '{device}' is assigned to more than one interface ({})",
+                    names.join(", ")
+                ),
+            ));
+        }
+    }
+
+    let system = target.get_child("system");
+    let alias_sections = [
+        target.get_child("aliases"),
+        target
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("Firewall"))
+            .and_then(|f| f.get_child("Alias"))
+            .and_then(|a| a.get_child("aliases")),
+    ];
+    let gateway_sections = [
+        target.get_child("gateways"),
+        target
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("Gateways")),
+    ];
+    let groups: [(&str, Vec<&XmlNode>, &str); 6] = [
+        ("user", children(system, "user"), "name"),
+        ("group", children(system, "group"), "name"),
+        ("certificate", target.get_children("cert"), "refid"),
+        ("CA", target.get_children("ca"), "refid"),
+        (
+            "alias",
+            alias_sections
+                .into_iter()
+                .flat_map(|s| children(s, "alias"))
+                .collect(),
+            "name",
+        ),
+        (
+            "gateway",
+            gateway_sections
+                .into_iter()
+                .flat_map(|s| children(s, "gateway_item"))
+                .collect(),
+            "name",
+        ),
+    ];
+    for (kind, nodes, key) in groups {
+        let mut seen = BTreeSet::new();
+        let mut reported = BTreeSet::new();
+        for name in nodes
+            .iter()
+            .filter_map(|n| n.get_text(&[key]).map(str::trim))
+            .filter(|n| !n.is_empty())
+        {
+            if !seen.insert(name) && reported.insert(name) {
+                out.push(warn(
+                    FindingCode::DuplicateObject,
+                    &format!("{kind} '{name}' is defined more than once"),
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn interface_issues(
+    source: &XmlNode,
+    target: &XmlNode,
+    interface_map: &BTreeMap<String, String>,
+) -> Vec<VerifyIssue> {
+    let source_map = collect_interfaces(source);
+    let target_map = collect_interfaces(target);
+    let mut out = Vec::new();
+    for (from, to) in interface_map {
+        if !source_map.contains_key(from) {
+            out.push(err(
+                FindingCode::MissingInterface,
+                &format!("mapped source interface '{from}' not found in the source"),
+            ));
+        }
+        if !target_map.contains_key(to) {
+            out.push(err(
+                FindingCode::MissingInterface,
+                &format!("mapped target interface '{to}' not found in the baseline"),
+            ));
+        }
+    }
+    for spec in unmatched_interfaces(source, target, Some(interface_map)) {
+        out.push(err(
+            FindingCode::MissingInterface,
+            &format!(
+                "source interface {} has no assignment in the baseline; assign it there or use --map-interface",
+                format_missing(&spec.name, &spec)
+            ),
+        ));
+    }
+    out
+}
+
+fn container_issues(
+    source: &XmlNode,
+    target: &XmlNode,
+    backend: EffectiveDhcpBackend,
+) -> Vec<VerifyIssue> {
+    let mut features: Vec<(&str, &[&str])> = OPNSENSE_CONTAINERS
+        .iter()
+        .filter(|(feature, _)| source_uses(source, feature))
+        .copied()
+        .collect();
+    let has_dhcp = ["dhcpd", "dhcpdv6", "kea"].iter().any(|tag| {
+        source
+            .get_child(tag)
+            .is_some_and(|n| !n.children.is_empty())
+    });
+    if backend == EffectiveDhcpBackend::Kea && has_dhcp {
+        features.push(("DHCP", &["Kea"]));
+    }
+
+    let opnsense = target.get_child("OPNsense");
+    features
+        .into_iter()
+        .filter(|(_, path)| {
+            path.iter()
+                .try_fold(opnsense, |node, tag| node.map(|n| n.get_child(tag)))
+                .flatten()
+                .is_none()
+        })
+        .map(|(feature, path)| {
+            warn(
+                FindingCode::MissingContainer,
+                &format!(
+                    "source uses {feature} but the baseline has no OPNsense.{}; it will be created without the release's model version",
+                    path.join(".")
+                ),
+            )
+        })
+        .collect()
+}
+
+fn source_uses(source: &XmlNode, feature: &str) -> bool {
+    let opnsense = source.get_child("OPNsense");
+    let nested = |path: &[&str]| {
+        path.iter()
+            .try_fold(opnsense, |node, tag| node.map(|n| n.get_child(tag)))
+            .flatten()
+            .is_some_and(|n| !n.children.is_empty())
+    };
+    let present = |tag: &str, child: &str| {
+        source
+            .get_child(tag)
+            .is_some_and(|n| n.get_child(child).is_some())
+    };
+    match feature {
+        "aliases" => present("aliases", "alias") || nested(&["Firewall", "Alias", "aliases"]),
+        "gateways" => present("gateways", "gateway_item") || nested(&["Gateways"]),
+        "OpenVPN" => {
+            present("openvpn", "openvpn-server")
+                || present("openvpn", "openvpn-client")
+                || nested(&["OpenVPN", "Instances"])
+        }
+        "IPsec" => present("ipsec", "phase1") || nested(&["Swanctl", "Connections"]),
+        "WireGuard" => {
+            source.get_child("wireguard").is_some()
+                || present("installedpackages", "wireguard")
+                || nested(&["wireguard"])
+        }
+        _ => false,
+    }
+}
+
+fn children<'a>(node: Option<&'a XmlNode>, tag: &str) -> Vec<&'a XmlNode> {
+    node.map(|n| n.get_children(tag)).unwrap_or_default()
+}
+
+fn flavor_name(flavor: ConfigFlavor) -> &'static str {
+    match flavor {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => "unknown",
+    }
+}
+
+fn err(code: FindingCode, message: &str) -> VerifyIssue {
+    VerifyIssue::new(VerifySeverity::Error, code, message)
+}
+
+fn warn(code: FindingCode, message: &str) -> VerifyIssue {
+    VerifyIssue::new(VerifySeverity::Warning, code, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use xml_diff_core::parse;
+
+    use super::validate_baseline;
+    use crate::transform::dhcp::RequestedDhcpBackend;
+
+    fn codes(report: &super::BaselineReport) -> Vec<&str> {
+        report.issues.iter().map(|i| i.code.as_str()).collect()
+    }
+
+    #[test]
+    fn flags_platform_mismatch_and_missing_skeleton() {
+        let target = parse(b"<pfsense><version>23.3</version><system/></pfsense>").expect("parse");
+
+        let report = validate_baseline(
+            &target,
+            Some("opnsense"),
+            None,
+            RequestedDhcpBackend::Auto,
+            &BTreeMap::new(),
+        );
+
+        assert_eq!(
+            codes(&report),
+            vec![
+                "platform_mismatch",
+                "missing_required_section",
+                "missing_required_section"
+            ]
+        );
+        assert_eq!(report.errors, 3);
+    }
+
+    #[test]
+    fn flags_conflicting_baseline_data() {
+        let target = parse(
+            br#"<opnsense><version>25.7</version><system>
+                <user><name>root</name></user><user><name>root</name></user>
+              </system>
+              <interfaces>
+                <wan><if>igb0</if></wan><lan><if>igb1</if></lan><opt1><if>igb1</if></opt1>
+              </interfaces>
+              <filter/>
+            </opnsense>"#,
+        )
+        .expect("parse");
+
+        let report = validate_baseline(
+            &target,
+            None,
+            None,
+            RequestedDhcpBackend::Auto,
+            &BTreeMap::new(),
+        );
+
+        assert_eq!(codes(&report), vec!["shared_device", "duplicate_object"]);
+        assert!(report.issues[0].message.contains("lan, opt1"));
+    }
+
+    #[test]
+    fn checks_source_interfaces_backend_and_containers() {
+        let source = parse(
+            br#"<pfsense>
+                <interfaces><wan><if>igb0</if></wan><lan><if>igb1</if></lan><opt1><if>igb2</if></opt1></interfaces>
+                <dhcpd><lan><enable/></lan></dhcpd>
+                <aliases><alias><name>A</name></alias></aliases>
+            </pfsense>"#,
+        )
+        .expect("parse");
+        let target = parse(
+            br#"<opnsense><version>26.1</version><system/>
+              <interfaces><wan><if>vtnet0</if></wan><lan><if>vtnet1</if></lan></interfaces>
+              <filter/>
+              <OPNsense><Firewall><Alias/></Firewall></OPNsense>
+            </opnsense>"#,
+        )
+        .expect("parse");
+
+        let report = validate_baseline(
+            &target,
+            Some("opnsense"),
+            Some(&source),
+            RequestedDhcpBackend::Auto,
+            &BTreeMap::new(),
+        );
+
+        assert_eq!(report.dhcp_backend.as_deref(), Some("kea"));
+        assert_eq!(
+            codes(&report),
+            vec![
+                "backend_not_ready",
+                "missing_interface",
+                "missing_container"
+            ]
+        );
+        assert!(report.issues[1].message.contains("opt1 (if=igb2)"));
+        assert!(report.issues[2].message.contains("OPNsense.Kea"));
+
+        let mapped = BTreeMap::from([("opt1".to_string(), "lan".to_string())]);
+        let report = validate_baseline(
+            &target,
+            Some("opnsense"),
+            Some(&source),
+            RequestedDhcpBackend::Auto,
+            &mapped,
+        );
+        assert!(!codes(&report).contains(&"missing_interface"));
+    }
+}
@@ -0,0 +1,742 @@
+//! Batch conversion of many configs.
+//!
+//! A batch is a list of [`BatchJob`]s, built either from a TOML manifest or
+//! from every `*.xml` file in a directory. [`run_batch`] converts them on a
+//! pool of worker threads with [`ConversionPipeline`] and returns one
+//! [`BatchEntryResult`] per job, in job order. A failing job never stops the
+//! others.
+//!
+//! ```toml
+//! [defaults]
+//! to = "opnsense"
+//! target_file = "baselines/opnsense.xml"
+//! output_dir = "out"
+//!
+//! [[device]]
+//! name = "branch-1"
+//! source = "configs/branch-1.xml"
+//! lan_ip = "10.1.0.1"
+//! map_interface = { opt1 = "opt2" }
+//!
+//! [[device]]
+//! source = "configs/hq.xml"
+//! target_file = "baselines/opnsense-hq.xml"
+//! backend = "isc"
+//!
+//! [[device]]
+//! source = "configs/lab.xml"
+//! target_version = "26.1"
+//! ```
+//!
+//! Relative paths are resolved against the manifest's directory. Every
+//! `[defaults]` key can be overridden per device; `name` defaults to the
+//! source file stem and `output` to `<output_dir>/<name>.xml`. A device that
+//! sets `target_file` or `target_version` ignores both defaults.
+//!
+//! A manifest may also carry `[[site]]` tables (see [`crate::site_map`]),
+//! loaded with [`SiteMap::load`]. With a site map, every job's tunnel peers
+//! follow sites to their new addresses, and once all jobs are done the
+//! converted outputs are cross-checked so both ends of each IPsec tunnel
+//! between batch devices agree ([`BatchReport::cross_checks`]).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use xml_diff_core::{parse_file, write_file_with_options, XmlNode};
+
+use crate::canonical;
+use crate::conversion_summary::ConversionSummary;
+use crate::convert_policy::load_conversion_policy;
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::diagnostics::Severity;
+use crate::encoding::parse_source_file;
+use crate::pipeline::ConversionPipeline;
+use crate::profile::{baseline_versions, load_baseline, seed_baseline_interfaces};
+use crate::provenance::Provenance;
+use crate::site_map::{Site, SiteMap};
+use crate::target_prune::PrunedNode;
+use crate::transform::dhcp::RequestedDhcpBackend;
+use crate::verify::{tunnel_pair_issues, VerifyIssue, VerifySeverity};
+use crate::verify_codes::FindingCode;
+
+/// DHCP backend request as written in a manifest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchBackend {
+    #[default]
+    Auto,
+    Kea,
+    Isc,
+}
+
+impl From<BatchBackend> for RequestedDhcpBackend {
+    fn from(backend: BatchBackend) -> Self {
+        match backend {
+            BatchBackend::Auto => RequestedDhcpBackend::Auto,
+            BatchBackend::Kea => RequestedDhcpBackend::Kea,
+            BatchBackend::Isc => RequestedDhcpBackend::Isc,
+        }
+    }
+}
+
+/// Options shared by every job unless a job overrides them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchDefaults {
+    /// Destination platform (`pfsense` or `opnsense`).
+    pub to: Option<String>,
+    /// Destination baseline config.
+    pub target_file: Option<PathBuf>,
+    /// Release of the embedded baseline to use when there is no `target_file`
+    /// (see [`crate::profile::load_baseline`]).
+    pub target_version: Option<String>,
+    /// Directory receiving `<name>.xml` for jobs without an explicit output.
+    pub output_dir: Option<PathBuf>,
+    pub backend: Option<BatchBackend>,
+    pub disable_dhcp: Option<bool>,
+    pub prune_orphans: Option<bool>,
+    /// Section policy file (see [`crate::convert_policy`]).
+    pub policy: Option<PathBuf>,
+}
+
+impl BatchDefaults {
+    /// Fill every unset option from `fallback`.
+    ///
+    /// `target_file` and `target_version` count as one option: setting either
+    /// ignores both fallback values.
+    pub fn or(self, fallback: BatchDefaults) -> BatchDefaults {
+        let (target_file, target_version) =
+            if self.target_file.is_some() || self.target_version.is_some() {
+                (self.target_file, self.target_version)
+            } else {
+                (fallback.target_file, fallback.target_version)
+            };
+        BatchDefaults {
+            to: self.to.or(fallback.to),
+            target_file,
+            target_version,
+            output_dir: self.output_dir.or(fallback.output_dir),
+            backend: self.backend.or(fallback.backend),
+            disable_dhcp: self.disable_dhcp.or(fallback.disable_dhcp),
+            prune_orphans: self.prune_orphans.or(fallback.prune_orphans),
+            policy: self.policy.or(fallback.policy),
+        }
+    }
+}
+
+/// Where a job's destination baseline comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchTarget {
+    /// A baseline config on disk.
+    File(PathBuf),
+    /// The embedded baseline for a release.
+    Version(String),
+}
+
+/// One fully resolved conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchJob {
+    /// Device name used in the report.
+    pub name: String,
+    pub source: PathBuf,
+    pub target: BatchTarget,
+    pub output: PathBuf,
+    pub to: String,
+    pub backend: BatchBackend,
+    pub lan_ip: Option<String>,
+    /// Source logical interface -> target logical interface.
+    pub map_interface: BTreeMap<String, String>,
+    pub disable_dhcp: bool,
+    pub prune_orphans: bool,
+    pub policy: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    #[serde(default)]
+    defaults: BatchDefaults,
+    #[serde(default, rename = "device")]
+    devices: Vec<ManifestDevice>,
+    /// Read separately by [`SiteMap::load`].
+    #[serde(default, rename = "site")]
+    _sites: Vec<Site>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestDevice {
+    name: Option<String>,
+    source: PathBuf,
+    output: Option<PathBuf>,
+    to: Option<String>,
+    target_file: Option<PathBuf>,
+    target_version: Option<String>,
+    output_dir: Option<PathBuf>,
+    backend: Option<BatchBackend>,
+    lan_ip: Option<String>,
+    #[serde(default)]
+    map_interface: BTreeMap<String, String>,
+    disable_dhcp: Option<bool>,
+    prune_orphans: Option<bool>,
+    policy: Option<PathBuf>,
+}
+
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("invalid batch manifest {path}: {source}")]
+    Parse {
+        path: String,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("batch has no devices")]
+    Empty,
+    #[error("device '{name}': {reason}")]
+    Device { name: String, reason: String },
+    #[error("devices '{first}' and '{second}' both write {output}")]
+    DuplicateOutput {
+        first: String,
+        second: String,
+        output: String,
+    },
+}
+
+/// Load jobs from a manifest file.
+///
+/// `fallback` supplies options neither the manifest defaults nor a device set,
+/// typically from command-line flags.
+pub fn load_manifest(path: &Path, fallback: BatchDefaults) -> Result<Vec<BatchJob>, BatchError> {
+    let raw = fs::read_to_string(path).map_err(|source| BatchError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    parse_manifest(&raw, path.display().to_string(), base, fallback)
+}
+
+/// Parse manifest TOML, resolving relative paths against `base`.
+pub fn parse_manifest(
+    raw: &str,
+    path: String,
+    base: &Path,
+    fallback: BatchDefaults,
+) -> Result<Vec<BatchJob>, BatchError> {
+    let parsed: ManifestFile =
+        toml::from_str(raw).map_err(|source| BatchError::Parse { path, source })?;
+    let defaults = BatchDefaults {
+        target_file: parsed.defaults.target_file.map(|p| base.join(p)),
+        output_dir: parsed.defaults.output_dir.map(|p| base.join(p)),
+        policy: parsed.defaults.policy.map(|p| base.join(p)),
+        ..parsed.defaults
+    }
+    .or(fallback);
+    let jobs = parsed
+        .devices
+        .into_iter()
+        .map(|device| {
+            let source = base.join(&device.source);
+            let options = BatchDefaults {
+                to: device.to,
+                target_file: device.target_file.map(|p| base.join(p)),
+                target_version: device.target_version,
+                output_dir: device.output_dir.map(|p| base.join(p)),
+                backend: device.backend,
+                disable_dhcp: device.disable_dhcp,
+                prune_orphans: device.prune_orphans,
+                policy: device.policy.map(|p| base.join(p)),
+            }
+            .or(defaults.clone());
+            resolve_job(
+                device.name,
+                source,
+                device.output.map(|p| base.join(p)),
+                device.lan_ip,
+                device.map_interface,
+                options,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    check_jobs(jobs)
+}
+
+/// Build one job per `*.xml` file directly inside `dir`, sorted by name.
+pub fn jobs_from_dir(dir: &Path, defaults: BatchDefaults) -> Result<Vec<BatchJob>, BatchError> {
+    let io_error = |source| BatchError::Io {
+        path: dir.display().to_string(),
+        source,
+    };
+    let mut sources = Vec::new();
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "xml") {
+            sources.push(path);
+        }
+    }
+    sources.sort();
+    let jobs = sources
+        .into_iter()
+        .map(|source| resolve_job(None, source, None, None, BTreeMap::new(), defaults.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    check_jobs(jobs)
+}
+
+fn resolve_job(
+    name: Option<String>,
+    source: PathBuf,
+    output: Option<PathBuf>,
+    lan_ip: Option<String>,
+    map_interface: BTreeMap<String, String>,
+    options: BatchDefaults,
+) -> Result<BatchJob, BatchError> {
+    let name = name.unwrap_or_else(|| {
+        source
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| source.display().to_string())
+    });
+    let device_error = |reason: &str| BatchError::Device {
+        name: name.clone(),
+        reason: reason.to_string(),
+    };
+    let to = options
+        .to
+        .ok_or_else(|| device_error("no target platform; set `to`"))?;
+    if to != "pfsense" && to != "opnsense" {
+        return Err(device_error(&format!(
+            "unsupported target platform '{to}'; expected pfsense or opnsense"
+        )));
+    }
+    let target = match (options.target_file, options.target_version) {
+        (Some(path), _) => BatchTarget::File(path),
+        (None, Some(version)) => BatchTarget::Version(version),
+        (None, None) => {
+            return Err(device_error(
+                "no target baseline; set `target_file` or `target_version`",
+            ))
+        }
+    };
+    let output = match (output, options.output_dir) {
+        (Some(output), _) => output,
+        (None, Some(dir)) => dir.join(format!("{name}.xml")),
+        (None, None) => return Err(device_error("no output; set `output` or `output_dir`")),
+    };
+    Ok(BatchJob {
+        name,
+        source,
+        target,
+        output,
+        to,
+        backend: options.backend.unwrap_or_default(),
+        lan_ip,
+        map_interface,
+        disable_dhcp: options.disable_dhcp.unwrap_or(false),
+        prune_orphans: options.prune_orphans.unwrap_or(false),
+        policy: options.policy,
+    })
+}
+
+fn check_jobs(jobs: Vec<BatchJob>) -> Result<Vec<BatchJob>, BatchError> {
+    if jobs.is_empty() {
+        return Err(BatchError::Empty);
+    }
+    let mut names = BTreeSet::new();
+    let mut outputs: BTreeMap<&Path, &str> = BTreeMap::new();
+    for job in &jobs {
+        if !names.insert(job.name.as_str()) {
+            return Err(BatchError::Device {
+                name: job.name.clone(),
+                reason: "name is used by more than one device".to_string(),
+            });
+        }
+        if let Some(first) = outputs.insert(&job.output, &job.name) {
+            return Err(BatchError::DuplicateOutput {
+                first: first.to_string(),
+                second: job.name.clone(),
+                output: job.output.display().to_string(),
+            });
+        }
+    }
+    Ok(jobs)
+}
+
+/// Outcome of one job.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchEntryResult {
+    pub name: String,
+    pub source: PathBuf,
+    pub output: PathBuf,
+    /// Whether the output was written.
+    pub ok: bool,
+    /// Why the job failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Warning diagnostics from the conversion.
+    pub warnings: Vec<String>,
+    /// Object counts for the written output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ConversionSummary>,
+    /// Sections, package configs, and models pruned from the output.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pruned: Vec<PrunedNode>,
+}
+
+/// Results of a whole batch, in job order.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub warnings: usize,
+    pub entries: Vec<BatchEntryResult>,
+    /// Tunnel pair issues between converted devices (with a site map).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cross_checks: Vec<VerifyIssue>,
+}
+
+impl BatchReport {
+    fn new(entries: Vec<BatchEntryResult>) -> Self {
+        let succeeded = entries.iter().filter(|e| e.ok).count();
+        Self {
+            succeeded,
+            failed: entries.len() - succeeded,
+            warnings: entries.iter().map(|e| e.warnings.len()).sum(),
+            entries,
+            cross_checks: Vec::new(),
+        }
+    }
+
+    /// Number of cross-check errors.
+    pub fn cross_check_errors(&self) -> usize {
+        self.cross_checks
+            .iter()
+            .filter(|i| i.severity == VerifySeverity::Error)
+            .count()
+    }
+}
+
+/// Convert every job on up to `workers` threads.
+///
+/// With a non-empty `sites` map, tunnel peers are rewritten in each job and
+/// the successful outputs are cross-checked afterwards.
+pub fn run_batch(jobs: &[BatchJob], workers: usize, sites: &SiteMap) -> BatchReport {
+    let workers = workers.clamp(1, jobs.len().max(1));
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(jobs.len()));
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+                let result = run_job(job, sites);
+                results
+                    .lock()
+                    .expect("batch results lock")
+                    .push((index, result));
+            });
+        }
+    });
+    let mut results = results.into_inner().expect("batch results lock");
+    results.sort_by_key(|(index, _)| *index);
+    let mut report = BatchReport::new(results.into_iter().map(|(_, result)| result).collect());
+    if !sites.is_empty() {
+        report.cross_checks = cross_check(&report.entries, sites);
+    }
+    report
+}
+
+/// Compare tunnel pairs across every output that was written.
+fn cross_check(entries: &[BatchEntryResult], sites: &SiteMap) -> Vec<VerifyIssue> {
+    let mut outputs = Vec::new();
+    for entry in entries.iter().filter(|e| e.ok) {
+        match parse_file(&entry.output) {
+            Ok(root) => outputs.push((entry.name.as_str(), root)),
+            Err(err) => {
+                return vec![VerifyIssue::new(
+                    VerifySeverity::Error,
+                    FindingCode::TunnelPairUnreadable,
+                    format!("failed to re-read {}: {err}", entry.output.display()),
+                )]
+            }
+        }
+    }
+    let devices: Vec<(&str, &XmlNode)> = outputs.iter().map(|(name, root)| (*name, root)).collect();
+    tunnel_pair_issues(&devices, sites)
+}
+
+fn run_job(job: &BatchJob, sites: &SiteMap) -> BatchEntryResult {
+    let mut entry = BatchEntryResult {
+        name: job.name.clone(),
+        source: job.source.clone(),
+        output: job.output.clone(),
+        ok: false,
+        error: None,
+        warnings: Vec::new(),
+        summary: None,
+        pruned: Vec::new(),
+    };
+    match convert_job(job, sites) {
+        Ok((warnings, summary, pruned)) => {
+            entry.ok = true;
+            entry.warnings = warnings;
+            entry.summary = Some(summary);
+            entry.pruned = pruned;
+        }
+        Err(err) => entry.error = Some(format!("{err:#}")),
+    }
+    entry
+}
+
+fn convert_job(
+    job: &BatchJob,
+    sites: &SiteMap,
+) -> Result<(Vec<String>, ConversionSummary, Vec<PrunedNode>)> {
+    let (source, parse_repairs) = parse_source_file(&job.source)
+        .with_context(|| format!("failed to parse {}", job.source.display()))?;
+    let target = match &job.target {
+        BatchTarget::File(path) => {
+            let target =
+                parse_file(path).with_context(|| format!("failed to parse {}", path.display()))?;
+            let target_flavor = match detect_config(&target) {
+                ConfigFlavor::PfSense => "pfsense",
+                ConfigFlavor::OpnSense => "opnsense",
+                ConfigFlavor::Unknown => {
+                    bail!("unable to auto-detect platform of {}", path.display())
+                }
+            };
+            if target_flavor != job.to {
+                bail!(
+                    "target-file platform ({target_flavor}) does not match to ({})",
+                    job.to
+                );
+            }
+            target
+        }
+        BatchTarget::Version(version) => {
+            let Some((mut target, _)) = load_baseline(&job.to, version) else {
+                bail!(
+                    "no embedded {} baseline for target_version '{version}'; available: {}",
+                    job.to,
+                    baseline_versions(&job.to).join(", ")
+                );
+            };
+            seed_baseline_interfaces(&mut target, &source, &job.map_interface);
+            target
+        }
+    };
+
+    let mut pipeline = ConversionPipeline::new(&job.to)
+        .backend(job.backend.into())
+        .disable_dhcp(job.disable_dhcp)
+        .prune_orphans(job.prune_orphans)
+        .parse_repairs(parse_repairs)
+        .provenance(Provenance::now())
+        .site_map(sites.clone());
+    for (src, dst) in &job.map_interface {
+        pipeline = pipeline.map_interface(src, dst);
+    }
+    if let Some(lan_ip) = &job.lan_ip {
+        pipeline = pipeline.lan_ip(lan_ip);
+    }
+    if let Some(path) = &job.policy {
+        pipeline = pipeline.policy(load_conversion_policy(path)?);
+    }
+    let result = pipeline.run(&source, &target)?;
+
+    if let Some(dir) = job.output.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    write_file_with_options(
+        &result.output,
+        &job.output,
+        &canonical::write_options(&result.to),
+    )
+    .with_context(|| format!("failed to write output XML {}", job.output.display()))?;
+
+    let warnings = result
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .map(ToString::to_string)
+        .collect();
+    Ok((warnings, result.summary, result.prune_log))
+}
+
+/// Render the aggregate report for the terminal.
+pub fn render_report(report: &BatchReport) -> String {
+    let width = report
+        .entries
+        .iter()
+        .map(|e| e.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = format!(
+        "batch: {} device(s), {} succeeded, {} failed, {} warning(s)",
+        report.entries.len(),
+        report.succeeded,
+        report.failed,
+        report.warnings
+    );
+    for entry in &report.entries {
+        let detail = match &entry.error {
+            Some(error) => format!("error: {error}"),
+            None => format!(
+                "warnings={} -> {}",
+                entry.warnings.len(),
+                entry.output.display()
+            ),
+        };
+        let status = if entry.ok { "ok" } else { "FAILED" };
+        out.push_str(&format!("\n  {status:<6} {:<width$}  {detail}", entry.name));
+    }
+    if !report.cross_checks.is_empty() {
+        out.push_str("\ntunnel cross-checks:");
+        for issue in &report.cross_checks {
+            let sev = match issue.severity {
+                VerifySeverity::Error => "error",
+                VerifySeverity::Warning => "warning",
+                VerifySeverity::Info => "info",
+            };
+            out.push_str(&format!("\n  - [{sev}] {}: {}", issue.code, issue.message));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{parse_manifest, BatchBackend, BatchDefaults, BatchError, BatchTarget};
+
+    #[test]
+    fn manifest_devices_inherit_and_override_defaults() {
+        let jobs = parse_manifest(
+            r#"
+[defaults]
+to = "opnsense"
+target_file = "base/opn.xml"
+output_dir = "out"
+
+[[device]]
+source = "cfg/branch.xml"
+lan_ip = "10.1.0.1"
+map_interface = { opt1 = "opt2" }
+
+[[device]]
+name = "hq"
+source = "cfg/main.xml"
+target_file = "base/hq.xml"
+output = "hq-converted.xml"
+backend = "isc"
+
+[[device]]
+source = "cfg/lab.xml"
+target_version = "26.1"
+
+[[site]]
+name = "hq"
+addresses = ["203.0.113.10"]
+"#,
+            "m.toml".to_string(),
+            Path::new("/srv/batch"),
+            BatchDefaults::default(),
+        )
+        .expect("parse");
+
+        assert_eq!(jobs.len(), 3);
+        assert_eq!(jobs[0].name, "branch");
+        assert_eq!(jobs[0].source, PathBuf::from("/srv/batch/cfg/branch.xml"));
+        assert_eq!(
+            jobs[0].target,
+            BatchTarget::File(PathBuf::from("/srv/batch/base/opn.xml"))
+        );
+        assert_eq!(jobs[0].output, PathBuf::from("/srv/batch/out/branch.xml"));
+        assert_eq!(
+            jobs[0].map_interface.get("opt1").map(String::as_str),
+            Some("opt2")
+        );
+        assert_eq!(jobs[0].backend, BatchBackend::Auto);
+        assert_eq!(
+            jobs[1].target,
+            BatchTarget::File(PathBuf::from("/srv/batch/base/hq.xml"))
+        );
+        assert_eq!(jobs[1].output, PathBuf::from("/srv/batch/hq-converted.xml"));
+        assert_eq!(jobs[1].backend, BatchBackend::Isc);
+        assert_eq!(jobs[2].target, BatchTarget::Version("26.1".to_string()));
+    }
+
+    #[test]
+    fn fallback_options_fill_gaps_only() {
+        let fallback = BatchDefaults {
+            to: Some("pfsense".to_string()),
+            target_file: Some(PathBuf::from("cli.xml")),
+            output_dir: Some(PathBuf::from("cli-out")),
+            ..BatchDefaults::default()
+        };
+        let jobs = parse_manifest(
+            "[defaults]\nto = \"opnsense\"\n[[device]]\nsource = \"a.xml\"\n",
+            "m.toml".to_string(),
+            Path::new(""),
+            fallback,
+        )
+        .expect("parse");
+        assert_eq!(jobs[0].to, "opnsense");
+        assert_eq!(jobs[0].output, PathBuf::from("cli-out/a.xml"));
+    }
+
+    #[test]
+    fn rejects_incomplete_or_conflicting_devices() {
+        let err = parse_manifest(
+            "[[device]]\nsource = \"a.xml\"\nto = \"opnsense\"\n",
+            "m.toml".to_string(),
+            Path::new(""),
+            BatchDefaults::default(),
+        )
+        .expect_err("missing target");
+        assert!(err.to_string().contains("target_file"));
+
+        let err = parse_manifest(
+            r#"
+[defaults]
+to = "opnsense"
+target_file = "b.xml"
+output_dir = "out"
+[[device]]
+source = "x/fw.xml"
+[[device]]
+source = "y/fw.xml"
+name = "other"
+output = "out/fw.xml"
+"#,
+            "m.toml".to_string(),
+            Path::new(""),
+            BatchDefaults::default(),
+        )
+        .expect_err("duplicate output");
+        assert!(matches!(err, BatchError::DuplicateOutput { .. }));
+
+        let err = parse_manifest(
+            "[[device]]\nsource = \"a.xml\"\nflavour = \"x\"\n",
+            "m.toml".to_string(),
+            Path::new(""),
+            BatchDefaults::default(),
+        )
+        .expect_err("unknown key");
+        assert!(err.to_string().contains("m.toml"));
+    }
+}
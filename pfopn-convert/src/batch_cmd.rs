@@ -0,0 +1,69 @@
+use std::thread;
+
+use anyhow::{bail, Result};
+use pfopn_convert::batch::{
+    self, jobs_from_dir, load_manifest, render_report, BatchDefaults, BatchTarget,
+};
+use pfopn_convert::site_map::SiteMap;
+
+use crate::cli::{BatchArgs, OutputFormat, Platform};
+use crate::path_guard::ensure_output_not_same;
+
+/// Convert every device in a manifest or directory; fails when any device
+/// failed or the tunnel cross-checks found errors.
+pub fn run_batch(args: BatchArgs) -> Result<()> {
+    let to = match args.to {
+        Some(Platform::Pfsense) => Some("pfsense".to_string()),
+        Some(Platform::Opnsense) => Some("opnsense".to_string()),
+        Some(Platform::Auto) => bail!("--to cannot be auto; specify pfsense or opnsense"),
+        None => None,
+    };
+    let defaults = BatchDefaults {
+        to,
+        target_file: args.target_file,
+        target_version: args.target_version,
+        output_dir: args.output_dir,
+        ..BatchDefaults::default()
+    };
+    let jobs = match (&args.manifest, &args.dir) {
+        (Some(manifest), _) => load_manifest(manifest, defaults)?,
+        (None, Some(dir)) => jobs_from_dir(dir, defaults)?,
+        (None, None) => bail!("provide --manifest or --dir"),
+    };
+    let mut sites = match &args.manifest {
+        Some(manifest) => SiteMap::load(manifest)?,
+        None => SiteMap::default(),
+    };
+    if let Some(path) = &args.site_map {
+        sites.extend(SiteMap::load(path)?)?;
+    }
+    for job in &jobs {
+        let mut inputs = vec![job.source.as_path()];
+        if let BatchTarget::File(path) = &job.target {
+            inputs.push(path.as_path());
+        }
+        ensure_output_not_same(&job.output, &inputs)?;
+    }
+
+    let workers = args
+        .jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let report = batch::run_batch(&jobs, workers, &sites);
+    match args.format {
+        OutputFormat::Text => println!("{}", render_report(&report)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    if report.failed > 0 {
+        bail!(
+            "batch failed: {} of {} device(s) did not convert",
+            report.failed,
+            report.entries.len()
+        );
+    }
+    let errors = report.cross_check_errors();
+    if errors > 0 {
+        bail!("batch failed: {errors} tunnel cross-check error(s)");
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! Canonical serialization settings for converted configs.
+//!
+//! Converted output is assembled from a baseline plus inserted source
+//! sections, so top-level order otherwise depends on which sections were
+//! merged. Writing with [`write_options`] sorts top-level sections into the
+//! order each platform uses in its own `config.xml`, which keeps repeated
+//! conversions byte-for-byte identical and diffs between runs small.
+
+use xml_diff_core::{ChildOrder, EmptyElementStyle, WriteOptions};
+
+/// Top-level section order used by pfSense `config.xml`.
+const PFSENSE_TOP_LEVEL: &[&str] = &[
+    "version",
+    "lastchange",
+    "system",
+    "interfaces",
+    "vlans",
+    "qinqs",
+    "laggs",
+    "gifs",
+    "gres",
+    "staticroutes",
+    "dhcpbackend",
+    "kea",
+    "dhcpd",
+    "dhcpdv6",
+    "dhcrelay",
+    "dhcrelay6",
+    "snmpd",
+    "diag",
+    "syslog",
+    "nat",
+    "filter",
+    "shaper",
+    "ipsec",
+    "aliases",
+    "proxyarp",
+    "cron",
+    "wol",
+    "rrd",
+    "widgets",
+    "openvpn",
+    "dnshaper",
+    "unbound",
+    "dnsmasq",
+    "ntpd",
+    "revision",
+    "dyndnses",
+    "cert",
+    "ppps",
+    "pppoes",
+    "l2tp",
+    "gateways",
+    "ezshaper",
+    "bridges",
+    "ifgroups",
+    "virtualip",
+    "notifications",
+    "installedpackages",
+    "ovpnserver",
+    "ca",
+    "hasync",
+    "sshdata",
+    "pfopn_snapshots",
+    "pfopn_provenance",
+];
+
+/// Top-level section order used by OPNsense `config.xml`.
+const OPNSENSE_TOP_LEVEL: &[&str] = &[
+    "theme",
+    "sysctl",
+    "system",
+    "interfaces",
+    "dnsmasq",
+    "snmpd",
+    "nat",
+    "filter",
+    "rrd",
+    "ntpd",
+    "widgets",
+    "revision",
+    "OPNsense",
+    "hasync",
+    "openvpn",
+    "ipsec",
+    "ifgroups",
+    "bridges",
+    "gifs",
+    "gres",
+    "laggs",
+    "virtualip",
+    "vlans",
+    "staticroutes",
+    "gateways",
+    "ppps",
+    "pppoes",
+    "l2tp",
+    "wireless",
+    "ca",
+    "dhcpd",
+    "dhcpdv6",
+    "dhcrelay",
+    "dhcrelay6",
+    "dhcp6relay",
+    "cert",
+    "syslog",
+    "pfopn_provenance",
+];
+
+/// Return the top-level section order for `platform`, if known.
+pub fn top_level_order(platform: &str) -> Option<&'static [&'static str]> {
+    match platform {
+        "pfsense" => Some(PFSENSE_TOP_LEVEL),
+        "opnsense" => Some(OPNSENSE_TOP_LEVEL),
+        _ => None,
+    }
+}
+
+/// Build canonical write options for a config whose root tag is `platform`.
+///
+/// Two-space indentation, self-closing empty elements, an XML declaration,
+/// and platform section ordering. Unknown platforms keep tree order.
+pub fn write_options(platform: &str) -> WriteOptions {
+    let child_order = match top_level_order(platform) {
+        Some(order) => ChildOrder::new().with_rule(platform, order.iter().copied()),
+        None => ChildOrder::new(),
+    };
+    WriteOptions {
+        indent_char: b' ',
+        indent_size: 2,
+        empty_elements: EmptyElementStyle::SelfClosing,
+        child_order,
+        xml_declaration: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::{parse, write_with_options};
+
+    use super::write_options;
+
+    #[test]
+    fn orders_top_level_sections_regardless_of_insertion_order() {
+        let a = parse(br#"<opnsense><cert/><filter/><system/><OPNsense/></opnsense>"#)
+            .expect("parse a");
+        let b = parse(br#"<opnsense><OPNsense/><system/><cert/><filter/></opnsense>"#)
+            .expect("parse b");
+
+        let opts = write_options("opnsense");
+        let out_a = write_with_options(&a, &opts).expect("write a");
+        let out_b = write_with_options(&b, &opts).expect("write b");
+        assert_eq!(out_a, out_b);
+
+        let text = String::from_utf8(out_a).expect("utf8");
+        assert!(text.starts_with("<?xml version=\"1.0\"?>"));
+        let system = text.find("<system/>").expect("system");
+        let filter = text.find("<filter/>").expect("filter");
+        let cert = text.find("<cert/>").expect("cert");
+        assert!(system < filter && filter < cert);
+    }
+}
@@ -0,0 +1,513 @@
+//! Supported section transforms and coverage introspection.
+//!
+//! [`capabilities`] lists every section the converter knows how to carry,
+//! with the directions it supports, how complete the translation is, and
+//! which target structures it relies on. [`section_coverage`] applies the
+//! table to one config so automation can tell up front whether a conversion
+//! is in scope, without running it.
+//!
+//! Sections are top-level tags, or `container/child` for the
+//! `installedpackages` and `OPNsense` containers, whose children are
+//! independent packages and models.
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::transform::small_packages;
+
+/// Conversion directions a capability supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    /// pfSense → OPNsense only.
+    PfToOpn,
+    /// OPNsense → pfSense only.
+    OpnToPf,
+    /// Both directions.
+    Both,
+}
+
+impl Direction {
+    /// Whether a `from` → `to` conversion is covered.
+    pub fn supports(self, from: &str, to: &str) -> bool {
+        match self {
+            Direction::Both => from != to,
+            Direction::PfToOpn => from == "pfsense" && to == "opnsense",
+            Direction::OpnToPf => from == "opnsense" && to == "pfsense",
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::PfToOpn => "pf-to-opn",
+            Direction::OpnToPf => "opn-to-pf",
+            Direction::Both => "both",
+        }
+    }
+}
+
+/// How much of a section a capability translates, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Coverage {
+    /// Copied unchanged; both platforms share the layout.
+    Passthrough,
+    /// Known fields are translated; others are dropped with a diagnostic.
+    Partial,
+    /// Every field the source platform writes is translated.
+    Full,
+}
+
+impl Coverage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Coverage::Passthrough => "passthrough",
+            Coverage::Partial => "partial",
+            Coverage::Full => "full",
+        }
+    }
+}
+
+/// One section transform the converter knows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Capability {
+    /// Stable identifier.
+    pub id: &'static str,
+    /// Source sections it reads, on either platform.
+    pub sections: &'static [&'static str],
+    pub direction: Direction,
+    pub coverage: Coverage,
+    /// Target structures the transform needs; empty when it creates its own.
+    pub requires: &'static [&'static str],
+    pub note: &'static str,
+}
+
+/// Every capability, in pipeline order.
+const CAPABILITIES: &[Capability] = &[
+    Capability {
+        id: "metadata",
+        sections: &["version", "revision", "lastchange", "ovpnserver"],
+        direction: Direction::Both,
+        coverage: Coverage::Passthrough,
+        requires: &[],
+        note: "config version and revision bookkeeping",
+    },
+    Capability {
+        id: "system-settings",
+        sections: &["system"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "identity, DNS, time, console, and tuning fields; platform-only fields are reported",
+    },
+    Capability {
+        id: "firmware",
+        sections: &["system"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "update branch; platform-specific mirrors are not carried",
+    },
+    Capability {
+        id: "webgui",
+        sections: &["system"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "protocol, port, certificate, and anti-lockout settings",
+    },
+    Capability {
+        id: "users-groups",
+        sections: &["system"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "local users and groups; privileges go through the privilege map",
+    },
+    Capability {
+        id: "interfaces",
+        sections: &["interfaces"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &["target baseline assigning every physical source interface"],
+        note: "logical settings; device bindings come from the target baseline",
+    },
+    Capability {
+        id: "vlans",
+        sections: &["vlans", "qinqs"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "pfSense QinQ entries become OPNsense VLANs with the service ethertype",
+    },
+    Capability {
+        id: "laggs",
+        sections: &["laggs"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "protocols both platforms accept; others are reported",
+    },
+    Capability {
+        id: "bridges",
+        sections: &["bridges"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "",
+    },
+    Capability {
+        id: "ifgroups",
+        sections: &["ifgroups"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "plugin-generated groups are pruned",
+    },
+    Capability {
+        id: "ppps",
+        sections: &["ppps"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "link options the target ignores are reported",
+    },
+    Capability {
+        id: "ppp-servers",
+        sections: &["pppoes", "l2tp"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "",
+    },
+    Capability {
+        id: "gateways",
+        sections: &["gateways"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "monitoring timings are rescaled between milliseconds and seconds",
+    },
+    Capability {
+        id: "staticroutes",
+        sections: &["staticroutes"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "",
+    },
+    Capability {
+        id: "filter",
+        sections: &["filter"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "interface references and flags are rewritten; pfBlockerNG floating rules are dropped for OPNsense",
+    },
+    Capability {
+        id: "nat",
+        sections: &["nat"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "outbound mode follows the source; outbound mapping targets are translated",
+    },
+    Capability {
+        id: "aliases",
+        sections: &["aliases", "OPNsense/Firewall"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "pfSense <aliases> and the OPNsense firewall alias model",
+    },
+    Capability {
+        id: "dhcp-isc",
+        sections: &["dhcpd", "dhcpdv6", "dhcpd6", "dhcpbackend"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "ISC DHCPv4/DHCPv6 scopes, static mappings, and MAC controls",
+    },
+    Capability {
+        id: "dhcp-kea",
+        sections: &["dhcpd", "kea", "OPNsense/Kea"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &["OPNsense/Kea in the target baseline (OPNsense 26+)"],
+        note: "ISC scopes migrate to Kea subnets and reservations; ranges that cannot migrate are reported",
+    },
+    Capability {
+        id: "dhcp-relay",
+        sections: &["dhcrelay", "dhcrelay6", "dhcp6relay", "OPNsense/DHCRelay"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "",
+    },
+    Capability {
+        id: "router-advertisements",
+        sections: &["dhcpdv6", "OPNsense/radvd"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &["OPNsense/radvd in the target baseline for the model layout"],
+        note: "legacy OPNsense fields are written when the model is absent",
+    },
+    Capability {
+        id: "openvpn",
+        sections: &["openvpn", "OPNsense/OpenVPN"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "servers, clients, and overrides; referenced users, certificates, and CAs are transferred",
+    },
+    Capability {
+        id: "ipsec",
+        sections: &["ipsec", "OPNsense/IPsec", "OPNsense/Swanctl"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "phase 1/2 tunnels and mobile clients",
+    },
+    Capability {
+        id: "wireguard",
+        sections: &["wireguard", "installedpackages/wireguard", "OPNsense/wireguard"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &["WireGuard package on pfSense targets"],
+        note: "tunnels and peers",
+    },
+    Capability {
+        id: "tailscale",
+        sections: &[
+            "tailscale",
+            "tailscaleauth",
+            "installedpackages/tailscale",
+            "installedpackages/tailscaleauth",
+            "OPNsense/tailscale",
+        ],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &["os-tailscale plugin on OPNsense targets"],
+        note: "",
+    },
+    Capability {
+        id: "acme",
+        sections: &["installedpackages/acme", "OPNsense/AcmeClient"],
+        direction: Direction::PfToOpn,
+        coverage: Coverage::Partial,
+        requires: &["os-acme-client plugin on OPNsense targets"],
+        note: "accounts, certificates, and challenges; post-issue actions are manual steps",
+    },
+    Capability {
+        id: "monitoring-agents",
+        sections: &[
+            "installedpackages/zabbixagentlts",
+            "installedpackages/zabbixagent",
+            "installedpackages/telegraf",
+            "OPNsense/ZabbixAgent",
+            "OPNsense/telegraf",
+        ],
+        direction: Direction::PfToOpn,
+        coverage: Coverage::Partial,
+        requires: &["os-zabbix-agent or os-telegraf plugin on OPNsense targets"],
+        note: "server addresses, hostname, PSK encryption, user parameters, and the InfluxDB output",
+    },
+    Capability {
+        id: "small-packages",
+        sections: small_packages::SECTIONS,
+        direction: Direction::PfToOpn,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "Service Watchdog, Shellcmd, Filer, System Patches, and AutoConfigBackup are reported as manual steps for their OPNsense counterpart",
+    },
+    Capability {
+        id: "certs",
+        sections: &["cert", "ca"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "",
+    },
+    Capability {
+        id: "wol",
+        sections: &["wol", "OPNsense/wol"],
+        direction: Direction::Both,
+        coverage: Coverage::Full,
+        requires: &[],
+        note: "",
+    },
+    Capability {
+        id: "ntpd",
+        sections: &["ntpd"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "fields with the same meaning on both platforms",
+    },
+    Capability {
+        id: "tunables",
+        sections: &["sysctl"],
+        direction: Direction::Both,
+        coverage: Coverage::Partial,
+        requires: &[],
+        note: "tunables missing on the target are reported (see the tunable map)",
+    },
+    Capability {
+        id: "shared-services",
+        sections: &["snmpd", "syslog", "rrd"],
+        direction: Direction::Both,
+        coverage: Coverage::Passthrough,
+        requires: &[],
+        note: "copied from the source unchanged",
+    },
+    Capability {
+        id: "package-registry",
+        sections: &[
+            "installedpackages/menu",
+            "installedpackages/package",
+            "installedpackages/service",
+        ],
+        direction: Direction::Both,
+        coverage: Coverage::Passthrough,
+        requires: &[],
+        note: "package menu and service entries kept with the packages they describe",
+    },
+    Capability {
+        id: "mvc-snapshots",
+        sections: &["OPNsense", "pfopn_snapshots"],
+        direction: Direction::OpnToPf,
+        coverage: Coverage::Passthrough,
+        requires: &[],
+        note: "OPNsense models are kept in a hidden container and restored on the way back",
+    },
+];
+
+/// Every section transform the converter knows.
+pub fn capabilities() -> &'static [Capability] {
+    CAPABILITIES
+}
+
+/// Capabilities covering a `from` → `to` conversion.
+pub fn capabilities_for(from: &str, to: &str) -> Vec<&'static Capability> {
+    CAPABILITIES
+        .iter()
+        .filter(|c| c.direction.supports(from, to))
+        .collect()
+}
+
+/// How one section of a config would be converted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SectionCoverage {
+    /// Top-level tag, or `container/child`.
+    pub section: String,
+    /// Capabilities that read the section.
+    pub capabilities: Vec<&'static str>,
+    /// Best coverage among them; `None` means the section is out of scope.
+    pub coverage: Option<Coverage>,
+}
+
+/// Classify every section of `root` for a conversion to `to`.
+///
+/// Children of the `installedpackages` and `OPNsense` containers are
+/// classified individually; the container itself is listed only when no
+/// child matched.
+pub fn section_coverage(root: &XmlNode, to: &str) -> Vec<SectionCoverage> {
+    let from = match detect_config(root) {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => "unknown",
+    };
+    let available = capabilities_for(from, to);
+
+    let mut sections = Vec::new();
+    for child in &root.children {
+        if matches!(child.tag.as_str(), "installedpackages" | "OPNsense") {
+            for nested in &child.children {
+                sections.push(format!("{}/{}", child.tag, nested.tag));
+            }
+        }
+        sections.push(child.tag.clone());
+    }
+    sections.sort();
+    sections.dedup();
+
+    let mut out: Vec<SectionCoverage> = sections
+        .into_iter()
+        .map(|section| {
+            let matched: Vec<&Capability> = available
+                .iter()
+                .copied()
+                .filter(|c| c.sections.contains(&section.as_str()))
+                .collect();
+            SectionCoverage {
+                coverage: matched.iter().map(|c| c.coverage).max(),
+                capabilities: matched.iter().map(|c| c.id).collect(),
+                section,
+            }
+        })
+        .collect();
+
+    // A container is covered when any of its children is.
+    let covered_containers: Vec<String> = out
+        .iter()
+        .filter(|s| s.coverage.is_some())
+        .filter_map(|s| s.section.split_once('/').map(|(c, _)| c.to_string()))
+        .collect();
+    out.retain(|s| !covered_containers.contains(&s.section));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{capabilities, capabilities_for, section_coverage, Coverage};
+
+    #[test]
+    fn capability_ids_are_unique() {
+        let mut ids: Vec<_> = capabilities().iter().map(|c| c.id).collect();
+        let total = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), total);
+    }
+
+    #[test]
+    fn filters_capabilities_by_direction() {
+        let pf_to_opn = capabilities_for("pfsense", "opnsense");
+        assert!(pf_to_opn.iter().all(|c| c.id != "mvc-snapshots"));
+        let opn_to_pf = capabilities_for("opnsense", "pfsense");
+        assert!(opn_to_pf.iter().any(|c| c.id == "mvc-snapshots"));
+    }
+
+    #[test]
+    fn classifies_config_sections() {
+        let root = parse(
+            br#"<pfsense><system/><syslog/><squid/><installedpackages><tailscale/><nrpe/></installedpackages></pfsense>"#,
+        )
+        .expect("parse");
+
+        let coverage = section_coverage(&root, "opnsense");
+        let find = |name: &str| coverage.iter().find(|s| s.section == name);
+
+        assert_eq!(
+            find("system").and_then(|s| s.coverage),
+            Some(Coverage::Partial)
+        );
+        assert_eq!(
+            find("syslog").and_then(|s| s.coverage),
+            Some(Coverage::Passthrough)
+        );
+        assert_eq!(find("squid").map(|s| s.coverage), Some(None));
+        assert_eq!(
+            find("installedpackages/tailscale").map(|s| s.capabilities.clone()),
+            Some(vec!["tailscale"])
+        );
+        assert_eq!(
+            find("installedpackages/nrpe").map(|s| s.coverage),
+            Some(None)
+        );
+        assert!(find("installedpackages").is_none());
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{bail, Context, Result};
+use pfopn_convert::capabilities::{capabilities, section_coverage, Capability};
+use xml_diff_core::parse_file;
+
+use crate::cli::{CapabilitiesArgs, OutputFormat, Platform};
+
+/// List supported transforms, or classify the sections of one config.
+pub fn run_capabilities(args: CapabilitiesArgs) -> Result<()> {
+    let from = platform_name(args.from, "--from")?;
+    let to = platform_name(args.to, "--to")?;
+
+    if let Some(file) = &args.file {
+        let root =
+            parse_file(file).with_context(|| format!("failed to parse {}", file.display()))?;
+        let to = to.unwrap_or_default();
+        let sections = section_coverage(&root, to);
+        match args.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&sections)?),
+            OutputFormat::Text => {
+                let out_of_scope = sections.iter().filter(|s| s.coverage.is_none()).count();
+                println!(
+                    "capabilities: {} section(s), {} out of scope for {to}",
+                    sections.len(),
+                    out_of_scope
+                );
+                for section in &sections {
+                    match section.coverage {
+                        Some(coverage) => println!(
+                            "{:<40} {:<12} {}",
+                            section.section,
+                            coverage.as_str(),
+                            section.capabilities.join(", ")
+                        ),
+                        None => println!("{:<40} out-of-scope", section.section),
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let listed: Vec<&Capability> = capabilities()
+        .iter()
+        .filter(|c| matches_direction(c, from, to))
+        .collect();
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&listed)?),
+        OutputFormat::Text => {
+            for capability in listed {
+                println!(
+                    "{:<24} {:<10} {:<12} {}",
+                    capability.id,
+                    capability.direction.as_str(),
+                    capability.coverage.as_str(),
+                    capability.sections.join(", ")
+                );
+                for requirement in capability.requires {
+                    println!("  requires: {requirement}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn matches_direction(capability: &Capability, from: Option<&str>, to: Option<&str>) -> bool {
+    match (from, to) {
+        (Some(from), Some(to)) => capability.direction.supports(from, to),
+        (Some(from), None) => capability.direction.supports(from, other(from)),
+        (None, Some(to)) => capability.direction.supports(other(to), to),
+        (None, None) => true,
+    }
+}
+
+fn other(platform: &str) -> &'static str {
+    if platform == "pfsense" {
+        "opnsense"
+    } else {
+        "pfsense"
+    }
+}
+
+fn platform_name(platform: Option<Platform>, flag: &str) -> Result<Option<&'static str>> {
+    match platform {
+        Some(Platform::Pfsense) => Ok(Some("pfsense")),
+        Some(Platform::Opnsense) => Ok(Some("opnsense")),
+        Some(Platform::Auto) => bail!("{flag} cannot be auto; specify pfsense or opnsense"),
+        None => Ok(None),
+    }
+}
@@ -1,6 +1,11 @@
+use std::net::IpAddr;
 use std::path::PathBuf;
 
 use clap::{Parser, ValueEnum};
+use pfopn_convert::extract::Feature;
+use pfopn_convert::merge::RulePlacement;
+use pfopn_convert::transform::renumber::SubnetMapping;
+use pfopn_convert::verify_fix::FixClass;
 
 #[derive(Parser, Debug)]
 #[command(name = "pfopn-convert")]
@@ -26,6 +31,42 @@ pub enum Command {
     MigrateCheck(MigrateCheckArgs),
     /// Convert one config toward a target platform.
     Convert(ConvertArgs),
+    /// Set, delete, or add nodes by path and write the edited config.
+    Edit(EditArgs),
+    /// Combine two configs of the same platform (aliases, rules, DHCP static maps).
+    Combine(CombineArgs),
+    /// Trace one packet through NAT and filter rules and report the verdict.
+    Simulate(SimulateArgs),
+    /// Compare source and converted filter rules semantically and report rules lost or added.
+    CompareRules(CompareRulesArgs),
+    /// Export the reference graph of config objects as GraphViz DOT or JSON.
+    Graph(GraphArgs),
+    /// Write a minimal config holding one feature and the objects it depends on.
+    Extract(ExtractArgs),
+    /// Print anonymized per-section hashes for drift detection.
+    Fingerprint(FingerprintArgs),
+    /// Show what a config changed from its platform's factory defaults.
+    DefaultsDiff(DefaultsDiffArgs),
+    /// List supported section transforms, or classify a config's sections against them.
+    Capabilities(CapabilitiesArgs),
+    /// Convert many configs from a manifest or directory in parallel.
+    Batch(BatchArgs),
+    /// Check that a target baseline is fit for converting a source onto it.
+    ValidateBaseline(ValidateBaselineArgs),
+    /// Show what each DHCP backend choice would do to every source DHCP interface.
+    DhcpPlan(DhcpPlanArgs),
+    /// Write a config as a lossless JSON or YAML tree document.
+    Export(ExportArgs),
+    /// Rebuild config.xml from a JSON or YAML tree document.
+    Import(ImportArgs),
+    /// Inspect the layered section mappings.
+    Mappings(MappingsArgs),
+    /// Download config.xml from a firewall.
+    #[cfg(feature = "remote")]
+    Fetch(FetchArgs),
+    /// Upload a config to a firewall, keeping the previous one as a backup.
+    #[cfg(feature = "remote")]
+    Push(PushArgs),
 }
 
 #[derive(Parser, Debug)]
@@ -34,8 +75,12 @@ pub struct DiffArgs {
     pub file2: PathBuf,
     #[arg(long)]
     pub section: Option<String>,
+    /// Ignore a path or tag; path queries like `filter.rule[5]` or `//staticmap[mac='..']` are also matched.
     #[arg(long)]
     pub ignore: Vec<String>,
+    /// Ignore a named group of volatile fields: `metadata`, `change-user`, `model-versions`, `stats`, `leases`, `dynamic-state` (comma-separated or repeatable).
+    #[arg(long, value_delimiter = ',')]
+    pub ignore_category: Vec<String>,
     #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
     pub format: OutputFormat,
     #[arg(long)]
@@ -50,6 +95,9 @@ pub struct DiffArgs {
     pub output: Option<PathBuf>,
     #[arg(long)]
     pub strict: bool,
+    /// With --strict, fail only on manual conflicts at or above this confidence (0-100).
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub strict_confidence: u8,
     #[arg(long, value_enum, default_value_t = MergeTo::Right)]
     pub merge_to: MergeTo,
     /// Do not transfer referenced system users for OpenVPN dependencies.
@@ -61,14 +109,32 @@ pub struct DiffArgs {
     /// Do not transfer referenced CAs for OpenVPN dependencies.
     #[arg(long)]
     pub no_transfer_cas: bool,
+    /// Where merged rules go: `source` (after their source predecessor), `append`, `prepend`, or `after:<descr>`.
+    #[arg(long, default_value = "source")]
+    pub rule_placement: RulePlacement,
     /// Show per-section summary table.
     #[arg(long)]
     pub section_summary: bool,
+    /// Compare section fingerprints instead of full trees; each file may be a
+    /// `fingerprint` JSON file or a config XML.
+    #[arg(long)]
+    pub fingerprints: bool,
+    /// Render the report through this minijinja template instead of --format output.
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+    /// User mappings TOML file whose `[key_fields]` override the diff key fields.
+    #[arg(long)]
+    pub mappings_file: Option<PathBuf>,
+    /// Mappings directory (expects sections.toml). Defaults to
+    /// $PFOPN_CONVERT_MAPPINGS_DIR or /usr/local/etc/pfopn-convert/mappings.
+    #[arg(long)]
+    pub mappings_dir: Option<PathBuf>,
 }
 
 #[derive(Parser, Debug)]
 pub struct InspectArgs {
     pub file: PathBuf,
+    /// Section or path query to print, e.g. `interfaces.lan` or `//staticmap[mac='..']`.
     #[arg(long)]
     pub section: Option<String>,
     #[arg(long, default_value_t = 3)]
@@ -78,6 +144,15 @@ pub struct InspectArgs {
     /// Show common plugin detection (declared/configured/enabled).
     #[arg(long)]
     pub plugins: bool,
+    /// Search tags, attributes, and text values and print matching paths.
+    #[arg(long, value_name = "PATTERN")]
+    pub grep: Option<String>,
+    /// Treat the `--grep` pattern as a regular expression instead of plain text.
+    #[arg(long, requires = "grep")]
+    pub regex: bool,
+    /// Limit `--grep` to the nodes matched by this path query, e.g. `filter` or `//rule`.
+    #[arg(long, value_name = "QUERY", requires = "grep")]
+    pub grep_section: Option<String>,
 }
 
 #[derive(Parser, Debug)]
@@ -94,12 +169,38 @@ pub struct SectionsArgs {
     /// Emit grouped extras/unmatched payload as JSON.
     #[arg(long)]
     pub extras_json: bool,
-    /// Optional mappings TOML file. Defaults to pfopn-convert/mappings/sections.toml if present.
-    #[arg(long, conflicts_with = "mappings_dir")]
+    /// User mappings TOML file, applied over the mappings directory and embedded defaults.
+    #[arg(long)]
     pub mappings_file: Option<PathBuf>,
-    /// Optional mappings directory (expects sections.toml, plugins.toml).
-    #[arg(long, conflicts_with = "mappings_file")]
+    /// Mappings directory (expects sections.toml, plugins.toml). Defaults to
+    /// $PFOPN_CONVERT_MAPPINGS_DIR or /usr/local/etc/pfopn-convert/mappings.
+    #[arg(long)]
+    pub mappings_dir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct MappingsArgs {
+    #[command(subcommand)]
+    pub action: MappingsAction,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum MappingsAction {
+    /// Report mappings that reference sections unknown to the section metadata.
+    Lint(MappingsLintArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct MappingsLintArgs {
+    /// User mappings TOML file, applied over the mappings directory and embedded defaults.
+    #[arg(long)]
+    pub mappings_file: Option<PathBuf>,
+    /// Mappings directory (expects sections.toml). Defaults to
+    /// $PFOPN_CONVERT_MAPPINGS_DIR or /usr/local/etc/pfopn-convert/mappings.
+    #[arg(long)]
     pub mappings_dir: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
@@ -127,6 +228,244 @@ pub struct ScanArgs {
     /// Show data source metadata.
     #[arg(long)]
     pub verbose: bool,
+    /// Render the report through this minijinja template instead of --format output.
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct CombineArgs {
+    /// Base config; wins every conflict.
+    pub base: PathBuf,
+    /// Config whose aliases, rules, and static maps are merged into the base.
+    pub other: PathBuf,
+    /// Output file path.
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// Where added rules go: `append`, `prepend`, `after:<descr>`, or `source` (after the base copy of the rule preceding them in the other config).
+    #[arg(long, default_value = "append")]
+    pub rule_placement: RulePlacement,
+    /// Report format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct SimulateArgs {
+    /// Config file to simulate against.
+    pub file: PathBuf,
+    /// Inbound interface (logical name such as `lan`/`opt1`, description, or group).
+    #[arg(long = "in", value_name = "INTERFACE")]
+    pub interface: String,
+    /// Protocol (`tcp`, `udp`, `icmp`, ...).
+    #[arg(long, default_value = "tcp")]
+    pub protocol: String,
+    /// Source address.
+    #[arg(long)]
+    pub src: IpAddr,
+    /// Source port.
+    #[arg(long)]
+    pub src_port: Option<u16>,
+    /// Destination address.
+    #[arg(long)]
+    pub dst: IpAddr,
+    /// Destination port.
+    #[arg(long)]
+    pub dst_port: Option<u16>,
+    /// Report format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct CompareRulesArgs {
+    /// Source config the conversion started from.
+    pub source: PathBuf,
+    /// Converted config to check.
+    pub converted: PathBuf,
+    /// Interface mapping used during conversion (`SRC=DST`, repeatable).
+    #[arg(long, value_name = "SRC=DST")]
+    pub map_interface: Vec<String>,
+    /// Report format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct GraphArgs {
+    /// Config file to graph.
+    pub file: PathBuf,
+    /// Export format.
+    #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+    pub format: GraphFormat,
+    /// Write the graph to this file instead of stdout.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ExtractArgs {
+    /// Config file to extract from.
+    pub file: PathBuf,
+    /// Feature to extract: `openvpn`, `ipsec`, `rules`, `aliases`, `gateways`, or `dhcp` (repeatable).
+    #[arg(long = "feature", required = true)]
+    pub features: Vec<Feature>,
+    /// Output file path.
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// Report format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct FingerprintArgs {
+    /// Config file to fingerprint.
+    pub file: PathBuf,
+    /// Output format (`json` is what `diff --fingerprints` reads).
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+    /// Write the fingerprint to this file instead of stdout.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct DefaultsDiffArgs {
+    /// Config file to compare against its factory defaults.
+    pub file: PathBuf,
+    /// Compare against this release's defaults (`2.7`, `2.8`, `plus`, `25.7`, ...)
+    /// instead of the detected one.
+    #[arg(long)]
+    pub release: Option<String>,
+    /// Only report this section (logical name like `firewall`, or a top-level tag).
+    #[arg(long)]
+    pub section: Option<String>,
+    /// Only print totals and per-section counts.
+    #[arg(long)]
+    pub summary: bool,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// User mappings TOML file whose `[key_fields]` override the diff key fields.
+    #[arg(long)]
+    pub mappings_file: Option<PathBuf>,
+    /// Mappings directory (expects sections.toml). Defaults to
+    /// $PFOPN_CONVERT_MAPPINGS_DIR or /usr/local/etc/pfopn-convert/mappings.
+    #[arg(long)]
+    pub mappings_dir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct CapabilitiesArgs {
+    /// Config to classify; lists only its sections and their coverage (requires --to).
+    #[arg(requires = "to", conflicts_with = "from")]
+    pub file: Option<PathBuf>,
+    /// Only list transforms that support converting from this platform.
+    #[arg(long, value_enum)]
+    pub from: Option<Platform>,
+    /// Only list transforms that support converting to this platform.
+    #[arg(long, value_enum)]
+    pub to: Option<Platform>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+#[command(group(clap::ArgGroup::new("batch_input").required(true).args(["manifest", "dir"])))]
+pub struct BatchArgs {
+    /// TOML manifest listing devices with per-device baselines and options.
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+    /// Convert every `*.xml` file in this directory.
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+    /// Destination platform for devices that do not set one.
+    #[arg(long, value_enum)]
+    pub to: Option<Platform>,
+    /// Target baseline config for devices that do not set one.
+    #[arg(long)]
+    pub target_file: Option<PathBuf>,
+    /// Embedded baseline release for devices that set neither a baseline file nor a release.
+    #[arg(long, conflicts_with = "target_file")]
+    pub target_version: Option<String>,
+    /// Directory receiving `<name>.xml` for devices without an explicit output.
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+    /// TOML file of `[[site]]` tables, added to any in the manifest.
+    #[arg(long)]
+    pub site_map: Option<PathBuf>,
+    /// Number of conversions to run at once (defaults to the CPU count).
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct ValidateBaselineArgs {
+    /// Target baseline config to check.
+    pub file: PathBuf,
+    /// Platform the baseline should be for.
+    #[arg(long, value_enum)]
+    pub to: Option<ScanTarget>,
+    /// Source config that will be converted, enabling interface, backend, and feature checks.
+    #[arg(long)]
+    pub source: Option<PathBuf>,
+    /// DHCP backend policy the conversion will use.
+    #[arg(long, value_enum, default_value_t = DhcpBackend::Auto)]
+    pub backend: DhcpBackend,
+    /// Interface mapping the conversion will use (`SRC=DST`, repeatable).
+    #[arg(long, value_name = "SRC=DST")]
+    pub map_interface: Vec<String>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// Treat warnings as failures.
+    #[arg(long)]
+    pub strict: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct DhcpPlanArgs {
+    /// Source config that will be converted.
+    pub input: PathBuf,
+    /// Destination platform.
+    #[arg(long, value_enum)]
+    pub to: ScanTarget,
+    /// Target baseline config (required unless --target-version is set).
+    #[arg(long)]
+    pub target_file: Option<PathBuf>,
+    /// Build the target baseline from the embedded template for this release.
+    #[arg(long, conflicts_with = "target_file")]
+    pub target_version: Option<String>,
+    /// Interface mapping the conversion will use (`SRC=DST`, repeatable).
+    #[arg(long, value_name = "SRC=DST")]
+    pub map_interface: Vec<String>,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    /// Config file to export.
+    pub file: PathBuf,
+    /// Document format.
+    #[arg(long, value_enum, default_value_t = TreeFormat::Yaml)]
+    pub format: TreeFormat,
+    /// Write the document to this file instead of stdout.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ImportArgs {
+    /// Tree document written by `export`.
+    pub file: PathBuf,
+    /// Document format (default: from the file extension, else YAML).
+    #[arg(long, value_enum)]
+    pub format: Option<TreeFormat>,
+    /// Output config.xml path.
+    #[arg(short, long)]
+    pub output: PathBuf,
 }
 
 #[derive(Parser, Debug)]
@@ -151,6 +490,45 @@ pub struct VerifyArgs {
     /// Treat warnings as failures.
     #[arg(long)]
     pub strict: bool,
+    /// Warn about certs, CAs, aliases, gateways, and schedules nothing refers to.
+    #[arg(long)]
+    pub orphans: bool,
+    /// Audit services and rules reachable from WAN.
+    #[arg(long)]
+    pub exposure: bool,
+    /// Pre-conversion config; exposures it did not have fail verify (implies --exposure).
+    #[arg(long, value_name = "FILE")]
+    pub exposure_source: Option<PathBuf>,
+    /// Pre-conversion config; PXE/netboot DHCP settings it served that are missing fail verify.
+    #[arg(long, value_name = "FILE")]
+    pub netboot_source: Option<PathBuf>,
+    /// Pre-conversion config; bridge members whose interface conversion pruned fail verify.
+    #[arg(long, value_name = "FILE")]
+    pub bridge_source: Option<PathBuf>,
+    /// Check that each OpenVPN server has what client export needs (certificate, CA, key exchange, public address).
+    #[arg(long)]
+    pub openvpn_export: bool,
+    /// Private keys were removed with `convert --strip-private-keys`; report missing keys as info.
+    #[arg(long)]
+    pub keys_stripped: bool,
+    /// Render the report through this minijinja template instead of --format output.
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+    /// Fix a class of findings: `schedules`, `interfaces`, `booleans`, or `uuids` (comma-separated or repeatable).
+    #[arg(long, value_delimiter = ',', value_name = "CLASS")]
+    pub fix: Vec<FixClass>,
+    /// Write the fixed config here (required with --fix unless --dry-run).
+    #[arg(long, value_name = "FILE", requires = "fix")]
+    pub fix_output: Option<PathBuf>,
+    /// List the fixes --fix would apply without writing anything.
+    #[arg(long, requires = "fix")]
+    pub dry_run: bool,
+    /// TOML file of accepted findings (code, path glob or message, justification) to drop from the report.
+    #[arg(long, value_name = "FILE")]
+    pub suppressions: Option<PathBuf>,
+    /// Write every current finding to the --suppressions file as the accepted baseline.
+    #[arg(long, requires = "suppressions")]
+    pub update_baseline: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -164,8 +542,8 @@ pub struct MigrateCheckArgs {
     #[arg(long)]
     pub target_version: Option<String>,
     /// Output format.
-    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
-    pub format: OutputFormat,
+    #[arg(long, value_enum, default_value_t = MigrateCheckFormat::Text)]
+    pub format: MigrateCheckFormat,
     /// Optional profiles directory (expects <dir>/<platform>/<version>.toml).
     #[arg(long)]
     pub profiles_dir: Option<PathBuf>,
@@ -177,12 +555,100 @@ pub struct MigrateCheckArgs {
     pub strict: bool,
 }
 
+#[derive(Parser, Debug)]
+pub struct EditArgs {
+    /// Config file to edit.
+    pub file: PathBuf,
+    /// Output config path.
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// Set text of nodes matching PATH (repeatable).
+    #[arg(long, num_args = 2, value_names = ["PATH", "VALUE"])]
+    pub set: Vec<String>,
+    /// Append inline XML under nodes matching PATH (repeatable).
+    #[arg(long, num_args = 2, value_names = ["PATH", "XML"])]
+    pub add: Vec<String>,
+    /// Delete nodes matching PATH (repeatable).
+    #[arg(long, value_name = "PATH")]
+    pub delete: Vec<String>,
+    /// Run verify on the edited config after writing it.
+    #[arg(long)]
+    pub verify: bool,
+}
+
+#[cfg(feature = "remote")]
+#[derive(clap::Args, Debug)]
+pub struct RemoteArgs {
+    /// Firewall platform.
+    #[arg(long, value_enum)]
+    pub platform: ScanTarget,
+    /// SSH destination (user@host or an ssh config alias).
+    #[arg(long, conflicts_with = "api_url", required_unless_present = "api_url")]
+    pub ssh: Option<String>,
+    /// SSH port.
+    #[arg(long, requires = "ssh")]
+    pub ssh_port: Option<u16>,
+    /// SSH private key file.
+    #[arg(long, requires = "ssh")]
+    pub ssh_identity: Option<PathBuf>,
+    /// OPNsense base URL for the REST API (for example https://fw.example.com).
+    #[arg(long, requires = "api_key_file")]
+    pub api_url: Option<String>,
+    /// OPNsense apikey.txt file (`key=...` and `secret=...` lines).
+    #[arg(long, requires = "api_url")]
+    pub api_key_file: Option<PathBuf>,
+    /// Accept self-signed certificates on the REST API.
+    #[arg(long, requires = "api_url")]
+    pub insecure: bool,
+}
+
+#[cfg(feature = "remote")]
+#[derive(Parser, Debug)]
+pub struct FetchArgs {
+    #[command(flatten)]
+    pub remote: RemoteArgs,
+    /// Where to write the downloaded config.
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+#[cfg(feature = "remote")]
+#[derive(Parser, Debug)]
+pub struct PushArgs {
+    /// Config file to upload.
+    pub file: PathBuf,
+    #[command(flatten)]
+    pub remote: RemoteArgs,
+    /// Upload even if verify reports errors.
+    #[arg(long)]
+    pub force: bool,
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 pub enum OutputFormat {
     Text,
     Json,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TreeFormat {
+    Json,
+    Yaml,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum MigrateCheckFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 pub enum MergeTo {
     Left,
@@ -203,6 +669,19 @@ pub enum DhcpBackend {
     Isc,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum LanLiterals {
+    Rewrite,
+    Review,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum StaticMapDns {
+    Off,
+    Materialize,
+    Verify,
+}
+
 #[derive(Parser, Debug)]
 pub struct ConvertArgs {
     /// Source config file to convert.
@@ -216,9 +695,13 @@ pub struct ConvertArgs {
     /// Destination platform.
     #[arg(long, value_enum)]
     pub to: Platform,
-    /// Target baseline/template config (required unless --minimal-template is set).
+    /// Target baseline/template config (required unless --target-version or --minimal-template is set).
     #[arg(long)]
     pub target_file: Option<PathBuf>,
+    /// Build the target baseline from the embedded template for this release
+    /// (for example 26.1, 25.7, 2.7.2, plus) instead of a --target-file.
+    #[arg(long, conflicts_with = "target_file")]
+    pub target_version: Option<String>,
     /// Build from a minimal target root instead of requiring --target-file (dev/testing only).
     #[arg(long)]
     pub minimal_template: bool,
@@ -234,10 +717,58 @@ pub struct ConvertArgs {
     /// Set LAN IPv4 address on generated output and remap LAN DHCP IPv4 values accordingly.
     #[arg(long)]
     pub lan_ip: Option<String>,
+    /// With --lan-ip, rewrite (`rewrite`) or only report (`review`) old-LAN addresses in rules, NAT, and aliases.
+    #[arg(long, value_enum, default_value_t = LanLiterals::Rewrite, requires = "lan_ip")]
+    pub lan_literals: LanLiterals,
+    /// Move every address in one subnet into another, keeping host bits (`OLD-CIDR=NEW-CIDR`, repeatable).
+    #[arg(long, value_name = "OLD=NEW")]
+    pub renumber: Vec<SubnetMapping>,
     /// Disable DHCP services in generated output (safety guard for lab restores).
     #[arg(long)]
     pub disable_dhcp: bool,
     /// DHCP backend policy for target conversion.
     #[arg(long, value_enum, default_value_t = DhcpBackend::Auto)]
     pub backend: DhcpBackend,
+    /// Map a source logical interface onto a different target interface (`SRC=DST`, repeatable).
+    #[arg(long, value_name = "SRC=DST")]
+    pub map_interface: Vec<String>,
+    /// Do not embed OPNsense round-trip snapshots in pfSense output (or restore them).
+    #[arg(long)]
+    pub no_snapshots: bool,
+    /// Per-section policy file (TOML `[sections]`: ignore, copy-verbatim, transform, force-target-default).
+    #[arg(long, value_name = "FILE")]
+    pub policy: Option<PathBuf>,
+    /// Remove certs, CAs, aliases, gateways, and schedules nothing refers to from the output.
+    #[arg(long)]
+    pub prune_orphans: bool,
+    /// Keep sections, package configs, and models the target cannot hold under `<pfopn_preserved>` instead of dropping them.
+    #[arg(long)]
+    pub keep_unknown: bool,
+    /// Remove private keys from certificates and CAs, for targets whose keys are provisioned separately (HSM, ACME).
+    #[arg(long)]
+    pub strip_private_keys: bool,
+    /// Add (`materialize`) or check (`verify`) Unbound host overrides for DHCP static mapping hostnames.
+    #[arg(long, value_enum, default_value_t = StaticMapDns::Off)]
+    pub static_map_dns: StaticMapDns,
+    /// Keep the baseline's `<revision>` instead of stamping one for this conversion.
+    #[arg(long)]
+    pub no_revision: bool,
+    /// Username written to the `<revision>` stamp.
+    #[arg(long, value_name = "NAME", conflicts_with = "no_revision")]
+    pub revision_user: Option<String>,
+    /// Also write a hidden `<pfopn_provenance>` marker with the tool version and options used.
+    #[arg(long, conflicts_with = "no_revision")]
+    pub provenance: bool,
+    /// Walk through conversion decisions with prompts before converting.
+    #[arg(long)]
+    pub interactive: bool,
+    /// Print per-stage timings (parse, diff, merge, each transform, DHCP migration, write) to stderr.
+    #[arg(long)]
+    pub progress: bool,
+    /// Fail without writing output if any section is pruned or copied untranslated, or any warning is reported.
+    #[arg(long)]
+    pub strict: bool,
+    /// Render the conversion report through this minijinja template instead of the summary lines.
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
 }
@@ -0,0 +1,593 @@
+//! Combine two configs of the same platform.
+//!
+//! Homelab users often run two firewalls (or rebuild one from scratch) and
+//! want a single config carrying both sets of objects. Unlike
+//! [`crate::merge::apply_safe_merge`], which inserts missing nodes
+//! structurally, this works entity by entity:
+//!
+//! - **Aliases** — matched by name; entries of same-typed aliases are unioned
+//! - **Filter rules** — inserted as a block at the [`RulePlacement`] spot
+//!   (after the base rules by default), skipping rules whose match
+//!   fingerprint already exists (same logic as duplicate-rule verify); with
+//!   [`RulePlacement::Source`] each skipped duplicate moves the insertion
+//!   point to just after its base copy, so shared rules anchor the new ones
+//! - **DHCP static maps** — matched by MAC and IP per interface
+//!
+//! The base config wins every conflict; each name collision is reported.
+//! Everything else (interfaces, system, services) comes from the base.
+//! Kea reservations and OPNsense MVC filter rules are not combined.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use thiserror::Error;
+use xml_diff_core::XmlNode;
+
+use crate::alias_members::union_alias_entries;
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::diagnostics::Diagnostics;
+use crate::merge::{placement_index, RulePlacement};
+use crate::uuid_registry::stable_uuid;
+use crate::verify_rule_dupes::fingerprint;
+
+/// Object counts for a combine run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CombineStats {
+    pub aliases_added: usize,
+    pub aliases_merged: usize,
+    pub rules_added: usize,
+    pub rules_skipped: usize,
+    pub staticmaps_added: usize,
+    pub staticmaps_skipped: usize,
+    pub collisions: usize,
+}
+
+/// Combined tree plus what happened to each entity type.
+#[derive(Debug, Clone)]
+pub struct CombineOutcome {
+    pub tree: XmlNode,
+    pub stats: CombineStats,
+    /// Name collisions and other issues to review before restore.
+    pub diagnostics: Diagnostics,
+}
+
+#[derive(Debug, Error)]
+pub enum CombineError {
+    #[error("unable to detect platform of {0} config")]
+    UnknownPlatform(&'static str),
+    #[error(
+        "cannot combine a {base} config with a {other} config; both must be the same platform"
+    )]
+    PlatformMismatch {
+        base: &'static str,
+        other: &'static str,
+    },
+}
+
+/// Combine `other` into `base`, returning the merged tree and a report.
+///
+/// `placement` decides where added filter rules go among the base rules.
+///
+/// # Errors
+///
+/// Returns error if either platform cannot be detected or they differ.
+pub fn combine_configs(
+    base: &XmlNode,
+    other: &XmlNode,
+    placement: &RulePlacement,
+) -> Result<CombineOutcome, CombineError> {
+    let platform = platform_name(base).ok_or(CombineError::UnknownPlatform("base"))?;
+    let other_platform = platform_name(other).ok_or(CombineError::UnknownPlatform("other"))?;
+    if platform != other_platform {
+        return Err(CombineError::PlatformMismatch {
+            base: platform,
+            other: other_platform,
+        });
+    }
+
+    let mut tree = base.clone();
+    let mut stats = CombineStats::default();
+    let mut diagnostics = Diagnostics::new();
+    combine_aliases(&mut tree, other, platform, &mut stats, &mut diagnostics);
+    combine_rules(&mut tree, other, placement, &mut stats, &mut diagnostics);
+    combine_staticmaps(&mut tree, other, &mut stats, &mut diagnostics);
+    Ok(CombineOutcome {
+        tree,
+        stats,
+        diagnostics,
+    })
+}
+
+fn platform_name(root: &XmlNode) -> Option<&'static str> {
+    match detect_config(root) {
+        ConfigFlavor::PfSense => Some("pfsense"),
+        ConfigFlavor::OpnSense => Some("opnsense"),
+        ConfigFlavor::Unknown => None,
+    }
+}
+
+/// Location of the alias list for a platform.
+fn alias_path(platform: &str) -> &'static [&'static str] {
+    match platform {
+        "opnsense" => &["OPNsense", "Firewall", "Alias", "aliases"],
+        _ => &["aliases"],
+    }
+}
+
+fn combine_aliases(
+    tree: &mut XmlNode,
+    other: &XmlNode,
+    platform: &str,
+    stats: &mut CombineStats,
+    diagnostics: &mut Diagnostics,
+) {
+    let path = alias_path(platform);
+    let Some(incoming) = find_path(other, path) else {
+        return;
+    };
+    let incoming: Vec<_> = incoming
+        .children
+        .iter()
+        .filter(|c| c.tag == "alias")
+        .cloned()
+        .collect();
+    if incoming.is_empty() {
+        return;
+    }
+    let container = ensure_path_mut(tree, path);
+    let mut uuids = collect_uuids(container, "alias");
+
+    for mut alias in incoming {
+        let name = text(&alias, "name");
+        let existing = container
+            .children
+            .iter_mut()
+            .find(|c| c.tag == "alias" && text(c, "name").eq_ignore_ascii_case(&name));
+        let Some(existing) = existing else {
+            ensure_unique_uuid(&mut alias, &name, &mut uuids);
+            container.children.push(alias);
+            stats.aliases_added += 1;
+            continue;
+        };
+
+        let (base_type, other_type) = (text(existing, "type"), text(&alias, "type"));
+        if !base_type.eq_ignore_ascii_case(&other_type) {
+            stats.collisions += 1;
+            diagnostics.warn(
+                "aliases",
+                format!(
+                    "alias '{name}' is a {base_type} alias in base but {other_type} in other; kept base"
+                ),
+            );
+            continue;
+        }
+        if union_alias_entries(existing, &alias, platform) {
+            stats.aliases_merged += 1;
+        }
+    }
+}
+
+fn combine_rules(
+    tree: &mut XmlNode,
+    other: &XmlNode,
+    placement: &RulePlacement,
+    stats: &mut CombineStats,
+    diagnostics: &mut Diagnostics,
+) {
+    let incoming: Vec<_> = other
+        .get_child("filter")
+        .map(|f| f.get_children("rule"))
+        .unwrap_or_default();
+    if incoming.is_empty() {
+        return;
+    }
+    let base_ifaces = interface_names(tree);
+    let other_ifaces = interface_names(other);
+    let filter = ensure_path_mut(tree, &["filter"]);
+    let mut fingerprints: BTreeSet<_> = filter
+        .get_children("rule")
+        .into_iter()
+        .map(fingerprint)
+        .collect();
+    let mut trackers: BTreeSet<String> = filter
+        .get_children("rule")
+        .iter()
+        .map(|r| text(r, "tracker"))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut uuids = collect_uuids(filter, "rule");
+    let append_at = |children: &[XmlNode]| {
+        placement_index(children, "rule", &RulePlacement::Append).unwrap_or(children.len())
+    };
+    let mut insert_at = match placement_index(&filter.children, "rule", placement) {
+        Some(idx) => idx,
+        None => {
+            if let RulePlacement::After(descr) = placement {
+                diagnostics.warn(
+                    "filter",
+                    format!("no base rule described '{descr}'; appending added rules"),
+                );
+            }
+            append_at(&filter.children)
+        }
+    };
+
+    for rule in incoming {
+        let print = fingerprint(rule);
+        if fingerprints.contains(&print) {
+            stats.rules_skipped += 1;
+            if *placement == RulePlacement::Source {
+                if let Some(idx) = filter
+                    .children
+                    .iter()
+                    .position(|c| c.tag == "rule" && fingerprint(c) == print)
+                {
+                    insert_at = idx + 1;
+                }
+            }
+            continue;
+        }
+        fingerprints.insert(print);
+        let mut rule = rule.clone();
+        let tracker = text(&rule, "tracker");
+        if !tracker.is_empty() && trackers.contains(&tracker) {
+            let fresh = next_tracker(&trackers);
+            set_text(&mut rule, "tracker", &fresh);
+            diagnostics.info(
+                "filter",
+                format!("rule tracker {tracker} already used in base; renumbered to {fresh}"),
+            );
+            trackers.insert(fresh);
+        } else if !tracker.is_empty() {
+            trackers.insert(tracker);
+        }
+        let seed = format!("rule{insert_at}{}", text(&rule, "descr"));
+        ensure_unique_uuid(&mut rule, &seed, &mut uuids);
+
+        for iface in text(&rule, "interface").split(',').map(str::trim) {
+            if other_ifaces.contains(iface) && !base_ifaces.contains(iface) {
+                diagnostics.warn(
+                    "filter",
+                    format!(
+                        "rule '{}' uses interface {iface}, which is not assigned in base",
+                        text(&rule, "descr")
+                    ),
+                );
+            }
+        }
+
+        filter.children.insert(insert_at, rule);
+        insert_at += 1;
+        stats.rules_added += 1;
+    }
+}
+
+fn combine_staticmaps(
+    tree: &mut XmlNode,
+    other: &XmlNode,
+    stats: &mut CombineStats,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(other_dhcpd) = other.get_child("dhcpd") else {
+        return;
+    };
+    for other_iface in &other_dhcpd.children {
+        let maps = other_iface.get_children("staticmap");
+        if maps.is_empty() {
+            continue;
+        }
+        let iface = other_iface.tag.as_str();
+        let base_iface = tree
+            .children
+            .iter_mut()
+            .find(|c| c.tag == "dhcpd")
+            .and_then(|d| d.children.iter_mut().find(|c| c.tag == iface));
+        let Some(base_iface) = base_iface else {
+            stats.staticmaps_skipped += maps.len();
+            diagnostics.warn(
+                "dhcpd",
+                format!(
+                    "{iface}: no DHCP server in base; {} static mapping(s) not merged",
+                    maps.len()
+                ),
+            );
+            continue;
+        };
+
+        for map in maps {
+            let mac = text(map, "mac").to_ascii_lowercase();
+            let ip = text(map, "ipaddr");
+            let same_mac = base_iface
+                .get_children("staticmap")
+                .into_iter()
+                .find(|m| !mac.is_empty() && text(m, "mac").eq_ignore_ascii_case(&mac))
+                .map(|m| text(m, "ipaddr"));
+            let same_ip = base_iface
+                .get_children("staticmap")
+                .into_iter()
+                .find(|m| !ip.is_empty() && text(m, "ipaddr") == ip)
+                .map(|m| text(m, "mac"));
+
+            match (same_mac, same_ip) {
+                (Some(base_ip), _) if base_ip == ip => {
+                    stats.staticmaps_skipped += 1;
+                }
+                (Some(base_ip), _) => {
+                    stats.staticmaps_skipped += 1;
+                    stats.collisions += 1;
+                    diagnostics.warn(
+                        "dhcpd",
+                        format!(
+                            "{iface}: {mac} is mapped to {base_ip} in base and {ip} in other; kept base"
+                        ),
+                    );
+                }
+                (None, Some(base_mac)) => {
+                    stats.staticmaps_skipped += 1;
+                    stats.collisions += 1;
+                    diagnostics.warn(
+                        "dhcpd",
+                        format!(
+                            "{iface}: {ip} is reserved for {base_mac} in base and {mac} in other; kept base"
+                        ),
+                    );
+                }
+                (None, None) => {
+                    let insert_at = base_iface
+                        .children
+                        .iter()
+                        .rposition(|c| c.tag == "staticmap")
+                        .map_or(base_iface.children.len(), |idx| idx + 1);
+                    base_iface.children.insert(insert_at, map.clone());
+                    stats.staticmaps_added += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Render a one-screen summary of a combine run.
+pub fn render_combine_text(stats: &CombineStats) -> String {
+    format!(
+        "aliases: added={} merged={}\nrules: added={} duplicates_skipped={}\nstatic_maps: added={} skipped={}\ncollisions={}",
+        stats.aliases_added,
+        stats.aliases_merged,
+        stats.rules_added,
+        stats.rules_skipped,
+        stats.staticmaps_added,
+        stats.staticmaps_skipped,
+        stats.collisions
+    )
+}
+
+fn interface_names(root: &XmlNode) -> BTreeSet<String> {
+    root.get_child("interfaces")
+        .map(|i| i.children.iter().map(|c| c.tag.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Next numeric tracker above every existing one.
+fn next_tracker(trackers: &BTreeSet<String>) -> String {
+    let max = trackers
+        .iter()
+        .filter_map(|t| t.parse::<u64>().ok())
+        .max()
+        .unwrap_or(0);
+    (max + 1).to_string()
+}
+
+fn collect_uuids(container: &XmlNode, tag: &str) -> BTreeSet<String> {
+    container
+        .children
+        .iter()
+        .filter(|c| c.tag == tag)
+        .filter_map(|c| c.attributes.get("uuid").cloned())
+        .collect()
+}
+
+/// Replace a `uuid` attribute that is already used in the base list.
+fn ensure_unique_uuid(node: &mut XmlNode, seed: &str, used: &mut BTreeSet<String>) {
+    let Some(uuid) = node.attributes.get("uuid").cloned() else {
+        return;
+    };
+    let mut candidate = uuid;
+    let mut idx = 0;
+    while used.contains(&candidate) {
+        candidate = stable_uuid("combine", &format!("{seed}#{idx}"));
+        idx += 1;
+    }
+    node.attributes
+        .insert("uuid".to_string(), candidate.clone());
+    used.insert(candidate);
+}
+
+fn find_path<'a>(root: &'a XmlNode, path: &[&str]) -> Option<&'a XmlNode> {
+    path.iter().try_fold(root, |node, tag| node.get_child(tag))
+}
+
+fn ensure_path_mut<'a>(root: &'a mut XmlNode, path: &[&str]) -> &'a mut XmlNode {
+    let mut node = root;
+    for tag in path {
+        let idx = match node.children.iter().position(|c| c.tag == *tag) {
+            Some(idx) => idx,
+            None => {
+                node.children.push(XmlNode::new(*tag));
+                node.children.len() - 1
+            }
+        };
+        node = &mut node.children[idx];
+    }
+    node
+}
+
+fn text(node: &XmlNode, tag: &str) -> String {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .unwrap_or("")
+        .to_string()
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{combine_configs, CombineError, RulePlacement};
+
+    #[test]
+    fn combines_pfsense_aliases_rules_and_staticmaps() {
+        let base = parse(
+            br#"<pfsense><interfaces><lan/></interfaces>
+                <aliases><alias><name>servers</name><type>host</type><address>10.0.0.1</address><detail>web</detail></alias><alias><name>ports</name><type>port</type><address>80</address></alias></aliases>
+                <filter><rule><tracker>100</tracker><type>pass</type><interface>lan</interface><source><any/></source><destination><any/></destination><descr>allow lan</descr></rule><separator/></filter>
+                <dhcpd><lan><staticmap><mac>aa:bb:cc:00:00:01</mac><ipaddr>10.0.0.10</ipaddr></staticmap></lan></dhcpd>
+            </pfsense>"#,
+        )
+        .expect("parse");
+        let other = parse(
+            br#"<pfsense><interfaces><lan/><opt1/></interfaces>
+                <aliases><alias><name>servers</name><type>host</type><address>10.0.0.1 10.0.0.2</address><detail>web||db</detail></alias><alias><name>ports</name><type>host</type><address>10.9.9.9</address></alias><alias><name>nas</name><type>host</type><address>10.0.0.5</address></alias></aliases>
+                <filter><rule><tracker>999</tracker><type>pass</type><interface>lan</interface><source><any/></source><destination><any/></destination><descr>dupe</descr></rule><rule><tracker>100</tracker><type>block</type><interface>opt1</interface><source><any/></source><destination><any/></destination><descr>iot block</descr></rule></filter>
+                <dhcpd><lan><staticmap><mac>AA:BB:CC:00:00:01</mac><ipaddr>10.0.0.10</ipaddr></staticmap><staticmap><mac>aa:bb:cc:00:00:02</mac><ipaddr>10.0.0.10</ipaddr></staticmap><staticmap><mac>aa:bb:cc:00:00:03</mac><ipaddr>10.0.0.11</ipaddr></staticmap></lan><opt1><staticmap><mac>aa:bb:cc:00:00:04</mac></staticmap></opt1></dhcpd>
+            </pfsense>"#,
+        )
+        .expect("parse");
+
+        let outcome = combine_configs(&base, &other, &RulePlacement::Append).expect("combine");
+        let stats = &outcome.stats;
+        assert_eq!(stats.aliases_added, 1);
+        assert_eq!(stats.aliases_merged, 1);
+        assert_eq!(stats.rules_added, 1);
+        assert_eq!(stats.rules_skipped, 1);
+        assert_eq!(stats.staticmaps_added, 1);
+        assert_eq!(stats.staticmaps_skipped, 3);
+        assert_eq!(stats.collisions, 2);
+
+        let tree = &outcome.tree;
+        let aliases = tree
+            .get_child("aliases")
+            .expect("aliases")
+            .get_children("alias");
+        assert_eq!(aliases.len(), 3);
+        assert_eq!(aliases[0].get_text(&["address"]), Some("10.0.0.1 10.0.0.2"));
+        assert_eq!(aliases[0].get_text(&["detail"]), Some("web||db"));
+        assert_eq!(aliases[1].get_text(&["address"]), Some("80"));
+
+        let filter = tree.get_child("filter").expect("filter");
+        let tags: Vec<_> = filter.children.iter().map(|c| c.tag.as_str()).collect();
+        assert_eq!(tags, vec!["rule", "rule", "separator"]);
+        assert_eq!(filter.children[1].get_text(&["tracker"]), Some("101"));
+
+        assert_eq!(
+            tree.get_child("dhcpd")
+                .and_then(|d| d.get_child("lan"))
+                .map(|l| l.get_children("staticmap").len()),
+            Some(2)
+        );
+        let messages: Vec<_> = outcome
+            .diagnostics
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect();
+        assert!(messages.iter().any(|m| m.contains("alias 'ports'")));
+        assert!(messages.iter().any(|m| m.contains("not assigned in base")));
+        assert!(messages.iter().any(|m| m.contains("10.0.0.10 is reserved")));
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("opt1: no DHCP server")));
+    }
+
+    #[test]
+    fn unions_opnsense_alias_content_and_renews_colliding_uuids() {
+        let base = parse(
+            br#"<opnsense><OPNsense><Firewall><Alias><aliases><alias uuid="u1"><name>lan_hosts</name><type>host</type><content>10.0.0.1</content></alias></aliases></Alias></Firewall></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let other = parse(
+            br#"<opnsense><OPNsense><Firewall><Alias><aliases><alias uuid="u9"><name>lan_hosts</name><type>host</type><content>10.0.0.1
+10.0.0.2</content></alias><alias uuid="u1"><name>dmz</name><type>network</type><content>172.16.0.0/24</content></alias></aliases></Alias></Firewall></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+
+        let outcome = combine_configs(&base, &other, &RulePlacement::Append).expect("combine");
+        let aliases = outcome
+            .tree
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("Firewall"))
+            .and_then(|f| f.get_child("Alias"))
+            .and_then(|a| a.get_child("aliases"))
+            .expect("aliases")
+            .get_children("alias");
+        assert_eq!(
+            aliases[0].get_text(&["content"]),
+            Some("10.0.0.1\n10.0.0.2")
+        );
+        assert_ne!(
+            aliases[1].attributes.get("uuid").map(String::as_str),
+            Some("u1")
+        );
+        assert!(outcome.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn places_added_rules_by_policy() {
+        let config = |descrs: &[&str]| {
+            let body: String = descrs
+                .iter()
+                .map(|d| {
+                    format!("<rule><type>pass</type><interface>lan</interface><source><any/></source><destination><address>{d}</address></destination><descr>{d}</descr></rule>")
+                })
+                .collect();
+            parse(
+                format!(
+                    "<pfsense><interfaces><lan/></interfaces><filter>{body}</filter></pfsense>"
+                )
+                .as_bytes(),
+            )
+            .expect("parse")
+        };
+        let base = config(&["a", "b", "c"]);
+        let other = config(&["b", "x", "y"]);
+        let placed = |placement: RulePlacement| {
+            combine_configs(&base, &other, &placement)
+                .expect("combine")
+                .tree
+                .get_child("filter")
+                .expect("filter")
+                .get_children("rule")
+                .into_iter()
+                .filter_map(|rule| rule.get_text(&["descr"]).map(str::to_string))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(placed(RulePlacement::Append), ["a", "b", "c", "x", "y"]);
+        assert_eq!(placed(RulePlacement::Prepend), ["x", "y", "a", "b", "c"]);
+        assert_eq!(placed(RulePlacement::Source), ["a", "b", "x", "y", "c"]);
+        assert_eq!(
+            placed(RulePlacement::After("a".to_string())),
+            ["a", "x", "y", "b", "c"]
+        );
+        let outcome = combine_configs(&base, &other, &RulePlacement::After("zz".to_string()))
+            .expect("combine");
+        assert_eq!(outcome.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn rejects_mixed_platforms() {
+        let base = parse(br#"<pfsense/>"#).expect("parse");
+        let other = parse(br#"<opnsense/>"#).expect("parse");
+        assert!(matches!(
+            combine_configs(&base, &other, &RulePlacement::Append),
+            Err(CombineError::PlatformMismatch { .. })
+        ));
+    }
+}
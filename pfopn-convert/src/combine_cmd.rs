@@ -0,0 +1,42 @@
+use anyhow::{Context, Result};
+use pfopn_convert::canonical;
+use pfopn_convert::combine::{combine_configs, render_combine_text};
+use serde_json::json;
+use xml_diff_core::{parse_file, write_file_with_options};
+
+use crate::cli::{CombineArgs, OutputFormat};
+use crate::path_guard::ensure_output_not_same;
+
+/// Combine two same-platform configs and write the result.
+pub fn run_combine(args: CombineArgs) -> Result<()> {
+    ensure_output_not_same(&args.output, &[&args.base, &args.other])?;
+    let base = parse_file(&args.base)
+        .with_context(|| format!("failed to parse {}", args.base.display()))?;
+    let other = parse_file(&args.other)
+        .with_context(|| format!("failed to parse {}", args.other.display()))?;
+
+    let outcome = combine_configs(&base, &other, &args.rule_placement)?;
+    write_file_with_options(
+        &outcome.tree,
+        &args.output,
+        &canonical::write_options(&outcome.tree.tag),
+    )
+    .with_context(|| format!("failed to write output XML {}", args.output.display()))?;
+
+    match args.format {
+        OutputFormat::Text => {
+            for diagnostic in &outcome.diagnostics {
+                eprintln!("{diagnostic}");
+            }
+            println!("{}", render_combine_text(&outcome.stats));
+        }
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "stats": outcome.stats,
+                "diagnostics": outcome.diagnostics,
+            }))?
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anyhow::{bail, Context, Result};
+use pfopn_convert::rule_equivalence::{compare_rulesets, render_rule_equivalence_text};
+use xml_diff_core::parse_file;
+
+use crate::cli::{CompareRulesArgs, OutputFormat};
+use crate::convert::parse_interface_map;
+
+/// Compare source and converted rulesets; fails when a source rule is lost.
+pub fn run_compare_rules(args: CompareRulesArgs) -> Result<()> {
+    let source = parse_file(&args.source)
+        .with_context(|| format!("failed to parse {}", args.source.display()))?;
+    let converted = parse_file(&args.converted)
+        .with_context(|| format!("failed to parse {}", args.converted.display()))?;
+    let interface_map = parse_interface_map(&args.map_interface)?;
+
+    let report = compare_rulesets(&source, &converted, &interface_map);
+    match args.format {
+        OutputFormat::Text => println!("{}", render_rule_equivalence_text(&report)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    if !report.equivalent() {
+        bail!(
+            "compare-rules failed: {} source rule(s) have no equivalent in the converted config",
+            report.missing.len()
+        );
+    }
+    Ok(())
+}
@@ -1,6 +1,14 @@
+use std::cmp::Ordering;
+
 use serde::Serialize;
 use xml_diff_core::XmlNode;
 
+use crate::encoding::EncodingRepair;
+use crate::legacy::LegacyUpgrade;
+use crate::orphans::Orphan;
+use crate::target_prune::{PruneKind, PrunedNode};
+use crate::transform::certs::CertRemap;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct ConversionSummary {
     pub interfaces: usize,
@@ -34,6 +42,174 @@ pub fn render(summary: ConversionSummary) -> String {
     )
 }
 
+/// Counts of one kind of object before and after conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SectionDelta {
+    pub section: &'static str,
+    pub source: usize,
+    /// Count in the target baseline the output was merged into.
+    pub baseline: usize,
+    pub output: usize,
+}
+
+/// Object kinds compared by [`section_deltas`], with the paths holding them
+/// on either platform; `*` matches any tag.
+const DELTA_SECTIONS: &[(&str, &[&[&str]])] = &[
+    (
+        "filter rules",
+        &[
+            &["filter", "rule"],
+            &["OPNsense", "Firewall", "Filter", "rules", "rule"],
+        ],
+    ),
+    (
+        "aliases",
+        &[
+            &["aliases", "alias"],
+            &["OPNsense", "Firewall", "Alias", "aliases", "alias"],
+        ],
+    ),
+    ("users", &[&["system", "user"]]),
+    ("certificates", &[&["cert"]]),
+    ("CAs", &[&["ca"]]),
+    (
+        "dhcp static maps",
+        &[
+            &["dhcpd", "*", "staticmap"],
+            &["dhcpdv6", "*", "staticmap"],
+            &["OPNsense", "Kea", "dhcp4", "reservations", "reservation"],
+            &["OPNsense", "Kea", "dhcp6", "reservations", "reservation"],
+        ],
+    ),
+    (
+        "vpn instances",
+        &[
+            &["openvpn", "openvpn-server"],
+            &["openvpn", "openvpn-client"],
+            &["OPNsense", "OpenVPN", "Instances", "Instance"],
+            &["ipsec", "phase1"],
+            &["OPNsense", "Swanctl", "Connections", "Connection"],
+            &["wireguard", "tunnels", "item"],
+            &["installedpackages", "wireguard", "tunnels", "item"],
+            &["OPNsense", "wireguard", "server", "servers", "server"],
+        ],
+    ),
+];
+
+/// Per-kind counts in the source, target baseline, and output; kinds absent
+/// from all three are left out.
+pub fn section_deltas(source: &XmlNode, baseline: &XmlNode, output: &XmlNode) -> Vec<SectionDelta> {
+    DELTA_SECTIONS
+        .iter()
+        .map(|(section, paths)| {
+            let count = |root: &XmlNode| paths.iter().map(|path| count_at(root, path)).sum();
+            SectionDelta {
+                section,
+                source: count(source),
+                baseline: count(baseline),
+                output: count(output),
+            }
+        })
+        .filter(|d| d.source + d.baseline + d.output > 0)
+        .collect()
+}
+
+/// Render the delta table: one line per kind, with what changed from source
+/// to output.
+pub fn render_section_deltas(deltas: &[SectionDelta]) -> String {
+    let width = deltas.iter().map(|d| d.section.len()).max().unwrap_or(0);
+    let mut lines = vec!["section_deltas".to_string()];
+    for delta in deltas {
+        let change = match delta.output.cmp(&delta.source) {
+            Ordering::Less => format!("{} pruned", delta.source - delta.output),
+            Ordering::Greater => format!("{} added", delta.output - delta.source),
+            Ordering::Equal => "unchanged".to_string(),
+        };
+        lines.push(format!(
+            "- {:<width$}  source {:>4}  baseline {:>4} -> output {:>4} ({change})",
+            format!("{}:", delta.section),
+            delta.source,
+            delta.baseline,
+            delta.output,
+            width = width + 1
+        ));
+    }
+    lines.join("\n")
+}
+
+/// List the objects removed by `--prune-orphans`, one per line.
+pub fn render_pruned_orphans(orphans: &[Orphan]) -> String {
+    let mut lines = vec![format!("pruned_orphans={}", orphans.len())];
+    for orphan in orphans {
+        if orphan.label == orphan.name {
+            lines.push(format!("- {} {}", orphan.kind, orphan.name));
+        } else {
+            lines.push(format!(
+                "- {} {} ({})",
+                orphan.kind, orphan.name, orphan.label
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// List every node pruned because the target cannot hold it, with the
+/// reason and suggested manual action.
+pub fn render_prune_log(log: &[PrunedNode]) -> String {
+    let mut lines = vec![format!("pruned={}", log.len())];
+    for node in log {
+        let kind = match node.kind {
+            PruneKind::Bookkeeping => "bookkeeping",
+            PruneKind::Carried => "carried",
+            PruneKind::Cosmetic => "cosmetic",
+            PruneKind::Dropped => "dropped",
+        };
+        lines.push(format!(
+            "- {} [{kind}]: {}; action: {}",
+            node.path, node.reason, node.action
+        ));
+    }
+    lines.join("\n")
+}
+
+/// List the source elements repaired for encoding, one per line.
+pub fn render_encoding_repairs(repairs: &[EncodingRepair]) -> String {
+    let mut lines = vec![format!("encoding_repairs={}", repairs.len())];
+    for repair in repairs {
+        lines.push(format!("- {}: {}", repair.path, repair.message));
+    }
+    lines.join("\n")
+}
+
+/// List the structural upgrades applied to a legacy source, one per line.
+pub fn render_legacy_upgrades(from_version: &str, upgrades: &[LegacyUpgrade]) -> String {
+    let mut lines = vec![format!(
+        "legacy_upgrades={} from_version={from_version}",
+        upgrades.len()
+    )];
+    for upgrade in upgrades {
+        let path = if upgrade.path.is_empty() {
+            "(root)"
+        } else {
+            upgrade.path.as_str()
+        };
+        lines.push(format!("- {path}: {}", upgrade.message));
+    }
+    lines.join("\n")
+}
+
+/// List duplicate certificates and CAs folded into a surviving copy.
+pub fn render_cert_remaps(remaps: &[CertRemap]) -> String {
+    let mut lines = vec![format!("cert_remaps={}", remaps.len())];
+    for remap in remaps {
+        lines.push(format!(
+            "- {} {} -> {} ({})",
+            remap.kind, remap.from, remap.to, remap.descr
+        ));
+    }
+    lines.join("\n")
+}
+
 fn count_interfaces(root: &XmlNode) -> usize {
     root.get_child("interfaces")
         .map(|n| n.children.len())
@@ -109,3 +285,15 @@ fn count_vpns(root: &XmlNode) -> usize {
         );
     openvpn + ipsec + wireguard + tailscale
 }
+
+fn count_at(node: &XmlNode, path: &[&str]) -> usize {
+    match path.split_first() {
+        None => 1,
+        Some((first, rest)) => node
+            .children
+            .iter()
+            .filter(|c| *first == "*" || c.tag == *first)
+            .map(|c| count_at(c, rest))
+            .sum(),
+    }
+}
@@ -1,31 +1,13 @@
-//! Firewall configuration conversion orchestration.
+//! `convert` command: CLI front-end for the conversion pipeline.
 //!
-//! This module implements the main conversion workflow that transforms a firewall
-//! configuration from one platform (pfSense or OPNsense) to another. The conversion
-//! process is complex and multi-staged:
-//!
-//! ## Conversion Pipeline
+//! The staged conversion itself lives in [`pfopn_convert::pipeline`]. This
+//! module handles what only the CLI needs:
 //!
 //! 1. **Parse & Validate** — Load source and target configs, validate platforms
-//! 2. **DHCP Backend Resolution** — Determine which DHCP backend to use (ISC/Kea)
-//! 3. **Interface Compatibility Check** — Ensure interfaces are compatible
-//! 4. **Diff & Merge** — Compute differences, apply safe merge operations
-//! 5. **Transform Pipeline** — Apply platform-specific transformations:
-//!    - Interface settings and presence normalization
-//!    - Logical interface reference updates (OPNsense assignments)
-//!    - Device reference normalization
-//!    - Platform-specific cleanup (pfBlocker, VLANs, WireGuard, bridges, ifgroups)
-//! 6. **DHCP Migration** — Migrate ISC DHCP to Kea if needed
-//! 7. **Write Output** — Serialize and write final configuration
-//!
-//! ## DHCP Backend Handling
-//!
-//! The converter supports both ISC DHCP and Kea DHCP backends. OPNsense 26+ defaults
-//! to Kea but can fall back to ISC if migration fails. The pipeline handles:
-//! - Auto-detection of source backend
-//! - User-requested backend (auto/kea/isc)
-//! - Automatic fallback on migration errors (in auto mode)
-//! - Preservation of legacy DHCPv6 when needed
+//!    and output paths
+//! 2. **Pipeline Setup** — Translate CLI flags into a [`ConversionPipeline`]
+//! 3. **Write Output** — Serialize the result, print warnings, DHCP migration
+//!    status, and the conversion summary
 //!
 //! ## Merge Strategy
 //!
@@ -34,45 +16,41 @@
 //! source configuration data. Dependencies (users, certs, CAs) are transferred
 //! automatically unless disabled via CLI flags.
 
-use anyhow::{bail, Context, Result};
-use xml_diff_core::{diff_with_options, parse_file, write_file, DiffOptions, XmlNode};
+use std::collections::BTreeMap;
+use std::path::Path;
 
-use crate::cli::{ConvertArgs, Platform};
-use crate::conversion_summary::{
-    render as render_conversion_summary, summarize as summarize_conversion,
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use pfopn_convert::canonical;
+use pfopn_convert::conversion_summary::{
+    render as render_conversion_summary, render_cert_remaps, render_encoding_repairs,
+    render_legacy_upgrades, render_prune_log, render_pruned_orphans, render_section_deltas,
 };
-use crate::interface_guard::enforce_interface_compat;
-use crate::path_guard::ensure_output_not_same;
-use crate::target_prune::prune_imported_incompatible_sections;
-use pfopn_convert::backend_detect::detect_dhcp_backend;
+use pfopn_convert::convert_policy::load_conversion_policy;
 use pfopn_convert::detect::{detect_config, ConfigFlavor};
-use pfopn_convert::merge::{apply_safe_merge, MergeOptions, MergeTarget};
-use pfopn_convert::transform::{
-    bridges, device_refs, dhcp, ifgroups, interface_presence, interface_settings, lan_ip,
-    logical_refs, opnsense_assignments, pfblocker, vlan_ifnames, wireguard,
-};
+use pfopn_convert::encoding::parse_source_file;
+use pfopn_convert::legacy;
+use pfopn_convert::pipeline::ConversionPipeline;
+use pfopn_convert::profile::{baseline_versions, load_baseline, seed_baseline_interfaces};
+use pfopn_convert::progress::{format_duration, StageTimer};
+use pfopn_convert::provenance::Provenance;
+use pfopn_convert::report_template::render_template_file;
+use pfopn_convert::transform::{dhcp, lan_ip};
+use xml_diff_core::{parse_file, write_file_with_options, XmlNode};
+
+use crate::cli::{ConvertArgs, DhcpBackend, LanLiterals, Platform, StaticMapDns};
+use crate::path_guard::ensure_output_not_same;
 
 /// Execute the main configuration conversion workflow.
 ///
-/// Orchestrates the complete conversion pipeline from source platform to target
-/// platform. The conversion is staged and applies multiple transformations in a
-/// specific order to ensure consistency.
+/// Handles file I/O and terminal output around [`ConversionPipeline`], which
+/// performs the staged conversion itself.
 ///
 /// ## Conversion Stages
 ///
 /// 1. **Validation** — Ensures output path differs from inputs, platforms differ
-/// 2. **DHCP Backend Resolution** — Determines ISC vs Kea backend strategy
-/// 3. **Diff & Merge** — Computes differences, merges source into target baseline
-/// 4. **Transform Pipeline** — Applies ordered platform-specific transformations
-/// 5. **DHCP Migration** — Migrates ISC DHCP to Kea for OPNsense 26+ if needed
-/// 6. **Output** — Writes final config and displays summary
-///
-/// ## Error Handling
-///
-/// - Fails if source and target are the same platform
-/// - Fails if Kea backend is requested but cannot be used
-/// - Fails if Kea-only source is being converted to ISC without legacy data
-/// - Auto-falls back to ISC on Kea migration errors (only in auto mode)
+/// 2. **Pipeline** — Builds a [`ConversionPipeline`] from CLI flags and runs it
+/// 3. **Output** — Writes final config, prints warnings and summary
 ///
 /// # Arguments
 ///
@@ -87,10 +65,10 @@ use pfopn_convert::transform::{
 /// Returns error if:
 /// - Output path conflicts with input paths
 /// - Source/target configs cannot be parsed
+/// - A legacy source is too old to upgrade
 /// - Platforms cannot be detected or are the same
-/// - DHCP backend requirements cannot be met
-/// - Interface compatibility check fails
-/// - Kea migration fails (in non-auto mode)
+/// - The conversion pipeline fails
+/// - `--strict` is set and the conversion left anything untranslated
 /// - Output file cannot be written
 pub fn run_convert(args: ConvertArgs) -> Result<()> {
     // Validate that output path doesn't overwrite inputs
@@ -99,10 +77,17 @@ pub fn run_convert(args: ConvertArgs) -> Result<()> {
         inputs.push(path.as_path());
     }
     ensure_output_not_same(&args.output, &inputs)?;
+    let mut timer = StageTimer::new(args.progress);
 
     // Parse source configuration
-    let input = parse_file(&args.input)
+    let (mut input, parse_repairs) = parse_source_file(&args.input)
         .with_context(|| format!("failed to parse {}", args.input.display()))?;
+    timer.mark("parse source");
+
+    // Bring m0n0wall and old pfSense layouts up to date before detection
+    let legacy_report = legacy::upgrade(&mut input)
+        .with_context(|| format!("cannot upgrade legacy config {}", args.input.display()))?;
+    let legacy_from = legacy_report.from_version.clone();
 
     // Determine source and target platforms
     let from = resolve_from_platform(args.from, &input)?;
@@ -114,168 +99,236 @@ pub fn run_convert(args: ConvertArgs) -> Result<()> {
     }
 
     // Load or create target baseline config
-    let target = resolve_target(&args, to)?;
-
-    // Resolve DHCP backend strategy (ISC vs Kea)
-    let requested_backend = match args.backend {
-        crate::cli::DhcpBackend::Auto => dhcp::RequestedDhcpBackend::Auto,
-        crate::cli::DhcpBackend::Kea => dhcp::RequestedDhcpBackend::Kea,
-        crate::cli::DhcpBackend::Isc => dhcp::RequestedDhcpBackend::Isc,
-    };
-    let source_backend = detect_dhcp_backend(&input);
-    let mut effective_backend =
-        dhcp::resolve_effective_backend(requested_backend, &input, &target, to);
-    dhcp::ensure_backend_readiness(&target, requested_backend, effective_backend)?;
-
-    // Ensure source and target have compatible interface assignments
-    enforce_interface_compat(&input, &target)?;
-
-    // Compute differences between source and target
-    let opts = DiffOptions {
-        include_identical: false,
-        ..DiffOptions::default()
-    };
-    let entries = diff_with_options(&input, &target, &opts);
+    let interface_map = parse_interface_map(&args.map_interface)?;
+    let target = resolve_target(&args, to, &input, &interface_map)?;
+    timer.mark("load target");
+
+    let mut pipeline = ConversionPipeline::new(to)
+        .from_platform(from)
+        .backend(requested_backend(args.backend))
+        .transfer_users(!args.no_transfer_users)
+        .transfer_certs(!args.no_transfer_certs)
+        .transfer_cas(!args.no_transfer_cas)
+        .disable_dhcp(args.disable_dhcp)
+        .snapshots(!args.no_snapshots)
+        .prune_orphans(args.prune_orphans)
+        .keep_unknown(args.keep_unknown)
+        .strip_private_keys(args.strip_private_keys)
+        .static_map_dns(static_map_dns(args.static_map_dns))
+        .parse_repairs(parse_repairs)
+        .legacy_upgrades(legacy_report)
+        .progress(args.progress);
+    for (src, dst) in interface_map {
+        pipeline = pipeline.map_interface(src, dst);
+    }
+    if let Some(new_lan_ip) = &args.lan_ip {
+        pipeline = pipeline
+            .lan_ip(new_lan_ip)
+            .lan_literals(lan_literals(args.lan_literals));
+    }
+    for mapping in &args.renumber {
+        pipeline = pipeline.renumber(*mapping);
+    }
+    if let Some(path) = &args.policy {
+        pipeline = pipeline.policy(load_conversion_policy(path)?);
+    }
+    if !args.no_revision {
+        let mut provenance = Provenance::now();
+        if let Some(user) = &args.revision_user {
+            provenance.username = user.clone();
+        }
+        provenance.marker = args.provenance;
+        provenance.flags = provenance_flags(&args);
+        pipeline = pipeline.provenance(provenance);
+    }
+    let result = pipeline.run(&input, &target)?;
+    timer.extend(result.stage_timings.clone());
 
-    // Configure dependency transfer options
-    let merge_options = MergeOptions {
-        transfer_users: !args.no_transfer_users,
-        transfer_certs: !args.no_transfer_certs,
-        transfer_cas: !args.no_transfer_cas,
-    };
+    for diagnostic in &result.diagnostics {
+        eprintln!("{diagnostic}");
+    }
+    if let (Some(stats), None) = (&result.dhcp_migration, &args.template) {
+        print_dhcp_migration_summary(
+            stats,
+            result.effective_backend,
+            result.preserved_legacy_dhcpv6,
+        );
+    }
 
-    // Merge source config into target baseline (builds from target, inserts from source)
-    let mut out = apply_safe_merge(&input, &target, &entries, MergeTarget::Right, merge_options)
-        .with_context(|| "failed while applying safe conversion merge")?;
+    // Strict mode: a conversion is either complete or not written at all
+    if args.strict {
+        let violations = result.strict_violations(&input);
+        if !violations.is_empty() {
+            eprintln!("strict: {} untranslated item(s)", violations.len());
+            for violation in &violations {
+                eprintln!("- {violation}");
+            }
+            bail!("strict mode failed: conversion is incomplete; no output written");
+        }
+    }
 
-    // Update root tag to match target platform
-    out.tag = to.to_string();
+    // Write final configuration in the target platform's canonical layout
+    write_file_with_options(&result.output, &args.output, &canonical::write_options(to))
+        .with_context(|| format!("failed to write output XML {}", args.output.display()))?;
+    timer.mark("write");
+    if args.progress {
+        eprintln!("progress: done in {}", format_duration(timer.total()));
+    }
 
-    // Apply interface-level transformations
-    interface_settings::apply(&mut out, &input, &target, None);
-    interface_presence::prune_missing(&mut out, &target);
+    // Display conversion summary
+    if let Some(template) = &args.template {
+        print!("{}", render_template_file(template, &result.report())?);
+        return Ok(());
+    }
+    println!("{}", render_conversion_summary(result.summary));
+    if !result.section_deltas.is_empty() {
+        println!("{}", render_section_deltas(&result.section_deltas));
+    }
+    if !result.prune_log.is_empty() {
+        println!("{}", render_prune_log(&result.prune_log));
+    }
+    if args.prune_orphans {
+        println!("{}", render_pruned_orphans(&result.pruned_orphans));
+    }
+    if !result.encoding_repairs.is_empty() {
+        println!("{}", render_encoding_repairs(&result.encoding_repairs));
+    }
+    if let Some(version) = &legacy_from {
+        println!(
+            "{}",
+            render_legacy_upgrades(version, &result.legacy_upgrades)
+        );
+    }
+    if !result.cert_remaps.is_empty() {
+        println!("{}", render_cert_remaps(&result.cert_remaps));
+    }
+    Ok(())
+}
 
-    // Build logical interface mapping for OPNsense (wan/lan/opt -> device references)
-    let logical_map = if to == "opnsense" {
-        let map = opnsense_assignments::normalize(&mut out);
-        if map.is_empty() {
-            None
-        } else {
-            Some(map)
+/// Parse `--map-interface SRC=DST` entries into a source -> target logical map.
+///
+/// # Errors
+///
+/// Returns error if an entry is not `SRC=DST`, either side is empty, or the
+/// same source interface is mapped twice.
+pub fn parse_interface_map(entries: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut map = BTreeMap::new();
+    for entry in entries {
+        let Some((from, to)) = entry.split_once('=') else {
+            bail!("invalid --map-interface '{entry}'; expected SRC=DST");
+        };
+        let (from, to) = (from.trim(), to.trim());
+        if from.is_empty() || to.is_empty() {
+            bail!("invalid --map-interface '{entry}'; expected SRC=DST");
         }
-    } else {
-        None
-    };
-
-    // Update references that use logical interface names
-    logical_refs::apply(&mut out, logical_map.as_ref());
-
-    // Remove sections incompatible with target platform
-    prune_imported_incompatible_sections(&mut out, to, &target);
+        if map.insert(from.to_string(), to.to_string()).is_some() {
+            bail!("--map-interface maps '{from}' more than once");
+        }
+    }
+    Ok(map)
+}
 
-    // Update device references (physical interface names)
-    device_refs::apply(&mut out, &input, &target, None);
+/// Translate the `--backend` flag into the pipeline's backend request.
+pub fn requested_backend(backend: DhcpBackend) -> dhcp::RequestedDhcpBackend {
+    match backend {
+        DhcpBackend::Auto => dhcp::RequestedDhcpBackend::Auto,
+        DhcpBackend::Kea => dhcp::RequestedDhcpBackend::Kea,
+        DhcpBackend::Isc => dhcp::RequestedDhcpBackend::Isc,
+    }
+}
 
-    // Apply platform-specific cleanup and normalization
-    if to == "opnsense" {
-        pfblocker::prune_pfblocker_floating_rules_for_opnsense(&mut out);
-        vlan_ifnames::normalize_opnsense_vlan_ifnames(&mut out);
-        wireguard::normalize_opnsense_interface_names(&mut out);
-        bridges::normalize_for_opnsense(&mut out);
-        ifgroups::normalize_for_opnsense(&mut out);
-    } else {
-        bridges::normalize_for_pfsense(&mut out);
-        ifgroups::normalize_for_pfsense(&mut out);
+/// Translate the `--static-map-dns` flag into the pipeline's mode.
+pub fn static_map_dns(mode: StaticMapDns) -> dhcp::StaticMapDns {
+    match mode {
+        StaticMapDns::Off => dhcp::StaticMapDns::Off,
+        StaticMapDns::Materialize => dhcp::StaticMapDns::Materialize,
+        StaticMapDns::Verify => dhcp::StaticMapDns::Verify,
     }
+}
 
-    // Override LAN IP if requested
-    if let Some(new_lan_ip) = &args.lan_ip {
-        lan_ip::apply(&mut out, new_lan_ip)?;
-    }
-
-    // Handle DHCP backend configuration based on target platform
-    if to == "pfsense" && effective_backend == dhcp::EffectiveDhcpBackend::Kea {
-        // pfSense with Kea: copy Kea config from source
-        seed_pfsense_kea_from_source(&mut out, &input);
-    }
-
-    if to == "opnsense" && effective_backend == dhcp::EffectiveDhcpBackend::Kea {
-        // OPNsense 26+ with Kea: attempt ISC → Kea migration
-        match dhcp::migrate_isc_to_kea_opnsense(&mut out, &input) {
-            Ok(stats) => {
-                let mut final_backend = effective_backend;
-
-                // Check if migration produced fatal errors
-                let error_warning_present = stats
-                    .warnings
-                    .iter()
-                    .any(|w| w.severity == dhcp::MigrationSeverity::Error);
-
-                // Fall back to ISC if errors occurred
-                if error_warning_present && final_backend == dhcp::EffectiveDhcpBackend::Kea {
-                    final_backend = dhcp::EffectiveDhcpBackend::Isc;
-                    eprintln!(
-                        "warning: Kea migration skipped due to fatal errors; falling back to ISC backend"
-                    );
-                }
-
-                // Preserve legacy DHCPv6 for interfaces that couldn't migrate
-                let preserve_legacy_ipv6 = final_backend == dhcp::EffectiveDhcpBackend::Kea
-                    && !stats.preserved_dhcpdv6_ifaces.is_empty();
-
-                dhcp::enforce_output_backend(&mut out, final_backend, to, preserve_legacy_ipv6);
-                effective_backend = final_backend;
-
-                // Display migration warnings
-                for warning in &stats.warnings {
-                    eprintln!("warning: {}", warning.message);
-                }
-                print_dhcp_migration_summary(&stats, final_backend, preserve_legacy_ipv6);
-            }
-            Err(err) if requested_backend == dhcp::RequestedDhcpBackend::Auto => {
-                // In auto mode, fall back to ISC on migration failure
-                eprintln!(
-                    "warning: Kea migration failed in auto mode ({err}); falling back to ISC backend"
-                );
-                effective_backend = dhcp::EffectiveDhcpBackend::Isc;
-                dhcp::enforce_output_backend(&mut out, effective_backend, to, false);
-            }
-            Err(err) => return Err(err), // In explicit mode, fail on migration error
-        }
-    } else {
-        // No migration needed, just enforce the backend
-        dhcp::enforce_output_backend(&mut out, effective_backend, to, false);
+/// Translate the `--lan-literals` flag into the pipeline's mode.
+pub fn lan_literals(mode: LanLiterals) -> lan_ip::LanLiterals {
+    match mode {
+        LanLiterals::Rewrite => lan_ip::LanLiterals::Rewrite,
+        LanLiterals::Review => lan_ip::LanLiterals::Review,
     }
+}
 
-    // Validate that Kea-only sources can't be downgraded to ISC without legacy data
-    if effective_backend == dhcp::EffectiveDhcpBackend::Isc
-        && source_backend.mode == "kea"
-        && !dhcp::has_legacy_dhcp_data(&input)
-    {
-        if to == "pfsense" {
-            bail!(
-                "cannot convert Kea-only source to pfSense ISC without source legacy DHCP data; use --backend kea or provide ISC-backed source"
-            );
-        }
-        if to == "opnsense" {
-            bail!(
-                "cannot convert Kea-only source to OPNsense ISC without source legacy DHCP data; use --backend kea or provide ISC-backed source"
-            );
+/// Options that differ from the defaults, for the provenance marker.
+///
+/// Files are recorded by name only so local paths stay out of the config.
+fn provenance_flags(args: &ConvertArgs) -> Vec<String> {
+    let name = |path: &Path| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let mut flags = vec![format!("--to {}", value_name(args.to))];
+    if args.from != Platform::Auto {
+        flags.push(format!("--from {}", value_name(args.from)));
+    }
+    if let Some(path) = &args.target_file {
+        flags.push(format!("--target-file {}", name(path)));
+    }
+    if let Some(version) = &args.target_version {
+        flags.push(format!("--target-version {version}"));
+    }
+    if args.backend != DhcpBackend::Auto {
+        flags.push(format!("--backend {}", value_name(args.backend)));
+    }
+    if args.static_map_dns != StaticMapDns::Off {
+        flags.push(format!(
+            "--static-map-dns {}",
+            value_name(args.static_map_dns)
+        ));
+    }
+    for entry in &args.map_interface {
+        flags.push(format!("--map-interface {entry}"));
+    }
+    if let Some(lan_ip) = &args.lan_ip {
+        flags.push(format!("--lan-ip {lan_ip}"));
+        if args.lan_literals != LanLiterals::Rewrite {
+            flags.push(format!("--lan-literals {}", value_name(args.lan_literals)));
         }
     }
-
-    // Optionally disable all DHCP if requested
-    if args.disable_dhcp {
-        dhcp::disable_all(&mut out);
+    for mapping in &args.renumber {
+        flags.push(format!("--renumber {mapping}"));
+    }
+    if let Some(path) = &args.policy {
+        flags.push(format!("--policy {}", name(path)));
     }
+    flags.extend(enabled_switches(args).map(str::to_string));
+    flags
+}
 
-    // Write final configuration
-    write_file(&out, &args.output)
-        .with_context(|| format!("failed to write output XML {}", args.output.display()))?;
+/// Output-affecting on/off flags that are set in `args`.
+///
+/// Shared by the provenance marker and the wizard's equivalent command so
+/// both record the same switches.
+pub fn enabled_switches(args: &ConvertArgs) -> impl Iterator<Item = &'static str> {
+    [
+        (args.minimal_template, "--minimal-template"),
+        (args.no_transfer_users, "--no-transfer-users"),
+        (args.no_transfer_certs, "--no-transfer-certs"),
+        (args.no_transfer_cas, "--no-transfer-cas"),
+        (args.disable_dhcp, "--disable-dhcp"),
+        (args.no_snapshots, "--no-snapshots"),
+        (args.prune_orphans, "--prune-orphans"),
+        (args.keep_unknown, "--keep-unknown"),
+        (args.strip_private_keys, "--strip-private-keys"),
+        (args.strict, "--strict"),
+    ]
+    .into_iter()
+    .filter(|(on, _)| *on)
+    .map(|(_, flag)| flag)
+}
 
-    // Display conversion summary
-    println!("{}", render_conversion_summary(summarize_conversion(&out)));
-    Ok(())
+/// Command-line spelling of a flag value.
+pub fn value_name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
 }
 
 /// Resolve source platform from CLI argument or auto-detection.
@@ -295,7 +348,7 @@ pub fn run_convert(args: ConvertArgs) -> Result<()> {
 /// # Errors
 ///
 /// Returns error if Auto is used but the root tag cannot be recognized.
-fn resolve_from_platform(platform: Platform, node: &XmlNode) -> Result<&'static str> {
+pub fn resolve_from_platform(platform: Platform, node: &XmlNode) -> Result<&'static str> {
     match platform {
         Platform::Pfsense => Ok("pfsense"),
         Platform::Opnsense => Ok("opnsense"),
@@ -339,13 +392,17 @@ fn normalize_to_platform(platform: Platform) -> Result<&'static str> {
 /// ## Resolution Strategy
 ///
 /// 1. If `--target-file` is provided, loads and validates that file
-/// 2. If `--minimal-template` is set, creates an empty root node (dev/testing only)
-/// 3. Otherwise, fails with error requiring one of the above
+/// 2. If `--target-version` is provided, generates the embedded baseline for
+///    that release (see [`generated_baseline`])
+/// 3. If `--minimal-template` is set, creates an empty root node (dev/testing only)
+/// 4. Otherwise, fails with error requiring one of the above
 ///
 /// # Arguments
 ///
-/// * `args` - CLI arguments containing target-file and minimal-template flags
+/// * `args` - CLI arguments containing target-file, target-version, and minimal-template flags
 /// * `to` - Target platform identifier ("pfsense" or "opnsense")
+/// * `input` - Parsed source config, used to seed generated baseline interfaces
+/// * `interface_map` - Parsed `--map-interface` entries
 ///
 /// # Returns
 ///
@@ -356,9 +413,48 @@ fn normalize_to_platform(platform: Platform) -> Result<&'static str> {
 /// Returns error if:
 /// - Target file cannot be parsed
 /// - Target file platform doesn't match `to` parameter
-/// - Neither --target-file nor --minimal-template is provided
-fn resolve_target(args: &ConvertArgs, to: &str) -> Result<XmlNode> {
-    if let Some(path) = &args.target_file {
+/// - No embedded baseline matches `--target-version`
+/// - None of --target-file, --target-version, or --minimal-template is provided
+fn resolve_target(
+    args: &ConvertArgs,
+    to: &str,
+    input: &XmlNode,
+    interface_map: &BTreeMap<String, String>,
+) -> Result<XmlNode> {
+    if args.target_file.is_some() || args.target_version.is_some() {
+        return load_target(
+            args.target_file.as_deref(),
+            args.target_version.as_deref(),
+            to,
+            input,
+            interface_map,
+        );
+    }
+
+    if args.minimal_template {
+        return Ok(XmlNode::new(to));
+    }
+
+    bail!(
+        "missing --target-file; provide a destination baseline config, pick a release with --target-version, or use --minimal-template for dev/testing"
+    );
+}
+
+/// Load the target baseline from `--target-file` or build it from the
+/// embedded template for `--target-version`.
+///
+/// # Errors
+///
+/// Returns error if the file cannot be parsed or is for another platform,
+/// no embedded baseline matches the version, or neither is given.
+pub fn load_target(
+    target_file: Option<&Path>,
+    target_version: Option<&str>,
+    to: &str,
+    input: &XmlNode,
+    interface_map: &BTreeMap<String, String>,
+) -> Result<XmlNode> {
+    if let Some(path) = target_file {
         let parsed =
             parse_file(path).with_context(|| format!("failed to parse {}", path.display()))?;
         let target_flavor = resolve_from_platform(Platform::Auto, &parsed)?;
@@ -370,13 +466,41 @@ fn resolve_target(args: &ConvertArgs, to: &str) -> Result<XmlNode> {
         return Ok(parsed);
     }
 
-    if args.minimal_template {
-        return Ok(XmlNode::new(to));
+    if let Some(version) = target_version {
+        return generated_baseline(to, version, input, interface_map);
     }
 
-    bail!(
-        "missing --target-file; provide a destination baseline config or use --minimal-template for dev/testing"
+    bail!("missing --target-file; provide a destination baseline config or pick a release with --target-version");
+}
+
+/// Build a target baseline from the embedded template for `version`.
+///
+/// Templates carry the release's default structure (Kea subtree, OPNsense
+/// MVC containers, DHCP backend marker) but no hardware, so physical
+/// interfaces are assigned the source's devices; a note listing them is
+/// printed since they need reassigning when the destination hardware differs.
+///
+/// # Errors
+///
+/// Returns error if no embedded template matches `version`.
+pub fn generated_baseline(
+    to: &str,
+    version: &str,
+    input: &XmlNode,
+    interface_map: &BTreeMap<String, String>,
+) -> Result<XmlNode> {
+    let Some((mut baseline, resolved)) = load_baseline(to, version) else {
+        bail!(
+            "no embedded {to} baseline for --target-version '{version}'; available: {}",
+            baseline_versions(to).join(", ")
+        );
+    };
+    let seeded = seed_baseline_interfaces(&mut baseline, input, interface_map);
+    eprintln!(
+        "info: generated {to} {resolved} baseline; interface devices taken from source ({}), reassign them after restore if the hardware differs",
+        seeded.join(", ")
     );
+    Ok(baseline)
 }
 
 /// Print human-readable DHCP migration summary to stdout.
@@ -468,42 +592,3 @@ fn print_dhcp_migration_summary(
         );
     }
 }
-
-/// Seed pfSense Kea configuration from source config.
-///
-/// When converting to pfSense with Kea backend, this copies the Kea configuration
-/// section from the source (if present) to the output. This preserves existing
-/// Kea settings when migrating between platforms.
-///
-/// ## Source Discovery
-///
-/// Looks for Kea config in:
-/// 1. `<kea>` at root level (pfSense format)
-/// 2. `<OPNsense><Kea>` (OPNsense format)
-///
-/// If found, copies the section and normalizes the tag to `<kea>`.
-///
-/// # Arguments
-///
-/// * `out` - Output config being built
-/// * `source` - Source config to extract Kea settings from
-fn seed_pfsense_kea_from_source(out: &mut XmlNode, source: &XmlNode) {
-    let source_kea = source
-        .get_child("kea")
-        .cloned()
-        .or_else(|| {
-            source
-                .get_child("OPNsense")
-                .and_then(|opn| opn.get_child("Kea"))
-                .cloned()
-        })
-        .map(|mut node| {
-            node.tag = "kea".to_string();
-            node
-        });
-    let Some(source_kea) = source_kea else {
-        return;
-    };
-    out.children.retain(|c| c.tag != "kea");
-    out.children.push(source_kea);
-}
@@ -0,0 +1,245 @@
+//! Per-section conversion policy.
+//!
+//! A policy file lets expert users override how the pipeline treats
+//! individual top-level sections without code changes:
+//!
+//! ```toml
+//! [sections]
+//! firewall = "force-target-default"   # keep the target's filter/nat/shaper
+//! syslog = "copy-verbatim"
+//! installedpackages = "ignore"
+//! ```
+//!
+//! Keys are logical section names (`system`, `interfaces`, `firewall`,
+//! `services`, `vpn`, `packages`) or literal top-level tags; a literal tag
+//! wins over a logical name that covers it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::section::section_tags;
+
+/// How the pipeline treats one top-level section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SectionPolicy {
+    /// Do not carry the source section over; the output keeps whatever the
+    /// target baseline provides.
+    Ignore,
+    /// Copy the source section into the output unchanged, skipping transforms,
+    /// reference rewrites, and target pruning.
+    CopyVerbatim,
+    /// Convert normally (the default).
+    Transform,
+    /// Replace the output section with the target baseline's section exactly,
+    /// undoing anything merge-time transforms changed. Removed when the
+    /// baseline has no such section.
+    ForceTargetDefault,
+}
+
+/// Parsed policy file, keyed by concrete top-level tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionPolicy {
+    pub sections: BTreeMap<String, SectionPolicy>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConversionPolicyFile {
+    #[serde(default)]
+    sections: BTreeMap<String, SectionPolicy>,
+}
+
+#[derive(Debug, Error)]
+pub enum PolicyLoadError {
+    #[error("failed to read conversion policy {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("failed to parse conversion policy {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+impl ConversionPolicy {
+    /// Policy for a top-level tag, defaulting to [`SectionPolicy::Transform`].
+    pub fn get(&self, tag: &str) -> SectionPolicy {
+        self.sections
+            .get(tag)
+            .copied()
+            .unwrap_or(SectionPolicy::Transform)
+    }
+
+    /// Set the policy for a logical section name or top-level tag.
+    pub fn set(&mut self, section: &str, policy: SectionPolicy) {
+        match section_tags(section) {
+            Some(tags) => {
+                for tag in tags {
+                    self.sections.insert((*tag).to_string(), policy);
+                }
+            }
+            None => {
+                self.sections.insert(section.to_string(), policy);
+            }
+        }
+    }
+
+    /// Whether every section converts normally.
+    pub fn is_default(&self) -> bool {
+        self.sections
+            .values()
+            .all(|p| *p == SectionPolicy::Transform)
+    }
+
+    /// Top-level tags the source should not contribute to the merge.
+    ///
+    /// Covers `ignore`, plus `copy-verbatim` and `force-target-default`
+    /// sections, which are replaced wholesale after the pipeline runs.
+    pub fn excluded_source_tags(&self) -> Vec<String> {
+        self.sections
+            .iter()
+            .filter(|(_, p)| **p != SectionPolicy::Transform)
+            .map(|(tag, _)| tag.clone())
+            .collect()
+    }
+
+    /// Apply `copy-verbatim` and `force-target-default` to a converted tree.
+    ///
+    /// Replaced sections keep their output position; new ones are appended.
+    pub fn apply_overrides(
+        &self,
+        out: &mut XmlNode,
+        source: &XmlNode,
+        target: &XmlNode,
+        diagnostics: &mut Diagnostics,
+    ) {
+        for (tag, policy) in &self.sections {
+            let (origin, replacement) = match policy {
+                SectionPolicy::CopyVerbatim => ("source", source.get_child(tag)),
+                SectionPolicy::ForceTargetDefault => ("target baseline", target.get_child(tag)),
+                SectionPolicy::Ignore | SectionPolicy::Transform => continue,
+            };
+            replace_section(out, tag, replacement);
+            match replacement {
+                Some(_) => diagnostics.info("policy", format!("<{tag}> taken from {origin}")),
+                None => diagnostics.info(
+                    "policy",
+                    format!("<{tag}> not present in {origin}; removed from output"),
+                ),
+            }
+        }
+    }
+}
+
+/// Load a policy file from disk.
+pub fn load_conversion_policy(path: &Path) -> Result<ConversionPolicy, PolicyLoadError> {
+    let raw = fs::read_to_string(path).map_err(|source| PolicyLoadError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    parse_conversion_policy(&raw, path.display().to_string())
+}
+
+/// Parse policy TOML, expanding logical section names.
+///
+/// Logical names are applied first so literal tags can override them.
+pub fn parse_conversion_policy(
+    raw: &str,
+    path: String,
+) -> Result<ConversionPolicy, PolicyLoadError> {
+    let parsed: ConversionPolicyFile =
+        toml::from_str(raw).map_err(|source| PolicyLoadError::Parse { path, source })?;
+    let (logical, literal): (Vec<_>, Vec<_>) = parsed
+        .sections
+        .into_iter()
+        .partition(|(name, _)| section_tags(name).is_some());
+    let mut policy = ConversionPolicy::default();
+    for (name, section_policy) in logical.into_iter().chain(literal) {
+        policy.set(&name, section_policy);
+    }
+    Ok(policy)
+}
+
+fn replace_section(out: &mut XmlNode, tag: &str, replacement: Option<&XmlNode>) {
+    let position = out.children.iter().position(|c| c.tag == tag);
+    out.children.retain(|c| c.tag != tag);
+    if let Some(node) = replacement {
+        let at = position.unwrap_or(out.children.len());
+        out.children.insert(at, node.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{parse_conversion_policy, SectionPolicy};
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn parses_logical_names_with_literal_overrides() {
+        let policy = parse_conversion_policy(
+            r#"
+[sections]
+firewall = "force-target-default"
+nat = "transform"
+syslog = "copy-verbatim"
+"#,
+            "test".to_string(),
+        )
+        .expect("parse");
+        assert_eq!(policy.get("filter"), SectionPolicy::ForceTargetDefault);
+        assert_eq!(policy.get("shaper"), SectionPolicy::ForceTargetDefault);
+        assert_eq!(policy.get("nat"), SectionPolicy::Transform);
+        assert_eq!(policy.get("syslog"), SectionPolicy::CopyVerbatim);
+        assert_eq!(policy.get("system"), SectionPolicy::Transform);
+        assert_eq!(
+            policy.excluded_source_tags(),
+            vec!["filter", "shaper", "syslog"]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_policy_values() {
+        let err = parse_conversion_policy("[sections]\nfilter = \"merge\"\n", "p.toml".into())
+            .expect_err("invalid");
+        assert!(err.to_string().contains("p.toml"));
+    }
+
+    #[test]
+    fn overrides_replace_sections_in_place() {
+        let policy = parse_conversion_policy(
+            "[sections]\nfilter = \"force-target-default\"\nsyslog = \"copy-verbatim\"\nshaper = \"force-target-default\"\n",
+            "test".into(),
+        )
+        .expect("parse");
+        let source =
+            parse(br#"<pfsense><syslog><remoteserver>10.0.0.5</remoteserver></syslog></pfsense>"#)
+                .expect("parse");
+        let target =
+            parse(br#"<opnsense><filter><rule><descr>default</descr></rule></filter></opnsense>"#)
+                .expect("parse");
+        let mut out = parse(
+            br#"<opnsense><filter><rule><descr>converted</descr></rule></filter><shaper/><system/></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        policy.apply_overrides(&mut out, &source, &target, &mut diagnostics);
+
+        let tags: Vec<_> = out.children.iter().map(|c| c.tag.as_str()).collect();
+        assert_eq!(tags, vec!["filter", "system", "syslog"]);
+        assert_eq!(out.get_text(&["filter", "rule", "descr"]), Some("default"));
+        assert_eq!(out.get_text(&["syslog", "remoteserver"]), Some("10.0.0.5"));
+        assert_eq!(diagnostics.iter().count(), 3);
+    }
+}
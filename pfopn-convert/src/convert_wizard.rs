@@ -0,0 +1,647 @@
+//! Interactive front-end for `convert --interactive`.
+//!
+//! The wizard walks through the decisions that most often need a human:
+//!
+//! 1. **Platforms** — confirm the detected source platform/version and the
+//!    target baseline
+//! 2. **DHCP backend** — pick auto/kea/isc with the source backend shown
+//! 3. **Interfaces** — map physical source interfaces that have no match on
+//!    the target baseline
+//! 4. **Transfers** — toggle user/cert/CA dependency transfer
+//! 5. **Plugins** — acknowledge plugins that cannot be carried to the target
+//!
+//! Every answer is folded back into [`ConvertArgs`], so the run is identical
+//! to a non-interactive invocation. The equivalent command line is printed
+//! before converting so the run can be reproduced in scripts.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use pfopn_convert::backend_detect::detect_dhcp_backend;
+use pfopn_convert::detect::detect_version_info;
+use pfopn_convert::interface_guard::{collect_interfaces, format_missing, unmatched_interfaces};
+use pfopn_convert::scan::build_scan_report;
+use xml_diff_core::{parse_file, XmlNode};
+
+use crate::cli::{ConvertArgs, DhcpBackend, LanLiterals, Platform, StaticMapDns};
+use crate::convert::{
+    enabled_switches, generated_baseline, parse_interface_map, resolve_from_platform, run_convert,
+    value_name,
+};
+
+/// Run the interactive wizard, print the equivalent command, then convert.
+pub fn run_interactive(args: ConvertArgs) -> Result<()> {
+    let stdin = io::stdin();
+    let mut prompter = Prompter::new(stdin.lock(), io::stderr());
+    let args = plan(args, &mut prompter)?;
+    println!("equivalent command: {}", render_command_line(&args));
+    run_convert(args)
+}
+
+/// Line-oriented question/answer helper over arbitrary reader/writer pairs.
+pub struct Prompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Print an informational line.
+    fn say(&mut self, line: &str) -> Result<()> {
+        writeln!(self.output, "{line}")?;
+        Ok(())
+    }
+
+    /// Ask a free-form question; an empty answer selects `default`.
+    fn ask(&mut self, question: &str, default: &str) -> Result<String> {
+        if default.is_empty() {
+            write!(self.output, "{question}: ")?;
+        } else {
+            write!(self.output, "{question} [{default}]: ")?;
+        }
+        self.output.flush()?;
+
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            bail!("interactive input closed before all questions were answered");
+        }
+        let answer = line.trim();
+        Ok(if answer.is_empty() {
+            default.to_string()
+        } else {
+            answer.to_string()
+        })
+    }
+
+    /// Ask a yes/no question, re-asking until the answer is recognised.
+    fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        let hint = if default { "Y/n" } else { "y/N" };
+        loop {
+            let answer = self.ask(&format!("{question} ({hint})"), "")?;
+            match answer.to_ascii_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => self.say("please answer y or n")?,
+            }
+        }
+    }
+
+    /// Ask the user to pick one of `options`, re-asking on unknown answers.
+    fn choose(&mut self, question: &str, options: &[&str], default: &str) -> Result<String> {
+        loop {
+            let answer = self.ask(&format!("{question} ({})", options.join("/")), default)?;
+            if options.contains(&answer.as_str()) {
+                return Ok(answer);
+            }
+            self.say(&format!("please choose one of: {}", options.join(", ")))?;
+        }
+    }
+}
+
+/// Resolve every wizard decision into a fully populated [`ConvertArgs`].
+///
+/// # Errors
+///
+/// Returns error if inputs cannot be parsed, input ends early, or the user
+/// aborts at a confirmation step.
+pub fn plan<R: BufRead, W: Write>(
+    mut args: ConvertArgs,
+    prompter: &mut Prompter<R, W>,
+) -> Result<ConvertArgs> {
+    let input = parse_file(&args.input)
+        .with_context(|| format!("failed to parse {}", args.input.display()))?;
+
+    confirm_platforms(&mut args, &input, prompter)?;
+    let target = resolve_target_baseline(&mut args, &input, prompter)?;
+    choose_backend(&mut args, &input, prompter)?;
+    if let Some(target) = &target {
+        map_interfaces(&mut args, &input, target, prompter)?;
+    }
+    choose_transfers(&mut args, prompter)?;
+    review_plugins(&args, &input, prompter)?;
+
+    args.interactive = false;
+    Ok(args)
+}
+
+fn confirm_platforms<R: BufRead, W: Write>(
+    args: &mut ConvertArgs,
+    input: &XmlNode,
+    prompter: &mut Prompter<R, W>,
+) -> Result<()> {
+    let detected = resolve_from_platform(args.from, input).ok();
+    let version = detect_version_info(input);
+    prompter.say(&format!(
+        "source: {} version={} ({})",
+        detected.unwrap_or("unknown"),
+        version.value,
+        version.source
+    ))?;
+
+    let accepted = match detected {
+        Some(_) => prompter.confirm("Is the source platform correct?", true)?,
+        None => false,
+    };
+    if !accepted {
+        let default = match detected {
+            Some("pfsense") => "opnsense",
+            _ => "pfsense",
+        };
+        let chosen = prompter.choose("Source platform", &["pfsense", "opnsense"], default)?;
+        args.from = platform_from_name(&chosen);
+    }
+    if args.from == Platform::Auto {
+        if let Some(name) = detected {
+            args.from = platform_from_name(name);
+        }
+    }
+
+    if args.to == Platform::Auto || args.to == args.from {
+        let default = if args.from == Platform::Pfsense {
+            "opnsense"
+        } else {
+            "pfsense"
+        };
+        let chosen = prompter.choose("Target platform", &["pfsense", "opnsense"], default)?;
+        args.to = platform_from_name(&chosen);
+    }
+    Ok(())
+}
+
+fn resolve_target_baseline<R: BufRead, W: Write>(
+    args: &mut ConvertArgs,
+    input: &XmlNode,
+    prompter: &mut Prompter<R, W>,
+) -> Result<Option<XmlNode>> {
+    if let Some(version) = &args.target_version {
+        let map = parse_interface_map(&args.map_interface)?;
+        let target = generated_baseline(platform_name(args.to), version, input, &map)?;
+        prompter.say(&format!(
+            "target: {} version={} (embedded baseline)",
+            platform_name(args.to),
+            detect_version_info(&target).value
+        ))?;
+        if !prompter.confirm("Convert to this target baseline?", true)? {
+            bail!("conversion aborted at target confirmation");
+        }
+        return Ok(Some(target));
+    }
+    if args.target_file.is_none() && !args.minimal_template {
+        let path = prompter.ask("Target baseline config (fresh export from destination)", "")?;
+        if path.is_empty() {
+            bail!("missing --target-file; a destination baseline config is required");
+        }
+        args.target_file = Some(PathBuf::from(path));
+    }
+    let Some(path) = &args.target_file else {
+        return Ok(None);
+    };
+
+    let target = parse_file(path).with_context(|| format!("failed to parse {}", path.display()))?;
+    let version = detect_version_info(&target);
+    prompter.say(&format!(
+        "target: {} version={} ({})",
+        platform_name(args.to),
+        version.value,
+        version.source
+    ))?;
+    if !prompter.confirm("Convert to this target baseline?", true)? {
+        bail!("conversion aborted at target confirmation");
+    }
+    Ok(Some(target))
+}
+
+fn choose_backend<R: BufRead, W: Write>(
+    args: &mut ConvertArgs,
+    input: &XmlNode,
+    prompter: &mut Prompter<R, W>,
+) -> Result<()> {
+    let source = detect_dhcp_backend(input);
+    prompter.say(&format!(
+        "source dhcp backend: {} ({})",
+        source.mode, source.reason
+    ))?;
+    let chosen = prompter.choose(
+        "DHCP backend for output",
+        &["auto", "kea", "isc"],
+        backend_name(args.backend),
+    )?;
+    args.backend = match chosen.as_str() {
+        "kea" => DhcpBackend::Kea,
+        "isc" => DhcpBackend::Isc,
+        _ => DhcpBackend::Auto,
+    };
+    Ok(())
+}
+
+fn map_interfaces<R: BufRead, W: Write>(
+    args: &mut ConvertArgs,
+    input: &XmlNode,
+    target: &XmlNode,
+    prompter: &mut Prompter<R, W>,
+) -> Result<()> {
+    let mut map = parse_interface_map(&args.map_interface)?;
+    let unmatched = unmatched_interfaces(input, target, Some(&map));
+    if unmatched.is_empty() {
+        return Ok(());
+    }
+
+    let source_names = collect_interfaces(input);
+    let candidates: Vec<String> = collect_interfaces(target)
+        .into_keys()
+        .filter(|name| !source_names.contains_key(name))
+        .collect();
+    for spec in unmatched {
+        let mut options: Vec<&str> = candidates
+            .iter()
+            .filter(|name| !map.values().any(|used| used == *name))
+            .map(String::as_str)
+            .collect();
+        options.push("abort");
+        prompter.say(&format!(
+            "source interface {} has no match on the target baseline",
+            format_missing(&spec.name, &spec)
+        ))?;
+        let chosen = prompter.choose(&format!("Map {} to", spec.name), &options, "abort")?;
+        if chosen == "abort" {
+            bail!(
+                "conversion aborted at interface mapping for '{}'",
+                spec.name
+            );
+        }
+        map.insert(spec.name.clone(), chosen);
+    }
+
+    args.map_interface = render_interface_map(&map);
+    Ok(())
+}
+
+fn choose_transfers<R: BufRead, W: Write>(
+    args: &mut ConvertArgs,
+    prompter: &mut Prompter<R, W>,
+) -> Result<()> {
+    args.no_transfer_users = !prompter.confirm(
+        "Transfer system users referenced by OpenVPN?",
+        !args.no_transfer_users,
+    )?;
+    args.no_transfer_certs = !prompter.confirm(
+        "Transfer certificates referenced by OpenVPN?",
+        !args.no_transfer_certs,
+    )?;
+    args.no_transfer_cas =
+        !prompter.confirm("Transfer CAs referenced by OpenVPN?", !args.no_transfer_cas)?;
+    Ok(())
+}
+
+fn review_plugins<R: BufRead, W: Write>(
+    args: &ConvertArgs,
+    input: &XmlNode,
+    prompter: &mut Prompter<R, W>,
+) -> Result<()> {
+    let report = build_scan_report(input, Some(platform_name(args.to)));
+    let mut unmappable = report.missing_target_compat;
+    unmappable.extend(report.unsupported_plugins);
+    unmappable.sort();
+    unmappable.dedup();
+    if unmappable.is_empty() {
+        return Ok(());
+    }
+
+    prompter.say(&format!(
+        "plugins without a {} equivalent (their config will not be carried over): {}",
+        platform_name(args.to),
+        unmappable.join(", ")
+    ))?;
+    if !prompter.confirm("Continue without these plugins?", true)? {
+        bail!("conversion aborted at plugin review");
+    }
+    Ok(())
+}
+
+/// Render the non-interactive `convert` invocation equivalent to `args`.
+pub fn render_command_line(args: &ConvertArgs) -> String {
+    let mut parts = vec![
+        "pfopn-convert".to_string(),
+        "convert".to_string(),
+        shell_quote_path(&args.input),
+        "--output".to_string(),
+        shell_quote_path(&args.output),
+        "--from".to_string(),
+        platform_name(args.from).to_string(),
+        "--to".to_string(),
+        platform_name(args.to).to_string(),
+    ];
+    if let Some(path) = &args.target_file {
+        parts.push("--target-file".to_string());
+        parts.push(shell_quote_path(path));
+    }
+    if let Some(version) = &args.target_version {
+        parts.push("--target-version".to_string());
+        parts.push(shell_quote(version));
+    }
+    parts.push("--backend".to_string());
+    parts.push(backend_name(args.backend).to_string());
+    if args.static_map_dns != StaticMapDns::Off {
+        parts.push("--static-map-dns".to_string());
+        parts.push(value_name(args.static_map_dns));
+    }
+    for entry in &args.map_interface {
+        parts.push("--map-interface".to_string());
+        parts.push(shell_quote(entry));
+    }
+    if let Some(lan_ip) = &args.lan_ip {
+        parts.push("--lan-ip".to_string());
+        parts.push(shell_quote(lan_ip));
+        if args.lan_literals != LanLiterals::Rewrite {
+            parts.push("--lan-literals".to_string());
+            parts.push(value_name(args.lan_literals));
+        }
+    }
+    for mapping in &args.renumber {
+        parts.push("--renumber".to_string());
+        parts.push(mapping.to_string());
+    }
+    parts.extend(enabled_switches(args).map(str::to_string));
+    if args.progress {
+        parts.push("--progress".to_string());
+    }
+    if args.no_revision {
+        parts.push("--no-revision".to_string());
+    }
+    if let Some(user) = &args.revision_user {
+        parts.push("--revision-user".to_string());
+        parts.push(shell_quote(user));
+    }
+    if args.provenance {
+        parts.push("--provenance".to_string());
+    }
+    if let Some(path) = &args.policy {
+        parts.push("--policy".to_string());
+        parts.push(shell_quote_path(path));
+    }
+    if let Some(path) = &args.template {
+        parts.push("--template".to_string());
+        parts.push(shell_quote_path(path));
+    }
+    parts.join(" ")
+}
+
+fn render_interface_map(map: &BTreeMap<String, String>) -> Vec<String> {
+    map.iter()
+        .map(|(from, to)| format!("{from}={to}"))
+        .collect()
+}
+
+fn shell_quote_path(path: &Path) -> String {
+    shell_quote(&path.display().to_string())
+}
+
+fn shell_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@".contains(c));
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+fn platform_from_name(name: &str) -> Platform {
+    match name {
+        "pfsense" => Platform::Pfsense,
+        "opnsense" => Platform::Opnsense,
+        _ => Platform::Auto,
+    }
+}
+
+fn platform_name(platform: Platform) -> &'static str {
+    match platform {
+        Platform::Auto => "auto",
+        Platform::Pfsense => "pfsense",
+        Platform::Opnsense => "opnsense",
+    }
+}
+
+fn backend_name(backend: DhcpBackend) -> &'static str {
+    match backend {
+        DhcpBackend::Auto => "auto",
+        DhcpBackend::Kea => "kea",
+        DhcpBackend::Isc => "isc",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Cursor;
+
+    use clap::Parser;
+
+    use super::{plan, render_command_line, Prompter};
+    use crate::cli::{Cli, Command, ConvertArgs, DhcpBackend, Platform};
+
+    fn convert_args(argv: &[&str]) -> ConvertArgs {
+        let mut full = vec!["pfopn-convert", "convert"];
+        full.extend_from_slice(argv);
+        match Cli::parse_from(full).command {
+            Command::Convert(args) => args,
+            other => panic!("unexpected command {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plan_folds_answers_into_args() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let input = dir.path().join("src.xml");
+        let target = dir.path().join("dst.xml");
+        fs::write(
+            &input,
+            r#"<pfsense><version>23.3</version><interfaces><lan><if>igb0</if></lan><opt2><if>igb2</if><descr>DMZ</descr></opt2></interfaces></pfsense>"#,
+        )
+        .expect("src write");
+        fs::write(
+            &target,
+            r#"<opnsense><interfaces><lan><if>vtnet0</if></lan><opt1><if>vtnet1</if></opt1></interfaces></opnsense>"#,
+        )
+        .expect("dst write");
+
+        let args = convert_args(&[
+            input.to_str().expect("utf8"),
+            "--output",
+            "/tmp/out.xml",
+            "--to",
+            "opnsense",
+            "--target-file",
+            target.to_str().expect("utf8"),
+            "--interactive",
+        ]);
+        // source ok, target ok, backend isc, map opt2 -> opt1, users no, certs default, cas default
+        let answers = "\n\nisc\nopt1\nn\n\n\n";
+        let mut prompter = Prompter::new(Cursor::new(answers), Vec::new());
+        let planned = plan(args, &mut prompter).expect("plan");
+
+        assert_eq!(planned.from, Platform::Pfsense);
+        assert_eq!(planned.backend, DhcpBackend::Isc);
+        assert_eq!(planned.map_interface, vec!["opt2=opt1".to_string()]);
+        assert!(planned.no_transfer_users);
+        assert!(!planned.no_transfer_certs);
+        assert!(!planned.interactive);
+
+        let line = render_command_line(&planned);
+        assert!(line.contains("--from pfsense --to opnsense"));
+        assert!(line.contains("--backend isc"));
+        assert!(line.contains("--map-interface opt2=opt1"));
+        assert!(line.contains("--no-transfer-users"));
+        assert!(!line.contains("--interactive"));
+    }
+
+    #[test]
+    fn plan_aborts_when_input_runs_out() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let input = dir.path().join("src.xml");
+        fs::write(
+            &input,
+            r#"<pfsense><interfaces><lan/></interfaces></pfsense>"#,
+        )
+        .expect("src write");
+        let args = convert_args(&[
+            input.to_str().expect("utf8"),
+            "--output",
+            "/tmp/out.xml",
+            "--to",
+            "opnsense",
+            "--minimal-template",
+        ]);
+        let mut prompter = Prompter::new(Cursor::new(""), Vec::new());
+        let err = plan(args, &mut prompter).expect_err("closed input");
+        assert!(err.to_string().contains("interactive input closed"));
+    }
+
+    #[test]
+    fn render_command_line_quotes_paths_with_spaces() {
+        let args = convert_args(&[
+            "/tmp/my config.xml",
+            "--output",
+            "/tmp/out.xml",
+            "--to",
+            "opnsense",
+            "--minimal-template",
+        ]);
+        let line = render_command_line(&args);
+        assert!(line.contains("'/tmp/my config.xml'"));
+        assert!(line.contains("--minimal-template"));
+    }
+
+    #[test]
+    fn render_command_line_keeps_output_affecting_options() {
+        let args = convert_args(&[
+            "/tmp/in.xml",
+            "--output",
+            "/tmp/out.xml",
+            "--to",
+            "opnsense",
+            "--minimal-template",
+            "--prune-orphans",
+            "--keep-unknown",
+            "--strip-private-keys",
+            "--progress",
+            "--strict",
+            "--static-map-dns",
+            "materialize",
+            "--template",
+            "/tmp/my report.tmpl",
+            "--lan-ip",
+            "10.0.0.1",
+            "--lan-literals",
+            "review",
+        ]);
+        let line = render_command_line(&args);
+        assert!(line.contains(" --prune-orphans"));
+        assert!(line.contains(" --keep-unknown"));
+        assert!(line.contains(" --strip-private-keys"));
+        assert!(line.contains(" --progress"));
+        assert!(line.contains(" --strict"));
+        assert!(line.contains(" --static-map-dns materialize"));
+        assert!(line.contains(" --template '/tmp/my report.tmpl'"));
+        assert!(line.contains(" --lan-ip 10.0.0.1 --lan-literals review"));
+    }
+
+    #[test]
+    fn render_command_line_keeps_revision_options() {
+        let args = convert_args(&[
+            "/tmp/in.xml",
+            "--output",
+            "/tmp/out.xml",
+            "--to",
+            "opnsense",
+            "--minimal-template",
+            "--revision-user",
+            "ops team",
+            "--provenance",
+        ]);
+        let line = render_command_line(&args);
+        assert!(line.contains(" --revision-user 'ops team' --provenance"));
+
+        let args = convert_args(&[
+            "/tmp/in.xml",
+            "--output",
+            "/tmp/out.xml",
+            "--to",
+            "opnsense",
+            "--minimal-template",
+            "--no-revision",
+        ]);
+        assert!(render_command_line(&args).contains(" --no-revision"));
+    }
+
+    #[test]
+    fn rendered_command_line_round_trips_renumbered_plan() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let input = dir.path().join("src.xml");
+        let target = dir.path().join("dst.xml");
+        fs::write(
+            &input,
+            r#"<pfsense><version>23.3</version><interfaces><lan><if>igb0</if></lan></interfaces></pfsense>"#,
+        )
+        .expect("src write");
+        fs::write(
+            &target,
+            r#"<opnsense><interfaces><lan><if>vtnet0</if></lan></interfaces></opnsense>"#,
+        )
+        .expect("dst write");
+
+        let args = convert_args(&[
+            input.to_str().expect("utf8"),
+            "--output",
+            "/tmp/out.xml",
+            "--to",
+            "opnsense",
+            "--target-file",
+            target.to_str().expect("utf8"),
+            "--renumber",
+            "192.168.1.0/24=10.10.1.0/24",
+            "--renumber",
+            "192.168.2.0/24=10.10.2.0/24",
+            "--interactive",
+        ]);
+        let mut prompter = Prompter::new(Cursor::new("\n\n\n\n\n\n"), Vec::new());
+        let planned = plan(args, &mut prompter).expect("plan");
+
+        let line = render_command_line(&planned);
+        assert!(line.contains(
+            "--renumber 192.168.1.0/24=10.10.1.0/24 --renumber 192.168.2.0/24=10.10.2.0/24"
+        ));
+        let argv: Vec<&str> = line.split_whitespace().skip(2).collect();
+        let replayed = convert_args(&argv);
+        assert_eq!(replayed.renumber, planned.renumber);
+        assert_eq!(format!("{replayed:?}"), format!("{planned:?}"));
+    }
+}
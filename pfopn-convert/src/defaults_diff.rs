@@ -0,0 +1,274 @@
+//! Comparison against factory defaults.
+//!
+//! `defaults-diff` diffs a config against the embedded baseline template for
+//! its platform and release (see [`load_baseline`]) and keeps only what was
+//! changed on the firewall: settings added, defaults changed, and defaults
+//! removed. Revision history and embedded runtime data (RRD graphs, leases)
+//! are ignored. The per-section counts show the configuration surface a
+//! migration actually has to cover.
+//!
+//! The template is picked by:
+//! 1. An explicit release (`2.7`, `plus`, `25.7`)
+//! 2. pfSense Plus edition → `plus`
+//! 3. pfSense: the CE template with the config's `<version>`, or the newest
+//!    one not newer than it
+//! 4. OPNsense: the newest template (configs do not record the release)
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use thiserror::Error;
+use xml_diff_core::{diff_with_options, DiffEntry, DiffOptions, XmlNode};
+
+use crate::detect::{detect_config, detect_pfsense_edition, ConfigFlavor, PfSenseEdition};
+use crate::profile::{baseline_versions, load_baseline};
+use crate::section::{
+    default_ordered_tags, ignore_category_paths, ignore_category_queries, section_tags,
+};
+use crate::sections_report::diff_path;
+
+/// Ignore categories that never hold user settings.
+const IGNORED_CATEGORIES: &[&str] = &["metadata", "dynamic-state"];
+
+#[derive(Debug, Error)]
+pub enum DefaultsDiffError {
+    #[error("unsupported root tag '{0}': expected pfsense or opnsense")]
+    UnknownPlatform(String),
+    #[error("no embedded {platform} defaults for release '{release}'; expected one of {known}")]
+    NoBaseline {
+        platform: String,
+        release: String,
+        known: String,
+    },
+}
+
+/// Changes in one top-level section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SectionChanges {
+    pub section: String,
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+}
+
+impl SectionChanges {
+    pub fn total(&self) -> usize {
+        self.added + self.changed + self.removed
+    }
+}
+
+/// User-made changes relative to the factory defaults.
+#[derive(Debug, Clone, Serialize)]
+pub struct DefaultsDiffReport {
+    pub platform: String,
+    /// Template compared against (`2.7`, `plus`, `25.7`).
+    pub baseline: String,
+    /// Why that template was picked.
+    pub baseline_reason: String,
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+    /// Sections with changes, most changed first.
+    pub sections: Vec<SectionChanges>,
+    pub entries: Vec<DiffEntry>,
+}
+
+/// Pick the factory-default template for `root`, with the reason.
+///
+/// `release` overrides detection.
+pub fn select_defaults(
+    root: &XmlNode,
+    release: Option<&str>,
+) -> Result<(XmlNode, &'static str, String), DefaultsDiffError> {
+    let platform = match detect_config(root) {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => return Err(DefaultsDiffError::UnknownPlatform(root.tag.clone())),
+    };
+    let load = |release: &str| {
+        load_baseline(platform, release).ok_or_else(|| DefaultsDiffError::NoBaseline {
+            platform: platform.to_string(),
+            release: release.to_string(),
+            known: baseline_versions(platform).join(", "),
+        })
+    };
+    if let Some(release) = release {
+        let (baseline, resolved) = load(release)?;
+        return Ok((baseline, resolved, format!("requested release {release}")));
+    }
+    if platform == "opnsense" {
+        let newest = baseline_versions(platform)
+            .last()
+            .expect("opnsense templates");
+        let (baseline, resolved) = load(newest)?;
+        return Ok((
+            baseline,
+            resolved,
+            "OPNsense configs do not record the release; using the newest template".to_string(),
+        ));
+    }
+    if detect_pfsense_edition(root).is_some_and(|e| e.edition == PfSenseEdition::Plus) {
+        let (baseline, resolved) = load("plus")?;
+        return Ok((baseline, resolved, "pfSense Plus edition".to_string()));
+    }
+
+    let version = root.get_text(&["version"]).map(str::trim).unwrap_or("");
+    let wanted = parse_version(version);
+    let mut picked = None;
+    for candidate in baseline_versions(platform).iter().filter(|v| **v != "plus") {
+        let (baseline, resolved) = load(candidate)?;
+        let schema = parse_version(baseline.get_text(&["version"]).unwrap_or(""));
+        if schema == wanted {
+            return Ok((
+                baseline,
+                resolved,
+                format!("config version {version} matches the {resolved} template"),
+            ));
+        }
+        if picked.is_none() || schema <= wanted {
+            picked = Some((baseline, resolved));
+        }
+    }
+    let (baseline, resolved) = picked.expect("pfsense CE templates");
+    Ok((
+        baseline,
+        resolved,
+        format!("config version '{version}' has no exact template; using {resolved}"),
+    ))
+}
+
+/// Diff `root` against its factory defaults.
+///
+/// `section` keeps only the top-level tags of a logical section (see
+/// [`section_tags`]) or the named tag.
+pub fn build_defaults_diff(
+    root: &XmlNode,
+    release: Option<&str>,
+    section: Option<&str>,
+    key_fields: HashMap<String, String>,
+) -> Result<DefaultsDiffReport, DefaultsDiffError> {
+    let (baseline, resolved, baseline_reason) = select_defaults(root, release)?;
+    let names: Vec<String> = IGNORED_CATEGORIES.iter().map(ToString::to_string).collect();
+    let opts = DiffOptions {
+        ignore_paths: ignore_category_paths(&names)
+            .expect("known categories")
+            .into_iter()
+            .map(ToString::to_string)
+            .collect(),
+        ignore_queries: ignore_category_queries(&names).expect("known categories"),
+        key_fields,
+        ordered_tags: default_ordered_tags(),
+        ..DiffOptions::default()
+    };
+
+    let wanted: Option<Vec<&str>> =
+        section.map(|s| section_tags(s).map_or_else(|| vec![s], <[_]>::to_vec));
+    let entries: Vec<DiffEntry> = diff_with_options(&baseline, root, &opts)
+        .into_iter()
+        .filter(|entry| {
+            wanted
+                .as_ref()
+                .is_none_or(|tags| tags.contains(&top_level(diff_path(entry))))
+        })
+        .collect();
+
+    let mut by_section: BTreeMap<&str, SectionChanges> = BTreeMap::new();
+    for entry in &entries {
+        let section = top_level(diff_path(entry));
+        let counts = by_section.entry(section).or_insert_with(|| SectionChanges {
+            section: section.to_string(),
+            ..SectionChanges::default()
+        });
+        match entry {
+            DiffEntry::OnlyRight { .. } => counts.added += 1,
+            DiffEntry::OnlyLeft { .. } => counts.removed += 1,
+            DiffEntry::Identical { .. } => {}
+            _ => counts.changed += 1,
+        }
+    }
+    let mut sections: Vec<SectionChanges> = by_section.into_values().collect();
+    sections.sort_by(|a, b| b.total().cmp(&a.total()).then(a.section.cmp(&b.section)));
+
+    Ok(DefaultsDiffReport {
+        platform: root.tag.clone(),
+        baseline: resolved.to_string(),
+        baseline_reason,
+        added: sections.iter().map(|s| s.added).sum(),
+        changed: sections.iter().map(|s| s.changed).sum(),
+        removed: sections.iter().map(|s| s.removed).sum(),
+        sections,
+        entries,
+    })
+}
+
+/// Summary lines: totals, the template, and per-section counts.
+pub fn render_defaults_summary(report: &DefaultsDiffReport) -> String {
+    let mut lines = vec![
+        format!(
+            "defaults_diff platform={} baseline={} added={} changed={} removed={}",
+            report.platform, report.baseline, report.added, report.changed, report.removed
+        ),
+        format!("baseline_reason: {}", report.baseline_reason),
+    ];
+    for section in &report.sections {
+        lines.push(format!(
+            "- {} added={} changed={} removed={}",
+            section.section, section.added, section.changed, section.removed
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Top-level tag of a diff path (`pfsense.system[1].hostname[1]` -> `system`).
+fn top_level(path: &str) -> &str {
+    let segment = path.split('.').nth(1).unwrap_or(path);
+    segment.split('[').next().unwrap_or(segment)
+}
+
+fn parse_version(raw: &str) -> (u32, u32) {
+    let mut parts = raw.split('.').map(|p| p.trim().parse::<u32>().unwrap_or(0));
+    (parts.next().unwrap_or(0), parts.next().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{build_defaults_diff, select_defaults, DefaultsDiffError};
+    use crate::section::default_key_fields;
+
+    #[test]
+    fn reports_only_changes_from_matching_template() {
+        let root = parse(
+            b"<pfsense><version>24.0</version><revision><time>1</time></revision>\
+              <system><hostname>fw1</hostname></system></pfsense>",
+        )
+        .expect("parse");
+        let (_, resolved, reason) = select_defaults(&root, None).expect("template");
+        assert_eq!(resolved, "2.8");
+        assert_eq!(reason, "config version 24.0 matches the 2.8 template");
+
+        let report =
+            build_defaults_diff(&root, None, Some("system"), default_key_fields()).expect("diff");
+        assert!(report.sections.iter().all(|s| s.section == "system"));
+        assert!(report.changed >= 1);
+        assert!(report.removed >= 1);
+        assert!(report
+            .entries
+            .iter()
+            .all(|e| !format!("{e:?}").contains("revision")));
+    }
+
+    #[test]
+    fn opnsense_uses_newest_template_unless_release_given() {
+        let root = parse(b"<opnsense><system/></opnsense>").expect("parse");
+        let (_, resolved, _) = select_defaults(&root, None).expect("template");
+        assert_eq!(resolved, "26.1");
+        let (_, resolved, _) = select_defaults(&root, Some("25.1.4")).expect("template");
+        assert_eq!(resolved, "25.1");
+        assert!(matches!(
+            select_defaults(&root, Some("19.7")),
+            Err(DefaultsDiffError::NoBaseline { .. })
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use pfopn_convert::defaults_diff::{build_defaults_diff, render_defaults_summary};
+use pfopn_convert::report::render_text;
+use xml_diff_core::parse_file;
+
+use crate::cli::{DefaultsDiffArgs, OutputFormat};
+use crate::mappings_cmd;
+
+/// Print what a config changed from its factory defaults.
+pub fn run_defaults_diff(args: DefaultsDiffArgs) -> Result<()> {
+    let root = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let key_fields = mappings_cmd::resolve_key_fields(
+        args.mappings_file.as_deref(),
+        args.mappings_dir.as_deref(),
+        &[&root],
+    );
+    let mut report = build_defaults_diff(
+        &root,
+        args.release.as_deref(),
+        args.section.as_deref(),
+        key_fields,
+    )?;
+    if args.summary {
+        report.entries.clear();
+    }
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Text => {
+            println!("{}", render_defaults_summary(&report));
+            if !report.entries.is_empty() {
+                println!();
+                println!("{}", render_text(&report.entries));
+            }
+        }
+    }
+    Ok(())
+}
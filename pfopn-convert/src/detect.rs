@@ -74,3 +74,116 @@ pub fn detect_version_info(node: &XmlNode) -> VersionDetection {
         confidence: "low".to_string(),
     }
 }
+
+/// pfSense edition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PfSenseEdition {
+    /// Community Edition.
+    Ce,
+    /// pfSense Plus.
+    Plus,
+}
+
+impl PfSenseEdition {
+    /// Lowercase name (`ce`, `plus`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PfSenseEdition::Ce => "ce",
+            PfSenseEdition::Plus => "plus",
+        }
+    }
+}
+
+/// OPNsense edition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpnSenseEdition {
+    /// Community edition.
+    Community,
+    /// Business Edition.
+    Business,
+}
+
+impl OpnSenseEdition {
+    /// Lowercase name (`community`, `business`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OpnSenseEdition::Community => "community",
+            OpnSenseEdition::Business => "business",
+        }
+    }
+}
+
+/// Detected edition with the evidence for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EditionDetection<E> {
+    pub edition: E,
+    /// Edition-only settings or hints found; empty for CE and community.
+    pub evidence: Vec<String>,
+}
+
+/// Tell pfSense Plus from CE; `None` for other platforms.
+///
+/// Plus is detected from settings only Plus writes (see
+/// [`crate::plus_features`]) and a Plus package repository in
+/// `<system><pkg_repo_conf_path>`. A config with neither is reported as CE,
+/// which is also how a Plus config without Plus-only settings converts.
+pub fn detect_pfsense_edition(node: &XmlNode) -> Option<EditionDetection<PfSenseEdition>> {
+    if detect_config(node) != ConfigFlavor::PfSense {
+        return None;
+    }
+    let mut evidence: Vec<String> = crate::plus_features::plus_feature_uses(node)
+        .into_iter()
+        .map(|u| format!("{} ({})", u.description, u.path))
+        .collect();
+    if let Some(repo) = node
+        .get_text(&["system", "pkg_repo_conf_path"])
+        .filter(|r| r.to_ascii_lowercase().contains("plus"))
+    {
+        evidence.push(format!("Plus package repository ({})", repo.trim()));
+    }
+    let edition = if evidence.is_empty() {
+        PfSenseEdition::Ce
+    } else {
+        PfSenseEdition::Plus
+    };
+    Some(EditionDetection { edition, evidence })
+}
+
+/// Tell OPNsense Business Edition from community; `None` for other platforms.
+///
+/// Business Edition is detected from the firmware settings (`business`
+/// release type, a subscription key, the Deciso mirror) and Business-only
+/// plugins (see [`crate::opnsense_editions`]).
+pub fn detect_opnsense_edition(node: &XmlNode) -> Option<EditionDetection<OpnSenseEdition>> {
+    if detect_config(node) != ConfigFlavor::OpnSense {
+        return None;
+    }
+    let mut evidence = Vec::new();
+    let firmware = |tag: &str| {
+        node.get_text(&["system", "firmware", tag])
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+    if let Some(kind) = firmware("type").filter(|t| t.to_ascii_lowercase().contains("business")) {
+        evidence.push(format!("firmware type {kind}"));
+    }
+    if firmware("subscription").is_some() {
+        evidence.push("firmware subscription key".to_string());
+    }
+    if let Some(mirror) = firmware("mirror").filter(|m| m.contains("deciso.com")) {
+        evidence.push(format!("Business mirror ({mirror})"));
+    }
+    evidence.extend(
+        crate::opnsense_editions::business_plugin_uses(node)
+            .into_iter()
+            .map(|u| format!("{} ({})", u.description, u.evidence)),
+    );
+    let edition = if evidence.is_empty() {
+        OpnSenseEdition::Community
+    } else {
+        OpnSenseEdition::Business
+    };
+    Some(EditionDetection { edition, evidence })
+}
@@ -0,0 +1,291 @@
+//! DHCP backend decision report (`dhcp-plan`).
+//!
+//! Runs the conversion once per `--backend` choice (`auto`, `kea`, `isc`)
+//! and reports, for every DHCP-enabled source interface and address family,
+//! what the output would hold:
+//!
+//! - **migrated** — the scope carries over in the chosen backend's format
+//! - **partial** — it carries over, but settings listed in `notes` do not
+//! - **legacy** — the scope stays in the ISC (`<dhcpd>`/`<dhcpdv6>`) format
+//!   on OPNsense, which needs the `os-isc-dhcp` plugin on 26.1 and later
+//! - **dropped** — the interface does not exist on the target
+//!
+//! A choice the conversion rejects (for example Kea on a baseline without a
+//! Kea subtree) is reported as unavailable with the reason.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::pipeline::{ConversionPipeline, ConversionResult};
+use crate::transform::dhcp::kea::isc_iface_enabled;
+use crate::transform::dhcp::{EffectiveDhcpBackend, RequestedDhcpBackend};
+
+/// What happens to one DHCP scope under a backend choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScopeOutcome {
+    Migrated,
+    Partial,
+    Legacy,
+    Dropped,
+}
+
+impl ScopeOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            ScopeOutcome::Migrated => "migrated",
+            ScopeOutcome::Partial => "partial",
+            ScopeOutcome::Legacy => "legacy",
+            ScopeOutcome::Dropped => "dropped",
+        }
+    }
+}
+
+/// One source DHCP scope under one backend choice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScopePlan {
+    /// Source logical interface (`lan`, `opt1`).
+    pub interface: String,
+    /// Target interface after `--map-interface`, when it differs.
+    pub mapped_to: Option<String>,
+    /// `v4` or `v6`.
+    pub family: &'static str,
+    pub outcome: ScopeOutcome,
+    /// Settings that do not carry over, and why a scope stayed legacy, as
+    /// reported by the conversion.
+    pub notes: Vec<String>,
+}
+
+/// The outcome of one `--backend` choice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackendPlan {
+    /// `auto`, `kea`, or `isc`.
+    pub requested: &'static str,
+    /// Backend the output would use after fallbacks; `None` when unavailable.
+    pub effective: Option<EffectiveDhcpBackend>,
+    /// Why the conversion rejects this choice.
+    pub error: Option<String>,
+    pub scopes: Vec<ScopePlan>,
+}
+
+/// Per-interface DHCP outcomes for every backend choice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DhcpPlan {
+    pub to: String,
+    pub backends: Vec<BackendPlan>,
+}
+
+const CHOICES: &[(&str, RequestedDhcpBackend)] = &[
+    ("auto", RequestedDhcpBackend::Auto),
+    ("kea", RequestedDhcpBackend::Kea),
+    ("isc", RequestedDhcpBackend::Isc),
+];
+
+/// Convert `source` onto `target` under each backend choice and classify
+/// every enabled source DHCP scope.
+pub fn build_dhcp_plan(
+    source: &XmlNode,
+    target: &XmlNode,
+    to: &str,
+    interface_map: &BTreeMap<String, String>,
+) -> DhcpPlan {
+    let scopes = source_scopes(source);
+    let backends = CHOICES
+        .iter()
+        .map(|(label, requested)| {
+            let mut pipeline = ConversionPipeline::new(to).backend(*requested);
+            for (from, dst) in interface_map {
+                pipeline = pipeline.map_interface(from, dst);
+            }
+            match pipeline.run(source, target) {
+                Ok(result) => BackendPlan {
+                    requested: label,
+                    effective: Some(result.effective_backend),
+                    error: None,
+                    scopes: scopes
+                        .iter()
+                        .map(|(iface, family)| classify(&result, iface, family, interface_map))
+                        .collect(),
+                },
+                Err(err) => BackendPlan {
+                    requested: label,
+                    effective: None,
+                    error: Some(format!("{err:#}")),
+                    scopes: Vec::new(),
+                },
+            }
+        })
+        .collect();
+    DhcpPlan {
+        to: to.to_string(),
+        backends,
+    }
+}
+
+/// Enabled ISC scopes in the source as (interface, family) pairs.
+fn source_scopes(source: &XmlNode) -> Vec<(String, &'static str)> {
+    let mut out = Vec::new();
+    let sections: [(&str, &'static str); 3] =
+        [("dhcpd", "v4"), ("dhcpdv6", "v6"), ("dhcpd6", "v6")];
+    for (section, family) in sections {
+        let Some(node) = source.get_child(section) else {
+            continue;
+        };
+        for iface in node.children.iter().filter(|c| isc_iface_enabled(c)) {
+            let entry = (iface.tag.clone(), family);
+            if !out.contains(&entry) {
+                out.push(entry);
+            }
+        }
+    }
+    out
+}
+
+fn classify(
+    result: &ConversionResult,
+    iface: &str,
+    family: &'static str,
+    interface_map: &BTreeMap<String, String>,
+) -> ScopePlan {
+    let mapped = interface_map.get(iface).cloned();
+    let target_iface = mapped.as_deref().unwrap_or(iface);
+    let prefix = format!("{iface}: ");
+    let v6_fallback = format!("DHCPv6 range on {iface} ");
+    let notes: Vec<String> = result
+        .diagnostics
+        .iter()
+        .filter(|d| d.source == "dhcp")
+        .filter_map(|d| {
+            if family == "v6" && d.message.starts_with(&v6_fallback) {
+                return Some(d.message.as_str());
+            }
+            d.message
+                .strip_prefix(&prefix)
+                .filter(|msg| msg.contains("DHCPv6") == (family == "v6"))
+        })
+        .map(ToOwned::to_owned)
+        .collect();
+    let present = result
+        .output
+        .get_child("interfaces")
+        .is_some_and(|i| i.get_child(target_iface).is_some());
+    let preserved_v6 = result
+        .dhcp_migration
+        .as_ref()
+        .is_some_and(|m| m.preserved_dhcpdv6_ifaces.iter().any(|p| p == iface));
+    let legacy = result.to == "opnsense"
+        && (result.effective_backend == EffectiveDhcpBackend::Isc
+            || (family == "v6" && preserved_v6));
+    let outcome = if !present {
+        ScopeOutcome::Dropped
+    } else if legacy {
+        ScopeOutcome::Legacy
+    } else if notes.is_empty() {
+        ScopeOutcome::Migrated
+    } else {
+        ScopeOutcome::Partial
+    };
+    ScopePlan {
+        interface: iface.to_string(),
+        mapped_to: mapped,
+        family,
+        outcome,
+        notes,
+    }
+}
+
+/// Render the plan as one block per backend choice.
+pub fn render_dhcp_plan_text(plan: &DhcpPlan) -> String {
+    let mut out = format!("DHCP backend plan for {}\n", plan.to);
+    for backend in &plan.backends {
+        match (&backend.error, backend.effective) {
+            (Some(err), _) => {
+                out.push_str(&format!(
+                    "\n--backend {}: unavailable: {err}\n",
+                    backend.requested
+                ));
+                continue;
+            }
+            (None, Some(effective)) => out.push_str(&format!(
+                "\n--backend {} (uses {}):\n",
+                backend.requested,
+                backend_name(effective)
+            )),
+            (None, None) => {}
+        }
+        if backend.scopes.is_empty() {
+            out.push_str("  no DHCP-enabled interfaces\n");
+        }
+        for scope in &backend.scopes {
+            let name = match &scope.mapped_to {
+                Some(mapped) => format!("{} -> {mapped}", scope.interface),
+                None => scope.interface.clone(),
+            };
+            out.push_str(&format!(
+                "  {name} {}: {}\n",
+                scope.family,
+                scope.outcome.label()
+            ));
+            for note in &scope.notes {
+                out.push_str(&format!("    - {note}\n"));
+            }
+        }
+    }
+    out.trim_end().to_string()
+}
+
+fn backend_name(backend: EffectiveDhcpBackend) -> &'static str {
+    match backend {
+        EffectiveDhcpBackend::Kea => "kea",
+        EffectiveDhcpBackend::Isc => "isc",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use xml_diff_core::parse;
+
+    use super::{build_dhcp_plan, ScopeOutcome};
+
+    const SOURCE: &[u8] = br#"<pfsense><version>23.3</version><interfaces><wan><if>em0</if><ipaddr>dhcp</ipaddr></wan><lan><if>em1</if><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan><opt1><if>em2</if><ipaddr>10.0.0.1</ipaddr><subnet>24</subnet></opt1></interfaces><dhcpd><lan><enable/><range><from>192.168.1.100</from><to>192.168.1.200</to></range></lan><opt1><enable/><range><from>10.0.0.100</from><to>10.0.0.200</to></range><rootpath>/srv/nfs</rootpath></opt1></dhcpd></pfsense>"#;
+
+    const TARGET: &[u8] = br#"<opnsense><version>26.1</version><interfaces><wan><if>vtnet0</if></wan><lan><if>vtnet1</if></lan><opt1><if>vtnet2</if></opt1></interfaces><dhcpd/><OPNsense><Kea><dhcp4><general><enabled>0</enabled></general><subnets/><reservations/></dhcp4></Kea></OPNsense><system><firmware><plugins>os-isc-dhcp</plugins></firmware></system></opnsense>"#;
+
+    #[test]
+    fn reports_each_scope_per_backend() {
+        let source = parse(SOURCE).expect("parse");
+        let target = parse(TARGET).expect("parse");
+        let plan = build_dhcp_plan(&source, &target, "opnsense", &BTreeMap::new());
+
+        let kea = plan
+            .backends
+            .iter()
+            .find(|b| b.requested == "kea")
+            .expect("kea");
+        assert!(kea.error.is_none(), "{:?}", kea.error);
+        let outcomes: Vec<(&str, ScopeOutcome)> = kea
+            .scopes
+            .iter()
+            .map(|s| (s.interface.as_str(), s.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("lan", ScopeOutcome::Migrated),
+                ("opt1", ScopeOutcome::Partial)
+            ]
+        );
+        assert!(kea.scopes[1].notes[0].contains("root path '/srv/nfs'"));
+
+        let isc = plan
+            .backends
+            .iter()
+            .find(|b| b.requested == "isc")
+            .expect("isc");
+        assert!(isc.scopes.iter().all(|s| s.outcome == ScopeOutcome::Legacy));
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::{Context, Result};
+use pfopn_convert::dhcp_plan::{build_dhcp_plan, render_dhcp_plan_text};
+use xml_diff_core::parse_file;
+
+use crate::cli::{DhcpPlanArgs, OutputFormat, ScanTarget};
+use crate::convert::{load_target, parse_interface_map};
+
+/// Print what each `--backend` choice would do to every source DHCP scope.
+pub fn run_dhcp_plan(args: DhcpPlanArgs) -> Result<()> {
+    let source = parse_file(&args.input)
+        .with_context(|| format!("failed to parse {}", args.input.display()))?;
+    let interface_map = parse_interface_map(&args.map_interface)?;
+    let to = match args.to {
+        ScanTarget::Pfsense => "pfsense",
+        ScanTarget::Opnsense => "opnsense",
+    };
+    let target = load_target(
+        args.target_file.as_deref(),
+        args.target_version.as_deref(),
+        to,
+        &source,
+        &interface_map,
+    )?;
+
+    let plan = build_dhcp_plan(&source, &target, to, &interface_map);
+    match args.format {
+        OutputFormat::Text => println!("{}", render_dhcp_plan_text(&plan)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&plan)?),
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Structured diagnostics collected while transforming configurations.
+//!
+//! Transforms and the conversion pipeline report non-fatal issues into a
+//! [`Diagnostics`] collector instead of printing to stderr, so library callers
+//! can inspect them and the CLI can decide how to render them.
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Informational note; no action required.
+    Info,
+    /// Non-fatal issue that should be reviewed before restore.
+    Warning,
+    /// Problem that made a step fall back or give up.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A single diagnostic message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// Severity level.
+    pub severity: Severity,
+    /// Component that produced the message (for example `system_users`, `dhcp`).
+    pub source: String,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Ordered collection of diagnostics.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a diagnostic.
+    pub fn push(&mut self, severity: Severity, source: &str, message: impl Into<String>) {
+        self.entries.push(Diagnostic {
+            severity,
+            source: source.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// Record an informational note.
+    pub fn info(&mut self, source: &str, message: impl Into<String>) {
+        self.push(Severity::Info, source, message);
+    }
+
+    /// Record a warning.
+    pub fn warn(&mut self, source: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, source, message);
+    }
+
+    /// Record an error.
+    pub fn error(&mut self, source: &str, message: impl Into<String>) {
+        self.push(Severity::Error, source, message);
+    }
+
+    /// Append every diagnostic from `other`.
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Iterate diagnostics in the order they were recorded.
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.entries.iter()
+    }
+
+    /// Number of recorded diagnostics.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of diagnostics at `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.entries
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+
+    /// Consume the collector and return the recorded diagnostics.
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.entries
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostics, Severity};
+
+    #[test]
+    fn records_in_order_and_counts_by_severity() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.warn("system_users", "uid collision");
+        diagnostics.info("pipeline", "note");
+        diagnostics.warn("dhcp", "fallback");
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics.count(Severity::Warning), 2);
+        let rendered: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rendered,
+            vec!["warning: uid collision", "info: note", "warning: fallback"]
+        );
+    }
+}
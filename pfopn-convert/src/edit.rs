@@ -0,0 +1,171 @@
+//! Offline config edits addressed by path query.
+//!
+//! Each [`EditOp`] targets nodes with an [`xml_diff_core::PathQuery`] (the same
+//! syntax accepted by `inspect --section`) and mutates every match. Queries are
+//! evaluated relative to the config root, so `system.hostname` addresses
+//! `<pfsense><system><hostname>`.
+
+use thiserror::Error;
+use xml_diff_core::{parse, ParseError, PathQuery, XmlNode};
+
+/// A single mutation applied to a config tree.
+#[derive(Debug, Clone)]
+pub enum EditOp {
+    /// Replace the text of every matched node.
+    Set {
+        /// Nodes to update.
+        path: PathQuery,
+        /// New text value.
+        value: String,
+    },
+    /// Remove every matched node.
+    Delete {
+        /// Nodes to remove.
+        path: PathQuery,
+    },
+    /// Append a copy of `child` under every matched node.
+    Add {
+        /// Parent nodes to append to.
+        path: PathQuery,
+        /// Element to append.
+        child: XmlNode,
+    },
+}
+
+impl EditOp {
+    /// Build an [`EditOp::Add`] from an inline XML fragment with a single root.
+    pub fn add_xml(path: PathQuery, xml: &str) -> Result<Self, EditError> {
+        let child = parse(xml.as_bytes()).map_err(|source| EditError::InvalidXml {
+            xml: xml.to_string(),
+            source,
+        })?;
+        Ok(Self::Add { path, child })
+    }
+
+    fn path(&self) -> &PathQuery {
+        match self {
+            Self::Set { path, .. } | Self::Delete { path } | Self::Add { path, .. } => path,
+        }
+    }
+}
+
+/// Errors produced while applying edits.
+#[derive(Debug, Error)]
+pub enum EditError {
+    /// The query matched no nodes.
+    #[error("path not found: {0}")]
+    NotFound(String),
+    /// Inline XML for an add could not be parsed.
+    #[error("invalid inline XML '{xml}': {source}")]
+    InvalidXml {
+        /// Fragment as given.
+        xml: String,
+        /// Underlying parse failure.
+        source: ParseError,
+    },
+}
+
+/// Apply `op` to `root` and return how many nodes were changed.
+///
+/// An op that matches nothing is an error so typos in paths are not silently
+/// ignored.
+pub fn apply_edit(root: &mut XmlNode, op: &EditOp) -> Result<usize, EditError> {
+    let mut paths = op.path().locate(root);
+    if paths.is_empty() {
+        return Err(EditError::NotFound(op.path().to_string()));
+    }
+
+    match op {
+        EditOp::Set { value, .. } => {
+            for path in &paths {
+                if let Some(node) = root.descendant_mut(path) {
+                    node.text = Some(value.clone());
+                }
+            }
+        }
+        EditOp::Delete { .. } => {
+            // Remove deepest/last matches first so earlier indices stay valid.
+            paths.sort_unstable_by(|a, b| b.cmp(a));
+            for path in &paths {
+                let Some((idx, parent_path)) = path.split_last() else {
+                    continue;
+                };
+                if let Some(parent) = root.descendant_mut(parent_path) {
+                    parent.children.remove(*idx);
+                }
+            }
+        }
+        EditOp::Add { child, .. } => {
+            for path in &paths {
+                if let Some(node) = root.descendant_mut(path) {
+                    node.children.push(child.clone());
+                }
+            }
+        }
+    }
+    Ok(paths.len())
+}
+
+/// Apply `ops` in order, stopping at the first failure.
+pub fn apply_edits(root: &mut XmlNode, ops: &[EditOp]) -> Result<usize, EditError> {
+    ops.iter()
+        .try_fold(0, |total, op| Ok(total + apply_edit(root, op)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::{parse, PathQuery};
+
+    use super::{apply_edit, apply_edits, EditError, EditOp};
+
+    fn query(raw: &str) -> PathQuery {
+        PathQuery::parse(raw).expect("query")
+    }
+
+    #[test]
+    fn set_delete_and_add_by_path() {
+        let mut root = parse(
+            br#"<pfsense><system><hostname>fw</hostname></system><filter><rule><tracker>1</tracker></rule><rule><tracker>2</tracker></rule></filter></pfsense>"#,
+        )
+        .expect("parse");
+        let ops = vec![
+            EditOp::Set {
+                path: query("system.hostname"),
+                value: "edge".to_string(),
+            },
+            EditOp::Delete {
+                path: query("filter.rule[tracker='1']"),
+            },
+            EditOp::add_xml(query("system"), "<timezone>UTC</timezone>").expect("add"),
+        ];
+
+        assert_eq!(apply_edits(&mut root, &ops).expect("apply"), 3);
+        assert_eq!(root.get_text(&["system", "hostname"]), Some("edge"));
+        assert_eq!(root.get_text(&["system", "timezone"]), Some("UTC"));
+        let filter = root.get_child("filter").expect("filter");
+        assert_eq!(filter.children.len(), 1);
+        assert_eq!(filter.get_text(&["rule", "tracker"]), Some("2"));
+    }
+
+    #[test]
+    fn delete_handles_nested_and_sibling_matches() {
+        let mut root = parse(br#"<r><a><a/></a><a/><b/></r>"#).expect("parse");
+        let changed = apply_edit(&mut root, &EditOp::Delete { path: query("//a") }).expect("apply");
+        assert_eq!(changed, 3);
+        assert_eq!(root.children.len(), 1);
+        assert_eq!(root.children[0].tag, "b");
+    }
+
+    #[test]
+    fn unmatched_path_is_an_error() {
+        let mut root = parse(b"<r/>").expect("parse");
+        let err = apply_edit(
+            &mut root,
+            &EditOp::Delete {
+                path: query("missing"),
+            },
+        )
+        .expect_err("should fail");
+        assert!(matches!(err, EditError::NotFound(path) if path == "missing"));
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{bail, Context, Result};
+use pfopn_convert::canonical;
+use pfopn_convert::edit::{apply_edits, EditOp};
+use pfopn_convert::verify::{build_verify_report, render_verify_text};
+use xml_diff_core::{parse_file, write_file_with_options, PathQuery};
+
+use crate::cli::EditArgs;
+use crate::path_guard::ensure_output_not_same;
+
+/// Apply `edit` mutations and write the result.
+///
+/// Ops run in a fixed order regardless of flag order: all `--set`, then all
+/// `--add`, then all `--delete`.
+pub fn run_edit(args: EditArgs) -> Result<()> {
+    ensure_output_not_same(&args.output, &[&args.file])?;
+    let ops = collect_ops(&args)?;
+    if ops.is_empty() {
+        bail!("nothing to do: pass at least one --set, --add, or --delete");
+    }
+
+    let mut root = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let changed = apply_edits(&mut root, &ops)?;
+
+    write_file_with_options(&root, &args.output, &canonical::write_options(&root.tag))
+        .with_context(|| format!("failed to write output XML {}", args.output.display()))?;
+    println!(
+        "edited {} node(s) with {} op(s), wrote {}",
+        changed,
+        ops.len(),
+        args.output.display()
+    );
+
+    if args.verify {
+        let report = build_verify_report(&root, None);
+        println!("{}", render_verify_text(&report, false));
+        if report.errors > 0 {
+            bail!("verify failed: {} errors", report.errors);
+        }
+    }
+    Ok(())
+}
+
+fn collect_ops(args: &EditArgs) -> Result<Vec<EditOp>> {
+    let mut ops = Vec::new();
+    for pair in args.set.chunks(2) {
+        ops.push(EditOp::Set {
+            path: parse_query(&pair[0])?,
+            value: pair[1].clone(),
+        });
+    }
+    for pair in args.add.chunks(2) {
+        ops.push(EditOp::add_xml(parse_query(&pair[0])?, &pair[1])?);
+    }
+    for raw in &args.delete {
+        ops.push(EditOp::Delete {
+            path: parse_query(raw)?,
+        });
+    }
+    Ok(ops)
+}
+
+fn parse_query(raw: &str) -> Result<PathQuery> {
+    PathQuery::parse(raw).with_context(|| format!("invalid path query '{raw}'"))
+}
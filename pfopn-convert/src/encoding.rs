@@ -0,0 +1,275 @@
+//! Character encoding and entity normalization for conversion input.
+//!
+//! pfSense exports sometimes carry text OPNsense's importer rejects:
+//!
+//! - bytes that are not valid UTF-8, usually Latin-1 or Windows-1252 text
+//!   pasted into a description; [`parse_source_file`] reads them as
+//!   Windows-1252 and records every element that held one
+//! - double-encoded entities such as `R&amp;amp;D` in free-text fields
+//!   (`descr`, `description`, `detail`), left behind when the GUI escaped
+//!   text that was escaped again on save; [`normalize`] decodes them until
+//!   no entity is left
+//! - control characters XML 1.0 does not allow (anything below `0x20`
+//!   except tab, line feed, and carriage return); [`normalize`] removes them
+//!   from every text and attribute value
+//!
+//! Each repaired element is reported as an [`EncodingRepair`] so the
+//! `convert` summary can list what changed.
+
+use std::path::Path;
+
+use serde::Serialize;
+use xml_diff_core::{parse_file_with_options, ParseError, ParseOptions, XmlNode};
+
+/// Free-text fields that are checked for double-encoded entities.
+const FREE_TEXT_TAGS: &[&str] = &["descr", "description", "detail"];
+
+/// Entities pfSense escapes free text with.
+const ENTITIES: &[(&str, &str)] = &[
+    ("&amp;", "&"),
+    ("&lt;", "<"),
+    ("&gt;", ">"),
+    ("&quot;", "\""),
+    ("&apos;", "'"),
+    ("&#039;", "'"),
+    ("&#39;", "'"),
+];
+
+/// One element whose text was changed to make it valid for the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EncodingRepair {
+    /// Element path as `root.tag[n]` segments, like diff paths.
+    pub path: String,
+    /// What was wrong and how it was fixed.
+    pub message: String,
+}
+
+/// Parse a source config, reading bytes that are not valid UTF-8 as
+/// Windows-1252 and UTF-16 input as UTF-16.
+///
+/// Structural damage still fails; only the encoding is repaired.
+///
+/// # Errors
+///
+/// Returns error if the file cannot be read or is not well-formed XML.
+pub fn parse_source_file(path: &Path) -> Result<(XmlNode, Vec<EncodingRepair>), ParseError> {
+    let outcome = parse_file_with_options(
+        path,
+        &ParseOptions {
+            repair_encoding: true,
+            ..ParseOptions::default()
+        },
+    )?;
+    let mut repairs: Vec<EncodingRepair> = outcome
+        .warnings
+        .iter()
+        .filter_map(|warning| {
+            Some(EncodingRepair {
+                path: warning.path.clone()?,
+                message: warning.message.clone(),
+            })
+        })
+        .collect();
+    // UTF-16 input, or bytes outside any element (comments)
+    if repairs.is_empty() {
+        repairs = outcome
+            .warnings
+            .into_iter()
+            .map(|warning| EncodingRepair {
+                path: outcome.root.tag.clone(),
+                message: warning.message,
+            })
+            .collect();
+    }
+    Ok((outcome.root, repairs))
+}
+
+/// Whether [`normalize`] would change anything in `root`.
+pub fn needs_normalizing(root: &XmlNode) -> bool {
+    let text_needs = root.text.as_deref().is_some_and(|text| {
+        has_control_chars(text) || (is_free_text(&root.tag) && has_entities(text))
+    });
+    text_needs
+        || root.attributes.values().any(|v| has_control_chars(v))
+        || root.children.iter().any(needs_normalizing)
+}
+
+/// Decode double-encoded entities in free-text fields and drop control
+/// characters from all text, returning one repair per changed element.
+pub fn normalize(root: &mut XmlNode) -> Vec<EncodingRepair> {
+    let mut repairs = Vec::new();
+    let path = root.tag.clone();
+    normalize_node(root, &path, &mut repairs);
+    repairs
+}
+
+fn normalize_node(node: &mut XmlNode, path: &str, repairs: &mut Vec<EncodingRepair>) {
+    let mut removed = 0;
+    for value in node.attributes.values_mut() {
+        removed += strip_control_chars(value);
+    }
+    let mut decoded = None;
+    if let Some(text) = &mut node.text {
+        removed += strip_control_chars(text);
+        if is_free_text(&node.tag) && has_entities(text) {
+            decoded = Some(std::mem::replace(text, decode_entities(text)));
+        }
+    }
+    if removed > 0 {
+        repairs.push(EncodingRepair {
+            path: path.to_string(),
+            message: format!("removed {removed} control character(s) XML does not allow"),
+        });
+    }
+    if let Some(original) = decoded {
+        repairs.push(EncodingRepair {
+            path: path.to_string(),
+            message: format!("decoded double-encoded entities in '{original}'"),
+        });
+    }
+
+    let mut seen: Vec<(String, usize)> = Vec::new();
+    for child in &mut node.children {
+        let nth = match seen.iter_mut().find(|(tag, _)| *tag == child.tag) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                seen.push((child.tag.clone(), 1));
+                1
+            }
+        };
+        let child_path = format!("{path}.{}[{nth}]", child.tag);
+        normalize_node(child, &child_path, repairs);
+    }
+}
+
+fn is_free_text(tag: &str) -> bool {
+    FREE_TEXT_TAGS.contains(&tag)
+}
+
+fn is_control_char(c: char) -> bool {
+    c < '\u{20}' && !matches!(c, '\t' | '\n' | '\r')
+}
+
+fn has_control_chars(text: &str) -> bool {
+    text.chars().any(is_control_char)
+}
+
+/// Remove control characters from `text`, returning how many were removed.
+fn strip_control_chars(text: &mut String) -> usize {
+    let before = text.chars().count();
+    text.retain(|c| !is_control_char(c));
+    before - text.chars().count()
+}
+
+fn has_entities(text: &str) -> bool {
+    ENTITIES.iter().any(|(entity, _)| text.contains(entity))
+}
+
+/// Decode entities until none are left, so `&amp;amp;` becomes `&`.
+fn decode_entities(text: &str) -> String {
+    let mut out = text.to_string();
+    while has_entities(&out) {
+        let mut next = String::with_capacity(out.len());
+        let mut rest = out.as_str();
+        while let Some(amp) = rest.find('&') {
+            next.push_str(&rest[..amp]);
+            rest = &rest[amp..];
+            match ENTITIES.iter().find(|(entity, _)| rest.starts_with(entity)) {
+                Some((entity, value)) => {
+                    next.push_str(value);
+                    rest = &rest[entity.len()..];
+                }
+                None => {
+                    next.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        next.push_str(rest);
+        out = next;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+    use xml_diff_core::parse;
+
+    use super::{needs_normalizing, normalize, parse_source_file};
+
+    #[test]
+    fn decodes_double_encoded_free_text_only() {
+        let mut root = parse(
+            br#"<pfsense><filter><rule><descr>R&amp;amp;D &amp;lt;lab&amp;gt;</descr></rule><rule><descr>Q&amp;amp;amp;A</descr><password>a&amp;amp;b</password></rule></filter></pfsense>"#,
+        )
+        .expect("parse");
+        assert!(needs_normalizing(&root));
+
+        let repairs = normalize(&mut root);
+        let rules = root
+            .get_child("filter")
+            .expect("filter")
+            .get_children("rule");
+        assert_eq!(rules[0].get_text(&["descr"]), Some("R&D <lab>"));
+        assert_eq!(rules[1].get_text(&["descr"]), Some("Q&A"));
+        assert_eq!(rules[1].get_text(&["password"]), Some("a&amp;b"));
+        let paths: Vec<&str> = repairs.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "pfsense.filter[1].rule[1].descr[1]",
+                "pfsense.filter[1].rule[2].descr[1]"
+            ]
+        );
+        assert_eq!(
+            repairs[0].message,
+            "decoded double-encoded entities in 'R&amp;D &lt;lab&gt;'"
+        );
+        assert!(!needs_normalizing(&root));
+    }
+
+    #[test]
+    fn strips_control_characters_from_text_and_attributes() {
+        let mut root = parse(b"<pfsense><system><hostname>fw\x08</hostname><motd x=\"a\x01\">line\n\tok</motd></system></pfsense>")
+            .expect("parse");
+
+        let repairs = normalize(&mut root);
+        assert_eq!(root.get_text(&["system", "hostname"]), Some("fw"));
+        assert_eq!(root.get_text(&["system", "motd"]), Some("line\n\tok"));
+        assert_eq!(repairs.len(), 2);
+        assert_eq!(repairs[1].path, "pfsense.system[1].motd[1]");
+        assert_eq!(
+            repairs[1].message,
+            "removed 1 control character(s) XML does not allow"
+        );
+    }
+
+    #[test]
+    fn source_parse_lists_latin1_elements() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("config.xml");
+        fs::write(
+            &path,
+            b"<pfsense><interfaces><lan><descr>B\xfcro</descr></lan></interfaces></pfsense>",
+        )
+        .expect("write");
+
+        let (root, repairs) = parse_source_file(&path).expect("parse");
+        assert_eq!(
+            root.get_text(&["interfaces", "lan", "descr"]),
+            Some("B\u{fc}ro")
+        );
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].path, "pfsense.interfaces[1].lan[1].descr[1]");
+
+        fs::write(&path, b"<pfsense><!-- \xa9 --><a>b</a></pfsense>").expect("write");
+        let (_, repairs) = parse_source_file(&path).expect("parse");
+        assert_eq!(repairs[0].path, "pfsense");
+    }
+}
@@ -0,0 +1,225 @@
+//! Lossless JSON and YAML form of a whole config (`export` / `import`).
+//!
+//! The document mirrors the XML tree node for node, so tools that do not
+//! speak XML can keep a config under version control and edit it, and
+//! [`import_tree`] rebuilds the exact tree [`export_tree`] was given:
+//! element order, attributes, empty versus absent text, CDATA sections,
+//! comments, and processing instructions all survive.
+//!
+//! ```yaml
+//! pfopn_tree: 1
+//! root:
+//!   tag: pfsense
+//!   children:
+//!   - tag: version
+//!     text: '23.3'
+//! ```
+//!
+//! Empty fields are omitted; an element with no text and no children is
+//! just its `tag`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use xml_diff_core::{MiscKind, XmlMisc, XmlNode};
+
+/// Document layout version written to `pfopn_tree`.
+pub const TREE_VERSION: u32 = 1;
+
+/// Serialization used for the exported document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeFormat {
+    Json,
+    Yaml,
+}
+
+impl TreeFormat {
+    /// Guess the format from a file extension (`.json`, `.yaml`, `.yml`).
+    pub fn from_extension(path: &std::path::Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(TreeFormat::Json),
+            "yaml" | "yml" => Some(TreeFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Errors produced while exporting or importing a tree document.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("invalid JSON tree document: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid YAML tree document: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("unsupported tree document version {found}; this build reads version {TREE_VERSION}")]
+    Version { found: u32 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Document {
+    pfopn_tree: u32,
+    root: Node,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Node {
+    tag: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    cdata: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Node>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    misc: Vec<Misc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    prolog: Vec<Misc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Misc {
+    kind: Kind,
+    content: String,
+    position: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    Comment,
+    ProcessingInstruction,
+}
+
+/// Serialize `root` as a tree document.
+pub fn export_tree(root: &XmlNode, format: TreeFormat) -> Result<String, ExportError> {
+    let doc = Document {
+        pfopn_tree: TREE_VERSION,
+        root: Node::from(root),
+    };
+    Ok(match format {
+        TreeFormat::Json => serde_json::to_string_pretty(&doc)?,
+        TreeFormat::Yaml => serde_yaml::to_string(&doc)?,
+    })
+}
+
+/// Rebuild the XML tree from a tree document.
+pub fn import_tree(input: &str, format: TreeFormat) -> Result<XmlNode, ExportError> {
+    let doc: Document = match format {
+        TreeFormat::Json => serde_json::from_str(input)?,
+        TreeFormat::Yaml => serde_yaml::from_str(input)?,
+    };
+    if doc.pfopn_tree != TREE_VERSION {
+        return Err(ExportError::Version {
+            found: doc.pfopn_tree,
+        });
+    }
+    Ok(doc.root.into())
+}
+
+impl From<&XmlNode> for Node {
+    fn from(node: &XmlNode) -> Self {
+        Self {
+            tag: node.tag.clone(),
+            attributes: node.attributes.clone(),
+            text: node.text.clone(),
+            cdata: node.cdata,
+            children: node.children.iter().map(Node::from).collect(),
+            misc: node.misc.iter().map(Misc::from).collect(),
+            prolog: node.prolog.iter().map(Misc::from).collect(),
+        }
+    }
+}
+
+impl From<Node> for XmlNode {
+    fn from(node: Node) -> Self {
+        let mut out = XmlNode::new(node.tag);
+        out.attributes = node.attributes;
+        out.text = node.text;
+        out.cdata = node.cdata;
+        out.children = node.children.into_iter().map(XmlNode::from).collect();
+        out.misc = node.misc.into_iter().map(XmlMisc::from).collect();
+        out.prolog = node.prolog.into_iter().map(XmlMisc::from).collect();
+        out
+    }
+}
+
+impl From<&XmlMisc> for Misc {
+    fn from(misc: &XmlMisc) -> Self {
+        Self {
+            kind: match misc.kind {
+                MiscKind::Comment => Kind::Comment,
+                MiscKind::ProcessingInstruction => Kind::ProcessingInstruction,
+            },
+            content: misc.content.clone(),
+            position: misc.position,
+        }
+    }
+}
+
+impl From<Misc> for XmlMisc {
+    fn from(misc: Misc) -> Self {
+        match misc.kind {
+            Kind::Comment => XmlMisc::comment(misc.content, misc.position),
+            Kind::ProcessingInstruction => {
+                XmlMisc::processing_instruction(misc.content, misc.position)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{export_tree, import_tree, ExportError, TreeFormat};
+
+    const CONFIG: &[u8] = br#"<?xml version="1.0"?>
+<!-- exported -->
+<pfsense>
+  <version>23.3</version>
+  <system><hostname>fw</hostname><domain></domain><!-- keep --></system>
+  <filter><rule uuid="r1"><descr><![CDATA[Allow <web>]]></descr><disabled/></rule></filter>
+</pfsense>"#;
+
+    #[test]
+    fn json_and_yaml_round_trip_the_tree() {
+        let root = parse(CONFIG).expect("parse");
+        for format in [TreeFormat::Json, TreeFormat::Yaml] {
+            let doc = export_tree(&root, format).expect("export");
+            let back = import_tree(&doc, format).expect("import");
+            assert_eq!(back, root, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn omits_empty_fields() {
+        let root =
+            parse(b"<pfsense><system><hostname>fw</hostname></system></pfsense>").expect("parse");
+        let doc = export_tree(&root, TreeFormat::Yaml).expect("export");
+        assert!(doc.starts_with("pfopn_tree: 1\n"), "{doc}");
+        assert!(!doc.contains("attributes"), "{doc}");
+        assert!(doc.contains("text: fw"), "{doc}");
+    }
+
+    #[test]
+    fn rejects_unknown_version_and_fields() {
+        let err = import_tree(
+            r#"{"pfopn_tree": 9, "root": {"tag": "pfsense"}}"#,
+            TreeFormat::Json,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ExportError::Version { found: 9 }));
+        let err = import_tree(
+            r#"{"pfopn_tree": 1, "root": {"tag": "pfsense", "txt": "x"}}"#,
+            TreeFormat::Json,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ExportError::Json(_)));
+    }
+}
@@ -0,0 +1,490 @@
+//! Extract one feature slice of a config.
+//!
+//! Produces a minimal config holding a chosen feature (OpenVPN, IPsec,
+//! filter rules, aliases, gateways, or DHCP) and only the objects it depends
+//! on, for sharing reproducible bug reports or staging a partial import.
+//!
+//! Dependencies come from the [reference graph](crate::graph): everything
+//! reachable from the feature's objects is kept. For OpenVPN that is the
+//! interfaces the instances bind to (and their VLAN/LAGG/bridge devices),
+//! their CAs and certificates with the issuing chain, and the client
+//! certificates the instance CAs issued along with the users holding them.
+//!
+//! The feature's sections are copied whole; every other section keeps just
+//! the referenced entries, and sections with none are left out. `version`
+//! and the system hostname, domain, and timezone are always kept so the
+//! result still loads, as is an empty `<filter>` when rules are not
+//! extracted.
+//!
+//! References the source does not define are reported as missing rather
+//! than failing the extract, since broken configs are what bug reports are
+//! about.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::graph::{build_graph, DependencyGraph};
+
+/// System settings kept in every extract so the result loads.
+const SYSTEM_BASICS: &[&str] = &["hostname", "domain", "timezone"];
+
+/// A feature that can be extracted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Feature {
+    OpenVpn,
+    Ipsec,
+    Rules,
+    Aliases,
+    Gateways,
+    Dhcp,
+}
+
+impl Feature {
+    pub const ALL: &'static [Feature] = &[
+        Feature::OpenVpn,
+        Feature::Ipsec,
+        Feature::Rules,
+        Feature::Aliases,
+        Feature::Gateways,
+        Feature::Dhcp,
+    ];
+
+    /// Name used on the command line and in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::OpenVpn => "openvpn",
+            Feature::Ipsec => "ipsec",
+            Feature::Rules => "rules",
+            Feature::Aliases => "aliases",
+            Feature::Gateways => "gateways",
+            Feature::Dhcp => "dhcp",
+        }
+    }
+
+    /// Graph node kind whose nodes seed the dependency walk.
+    fn seed_kind(self) -> &'static str {
+        match self {
+            Feature::OpenVpn => "openvpn",
+            Feature::Ipsec => "ipsec",
+            Feature::Rules => "rule",
+            Feature::Aliases => "alias",
+            Feature::Gateways => "gateway",
+            Feature::Dhcp => "dhcp",
+        }
+    }
+
+    /// Top-level sections copied whole.
+    fn sections(self) -> &'static [&'static str] {
+        match self {
+            Feature::OpenVpn => &["openvpn"],
+            Feature::Ipsec => &["ipsec"],
+            Feature::Rules => &["filter"],
+            Feature::Aliases => &["aliases"],
+            Feature::Gateways => &["gateways"],
+            Feature::Dhcp => &["dhcpd", "dhcpdv6"],
+        }
+    }
+
+    /// Children of `<OPNsense>` copied whole.
+    fn mvc_sections(self) -> &'static [&'static str] {
+        match self {
+            Feature::OpenVpn => &["OpenVPN"],
+            Feature::Ipsec => &["IPsec", "Swanctl"],
+            Feature::Gateways => &["Gateways"],
+            Feature::Dhcp => &["Kea"],
+            Feature::Rules | Feature::Aliases => &[],
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Feature::ALL
+            .iter()
+            .copied()
+            .find(|feature| feature.name().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Feature::ALL.iter().map(|f| f.name()).collect();
+                format!(
+                    "unknown feature '{value}'; expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Extracted tree plus what was kept.
+#[derive(Debug, Clone)]
+pub struct ExtractOutcome {
+    pub tree: XmlNode,
+    pub report: ExtractReport,
+}
+
+/// Summary of an extract.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExtractReport {
+    pub features: Vec<Feature>,
+    /// Kept dependency count per object kind (`interface`, `cert`, ...).
+    pub kept: BTreeMap<String, usize>,
+    /// Ids (`kind:name`) of referenced objects the source does not define.
+    pub missing: Vec<String>,
+}
+
+/// Extract `features` and their dependencies from `root`.
+pub fn extract_features(root: &XmlNode, features: &[Feature]) -> ExtractOutcome {
+    let graph = build_graph(root);
+    let needed = dependency_closure(&graph, features);
+    let mut kept: BTreeMap<String, usize> = BTreeMap::new();
+    let mut missing = Vec::new();
+    for node in graph.nodes.iter().filter(|n| needed.contains(&n.id)) {
+        if node.missing {
+            missing.push(node.id.clone());
+        } else if !features.iter().any(|f| f.seed_kind() == node.kind) {
+            *kept.entry(node.kind.clone()).or_default() += 1;
+        }
+    }
+
+    let slicer = Slicer { features, needed };
+    let mut tree = XmlNode::new(&root.tag);
+    tree.attributes = root.attributes.clone();
+    tree.prolog = root.prolog.clone();
+    tree.children = root
+        .children
+        .iter()
+        .filter_map(|child| slicer.slice(child))
+        .collect();
+
+    let mut features = features.to_vec();
+    features.sort();
+    features.dedup();
+    ExtractOutcome {
+        tree,
+        report: ExtractReport {
+            features,
+            kept,
+            missing,
+        },
+    }
+}
+
+/// Render the extract report for the terminal.
+pub fn render_extract_text(report: &ExtractReport) -> String {
+    let features: Vec<&str> = report.features.iter().map(|f| f.name()).collect();
+    let kept: Vec<String> = report
+        .kept
+        .iter()
+        .map(|(kind, count)| format!("{kind}={count}"))
+        .collect();
+    let mut lines = vec![
+        format!("features: {}", features.join(", ")),
+        format!(
+            "dependencies: {}",
+            if kept.is_empty() {
+                "none".to_string()
+            } else {
+                kept.join(" ")
+            }
+        ),
+        format!("missing: {}", report.missing.len()),
+    ];
+    lines.extend(report.missing.iter().map(|id| format!("- {id}")));
+    lines.join("\n")
+}
+
+/// Ids of every graph node reachable from the features' objects.
+///
+/// VPN instances also pull in their clients: certificates issued by a CA an
+/// instance names directly, and the users holding those certificates. Those
+/// point at the instance's CA rather than the other way round, so the walk
+/// would not reach them.
+fn dependency_closure(graph: &DependencyGraph, features: &[Feature]) -> BTreeSet<String> {
+    let mut outgoing: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for edge in &graph.edges {
+        outgoing.entry(&edge.from).or_default().push(&edge.to);
+    }
+    let seeds: Vec<&str> = graph
+        .nodes
+        .iter()
+        .filter(|n| features.iter().any(|f| f.seed_kind() == n.kind))
+        .map(|n| n.id.as_str())
+        .collect();
+
+    let vpn_cas: BTreeSet<&str> = graph
+        .edges
+        .iter()
+        .filter(|e| e.relation == "ca" && seeds.contains(&e.from.as_str()))
+        .map(|e| e.to.as_str())
+        .collect();
+    let client_certs: BTreeSet<&str> = graph
+        .edges
+        .iter()
+        .filter(|e| e.relation == "issuer" && vpn_cas.contains(e.to.as_str()))
+        .filter(|e| e.from.starts_with("cert:"))
+        .map(|e| e.from.as_str())
+        .collect();
+    let client_users = graph
+        .edges
+        .iter()
+        .filter(|e| e.from.starts_with("user:") && client_certs.contains(e.to.as_str()))
+        .map(|e| e.from.as_str());
+
+    let mut queue: VecDeque<&str> = seeds.iter().copied().collect();
+    queue.extend(client_certs.iter().copied());
+    queue.extend(client_users);
+    let mut needed = BTreeSet::new();
+    while let Some(id) = queue.pop_front() {
+        if !needed.insert(id.to_string()) {
+            continue;
+        }
+        queue.extend(outgoing.get(id).into_iter().flatten().copied());
+    }
+    needed
+}
+
+struct Slicer<'a> {
+    features: &'a [Feature],
+    needed: BTreeSet<String>,
+}
+
+impl Slicer<'_> {
+    fn is_needed(&self, kind: &str, name: Option<&str>) -> bool {
+        name.is_some_and(|name| self.needed.contains(&format!("{kind}:{}", name.trim())))
+    }
+
+    /// The part of one top-level section the extract keeps.
+    fn slice(&self, node: &XmlNode) -> Option<XmlNode> {
+        if self
+            .features
+            .iter()
+            .any(|f| f.sections().contains(&node.tag.as_str()))
+        {
+            return Some(node.clone());
+        }
+        match node.tag.as_str() {
+            "version" => Some(node.clone()),
+            "filter" => Some(XmlNode::new(&node.tag)),
+            "system" => Some(self.slice_system(node)),
+            "interfaces" => {
+                self.keep_children(node, |child| self.is_needed("interface", Some(&child.tag)))
+            }
+            "vlans" => self.keep_children(node, |c| self.is_needed("device", text(c, "vlanif"))),
+            "laggs" => self.keep_children(node, |c| self.is_needed("device", text(c, "laggif"))),
+            "bridges" => {
+                self.keep_children(node, |c| self.is_needed("device", text(c, "bridgeif")))
+            }
+            "gateways" => self.keep_children(node, |c| self.is_needed("gateway", text(c, "name"))),
+            "aliases" => self.keep_children(node, |c| self.is_needed("alias", text(c, "name"))),
+            "schedules" => {
+                self.keep_children(node, |c| self.is_needed("schedule", text(c, "name")))
+            }
+            "ca" => self
+                .is_needed("ca", text(node, "refid"))
+                .then(|| node.clone()),
+            "cert" => self
+                .is_needed("cert", text(node, "refid"))
+                .then(|| node.clone()),
+            "dhcpd" | "dhcpdv6" => self.keep_children(node, |child| {
+                self.needed
+                    .contains(&format!("dhcp:{}/{}", node.tag, child.tag))
+            }),
+            "OPNsense" => self.slice_mvc(node),
+            _ => None,
+        }
+    }
+
+    /// Basics plus the users the feature needs.
+    fn slice_system(&self, system: &XmlNode) -> XmlNode {
+        let mut out = XmlNode::new(&system.tag);
+        out.children = system
+            .children
+            .iter()
+            .filter(|c| {
+                SYSTEM_BASICS.contains(&c.tag.as_str())
+                    || (c.tag == "user" && self.is_needed("user", text(c, "name")))
+            })
+            .cloned()
+            .collect();
+        out
+    }
+
+    /// Feature model sections whole, plus referenced firewall aliases and
+    /// gateways from the MVC models.
+    fn slice_mvc(&self, opnsense: &XmlNode) -> Option<XmlNode> {
+        let mut out = opnsense.clone();
+        out.children = opnsense
+            .children
+            .iter()
+            .filter_map(|child| {
+                if self
+                    .features
+                    .iter()
+                    .any(|f| f.mvc_sections().contains(&child.tag.as_str()))
+                {
+                    return Some(child.clone());
+                }
+                match child.tag.as_str() {
+                    "Gateways" => {
+                        self.keep_children(child, |c| self.is_needed("gateway", text(c, "name")))
+                    }
+                    "Firewall" => self.slice_mvc_aliases(child),
+                    _ => None,
+                }
+            })
+            .collect();
+        (!out.children.is_empty()).then_some(out)
+    }
+
+    /// `<Firewall><Alias><aliases>` with only the referenced aliases.
+    fn slice_mvc_aliases(&self, firewall: &XmlNode) -> Option<XmlNode> {
+        let alias = firewall.get_child("Alias")?;
+        let whole = self.features.contains(&Feature::Aliases);
+        let mut alias_out = alias.clone();
+        alias_out.children = alias
+            .children
+            .iter()
+            .filter(|c| c.tag == "aliases")
+            .filter_map(|aliases| {
+                if whole {
+                    Some(aliases.clone())
+                } else {
+                    self.keep_children(aliases, |c| self.is_needed("alias", text(c, "name")))
+                }
+            })
+            .collect();
+        if alias_out.children.is_empty() {
+            return None;
+        }
+        let mut out = firewall.clone();
+        out.children = vec![alias_out];
+        Some(out)
+    }
+
+    /// Copy of `node` with only the children `keep` accepts, or `None` when
+    /// it accepts none.
+    fn keep_children(&self, node: &XmlNode, keep: impl Fn(&XmlNode) -> bool) -> Option<XmlNode> {
+        let mut out = node.clone();
+        out.children.retain(|child| keep(child));
+        (!out.children.is_empty()).then_some(out)
+    }
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{extract_features, Feature};
+
+    #[test]
+    fn extracts_openvpn_with_its_certs_users_and_interfaces() {
+        let root = parse(
+            br#"<pfsense><version>23.3</version>
+                <system><hostname>fw</hostname><domain>lab</domain><dnsserver>1.1.1.1</dnsserver><user><name>alice</name><cert>c1</cert></user><user><name>bob</name></user></system>
+                <interfaces><wan><if>em0</if></wan><lan><if>em1</if></lan><opt1><if>em1.20</if></opt1></interfaces>
+                <vlans><vlan><if>em1</if><tag>20</tag><vlanif>em1.20</vlanif></vlan></vlans>
+                <filter><rule><interface>lan</interface></rule></filter>
+                <openvpn><openvpn-server><vpnid>1</vpnid><interface>opt1</interface><caref>ca1</caref><certref>srv</certref></openvpn-server></openvpn>
+                <ca><refid>root</refid></ca><ca><refid>ca1</refid><caref>root</caref></ca><ca><refid>other</refid></ca>
+                <cert><refid>srv</refid><caref>ca1</caref></cert><cert><refid>c1</refid><caref>ca1</caref></cert><cert><refid>web</refid></cert>
+            </pfsense>"#,
+        )
+        .expect("parse");
+
+        let outcome = extract_features(&root, &[Feature::OpenVpn]);
+        let tree = &outcome.tree;
+        let tags: Vec<&str> = tree.children.iter().map(|c| c.tag.as_str()).collect();
+        assert_eq!(
+            tags,
+            [
+                "version",
+                "system",
+                "interfaces",
+                "vlans",
+                "filter",
+                "openvpn",
+                "ca",
+                "ca",
+                "cert",
+                "cert"
+            ]
+        );
+        let system: Vec<&str> = tree
+            .get_child("system")
+            .expect("system")
+            .children
+            .iter()
+            .map(|c| c.tag.as_str())
+            .collect();
+        assert_eq!(system, ["hostname", "domain", "user"]);
+        assert_eq!(tree.get_text(&["system", "user", "name"]), Some("alice"));
+        let interfaces: Vec<&str> = tree
+            .get_child("interfaces")
+            .expect("interfaces")
+            .children
+            .iter()
+            .map(|c| c.tag.as_str())
+            .collect();
+        assert_eq!(interfaces, ["opt1"]);
+        assert_eq!(outcome.report.kept.get("ca"), Some(&2));
+        assert_eq!(outcome.report.kept.get("cert"), Some(&2));
+        assert_eq!(outcome.report.kept.get("device"), Some(&2));
+        assert!(outcome.report.missing.is_empty());
+    }
+
+    #[test]
+    fn keeps_rule_users_of_certs_and_reports_missing_references() {
+        let root = parse(
+            br#"<pfsense><system><user><name>alice</name><cert>c1</cert></user></system>
+                <interfaces><lan><if>em1</if></lan><opt2><if>em2</if></opt2></interfaces>
+                <aliases><alias><name>servers</name><address>10.0.0.1 nested</address></alias><alias><name>nested</name><address>10.0.0.2</address></alias><alias><name>unused</name></alias></aliases>
+                <filter><rule><interface>lan</interface><source><address>servers</address></source><gateway>GONE</gateway></rule></filter>
+            </pfsense>"#,
+        )
+        .expect("parse");
+
+        let outcome = extract_features(&root, &[Feature::Rules]);
+        let aliases: Vec<&str> = outcome
+            .tree
+            .get_child("aliases")
+            .expect("aliases")
+            .get_children("alias")
+            .into_iter()
+            .filter_map(|a| a.get_text(&["name"]))
+            .collect();
+        assert_eq!(aliases, ["servers", "nested"]);
+        assert!(outcome
+            .tree
+            .get_child("system")
+            .expect("system")
+            .children
+            .is_empty());
+        assert_eq!(outcome.report.missing, ["gateway:GONE"]);
+    }
+
+    #[test]
+    fn parses_feature_names() {
+        assert_eq!("OpenVPN".parse(), Ok(Feature::OpenVpn));
+        assert!("wireguard"
+            .parse::<Feature>()
+            .expect_err("unknown")
+            .contains("openvpn, ipsec, rules, aliases, gateways, dhcp"));
+    }
+}
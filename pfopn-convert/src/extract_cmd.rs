@@ -0,0 +1,32 @@
+use anyhow::{Context, Result};
+use pfopn_convert::canonical;
+use pfopn_convert::extract::{extract_features, render_extract_text};
+use xml_diff_core::{parse_file, write_file_with_options};
+
+use crate::cli::{ExtractArgs, OutputFormat};
+use crate::path_guard::ensure_output_not_same;
+
+/// Extract one or more features and their dependencies into a new config.
+pub fn run_extract(args: ExtractArgs) -> Result<()> {
+    ensure_output_not_same(&args.output, &[&args.file])?;
+    let root = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+
+    let outcome = extract_features(&root, &args.features);
+    write_file_with_options(
+        &outcome.tree,
+        &args.output,
+        &canonical::write_options(&outcome.tree.tag),
+    )
+    .with_context(|| format!("failed to write output XML {}", args.output.display()))?;
+
+    match args.format {
+        OutputFormat::Text => println!(
+            "{}\nwrote {}",
+            render_extract_text(&outcome.report),
+            args.output.display()
+        ),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&outcome.report)?),
+    }
+    Ok(())
+}
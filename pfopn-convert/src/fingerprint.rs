@@ -0,0 +1,284 @@
+//! Anonymized per-section config fingerprints for drift detection.
+//!
+//! A [`ConfigFingerprint`] holds one SHA-256 hash per top-level section, so a
+//! running firewall's config can be compared against the last converted or
+//! approved config without moving either file: only the hashes are shared.
+//!
+//! Sections are normalized before hashing:
+//! - text is trimmed and whitespace-only text is ignored
+//! - attributes are hashed in sorted order
+//! - bookkeeping that changes without a config change is skipped: the
+//!   top-level `revision`/`lastchange` sections and `created`/`updated`
+//!   stamps anywhere below a section
+//! - repeated top-level tags (e.g. `cert`, `ca`) hash together as one section
+//!
+//! Child order is kept, since rule order is meaningful.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use xml_diff_core::XmlNode;
+
+use crate::detect::{detect_config, ConfigFlavor};
+
+/// Version of the normalization and hash scheme; bumped when either changes.
+pub const FINGERPRINT_FORMAT: u32 = 1;
+
+/// Top-level sections skipped entirely.
+const VOLATILE_SECTIONS: &[&str] = &["revision", "lastchange"];
+
+/// Nested bookkeeping elements skipped inside sections.
+const VOLATILE_ELEMENTS: &[&str] = &["created", "updated"];
+
+/// Hashes of one config, keyed by top-level section tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigFingerprint {
+    /// [`FINGERPRINT_FORMAT`] the hashes were computed with.
+    pub format: u32,
+    /// `pfsense`, `opnsense`, or `unknown`.
+    pub platform: String,
+    /// Hash over every section hash; equal configs have equal values.
+    pub config: String,
+    /// Section tag -> hex SHA-256 of the normalized section.
+    pub sections: BTreeMap<String, String>,
+}
+
+/// Sections that differ between two fingerprints.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FingerprintDiff {
+    /// Present on both sides with different hashes.
+    pub changed: Vec<String>,
+    /// Only in the right-hand fingerprint.
+    pub added: Vec<String>,
+    /// Only in the left-hand fingerprint.
+    pub removed: Vec<String>,
+    /// Number of sections with equal hashes.
+    pub unchanged: usize,
+}
+
+impl FingerprintDiff {
+    /// True when no section changed, appeared, or disappeared.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compute the fingerprint of a parsed config.
+pub fn fingerprint(root: &XmlNode) -> ConfigFingerprint {
+    let mut grouped: BTreeMap<&str, Vec<&XmlNode>> = BTreeMap::new();
+    for child in &root.children {
+        if VOLATILE_SECTIONS.contains(&child.tag.as_str()) {
+            continue;
+        }
+        grouped.entry(child.tag.as_str()).or_default().push(child);
+    }
+
+    let sections: BTreeMap<String, String> = grouped
+        .into_iter()
+        .map(|(tag, nodes)| {
+            let mut buf = Vec::new();
+            for node in nodes {
+                encode(node, &mut buf);
+            }
+            (tag.to_string(), hex(&sha256(&buf)))
+        })
+        .collect();
+
+    let mut all = format!("format={FINGERPRINT_FORMAT}\n").into_bytes();
+    for (tag, hash) in &sections {
+        all.extend_from_slice(format!("{tag}={hash}\n").as_bytes());
+    }
+
+    let platform = match detect_config(root) {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => "unknown",
+    };
+    ConfigFingerprint {
+        format: FINGERPRINT_FORMAT,
+        platform: platform.to_string(),
+        config: hex(&sha256(&all)),
+        sections,
+    }
+}
+
+/// Report which sections differ from `left` to `right`.
+pub fn compare(left: &ConfigFingerprint, right: &ConfigFingerprint) -> FingerprintDiff {
+    let mut diff = FingerprintDiff::default();
+    for (tag, hash) in &left.sections {
+        match right.sections.get(tag) {
+            Some(other) if other == hash => diff.unchanged += 1,
+            Some(_) => diff.changed.push(tag.clone()),
+            None => diff.removed.push(tag.clone()),
+        }
+    }
+    diff.added = right
+        .sections
+        .keys()
+        .filter(|tag| !left.sections.contains_key(*tag))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// Append an unambiguous, length-prefixed encoding of `node` to `buf`.
+fn encode(node: &XmlNode, buf: &mut Vec<u8>) {
+    push_field(buf, b'<', &node.tag);
+    for (name, value) in &node.attributes {
+        push_field(buf, b'@', name);
+        push_field(buf, b'=', value);
+    }
+    if let Some(text) = node
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        push_field(buf, b'"', text);
+    }
+    for child in &node.children {
+        if !VOLATILE_ELEMENTS.contains(&child.tag.as_str()) {
+            encode(child, buf);
+        }
+    }
+    buf.push(b'>');
+}
+
+fn push_field(buf: &mut Vec<u8>, marker: u8, value: &str) {
+    buf.push(marker);
+    buf.extend_from_slice(&(value.len() as u64).to_be_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 (FIPS 180-4); inputs here are small, so the whole message is padded in memory.
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in msg.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (slot, value) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *slot = slot.wrapping_add(value);
+        }
+    }
+
+    let mut out = [0u8; 32];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{compare, fingerprint, hex, sha256};
+
+    #[test]
+    fn sha256_matches_known_vectors() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn ignores_whitespace_and_bookkeeping() {
+        let a = parse(
+            br#"<pfsense><revision><time>1</time></revision><filter><rule><descr>x</descr><updated><time>1</time></updated></rule></filter></pfsense>"#,
+        )
+        .expect("parse a");
+        let b = parse(
+            br#"<pfsense><revision><time>2</time></revision><filter>
+  <rule><descr> x </descr><updated><time>9</time></updated></rule>
+</filter></pfsense>"#,
+        )
+        .expect("parse b");
+
+        let fa = fingerprint(&a);
+        assert_eq!(fa, fingerprint(&b));
+        assert!(!fa.sections.contains_key("revision"));
+        assert_eq!(fa.platform, "pfsense");
+    }
+
+    #[test]
+    fn reports_changed_added_and_removed_sections() {
+        let a =
+            parse(br#"<opnsense><system><hostname>fw</hostname></system><nat/><wol/></opnsense>"#)
+                .expect("parse a");
+        let b = parse(
+            br#"<opnsense><system><hostname>fw2</hostname></system><nat/><cron/></opnsense>"#,
+        )
+        .expect("parse b");
+
+        let diff = compare(&fingerprint(&a), &fingerprint(&b));
+        assert_eq!(diff.changed, vec!["system".to_string()]);
+        assert_eq!(diff.added, vec!["cron".to_string()]);
+        assert_eq!(diff.removed, vec!["wol".to_string()]);
+        assert_eq!(diff.unchanged, 1);
+        assert!(!diff.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use pfopn_convert::fingerprint::{compare, fingerprint, ConfigFingerprint, FINGERPRINT_FORMAT};
+use xml_diff_core::parse_file;
+
+use crate::cli::{DiffArgs, FingerprintArgs, OutputFormat};
+use crate::path_guard::ensure_output_not_same;
+
+/// Print or write the per-section fingerprint of one config.
+pub fn run_fingerprint(args: FingerprintArgs) -> Result<()> {
+    if let Some(output) = &args.output {
+        ensure_output_not_same(output, &[&args.file])?;
+    }
+    let root = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let print = fingerprint(&root);
+
+    let rendered = match args.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&print)?,
+        OutputFormat::Text => {
+            let mut lines = vec![format!("config {}", print.config)];
+            lines.extend(
+                print
+                    .sections
+                    .iter()
+                    .map(|(section, hash)| format!("{hash}  {section}")),
+            );
+            lines.join("\n")
+        }
+    };
+
+    match &args.output {
+        Some(output) => {
+            std::fs::write(output, format!("{rendered}\n"))
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            println!(
+                "fingerprint: {} section(s), wrote {}",
+                print.sections.len(),
+                output.display()
+            );
+        }
+        None => println!("{rendered}"),
+    }
+    Ok(())
+}
+
+/// `diff --fingerprints`: report which sections changed between two fingerprints.
+pub fn run_fingerprint_diff(args: &DiffArgs) -> Result<()> {
+    let left = load_fingerprint(&args.file1)?;
+    let right = load_fingerprint(&args.file2)?;
+    let diff = compare(&left, &right);
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        OutputFormat::Text => {
+            println!(
+                "fingerprint drift: {} changed, {} added, {} removed, {} unchanged",
+                diff.changed.len(),
+                diff.added.len(),
+                diff.removed.len(),
+                diff.unchanged
+            );
+            for section in &diff.changed {
+                println!("changed: {section}");
+            }
+            for section in &diff.added {
+                println!("added: {section}");
+            }
+            for section in &diff.removed {
+                println!("removed: {section}");
+            }
+        }
+    }
+
+    if args.strict && !diff.is_empty() {
+        bail!("strict mode failed: fingerprints differ");
+    }
+    Ok(())
+}
+
+/// Read a fingerprint JSON file, or fingerprint a config XML on the fly.
+fn load_fingerprint(path: &Path) -> Result<ConfigFingerprint> {
+    let raw = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if let Ok(print) = serde_json::from_slice::<ConfigFingerprint>(&raw) {
+        if print.format != FINGERPRINT_FORMAT {
+            bail!(
+                "{} uses fingerprint format {}, expected {FINGERPRINT_FORMAT}; regenerate it",
+                path.display(),
+                print.format
+            );
+        }
+        return Ok(print);
+    }
+    let root = parse_file(path)
+        .with_context(|| format!("{} is neither a fingerprint nor a config", path.display()))?;
+    Ok(fingerprint(&root))
+}
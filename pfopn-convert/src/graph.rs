@@ -0,0 +1,589 @@
+//! Reference graph of config objects.
+//!
+//! Builds a directed graph of which objects depend on which:
+//! - filter rules → interfaces, aliases, gateways, and schedules
+//! - aliases → nested aliases, gateway groups → gateways → interfaces
+//! - OpenVPN instances and IPsec phase 1 entries → CAs, certificates, users
+//! - certificates → CAs, users → certificates
+//! - interfaces → devices; VLANs → parent devices; LAGGs and bridges → members
+//! - DHCP subnets (ISC per-interface and Kea `subnet4`/`subnet6`) → interfaces
+//!
+//! A reference to an object the config does not define still gets a node,
+//! flagged `missing`, so broken dependencies show up in the export. The graph
+//! renders as GraphViz DOT ([`render_dot`]) or serializes to JSON.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::simulate::load_aliases;
+use crate::verify_routes::{collect_interface_subnets, parse_cidr, same_network};
+
+/// One config object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    /// Stable id: `kind:name`.
+    pub id: String,
+    pub kind: String,
+    pub label: String,
+    /// Referenced but not defined anywhere in the config.
+    pub missing: bool,
+}
+
+/// A dependency from one object to another.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub relation: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl DependencyGraph {
+    /// Nodes referenced but not defined.
+    pub fn missing(&self) -> impl Iterator<Item = &GraphNode> {
+        self.nodes.iter().filter(|n| n.missing)
+    }
+}
+
+/// Build the reference graph for one config.
+pub fn build_graph(root: &XmlNode) -> DependencyGraph {
+    let mut builder = Builder::default();
+    add_interfaces(root, &mut builder);
+    add_devices(root, &mut builder);
+    add_gateways(root, &mut builder);
+    add_aliases(root, &mut builder);
+    add_schedules(root, &mut builder);
+    add_certificates(root, &mut builder);
+    add_filter_rules(root, &mut builder);
+    add_vpn_instances(root, &mut builder);
+    add_dhcp_subnets(root, &mut builder);
+    builder.finish()
+}
+
+/// Render the graph in GraphViz DOT; missing nodes are drawn dashed red.
+pub fn render_dot(graph: &DependencyGraph) -> String {
+    let mut lines = vec![
+        "digraph config {".to_string(),
+        "  rankdir=LR;".to_string(),
+        "  node [shape=box];".to_string(),
+    ];
+    for node in &graph.nodes {
+        let style = if node.missing {
+            ", style=dashed, color=red"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "  \"{}\" [label=\"{}\\n{}\"{style}];",
+            dot_escape(&node.id),
+            dot_escape(&node.kind),
+            dot_escape(&node.label)
+        ));
+    }
+    for edge in &graph.edges {
+        lines.push(format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\"];",
+            dot_escape(&edge.from),
+            dot_escape(&edge.to),
+            dot_escape(&edge.relation)
+        ));
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+fn dot_escape(raw: &str) -> String {
+    raw.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[derive(Default)]
+struct Builder {
+    nodes: BTreeMap<String, GraphNode>,
+    edges: BTreeSet<GraphEdge>,
+}
+
+impl Builder {
+    /// Add a defined object; returns its id.
+    fn define(&mut self, kind: &str, name: &str, label: &str) -> String {
+        let id = node_id(kind, name);
+        let node = self.nodes.entry(id.clone()).or_insert_with(|| GraphNode {
+            id: id.clone(),
+            kind: kind.to_string(),
+            label: label.to_string(),
+            missing: false,
+        });
+        node.missing = false;
+        node.label = label.to_string();
+        id
+    }
+
+    /// Add an edge to `kind:name`, creating a missing node if it is undefined.
+    ///
+    /// Physical devices are never declared in the config, so an undeclared
+    /// device is taken to be a NIC rather than a missing object.
+    fn link(&mut self, from: &str, kind: &str, name: &str, relation: &str) {
+        let to = node_id(kind, name);
+        self.nodes.entry(to.clone()).or_insert_with(|| GraphNode {
+            id: to.clone(),
+            kind: kind.to_string(),
+            label: name.to_string(),
+            missing: kind != "device",
+        });
+        self.edges.insert(GraphEdge {
+            from: from.to_string(),
+            to,
+            relation: relation.to_string(),
+        });
+    }
+
+    fn is_defined(&self, kind: &str, name: &str) -> bool {
+        self.nodes
+            .get(&node_id(kind, name))
+            .is_some_and(|n| !n.missing)
+    }
+
+    fn finish(self) -> DependencyGraph {
+        DependencyGraph {
+            nodes: self.nodes.into_values().collect(),
+            edges: self.edges.into_iter().collect(),
+        }
+    }
+}
+
+fn node_id(kind: &str, name: &str) -> String {
+    format!("{kind}:{name}")
+}
+
+fn add_interfaces(root: &XmlNode, builder: &mut Builder) {
+    let Some(interfaces) = root.get_child("interfaces") else {
+        return;
+    };
+    for iface in &interfaces.children {
+        let label = text(iface, "descr").unwrap_or(&iface.tag);
+        let id = builder.define("interface", &iface.tag, label);
+        if let Some(device) = text(iface, "if") {
+            builder.link(&id, "device", device, "device");
+        }
+    }
+}
+
+/// VLAN, LAGG, and bridge devices with their parents and members.
+fn add_devices(root: &XmlNode, builder: &mut Builder) {
+    for vlan in section_items(root, "vlans", "vlan") {
+        let Some(name) = text(vlan, "vlanif") else {
+            continue;
+        };
+        let id = builder.define("device", name, &device_label("vlan", vlan));
+        if let Some(parent) = text(vlan, "if") {
+            builder.link(&id, "device", parent, "parent");
+        }
+    }
+    for lagg in section_items(root, "laggs", "lagg") {
+        let Some(name) = text(lagg, "laggif") else {
+            continue;
+        };
+        let id = builder.define("device", name, &device_label("lagg", lagg));
+        for member in split_list(text(lagg, "members")) {
+            builder.link(&id, "device", &member, "member");
+        }
+    }
+    for bridge in section_items(root, "bridges", "bridged") {
+        let Some(name) = text(bridge, "bridgeif") else {
+            continue;
+        };
+        let id = builder.define("device", name, &device_label("bridge", bridge));
+        // Bridge members are interface assignments, not devices.
+        for member in split_list(text(bridge, "members")) {
+            builder.link(&id, "interface", &member, "member");
+        }
+    }
+}
+
+fn device_label(kind: &str, node: &XmlNode) -> String {
+    match text(node, "descr") {
+        Some(descr) => format!("{kind} ({descr})"),
+        None => kind.to_string(),
+    }
+}
+
+fn add_gateways(root: &XmlNode, builder: &mut Builder) {
+    let legacy = root.get_child("gateways").into_iter();
+    let mvc = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("Gateways"))
+        .into_iter();
+    for section in legacy.chain(mvc) {
+        for gw in &section.children {
+            let Some(name) = text(gw, "name") else {
+                continue;
+            };
+            if gw.tag == "gateway_group" {
+                let id = builder.define("gateway", name, text(gw, "descr").unwrap_or(name));
+                for item in gw.get_children("item") {
+                    // Group members are `GATEWAY|tier|vip`.
+                    if let Some(member) = item
+                        .text
+                        .as_deref()
+                        .and_then(|v| v.split('|').next())
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                    {
+                        builder.link(&id, "gateway", member, "member");
+                    }
+                }
+                continue;
+            }
+            let id = builder.define("gateway", name, text(gw, "descr").unwrap_or(name));
+            if let Some(iface) = text(gw, "interface") {
+                builder.link(&id, "interface", iface, "interface");
+            }
+        }
+    }
+}
+
+fn add_aliases(root: &XmlNode, builder: &mut Builder) {
+    let aliases = load_aliases(root);
+    for name in aliases.keys() {
+        builder.define("alias", name, name);
+    }
+    for (name, alias) in &aliases {
+        let id = node_id("alias", name);
+        for entry in alias.entries.iter().filter(|e| aliases.contains_key(*e)) {
+            builder.link(&id, "alias", entry, "contains");
+        }
+    }
+}
+
+fn add_schedules(root: &XmlNode, builder: &mut Builder) {
+    for schedule in section_items(root, "schedules", "schedule") {
+        if let Some(name) = text(schedule, "name") {
+            builder.define("schedule", name, text(schedule, "descr").unwrap_or(name));
+        }
+    }
+}
+
+/// CAs, certificates, and local users with their issuer/cert links.
+fn add_certificates(root: &XmlNode, builder: &mut Builder) {
+    for ca in root.children.iter().filter(|c| c.tag == "ca") {
+        if let Some(refid) = text(ca, "refid") {
+            let id = builder.define("ca", refid, text(ca, "descr").unwrap_or(refid));
+            if let Some(issuer) = text(ca, "caref").filter(|v| *v != refid) {
+                builder.link(&id, "ca", issuer, "issuer");
+            }
+        }
+    }
+    for cert in root.children.iter().filter(|c| c.tag == "cert") {
+        if let Some(refid) = text(cert, "refid") {
+            let id = builder.define("cert", refid, text(cert, "descr").unwrap_or(refid));
+            if let Some(issuer) = text(cert, "caref") {
+                builder.link(&id, "ca", issuer, "issuer");
+            }
+        }
+    }
+    let Some(system) = root.get_child("system") else {
+        return;
+    };
+    for user in system.get_children("user") {
+        let Some(name) = text(user, "name") else {
+            continue;
+        };
+        let id = builder.define("user", name, name);
+        for cert in user.get_children("cert") {
+            if let Some(refid) = cert
+                .text
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+            {
+                builder.link(&id, "cert", refid, "cert");
+            }
+        }
+    }
+}
+
+/// Rule interface names that stand for a VPN group or "all", not an assignment.
+const PSEUDO_INTERFACES: &[&str] = &[
+    "any",
+    "floating",
+    "lo0",
+    "enc0",
+    "ipsec",
+    "openvpn",
+    "wireguard",
+    "tailscale",
+];
+
+fn add_filter_rules(root: &XmlNode, builder: &mut Builder) {
+    let Some(filter) = root.get_child("filter") else {
+        return;
+    };
+    for (idx, rule) in filter.get_children("rule").into_iter().enumerate() {
+        let number = idx + 1;
+        let label = text(rule, "descr")
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| format!("rule #{number}"));
+        let id = builder.define("rule", &number.to_string(), &label);
+        for iface in split_list(text(rule, "interface")) {
+            if !PSEUDO_INTERFACES.contains(&iface.to_ascii_lowercase().as_str()) {
+                builder.link(&id, "interface", &iface, "interface");
+            }
+        }
+        for side in ["source", "destination"] {
+            let Some(endpoint) = rule.get_child(side) else {
+                continue;
+            };
+            for field in ["address", "port"] {
+                if let Some(value) = text(endpoint, field) {
+                    if builder.is_defined("alias", value) || looks_like_alias(value) {
+                        builder.link(&id, "alias", value, side);
+                    }
+                }
+            }
+        }
+        if let Some(gateway) = text(rule, "gateway") {
+            builder.link(&id, "gateway", gateway, "gateway");
+        }
+        if let Some(sched) = text(rule, "sched").or_else(|| text(rule, "schedule")) {
+            builder.link(&id, "schedule", sched, "schedule");
+        }
+    }
+}
+
+/// Whether an undefined rule address/port is an alias name rather than a
+/// literal (IP, CIDR, port number or range).
+fn looks_like_alias(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !value.eq_ignore_ascii_case("any")
+        && parse_cidr(value).is_none()
+}
+
+fn add_vpn_instances(root: &XmlNode, builder: &mut Builder) {
+    if let Some(openvpn) = root.get_child("openvpn") {
+        for tag in ["openvpn-server", "openvpn-client"] {
+            for instance in openvpn.get_children(tag) {
+                let name = text(instance, "vpnid").unwrap_or("?");
+                let kind = tag.trim_start_matches("openvpn-");
+                let label = text(instance, "description").unwrap_or(name);
+                let id = builder.define("openvpn", &format!("{kind}{name}"), label);
+                link_vpn_refs(builder, &id, instance);
+            }
+        }
+    }
+    let mvc = ["OPNsense", "OpenVPN", "Instances"]
+        .iter()
+        .try_fold(root, |node, tag| node.get_child(tag));
+    if let Some(instances) = mvc {
+        for instance in instances.get_children("Instance") {
+            let name = instance
+                .attributes
+                .get("uuid")
+                .map(String::as_str)
+                .or_else(|| text(instance, "vpnid"))
+                .unwrap_or("?");
+            let label = text(instance, "description").unwrap_or(name);
+            let id = builder.define("openvpn", name, label);
+            link_vpn_refs(builder, &id, instance);
+        }
+    }
+    if let Some(ipsec) = root.get_child("ipsec") {
+        for phase1 in ipsec.get_children("phase1") {
+            let name = text(phase1, "ikeid").unwrap_or("?");
+            let label = text(phase1, "descr").unwrap_or(name);
+            let id = builder.define("ipsec", name, label);
+            link_vpn_refs(builder, &id, phase1);
+        }
+    }
+}
+
+fn link_vpn_refs(builder: &mut Builder, id: &str, instance: &XmlNode) {
+    for child in &instance.children {
+        let Some(value) = child
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        else {
+            continue;
+        };
+        match child.tag.as_str() {
+            "caref" | "ca" | "authcertca" => builder.link(id, "ca", value, "ca"),
+            "certref" | "cert" | "authcertname" => builder.link(id, "cert", value, "cert"),
+            "username" | "local_user" => {
+                for user in split_list(Some(value)) {
+                    builder.link(id, "user", &user, "user");
+                }
+            }
+            "interface" => builder.link(id, "interface", value, "interface"),
+            _ => {}
+        }
+    }
+}
+
+fn add_dhcp_subnets(root: &XmlNode, builder: &mut Builder) {
+    for section in ["dhcpd", "dhcpdv6"] {
+        let Some(dhcp) = root.get_child(section) else {
+            continue;
+        };
+        for iface in &dhcp.children {
+            let label = match iface.get_text(&["range", "from"]) {
+                Some(from) => format!(
+                    "{from} - {}",
+                    iface.get_text(&["range", "to"]).unwrap_or("?")
+                ),
+                None => iface.tag.clone(),
+            };
+            let id = builder.define("dhcp", &format!("{section}/{}", iface.tag), &label);
+            builder.link(&id, "interface", &iface.tag, "serves");
+        }
+    }
+
+    let interface_subnets = collect_interface_subnets(root);
+    for (family, tag) in [("dhcp4", "subnet4"), ("dhcp6", "subnet6")] {
+        let subnets = ["OPNsense", "Kea", family, "subnets"]
+            .iter()
+            .try_fold(root, |node, t| node.get_child(t));
+        let Some(subnets) = subnets else {
+            continue;
+        };
+        for subnet in subnets.get_children(tag) {
+            let Some(cidr) = text(subnet, "subnet") else {
+                continue;
+            };
+            let id = builder.define("dhcp", &format!("kea/{cidr}"), cidr);
+            let Some((network, prefix)) = parse_cidr(cidr) else {
+                continue;
+            };
+            for (iface, addr, iface_prefix) in &interface_subnets {
+                if *iface_prefix == prefix && same_network(network, *addr, prefix) {
+                    builder.link(&id, "interface", iface, "serves");
+                }
+            }
+        }
+    }
+}
+
+fn section_items<'a>(root: &'a XmlNode, section: &str, item: &str) -> Vec<&'a XmlNode> {
+    root.get_child(section)
+        .map(|s| s.get_children(item))
+        .unwrap_or_default()
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn split_list(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{build_graph, render_dot, DependencyGraph};
+
+    fn has_edge(graph: &DependencyGraph, from: &str, to: &str) -> bool {
+        graph.edges.iter().any(|e| e.from == from && e.to == to)
+    }
+
+    #[test]
+    fn links_rules_to_aliases_gateways_and_schedules() {
+        let root = parse(
+            br#"<pfsense>
+                <interfaces><lan><if>igb1</if></lan></interfaces>
+                <aliases><alias><name>WEB</name><address>10.0.0.1</address></alias></aliases>
+                <gateways><gateway_item><name>GW1</name><interface>wan</interface></gateway_item></gateways>
+                <schedules><schedule><name>work</name></schedule></schedules>
+                <filter><rule><interface>lan</interface><source><any/></source><destination><address>WEB</address><port>WEB_PORTS</port></destination><gateway>GW1</gateway><sched>work</sched></rule></filter>
+            </pfsense>"#,
+        )
+        .expect("parse");
+        let graph = build_graph(&root);
+
+        assert!(has_edge(&graph, "rule:1", "interface:lan"));
+        assert!(has_edge(&graph, "rule:1", "alias:WEB"));
+        assert!(has_edge(&graph, "rule:1", "gateway:GW1"));
+        assert!(has_edge(&graph, "rule:1", "schedule:work"));
+        assert!(has_edge(&graph, "gateway:GW1", "interface:wan"));
+        let missing: Vec<_> = graph.missing().map(|n| n.id.as_str()).collect();
+        assert_eq!(missing, vec!["alias:WEB_PORTS", "interface:wan"]);
+    }
+
+    #[test]
+    fn links_vpn_instances_to_certificates_and_users() {
+        let root = parse(
+            br#"<pfsense>
+                <system><user><name>alice</name><cert>c2</cert></user></system>
+                <ca><refid>ca1</refid><descr>Root</descr></ca>
+                <cert><refid>c1</refid><caref>ca1</caref></cert>
+                <openvpn><openvpn-server><vpnid>1</vpnid><caref>ca1</caref><certref>c1</certref></openvpn-server></openvpn>
+                <ipsec><phase1><ikeid>3</ikeid><certref>gone</certref></phase1></ipsec>
+            </pfsense>"#,
+        )
+        .expect("parse");
+        let graph = build_graph(&root);
+
+        assert!(has_edge(&graph, "openvpn:server1", "ca:ca1"));
+        assert!(has_edge(&graph, "openvpn:server1", "cert:c1"));
+        assert!(has_edge(&graph, "cert:c1", "ca:ca1"));
+        assert!(has_edge(&graph, "user:alice", "cert:c2"));
+        assert!(has_edge(&graph, "ipsec:3", "cert:gone"));
+        assert!(graph.missing().any(|n| n.id == "cert:gone"));
+    }
+
+    #[test]
+    fn links_interfaces_devices_and_dhcp_subnets() {
+        let root = parse(
+            br#"<opnsense>
+                <interfaces>
+                  <lan><if>vlan0.10</if><ipaddr>192.168.10.1</ipaddr><subnet>24</subnet></lan>
+                  <opt1><if>bridge0</if></opt1>
+                </interfaces>
+                <vlans><vlan><if>lagg0</if><tag>10</tag><vlanif>vlan0.10</vlanif></vlan></vlans>
+                <laggs><lagg><laggif>lagg0</laggif><members>igb0,igb1</members></lagg></laggs>
+                <bridges><bridged><bridgeif>bridge0</bridgeif><members>lan</members></bridged></bridges>
+                <dhcpd><opt1><range><from>10.0.0.10</from><to>10.0.0.20</to></range></opt1></dhcpd>
+                <OPNsense><Kea><dhcp4><subnets><subnet4><subnet>192.168.10.0/24</subnet></subnet4></subnets></dhcp4></Kea></OPNsense>
+            </opnsense>"#,
+        )
+        .expect("parse");
+        let graph = build_graph(&root);
+
+        assert!(has_edge(&graph, "interface:lan", "device:vlan0.10"));
+        assert!(has_edge(&graph, "device:vlan0.10", "device:lagg0"));
+        assert!(has_edge(&graph, "device:lagg0", "device:igb0"));
+        assert!(has_edge(&graph, "device:bridge0", "interface:lan"));
+        assert!(has_edge(&graph, "dhcp:dhcpd/opt1", "interface:opt1"));
+        assert!(has_edge(
+            &graph,
+            "dhcp:kea/192.168.10.0/24",
+            "interface:lan"
+        ));
+    }
+
+    #[test]
+    fn renders_dot_with_missing_nodes_dashed() {
+        let root = parse(br#"<pfsense><filter><rule><descr>say "hi"</descr><gateway>GONE</gateway></rule></filter></pfsense>"#)
+            .expect("parse");
+        let dot = render_dot(&build_graph(&root));
+
+        assert!(dot.starts_with("digraph config {"));
+        assert!(dot.contains(r#""rule:1" [label="rule\nsay \"hi\""];"#));
+        assert!(dot.contains(r#""gateway:GONE" [label="gateway\nGONE", style=dashed, color=red];"#));
+        assert!(dot.contains(r#""rule:1" -> "gateway:GONE" [label="gateway"];"#));
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use pfopn_convert::graph::{build_graph, render_dot};
+use xml_diff_core::parse_file;
+
+use crate::cli::{GraphArgs, GraphFormat};
+use crate::path_guard::ensure_output_not_same;
+
+/// Export the reference graph of one config to stdout or `--output`.
+pub fn run_graph(args: GraphArgs) -> Result<()> {
+    if let Some(output) = &args.output {
+        ensure_output_not_same(output, &[&args.file])?;
+    }
+    let root = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+
+    let graph = build_graph(&root);
+    let rendered = match args.format {
+        GraphFormat::Dot => render_dot(&graph),
+        GraphFormat::Json => serde_json::to_string_pretty(&graph)?,
+    };
+
+    match &args.output {
+        Some(output) => {
+            std::fs::write(output, format!("{rendered}\n"))
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            println!(
+                "graph: {} node(s), {} edge(s), {} missing reference(s), wrote {}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                graph.missing().count(),
+                output.display()
+            );
+        }
+        None => println!("{rendered}"),
+    }
+    Ok(())
+}
@@ -19,3 +19,240 @@ fn render_node(node: &XmlNode, depth: usize, max_depth: usize, out: &mut String)
         render_node(child, depth + 1, max_depth, out);
     }
 }
+
+/// What `inspect --grep` looks for.
+#[derive(Debug, Clone)]
+pub enum GrepPattern {
+    /// Case-insensitive substring.
+    Text(String),
+    /// Regular expression, matched as written.
+    Regex(regex::Regex),
+}
+
+impl GrepPattern {
+    /// Build a pattern, compiling `raw` as a regex when `regex` is set.
+    pub fn new(raw: &str, regex: bool) -> Result<Self, regex::Error> {
+        if regex {
+            regex::Regex::new(raw).map(Self::Regex)
+        } else {
+            Ok(Self::Text(raw.to_lowercase()))
+        }
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        match self {
+            Self::Text(needle) => value.to_lowercase().contains(needle),
+            Self::Regex(re) => re.is_match(value),
+        }
+    }
+}
+
+/// Which part of a node matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrepHit {
+    /// The element's tag name.
+    Tag,
+    /// An attribute value (or name), keyed by attribute name.
+    Attribute(String),
+    /// The element's text.
+    Text,
+}
+
+/// One `inspect --grep` match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepMatch {
+    /// Dotted path below the search root, usable as a `--section` query.
+    pub path: String,
+    /// Matched part of the node.
+    pub hit: GrepHit,
+    /// Matched value (tag name, attribute value, or text).
+    pub value: String,
+    /// Description of the nearest enclosing item, if any.
+    pub context: Option<String>,
+}
+
+/// Search tags, attribute names and values, and text below `node`.
+///
+/// Paths start below `node` and use `[n]` on repeated siblings, so each one
+/// can be fed back to `inspect --section`. Matches carry the `<descr>` of the
+/// nearest element that has one, which is usually the rule, alias, or
+/// mapping the value belongs to.
+pub fn grep_tree(node: &XmlNode, pattern: &GrepPattern) -> Vec<GrepMatch> {
+    let mut out = Vec::new();
+    for (idx, child) in node.children.iter().enumerate() {
+        let path = step_name(node, idx);
+        grep_node(child, &path, describe(node), pattern, &mut out);
+    }
+    out
+}
+
+/// Search the subtree at `location` (child indexes from `root`, as returned
+/// by `PathQuery::locate`), keeping paths and context relative to `root`.
+pub fn grep_at(root: &XmlNode, location: &[usize], pattern: &GrepPattern) -> Vec<GrepMatch> {
+    let mut out = Vec::new();
+    let Some((&last, parents)) = location.split_last() else {
+        return grep_tree(root, pattern);
+    };
+    let mut node = root;
+    let mut steps = Vec::new();
+    let mut context = describe(root);
+    for &idx in parents {
+        steps.push(step_name(node, idx));
+        node = &node.children[idx];
+        context = describe(node).or(context);
+    }
+    steps.push(step_name(node, last));
+    grep_node(
+        &node.children[last],
+        &steps.join("."),
+        context,
+        pattern,
+        &mut out,
+    );
+    out
+}
+
+fn grep_node(
+    node: &XmlNode,
+    path: &str,
+    inherited: Option<String>,
+    pattern: &GrepPattern,
+    out: &mut Vec<GrepMatch>,
+) {
+    let context = describe(node).or(inherited);
+    if pattern.is_match(&node.tag) {
+        out.push(GrepMatch {
+            path: path.to_string(),
+            hit: GrepHit::Tag,
+            value: node.tag.clone(),
+            context: context.clone(),
+        });
+    }
+    for (name, value) in &node.attributes {
+        if pattern.is_match(name) || pattern.is_match(value) {
+            out.push(GrepMatch {
+                path: path.to_string(),
+                hit: GrepHit::Attribute(name.clone()),
+                value: value.clone(),
+                context: context.clone(),
+            });
+        }
+    }
+    if let Some(text) = node.text.as_deref().map(str::trim) {
+        if !text.is_empty() && pattern.is_match(text) {
+            out.push(GrepMatch {
+                path: path.to_string(),
+                hit: GrepHit::Text,
+                value: text.to_string(),
+                context: context.clone(),
+            });
+        }
+    }
+    for (idx, child) in node.children.iter().enumerate() {
+        let child_path = format!("{path}.{}", step_name(node, idx));
+        grep_node(child, &child_path, context.clone(), pattern, out);
+    }
+}
+
+/// Tag of `parent.children[idx]`, indexed when the tag repeats among siblings.
+fn step_name(parent: &XmlNode, idx: usize) -> String {
+    let tag = &parent.children[idx].tag;
+    let same: Vec<usize> = parent
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| &c.tag == tag)
+        .map(|(i, _)| i)
+        .collect();
+    if same.len() == 1 {
+        return tag.clone();
+    }
+    let nth = same.iter().position(|&i| i == idx).unwrap_or(0) + 1;
+    format!("{tag}[{nth}]")
+}
+
+fn describe(node: &XmlNode) -> Option<String> {
+    node.get_text(&["descr"])
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+/// Render grep matches one per line, followed by a match count.
+pub fn render_grep(matches: &[GrepMatch]) -> String {
+    let mut out = String::new();
+    for m in matches {
+        let line = match &m.hit {
+            GrepHit::Tag => format!("{} <{}>", m.path, m.value),
+            GrepHit::Attribute(name) => format!("{}@{name} = {}", m.path, m.value),
+            GrepHit::Text => format!("{} = {}", m.path, m.value),
+        };
+        out.push_str(&line);
+        if let Some(context) = &m.context {
+            out.push_str(&format!("  (descr: {context})"));
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("matches={}\n", matches.len()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use xml_diff_core::PathQuery;
+
+    use super::{grep_at, grep_tree, render_grep, GrepHit, GrepPattern};
+
+    const CONFIG: &[u8] = br#"<pfsense><interfaces><lan><ipaddr>10.0.0.1</ipaddr></lan></interfaces><filter><rule><descr>Allow DNS</descr><destination><address>10.0.0.53</address></destination></rule><rule uuid="r-10.0.0.9"><descr>Block</descr><source><address>192.168.9.9</address></source></rule></filter></pfsense>"#;
+
+    #[test]
+    fn finds_text_with_indexed_paths_and_descr_context() {
+        let root = parse(CONFIG).expect("parse");
+        let pattern = GrepPattern::new("10.0.0.5", false).expect("pattern");
+        let matches = grep_tree(&root, &pattern);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, "filter.rule[1].destination.address");
+        assert_eq!(matches[0].hit, GrepHit::Text);
+        assert_eq!(matches[0].context.as_deref(), Some("Allow DNS"));
+    }
+
+    #[test]
+    fn regex_matches_attributes_and_text() {
+        let root = parse(CONFIG).expect("parse");
+        let pattern = GrepPattern::new(r"^r?-?10\.0\.0\.\d$", true).expect("pattern");
+        let rendered = render_grep(&grep_tree(&root, &pattern));
+        assert_eq!(
+            rendered,
+            "interfaces.lan.ipaddr = 10.0.0.1\nfilter.rule[2]@uuid = r-10.0.0.9  (descr: Block)\nmatches=2\n"
+        );
+    }
+
+    #[test]
+    fn section_scope_keeps_root_relative_paths() {
+        let root = parse(CONFIG).expect("parse");
+        let pattern = GrepPattern::new("10.0.0", false).expect("pattern");
+        let query = PathQuery::parse("filter").expect("query");
+        let matches: Vec<_> = query
+            .locate(&root)
+            .iter()
+            .flat_map(|loc| grep_at(&root, loc, &pattern))
+            .map(|m| m.path)
+            .collect();
+        assert_eq!(
+            matches,
+            vec!["filter.rule[1].destination.address", "filter.rule[2]"]
+        );
+    }
+
+    #[test]
+    fn text_search_is_case_insensitive_and_matches_tags() {
+        let root = parse(CONFIG).expect("parse");
+        let pattern = GrepPattern::new("IPADDR", false).expect("pattern");
+        let matches = grep_tree(&root, &pattern);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].hit, GrepHit::Tag);
+        assert_eq!(matches[0].path, "interfaces.lan.ipaddr");
+    }
+}
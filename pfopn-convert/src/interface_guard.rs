@@ -35,7 +35,11 @@ pub fn collect_interfaces(root: &XmlNode) -> BTreeMap<String, InterfaceSpec> {
     out
 }
 
-pub fn enforce_interface_compat(source: &XmlNode, target: &XmlNode) -> Result<()> {
+pub fn enforce_interface_compat(
+    source: &XmlNode,
+    target: &XmlNode,
+    interface_map: Option<&BTreeMap<String, String>>,
+) -> Result<()> {
     let source_map = collect_interfaces(source);
     let target_map = collect_interfaces(target);
 
@@ -47,24 +51,22 @@ pub fn enforce_interface_compat(source: &XmlNode, target: &XmlNode) -> Result<()
         );
     }
 
-    let mut missing = Vec::new();
-    for (name, src) in &source_map {
-        let Some(dst) = target_map.get(name) else {
-            if src
-                .if_name
-                .as_deref()
-                .map(is_virtual_if_name)
-                .unwrap_or(false)
-            {
-                // Virtual-backed interfaces (vlan/wg/openvpn/etc) can be created from source config.
-                continue;
+    if let Some(map) = interface_map {
+        for (from, to) in map {
+            if !source_map.contains_key(from) {
+                bail!("interface preflight failed: mapped source interface '{from}' not found");
             }
-            missing.push(format_missing(name, src));
-            continue;
-        };
-        let _ = dst;
+            if !target_map.contains_key(to) {
+                bail!("interface preflight failed: mapped target interface '{to}' not found");
+            }
+        }
     }
 
+    let missing: Vec<String> = unmatched_interfaces(source, target, interface_map)
+        .iter()
+        .map(|spec| format_missing(&spec.name, spec))
+        .collect();
+
     if !missing.is_empty() {
         bail!(
             "interface preflight failed: missing target interfaces: {}",
@@ -74,6 +76,36 @@ pub fn enforce_interface_compat(source: &XmlNode, target: &XmlNode) -> Result<()
     Ok(())
 }
 
+/// Return physical-backed source interfaces with no destination on the target.
+///
+/// An interface counts as matched when the target has the same logical name,
+/// or when `interface_map` routes it to a logical name the target has.
+/// Virtual-backed interfaces are never reported since they can be created
+/// from source config.
+pub fn unmatched_interfaces(
+    source: &XmlNode,
+    target: &XmlNode,
+    interface_map: Option<&BTreeMap<String, String>>,
+) -> Vec<InterfaceSpec> {
+    let target_map = collect_interfaces(target);
+    collect_interfaces(source)
+        .into_values()
+        .filter(|src| {
+            let mapped = interface_map
+                .and_then(|m| m.get(&src.name))
+                .unwrap_or(&src.name);
+            if target_map.contains_key(mapped) {
+                return false;
+            }
+            // Virtual-backed interfaces (vlan/wg/openvpn/etc) can be created from source config.
+            !src.if_name
+                .as_deref()
+                .map(is_virtual_if_name)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
 fn is_virtual_if_name(if_name: &str) -> bool {
     let lower = if_name.trim().to_ascii_lowercase();
     if lower.contains('.') {
@@ -92,7 +124,7 @@ fn is_virtual_if_name(if_name: &str) -> bool {
     .any(|prefix| lower.starts_with(prefix))
 }
 
-fn format_missing(name: &str, spec: &InterfaceSpec) -> String {
+pub fn format_missing(name: &str, spec: &InterfaceSpec) -> String {
     let mut parts = Vec::new();
     if let Some(descr) = spec.descr.as_deref().filter(|d| !d.is_empty()) {
         parts.push(format!("descr={descr}"));
@@ -111,7 +143,9 @@ fn format_missing(name: &str, spec: &InterfaceSpec) -> String {
 mod tests {
     use xml_diff_core::parse;
 
-    use super::enforce_interface_compat;
+    use std::collections::BTreeMap;
+
+    use super::{enforce_interface_compat, unmatched_interfaces};
 
     #[test]
     fn allows_subnet_differences() {
@@ -123,7 +157,8 @@ mod tests {
         )
         .expect("target parse");
 
-        enforce_interface_compat(&source, &target).expect("subnet differences should not block");
+        enforce_interface_compat(&source, &target, None)
+            .expect("subnet differences should not block");
     }
 
     #[test]
@@ -136,7 +171,8 @@ mod tests {
             br#"<opnsense><interfaces><lan><if>vtnet0</if><subnet>24</subnet></lan></interfaces></opnsense>"#,
         )
         .expect("target parse");
-        enforce_interface_compat(&source, &target).expect("virtual-backed missing should pass");
+        enforce_interface_compat(&source, &target, None)
+            .expect("virtual-backed missing should pass");
     }
 
     #[test]
@@ -149,6 +185,29 @@ mod tests {
             br#"<opnsense><interfaces><lan><if>vtnet0</if><subnet>24</subnet></lan></interfaces></opnsense>"#,
         )
         .expect("target parse");
-        enforce_interface_compat(&source, &target).expect("dotted vlan-backed missing should pass");
+        enforce_interface_compat(&source, &target, None)
+            .expect("dotted vlan-backed missing should pass");
+    }
+
+    #[test]
+    fn interface_map_satisfies_missing_physical_interface() {
+        let source = parse(
+            br#"<pfsense><interfaces><lan><if>igb0</if></lan><opt2><if>igb2</if></opt2></interfaces></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet0</if></lan><opt1><if>vtnet1</if></opt1></interfaces></opnsense>"#,
+        )
+        .expect("target parse");
+        assert_eq!(unmatched_interfaces(&source, &target, None).len(), 1);
+        assert!(enforce_interface_compat(&source, &target, None).is_err());
+
+        let map = BTreeMap::from([("opt2".to_string(), "opt1".to_string())]);
+        assert!(unmatched_interfaces(&source, &target, Some(&map)).is_empty());
+        enforce_interface_compat(&source, &target, Some(&map)).expect("mapped interface passes");
+
+        let bad = BTreeMap::from([("opt2".to_string(), "opt9".to_string())]);
+        let err = enforce_interface_compat(&source, &target, Some(&bad)).expect_err("bad map");
+        assert!(err.to_string().contains("mapped target interface 'opt9'"));
     }
 }
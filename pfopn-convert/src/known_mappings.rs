@@ -1,8 +1,13 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use xml_diff_core::XmlNode;
+
+use crate::section::{default_key_fields, is_known_section};
 
 /// Canonical mapping metadata for known cross-platform section relationships.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -13,9 +18,14 @@ pub struct KnownSectionMapping {
     pub note: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// A mappings TOML file: `[[mapping]]` entries and an optional
+/// `[key_fields]` table of repeated element tag → identifying child.
+#[derive(Debug, Default, Deserialize)]
 struct MappingFile {
+    #[serde(default)]
     mapping: Vec<KnownSectionMapping>,
+    #[serde(default)]
+    key_fields: BTreeMap<String, String>,
 }
 
 /// Errors returned when loading mapping files.
@@ -43,6 +53,296 @@ pub fn load_section_mappings(path: &Path) -> Result<Vec<KnownSectionMapping>, Ma
     parse_mappings(&raw, path.display().to_string())
 }
 
+fn load_mapping_file(path: &Path) -> Result<MappingFile, MappingLoadError> {
+    let raw = fs::read_to_string(path).map_err(|source| MappingLoadError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    parse_mapping_file(&raw, path.display().to_string())
+}
+
+/// Mappings directory consulted between the embedded defaults and a user
+/// file, unless overridden.
+pub const SYSTEM_MAPPINGS_DIR: &str = "/usr/local/etc/pfopn-convert/mappings";
+
+/// Environment variable overriding [`SYSTEM_MAPPINGS_DIR`].
+pub const MAPPINGS_DIR_ENV: &str = "PFOPN_CONVERT_MAPPINGS_DIR";
+
+/// One mapping after layering, with the layer it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedMapping {
+    pub mapping: KnownSectionMapping,
+    /// `embedded` or `file:<path>`.
+    pub origin: String,
+}
+
+/// Diff key field for a repeated element tag after layering.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedKeyField {
+    pub tag: String,
+    /// Child whose text identifies an element; empty disables keyed
+    /// matching for the tag.
+    pub key: String,
+    /// `embedded` or `file:<path>`.
+    pub origin: String,
+}
+
+/// Section mappings merged from every layer, lowest precedence first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayeredMappings {
+    pub entries: Vec<ResolvedMapping>,
+    /// Key fields by tag, starting from [`default_key_fields`].
+    pub key_fields: BTreeMap<String, ResolvedKeyField>,
+    /// Layers that contributed, in the order they were applied.
+    pub sources: Vec<String>,
+    /// Mappings a later mapping of the same `left` in the same file replaced.
+    pub duplicates: Vec<ResolvedMapping>,
+}
+
+impl LayeredMappings {
+    pub fn mappings(&self) -> Vec<KnownSectionMapping> {
+        self.entries.iter().map(|e| e.mapping.clone()).collect()
+    }
+
+    /// Key fields for `DiffOptions::key_fields`; tags whose key was set to
+    /// an empty string are left out and matched by position.
+    pub fn key_fields_map(&self) -> HashMap<String, String> {
+        self.key_fields
+            .values()
+            .filter(|k| !k.key.is_empty())
+            .map(|k| (k.tag.clone(), k.key.clone()))
+            .collect()
+    }
+
+    /// File layers joined with `+` (`file:/a/sections.toml+file:/b.toml`),
+    /// or `embedded` when only the defaults apply.
+    pub fn source_label(&self) -> String {
+        let files: Vec<&str> = self
+            .sources
+            .iter()
+            .map(String::as_str)
+            .filter(|s| *s != "embedded")
+            .collect();
+        if files.is_empty() {
+            "embedded".to_string()
+        } else {
+            files.join("+")
+        }
+    }
+}
+
+/// System mappings directory: `$PFOPN_CONVERT_MAPPINGS_DIR`, or
+/// [`SYSTEM_MAPPINGS_DIR`].
+pub fn system_mappings_dir() -> PathBuf {
+    env::var_os(MAPPINGS_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(SYSTEM_MAPPINGS_DIR))
+}
+
+/// Resolve section mappings from the embedded defaults, `<system_dir>/sections.toml`,
+/// and `user_file`, each overriding the one before.
+///
+/// A mapping replaces an earlier one with the same `left` section and keeps
+/// its position; new sections are appended. A directory without
+/// `sections.toml` is skipped, so a directory holding only `plugins.toml`
+/// still works; a missing or unreadable user file is an error.
+pub fn resolve_layered_mappings(
+    system_dir: Option<&Path>,
+    user_file: Option<&Path>,
+) -> Result<LayeredMappings, MappingLoadError> {
+    let mut layered = LayeredMappings {
+        entries: Vec::new(),
+        key_fields: BTreeMap::new(),
+        sources: Vec::new(),
+        duplicates: Vec::new(),
+    };
+    let embedded = MappingFile {
+        mapping: default_section_mappings(),
+        key_fields: default_key_fields().into_iter().collect(),
+    };
+    merge_layer(&mut layered, embedded, "embedded".to_string());
+    if let Some(path) = system_dir.map(|dir| dir.join("sections.toml")) {
+        if path.is_file() {
+            let file = load_mapping_file(&path)?;
+            merge_layer(&mut layered, file, format!("file:{}", path.display()));
+        }
+    }
+    if let Some(path) = user_file {
+        let file = load_mapping_file(path)?;
+        merge_layer(&mut layered, file, format!("file:{}", path.display()));
+    }
+    Ok(layered)
+}
+
+fn merge_layer(layered: &mut LayeredMappings, file: MappingFile, origin: String) {
+    for (tag, key) in file.key_fields {
+        layered.key_fields.insert(
+            tag.clone(),
+            ResolvedKeyField {
+                tag,
+                key: key.trim().to_string(),
+                origin: origin.clone(),
+            },
+        );
+    }
+    for mapping in file.mapping {
+        let entry = ResolvedMapping {
+            mapping,
+            origin: origin.clone(),
+        };
+        match layered
+            .entries
+            .iter_mut()
+            .find(|e| e.mapping.left == entry.mapping.left)
+        {
+            Some(existing) => {
+                let replaced = std::mem::replace(existing, entry);
+                if replaced.origin == origin {
+                    layered.duplicates.push(replaced);
+                }
+            }
+            None => layered.entries.push(entry),
+        }
+    }
+    layered.sources.push(origin);
+}
+
+/// A problem in a mapping that makes it dead weight or misleading.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MappingLintFinding {
+    /// `unknown_section`, `empty_right`, or `duplicate_left`.
+    pub code: &'static str,
+    /// Layer the mapping came from.
+    pub origin: String,
+    pub left: String,
+    pub message: String,
+}
+
+/// Check resolved mappings against the section metadata.
+///
+/// Reports `left` or `right` names no known section matches, mappings with
+/// no `right` candidates, and `left` sections mapped twice in one file
+/// (only the last of those takes effect).
+pub fn lint_mappings(layered: &LayeredMappings) -> Vec<MappingLintFinding> {
+    let mut out = Vec::new();
+    for entry in &layered.entries {
+        let finding = |code, message| MappingLintFinding {
+            code,
+            origin: entry.origin.clone(),
+            left: entry.mapping.left.clone(),
+            message,
+        };
+        let mapping = &entry.mapping;
+        if !is_known_section(&mapping.left) {
+            out.push(finding(
+                "unknown_section",
+                format!("left section '{}' is not a known section", mapping.left),
+            ));
+        }
+        if mapping.right.is_empty() {
+            out.push(finding(
+                "empty_right",
+                format!("'{}' maps to no right-hand sections", mapping.left),
+            ));
+        }
+        for right in mapping.right.iter().filter(|r| !is_known_section(r)) {
+            out.push(finding(
+                "unknown_section",
+                format!(
+                    "'{}' maps to '{right}', which is not a known section",
+                    mapping.left
+                ),
+            ));
+        }
+    }
+    for entry in &layered.duplicates {
+        out.push(MappingLintFinding {
+            code: "duplicate_left",
+            origin: entry.origin.clone(),
+            left: entry.mapping.left.clone(),
+            message: format!(
+                "'{}' is mapped more than once in this file; only the last mapping applies",
+                entry.mapping.left
+            ),
+        });
+    }
+    out
+}
+
+/// Elements per config [`validate_key_fields`] inspects for each tag.
+pub const KEY_FIELD_SAMPLE: usize = 50;
+
+/// A configured key field that sampled elements do not carry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyFieldWarning {
+    pub tag: String,
+    pub key: String,
+    /// Layer the key field came from.
+    pub origin: String,
+    pub sampled: usize,
+    pub missing: usize,
+    pub message: String,
+}
+
+/// Check key fields set by mapping files against the first
+/// [`KEY_FIELD_SAMPLE`] elements of their tag in each of `roots`.
+///
+/// Elements without the key child are matched by position, which is the
+/// noise a key field is meant to remove, so a key missing from any sampled
+/// element is reported. Embedded defaults are not checked.
+pub fn validate_key_fields(layered: &LayeredMappings, roots: &[&XmlNode]) -> Vec<KeyFieldWarning> {
+    let mut out = Vec::new();
+    for field in layered.key_fields.values() {
+        if field.key.is_empty() || field.origin == "embedded" {
+            continue;
+        }
+        let mut sampled = Vec::new();
+        for root in roots {
+            let before = sampled.len();
+            collect_tagged(root, &field.tag, before + KEY_FIELD_SAMPLE, &mut sampled);
+        }
+        let missing = sampled
+            .iter()
+            .filter(|node| {
+                node.get_text(&[field.key.as_str()])
+                    .is_none_or(|v| v.trim().is_empty())
+            })
+            .count();
+        if missing == 0 {
+            continue;
+        }
+        out.push(KeyFieldWarning {
+            tag: field.tag.clone(),
+            key: field.key.clone(),
+            origin: field.origin.clone(),
+            sampled: sampled.len(),
+            missing,
+            message: format!(
+                "key field '{}' is missing from {missing} of {} sampled <{}> entries; those are matched by position",
+                field.key,
+                sampled.len(),
+                field.tag
+            ),
+        });
+    }
+    out
+}
+
+/// Push elements tagged `tag` below `node`, depth first, until `out` holds
+/// `limit`.
+fn collect_tagged<'a>(node: &'a XmlNode, tag: &str, limit: usize, out: &mut Vec<&'a XmlNode>) {
+    for child in &node.children {
+        if out.len() >= limit {
+            return;
+        }
+        if child.tag == tag {
+            out.push(child);
+        } else {
+            collect_tagged(child, tag, limit, out);
+        }
+    }
+}
+
 /// Built-in fallback mappings.
 pub fn default_section_mappings() -> Vec<KnownSectionMapping> {
     let embedded = include_str!(concat!(
@@ -56,9 +356,11 @@ pub fn default_section_mappings() -> Vec<KnownSectionMapping> {
 }
 
 fn parse_mappings(raw: &str, path: String) -> Result<Vec<KnownSectionMapping>, MappingLoadError> {
-    let parsed: MappingFile =
-        toml::from_str(raw).map_err(|source| MappingLoadError::Parse { path, source })?;
-    Ok(parsed.mapping)
+    Ok(parse_mapping_file(raw, path)?.mapping)
+}
+
+fn parse_mapping_file(raw: &str, path: String) -> Result<MappingFile, MappingLoadError> {
+    toml::from_str(raw).map_err(|source| MappingLoadError::Parse { path, source })
 }
 
 fn fallback_section_mappings() -> Vec<KnownSectionMapping> {
@@ -163,9 +465,11 @@ fn fallback_section_mappings() -> Vec<KnownSectionMapping> {
 #[cfg(test)]
 mod tests {
     use super::{
-        default_section_mappings, load_section_mappings, parse_mappings, MappingLoadError,
+        default_section_mappings, lint_mappings, load_section_mappings, parse_mappings,
+        resolve_layered_mappings, validate_key_fields, MappingLoadError,
     };
     use std::fs;
+    use xml_diff_core::parse;
 
     #[test]
     fn loads_valid_mappings_file() {
@@ -218,4 +522,190 @@ note = "example"
             .expect("embedded mappings should parse");
         assert!(mappings.iter().any(|m| m.left == "installedpackages"));
     }
+
+    #[test]
+    fn layers_system_dir_and_user_file_over_defaults() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let system = dir.path().join("system");
+        fs::create_dir(&system).expect("mkdir");
+        fs::write(
+            system.join("sections.toml"),
+            r#"
+[[mapping]]
+left = "aliases"
+right = ["Alias"]
+category = "firewall"
+note = "system override"
+
+[[mapping]]
+left = "foo"
+right = ["bar"]
+category = "test"
+note = "system addition"
+"#,
+        )
+        .expect("write system");
+        let user = dir.path().join("user.toml");
+        fs::write(
+            &user,
+            r#"
+[[mapping]]
+left = "foo"
+right = ["baz"]
+category = "test"
+note = "user override"
+"#,
+        )
+        .expect("write user");
+
+        let layered = resolve_layered_mappings(Some(&system), Some(&user)).expect("resolve");
+        let defaults = default_section_mappings();
+        assert_eq!(layered.entries.len(), defaults.len() + 1);
+        let aliases = layered
+            .entries
+            .iter()
+            .position(|e| e.mapping.left == "aliases")
+            .expect("aliases");
+        assert_eq!(
+            Some(aliases),
+            defaults.iter().position(|m| m.left == "aliases")
+        );
+        assert_eq!(layered.entries[aliases].mapping.note, "system override");
+        let foo = layered.entries.last().expect("foo");
+        assert_eq!(foo.mapping.right, vec!["baz"]);
+        assert_eq!(foo.origin, format!("file:{}", user.display()));
+        assert_eq!(layered.sources.len(), 3);
+        assert_eq!(
+            layered.source_label(),
+            format!(
+                "file:{}+file:{}",
+                system.join("sections.toml").display(),
+                user.display()
+            )
+        );
+    }
+
+    #[test]
+    fn skips_system_dir_without_sections_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let layered = resolve_layered_mappings(Some(dir.path()), None).expect("resolve");
+        assert_eq!(layered.sources, vec!["embedded"]);
+        assert!(resolve_layered_mappings(None, Some(&dir.path().join("missing.toml"))).is_err());
+    }
+
+    #[test]
+    fn embedded_mappings_lint_clean() {
+        let layered = resolve_layered_mappings(None, None).expect("resolve");
+        assert_eq!(lint_mappings(&layered), Vec::new());
+    }
+
+    #[test]
+    fn lint_reports_unknown_sections_empty_right_and_duplicates() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let user = dir.path().join("user.toml");
+        fs::write(
+            &user,
+            r#"
+[[mapping]]
+left = "widgetz"
+right = []
+category = "test"
+note = "typo"
+
+[[mapping]]
+left = "cron"
+right = ["Cron", "cronjobs"]
+category = "system"
+note = "first"
+
+[[mapping]]
+left = "cron"
+right = ["cron"]
+category = "system"
+note = "second"
+
+[[mapping]]
+left = "vlans"
+right = ["vlan_list"]
+category = "network"
+note = "bad target"
+"#,
+        )
+        .expect("write user");
+        let layered = resolve_layered_mappings(None, Some(&user)).expect("resolve");
+        let findings: Vec<(&str, String)> = lint_mappings(&layered)
+            .into_iter()
+            .map(|f| (f.code, f.message))
+            .collect();
+        assert_eq!(
+            findings,
+            vec![
+                (
+                    "unknown_section",
+                    "left section 'widgetz' is not a known section".to_string()
+                ),
+                (
+                    "empty_right",
+                    "'widgetz' maps to no right-hand sections".to_string()
+                ),
+                (
+                    "unknown_section",
+                    "'vlans' maps to 'vlan_list', which is not a known section".to_string()
+                ),
+                (
+                    "duplicate_left",
+                    "'cron' is mapped more than once in this file; only the last mapping applies"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn key_fields_layer_over_defaults_and_empty_key_disables() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let user = dir.path().join("user.toml");
+        fs::write(
+            &user,
+            r#"
+[key_fields]
+staticmap = "mac"
+alias = ""
+"#,
+        )
+        .expect("write user");
+
+        let layered = resolve_layered_mappings(None, Some(&user)).expect("resolve");
+        assert_eq!(layered.entries.len(), default_section_mappings().len());
+        let keys = layered.key_fields_map();
+        assert_eq!(keys.get("staticmap").map(String::as_str), Some("mac"));
+        assert_eq!(keys.get("rule").map(String::as_str), Some("tracker"));
+        assert!(!keys.contains_key("alias"));
+        assert_eq!(
+            layered.key_fields["staticmap"].origin,
+            format!("file:{}", user.display())
+        );
+    }
+
+    #[test]
+    fn warns_when_configured_key_is_missing_from_sampled_entries() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let user = dir.path().join("user.toml");
+        fs::write(&user, "[key_fields]\nstaticmap = \"mac\"\nitem = \"id\"\n").expect("write user");
+        let left = parse(
+            br#"<pfsense><dhcpd><lan><staticmap><mac>aa</mac></staticmap><staticmap><ipaddr>10.0.0.2</ipaddr></staticmap></lan></dhcpd><rule/></pfsense>"#,
+        )
+        .expect("parse");
+        let right = parse(br#"<opnsense><dhcpd><lan><staticmap><mac> </mac></staticmap></lan></dhcpd></opnsense>"#)
+            .expect("parse");
+
+        let layered = resolve_layered_mappings(None, Some(&user)).expect("resolve");
+        let warnings = validate_key_fields(&layered, &[&left, &right]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].sampled, warnings[0].missing), (3, 2));
+        assert_eq!(
+            warnings[0].message,
+            "key field 'mac' is missing from 2 of 3 sampled <staticmap> entries; those are matched by position"
+        );
+    }
 }
@@ -0,0 +1,543 @@
+//! Structural upgrades for m0n0wall and old pfSense configs.
+//!
+//! pfSense upgrades a restored config step by step on boot, so a 2.4-era or
+//! m0n0wall export still restores there, but the conversion expects the
+//! modern layout. [`upgrade`] applies the structural changes that matter
+//! for conversion to any config older than [`MODERN_VERSION`]:
+//!
+//! 1. **Root** — `<m0n0wall>` becomes `<pfsense>`
+//! 2. **Admin user** — The single `<system><username>`/`<password>` login
+//!    becomes an `admin` user in an `admins` group
+//! 3. **SSH** — `<system><enablesshd>`, `<sshport>`, and `<sshdkeyonly>`
+//!    move under `<system><ssh>`
+//! 4. **Outbound NAT** — `<nat><advancedoutbound>` becomes
+//!    `<nat><outbound>` with a `<mode>`
+//! 5. **Port forwards** — `<external-address>`/`<external-port>` become a
+//!    `<destination>`, with `<source><any/>`
+//! 6. **Gateways** — An IP address in `<interfaces><X><gateway>` becomes a
+//!    named `<gateway_item>` (`WANGW`); static routes pointing at an address
+//!    follow it
+//!
+//! Every change is listed as a [`LegacyUpgrade`]. Old sections the shim
+//! does not rewrite (pre-2.0 IPsec tunnels, the old traffic shaper, proxy
+//! ARP, PPTP server) are kept as they are and listed in
+//! [`LegacyReport::unsupported`].
+
+use std::net::IpAddr;
+
+use serde::Serialize;
+use thiserror::Error;
+use xml_diff_core::XmlNode;
+
+/// Configs with a lower `<version>` are upgraded.
+pub const MODERN_VERSION: &str = "21.0";
+
+/// Oldest `<version>` the upgrades cover.
+pub const MINIMUM_VERSION: &str = "1.6";
+
+/// Why a legacy config cannot be upgraded.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LegacyError {
+    #[error("config version {version} is too old to convert; the oldest supported version is {MINIMUM_VERSION}. Restore it on pfSense first and export the upgraded config")]
+    TooOld { version: String },
+    #[error("<{root}> config has no readable <version>; cannot tell which upgrades apply")]
+    MissingVersion { root: String },
+}
+
+/// One structural change made to a legacy config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LegacyUpgrade {
+    /// Path of the changed element (`nat/rule[2]`).
+    pub path: String,
+    pub message: String,
+}
+
+/// What [`upgrade`] did to a config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LegacyReport {
+    /// `<version>` the config had, when it was upgraded.
+    pub from_version: Option<String>,
+    pub upgrades: Vec<LegacyUpgrade>,
+    /// Old-layout content left as is.
+    pub unsupported: Vec<LegacyUpgrade>,
+}
+
+/// Whether `root` is a m0n0wall config or a pfSense config older than
+/// [`MODERN_VERSION`].
+pub fn is_legacy(root: &XmlNode) -> bool {
+    match root.tag.as_str() {
+        "m0n0wall" => true,
+        "pfsense" => config_version(root).is_some_and(|v| v < parse_version(MODERN_VERSION)),
+        _ => false,
+    }
+}
+
+/// Bring a legacy config to the modern pfSense layout in place.
+///
+/// Configs that are not legacy are left alone and yield an empty report.
+///
+/// # Errors
+///
+/// Returns error if a m0n0wall config has no readable version, or the
+/// version is older than [`MINIMUM_VERSION`].
+pub fn upgrade(root: &mut XmlNode) -> Result<LegacyReport, LegacyError> {
+    if !is_legacy(root) {
+        return Ok(LegacyReport::default());
+    }
+    let Some(version) = config_version(root) else {
+        return Err(LegacyError::MissingVersion {
+            root: root.tag.clone(),
+        });
+    };
+    let raw = root.get_text(&["version"]).unwrap_or_default().trim();
+    if version < parse_version(MINIMUM_VERSION) {
+        return Err(LegacyError::TooOld {
+            version: raw.to_string(),
+        });
+    }
+    let mut report = LegacyReport {
+        from_version: Some(raw.to_string()),
+        ..LegacyReport::default()
+    };
+
+    if root.tag == "m0n0wall" {
+        root.tag = "pfsense".to_string();
+        note(&mut report.upgrades, "", "renamed m0n0wall root to pfsense");
+    }
+    upgrade_admin_user(root, &mut report.upgrades);
+    upgrade_ssh(root, &mut report.upgrades);
+    upgrade_outbound_nat(root, &mut report.upgrades);
+    upgrade_port_forwards(root, &mut report.upgrades);
+    upgrade_gateways(root, &mut report.upgrades);
+    list_unsupported(root, &mut report.unsupported);
+    Ok(report)
+}
+
+fn upgrade_admin_user(root: &mut XmlNode, out: &mut Vec<LegacyUpgrade>) {
+    let Some(system) = child_mut(root, "system") else {
+        return;
+    };
+    if system.get_child("user").is_some() {
+        return;
+    }
+    let Some(username) = text(system, "username").map(ToString::to_string) else {
+        return;
+    };
+    let hash = text(system, "password").unwrap_or_default().to_string();
+    system
+        .children
+        .retain(|c| c.tag != "username" && c.tag != "password");
+
+    if system.get_child("group").is_none() {
+        let mut group = XmlNode::new("group");
+        push_text(&mut group, "name", "admins");
+        push_text(&mut group, "description", "System Administrators");
+        push_text(&mut group, "scope", "system");
+        push_text(&mut group, "gid", "1999");
+        push_text(&mut group, "member", "0");
+        push_text(&mut group, "priv", "page-all");
+        system.children.push(group);
+    }
+    let mut user = XmlNode::new("user");
+    push_text(&mut user, "name", &username);
+    push_text(&mut user, "descr", "System Administrator");
+    push_text(&mut user, "scope", "system");
+    push_text(&mut user, "groupname", "admins");
+    push_text(&mut user, "md5-hash", &hash);
+    push_text(&mut user, "uid", "0");
+    push_text(&mut user, "priv", "user-shell-access");
+    system.children.push(user);
+    note(
+        out,
+        "system/user",
+        &format!("created user '{username}' from system/username and system/password"),
+    );
+}
+
+fn upgrade_ssh(root: &mut XmlNode, out: &mut Vec<LegacyUpgrade>) {
+    let Some(system) = child_mut(root, "system") else {
+        return;
+    };
+    let moved = [
+        ("enablesshd", "enable"),
+        ("sshport", "port"),
+        ("sshdkeyonly", "sshdkeyonly"),
+    ];
+    if system.get_child("ssh").is_some()
+        || !moved.iter().any(|(old, _)| system.get_child(old).is_some())
+    {
+        return;
+    }
+    let mut ssh = XmlNode::new("ssh");
+    for (old, new) in moved {
+        let Some(node) = system.get_child(old) else {
+            continue;
+        };
+        let value = match old {
+            "sshport" => node.text.clone().unwrap_or_default(),
+            _ => "enabled".to_string(),
+        };
+        push_text(&mut ssh, new, &value);
+        note(
+            out,
+            &format!("system/{old}"),
+            &format!("moved to system/ssh/{new}"),
+        );
+    }
+    system
+        .children
+        .retain(|c| !moved.iter().any(|(old, _)| c.tag == *old));
+    system.children.push(ssh);
+}
+
+fn upgrade_outbound_nat(root: &mut XmlNode, out: &mut Vec<LegacyUpgrade>) {
+    let Some(nat) = child_mut(root, "nat") else {
+        return;
+    };
+    if nat.get_child("outbound").is_some() {
+        return;
+    }
+    let Some(idx) = nat
+        .children
+        .iter()
+        .position(|c| c.tag == "advancedoutbound")
+    else {
+        return;
+    };
+    let outbound = &mut nat.children[idx];
+    outbound.tag = "outbound".to_string();
+    let mode = if outbound.get_child("enable").is_some() {
+        "advanced"
+    } else {
+        "automatic"
+    };
+    outbound.children.retain(|c| c.tag != "enable");
+    let mut mode_node = XmlNode::new("mode");
+    mode_node.text = Some(mode.to_string());
+    outbound.children.insert(0, mode_node);
+    note(
+        out,
+        "nat/advancedoutbound",
+        &format!("renamed to nat/outbound with mode {mode}"),
+    );
+}
+
+fn upgrade_port_forwards(root: &mut XmlNode, out: &mut Vec<LegacyUpgrade>) {
+    let Some(nat) = child_mut(root, "nat") else {
+        return;
+    };
+    let count = nat.children.iter().filter(|c| c.tag == "rule").count();
+    let mut nth = 0;
+    for rule in nat.children.iter_mut().filter(|c| c.tag == "rule") {
+        nth += 1;
+        if rule.get_child("destination").is_some()
+            || (rule.get_child("external-address").is_none()
+                && rule.get_child("external-port").is_none())
+        {
+            continue;
+        }
+        let mut destination = XmlNode::new("destination");
+        match text(rule, "external-address") {
+            Some(address) => push_text(&mut destination, "address", address),
+            None => {
+                let interface = text(rule, "interface").unwrap_or("wan").to_string();
+                push_text(&mut destination, "network", &format!("{interface}ip"));
+            }
+        }
+        if let Some(port) = text(rule, "external-port") {
+            push_text(&mut destination, "port", port);
+        }
+        rule.children
+            .retain(|c| c.tag != "external-address" && c.tag != "external-port");
+        if rule.get_child("source").is_none() {
+            let mut source = XmlNode::new("source");
+            source.children.push(XmlNode::new("any"));
+            rule.children.push(source);
+        }
+        rule.children.push(destination);
+        note(
+            out,
+            &indexed("nat/rule", nth, count),
+            "moved external-address/external-port to destination",
+        );
+    }
+}
+
+fn upgrade_gateways(root: &mut XmlNode, out: &mut Vec<LegacyUpgrade>) {
+    // (interface, address) for every interface gateway given as an address
+    let mut addressed = Vec::new();
+    if let Some(interfaces) = child_mut(root, "interfaces") {
+        for iface in &mut interfaces.children {
+            let Some(ip) = text(iface, "gateway").and_then(|g| g.parse::<IpAddr>().ok()) else {
+                continue;
+            };
+            let name = format!("{}GW", iface.tag.to_ascii_uppercase());
+            set_text(iface, "gateway", &name);
+            addressed.push((iface.tag.clone(), ip, name));
+        }
+    }
+    let mut routes_to = Vec::new();
+    if let Some(routes) = child_mut(root, "staticroutes") {
+        let count = routes.children.iter().filter(|c| c.tag == "route").count();
+        for (idx, route) in routes
+            .children
+            .iter_mut()
+            .filter(|c| c.tag == "route")
+            .enumerate()
+        {
+            let Some(ip) = text(route, "gateway").and_then(|g| g.parse::<IpAddr>().ok()) else {
+                continue;
+            };
+            let interface = text(route, "interface").unwrap_or("wan").to_string();
+            let name = match addressed.iter().find(|(_, addr, _)| *addr == ip) {
+                Some((_, _, name)) => name.clone(),
+                None => {
+                    let name = format!("GW_{}", ip.to_string().replace(['.', ':'], "_"));
+                    if !addressed.iter().any(|(_, _, n)| *n == name) {
+                        addressed.push((interface, ip, name.clone()));
+                    }
+                    name
+                }
+            };
+            set_text(route, "gateway", &name);
+            route.children.retain(|c| c.tag != "interface");
+            routes_to.push((indexed("staticroutes/route", idx + 1, count), name));
+        }
+    }
+    if addressed.is_empty() {
+        return;
+    }
+
+    if root.get_child("gateways").is_none() {
+        root.children.push(XmlNode::new("gateways"));
+    }
+    let gateways = child_mut(root, "gateways").expect("gateways container");
+    for (interface, ip, name) in &addressed {
+        let mut item = XmlNode::new("gateway_item");
+        push_text(&mut item, "interface", interface);
+        push_text(&mut item, "gateway", &ip.to_string());
+        push_text(&mut item, "name", name);
+        push_text(&mut item, "weight", "1");
+        push_text(
+            &mut item,
+            "ipprotocol",
+            if ip.is_ipv4() { "inet" } else { "inet6" },
+        );
+        push_text(
+            &mut item,
+            "descr",
+            &format!("Interface {} Gateway", interface.to_ascii_uppercase()),
+        );
+        gateways.children.push(item);
+        note(
+            out,
+            &format!("interfaces/{interface}/gateway"),
+            &format!("address {ip} became gateway {name}"),
+        );
+    }
+    let wan_v4 = addressed
+        .iter()
+        .find(|(interface, ip, _)| interface == "wan" && ip.is_ipv4());
+    if let (Some((_, _, name)), None) = (wan_v4, gateways.get_child("defaultgw4")) {
+        push_text(gateways, "defaultgw4", name);
+    }
+    for (path, name) in routes_to {
+        note(out, &path, &format!("gateway address replaced by {name}"));
+    }
+}
+
+fn list_unsupported(root: &XmlNode, out: &mut Vec<LegacyUpgrade>) {
+    if root
+        .get_child("ipsec")
+        .is_some_and(|i| i.get_child("tunnel").is_some())
+    {
+        note(
+            out,
+            "ipsec/tunnel",
+            "pre-2.0 IPsec tunnels are not upgraded; recreate them as phase1/phase2 entries",
+        );
+    }
+    for (tag, message) in [
+        (
+            "shaper",
+            "the old traffic shaper is not upgraded; recreate queues and limiters by hand",
+        ),
+        (
+            "proxyarp",
+            "proxy ARP networks are not upgraded; recreate them as proxy ARP virtual IPs",
+        ),
+        (
+            "pptpd",
+            "the PPTP server was removed from pfSense and is not carried",
+        ),
+    ] {
+        if root.get_child(tag).is_some() {
+            note(out, tag, message);
+        }
+    }
+}
+
+fn config_version(root: &XmlNode) -> Option<(u32, u32)> {
+    let raw = root.get_text(&["version"])?.trim();
+    let mut parts = raw.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    Some((major, minor))
+}
+
+fn parse_version(value: &str) -> (u32, u32) {
+    let (major, minor) = value.split_once('.').unwrap_or((value, "0"));
+    (
+        major.parse().unwrap_or_default(),
+        minor.parse().unwrap_or_default(),
+    )
+}
+
+fn indexed(path: &str, nth: usize, count: usize) -> String {
+    if count > 1 {
+        format!("{path}[{nth}]")
+    } else {
+        path.to_string()
+    }
+}
+
+fn note(out: &mut Vec<LegacyUpgrade>, path: &str, message: &str) {
+    out.push(LegacyUpgrade {
+        path: path.to_string(),
+        message: message.to_string(),
+    });
+}
+
+fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
+    node.children.iter_mut().find(|c| c.tag == tag)
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    match child_mut(node, tag) {
+        Some(child) => child.text = Some(value.to_string()),
+        None => push_text(node, tag, value),
+    }
+}
+
+fn push_text(parent: &mut XmlNode, tag: &str, value: &str) {
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    parent.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{is_legacy, upgrade, LegacyError};
+
+    const M0N0WALL: &[u8] = br#"<m0n0wall>
+      <version>1.8</version>
+      <system>
+        <hostname>fw</hostname>
+        <username>admin</username>
+        <password>$1$abc$def</password>
+        <enablesshd/>
+        <sshport>2222</sshport>
+      </system>
+      <interfaces>
+        <wan><if>fxp0</if><ipaddr>203.0.113.2</ipaddr><subnet>29</subnet><gateway>203.0.113.1</gateway></wan>
+        <lan><if>fxp1</if><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan>
+      </interfaces>
+      <staticroutes>
+        <route><interface>lan</interface><network>10.10.0.0/16</network><gateway>192.168.1.254</gateway></route>
+      </staticroutes>
+      <nat>
+        <rule><interface>wan</interface><protocol>tcp</protocol><external-port>80</external-port><target>192.168.1.10</target><local-port>80</local-port></rule>
+        <advancedoutbound><enable/><rule><interface>wan</interface></rule></advancedoutbound>
+      </nat>
+      <shaper><enable/></shaper>
+    </m0n0wall>"#;
+
+    #[test]
+    fn upgrades_m0n0wall_config_to_modern_layout() {
+        let mut root = parse(M0N0WALL).expect("parse");
+        let report = upgrade(&mut root).expect("upgrade");
+
+        assert_eq!(root.tag, "pfsense");
+        assert_eq!(report.from_version.as_deref(), Some("1.8"));
+        assert_eq!(
+            root.get_text(&["system", "user", "md5-hash"]),
+            Some("$1$abc$def")
+        );
+        assert_eq!(root.get_text(&["system", "group", "name"]), Some("admins"));
+        assert!(root
+            .get_child("system")
+            .unwrap()
+            .get_child("username")
+            .is_none());
+        assert_eq!(root.get_text(&["system", "ssh", "enable"]), Some("enabled"));
+        assert_eq!(root.get_text(&["system", "ssh", "port"]), Some("2222"));
+
+        assert_eq!(
+            root.get_text(&["nat", "outbound", "mode"]),
+            Some("advanced")
+        );
+        assert_eq!(
+            root.get_text(&["nat", "rule", "destination", "network"]),
+            Some("wanip")
+        );
+        assert_eq!(
+            root.get_text(&["nat", "rule", "destination", "port"]),
+            Some("80")
+        );
+
+        assert_eq!(
+            root.get_text(&["interfaces", "wan", "gateway"]),
+            Some("WANGW")
+        );
+        assert_eq!(root.get_text(&["gateways", "defaultgw4"]), Some("WANGW"));
+        assert_eq!(
+            root.get_text(&["staticroutes", "route", "gateway"]),
+            Some("GW_192_168_1_254")
+        );
+        let items = root
+            .get_child("gateways")
+            .unwrap()
+            .get_children("gateway_item");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].get_text(&["interface"]), Some("lan"));
+
+        assert_eq!(report.unsupported.len(), 1);
+        assert_eq!(report.unsupported[0].path, "shaper");
+    }
+
+    #[test]
+    fn leaves_modern_configs_alone() {
+        let xml = b"<pfsense><version>23.3</version><system><enablesshd/></system></pfsense>";
+        let mut root = parse(xml).expect("parse");
+        assert!(!is_legacy(&root));
+        let report = upgrade(&mut root).expect("upgrade");
+        assert!(report.upgrades.is_empty());
+        assert_eq!(root, parse(xml).expect("parse"));
+    }
+
+    #[test]
+    fn rejects_configs_too_old_or_without_version() {
+        let mut root = parse(b"<m0n0wall><version>1.2</version></m0n0wall>").expect("parse");
+        assert_eq!(
+            upgrade(&mut root),
+            Err(LegacyError::TooOld {
+                version: "1.2".to_string()
+            })
+        );
+        let mut root = parse(b"<m0n0wall><system/></m0n0wall>").expect("parse");
+        assert_eq!(
+            upgrade(&mut root),
+            Err(LegacyError::MissingVersion {
+                root: "m0n0wall".to_string()
+            })
+        );
+    }
+}
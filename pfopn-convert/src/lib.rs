@@ -14,9 +14,13 @@
 //!
 //! - [`detect`] — Auto-detect platform (pfSense/OPNsense) and version
 //! - [`backend_detect`] — Detect DHCP backend (ISC vs Kea)
+//! - [`dhcp_plan`] — Per-interface DHCP outcome under each backend choice
 //! - [`plugin_detect`] — Identify installed plugins and their status
 //! - [`scan`] — Assess migration readiness and compatibility
+//! - [`defaults_diff`] — What a config changed from its factory defaults
+//! - [`readiness`] — Weighted per-feature readiness matrix for `migrate-check`
 //! - [`analyze`] — Analyze diff results for actionable recommendations
+//! - [`alias_members`] — Set-based comparison and merging of alias members
 //!
 //! ## Transformation
 //!
@@ -26,32 +30,82 @@
 //!   - DHCP backend migration (ISC → Kea)
 //!   - Interface assignments and references
 //!   - Firewall rules, NAT, aliases, routes
+//! - [`model`] — Typed read/write views over rules, aliases, static maps, OpenVPN, and WireGuard peers
 //! - [`merge`] — Intelligent merging of configurations with dependency transfer
+//! - [`pipeline`] — End-to-end conversion builder returning structured results
+//! - [`batch`] — Parallel conversion of many configs from a manifest or directory
+//! - [`target_prune`] — Removal of sections the target platform cannot hold
 //!
 //! ## Validation
 //!
 //! - [`verify`] — Main verification orchestration
+//! - [`verify_identity`] — Duplicate refids, alias/user/gateway names, uids, and static MACs
 //! - [`verify_interfaces`] — Interface reference validation
+//! - [`verify_link_settings`] — Interface MTU and forced media the target NIC may reject
+//! - [`verify_ipsec_algorithms`] — Weak or deprecated IPsec proposal algorithms
 //! - [`verify_nat`] — NAT configuration validation
 //! - [`verify_bridges`] — Bridge interface validation
+//! - [`verify_certs`] — Certificate, key, and CA chain validation
+//! - [`verify_codes`] — Stable machine-readable codes for verify findings
+//! - [`verify_dhcp`] — DHCP pool, reservation, and subnet consistency (ISC and Kea)
+//! - [`verify_dhcp_relay`] — DHCP relay interface and destination validation
+//! - [`verify_exposure`] — Services and rules reachable from WAN, before vs after conversion
+//! - [`verify_fix`] — `verify --fix` remediation of schedules, interfaces, booleans, and UUIDs
+//! - [`verify_netboot`] — PXE / netboot DHCP settings the conversion dropped
+//! - [`verify_openvpn`] — OpenVPN compression, ciphers, and topology the target rejects
+//! - [`verify_openvpn_export`] — What each OpenVPN server still needs for client export
 //! - [`verify_wireguard`] — WireGuard VPN validation
+//! - [`verify_ppps`] — PPP link options the platform ignores
 //! - [`verify_rule_dupes`] — Duplicate firewall rule detection
 //! - [`verify_rule_refs`] — Firewall rule reference validation
+//! - [`rule_equivalence`] — Semantic comparison of source and converted rulesets
+//! - [`orphans`] — Unreferenced certs, CAs, aliases, gateways, and schedules
 //! - [`verify_profile`] — Platform-specific profile validation
+//! - [`baseline_check`] — Target baseline sanity checks before conversion
+//! - [`verify_routes`] — Static route gateway and subnet validation
+//! - [`verify_shellcmds`] — Boot-time shell commands listed for security review
+//! - [`verify_suppress`] — Suppressions file of accepted findings and `--update-baseline`
+//! - [`verify_tunnel_pairs`] — Both ends of IPsec tunnels between devices in one batch
+//! - [`verify_values`] — IP, CIDR, MAC, port, and hostname formats
+//! - [`verify_uuid_refs`] — OPNsense model UUID references and duplicates
 //!
 //! ## Reporting
 //!
 //! - [`report`] — Terminal-friendly colored diff output
+//! - [`report_template`] — User-supplied minijinja templates for report output
 //! - [`sections_report`] — Section-level analysis and mapping hints
 //! - [`conversion_summary`] — Post-conversion summary statistics
 //! - [`inspect`] — Configuration tree visualization
+//! - [`graph`] — Reference graph of config objects (DOT/JSON export)
+//! - [`fingerprint`] — Anonymized per-section hashes for drift detection
+//! - [`capabilities`] — Supported section transforms and per-config coverage
 //!
 //! ## Utilities
 //!
+//! - [`canonical`] — Canonical per-platform XML writer options
+//! - [`combine`] — Entity-level combine of two same-platform configs
+//! - [`convert_policy`] — Per-section ignore/copy/force policy for convert
+//! - [`diagnostics`] — Structured warnings collected during transforms
+//! - [`edit`] — Set, delete, and add nodes by path query
+//! - [`export`] — Lossless JSON/YAML tree documents for `export` and `import`
+//! - [`encoding`] — Latin-1 bytes, double-encoded entities, and control characters in source text
+//! - [`extract`] — Minimal config holding one feature and its dependencies
 //! - [`known_mappings`] — Known section name mappings between platforms
+//! - [`legacy`] — Structural upgrades for m0n0wall and old pfSense configs
+//! - [`opnsense_editions`] — OPNsense Business Edition-only plugins and edition evidence
 //! - [`plugin_matrix`] — Plugin compatibility matrix
+//! - [`plus_features`] — pfSense Plus-only settings and edition evidence
+//! - [`privilege_map`] — GUI privilege translation table
 //! - [`profile`] — Platform version profiles
+//! - [`provenance`] — Revision stamp and hidden provenance marker on converted output
+//! - [`progress`] — Per-stage timings and `--progress` reporting
+//! - `remote` — Fetch/push `config.xml` over SSH or the OPNsense API (feature `remote`)
 //! - [`section`] — Section metadata and key field definitions
+//! - [`simulate`] — Trace a packet through NAT and filter rules
+//! - [`site_map`] — Fleet site addresses for rewriting tunnel peers in `batch`
+//! - [`synthetic`] — Generated large configs for benchmarks and regression tests
+//! - [`tunable_map`] — System tunable (sysctl) compatibility table
+//! - [`uuid_registry`] — Deterministic OPNsense model UUIDs and reference rewriting
 //! - [`interface_guard`] — Interface compatibility checks
 //!
 //! # Workflow
@@ -88,32 +142,89 @@
 //! This library uses `xml-diff-core` for generic XML parsing, diffing, and tree
 //! manipulation. All firewall-specific logic is contained in this crate.
 
+pub mod alias_members;
 pub mod analyze;
 pub mod backend_detect;
+pub mod baseline_check;
+pub mod batch;
+pub mod canonical;
+pub mod capabilities;
+pub mod combine;
 pub mod conversion_summary;
+pub mod convert_policy;
+pub mod defaults_diff;
 pub mod detect;
+pub mod dhcp_plan;
+pub mod diagnostics;
+pub mod edit;
+pub mod encoding;
+pub mod export;
+pub mod extract;
+pub mod fingerprint;
+pub mod graph;
 pub mod inspect;
 pub mod interface_guard;
 pub mod ipsec_dependencies;
 pub mod known_mappings;
+pub mod legacy;
 pub mod merge;
 pub mod migrate_check;
+pub mod model;
 pub mod openvpn_dependencies;
+pub mod opnsense_editions;
+pub mod orphans;
+pub mod pipeline;
 pub mod plugin_detect;
 pub mod plugin_matrix;
+pub mod plus_features;
+pub mod privilege_map;
 pub mod profile;
+pub mod progress;
+pub mod provenance;
+pub mod readiness;
+#[cfg(feature = "remote")]
+pub mod remote;
 pub mod report;
+pub mod report_template;
+pub mod rule_equivalence;
 pub mod scan;
+mod scan_metrics;
+mod scan_packages;
 mod scan_plugins;
 pub mod section;
 pub mod sections_report;
+pub mod simulate;
+pub mod site_map;
+pub mod synthetic;
+pub mod target_prune;
 pub mod transform;
+pub mod tunable_map;
+pub mod uuid_registry;
 pub mod verify;
 pub mod verify_bridges;
+pub mod verify_certs;
+pub mod verify_codes;
+pub mod verify_dhcp;
+pub mod verify_dhcp_relay;
+pub mod verify_exposure;
+pub mod verify_fix;
+pub mod verify_identity;
 pub mod verify_interfaces;
+pub mod verify_ipsec_algorithms;
+pub mod verify_link_settings;
 pub mod verify_nat;
+pub mod verify_netboot;
+pub mod verify_openvpn;
+pub mod verify_openvpn_export;
+pub mod verify_ppps;
 pub mod verify_profile;
+pub mod verify_routes;
 pub mod verify_rule_dupes;
 pub mod verify_rule_refs;
+pub mod verify_shellcmds;
+pub mod verify_suppress;
+pub mod verify_tunnel_pairs;
+pub mod verify_uuid_refs;
+pub mod verify_values;
 pub mod verify_wireguard;
 pub mod wireguard_dependencies;
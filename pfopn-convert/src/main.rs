@@ -2,32 +2,51 @@ use std::fs;
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
-use pfopn_convert::analyze::{analyze, summarize_analysis, AnalysisEntry, RecommendedAction};
+use pfopn_convert::alias_members::{collapse_alias_list_entries, render_alias_changes};
+use pfopn_convert::analyze::{analyze, summarize_analysis, RecommendedAction};
 use pfopn_convert::backend_detect::{backend_transition, detect_dhcp_backend};
-use pfopn_convert::detect::{detect_config, detect_version_info, ConfigFlavor};
-use pfopn_convert::inspect::render_tree;
-use pfopn_convert::known_mappings::{
-    default_section_mappings, load_section_mappings, KnownSectionMapping,
+use pfopn_convert::detect::{
+    detect_config, detect_opnsense_edition, detect_pfsense_edition, detect_version_info,
+    ConfigFlavor,
 };
-use pfopn_convert::merge::{apply_safe_merge, MergeOptions, MergeTarget};
+use pfopn_convert::inspect::{grep_at, grep_tree, render_grep, render_tree, GrepPattern};
+use pfopn_convert::merge::{apply_safe_merge_with_diagnostics, MergeOptions, MergeTarget};
 use pfopn_convert::plugin_detect::detect_plugins;
 use pfopn_convert::report::{
     render_analysis, render_section_inventory, render_section_stats, render_summary, render_text,
+    DiffReport,
 };
-use pfopn_convert::section::{default_key_fields, section_tags};
-use pfopn_convert::sections_report::{
-    build_inventory, extras_json_report, summarize_by_section, SectionStats,
+use pfopn_convert::report_template::render_template_file;
+use pfopn_convert::section::{
+    default_ordered_tags, ignore_category_paths, ignore_category_queries, section_tags,
+};
+use pfopn_convert::sections_report::{build_inventory, extras_json_report, summarize_by_section};
+use xml_diff_core::{
+    diff_with_options, parse_file, write_file, DiffEntry, DiffOptions, PathQuery, XmlNode,
 };
-use xml_diff_core::{diff_with_options, parse_file, write_file, DiffEntry, DiffOptions};
 
+mod batch_cmd;
+mod capabilities_cmd;
 mod cli;
-mod conversion_summary;
+mod combine_cmd;
+mod compare_rules_cmd;
 mod convert;
-mod interface_guard;
+mod convert_wizard;
+mod defaults_diff_cmd;
+mod dhcp_plan_cmd;
+mod edit_cmd;
+mod extract_cmd;
+mod fingerprint_cmd;
+mod graph_cmd;
+mod mappings_cmd;
 mod migrate_check_cmd;
 mod path_guard;
+#[cfg(feature = "remote")]
+mod remote_cmd;
 mod scan_cmd;
-mod target_prune;
+mod simulate_cmd;
+mod tree_cmd;
+mod validate_baseline_cmd;
 mod verify_cmd;
 
 use cli::{Cli, Command, DiffArgs, InspectArgs, MergeTo, OutputFormat, SectionsArgs};
@@ -42,26 +61,75 @@ fn main() -> Result<()> {
         Command::Scan(args) => scan_cmd::run_scan(args),
         Command::Verify(args) => verify_cmd::run_verify(args),
         Command::MigrateCheck(args) => migrate_check_cmd::run_migrate_check(args),
+        Command::Convert(args) if args.interactive => convert_wizard::run_interactive(args),
         Command::Convert(args) => convert::run_convert(args),
+        Command::Edit(args) => edit_cmd::run_edit(args),
+        Command::Combine(args) => combine_cmd::run_combine(args),
+        Command::Simulate(args) => simulate_cmd::run_simulate(args),
+        Command::CompareRules(args) => compare_rules_cmd::run_compare_rules(args),
+        Command::Graph(args) => graph_cmd::run_graph(args),
+        Command::Extract(args) => extract_cmd::run_extract(args),
+        Command::Fingerprint(args) => fingerprint_cmd::run_fingerprint(args),
+        Command::DefaultsDiff(args) => defaults_diff_cmd::run_defaults_diff(args),
+        Command::Capabilities(args) => capabilities_cmd::run_capabilities(args),
+        Command::Batch(args) => batch_cmd::run_batch(args),
+        Command::ValidateBaseline(args) => validate_baseline_cmd::run_validate_baseline(args),
+        Command::DhcpPlan(args) => dhcp_plan_cmd::run_dhcp_plan(args),
+        Command::Export(args) => tree_cmd::run_export(args),
+        Command::Import(args) => tree_cmd::run_import(args),
+        Command::Mappings(args) => mappings_cmd::run_mappings(args),
+        #[cfg(feature = "remote")]
+        Command::Fetch(args) => remote_cmd::run_fetch(args),
+        #[cfg(feature = "remote")]
+        Command::Push(args) => remote_cmd::run_push(args),
     }
 }
 
 fn run_diff(args: DiffArgs) -> Result<()> {
+    if args.fingerprints {
+        return fingerprint_cmd::run_fingerprint_diff(&args);
+    }
     let left = parse_file(&args.file1)
         .with_context(|| format!("failed to parse {}", args.file1.display()))?;
     let right = parse_file(&args.file2)
         .with_context(|| format!("failed to parse {}", args.file2.display()))?;
 
+    // Values that are not valid queries still apply as literal paths or tags
+    let mut ignore_queries: Vec<PathQuery> = args
+        .ignore
+        .iter()
+        .filter_map(|raw| PathQuery::parse(raw).ok())
+        .collect();
+    ignore_queries
+        .extend(ignore_category_queries(&args.ignore_category).map_err(anyhow::Error::msg)?);
+    let mut ignore_paths = args.ignore;
+    ignore_paths.extend(
+        ignore_category_paths(&args.ignore_category)
+            .map_err(anyhow::Error::msg)?
+            .into_iter()
+            .map(ToString::to_string),
+    );
     let opts = DiffOptions {
         include_identical: args.verbose,
-        ignore_paths: args.ignore,
-        key_fields: default_key_fields(),
+        ignore_paths,
+        ignore_queries,
+        key_fields: mappings_cmd::resolve_key_fields(
+            args.mappings_file.as_deref(),
+            args.mappings_dir.as_deref(),
+            &[&left, &right],
+        ),
+        ordered_tags: default_ordered_tags(),
+        detect_relocations: true,
         ..DiffOptions::default()
     };
 
     let mut entries = diff_with_options(&left, &right, &opts);
+    let mut alias_changes = collapse_alias_list_entries(&mut entries, &left, &right);
     if let Some(section) = &args.section {
         entries = filter_section(entries, section);
+        if !section_covers_aliases(section) {
+            alias_changes.clear();
+        }
     }
 
     let analysis = analyze(&entries);
@@ -71,9 +139,9 @@ fn run_diff(args: DiffArgs) -> Result<()> {
     let transition = backend_transition(&left_backend, &right_backend);
 
     if args.strict
-        && analysis
-            .iter()
-            .any(|a| a.action == RecommendedAction::ConflictManual)
+        && analysis.iter().any(|a| {
+            a.action == RecommendedAction::ConflictManual && a.confidence >= args.strict_confidence
+        })
     {
         bail!("strict mode failed: manual conflicts detected");
     }
@@ -94,11 +162,17 @@ fn run_diff(args: DiffArgs) -> Result<()> {
             transfer_users: !args.no_transfer_users,
             transfer_certs: !args.no_transfer_certs,
             transfer_cas: !args.no_transfer_cas,
+            rule_placement: args.rule_placement,
+            ..MergeOptions::default()
         };
 
-        let merged = apply_safe_merge(&left, &right, &entries, target, merge_options)
-            .with_context(|| "failed while applying safe merge actions")?;
-        write_file(&merged, &out_path)
+        let merged =
+            apply_safe_merge_with_diagnostics(&left, &right, &entries, target, merge_options)
+                .with_context(|| "failed while applying safe merge actions")?;
+        for diagnostic in &merged.diagnostics {
+            eprintln!("{diagnostic}");
+        }
+        write_file(&merged.tree, &out_path)
             .with_context(|| format!("failed to write output XML {}", out_path.display()))?;
     }
 
@@ -108,6 +182,9 @@ fn run_diff(args: DiffArgs) -> Result<()> {
             left_backend.mode, right_backend.mode, transition
         );
         println!("{}", render_summary(&entries));
+        if !alias_changes.is_empty() {
+            println!("alias_member_changes={}", alias_changes.len());
+        }
         println!("{}", summarize_analysis(&analysis));
         if args.section_summary {
             println!();
@@ -117,9 +194,28 @@ fn run_diff(args: DiffArgs) -> Result<()> {
         return Ok(());
     }
 
+    if let Some(template) = &args.template {
+        let report = DiffReport {
+            entries,
+            analysis,
+            section_stats,
+            left_backend,
+            right_backend,
+            backend_transition: transition,
+            alias_changes,
+        };
+        print!("{}", render_template_file(template, &report)?);
+        return Ok(());
+    }
+
     match args.format {
         OutputFormat::Text => {
             println!("{}", render_text(&entries));
+            if !alias_changes.is_empty() {
+                println!();
+                println!("Alias Members");
+                println!("{}", render_alias_changes(&alias_changes));
+            }
             println!();
             println!("Action Analysis");
             println!("{}", render_analysis(&analysis));
@@ -137,6 +233,7 @@ fn run_diff(args: DiffArgs) -> Result<()> {
                 left_backend,
                 right_backend,
                 backend_transition: transition,
+                alias_changes,
             };
             println!("{}", serde_json::to_string_pretty(&report)?);
         }
@@ -161,6 +258,15 @@ fn run_inspect(args: InspectArgs) -> Result<()> {
             "type={flavor} version={} version_source={} version_confidence={} dhcp_backend={} backend_reason={}",
             version.value, version.source, version.confidence, backend.mode, backend.reason
         );
+        let edition = detect_pfsense_edition(&node)
+            .map(|e| (e.edition.as_str(), e.evidence))
+            .or_else(|| detect_opnsense_edition(&node).map(|e| (e.edition.as_str(), e.evidence)));
+        if let Some((edition, evidence)) = edition {
+            println!("edition={edition}");
+            for item in evidence {
+                println!("- {item}");
+            }
+        }
     }
 
     if args.plugins {
@@ -177,14 +283,43 @@ fn run_inspect(args: InspectArgs) -> Result<()> {
         }
     }
 
-    let target = if let Some(section) = args.section {
-        node.get_child(&section)
-            .with_context(|| format!("section '{}' not found", section))?
+    if let Some(raw) = &args.grep {
+        let pattern = GrepPattern::new(raw, args.regex)
+            .with_context(|| format!("invalid --grep pattern '{raw}'"))?;
+        let matches = match &args.grep_section {
+            Some(section) => {
+                let query = PathQuery::parse(section)
+                    .with_context(|| format!("invalid section query '{section}'"))?;
+                let locations = query.locate(&node);
+                if locations.is_empty() {
+                    bail!("section '{}' not found", section);
+                }
+                locations
+                    .iter()
+                    .flat_map(|loc| grep_at(&node, loc, &pattern))
+                    .collect()
+            }
+            None => grep_tree(&node, &pattern),
+        };
+        print!("{}", render_grep(&matches));
+        return Ok(());
+    }
+
+    let targets: Vec<&XmlNode> = if let Some(section) = args.section {
+        let query = PathQuery::parse(&section)
+            .with_context(|| format!("invalid section query '{section}'"))?;
+        let matches = query.select(&node);
+        if matches.is_empty() {
+            bail!("section '{}' not found", section);
+        }
+        matches
     } else {
-        &node
+        vec![&node]
     };
 
-    print!("{}", render_tree(target, args.depth));
+    for target in targets {
+        print!("{}", render_tree(target, args.depth));
+    }
     Ok(())
 }
 
@@ -195,7 +330,7 @@ fn run_sections(args: SectionsArgs) -> Result<()> {
         .with_context(|| format!("failed to parse {}", args.file2.display()))?;
 
     let (mappings, mappings_source) =
-        resolve_mappings(args.mappings_file.as_deref(), args.mappings_dir.as_deref());
+        mappings_cmd::resolve_mappings(args.mappings_file.as_deref(), args.mappings_dir.as_deref());
     let inventory = build_inventory(
         &left,
         &right,
@@ -223,30 +358,6 @@ fn run_sections(args: SectionsArgs) -> Result<()> {
     Ok(())
 }
 
-fn resolve_mappings(
-    path: Option<&std::path::Path>,
-    mappings_dir: Option<&std::path::Path>,
-) -> (Vec<KnownSectionMapping>, String) {
-    let chosen = if let Some(path) = path {
-        path.to_path_buf()
-    } else if let Some(dir) = mappings_dir {
-        dir.join("sections.toml")
-    } else {
-        return (default_section_mappings(), "embedded".to_string());
-    };
-
-    match load_section_mappings(&chosen) {
-        Ok(mappings) => (mappings, format!("file:{}", chosen.display())),
-        Err(err) => {
-            eprintln!(
-                "warning: failed to load mappings from {} ({err}); using embedded defaults",
-                chosen.display()
-            );
-            (default_section_mappings(), "embedded".to_string())
-        }
-    }
-}
-
 fn filter_section(entries: Vec<DiffEntry>, section: &str) -> Vec<DiffEntry> {
     let filters: Vec<String> = section_tags(section)
         .map(|tags| tags.iter().map(|tag| format!(".{tag}")).collect())
@@ -263,22 +374,23 @@ fn filter_section(entries: Vec<DiffEntry>, section: &str) -> Vec<DiffEntry> {
         .collect()
 }
 
+/// Whether `--section` keeps alias entries (pfSense `<aliases>` or the
+/// OPNsense alias model).
+fn section_covers_aliases(section: &str) -> bool {
+    let tags: Vec<&str> = section_tags(section).map_or_else(|| vec![section], <[_]>::to_vec);
+    tags.iter()
+        .any(|tag| matches!(*tag, "aliases" | "OPNsense" | "Firewall" | "Alias"))
+}
+
 fn diff_path(entry: &DiffEntry) -> &str {
     match entry {
         DiffEntry::Identical { path }
         | DiffEntry::Modified { path, .. }
         | DiffEntry::OnlyLeft { path, .. }
         | DiffEntry::OnlyRight { path, .. }
-        | DiffEntry::Structural { path, .. } => path,
+        | DiffEntry::Structural { path, .. }
+        | DiffEntry::Moved { path, .. }
+        | DiffEntry::AttributeChanged { path, .. }
+        | DiffEntry::Relocated { to: path, .. } => path,
     }
 }
-
-#[derive(Debug, serde::Serialize)]
-struct DiffReport {
-    entries: Vec<DiffEntry>,
-    analysis: Vec<AnalysisEntry>,
-    section_stats: Vec<SectionStats>,
-    left_backend: pfopn_convert::backend_detect::BackendDetection,
-    right_backend: pfopn_convert::backend_detect::BackendDetection,
-    backend_transition: String,
-}
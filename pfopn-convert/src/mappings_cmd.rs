@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use pfopn_convert::known_mappings::{
+    default_section_mappings, lint_mappings, resolve_layered_mappings, system_mappings_dir,
+    validate_key_fields, KnownSectionMapping,
+};
+use pfopn_convert::section::default_key_fields;
+use xml_diff_core::XmlNode;
+
+use crate::cli::{MappingsAction, MappingsArgs, MappingsLintArgs, OutputFormat};
+
+pub fn run_mappings(args: MappingsArgs) -> Result<()> {
+    match args.action {
+        MappingsAction::Lint(args) => run_lint(args),
+    }
+}
+
+/// Resolve section mappings for a command: embedded defaults, then the
+/// mappings directory, then the user file.
+///
+/// A layer that fails to load is reported on stderr and the embedded
+/// defaults are used instead, as before layering.
+pub fn resolve_mappings(
+    path: Option<&Path>,
+    mappings_dir: Option<&Path>,
+) -> (Vec<KnownSectionMapping>, String) {
+    let system_dir = mappings_dir.map_or_else(system_mappings_dir, Path::to_path_buf);
+    match resolve_layered_mappings(Some(&system_dir), path) {
+        Ok(layered) => (layered.mappings(), layered.source_label()),
+        Err(err) => {
+            eprintln!("warning: {err}; using embedded defaults");
+            (default_section_mappings(), "embedded".to_string())
+        }
+    }
+}
+
+/// Resolve diff key fields from the same layers as [`resolve_mappings`].
+///
+/// Key fields set by a mappings file are checked against `roots`, and keys
+/// missing from sampled entries are reported on stderr.
+pub fn resolve_key_fields(
+    path: Option<&Path>,
+    mappings_dir: Option<&Path>,
+    roots: &[&XmlNode],
+) -> HashMap<String, String> {
+    let system_dir = mappings_dir.map_or_else(system_mappings_dir, Path::to_path_buf);
+    match resolve_layered_mappings(Some(&system_dir), path) {
+        Ok(layered) => {
+            for warning in validate_key_fields(&layered, roots) {
+                eprintln!("warning: {} ({})", warning.message, warning.origin);
+            }
+            layered.key_fields_map()
+        }
+        Err(err) => {
+            eprintln!("warning: {err}; using default key fields");
+            default_key_fields()
+        }
+    }
+}
+
+fn run_lint(args: MappingsLintArgs) -> Result<()> {
+    let system_dir = args
+        .mappings_dir
+        .clone()
+        .unwrap_or_else(system_mappings_dir);
+    let layered = resolve_layered_mappings(Some(&system_dir), args.mappings_file.as_deref())?;
+    let findings = lint_mappings(&layered);
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&findings)?),
+        OutputFormat::Text => {
+            println!(
+                "mappings: {} from {}; {} finding(s)",
+                layered.entries.len(),
+                layered.source_label(),
+                findings.len()
+            );
+            for finding in &findings {
+                println!(
+                    "- [{}] {} ({})",
+                    finding.code, finding.message, finding.origin
+                );
+            }
+        }
+    }
+    if !findings.is_empty() {
+        bail!("mappings lint failed: {} finding(s)", findings.len());
+    }
+    Ok(())
+}
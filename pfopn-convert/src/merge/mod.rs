@@ -1,14 +1,24 @@
+use std::collections::HashMap;
+
 use thiserror::Error;
 use xml_diff_core::{DiffEntry, XmlNode};
 
+use crate::alias_members;
+use crate::diagnostics::Diagnostics;
+use crate::progress::{StageTimer, StageTiming};
 use crate::transform::{
-    aliases, certs, dhcp, ipsec, openvpn, ppps, section_sync, staticroutes, system_identity,
-    system_users, tailscale, users, wireguard,
+    acme, aliases, certs, dhcp, firmware, gateways, ipsec, laggs, monitoring_agents, nat, ntpd,
+    openvpn, ppp_servers, ppps, section_sync, small_packages, staticroutes, system_groups,
+    system_settings, system_users, tailscale, tunables, users, vlans, webgui, wireguard, wol,
 };
 
 mod openvpn_transfer;
+mod ordering;
 mod pathing;
 
+pub(crate) use ordering::placement_index;
+pub use ordering::RulePlacement;
+
 /// Merge destination side.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MergeTarget {
@@ -19,11 +29,15 @@ pub enum MergeTarget {
 }
 
 /// Merge-time transfer behavior for dependency-backed sections.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MergeOptions {
     pub transfer_users: bool,
     pub transfer_certs: bool,
     pub transfer_cas: bool,
+    /// Print each merge-time transform's timing to stderr as it finishes.
+    pub progress: bool,
+    /// Where inserted rules go among the target's rules.
+    pub rule_placement: RulePlacement,
 }
 
 impl Default for MergeOptions {
@@ -32,6 +46,8 @@ impl Default for MergeOptions {
             transfer_users: true,
             transfer_certs: true,
             transfer_cas: true,
+            progress: false,
+            rule_placement: RulePlacement::default(),
         }
     }
 }
@@ -47,7 +63,21 @@ pub enum MergeError {
     ParentNotFound(String),
 }
 
+/// Merged output tree plus diagnostics reported by merge-time transforms.
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    /// Merged output tree.
+    pub tree: XmlNode,
+    /// Non-fatal issues found while transforming merged sections.
+    pub diagnostics: Diagnostics,
+    /// Time spent inserting source nodes and in each merge-time transform.
+    pub timings: Vec<StageTiming>,
+}
+
 /// Apply safe insert-only actions and return merged output tree.
+///
+/// Diagnostics from merge-time transforms are discarded; use
+/// [`apply_safe_merge_with_diagnostics`] to keep them.
 pub fn apply_safe_merge(
     left: &XmlNode,
     right: &XmlNode,
@@ -55,76 +85,223 @@ pub fn apply_safe_merge(
     target: MergeTarget,
     options: MergeOptions,
 ) -> Result<XmlNode, MergeError> {
+    apply_safe_merge_with_diagnostics(left, right, entries, target, options)
+        .map(|outcome| outcome.tree)
+}
+
+/// Apply safe insert-only actions and return the merged tree with diagnostics.
+pub fn apply_safe_merge_with_diagnostics(
+    left: &XmlNode,
+    right: &XmlNode,
+    entries: &[DiffEntry],
+    target: MergeTarget,
+    options: MergeOptions,
+) -> Result<MergeOutcome, MergeError> {
+    let mut diagnostics = Diagnostics::new();
+    let mut timer = StageTimer::new(options.progress);
     let mut out = match target {
         MergeTarget::Left => left.clone(),
         MergeTarget::Right => right.clone(),
     };
 
+    // Group insertions by parent so each parent path is resolved once, not
+    // once per inserted node (thousands of rules share one `<filter>`).
+    let mut inserts: Vec<(String, String, Vec<&XmlNode>)> = Vec::new();
+    let mut slot_by_parent: HashMap<String, usize> = HashMap::new();
     for entry in entries {
         match (target, entry) {
             (MergeTarget::Right, DiffEntry::OnlyLeft { path, node })
             | (MergeTarget::Left, DiffEntry::OnlyRight { path, node }) => {
                 let parent_path = pathing::split_parent_path(path)
                     .ok_or_else(|| MergeError::UnsupportedPath(path.clone()))?;
-                let parent = if parent_path == left.tag || parent_path == right.tag {
-                    &mut out
+                let normalized_parent = if parent_path == left.tag || parent_path == right.tag {
+                    out.tag.clone()
                 } else {
-                    let normalized_parent =
-                        pathing::normalize_root_path(&parent_path, &out.tag, &left.tag, &right.tag);
-                    pathing::find_node_mut_by_path(&mut out, &normalized_parent)
-                        .ok_or_else(|| MergeError::ParentNotFound(parent_path.clone()))?
+                    pathing::normalize_root_path(&parent_path, &out.tag, &left.tag, &right.tag)
                 };
-                parent.children.push(node.clone());
+                let slot = *slot_by_parent
+                    .entry(normalized_parent.clone())
+                    .or_insert_with(|| {
+                        inserts.push((normalized_parent, parent_path, Vec::new()));
+                        inserts.len() - 1
+                    });
+                inserts[slot].2.push(node);
             }
             _ => {}
         }
     }
+    let inserted_from = match target {
+        MergeTarget::Right => left,
+        MergeTarget::Left => right,
+    };
+    for (normalized_parent, parent_path, nodes) in inserts {
+        let source_parent = pathing::find_node_by_path(inserted_from, &parent_path);
+        let parent = if normalized_parent == out.tag {
+            &mut out
+        } else {
+            pathing::find_node_mut_by_path(&mut out, &normalized_parent)
+                .ok_or(MergeError::ParentNotFound(parent_path))?
+        };
+        ordering::insert_children(
+            parent,
+            source_parent,
+            nodes,
+            &options.rule_placement,
+            &mut diagnostics,
+        );
+    }
 
-    openvpn_transfer::apply_openvpn_dependency_transfer(&mut out, left, right, target, options);
+    for name in alias_members::union_matching_aliases(&mut out, inserted_from) {
+        diagnostics.info("aliases", format!("merged members into alias '{name}'"));
+    }
+    timer.mark("merge/insert");
+    openvpn_transfer::apply_openvpn_dependency_transfer(&mut out, left, right, target, &options);
+    timer.mark("merge/openvpn_dependencies");
     let (source, destination_baseline) = match target {
         MergeTarget::Right => (left, right),
         MergeTarget::Left => (right, left),
     };
     section_sync::sync_shared_top_level_sections(&mut out, source);
+    timer.mark("merge/section_sync");
     match out.tag.as_str() {
         "opnsense" => {
-            system_identity::to_opnsense(&mut out, source, destination_baseline);
+            system_settings::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/system_settings");
+            firmware::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/firmware");
             users::to_opnsense(&mut out, source, destination_baseline);
-            system_users::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/users");
+            system_users::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/system_users");
+            system_groups::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/system_groups");
             aliases::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/aliases");
+            acme::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/acme");
             tailscale::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/tailscale");
+            small_packages::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/small_packages");
+            monitoring_agents::to_opnsense(
+                &mut out,
+                source,
+                destination_baseline,
+                &mut diagnostics,
+            );
+            timer.mark("merge/monitoring_agents");
             openvpn::to_opnsense(&mut out, source, destination_baseline);
-            ppps::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/openvpn");
+            ppps::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/ppps");
+            ppp_servers::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/ppp_servers");
             wireguard::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/wireguard");
             ipsec::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/ipsec");
             staticroutes::to_opnsense(&mut out, source, destination_baseline);
-            dhcp::relay::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/staticroutes");
+            gateways::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/gateways");
+            dhcp::relay::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/dhcp/relay");
+            dhcp::mac_control::to_opnsense(
+                &mut out,
+                source,
+                destination_baseline,
+                &mut diagnostics,
+            );
+            timer.mark("merge/dhcp/mac_control");
+            dhcp::router_adv::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/dhcp/router_adv");
+            wol::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/wol");
+            nat::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/nat");
+            ntpd::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/ntpd");
+            tunables::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/tunables");
+            vlans::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/vlans");
+            laggs::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/laggs");
+            webgui::to_opnsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/webgui");
             certs::to_opnsense(&mut out, source, destination_baseline);
+            timer.mark("merge/certs");
         }
         "pfsense" => {
-            system_identity::to_pfsense(&mut out, source, destination_baseline);
+            system_settings::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/system_settings");
+            firmware::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/firmware");
             users::to_pfsense(&mut out, source, destination_baseline);
-            system_users::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/users");
+            system_users::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/system_users");
+            system_groups::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/system_groups");
             aliases::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/aliases");
             tailscale::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/tailscale");
             openvpn::to_pfsense(&mut out, source, destination_baseline);
-            ppps::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/openvpn");
+            ppps::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/ppps");
+            ppp_servers::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/ppp_servers");
             wireguard::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/wireguard");
             ipsec::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/ipsec");
             staticroutes::to_pfsense(&mut out, source, destination_baseline);
-            dhcp::relay::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/staticroutes");
+            gateways::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/gateways");
+            dhcp::relay::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/dhcp/relay");
+            dhcp::mac_control::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/dhcp/mac_control");
+            dhcp::router_adv::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/dhcp/router_adv");
+            wol::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/wol");
+            nat::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/nat");
+            ntpd::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/ntpd");
+            tunables::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/tunables");
+            vlans::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/vlans");
+            laggs::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/laggs");
+            webgui::to_pfsense(&mut out, source, destination_baseline, &mut diagnostics);
+            timer.mark("merge/webgui");
             certs::to_pfsense(&mut out, source, destination_baseline);
+            timer.mark("merge/certs");
         }
         _ => {}
     }
 
-    Ok(out)
+    Ok(MergeOutcome {
+        tree: out,
+        diagnostics,
+        timings: timer.into_timings(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{apply_safe_merge, MergeOptions, MergeTarget};
-    use xml_diff_core::{diff, parse, DiffEntry};
+    use super::{
+        apply_safe_merge, apply_safe_merge_with_diagnostics, MergeOptions, MergeTarget,
+        RulePlacement,
+    };
+    use crate::section::{default_key_fields, default_ordered_tags};
+    use xml_diff_core::{diff, diff_with_options, parse, DiffEntry, DiffOptions};
 
     #[test]
     fn merges_only_left_nodes_into_right_target() {
@@ -150,6 +327,140 @@ mod tests {
         assert_eq!(items.get_children("item").len(), 2);
     }
 
+    #[test]
+    fn inserts_into_shared_parents_in_diff_order() {
+        let left = parse(
+            br#"<root><items><item><id>1</id></item><item><id>2</id></item><item><id>3</id></item></items><extra/></root>"#,
+        )
+        .expect("left parse");
+        let right =
+            parse(br#"<root><items><item><id>1</id></item></items></root>"#).expect("right parse");
+        let entries = diff(&left, &right);
+
+        let merged = apply_safe_merge(
+            &left,
+            &right,
+            &entries,
+            MergeTarget::Right,
+            MergeOptions::default(),
+        )
+        .expect("merge");
+        let ids: Vec<&str> = merged
+            .get_child("items")
+            .expect("items exists")
+            .get_children("item")
+            .into_iter()
+            .filter_map(|item| item.get_text(&["id"]))
+            .collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+        assert!(merged.get_child("extra").is_some());
+    }
+
+    #[test]
+    fn inserts_ordered_rules_after_their_source_predecessor() {
+        let rules = |trackers: &[&str]| {
+            let body: String = trackers
+                .iter()
+                .map(|t| format!("<rule><tracker>{t}</tracker></rule>"))
+                .collect();
+            parse(format!("<root><rules>{body}<separator/></rules></root>").as_bytes())
+                .expect("parse")
+        };
+        let left = rules(&["1", "2", "3", "4", "5"]);
+        let right = rules(&["2", "4", "9"]);
+        let opts = DiffOptions {
+            key_fields: default_key_fields(),
+            ordered_tags: default_ordered_tags(),
+            ..DiffOptions::default()
+        };
+        let entries = diff_with_options(&left, &right, &opts);
+
+        let merged = apply_safe_merge(
+            &left,
+            &right,
+            &entries,
+            MergeTarget::Right,
+            MergeOptions::default(),
+        )
+        .expect("merge");
+        let rules = merged.get_child("rules").expect("rules");
+        let trackers: Vec<&str> = rules
+            .get_children("rule")
+            .into_iter()
+            .filter_map(|rule| rule.get_text(&["tracker"]))
+            .collect();
+        assert_eq!(trackers, ["1", "2", "3", "4", "5", "9"]);
+        assert_eq!(
+            rules.children.last().map(|c| c.tag.as_str()),
+            Some("separator")
+        );
+    }
+
+    #[test]
+    fn places_inserted_rules_by_policy() {
+        let rules = |descrs: &[&str]| {
+            let body: String = descrs
+                .iter()
+                .map(|d| format!("<rule><tracker>{d}</tracker><descr>{d}</descr></rule>"))
+                .collect();
+            parse(format!("<root><rules><header/>{body}</rules></root>").as_bytes()).expect("parse")
+        };
+        let left = rules(&["a", "x", "y"]);
+        let right = rules(&["a", "b", "c"]);
+        let opts = DiffOptions {
+            key_fields: default_key_fields(),
+            ..DiffOptions::default()
+        };
+        let entries = diff_with_options(&left, &right, &opts);
+        let placed = |placement: RulePlacement| {
+            let merged = apply_safe_merge_with_diagnostics(
+                &left,
+                &right,
+                &entries,
+                MergeTarget::Right,
+                MergeOptions {
+                    rule_placement: placement,
+                    ..MergeOptions::default()
+                },
+            )
+            .expect("merge");
+            let descrs: Vec<String> = merged
+                .tree
+                .get_child("rules")
+                .expect("rules")
+                .get_children("rule")
+                .into_iter()
+                .filter_map(|rule| rule.get_text(&["descr"]).map(str::to_string))
+                .collect();
+            (descrs, merged.diagnostics)
+        };
+
+        let (descrs, _) = placed(RulePlacement::Append);
+        assert_eq!(descrs, ["a", "b", "c", "x", "y"]);
+        let (descrs, _) = placed(RulePlacement::Prepend);
+        assert_eq!(descrs, ["x", "y", "a", "b", "c"]);
+        let (descrs, diagnostics) = placed(RulePlacement::After("b".to_string()));
+        assert_eq!(descrs, ["a", "b", "x", "y", "c"]);
+        assert!(diagnostics.is_empty());
+
+        let (descrs, diagnostics) = placed(RulePlacement::After("missing".to_string()));
+        assert_eq!(descrs, ["a", "b", "c", "x", "y"]);
+        let warning = diagnostics.iter().next().expect("warning");
+        assert!(warning.message.contains("'missing'"), "{}", warning.message);
+    }
+
+    #[test]
+    fn parses_rule_placement_values() {
+        assert_eq!("source".parse(), Ok(RulePlacement::Source));
+        assert_eq!("prepend".parse(), Ok(RulePlacement::Prepend));
+        assert_eq!(
+            "after:Allow LAN".parse(),
+            Ok(RulePlacement::After("Allow LAN".to_string()))
+        );
+        assert!("after:".parse::<RulePlacement>().is_err());
+        assert!("middle".parse::<RulePlacement>().is_err());
+    }
+
     #[test]
     fn transfers_openvpn_cert_dependency_by_default() {
         let left = parse(
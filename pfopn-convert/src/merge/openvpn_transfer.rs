@@ -62,7 +62,7 @@ pub(super) fn apply_openvpn_dependency_transfer(
     left: &XmlNode,
     right: &XmlNode,
     target: MergeTarget,
-    options: MergeOptions,
+    options: &MergeOptions,
 ) {
     // Determine source/target based on merge direction
     let (source, target_tree, to_target) = match target {
@@ -125,14 +125,9 @@ fn transfer_section_by_refids(
         .map(ToOwned::to_owned)
         .collect();
 
-    // Collect all CA/cert nodes from source
-    let source_nodes: Vec<XmlNode> = source
-        .children
-        .iter()
-        .filter(|n| n.tag == section_tag)
-        .filter(|n| n.get_text(&["refid"]).is_some())
-        .cloned()
-        .collect();
+    // Index source CA/cert nodes by refid
+    let source_index = source.index();
+    let source_by_refid = source_index.keyed(section_tag, "refid");
 
     // Transfer each missing dependency
     for missing in missing_ids {
@@ -142,11 +137,11 @@ fn transfer_section_by_refids(
         }
 
         // Find matching node in source and transfer it
-        if let Some(node) = source_nodes
-            .iter()
-            .find(|n| n.get_text(&["refid"]) == Some(missing.as_str()))
+        if let Some(idx) = source_by_refid
+            .get(missing.as_str())
+            .and_then(|positions| positions.first())
         {
-            out.children.push(node.clone());
+            out.children.push(source.children[*idx].clone());
             existing.insert(missing.clone());
         }
     }
@@ -0,0 +1,222 @@
+//! Placement of inserted nodes in ordered sections.
+//!
+//! Most inserted nodes are appended to their parent, but elements listed in
+//! [`ORDERED_ELEMENTS`] (firewall rules) are evaluated in document order, so
+//! where an inserted rule lands changes what it matches. [`RulePlacement`]
+//! picks the spot; inserted rules always keep their relative source order.
+//!
+//! The default, [`RulePlacement::Source`], places each node right after the
+//! element that precedes it in the source, found by its key (`tracker` for
+//! rules); that element is either already in the target or inserted just
+//! before. Nodes first among their siblings in the source go before the
+//! target's first sibling. Nodes without a key, or whose source neighbour is
+//! not in the output (an unkeyed diff paired it with a different element),
+//! are appended.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::section::{ordered_key, ORDERED_ELEMENTS};
+
+/// Where inserted rules go among the target's existing rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RulePlacement {
+    /// After the rule that precedes each one in the source.
+    #[default]
+    Source,
+    /// After the target's last rule.
+    Append,
+    /// Before the target's first rule.
+    Prepend,
+    /// After the target rule with this description; appended when no rule
+    /// has it.
+    After(String),
+}
+
+impl FromStr for RulePlacement {
+    type Err = String;
+
+    /// Parse `source`, `append`, `prepend`, or `after:<descr>`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "source" => Ok(Self::Source),
+            "append" => Ok(Self::Append),
+            "prepend" => Ok(Self::Prepend),
+            _ => match value.strip_prefix("after:") {
+                Some(descr) if !descr.is_empty() => Ok(Self::After(descr.to_string())),
+                _ => Err(format!(
+                    "invalid rule placement '{value}'; expected source, append, prepend, or after:<descr>"
+                )),
+            },
+        }
+    }
+}
+
+/// Position in `children` where new `tag` siblings go under a fixed
+/// placement.
+///
+/// Returns `None` for [`RulePlacement::Source`], which places each node on
+/// its own, and for [`RulePlacement::After`] when no sibling has the
+/// description.
+pub(crate) fn placement_index(
+    children: &[XmlNode],
+    tag: &str,
+    placement: &RulePlacement,
+) -> Option<usize> {
+    match placement {
+        RulePlacement::Source => None,
+        RulePlacement::Append => Some(
+            children
+                .iter()
+                .rposition(|c| c.tag == tag)
+                .map_or(children.len(), |idx| idx + 1),
+        ),
+        RulePlacement::Prepend => Some(
+            children
+                .iter()
+                .position(|c| c.tag == tag)
+                .unwrap_or(children.len()),
+        ),
+        RulePlacement::After(descr) => children
+            .iter()
+            .position(|c| c.tag == tag && c.get_text(&["descr"]) == Some(descr.as_str()))
+            .map(|idx| idx + 1),
+    }
+}
+
+/// Insert `nodes` into `parent`, placing ordered elements by `placement`.
+///
+/// `source_parent` is the node `nodes` came from; without it
+/// [`RulePlacement::Source`] appends every node.
+pub(super) fn insert_children(
+    parent: &mut XmlNode,
+    source_parent: Option<&XmlNode>,
+    nodes: Vec<&XmlNode>,
+    placement: &RulePlacement,
+    diagnostics: &mut Diagnostics,
+) {
+    let (ordered, appended): (Vec<&XmlNode>, Vec<&XmlNode>) = nodes
+        .into_iter()
+        .partition(|node| ordered_key(&node.tag).is_some());
+    parent.children.extend(appended.into_iter().cloned());
+
+    for (tag, key) in ORDERED_ELEMENTS {
+        let group: Vec<&XmlNode> = ordered.iter().copied().filter(|n| n.tag == *tag).collect();
+        if group.is_empty() {
+            continue;
+        }
+        if *placement == RulePlacement::Source {
+            match source_parent {
+                Some(source_parent) => {
+                    place_in_source_order(parent, source_parent, tag, key, &group);
+                }
+                None => parent.children.extend(group.into_iter().cloned()),
+            }
+            continue;
+        }
+        let at = placement_index(&parent.children, tag, placement).unwrap_or_else(|| {
+            if let RulePlacement::After(descr) = placement {
+                diagnostics.warn(
+                    &parent.tag,
+                    format!(
+                        "no <{tag}> described '{descr}' to place {} inserted <{tag}> after; appended them",
+                        group.len()
+                    ),
+                );
+            }
+            placement_index(&parent.children, tag, &RulePlacement::Append)
+                .unwrap_or(parent.children.len())
+        });
+        parent.children.splice(at..at, group.into_iter().cloned());
+    }
+}
+
+/// Rebuild `parent.children` with each `tag` node of `group` after its
+/// source predecessor.
+fn place_in_source_order(
+    parent: &mut XmlNode,
+    source_parent: &XmlNode,
+    tag: &str,
+    key: &str,
+    group: &[&XmlNode],
+) {
+    // Key of the keyed sibling before each source element; `None` for the first.
+    let mut predecessor: HashMap<&str, Option<&str>> = HashMap::new();
+    let mut previous = None;
+    for sibling in source_parent.index().get_children(tag) {
+        if let Some(value) = sibling.get_text(&[key]) {
+            predecessor.entry(value).or_insert(previous);
+            previous = Some(value);
+        }
+    }
+
+    // Group positions in `group` by the key they follow.
+    let mut first = Vec::new();
+    let mut after: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut unplaced = Vec::new();
+    for (idx, node) in group.iter().enumerate() {
+        match node
+            .get_text(&[key])
+            .and_then(|value| predecessor.get(value))
+        {
+            Some(Some(anchor)) => after.entry(*anchor).or_default().push(idx),
+            Some(None) => first.push(idx),
+            None => unplaced.push(idx),
+        }
+    }
+
+    let existing = std::mem::take(&mut parent.children);
+    let mut children = Vec::with_capacity(existing.len() + group.len());
+    let mut first = (!first.is_empty()).then_some(first);
+    for child in existing {
+        let anchor = if child.tag == tag {
+            if let Some(first) = first.take() {
+                push_chain(first, group, key, &mut after, &mut children);
+            }
+            child.get_text(&[key]).and_then(|value| after.remove(value))
+        } else {
+            None
+        };
+        children.push(child);
+        if let Some(next) = anchor {
+            push_chain(next, group, key, &mut after, &mut children);
+        }
+    }
+
+    // Whatever found no anchor in the output goes last, in source order.
+    if let Some(first) = first {
+        push_chain(first, group, key, &mut after, &mut children);
+    }
+    for node in group {
+        let anchor = node
+            .get_text(&[key])
+            .and_then(|value| predecessor.get(value).copied().flatten());
+        if let Some(rest) = anchor.and_then(|value| after.remove(value)) {
+            push_chain(rest, group, key, &mut after, &mut children);
+        }
+    }
+    children.extend(unplaced.into_iter().map(|idx| group[idx].clone()));
+    parent.children = children;
+}
+
+/// Push `start` and, after each node, the nodes anchored to it.
+fn push_chain<'a>(
+    start: Vec<usize>,
+    group: &[&'a XmlNode],
+    key: &str,
+    after: &mut HashMap<&'a str, Vec<usize>>,
+    children: &mut Vec<XmlNode>,
+) {
+    // Explicit stack: a run of thousands of new rules is one long chain.
+    let mut stack: Vec<usize> = start.into_iter().rev().collect();
+    while let Some(idx) = stack.pop() {
+        let node = group[idx];
+        children.push(node.clone());
+        if let Some(next) = node.get_text(&[key]).and_then(|value| after.remove(value)) {
+            stack.extend(next.into_iter().rev());
+        }
+    }
+}
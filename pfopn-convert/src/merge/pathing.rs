@@ -88,6 +88,20 @@ pub(super) fn find_node_mut_by_path<'a>(
     descend(root, &segments[1..])
 }
 
+/// Find a node by following a dot-separated path; the read-only
+/// counterpart of [`find_node_mut_by_path`].
+pub(super) fn find_node_by_path<'a>(root: &'a XmlNode, path: &str) -> Option<&'a XmlNode> {
+    let segments = parse_path(path)?;
+    let (first, rest) = segments.split_first()?;
+    if first.0 != root.tag || first.1 != 1 {
+        return None;
+    }
+    rest.iter().try_fold(root, |node, (tag, idx)| {
+        let pos = nth_tag_child_index(&node.children, tag, *idx)?;
+        Some(&node.children[pos])
+    })
+}
+
 /// Recursively descend through XML tree following path segments.
 ///
 /// Navigates from the current node to its descendants by following each
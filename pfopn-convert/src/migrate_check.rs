@@ -2,8 +2,12 @@ use serde::Serialize;
 use xml_diff_core::XmlNode;
 
 use crate::conversion_summary::{summarize, ConversionSummary};
+use crate::readiness::{
+    build_readiness_matrix, render_readiness_markdown, render_readiness_text, ReadinessMatrix,
+};
 use crate::scan::{build_scan_report_with_version, ScanReport};
 use crate::verify::{build_verify_report_with_version, VerifyReport};
+use crate::verify_codes::FindingCode;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MigrateCheckItem {
@@ -21,6 +25,7 @@ pub struct MigrateCheckReport {
     pub warnings: usize,
     pub summary: ConversionSummary,
     pub items: Vec<MigrateCheckItem>,
+    pub readiness: ReadinessMatrix,
     pub verify: VerifyReport,
     pub scan: ScanReport,
 }
@@ -51,7 +56,7 @@ pub fn build_migrate_check_report_with_version(
         ),
         item(
             "required_sections",
-            !has_issue(&verify, "missing_required_section"),
+            !has_issue(&verify, FindingCode::MissingRequiredSection),
             "system/interfaces/filter baseline present".to_string(),
         ),
         item(
@@ -59,17 +64,23 @@ pub fn build_migrate_check_report_with_version(
             !has_any_issue(
                 &verify,
                 &[
-                    "duplicate_interface_assignment",
-                    "missing_interface_reference",
-                    "missing_gateway_interface",
-                    "missing_route_interface",
+                    FindingCode::DuplicateInterfaceAssignment,
+                    FindingCode::MissingInterfaceReference,
+                    FindingCode::MissingGatewayInterface,
+                    FindingCode::MissingRouteInterface,
                 ],
             ),
             "interface refs and assignments are valid".to_string(),
         ),
         item(
             "bridge_integrity",
-            !has_any_issue(&verify, &["empty_bridge_members", "missing_bridge_member"]),
+            !has_any_issue(
+                &verify,
+                &[
+                    FindingCode::EmptyBridgeMembers,
+                    FindingCode::MissingBridgeMember,
+                ],
+            ),
             "bridge members are valid".to_string(),
         ),
         item(
@@ -77,10 +88,10 @@ pub fn build_migrate_check_report_with_version(
             !has_any_issue(
                 &verify,
                 &[
-                    "missing_alias_reference",
-                    "missing_gateway_reference",
-                    "missing_route_gateway",
-                    "missing_schedule_reference",
+                    FindingCode::MissingAliasReference,
+                    FindingCode::MissingGatewayReference,
+                    FindingCode::MissingRouteGateway,
+                    FindingCode::MissingScheduleReference,
                 ],
             ),
             "rule/route references resolve".to_string(),
@@ -90,16 +101,16 @@ pub fn build_migrate_check_report_with_version(
             !has_any_issue(
                 &verify,
                 &[
-                    "nat_missing_interface",
-                    "nat_missing_associated_rule",
-                    "nat_invalid_outbound_mode",
+                    FindingCode::NatMissingInterface,
+                    FindingCode::NatMissingAssociatedRule,
+                    FindingCode::NatInvalidOutboundMode,
                 ],
             ),
             "nat mode/bindings/associations are valid".to_string(),
         ),
         item(
             "dhcp_integrity",
-            !has_issue(&verify, "dhcp_backend_inconsistent"),
+            !has_issue(&verify, FindingCode::DhcpBackendInconsistent),
             "dhcp backend policy and section layout are consistent".to_string(),
         ),
         item(
@@ -128,6 +139,7 @@ pub fn build_migrate_check_report_with_version(
     ];
 
     let pass = verify.errors == 0 && items.iter().all(|i| i.pass);
+    let readiness = build_readiness_matrix(root, target, &scan, &verify);
     MigrateCheckReport {
         platform: scan.platform.clone(),
         target_platform: target.to_string(),
@@ -136,6 +148,7 @@ pub fn build_migrate_check_report_with_version(
         warnings: verify.warnings,
         summary,
         items,
+        readiness,
         verify,
         scan,
     }
@@ -166,6 +179,36 @@ pub fn render_migrate_check_text(report: &MigrateCheckReport, verbose: bool) ->
         let state = if item.pass { "PASS" } else { "FAIL" };
         out.push(format!("- [{state}] {}: {}", item.id, item.detail));
     }
+    out.push(render_readiness_text(&report.readiness));
+    out.join("\n")
+}
+
+/// Render the report as Markdown, suitable for pasting into a migration plan.
+pub fn render_migrate_check_markdown(report: &MigrateCheckReport) -> String {
+    let mut out = vec![
+        format!(
+            "# Migration check: {} → {}",
+            report.platform, report.target_platform
+        ),
+        String::new(),
+        format!(
+            "**Result:** {} ({} errors, {} warnings)",
+            if report.pass { "PASS" } else { "FAIL" },
+            report.errors,
+            report.warnings
+        ),
+        String::new(),
+        "## Feature readiness".to_string(),
+        String::new(),
+        render_readiness_markdown(&report.readiness),
+        String::new(),
+        "## Checks".to_string(),
+        String::new(),
+    ];
+    for item in &report.items {
+        let state = if item.pass { "x" } else { " " };
+        out.push(format!("- [{state}] `{}`: {}", item.id, item.detail));
+    }
     out.join("\n")
 }
 
@@ -177,26 +220,26 @@ fn item(id: &str, pass: bool, detail: String) -> MigrateCheckItem {
     }
 }
 
-fn has_issue(report: &VerifyReport, code: &str) -> bool {
+fn has_issue(report: &VerifyReport, code: FindingCode) -> bool {
     report.issues.iter().any(|i| i.code == code)
 }
 
 fn has_issue_prefix(report: &VerifyReport, prefix: &str) -> bool {
-    report.issues.iter().any(|i| i.code.starts_with(prefix))
-}
-
-fn has_any_issue(report: &VerifyReport, codes: &[&str]) -> bool {
     report
         .issues
         .iter()
-        .any(|i| codes.iter().any(|code| i.code == *code))
+        .any(|i| i.code.as_str().starts_with(prefix))
+}
+
+fn has_any_issue(report: &VerifyReport, codes: &[FindingCode]) -> bool {
+    report.issues.iter().any(|i| codes.contains(&i.code))
 }
 
 fn count_issue_prefix(report: &VerifyReport, prefix: &str) -> usize {
     report
         .issues
         .iter()
-        .filter(|i| i.code.starts_with(prefix))
+        .filter(|i| i.code.as_str().starts_with(prefix))
         .count()
 }
 
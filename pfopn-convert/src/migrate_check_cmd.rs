@@ -1,10 +1,11 @@
 use anyhow::{bail, Context, Result};
 use pfopn_convert::migrate_check::{
-    build_migrate_check_report_with_version, render_migrate_check_text,
+    build_migrate_check_report_with_version, render_migrate_check_markdown,
+    render_migrate_check_text,
 };
 use xml_diff_core::parse_file;
 
-use crate::cli::{MigrateCheckArgs, OutputFormat, ScanTarget};
+use crate::cli::{MigrateCheckArgs, MigrateCheckFormat, ScanTarget};
 
 pub fn run_migrate_check(args: MigrateCheckArgs) -> Result<()> {
     let node = parse_file(&args.file)
@@ -18,8 +19,11 @@ pub fn run_migrate_check(args: MigrateCheckArgs) -> Result<()> {
     );
 
     match args.format {
-        OutputFormat::Text => println!("{}", render_migrate_check_text(&report, args.verbose)),
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        MigrateCheckFormat::Text => {
+            println!("{}", render_migrate_check_text(&report, args.verbose))
+        }
+        MigrateCheckFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        MigrateCheckFormat::Markdown => println!("{}", render_migrate_check_markdown(&report)),
     }
 
     if !report.pass {
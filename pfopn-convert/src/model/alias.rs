@@ -0,0 +1,193 @@
+use xml_diff_core::XmlNode;
+
+use super::{expect_tag, set_text, text, ModelError};
+use crate::transform::booleans::{flag_set, numeric};
+
+/// Where an alias keeps its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasFormat {
+    /// `<aliases><alias>` with space-separated `<address>` and `||`-separated
+    /// `<detail>` (pfSense, older OPNsense).
+    Legacy,
+    /// `<OPNsense><Firewall><Alias><aliases><alias uuid>` with
+    /// newline-separated `<content>`.
+    Model,
+}
+
+/// One firewall alias in either layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alias {
+    node: XmlNode,
+    pub format: AliasFormat,
+    /// Model `uuid` attribute.
+    pub uuid: Option<String>,
+    pub name: Option<String>,
+    /// `host`, `network`, `port`, `url`, ...
+    pub kind: Option<String>,
+    pub members: Vec<String>,
+    /// Per-member descriptions, aligned with `members` (legacy only).
+    pub details: Vec<String>,
+    /// `<descr>` or `<description>`.
+    pub description: Option<String>,
+    /// Model `<enabled>`; legacy aliases are always enabled.
+    pub enabled: bool,
+}
+
+impl Alias {
+    /// Read an `<alias>` node; the layout follows from `<content>` or a
+    /// `uuid` attribute.
+    pub fn from_node(node: &XmlNode) -> Result<Self, ModelError> {
+        expect_tag(node, &["alias"], "alias")?;
+        let format = if node.get_child("content").is_some() || node.attributes.contains_key("uuid")
+        {
+            AliasFormat::Model
+        } else {
+            AliasFormat::Legacy
+        };
+        let (members, details) = read_members(node, format);
+        Ok(Self {
+            node: node.clone(),
+            format,
+            uuid: node.attributes.get("uuid").cloned(),
+            name: text(node, "name"),
+            kind: text(node, "type"),
+            members,
+            details,
+            description: match format {
+                AliasFormat::Legacy => text(node, "descr"),
+                AliasFormat::Model => text(node, "description"),
+            },
+            enabled: match format {
+                AliasFormat::Legacy => true,
+                AliasFormat::Model => {
+                    node.get_child("enabled").is_none() || flag_set(node, "enabled")
+                }
+            },
+        })
+    }
+
+    /// Write the fields back onto the node this alias was read from.
+    ///
+    /// Member lists are rewritten only when they changed, so an unchanged
+    /// alias keeps its original spacing.
+    pub fn to_node(&self) -> XmlNode {
+        let mut node = self.node.clone();
+        match &self.uuid {
+            Some(uuid) => {
+                node.attributes.insert("uuid".to_string(), uuid.clone());
+            }
+            None => {
+                node.attributes.remove("uuid");
+            }
+        }
+        set_text(&mut node, "name", self.name.as_deref());
+        set_text(&mut node, "type", self.kind.as_deref());
+        let unchanged =
+            read_members(&node, self.format) == (self.members.clone(), self.details.clone());
+        match self.format {
+            AliasFormat::Legacy => {
+                if !unchanged {
+                    let address = self.members.join(" ");
+                    set_text(&mut node, "address", Some(address.as_str()));
+                    let detail = self.details.join("||");
+                    set_text(
+                        &mut node,
+                        "detail",
+                        Some(detail.as_str()).filter(|v| !v.is_empty()),
+                    );
+                }
+                set_text(&mut node, "descr", self.description.as_deref());
+            }
+            AliasFormat::Model => {
+                if !unchanged {
+                    let content = self.members.join("\n");
+                    set_text(&mut node, "content", Some(content.as_str()));
+                }
+                set_text(&mut node, "description", self.description.as_deref());
+                if self.enabled
+                    != (node.get_child("enabled").is_none() || flag_set(&node, "enabled"))
+                {
+                    set_text(&mut node, "enabled", Some(numeric(self.enabled)));
+                }
+            }
+        }
+        node
+    }
+}
+
+fn read_members(node: &XmlNode, format: AliasFormat) -> (Vec<String>, Vec<String>) {
+    match format {
+        AliasFormat::Legacy => {
+            let members: Vec<String> = text(node, "address")
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(ToString::to_string)
+                .collect();
+            let details = text(node, "detail")
+                .map(|d| d.split("||").map(ToString::to_string).collect())
+                .unwrap_or_default();
+            (members, details)
+        }
+        AliasFormat::Model => {
+            let members = text(node, "content")
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(ToString::to_string)
+                .collect();
+            (members, Vec::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{Alias, AliasFormat};
+
+    #[test]
+    fn reads_legacy_members_with_details() {
+        let node = parse(
+            br#"<alias><name>web</name><type>host</type><address>10.0.0.1 10.0.0.2</address><descr>Web</descr><detail>one||two</detail></alias>"#,
+        )
+        .expect("parse");
+        let alias = Alias::from_node(&node).expect("alias");
+        assert_eq!(alias.format, AliasFormat::Legacy);
+        assert_eq!(alias.members, ["10.0.0.1", "10.0.0.2"]);
+        assert_eq!(alias.details, ["one", "two"]);
+        assert_eq!(alias.to_node(), node);
+
+        let mut alias = alias;
+        alias.members.push("10.0.0.3".to_string());
+        alias.details.push("three".to_string());
+        let out = alias.to_node();
+        assert_eq!(
+            out.get_text(&["address"]),
+            Some("10.0.0.1 10.0.0.2 10.0.0.3")
+        );
+        assert_eq!(out.get_text(&["detail"]), Some("one||two||three"));
+    }
+
+    #[test]
+    fn reads_model_content_and_enabled() {
+        let node = parse(
+            br#"<alias uuid="a1"><enabled>0</enabled><name>ports</name><type>port</type><content>80
+443</content><description>Web ports</description></alias>"#,
+        )
+        .expect("parse");
+        let mut alias = Alias::from_node(&node).expect("alias");
+        assert_eq!(alias.format, AliasFormat::Model);
+        assert_eq!(alias.uuid.as_deref(), Some("a1"));
+        assert_eq!(alias.members, ["80", "443"]);
+        assert!(!alias.enabled);
+        assert_eq!(alias.to_node(), node);
+
+        alias.enabled = true;
+        alias.members.retain(|m| m != "80");
+        let out = alias.to_node();
+        assert_eq!(out.get_text(&["enabled"]), Some("1"));
+        assert_eq!(out.get_text(&["content"]), Some("443"));
+    }
+}
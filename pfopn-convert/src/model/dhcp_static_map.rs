@@ -0,0 +1,76 @@
+use xml_diff_core::XmlNode;
+
+use super::{expect_tag, set_text, text, ModelError};
+
+/// One ISC DHCP static mapping (`<dhcpd><{iface}><staticmap>`).
+///
+/// The same layout is used under `<dhcpdv6>`, where `duid` takes the place
+/// of `mac` and `ipaddrv6` of `ipaddr`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DhcpStaticMap {
+    node: XmlNode,
+    pub mac: Option<String>,
+    /// Client identifier.
+    pub cid: Option<String>,
+    pub ipaddr: Option<String>,
+    pub duid: Option<String>,
+    pub ipaddrv6: Option<String>,
+    pub hostname: Option<String>,
+    pub descr: Option<String>,
+}
+
+impl DhcpStaticMap {
+    /// Read a `<staticmap>` node.
+    pub fn from_node(node: &XmlNode) -> Result<Self, ModelError> {
+        expect_tag(node, &["staticmap"], "staticmap")?;
+        Ok(Self {
+            node: node.clone(),
+            mac: text(node, "mac"),
+            cid: text(node, "cid"),
+            ipaddr: text(node, "ipaddr"),
+            duid: text(node, "duid"),
+            ipaddrv6: text(node, "ipaddrv6"),
+            hostname: text(node, "hostname"),
+            descr: text(node, "descr"),
+        })
+    }
+
+    /// Write the fields back onto the node this mapping was read from.
+    pub fn to_node(&self) -> XmlNode {
+        let mut node = self.node.clone();
+        set_text(&mut node, "mac", self.mac.as_deref());
+        set_text(&mut node, "cid", self.cid.as_deref());
+        set_text(&mut node, "ipaddr", self.ipaddr.as_deref());
+        set_text(&mut node, "duid", self.duid.as_deref());
+        set_text(&mut node, "ipaddrv6", self.ipaddrv6.as_deref());
+        set_text(&mut node, "hostname", self.hostname.as_deref());
+        set_text(&mut node, "descr", self.descr.as_deref());
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::DhcpStaticMap;
+
+    #[test]
+    fn round_trips_and_updates_in_place() {
+        let node = parse(
+            br#"<staticmap><mac>00:11:22:33:44:55</mac><cid></cid><ipaddr>192.168.1.10</ipaddr><hostname>nas</hostname><descr>NAS</descr><arp_table_static_entry/></staticmap>"#,
+        )
+        .expect("parse");
+        let mut map = DhcpStaticMap::from_node(&node).expect("staticmap");
+        assert_eq!(map.mac.as_deref(), Some("00:11:22:33:44:55"));
+        assert_eq!(map.cid, None);
+        assert_eq!(map.to_node(), node);
+
+        map.ipaddr = Some("192.168.1.11".to_string());
+        map.descr = None;
+        let out = map.to_node();
+        assert_eq!(out.children[2].text.as_deref(), Some("192.168.1.11"));
+        assert!(out.get_child("descr").is_none());
+        assert!(out.get_child("arp_table_static_entry").is_some());
+    }
+}
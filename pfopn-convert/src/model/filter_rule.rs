@@ -0,0 +1,198 @@
+use xml_diff_core::XmlNode;
+
+use super::{expect_tag, set_bool, set_text, text, ModelError};
+use crate::transform::booleans::flag_set;
+
+/// One firewall rule (`<filter><rule>`), same layout on both platforms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterRule {
+    node: XmlNode,
+    /// `pass`, `block`, or `reject` (`<type>`); absent means pass.
+    pub action: Option<String>,
+    /// Logical interfaces or groups; floating rules may list several.
+    pub interfaces: Vec<String>,
+    /// `inet`, `inet6`, or `inet46`.
+    pub ipprotocol: Option<String>,
+    pub protocol: Option<String>,
+    pub source: RuleEndpoint,
+    pub destination: RuleEndpoint,
+    pub gateway: Option<String>,
+    pub sched: Option<String>,
+    pub tracker: Option<String>,
+    pub descr: Option<String>,
+    pub disabled: bool,
+    pub log: bool,
+    pub quick: bool,
+    /// `<floating>yes</floating>`.
+    pub floating: bool,
+}
+
+/// The `<source>` or `<destination>` of a rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleEndpoint {
+    pub any: bool,
+    /// Interface network or address keyword (`lan`, `wanip`, `(self)`).
+    pub network: Option<String>,
+    /// Host, CIDR, or alias name.
+    pub address: Option<String>,
+    /// Port, range, or port alias.
+    pub port: Option<String>,
+    /// Match everything except this endpoint.
+    pub not: bool,
+}
+
+impl FilterRule {
+    /// Read a `<rule>` node.
+    pub fn from_node(node: &XmlNode) -> Result<Self, ModelError> {
+        expect_tag(node, &["rule"], "rule")?;
+        Ok(Self {
+            node: node.clone(),
+            action: text(node, "type"),
+            interfaces: text(node, "interface")
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(ToString::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ipprotocol: text(node, "ipprotocol"),
+            protocol: text(node, "protocol"),
+            source: RuleEndpoint::read(node.get_child("source")),
+            destination: RuleEndpoint::read(node.get_child("destination")),
+            gateway: text(node, "gateway"),
+            sched: text(node, "sched"),
+            tracker: text(node, "tracker"),
+            descr: text(node, "descr"),
+            disabled: flag_set(node, "disabled"),
+            log: flag_set(node, "log"),
+            quick: flag_set(node, "quick"),
+            floating: text(node, "floating").is_some_and(|v| v == "yes"),
+        })
+    }
+
+    /// Write the fields back onto the node this rule was read from.
+    pub fn to_node(&self) -> XmlNode {
+        let mut node = self.node.clone();
+        set_text(&mut node, "type", self.action.as_deref());
+        let interfaces = self.interfaces.join(",");
+        set_text(
+            &mut node,
+            "interface",
+            Some(interfaces.as_str()).filter(|v| !v.is_empty()),
+        );
+        set_text(&mut node, "ipprotocol", self.ipprotocol.as_deref());
+        set_text(&mut node, "protocol", self.protocol.as_deref());
+        self.source.write(&mut node, "source");
+        self.destination.write(&mut node, "destination");
+        set_text(&mut node, "gateway", self.gateway.as_deref());
+        set_text(&mut node, "sched", self.sched.as_deref());
+        set_text(&mut node, "tracker", self.tracker.as_deref());
+        set_text(&mut node, "descr", self.descr.as_deref());
+        set_bool(&mut node, "disabled", self.disabled);
+        set_bool(&mut node, "log", self.log);
+        set_bool(&mut node, "quick", self.quick);
+        if self.floating != text(&node, "floating").is_some_and(|v| v == "yes") {
+            set_text(&mut node, "floating", self.floating.then_some("yes"));
+        }
+        node
+    }
+}
+
+impl RuleEndpoint {
+    fn read(node: Option<&XmlNode>) -> Self {
+        let Some(node) = node else {
+            return Self::default();
+        };
+        Self {
+            any: node.get_child("any").is_some(),
+            network: text(node, "network"),
+            address: text(node, "address"),
+            port: text(node, "port"),
+            not: flag_set(node, "not"),
+        }
+    }
+
+    fn write(&self, rule: &mut XmlNode, tag: &str) {
+        if rule.get_child(tag).is_none() && *self == Self::default() {
+            return;
+        }
+        let idx = match rule.children.iter().position(|c| c.tag == tag) {
+            Some(idx) => idx,
+            None => {
+                rule.children.push(XmlNode::new(tag));
+                rule.children.len() - 1
+            }
+        };
+        let node = &mut rule.children[idx];
+        if self.any != node.get_child("any").is_some() {
+            if self.any {
+                node.children.push(XmlNode::new("any"));
+            } else {
+                node.children.retain(|c| c.tag != "any");
+            }
+        }
+        set_text(node, "network", self.network.as_deref());
+        set_text(node, "address", self.address.as_deref());
+        set_text(node, "port", self.port.as_deref());
+        set_bool(node, "not", self.not);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::FilterRule;
+    use crate::model::ModelError;
+
+    const RULE: &[u8] = br#"<rule><id/><tracker>100</tracker><type>pass</type><interface>lan</interface><ipprotocol>inet</ipprotocol><protocol>tcp</protocol><source><network>lan</network></source><destination><address>web_servers</address><port>443</port></destination><descr>HTTPS out</descr><log/><created><time>1</time></created></rule>"#;
+
+    #[test]
+    fn round_trips_unchanged_rule() {
+        let node = parse(RULE).expect("parse");
+        let rule = FilterRule::from_node(&node).expect("rule");
+        assert_eq!(rule.interfaces, ["lan"]);
+        assert_eq!(rule.source.network.as_deref(), Some("lan"));
+        assert_eq!(rule.destination.port.as_deref(), Some("443"));
+        assert!(rule.log);
+        assert!(!rule.disabled);
+        assert_eq!(rule.to_node(), node);
+    }
+
+    #[test]
+    fn writes_changes_in_place_and_keeps_unknown_fields() {
+        let node = parse(RULE).expect("parse");
+        let mut rule = FilterRule::from_node(&node).expect("rule");
+        rule.disabled = true;
+        rule.log = false;
+        rule.source.network = None;
+        rule.source.any = true;
+        rule.destination.not = true;
+        let out = rule.to_node();
+        assert_eq!(out.get_text(&["disabled"]), Some("1"));
+        assert!(out.get_child("log").is_none());
+        assert!(out.get_child("source").unwrap().get_child("any").is_some());
+        assert!(out
+            .get_child("source")
+            .unwrap()
+            .get_child("network")
+            .is_none());
+        assert_eq!(out.get_text(&["destination", "not"]), Some("1"));
+        assert_eq!(out.get_text(&["created", "time"]), Some("1"));
+        assert_eq!(out.children[2].tag, "type");
+    }
+
+    #[test]
+    fn rejects_other_tags() {
+        let node = parse(b"<alias><name>x</name></alias>").expect("parse");
+        assert_eq!(
+            FilterRule::from_node(&node),
+            Err(ModelError::UnexpectedTag {
+                expected: "rule",
+                found: "alias".to_string()
+            })
+        );
+    }
+}
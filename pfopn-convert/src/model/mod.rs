@@ -0,0 +1,106 @@
+//! Typed views over common configuration entries.
+//!
+//! Transforms mostly walk [`XmlNode`] trees by tag name, which makes a
+//! misspelled field or a forgotten fallback easy to miss. The types here read
+//! one entry into named fields with [`from_node`](FilterRule::from_node) and
+//! write it back with [`to_node`](FilterRule::to_node).
+//!
+//! Each view keeps the node it was read from. `to_node` updates the known
+//! fields in place and leaves every other child, attribute, and the child
+//! order untouched, so reading and writing an unchanged view returns the
+//! original node.
+//!
+//! - [`FilterRule`] — `<filter><rule>`
+//! - [`Alias`] — legacy `<aliases><alias>` and OPNsense model `<alias>`
+//! - [`DhcpStaticMap`] — `<dhcpd><{iface}><staticmap>`
+//! - [`OpenVpnInstance`] — `<openvpn><openvpn-server>` / `<openvpn-client>`
+//! - [`WireguardPeer`] — pfSense peer `<item>` and OPNsense `<client>`
+
+mod alias;
+mod dhcp_static_map;
+mod filter_rule;
+mod openvpn;
+mod wireguard;
+
+pub use alias::{Alias, AliasFormat};
+pub use dhcp_static_map::DhcpStaticMap;
+pub use filter_rule::{FilterRule, RuleEndpoint};
+pub use openvpn::{OpenVpnInstance, OpenVpnRole};
+pub use wireguard::{PeerFormat, WireguardPeer};
+
+use thiserror::Error;
+use xml_diff_core::XmlNode;
+
+use crate::transform::booleans::flag_set;
+
+/// Errors produced while reading a typed view.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ModelError {
+    /// The node is not the entry the view reads.
+    #[error("expected <{expected}>, found <{found}>")]
+    UnexpectedTag {
+        expected: &'static str,
+        found: String,
+    },
+}
+
+fn expect_tag(node: &XmlNode, tags: &[&str], expected: &'static str) -> Result<(), ModelError> {
+    if tags.contains(&node.tag.as_str()) {
+        Ok(())
+    } else {
+        Err(ModelError::UnexpectedTag {
+            expected,
+            found: node.tag.clone(),
+        })
+    }
+}
+
+/// Trimmed, non-empty text of a direct child.
+fn text(node: &XmlNode, tag: &str) -> Option<String> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Set a direct child's text in place, appending it when missing.
+///
+/// `None` removes a child that holds text; an empty child already reads as
+/// `None` and is kept.
+fn set_text(node: &mut XmlNode, tag: &str, value: Option<&str>) {
+    let Some(value) = value else {
+        if text(node, tag).is_some() {
+            node.children.retain(|c| c.tag != tag);
+        }
+        return;
+    };
+    match node.children.iter_mut().find(|c| c.tag == tag) {
+        Some(child) => {
+            if child.text.as_deref().map(str::trim) != Some(value) {
+                child.text = Some(value.to_string());
+            }
+        }
+        None => {
+            let mut child = XmlNode::new(tag);
+            child.text = Some(value.to_string());
+            node.children.push(child);
+        }
+    }
+}
+
+/// Switch a flag child in an encoding both platforms read.
+///
+/// An unchanged flag keeps its original spelling. Switching on writes `1`,
+/// which pfSense reads as present and OPNsense as set; switching off removes
+/// the tag. [`crate::transform::booleans::normalize`] re-encodes the result
+/// for the target at the end of a conversion.
+fn set_bool(node: &mut XmlNode, tag: &str, on: bool) {
+    if flag_set(node, tag) == on {
+        return;
+    }
+    if on {
+        set_text(node, tag, Some("1"));
+    } else {
+        node.children.retain(|c| c.tag != tag);
+    }
+}
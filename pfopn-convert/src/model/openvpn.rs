@@ -0,0 +1,136 @@
+use xml_diff_core::XmlNode;
+
+use super::{expect_tag, set_bool, set_text, text, ModelError};
+use crate::transform::booleans::flag_set;
+
+/// Whether an OpenVPN instance accepts or makes connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenVpnRole {
+    Server,
+    Client,
+}
+
+impl OpenVpnRole {
+    /// Element name of an instance with this role.
+    pub fn tag(self) -> &'static str {
+        match self {
+            OpenVpnRole::Server => "openvpn-server",
+            OpenVpnRole::Client => "openvpn-client",
+        }
+    }
+}
+
+/// One legacy OpenVPN instance (`<openvpn><openvpn-server>` or
+/// `<openvpn-client>`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenVpnInstance {
+    node: XmlNode,
+    /// Taken from the element name; writing a different role renames it.
+    pub role: OpenVpnRole,
+    pub vpnid: Option<String>,
+    pub disabled: bool,
+    /// `server_tls`, `server_user`, `p2p_tls`, ...
+    pub mode: Option<String>,
+    pub protocol: Option<String>,
+    /// `tun` or `tap`.
+    pub dev_mode: Option<String>,
+    pub interface: Option<String>,
+    pub local_port: Option<String>,
+    /// Remote host of a client instance.
+    pub server_addr: Option<String>,
+    pub server_port: Option<String>,
+    pub tunnel_network: Option<String>,
+    pub caref: Option<String>,
+    pub certref: Option<String>,
+    pub description: Option<String>,
+}
+
+impl OpenVpnInstance {
+    /// Read an `<openvpn-server>` or `<openvpn-client>` node.
+    pub fn from_node(node: &XmlNode) -> Result<Self, ModelError> {
+        expect_tag(
+            node,
+            &["openvpn-server", "openvpn-client"],
+            "openvpn-server or openvpn-client",
+        )?;
+        let role = if node.tag == "openvpn-server" {
+            OpenVpnRole::Server
+        } else {
+            OpenVpnRole::Client
+        };
+        Ok(Self {
+            node: node.clone(),
+            role,
+            vpnid: text(node, "vpnid"),
+            disabled: flag_set(node, "disable"),
+            mode: text(node, "mode"),
+            protocol: text(node, "protocol"),
+            dev_mode: text(node, "dev_mode"),
+            interface: text(node, "interface"),
+            local_port: text(node, "local_port"),
+            server_addr: text(node, "server_addr"),
+            server_port: text(node, "server_port"),
+            tunnel_network: text(node, "tunnel_network"),
+            caref: text(node, "caref"),
+            certref: text(node, "certref"),
+            description: text(node, "description"),
+        })
+    }
+
+    /// Write the fields back onto the node this instance was read from.
+    pub fn to_node(&self) -> XmlNode {
+        let mut node = self.node.clone();
+        node.tag = self.role.tag().to_string();
+        set_text(&mut node, "vpnid", self.vpnid.as_deref());
+        set_bool(&mut node, "disable", self.disabled);
+        set_text(&mut node, "mode", self.mode.as_deref());
+        set_text(&mut node, "protocol", self.protocol.as_deref());
+        set_text(&mut node, "dev_mode", self.dev_mode.as_deref());
+        set_text(&mut node, "interface", self.interface.as_deref());
+        set_text(&mut node, "local_port", self.local_port.as_deref());
+        set_text(&mut node, "server_addr", self.server_addr.as_deref());
+        set_text(&mut node, "server_port", self.server_port.as_deref());
+        set_text(&mut node, "tunnel_network", self.tunnel_network.as_deref());
+        set_text(&mut node, "caref", self.caref.as_deref());
+        set_text(&mut node, "certref", self.certref.as_deref());
+        set_text(&mut node, "description", self.description.as_deref());
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{OpenVpnInstance, OpenVpnRole};
+
+    #[test]
+    fn reads_server_and_keeps_unmodelled_fields() {
+        let node = parse(
+            br#"<openvpn-server><vpnid>1</vpnid><mode>server_tls</mode><protocol>UDP4</protocol><dev_mode>tun</dev_mode><interface>wan</interface><local_port>1194</local_port><description>Remote</description><tunnel_network>10.8.0.0/24</tunnel_network><compression>lzo</compression></openvpn-server>"#,
+        )
+        .expect("parse");
+        let mut server = OpenVpnInstance::from_node(&node).expect("server");
+        assert_eq!(server.role, OpenVpnRole::Server);
+        assert!(!server.disabled);
+        assert_eq!(server.local_port.as_deref(), Some("1194"));
+        assert_eq!(server.to_node(), node);
+
+        server.disabled = true;
+        server.interface = Some("opt1".to_string());
+        let out = server.to_node();
+        assert!(out.get_child("disable").is_some());
+        assert_eq!(out.get_text(&["interface"]), Some("opt1"));
+        assert_eq!(out.get_text(&["compression"]), Some("lzo"));
+    }
+
+    #[test]
+    fn presence_tag_reads_as_disabled() {
+        let node = parse(b"<openvpn-client><vpnid>2</vpnid><disable></disable></openvpn-client>")
+            .expect("parse");
+        let client = OpenVpnInstance::from_node(&node).expect("client");
+        assert_eq!(client.role, OpenVpnRole::Client);
+        assert!(client.disabled);
+        assert_eq!(client.to_node(), node);
+    }
+}
@@ -0,0 +1,252 @@
+use xml_diff_core::XmlNode;
+
+use super::{expect_tag, set_text, text, ModelError};
+use crate::transform::booleans::{is_truthy, numeric};
+
+/// Which platform's peer layout a [`WireguardPeer`] was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerFormat {
+    /// `<installedpackages><wireguard><peers><item>`.
+    Pfsense,
+    /// `<OPNsense><wireguard><client><clients><client uuid>`.
+    Opnsense,
+}
+
+/// One WireGuard peer in either platform's layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WireguardPeer {
+    node: XmlNode,
+    pub format: PeerFormat,
+    /// OPNsense `uuid` attribute.
+    pub uuid: Option<String>,
+    /// pfSense `yes`/`no`, OPNsense `1`/`0`.
+    pub enabled: bool,
+    /// pfSense `<descr>`, OPNsense `<name>`.
+    pub name: Option<String>,
+    pub public_key: Option<String>,
+    pub preshared_key: Option<String>,
+    /// Allowed IPs as CIDRs: pfSense `<allowedips><row>`, OPNsense
+    /// comma-separated `<tunneladdress>`.
+    pub allowed_ips: Vec<String>,
+    pub endpoint: Option<String>,
+    pub endpoint_port: Option<String>,
+    pub keepalive: Option<String>,
+    /// pfSense tunnel the peer belongs to (`tun_wg0`); OPNsense links peers
+    /// from the server side instead.
+    pub tunnel: Option<String>,
+}
+
+impl WireguardPeer {
+    /// Read a pfSense peer `<item>` or an OPNsense `<client>`.
+    pub fn from_node(node: &XmlNode) -> Result<Self, ModelError> {
+        expect_tag(node, &["item", "client"], "item or client")?;
+        let peer = if node.tag == "item" {
+            Self {
+                node: node.clone(),
+                format: PeerFormat::Pfsense,
+                uuid: None,
+                enabled: text(node, "enabled").is_some_and(|v| is_truthy(&v)),
+                name: text(node, "descr"),
+                public_key: text(node, "publickey"),
+                preshared_key: text(node, "presharedkey"),
+                allowed_ips: allowed_ip_rows(node),
+                endpoint: pfsense_endpoint(node, "address"),
+                endpoint_port: text(node, "port").or_else(|| pfsense_endpoint(node, "port")),
+                keepalive: text(node, "persistentkeepalive"),
+                tunnel: text(node, "tun"),
+            }
+        } else {
+            Self {
+                node: node.clone(),
+                format: PeerFormat::Opnsense,
+                uuid: node.attributes.get("uuid").cloned(),
+                enabled: text(node, "enabled").is_some_and(|v| is_truthy(&v)),
+                name: text(node, "name"),
+                public_key: text(node, "pubkey"),
+                preshared_key: text(node, "psk"),
+                allowed_ips: split_cidrs(text(node, "tunneladdress").as_deref()),
+                endpoint: text(node, "serveraddress"),
+                endpoint_port: text(node, "serverport"),
+                keepalive: text(node, "keepalive"),
+                tunnel: None,
+            }
+        };
+        Ok(peer)
+    }
+
+    /// Write the fields back onto the node this peer was read from.
+    pub fn to_node(&self) -> XmlNode {
+        let mut node = self.node.clone();
+        let enabled = text(&node, "enabled").is_some_and(|v| is_truthy(&v));
+        match self.format {
+            PeerFormat::Pfsense => {
+                if enabled != self.enabled {
+                    set_text(
+                        &mut node,
+                        "enabled",
+                        Some(if self.enabled { "yes" } else { "no" }),
+                    );
+                }
+                set_text(&mut node, "tun", self.tunnel.as_deref());
+                set_text(&mut node, "descr", self.name.as_deref());
+                set_text(&mut node, "publickey", self.public_key.as_deref());
+                set_text(&mut node, "presharedkey", self.preshared_key.as_deref());
+                if allowed_ip_rows(&node) != self.allowed_ips {
+                    write_allowed_ip_rows(&mut node, &self.allowed_ips);
+                }
+                match node.children.iter_mut().find(|c| c.tag == "endpoint") {
+                    Some(nested) if !nested.children.is_empty() => {
+                        set_text(nested, "address", self.endpoint.as_deref());
+                        set_text(nested, "port", self.endpoint_port.as_deref());
+                    }
+                    _ => {
+                        set_text(&mut node, "endpoint", self.endpoint.as_deref());
+                        set_text(&mut node, "port", self.endpoint_port.as_deref());
+                    }
+                }
+                set_text(&mut node, "persistentkeepalive", self.keepalive.as_deref());
+            }
+            PeerFormat::Opnsense => {
+                match &self.uuid {
+                    Some(uuid) => {
+                        node.attributes.insert("uuid".to_string(), uuid.clone());
+                    }
+                    None => {
+                        node.attributes.remove("uuid");
+                    }
+                }
+                if enabled != self.enabled {
+                    set_text(&mut node, "enabled", Some(numeric(self.enabled)));
+                }
+                set_text(&mut node, "name", self.name.as_deref());
+                set_text(&mut node, "pubkey", self.public_key.as_deref());
+                set_text(&mut node, "psk", self.preshared_key.as_deref());
+                if split_cidrs(text(&node, "tunneladdress").as_deref()) != self.allowed_ips {
+                    let joined = self.allowed_ips.join(",");
+                    set_text(&mut node, "tunneladdress", Some(joined.as_str()));
+                }
+                set_text(&mut node, "serveraddress", self.endpoint.as_deref());
+                set_text(&mut node, "serverport", self.endpoint_port.as_deref());
+                set_text(&mut node, "keepalive", self.keepalive.as_deref());
+            }
+        }
+        node
+    }
+}
+
+/// pfSense writes the endpoint host as `<endpoint>` text with a sibling
+/// `<port>`; older exports nest `<address>` and `<port>` under `<endpoint>`.
+fn pfsense_endpoint(node: &XmlNode, nested: &str) -> Option<String> {
+    let endpoint = node.get_child("endpoint")?;
+    if endpoint.children.is_empty() {
+        return (nested == "address")
+            .then(|| text(node, "endpoint"))
+            .flatten();
+    }
+    text(endpoint, nested)
+}
+
+/// `<allowedips><row>` entries as CIDRs; a row without a mask is one host.
+fn allowed_ip_rows(node: &XmlNode) -> Vec<String> {
+    let Some(allowed) = node.get_child("allowedips") else {
+        return Vec::new();
+    };
+    allowed
+        .get_children("row")
+        .into_iter()
+        .filter_map(|row| {
+            let addr = text(row, "address")?;
+            let mask = text(row, "mask")
+                .unwrap_or_else(|| if addr.contains(':') { "128" } else { "32" }.to_string());
+            Some(format!("{addr}/{mask}"))
+        })
+        .collect()
+}
+
+fn write_allowed_ip_rows(node: &mut XmlNode, cidrs: &[String]) {
+    let mut allowed = XmlNode::new("allowedips");
+    for cidr in cidrs {
+        let (addr, mask) = cidr.split_once('/').unwrap_or((cidr.as_str(), ""));
+        let mut row = XmlNode::new("row");
+        set_text(&mut row, "address", Some(addr));
+        set_text(&mut row, "mask", Some(mask).filter(|m| !m.is_empty()));
+        set_text(&mut row, "descr", Some(""));
+        allowed.children.push(row);
+    }
+    match node.children.iter_mut().find(|c| c.tag == "allowedips") {
+        Some(existing) => *existing = allowed,
+        None => node.children.push(allowed),
+    }
+}
+
+fn split_cidrs(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{PeerFormat, WireguardPeer};
+
+    #[test]
+    fn reads_pfsense_item() {
+        let node = parse(
+            br#"<item><enabled>yes</enabled><tun>tun_wg0</tun><descr>laptop</descr><endpoint>vpn.example.com</endpoint><port>51820</port><persistentkeepalive>25</persistentkeepalive><publickey>PUB</publickey><presharedkey></presharedkey><allowedips><row><address>10.6.0.2</address><mask>32</mask><descr></descr></row><row><address>fd00::2</address></row></allowedips></item>"#,
+        )
+        .expect("parse");
+        let mut peer = WireguardPeer::from_node(&node).expect("peer");
+        assert_eq!(peer.format, PeerFormat::Pfsense);
+        assert!(peer.enabled);
+        assert_eq!(peer.endpoint.as_deref(), Some("vpn.example.com"));
+        assert_eq!(peer.endpoint_port.as_deref(), Some("51820"));
+        assert_eq!(peer.allowed_ips, ["10.6.0.2/32", "fd00::2/128"]);
+        assert_eq!(peer.preshared_key, None);
+        assert_eq!(peer.to_node(), node);
+
+        peer.enabled = false;
+        peer.allowed_ips = vec!["10.6.0.3/32".to_string()];
+        let out = peer.to_node();
+        assert_eq!(out.get_text(&["enabled"]), Some("no"));
+        assert_eq!(
+            out.get_text(&["allowedips", "row", "address"]),
+            Some("10.6.0.3")
+        );
+        assert_eq!(out.get_text(&["allowedips", "row", "mask"]), Some("32"));
+    }
+
+    #[test]
+    fn reads_nested_pfsense_endpoint() {
+        let node = parse(
+            br#"<item><endpoint><address>203.0.113.5</address><port>51821</port></endpoint></item>"#,
+        )
+        .expect("parse");
+        let peer = WireguardPeer::from_node(&node).expect("peer");
+        assert_eq!(peer.endpoint.as_deref(), Some("203.0.113.5"));
+        assert_eq!(peer.endpoint_port.as_deref(), Some("51821"));
+        assert_eq!(peer.to_node(), node);
+    }
+
+    #[test]
+    fn reads_opnsense_client() {
+        let node = parse(
+            br#"<client uuid="c1"><enabled>1</enabled><name>phone</name><pubkey>PUB</pubkey><psk/><tunneladdress>10.6.0.4/32,fd00::4/128</tunneladdress><serveraddress/><serverport/><keepalive>25</keepalive></client>"#,
+        )
+        .expect("parse");
+        let mut peer = WireguardPeer::from_node(&node).expect("peer");
+        assert_eq!(peer.format, PeerFormat::Opnsense);
+        assert_eq!(peer.uuid.as_deref(), Some("c1"));
+        assert_eq!(peer.allowed_ips.len(), 2);
+        assert_eq!(peer.to_node(), node);
+
+        peer.endpoint = Some("198.51.100.1".to_string());
+        let out = peer.to_node();
+        assert_eq!(out.get_text(&["serveraddress"]), Some("198.51.100.1"));
+    }
+}
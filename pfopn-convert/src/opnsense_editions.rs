@@ -0,0 +1,128 @@
+//! OPNsense Business Edition plugins.
+//!
+//! Business Edition is community OPNsense plus a subscription and a few
+//! plugins only Deciso's Business repository ships. Their models sit under
+//! `<OPNsense>` like any other plugin, so a community target restores the
+//! config but silently drops them. [`BUSINESS_PLUGINS`] lists them;
+//! [`business_plugin_uses`] finds each one a config declares or carries,
+//! and [`crate::detect::detect_opnsense_edition`] treats any use as
+//! Business Edition evidence.
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+/// A plugin only the Business repository ships.
+#[derive(Debug, Clone, Copy)]
+pub struct BusinessPlugin {
+    /// Package name in `<system><firmware><plugins>`.
+    pub package: &'static str,
+    /// Model under `<OPNsense>`, when the plugin has one.
+    pub model: Option<&'static str>,
+    pub description: &'static str,
+}
+
+/// Known Business-only plugins.
+pub const BUSINESS_PLUGINS: &[BusinessPlugin] = &[
+    BusinessPlugin {
+        package: "os-OPNBEcore",
+        model: None,
+        description: "Business Edition core",
+    },
+    BusinessPlugin {
+        package: "os-OPNcentral",
+        model: Some("OPNcentral"),
+        description: "OPNcentral multi-firewall management",
+    },
+    BusinessPlugin {
+        package: "os-OPNWAF",
+        model: Some("OPNWAF"),
+        description: "OPNWAF web application firewall",
+    },
+];
+
+/// One Business-only plugin found in a config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BusinessPluginUse {
+    pub package: &'static str,
+    pub description: &'static str,
+    /// Where it was found (`firmware.plugins`, `OPNsense.OPNWAF`).
+    pub evidence: String,
+}
+
+/// Every [`BUSINESS_PLUGINS`] entry `root` declares or carries a model for.
+pub fn business_plugin_uses(root: &XmlNode) -> Vec<BusinessPluginUse> {
+    let declared = root
+        .get_text(&["system", "firmware", "plugins"])
+        .unwrap_or_default();
+    let declared: Vec<&str> = declared
+        .split([' ', ',', ';'])
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    let models = root.get_child("OPNsense");
+    let mut out = Vec::new();
+    for plugin in BUSINESS_PLUGINS {
+        let evidence = if declared
+            .iter()
+            .any(|p| p.eq_ignore_ascii_case(plugin.package))
+        {
+            "firmware.plugins".to_string()
+        } else if let Some(model) = plugin
+            .model
+            .filter(|m| models.is_some_and(|o| o.get_child(m).is_some()))
+        {
+            format!("OPNsense.{model}")
+        } else {
+            continue;
+        };
+        out.push(BusinessPluginUse {
+            package: plugin.package,
+            description: plugin.description,
+            evidence,
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::business_plugin_uses;
+    use crate::detect::{detect_opnsense_edition, OpnSenseEdition};
+
+    #[test]
+    fn finds_declared_plugins_and_models() {
+        let root = parse(
+            b"<opnsense><system><firmware><plugins>os-isc-dhcp os-OPNBEcore</plugins></firmware></system>\
+              <OPNsense><OPNWAF><general/></OPNWAF></OPNsense></opnsense>",
+        )
+        .expect("parse");
+        let uses = business_plugin_uses(&root);
+        let found: Vec<(&str, &str)> = uses
+            .iter()
+            .map(|u| (u.package, u.evidence.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("os-OPNBEcore", "firmware.plugins"),
+                ("os-OPNWAF", "OPNsense.OPNWAF")
+            ]
+        );
+        let edition = detect_opnsense_edition(&root).expect("opnsense");
+        assert_eq!(edition.edition, OpnSenseEdition::Business);
+    }
+
+    #[test]
+    fn community_config_has_no_business_evidence() {
+        let root = parse(
+            b"<opnsense><system><firmware><mirror>https://pkg.opnsense.org</mirror><type/><subscription/>\
+              <plugins>os-isc-dhcp</plugins></firmware></system></opnsense>",
+        )
+        .expect("parse");
+        let edition = detect_opnsense_edition(&root).expect("opnsense");
+        assert_eq!(edition.edition, OpnSenseEdition::Community);
+        assert!(edition.evidence.is_empty());
+    }
+}
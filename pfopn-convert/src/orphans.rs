@@ -0,0 +1,252 @@
+//! Orphaned object detection and pruning.
+//!
+//! An object is orphaned when nothing in the config refers to it: no edge in
+//! the [`graph`](crate::graph) points at it, and its name (or `refid`) does
+//! not appear as a token in any text outside the object definitions. The text
+//! scan keeps detection conservative — references the graph does not model
+//! (web GUI certificate, default gateways, NAT rules, package settings) still
+//! keep an object alive.
+//!
+//! Only certificates, CAs, aliases, gateways, and schedules are considered.
+//! OPNsense `internal` and `external` aliases are filled by the system or
+//! scripts rather than by config references, so they are never orphans.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::graph::build_graph;
+use crate::simulate::load_aliases;
+
+/// Object kinds that can be reported as orphaned.
+const ORPHAN_KINDS: &[&str] = &["ca", "cert", "alias", "gateway", "schedule"];
+
+/// An object nothing refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Orphan {
+    /// `ca`, `cert`, `alias`, `gateway`, or `schedule`.
+    pub kind: String,
+    /// Name, or `refid` for certificates and CAs.
+    pub name: String,
+    /// Description when the config has one, otherwise the name.
+    pub label: String,
+}
+
+/// List every orphaned object in `root`.
+pub fn find_orphans(root: &XmlNode) -> Vec<Orphan> {
+    let graph = build_graph(root);
+    let mut tokens = BTreeSet::new();
+    collect_reference_tokens(root, &mut tokens);
+    let aliases = load_aliases(root);
+
+    graph
+        .nodes
+        .iter()
+        .filter(|node| !node.missing && ORPHAN_KINDS.contains(&node.kind.as_str()))
+        .filter(|node| {
+            !graph
+                .edges
+                .iter()
+                .any(|edge| edge.to == node.id && edge.from != node.id)
+        })
+        .filter_map(|node| {
+            let name = node.id.split_once(':')?.1;
+            let managed = node.kind == "alias"
+                && aliases
+                    .get(name)
+                    .is_some_and(|a| matches!(a.kind.as_str(), "internal" | "external"));
+            (!managed && !tokens.contains(name)).then(|| Orphan {
+                kind: node.kind.clone(),
+                name: name.to_string(),
+                label: node.label.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Remove orphaned objects from `root` and return what was removed.
+///
+/// Runs until nothing else becomes orphaned, so a CA whose only certificate
+/// was pruned goes too.
+pub fn prune_orphans(root: &mut XmlNode) -> Vec<Orphan> {
+    let mut removed = Vec::new();
+    loop {
+        let orphans = find_orphans(root);
+        if orphans.is_empty() {
+            return removed;
+        }
+        for orphan in &orphans {
+            remove_definition(root, orphan);
+        }
+        removed.extend(orphans);
+    }
+}
+
+fn remove_definition(root: &mut XmlNode, orphan: &Orphan) {
+    let name = orphan.name.as_str();
+    match orphan.kind.as_str() {
+        "ca" | "cert" => root
+            .children
+            .retain(|c| !(c.tag == orphan.kind && key(c, "refid") == Some(name))),
+        "alias" => {
+            for aliases in alias_sections(root) {
+                aliases
+                    .children
+                    .retain(|c| !(c.tag == "alias" && key(c, "name") == Some(name)));
+            }
+        }
+        "gateway" => {
+            for section in gateway_sections(root) {
+                section.children.retain(|c| key(c, "name") != Some(name));
+            }
+        }
+        "schedule" => {
+            if let Some(schedules) = child_mut(root, "schedules") {
+                schedules
+                    .children
+                    .retain(|c| !(c.tag == "schedule" && key(c, "name") == Some(name)));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn alias_sections(root: &mut XmlNode) -> Vec<&mut XmlNode> {
+    let mut out = Vec::new();
+    for child in &mut root.children {
+        match child.tag.as_str() {
+            "aliases" => out.push(child),
+            "OPNsense" => {
+                let mvc = ["Firewall", "Alias", "aliases"]
+                    .iter()
+                    .try_fold(child, |node, tag| child_mut(node, tag));
+                out.extend(mvc);
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn gateway_sections(root: &mut XmlNode) -> Vec<&mut XmlNode> {
+    let mut out = Vec::new();
+    for child in &mut root.children {
+        match child.tag.as_str() {
+            "gateways" => out.push(child),
+            "OPNsense" => out.extend(child_mut(child, "Gateways")),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
+    node.children.iter_mut().find(|c| c.tag == tag)
+}
+
+fn key<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag]).map(str::trim)
+}
+
+/// Collect text tokens outside object definitions, which only name themselves.
+fn collect_reference_tokens(node: &XmlNode, tokens: &mut BTreeSet<String>) {
+    if let Some(text) = &node.text {
+        for token in text
+            .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '|'))
+            .map(|t| t.trim_start_matches('!'))
+            .filter(|t| !t.is_empty())
+        {
+            tokens.insert(token.to_string());
+        }
+    }
+    for child in &node.children {
+        if !is_definition(node, child) {
+            collect_reference_tokens(child, tokens);
+        }
+    }
+}
+
+fn is_definition(parent: &XmlNode, child: &XmlNode) -> bool {
+    matches!(
+        (parent.tag.as_str(), child.tag.as_str()),
+        ("pfsense" | "opnsense", "ca" | "cert")
+            | ("aliases", "alias")
+            | ("gateways" | "Gateways", "gateway_item" | "gateway_group")
+            | ("schedules", "schedule")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{find_orphans, prune_orphans};
+
+    fn names(orphans: &[super::Orphan]) -> Vec<String> {
+        orphans
+            .iter()
+            .map(|o| format!("{}:{}", o.kind, o.name))
+            .collect()
+    }
+
+    #[test]
+    fn reports_unreferenced_objects_only() {
+        let root = parse(
+            br#"<pfsense>
+                <system><webgui><ssl-certref>gui</ssl-certref></webgui></system>
+                <ca><refid>ca1</refid></ca>
+                <cert><refid>gui</refid><caref>ca1</caref></cert>
+                <cert><refid>old</refid></cert>
+                <aliases>
+                  <alias><name>USED</name><address>NESTED</address></alias>
+                  <alias><name>NESTED</name><address>10.0.0.1</address></alias>
+                  <alias><name>STALE</name><descr>STALE</descr></alias>
+                  <alias><name>NATTED</name></alias>
+                </aliases>
+                <gateways>
+                  <gateway_item><name>GW1</name></gateway_item>
+                  <gateway_item><name>GW2</name></gateway_item>
+                  <defaultgw4>GW2</defaultgw4>
+                </gateways>
+                <schedules><schedule><name>never</name></schedule></schedules>
+                <nat><rule><destination><address>NATTED</address></destination></rule></nat>
+                <filter><rule><destination><address>USED</address></destination></rule></filter>
+            </pfsense>"#,
+        )
+        .expect("parse");
+
+        assert_eq!(
+            names(&find_orphans(&root)),
+            vec!["alias:STALE", "cert:old", "gateway:GW1", "schedule:never"]
+        );
+    }
+
+    #[test]
+    fn prunes_until_nothing_is_orphaned() {
+        let mut root = parse(
+            br#"<opnsense>
+                <ca><refid>ca1</refid></ca>
+                <cert><refid>c1</refid><caref>ca1</caref></cert>
+                <OPNsense><Firewall><Alias><aliases>
+                  <alias uuid="a"><name>KEEP</name></alias>
+                  <alias uuid="b"><name>DROP</name></alias>
+                  <alias uuid="c"><name>sshlockout</name><type>external</type></alias>
+                </aliases></Alias></Firewall></OPNsense>
+                <filter><rule><source><address>KEEP</address></source></rule></filter>
+            </opnsense>"#,
+        )
+        .expect("parse");
+
+        let removed = prune_orphans(&mut root);
+
+        assert_eq!(names(&removed), vec!["alias:DROP", "cert:c1", "ca:ca1"]);
+        assert!(root.get_child("ca").is_none());
+        assert!(root.get_child("cert").is_none());
+        assert_eq!(
+            root.get_text(&["OPNsense", "Firewall", "Alias", "aliases", "alias", "name"]),
+            Some("KEEP")
+        );
+        assert!(find_orphans(&root).is_empty());
+    }
+}
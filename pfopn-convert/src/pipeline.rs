@@ -0,0 +1,1103 @@
+//! Programmatic conversion pipeline.
+//!
+//! [`ConversionPipeline`] runs the same staged conversion as the `convert`
+//! command, but takes parsed trees and returns structured results instead of
+//! reading files and printing to the terminal.
+//!
+//! ## Stages
+//!
+//! 1. **Legacy Upgrade** — Bring m0n0wall and old pfSense configs to the
+//!    modern pfSense layout (see [`crate::legacy`])
+//! 2. **Platforms** — Resolve source platform (explicit or detected) and
+//!    reject same-platform conversions
+//! 3. **Section Filters** — Drop source top-level sections that were not
+//!    selected for transfer
+//! 4. **Encoding** — Decode double-encoded entities and drop control
+//!    characters in source text (see [`crate::encoding`])
+//! 5. **DHCP Backend Resolution** — Determine ISC vs Kea strategy
+//! 6. **Interface Preflight** — Ensure every physical source interface has a
+//!    target destination
+//! 7. **Diff & Merge** — Merge source into the target baseline
+//! 8. **Transform Pipeline** — Interface, reference, and platform cleanup,
+//!    sections the target cannot hold pruned and logged (see
+//!    [`crate::target_prune`]), duplicate certificates and CAs folded by fingerprint (see
+//!    [`crate::transform::certs::reconcile_duplicates`]), then boolean flags
+//!    re-encoded for the target (see [`crate::transform::booleans`])
+//! 9. **DHCP Migration** — Migrate ISC DHCP to Kea when required
+//! 10. **Round-Trip Snapshots** — Restore OPNsense models from a previous
+//!     OPNsense → pfSense hop, or snapshot them into pfSense output
+//! 11. **Static Map DNS** — Optionally add or verify Unbound host overrides
+//!     for DHCP static mappings (see [`crate::transform::dhcp::static_dns`])
+//! 12. **Section Policy** — Apply `copy-verbatim` / `force-target-default`
+//!     overrides from a [`ConversionPolicy`]
+//! 13. **Orphan Pruning** — Optionally remove certs, CAs, aliases, gateways,
+//!     and schedules nothing refers to (see [`crate::orphans`])
+//! 14. **Key Stripping** — Optionally remove certificate and CA private keys
+//!     (see [`crate::transform::certs::strip_private_keys`])
+//!
+//! Each stage's duration is recorded in [`ConversionResult::stage_timings`];
+//! [`ConversionPipeline::progress`] also prints them as they finish.
+//!
+//! ## DHCP Backend Handling
+//!
+//! OPNsense 26+ defaults to Kea. With [`RequestedDhcpBackend::Auto`], a Kea
+//! migration that fails or reports fatal errors falls back to ISC and records
+//! a warning; an explicit Kea request fails instead. Legacy DHCPv6 is kept for
+//! interfaces whose prefix information cannot be migrated.
+//!
+//! [`RequestedDhcpBackend::Auto`]: crate::transform::dhcp::RequestedDhcpBackend::Auto
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use pfopn_convert::pipeline::ConversionPipeline;
+//! use pfopn_convert::transform::dhcp::RequestedDhcpBackend;
+//! use xml_diff_core::parse_file;
+//!
+//! let source = parse_file("pfsense-config.xml".as_ref())?;
+//! let baseline = parse_file("opnsense-fresh.xml".as_ref())?;
+//!
+//! let result = ConversionPipeline::new("opnsense")
+//!     .backend(RequestedDhcpBackend::Kea)
+//!     .transfer_users(false)
+//!     .exclude_section("packages")
+//!     .run(&source, &baseline)?;
+//!
+//! for diagnostic in &result.diagnostics {
+//!     eprintln!("{diagnostic}");
+//! }
+//! xml_diff_core::write_file(&result.output, "converted.xml".as_ref())?;
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use xml_diff_core::{diff_with_options, DiffOptions, XmlNode};
+
+use crate::backend_detect::detect_dhcp_backend;
+use crate::conversion_summary::{section_deltas, summarize, ConversionSummary, SectionDelta};
+use crate::convert_policy::ConversionPolicy;
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::diagnostics::{Diagnostics, Severity};
+use crate::encoding::{self, EncodingRepair};
+use crate::interface_guard::enforce_interface_compat;
+use crate::legacy::{self, LegacyReport, LegacyUpgrade};
+use crate::merge::{apply_safe_merge_with_diagnostics, MergeOptions, MergeTarget};
+use crate::orphans::{prune_orphans, Orphan};
+use crate::plus_features::plus_feature_uses;
+use crate::progress::{StageTimer, StageTiming};
+use crate::provenance::{self, Provenance};
+use crate::scan::review_sections;
+use crate::section::section_tags;
+use crate::site_map::{rewrite_peers, SiteMap};
+use crate::target_prune::{
+    prune_imported_incompatible_sections, PrunedNode, BOOKKEEPING_SECTIONS, CARRIED_MODELS,
+    CARRIED_PACKAGES, CARRIED_SECTIONS, COSMETIC_SECTIONS,
+};
+use crate::transform::{
+    booleans, bridges, certs, device_refs, dhcp, ifgroups, interface_presence, interface_settings,
+    laggs, lan_ip, logical_refs, mvc_snapshot, opnsense_assignments, pfblocker, renumber,
+    section_sync, small_packages, staticroutes, vlan_ifnames, wireguard,
+};
+use crate::verify_codes::FindingCode;
+use crate::verify_nat;
+
+/// Builder for a single source -> target conversion.
+#[derive(Debug, Clone)]
+pub struct ConversionPipeline {
+    to: String,
+    from: Option<String>,
+    backend: dhcp::RequestedDhcpBackend,
+    merge_options: MergeOptions,
+    lan_ip: Option<String>,
+    lan_literals: lan_ip::LanLiterals,
+    renumber: Vec<renumber::SubnetMapping>,
+    site_map: SiteMap,
+    disable_dhcp: bool,
+    interface_map: BTreeMap<String, String>,
+    include_sections: Vec<String>,
+    exclude_sections: Vec<String>,
+    policy: ConversionPolicy,
+    snapshots: bool,
+    prune_orphans: bool,
+    keep_unknown: bool,
+    strip_private_keys: bool,
+    static_map_dns: dhcp::StaticMapDns,
+    parse_repairs: Vec<EncodingRepair>,
+    legacy: LegacyReport,
+    provenance: Option<Provenance>,
+}
+
+/// Structured outcome of [`ConversionPipeline::run`].
+#[derive(Debug, Clone)]
+pub struct ConversionResult {
+    /// Converted configuration tree, ready to be written.
+    pub output: XmlNode,
+    /// Resolved source platform (`pfsense` or `opnsense`).
+    pub from: String,
+    /// Target platform (`pfsense` or `opnsense`).
+    pub to: String,
+    /// DHCP backend used for the output after any fallback.
+    pub effective_backend: dhcp::EffectiveDhcpBackend,
+    /// ISC -> Kea migration statistics, when a migration was attempted and succeeded.
+    pub dhcp_migration: Option<dhcp::KeaMigrationStats>,
+    /// Whether legacy DHCPv6 was kept for interfaces that could not move to Kea.
+    pub preserved_legacy_dhcpv6: bool,
+    /// Top-level source sections dropped because the target cannot hold them.
+    pub pruned_sections: Vec<String>,
+    /// Every pruned section, package config, and model, with the reason and
+    /// suggested manual action (see [`crate::target_prune`]).
+    pub prune_log: Vec<PrunedNode>,
+    /// Unreferenced objects removed when orphan pruning is enabled.
+    pub pruned_orphans: Vec<Orphan>,
+    /// Source elements whose text was repaired, parse-time repairs first.
+    pub encoding_repairs: Vec<EncodingRepair>,
+    /// Structural upgrades applied to a legacy source (see [`crate::legacy`]).
+    pub legacy_upgrades: Vec<LegacyUpgrade>,
+    /// Duplicate certificates and CAs removed, with the refid that replaced each.
+    pub cert_remaps: Vec<certs::CertRemap>,
+    /// Refids of certificates and CAs whose private keys were removed.
+    pub stripped_keys: Vec<String>,
+    /// Object counts for the converted output.
+    pub summary: ConversionSummary,
+    /// Per-kind counts in the source, target baseline, and output.
+    pub section_deltas: Vec<SectionDelta>,
+    /// Non-fatal issues that should be reviewed before restore.
+    pub diagnostics: Diagnostics,
+    /// Time spent in each stage, merge-time transforms included.
+    pub stage_timings: Vec<StageTiming>,
+}
+
+impl ConversionPipeline {
+    /// Create a pipeline targeting `to` (`pfsense` or `opnsense`) with CLI defaults.
+    pub fn new(to: impl Into<String>) -> Self {
+        Self {
+            to: to.into(),
+            from: None,
+            backend: dhcp::RequestedDhcpBackend::Auto,
+            merge_options: MergeOptions::default(),
+            lan_ip: None,
+            lan_literals: lan_ip::LanLiterals::Rewrite,
+            renumber: Vec::new(),
+            site_map: SiteMap::default(),
+            disable_dhcp: false,
+            interface_map: BTreeMap::new(),
+            include_sections: Vec::new(),
+            exclude_sections: Vec::new(),
+            policy: ConversionPolicy::default(),
+            snapshots: true,
+            prune_orphans: false,
+            keep_unknown: false,
+            strip_private_keys: false,
+            static_map_dns: dhcp::StaticMapDns::Off,
+            parse_repairs: Vec::new(),
+            legacy: LegacyReport::default(),
+            provenance: None,
+        }
+    }
+
+    /// Set the source platform explicitly instead of detecting it from the root tag.
+    pub fn from_platform(mut self, from: impl Into<String>) -> Self {
+        self.from = Some(from.into());
+        self
+    }
+
+    /// Set the DHCP backend policy.
+    pub fn backend(mut self, backend: dhcp::RequestedDhcpBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Toggle transfer of system users referenced by OpenVPN.
+    pub fn transfer_users(mut self, enabled: bool) -> Self {
+        self.merge_options.transfer_users = enabled;
+        self
+    }
+
+    /// Toggle transfer of certificates referenced by OpenVPN.
+    pub fn transfer_certs(mut self, enabled: bool) -> Self {
+        self.merge_options.transfer_certs = enabled;
+        self
+    }
+
+    /// Toggle transfer of CAs referenced by OpenVPN.
+    pub fn transfer_cas(mut self, enabled: bool) -> Self {
+        self.merge_options.transfer_cas = enabled;
+        self
+    }
+
+    /// Set the LAN IPv4 address (with optional `/prefix`) on the output.
+    pub fn lan_ip(mut self, lan_ip: impl Into<String>) -> Self {
+        self.lan_ip = Some(lan_ip.into());
+        self
+    }
+
+    /// Choose whether old-LAN literals in rules, NAT, and aliases are
+    /// rewritten or reported when the LAN IP changes.
+    pub fn lan_literals(mut self, mode: lan_ip::LanLiterals) -> Self {
+        self.lan_literals = mode;
+        self
+    }
+
+    /// Move every address in one subnet into another (repeatable).
+    pub fn renumber(mut self, mapping: renumber::SubnetMapping) -> Self {
+        self.renumber.push(mapping);
+        self
+    }
+
+    /// Point tunnel peers at the new addresses of sites in `sites`.
+    pub fn site_map(mut self, sites: SiteMap) -> Self {
+        self.site_map = sites;
+        self
+    }
+
+    /// Disable DHCP services in the output.
+    pub fn disable_dhcp(mut self, disabled: bool) -> Self {
+        self.disable_dhcp = disabled;
+        self
+    }
+
+    /// Route a source logical interface onto a differently named target interface.
+    pub fn map_interface(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.interface_map.insert(from.into(), to.into());
+        self
+    }
+
+    /// Only carry over the given source section.
+    ///
+    /// Accepts logical section names (`system`, `interfaces`, `firewall`,
+    /// `services`, `vpn`, `packages`) or literal top-level tags. Repeatable;
+    /// when never called, every section is carried over.
+    pub fn include_section(mut self, section: impl Into<String>) -> Self {
+        self.include_sections.push(section.into());
+        self
+    }
+
+    /// Never carry over the given source section (logical name or top-level tag).
+    pub fn exclude_section(mut self, section: impl Into<String>) -> Self {
+        self.exclude_sections.push(section.into());
+        self
+    }
+
+    /// Apply a per-section policy (see [`crate::convert_policy`]).
+    ///
+    /// `ignore` sections are dropped from the source like
+    /// [`exclude_section`](Self::exclude_section); `copy-verbatim` and
+    /// `force-target-default` sections replace the converted output section
+    /// after every other stage except `disable_dhcp`.
+    pub fn policy(mut self, policy: ConversionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Toggle round-trip snapshots of OPNsense models (see [`mvc_snapshot`]).
+    ///
+    /// When disabled, pfSense output carries no hidden snapshot container and
+    /// snapshots found in a pfSense source are ignored.
+    pub fn snapshots(mut self, enabled: bool) -> Self {
+        self.snapshots = enabled;
+        self
+    }
+
+    /// Print each stage's timing to stderr as it finishes (see [`crate::progress`]).
+    ///
+    /// Timings are recorded in [`ConversionResult::stage_timings`] either way.
+    pub fn progress(mut self, enabled: bool) -> Self {
+        self.merge_options.progress = enabled;
+        self
+    }
+
+    /// Toggle removal of unreferenced certs, CAs, aliases, gateways, and
+    /// schedules from the output (see [`crate::orphans`]).
+    pub fn prune_orphans(mut self, enabled: bool) -> Self {
+        self.prune_orphans = enabled;
+        self
+    }
+
+    /// Keep content the target cannot hold under
+    /// [`crate::target_prune::PRESERVED_CONTAINER`] instead of dropping it.
+    pub fn keep_unknown(mut self, enabled: bool) -> Self {
+        self.keep_unknown = enabled;
+        self
+    }
+
+    /// Remove private keys from certificates and CAs in the output, for
+    /// targets whose keys are provisioned separately.
+    pub fn strip_private_keys(mut self, enabled: bool) -> Self {
+        self.strip_private_keys = enabled;
+        self
+    }
+
+    /// Add (`Materialize`) or check (`Verify`) Unbound host overrides for
+    /// DHCP static mapping hostnames (see [`crate::transform::dhcp::static_dns`]).
+    pub fn static_map_dns(mut self, mode: dhcp::StaticMapDns) -> Self {
+        self.static_map_dns = mode;
+        self
+    }
+
+    /// Report encoding repairs made while parsing the source (see
+    /// [`encoding::parse_source_file`]) with the pipeline's own.
+    pub fn parse_repairs(mut self, repairs: Vec<EncodingRepair>) -> Self {
+        self.parse_repairs = repairs;
+        self
+    }
+
+    /// Report legacy upgrades already applied to the source (see
+    /// [`legacy::upgrade`]) with the pipeline's own.
+    pub fn legacy_upgrades(mut self, report: LegacyReport) -> Self {
+        self.legacy = report;
+        self
+    }
+
+    /// Stamp the output with a `<revision>` for this conversion and, if
+    /// requested, a provenance marker (see [`crate::provenance`]).
+    pub fn provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Convert `source` onto the `target` baseline.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// - A legacy source is too old to upgrade
+    /// - Platforms cannot be resolved or are the same
+    /// - DHCP backend requirements cannot be met
+    /// - Interface preflight fails
+    /// - The merge or an explicitly requested Kea migration fails
+    /// - `lan_ip` is not a valid IPv4 address
+    /// - `renumber` mappings overlap each other or an interface left in place
+    pub fn run(&self, source: &XmlNode, target: &XmlNode) -> Result<ConversionResult> {
+        let mut timer = StageTimer::new(self.merge_options.progress);
+
+        // m0n0wall and old pfSense layouts must be upgraded before anything reads them
+        let mut legacy_report = self.legacy.clone();
+        let upgraded;
+        let source = if legacy::is_legacy(source) {
+            let mut tree = source.clone();
+            let report = legacy::upgrade(&mut tree)?;
+            legacy_report.from_version = legacy_report.from_version.or(report.from_version);
+            legacy_report.upgrades.extend(report.upgrades);
+            legacy_report.unsupported.extend(report.unsupported);
+            upgraded = tree;
+            &upgraded
+        } else {
+            source
+        };
+        timer.mark("legacy upgrade");
+
+        let to = normalize_platform(&self.to)
+            .with_context(|| format!("unsupported target platform '{}'", self.to))?;
+        let from = match &self.from {
+            Some(from) => normalize_platform(from)
+                .with_context(|| format!("unsupported source platform '{from}'"))?,
+            None => detect_platform(source)?,
+        };
+        if from == to {
+            bail!(
+                "from and to are the same platform ({from}); conversion requires different platforms"
+            );
+        }
+
+        let filtered;
+        let input = if self.include_sections.is_empty()
+            && self.exclude_sections.is_empty()
+            && self.policy.is_default()
+            && source.get_child(provenance::MARKER_TAG).is_none()
+        {
+            source
+        } else {
+            filtered = self.filter_sections(source);
+            &filtered
+        };
+        timer.mark("filter sections");
+
+        // Double-encoded entities and control characters break the importer
+        let mut encoding_repairs = self.parse_repairs.clone();
+        let normalized;
+        let input = if encoding::needs_normalizing(input) {
+            let mut tree = input.clone();
+            encoding_repairs.extend(encoding::normalize(&mut tree));
+            normalized = tree;
+            &normalized
+        } else {
+            input
+        };
+        timer.mark("encoding");
+
+        // pfSense reads any present legacy flag as on, even `<disabled>0</disabled>`
+        let canonical;
+        let input = if booleans::needs_canonicalizing(input, from) {
+            let mut tree = input.clone();
+            booleans::canonicalize(&mut tree, from);
+            canonical = tree;
+            &canonical
+        } else {
+            input
+        };
+        timer.mark("boolean source flags");
+
+        // Resolve DHCP backend strategy (ISC vs Kea)
+        let source_backend = detect_dhcp_backend(input);
+        let mut effective_backend =
+            dhcp::resolve_effective_backend(self.backend, input, target, to);
+        dhcp::ensure_backend_readiness(target, self.backend, effective_backend)?;
+        timer.mark("dhcp backend");
+
+        // Ensure source and target have compatible interface assignments
+        let interface_map = (!self.interface_map.is_empty()).then_some(&self.interface_map);
+        enforce_interface_compat(input, target, interface_map)?;
+        timer.mark("interface preflight");
+
+        // Merge source config into target baseline (builds from target, inserts from source)
+        let opts = DiffOptions {
+            include_identical: false,
+            ..DiffOptions::default()
+        };
+        let entries = diff_with_options(input, target, &opts);
+        timer.mark("diff");
+        let merged = apply_safe_merge_with_diagnostics(
+            input,
+            target,
+            &entries,
+            MergeTarget::Right,
+            self.merge_options.clone(),
+        )
+        .with_context(|| "failed while applying safe conversion merge")?;
+        timer.extend(merged.timings);
+        let mut out = merged.tree;
+        let mut diagnostics = merged.diagnostics;
+        for item in &legacy_report.unsupported {
+            diagnostics.warn("legacy", format!("{}: {}", item.path, item.message));
+        }
+        // Plus-only settings have no OPNsense counterpart
+        if from == "pfsense" {
+            for used in plus_feature_uses(input) {
+                diagnostics.warn(
+                    "plus",
+                    format!(
+                        "{}: {} is a pfSense Plus feature {to} lacks; {}",
+                        used.path, used.description, used.action
+                    ),
+                );
+            }
+        }
+
+        // Update root tag to match target platform
+        out.tag = to.to_string();
+
+        // Apply interface-level transformations
+        for note in interface_settings::apply(&mut out, input, target, interface_map) {
+            diagnostics.warn("interfaces", note);
+        }
+        interface_presence::prune_missing(&mut out, target);
+        timer.mark("interface settings");
+
+        // Point references at user-mapped interfaces (e.g. opt2 -> opt1)
+        logical_refs::apply(&mut out, interface_map);
+        bridges::rewrite_member_options(&mut out, interface_map);
+        staticroutes::rewrite_gateway_refs(&mut out, interface_map);
+
+        // Build logical interface mapping for OPNsense (wan/lan/opt -> device references)
+        let logical_map = if to == "opnsense" {
+            let map = opnsense_assignments::normalize(&mut out);
+            if map.is_empty() {
+                None
+            } else {
+                Some(map)
+            }
+        } else {
+            None
+        };
+
+        // Update references that use logical interface names
+        logical_refs::apply(&mut out, logical_map.as_ref());
+        staticroutes::rewrite_gateway_refs(&mut out, logical_map.as_ref());
+
+        timer.mark("logical refs");
+
+        // Remove sections incompatible with target platform
+        let (pruned_sections, prune_log) =
+            prune_imported_incompatible_sections(&mut out, to, target, self.keep_unknown);
+        timer.mark("prune sections");
+
+        // Update device references (physical interface names)
+        device_refs::apply(&mut out, input, target, interface_map);
+        laggs::reconcile(&mut out, input, target, &mut diagnostics);
+        timer.mark("device refs");
+
+        // Outbound NAT mappings must still resolve once interfaces are remapped
+        for finding in verify_nat::nat_findings(&out) {
+            if matches!(
+                finding.code,
+                FindingCode::NatMissingInterface | FindingCode::NatMissingAlias
+            ) {
+                diagnostics.warn("nat", finding.message);
+            }
+        }
+        timer.mark("nat refs");
+
+        // Source and baseline may carry the same certificate under different refids
+        let cert_remaps = certs::reconcile_duplicates(&mut out, target);
+        timer.mark("cert reconcile");
+
+        // Apply platform-specific cleanup and normalization
+        if to == "opnsense" {
+            pfblocker::prune_pfblocker_floating_rules_for_opnsense(&mut out);
+            vlan_ifnames::normalize_opnsense_vlan_ifnames(&mut out);
+            wireguard::normalize_opnsense_interface_names(&mut out);
+            for note in bridges::normalize_for_opnsense(&mut out) {
+                diagnostics.warn("bridges", note);
+            }
+            ifgroups::normalize_for_opnsense(&mut out);
+        } else {
+            for note in bridges::normalize_for_pfsense(&mut out) {
+                diagnostics.warn("bridges", note);
+            }
+            ifgroups::normalize_for_pfsense(&mut out);
+        }
+        timer.mark("platform cleanup");
+
+        // Presence tags vs 1/0: legacy sections still carry the source encoding
+        booleans::normalize(&mut out, to);
+        timer.mark("boolean flags");
+
+        // Override LAN IP if requested
+        if let Some(new_lan_ip) = &self.lan_ip {
+            for literal in lan_ip::apply(&mut out, new_lan_ip, self.lan_literals)? {
+                match literal.new {
+                    Some(new) => diagnostics.info(
+                        "lan_ip",
+                        format!("{}: renumbered {} to {new}", literal.path, literal.old),
+                    ),
+                    None => diagnostics.warn(
+                        "lan_ip",
+                        format!(
+                            "{}: '{}' overlaps the old LAN subnet and was not renumbered; review it for the new LAN addressing",
+                            literal.path, literal.old
+                        ),
+                    ),
+                }
+            }
+            timer.mark("lan ip");
+        }
+
+        if !self.renumber.is_empty() {
+            for value in renumber::apply(&mut out, &self.renumber)? {
+                match value.new {
+                    Some(new) => diagnostics.info(
+                        "renumber",
+                        format!("{}: renumbered {} to {new}", value.path, value.old),
+                    ),
+                    None => diagnostics.warn(
+                        "renumber",
+                        format!(
+                            "{}: '{}' overlaps a renumbered subnet but cannot be moved consistently; review it",
+                            value.path, value.old
+                        ),
+                    ),
+                }
+            }
+            timer.mark("renumber");
+        }
+
+        if !self.site_map.is_empty() {
+            for rewrite in rewrite_peers(&mut out, &self.site_map) {
+                diagnostics.info(
+                    "site_map",
+                    format!(
+                        "{}: peer {} moved from {} to {}",
+                        rewrite.path, rewrite.site, rewrite.old, rewrite.new
+                    ),
+                );
+            }
+            timer.mark("site map");
+        }
+
+        // Handle DHCP backend configuration based on target platform
+        if to == "pfsense" && effective_backend == dhcp::EffectiveDhcpBackend::Kea {
+            // pfSense with Kea: copy Kea config from source
+            seed_pfsense_kea_from_source(&mut out, input);
+        }
+
+        let mut dhcp_migration = None;
+        let mut preserved_legacy_dhcpv6 = false;
+        if to == "opnsense" && effective_backend == dhcp::EffectiveDhcpBackend::Kea {
+            // OPNsense 26+ with Kea: attempt ISC → Kea migration
+            match dhcp::migrate_isc_to_kea_opnsense(&mut out, input) {
+                Ok(stats) => {
+                    // Fall back to ISC if migration produced fatal errors
+                    if stats
+                        .warnings
+                        .iter()
+                        .any(|w| w.severity == dhcp::MigrationSeverity::Error)
+                    {
+                        effective_backend = dhcp::EffectiveDhcpBackend::Isc;
+                        diagnostics.warn(
+                            "dhcp",
+                            "Kea migration skipped due to fatal errors; falling back to ISC backend",
+                        );
+                    }
+
+                    // Preserve legacy DHCPv6 for interfaces that couldn't migrate
+                    preserved_legacy_dhcpv6 = effective_backend == dhcp::EffectiveDhcpBackend::Kea
+                        && !stats.preserved_dhcpdv6_ifaces.is_empty();
+
+                    dhcp::enforce_output_backend(
+                        &mut out,
+                        effective_backend,
+                        to,
+                        preserved_legacy_dhcpv6,
+                    );
+                    for warning in &stats.warnings {
+                        let severity = match warning.severity {
+                            dhcp::MigrationSeverity::Error => Severity::Error,
+                            dhcp::MigrationSeverity::Warning => Severity::Warning,
+                        };
+                        diagnostics.push(severity, "dhcp", warning.message.clone());
+                    }
+                    dhcp_migration = Some(stats);
+                }
+                Err(err) if self.backend == dhcp::RequestedDhcpBackend::Auto => {
+                    // In auto mode, fall back to ISC on migration failure
+                    diagnostics.warn(
+                        "dhcp",
+                        format!(
+                            "Kea migration failed in auto mode ({err}); falling back to ISC backend"
+                        ),
+                    );
+                    effective_backend = dhcp::EffectiveDhcpBackend::Isc;
+                    dhcp::enforce_output_backend(&mut out, effective_backend, to, false);
+                }
+                Err(err) => return Err(err), // In explicit mode, fail on migration error
+            }
+        } else {
+            // No migration needed, just enforce the backend
+            dhcp::enforce_output_backend(&mut out, effective_backend, to, false);
+        }
+
+        // Validate that Kea-only sources can't be downgraded to ISC without legacy data
+        if effective_backend == dhcp::EffectiveDhcpBackend::Isc
+            && source_backend.mode == "kea"
+            && !dhcp::has_legacy_dhcp_data(input)
+        {
+            let target_label = if to == "pfsense" {
+                "pfSense"
+            } else {
+                "OPNsense"
+            };
+            bail!(
+                "cannot convert Kea-only source to {target_label} ISC without source legacy DHCP data; use --backend kea or provide ISC-backed source"
+            );
+        }
+
+        timer.mark("dhcp migration");
+
+        // Bring back OPNsense-only fields from a previous OPNsense -> pfSense hop
+        if self.snapshots && to == "opnsense" {
+            let skip: &[&str] = if effective_backend == dhcp::EffectiveDhcpBackend::Kea {
+                &[]
+            } else {
+                &["OPNsense/Kea"]
+            };
+            mvc_snapshot::restore(&mut out, input, skip, &mut diagnostics);
+            timer.mark("snapshot restore");
+        }
+
+        if self.static_map_dns != dhcp::StaticMapDns::Off {
+            dhcp::static_dns::apply(&mut out, input, self.static_map_dns, to, &mut diagnostics);
+            timer.mark("static map dns");
+        }
+
+        // Per-section policy overrides win over every conversion stage
+        self.policy
+            .apply_overrides(&mut out, source, target, &mut diagnostics);
+        timer.mark("section policy");
+
+        let pruned_orphans = if self.prune_orphans {
+            let pruned = prune_orphans(&mut out);
+            timer.mark("orphans");
+            pruned
+        } else {
+            Vec::new()
+        };
+
+        let stripped_keys = if self.strip_private_keys {
+            let stripped = certs::strip_private_keys(&mut out);
+            if !stripped.is_empty() {
+                diagnostics.info(
+                    "certs",
+                    format!(
+                        "removed private keys from {} certificate(s)/CA(s); install them on the target",
+                        stripped.len()
+                    ),
+                );
+            }
+            timer.mark("strip keys");
+            stripped
+        } else {
+            Vec::new()
+        };
+
+        // Optionally disable all DHCP if requested
+        if self.disable_dhcp {
+            dhcp::disable_all(&mut out);
+        }
+
+        // Snapshot last so the digests match the written pfSense sections
+        if self.snapshots && to == "pfsense" {
+            mvc_snapshot::capture(&mut out, input);
+            timer.mark("snapshot capture");
+        }
+
+        if let Some(provenance) = &self.provenance {
+            provenance::stamp(&mut out, source, from, to, provenance);
+        }
+
+        let summary = summarize(&out);
+        let section_deltas = section_deltas(source, target, &out);
+        timer.mark("summary");
+        Ok(ConversionResult {
+            output: out,
+            from: from.to_string(),
+            to: to.to_string(),
+            effective_backend,
+            dhcp_migration,
+            preserved_legacy_dhcpv6,
+            pruned_sections,
+            prune_log,
+            pruned_orphans,
+            encoding_repairs,
+            legacy_upgrades: legacy_report.upgrades,
+            cert_remaps,
+            stripped_keys,
+            summary,
+            section_deltas,
+            diagnostics,
+            stage_timings: timer.into_timings(),
+        })
+    }
+
+    /// Return a copy of `source` with only the selected top-level sections.
+    fn filter_sections(&self, source: &XmlNode) -> XmlNode {
+        let include = expand_sections(&self.include_sections);
+        let mut exclude = expand_sections(&self.exclude_sections);
+        exclude.extend(self.policy.excluded_source_tags());
+        // A marker from an earlier conversion describes that conversion
+        exclude.push(provenance::MARKER_TAG.to_string());
+        let mut out = source.clone();
+        out.children.retain(|child| {
+            let tag = child.tag.as_str();
+            (include.is_empty() || include.iter().any(|t| t == tag))
+                && !exclude.iter().any(|t| t == tag)
+        });
+        out
+    }
+}
+
+/// Serializable view of a [`ConversionResult`] without the output tree,
+/// passed to `convert --template`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionReport {
+    pub from: String,
+    pub to: String,
+    pub effective_backend: dhcp::EffectiveDhcpBackend,
+    pub preserved_legacy_dhcpv6: bool,
+    pub pruned_sections: Vec<String>,
+    pub prune_log: Vec<PrunedNode>,
+    pub pruned_orphans: Vec<Orphan>,
+    pub encoding_repairs: Vec<EncodingRepair>,
+    pub legacy_upgrades: Vec<LegacyUpgrade>,
+    pub cert_remaps: Vec<certs::CertRemap>,
+    pub stripped_keys: Vec<String>,
+    pub summary: ConversionSummary,
+    pub section_deltas: Vec<SectionDelta>,
+    pub diagnostics: Diagnostics,
+    pub stage_timings: Vec<StageTiming>,
+}
+
+impl ConversionResult {
+    /// Everything but the output tree, for templates and JSON.
+    pub fn report(&self) -> ConversionReport {
+        ConversionReport {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            effective_backend: self.effective_backend,
+            preserved_legacy_dhcpv6: self.preserved_legacy_dhcpv6,
+            pruned_sections: self.pruned_sections.clone(),
+            prune_log: self.prune_log.clone(),
+            pruned_orphans: self.pruned_orphans.clone(),
+            encoding_repairs: self.encoding_repairs.clone(),
+            legacy_upgrades: self.legacy_upgrades.clone(),
+            cert_remaps: self.cert_remaps.clone(),
+            stripped_keys: self.stripped_keys.clone(),
+            summary: self.summary,
+            section_deltas: self.section_deltas.clone(),
+            diagnostics: self.diagnostics.clone(),
+            stage_timings: self.stage_timings.clone(),
+        }
+    }
+
+    /// Everything a strict conversion must refuse, in report order.
+    ///
+    /// - sections pruned because the target platform cannot hold them (other
+    ///   than bookkeeping, carried, and cosmetic sections), and
+    ///   each untranslated package config or model inside a pruned
+    ///   `installedpackages` or `OPNsense` container
+    /// - source sections without dedicated handling (see
+    ///   [`crate::scan::review_sections`]) that reached the output unchanged
+    /// - every warning or error diagnostic
+    ///
+    /// `source` is the configuration passed to [`ConversionPipeline::run`].
+    pub fn strict_violations(&self, source: &XmlNode) -> Vec<String> {
+        let mut out = Vec::new();
+        for tag in &self.pruned_sections {
+            if BOOKKEEPING_SECTIONS.contains(&tag.as_str())
+                || CARRIED_SECTIONS.contains(&tag.as_str())
+                || COSMETIC_SECTIONS.contains(&tag.as_str())
+            {
+                continue;
+            }
+            let (kind, carried) = match tag.as_str() {
+                "installedpackages" => ("package", CARRIED_PACKAGES),
+                "OPNsense" => ("model", CARRIED_MODELS),
+                _ => {
+                    out.push(format!(
+                        "section '{tag}' dropped: {} cannot hold it",
+                        self.to
+                    ));
+                    continue;
+                }
+            };
+            let children: BTreeSet<&str> = source
+                .get_child(tag)
+                .map(|c| c.children.iter().map(|c| c.tag.as_str()).collect())
+                .unwrap_or_default();
+            out.extend(
+                children
+                    .into_iter()
+                    .filter(|child| !carried.contains(child))
+                    .filter(|child| kind != "package" || small_packages::find(child).is_none())
+                    .map(|child| format!("{kind} '{child}' dropped: {} cannot hold it", self.to)),
+            );
+        }
+        for tag in review_sections(source) {
+            if BOOKKEEPING_SECTIONS.contains(&tag.as_str()) || section_sync::is_synced_section(&tag)
+            {
+                continue;
+            }
+            let copied = source
+                .get_child(&tag)
+                .is_some_and(|node| self.output.get_child(&tag) == Some(node));
+            if copied {
+                out.push(format!(
+                    "section '{tag}' copied verbatim without translation"
+                ));
+            }
+        }
+        out.extend(
+            self.diagnostics
+                .iter()
+                .filter(|d| d.severity >= Severity::Warning)
+                .map(ToString::to_string),
+        );
+        out
+    }
+}
+
+/// Expand logical section names into concrete top-level tags.
+fn expand_sections(sections: &[String]) -> Vec<String> {
+    sections
+        .iter()
+        .flat_map(|section| match section_tags(section) {
+            Some(tags) => tags.iter().map(|t| t.to_string()).collect(),
+            None => vec![section.clone()],
+        })
+        .collect()
+}
+
+fn normalize_platform(name: &str) -> Option<&'static str> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "pfsense" => Some("pfsense"),
+        "opnsense" => Some("opnsense"),
+        _ => None,
+    }
+}
+
+fn detect_platform(node: &XmlNode) -> Result<&'static str> {
+    match detect_config(node) {
+        ConfigFlavor::PfSense => Ok("pfsense"),
+        ConfigFlavor::OpnSense => Ok("opnsense"),
+        ConfigFlavor::Unknown => bail!("unable to auto-detect platform from root tag"),
+    }
+}
+
+/// Seed pfSense Kea configuration from source config.
+///
+/// Looks for Kea config in `<kea>` (pfSense) or `<OPNsense><Kea>` (OPNsense)
+/// and copies it to the output as `<kea>`, replacing any baseline section.
+fn seed_pfsense_kea_from_source(out: &mut XmlNode, source: &XmlNode) {
+    let source_kea = source
+        .get_child("kea")
+        .cloned()
+        .or_else(|| {
+            source
+                .get_child("OPNsense")
+                .and_then(|opn| opn.get_child("Kea"))
+                .cloned()
+        })
+        .map(|mut node| {
+            node.tag = "kea".to_string();
+            node
+        });
+    let Some(source_kea) = source_kea else {
+        return;
+    };
+    out.children.retain(|c| c.tag != "kea");
+    out.children.push(source_kea);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::ConversionPipeline;
+    use crate::convert_policy::parse_conversion_policy;
+
+    #[test]
+    fn run_returns_output_tree_and_summary() {
+        let source = parse(
+            br#"<pfsense><interfaces><lan><if>igb0</if><ipaddr>10.0.0.1</ipaddr><subnet>24</subnet></lan></interfaces><filter><rule><interface>lan</interface></rule></filter></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet0</if><subnet>24</subnet></lan></interfaces></opnsense>"#,
+        )
+        .expect("target parse");
+
+        let result = ConversionPipeline::new("opnsense")
+            .run(&source, &target)
+            .expect("convert");
+
+        assert_eq!(result.from, "pfsense");
+        assert_eq!(result.output.tag, "opnsense");
+        assert_eq!(result.summary.rules, 1);
+        assert_eq!(
+            result.output.get_text(&["interfaces", "lan", "if"]),
+            Some("vtnet0")
+        );
+        assert_eq!(
+            result.output.get_text(&["interfaces", "lan", "ipaddr"]),
+            Some("10.0.0.1")
+        );
+    }
+
+    #[test]
+    fn run_reencodes_presence_flags_for_opnsense() {
+        let source = parse(
+            br#"<pfsense><interfaces><lan><enable></enable><if>igb0</if><blockbogons></blockbogons></lan></interfaces><filter><rule><interface>lan</interface><disabled></disabled></rule></filter></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet0</if><enable>1</enable></lan></interfaces></opnsense>"#,
+        )
+        .expect("target parse");
+
+        let result = ConversionPipeline::new("opnsense")
+            .run(&source, &target)
+            .expect("convert");
+
+        let out = &result.output;
+        assert_eq!(out.get_text(&["interfaces", "lan", "enable"]), Some("1"));
+        assert_eq!(
+            out.get_text(&["interfaces", "lan", "blockbogons"]),
+            Some("1")
+        );
+        assert_eq!(out.get_text(&["filter", "rule", "disabled"]), Some("1"));
+    }
+
+    #[test]
+    fn run_rejects_same_platform() {
+        let source = parse(br#"<opnsense><interfaces><lan/></interfaces></opnsense>"#)
+            .expect("source parse");
+        let err = ConversionPipeline::new("opnsense")
+            .run(&source, &source)
+            .expect_err("same platform");
+        assert!(err.to_string().contains("same platform"));
+    }
+
+    #[test]
+    fn exclude_section_drops_source_sections() {
+        let source = parse(
+            br#"<pfsense><interfaces><lan><if>igb0</if></lan></interfaces><filter><rule><interface>lan</interface></rule></filter></pfsense>"#,
+        )
+        .expect("source parse");
+        let target =
+            parse(br#"<opnsense><interfaces><lan><if>vtnet0</if></lan></interfaces></opnsense>"#)
+                .expect("target parse");
+
+        let result = ConversionPipeline::new("opnsense")
+            .exclude_section("firewall")
+            .run(&source, &target)
+            .expect("convert");
+        assert_eq!(result.summary.rules, 0);
+        assert!(result.output.get_child("filter").is_none());
+    }
+
+    #[test]
+    fn policy_keeps_target_filter_and_copies_section_verbatim() {
+        let source = parse(
+            br#"<pfsense><interfaces><lan><if>igb0</if></lan></interfaces><filter><rule><interface>lan</interface></rule></filter><syslog><interface>lan</interface></syslog></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet0</if></lan></interfaces><filter><rule><descr>baseline</descr></rule></filter></opnsense>"#,
+        )
+        .expect("target parse");
+        let policy = parse_conversion_policy(
+            "[sections]\nfilter = \"force-target-default\"\nsyslog = \"copy-verbatim\"\n",
+            "test".to_string(),
+        )
+        .expect("policy");
+
+        let result = ConversionPipeline::new("opnsense")
+            .policy(policy)
+            .run(&source, &target)
+            .expect("convert");
+        assert_eq!(
+            result.output.get_text(&["filter", "rule", "descr"]),
+            Some("baseline")
+        );
+        assert_eq!(result.summary.rules, 1);
+        assert_eq!(
+            result.output.get_text(&["syslog", "interface"]),
+            Some("lan")
+        );
+    }
+
+    #[test]
+    fn map_interface_rewrites_rule_references() {
+        let source = parse(
+            br#"<pfsense><interfaces><lan><if>igb0</if></lan><opt2><if>igb2</if></opt2></interfaces><filter><rule><interface>opt2</interface></rule></filter></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet0</if></lan><opt1><if>vtnet1</if></opt1></interfaces></opnsense>"#,
+        )
+        .expect("target parse");
+
+        assert!(ConversionPipeline::new("opnsense")
+            .run(&source, &target)
+            .is_err());
+        let result = ConversionPipeline::new("opnsense")
+            .map_interface("opt2", "opt1")
+            .run(&source, &target)
+            .expect("convert");
+        assert_eq!(
+            result.output.get_text(&["filter", "rule", "interface"]),
+            Some("opt1")
+        );
+    }
+}
@@ -211,7 +211,7 @@ fn collect_pfsense_installed_packages(root: &XmlNode) -> Vec<String> {
     out
 }
 
-fn collect_opnsense_declared_plugins(root: &XmlNode) -> Vec<String> {
+pub(crate) fn collect_opnsense_declared_plugins(root: &XmlNode) -> Vec<String> {
     let Some(system) = root.get_child("system") else {
         return Vec::new();
     };
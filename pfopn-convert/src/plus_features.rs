@@ -0,0 +1,109 @@
+//! pfSense Plus features that CE and OPNsense cannot hold.
+//!
+//! Plus and CE write the same `<pfsense>` root and overlapping `<version>`
+//! numbers, so the edition is told apart by the settings only Plus writes.
+//! [`PLUS_FEATURES`] lists them; [`plus_feature_uses`] finds each one that
+//! is switched on, and [`crate::detect::detect_pfsense_edition`] treats any
+//! use as Plus evidence.
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::transform::booleans::is_truthy;
+
+/// A setting only pfSense Plus writes.
+#[derive(Debug, Clone, Copy)]
+pub struct PlusFeature {
+    /// Short identifier used in reports.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Items that may carry the setting (`openvpn/openvpn-server`).
+    pub items: &'static [&'static str],
+    /// Child of each item that turns the feature on.
+    pub field: &'static str,
+    /// What to do on a target without the feature.
+    pub action: &'static str,
+}
+
+/// Known Plus-only settings.
+pub const PLUS_FEATURES: &[PlusFeature] = &[PlusFeature {
+    name: "openvpn-dco",
+    description: "OpenVPN Data Channel Offload",
+    items: &["openvpn/openvpn-server", "openvpn/openvpn-client"],
+    field: "dco",
+    action: "the tunnel runs without kernel offload; check throughput and that peers do not rely on DCO-only ciphers",
+}];
+
+/// One switched-on Plus feature in a config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlusFeatureUse {
+    pub feature: &'static str,
+    pub description: &'static str,
+    /// Path of the item using it (`openvpn/openvpn-server[2]`).
+    pub path: String,
+    pub action: &'static str,
+}
+
+/// Every switched-on [`PLUS_FEATURES`] entry in `root`, in table order.
+pub fn plus_feature_uses(root: &XmlNode) -> Vec<PlusFeatureUse> {
+    let mut out = Vec::new();
+    for feature in PLUS_FEATURES {
+        for item_path in feature.items {
+            let Some((parent_path, tag)) = item_path.rsplit_once('/') else {
+                continue;
+            };
+            let parent_steps: Vec<&str> = parent_path.split('/').collect();
+            let Some(parent) = descend(root, &parent_steps) else {
+                continue;
+            };
+            let items = parent.get_children(tag);
+            for (idx, item) in items.iter().enumerate() {
+                let on = item.get_text(&[feature.field]).is_some_and(is_truthy);
+                if !on {
+                    continue;
+                }
+                let path = if items.len() > 1 {
+                    format!("{item_path}[{}]", idx + 1)
+                } else {
+                    (*item_path).to_string()
+                };
+                out.push(PlusFeatureUse {
+                    feature: feature.name,
+                    description: feature.description,
+                    path,
+                    action: feature.action,
+                });
+            }
+        }
+    }
+    out
+}
+
+fn descend<'a>(root: &'a XmlNode, steps: &[&str]) -> Option<&'a XmlNode> {
+    steps
+        .iter()
+        .try_fold(root, |node, step| node.get_child(step))
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::plus_feature_uses;
+
+    #[test]
+    fn finds_enabled_plus_settings_with_paths() {
+        let root = parse(
+            b"<pfsense><openvpn>\
+              <openvpn-server><vpnid>1</vpnid><dco>enabled</dco></openvpn-server>\
+              <openvpn-server><vpnid>2</vpnid></openvpn-server>\
+              <openvpn-client><vpnid>3</vpnid><dco></dco></openvpn-client>\
+              </openvpn></pfsense>",
+        )
+        .expect("parse");
+        let uses = plus_feature_uses(&root);
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses[0].feature, "openvpn-dco");
+        assert_eq!(uses[0].path, "openvpn/openvpn-server[1]");
+    }
+}
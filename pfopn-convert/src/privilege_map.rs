@@ -0,0 +1,113 @@
+//! GUI privilege translation between pfSense and OPNsense.
+//!
+//! Both platforms grant web GUI access through `<priv>` entries on users and
+//! groups, but many identifiers diverged (for example pfSense
+//! `page-services-dnsresolver` is OPNsense `page-services-unbound`). The table
+//! lives in `mappings/privileges.toml` and is embedded at build time.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// One pfSense <-> OPNsense privilege pair.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PrivilegeMapEntry {
+    pub pfsense: String,
+    pub opnsense: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivilegeMap {
+    pub entries: Vec<PrivilegeMapEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrivilegeMapFile {
+    privilege: Vec<PrivilegeMapEntry>,
+}
+
+#[derive(Debug, Error)]
+pub enum PrivilegeMapLoadError {
+    #[error("failed to read privilege map {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("failed to parse privilege map {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+impl PrivilegeMap {
+    /// Translate `privilege` into the identifier used by platform `to`.
+    ///
+    /// Returns `None` when the table has no entry for the source side.
+    pub fn translate(&self, privilege: &str, to: &str) -> Option<&str> {
+        let privilege = privilege.trim();
+        self.entries.iter().find_map(|entry| match to {
+            "opnsense" if entry.pfsense == privilege => Some(entry.opnsense.as_str()),
+            "pfsense" if entry.opnsense == privilege => Some(entry.pfsense.as_str()),
+            _ => None,
+        })
+    }
+}
+
+pub fn load_privilege_map(path: &Path) -> Result<PrivilegeMap, PrivilegeMapLoadError> {
+    let raw = fs::read_to_string(path).map_err(|source| PrivilegeMapLoadError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    parse_privilege_map(&raw, path.display().to_string())
+}
+
+pub fn default_privilege_map() -> PrivilegeMap {
+    let embedded = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/mappings/privileges.toml"
+    ));
+    match parse_privilege_map(embedded, "embedded privilege map".to_string()) {
+        Ok(map) if !map.entries.is_empty() => map,
+        _ => fallback_privilege_map(),
+    }
+}
+
+fn parse_privilege_map(raw: &str, path: String) -> Result<PrivilegeMap, PrivilegeMapLoadError> {
+    let parsed: PrivilegeMapFile =
+        toml::from_str(raw).map_err(|source| PrivilegeMapLoadError::Parse { path, source })?;
+    Ok(PrivilegeMap {
+        entries: parsed.privilege,
+    })
+}
+
+fn fallback_privilege_map() -> PrivilegeMap {
+    PrivilegeMap {
+        entries: vec![PrivilegeMapEntry {
+            pfsense: "page-all".to_string(),
+            opnsense: "page-all".to_string(),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::default_privilege_map;
+
+    #[test]
+    fn translates_both_directions() {
+        let map = default_privilege_map();
+        assert_eq!(
+            map.translate("page-services-dnsresolver", "opnsense"),
+            Some("page-services-unbound")
+        );
+        assert_eq!(
+            map.translate("page-services-unbound", "pfsense"),
+            Some("page-services-dnsresolver")
+        );
+        assert_eq!(map.translate("page-all", "opnsense"), Some("page-all"));
+        assert_eq!(map.translate("page-pfblockerng", "opnsense"), None);
+    }
+}
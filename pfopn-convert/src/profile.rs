@@ -1,6 +1,11 @@
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use xml_diff_core::{parse, XmlNode};
+
+use crate::interface_guard::unmatched_interfaces;
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct ExpectedProfile {
     #[serde(default)]
@@ -19,6 +24,25 @@ pub struct ExpectedProfile {
     pub bridge_require_members: bool,
     #[serde(default)]
     pub deprecated_sections: Vec<String>,
+    /// OpenVPN directives the target's OpenVPN refuses to start with.
+    #[serde(default)]
+    pub openvpn_rejected_options: Vec<String>,
+    /// Data-channel ciphers the target's OpenVPN no longer negotiates.
+    #[serde(default)]
+    pub openvpn_rejected_ciphers: Vec<String>,
+    /// Tunnel topologies the target's GUI accepts; empty accepts any.
+    #[serde(default)]
+    pub openvpn_topologies: Vec<String>,
+    /// TLS key modes (`auth`, `crypt`) the target supports; empty accepts any.
+    #[serde(default)]
+    pub openvpn_tls_modes: Vec<String>,
+    /// The target holds pfSense Plus-only settings (see [`crate::plus_features`]).
+    #[serde(default)]
+    pub plus_features: bool,
+    /// The target holds Business Edition-only plugins (see
+    /// [`crate::opnsense_editions`]).
+    #[serde(default)]
+    pub business_plugins: bool,
 }
 
 pub fn load_profile(platform: &str, version: &str) -> Option<ExpectedProfile> {
@@ -32,9 +56,14 @@ pub fn load_profile_with_source(
 ) -> Option<(ExpectedProfile, String)> {
     let mut names = Vec::new();
     if !version.trim().is_empty() {
-        names.push(format!("{}.toml", version.trim()));
-        if let Some((major, _)) = version.trim().split_once('.') {
-            names.push(format!("{major}.toml"));
+        let version = version.trim();
+        names.push(format!("{version}.toml"));
+        let parts: Vec<&str> = version.split('.').collect();
+        if parts.len() > 2 {
+            names.push(format!("{}.{}.toml", parts[0], parts[1]));
+        }
+        if parts.len() > 1 {
+            names.push(format!("{}.toml", parts[0]));
         }
     }
     names.push("default.toml".to_string());
@@ -60,6 +89,14 @@ fn load_embedded_profile(platform: &str, name: &str) -> Option<ExpectedProfile>
             env!("CARGO_MANIFEST_DIR"),
             "/profiles/pfsense/default.toml"
         ))),
+        ("pfsense", "2.6.toml") => Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/pfsense/2.6.toml"
+        ))),
+        ("pfsense", "plus.toml") => Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/pfsense/plus.toml"
+        ))),
         ("pfsense", "99.toml") => Some(include_str!(concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/profiles/pfsense/99.toml"
@@ -68,6 +105,10 @@ fn load_embedded_profile(platform: &str, name: &str) -> Option<ExpectedProfile>
             env!("CARGO_MANIFEST_DIR"),
             "/profiles/opnsense/default.toml"
         ))),
+        ("opnsense", "business.toml") => Some(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/opnsense/business.toml"
+        ))),
         ("opnsense", "99.toml") => Some(include_str!(concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/profiles/opnsense/99.toml"
@@ -78,6 +119,131 @@ fn load_embedded_profile(platform: &str, name: &str) -> Option<ExpectedProfile>
     parse_profile(raw).ok()
 }
 
+/// Versions with an embedded baseline template, oldest first.
+pub fn baseline_versions(platform: &str) -> &'static [&'static str] {
+    match platform {
+        "opnsense" => &["24.7", "25.1", "25.7", "26.1"],
+        "pfsense" => &["2.7", "2.8", "plus"],
+        _ => &[],
+    }
+}
+
+/// Pick the embedded baseline for a requested release.
+///
+/// OPNsense picks the newest template of the same major release that is not
+/// newer than the request (`26.1.2` -> `26.1`, `25` or `25.x` -> `25.7`).
+/// pfSense maps `2.7.x` and `2.8.x` to the CE templates and `plus` or a Plus
+/// release number (`24.11`, `25.07`) to the Plus template.
+pub fn resolve_baseline_version(platform: &str, version: &str) -> Option<&'static str> {
+    let version = version.trim().to_ascii_lowercase();
+    let mut parts = version.split('.');
+    let major = parts.next().and_then(|m| m.parse::<u32>().ok());
+    let minor = parts.next().and_then(|m| m.parse::<u32>().ok());
+    match platform {
+        "opnsense" => {
+            let major = major?;
+            baseline_versions(platform)
+                .iter()
+                .rev()
+                .find(|candidate| {
+                    let (c_major, c_minor) = candidate.split_once('.').expect("major.minor");
+                    c_major.parse() == Ok(major)
+                        && minor
+                            .is_none_or(|minor| c_minor.parse::<u32>().is_ok_and(|c| c <= minor))
+                })
+                .copied()
+        }
+        "pfsense" => match (major, minor) {
+            _ if version == "plus" => Some("plus"),
+            (Some(2), Some(7)) => Some("2.7"),
+            (Some(2), Some(8)) => Some("2.8"),
+            (Some(major), _) if major >= 21 => Some("plus"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Load the embedded baseline for a requested release, with the template
+/// version it resolved to.
+pub fn load_baseline(platform: &str, version: &str) -> Option<(XmlNode, &'static str)> {
+    let resolved = resolve_baseline_version(platform, version)?;
+    let raw = match (platform, resolved) {
+        ("opnsense", "24.7") => include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/opnsense/baseline/24.7.xml"
+        )),
+        ("opnsense", "25.1") => include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/opnsense/baseline/25.1.xml"
+        )),
+        ("opnsense", "25.7") => include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/opnsense/baseline/25.7.xml"
+        )),
+        ("opnsense", "26.1") => include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/opnsense/baseline/26.1.xml"
+        )),
+        ("pfsense", "2.7") => include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/pfsense/baseline/2.7.xml"
+        )),
+        ("pfsense", "2.8") => include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/pfsense/baseline/2.8.xml"
+        )),
+        ("pfsense", "plus") => include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/profiles/pfsense/baseline/plus.xml"
+        )),
+        _ => return None,
+    };
+    let baseline = parse(raw.as_bytes()).ok()?;
+    Some((baseline, resolved))
+}
+
+/// Give a generated baseline the source's interface assignments.
+///
+/// A template knows nothing about the destination hardware, so every
+/// physical-backed source interface is assigned the source's device on its
+/// target logical name (after `interface_map`), adding the interface when
+/// the template lacks it. Returns `name=device` for each assignment.
+pub fn seed_baseline_interfaces(
+    baseline: &mut XmlNode,
+    source: &XmlNode,
+    interface_map: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let physical = unmatched_interfaces(source, &XmlNode::new(&baseline.tag), None);
+    let Some(interfaces) = baseline.children.iter_mut().find(|c| c.tag == "interfaces") else {
+        return Vec::new();
+    };
+    let mut seeded = Vec::new();
+    for spec in physical {
+        let Some(device) = spec.if_name.filter(|d| !d.is_empty()) else {
+            continue;
+        };
+        let name = interface_map.get(&spec.name).unwrap_or(&spec.name);
+        let iface = match interfaces.children.iter().position(|c| &c.tag == name) {
+            Some(index) => &mut interfaces.children[index],
+            None => {
+                interfaces.children.push(XmlNode::new(name));
+                interfaces.children.last_mut().expect("just pushed")
+            }
+        };
+        match iface.children.iter_mut().find(|c| c.tag == "if") {
+            Some(node) => node.text = Some(device.clone()),
+            None => {
+                let mut node = XmlNode::new("if");
+                node.text = Some(device.clone());
+                iface.children.push(node);
+            }
+        }
+        seeded.push(format!("{name}={device}"));
+    }
+    seeded
+}
+
 fn profile_path(base: &Path, platform: &str, name: &str) -> PathBuf {
     base.join(platform).join(name)
 }
@@ -93,9 +259,106 @@ fn parse_profile(raw: &str) -> Result<ExpectedProfile, toml::de::Error> {
 
 #[cfg(test)]
 mod tests {
-    use super::{load_embedded_profile, load_profile, load_profile_with_source};
+    use super::{
+        baseline_versions, load_baseline, load_embedded_profile, load_profile,
+        load_profile_with_source, resolve_baseline_version, seed_baseline_interfaces,
+    };
+    use crate::backend_detect::detect_dhcp_backend;
+    use crate::detect::detect_version_info;
+    use std::collections::BTreeMap;
     use std::fs;
     use tempfile::tempdir;
+    use xml_diff_core::parse;
+
+    #[test]
+    fn every_embedded_baseline_parses() {
+        for platform in ["opnsense", "pfsense"] {
+            for version in baseline_versions(platform) {
+                let (baseline, resolved) = load_baseline(platform, version).expect("baseline");
+                assert_eq!(resolved, *version);
+                assert_eq!(baseline.tag, platform);
+                for section in ["system", "interfaces", "filter"] {
+                    assert!(
+                        baseline.get_child(section).is_some(),
+                        "{platform} {version}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_requested_releases_to_templates() {
+        assert_eq!(resolve_baseline_version("opnsense", "26.1.2"), Some("26.1"));
+        assert_eq!(resolve_baseline_version("opnsense", "25"), Some("25.7"));
+        assert_eq!(resolve_baseline_version("opnsense", "25.x"), Some("25.7"));
+        assert_eq!(resolve_baseline_version("opnsense", "25.4"), Some("25.1"));
+        assert_eq!(resolve_baseline_version("opnsense", "24.1"), None);
+        assert_eq!(resolve_baseline_version("pfsense", "2.7.2"), Some("2.7"));
+        assert_eq!(resolve_baseline_version("pfsense", "Plus"), Some("plus"));
+        assert_eq!(resolve_baseline_version("pfsense", "25.07"), Some("plus"));
+        assert_eq!(resolve_baseline_version("pfsense", "2.6.0"), None);
+    }
+
+    #[test]
+    fn opnsense_baselines_match_release_dhcp_defaults() {
+        let (older, _) = load_baseline("opnsense", "24.7").expect("baseline");
+        assert!(older.get_child("dhcpd").is_some());
+        assert!(older
+            .get_text(&["OPNsense", "Kea", "dhcp4", "general", "enabled"])
+            .is_some());
+
+        let (current, _) = load_baseline("opnsense", "26.1").expect("baseline");
+        assert_eq!(detect_version_info(&current).value, "26.1");
+        assert!(current.get_child("dhcpd").is_none());
+        assert!(current
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("Kea"))
+            .is_some());
+
+        let (plus, _) = load_baseline("pfsense", "plus").expect("baseline");
+        assert_eq!(detect_dhcp_backend(&plus).mode, "kea");
+    }
+
+    #[test]
+    fn seeds_source_devices_into_baseline() {
+        let (mut baseline, _) = load_baseline("opnsense", "26.1").expect("baseline");
+        let source = parse(
+            br#"<pfsense><interfaces>
+                <wan><if>igb0</if></wan>
+                <lan><if>igb1</if></lan>
+                <opt1><if>igb2</if></opt1>
+                <opt2><if>igb1.20</if></opt2>
+            </interfaces></pfsense>"#,
+        )
+        .expect("parse");
+        let map = BTreeMap::from([("opt1".to_string(), "opt5".to_string())]);
+
+        let seeded = seed_baseline_interfaces(&mut baseline, &source, &map);
+
+        assert_eq!(seeded, vec!["lan=igb1", "opt5=igb2", "wan=igb0"]);
+        assert_eq!(
+            baseline.get_text(&["interfaces", "wan", "if"]),
+            Some("igb0")
+        );
+        assert_eq!(
+            baseline.get_text(&["interfaces", "opt5", "if"]),
+            Some("igb2")
+        );
+        assert!(baseline.get_text(&["interfaces", "opt2", "if"]).is_none());
+    }
+
+    #[test]
+    fn prefers_minor_version_profile_for_point_releases() {
+        let profile = load_profile("pfsense", "2.6.0").expect("profile");
+        assert!(!profile
+            .openvpn_rejected_options
+            .contains(&"comp-lzo".to_string()));
+        let profile = load_profile("pfsense", "2.7.2").expect("profile");
+        assert!(profile
+            .openvpn_rejected_options
+            .contains(&"comp-lzo".to_string()));
+    }
 
     #[test]
     fn falls_back_to_major_version_profile() {
@@ -128,6 +391,22 @@ mod tests {
         assert_eq!(source, "embedded");
     }
 
+    #[test]
+    fn edition_profiles_expect_edition_features() {
+        assert!(load_profile("pfsense", "plus").expect("plus").plus_features);
+        assert!(!load_profile("pfsense", "23.3").expect("ce").plus_features);
+        assert!(
+            load_profile("opnsense", "business")
+                .expect("business")
+                .business_plugins
+        );
+        assert!(
+            !load_profile("opnsense", "25.7")
+                .expect("community")
+                .business_plugins
+        );
+    }
+
     #[test]
     fn profile_source_reports_override_dir() {
         let dir = tempdir().expect("tempdir");
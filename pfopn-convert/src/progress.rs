@@ -0,0 +1,116 @@
+//! Per-stage timing for long conversions.
+//!
+//! A [`StageTimer`] records how long each stage took since the previous one.
+//! With progress enabled it also prints each stage to stderr as it
+//! finishes, so a slow or hung conversion shows the last stage reached:
+//!
+//! ```text
+//! progress: diff 41.2ms
+//! progress: merge/openvpn 0.3ms
+//! ```
+//!
+//! Timings are returned with the result either way (see
+//! [`crate::pipeline::ConversionResult::stage_timings`]).
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// How long one stage took.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageTiming {
+    /// Stage name; merge-time transforms are prefixed with `merge/`.
+    pub stage: String,
+    /// Wall-clock time spent in the stage.
+    pub elapsed: Duration,
+}
+
+/// Records stage timings and optionally reports them as they complete.
+#[derive(Debug, Clone)]
+pub struct StageTimer {
+    verbose: bool,
+    started: Instant,
+    last: Instant,
+    timings: Vec<StageTiming>,
+}
+
+impl StageTimer {
+    /// Start timing; `verbose` prints each stage to stderr when marked.
+    pub fn new(verbose: bool) -> Self {
+        let now = Instant::now();
+        Self {
+            verbose,
+            started: now,
+            last: now,
+            timings: Vec::new(),
+        }
+    }
+
+    /// Close the current stage under `stage` and start the next one.
+    pub fn mark(&mut self, stage: &str) {
+        let now = Instant::now();
+        let timing = StageTiming {
+            stage: stage.to_string(),
+            elapsed: now - self.last,
+        };
+        self.last = now;
+        if self.verbose {
+            eprintln!(
+                "progress: {} {}",
+                timing.stage,
+                format_duration(timing.elapsed)
+            );
+        }
+        self.timings.push(timing);
+    }
+
+    /// Append timings recorded by a nested timer and restart the current stage.
+    ///
+    /// Nested stages were already reported when they were marked.
+    pub fn extend(&mut self, timings: Vec<StageTiming>) {
+        self.timings.extend(timings);
+        self.last = Instant::now();
+    }
+
+    /// Time since the timer started.
+    pub fn total(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Recorded timings in stage order.
+    pub fn into_timings(self) -> Vec<StageTiming> {
+        self.timings
+    }
+}
+
+/// Render a duration as milliseconds, or seconds once it passes one second.
+pub fn format_duration(elapsed: Duration) -> String {
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    if ms >= 1000.0 {
+        format!("{:.2}s", ms / 1000.0)
+    } else {
+        format!("{ms:.1}ms")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{format_duration, StageTimer};
+
+    #[test]
+    fn records_stages_in_order() {
+        let mut timer = StageTimer::new(false);
+        timer.mark("parse");
+        timer.mark("merge");
+        let stages: Vec<_> = timer.into_timings().into_iter().map(|t| t.stage).collect();
+        assert_eq!(stages, vec!["parse", "merge"]);
+    }
+
+    #[test]
+    fn formats_short_and_long_durations() {
+        assert_eq!(format_duration(Duration::from_micros(1500)), "1.5ms");
+        assert_eq!(format_duration(Duration::from_millis(2500)), "2.50s");
+    }
+}
@@ -0,0 +1,227 @@
+//! Revision stamp and provenance marker for converted configs.
+//!
+//! Both firewalls record the last change in a top-level `<revision>`
+//! (time, description, username) and show it in the backup history.
+//! [`stamp`] writes one for the conversion, so a restored config shows
+//! where it came from:
+//!
+//! ```xml
+//! <revision>
+//!   <time>1767225600</time>
+//!   <description>converted by pfopn-convert v0.1.0 from pfsense 23.3</description>
+//!   <username>pfopn-convert</username>
+//! </revision>
+//! ```
+//!
+//! With [`Provenance::marker`] set, a hidden `<pfopn_provenance>` container
+//! also records the tool version, both platforms, and the options used.
+//! Neither firewall reads it; [`read_marker`] does. A marker left in a
+//! source from an earlier conversion is never carried over.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::detect::detect_version;
+
+/// Top-level tag of the hidden provenance container.
+pub const MARKER_TAG: &str = "pfopn_provenance";
+
+/// Username written to `<revision>` unless overridden.
+pub const DEFAULT_USERNAME: &str = "pfopn-convert";
+
+/// Version of this tool, as written to the revision and marker.
+pub const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How a conversion is recorded in its output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// `<revision><username>`.
+    pub username: String,
+    /// Unix time of the conversion.
+    pub time: u64,
+    /// Options the conversion ran with, for the marker.
+    pub flags: Vec<String>,
+    /// Also write the hidden [`MARKER_TAG`] container.
+    pub marker: bool,
+}
+
+impl Provenance {
+    /// Stamp with `time`, the default username, and no marker.
+    pub fn new(time: u64) -> Self {
+        Self {
+            username: DEFAULT_USERNAME.to_string(),
+            time,
+            flags: Vec::new(),
+            marker: false,
+        }
+    }
+
+    /// Stamp with the current time, or `SOURCE_DATE_EPOCH` when set so
+    /// repeated conversions produce identical output.
+    pub fn now() -> Self {
+        let time = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default()
+            });
+        Self::new(time)
+    }
+}
+
+/// What a provenance marker says about a converted config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProvenanceMarker {
+    pub tool_version: String,
+    pub from: String,
+    pub from_version: Option<String>,
+    pub to: String,
+    pub time: u64,
+    pub flags: Vec<String>,
+}
+
+/// Revision description for a conversion from `from` at `from_version`.
+pub fn revision_description(from: &str, from_version: Option<&str>) -> String {
+    match from_version {
+        Some(version) => {
+            format!("converted by pfopn-convert v{TOOL_VERSION} from {from} {version}")
+        }
+        None => format!("converted by pfopn-convert v{TOOL_VERSION} from {from}"),
+    }
+}
+
+/// Replace the `<revision>` in `out` and, when requested, the marker.
+///
+/// # Arguments
+///
+/// * `out` - Converted configuration
+/// * `source` - Source configuration, for its version
+/// * `from` - Source platform
+/// * `to` - Target platform
+/// * `provenance` - Time, username, and marker options
+pub fn stamp(out: &mut XmlNode, source: &XmlNode, from: &str, to: &str, provenance: &Provenance) {
+    let from_version = detect_version(source)
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    let mut revision = XmlNode::new("revision");
+    push_text(&mut revision, "time", &provenance.time.to_string());
+    push_text(
+        &mut revision,
+        "description",
+        &revision_description(from, from_version),
+    );
+    push_text(&mut revision, "username", &provenance.username);
+    match out.children.iter_mut().find(|c| c.tag == "revision") {
+        Some(existing) => *existing = revision,
+        None => out.children.push(revision),
+    }
+
+    out.children.retain(|c| c.tag != MARKER_TAG);
+    if provenance.marker {
+        let mut marker = XmlNode::new(MARKER_TAG);
+        push_text(&mut marker, "tool_version", TOOL_VERSION);
+        push_text(&mut marker, "from", from);
+        if let Some(version) = from_version {
+            push_text(&mut marker, "from_version", version);
+        }
+        push_text(&mut marker, "to", to);
+        push_text(&mut marker, "time", &provenance.time.to_string());
+        for flag in &provenance.flags {
+            push_text(&mut marker, "flag", flag);
+        }
+        out.children.push(marker);
+    }
+}
+
+/// Read the provenance marker of a converted config, if it has one.
+pub fn read_marker(root: &XmlNode) -> Option<ProvenanceMarker> {
+    let marker = root.get_child(MARKER_TAG)?;
+    let text = |tag: &str| {
+        marker
+            .get_text(&[tag])
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(ToString::to_string)
+    };
+    Some(ProvenanceMarker {
+        tool_version: text("tool_version")?,
+        from: text("from")?,
+        from_version: text("from_version"),
+        to: text("to")?,
+        time: text("time")
+            .and_then(|t| t.parse().ok())
+            .unwrap_or_default(),
+        flags: marker
+            .get_children("flag")
+            .into_iter()
+            .filter_map(|f| f.text.clone())
+            .collect(),
+    })
+}
+
+fn push_text(parent: &mut XmlNode, tag: &str, value: &str) {
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    parent.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{read_marker, stamp, Provenance, MARKER_TAG, TOOL_VERSION};
+
+    #[test]
+    fn replaces_revision_and_writes_marker() {
+        let source = parse(b"<pfsense><version>23.3</version></pfsense>").expect("parse");
+        let mut out = parse(
+            b"<opnsense><revision><time>1</time><description>baseline</description><username>root@10.0.0.1</username></revision><pfopn_provenance><from>old</from></pfopn_provenance></opnsense>",
+        )
+        .expect("parse");
+        let mut provenance = Provenance::new(1_767_225_600);
+        provenance.marker = true;
+        provenance.flags = vec!["--backend kea".to_string()];
+        stamp(&mut out, &source, "pfsense", "opnsense", &provenance);
+
+        assert_eq!(out.get_text(&["revision", "time"]), Some("1767225600"));
+        assert_eq!(
+            out.get_text(&["revision", "description"]),
+            Some(format!("converted by pfopn-convert v{TOOL_VERSION} from pfsense 23.3").as_str())
+        );
+        assert_eq!(
+            out.get_text(&["revision", "username"]),
+            Some("pfopn-convert")
+        );
+        assert_eq!(out.get_children(MARKER_TAG).len(), 1);
+
+        let marker = read_marker(&out).expect("marker");
+        assert_eq!(marker.from, "pfsense");
+        assert_eq!(marker.from_version.as_deref(), Some("23.3"));
+        assert_eq!(marker.to, "opnsense");
+        assert_eq!(marker.flags, ["--backend kea"]);
+    }
+
+    #[test]
+    fn drops_stale_marker_when_not_requested() {
+        let source = parse(b"<opnsense/>").expect("parse");
+        let mut out =
+            parse(b"<pfsense><pfopn_provenance><from>x</from></pfopn_provenance></pfsense>")
+                .expect("parse");
+        stamp(
+            &mut out,
+            &source,
+            "opnsense",
+            "pfsense",
+            &Provenance::new(5),
+        );
+        assert!(read_marker(&out).is_none());
+        assert!(out
+            .get_text(&["revision", "description"])
+            .is_some_and(|d| d.ends_with("from opnsense")));
+    }
+}
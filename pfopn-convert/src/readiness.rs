@@ -0,0 +1,575 @@
+//! Per-feature migration readiness matrix.
+//!
+//! Classifies each feature found in a config by how much of it the converter
+//! handles when moving to the target platform, and folds the result into a
+//! weighted 0-100 score. Classification is driven by the config contents plus
+//! the scan and verify reports, so a feature that is absent does not count
+//! toward the score.
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::scan::ScanReport;
+use crate::transform::dhcp::mac_control::kea_unsupported_flags;
+use crate::verify::VerifyReport;
+use crate::verify_codes::FindingCode;
+
+/// How much of a feature converts without manual work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessStatus {
+    /// Converted automatically.
+    AutoConvertible,
+    /// Converted, but some settings need review or are dropped.
+    Partial,
+    /// Present but must be recreated by hand on the target.
+    Manual,
+    /// Has no equivalent on the target.
+    Unsupported,
+    /// Not configured; excluded from the score.
+    NotPresent,
+}
+
+impl ReadinessStatus {
+    fn label(self) -> &'static str {
+        match self {
+            Self::AutoConvertible => "auto",
+            Self::Partial => "partial",
+            Self::Manual => "manual",
+            Self::Unsupported => "unsupported",
+            Self::NotPresent => "n/a",
+        }
+    }
+
+    /// Points out of 100 a feature in this state contributes.
+    fn points(self) -> u32 {
+        match self {
+            Self::AutoConvertible => 100,
+            Self::Partial => 60,
+            Self::Manual => 25,
+            Self::Unsupported | Self::NotPresent => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureReadiness {
+    pub feature: String,
+    pub status: ReadinessStatus,
+    /// Relative importance in the score.
+    pub weight: u32,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadinessMatrix {
+    /// Weighted score over present features (100 when nothing needs work).
+    pub score: u32,
+    pub features: Vec<FeatureReadiness>,
+}
+
+/// Build the readiness matrix for moving `root` to `target`.
+pub fn build_readiness_matrix(
+    root: &XmlNode,
+    target: &str,
+    scan: &ScanReport,
+    verify: &VerifyReport,
+) -> ReadinessMatrix {
+    let ctx = Context {
+        root,
+        from: scan.platform.as_str(),
+        target,
+        verify,
+    };
+    let features = vec![
+        ctx.openvpn(),
+        ctx.ipsec(),
+        ctx.wireguard(),
+        ctx.dhcp(),
+        plugins(scan),
+        ctx.ha(),
+        ctx.shaper(),
+        ctx.ids(),
+        ctx.certificates(),
+    ];
+
+    let (mut total, mut weights) = (0, 0);
+    for feature in &features {
+        if feature.status != ReadinessStatus::NotPresent {
+            total += feature.weight * feature.status.points();
+            weights += feature.weight;
+        }
+    }
+    let score = total.checked_div(weights).unwrap_or(100);
+    ReadinessMatrix { score, features }
+}
+
+struct Context<'a> {
+    root: &'a XmlNode,
+    from: &'a str,
+    target: &'a str,
+    verify: &'a VerifyReport,
+}
+
+impl Context<'_> {
+    fn cross_platform(&self) -> bool {
+        self.from != self.target
+    }
+
+    fn issues(&self, prefix: &str) -> usize {
+        self.verify
+            .issues
+            .iter()
+            .filter(|i| i.code.as_str().starts_with(prefix))
+            .count()
+    }
+
+    fn openvpn(&self) -> FeatureReadiness {
+        let legacy = self.root.get_child("openvpn").map_or(0, |o| {
+            o.children
+                .iter()
+                .filter(|c| c.tag == "openvpn-server" || c.tag == "openvpn-client")
+                .count()
+        });
+        let instances = count_at(self.root, &["OPNsense", "OpenVPN", "Instances"], "Instance");
+        let total = legacy + instances;
+        if total == 0 {
+            return not_present("openvpn", 15);
+        }
+        let issues = self.issues("openvpn_missing_");
+        if issues > 0 {
+            return feature(
+                "openvpn",
+                ReadinessStatus::Partial,
+                15,
+                format!("{total} instance(s); {issues} unresolved user/cert/CA reference(s)"),
+            );
+        }
+        feature(
+            "openvpn",
+            ReadinessStatus::AutoConvertible,
+            15,
+            format!("{total} instance(s) with dependencies resolved"),
+        )
+    }
+
+    fn ipsec(&self) -> FeatureReadiness {
+        let phase1 = self
+            .root
+            .get_child("ipsec")
+            .map_or(0, |i| i.get_children("phase1").len());
+        let swanctl = count_at(
+            self.root,
+            &["OPNsense", "Swanctl", "Connections"],
+            "Connection",
+        );
+        if phase1 + swanctl == 0 {
+            return not_present("ipsec", 15);
+        }
+        if self.cross_platform() && self.target == "pfsense" && swanctl > 0 {
+            return feature(
+                "ipsec",
+                ReadinessStatus::Manual,
+                15,
+                format!("{swanctl} Swanctl connection(s) have no pfSense mapping"),
+            );
+        }
+        let mobile = self.root.get_child("ipsec").map_or(0, |i| {
+            i.get_children("phase1")
+                .into_iter()
+                .filter(|p| p.get_child("mobile").is_some())
+                .count()
+        });
+        let issues = self.issues("ipsec_missing_");
+        if mobile > 0 || issues > 0 {
+            return feature(
+                "ipsec",
+                ReadinessStatus::Partial,
+                15,
+                format!(
+                    "{} tunnel(s); {mobile} mobile client tunnel(s), {issues} unresolved reference(s)",
+                    phase1 + swanctl
+                ),
+            );
+        }
+        feature(
+            "ipsec",
+            ReadinessStatus::AutoConvertible,
+            15,
+            format!("{} tunnel(s)", phase1 + swanctl),
+        )
+    }
+
+    fn wireguard(&self) -> FeatureReadiness {
+        let pf = self
+            .root
+            .get_child("installedpackages")
+            .and_then(|p| p.get_child("wireguard"))
+            .or_else(|| self.root.get_child("wireguard"))
+            .and_then(|w| w.get_child("tunnels"))
+            .map_or(0, |t| t.get_children("item").len());
+        let opn = count_at(
+            self.root,
+            &["OPNsense", "wireguard", "server", "servers"],
+            "server",
+        );
+        if pf + opn == 0 {
+            return not_present("wireguard", 10);
+        }
+        let issues = self.issues("wireguard_");
+        let status = if issues > 0 {
+            ReadinessStatus::Partial
+        } else {
+            ReadinessStatus::AutoConvertible
+        };
+        feature(
+            "wireguard",
+            status,
+            10,
+            format!("{} tunnel(s); {issues} readiness issue(s)", pf + opn),
+        )
+    }
+
+    fn dhcp(&self) -> FeatureReadiness {
+        let isc = self.root.get_child("dhcpd").map_or(0, |d| {
+            d.children
+                .iter()
+                .filter(|c| {
+                    c.get_child("range").is_some() || !c.get_children("staticmap").is_empty()
+                })
+                .count()
+        });
+        let kea = self
+            .root
+            .get_child("kea")
+            .or_else(|| {
+                self.root
+                    .get_child("OPNsense")
+                    .and_then(|o| o.get_child("Kea"))
+            })
+            .is_some();
+        if isc == 0 && !kea {
+            return not_present("dhcp", 15);
+        }
+        let flags = kea_unsupported_flags(self.root);
+        let backend_issue = self.issues("dhcp_backend_inconsistent");
+        if !flags.is_empty() || backend_issue > 0 {
+            let flags: Vec<_> = flags.iter().map(|(i, f)| format!("{i}:{f}")).collect();
+            return feature(
+                "dhcp",
+                ReadinessStatus::Partial,
+                15,
+                format!(
+                    "{isc} ISC interface(s); Kea cannot express [{}]; {backend_issue} backend issue(s)",
+                    flags.join(", ")
+                ),
+            );
+        }
+        feature(
+            "dhcp",
+            ReadinessStatus::AutoConvertible,
+            15,
+            format!(
+                "{isc} ISC interface(s){}",
+                if kea { ", Kea configured" } else { "" }
+            ),
+        )
+    }
+
+    fn ha(&self) -> FeatureReadiness {
+        let carp = self.root.get_child("virtualip").map_or(0, |v| {
+            v.get_children("vip")
+                .into_iter()
+                .filter(|vip| vip.get_text(&["mode"]).map(str::trim) == Some("carp"))
+                .count()
+        });
+        let sync = self
+            .root
+            .get_child("hasync")
+            .is_some_and(|h| h.children.iter().any(has_value));
+        if carp == 0 && !sync {
+            return not_present("ha", 10);
+        }
+        if !self.cross_platform() {
+            return feature(
+                "ha",
+                ReadinessStatus::AutoConvertible,
+                10,
+                format!("{carp} CARP VIP(s)"),
+            );
+        }
+        feature(
+            "ha",
+            ReadinessStatus::Partial,
+            10,
+            format!(
+                "{carp} CARP VIP(s) carry over; HA sync peer, credentials, and sync options must be reviewed"
+            ),
+        )
+    }
+
+    fn shaper(&self) -> FeatureReadiness {
+        let altq = self
+            .root
+            .get_child("shaper")
+            .map_or(0, |s| s.get_children("queue").len());
+        let limiters = self
+            .root
+            .get_child("dnshaper")
+            .map_or(0, |s| s.get_children("queue").len());
+        let opn = self
+            .root
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("TrafficShaper"))
+            .map_or(0, |t| {
+                count_at(t, &["pipes"], "pipe") + count_at(t, &["queues"], "queue")
+            });
+        if altq + limiters + opn == 0 {
+            return not_present("shaper", 5);
+        }
+        if !self.cross_platform() {
+            return feature(
+                "shaper",
+                ReadinessStatus::AutoConvertible,
+                5,
+                "traffic shaping already in target format".to_string(),
+            );
+        }
+        if altq > 0 && self.target == "opnsense" {
+            return feature(
+                "shaper",
+                ReadinessStatus::Unsupported,
+                5,
+                format!("{altq} ALTQ queue(s); OPNsense only supports dummynet pipes/queues"),
+            );
+        }
+        feature(
+            "shaper",
+            ReadinessStatus::Manual,
+            5,
+            format!(
+                "{} limiter/pipe/queue object(s) must be recreated",
+                limiters + opn
+            ),
+        )
+    }
+
+    fn ids(&self) -> FeatureReadiness {
+        let packages: Vec<_> = ["suricata", "snort"]
+            .into_iter()
+            .filter(|p| {
+                self.root
+                    .get_child("installedpackages")
+                    .and_then(|i| i.get_child(p))
+                    .is_some()
+            })
+            .collect();
+        let opn_ids = self
+            .root
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("IDS"))
+            .is_some_and(|ids| ids.get_text(&["general", "enabled"]).map(str::trim) == Some("1"));
+        if packages.is_empty() && !opn_ids {
+            return not_present("ids", 10);
+        }
+        let what = if opn_ids {
+            "OPNsense IDS".to_string()
+        } else {
+            packages.join(", ")
+        };
+        if !self.cross_platform() {
+            return feature(
+                "ids",
+                ReadinessStatus::AutoConvertible,
+                10,
+                format!("{what} already on target platform"),
+            );
+        }
+        feature(
+            "ids",
+            ReadinessStatus::Manual,
+            10,
+            format!("{what}: rulesets, policies, and interface bindings must be recreated"),
+        )
+    }
+
+    fn certificates(&self) -> FeatureReadiness {
+        let certs = self.root.get_children("cert").len();
+        let cas = self.root.get_children("ca").len();
+        if certs + cas == 0 {
+            return not_present("certificates", 10);
+        }
+        let errors = self
+            .verify
+            .issues
+            .iter()
+            .filter(|i| {
+                i.code.as_str().starts_with("cert_") || i.code == FindingCode::DuplicateCertRefid
+            })
+            .filter(|i| !matches!(i.code, FindingCode::CertExpired | FindingCode::CertCaNotCa))
+            .count();
+        let status = if errors > 0 {
+            ReadinessStatus::Partial
+        } else {
+            ReadinessStatus::AutoConvertible
+        };
+        feature(
+            "certificates",
+            status,
+            10,
+            format!("{certs} cert(s), {cas} CA(s); {errors} chain/key problem(s)"),
+        )
+    }
+}
+
+fn plugins(scan: &ScanReport) -> FeatureReadiness {
+    let weight = 10;
+    if !scan.unsupported_plugins.is_empty() {
+        return feature(
+            "plugins",
+            ReadinessStatus::Unsupported,
+            weight,
+            format!("unsupported: {}", scan.unsupported_plugins.join(", ")),
+        );
+    }
+    if !scan.missing_target_compat.is_empty() {
+        return feature(
+            "plugins",
+            ReadinessStatus::Manual,
+            weight,
+            format!(
+                "no target equivalent: {}",
+                scan.missing_target_compat.join(", ")
+            ),
+        );
+    }
+    if scan.known_plugins_present.is_empty() {
+        return not_present("plugins", weight);
+    }
+    feature(
+        "plugins",
+        ReadinessStatus::AutoConvertible,
+        weight,
+        format!("known: {}", scan.known_plugins_present.join(", ")),
+    )
+}
+
+/// Render the matrix as text lines for `migrate-check` output.
+pub fn render_readiness_text(matrix: &ReadinessMatrix) -> String {
+    let mut out = vec![format!("readiness score={}", matrix.score)];
+    for f in &matrix.features {
+        out.push(format!(
+            "- [{}] {} (weight {}): {}",
+            f.status.label(),
+            f.feature,
+            f.weight,
+            f.detail
+        ));
+    }
+    out.join("\n")
+}
+
+/// Render the matrix as a Markdown table for migration planning docs.
+pub fn render_readiness_markdown(matrix: &ReadinessMatrix) -> String {
+    let mut out = vec![
+        format!("**Readiness score:** {}/100", matrix.score),
+        String::new(),
+        "| Feature | Status | Weight | Detail |".to_string(),
+        "|---|---|---|---|".to_string(),
+    ];
+    for f in &matrix.features {
+        out.push(format!(
+            "| {} | {} | {} | {} |",
+            f.feature,
+            f.status.label(),
+            f.weight,
+            f.detail.replace('|', "\\|")
+        ));
+    }
+    out.join("\n")
+}
+
+fn feature(name: &str, status: ReadinessStatus, weight: u32, detail: String) -> FeatureReadiness {
+    FeatureReadiness {
+        feature: name.to_string(),
+        status,
+        weight,
+        detail,
+    }
+}
+
+fn not_present(name: &str, weight: u32) -> FeatureReadiness {
+    feature(
+        name,
+        ReadinessStatus::NotPresent,
+        weight,
+        "not configured".to_string(),
+    )
+}
+
+fn count_at(root: &XmlNode, path: &[&str], tag: &str) -> usize {
+    path.iter()
+        .try_fold(root, |node, t| node.get_child(t))
+        .map_or(0, |n| n.get_children(tag).len())
+}
+
+fn has_value(node: &XmlNode) -> bool {
+    node.text.as_deref().is_some_and(|t| !t.trim().is_empty()) || !node.children.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{build_readiness_matrix, render_readiness_markdown, ReadinessStatus};
+    use crate::scan::build_scan_report;
+    use crate::verify::build_verify_report;
+
+    fn matrix(xml: &[u8], target: &str) -> super::ReadinessMatrix {
+        let root = parse(xml).expect("parse");
+        let scan = build_scan_report(&root, Some(target));
+        let verify = build_verify_report(&root, Some(target));
+        build_readiness_matrix(&root, target, &scan, &verify)
+    }
+
+    fn status(m: &super::ReadinessMatrix, feature: &str) -> ReadinessStatus {
+        m.features
+            .iter()
+            .find(|f| f.feature == feature)
+            .expect("feature")
+            .status
+    }
+
+    #[test]
+    fn empty_config_scores_full_with_nothing_present() {
+        let m = matrix(b"<pfsense><system/><interfaces/></pfsense>", "opnsense");
+        assert_eq!(m.score, 100);
+        assert!(m
+            .features
+            .iter()
+            .all(|f| f.status == ReadinessStatus::NotPresent));
+    }
+
+    #[test]
+    fn classifies_features_from_config_contents() {
+        let m = matrix(
+            br#"<pfsense><system/><interfaces/>
+                <shaper><queue><name>qWAN</name></queue></shaper>
+                <virtualip><vip><mode>carp</mode></vip></virtualip>
+                <installedpackages><suricata/></installedpackages>
+                <dhcpd><lan><enable/><range><from>10.0.0.10</from></range><staticarp/></lan></dhcpd>
+            </pfsense>"#,
+            "opnsense",
+        );
+        assert_eq!(status(&m, "shaper"), ReadinessStatus::Unsupported);
+        assert_eq!(status(&m, "ha"), ReadinessStatus::Partial);
+        assert_eq!(status(&m, "ids"), ReadinessStatus::Manual);
+        assert_eq!(status(&m, "dhcp"), ReadinessStatus::Partial);
+        assert_eq!(status(&m, "openvpn"), ReadinessStatus::NotPresent);
+        // shaper 5*0 + ha 10*60 + ids 10*25 + dhcp 15*60 (+ plugins if detected)
+        assert!(m.score < 60, "score={}", m.score);
+
+        let md = render_readiness_markdown(&m);
+        assert!(md.contains("| shaper | unsupported | 5 |"));
+    }
+}
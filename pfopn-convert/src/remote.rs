@@ -0,0 +1,327 @@
+//! Fetch and push `config.xml` directly from firewalls.
+//!
+//! Enabled with the `remote` cargo feature. Transfers shell out to the system
+//! `ssh` and `curl` binaries rather than linking a TLS/SSH stack, so existing
+//! SSH agents, `~/.ssh/config` host aliases, and known-hosts policy apply
+//! unchanged.
+//!
+//! ## Transports
+//!
+//! - [`Transport::Ssh`] — both platforms; reads and writes the live config
+//!   file (`/cf/conf/config.xml` on pfSense, `/conf/config.xml` on OPNsense)
+//! - [`Transport::OpnsenseApi`] — OPNsense REST backup endpoint
+//!   (`/api/core/backup/download/this`); fetch only
+//!
+//! ## Pushing
+//!
+//! [`push_config`] uploads to a temporary file next to the live config, keeps
+//! the previous config as `config.xml.pfopn-bak`, moves the new file into
+//! place, and clears `/tmp/config.cache`. The firewall still needs a reboot
+//! (or a manual service reload) before the new config takes effect.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use thiserror::Error;
+
+/// How to reach a firewall.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transport {
+    /// SSH as `destination` (`user@host` or an ssh config alias).
+    Ssh {
+        destination: String,
+        port: Option<u16>,
+        identity: Option<PathBuf>,
+    },
+    /// OPNsense REST API with an API key/secret pair.
+    OpnsenseApi {
+        /// Base URL such as `https://fw.example.com`.
+        base_url: String,
+        key: String,
+        secret: String,
+        /// Accept self-signed certificates.
+        insecure: bool,
+    },
+}
+
+/// A firewall to fetch from or push to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEndpoint {
+    /// `pfsense` or `opnsense`.
+    pub platform: String,
+    pub transport: Transport,
+}
+
+/// Errors produced while talking to a firewall.
+#[derive(Debug, Error)]
+pub enum RemoteError {
+    /// Platform was not `pfsense` or `opnsense`.
+    #[error("unsupported remote platform '{0}'")]
+    UnknownPlatform(String),
+    /// SSH destination would be read as an ssh option.
+    #[error("invalid ssh destination '{0}': must not start with '-'")]
+    InvalidDestination(String),
+    /// The transport cannot perform the requested operation.
+    #[error("{0}")]
+    Unsupported(String),
+    /// The helper binary could not be started.
+    #[error("failed to run {program}: {source}")]
+    Spawn {
+        program: String,
+        source: std::io::Error,
+    },
+    /// The helper binary exited unsuccessfully.
+    #[error("{program} failed ({status}): {stderr}")]
+    Failed {
+        program: String,
+        status: String,
+        stderr: String,
+    },
+}
+
+/// Path of the live config file on `platform`.
+pub fn config_path(platform: &str) -> Result<&'static str, RemoteError> {
+    match platform {
+        "pfsense" => Ok("/cf/conf/config.xml"),
+        "opnsense" => Ok("/conf/config.xml"),
+        other => Err(RemoteError::UnknownPlatform(other.to_string())),
+    }
+}
+
+/// Download the current `config.xml` from `endpoint`.
+pub fn fetch_config(endpoint: &RemoteEndpoint) -> Result<Vec<u8>, RemoteError> {
+    let (command, stdin) = fetch_command(endpoint)?;
+    let output = run(command, stdin.as_deref())?;
+    Ok(output.stdout)
+}
+
+/// Upload `xml` as the live config on `endpoint`.
+pub fn push_config(endpoint: &RemoteEndpoint, xml: &[u8]) -> Result<(), RemoteError> {
+    let command = push_command(endpoint)?;
+    run(command, Some(xml))?;
+    Ok(())
+}
+
+/// Build the command (and optional stdin payload) used by [`fetch_config`].
+fn fetch_command(endpoint: &RemoteEndpoint) -> Result<(Command, Option<Vec<u8>>), RemoteError> {
+    let path = config_path(&endpoint.platform)?;
+    match &endpoint.transport {
+        Transport::Ssh { .. } => {
+            let command = ssh_command(&endpoint.transport, &format!("cat {path}"))?;
+            Ok((command, None))
+        }
+        Transport::OpnsenseApi {
+            base_url,
+            key,
+            secret,
+            insecure,
+        } => {
+            if endpoint.platform != "opnsense" {
+                return Err(RemoteError::Unsupported(
+                    "the REST API transport only supports OPNsense".to_string(),
+                ));
+            }
+            let mut command = Command::new("curl");
+            command.args(["--fail", "--silent", "--show-error", "--config", "-"]);
+            if *insecure {
+                command.arg("--insecure");
+            }
+            command.arg(format!(
+                "{}/api/core/backup/download/this",
+                base_url.trim_end_matches('/')
+            ));
+            // Credentials go through stdin so they never appear in process listings.
+            let config = format!("user = \"{}:{}\"\n", escape_curl(key), escape_curl(secret));
+            Ok((command, Some(config.into_bytes())))
+        }
+    }
+}
+
+/// Build the command used by [`push_config`]; the new config is piped on stdin.
+fn push_command(endpoint: &RemoteEndpoint) -> Result<Command, RemoteError> {
+    let path = config_path(&endpoint.platform)?;
+    match &endpoint.transport {
+        Transport::Ssh { .. } => {
+            let script = format!(
+                "cat > {path}.pfopn-new && cp {path} {path}.pfopn-bak && mv {path}.pfopn-new {path} && rm -f /tmp/config.cache"
+            );
+            ssh_command(&endpoint.transport, &script)
+        }
+        Transport::OpnsenseApi { .. } => Err(RemoteError::Unsupported(
+            "pushing a full config requires the ssh transport".to_string(),
+        )),
+    }
+}
+
+fn ssh_command(transport: &Transport, remote_command: &str) -> Result<Command, RemoteError> {
+    let mut command = Command::new("ssh");
+    command.args(["-o", "BatchMode=yes"]);
+    if let Transport::Ssh {
+        destination,
+        port,
+        identity,
+    } = transport
+    {
+        if let Some(port) = port {
+            command.arg("-p").arg(port.to_string());
+        }
+        if let Some(identity) = identity {
+            command.arg("-i").arg(identity);
+        }
+        if destination.starts_with('-') {
+            return Err(RemoteError::InvalidDestination(destination.clone()));
+        }
+        command.arg("--").arg(destination);
+    }
+    command.arg(remote_command);
+    Ok(command)
+}
+
+fn escape_curl(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn run(mut command: Command, stdin: Option<&[u8]>) -> Result<Output, RemoteError> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let spawn_error = |source| RemoteError::Spawn {
+        program: program.clone(),
+        source,
+    };
+    let mut child = command
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+    if let (Some(payload), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(payload).map_err(spawn_error)?;
+    }
+    let output = child.wait_with_output().map_err(spawn_error)?;
+    if !output.status.success() {
+        return Err(RemoteError::Failed {
+            program,
+            status: output.status.to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    use super::{fetch_command, push_command, RemoteEndpoint, RemoteError, Transport};
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn ssh(platform: &str) -> RemoteEndpoint {
+        RemoteEndpoint {
+            platform: platform.to_string(),
+            transport: Transport::Ssh {
+                destination: "admin@fw".to_string(),
+                port: Some(2222),
+                identity: Some(PathBuf::from("/keys/fw")),
+            },
+        }
+    }
+
+    #[test]
+    fn ssh_fetch_reads_platform_config_path() {
+        let (command, stdin) = fetch_command(&ssh("pfsense")).expect("command");
+        assert_eq!(command.get_program(), "ssh");
+        assert_eq!(
+            args(&command),
+            vec![
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "-i",
+                "/keys/fw",
+                "--",
+                "admin@fw",
+                "cat /cf/conf/config.xml"
+            ]
+        );
+        assert!(stdin.is_none());
+    }
+
+    #[test]
+    fn ssh_rejects_destinations_that_look_like_options() {
+        let endpoint = RemoteEndpoint {
+            platform: "pfsense".to_string(),
+            transport: Transport::Ssh {
+                destination: "-oProxyCommand=touch /tmp/x".to_string(),
+                port: None,
+                identity: None,
+            },
+        };
+        assert!(matches!(
+            fetch_command(&endpoint),
+            Err(RemoteError::InvalidDestination(_))
+        ));
+        assert!(matches!(
+            push_command(&endpoint),
+            Err(RemoteError::InvalidDestination(_))
+        ));
+    }
+
+    #[test]
+    fn ssh_push_keeps_backup_and_clears_cache() {
+        let command = push_command(&ssh("opnsense")).expect("command");
+        let script = args(&command).pop().expect("script");
+        assert!(script.starts_with("cat > /conf/config.xml.pfopn-new"));
+        assert!(script.contains("cp /conf/config.xml /conf/config.xml.pfopn-bak"));
+        assert!(script.ends_with("rm -f /tmp/config.cache"));
+    }
+
+    #[test]
+    fn api_fetch_passes_credentials_on_stdin() {
+        let endpoint = RemoteEndpoint {
+            platform: "opnsense".to_string(),
+            transport: Transport::OpnsenseApi {
+                base_url: "https://fw.example/".to_string(),
+                key: "k".to_string(),
+                secret: "s\"x".to_string(),
+                insecure: true,
+            },
+        };
+        let (command, stdin) = fetch_command(&endpoint).expect("command");
+        let args = args(&command);
+        assert!(args.contains(&"--insecure".to_string()));
+        assert_eq!(
+            args.last().map(String::as_str),
+            Some("https://fw.example/api/core/backup/download/this")
+        );
+        assert!(!args.iter().any(|a| a.contains("k:")));
+        assert_eq!(
+            String::from_utf8(stdin.expect("stdin")).expect("utf8"),
+            "user = \"k:s\\\"x\"\n"
+        );
+
+        assert!(matches!(
+            push_command(&endpoint),
+            Err(RemoteError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_platform() {
+        assert!(matches!(
+            fetch_command(&ssh("ipfire")),
+            Err(RemoteError::UnknownPlatform(_))
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use pfopn_convert::detect::{detect_config, ConfigFlavor};
+use pfopn_convert::remote::{fetch_config, push_config, RemoteEndpoint, Transport};
+use pfopn_convert::verify::{build_verify_report, render_verify_text};
+use xml_diff_core::{parse, parse_file, XmlNode};
+
+use crate::cli::{FetchArgs, PushArgs, RemoteArgs, ScanTarget};
+
+/// Download a config and write it to `--output` after checking it parses.
+pub fn run_fetch(args: FetchArgs) -> Result<()> {
+    let endpoint = endpoint(&args.remote)?;
+    let bytes = fetch_config(&endpoint)?;
+    let node = parse(&bytes).context("downloaded config is not valid XML")?;
+    ensure_platform(&node, &endpoint.platform)?;
+
+    fs::write(&args.output, &bytes)
+        .with_context(|| format!("failed to write {}", args.output.display()))?;
+    println!(
+        "fetched {} config ({} bytes) to {}",
+        endpoint.platform,
+        bytes.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Verify a config and upload it as the firewall's live config.
+pub fn run_push(args: PushArgs) -> Result<()> {
+    let endpoint = endpoint(&args.remote)?;
+    let node = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    ensure_platform(&node, &endpoint.platform)?;
+
+    let report = build_verify_report(&node, Some(&endpoint.platform));
+    if report.errors > 0 {
+        println!("{}", render_verify_text(&report, false));
+        if !args.force {
+            bail!(
+                "refusing to push: verify reported {} errors (use --force to override)",
+                report.errors
+            );
+        }
+    }
+
+    let bytes =
+        fs::read(&args.file).with_context(|| format!("failed to read {}", args.file.display()))?;
+    push_config(&endpoint, &bytes)?;
+    println!(
+        "pushed {} to {}; previous config kept as config.xml.pfopn-bak. Reboot the firewall to apply.",
+        args.file.display(),
+        endpoint.platform
+    );
+    Ok(())
+}
+
+fn endpoint(args: &RemoteArgs) -> Result<RemoteEndpoint> {
+    let platform = match args.platform {
+        ScanTarget::Pfsense => "pfsense",
+        ScanTarget::Opnsense => "opnsense",
+    }
+    .to_string();
+
+    let transport = if let Some(destination) = &args.ssh {
+        Transport::Ssh {
+            destination: destination.clone(),
+            port: args.ssh_port,
+            identity: args.ssh_identity.clone(),
+        }
+    } else if let (Some(base_url), Some(key_file)) = (&args.api_url, &args.api_key_file) {
+        let (key, secret) = read_api_key_file(key_file)?;
+        Transport::OpnsenseApi {
+            base_url: base_url.clone(),
+            key,
+            secret,
+            insecure: args.insecure,
+        }
+    } else {
+        bail!("pass --ssh or --api-url with --api-key-file");
+    };
+
+    Ok(RemoteEndpoint {
+        platform,
+        transport,
+    })
+}
+
+/// Read an OPNsense `apikey.txt` (`key=...` / `secret=...` lines).
+fn read_api_key_file(path: &Path) -> Result<(String, String)> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("failed to read API key file {}", path.display()))?;
+    let mut key = None;
+    let mut secret = None;
+    for line in raw.lines() {
+        match line.trim().split_once('=') {
+            Some(("key", value)) => key = Some(value.trim().to_string()),
+            Some(("secret", value)) => secret = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    match (key, secret) {
+        (Some(key), Some(secret)) => Ok((key, secret)),
+        _ => bail!(
+            "API key file {} must contain key= and secret= lines",
+            path.display()
+        ),
+    }
+}
+
+fn ensure_platform(node: &XmlNode, platform: &str) -> Result<()> {
+    let detected = match detect_config(node) {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => "unknown",
+    };
+    if detected != platform {
+        bail!("config platform is {detected}, expected {platform}");
+    }
+    Ok(())
+}
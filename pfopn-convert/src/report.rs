@@ -1,9 +1,27 @@
 use colored::Colorize;
+use serde::Serialize;
 use xml_diff_core::{format_summary, format_text, DiffEntry};
 
+use crate::alias_members::AliasMemberChange;
 use crate::analyze::{AnalysisEntry, RecommendedAction};
+use crate::backend_detect::BackendDetection;
 use crate::sections_report::{SectionInventory, SectionStats};
 
+/// Structured `diff` output, as written by `--format json` and passed to
+/// `--template`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffReport {
+    pub entries: Vec<DiffEntry>,
+    pub analysis: Vec<AnalysisEntry>,
+    pub section_stats: Vec<SectionStats>,
+    pub left_backend: BackendDetection,
+    pub right_backend: BackendDetection,
+    /// `left->right` DHCP backend modes.
+    pub backend_transition: String,
+    /// Alias member changes, compared as sets (see [`crate::alias_members`]).
+    pub alias_changes: Vec<AliasMemberChange>,
+}
+
 /// Render diff entries for terminal output.
 pub fn render_text(entries: &[DiffEntry]) -> String {
     let raw = format_text(entries);
@@ -18,6 +36,10 @@ pub fn render_text(entries: &[DiffEntry]) -> String {
             line.yellow().to_string()
         } else if line.starts_with('!') {
             line.magenta().to_string()
+        } else if line.starts_with('>') {
+            line.blue().to_string()
+        } else if line.starts_with('@') {
+            line.cyan().to_string()
         } else {
             line.to_string()
         };
@@ -41,9 +63,14 @@ pub fn render_analysis(entries: &[AnalysisEntry]) -> String {
             RecommendedAction::ConflictManual => "MANUAL",
             RecommendedAction::Noop => "NOOP",
         };
+        let rationale: Vec<&str> = entry.rationale.iter().map(|h| h.as_str()).collect();
         out.push(format!(
-            "{prefix} action={:?} path={} reason={}",
-            entry.action, entry.path, entry.reason
+            "{prefix} action={:?} path={} confidence={} rationale={} reason={}",
+            entry.action,
+            entry.path,
+            entry.confidence,
+            rationale.join(","),
+            entry.reason
         ));
     }
     out.join("\n")
@@ -63,12 +90,15 @@ pub fn render_section_stats(rows: &[SectionStats]) -> String {
     out.push("section_summary".to_string());
     for row in rows_sorted {
         out.push(format!(
-            "- {}: modified={} only_left={} only_right={} structural={} conflicts={} safe={}",
+            "- {}: modified={} only_left={} only_right={} structural={} moved={} attributes={} relocated={} conflicts={} safe={}",
             row.section,
             row.modified,
             row.only_left,
             row.only_right,
             row.structural,
+            row.moved,
+            row.attributes,
+            row.relocated,
             row.conflict_manual,
             row.safe_actions
         ));
@@ -0,0 +1,129 @@
+//! User-supplied report templates.
+//!
+//! The `--template <FILE>` option of `diff`, `scan`, `verify`, and `convert`
+//! renders the command's report through a [minijinja] template instead of
+//! the built-in text or JSON output, so migration documents can be written
+//! in any language or format.
+//!
+//! The report is available to the template as `report`, with the same
+//! fields as the command's JSON output:
+//!
+//! | Command | Report type |
+//! |---------|-------------|
+//! | `diff` | [`crate::report::DiffReport`] |
+//! | `scan` | [`crate::scan::ScanReport`] |
+//! | `verify` | [`crate::verify::VerifyReport`] |
+//! | `convert` | [`crate::pipeline::ConversionReport`] |
+//!
+//! ```text
+//! Migration of {{ report.platform }} {{ report.version }}
+//! {% for issue in report.issues %}- [{{ issue.severity }}] {{ issue.message }}
+//! {% endfor %}
+//! ```
+//!
+//! [minijinja]: https://docs.rs/minijinja
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use minijinja::{context, Environment, UndefinedBehavior};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Errors from loading or rendering a report template.
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    /// Template file could not be read.
+    #[error("failed to read template {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Template has a syntax error or failed while rendering.
+    #[error("failed to render template {name}: {source:#}")]
+    Render {
+        name: String,
+        source: minijinja::Error,
+    },
+}
+
+/// Render `report` through the template file at `path`.
+///
+/// # Errors
+///
+/// Returns error if the file cannot be read or the template fails to
+/// parse or render.
+pub fn render_template_file(path: &Path, report: &impl Serialize) -> Result<String, TemplateError> {
+    let source = fs::read_to_string(path).map_err(|source| TemplateError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    render_template(&path.display().to_string(), &source, report)
+}
+
+/// Render `report` through template `source`; `name` identifies it in errors.
+///
+/// Undefined fields are errors rather than empty output, so a misspelt
+/// field name is caught instead of silently dropped.
+///
+/// # Errors
+///
+/// Returns error if the template fails to parse or render.
+pub fn render_template(
+    name: &str,
+    source: &str,
+    report: &impl Serialize,
+) -> Result<String, TemplateError> {
+    let render_error = |source| TemplateError::Render {
+        name: name.to_string(),
+        source,
+    };
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    env.add_template(name, source).map_err(render_error)?;
+    env.get_template(name)
+        .and_then(|template| template.render(context! { report => report }))
+        .map_err(render_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::{render_template, TemplateError};
+
+    #[derive(Serialize)]
+    struct Report {
+        platform: &'static str,
+        issues: Vec<&'static str>,
+    }
+
+    fn report() -> Report {
+        Report {
+            platform: "pfsense",
+            issues: vec!["a", "b"],
+        }
+    }
+
+    #[test]
+    fn renders_report_fields() {
+        let out = render_template(
+            "t",
+            "Plattform: {{ report.platform }}\n{% for i in report.issues %}* {{ i }}\n{% endfor %}",
+            &report(),
+        )
+        .expect("render");
+        assert_eq!(out, "Plattform: pfsense\n* a\n* b\n");
+    }
+
+    #[test]
+    fn rejects_undefined_fields_and_bad_syntax() {
+        let undefined = render_template("t", "{{ report.platfrom }}", &report());
+        assert!(matches!(undefined, Err(TemplateError::Render { .. })));
+
+        let syntax = render_template("t", "{% for %}", &report());
+        let message = syntax.expect_err("syntax error").to_string();
+        assert!(message.starts_with("failed to render template t:"));
+    }
+}
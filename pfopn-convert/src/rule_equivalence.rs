@@ -0,0 +1,501 @@
+//! Semantic comparison of a source ruleset with its converted output.
+//!
+//! Each enabled `<filter>` rule is normalized to a signature of the fields
+//! that decide what it matches — interfaces, direction, address family,
+//! protocol, source/destination with ports, action, and quick — with aliases
+//! expanded to their sorted entries and port names/ranges canonicalized. The
+//! two sides are then compared as multisets, so reordering and alias
+//! renames are not reported, but a rule dropped by pruning or changed by a
+//! mapping bug is.
+//!
+//! Source interface names are passed through the same `SRC=DST` map given to
+//! `convert --map-interface`. Rule order and OPNsense automation (MVC) rules
+//! are not compared.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::simulate::{load_aliases, parse_port_range, Alias};
+use crate::transform::booleans::flag_set_on;
+
+/// Matching-relevant fields of one rule after normalization.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct RuleSignature {
+    pub interfaces: Vec<String>,
+    pub direction: String,
+    pub ipprotocol: String,
+    pub protocol: String,
+    pub source: String,
+    pub source_port: String,
+    pub destination: String,
+    pub destination_port: String,
+    pub action: String,
+    pub quick: bool,
+}
+
+impl fmt::Display for RuleSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} on {} {} {} {}:{} -> {}:{}{}",
+            self.action,
+            self.direction,
+            self.interfaces.join(","),
+            self.ipprotocol,
+            self.protocol,
+            self.source,
+            self.source_port,
+            self.destination,
+            self.destination_port,
+            if self.quick { " quick" } else { "" }
+        )
+    }
+}
+
+/// A rule present on one side only.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleDifference {
+    /// 1-based position among `<filter>` rules on its side.
+    pub index: usize,
+    pub tracker: Option<String>,
+    pub descr: String,
+    pub signature: RuleSignature,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleEquivalenceReport {
+    pub source_platform: String,
+    pub converted_platform: String,
+    /// Enabled rules compared on each side.
+    pub source_rules: usize,
+    pub converted_rules: usize,
+    /// Disabled rules skipped on each side.
+    pub source_disabled: usize,
+    pub converted_disabled: usize,
+    pub matched: usize,
+    /// Source rules with no equivalent in the converted output.
+    pub missing: Vec<RuleDifference>,
+    /// Converted rules with no equivalent in the source.
+    pub added: Vec<RuleDifference>,
+}
+
+impl RuleEquivalenceReport {
+    /// Whether every source rule has an equivalent in the output.
+    pub fn equivalent(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Compare the filter rules of `source` and `converted`.
+///
+/// `interface_map` renames source logical interfaces (as with
+/// `convert --map-interface`) before comparing.
+pub fn compare_rulesets(
+    source: &XmlNode,
+    converted: &XmlNode,
+    interface_map: &BTreeMap<String, String>,
+) -> RuleEquivalenceReport {
+    let no_map = BTreeMap::new();
+    let (source_rules, source_disabled) = normalized_rules(source, interface_map);
+    let (converted_rules, converted_disabled) = normalized_rules(converted, &no_map);
+
+    let mut remaining: BTreeMap<&RuleSignature, Vec<&RuleDifference>> = BTreeMap::new();
+    for rule in &converted_rules {
+        remaining.entry(&rule.signature).or_default().push(rule);
+    }
+    let mut missing = Vec::new();
+    let mut matched = 0;
+    for rule in &source_rules {
+        match remaining.get_mut(&rule.signature).and_then(|v| {
+            if v.is_empty() {
+                None
+            } else {
+                Some(v.remove(0))
+            }
+        }) {
+            Some(_) => matched += 1,
+            None => missing.push(rule.clone()),
+        }
+    }
+    let mut added: Vec<RuleDifference> = remaining
+        .into_values()
+        .flatten()
+        .map(|r| (*r).clone())
+        .collect();
+    added.sort_by_key(|r| r.index);
+
+    RuleEquivalenceReport {
+        source_platform: platform_name(source).to_string(),
+        converted_platform: platform_name(converted).to_string(),
+        source_rules: source_rules.len(),
+        converted_rules: converted_rules.len(),
+        source_disabled,
+        converted_disabled,
+        matched,
+        missing,
+        added,
+    }
+}
+
+/// Render the report as text.
+pub fn render_rule_equivalence_text(report: &RuleEquivalenceReport) -> String {
+    let mut out = vec![format!(
+        "rule_equivalence equivalent={} source={} rules={} converted={} rules={} matched={} missing={} added={}",
+        report.equivalent(),
+        report.source_platform,
+        report.source_rules,
+        report.converted_platform,
+        report.converted_rules,
+        report.matched,
+        report.missing.len(),
+        report.added.len()
+    )];
+    if report.source_disabled + report.converted_disabled > 0 {
+        out.push(format!(
+            "disabled rules skipped: source={} converted={}",
+            report.source_disabled, report.converted_disabled
+        ));
+    }
+    for (title, rules) in [
+        ("missing in converted", &report.missing),
+        ("added in converted", &report.added),
+    ] {
+        if rules.is_empty() {
+            continue;
+        }
+        out.push(format!("{title}:"));
+        for rule in rules {
+            let mut line = format!("- #{}", rule.index);
+            if let Some(tracker) = &rule.tracker {
+                line.push_str(&format!(" (tracker {tracker})"));
+            }
+            if !rule.descr.is_empty() {
+                line.push_str(&format!(" \"{}\"", rule.descr));
+            }
+            line.push_str(&format!(": {}", rule.signature));
+            out.push(line);
+        }
+    }
+    out.join("\n")
+}
+
+fn platform_name(root: &XmlNode) -> &'static str {
+    match detect_config(root) {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => "unknown",
+    }
+}
+
+/// Normalize enabled rules; returns them with the count of disabled rules.
+fn normalized_rules(
+    root: &XmlNode,
+    interface_map: &BTreeMap<String, String>,
+) -> (Vec<RuleDifference>, usize) {
+    let Some(filter) = root.get_child("filter") else {
+        return (Vec::new(), 0);
+    };
+    let platform = platform_name(root);
+    let normalizer = Normalizer {
+        platform,
+        aliases: load_aliases(root),
+        interface_map,
+    };
+    let mut rules = Vec::new();
+    let mut disabled = 0;
+    for (i, rule) in filter.get_children("rule").into_iter().enumerate() {
+        if flag_set_on(rule, "disabled", platform) {
+            disabled += 1;
+            continue;
+        }
+        rules.push(RuleDifference {
+            index: i + 1,
+            tracker: non_empty_text(rule, "tracker").map(str::to_string),
+            descr: text(rule, "descr"),
+            signature: normalizer.signature(rule),
+        });
+    }
+    (rules, disabled)
+}
+
+struct Normalizer<'a> {
+    platform: &'static str,
+    aliases: BTreeMap<String, Alias>,
+    interface_map: &'a BTreeMap<String, String>,
+}
+
+impl Normalizer<'_> {
+    fn signature(&self, rule: &XmlNode) -> RuleSignature {
+        let floating = rule.get_child("floating").is_some();
+        let mut interfaces: Vec<String> = text(rule, "interface")
+            .split(',')
+            .map(|i| i.trim().to_ascii_lowercase())
+            .filter(|i| !i.is_empty())
+            .map(|i| self.map_interface(&i))
+            .collect();
+        interfaces.sort();
+        interfaces.dedup();
+        if interfaces.is_empty() {
+            interfaces.push("any".to_string());
+        }
+
+        let protocol = match text(rule, "protocol").to_ascii_lowercase().as_str() {
+            "" => "any".to_string(),
+            other => other.to_string(),
+        };
+        let uses_ports = protocol.split('/').any(|p| matches!(p, "tcp" | "udp"));
+        let (source, source_port) = self.side(rule, "source", uses_ports);
+        let (destination, destination_port) = self.side(rule, "destination", uses_ports);
+
+        RuleSignature {
+            interfaces,
+            direction: if floating {
+                match text(rule, "direction").as_str() {
+                    "" => "any".to_string(),
+                    other => other.to_string(),
+                }
+            } else {
+                "in".to_string()
+            },
+            ipprotocol: match text(rule, "ipprotocol").as_str() {
+                "" => "inet".to_string(),
+                other => other.to_string(),
+            },
+            protocol,
+            source,
+            source_port,
+            destination,
+            destination_port,
+            action: match text(rule, "type").as_str() {
+                "" => "pass".to_string(),
+                other => other.to_string(),
+            },
+            quick: !floating || flag_set_on(rule, "quick", self.platform),
+        }
+    }
+
+    fn map_interface(&self, name: &str) -> String {
+        self.interface_map
+            .get(name)
+            .map_or_else(|| name.to_string(), |n| n.to_ascii_lowercase())
+    }
+
+    /// Normalized `(address, port)` of a rule side.
+    fn side(&self, rule: &XmlNode, side: &str, uses_ports: bool) -> (String, String) {
+        let Some(node) = rule.get_child(side) else {
+            return ("any".to_string(), "any".to_string());
+        };
+        let address = if node.get_child("any").is_some() {
+            "any".to_string()
+        } else if let Some(spec) = non_empty_text(node, "address") {
+            self.address(spec)
+        } else if let Some(spec) = non_empty_text(node, "network") {
+            self.network(spec)
+        } else {
+            "any".to_string()
+        };
+        let address = if node.get_child("not").is_some() {
+            format!("!{address}")
+        } else {
+            address
+        };
+        let port = match non_empty_text(node, "port") {
+            Some(spec) if uses_ports => self.port(spec),
+            _ => "any".to_string(),
+        };
+        (address, port)
+    }
+
+    fn address(&self, spec: &str) -> String {
+        let mut entries = BTreeSet::new();
+        if !self.expand_address(spec, &mut entries, 0) {
+            return format!("alias:{spec}");
+        }
+        join_set(entries)
+    }
+
+    /// Expand host/network aliases into `out`; false if `spec` is an alias
+    /// that cannot be flattened (URL, GeoIP, ...).
+    fn expand_address(&self, spec: &str, out: &mut BTreeSet<String>, depth: usize) -> bool {
+        match self.aliases.get(spec) {
+            Some(alias) if depth < 8 => {
+                if !matches!(
+                    alias.kind.as_str(),
+                    "host" | "network" | "networkgroup" | ""
+                ) {
+                    return false;
+                }
+                alias
+                    .entries
+                    .iter()
+                    .all(|entry| self.expand_address(entry, out, depth + 1))
+            }
+            Some(_) => false,
+            None => {
+                out.insert(canonical_address(spec));
+                true
+            }
+        }
+    }
+
+    /// Interface networks (`lan`, `lanip`, `(self)`) keep their symbolic
+    /// form with the interface renamed; literal networks are canonicalized.
+    fn network(&self, spec: &str) -> String {
+        let lower = spec.to_ascii_lowercase();
+        if lower == "(self)" {
+            return "(self)".to_string();
+        }
+        if let Some(iface) = lower.strip_suffix("ip") {
+            if self.interface_map.contains_key(iface) {
+                return format!("{}ip", self.map_interface(iface));
+            }
+        }
+        if self.interface_map.contains_key(&lower) {
+            return self.map_interface(&lower);
+        }
+        if self.aliases.contains_key(spec) {
+            return self.address(spec);
+        }
+        canonical_address(&lower)
+    }
+
+    fn port(&self, spec: &str) -> String {
+        let mut entries = BTreeSet::new();
+        self.expand_port(spec, &mut entries, 0);
+        join_set(entries)
+    }
+
+    fn expand_port(&self, spec: &str, out: &mut BTreeSet<String>, depth: usize) {
+        if let Some(alias) = self.aliases.get(spec).filter(|_| depth < 8) {
+            for entry in &alias.entries {
+                self.expand_port(entry, out, depth + 1);
+            }
+            return;
+        }
+        let canonical = match parse_port_range(spec) {
+            Some((a, b)) if a == b => a.to_string(),
+            Some((a, b)) => format!("{a}-{b}"),
+            None => spec.to_string(),
+        };
+        out.insert(canonical);
+    }
+}
+
+/// Drop host prefixes (`/32`, `/128`) and lowercase IPv6.
+fn canonical_address(raw: &str) -> String {
+    let raw = raw.trim().to_ascii_lowercase();
+    match raw.split_once('/') {
+        Some((addr, "32")) if !addr.contains(':') => addr.to_string(),
+        Some((addr, "128")) if addr.contains(':') => addr.to_string(),
+        _ => raw,
+    }
+}
+
+fn join_set(entries: BTreeSet<String>) -> String {
+    match entries.len() {
+        0 => "any".to_string(),
+        1 => entries.into_iter().next().unwrap_or_default(),
+        _ => format!("{{{}}}", entries.into_iter().collect::<Vec<_>>().join(",")),
+    }
+}
+
+fn text(node: &XmlNode, tag: &str) -> String {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .unwrap_or("")
+        .to_string()
+}
+
+fn non_empty_text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use xml_diff_core::parse;
+
+    use super::compare_rulesets;
+
+    #[test]
+    fn alias_renames_and_reordering_are_equivalent() {
+        let source = parse(
+            br#"<pfsense><aliases><alias><name>dns</name><type>host</type><address>1.1.1.1 8.8.8.8</address></alias>
+            <alias><name>web</name><type>port</type><address>443 80</address></alias></aliases>
+            <filter>
+              <rule><type>pass</type><interface>lan</interface><protocol>tcp</protocol><source><any/></source><destination><address>dns</address><port>web</port></destination></rule>
+              <rule><type>block</type><interface>opt2</interface><source><any/></source><destination><any/></destination></rule>
+            </filter></pfsense>"#,
+        )
+        .expect("parse");
+        let converted = parse(
+            br#"<opnsense><OPNsense><Firewall><Alias><aliases><alias><name>resolvers</name><type>host</type><content>8.8.8.8
+1.1.1.1/32</content></alias><alias><name>web_ports</name><type>port</type><content>https
+80:80</content></alias></aliases></Alias></Firewall></OPNsense>
+            <filter>
+              <rule><type>block</type><interface>opt1</interface><source><any/></source><destination><any/></destination></rule>
+              <rule><interface>lan</interface><protocol>tcp</protocol><source><any/></source><destination><address>resolvers</address><port>web_ports</port></destination></rule>
+            </filter></opnsense>"#,
+        )
+        .expect("parse");
+        let map = BTreeMap::from([("opt2".to_string(), "opt1".to_string())]);
+
+        let report = compare_rulesets(&source, &converted, &map);
+        assert!(report.equivalent(), "{report:?}");
+        assert_eq!(report.matched, 2);
+        assert!(report.added.is_empty());
+    }
+
+    #[test]
+    fn reports_dropped_and_changed_rules() {
+        let source = parse(
+            br#"<pfsense><filter>
+              <rule><type>pass</type><interface>lan</interface><tracker>1</tracker><source><any/></source><destination><any/></destination></rule>
+              <rule><type>pass</type><interface>opt1</interface><tracker>2</tracker><source><any/></source><destination><any/></destination></rule>
+              <rule><type>pass</type><interface>opt2</interface><disabled/><source><any/></source><destination><any/></destination></rule>
+            </filter></pfsense>"#,
+        )
+        .expect("parse");
+        let converted = parse(
+            br#"<opnsense><filter>
+              <rule><type>block</type><interface>lan</interface><source><any/></source><destination><any/></destination></rule>
+            </filter></opnsense>"#,
+        )
+        .expect("parse");
+
+        let report = compare_rulesets(&source, &converted, &BTreeMap::new());
+        assert!(!report.equivalent());
+        let missing: Vec<_> = report.missing.iter().map(|r| r.index).collect();
+        assert_eq!(missing, vec![1, 2]);
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.source_disabled, 1);
+    }
+
+    #[test]
+    fn disabled_flags_are_read_per_platform() {
+        let source = parse(
+            br#"<pfsense><filter>
+              <rule><type>pass</type><interface>lan</interface><disabled>0</disabled><source><any/></source><destination><any/></destination></rule>
+            </filter></pfsense>"#,
+        )
+        .expect("parse");
+        let converted = parse(
+            br#"<opnsense><filter>
+              <rule><type>pass</type><interface>lan</interface><disabled>0</disabled><source><any/></source><destination><any/></destination></rule>
+            </filter></opnsense>"#,
+        )
+        .expect("parse");
+
+        let report = compare_rulesets(&source, &converted, &BTreeMap::new());
+        assert_eq!(report.source_disabled, 1);
+        assert_eq!(report.converted_disabled, 0);
+        assert_eq!(report.added.len(), 1);
+    }
+}
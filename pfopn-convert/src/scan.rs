@@ -7,6 +7,10 @@
 //! - DHCP backend (ISC vs Kea)
 //! - Supported vs unsupported config sections
 //! - Plugin compatibility and target platform support
+//! - Package settings left behind by removed packages, and installed
+//!   packages with no settings
+//! - Boot-time shell commands that need a security review
+//! - Config scale: item counts, size, and a complexity class
 //! - Migration blockers and recommendations
 //!
 //! ## Scan Workflow
@@ -22,15 +26,20 @@
 use std::collections::BTreeSet;
 
 use serde::Serialize;
-use xml_diff_core::XmlNode;
+use xml_diff_core::{ParseWarning, XmlNode};
 
 use crate::backend_detect::detect_dhcp_backend;
 use crate::detect::{detect_config, detect_version_info, ConfigFlavor, VersionDetection};
 use crate::plugin_detect::detect_plugins;
+use crate::scan_metrics::scan_metrics;
+use crate::scan_packages::check_packages;
 use crate::scan_plugins::{
     detect_known_plugins_present, detect_missing_target_compat, detect_unsupported_plugins,
     load_default_plugin_matrix_with_source,
 };
+use crate::verify_shellcmds::shell_commands;
+
+pub use crate::scan_metrics::{ComplexityClass, ScanMetrics};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ScanReport {
@@ -47,6 +56,18 @@ pub struct ScanReport {
     pub known_plugins_present: Vec<String>,
     pub unsupported_plugins: Vec<String>,
     pub missing_target_compat: Vec<String>,
+    /// `<installedpackages>` settings whose package is not installed, as
+    /// `package (section, ...)`.
+    pub stale_package_configs: Vec<String>,
+    /// Installed packages with no settings section.
+    pub unconfigured_packages: Vec<String>,
+    /// Boot-time shell commands, listed for a security review.
+    pub security_review: Vec<String>,
+    /// Item counts, size, and complexity class.
+    pub metrics: ScanMetrics,
+    /// Damage lenient parsing recovered from; the rest of the report
+    /// describes the recovered tree.
+    pub parse_warnings: Vec<String>,
     pub recommendations: Vec<String>,
 }
 
@@ -113,11 +134,7 @@ pub fn build_scan_report_with_version(
     ));
     supported_sections.sort();
     supported_sections.dedup();
-    let review_sections = top_level_sections
-        .iter()
-        .filter(|s| is_review_section(root, s, &supported_set))
-        .cloned()
-        .collect::<Vec<_>>();
+    let review_sections = review_sections(root);
 
     let plugin_inventory = detect_plugins(root);
     let (plugin_matrix, mappings_source) = load_default_plugin_matrix_with_source(mappings_dir);
@@ -126,6 +143,13 @@ pub fn build_scan_report_with_version(
     let unsupported_plugins = detect_unsupported_plugins(root, &platform, &plugin_matrix);
     let missing_target_compat =
         detect_missing_target_compat(&known_plugins_present, &platform, target, &plugin_matrix);
+    let packages = check_packages(root);
+
+    let security_review: Vec<String> = shell_commands(root)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let metrics = scan_metrics(root);
 
     let mut recommendations = Vec::new();
     if !unsupported_plugins.is_empty() {
@@ -145,6 +169,34 @@ pub fn build_scan_report_with_version(
             "plugins present in source are not marked compatible with selected target".to_string(),
         );
     }
+    if !packages.stale_configs.is_empty() {
+        recommendations.push(
+            "installedpackages holds settings for packages that are not installed (stale_package_configs); remove them before converting so they are not reported as dropped package configs"
+                .to_string(),
+        );
+    }
+    if !packages.unconfigured.is_empty() {
+        recommendations.push(
+            "installed packages have no settings (unconfigured_packages); uninstall the unused ones before converting so plugin checks reflect what is in use"
+                .to_string(),
+        );
+    }
+    if !security_review.is_empty() {
+        recommendations.push(
+            "boot-time shell commands run as root; review each in security_review and recreate the ones still needed on the target"
+                .to_string(),
+        );
+    }
+    if matches!(
+        metrics.complexity,
+        ComplexityClass::Large | ComplexityClass::VeryLarge
+    ) {
+        recommendations.push(format!(
+            "{} config: {}; include the metrics line when reporting issues",
+            metrics.complexity.as_str().replace('_', " "),
+            metrics.complexity.expectation()
+        ));
+    }
     if recommendations.is_empty() {
         recommendations.push(
             "no immediate blockers detected; run diff/convert for full validation".to_string(),
@@ -165,10 +217,34 @@ pub fn build_scan_report_with_version(
         known_plugins_present,
         unsupported_plugins,
         missing_target_compat,
+        stale_package_configs: packages.stale_configs,
+        unconfigured_packages: packages.unconfigured,
+        security_review,
+        metrics,
+        parse_warnings: Vec::new(),
         recommendations,
     }
 }
 
+/// Record the problems lenient parsing recovered from in `report`.
+///
+/// A recovered file may be missing whatever sat past the damage, so a fresh
+/// export is recommended ahead of every other step.
+pub fn add_parse_warnings(report: &mut ScanReport, warnings: &[ParseWarning]) {
+    if warnings.is_empty() {
+        return;
+    }
+    report.parse_warnings = warnings.iter().map(ToString::to_string).collect();
+    report
+        .recommendations
+        .retain(|r| !r.starts_with("no immediate blockers"));
+    report.recommendations.insert(
+        0,
+        "the file is damaged and was read leniently (see parse_warnings); take a fresh export before converting"
+            .to_string(),
+    );
+}
+
 pub fn render_scan_text(report: &ScanReport, verbose: bool) -> String {
     let mut out = Vec::new();
     out.push(format!(
@@ -182,12 +258,30 @@ pub fn render_scan_text(report: &ScanReport, verbose: bool) -> String {
     if verbose {
         out.push(format!("Using mappings: {}", report.mappings_source));
     }
+    let m = &report.metrics;
+    out.push(format!(
+        "metrics filter_rules={} nat_rules={} aliases={} static_maps={} vpn_instances={} certificates={} cert_store_bytes={} nodes={} estimated_output_bytes={} complexity={}",
+        m.filter_rules,
+        m.nat_rules,
+        m.aliases,
+        m.static_maps,
+        m.vpn_instances,
+        m.certificates,
+        m.cert_store_bytes,
+        m.nodes,
+        m.estimated_output_bytes,
+        m.complexity.as_str()
+    ));
     if let Some(to) = &report.target_platform {
         out.push(format!("target_platform={to}"));
     }
     if let Some(target_version) = &report.target_version {
         out.push(format!("target_version={target_version}"));
     }
+    if !report.parse_warnings.is_empty() {
+        out.push("parse_warnings".to_string());
+        append_list(&mut out, &report.parse_warnings);
+    }
     out.push("supported_sections".to_string());
     append_list(&mut out, &report.supported_sections);
     out.push("review_sections".to_string());
@@ -200,6 +294,12 @@ pub fn render_scan_text(report: &ScanReport, verbose: bool) -> String {
         out.push("missing_target_compat".to_string());
         append_list(&mut out, &report.missing_target_compat);
     }
+    out.push("stale_package_configs".to_string());
+    append_list(&mut out, &report.stale_package_configs);
+    out.push("unconfigured_packages".to_string());
+    append_list(&mut out, &report.unconfigured_packages);
+    out.push("security_review".to_string());
+    append_list(&mut out, &report.security_review);
     out.push("recommendations".to_string());
     append_list(&mut out, &report.recommendations);
     out.join("\n")
@@ -226,6 +326,26 @@ fn collect_top_sections(root: &XmlNode) -> Vec<String> {
     sections
 }
 
+/// Top-level sections of `root` outside the supported set for its platform.
+///
+/// These are the sections `scan` lists under `review_sections`: conversion
+/// has no dedicated handling for them.
+pub fn review_sections(root: &XmlNode) -> Vec<String> {
+    let platform = match detect_config(root) {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => "unknown",
+    };
+    let supported_set: BTreeSet<String> = supported_sections_for_platform(platform)
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect();
+    collect_top_sections(root)
+        .into_iter()
+        .filter(|s| is_review_section(root, s, &supported_set))
+        .collect()
+}
+
 fn supported_sections_for_platform(platform: &str) -> Vec<&'static str> {
     match platform {
         "pfsense" => vec![
@@ -250,11 +370,16 @@ fn supported_sections_for_platform(platform: &str) -> Vec<&'static str> {
             "ppps",
             "ovpnserver",
             "vlans",
+            "qinqs",
+            "laggs",
+            "pppoes",
+            "l2tp",
             "virtualip",
             "wireguard",
             "ifgroups",
             "gateways",
             "staticroutes",
+            "pfopn_snapshots",
         ],
         "opnsense" => vec![
             "system",
@@ -279,6 +404,9 @@ fn supported_sections_for_platform(platform: &str) -> Vec<&'static str> {
             "ppps",
             "ovpnserver",
             "vlans",
+            "laggs",
+            "pppoes",
+            "l2tp",
             "virtualip",
         ],
         _ => vec![],
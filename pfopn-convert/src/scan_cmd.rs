@@ -1,23 +1,33 @@
 use anyhow::{Context, Result};
-use pfopn_convert::scan::{build_scan_report_with_version, render_scan_text};
-use xml_diff_core::parse_file;
+use pfopn_convert::report_template::render_template_file;
+use pfopn_convert::scan::{add_parse_warnings, build_scan_report_with_version, render_scan_text};
+use xml_diff_core::{parse_file_with_options, ParseOptions};
 
 use crate::cli::{OutputFormat, ScanArgs, ScanTarget};
 
 pub fn run_scan(args: ScanArgs) -> Result<()> {
-    let node = parse_file(&args.file)
-        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let parsed = parse_file_with_options(
+        &args.file,
+        &ParseOptions {
+            lenient: true,
+            ..ParseOptions::default()
+        },
+    )
+    .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let node = parsed.root;
     let to = args.to.map(scan_target_name);
-    let report = build_scan_report_with_version(
+    let mut report = build_scan_report_with_version(
         &node,
         to,
         args.target_version.as_deref(),
         args.mappings_dir.as_deref(),
     );
+    add_parse_warnings(&mut report, &parsed.warnings);
 
-    match args.format {
-        OutputFormat::Text => println!("{}", render_scan_text(&report, args.verbose)),
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    match (&args.template, args.format) {
+        (Some(template), _) => print!("{}", render_template_file(template, &report)?),
+        (None, OutputFormat::Text) => println!("{}", render_scan_text(&report, args.verbose)),
+        (None, OutputFormat::Json) => println!("{}", serde_json::to_string_pretty(&report)?),
     }
 
     Ok(())
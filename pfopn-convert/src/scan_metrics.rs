@@ -0,0 +1,244 @@
+//! Config scale metrics for `scan`.
+//!
+//! Counts the repeated items conversion works through (rules, aliases,
+//! static maps, VPN instances, certificates) and sizes the tree, then files
+//! the config into a [`ComplexityClass`]. Users of very large configs learn
+//! what to expect before converting, and issue reports can be triaged by
+//! scale.
+//!
+//! Conversion keeps a pfSense-format copy of some sections in OPNsense
+//! output, so OPNsense models (aliases, `<OpenVPN>`, `<Swanctl>`, `<Kea>`,
+//! WireGuard) are counted instead of the legacy sections when they have
+//! entries. Legacy and MVC filter rules are both live and are added up.
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+/// Scale of a config, by tree size and rule count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplexityClass {
+    Small,
+    Medium,
+    Large,
+    VeryLarge,
+}
+
+impl ComplexityClass {
+    /// Upper bounds (exclusive) on element count and filter + NAT rules for
+    /// each class below [`ComplexityClass::VeryLarge`].
+    const BOUNDS: [(ComplexityClass, usize, usize); 3] = [
+        (ComplexityClass::Small, 5_000, 200),
+        (ComplexityClass::Medium, 25_000, 1_000),
+        (ComplexityClass::Large, 100_000, 5_000),
+    ];
+
+    fn classify(nodes: usize, rules: usize) -> Self {
+        Self::BOUNDS
+            .iter()
+            .find(|(_, max_nodes, max_rules)| nodes < *max_nodes && rules < *max_rules)
+            .map_or(ComplexityClass::VeryLarge, |(class, _, _)| *class)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ComplexityClass::Small => "small",
+            ComplexityClass::Medium => "medium",
+            ComplexityClass::Large => "large",
+            ComplexityClass::VeryLarge => "very_large",
+        }
+    }
+
+    /// What to expect from convert and verify at this scale.
+    pub fn expectation(self) -> &'static str {
+        match self {
+            ComplexityClass::Small | ComplexityClass::Medium => "no special handling needed",
+            ComplexityClass::Large => {
+                "convert and verify take noticeably longer than on typical configs"
+            }
+            ComplexityClass::VeryLarge => {
+                "convert and verify are slow and hold several copies of the config in memory; run them on a machine with memory to spare"
+            }
+        }
+    }
+}
+
+/// Item counts and size of a config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanMetrics {
+    pub filter_rules: usize,
+    /// Port forwards, outbound, 1:1, and NPT rules.
+    pub nat_rules: usize,
+    pub aliases: usize,
+    /// DHCP static mappings and Kea reservations.
+    pub static_maps: usize,
+    /// OpenVPN servers and clients, IPsec tunnels, and WireGuard tunnels.
+    pub vpn_instances: usize,
+    /// Certificates and CAs.
+    pub certificates: usize,
+    /// Bytes of certificate and key text in the cert store.
+    pub cert_store_bytes: usize,
+    /// Elements in the tree.
+    pub nodes: usize,
+    /// Size of the config when written back out; converted output is
+    /// usually close to the source's size.
+    pub estimated_output_bytes: usize,
+    pub complexity: ComplexityClass,
+}
+
+/// Count the items and size of `root`.
+pub fn scan_metrics(root: &XmlNode) -> ScanMetrics {
+    let filter_rules = count(root, &["filter", "rule"])
+        + count(root, &["OPNsense", "Firewall", "Filter", "rules", "rule"]);
+    let nat_rules = count(root, &["nat", "rule"])
+        + count(root, &["nat", "outbound", "rule"])
+        + count(root, &["nat", "onetoone"])
+        + count(root, &["nat", "npt"]);
+    let aliases = first_non_zero(&[
+        count(root, &["OPNsense", "Firewall", "Alias", "aliases", "alias"]),
+        count(root, &["aliases", "alias"]),
+    ]);
+    let static_maps = first_non_zero(&[
+        count(
+            root,
+            &["OPNsense", "Kea", "dhcp4", "reservations", "reservation"],
+        ) + count(
+            root,
+            &["OPNsense", "Kea", "dhcp6", "reservations", "reservation"],
+        ),
+        ["dhcpd", "dhcpdv6", "dhcpd6"]
+            .iter()
+            .filter_map(|section| root.get_child(section))
+            .flat_map(|section| &section.children)
+            .map(|scope| scope.get_children("staticmap").len())
+            .sum(),
+    ]);
+    let vpn_instances = first_non_zero(&[
+        count(root, &["OPNsense", "OpenVPN", "Instances", "Instance"]),
+        count(root, &["openvpn", "openvpn-server"]) + count(root, &["openvpn", "openvpn-client"]),
+    ]) + first_non_zero(&[
+        count(root, &["OPNsense", "Swanctl", "Connections", "Connection"]),
+        count(root, &["ipsec", "phase1"]),
+    ]) + first_non_zero(&[
+        count(
+            root,
+            &["OPNsense", "wireguard", "server", "servers", "server"],
+        ),
+        count(root, &["wireguard", "tunnels", "item"]),
+    ]);
+    let store: Vec<&XmlNode> = root
+        .children
+        .iter()
+        .filter(|c| c.tag == "cert" || c.tag == "ca")
+        .collect();
+    let cert_store_bytes = store
+        .iter()
+        .flat_map(|entry| ["crt", "prv"].map(|field| entry.get_text(&[field])))
+        .flatten()
+        .map(|text| text.trim().len())
+        .sum();
+    let nodes = node_count(root);
+    let estimated_output_bytes = xml_diff_core::write(root).map_or(0, |bytes| bytes.len());
+
+    ScanMetrics {
+        filter_rules,
+        nat_rules,
+        aliases,
+        static_maps,
+        vpn_instances,
+        certificates: store.len(),
+        cert_store_bytes,
+        nodes,
+        estimated_output_bytes,
+        complexity: ComplexityClass::classify(nodes, filter_rules + nat_rules),
+    }
+}
+
+/// Number of elements at `path`, where the last segment is the repeated tag.
+fn count(root: &XmlNode, path: &[&str]) -> usize {
+    let (tag, parents) = path.split_last().expect("non-empty path");
+    let mut node = root;
+    for part in parents {
+        match node.get_child(part) {
+            Some(child) => node = child,
+            None => return 0,
+        }
+    }
+    node.get_children(tag).len()
+}
+
+fn first_non_zero(counts: &[usize]) -> usize {
+    counts.iter().copied().find(|c| *c > 0).unwrap_or(0)
+}
+
+fn node_count(node: &XmlNode) -> usize {
+    1 + node.children.iter().map(node_count).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{scan_metrics, ComplexityClass};
+
+    #[test]
+    fn counts_pfsense_items() {
+        let root = parse(
+            br#"<pfsense>
+            <filter><rule><tracker>1</tracker></rule><rule><tracker>2</tracker></rule></filter>
+            <nat><rule/><outbound><rule/></outbound><onetoone/></nat>
+            <aliases><alias><name>a</name></alias></aliases>
+            <dhcpd><lan><staticmap/><staticmap/></lan><opt1><staticmap/></opt1></dhcpd>
+            <openvpn><openvpn-server/><openvpn-client/></openvpn>
+            <ipsec><phase1/><phase2/></ipsec>
+            <cert><crt>abcd</crt><prv>ef</prv></cert><ca><crt>xyz</crt></ca>
+            </pfsense>"#,
+        )
+        .expect("parse");
+        let metrics = scan_metrics(&root);
+        assert_eq!(metrics.filter_rules, 2);
+        assert_eq!(metrics.nat_rules, 3);
+        assert_eq!(metrics.aliases, 1);
+        assert_eq!(metrics.static_maps, 3);
+        assert_eq!(metrics.vpn_instances, 3);
+        assert_eq!(metrics.certificates, 2);
+        assert_eq!(metrics.cert_store_bytes, 9);
+        assert_eq!(metrics.complexity, ComplexityClass::Small);
+        assert!(metrics.estimated_output_bytes > 0);
+    }
+
+    #[test]
+    fn prefers_opnsense_models_over_legacy_copies() {
+        let root = parse(
+            br#"<opnsense>
+            <filter><rule/></filter>
+            <openvpn><openvpn-server/></openvpn>
+            <OPNsense>
+              <Firewall><Alias><aliases><alias/><alias/></aliases></Alias></Firewall>
+              <OpenVPN><Instances><Instance/><Instance/></Instances></OpenVPN>
+              <Kea><dhcp4><reservations><reservation/></reservations></dhcp4></Kea>
+            </OPNsense>
+            </opnsense>"#,
+        )
+        .expect("parse");
+        let metrics = scan_metrics(&root);
+        assert_eq!(metrics.filter_rules, 1);
+        assert_eq!(metrics.aliases, 2);
+        assert_eq!(metrics.vpn_instances, 2);
+        assert_eq!(metrics.static_maps, 1);
+    }
+
+    #[test]
+    fn classifies_by_size_and_rule_count() {
+        assert_eq!(ComplexityClass::classify(100, 10), ComplexityClass::Small);
+        assert_eq!(ComplexityClass::classify(100, 500), ComplexityClass::Medium);
+        assert_eq!(
+            ComplexityClass::classify(30_000, 10),
+            ComplexityClass::Large
+        );
+        assert_eq!(
+            ComplexityClass::classify(200_000, 10),
+            ComplexityClass::VeryLarge
+        );
+    }
+}
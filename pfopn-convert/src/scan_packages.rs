@@ -0,0 +1,302 @@
+//! Package assurance for `scan`.
+//!
+//! pfSense keeps each package's settings under `<installedpackages>` next to
+//! the `<package>` entries that record what is installed. Removing a package
+//! leaves its settings behind, so a config can carry sections for packages
+//! that are long gone, and an installed package can have no settings at all.
+//! Both are worth cleaning up before converting: stale sections show up as
+//! dropped package configs, and unused packages as plugins to migrate.
+//!
+//! Only packages in [`PACKAGE_CONFIGS`] are checked; sections and packages
+//! the table does not know are left alone rather than guessed at.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use xml_diff_core::XmlNode;
+
+/// `<installedpackages>` children that are package-manager metadata.
+const METADATA: &[&str] = &["package", "menu", "service", "tab"];
+
+/// A package and the `<installedpackages>` sections holding its settings.
+struct PackageConfig {
+    /// Package `<name>` or `<internal_name>` values, lowercased with spaces
+    /// as `_`; the first is used in reports.
+    names: &'static [&'static str],
+    /// Section tags; a trailing `*` matches any tag with that prefix.
+    sections: &'static [&'static str],
+}
+
+const PACKAGE_CONFIGS: &[PackageConfig] = &[
+    PackageConfig {
+        names: &["pfblockerng", "pfblockerng-devel"],
+        sections: &["pfblockerng*"],
+    },
+    PackageConfig {
+        names: &["snort"],
+        sections: &["snortglobal", "snortsync"],
+    },
+    PackageConfig {
+        names: &["suricata"],
+        sections: &["suricata", "suricatasync"],
+    },
+    PackageConfig {
+        names: &["squid"],
+        sections: &[
+            "squid",
+            "squidcache",
+            "squidremote",
+            "squidauth",
+            "squidantivirus",
+            "squidnac",
+            "squidtraffic",
+            "squidusers",
+            "squidreversegeneral",
+            "squidreversepeer",
+            "squidreverseuri",
+            "squidreverseredir",
+        ],
+    },
+    PackageConfig {
+        names: &["squidguard"],
+        sections: &["squidguard*"],
+    },
+    PackageConfig {
+        names: &["lightsquid"],
+        sections: &["lightsquid"],
+    },
+    PackageConfig {
+        names: &["net-snmp"],
+        sections: &["netsnmp*"],
+    },
+    PackageConfig {
+        names: &["nrpe"],
+        sections: &["nrpe"],
+    },
+    PackageConfig {
+        names: &["service_watchdog"],
+        sections: &["servicewatchdog"],
+    },
+    PackageConfig {
+        names: &["shellcmd"],
+        sections: &["shellcmd", "shellcmdsettings"],
+    },
+    PackageConfig {
+        names: &["system_patches"],
+        sections: &["patches"],
+    },
+    PackageConfig {
+        names: &["tailscale"],
+        sections: &["tailscale", "tailscaleauth"],
+    },
+    PackageConfig {
+        names: &["wireguard"],
+        sections: &["wireguard"],
+    },
+    PackageConfig {
+        names: &["haproxy", "haproxy-devel"],
+        sections: &["haproxy"],
+    },
+    PackageConfig {
+        names: &["acme"],
+        sections: &["acme"],
+    },
+    PackageConfig {
+        names: &["filer"],
+        sections: &["filer"],
+    },
+    PackageConfig {
+        names: &["frr"],
+        sections: &["frr*"],
+    },
+    PackageConfig {
+        names: &["openbgpd"],
+        sections: &["openbgpd*"],
+    },
+    PackageConfig {
+        names: &["freeradius3"],
+        sections: &["freeradius*"],
+    },
+    PackageConfig {
+        names: &["bind"],
+        sections: &["bind*"],
+    },
+    PackageConfig {
+        names: &["avahi"],
+        sections: &["avahi"],
+    },
+    PackageConfig {
+        names: &["ntopng"],
+        sections: &["ntopng"],
+    },
+    PackageConfig {
+        names: &["telegraf"],
+        sections: &["telegraf"],
+    },
+    PackageConfig {
+        names: &["lldpd"],
+        sections: &["lldpd"],
+    },
+    PackageConfig {
+        names: &["nut"],
+        sections: &["nut"],
+    },
+    PackageConfig {
+        names: &["arpwatch"],
+        sections: &["arpwatch"],
+    },
+    PackageConfig {
+        names: &["cron"],
+        sections: &["cron"],
+    },
+    PackageConfig {
+        names: &["sudo"],
+        sections: &["sudo"],
+    },
+    PackageConfig {
+        names: &["mailreport"],
+        sections: &["mailreports"],
+    },
+    PackageConfig {
+        names: &["bandwidthd"],
+        sections: &["bandwidthd"],
+    },
+    PackageConfig {
+        names: &["darkstat"],
+        sections: &["darkstat"],
+    },
+    PackageConfig {
+        names: &["pimd"],
+        sections: &["pimd*"],
+    },
+];
+
+/// Stale and unused packages found in `<installedpackages>`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct PackageAssurance {
+    /// `package (section, ...)` for settings whose package is not installed.
+    pub stale_configs: Vec<String>,
+    /// Installed packages (by `<name>`) with no settings section.
+    pub unconfigured: Vec<String>,
+}
+
+/// Compare installed packages with the settings sections beside them.
+pub(crate) fn check_packages(root: &XmlNode) -> PackageAssurance {
+    let Some(installed) = root.get_child("installedpackages") else {
+        return PackageAssurance::default();
+    };
+
+    let mut installed_configs = BTreeSet::new();
+    let mut installed_names = Vec::new();
+    for package in installed.children.iter().filter(|c| c.tag == "package") {
+        let names: Vec<String> = ["name", "internal_name"]
+            .iter()
+            .filter_map(|field| package.get_text(&[*field]))
+            .map(normalize)
+            .filter(|n| !n.is_empty())
+            .collect();
+        let Some(display) = package.get_text(&["name"]).map(str::trim) else {
+            continue;
+        };
+        let config = PACKAGE_CONFIGS
+            .iter()
+            .position(|c| names.iter().any(|n| c.names.contains(&n.as_str())));
+        if let Some(config) = config {
+            installed_configs.insert(config);
+            installed_names.push((config, display.to_string()));
+        }
+    }
+
+    let mut stale: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    let mut configured = BTreeSet::new();
+    for section in &installed.children {
+        let tag = section.tag.as_str();
+        if METADATA.contains(&tag) {
+            continue;
+        }
+        let Some(config) = PACKAGE_CONFIGS
+            .iter()
+            .position(|c| c.sections.iter().any(|s| section_matches(s, tag)))
+        else {
+            continue;
+        };
+        configured.insert(config);
+        if !installed_configs.contains(&config) {
+            let sections = stale.entry(config).or_default();
+            if !sections.contains(&tag) {
+                sections.push(tag);
+            }
+        }
+    }
+
+    let mut stale_configs: Vec<String> = stale
+        .into_iter()
+        .map(|(config, sections)| {
+            format!(
+                "{} ({})",
+                PACKAGE_CONFIGS[config].names[0],
+                sections.join(", ")
+            )
+        })
+        .collect();
+    stale_configs.sort();
+    let mut unconfigured: Vec<String> = installed_names
+        .into_iter()
+        .filter(|(config, _)| !configured.contains(config))
+        .map(|(_, name)| name)
+        .collect();
+    unconfigured.sort();
+    unconfigured.dedup();
+    PackageAssurance {
+        stale_configs,
+        unconfigured,
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_ascii_lowercase().replace(' ', "_")
+}
+
+fn section_matches(pattern: &str, tag: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => tag.starts_with(prefix),
+        None => tag == pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::check_packages;
+
+    #[test]
+    fn flags_stale_configs_and_unconfigured_packages() {
+        let root = parse(
+            br#"<pfsense><installedpackages>
+                <package><name>System Patches</name><internal_name>System_Patches</internal_name></package>
+                <package><name>Tailscale</name></package>
+                <package><name>iperf</name></package>
+                <snortglobal/><pfblockerng/><pfblockernglistsv4/><pfblockernglistsv4/>
+                <squidguardgeneral/><tailscale/><tailscaleauth/><menu/><service/><mystery/>
+            </installedpackages></pfsense>"#,
+        )
+        .expect("parse");
+
+        let report = check_packages(&root);
+        assert_eq!(
+            report.stale_configs,
+            [
+                "pfblockerng (pfblockerng, pfblockernglistsv4)",
+                "snort (snortglobal)",
+                "squidguard (squidguardgeneral)",
+            ]
+        );
+        assert_eq!(report.unconfigured, ["System Patches"]);
+    }
+
+    #[test]
+    fn configs_without_installedpackages_are_clean() {
+        let root = parse(b"<opnsense><system/></opnsense>").expect("parse");
+        assert_eq!(check_packages(&root), Default::default());
+    }
+}
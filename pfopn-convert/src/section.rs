@@ -1,4 +1,14 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use xml_diff_core::PathQuery;
+
+use crate::canonical::top_level_order;
+use crate::capabilities::capabilities;
+
+/// Repeated elements whose document order carries meaning, with the child
+/// that identifies one element across configs. Filter and NAT rules are
+/// evaluated top to bottom, so a moved rule changes behaviour.
+pub const ORDERED_ELEMENTS: &[(&str, &str)] = &[("rule", "tracker")];
 
 /// Return default key-field mappings for better repeated-element matching.
 pub fn default_key_fields() -> HashMap<String, String> {
@@ -8,6 +18,128 @@ pub fn default_key_fields() -> HashMap<String, String> {
     key_fields
 }
 
+/// Return the tags diffed as ordered sections (see [`ORDERED_ELEMENTS`]).
+pub fn default_ordered_tags() -> HashSet<String> {
+    ORDERED_ELEMENTS
+        .iter()
+        .map(|(tag, _)| tag.to_string())
+        .collect()
+}
+
+/// Key child of an ordered element tag, if `tag` is one.
+pub fn ordered_key(tag: &str) -> Option<&'static str> {
+    ORDERED_ELEMENTS
+        .iter()
+        .find(|(ordered, _)| *ordered == tag)
+        .map(|(_, key)| *key)
+}
+
+/// A named group of fields that change without a configuration change,
+/// ignored together by `diff --ignore-category`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IgnoreCategory {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Paths or tags in `--ignore` syntax.
+    pub paths: &'static [&'static str],
+    /// Path queries (see [`PathQuery`]) for fields only ignored below a
+    /// particular parent.
+    pub queries: &'static [&'static str],
+}
+
+/// Ignore presets for routine diffs.
+pub const IGNORE_CATEGORIES: &[IgnoreCategory] = &[
+    IgnoreCategory {
+        name: "metadata",
+        description: "revision history, last-change stamps, and per-object created/updated times",
+        paths: &["revision", "lastchange", "created", "updated"],
+        queries: &[],
+    },
+    IgnoreCategory {
+        name: "change-user",
+        description: "who made the last change to the config or an object",
+        paths: &[],
+        queries: &[
+            "//revision.username",
+            "//created.username",
+            "//updated.username",
+        ],
+    },
+    IgnoreCategory {
+        name: "model-versions",
+        description: "OPNsense model version attributes bumped by firmware updates",
+        paths: &["@version"],
+        queries: &[],
+    },
+    IgnoreCategory {
+        name: "stats",
+        description: "RRD graph data embedded in backups",
+        paths: &["rrddata"],
+        queries: &[],
+    },
+    IgnoreCategory {
+        name: "leases",
+        description: "DHCP lease data embedded in backups",
+        paths: &["dhcpleases"],
+        queries: &[],
+    },
+    IgnoreCategory {
+        name: "dynamic-state",
+        description: "runtime data embedded in backups (stats and leases)",
+        paths: &["rrddata", "dhcpleases"],
+        queries: &[],
+    },
+];
+
+/// Expand ignore category names into `--ignore` paths.
+///
+/// Returns an error naming the known categories when one is unknown.
+pub fn ignore_category_paths(names: &[String]) -> Result<Vec<&'static str>, String> {
+    let mut out: Vec<&'static str> = Vec::new();
+    for category in find_ignore_categories(names)? {
+        for path in category.paths {
+            if !out.contains(path) {
+                out.push(path);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Expand ignore category names into path queries.
+///
+/// Returns an error naming the known categories when one is unknown.
+pub fn ignore_category_queries(names: &[String]) -> Result<Vec<PathQuery>, String> {
+    let mut out: Vec<PathQuery> = Vec::new();
+    for category in find_ignore_categories(names)? {
+        for raw in category.queries {
+            let query = PathQuery::parse(raw).expect("preset queries parse");
+            if !out.contains(&query) {
+                out.push(query);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn find_ignore_categories(names: &[String]) -> Result<Vec<&'static IgnoreCategory>, String> {
+    names
+        .iter()
+        .map(|name| {
+            IGNORE_CATEGORIES
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| {
+                    let known: Vec<&str> = IGNORE_CATEGORIES.iter().map(|c| c.name).collect();
+                    format!(
+                        "unknown ignore category '{name}'; expected one of {}",
+                        known.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
 /// Map a logical section flag to concrete top-level tags.
 pub fn section_tags(section: &str) -> Option<&'static [&'static str]> {
     match section {
@@ -20,3 +152,89 @@ pub fn section_tags(section: &str) -> Option<&'static [&'static str]> {
         _ => None,
     }
 }
+
+/// Containers below the top level that section mappings point at: OPNsense
+/// models and the package or plugin sections they replace.
+const NESTED_SECTIONS: &[&str] = &[
+    "Alias",
+    "Firewall",
+    "Gateways",
+    "gateway",
+    "TrafficShaper",
+    "cron",
+    "Kea",
+    "isc",
+    "DHCRelay",
+    "IPsec",
+    "Swanctl",
+    "VTIs",
+    "vtimaps",
+    "OpenVPN",
+    "wireguard",
+    "tailscale",
+    "tailscaleauth",
+    "Monit",
+    "unboundplus",
+    "IDS",
+    "captiveportal",
+    "Netflow",
+];
+
+/// Every section name the tool knows, normalized for comparison.
+///
+/// Covers both platforms' top-level sections, the logical section groups of
+/// [`section_tags`], the sections capabilities read (including
+/// `installedpackages/<pkg>` and `OPNsense/<model>` children), and
+/// [`NESTED_SECTIONS`].
+pub fn known_sections() -> BTreeSet<String> {
+    let mut names: Vec<&str> = Vec::new();
+    for platform in ["pfsense", "opnsense"] {
+        names.extend(top_level_order(platform).unwrap_or_default());
+    }
+    for group in [
+        "system",
+        "interfaces",
+        "firewall",
+        "services",
+        "vpn",
+        "packages",
+    ] {
+        names.push(group);
+        names.extend(section_tags(group).unwrap_or_default());
+    }
+    for capability in capabilities() {
+        names.extend(capability.sections.iter().flat_map(|s| s.split('/')));
+    }
+    names.extend(NESTED_SECTIONS);
+    names.into_iter().map(normalize_section).collect()
+}
+
+/// Whether `name` is a section [`known_sections`] lists, ignoring case and
+/// punctuation.
+pub fn is_known_section(name: &str) -> bool {
+    known_sections().contains(&normalize_section(name))
+}
+
+fn normalize_section(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ignore_category_paths;
+
+    #[test]
+    fn expands_categories_without_duplicates() {
+        let names = vec!["stats".to_string(), "Dynamic-State".to_string()];
+        assert_eq!(
+            ignore_category_paths(&names).expect("known"),
+            ["rrddata", "dhcpleases"]
+        );
+        assert!(ignore_category_paths(&["nope".to_string()])
+            .unwrap_err()
+            .contains("expected one of metadata"));
+    }
+}
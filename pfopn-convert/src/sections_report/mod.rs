@@ -100,6 +100,9 @@ pub struct SectionStats {
     pub only_left: usize,
     pub only_right: usize,
     pub structural: usize,
+    pub moved: usize,
+    pub attributes: usize,
+    pub relocated: usize,
     pub conflict_manual: usize,
     pub safe_actions: usize,
 }
@@ -267,6 +270,9 @@ pub(crate) fn diff_path(entry: &DiffEntry) -> &str {
         | DiffEntry::Modified { path, .. }
         | DiffEntry::OnlyLeft { path, .. }
         | DiffEntry::OnlyRight { path, .. }
-        | DiffEntry::Structural { path, .. } => path,
+        | DiffEntry::Structural { path, .. }
+        | DiffEntry::Moved { path, .. }
+        | DiffEntry::AttributeChanged { path, .. }
+        | DiffEntry::Relocated { to: path, .. } => path,
     }
 }
@@ -10,6 +10,10 @@
 //! - **OnlyLeft** — Elements only in left config
 //! - **OnlyRight** — Elements only in right config
 //! - **Structural** — Schema or structural differences
+//! - **Moved** — Elements of ordered sections whose position changed
+//! - **Attributes** — Attributes (`uuid`, model `version`) that differ
+//! - **Relocated** — Unchanged elements moved under another parent, counted
+//!   in the section they moved to
 //! - **ConflictManual** — Actions requiring manual intervention
 //! - **SafeActions** — Actions that can be safely automated
 //!
@@ -54,6 +58,9 @@ pub fn summarize_by_section(
                 only_left: 0,
                 only_right: 0,
                 structural: 0,
+                moved: 0,
+                attributes: 0,
+                relocated: 0,
                 conflict_manual: 0,
                 safe_actions: 0,
             });
@@ -63,6 +70,9 @@ pub fn summarize_by_section(
             DiffEntry::OnlyLeft { .. } => row.only_left += 1,
             DiffEntry::OnlyRight { .. } => row.only_right += 1,
             DiffEntry::Structural { .. } => row.structural += 1,
+            DiffEntry::Moved { .. } => row.moved += 1,
+            DiffEntry::AttributeChanged { .. } => row.attributes += 1,
+            DiffEntry::Relocated { .. } => row.relocated += 1,
             DiffEntry::Identical { .. } => {}
         }
     }
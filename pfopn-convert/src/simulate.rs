@@ -0,0 +1,1144 @@
+//! Firewall rule simulation for a single packet.
+//!
+//! Walks a config the way pf evaluates it for a packet arriving on one
+//! interface, so a converted ruleset can be checked against the original:
+//!
+//! 1. Inbound translation — 1:1 NAT, then port forwards (first match wins).
+//!    A port forward with a "pass" filter association passes immediately.
+//! 2. "Block private networks" on the inbound interface.
+//! 3. Filter rules — floating, then interface group, then interface rules.
+//!    Quick rules decide on first match; non-quick floating rules are
+//!    last-match and only stand if no later rule matches.
+//! 4. Default deny.
+//! 5. Outbound NAT on the egress interface (the one whose subnet holds the
+//!    destination, otherwise `wan`).
+//!
+//! Both platforms share this order for the legacy `<filter>`/`<nat>`
+//! sections. Hostname and URL aliases, bogon tables, schedules, and OPNsense
+//! MVC (automation) rules cannot be evaluated offline; they are reported as
+//! notes, and a rule that depends on them is treated as not matching.
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use serde::Serialize;
+use thiserror::Error;
+use xml_diff_core::XmlNode;
+
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::transform::booleans::flag_set_on;
+use crate::verify_routes::same_network;
+
+/// The packet to trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Packet {
+    /// Inbound interface: logical name (`lan`, `opt1`), description, or group.
+    pub interface: String,
+    /// `tcp`, `udp`, `icmp`, or another protocol name.
+    pub protocol: String,
+    pub src: IpAddr,
+    pub src_port: Option<u16>,
+    pub dst: IpAddr,
+    pub dst_port: Option<u16>,
+}
+
+/// Final verdict for the packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Pass,
+    Block,
+    Reject,
+}
+
+impl Verdict {
+    fn from_type(value: &str) -> Option<Self> {
+        match value {
+            "" | "pass" => Some(Self::Pass),
+            "block" => Some(Self::Block),
+            "reject" => Some(Self::Reject),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Block => "block",
+            Self::Reject => "reject",
+        }
+    }
+}
+
+/// The rule that decided the verdict.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleHit {
+    /// `floating`, `group`, `interface`, `block_private`, `nat_pass`, or `default`.
+    pub stage: String,
+    /// 1-based position among `<filter>` (or `<nat>`) rules.
+    pub index: Option<usize>,
+    pub tracker: Option<String>,
+    pub descr: String,
+    pub verdict: Verdict,
+    /// Policy-routing gateway set on the rule.
+    pub gateway: Option<String>,
+}
+
+/// A NAT rule that translated the packet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NatHit {
+    /// `one_to_one`, `port_forward`, `outbound`, `automatic_outbound`, or `no_nat`.
+    pub kind: String,
+    pub index: Option<usize>,
+    pub descr: String,
+    /// Translated address (and port), e.g. `192.168.1.10:443`.
+    pub translated: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimulationResult {
+    pub platform: String,
+    pub packet: Packet,
+    /// Logical interface the packet was resolved to.
+    pub interface: String,
+    pub interface_groups: Vec<String>,
+    pub inbound_nat: Option<NatHit>,
+    pub rule: RuleHit,
+    /// Non-quick floating rules that matched but were overridden.
+    pub overridden: Vec<RuleHit>,
+    pub egress_interface: Option<String>,
+    pub outbound_nat: Option<NatHit>,
+    pub verdict: Verdict,
+    /// Rules or inputs that could not be evaluated offline.
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum SimulateError {
+    #[error("unable to detect platform of config")]
+    UnknownPlatform,
+    #[error("interface '{0}' is not assigned in this config")]
+    UnknownInterface(String),
+}
+
+/// Trace `packet` through the filter and NAT rules of `root`.
+pub fn simulate(root: &XmlNode, packet: &Packet) -> Result<SimulationResult, SimulateError> {
+    let platform = match detect_config(root) {
+        ConfigFlavor::PfSense => "pfsense",
+        ConfigFlavor::OpnSense => "opnsense",
+        ConfigFlavor::Unknown => return Err(SimulateError::UnknownPlatform),
+    };
+    let ctx = Context::new(root, platform);
+    let interface = ctx
+        .resolve_interface(&packet.interface)
+        .ok_or_else(|| SimulateError::UnknownInterface(packet.interface.clone()))?;
+    let groups = ctx.groups_of(&interface);
+    let mut sim = Simulation {
+        ctx: &ctx,
+        interface,
+        groups,
+        protocol: packet.protocol.to_ascii_lowercase(),
+        src: packet.src,
+        src_port: packet.src_port,
+        dst: packet.dst,
+        dst_port: packet.dst_port,
+        notes: Vec::new(),
+    };
+
+    if root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("Firewall"))
+        .and_then(|f| f.get_child("Filter"))
+        .and_then(|f| f.get_child("rules"))
+        .is_some_and(|r| !r.children.is_empty())
+    {
+        sim.notes
+            .push("OPNsense automation (MVC) filter rules are present but not simulated".into());
+    }
+
+    let (inbound_nat, nat_pass) = sim.inbound_nat();
+    let (rule, overridden) = match nat_pass {
+        Some(hit) => (hit, Vec::new()),
+        None => sim.filter(),
+    };
+
+    let (egress_interface, outbound_nat) = if rule.verdict == Verdict::Pass {
+        sim.outbound_nat()
+    } else {
+        (None, None)
+    };
+
+    Ok(SimulationResult {
+        platform: platform.to_string(),
+        packet: packet.clone(),
+        interface: sim.interface.clone(),
+        interface_groups: sim.groups.clone(),
+        inbound_nat,
+        verdict: rule.verdict,
+        rule,
+        overridden,
+        egress_interface,
+        outbound_nat,
+        notes: sim.notes,
+    })
+}
+
+/// Render a simulation result as text.
+pub fn render_simulation_text(result: &SimulationResult) -> String {
+    let p = &result.packet;
+    let mut out = vec![format!(
+        "simulate platform={} interface={} {} {} -> {}",
+        result.platform,
+        result.interface,
+        p.protocol,
+        endpoint(p.src, p.src_port),
+        endpoint(p.dst, p.dst_port)
+    )];
+    if !result.interface_groups.is_empty() {
+        out.push(format!("groups: {}", result.interface_groups.join(", ")));
+    }
+    out.push(format!(
+        "inbound_nat: {}",
+        nat_line(result.inbound_nat.as_ref())
+    ));
+    for hit in &result.overridden {
+        out.push(format!("overridden: {}", rule_line(hit)));
+    }
+    out.push(format!("rule: {}", rule_line(&result.rule)));
+    if let Some(egress) = &result.egress_interface {
+        out.push(format!("egress: {egress}"));
+    }
+    if result.verdict == Verdict::Pass {
+        out.push(format!(
+            "outbound_nat: {}",
+            nat_line(result.outbound_nat.as_ref())
+        ));
+    }
+    out.push(format!("result: {}", result.verdict.label()));
+    for note in &result.notes {
+        out.push(format!("note: {note}"));
+    }
+    out.join("\n")
+}
+
+fn rule_line(hit: &RuleHit) -> String {
+    let mut line = hit.stage.clone();
+    if let Some(index) = hit.index {
+        line.push_str(&format!(" #{index}"));
+    }
+    if let Some(tracker) = &hit.tracker {
+        line.push_str(&format!(" (tracker {tracker})"));
+    }
+    if !hit.descr.is_empty() {
+        line.push_str(&format!(" \"{}\"", hit.descr));
+    }
+    line.push_str(&format!(" action={}", hit.verdict.label()));
+    if let Some(gateway) = &hit.gateway {
+        line.push_str(&format!(" gateway={gateway}"));
+    }
+    line
+}
+
+fn nat_line(hit: Option<&NatHit>) -> String {
+    let Some(hit) = hit else {
+        return "none".to_string();
+    };
+    let mut line = hit.kind.clone();
+    if let Some(index) = hit.index {
+        line.push_str(&format!(" #{index}"));
+    }
+    if !hit.descr.is_empty() {
+        line.push_str(&format!(" \"{}\"", hit.descr));
+    }
+    if let Some(translated) = &hit.translated {
+        line.push_str(&format!(" -> {translated}"));
+    }
+    line
+}
+
+fn endpoint(ip: IpAddr, port: Option<u16>) -> String {
+    match (ip, port) {
+        (IpAddr::V4(ip), Some(port)) => format!("{ip}:{port}"),
+        (IpAddr::V6(ip), Some(port)) => format!("[{ip}]:{port}"),
+        (ip, None) => ip.to_string(),
+    }
+}
+
+/// Address/port spec outcome: `Err` means it cannot be evaluated offline.
+type Eval = Result<bool, String>;
+
+#[derive(Debug, Clone, Copy)]
+struct Net {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Net {
+    fn contains(self, ip: IpAddr) -> bool {
+        same_network(self.addr, ip, self.prefix)
+    }
+}
+
+/// Alias type and entries, from legacy `<aliases>` or the OPNsense MVC model.
+pub(crate) struct Alias {
+    pub(crate) kind: String,
+    pub(crate) entries: Vec<String>,
+}
+
+/// Load every alias by name; MVC aliases win over legacy ones.
+pub(crate) fn load_aliases(root: &XmlNode) -> BTreeMap<String, Alias> {
+    let mut aliases = BTreeMap::new();
+    if let Some(legacy) = root.get_child("aliases") {
+        for alias in legacy.get_children("alias") {
+            if let Some(name) = non_empty_text(alias, "name") {
+                aliases.insert(
+                    name.to_string(),
+                    Alias {
+                        kind: non_empty_text(alias, "type").unwrap_or("").to_string(),
+                        entries: split_entries(non_empty_text(alias, "address")),
+                    },
+                );
+            }
+        }
+    }
+    let mvc = ["OPNsense", "Firewall", "Alias", "aliases"]
+        .iter()
+        .try_fold(root, |node, tag| node.get_child(tag));
+    if let Some(mvc) = mvc {
+        for alias in mvc.get_children("alias") {
+            if let Some(name) = non_empty_text(alias, "name") {
+                aliases.insert(
+                    name.to_string(),
+                    Alias {
+                        kind: non_empty_text(alias, "type").unwrap_or("").to_string(),
+                        // Converted aliases may still carry pfSense's `<address>`.
+                        entries: split_entries(
+                            non_empty_text(alias, "content")
+                                .or_else(|| non_empty_text(alias, "address")),
+                        ),
+                    },
+                );
+            }
+        }
+    }
+    aliases
+}
+
+/// Interface, group, and alias lookups over one config.
+struct Context<'a> {
+    platform: &'static str,
+    interfaces: Option<&'a XmlNode>,
+    nat: Option<&'a XmlNode>,
+    filter: Option<&'a XmlNode>,
+    groups: Vec<(String, Vec<String>)>,
+    aliases: BTreeMap<String, Alias>,
+}
+
+impl<'a> Context<'a> {
+    fn new(root: &'a XmlNode, platform: &'static str) -> Self {
+        let groups = root
+            .get_child("ifgroups")
+            .map(|g| {
+                g.get_children("ifgroupentry")
+                    .into_iter()
+                    .filter_map(|entry| {
+                        let name = non_empty_text(entry, "ifname")?;
+                        let members = non_empty_text(entry, "members")
+                            .unwrap_or("")
+                            .split_whitespace()
+                            .map(str::to_string)
+                            .collect();
+                        Some((name.to_string(), members))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let aliases = load_aliases(root);
+
+        Self {
+            platform,
+            interfaces: root.get_child("interfaces"),
+            nat: root.get_child("nat"),
+            filter: root.get_child("filter"),
+            groups,
+            aliases,
+        }
+    }
+
+    fn interface(&self, name: &str) -> Option<&'a XmlNode> {
+        self.interfaces?.get_child(name)
+    }
+
+    /// Resolve a logical name, description, or group name.
+    fn resolve_interface(&self, name: &str) -> Option<String> {
+        let lower = name.to_ascii_lowercase();
+        if let Some(interfaces) = self.interfaces {
+            if interfaces.get_child(&lower).is_some() {
+                return Some(lower);
+            }
+            if let Some(iface) = interfaces
+                .children
+                .iter()
+                .find(|i| non_empty_text(i, "descr").is_some_and(|d| d.eq_ignore_ascii_case(name)))
+            {
+                return Some(iface.tag.clone());
+            }
+        }
+        if self
+            .groups
+            .iter()
+            .any(|(g, _)| g.eq_ignore_ascii_case(name))
+            || matches!(lower.as_str(), "openvpn" | "wireguard" | "ipsec" | "enc0")
+        {
+            return Some(name.to_string());
+        }
+        None
+    }
+
+    /// Interface groups (explicit and implicit VPN groups) containing `iface`.
+    fn groups_of(&self, iface: &str) -> Vec<String> {
+        let mut out: Vec<String> = self
+            .groups
+            .iter()
+            .filter(|(_, members)| members.iter().any(|m| m.eq_ignore_ascii_case(iface)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let device = self
+            .interface(iface)
+            .and_then(|i| non_empty_text(i, "if"))
+            .unwrap_or("");
+        let implicit = if device.starts_with("ovpn") {
+            Some("openvpn")
+        } else if device.starts_with("tun_wg") || device.starts_with("wg") {
+            Some("wireguard")
+        } else {
+            None
+        };
+        if let Some(group) = implicit {
+            if !out.iter().any(|g| g.eq_ignore_ascii_case(group)) {
+                out.push(group.to_string());
+            }
+        }
+        out
+    }
+
+    fn interface_net(&self, name: &str) -> Vec<Net> {
+        let Some(iface) = self.interface(name) else {
+            return Vec::new();
+        };
+        [("ipaddr", "subnet"), ("ipaddrv6", "subnetv6")]
+            .into_iter()
+            .filter_map(|(addr, prefix)| {
+                let addr = non_empty_text(iface, addr)?.parse::<IpAddr>().ok()?;
+                let prefix = non_empty_text(iface, prefix)?.parse::<u8>().ok()?;
+                Some(Net { addr, prefix })
+            })
+            .collect()
+    }
+
+    fn interface_addrs(&self, name: &str) -> Vec<IpAddr> {
+        self.interface_net(name)
+            .into_iter()
+            .map(|n| n.addr)
+            .collect()
+    }
+
+    fn all_interface_addrs(&self) -> Vec<IpAddr> {
+        self.interfaces
+            .map(|i| {
+                i.children
+                    .iter()
+                    .flat_map(|c| self.interface_addrs(&c.tag))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether `ip` matches an address spec (`any`, CIDR, alias, network name).
+    fn address_matches(&self, spec: &str, ip: IpAddr, depth: usize) -> Eval {
+        let spec = spec.trim();
+        if spec.is_empty() || spec == "any" {
+            return Ok(true);
+        }
+        if let Some((addr, prefix)) = parse_cidr(spec) {
+            return Ok(same_network(addr, ip, prefix));
+        }
+        if let Some((start, end)) = spec.split_once('-') {
+            if let (Ok(start), Ok(end)) = (start.parse::<IpAddr>(), end.parse::<IpAddr>()) {
+                return Ok(ip_in_range(ip, start, end));
+            }
+        }
+        let lower = spec.to_ascii_lowercase();
+        if lower == "(self)" || lower == "this firewall" {
+            return Ok(self.all_interface_addrs().contains(&ip));
+        }
+        if let Some(iface) = lower.strip_suffix("ip") {
+            if self.interface(iface).is_some() {
+                return Ok(self.interface_addrs(iface).contains(&ip));
+            }
+        }
+        if self.interface(&lower).is_some() {
+            return Ok(self.interface_net(&lower).iter().any(|n| n.contains(ip)));
+        }
+        if let Some(alias) = self.aliases.get(spec) {
+            if depth > 8 {
+                return Err(format!("alias '{spec}' nests too deeply"));
+            }
+            if !matches!(
+                alias.kind.as_str(),
+                "host" | "network" | "networkgroup" | ""
+            ) {
+                return Err(format!(
+                    "alias '{spec}' is a {} alias and cannot be resolved offline",
+                    alias.kind
+                ));
+            }
+            for entry in &alias.entries {
+                if self.address_matches(entry, ip, depth + 1)? {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        Err(format!("address '{spec}' cannot be resolved offline"))
+    }
+
+    /// Whether `port` matches a port spec (number, range, alias, service).
+    fn port_matches(&self, spec: &str, port: Option<u16>, depth: usize) -> Eval {
+        let spec = spec.trim();
+        if spec.is_empty() || spec == "any" {
+            return Ok(true);
+        }
+        let Some(port) = port else {
+            return Err(format!("rule requires port '{spec}' but no port was given"));
+        };
+        if let Some(range) = parse_port_range(spec) {
+            return Ok(range.0 <= port && port <= range.1);
+        }
+        if let Some(alias) = self.aliases.get(spec) {
+            if depth > 8 {
+                return Err(format!("alias '{spec}' nests too deeply"));
+            }
+            for entry in &alias.entries {
+                if self.port_matches(entry, Some(port), depth + 1)? {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        Err(format!("port '{spec}' cannot be resolved offline"))
+    }
+
+    /// First host address an address spec names, for NAT targets.
+    fn first_host(&self, spec: &str) -> Option<IpAddr> {
+        let spec = spec.trim();
+        if let Some((addr, _)) = parse_cidr(spec) {
+            return Some(addr);
+        }
+        if let Some(iface) = spec.to_ascii_lowercase().strip_suffix("ip") {
+            return self.interface_addrs(iface).first().copied();
+        }
+        self.aliases
+            .get(spec)
+            .and_then(|a| a.entries.iter().find_map(|e| self.first_host(e)))
+    }
+}
+
+/// Per-packet evaluation state.
+struct Simulation<'c, 'a> {
+    ctx: &'c Context<'a>,
+    interface: String,
+    groups: Vec<String>,
+    protocol: String,
+    src: IpAddr,
+    src_port: Option<u16>,
+    dst: IpAddr,
+    dst_port: Option<u16>,
+    notes: Vec<String>,
+}
+
+impl Simulation<'_, '_> {
+    fn uses_ports(&self) -> bool {
+        matches!(self.protocol.as_str(), "tcp" | "udp")
+    }
+
+    fn on_interface(&self, spec: &str) -> bool {
+        spec.split(',').map(str::trim).any(|name| {
+            name.eq_ignore_ascii_case(&self.interface)
+                || self.groups.iter().any(|g| g.eq_ignore_ascii_case(name))
+        })
+    }
+
+    fn protocol_matches(&self, rule: &XmlNode) -> bool {
+        let proto = text(rule, "protocol").to_ascii_lowercase();
+        proto.is_empty() || proto == "any" || proto.split('/').any(|p| p == self.protocol)
+    }
+
+    fn family_matches(&self, rule: &XmlNode) -> bool {
+        match text(rule, "ipprotocol").as_str() {
+            "inet6" => self.src.is_ipv6(),
+            "inet46" => true,
+            _ => self.src.is_ipv4(),
+        }
+    }
+
+    /// Evaluate one side of a rule (`source`/`destination`).
+    fn side_matches(&self, rule: &XmlNode, side: &str, ip: IpAddr, port: Option<u16>) -> Eval {
+        let Some(node) = rule.get_child(side) else {
+            return Ok(true);
+        };
+        let address = if node.get_child("any").is_some() {
+            Ok(true)
+        } else if let Some(spec) = non_empty_text(node, "address") {
+            self.ctx.address_matches(spec, ip, 0)
+        } else if let Some(spec) = non_empty_text(node, "network") {
+            self.ctx.address_matches(spec, ip, 0)
+        } else {
+            Ok(true)
+        }?;
+        let address = address != node.get_child("not").is_some();
+        if !address {
+            return Ok(false);
+        }
+        if !self.uses_ports() {
+            return Ok(true);
+        }
+        match non_empty_text(node, "port") {
+            Some(spec) => self.ctx.port_matches(spec, port, 0),
+            None => Ok(true),
+        }
+    }
+
+    /// Whether a filter or NAT rule matches the current packet.
+    fn rule_matches(&mut self, rule: &XmlNode, label: &str) -> bool {
+        if is_disabled(rule, self.ctx.platform)
+            || !self.family_matches(rule)
+            || !self.protocol_matches(rule)
+        {
+            return false;
+        }
+        let result = self
+            .side_matches(rule, "source", self.src, self.src_port)
+            .and_then(|m| {
+                if m {
+                    self.side_matches(rule, "destination", self.dst, self.dst_port)
+                } else {
+                    Ok(false)
+                }
+            });
+        match result {
+            Ok(matched) => {
+                if matched && non_empty_text(rule, "sched").is_some() {
+                    self.notes
+                        .push(format!("{label} has a schedule; assumed active"));
+                }
+                matched
+            }
+            Err(reason) => {
+                self.notes.push(format!("{label} skipped: {reason}"));
+                false
+            }
+        }
+    }
+
+    /// Apply 1:1 NAT and port forwards; returns the translation and, for a
+    /// "pass" association, the deciding hit.
+    fn inbound_nat(&mut self) -> (Option<NatHit>, Option<RuleHit>) {
+        let Some(nat) = self.ctx.nat else {
+            return (None, None);
+        };
+        for (i, rule) in nat.get_children("onetoone").into_iter().enumerate() {
+            if is_disabled(rule, self.ctx.platform) || !self.on_interface(&text(rule, "interface"))
+            {
+                continue;
+            }
+            let Some(external) = non_empty_text(rule, "external") else {
+                continue;
+            };
+            let internal = rule
+                .get_child("source")
+                .and_then(|s| non_empty_text(s, "address"))
+                .and_then(|a| self.ctx.first_host(a));
+            if external.parse::<IpAddr>().ok() == Some(self.dst) {
+                if let Some(internal) = internal {
+                    self.dst = internal;
+                    return (
+                        Some(NatHit {
+                            kind: "one_to_one".into(),
+                            index: Some(i + 1),
+                            descr: text(rule, "descr"),
+                            translated: Some(endpoint(self.dst, self.dst_port)),
+                        }),
+                        None,
+                    );
+                }
+            }
+        }
+
+        for (i, rule) in nat.get_children("rule").into_iter().enumerate() {
+            if !self.on_interface(&text(rule, "interface")) {
+                continue;
+            }
+            let label = format!("port forward #{}", i + 1);
+            if !self.rule_matches(rule, &label) {
+                continue;
+            }
+            let descr = text(rule, "descr");
+            if rule.get_child("nordr").is_some() {
+                let hit = NatHit {
+                    kind: "no_nat".into(),
+                    index: Some(i + 1),
+                    descr,
+                    translated: None,
+                };
+                return (Some(hit), None);
+            }
+            let Some(target) = self.ctx.first_host(&text(rule, "target")) else {
+                self.notes.push(format!(
+                    "{label} matched but its target cannot be resolved offline"
+                ));
+                continue;
+            };
+            let original_port = self.dst_port;
+            self.dst = target;
+            if let Some(local) = parse_port_range(&text(rule, "local-port")) {
+                let first = rule
+                    .get_child("destination")
+                    .and_then(|d| non_empty_text(d, "port"))
+                    .and_then(parse_port_range)
+                    .map_or(local.0, |r| r.0);
+                self.dst_port = original_port
+                    .map(|p| local.0.saturating_add(p.saturating_sub(first)))
+                    .or(Some(local.0));
+            }
+            let hit = NatHit {
+                kind: "port_forward".into(),
+                index: Some(i + 1),
+                descr: descr.clone(),
+                translated: Some(endpoint(self.dst, self.dst_port)),
+            };
+            let pass = (text(rule, "associated-rule-id") == "pass").then(|| RuleHit {
+                stage: "nat_pass".into(),
+                index: Some(i + 1),
+                tracker: None,
+                descr,
+                verdict: Verdict::Pass,
+                gateway: None,
+            });
+            return (Some(hit), pass);
+        }
+        (None, None)
+    }
+
+    /// Walk filter rules; returns the deciding hit and overridden matches.
+    fn filter(&mut self) -> (RuleHit, Vec<RuleHit>) {
+        if let Some(hit) = self.block_private() {
+            return (hit, Vec::new());
+        }
+        let rules: Vec<(usize, &XmlNode)> = self
+            .ctx
+            .filter
+            .map(|f| f.get_children("rule").into_iter().enumerate().collect())
+            .unwrap_or_default();
+
+        let mut pending: Option<RuleHit> = None;
+        let mut overridden = Vec::new();
+        let floating = rules
+            .iter()
+            .filter(|(_, r)| r.get_child("floating").is_some());
+        for (i, rule) in floating {
+            let iface = text(rule, "interface");
+            if !(iface.is_empty() || iface == "any" || self.on_interface(&iface)) {
+                continue;
+            }
+            if !matches!(text(rule, "direction").as_str(), "" | "any" | "in") {
+                continue;
+            }
+            let Some(verdict) = Verdict::from_type(&text(rule, "type")) else {
+                continue;
+            };
+            if !self.rule_matches(rule, &format!("floating rule #{}", i + 1)) {
+                continue;
+            }
+            let hit = rule_hit("floating", *i, rule, verdict);
+            if is_quick(rule, self.ctx.platform) {
+                overridden.extend(pending.take());
+                return (hit, overridden);
+            }
+            overridden.extend(pending.replace(hit));
+        }
+
+        let bound: Vec<_> = rules
+            .iter()
+            .filter(|(_, r)| r.get_child("floating").is_none())
+            .collect();
+        // Group rules are evaluated before the interface's own rules.
+        for stage in ["group", "interface"] {
+            for (i, rule) in &bound {
+                let iface = text(rule, "interface");
+                let applies = if stage == "group" {
+                    self.groups.iter().any(|g| g.eq_ignore_ascii_case(&iface))
+                } else {
+                    iface.eq_ignore_ascii_case(&self.interface)
+                };
+                if !applies {
+                    continue;
+                }
+                let Some(verdict) = Verdict::from_type(&text(rule, "type")) else {
+                    continue;
+                };
+                if self.rule_matches(rule, &format!("{stage} rule #{}", i + 1)) {
+                    overridden.extend(pending.take());
+                    return (rule_hit(stage, *i, rule, verdict), overridden);
+                }
+            }
+        }
+
+        let hit = pending.unwrap_or_else(|| RuleHit {
+            stage: "default".into(),
+            index: None,
+            tracker: None,
+            descr: "Default deny rule".into(),
+            verdict: Verdict::Block,
+            gateway: None,
+        });
+        (hit, overridden)
+    }
+
+    /// "Block private networks" / "Block bogon networks" on the interface.
+    fn block_private(&mut self) -> Option<RuleHit> {
+        let iface = self.ctx.interface(&self.interface)?;
+        if flag_set_on(iface, "blockbogons", self.ctx.platform) {
+            self.notes.push(format!(
+                "{} blocks bogon networks; bogon table not simulated",
+                self.interface
+            ));
+        }
+        if !flag_set_on(iface, "blockpriv", self.ctx.platform) || !is_private(self.src) {
+            return None;
+        }
+        Some(RuleHit {
+            stage: "block_private".into(),
+            index: None,
+            tracker: None,
+            descr: "Block private networks".into(),
+            verdict: Verdict::Block,
+            gateway: None,
+        })
+    }
+
+    /// Pick the egress interface and apply outbound NAT.
+    fn outbound_nat(&mut self) -> (Option<String>, Option<NatHit>) {
+        let egress = self
+            .ctx
+            .interfaces
+            .and_then(|interfaces| {
+                interfaces
+                    .children
+                    .iter()
+                    .find(|i| {
+                        self.ctx
+                            .interface_net(&i.tag)
+                            .iter()
+                            .any(|n| n.contains(self.dst))
+                    })
+                    .map(|i| i.tag.clone())
+            })
+            .or_else(|| self.ctx.interface("wan").map(|_| "wan".to_string()));
+        let Some(egress) = egress else {
+            return (None, None);
+        };
+        if egress == self.interface {
+            return (Some(egress), None);
+        }
+
+        let outbound = self.ctx.nat.and_then(|n| n.get_child("outbound"));
+        let mode = outbound
+            .and_then(|o| non_empty_text(o, "mode"))
+            .unwrap_or("automatic")
+            .to_string();
+        if mode == "disabled" {
+            return (Some(egress), None);
+        }
+        if matches!(mode.as_str(), "hybrid" | "advanced") {
+            let rules = outbound.map(|o| o.get_children("rule")).unwrap_or_default();
+            for (i, rule) in rules.into_iter().enumerate() {
+                if is_disabled(rule, self.ctx.platform)
+                    || !text(rule, "interface").eq_ignore_ascii_case(&egress)
+                {
+                    continue;
+                }
+                if !self.rule_matches(rule, &format!("outbound NAT rule #{}", i + 1)) {
+                    continue;
+                }
+                let descr = text(rule, "descr");
+                if rule.get_child("nonat").is_some() {
+                    let hit = NatHit {
+                        kind: "no_nat".into(),
+                        index: Some(i + 1),
+                        descr,
+                        translated: None,
+                    };
+                    return (Some(egress), Some(hit));
+                }
+                let target = text(rule, "target");
+                let translated = if target.is_empty() {
+                    self.ctx.interface_addrs(&egress).first().copied()
+                } else {
+                    self.ctx.first_host(&target)
+                };
+                let hit = NatHit {
+                    kind: "outbound".into(),
+                    index: Some(i + 1),
+                    descr,
+                    translated: Some(
+                        translated.map_or_else(|| format!("{egress} address"), |ip| ip.to_string()),
+                    ),
+                };
+                return (Some(egress), Some(hit));
+            }
+        }
+        if mode == "advanced" {
+            return (Some(egress), None);
+        }
+
+        // Automatic rules translate traffic from local subnets leaving an
+        // interface that has an upstream gateway.
+        let upstream = egress == "wan"
+            || self
+                .ctx
+                .interface(&egress)
+                .is_some_and(|i| non_empty_text(i, "gateway").is_some());
+        let local_source = self.ctx.interfaces.is_some_and(|interfaces| {
+            interfaces.children.iter().any(|i| {
+                i.tag != egress
+                    && self
+                        .ctx
+                        .interface_net(&i.tag)
+                        .iter()
+                        .any(|n| n.contains(self.src))
+            })
+        });
+        if !upstream || !local_source {
+            return (Some(egress), None);
+        }
+        let translated = self
+            .ctx
+            .interface_addrs(&egress)
+            .first()
+            .map_or_else(|| format!("{egress} address"), IpAddr::to_string);
+        let hit = NatHit {
+            kind: "automatic_outbound".into(),
+            index: None,
+            descr: String::new(),
+            translated: Some(translated),
+        };
+        (Some(egress), Some(hit))
+    }
+}
+
+fn rule_hit(stage: &str, index: usize, rule: &XmlNode, verdict: Verdict) -> RuleHit {
+    RuleHit {
+        stage: stage.to_string(),
+        index: Some(index + 1),
+        tracker: non_empty_text(rule, "tracker").map(str::to_string),
+        descr: text(rule, "descr"),
+        verdict,
+        gateway: non_empty_text(rule, "gateway").map(str::to_string),
+    }
+}
+
+fn is_disabled(rule: &XmlNode, platform: &str) -> bool {
+    flag_set_on(rule, "disabled", platform)
+}
+
+/// Non-floating rules are always quick; floating ones only when flagged.
+fn is_quick(rule: &XmlNode, platform: &str) -> bool {
+    flag_set_on(rule, "quick", platform)
+}
+
+fn is_private(ip: IpAddr) -> bool {
+    const PRIVATE: &[&str] = &[
+        "10.0.0.0/8",
+        "100.64.0.0/10",
+        "127.0.0.0/8",
+        "172.16.0.0/12",
+        "192.168.0.0/16",
+        "fc00::/7",
+    ];
+    PRIVATE
+        .iter()
+        .filter_map(|net| parse_cidr(net))
+        .any(|(addr, prefix)| same_network(addr, ip, prefix))
+}
+
+fn split_entries(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or("")
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse `80`, `1000-2000`, `1000:2000`, or a well-known service name.
+pub(crate) fn parse_port_range(raw: &str) -> Option<(u16, u16)> {
+    let raw = raw.trim();
+    if let Some((a, b)) = raw.split_once(['-', ':']) {
+        return Some((a.trim().parse().ok()?, b.trim().parse().ok()?));
+    }
+    if let Ok(port) = raw.parse() {
+        return Some((port, port));
+    }
+    let port = match raw.to_ascii_lowercase().as_str() {
+        "ftp" => 21,
+        "ssh" => 22,
+        "telnet" => 23,
+        "smtp" => 25,
+        "domain" | "dns" => 53,
+        "http" | "www" => 80,
+        "pop3" => 110,
+        "ntp" => 123,
+        "imap" => 143,
+        "snmp" => 161,
+        "https" => 443,
+        "submission" => 587,
+        "imaps" => 993,
+        "openvpn" => 1194,
+        "rdp" | "ms-wbt-server" => 3389,
+        _ => return None,
+    };
+    Some((port, port))
+}
+
+/// Parse `addr/prefix`; a bare address is a host.
+fn parse_cidr(raw: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match raw.split_once('/') {
+        Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, prefix.parse::<u8>().ok()?),
+        None => {
+            let addr = raw.parse::<IpAddr>().ok()?;
+            (addr, if addr.is_ipv4() { 32 } else { 128 })
+        }
+    };
+    Some((addr, prefix))
+}
+
+fn ip_in_range(ip: IpAddr, start: IpAddr, end: IpAddr) -> bool {
+    match (ip, start, end) {
+        (IpAddr::V4(ip), IpAddr::V4(s), IpAddr::V4(e)) => s <= ip && ip <= e,
+        (IpAddr::V6(ip), IpAddr::V6(s), IpAddr::V6(e)) => s <= ip && ip <= e,
+        _ => false,
+    }
+}
+
+fn text(node: &XmlNode, tag: &str) -> String {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .unwrap_or("")
+        .to_string()
+}
+
+fn non_empty_text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{simulate, Packet, Verdict};
+
+    const CONFIG: &[u8] = br#"<pfsense>
+        <interfaces>
+            <wan><if>igb0</if><ipaddr>198.51.100.2</ipaddr><subnet>24</subnet><blockpriv/></wan>
+            <lan><if>igb1</if><descr>LAN</descr><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan>
+        </interfaces>
+        <aliases><alias><name>web_ports</name><type>port</type><address>80 443</address></alias></aliases>
+        <nat>
+            <outbound><mode>automatic</mode></outbound>
+            <rule><interface>wan</interface><protocol>tcp</protocol><source><any/></source>
+                <destination><network>wanip</network><port>443</port></destination>
+                <target>192.168.1.10</target><local-port>8443</local-port><descr>web</descr>
+                <associated-rule-id>nat_1</associated-rule-id></rule>
+        </nat>
+        <filter>
+            <rule><type>block</type><floating>yes</floating><interface>lan</interface>
+                <source><any/></source><destination><address>203.0.113.9</address></destination>
+                <descr>no quick</descr></rule>
+            <rule><type>pass</type><interface>wan</interface><tracker>100</tracker><protocol>tcp</protocol>
+                <source><any/></source><destination><address>192.168.1.10</address><port>8443</port></destination>
+                <descr>NAT web</descr></rule>
+            <rule><type>pass</type><interface>lan</interface><tracker>200</tracker><protocol>tcp</protocol>
+                <source><network>lan</network></source><destination><any/><port>web_ports</port></destination>
+                <descr>LAN web</descr></rule>
+        </filter>
+    </pfsense>"#;
+
+    fn packet(interface: &str, src: &str, dst: &str, dst_port: u16) -> Packet {
+        Packet {
+            interface: interface.into(),
+            protocol: "tcp".into(),
+            src: src.parse().expect("ip"),
+            src_port: Some(40000),
+            dst: dst.parse().expect("ip"),
+            dst_port: Some(dst_port),
+        }
+    }
+
+    #[test]
+    fn port_forward_translates_before_filter_rules() {
+        let root = parse(CONFIG).expect("parse");
+        let result =
+            simulate(&root, &packet("wan", "203.0.113.5", "198.51.100.2", 443)).expect("simulate");
+        let nat = result.inbound_nat.expect("port forward");
+        assert_eq!(nat.translated.as_deref(), Some("192.168.1.10:8443"));
+        assert_eq!(result.rule.tracker.as_deref(), Some("100"));
+        assert_eq!(result.verdict, Verdict::Pass);
+        assert_eq!(result.egress_interface.as_deref(), Some("lan"));
+        assert!(result.outbound_nat.is_none());
+    }
+
+    #[test]
+    fn interface_rule_overrides_non_quick_floating_and_gets_outbound_nat() {
+        let root = parse(CONFIG).expect("parse");
+        let result =
+            simulate(&root, &packet("LAN", "192.168.1.50", "203.0.113.9", 80)).expect("simulate");
+        assert_eq!(result.interface, "lan");
+        assert_eq!(result.rule.stage, "interface");
+        assert_eq!(result.overridden.len(), 1);
+        let nat = result.outbound_nat.expect("automatic outbound");
+        assert_eq!(nat.kind, "automatic_outbound");
+        assert_eq!(nat.translated.as_deref(), Some("198.51.100.2"));
+
+        let blocked =
+            simulate(&root, &packet("lan", "192.168.1.50", "203.0.113.9", 22)).expect("simulate");
+        assert_eq!(blocked.rule.descr, "no quick");
+        assert_eq!(blocked.verdict, Verdict::Block);
+    }
+
+    #[test]
+    fn private_sources_blocked_on_wan_and_unknown_interface_rejected() {
+        let root = parse(CONFIG).expect("parse");
+        let result =
+            simulate(&root, &packet("wan", "10.0.0.5", "198.51.100.2", 443)).expect("simulate");
+        assert_eq!(result.rule.stage, "block_private");
+        assert_eq!(result.verdict, Verdict::Block);
+
+        assert!(simulate(&root, &packet("opt7", "10.0.0.5", "198.51.100.2", 443)).is_err());
+    }
+
+    #[test]
+    fn pfsense_disabled_flag_with_zero_value_skips_rule() {
+        let config = String::from_utf8_lossy(CONFIG).replace(
+            "<descr>LAN web</descr>",
+            "<descr>LAN web</descr><disabled>0</disabled>",
+        );
+        let root = parse(config.as_bytes()).expect("parse");
+        let result =
+            simulate(&root, &packet("lan", "192.168.1.50", "203.0.113.20", 80)).expect("simulate");
+        assert_ne!(result.rule.tracker.as_deref(), Some("200"));
+        assert_eq!(result.verdict, Verdict::Block);
+    }
+}
@@ -0,0 +1,26 @@
+use anyhow::{Context, Result};
+use pfopn_convert::simulate::{render_simulation_text, simulate, Packet};
+use xml_diff_core::parse_file;
+
+use crate::cli::{OutputFormat, SimulateArgs};
+
+/// Trace one packet through a config and print the verdict.
+pub fn run_simulate(args: SimulateArgs) -> Result<()> {
+    let root = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let packet = Packet {
+        interface: args.interface,
+        protocol: args.protocol.to_ascii_lowercase(),
+        src: args.src,
+        src_port: args.src_port,
+        dst: args.dst,
+        dst_port: args.dst_port,
+    };
+    let result = simulate(&root, &packet)?;
+
+    match args.format {
+        OutputFormat::Text => println!("{}", render_simulation_text(&result)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+    }
+    Ok(())
+}
@@ -0,0 +1,302 @@
+//! Site map for fleet conversions.
+//!
+//! Site-to-site tunnels name the far end by address: an IPsec phase1
+//! `remote-gateway`, a Swanctl connection's `remote_addrs`, an OpenVPN
+//! client's `server_addr`. When a fleet is converted together and a site's
+//! WAN address changes, every peer pointing at it has to change too. A site
+//! map lists each site's public addresses and hostname:
+//!
+//! ```toml
+//! [[site]]
+//! name = "hq"
+//! addresses = ["203.0.113.10"]
+//! hostname = "hq.example.net"
+//! new_address = "198.51.100.10"
+//!
+//! [[site]]
+//! name = "branch-1"
+//! addresses = ["203.0.113.20"]
+//! ```
+//!
+//! `name` matches a batch device name when the site is converted in the
+//! same batch. [`rewrite_peers`] moves peer fields that hold one of a site's
+//! `addresses` to its `new_address`; peers given by hostname follow DNS and
+//! are left alone. [`SiteMap::site_for`] resolves a peer value to its site by
+//! address, new address, or hostname, which
+//! [`crate::verify_tunnel_pairs`] uses to find both ends of a tunnel.
+//!
+//! The `[[site]]` tables can live in the batch manifest or in a separate
+//! file passed to `batch --site-map`.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+use xml_diff_core::XmlNode;
+
+use crate::transform::booleans::nodes_at_mut;
+use crate::transform::lan_ip::map_tokens;
+use crate::verify_values::nodes_at;
+
+/// Fields naming a tunnel's far end; Swanctl `remote_addrs` is a list.
+const PEER_FIELDS: &[&[&str]] = &[
+    &["ipsec", "phase1", "remote-gateway"],
+    &[
+        "OPNsense",
+        "Swanctl",
+        "Connections",
+        "Connection",
+        "remote_addrs",
+    ],
+    &["openvpn", "openvpn-client", "server_addr"],
+];
+
+/// One site in the fleet.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Site {
+    pub name: String,
+    /// Addresses peers currently use to reach the site.
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    /// DNS name peers may use instead of an address.
+    pub hostname: Option<String>,
+    /// Address the site moves to; peers using an old address are rewritten.
+    pub new_address: Option<String>,
+}
+
+/// Every known site.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SiteMap {
+    pub sites: Vec<Site>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteMapFile {
+    #[serde(default, rename = "site")]
+    sites: Vec<Site>,
+}
+
+#[derive(Debug, Error)]
+pub enum SiteMapError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("invalid site map {path}: {source}")]
+    Parse {
+        path: String,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("site '{name}' is listed more than once")]
+    DuplicateSite { name: String },
+    #[error("sites '{first}' and '{second}' both claim {value}")]
+    SharedAddress {
+        first: String,
+        second: String,
+        value: String,
+    },
+}
+
+impl SiteMap {
+    /// Read the `[[site]]` tables of a TOML file; other tables are ignored,
+    /// so a batch manifest can be loaded directly.
+    pub fn load(path: &Path) -> Result<Self, SiteMapError> {
+        let raw = fs::read_to_string(path).map_err(|source| SiteMapError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::parse(&raw, path.display().to_string())
+    }
+
+    /// Parse `[[site]]` tables from TOML text.
+    pub fn parse(raw: &str, path: String) -> Result<Self, SiteMapError> {
+        let parsed: SiteMapFile =
+            toml::from_str(raw).map_err(|source| SiteMapError::Parse { path, source })?;
+        let map = Self {
+            sites: parsed.sites,
+        };
+        map.check()?;
+        Ok(map)
+    }
+
+    /// Add the sites of `other`, rejecting names or addresses already used.
+    pub fn extend(&mut self, other: SiteMap) -> Result<(), SiteMapError> {
+        self.sites.extend(other.sites);
+        self.check()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+
+    /// The site a peer value names, by address, new address, or hostname.
+    pub fn site_for(&self, value: &str) -> Option<&Site> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        self.sites
+            .iter()
+            .find(|site| site_values(site).any(|v| v.eq_ignore_ascii_case(value)))
+    }
+
+    /// The site named `name`.
+    pub fn site_named(&self, name: &str) -> Option<&Site> {
+        self.sites.iter().find(|site| site.name == name)
+    }
+
+    fn check(&self) -> Result<(), SiteMapError> {
+        for (idx, site) in self.sites.iter().enumerate() {
+            for other in &self.sites[..idx] {
+                if other.name == site.name {
+                    return Err(SiteMapError::DuplicateSite {
+                        name: site.name.clone(),
+                    });
+                }
+                if let Some(value) = site_values(site)
+                    .find(|v| site_values(other).any(|o| o.eq_ignore_ascii_case(v)))
+                {
+                    return Err(SiteMapError::SharedAddress {
+                        first: other.name.clone(),
+                        second: site.name.clone(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn site_values(site: &Site) -> impl Iterator<Item = &str> {
+    site.addresses
+        .iter()
+        .map(String::as_str)
+        .chain(site.hostname.as_deref())
+        .chain(site.new_address.as_deref())
+}
+
+/// One peer field moved to a site's new address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerRewrite {
+    /// Field rewritten (`ipsec/phase1[2]/remote-gateway`).
+    pub path: String,
+    pub site: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// Point every peer field at its site's `new_address`.
+pub fn rewrite_peers(root: &mut XmlNode, sites: &SiteMap) -> Vec<PeerRewrite> {
+    let snapshot = root.clone();
+    let mut out = Vec::new();
+    for path in PEER_FIELDS {
+        let mut labelled = Vec::new();
+        nodes_at(&snapshot, path, String::new(), &mut labelled);
+        let nodes = nodes_at_mut(root, path);
+        for ((label, _), node) in labelled.into_iter().zip(nodes) {
+            let Some(text) = node.text.clone() else {
+                continue;
+            };
+            let rewritten = map_tokens(&text, |token| {
+                let site = sites.site_for(token)?;
+                let new = site.new_address.as_deref()?;
+                if !site.addresses.iter().any(|a| a.eq_ignore_ascii_case(token)) {
+                    return None;
+                }
+                out.push(PeerRewrite {
+                    path: label.clone(),
+                    site: site.name.clone(),
+                    old: token.to_string(),
+                    new: new.to_string(),
+                });
+                Some(new.to_string())
+            });
+            if rewritten != text {
+                node.text = Some(rewritten);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{rewrite_peers, SiteMap, SiteMapError};
+
+    const SITES: &str = r#"
+        [defaults]
+        to = "opnsense"
+
+        [[site]]
+        name = "hq"
+        addresses = ["203.0.113.10"]
+        hostname = "hq.example.net"
+        new_address = "198.51.100.10"
+
+        [[site]]
+        name = "branch-1"
+        addresses = ["203.0.113.20"]
+    "#;
+
+    #[test]
+    fn rewrites_old_addresses_and_leaves_hostnames() {
+        let sites = SiteMap::parse(SITES, "sites.toml".to_string()).expect("sites");
+        let mut root = parse(
+            b"<opnsense><ipsec><phase1><remote-gateway>203.0.113.10</remote-gateway></phase1>\
+              <phase1><remote-gateway>hq.example.net</remote-gateway></phase1>\
+              <phase1><remote-gateway>203.0.113.20</remote-gateway></phase1></ipsec>\
+              <OPNsense><Swanctl><Connections><Connection><remote_addrs>203.0.113.10,192.0.2.1</remote_addrs>\
+              </Connection></Connections></Swanctl></OPNsense></opnsense>",
+        )
+        .expect("parse");
+
+        let rewrites = rewrite_peers(&mut root, &sites);
+        let paths: Vec<&str> = rewrites.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "ipsec/phase1[1]/remote-gateway",
+                "OPNsense/Swanctl/Connections/Connection/remote_addrs"
+            ]
+        );
+        assert_eq!(
+            root.get_text(&[
+                "OPNsense",
+                "Swanctl",
+                "Connections",
+                "Connection",
+                "remote_addrs"
+            ]),
+            Some("198.51.100.10,192.0.2.1")
+        );
+        assert_eq!(
+            sites.site_for("HQ.example.net").map(|s| s.name.as_str()),
+            Some("hq")
+        );
+    }
+
+    #[test]
+    fn rejects_sites_sharing_an_address() {
+        let err = SiteMap::parse(
+            r#"
+            [[site]]
+            name = "a"
+            addresses = ["203.0.113.10"]
+            [[site]]
+            name = "b"
+            new_address = "203.0.113.10"
+            "#,
+            "sites.toml".to_string(),
+        )
+        .expect_err("shared");
+        assert!(matches!(err, SiteMapError::SharedAddress { .. }));
+    }
+}
@@ -0,0 +1,330 @@
+//! Synthetic configs for benchmarks and regression tests.
+//!
+//! [`SyntheticConfig`] builds a pfSense config of any size so slowness in
+//! diff, merge, and the transforms can be measured without a real customer
+//! export. The output is deterministic: the same sizes always produce the
+//! same tree, so timings from two runs or two machines are comparable.
+//!
+//! ## Contents
+//!
+//! - `wan`, `lan`, and `opt1` interfaces; `lan` is `10.0.0.1/16`
+//! - `rules` filter rules spread over the three interfaces, each referring
+//!   to one of the aliases (as source address, or destination port for
+//!   port aliases); every 7th rule is disabled and every 5th logs
+//! - `aliases` host, network, and port aliases in turn
+//! - `static_maps` DHCP static mappings on `lan`, outside the dynamic range
+//! - `certs` server certificates signed by one CA, with the `REDACTED`
+//!   blobs of a sanitized export so certificate checks skip them
+//!
+//! ```ignore
+//! use pfopn_convert::synthetic::SyntheticConfig;
+//!
+//! let config = SyntheticConfig {
+//!     rules: 10_000,
+//!     ..SyntheticConfig::default()
+//! }
+//! .pfsense();
+//! ```
+
+use xml_diff_core::XmlNode;
+
+/// Static mappings fit in `10.0.1.1` - `10.0.254.250`; larger counts wrap.
+const MAX_STATIC_MAPS: usize = 254 * 250;
+
+/// Object counts for a generated config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticConfig {
+    /// Filter rules.
+    pub rules: usize,
+    /// Firewall aliases.
+    pub aliases: usize,
+    /// DHCP static mappings on `lan`.
+    pub static_maps: usize,
+    /// Certificates, plus one CA when non-zero.
+    pub certs: usize,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            rules: 1_000,
+            aliases: 100,
+            static_maps: 100,
+            certs: 10,
+        }
+    }
+}
+
+impl SyntheticConfig {
+    /// Build the pfSense config.
+    pub fn pfsense(&self) -> XmlNode {
+        let mut root = XmlNode::new("pfsense");
+        root.children.push(leaf("version", "23.3"));
+        root.children.push(self.system());
+        root.children.push(interfaces());
+        root.children.push(self.filter());
+        root.children.push(self.aliases());
+        root.children.push(self.dhcpd());
+        if self.certs > 0 {
+            root.children.push(ca());
+            root.children.extend((0..self.certs).map(cert));
+        }
+        root
+    }
+
+    fn system(&self) -> XmlNode {
+        let mut webgui = node("webgui", vec![leaf("protocol", "https")]);
+        if self.certs > 0 {
+            webgui.children.push(leaf("ssl-certref", "syn_cert0"));
+        }
+        node(
+            "system",
+            vec![
+                leaf("hostname", "synthetic"),
+                leaf("domain", "example.com"),
+                leaf("timezone", "Etc/UTC"),
+                webgui,
+            ],
+        )
+    }
+
+    fn filter(&self) -> XmlNode {
+        node("filter", (0..self.rules).map(|i| self.rule(i)).collect())
+    }
+
+    fn rule(&self, i: usize) -> XmlNode {
+        let interface = ["lan", "wan", "opt1"][i % 3];
+        let protocol = ["tcp", "udp"][i % 2];
+        let mut source = node("source", Vec::new());
+        let mut destination = node("destination", vec![empty("any")]);
+        let alias = (self.aliases > 0).then(|| i % self.aliases);
+        match alias {
+            Some(a) if alias_type(a) == "port" => {
+                source.children.push(empty("any"));
+                destination.children.push(leaf("port", &alias_name(a)));
+            }
+            Some(a) => {
+                source.children.push(leaf("address", &alias_name(a)));
+                destination
+                    .children
+                    .push(leaf("port", &(1024 + i % 60_000).to_string()));
+            }
+            None => {
+                source.children.push(leaf("network", interface));
+                destination
+                    .children
+                    .push(leaf("port", &(1024 + i % 60_000).to_string()));
+            }
+        }
+
+        let mut rule = node(
+            "rule",
+            vec![
+                leaf("tracker", &(1_000_000_000 + i).to_string()),
+                leaf("type", "pass"),
+                leaf("interface", interface),
+                leaf("ipprotocol", "inet"),
+                leaf("protocol", protocol),
+                source,
+                destination,
+                leaf("descr", &format!("synthetic rule {i}")),
+            ],
+        );
+        if i % 7 == 6 {
+            rule.children.push(empty("disabled"));
+        }
+        if i % 5 == 4 {
+            rule.children.push(empty("log"));
+        }
+        rule
+    }
+
+    fn aliases(&self) -> XmlNode {
+        let aliases = (0..self.aliases)
+            .map(|i| {
+                let address = match alias_type(i) {
+                    "host" => format!("172.16.{}.{}", (i / 250) % 256, i % 250 + 1),
+                    "network" => format!("172.17.{}.0/24", i % 256),
+                    _ => format!("{}:{}", 10_000 + i % 50_000, 10_010 + i % 50_000),
+                };
+                node(
+                    "alias",
+                    vec![
+                        leaf("name", &alias_name(i)),
+                        leaf("type", alias_type(i)),
+                        leaf("address", &address),
+                        leaf("descr", &format!("synthetic alias {i}")),
+                        leaf("detail", "Entry added"),
+                    ],
+                )
+            })
+            .collect();
+        node("aliases", aliases)
+    }
+
+    fn dhcpd(&self) -> XmlNode {
+        let mut lan = node(
+            "lan",
+            vec![
+                empty("enable"),
+                node(
+                    "range",
+                    vec![leaf("from", "10.0.255.1"), leaf("to", "10.0.255.250")],
+                ),
+            ],
+        );
+        lan.children
+            .extend((0..self.static_maps).map(|i| static_map(i % MAX_STATIC_MAPS)));
+        node("dhcpd", vec![lan])
+    }
+}
+
+fn interfaces() -> XmlNode {
+    let wan = node(
+        "wan",
+        vec![
+            empty("enable"),
+            leaf("if", "em0"),
+            leaf("descr", "WAN"),
+            leaf("ipaddr", "dhcp"),
+        ],
+    );
+    let lan = node(
+        "lan",
+        vec![
+            empty("enable"),
+            leaf("if", "em1"),
+            leaf("descr", "LAN"),
+            leaf("ipaddr", "10.0.0.1"),
+            leaf("subnet", "16"),
+        ],
+    );
+    let opt1 = node(
+        "opt1",
+        vec![
+            empty("enable"),
+            leaf("if", "em2"),
+            leaf("descr", "DMZ"),
+            leaf("ipaddr", "192.168.50.1"),
+            leaf("subnet", "24"),
+        ],
+    );
+    node("interfaces", vec![wan, lan, opt1])
+}
+
+fn alias_name(i: usize) -> String {
+    format!("syn_alias_{i}")
+}
+
+fn alias_type(i: usize) -> &'static str {
+    ["host", "network", "port"][i % 3]
+}
+
+fn static_map(i: usize) -> XmlNode {
+    node(
+        "staticmap",
+        vec![
+            leaf(
+                "mac",
+                &format!("02:00:00:00:{:02x}:{:02x}", (i >> 8) & 0xff, i & 0xff),
+            ),
+            leaf("ipaddr", &format!("10.0.{}.{}", 1 + i / 250, 1 + i % 250)),
+            leaf("hostname", &format!("host{i}")),
+            leaf("descr", &format!("synthetic static map {i}")),
+        ],
+    )
+}
+
+fn ca() -> XmlNode {
+    node(
+        "ca",
+        vec![
+            leaf("refid", "syn_ca"),
+            leaf("descr", "Synthetic CA"),
+            leaf("crt", "REDACTED"),
+            leaf("prv", "REDACTED"),
+            leaf("serial", "1"),
+        ],
+    )
+}
+
+fn cert(i: usize) -> XmlNode {
+    node(
+        "cert",
+        vec![
+            leaf("refid", &format!("syn_cert{i}")),
+            leaf("descr", &format!("synthetic cert {i}")),
+            leaf("type", "server"),
+            leaf("caref", "syn_ca"),
+            leaf("crt", "REDACTED"),
+            leaf("prv", "REDACTED"),
+        ],
+    )
+}
+
+fn node(tag: &str, children: Vec<XmlNode>) -> XmlNode {
+    let mut node = XmlNode::new(tag);
+    node.children = children;
+    node
+}
+
+fn leaf(tag: &str, text: &str) -> XmlNode {
+    let mut node = XmlNode::new(tag);
+    node.text = Some(text.to_string());
+    node
+}
+
+fn empty(tag: &str) -> XmlNode {
+    XmlNode::new(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SyntheticConfig;
+    use crate::verify::{build_verify_report, VerifySeverity};
+
+    #[test]
+    fn generates_requested_counts() {
+        let config = SyntheticConfig {
+            rules: 50,
+            aliases: 9,
+            static_maps: 30,
+            certs: 3,
+        }
+        .pfsense();
+
+        let count = |path: &[&str], tag: &str| {
+            path.iter()
+                .try_fold(&config, |node, segment| node.get_child(segment))
+                .map_or(0, |node| node.get_children(tag).len())
+        };
+        assert_eq!(count(&["filter"], "rule"), 50);
+        assert_eq!(count(&["aliases"], "alias"), 9);
+        assert_eq!(count(&["dhcpd", "lan"], "staticmap"), 30);
+        assert_eq!(count(&[], "cert"), 3);
+        assert_eq!(count(&[], "ca"), 1);
+        assert_eq!(
+            config,
+            SyntheticConfig {
+                rules: 50,
+                aliases: 9,
+                static_maps: 30,
+                certs: 3
+            }
+            .pfsense()
+        );
+    }
+
+    #[test]
+    fn generated_config_verifies_without_errors() {
+        let config = SyntheticConfig::default().pfsense();
+        let report = build_verify_report(&config, None);
+        let errors: Vec<&str> = report
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == VerifySeverity::Error)
+            .map(|issue| issue.message.as_str())
+            .collect();
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+}
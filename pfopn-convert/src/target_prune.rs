@@ -1,27 +1,283 @@
+//! Removal of top-level sections the target platform cannot hold.
+//!
+//! After merging, the output can carry source sections with no place on the
+//! target (`<installedpackages>` on OPNsense, `<OPNsense>` on pfSense).
+//! [`prune_imported_incompatible_sections`] removes every top-level section
+//! that is neither in the target baseline nor on the platform's allowed
+//! list, and logs each pruned node with why it went and what to do about it:
+//!
+//! - **bookkeeping** — metadata the target keeps its own copy of
+//! - **carried** — content a transform already translated (aliases, the
+//!   DHCP backend marker, translated packages and OPNsense models)
+//! - **cosmetic** — dashboard widget layout and GUI theme, with nothing
+//!   functional in them (the web GUI transform maps themes that have an
+//!   equivalent)
+//! - **dropped** — content nothing translated, which must be recreated by
+//!   hand
+//!
+//! `<installedpackages>` and `<OPNsense>` are logged per child, so every
+//! package config and model is accounted for. With `keep_unknown`, dropped
+//! content is moved under [`PRESERVED_CONTAINER`] instead of being lost.
+
 use std::collections::BTreeSet;
 
+use serde::Serialize;
 use xml_diff_core::XmlNode;
 
+use crate::transform::small_packages;
+
+/// Top-level container that `keep_unknown` moves dropped content into.
+pub const PRESERVED_CONTAINER: &str = "pfopn_preserved";
+
+/// Top-level bookkeeping sections that never count as untranslated
+/// (`ovpnserver` is OpenVPN wizard state).
+pub(crate) const BOOKKEEPING_SECTIONS: &[&str] =
+    &["version", "revision", "lastchange", "ovpnserver"];
+
+/// Sections pruned from the output after a transform already carried their
+/// content over (aliases move into the OPNsense model, `dhcpbackend` drives
+/// the backend choice).
+pub(crate) const CARRIED_SECTIONS: &[&str] = &["aliases", "dhcpbackend"];
+
+/// Top-level sections holding only dashboard layout (pfSense `<widgets>`) or
+/// the GUI theme (OPNsense `<theme>`).
+pub(crate) const COSMETIC_SECTIONS: &[&str] = &["widgets", "theme"];
+
+/// `<installedpackages>` children that are package-manager metadata or are
+/// translated by a transform (small packages report their own manual steps,
+/// see [`small_packages`]).
+pub(crate) const CARRIED_PACKAGES: &[&str] = &[
+    "acme",
+    "menu",
+    "package",
+    "service",
+    "tailscale",
+    "tailscaleauth",
+    "telegraf",
+    "wireguard",
+    "zabbixagent",
+    "zabbixagentlts",
+];
+
+/// `<OPNsense>` models that transforms translate into pfSense sections.
+pub(crate) const CARRIED_MODELS: &[&str] = &[
+    "DHCRelay",
+    "Firewall",
+    "IPsec",
+    "Kea",
+    "OpenVPN",
+    "Swanctl",
+    "radvd",
+    "tailscale",
+    "wireguard",
+    "wol",
+];
+
+/// Why a node was pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneKind {
+    Bookkeeping,
+    Carried,
+    Cosmetic,
+    Dropped,
+}
+
+/// One pruned node with the reason and the suggested manual action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrunedNode {
+    /// Dotted path of the node in the merged output (`installedpackages.snort`).
+    pub path: String,
+    pub kind: PruneKind,
+    pub reason: String,
+    pub action: String,
+    /// Whether the node was kept under [`PRESERVED_CONTAINER`].
+    pub preserved: bool,
+}
+
+/// Remove top-level sections `target_platform` cannot hold from `out`.
+///
+/// Returns the pruned section tags, sorted, and the prune log. With
+/// `keep_unknown`, dropped nodes are moved under [`PRESERVED_CONTAINER`]
+/// (keeping their parent section for package configs and models) instead of
+/// being removed.
 pub fn prune_imported_incompatible_sections(
     out: &mut XmlNode,
     target_platform: &str,
     target_baseline: &XmlNode,
-) -> Vec<String> {
+    keep_unknown: bool,
+) -> (Vec<String>, Vec<PrunedNode>) {
     let baseline = collect_top_level_tags(target_baseline);
     let allowed = allowed_sections(target_platform);
 
     let mut removed = Vec::new();
+    let mut pruned_nodes = Vec::new();
     out.children.retain(|child| {
-        let keep = baseline.contains(&child.tag) || allowed.contains(child.tag.as_str());
+        let keep = child.tag == PRESERVED_CONTAINER
+            || baseline.contains(&child.tag)
+            || allowed.contains(child.tag.as_str());
         if !keep {
             removed.push(child.tag.clone());
+            pruned_nodes.push(child.clone());
         }
         keep
     });
 
+    let mut log = Vec::new();
+    let mut preserved = Vec::new();
+    for node in pruned_nodes {
+        let kept = log_section(&node, target_platform, keep_unknown, &mut log);
+        preserved.extend(kept);
+    }
+    if !preserved.is_empty() {
+        let index = match out
+            .children
+            .iter()
+            .position(|c| c.tag == PRESERVED_CONTAINER)
+        {
+            Some(index) => index,
+            None => {
+                out.children.push(XmlNode::new(PRESERVED_CONTAINER));
+                out.children.len() - 1
+            }
+        };
+        out.children[index].children.extend(preserved);
+    }
+
     removed.sort();
     removed.dedup();
-    removed
+    (removed, log)
+}
+
+/// Log a pruned top-level section, returning what to preserve.
+fn log_section(
+    node: &XmlNode,
+    platform: &str,
+    keep_unknown: bool,
+    log: &mut Vec<PrunedNode>,
+) -> Option<XmlNode> {
+    let tag = node.tag.as_str();
+    if BOOKKEEPING_SECTIONS.contains(&tag) {
+        log.push(PrunedNode {
+            path: tag.to_string(),
+            kind: PruneKind::Bookkeeping,
+            reason: format!("{platform} keeps its own {tag}"),
+            action: "none".to_string(),
+            preserved: false,
+        });
+        return None;
+    }
+    if CARRIED_SECTIONS.contains(&tag) {
+        log.push(PrunedNode {
+            path: tag.to_string(),
+            kind: PruneKind::Carried,
+            reason: "content was translated by conversion".to_string(),
+            action: "none; review the translated entries".to_string(),
+            preserved: false,
+        });
+        return None;
+    }
+    if COSMETIC_SECTIONS.contains(&tag) {
+        let (reason, action) = if tag == "widgets" {
+            (
+                "dashboard widget layout only; nothing functional",
+                format!("none; arrange the {platform} dashboard by hand if wanted"),
+            )
+        } else {
+            (
+                "GUI theme only; nothing functional",
+                "none; the theme is mapped where an equivalent exists".to_string(),
+            )
+        };
+        log.push(PrunedNode {
+            path: tag.to_string(),
+            kind: PruneKind::Cosmetic,
+            reason: reason.to_string(),
+            action,
+            preserved: false,
+        });
+        return None;
+    }
+    let (carried, action) = match tag {
+        "installedpackages" => (
+            CARRIED_PACKAGES,
+            format!("install the equivalent {platform} plugin and configure it by hand"),
+        ),
+        "OPNsense" => (
+            CARRIED_MODELS,
+            format!("recreate the settings in the {platform} GUI or package"),
+        ),
+        _ => {
+            log.push(dropped(
+                tag.to_string(),
+                format!("{platform} has no equivalent section"),
+                format!("recreate the settings on {platform} by hand"),
+                keep_unknown,
+            ));
+            return keep_unknown.then(|| node.clone());
+        }
+    };
+
+    let mut kept = XmlNode::new(tag);
+    kept.attributes = node.attributes.clone();
+    let mut logged = BTreeSet::new();
+    for child in &node.children {
+        let path = format!("{tag}.{}", child.tag);
+        let entry = if carried.contains(&child.tag.as_str()) {
+            PrunedNode {
+                path,
+                kind: PruneKind::Carried,
+                reason: "translated by conversion".to_string(),
+                action: "none; review the translated settings".to_string(),
+                preserved: false,
+            }
+        } else if tag == "installedpackages" && small_packages::find(&child.tag).is_some() {
+            PrunedNode {
+                path,
+                kind: PruneKind::Carried,
+                reason: "package settings are reported as manual steps".to_string(),
+                action: "follow the package's manual steps in the warnings".to_string(),
+                preserved: false,
+            }
+        } else {
+            if keep_unknown {
+                kept.children.push(child.clone());
+            }
+            dropped(
+                path,
+                format!("{platform} cannot hold this {}", kind_label(tag)),
+                action.clone(),
+                keep_unknown,
+            )
+        };
+        if logged.insert(entry.path.clone()) {
+            log.push(entry);
+        }
+    }
+    (!kept.children.is_empty()).then_some(kept)
+}
+
+fn dropped(path: String, reason: String, action: String, keep_unknown: bool) -> PrunedNode {
+    let action = if keep_unknown {
+        format!("{action}; a copy is kept under {PRESERVED_CONTAINER}.{path}")
+    } else {
+        format!("{action}, or rerun with --keep-unknown to keep a copy")
+    };
+    PrunedNode {
+        path,
+        kind: PruneKind::Dropped,
+        reason,
+        action,
+        preserved: keep_unknown,
+    }
+}
+
+fn kind_label(container: &str) -> &'static str {
+    if container == "installedpackages" {
+        "package config"
+    } else {
+        "model"
+    }
 }
 
 fn collect_top_level_tags(root: &XmlNode) -> BTreeSet<String> {
@@ -43,6 +299,9 @@ fn allowed_sections(platform: &str) -> BTreeSet<&'static str> {
             "dhcrelay6",
             "dhcp6relay",
             "vlans",
+            "laggs",
+            "pppoes",
+            "l2tp",
             "openvpn",
             "ipsec",
             "cert",
@@ -52,6 +311,7 @@ fn allowed_sections(platform: &str) -> BTreeSet<&'static str> {
             "staticroutes",
             "gateways",
             "hasync",
+            "sysctl",
             "revision",
         ]),
         "pfsense" => BTreeSet::from([
@@ -69,6 +329,10 @@ fn allowed_sections(platform: &str) -> BTreeSet<&'static str> {
             "dhcrelay6",
             "dhcp6relay",
             "vlans",
+            "qinqs",
+            "laggs",
+            "pppoes",
+            "l2tp",
             "openvpn",
             "ipsec",
             "cert",
@@ -78,6 +342,8 @@ fn allowed_sections(platform: &str) -> BTreeSet<&'static str> {
             "staticroutes",
             "gateways",
             "hasync",
+            "wol",
+            "sysctl",
             "revision",
         ]),
         _ => BTreeSet::new(),
@@ -88,7 +354,7 @@ fn allowed_sections(platform: &str) -> BTreeSet<&'static str> {
 mod tests {
     use xml_diff_core::parse;
 
-    use super::prune_imported_incompatible_sections;
+    use super::{prune_imported_incompatible_sections, PruneKind, PRESERVED_CONTAINER};
 
     #[test]
     fn prunes_pfsense_packages_when_target_is_opnsense() {
@@ -97,7 +363,8 @@ mod tests {
                 .expect("parse");
         let target = parse(br#"<opnsense><system/><interfaces/></opnsense>"#).expect("target");
 
-        let removed = prune_imported_incompatible_sections(&mut out, "opnsense", &target);
+        let (removed, _) =
+            prune_imported_incompatible_sections(&mut out, "opnsense", &target, false);
         assert!(removed.contains(&"installedpackages".to_string()));
         assert!(out.get_child("installedpackages").is_none());
     }
@@ -108,7 +375,8 @@ mod tests {
             .expect("parse");
         let target = parse(br#"<pfsense><system/><interfaces/></pfsense>"#).expect("target");
 
-        let removed = prune_imported_incompatible_sections(&mut out, "pfsense", &target);
+        let (removed, _) =
+            prune_imported_incompatible_sections(&mut out, "pfsense", &target, false);
         assert!(removed.contains(&"OPNsense".to_string()));
         assert!(out.get_child("OPNsense").is_none());
     }
@@ -119,7 +387,8 @@ mod tests {
             parse(br#"<opnsense><system/><interfaces/><OPNsense/></opnsense>"#).expect("parse");
         let target = parse(br#"<opnsense><system/><interfaces/></opnsense>"#).expect("target");
 
-        let removed = prune_imported_incompatible_sections(&mut out, "opnsense", &target);
+        let (removed, _) =
+            prune_imported_incompatible_sections(&mut out, "opnsense", &target, false);
         assert!(!removed.contains(&"OPNsense".to_string()));
         assert!(out.get_child("OPNsense").is_some());
     }
@@ -132,10 +401,70 @@ mod tests {
         .expect("parse");
         let target = parse(br#"<opnsense><system/><interfaces/></opnsense>"#).expect("target");
 
-        let removed = prune_imported_incompatible_sections(&mut out, "opnsense", &target);
+        let (removed, _) =
+            prune_imported_incompatible_sections(&mut out, "opnsense", &target, false);
         assert!(!removed.contains(&"dhcrelay".to_string()));
         assert!(!removed.contains(&"dhcp6relay".to_string()));
         assert!(out.get_child("dhcrelay").is_some());
         assert!(out.get_child("dhcp6relay").is_some());
     }
+
+    #[test]
+    fn logs_every_pruned_package_with_reason() {
+        let mut out = parse(
+            br#"<opnsense><system/><aliases/><installedpackages><package/><package/><snort/><filer/></installedpackages></opnsense>"#,
+        )
+        .expect("parse");
+        let target = parse(br#"<opnsense><system/></opnsense>"#).expect("target");
+
+        let (_, log) = prune_imported_incompatible_sections(&mut out, "opnsense", &target, false);
+        let entries: Vec<(&str, PruneKind)> =
+            log.iter().map(|n| (n.path.as_str(), n.kind)).collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("aliases", PruneKind::Carried),
+                ("installedpackages.package", PruneKind::Carried),
+                ("installedpackages.snort", PruneKind::Dropped),
+                ("installedpackages.filer", PruneKind::Carried),
+            ]
+        );
+        assert_eq!(log[2].reason, "opnsense cannot hold this package config");
+        assert!(log[2]
+            .action
+            .ends_with("or rerun with --keep-unknown to keep a copy"));
+        assert!(out.get_child(PRESERVED_CONTAINER).is_none());
+    }
+
+    #[test]
+    fn keep_unknown_preserves_dropped_nodes_only() {
+        let mut out = parse(
+            br#"<opnsense><system/><installedpackages><package/><snort><rule>1</rule></snort></installedpackages><notes><w/></notes><widgets><w/></widgets></opnsense>"#,
+        )
+        .expect("parse");
+        let target = parse(br#"<opnsense><system/></opnsense>"#).expect("target");
+
+        let (removed, log) =
+            prune_imported_incompatible_sections(&mut out, "opnsense", &target, true);
+        assert_eq!(removed, vec!["installedpackages", "notes", "widgets"]);
+        assert!(out.get_child("installedpackages").is_none());
+        let preserved = out.get_child(PRESERVED_CONTAINER).expect("container");
+        assert_eq!(
+            preserved.get_text(&["installedpackages", "snort", "rule"]),
+            Some("1")
+        );
+        assert!(preserved
+            .get_child("installedpackages")
+            .and_then(|p| p.get_child("package"))
+            .is_none());
+        assert!(preserved.get_child("notes").is_some());
+        assert!(preserved.get_child("widgets").is_none());
+        assert!(log
+            .iter()
+            .any(|n| n.path == "widgets" && n.kind == PruneKind::Cosmetic));
+        assert!(log
+            .iter()
+            .filter(|n| n.kind == PruneKind::Dropped)
+            .all(|n| n.preserved));
+    }
 }
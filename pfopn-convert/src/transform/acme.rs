@@ -0,0 +1,521 @@
+//! pfSense ACME package to OPNsense os-acme-client.
+//!
+//! pfSense keeps ACME settings under `<installedpackages><acme>`:
+//!
+//! - `accountkeys/item`: account name, email, ACME server, and account key
+//! - `certificates/item`: certificate name, account, key length, renewal
+//!   interval, a domain list (`a_domainlist/item`) with a challenge method
+//!   per domain, and post-issue actions (`a_actionlist/item`)
+//!
+//! os-acme-client stores the same data as an MVC model,
+//! `<OPNsense><AcmeClient>`, with accounts, certificates, and validation
+//! methods linked by UUID. A certificate has a single validation method, so
+//! each distinct challenge (method and DNS API credentials) becomes one
+//! `<validation>` shared by the certificates that use it.
+//!
+//! DNS API credentials are stored on pfSense domain entries as
+//! `<{method}{variable}>` (`dns_cfcf_key` for `CF_Key` with `dns_cf`) and
+//! become os-acme-client's `dns_{variable}` fields.
+//!
+//! The issued certificate lives in the certificate store under the ACME
+//! certificate's name; its `refid` is written to `certRefId`, so services
+//! that used it keep working and renewals replace it in place.
+
+use std::collections::BTreeMap;
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::is_truthy;
+use crate::uuid_registry::UuidRegistry;
+
+/// pfSense ACME server names and their os-acme-client CA.
+const SERVERS: &[(&str, &str)] = &[
+    ("letsencrypt-production-2", "letsencrypt"),
+    ("letsencrypt-production", "letsencrypt"),
+    ("letsencrypt-staging-2", "letsencrypt_test"),
+    ("letsencrypt-staging", "letsencrypt_test"),
+    ("buypass-production", "buypass"),
+    ("buypass-test", "buypass_test"),
+    ("zerossl-production", "zerossl"),
+    ("google-production", "google"),
+    ("google-test", "google_test"),
+    ("sslcom-rsa", "sslcom"),
+    ("sslcom-ecc", "sslcom"),
+];
+
+/// pfSense key lengths and their os-acme-client value.
+const KEY_LENGTHS: &[(&str, &str)] = &[
+    ("2048", "key_2048"),
+    ("3072", "key_3072"),
+    ("4096", "key_4096"),
+    ("ec-256", "key_ec256"),
+    ("ec-384", "key_ec384"),
+];
+
+/// Domain entry fields that are not challenge credentials.
+const DOMAIN_FIELDS: &[&str] = &["status", "name", "method"];
+
+/// A challenge, shared by every certificate with the same method and
+/// credentials.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Challenge {
+    method: String,
+    /// os-acme-client field name and value.
+    credentials: BTreeMap<String, String>,
+}
+
+/// Convert the pfSense ACME package config in `source` into an
+/// `<OPNsense><AcmeClient>` model in `out`.
+///
+/// Accounts and certificates already in the output with the same name are
+/// kept as-is. Challenge methods os-acme-client cannot run the same way and
+/// post-issue actions are reported as `acme` warnings.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(acme) = source
+        .get_child("installedpackages")
+        .and_then(|ip| ip.get_child("acme"))
+    else {
+        return;
+    };
+    let accounts = items(acme, "accountkeys");
+    let certificates = items(acme, "certificates");
+    if accounts.is_empty() && certificates.is_empty() {
+        return;
+    }
+
+    let mut registry = UuidRegistry::from_config(out);
+    let cert_refids = issued_refids(source, out);
+    let mut model = out
+        .get_child("OPNsense")
+        .and_then(|opn| opn.get_child("AcmeClient"))
+        .cloned()
+        .unwrap_or_else(|| XmlNode::new("AcmeClient"));
+
+    let settings = ensure_child_mut(&mut model, "settings");
+    set_text(settings, "enabled", "1");
+    set_text(
+        settings,
+        "autoRenewal",
+        if is_truthy(field(acme, "enable")) {
+            "1"
+        } else {
+            "0"
+        },
+    );
+
+    for account in &accounts {
+        let name = field(account, "name");
+        if name.is_empty() || has_named(&model, "accounts", "account", name) {
+            continue;
+        }
+        let uuid = registry.uuid_for("acme-account", name);
+        let server = field(account, "acmeserver");
+        let ca = SERVERS
+            .iter()
+            .find(|(pf, _)| *pf == server)
+            .map_or_else(
+                || {
+                    diagnostics.warn(
+                        "acme",
+                        format!(
+                            "account '{name}': unknown ACME server '{server}'; set the CA by hand (defaulted to letsencrypt)"
+                        ),
+                    );
+                    "letsencrypt"
+                },
+                |(_, opn)| *opn,
+            );
+        let mut node = item("account", &uuid);
+        for (tag, value) in [
+            ("enabled", "1"),
+            ("name", name),
+            ("description", field(account, "descr")),
+            ("email", field(account, "email")),
+            ("ca", ca),
+            ("key", field(account, "accountkey")),
+        ] {
+            set_text(&mut node, tag, value);
+        }
+        ensure_child_mut(&mut model, "accounts").children.push(node);
+    }
+
+    for cert in &certificates {
+        let name = field(cert, "name");
+        let domains: Vec<&XmlNode> = items(cert, "a_domainlist")
+            .into_iter()
+            .filter(|d| field(d, "status") != "disable" && !field(d, "name").is_empty())
+            .collect();
+        let Some((first, rest)) = domains.split_first() else {
+            diagnostics.warn(
+                "acme",
+                format!("certificate '{name}' has no enabled domains; not converted"),
+            );
+            continue;
+        };
+        let common_name = field(first, "name");
+        if has_named(&model, "certificates", "certificate", common_name) {
+            continue;
+        }
+
+        let challenge = challenge(first);
+        if rest.iter().any(|d| field(d, "method") != challenge.method) {
+            diagnostics.warn(
+                "acme",
+                format!(
+                    "certificate '{name}' mixes challenge methods; all domains now use '{}'",
+                    challenge.method
+                ),
+            );
+        }
+        let validation = validation_uuid(&mut model, &mut registry, &challenge, name, diagnostics);
+
+        let account = field(cert, "acmeaccount");
+        let account_uuid = registry
+            .get("acme-account", account)
+            .map(ToString::to_string)
+            .or_else(|| named_uuid(&model, "accounts", "account", account))
+            .unwrap_or_default();
+        if account_uuid.is_empty() {
+            diagnostics.warn(
+                "acme",
+                format!("certificate '{name}' references missing account '{account}'"),
+            );
+        }
+        let key_length = field(cert, "keylength");
+        let key_length = KEY_LENGTHS
+            .iter()
+            .find(|(pf, _)| *pf == key_length)
+            .map_or("key_2048", |(_, opn)| *opn);
+        let alt_names: Vec<&str> = rest.iter().map(|d| field(d, "name")).collect();
+        let cert_refid = cert_refids.get(name).map_or("", String::as_str);
+
+        let uuid = registry.uuid_for("acme-certificate", name);
+        let mut node = item("certificate", &uuid);
+        for (tag, value) in [
+            (
+                "enabled",
+                if field(cert, "status") == "active" {
+                    "1"
+                } else {
+                    "0"
+                },
+            ),
+            ("name", common_name),
+            ("description", description(cert)),
+            ("altNames", alt_names.join(",").as_str()),
+            ("account", account_uuid.as_str()),
+            ("validationMethod", validation.as_str()),
+            ("keyLength", key_length),
+            ("autoRenewal", "1"),
+            ("renewInterval", field(cert, "renewafter")),
+            ("certRefId", cert_refid),
+        ] {
+            set_text(&mut node, tag, value);
+        }
+        ensure_child_mut(&mut model, "certificates")
+            .children
+            .push(node);
+
+        for action in items(cert, "a_actionlist") {
+            if field(action, "status") == "disable" {
+                continue;
+            }
+            diagnostics.warn(
+                "acme",
+                format!(
+                    "certificate '{name}': recreate the {} action '{}' as an automation",
+                    field(action, "method"),
+                    field(action, "command")
+                ),
+            );
+        }
+    }
+
+    let opn = ensure_child_mut(out, "OPNsense");
+    opn.children.retain(|c| c.tag != "AcmeClient");
+    opn.children.push(model);
+    diagnostics.info(
+        "acme",
+        "ACME settings converted; install os-acme-client on the target before restore",
+    );
+}
+
+/// The UUID of the validation for `challenge`, adding one when no
+/// certificate converted so far uses the same challenge.
+fn validation_uuid(
+    model: &mut XmlNode,
+    registry: &mut UuidRegistry,
+    challenge: &Challenge,
+    cert_name: &str,
+    diagnostics: &mut Diagnostics,
+) -> String {
+    let key = format!("{challenge:?}");
+    if let Some(uuid) = registry.get("acme-validation", &key) {
+        return uuid.to_string();
+    }
+    let uuid = registry.uuid_for("acme-validation", &key);
+    let method = challenge.method.as_str();
+    let mut node = item("validation", &uuid);
+    set_text(&mut node, "enabled", "1");
+    set_text(&mut node, "name", &format!("{method} ({cert_name})"));
+    match method {
+        "dns_manual" => {
+            diagnostics.warn(
+                "acme",
+                format!(
+                    "certificate '{cert_name}': manual DNS challenges cannot run unattended; pick a DNS API"
+                ),
+            );
+            set_text(&mut node, "method", "dns01");
+        }
+        dns if dns.starts_with("dns_") => {
+            set_text(&mut node, "method", "dns01");
+            set_text(&mut node, "dns_service", dns);
+            for (tag, value) in &challenge.credentials {
+                set_text(&mut node, tag, value);
+            }
+        }
+        "standalonetls" => set_text(&mut node, "method", "tlsalpn01"),
+        other => {
+            if other != "standalone" {
+                diagnostics.warn(
+                    "acme",
+                    format!(
+                        "certificate '{cert_name}': '{other}' challenges become HTTP-01 served by the firewall; check port 80 reaches it"
+                    ),
+                );
+            }
+            set_text(&mut node, "method", "http01");
+            set_text(&mut node, "http_service", "opnsense");
+        }
+    }
+    ensure_child_mut(model, "validations").children.push(node);
+    uuid
+}
+
+/// The challenge a pfSense domain entry uses.
+fn challenge(domain: &XmlNode) -> Challenge {
+    let method = field(domain, "method").to_string();
+    let credentials = domain
+        .children
+        .iter()
+        .filter(|c| !DOMAIN_FIELDS.contains(&c.tag.as_str()))
+        .filter_map(|c| {
+            let var = c.tag.strip_prefix(method.as_str())?;
+            let value = c.text.as_deref().map(str::trim).unwrap_or("");
+            (!value.is_empty()).then(|| {
+                (
+                    format!("dns_{}", var.trim_start_matches('_')),
+                    value.to_string(),
+                )
+            })
+        })
+        .collect();
+    Challenge {
+        method,
+        credentials,
+    }
+}
+
+/// Output refids of issued certificates, by ACME certificate name.
+///
+/// pfSense files an issued certificate under the ACME certificate's name;
+/// only entries that made it into the output are returned.
+fn issued_refids(source: &XmlNode, out: &XmlNode) -> BTreeMap<String, String> {
+    source
+        .get_children("cert")
+        .into_iter()
+        .filter_map(|cert| {
+            let refid = field(cert, "refid");
+            out.get_children("cert")
+                .iter()
+                .any(|c| field(c, "refid") == refid)
+                .then(|| (field(cert, "descr").to_string(), refid.to_string()))
+        })
+        .collect()
+}
+
+fn description(cert: &XmlNode) -> &str {
+    match field(cert, "descr") {
+        "" => field(cert, "name"),
+        descr => descr,
+    }
+}
+
+fn items<'a>(node: &'a XmlNode, list: &str) -> Vec<&'a XmlNode> {
+    node.get_child(list)
+        .map(|l| l.get_children("item"))
+        .unwrap_or_default()
+}
+
+fn has_named(model: &XmlNode, list: &str, tag: &str, name: &str) -> bool {
+    named_uuid(model, list, tag, name).is_some()
+}
+
+fn named_uuid(model: &XmlNode, list: &str, tag: &str, name: &str) -> Option<String> {
+    model
+        .get_child(list)?
+        .get_children(tag)
+        .into_iter()
+        .find(|n| field(n, "name") == name)
+        .map(|n| n.attributes.get("uuid").cloned().unwrap_or_default())
+}
+
+fn item(tag: &str, uuid: &str) -> XmlNode {
+    let mut node = XmlNode::new(tag);
+    node.attributes.insert("uuid".to_string(), uuid.to_string());
+    node
+}
+
+fn field<'a>(node: &'a XmlNode, tag: &str) -> &'a str {
+    node.get_text(&[tag]).map(str::trim).unwrap_or("")
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+/// Get a mutable reference to a child, creating it if it doesn't exist.
+fn ensure_child_mut<'a>(parent: &'a mut XmlNode, tag: &str) -> &'a mut XmlNode {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == tag) {
+        return &mut parent.children[idx];
+    }
+    parent.children.push(XmlNode::new(tag));
+    let last = parent.children.len() - 1;
+    &mut parent.children[last]
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::to_opnsense;
+    use crate::diagnostics::Diagnostics;
+
+    const SOURCE: &[u8] = br#"<pfsense>
+        <cert><refid>acme1</refid><descr>www</descr><crt>C</crt></cert>
+        <installedpackages><acme>
+            <enable>yes</enable>
+            <accountkeys><item>
+                <name>LE</name><email>admin@example.com</email>
+                <acmeserver>letsencrypt-production-2</acmeserver><accountkey>KEY</accountkey>
+            </item></accountkeys>
+            <certificates>
+                <item>
+                    <name>www</name><status>active</status><acmeaccount>LE</acmeaccount>
+                    <keylength>ec-256</keylength><renewafter>60</renewafter>
+                    <a_domainlist>
+                        <item><status>enable</status><name>www.example.com</name><method>dns_cf</method><dns_cfcf_token>TOKEN</dns_cfcf_token></item>
+                        <item><status>enable</status><name>example.com</name><method>dns_cf</method><dns_cfcf_token>TOKEN</dns_cfcf_token></item>
+                        <item><status>disable</status><name>old.example.com</name><method>dns_cf</method></item>
+                    </a_domainlist>
+                    <a_actionlist><item><status>active</status><command>/root/deploy.sh</command><method>shellcommand</method></item></a_actionlist>
+                </item>
+                <item>
+                    <name>mail</name><status>active</status><acmeaccount>LE</acmeaccount>
+                    <a_domainlist><item><status>enable</status><name>mail.example.com</name><method>dns_cf</method><dns_cfcf_token>TOKEN</dns_cfcf_token></item></a_domainlist>
+                </item>
+            </certificates>
+        </acme></installedpackages>
+    </pfsense>"#;
+
+    #[test]
+    fn converts_accounts_certificates_and_shared_validations() {
+        let source = parse(SOURCE).expect("parse");
+        let baseline = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out =
+            parse(br#"<opnsense><cert><refid>acme1</refid><crt>C</crt></cert></opnsense>"#)
+                .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let model = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("AcmeClient"))
+            .expect("model");
+        assert_eq!(model.get_text(&["settings", "autoRenewal"]), Some("1"));
+        let account = model
+            .get_child("accounts")
+            .expect("accounts")
+            .get_children("account")[0];
+        assert_eq!(account.get_text(&["ca"]), Some("letsencrypt"));
+        assert_eq!(account.get_text(&["key"]), Some("KEY"));
+
+        let certs = model
+            .get_child("certificates")
+            .expect("certificates")
+            .get_children("certificate");
+        assert_eq!(certs.len(), 2);
+        let www = certs[0];
+        assert_eq!(www.get_text(&["name"]), Some("www.example.com"));
+        assert_eq!(www.get_text(&["altNames"]), Some("example.com"));
+        assert_eq!(www.get_text(&["keyLength"]), Some("key_ec256"));
+        assert_eq!(www.get_text(&["certRefId"]), Some("acme1"));
+        assert_eq!(
+            www.get_text(&["account"]),
+            account.attributes.get("uuid").map(String::as_str)
+        );
+        assert_eq!(certs[1].get_text(&["certRefId"]), Some(""));
+
+        let validations = model
+            .get_child("validations")
+            .expect("validations")
+            .get_children("validation");
+        assert_eq!(validations.len(), 1, "same challenge is shared");
+        assert_eq!(validations[0].get_text(&["method"]), Some("dns01"));
+        assert_eq!(validations[0].get_text(&["dns_service"]), Some("dns_cf"));
+        assert_eq!(validations[0].get_text(&["dns_cf_token"]), Some("TOKEN"));
+        assert_eq!(
+            certs[1].get_text(&["validationMethod"]),
+            validations[0].attributes.get("uuid").map(String::as_str)
+        );
+
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("recreate the shellcommand action '/root/deploy.sh'")));
+    }
+
+    #[test]
+    fn keeps_existing_model_entries() {
+        let source = parse(SOURCE).expect("parse");
+        let baseline = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = parse(
+            br#"<opnsense><OPNsense><AcmeClient version="4.0.0"><certificates><certificate uuid="u1"><name>www.example.com</name></certificate></certificates></AcmeClient></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let model = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("AcmeClient"))
+            .expect("model");
+        assert_eq!(
+            model.attributes.get("version").map(String::as_str),
+            Some("4.0.0")
+        );
+        let names: Vec<&str> = model
+            .get_child("certificates")
+            .expect("certificates")
+            .get_children("certificate")
+            .into_iter()
+            .filter_map(|c| c.get_text(&["name"]))
+            .collect();
+        assert_eq!(names, vec!["www.example.com", "mail.example.com"]);
+    }
+}
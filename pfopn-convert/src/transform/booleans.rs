@@ -0,0 +1,340 @@
+use xml_diff_core::XmlNode;
+
+/// Boolean fields in legacy (non-MVC) sections, by section path.
+///
+/// pfSense reads these with `isset()`, so an empty presence tag means on and
+/// any value — including `0` — also means on. OPNsense reads them with
+/// `!empty()` and writes `1`/`0`, so an empty pfSense tag reads as off. `*`
+/// matches any child (interface names under `<interfaces>` and `<dhcpd>`).
+/// Run [`canonicalize`] on a pfSense source before reading it with
+/// [`flag_set`].
+pub const LEGACY_FLAGS: &[FlagFields] = &[
+    FlagFields::new(
+        &["interfaces", "*"],
+        &["enable", "blockpriv", "blockbogons"],
+    ),
+    FlagFields::new(&["dhcpd", "*"], &["enable", "staticarp"]),
+    FlagFields::new(&["dhcpd", "*", "staticmap"], &["arp_table_static_entry"]),
+    FlagFields::new(&["dhcpdv6", "*"], &["enable"]),
+    FlagFields::new(&["dhcpd6", "*"], &["enable"]),
+    FlagFields::new(&["filter", "rule"], &["disabled", "log", "quick"]),
+    FlagFields::new(&["nat", "rule"], &["disabled", "nordr", "nosync"]),
+    FlagFields::new(
+        &["nat", "outbound", "rule"],
+        &["disabled", "nonat", "nosync", "staticnatport"],
+    ),
+    FlagFields::new(&["nat", "onetoone"], &["disabled"]),
+    FlagFields::new(&["staticroutes", "route"], &["disabled"]),
+    FlagFields::new(
+        &["gateways", "gateway_item"],
+        &[
+            "disabled",
+            "monitor_disable",
+            "action_disable",
+            "force_down",
+            "nonlocalgateway",
+            "fargw",
+            "dpinger_dont_add_static_route",
+            "monitor_noroute",
+        ],
+    ),
+    FlagFields::new(&["system", "user"], &["disabled"]),
+];
+
+/// Boolean fields of the nodes at one section path.
+#[derive(Debug, Clone, Copy)]
+pub struct FlagFields {
+    pub path: &'static [&'static str],
+    pub fields: &'static [&'static str],
+}
+
+impl FlagFields {
+    const fn new(path: &'static [&'static str], fields: &'static [&'static str]) -> Self {
+        Self { path, fields }
+    }
+}
+
+/// True for the "on" spellings either platform writes: `1`, `yes`, `true`,
+/// `enabled`, `on` (case-insensitive).
+pub fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "yes" | "true" | "enabled" | "on"
+    )
+}
+
+/// True for the explicit "off" spellings: `0`, `no`, `false`, `off`.
+pub fn is_falsy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "0" | "no" | "false" | "off"
+    )
+}
+
+/// A flag is set when the tag is present, unless its value is explicitly off.
+///
+/// This reads both encodings: pfSense presence tags and OPNsense `1`/`0`.
+/// A pfSense tag holding an off spelling still means on there; use
+/// [`flag_set_on`] for configs read in place.
+pub fn flag_set(node: &XmlNode, tag: &str) -> bool {
+    node.get_child(tag)
+        .is_some_and(|c| !c.text.as_deref().is_some_and(is_falsy))
+}
+
+/// Read `tag` the way `platform` does: any present tag is on for pfSense.
+pub fn flag_set_on(node: &XmlNode, tag: &str, platform: &str) -> bool {
+    if platform == "pfsense" {
+        node.get_child(tag).is_some()
+    } else {
+        flag_set(node, tag)
+    }
+}
+
+/// OPNsense `1`/`0` spelling of a boolean.
+pub fn numeric(on: bool) -> &'static str {
+    if on {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+/// Switch `tag` on or off in place, keeping its position when present.
+///
+/// On writes `1` for OPNsense and an empty presence tag for pfSense; off
+/// removes the tag on both.
+pub fn set_flag(node: &mut XmlNode, tag: &str, on: bool, platform: &str) {
+    if !on {
+        node.children.retain(|c| c.tag != tag);
+        return;
+    }
+    let flag = flag_node(tag, on, platform);
+    match node.children.iter_mut().find(|c| c.tag == tag) {
+        Some(existing) => *existing = flag,
+        None => node.children.push(flag),
+    }
+}
+
+/// Re-encode an existing `tag` for `platform`; absent tags stay absent.
+///
+/// OPNsense keeps the tag as `1`/`0`; pfSense keeps an empty presence tag
+/// when on and drops the tag when off.
+pub fn normalize_flag(node: &mut XmlNode, tag: &str, platform: &str) {
+    if node.get_child(tag).is_none() {
+        return;
+    }
+    let on = flag_set(node, tag);
+    if platform == "opnsense" {
+        for flag in node.children.iter_mut().filter(|c| c.tag == tag) {
+            *flag = flag_node(tag, on, platform);
+        }
+    } else {
+        set_flag(node, tag, on, platform);
+    }
+}
+
+/// Rewrite a present `tag` of a `platform` config as an empty presence tag
+/// when `platform` reads it as on but [`flag_set`] would not.
+pub fn canonicalize_flag(node: &mut XmlNode, tag: &str, platform: &str) {
+    if flag_set_on(node, tag, platform) && !flag_set(node, tag) {
+        for flag in node.children.iter_mut().filter(|c| c.tag == tag) {
+            *flag = XmlNode::new(tag);
+        }
+    }
+}
+
+/// Whether any [`LEGACY_FLAGS`] field of a `platform` config reads
+/// differently through [`flag_set`].
+pub fn needs_canonicalizing(root: &XmlNode, platform: &str) -> bool {
+    LEGACY_FLAGS.iter().any(|entry| {
+        nodes_at(root, entry.path).into_iter().any(|node| {
+            entry
+                .fields
+                .iter()
+                .any(|field| flag_set_on(node, field, platform) != flag_set(node, field))
+        })
+    })
+}
+
+/// Canonicalize every [`LEGACY_FLAGS`] field of a `platform` config so
+/// [`flag_set`] and [`normalize`] read it as `platform` does.
+pub fn canonicalize(root: &mut XmlNode, platform: &str) {
+    for entry in LEGACY_FLAGS {
+        for node in nodes_at_mut(root, entry.path) {
+            for field in entry.fields {
+                canonicalize_flag(node, field, platform);
+            }
+        }
+    }
+}
+
+/// Re-encode every [`LEGACY_FLAGS`] field in `out` for `to`.
+///
+/// Legacy sections are copied from the source, so their flags still use the
+/// source platform's encoding; a pfSense source must be [`canonicalize`]d
+/// first.
+pub fn normalize(out: &mut XmlNode, to: &str) {
+    for entry in LEGACY_FLAGS {
+        for node in nodes_at_mut(out, entry.path) {
+            for field in entry.fields {
+                normalize_flag(node, field, to);
+            }
+        }
+    }
+}
+
+fn flag_node(tag: &str, on: bool, platform: &str) -> XmlNode {
+    let mut node = XmlNode::new(tag);
+    if platform == "opnsense" {
+        node.text = Some(numeric(on).to_string());
+    }
+    node
+}
+
+/// Collect the nodes at `path` below `root`; `*` matches any tag.
+fn nodes_at<'a>(root: &'a XmlNode, path: &[&str]) -> Vec<&'a XmlNode> {
+    let Some((first, rest)) = path.split_first() else {
+        return vec![root];
+    };
+    root.children
+        .iter()
+        .filter(|c| *first == "*" || c.tag == *first)
+        .flat_map(|c| nodes_at(c, rest))
+        .collect()
+}
+
+/// Mutable counterpart of [`nodes_at`].
+pub(crate) fn nodes_at_mut<'a>(root: &'a mut XmlNode, path: &[&str]) -> Vec<&'a mut XmlNode> {
+    let Some((first, rest)) = path.split_first() else {
+        return vec![root];
+    };
+    root.children
+        .iter_mut()
+        .filter(|c| *first == "*" || c.tag == *first)
+        .flat_map(|c| nodes_at_mut(c, rest))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{
+        canonicalize, flag_set, flag_set_on, is_truthy, needs_canonicalizing, normalize, set_flag,
+    };
+
+    #[test]
+    fn reads_both_encodings() {
+        let node = parse(
+            br#"<rule><disabled></disabled><log>0</log><quick>yes</quick><nosync>1</nosync></rule>"#,
+        )
+        .expect("parse");
+        assert!(flag_set(&node, "disabled"));
+        assert!(!flag_set(&node, "log"));
+        assert!(flag_set(&node, "quick"));
+        assert!(flag_set(&node, "nosync"));
+        assert!(!flag_set(&node, "missing"));
+        assert!(is_truthy("On"));
+        assert!(!is_truthy(""));
+    }
+
+    #[test]
+    fn pfsense_reads_any_present_tag_as_on() {
+        let node = parse(br#"<rule><disabled>0</disabled><log>no</log></rule>"#).expect("parse");
+        assert!(flag_set_on(&node, "disabled", "pfsense"));
+        assert!(flag_set_on(&node, "log", "pfsense"));
+        assert!(!flag_set_on(&node, "disabled", "opnsense"));
+        assert!(!flag_set_on(&node, "quick", "pfsense"));
+    }
+
+    #[test]
+    fn pfsense_zero_flag_normalizes_to_on_for_opnsense() {
+        let mut out = parse(
+            br#"<pfsense><filter><rule><disabled>0</disabled></rule></filter><nat><rule><disabled>0</disabled></rule></nat><staticroutes><route><disabled>off</disabled></route></staticroutes><system><user><disabled>false</disabled></user></system></pfsense>"#,
+        )
+        .expect("parse");
+
+        assert!(needs_canonicalizing(&out, "pfsense"));
+        canonicalize(&mut out, "pfsense");
+        normalize(&mut out, "opnsense");
+
+        assert_eq!(out.get_text(&["filter", "rule", "disabled"]), Some("1"));
+        assert_eq!(out.get_text(&["nat", "rule", "disabled"]), Some("1"));
+        assert_eq!(
+            out.get_text(&["staticroutes", "route", "disabled"]),
+            Some("1")
+        );
+        assert_eq!(out.get_text(&["system", "user", "disabled"]), Some("1"));
+    }
+
+    #[test]
+    fn canonicalize_leaves_opnsense_flags_alone() {
+        let mut out =
+            parse(br#"<opnsense><filter><rule><disabled>0</disabled></rule></filter></opnsense>"#)
+                .expect("parse");
+        assert!(!needs_canonicalizing(&out, "opnsense"));
+        canonicalize(&mut out, "opnsense");
+        assert_eq!(out.get_text(&["filter", "rule", "disabled"]), Some("0"));
+    }
+
+    #[test]
+    fn normalizes_pfsense_presence_tags_for_opnsense() {
+        let mut out = parse(
+            br#"<opnsense><interfaces><lan><enable></enable><blockpriv/><if>igb0</if></lan></interfaces><filter><rule><disabled></disabled><quick>yes</quick></rule></filter><dhcpd><lan><staticarp/><staticmap><arp_table_static_entry/></staticmap></lan></dhcpd></opnsense>"#,
+        )
+        .expect("parse");
+
+        normalize(&mut out, "opnsense");
+
+        assert_eq!(out.get_text(&["interfaces", "lan", "enable"]), Some("1"));
+        assert_eq!(out.get_text(&["interfaces", "lan", "blockpriv"]), Some("1"));
+        assert_eq!(out.get_text(&["interfaces", "lan", "if"]), Some("igb0"));
+        assert_eq!(out.get_text(&["filter", "rule", "disabled"]), Some("1"));
+        assert_eq!(out.get_text(&["filter", "rule", "quick"]), Some("1"));
+        assert_eq!(out.get_text(&["dhcpd", "lan", "staticarp"]), Some("1"));
+        assert_eq!(
+            out.get_text(&["dhcpd", "lan", "staticmap", "arp_table_static_entry"]),
+            Some("1")
+        );
+    }
+
+    #[test]
+    fn normalizes_opnsense_numeric_flags_for_pfsense() {
+        let mut out = parse(
+            br#"<pfsense><filter><rule><disabled>0</disabled><log>1</log><interface>lan</interface></rule></filter><nat><outbound><rule><nonat>0</nonat><nosync>0</nosync></rule></outbound></nat><staticroutes><route><disabled>1</disabled></route></staticroutes></pfsense>"#,
+        )
+        .expect("parse");
+
+        normalize(&mut out, "pfsense");
+
+        let rule = out
+            .get_child("filter")
+            .and_then(|f| f.get_child("rule"))
+            .expect("rule");
+        assert!(rule.get_child("disabled").is_none());
+        assert_eq!(
+            rule.get_child("log")
+                .map(|l| l.text.as_deref().unwrap_or("")),
+            Some("")
+        );
+        let outbound = out.get_child("nat").and_then(|n| n.get_child("outbound"));
+        let outbound_rule = outbound.and_then(|o| o.get_child("rule")).expect("rule");
+        assert!(outbound_rule.children.is_empty());
+        assert!(out
+            .get_child("staticroutes")
+            .and_then(|s| s.get_child("route"))
+            .and_then(|r| r.get_child("disabled"))
+            .is_some());
+    }
+
+    #[test]
+    fn set_flag_writes_platform_encoding() {
+        let mut node = parse(br#"<system><a/><b>1</b></system>"#).expect("parse");
+        set_flag(&mut node, "a", true, "opnsense");
+        set_flag(&mut node, "b", false, "opnsense");
+        set_flag(&mut node, "c", true, "pfsense");
+        assert_eq!(node.get_text(&["a"]), Some("1"));
+        assert!(node.get_child("b").is_none());
+        assert!(node.get_child("c").is_some_and(|c| c.text.is_none()));
+    }
+}
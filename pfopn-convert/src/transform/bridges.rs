@@ -1,104 +1,232 @@
+use std::collections::BTreeMap;
+
 use xml_diff_core::XmlNode;
 
+use crate::uuid_registry::UuidRegistry;
+
+/// Per-member option lists: comma-separated member interface names.
+pub const MEMBER_FLAG_LISTS: &[&str] = &[
+    "stp", "edge", "autoedge", "ptp", "autoptp", "static", "private", "span",
+];
+
+/// Per-member STP values: comma-separated `<interface>:<value>` pairs.
+pub const MEMBER_VALUE_LISTS: &[&str] = &["ifpriority", "ifpathcost"];
+
 /// Normalizes bridge configuration for OPNsense format.
 ///
 /// OPNsense requires each `<bridged>` element to carry a `uuid` attribute.
 /// pfSense configs don't include these, so when converting pf -> opn we
 /// generate a deterministic UUID from the bridge's member list (or interface
 /// name as fallback). Already-present UUIDs are left untouched.
-pub fn normalize_for_opnsense(root: &mut XmlNode) {
+///
+/// STP and member options carry over as-is, except:
+/// - `ip6linklocal` is renamed to OPNsense's `linklocal`
+/// - `enablestp` is set when any member has STP enabled, since OPNsense only
+///   runs (R)STP when that toggle is on
+/// - member options naming an interface that is not a bridge member are
+///   dropped; OPNsense rejects them on save
+///
+/// Returns one note per dropped member option.
+pub fn normalize_for_opnsense(root: &mut XmlNode) -> Vec<String> {
+    let mut registry = UuidRegistry::from_config(root);
     let Some(bridges) = child_mut(root, "bridges") else {
-        return;
+        return Vec::new();
     };
+    let mut notes = Vec::new();
     for (idx, bridged) in bridges
         .children
         .iter_mut()
         .filter(|c| c.tag == "bridged")
         .enumerate()
     {
-        if !bridged.attributes.contains_key("uuid") {
-            // Use the member list as the seed so the same bridge always gets the
-            // same UUID. Fall back to the bridge interface name, then a generic
-            // constant if neither is present.
-            let seed = bridged
-                .get_text(&["members"])
-                .or_else(|| bridged.get_text(&["bridgeif"]))
-                .unwrap_or("bridge");
-            bridged
-                .attributes
-                .insert("uuid".to_string(), stable_uuid(seed.as_bytes(), idx));
+        rename_child(bridged, "ip6linklocal", "linklocal");
+        notes.extend(drop_non_member_options(bridged, idx));
+        if has_text(bridged, "stp") && !has_text(bridged, "enablestp") {
+            set_text(bridged, "enablestp", "1");
         }
     }
+    // Key by the member list so the same bridge always gets the same UUID,
+    // falling back to the bridge interface name.
+    registry.assign_missing(bridges, "bridged", "bridge", |_, bridged| {
+        bridged
+            .get_text(&["members"])
+            .or_else(|| bridged.get_text(&["bridgeif"]))
+            .unwrap_or("bridge")
+            .to_string()
+    });
+    notes
 }
 
 /// Normalizes bridge configuration for pfSense format.
 ///
 /// pfSense does not use `uuid` attributes on `<bridged>` elements, so when
-/// converting opn -> pf we strip them.
-pub fn normalize_for_pfsense(root: &mut XmlNode) {
+/// converting opn -> pf we strip them. `linklocal` becomes `ip6linklocal`,
+/// and because pfSense has no `enablestp` toggle, the member STP list is
+/// cleared when OPNsense had (R)STP switched off. Member options naming an
+/// interface that is not a bridge member are dropped.
+///
+/// Returns one note per dropped member option.
+pub fn normalize_for_pfsense(root: &mut XmlNode) -> Vec<String> {
+    let Some(bridges) = child_mut(root, "bridges") else {
+        return Vec::new();
+    };
+    let mut notes = Vec::new();
+    for (idx, bridged) in bridges
+        .children
+        .iter_mut()
+        .filter(|c| c.tag == "bridged")
+        .enumerate()
+    {
+        bridged.attributes.remove("uuid");
+        rename_child(bridged, "linklocal", "ip6linklocal");
+        if !has_text(bridged, "enablestp") && has_text(bridged, "stp") {
+            bridged.children.retain(|c| c.tag != "stp");
+            notes.push(format!(
+                "bridge #{idx}: dropped member STP list because (R)STP was disabled"
+            ));
+        }
+        bridged.children.retain(|c| c.tag != "enablestp");
+        notes.extend(drop_non_member_options(bridged, idx));
+    }
+    notes
+}
+
+/// Rename interfaces in bridge member option lists (`stp`, `edge`,
+/// `ifpriority`, ...) using the logical interface map. `<members>` itself is
+/// handled by [`crate::transform::logical_refs`].
+pub fn rewrite_member_options(root: &mut XmlNode, logical_map: Option<&BTreeMap<String, String>>) {
+    let Some(logical_map) = logical_map.filter(|m| !m.is_empty()) else {
+        return;
+    };
     let Some(bridges) = child_mut(root, "bridges") else {
         return;
     };
     for bridged in bridges.children.iter_mut().filter(|c| c.tag == "bridged") {
-        bridged.attributes.remove("uuid");
+        for option in &mut bridged.children {
+            if !is_member_option(&option.tag) {
+                continue;
+            }
+            let Some(text) = option.text.as_deref() else {
+                continue;
+            };
+            let rewritten = text
+                .split(',')
+                .map(|entry| {
+                    let (name, value) = split_entry(entry);
+                    let name = logical_map.get(name).map_or(name, String::as_str);
+                    match value {
+                        Some(value) => format!("{name}:{value}"),
+                        None => name.to_string(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            option.text = Some(rewritten);
+        }
     }
 }
 
-/// Returns a mutable reference to the first child with the given tag name.
-fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
-    let idx = node.children.iter().position(|c| c.tag == tag)?;
-    Some(&mut node.children[idx])
+/// Interfaces named by a bridge member option, in list order.
+pub fn member_option_interfaces(option: &XmlNode) -> Vec<String> {
+    option
+        .text
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(|entry| split_entry(entry).0)
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_ascii_lowercase())
+        .collect()
 }
 
-/// Generates a deterministic, RFC 4122 v4-shaped UUID from a byte seed and an
-/// index. This is NOT a true random UUID — it's a content-addressed hash so
-/// that the same bridge definition always produces the same identifier across
-/// runs, which keeps diffs stable.
-///
-/// The algorithm mixes `seed` bytes into a 16-byte accumulator using wrapping
-/// addition and bit rotation, then folds in `idx` to disambiguate bridges that
-/// would otherwise share the same seed. Finally it stamps the version (4) and
-/// variant (RFC 4122) nibbles so the result looks like a valid v4 UUID.
-fn stable_uuid(seed: &[u8], idx: usize) -> String {
-    let mut acc = [0u8; 16];
-    // Mix seed bytes into the accumulator with position-dependent rotation.
-    for (i, b) in seed.iter().enumerate() {
-        acc[i % 16] = acc[i % 16].wrapping_add(*b).rotate_left((i % 7) as u32);
+/// Whether `tag` is a per-member bridge option.
+pub fn is_member_option(tag: &str) -> bool {
+    MEMBER_FLAG_LISTS.contains(&tag) || MEMBER_VALUE_LISTS.contains(&tag)
+}
+
+/// Split `lan:128` into (`lan`, `Some("128")`); plain names have no value.
+fn split_entry(entry: &str) -> (&str, Option<&str>) {
+    match entry.trim().split_once(':') {
+        Some((name, value)) => (name.trim(), Some(value.trim())),
+        None => (entry.trim(), None),
+    }
+}
+
+/// Remove member option entries for interfaces not in `<members>`.
+fn drop_non_member_options(bridged: &mut XmlNode, idx: usize) -> Vec<String> {
+    let members: Vec<String> = bridged
+        .get_text(&["members"])
+        .unwrap_or_default()
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
+    let mut notes = Vec::new();
+    for option in &mut bridged.children {
+        if !is_member_option(&option.tag) {
+            continue;
+        }
+        let Some(text) = option.text.as_deref() else {
+            continue;
+        };
+        let mut kept = Vec::new();
+        for entry in text.split(',').filter(|e| !e.trim().is_empty()) {
+            let name = split_entry(entry).0;
+            if members.contains(&name.to_ascii_lowercase()) {
+                kept.push(entry.trim());
+            } else {
+                notes.push(format!(
+                    "bridge #{idx}: dropped {} entry '{name}', which is not a bridge member",
+                    option.tag
+                ));
+            }
+        }
+        option.text = Some(kept.join(","));
+    }
+    notes
+}
+
+/// Whether `node` has a `<tag>` child with non-empty text.
+fn has_text(node: &XmlNode, tag: &str) -> bool {
+    node.get_text(&[tag]).is_some_and(|v| !v.trim().is_empty())
+}
+
+/// Rename the first `<from>` child to `<to>`, unless `<to>` already exists.
+fn rename_child(node: &mut XmlNode, from: &str, to: &str) {
+    if node.get_child(to).is_some() {
+        node.children.retain(|c| c.tag != from);
+        return;
+    }
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == from) {
+        child.tag = to.to_string();
     }
-    // Fold in the bridge index so identical member lists at different positions
-    // still produce distinct UUIDs.
-    for (i, a) in acc.iter_mut().enumerate() {
-        *a = a.wrapping_add(((idx + i) as u8).rotate_left((idx % 5) as u32));
+}
+
+/// Set the text of an existing `<tag>` child, or append one.
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
     }
-    // Set UUID version 4 nibble and RFC 4122 variant bits.
-    acc[6] = (acc[6] & 0x0f) | 0x40; // version 4
-    acc[8] = (acc[8] & 0x3f) | 0x80; // variant 10xx
-    format!(
-        "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-        acc[0],
-        acc[1],
-        acc[2],
-        acc[3],
-        acc[4],
-        acc[5],
-        acc[6],
-        acc[7],
-        acc[8],
-        acc[9],
-        acc[10],
-        acc[11],
-        acc[12],
-        acc[13],
-        acc[14],
-        acc[15]
-    )
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+/// Returns a mutable reference to the first child with the given tag name.
+fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
+    let idx = node.children.iter().position(|c| c.tag == tag)?;
+    Some(&mut node.children[idx])
 }
 
 #[cfg(test)]
 mod tests {
     use xml_diff_core::parse;
 
-    use super::{normalize_for_opnsense, normalize_for_pfsense};
+    use std::collections::BTreeMap;
+
+    use super::{normalize_for_opnsense, normalize_for_pfsense, rewrite_member_options};
 
     #[test]
     fn adds_uuid_to_opnsense_bridges() {
@@ -127,4 +255,70 @@ mod tests {
             .expect("bridged");
         assert!(!bridged.attributes.contains_key("uuid"));
     }
+
+    #[test]
+    fn maps_stp_options_for_opnsense() {
+        let mut root = parse(
+            br#"<pfsense><bridges><bridged><members>lan,opt1</members><proto>rstp</proto><priority>4096</priority><stp>lan,opt1</stp><ifpriority>lan:64,opt1:128</ifpriority><ifpathcost>opt1:2000</ifpathcost><edge>opt3</edge><ip6linklocal/></bridged></bridges></pfsense>"#,
+        )
+        .expect("parse");
+        let notes = normalize_for_opnsense(&mut root);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains("edge entry 'opt3'"));
+        assert_eq!(
+            root.get_text(&["bridges", "bridged", "enablestp"]),
+            Some("1")
+        );
+        assert_eq!(
+            root.get_text(&["bridges", "bridged", "proto"]),
+            Some("rstp")
+        );
+        assert_eq!(
+            root.get_text(&["bridges", "bridged", "ifpriority"]),
+            Some("lan:64,opt1:128")
+        );
+        assert_eq!(root.get_text(&["bridges", "bridged", "edge"]), Some(""));
+        let bridged = root
+            .get_child("bridges")
+            .and_then(|b| b.get_child("bridged"))
+            .expect("bridged");
+        assert!(bridged.get_child("linklocal").is_some());
+        assert!(bridged.get_child("ip6linklocal").is_none());
+    }
+
+    #[test]
+    fn clears_stp_list_when_opnsense_stp_disabled() {
+        let mut root = parse(
+            br#"<pfsense><bridges><bridged uuid="abc"><members>lan,opt1</members><enablestp/><stp>lan</stp><linklocal>1</linklocal></bridged></bridges></pfsense>"#,
+        )
+        .expect("parse");
+        let notes = normalize_for_pfsense(&mut root);
+        assert_eq!(notes.len(), 1);
+        let bridged = root
+            .get_child("bridges")
+            .and_then(|b| b.get_child("bridged"))
+            .expect("bridged");
+        assert!(bridged.get_child("stp").is_none());
+        assert!(bridged.get_child("enablestp").is_none());
+        assert_eq!(bridged.get_text(&["ip6linklocal"]), Some("1"));
+    }
+
+    #[test]
+    fn rewrites_member_options_with_interface_map() {
+        let mut root = parse(
+            br#"<opnsense><bridges><bridged><members>lan opt2</members><stp>lan,opt2</stp><ifpathcost>opt2:2000</ifpathcost></bridged></bridges></opnsense>"#,
+        )
+        .expect("parse");
+        let mut map = BTreeMap::new();
+        map.insert("opt2".to_string(), "opt1".to_string());
+        rewrite_member_options(&mut root, Some(&map));
+        assert_eq!(
+            root.get_text(&["bridges", "bridged", "stp"]),
+            Some("lan,opt1")
+        );
+        assert_eq!(
+            root.get_text(&["bridges", "bridged", "ifpathcost"]),
+            Some("opt1:2000")
+        );
+    }
 }
@@ -1,5 +1,35 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
 use xml_diff_core::XmlNode;
 
+use crate::uuid_registry::UuidRegistry;
+use crate::verify_certs::certificate_der;
+
+/// Leaf tags whose text names a certificate or CA `refid`.
+const REF_TAGS: &[&str] = &[
+    "ca",
+    "caref",
+    "cert",
+    "certificate",
+    "certRefId",
+    "certref",
+    "ssl-certref",
+];
+
+/// A duplicate certificate or CA folded into its surviving copy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertRemap {
+    /// `ca` or `cert`.
+    pub kind: String,
+    /// Description of the surviving entry.
+    pub descr: String,
+    /// Refid of the removed duplicate.
+    pub from: String,
+    /// Refid references now point at.
+    pub to: String,
+}
+
 /// Transform certificate and CA entries for OPNsense output.
 ///
 /// OPNsense requires `uuid` attributes on `<ca>` and `<cert>` elements.
@@ -7,8 +37,9 @@ use xml_diff_core::XmlNode;
 /// entries that are missing one, seeded from the refid or description so
 /// the same input always produces the same UUID.
 pub fn to_opnsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline: &XmlNode) {
-    normalize_uuid_attrs(out, "ca");
-    normalize_uuid_attrs(out, "cert");
+    let mut registry = UuidRegistry::from_config(out);
+    normalize_uuid_attrs(out, "ca", &mut registry);
+    normalize_uuid_attrs(out, "cert", &mut registry);
 }
 
 /// Transform certificate and CA entries for pfSense output.
@@ -20,82 +51,198 @@ pub fn to_pfsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline: &
     strip_uuid_attrs(out, "cert");
 }
 
-/// Ensure every `<{tag}>` child of `root` has a `uuid` attribute.
+/// Fold certificates and CAs that carry the same certificate under
+/// different refids into one entry.
 ///
-/// Nodes that already have a uuid are left untouched. For nodes without one,
-/// a deterministic UUID is generated using the node's `<refid>` as the primary
-/// seed, falling back to `<descr>`, and finally to `"{tag}:{ordinal}"` if
-/// neither is present. The ordinal counter tracks position so that nodes with
-/// identical seeds still get unique UUIDs.
-fn normalize_uuid_attrs(root: &mut XmlNode, tag: &str) {
-    let mut ordinal = 0usize;
-    for node in root.children.iter_mut().filter(|n| n.tag == tag) {
-        // Skip nodes that already carry a uuid.
-        if node.attributes.contains_key("uuid") {
-            ordinal += 1;
+/// Entries match when their decoded DER bytes are identical. The copy whose
+/// refid already exists in `destination_baseline` survives, otherwise the
+/// first in document order; it takes over a private key only a duplicate
+/// had. References to a removed refid are rewritten to the survivor. CAs are
+/// reconciled before certificates so `caref`s settle first.
+pub fn reconcile_duplicates(out: &mut XmlNode, destination_baseline: &XmlNode) -> Vec<CertRemap> {
+    let mut remaps = Vec::new();
+    for tag in ["ca", "cert"] {
+        let kept: Vec<&str> = destination_baseline
+            .get_children(tag)
+            .into_iter()
+            .filter_map(|n| n.get_text(&["refid"]))
+            .collect();
+        let found = duplicate_groups(out, tag, &kept);
+        if found.is_empty() {
             continue;
         }
-        // Build a seed string: prefer refid, then descr, then a positional fallback.
-        let seed = node
+        let table: BTreeMap<String, String> = found
+            .iter()
+            .map(|r| (r.from.clone(), r.to.clone()))
+            .collect();
+        adopt_private_keys(out, tag, &table);
+        out.children.retain(|n| {
+            n.tag != tag
+                || !n
+                    .get_text(&["refid"])
+                    .is_some_and(|refid| table.contains_key(refid))
+        });
+        rewrite_refs(out, &table);
+        remaps.extend(found);
+    }
+    remaps
+}
+
+/// Remove private keys from every certificate and CA, keeping the
+/// certificates themselves and every reference to them.
+///
+/// For targets whose keys are provisioned separately (an HSM, or ACME on the
+/// target). Returns the refids of the entries that lost a key.
+pub fn strip_private_keys(out: &mut XmlNode) -> Vec<String> {
+    let mut stripped = Vec::new();
+    for node in out
+        .children
+        .iter_mut()
+        .filter(|n| n.tag == "ca" || n.tag == "cert")
+    {
+        if !node.get_child("prv").is_some_and(has_text) {
+            continue;
+        }
+        node.children.retain(|c| c.tag != "prv");
+        stripped.push(node.get_text(&["refid"]).unwrap_or("").to_string());
+    }
+    stripped
+}
+
+/// Remaps for every `<{tag}>` entry whose certificate an earlier, or
+/// baseline-owned, entry already carries.
+fn duplicate_groups(root: &XmlNode, tag: &str, kept: &[&str]) -> Vec<CertRemap> {
+    let mut groups: BTreeMap<Vec<u8>, Vec<(String, String)>> = BTreeMap::new();
+    let mut order = Vec::new();
+    for node in root.children.iter().filter(|n| n.tag == tag) {
+        let Some(refid) = node
             .get_text(&["refid"])
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .or_else(|| {
-                node.get_text(&["descr"])
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-            })
-            .unwrap_or_else(|| format!("{tag}:{ordinal}"));
-        node.attributes
-            .insert("uuid".to_string(), stable_uuid(seed.as_bytes(), ordinal));
-        ordinal += 1;
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+        else {
+            continue;
+        };
+        let Some(der) = certificate_der(node) else {
+            continue;
+        };
+        let descr = node.get_text(&["descr"]).unwrap_or("").trim().to_string();
+        let group = groups.entry(der.clone()).or_default();
+        if group.iter().any(|(r, _)| r == refid) {
+            continue;
+        }
+        if group.is_empty() {
+            order.push(der);
+        }
+        group.push((refid.to_string(), descr));
+    }
+
+    let mut out = Vec::new();
+    for der in order {
+        let group = &groups[&der];
+        if group.len() < 2 {
+            continue;
+        }
+        let survivor = group
+            .iter()
+            .find(|(r, _)| kept.contains(&r.as_str()))
+            .unwrap_or(&group[0]);
+        for (refid, _) in group.iter().filter(|(r, _)| r != &survivor.0) {
+            out.push(CertRemap {
+                kind: tag.to_string(),
+                descr: survivor.1.clone(),
+                from: refid.clone(),
+                to: survivor.0.clone(),
+            });
+        }
     }
+    out
 }
 
-/// Remove the `uuid` attribute from every `<{tag}>` child of `root`.
-fn strip_uuid_attrs(root: &mut XmlNode, tag: &str) {
+/// Copy a duplicate's `<prv>` onto a survivor that has none.
+fn adopt_private_keys(root: &mut XmlNode, tag: &str, table: &BTreeMap<String, String>) {
+    let mut keys: BTreeMap<String, XmlNode> = BTreeMap::new();
+    for node in root.children.iter().filter(|n| n.tag == tag) {
+        let Some(to) = node.get_text(&["refid"]).and_then(|r| table.get(r)) else {
+            continue;
+        };
+        if let Some(prv) = node.get_child("prv").filter(|p| has_text(p)) {
+            keys.entry(to.clone()).or_insert_with(|| prv.clone());
+        }
+    }
     for node in root.children.iter_mut().filter(|n| n.tag == tag) {
-        node.attributes.remove("uuid");
+        let Some(prv) = node.get_text(&["refid"]).and_then(|r| keys.remove(r)) else {
+            continue;
+        };
+        if node.get_child("prv").is_some_and(has_text) {
+            continue;
+        }
+        node.children.retain(|c| c.tag != "prv");
+        node.children.push(prv);
     }
 }
 
-/// Generate a deterministic UUID-formatted string from a byte seed and index.
-///
-/// The first segment is derived from a CRC-32 of the seed XORed with the index,
-/// giving each entry a unique but reproducible identifier. The trailing segment
-/// encodes the 1-based index. Middle segments are zeroed since we only need
-/// uniqueness within a single config file, not RFC 4122 compliance.
-fn stable_uuid(seed: &[u8], idx: usize) -> String {
-    format!(
-        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
-        crc32(seed) ^ (idx as u32),
-        0,
-        0,
-        0,
-        (idx as u64) + 1
-    )
+fn has_text(node: &XmlNode) -> bool {
+    node.text.as_deref().is_some_and(|t| !t.trim().is_empty())
 }
 
-/// Compute the CRC-32 (ISO 3309 / ITU-T V.42) checksum of `input`.
-///
-/// Uses the standard polynomial 0xEDB88320 (bit-reversed representation).
-fn crc32(input: &[u8]) -> u32 {
-    let mut crc = 0xffff_ffffu32;
-    for b in input {
-        crc ^= *b as u32;
-        for _ in 0..8 {
-            let mask = (crc & 1).wrapping_neg(); // all-ones if LSB set, else all-zeros
-            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+/// Point every leaf certificate reference in `node` at its survivor.
+fn rewrite_refs(node: &mut XmlNode, table: &BTreeMap<String, String>) {
+    for child in &mut node.children {
+        if child.children.is_empty() && REF_TAGS.contains(&child.tag.as_str()) {
+            if let Some(to) = child.text.as_deref().and_then(|t| table.get(t.trim())) {
+                child.text = Some(to.clone());
+            }
+        } else {
+            rewrite_refs(child, table);
         }
     }
-    !crc // final inversion
+}
+
+/// Ensure every `<{tag}>` child of `root` has a `uuid` attribute.
+///
+/// Nodes that already have a uuid are left untouched. New UUIDs are keyed by
+/// the node's `<refid>`, falling back to `<descr>`, then to its position.
+fn normalize_uuid_attrs(root: &mut XmlNode, tag: &str, registry: &mut UuidRegistry) {
+    let scope = format!("system-{tag}");
+    registry.assign_missing(root, tag, &scope, |idx, node| {
+        ["refid", "descr"]
+            .iter()
+            .filter_map(|f| node.get_text(&[f]).map(str::trim))
+            .find(|s| !s.is_empty())
+            .map_or_else(|| idx.to_string(), ToString::to_string)
+    });
+}
+
+/// Remove the `uuid` attribute from every `<{tag}>` child of `root`.
+fn strip_uuid_attrs(root: &mut XmlNode, tag: &str) {
+    for node in root.children.iter_mut().filter(|n| n.tag == tag) {
+        node.attributes.remove("uuid");
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
+    use base64::Engine;
     use xml_diff_core::parse;
 
-    use super::{to_opnsense, to_pfsense};
+    use super::{reconcile_duplicates, strip_private_keys, to_opnsense, to_pfsense};
+
+    fn blob(name: &str) -> String {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../fixtures/certs")
+            .join(name);
+        let pem = std::fs::read(&path).expect("read fixture");
+        base64::engine::general_purpose::STANDARD.encode(pem)
+    }
+
+    fn refids<'a>(root: &'a xml_diff_core::XmlNode, tag: &str) -> Vec<&'a str> {
+        root.get_children(tag)
+            .into_iter()
+            .filter_map(|n| n.get_text(&["refid"]))
+            .collect()
+    }
 
     #[test]
     fn adds_uuid_to_ca_and_cert_for_opnsense() {
@@ -128,4 +275,101 @@ mod tests {
         assert!(!ca.attributes.contains_key("uuid"));
         assert!(!cert.attributes.contains_key("uuid"));
     }
+
+    #[test]
+    fn folds_duplicates_onto_baseline_copy_and_rewrites_refs() {
+        let baseline = parse(
+            format!(
+                "<opnsense><ca><refid>ca-base</refid><crt>{}</crt></ca></opnsense>",
+                blob("ca.crt")
+            )
+            .as_bytes(),
+        )
+        .expect("parse");
+        let mut out = parse(
+            format!(
+                "<opnsense><ca><refid>ca-base</refid><descr>Base CA</descr><crt>{ca}</crt></ca>\
+                 <ca><refid>ca-src</refid><descr>Source CA</descr><crt>{ca}</crt><prv>{key}</prv></ca>\
+                 <cert><refid>c1</refid><caref>ca-src</caref><crt>{leaf}</crt></cert>\
+                 <cert><refid>c2</refid><caref>ca-src</caref><crt>{leaf}</crt></cert>\
+                 <openvpn><openvpn-server><caref>ca-src</caref><certref>c2</certref></openvpn-server></openvpn>\
+                 <OPNsense><OpenVPN><Instances><Instance><ca>ca-src</ca><cert>c2</cert></Instance></Instances></OpenVPN></OPNsense></opnsense>",
+                ca = blob("ca.crt"),
+                key = blob("ca.key"),
+                leaf = blob("leaf.crt"),
+            )
+            .as_bytes(),
+        )
+        .expect("parse");
+
+        let remaps = reconcile_duplicates(&mut out, &baseline);
+        let table: Vec<(&str, &str, &str)> = remaps
+            .iter()
+            .map(|r| (r.kind.as_str(), r.from.as_str(), r.to.as_str()))
+            .collect();
+        assert_eq!(
+            table,
+            vec![("ca", "ca-src", "ca-base"), ("cert", "c2", "c1")]
+        );
+        assert_eq!(remaps[0].descr, "Base CA");
+        assert_eq!(refids(&out, "ca"), vec!["ca-base"]);
+        assert_eq!(refids(&out, "cert"), vec!["c1"]);
+
+        let ca = out.get_child("ca").expect("ca");
+        assert!(ca.get_text(&["prv"]).is_some(), "survivor adopts the key");
+        assert_eq!(out.get_text(&["cert", "caref"]), Some("ca-base"));
+        assert_eq!(
+            out.get_text(&["openvpn", "openvpn-server", "caref"]),
+            Some("ca-base")
+        );
+        assert_eq!(
+            out.get_text(&["openvpn", "openvpn-server", "certref"]),
+            Some("c1")
+        );
+        let instance = ["OPNsense", "OpenVPN", "Instances", "Instance"];
+        assert_eq!(
+            out.get_text(&[instance.as_slice(), &["ca"]].concat()),
+            Some("ca-base")
+        );
+        assert_eq!(
+            out.get_text(&[instance.as_slice(), &["cert"]].concat()),
+            Some("c1")
+        );
+    }
+
+    #[test]
+    fn leaves_distinct_and_undecodable_entries_alone() {
+        let baseline = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = parse(
+            format!(
+                "<opnsense><cert><refid>c1</refid><crt>{}</crt></cert>\
+                 <cert><refid>c2</refid><crt>{}</crt></cert>\
+                 <cert><refid>c3</refid><crt>REDACTED</crt></cert>\
+                 <cert><refid>c4</refid><crt>REDACTED</crt></cert></opnsense>",
+                blob("leaf.crt"),
+                blob("expired.crt")
+            )
+            .as_bytes(),
+        )
+        .expect("parse");
+
+        assert!(reconcile_duplicates(&mut out, &baseline).is_empty());
+        assert_eq!(refids(&out, "cert"), vec!["c1", "c2", "c3", "c4"]);
+    }
+
+    #[test]
+    fn strips_keys_but_keeps_certificates() {
+        let mut root = parse(
+            br#"<pfsense><ca><refid>ca1</refid><crt>C</crt><prv>K</prv></ca><cert><refid>c1</refid><caref>ca1</caref><crt>C</crt><prv>K</prv></cert><cert><refid>c2</refid><crt>C</crt></cert></pfsense>"#,
+        )
+        .expect("parse");
+        assert_eq!(strip_private_keys(&mut root), vec!["ca1", "c1"]);
+        for tag in ["ca", "cert"] {
+            for node in root.get_children(tag) {
+                assert!(node.get_child("prv").is_none());
+                assert_eq!(node.get_text(&["crt"]), Some("C"));
+            }
+        }
+        assert_eq!(root.get_text(&["cert", "caref"]), Some("ca1"));
+    }
 }
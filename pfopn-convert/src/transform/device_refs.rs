@@ -146,7 +146,13 @@ fn rewrite_tree(
 fn should_skip_rewrite(path: &[String]) -> bool {
     // Preserve the PPP interface reference itself (e.g., pppoe0) when it appears
     // inside <ppps><ppp><if>. Only the physical <ports> element should be rewritten.
+    // LAGG device names are likewise kept; only their member lists are hardware.
     path.ends_with(&["ppps".to_string(), "ppp".to_string(), "if".to_string()])
+        || path.ends_with(&[
+            "laggs".to_string(),
+            "lagg".to_string(),
+            "laggif".to_string(),
+        ])
 }
 
 fn is_pppoe_ifname(v: &str) -> bool {
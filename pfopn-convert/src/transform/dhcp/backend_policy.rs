@@ -1,4 +1,5 @@
 use anyhow::{bail, Result};
+use serde::Serialize;
 use xml_diff_core::XmlNode;
 
 use crate::backend_detect::detect_dhcp_backend;
@@ -21,7 +22,8 @@ pub enum RequestedDhcpBackend {
 ///
 /// After analyzing the requested backend, source config, and target config,
 /// this represents the final backend decision.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EffectiveDhcpBackend {
     /// Use Kea DHCP format
     Kea,
@@ -219,12 +221,13 @@ pub fn enforce_output_backend(
     if to_platform == "opnsense" {
         match backend {
             EffectiveDhcpBackend::Kea => {
-                if preserve_ipv6_legacy {
-                    root.children.retain(|c| c.tag != "dhcpd");
-                } else {
-                    root.children
-                        .retain(|c| c.tag != "dhcpd" && c.tag != "dhcpdv6" && c.tag != "dhcpd6");
+                root.children.retain(|c| c.tag != "dhcpd");
+                if !preserve_ipv6_legacy {
+                    // Router advertisements still run from the legacy fields
+                    super::router_adv::retain_ra_only(root);
                 }
+                // Kea-era OPNsense relays only through the DHCRelay model.
+                super::relay::drop_legacy_sections(root);
                 let opn = ensure_child_mut(root, "OPNsense");
                 ensure_child_mut(opn, "Kea");
             }
@@ -242,9 +245,10 @@ pub fn enforce_output_backend(
                 if root.get_child("kea").is_none() {
                     root.children.push(XmlNode::new("kea"));
                 }
-                // Remove legacy ISC sections that conflict with Kea backend
-                root.children
-                    .retain(|c| c.tag != "dhcpd" && c.tag != "dhcpdv6" && c.tag != "dhcpd6");
+                // Remove legacy ISC sections that conflict with Kea backend;
+                // radvd still reads its settings from <dhcpdv6>
+                root.children.retain(|c| c.tag != "dhcpd");
+                super::router_adv::retain_ra_only(root);
             }
             EffectiveDhcpBackend::Isc => {
                 set_or_insert_top_text(root, "dhcpbackend", "isc");
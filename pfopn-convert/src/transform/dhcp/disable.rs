@@ -1,5 +1,7 @@
 use xml_diff_core::XmlNode;
 
+use crate::transform::booleans::set_flag;
+
 /// Disable DHCP backends in-place on a generated config tree.
 ///
 /// This is intentionally opt-in and should only be called when the user
@@ -21,8 +23,12 @@ pub fn apply(root: &mut XmlNode) {
 /// - `<enabled>0</enabled>`
 /// - `<disabled>1</disabled>`
 ///
+/// pfSense reads these as presence tags, so on a pfSense tree `<enable>` and
+/// `<enabled>` are removed and `<disabled>` is left empty instead.
+///
 /// Skips children whose tags start with '#' (comments).
 fn disable_legacy_section(root: &mut XmlNode, section: &str) {
+    let platform = root.tag.clone();
     let Some(node) = child_mut(root, section) else {
         return;
     };
@@ -30,6 +36,12 @@ fn disable_legacy_section(root: &mut XmlNode, section: &str) {
         if iface.tag.starts_with('#') {
             continue;
         }
+        if platform == "pfsense" {
+            set_flag(iface, "enable", false, &platform);
+            set_flag(iface, "enabled", false, &platform);
+            set_flag(iface, "disabled", true, &platform);
+            continue;
+        }
         set_or_insert_text_child(iface, "enable", "0");
         set_or_insert_text_child(iface, "enabled", "0");
         set_or_insert_text_child(iface, "disabled", "1");
@@ -127,7 +139,7 @@ mod tests {
     #[test]
     fn disables_legacy_dhcp_sections() {
         let mut root = parse(
-            br#"<pfsense><dhcpd><lan><enable>1</enable></lan></dhcpd><dhcpdv6><lan/></dhcpdv6></pfsense>"#,
+            br#"<opnsense><dhcpd><lan><enable>1</enable></lan></dhcpd><dhcpdv6><lan/></dhcpdv6></opnsense>"#,
         )
         .expect("parse");
         apply(&mut root);
@@ -146,6 +158,20 @@ mod tests {
         assert_eq!(lan6.get_text(&["disabled"]), Some("1"));
     }
 
+    #[test]
+    fn removes_pfsense_presence_enable_flags() {
+        let mut root = parse(br#"<pfsense><dhcpd><lan><enable></enable></lan></dhcpd></pfsense>"#)
+            .expect("parse");
+        apply(&mut root);
+
+        let lan = root
+            .get_child("dhcpd")
+            .and_then(|n| n.get_child("lan"))
+            .expect("dhcpd lan");
+        assert!(lan.get_child("enable").is_none());
+        assert!(lan.get_child("disabled").is_some());
+    }
+
     #[test]
     fn disables_opnsense_kea_general_flags() {
         let mut root = parse(
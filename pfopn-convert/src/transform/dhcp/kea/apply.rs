@@ -188,8 +188,11 @@ pub(crate) fn apply_isc_reservations_v6(
 /// - `domain_name` — Domain name
 /// - `domain_search` — Domain search list
 /// - `ntp_servers` — NTP servers
+/// - `tftp_server_name`, `boot_file_name`, and other fields from numbered
+///   options, plus the subnet's `next_server`
 ///
 /// Options are applied per-subnet based on the interface-to-subnet mapping.
+/// Interfaces with nothing Kea can carry are skipped.
 ///
 /// # Arguments
 ///
@@ -211,7 +214,7 @@ pub(crate) fn apply_isc_options_v4_to_subnets(
 ) -> Result<usize> {
     let mut applied = 0;
     let subnets = ensure_child_mut(dhcp4, "subnets");
-    for (iface, opts) in opts_by_iface {
+    for (iface, opts) in opts_by_iface.iter().filter(|(_, o)| o.has_values()) {
         let Some(uuid) = subnet_uuid_by_iface.get(iface) else {
             anyhow::bail!(
                 "cannot apply DHCPv4 options for iface '{}': no matching Kea subnet",
@@ -239,6 +242,12 @@ pub(crate) fn apply_isc_options_v4_to_subnets(
             if !opts.ntp_servers.is_empty() {
                 set_or_insert_text_child(option_data, "ntp_servers", &opts.ntp_servers.join(","));
             }
+            for (field, value) in &opts.extra {
+                set_or_insert_text_child(option_data, field, value);
+            }
+            if let Some(v) = &opts.next_server {
+                set_or_insert_text_child(subnet, "next_server", v);
+            }
             applied += 1;
         } else {
             anyhow::bail!(
@@ -258,7 +267,9 @@ pub(crate) fn apply_isc_options_v4_to_subnets(
 /// - `dns_servers` — DNS servers
 /// - `domain_search` — Domain search list
 ///
+/// Numbered options fill the same fields when no dedicated setting did.
 /// Options are applied per-subnet based on the interface-to-subnet mapping.
+/// Interfaces with nothing Kea can carry are skipped.
 ///
 /// # Arguments
 ///
@@ -280,7 +291,7 @@ pub(crate) fn apply_isc_options_v6_to_subnets(
 ) -> Result<usize> {
     let mut applied = 0;
     let subnets = ensure_child_mut(dhcp6, "subnets");
-    for (iface, opts) in opts_by_iface {
+    for (iface, opts) in opts_by_iface.iter().filter(|(_, o)| o.has_values()) {
         let Some(uuid) = subnet_uuid_by_iface.get(iface) else {
             anyhow::bail!(
                 "cannot apply DHCPv6 options for iface '{}': no matching Kea subnet",
@@ -295,6 +306,9 @@ pub(crate) fn apply_isc_options_v6_to_subnets(
             if let Some(v) = &opts.domain_search {
                 set_or_insert_text_child(option_data, "domain_search", v);
             }
+            for (field, value) in &opts.extra {
+                set_or_insert_text_child(option_data, field, value);
+            }
             applied += 1;
         } else {
             anyhow::bail!(
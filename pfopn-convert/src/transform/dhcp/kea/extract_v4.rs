@@ -5,7 +5,10 @@ use xml_diff_core::XmlNode;
 
 use super::extract_common::isc_iface_enabled;
 use super::model::{OptsV4, StaticMapV4};
+use super::numbered_options::{self, Family};
+use super::scope_settings;
 use super::util::normalize_domain_search;
+use crate::model::DhcpStaticMap;
 
 /// Extract all IPv4 static mappings (DHCP reservations) from ISC DHCP config.
 ///
@@ -25,37 +28,19 @@ pub(crate) fn extract_isc_staticmaps_v4(root: &XmlNode) -> Vec<StaticMapV4> {
         }
         let iface_name = iface.tag.clone();
         for staticmap in iface.get_children("staticmap") {
-            let Some(mac) = staticmap.get_text(&["mac"]).map(str::trim) else {
+            let Ok(staticmap) = DhcpStaticMap::from_node(staticmap) else {
                 continue;
             };
-            let Some(ip) = staticmap.get_text(&["ipaddr"]).map(str::trim) else {
+            let (Some(mac), Some(ip)) = (staticmap.mac, staticmap.ipaddr) else {
                 continue;
             };
-            if mac.is_empty() || ip.is_empty() {
-                continue;
-            }
-            let hostname = staticmap
-                .get_text(&["hostname"])
-                .map(str::trim)
-                .unwrap_or("")
-                .to_string();
-            let cid = staticmap
-                .get_text(&["cid"])
-                .map(str::trim)
-                .unwrap_or("")
-                .to_string();
-            let descr = staticmap
-                .get_text(&["descr"])
-                .map(str::trim)
-                .unwrap_or("")
-                .to_string();
             out.push(StaticMapV4 {
                 iface: iface_name.clone(),
-                mac: mac.to_string(),
-                ipaddr: ip.to_string(),
-                hostname,
-                cid,
-                descr,
+                mac,
+                ipaddr: ip,
+                hostname: staticmap.hostname.unwrap_or_default(),
+                cid: staticmap.cid.unwrap_or_default(),
+                descr: staticmap.descr.unwrap_or_default(),
             });
         }
     }
@@ -144,6 +129,12 @@ pub(crate) fn extract_iface_networks_v4(root: &XmlNode) -> HashMap<String, (Ipv4
 /// - `<domain>` — Domain name
 /// - `<domainsearchlist>` — Domain search list
 /// - `<ntpserver>` — NTP servers
+/// - `<tftp>` / `<filename>` — TFTP server and boot file (options 66/67)
+/// - `<nextserver>` — Next server (`siaddr`)
+/// - `<numberoptions>` — Numbered options (see [`numbered_options::collect`])
+///
+/// Per-scope settings Kea cannot carry (dynamic DNS, architecture-specific
+/// boot files) are described in `unsupported` (see [`scope_settings`]).
 ///
 /// Returns a map of interface name → DHCP options.
 /// Only includes interfaces that have at least one option configured.
@@ -209,15 +200,49 @@ pub(crate) fn extract_isc_options_v4(root: &XmlNode) -> HashMap<String, OptsV4>
                         opts.ntp_servers.push(v.to_string());
                     }
                 }
+                "tftp" | "filename" => {
+                    if let Some(v) = child
+                        .text
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                    {
+                        let field = if child.tag == "tftp" {
+                            "tftp_server_name"
+                        } else {
+                            "boot_file_name"
+                        };
+                        opts.extra.insert(field, v.to_string());
+                    }
+                }
+                "nextserver" => {
+                    if let Some(v) = child
+                        .text
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                    {
+                        opts.next_server = Some(v.to_string());
+                    }
+                }
                 _ => {}
             }
         }
-        if !opts.dns_servers.is_empty()
-            || opts.routers.is_some()
-            || opts.domain_name.is_some()
-            || opts.domain_search.is_some()
-            || !opts.ntp_servers.is_empty()
-        {
+        numbered_options::collect(iface, Family::V4, &mut opts.extra, &mut opts.unsupported);
+        opts.unsupported
+            .extend(scope_settings::unsupported_v4(iface));
+        for (field, dedicated) in [
+            ("domain_name_servers", !opts.dns_servers.is_empty()),
+            ("routers", opts.routers.is_some()),
+            ("domain_name", opts.domain_name.is_some()),
+            ("domain_search", opts.domain_search.is_some()),
+            ("ntp_servers", !opts.ntp_servers.is_empty()),
+        ] {
+            if dedicated {
+                opts.extra.remove(field);
+            }
+        }
+        if opts.has_values() || !opts.unsupported.is_empty() {
             out.insert(iface.tag.clone(), opts);
         }
     }
@@ -229,7 +254,7 @@ pub(crate) fn extract_isc_options_v4(root: &XmlNode) -> HashMap<String, OptsV4>
 /// An interface "demands" DHCP if it has any of:
 /// - Static mappings (reservations)
 /// - Dynamic ranges (pools)
-/// - DHCP options configured
+/// - DHCP options Kea can carry
 ///
 /// Returns a sorted set of interface names that need Kea subnets created.
 pub(crate) fn demanded_ifaces_v4(
@@ -244,8 +269,10 @@ pub(crate) fn demanded_ifaces_v4(
     for k in ranges.keys() {
         out.insert(k.clone());
     }
-    for k in opts.keys() {
-        out.insert(k.clone());
+    for (k, o) in opts {
+        if o.has_values() {
+            out.insert(k.clone());
+        }
     }
     out
 }
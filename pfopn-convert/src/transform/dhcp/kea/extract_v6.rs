@@ -5,6 +5,8 @@ use xml_diff_core::XmlNode;
 
 use super::extract_common::isc_iface_enabled;
 use super::model::{OptsV6, StaticMapV6};
+use super::numbered_options::{self, Family};
+use super::scope_settings;
 use super::util::{ipv6_mask, normalize_domain_search};
 
 /// Extract all IPv6 static mappings (DHCP reservations) from ISC DHCP config.
@@ -172,6 +174,10 @@ pub(crate) fn collect_prefixrange_intent(root: &XmlNode) -> HashMap<String, bool
 /// Collects DHCPv6 options configured for each enabled interface:
 /// - `<dnsserver>` — DNS servers
 /// - `<domainsearchlist>` — Domain search list
+/// - `<numberoptions>` — Numbered options (see [`numbered_options::collect`])
+///
+/// Settings Kea cannot carry (dynamic DNS, NTP servers, boot file URL) are
+/// described in `unsupported` (see [`scope_settings`]).
 ///
 /// Returns a map of interface name → DHCP options.
 /// Only includes interfaces that have at least one option configured.
@@ -208,7 +214,16 @@ pub(crate) fn extract_isc_options_v6(root: &XmlNode) -> HashMap<String, OptsV6>
                     _ => {}
                 }
             }
-            if !opts.dns_servers.is_empty() || opts.domain_search.is_some() {
+            numbered_options::collect(iface, Family::V6, &mut opts.extra, &mut opts.unsupported);
+            opts.unsupported
+                .extend(scope_settings::unsupported_v6(iface));
+            if !opts.dns_servers.is_empty() {
+                opts.extra.remove("dns_servers");
+            }
+            if opts.domain_search.is_some() {
+                opts.extra.remove("domain_search");
+            }
+            if opts.has_values() || !opts.unsupported.is_empty() {
                 let entry = out.entry(iface.tag.clone()).or_insert_with(OptsV6::default);
                 merge_opts_v6(entry, &opts);
             }
@@ -222,7 +237,7 @@ pub(crate) fn extract_isc_options_v6(root: &XmlNode) -> HashMap<String, OptsV6>
 /// An interface "demands" DHCPv6 if it has any of:
 /// - Static mappings (reservations)
 /// - Dynamic ranges (pools)
-/// - DHCP options configured
+/// - DHCP options Kea can carry
 /// - Prefix delegation configured
 ///
 /// Returns a sorted set of interface names that need Kea subnets created.
@@ -239,8 +254,10 @@ pub(crate) fn demanded_ifaces_v6(
     for k in ranges.keys() {
         out.insert(k.clone());
     }
-    for k in opts.keys() {
-        out.insert(k.clone());
+    for (k, o) in opts {
+        if o.has_values() {
+            out.insert(k.clone());
+        }
     }
     for k in prefix_intent.keys() {
         out.insert(k.clone());
@@ -265,7 +282,8 @@ fn dhcpv6_legacy_sections(root: &XmlNode) -> Vec<&XmlNode> {
 
 /// Merge IPv6 DHCP options from source into destination.
 ///
-/// Combines DNS servers (avoiding duplicates) and domain search list.
+/// Combines DNS servers (avoiding duplicates), domain search list, numbered
+/// option fields, and unsupported option notes.
 /// Used when multiple config sections provide options for the same interface.
 fn merge_opts_v6(dst: &mut OptsV6, src: &OptsV6) {
     for dns in &src.dns_servers {
@@ -276,4 +294,8 @@ fn merge_opts_v6(dst: &mut OptsV6, src: &OptsV6) {
     if dst.domain_search.is_none() {
         dst.domain_search = src.domain_search.clone();
     }
+    for (field, value) in &src.extra {
+        dst.extra.entry(field).or_insert_with(|| value.clone());
+    }
+    dst.unsupported.extend(src.unsupported.iter().cloned());
 }
@@ -31,6 +31,10 @@
 //!
 //! 4. **Apply options:**
 //!    - Converts ISC DHCP options to Kea option-data format
+//!    - Carries TFTP server, boot file, and next server (PXE) to each subnet
+//!    - Maps numbered custom options onto Kea option-data fields and warns for
+//!      options and per-scope settings (dynamic DNS, per-architecture boot
+//!      files) Kea cannot represent
 //!    - Applies per-subnet or globally as appropriate
 //!
 //! ## Data Structure Differences
@@ -74,6 +78,8 @@
 //!
 //! - **extract_v4** — Extract IPv4 DHCP config from ISC format
 //! - **extract_v6** — Extract IPv6 DHCP config from ISC format
+//! - **numbered_options** — Translate ISC `<numberoptions>` to Kea option names
+//! - **scope_settings** — Report per-scope ISC settings Kea cannot carry
 //! - **apply** — Apply extracted config to Kea structure
 //! - **subnets** — Subnet creation and management utilities
 //! - **util** — Common utilities for Kea config manipulation
@@ -84,14 +90,21 @@ use std::collections::HashMap;
 use anyhow::Result;
 use xml_diff_core::XmlNode;
 
+use crate::uuid_registry::UuidRegistry;
+
 mod apply;
 mod extract_common;
 mod extract_v4;
 mod extract_v6;
 mod model;
+mod numbered_options;
+mod scope_settings;
 mod subnets;
 mod util;
 
+pub(crate) use extract_common::isc_iface_enabled;
+pub(crate) use numbered_options::option_value;
+
 #[cfg(test)]
 mod tests;
 
@@ -172,9 +185,10 @@ pub struct KeaMigrationStats {
 ///
 /// ## UUID Generation
 ///
-/// Kea requires UUIDs for subnets and reservations. This function generates deterministic
-/// UUIDs using `migrated-subnet4-{id}`, `migrated-subnet6-{id}`, etc. to ensure idempotent
-/// conversions.
+/// Kea requires UUIDs for subnets and reservations. New subnets get a UUID keyed by their
+/// CIDR and new reservations one keyed by their address, both issued through
+/// [`UuidRegistry`] so conversions are idempotent and never collide with UUIDs already in
+/// the output.
 ///
 /// # Arguments
 ///
@@ -195,7 +209,7 @@ pub fn migrate_isc_to_kea_opnsense(
     source: &XmlNode,
 ) -> Result<KeaMigrationStats> {
     let mut stats = KeaMigrationStats::default();
-    let mut next_id = util::next_synthetic_id(1);
+    let mut registry = UuidRegistry::from_config(out);
 
     // ====== IPv4 Migration ======
     {
@@ -204,11 +218,20 @@ pub fn migrate_isc_to_kea_opnsense(
         let ranges_v4 = extract_v4::extract_isc_ranges_v4(source); // Dynamic address pools
         let iface_networks_v4 = extract_v4::extract_iface_networks_v4(source); // Interface IP/subnet
         let opts_v4 = extract_v4::extract_isc_options_v4(source); // DHCP options (DNS, etc.)
+        push_unsupported_options(&mut stats, opts_v4.values().map(|o| &o.unsupported));
 
         // Determine which interfaces actually need DHCP (have mappings, ranges, or options)
         let demanded_ifaces_v4 = extract_v4::demanded_ifaces_v4(&maps_v4, &ranges_v4, &opts_v4);
         let mut subnet_uuid_by_iface_v4 = HashMap::new();
 
+        // Kea has no per-subnet static ARP or MAC deny setting; report what is dropped
+        for (iface, flag) in super::mac_control::kea_unsupported_flags(source) {
+            stats.warnings.push(MigrationWarning {
+                message: format!("{iface}: {flag} has no Kea equivalent and was not migrated"),
+                severity: MigrationSeverity::Warning,
+            });
+        }
+
         // Step 2: Ensure Kea structure exists in output
         let kea = util::ensure_opnsense_kea(out);
         let dhcp4 = util::ensure_child_mut(kea, "dhcp4");
@@ -237,9 +260,8 @@ pub fn migrate_isc_to_kea_opnsense(
                 continue; // Reuse existing subnet
             }
 
-            // Create new subnet with deterministic UUID
-            let uuid = format!("migrated-subnet4-{next_id}");
-            next_id += 1;
+            // Create new subnet with a UUID keyed by its CIDR
+            let uuid = registry.uuid_for("kea-subnet4", &cidr);
             let mut subnet = XmlNode::new("subnet4");
             subnet.attributes.insert("uuid".to_string(), uuid.clone());
             util::push_text_child(&mut subnet, "subnet", &cidr);
@@ -247,11 +269,14 @@ pub fn migrate_isc_to_kea_opnsense(
             subnets::push_option_data_v4_defaults(&mut subnet);
             util::push_text_child(&mut subnet, "match-client-id", "1");
             if let Some(ranges) = ranges_v4.get(iface) {
-                let pools = ranges
-                    .iter()
-                    .map(|(from, to)| format!("{from}-{to}"))
-                    .collect::<Vec<_>>()
-                    .join(",");
+                // Drop ranges Kea would refuse to load rather than write them out
+                let pools = subnets::usable_pools_v4(
+                    iface,
+                    ranges,
+                    (*network, *prefix),
+                    &mut stats.warnings,
+                )
+                .join(",");
                 if !pools.is_empty() {
                     util::push_text_child(&mut subnet, "pools", &pools);
                 }
@@ -272,6 +297,10 @@ pub fn migrate_isc_to_kea_opnsense(
             apply::apply_isc_reservations_v4(dhcp4, &maps_v4, &subnet_uuid_by_iface_v4)?;
         stats.reservations_added_v4 += added_v4;
         stats.reservations_skipped_conflict_v4 += skipped_v4;
+        let reservations = util::ensure_child_mut(dhcp4, "reservations");
+        registry.assign_missing(reservations, "reservation", "kea-reservation4", |_, r| {
+            r.get_text(&["ip_address"]).unwrap_or("").trim().to_string()
+        });
 
         // Step 6: Enable Kea DHCPv4 on interfaces that were migrated
         if !subnet_uuid_by_iface_v4.is_empty() || stats.reservations_added_v4 > 0 {
@@ -286,6 +315,7 @@ pub fn migrate_isc_to_kea_opnsense(
         let ranges_v6 = extract_v6::extract_isc_ranges_v6(source);
         let iface_networks_v6 = extract_v6::extract_iface_networks_v6(source);
         let opts_v6 = extract_v6::extract_isc_options_v6(source);
+        push_unsupported_options(&mut stats, opts_v6.values().map(|o| &o.unsupported));
         let prefixrange_intent = extract_v6::collect_prefixrange_intent(source);
         let demanded_ifaces_v6 =
             extract_v6::demanded_ifaces_v6(&maps_v6, &ranges_v6, &opts_v6, &prefixrange_intent);
@@ -321,8 +351,7 @@ pub fn migrate_isc_to_kea_opnsense(
                 continue;
             }
 
-            let uuid = format!("migrated-subnet6-{next_id}");
-            next_id += 1;
+            let uuid = registry.uuid_for("kea-subnet6", &cidr);
             let mut subnet = XmlNode::new("subnet6");
             subnet.attributes.insert("uuid".to_string(), uuid.clone());
             util::push_text_child(&mut subnet, "subnet", &cidr);
@@ -362,6 +391,10 @@ pub fn migrate_isc_to_kea_opnsense(
         )?;
         stats.reservations_added_v6 += added_v6;
         stats.reservations_skipped_conflict_v6 += skipped_v6;
+        let reservations = util::ensure_child_mut(dhcp6, "reservations");
+        registry.assign_missing(reservations, "reservation", "kea-reservation6", |_, r| {
+            r.get_text(&["ip_address"]).unwrap_or("").trim().to_string()
+        });
 
         if !subnet_uuid_by_iface_v6.is_empty() || stats.reservations_added_v6 > 0 {
             let general = util::ensure_child_mut(dhcp6, "general");
@@ -385,3 +418,18 @@ pub fn migrate_isc_to_kea_opnsense(
 
     Ok(stats)
 }
+
+/// Warn once per numbered ISC option Kea cannot take, in a stable order.
+fn push_unsupported_options<'a>(
+    stats: &mut KeaMigrationStats,
+    notes: impl Iterator<Item = &'a Vec<String>>,
+) {
+    let mut messages: Vec<&String> = notes.flatten().collect();
+    messages.sort();
+    for message in messages {
+        stats.warnings.push(MigrationWarning {
+            message: message.clone(),
+            severity: MigrationSeverity::Warning,
+        });
+    }
+}
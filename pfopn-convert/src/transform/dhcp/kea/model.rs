@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 /// IPv4 DHCP static mapping (reservation) extracted from ISC DHCP config.
 ///
 /// Represents a fixed IP address assignment for a specific MAC address.
@@ -27,7 +29,9 @@ pub(crate) struct StaticMapV6 {
 /// IPv4 DHCP options extracted from ISC DHCP config.
 ///
 /// Contains global or per-interface DHCP options like DNS servers, routers (gateways),
-/// domain name, and NTP servers.
+/// domain name, and NTP servers. `extra` holds further `<option_data>` fields (TFTP
+/// server, boot file, numbered options) and `unsupported` describes options Kea
+/// cannot take.
 #[derive(Debug, Clone, Default)]
 pub(crate) struct OptsV4 {
     pub(crate) dns_servers: Vec<String>,
@@ -35,13 +39,39 @@ pub(crate) struct OptsV4 {
     pub(crate) domain_name: Option<String>,
     pub(crate) domain_search: Option<String>,
     pub(crate) ntp_servers: Vec<String>,
+    pub(crate) next_server: Option<String>,
+    pub(crate) extra: BTreeMap<&'static str, String>,
+    pub(crate) unsupported: Vec<String>,
+}
+
+impl OptsV4 {
+    /// Whether any option would be written to a Kea subnet.
+    pub(crate) fn has_values(&self) -> bool {
+        !self.dns_servers.is_empty()
+            || self.routers.is_some()
+            || self.domain_name.is_some()
+            || self.domain_search.is_some()
+            || !self.ntp_servers.is_empty()
+            || self.next_server.is_some()
+            || !self.extra.is_empty()
+    }
 }
 
 /// IPv6 DHCP options extracted from ISC DHCP config.
 ///
-/// Contains DHCPv6 options like DNS servers and domain search list.
+/// Contains DHCPv6 options like DNS servers and domain search list, plus
+/// `<option_data>` fields and unsupported options from `<numberoptions>`.
 #[derive(Debug, Clone, Default)]
 pub(crate) struct OptsV6 {
     pub(crate) dns_servers: Vec<String>,
     pub(crate) domain_search: Option<String>,
+    pub(crate) extra: BTreeMap<&'static str, String>,
+    pub(crate) unsupported: Vec<String>,
+}
+
+impl OptsV6 {
+    /// Whether any option would be written to a Kea subnet.
+    pub(crate) fn has_values(&self) -> bool {
+        !self.dns_servers.is_empty() || self.domain_search.is_some() || !self.extra.is_empty()
+    }
 }
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use xml_diff_core::XmlNode;
+
+/// Address family of an ISC interface block, selecting the Kea option space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Family {
+    V4,
+    V6,
+}
+
+impl Family {
+    fn space(self) -> &'static str {
+        match self {
+            Family::V4 => "dhcp4",
+            Family::V6 => "dhcp6",
+        }
+    }
+
+    fn max_code(self) -> u32 {
+        match self {
+            Family::V4 => 254,
+            Family::V6 => 65_535,
+        }
+    }
+}
+
+/// Standard Kea option: code, Kea option name, and the OPNsense `<option_data>`
+/// field that carries it, if the Kea model has one.
+type KnownOption = (u32, &'static str, Option<&'static str>);
+
+/// Standard `dhcp4` options custom ISC configs commonly set.
+const OPTIONS_V4: &[KnownOption] = &[
+    (2, "time-offset", None),
+    (3, "routers", Some("routers")),
+    (4, "time-servers", Some("time_servers")),
+    (6, "domain-name-servers", Some("domain_name_servers")),
+    (7, "log-servers", None),
+    (12, "host-name", None),
+    (15, "domain-name", Some("domain_name")),
+    (17, "root-path", None),
+    (26, "interface-mtu", None),
+    (28, "broadcast-address", None),
+    (33, "static-routes", Some("static_routes")),
+    (42, "ntp-servers", Some("ntp_servers")),
+    (43, "vendor-encapsulated-options", None),
+    (44, "netbios-name-servers", None),
+    (46, "netbios-node-type", None),
+    (60, "vendor-class-identifier", None),
+    (66, "tftp-server-name", Some("tftp_server_name")),
+    (67, "boot-file-name", Some("boot_file_name")),
+    (69, "smtp-server", None),
+    (72, "www-server", None),
+    (108, "v6-only-preferred", Some("v6_only_preferred")),
+    (119, "domain-search", Some("domain_search")),
+    (
+        121,
+        "classless-static-route",
+        Some("classless_static_route"),
+    ),
+];
+
+/// Standard `dhcp6` options custom ISC configs commonly set.
+const OPTIONS_V6: &[KnownOption] = &[
+    (17, "vendor-opts", None),
+    (21, "sip-server-dns", None),
+    (22, "sip-server-addr", None),
+    (23, "dns-servers", Some("dns_servers")),
+    (24, "domain-search", Some("domain_search")),
+    (31, "sntp-servers", None),
+    (56, "ntp-server", None),
+    (59, "bootfile-url", None),
+    (60, "bootfile-param", None),
+];
+
+/// Translate an interface's `<numberoptions>` into Kea `<option_data>` fields.
+///
+/// Options with an OPNsense Kea field are added to `fields` unless a dedicated
+/// ISC setting already filled it. Everything else (vendor blobs, standard
+/// options the Kea model has no field for, and site-specific codes Kea only
+/// accepts with an option definition) is described in `unsupported`.
+pub(crate) fn collect(
+    iface: &XmlNode,
+    family: Family,
+    fields: &mut BTreeMap<&'static str, String>,
+    unsupported: &mut Vec<String>,
+) {
+    let Some(items) = iface.get_child("numberoptions") else {
+        return;
+    };
+    let name = &iface.tag;
+    let space = family.space();
+    let known = match family {
+        Family::V4 => OPTIONS_V4,
+        Family::V6 => OPTIONS_V6,
+    };
+    for item in items.get_children("item") {
+        let raw = item.get_text(&["number"]).unwrap_or("").trim();
+        let Some(code) = raw
+            .parse::<u32>()
+            .ok()
+            .filter(|c| (1..=family.max_code()).contains(c))
+        else {
+            unsupported.push(format!(
+                "{name}: DHCP option '{raw}' is not a valid {space} option code and was not migrated"
+            ));
+            continue;
+        };
+        let value = option_value(item.get_text(&["value"]).unwrap_or(""));
+        match known.iter().find(|(c, _, _)| *c == code) {
+            Some((_, _, Some(field))) => {
+                if !value.is_empty() {
+                    fields.entry(field).or_insert(value);
+                }
+            }
+            Some((43, option, None)) if family == Family::V4 => unsupported.push(format!(
+                "{name}: DHCP option 43 ({option}) needs its sub-options defined in vendor-encapsulated-options-space and was not migrated"
+            )),
+            Some((_, option, None)) => unsupported.push(format!(
+                "{name}: DHCP option {code} ({option}) has no OPNsense Kea field and was not migrated"
+            )),
+            None => unsupported.push(format!(
+                "{name}: custom DHCP option {code} is not in Kea's {space} option space and needs an option definition; not migrated"
+            )),
+        }
+    }
+}
+
+/// Plain option value from a `<numberoptions>` item.
+///
+/// pfSense stores values base64-encoded; OPNsense stores them as typed. A
+/// value is only decoded when the result is printable ASCII, and surrounding
+/// quotes from `text`/`string` options are dropped.
+pub(crate) fn option_value(raw: &str) -> String {
+    let raw = raw.trim();
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(raw)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .filter(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_graphic() || c == ' '));
+    let value = decoded.as_deref().unwrap_or(raw).trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use xml_diff_core::parse;
+
+    use super::{collect, option_value, Family};
+
+    #[test]
+    fn decodes_pfsense_base64_values_and_keeps_plain_ones() {
+        assert_eq!(option_value("InB4ZWxpbnV4LjAi"), "pxelinux.0");
+        assert_eq!(option_value("10.22.1.30"), "10.22.1.30");
+        assert_eq!(option_value("\"boot.ipxe\""), "boot.ipxe");
+    }
+
+    #[test]
+    fn maps_known_codes_and_describes_the_rest() {
+        let iface = parse(
+            br#"<lan><numberoptions>
+                <item><number>66</number><type>text</type><value>10.0.0.5</value></item>
+                <item><number>6</number><type>ip-address</type><value>10.0.0.1</value></item>
+                <item><number>43</number><type>string</type><value>01:04:0a:00:00:05</value></item>
+                <item><number>26</number><type>unsigned integer 16</type><value>1400</value></item>
+                <item><number>224</number><type>text</type><value>site</value></item>
+                <item><number>300</number><type>text</type><value>bad</value></item>
+            </numberoptions></lan>"#,
+        )
+        .expect("parse");
+        let mut fields = BTreeMap::from([("domain_name_servers", "10.0.0.53".to_string())]);
+        let mut unsupported = Vec::new();
+        collect(&iface, Family::V4, &mut fields, &mut unsupported);
+
+        assert_eq!(fields["tftp_server_name"], "10.0.0.5");
+        assert_eq!(fields["domain_name_servers"], "10.0.0.53");
+        assert_eq!(
+            unsupported,
+            vec![
+                "lan: DHCP option 43 (vendor-encapsulated-options) needs its sub-options defined in vendor-encapsulated-options-space and was not migrated",
+                "lan: DHCP option 26 (interface-mtu) has no OPNsense Kea field and was not migrated",
+                "lan: custom DHCP option 224 is not in Kea's dhcp4 option space and needs an option definition; not migrated",
+                "lan: DHCP option '300' is not a valid dhcp4 option code and was not migrated",
+            ]
+        );
+    }
+}
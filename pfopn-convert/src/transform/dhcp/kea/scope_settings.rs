@@ -0,0 +1,132 @@
+use xml_diff_core::XmlNode;
+
+use crate::transform::booleans::flag_set;
+
+/// Architecture-specific PXE boot files; Kea selects these with client
+/// classes, which the OPNsense subnet model cannot express.
+const ARCH_BOOT_FILES: &[(&str, &str)] = &[
+    ("filename32", "32-bit UEFI boot file"),
+    ("filename64", "64-bit UEFI boot file"),
+    ("filename32arm", "32-bit ARM boot file"),
+    ("filename64arm", "64-bit ARM boot file"),
+    ("uefihttpboot", "UEFI HTTP boot URL"),
+];
+
+/// Describe per-scope ISC DHCPv4 settings a Kea subnet cannot carry.
+///
+/// Covers dynamic DNS updates, architecture-specific PXE boot files, and the
+/// root path. TFTP server, boot file, next server, and NTP servers are
+/// migrated by option extraction instead.
+pub(crate) fn unsupported_v4(iface: &XmlNode) -> Vec<String> {
+    let name = &iface.tag;
+    let mut out = ddns(iface).into_iter().collect::<Vec<_>>();
+    for (tag, label) in ARCH_BOOT_FILES {
+        if let Some(v) = text(iface, tag) {
+            out.push(format!(
+                "{name}: {label} '{v}' needs a Kea client class and was not migrated"
+            ));
+        }
+    }
+    if let Some(v) = text(iface, "rootpath") {
+        out.push(format!(
+            "{name}: root path '{v}' (option 17) has no OPNsense Kea field and was not migrated"
+        ));
+    }
+    out
+}
+
+/// Describe per-scope ISC DHCPv6 settings a Kea subnet cannot carry.
+pub(crate) fn unsupported_v6(iface: &XmlNode) -> Vec<String> {
+    let name = &iface.tag;
+    let mut out = ddns(iface).into_iter().collect::<Vec<_>>();
+    let ntp: Vec<&str> = iface
+        .get_children("ntpserver")
+        .into_iter()
+        .filter_map(|n| n.text.as_deref().map(str::trim))
+        .filter(|v| !v.is_empty())
+        .collect();
+    if !ntp.is_empty() {
+        out.push(format!(
+            "{name}: DHCPv6 NTP servers {} have no OPNsense Kea field and were not migrated",
+            ntp.join(", ")
+        ));
+    }
+    if let Some(v) = text(iface, "bootfile_url") {
+        out.push(format!(
+            "{name}: DHCPv6 boot file URL '{v}' (option 59) has no OPNsense Kea field and was not migrated"
+        ));
+    }
+    out
+}
+
+/// Dynamic DNS update settings, when updates are enabled.
+///
+/// OPNsense's Kea model has no DDNS settings, so updates stop until
+/// `kea-dhcp-ddns` is configured by hand.
+fn ddns(iface: &XmlNode) -> Option<String> {
+    if !flag_set(iface, "ddnsupdate") {
+        return None;
+    }
+    let mut detail = Vec::new();
+    if let Some(d) = text(iface, "ddnsdomain") {
+        detail.push(format!("domain {d}"));
+    }
+    if let Some(server) = text(iface, "ddnsdomainprimary") {
+        detail.push(format!("server {server}"));
+    }
+    if let Some(key) = text(iface, "ddnsdomainkeyname") {
+        detail.push(format!("key {key}"));
+    }
+    let detail = if detail.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", detail.join(", "))
+    };
+    Some(format!(
+        "{}: dynamic DNS updates{detail} have no OPNsense Kea setting and were not migrated; configure kea-dhcp-ddns manually",
+        iface.tag
+    ))
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{unsupported_v4, unsupported_v6};
+
+    #[test]
+    fn reports_ddns_arch_boot_files_and_root_path() {
+        let iface = parse(
+            br#"<lan><ddnsupdate/><ddnsdomain>dyn.example.com</ddnsdomain><ddnsdomainprimary>10.0.0.2</ddnsdomainprimary><ddnsdomainkeyname>dhcp</ddnsdomainkeyname><filename64>ipxe.efi</filename64><filename32></filename32><rootpath>/srv/nfs</rootpath></lan>"#,
+        )
+        .expect("parse");
+        assert_eq!(
+            unsupported_v4(&iface),
+            vec![
+                "lan: dynamic DNS updates (domain dyn.example.com, server 10.0.0.2, key dhcp) have no OPNsense Kea setting and were not migrated; configure kea-dhcp-ddns manually",
+                "lan: 64-bit UEFI boot file 'ipxe.efi' needs a Kea client class and was not migrated",
+                "lan: root path '/srv/nfs' (option 17) has no OPNsense Kea field and was not migrated",
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_disabled_ddns_and_reports_v6_ntp() {
+        let iface = parse(
+            br#"<lan><ddnsupdate>0</ddnsupdate><ddnsdomain>dyn.example.com</ddnsdomain><ntpserver>fd00::20</ntpserver><ntpserver/></lan>"#,
+        )
+        .expect("parse");
+        assert_eq!(
+            unsupported_v6(&iface),
+            vec![
+                "lan: DHCPv6 NTP servers fd00::20 have no OPNsense Kea field and were not migrated"
+            ]
+        );
+    }
+}
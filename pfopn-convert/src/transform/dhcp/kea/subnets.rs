@@ -1,6 +1,10 @@
+use std::net::Ipv4Addr;
+
 use xml_diff_core::XmlNode;
 
 use super::util::{ensure_child_mut, set_or_insert_text_child};
+use super::{MigrationSeverity, MigrationWarning};
+use crate::verify_dhcp::{describe_range_problem, range_problems, Subnet4};
 
 /// Add default (empty) IPv4 DHCP option placeholders to a Kea subnet.
 ///
@@ -73,3 +77,45 @@ pub(crate) fn find_subnet_mut_by_uuid<'a>(
         .position(|c| c.tag == tag && c.attributes.get("uuid").map(String::as_str) == Some(uuid))?;
     Some(&mut subnets.children[pos])
 }
+
+/// Keep the ISC ranges of one interface that form a valid Kea pool list.
+///
+/// Ranges that are not IPv4 address pairs, run backwards, fall outside
+/// `subnet`, or overlap a kept range are left out with a warning, as
+/// reported by [`range_problems`]. Returns the kept ranges as `from-to`.
+pub(crate) fn usable_pools_v4(
+    iface: &str,
+    ranges: &[(String, String)],
+    subnet: Subnet4,
+    warnings: &mut Vec<MigrationWarning>,
+) -> Vec<String> {
+    let mut parsed = Vec::new();
+    for (from, to) in ranges {
+        match (from.parse::<Ipv4Addr>(), to.parse::<Ipv4Addr>()) {
+            (Ok(from), Ok(to)) => parsed.push((from, to)),
+            _ => warnings.push(MigrationWarning {
+                message: format!(
+                    "{iface}: DHCP range {from} - {to} is not a pair of IPv4 addresses; not migrated to Kea"
+                ),
+                severity: MigrationSeverity::Warning,
+            }),
+        }
+    }
+    let problems = range_problems(Some(subnet), &parsed);
+    for (idx, problem) in &problems {
+        let (from, to) = parsed[*idx];
+        warnings.push(MigrationWarning {
+            message: format!(
+                "{iface}: DHCP range {from} - {to} {}; not migrated to Kea",
+                describe_range_problem(*problem, Some(subnet), &parsed)
+            ),
+            severity: MigrationSeverity::Warning,
+        });
+    }
+    parsed
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !problems.iter().any(|(p, _)| p == idx))
+        .map(|(_, (from, to))| format!("{from}-{to}"))
+        .collect()
+}
@@ -294,3 +294,112 @@ fn reports_skipped_reservation_conflicts() {
     assert_eq!(stats.reservations_added_v4, 0);
     assert_eq!(stats.reservations_skipped_conflict_v4, 1);
 }
+
+#[test]
+fn warns_when_isc_mac_control_flags_cannot_migrate() {
+    let source = parse(
+        br#"<pfsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces><dhcpd><lan><range><from>192.168.1.100</from><to>192.168.1.200</to></range><staticarp/><denyunknown>enabled</denyunknown></lan></dhcpd></pfsense>"#,
+    )
+    .expect("parse");
+    let mut out = parse(br#"<opnsense><OPNsense><Kea/></OPNsense></opnsense>"#).expect("parse");
+
+    let stats = migrate_isc_to_kea_opnsense(&mut out, &source).expect("migrate");
+    let messages: Vec<_> = stats.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert!(messages.contains(&"lan: static ARP has no Kea equivalent and was not migrated"));
+    assert!(
+        messages.contains(&"lan: deny unknown clients has no Kea equivalent and was not migrated")
+    );
+}
+
+#[test]
+fn drops_invalid_isc_ranges_with_warnings() {
+    let source = parse(
+        br#"<pfsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces><dhcpd><lan><range><from>192.168.1.100</from><to>192.168.1.200</to></range><range><from>192.168.1.150</from><to>192.168.1.250</to></range><range><from>192.168.2.10</from><to>192.168.2.20</to></range><range><from>192.168.1.90</from><to>192.168.1.80</to></range><range><from>192.168.1.300</from><to>192.168.1.310</to></range></lan></dhcpd></pfsense>"#,
+    )
+    .expect("parse");
+    let mut out = parse(br#"<opnsense><OPNsense><Kea/></OPNsense></opnsense>"#).expect("parse");
+
+    let stats = migrate_isc_to_kea_opnsense(&mut out, &source).expect("migrate");
+    assert_eq!(
+        out.get_text(&["OPNsense", "Kea", "dhcp4", "subnets", "subnet4", "pools"]),
+        Some("192.168.1.100-192.168.1.200")
+    );
+    let messages: Vec<_> = stats.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "lan: DHCP range 192.168.1.300 - 192.168.1.310 is not a pair of IPv4 addresses; not migrated to Kea",
+            "lan: DHCP range 192.168.1.150 - 192.168.1.250 overlaps range 192.168.1.100 - 192.168.1.200; not migrated to Kea",
+            "lan: DHCP range 192.168.2.10 - 192.168.2.20 is outside the subnet 192.168.1.0/24; not migrated to Kea",
+            "lan: DHCP range 192.168.1.90 - 192.168.1.80 ends before it starts; not migrated to Kea",
+        ]
+    );
+}
+
+#[test]
+fn migrates_tftp_boot_and_numbered_options() {
+    let source = parse(
+        br#"<pfsense>
+            <interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet><ipaddrv6>fd00:1::1</ipaddrv6><subnetv6>64</subnetv6></lan></interfaces>
+            <dhcpd>
+              <lan>
+                <range><from>192.168.1.100</from><to>192.168.1.200</to></range>
+                <tftp>192.168.1.5</tftp>
+                <nextserver>192.168.1.5</nextserver>
+                <numberoptions>
+                  <item><number>67</number><type>text</type><value>InB4ZWxpbnV4LjAi</value></item>
+                  <item><number>66</number><type>text</type><value>ignored.example</value></item>
+                  <item><number>43</number><type>string</type><value>01:04:c0:a8:01:05</value></item>
+                  <item><number>252</number><type>text</type><value>http://wpad/wpad.dat</value></item>
+                </numberoptions>
+              </lan>
+            </dhcpd>
+            <dhcpdv6>
+              <lan>
+                <range><from>::100</from><to>::200</to></range>
+                <numberoptions>
+                  <item><number>23</number><type>text</type><value>fd00:1::53</value></item>
+                  <item><number>59</number><type>text</type><value>tftp://[fd00:1::5]/boot.efi</value></item>
+                </numberoptions>
+              </lan>
+            </dhcpdv6>
+        </pfsense>"#,
+    )
+    .expect("parse");
+    let mut out = parse(br#"<opnsense/>"#).expect("parse");
+
+    let stats = migrate_isc_to_kea_opnsense(&mut out, &source).expect("migrate");
+    let subnet4 = ["OPNsense", "Kea", "dhcp4", "subnets", "subnet4"];
+    let option4 = |field: &str| {
+        out.get_text(&[subnet4.as_slice(), &["option_data", field]].concat())
+            .map(str::to_string)
+    };
+    assert_eq!(option4("tftp_server_name").as_deref(), Some("192.168.1.5"));
+    assert_eq!(option4("boot_file_name").as_deref(), Some("pxelinux.0"));
+    assert_eq!(
+        out.get_text(&[subnet4.as_slice(), &["next_server"]].concat()),
+        Some("192.168.1.5")
+    );
+    assert_eq!(
+        out.get_text(&[
+            "OPNsense",
+            "Kea",
+            "dhcp6",
+            "subnets",
+            "subnet6",
+            "option_data",
+            "dns_servers"
+        ]),
+        Some("fd00:1::53")
+    );
+
+    let messages: Vec<&str> = stats.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "lan: DHCP option 43 (vendor-encapsulated-options) needs its sub-options defined in vendor-encapsulated-options-space and was not migrated",
+            "lan: custom DHCP option 252 is not in Kea's dhcp4 option space and needs an option definition; not migrated",
+            "lan: DHCP option 59 (bootfile-url) has no OPNsense Kea field and was not migrated",
+        ]
+    );
+}
@@ -110,8 +110,3 @@ pub(crate) fn enable_family_interfaces(general: &mut XmlNode, iface_map: &HashMa
         set_or_insert_text_child(general, "interfaces", &iface_list);
     }
 }
-
-/// Get the next synthetic ID for UUID generation, starting from at least 1.
-pub(crate) fn next_synthetic_id(start: usize) -> usize {
-    start.max(1)
-}
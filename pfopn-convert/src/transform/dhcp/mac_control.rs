@@ -0,0 +1,175 @@
+//! Static ARP and MAC access control flags on ISC DHCP interfaces.
+//!
+//! Both platforms store these flags in `<dhcpd><{iface}>` but encode them
+//! differently:
+//!
+//! | Flag | pfSense | OPNsense |
+//! |------|---------|----------|
+//! | `staticarp` | empty presence tag | `1` |
+//! | `denyunknown` | `enabled` or `class` | `1` |
+//! | `staticmap/arp_table_static_entry` | empty presence tag | `1` |
+//!
+//! pfSense `denyunknown=class` only admits clients mapped on the same
+//! interface; OPNsense has no equivalent and admits clients mapped on any
+//! interface, so the conversion widens it and records a warning.
+//!
+//! Kea has no per-subnet static ARP or deny-unknown setting, so
+//! [`kea_unsupported_flags`] reports what an ISC → Kea migration drops.
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::{flag_set, normalize_flag};
+
+/// Normalize MAC control flags in the output `<dhcpd>` for OPNsense.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    _source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(dhcpd) = out.children.iter_mut().find(|c| c.tag == "dhcpd") else {
+        return;
+    };
+    for iface in &mut dhcpd.children {
+        normalize_flag(iface, "staticarp", "opnsense");
+        if let Some(deny) = iface.children.iter_mut().find(|c| c.tag == "denyunknown") {
+            if deny.text.as_deref().map(str::trim) == Some("class") {
+                diagnostics.warn(
+                    "dhcp",
+                    format!(
+                        "{}: deny unknown clients 'class' mode has no OPNsense equivalent; clients mapped on any interface will be allowed",
+                        iface.tag
+                    ),
+                );
+            }
+            deny.text = Some("1".to_string());
+        }
+        for staticmap in iface.children.iter_mut().filter(|c| c.tag == "staticmap") {
+            normalize_flag(staticmap, "arp_table_static_entry", "opnsense");
+        }
+    }
+}
+
+/// Normalize MAC control flags in the output `<dhcpd>` for pfSense.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    _source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    _diagnostics: &mut Diagnostics,
+) {
+    let Some(dhcpd) = out.children.iter_mut().find(|c| c.tag == "dhcpd") else {
+        return;
+    };
+    for iface in &mut dhcpd.children {
+        if !flag_set(iface, "denyunknown") {
+            iface.children.retain(|c| c.tag != "denyunknown");
+            continue;
+        }
+        if let Some(deny) = iface.children.iter_mut().find(|c| c.tag == "denyunknown") {
+            let value = deny.text.as_deref().map(str::trim).unwrap_or("");
+            if value != "class" {
+                deny.text = Some("enabled".to_string());
+            }
+        }
+    }
+}
+
+/// List `(interface, flag)` pairs on enabled ISC interfaces that Kea cannot express.
+pub fn kea_unsupported_flags(root: &XmlNode) -> Vec<(String, &'static str)> {
+    let Some(dhcpd) = root.get_child("dhcpd") else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for iface in &dhcpd.children {
+        if !super::kea::isc_iface_enabled(iface) {
+            continue;
+        }
+        if flag_set(iface, "staticarp") {
+            out.push((iface.tag.clone(), "static ARP"));
+        }
+        if flag_set(iface, "denyunknown") {
+            out.push((iface.tag.clone(), "deny unknown clients"));
+        }
+        if iface
+            .get_children("staticmap")
+            .iter()
+            .any(|m| flag_set(m, "arp_table_static_entry"))
+        {
+            out.push((iface.tag.clone(), "static ARP entries on mappings"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{kea_unsupported_flags, to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn normalizes_pfsense_flags_for_opnsense() {
+        let source = parse(b"<pfsense/>").expect("parse");
+        let mut out = parse(
+            br#"<opnsense><dhcpd><lan><staticarp></staticarp><denyunknown>class</denyunknown><staticmap><mac>aa</mac><arp_table_static_entry/></staticmap></lan></dhcpd></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &source, &mut diagnostics);
+
+        assert_eq!(out.get_text(&["dhcpd", "lan", "staticarp"]), Some("1"));
+        assert_eq!(out.get_text(&["dhcpd", "lan", "denyunknown"]), Some("1"));
+        assert_eq!(
+            out.get_text(&["dhcpd", "lan", "staticmap", "arp_table_static_entry"]),
+            Some("1")
+        );
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.source == "dhcp" && d.message.contains("'class'")));
+    }
+
+    #[test]
+    fn maps_opnsense_deny_unknown_to_pfsense_enabled() {
+        let source = parse(b"<opnsense/>").expect("parse");
+        let mut out = parse(
+            br#"<pfsense><dhcpd><opt1><staticarp>1</staticarp><denyunknown>1</denyunknown></opt1></dhcpd></pfsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_pfsense(&mut out, &source, &source, &mut diagnostics);
+
+        assert_eq!(out.get_text(&["dhcpd", "opt1", "staticarp"]), Some("1"));
+        assert_eq!(
+            out.get_text(&["dhcpd", "opt1", "denyunknown"]),
+            Some("enabled")
+        );
+    }
+
+    #[test]
+    fn drops_disabled_opnsense_deny_unknown_for_pfsense() {
+        let source = parse(b"<opnsense/>").expect("parse");
+        let mut out =
+            parse(br#"<pfsense><dhcpd><lan><denyunknown>0</denyunknown></lan></dhcpd></pfsense>"#)
+                .expect("parse");
+
+        to_pfsense(&mut out, &source, &source, &mut Diagnostics::new());
+
+        assert!(out.get_text(&["dhcpd", "lan", "denyunknown"]).is_none());
+    }
+
+    #[test]
+    fn reports_flags_kea_cannot_express() {
+        let root = parse(
+            br#"<pfsense><dhcpd><lan><enable/><staticarp/></lan><opt1><enable/><denyunknown>0</denyunknown></opt1><opt2><enable>0</enable><staticarp/></opt2></dhcpd></pfsense>"#,
+        )
+        .expect("parse");
+        assert_eq!(
+            kea_unsupported_flags(&root),
+            vec![("lan".to_string(), "static ARP")]
+        );
+    }
+}
@@ -23,7 +23,10 @@
 //! - **backend_policy** — Determines which DHCP backend to use and enforces backend preferences
 //! - **disable** — Handles disabling DHCP on interfaces when needed
 //! - **kea** — ISC DHCP to Kea migration and Kea-specific configuration
+//! - **mac_control** — Static ARP and deny-unknown-clients flag conversion
 //! - **relay** — DHCP relay agent configuration conversion
+//! - **router_adv** — IPv6 router advertisement (radvd) settings, kept when DHCPv6 moves to Kea
+//! - **static_dns** — Static mapping hostnames reconciled with Unbound host overrides
 //!
 //! ## Conversion Strategy
 //!
@@ -47,7 +50,10 @@
 pub mod backend_policy;
 pub mod disable;
 pub mod kea;
+pub mod mac_control;
 pub mod relay;
+pub mod router_adv;
+pub mod static_dns;
 
 pub use backend_policy::{
     enforce_output_backend, ensure_backend_readiness, has_legacy_dhcp_data,
@@ -55,3 +61,4 @@ pub use backend_policy::{
 };
 pub use disable::apply as disable_all;
 pub use kea::{migrate_isc_to_kea_opnsense, KeaMigrationStats, MigrationSeverity};
+pub use static_dns::StaticMapDns;
@@ -40,34 +40,6 @@ pub(super) fn push_text_child(parent: &mut XmlNode, tag: &str, value: &str) {
     parent.children.push(child);
 }
 
-/// Convert a boolean to "1" or "0" string representation.
-pub(super) fn bool_to_01(enabled: bool) -> &'static str {
-    if enabled {
-        "1"
-    } else {
-        "0"
-    }
-}
-
-/// Parse relay enabled text to boolean.
-///
-/// Returns `true` if the value is "1", "on", or "true" (case-insensitive).
-pub(super) fn relay_enabled_text(v: Option<&str>) -> bool {
-    let Some(v) = v else {
-        return false;
-    };
-    let v = v.trim();
-    v == "1" || v.eq_ignore_ascii_case("on") || v.eq_ignore_ascii_case("true")
-}
-
-/// Generate a deterministic UUID from a seed value.
-///
-/// Creates stable UUIDs for relay configuration elements. Used for OPNsense
-/// plugin config which requires UUIDs for each relay instance.
-pub(super) fn synthetic_uuid(seed: usize) -> String {
-    format!("00000000-0000-4000-8000-{seed:012x}")
-}
-
 /// Add a value to a vector if it's not already present.
 ///
 /// Used for collecting unique interface names or server addresses.
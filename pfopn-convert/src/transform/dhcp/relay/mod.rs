@@ -14,9 +14,20 @@
 //! - Same structure (`<dhcrelay>` and `<dhcrelay6>`) for compatibility
 //! - Also supports relay via the `os-dhcrelay` plugin with enhanced UI
 //! - Plugin config stored in `<OPNsense><dhcrelay>`
+//! - With the Kea backend only the plugin model is written; the legacy
+//!   sections are dropped by [`drop_legacy_sections`]
+//!
+//! ## Per-Interface Settings
+//!
+//! pfSense applies one server list, agent-info flag, and CARP dependency to
+//! every relay interface of an address family. OPNsense keeps them per
+//! interface (`<relays>` entries), so converting to pfSense collapses them
+//! and warns when interfaces disagreed.
 
 use xml_diff_core::XmlNode;
 
+use crate::diagnostics::Diagnostics;
+
 mod common;
 mod to_opnsense;
 mod to_pfsense;
@@ -28,16 +39,36 @@ mod tests;
 ///
 /// Syncs the base relay sections (`<dhcrelay>`, `<dhcrelay6>`) and maps any
 /// pfSense-specific relay config to OPNsense plugin format if needed.
-pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, _target: &XmlNode) {
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
     common::sync_relay_sections(out, source);
-    to_opnsense::map_pf_relay_to_opnsense_plugin(out, source);
+    to_opnsense::map_pf_relay_to_opnsense_plugin(out, source, diagnostics);
 }
 
 /// Convert DHCP relay configuration to pfSense format.
 ///
 /// Syncs the base relay sections and maps any OPNsense plugin relay config back
 /// to pfSense's standard relay format.
-pub fn to_pfsense(out: &mut XmlNode, source: &XmlNode, _target: &XmlNode) {
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
     common::sync_relay_sections(out, source);
-    to_pfsense::map_opnsense_plugin_to_pf_relay(out, source);
+    to_pfsense::map_opnsense_plugin_to_pf_relay(out, source, diagnostics);
+}
+
+/// Remove the legacy relay sections from an OPNsense config.
+///
+/// Used when the output runs Kea: relaying is then configured only through
+/// the `<OPNsense><DHCRelay>` model, and leftover `<dhcrelay>` sections would
+/// only confuse later edits.
+pub fn drop_legacy_sections(root: &mut XmlNode) {
+    root.children
+        .retain(|child| !common::RELAY_TAGS.iter().any(|tag| child.tag == *tag));
 }
@@ -1,6 +1,7 @@
 use xml_diff_core::parse;
 
-use super::{to_opnsense, to_pfsense};
+use super::{drop_legacy_sections, to_opnsense, to_pfsense};
+use crate::diagnostics::Diagnostics;
 
 #[test]
 fn replaces_target_relay_with_source_relay_opnsense() {
@@ -14,7 +15,7 @@ fn replaces_target_relay_with_source_relay_opnsense() {
     .expect("parse");
 
     let mut out = target.clone();
-    to_opnsense(&mut out, &source, &target);
+    to_opnsense(&mut out, &source, &target, &mut Diagnostics::default());
 
     assert_eq!(out.get_text(&["dhcrelay", "interface"]), Some("opt3,opt4"));
     assert_eq!(out.get_text(&["dhcrelay", "server"]), Some("10.1.10.1"));
@@ -41,7 +42,7 @@ fn removes_target_relay_when_source_has_none() {
     .expect("parse");
 
     let mut out = target.clone();
-    to_pfsense(&mut out, &source, &target);
+    to_pfsense(&mut out, &source, &target, &mut Diagnostics::default());
 
     assert!(out.get_child("dhcrelay").is_none());
     assert!(out.get_child("dhcp6relay").is_none());
@@ -54,7 +55,7 @@ fn preserves_opnsense_dhcrelay_when_source_has_no_relay_sections() {
         .expect("parse");
 
     let mut out = target.clone();
-    to_opnsense(&mut out, &source, &target);
+    to_opnsense(&mut out, &source, &target, &mut Diagnostics::default());
 
     assert!(out
         .get_child("OPNsense")
@@ -71,7 +72,7 @@ fn maps_opnsense_dhcrelay_plugin_to_pfsense_dhcrelay() {
     let target = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
 
     let mut out = target.clone();
-    to_pfsense(&mut out, &source, &target);
+    to_pfsense(&mut out, &source, &target, &mut Diagnostics::default());
 
     assert_eq!(out.get_text(&["dhcrelay", "interface"]), Some("opt4"));
     assert_eq!(out.get_text(&["dhcrelay", "server"]), Some("10.1.10.254"));
@@ -86,7 +87,7 @@ fn maps_opnsense_dhcrelay_plugin_to_pfsense_dhcp6relay() {
     let target = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
 
     let mut out = target.clone();
-    to_pfsense(&mut out, &source, &target);
+    to_pfsense(&mut out, &source, &target, &mut Diagnostics::default());
 
     assert_eq!(out.get_text(&["dhcp6relay", "interface"]), Some("opt6"));
     assert_eq!(
@@ -94,3 +95,64 @@ fn maps_opnsense_dhcrelay_plugin_to_pfsense_dhcp6relay() {
         Some("2001:db8::10")
     );
 }
+
+#[test]
+fn carries_agent_option_and_carp_dependency_per_interface() {
+    let source = parse(
+        br#"<pfsense><dhcrelay><enable/><interface>lan,opt1</interface><server>10.0.0.5,10.0.0.6</server><agentoption/><carpstatusvip>_vip1</carpstatusvip></dhcrelay></pfsense>"#,
+    )
+    .expect("parse");
+    let target = parse(br#"<opnsense/>"#).expect("parse");
+
+    let mut out = target.clone();
+    to_opnsense(&mut out, &source, &target, &mut Diagnostics::default());
+
+    let dhc = out
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("DHCRelay"))
+        .expect("DHCRelay");
+    let relays = dhc.get_children("relays");
+    assert_eq!(relays.len(), 2);
+    for relay in relays {
+        assert_eq!(relay.get_text(&["agent_info"]), Some("1"));
+        assert_eq!(relay.get_text(&["carp_depend_on"]), Some("_vip1"));
+    }
+}
+
+#[test]
+fn collapses_divergent_opnsense_relays_with_warnings() {
+    let source = parse(
+        br#"<opnsense><OPNsense><DHCRelay version="1.0.1"><destinations uuid="d1"><server>10.0.0.5</server></destinations><destinations uuid="d2"><server>10.0.0.9</server></destinations><relays uuid="r1"><enabled>1</enabled><interface>lan</interface><destination>d1</destination><agent_info>1</agent_info></relays><relays uuid="r2"><enabled>1</enabled><interface>opt1</interface><destination>d2</destination><agent_info>0</agent_info></relays><relays uuid="r3"><enabled>0</enabled><interface>opt2</interface><destination>d1</destination></relays></DHCRelay></OPNsense></opnsense>"#,
+    )
+    .expect("parse");
+    let target = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
+
+    let mut out = target.clone();
+    let mut diagnostics = Diagnostics::default();
+    to_pfsense(&mut out, &source, &target, &mut diagnostics);
+
+    assert_eq!(out.get_text(&["dhcrelay", "interface"]), Some("lan,opt1"));
+    assert_eq!(
+        out.get_text(&["dhcrelay", "server"]),
+        Some("10.0.0.5,10.0.0.9")
+    );
+    assert!(out
+        .get_child("dhcrelay")
+        .is_some_and(|r| r.get_child("agentoption").is_some()));
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].contains("opt2 dropped"));
+    assert!(messages[1].contains("merged"));
+}
+
+#[test]
+fn drops_legacy_relay_sections() {
+    let mut root = parse(
+        br#"<opnsense><dhcrelay><enable/></dhcrelay><dhcrelay6/><OPNsense><DHCRelay/></OPNsense></opnsense>"#,
+    )
+    .expect("parse");
+    drop_legacy_sections(&mut root);
+    assert!(root.get_child("dhcrelay").is_none());
+    assert!(root.get_child("dhcrelay6").is_none());
+    assert!(root.get_child("OPNsense").is_some());
+}
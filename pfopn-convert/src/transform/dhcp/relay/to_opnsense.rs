@@ -1,8 +1,10 @@
 use xml_diff_core::XmlNode;
 
-use super::common::{
-    bool_to_01, ensure_child_mut, push_text_child, relay_enabled_text, synthetic_uuid,
-};
+use crate::diagnostics::Diagnostics;
+
+use super::common::{ensure_child_mut, push_text_child};
+use crate::transform::booleans::{flag_set, numeric};
+use crate::uuid_registry::UuidRegistry;
 
 /// Map pfSense DHCP relay config to OPNsense DHCRelay plugin format.
 ///
@@ -45,7 +47,14 @@ use super::common::{
 /// Each pfSense relay section (IPv4 or IPv6) creates:
 /// - One `<destinations>` entry for the server address
 /// - One `<relays>` entry per interface, all pointing to the same destination
-pub(super) fn map_pf_relay_to_opnsense_plugin(out: &mut XmlNode, source: &XmlNode) {
+///
+/// pfSense's section-wide `<agentoption/>` and `<carpstatusvip>` are copied
+/// onto every relay as `agent_info` and `carp_depend_on`.
+pub(super) fn map_pf_relay_to_opnsense_plugin(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
     let mut source_entries = Vec::new();
     if let Some(relay4) = source.get_child("dhcrelay") {
         source_entries.push((relay4, "v4"));
@@ -62,6 +71,8 @@ pub(super) fn map_pf_relay_to_opnsense_plugin(out: &mut XmlNode, source: &XmlNod
 
     let opn = ensure_child_mut(out, "OPNsense");
     opn.children.retain(|c| c.tag != "DHCRelay");
+    let mut registry = UuidRegistry::from_config(out);
+    let opn = ensure_child_mut(out, "OPNsense");
 
     let mut dhc = XmlNode::new("DHCRelay");
     dhc.attributes
@@ -71,7 +82,6 @@ pub(super) fn map_pf_relay_to_opnsense_plugin(out: &mut XmlNode, source: &XmlNod
         "DHCRelay configuration".to_string(),
     );
 
-    let mut seed = 1usize;
     for (relay, family) in source_entries {
         let interfaces: Vec<String> = relay
             .get_text(&["interface"])
@@ -82,15 +92,27 @@ pub(super) fn map_pf_relay_to_opnsense_plugin(out: &mut XmlNode, source: &XmlNod
             .map(ToOwned::to_owned)
             .collect();
         let server = relay.get_text(&["server"]).unwrap_or("").trim().to_string();
-        let enabled = bool_to_01(
-            relay.get_child("enable").is_some() || relay_enabled_text(relay.get_text(&["enable"])),
-        );
+        let enabled = numeric(flag_set(relay, "enable"));
         if server.is_empty() || interfaces.is_empty() {
+            if enabled == "1" {
+                diagnostics.warn(
+                    "dhcp_relay",
+                    format!(
+                        "{} is enabled without a server or interface; not converted",
+                        relay.tag
+                    ),
+                );
+            }
             continue;
         }
+        let agent_info = numeric(flag_set(relay, "agentoption"));
+        let carp_vip = relay
+            .get_text(&["carpstatusvip"])
+            .map(str::trim)
+            .filter(|v| !v.is_empty() && *v != "none")
+            .unwrap_or("");
 
-        let destination_uuid = synthetic_uuid(seed);
-        seed += 1;
+        let destination_uuid = registry.uuid_for("dhcrelay-destination", family);
 
         let mut destination = XmlNode::new("destinations");
         destination
@@ -106,15 +128,15 @@ pub(super) fn map_pf_relay_to_opnsense_plugin(out: &mut XmlNode, source: &XmlNod
 
         for iface in &interfaces {
             let mut relay_item = XmlNode::new("relays");
-            relay_item
-                .attributes
-                .insert("uuid".to_string(), synthetic_uuid(seed + 100));
-            seed += 1;
+            relay_item.attributes.insert(
+                "uuid".to_string(),
+                registry.uuid_for("dhcrelay-relay", &format!("{family}|{iface}")),
+            );
             push_text_child(&mut relay_item, "enabled", enabled);
             push_text_child(&mut relay_item, "interface", iface);
             push_text_child(&mut relay_item, "destination", &destination_uuid);
-            push_text_child(&mut relay_item, "agent_info", "0");
-            push_text_child(&mut relay_item, "carp_depend_on", "");
+            push_text_child(&mut relay_item, "agent_info", agent_info);
+            push_text_child(&mut relay_item, "carp_depend_on", carp_vip);
             dhc.children.push(relay_item);
         }
     }
@@ -1,6 +1,9 @@
 use xml_diff_core::XmlNode;
 
+use crate::diagnostics::Diagnostics;
+
 use super::common::{push_text_child, push_unique};
+use crate::transform::booleans::flag_set;
 
 /// Map OPNsense DHCRelay plugin config to pfSense relay format.
 ///
@@ -37,9 +40,18 @@ use super::common::{push_text_child, push_unique};
 /// The function:
 /// 1. Collects all relay entries and their destination servers
 /// 2. Separates IPv4 (no colons) from IPv6 (contains colons) based on server address
-/// 3. Aggregates interfaces and servers for each IP version
+/// 3. Aggregates interfaces, servers, and per-interface options for each IP version
 /// 4. Creates separate relay sections for IPv4 and IPv6
-pub(super) fn map_opnsense_plugin_to_pf_relay(out: &mut XmlNode, source: &XmlNode) {
+///
+/// pfSense has a single enable flag, server list, agent-info flag, and CARP
+/// dependency per family. When only some relays of a family are enabled,
+/// the disabled interfaces are left out; when enabled relays disagree on
+/// destination or options, the union is used and a warning is emitted.
+pub(super) fn map_opnsense_plugin_to_pf_relay(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
     let Some(dhc) = source
         .get_child("OPNsense")
         .and_then(|n| n.get_child("DHCRelay"))
@@ -57,13 +69,7 @@ pub(super) fn map_opnsense_plugin_to_pf_relay(out: &mut XmlNode, source: &XmlNod
         String::new()
     };
 
-    let mut ifaces_v4 = Vec::new();
-    let mut ifaces_v6 = Vec::new();
-    let mut servers_v4 = Vec::new();
-    let mut servers_v6 = Vec::new();
-    let mut enabled_v4 = false;
-    let mut enabled_v6 = false;
-
+    let mut families = [Family::new("dhcrelay"), Family::new("dhcp6relay")];
     for r in dhc.get_children("relays") {
         let Some(iface) = r
             .get_text(&["interface"])
@@ -83,42 +89,111 @@ pub(super) fn map_opnsense_plugin_to_pf_relay(out: &mut XmlNode, source: &XmlNod
             continue;
         }
 
-        let is_v6 = server.contains(':');
-        if is_v6 {
-            push_unique(&mut ifaces_v6, iface.to_string());
-            push_unique(&mut servers_v6, server.clone());
-            if r.get_text(&["enabled"]).unwrap_or("0").trim() == "1" {
-                enabled_v6 = true;
-            }
-        } else {
-            push_unique(&mut ifaces_v4, iface.to_string());
-            push_unique(&mut servers_v4, server.clone());
-            if r.get_text(&["enabled"]).unwrap_or("0").trim() == "1" {
-                enabled_v4 = true;
-            }
-        }
+        let family = &mut families[usize::from(server.contains(':'))];
+        family.relays.push(RelayEntry {
+            interface: iface.to_string(),
+            server,
+            enabled: flag_set(r, "enabled"),
+            agent_info: flag_set(r, "agent_info"),
+            carp_vip: r
+                .get_text(&["carp_depend_on"])
+                .map(str::trim)
+                .unwrap_or("")
+                .to_string(),
+        });
     }
 
     out.children
         .retain(|c| c.tag != "dhcrelay" && c.tag != "dhcp6relay" && c.tag != "dhcrelay6");
 
-    if !ifaces_v4.is_empty() || !servers_v4.is_empty() {
-        let mut relay = XmlNode::new("dhcrelay");
-        if enabled_v4 {
-            relay.children.push(XmlNode::new("enable"));
+    for family in families {
+        if let Some(relay) = family.into_pf_section(diagnostics) {
+            out.children.push(relay);
         }
-        push_text_child(&mut relay, "interface", &ifaces_v4.join(","));
-        push_text_child(&mut relay, "server", &servers_v4.join(","));
-        out.children.push(relay);
     }
+}
+
+/// One OPNsense `<relays>` entry with its destination resolved.
+struct RelayEntry {
+    interface: String,
+    server: String,
+    enabled: bool,
+    agent_info: bool,
+    carp_vip: String,
+}
 
-    if !ifaces_v6.is_empty() || !servers_v6.is_empty() {
-        let mut relay = XmlNode::new("dhcp6relay");
-        if enabled_v6 {
+/// Relays of one address family, destined for one pfSense section.
+struct Family {
+    tag: &'static str,
+    relays: Vec<RelayEntry>,
+}
+
+impl Family {
+    fn new(tag: &'static str) -> Self {
+        Self {
+            tag,
+            relays: Vec::new(),
+        }
+    }
+
+    fn into_pf_section(self, diagnostics: &mut Diagnostics) -> Option<XmlNode> {
+        if self.relays.is_empty() {
+            return None;
+        }
+        let enabled = self.relays.iter().any(|r| r.enabled);
+        let (active, inactive): (Vec<&RelayEntry>, Vec<&RelayEntry>) =
+            self.relays.iter().partition(|r| r.enabled || !enabled);
+        if !inactive.is_empty() {
+            let names: Vec<&str> = inactive.iter().map(|r| r.interface.as_str()).collect();
+            diagnostics.warn(
+                "dhcp_relay",
+                format!(
+                    "{}: disabled relay interface(s) {} dropped; pfSense has one enable flag per address family",
+                    self.tag,
+                    names.join(",")
+                ),
+            );
+        }
+
+        let mut ifaces = Vec::new();
+        let mut servers = Vec::new();
+        let mut carp_vips = Vec::new();
+        for r in &active {
+            push_unique(&mut ifaces, r.interface.clone());
+            for server in r.server.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                push_unique(&mut servers, server.to_string());
+            }
+            if !r.carp_vip.is_empty() {
+                push_unique(&mut carp_vips, r.carp_vip.clone());
+            }
+        }
+        let agent_info = active.iter().any(|r| r.agent_info);
+        let mixed = active.iter().any(|r| r.server != active[0].server)
+            || active.iter().any(|r| r.agent_info != agent_info)
+            || active.iter().any(|r| r.carp_vip != active[0].carp_vip);
+        if mixed {
+            diagnostics.warn(
+                "dhcp_relay",
+                format!(
+                    "{}: per-interface relay destinations/options merged; pfSense relays every interface to {}",
+                    self.tag,
+                    servers.join(",")
+                ),
+            );
+        }
+
+        let mut relay = XmlNode::new(self.tag);
+        if enabled {
             relay.children.push(XmlNode::new("enable"));
         }
-        push_text_child(&mut relay, "interface", &ifaces_v6.join(","));
-        push_text_child(&mut relay, "server", &servers_v6.join(","));
-        out.children.push(relay);
+        push_text_child(&mut relay, "interface", &ifaces.join(","));
+        push_text_child(&mut relay, "server", &servers.join(","));
+        if agent_info {
+            relay.children.push(XmlNode::new("agentoption"));
+        }
+        if let Some(vip) = carp_vips.first() {
+            push_text_child(&mut relay, "carpstatusvip", vip);
+        }
+        Some(relay)
     }
 }
@@ -0,0 +1,537 @@
+//! IPv6 router advertisement (radvd) settings.
+//!
+//! Both platforms run radvd as a service separate from the DHCPv6 server,
+//! but configure it in different places:
+//!
+//! | Setting | pfSense `<dhcpdv6><iface>` | OPNsense legacy `<dhcpdv6><iface>` | OPNsense `<OPNsense><radvd><entries><entry>` |
+//! |---------|---------|---------|---------|
+//! | mode | `ramode` | `ramode` | `mode` |
+//! | priority | `rapriority` | `rapriority` | `priority` |
+//! | DNS servers | `radnsserver` (repeated) | `radnsserver` (repeated) | `RDNSS` (comma list) |
+//! | domain search | `radomainsearchlist` | `radomainsearchlist` | `DNSSL` (comma list) |
+//! | interval | `raminrtradvinterval`, `ramaxrtradvinterval` | `ramininterval`, `ramaxinterval` | `MinRtrAdvInterval`, `MaxRtrAdvInterval` |
+//! | router lifetime | `raadvdefaultlifetime` | `AdvDefaultLifetime` | `AdvDefaultLifetime` |
+//!
+//! pfSense spells the stateless DHCP mode `stateless_dhcp`, OPNsense
+//! `stateless`.
+//!
+//! OPNsense targets whose baseline carries the `<OPNsense><radvd>` model get
+//! MVC entries; older targets keep the legacy fields. RA keeps running when
+//! DHCPv6 moves to Kea, so [`retain_ra_only`] keeps the RA fields of a
+//! legacy `<dhcpdv6>` block that the Kea backend would otherwise drop.
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::{flag_set, is_falsy, set_flag};
+
+/// One scalar RA setting and its tag in each layout.
+struct RaField {
+    pfsense: &'static str,
+    opnsense: &'static str,
+    mvc: &'static str,
+}
+
+impl RaField {
+    fn legacy(&self, platform: &str) -> &'static str {
+        if platform == "opnsense" {
+            self.opnsense
+        } else {
+            self.pfsense
+        }
+    }
+}
+
+const MODE: RaField = RaField {
+    pfsense: "ramode",
+    opnsense: "ramode",
+    mvc: "mode",
+};
+
+const DNS: RaField = RaField {
+    pfsense: "radnsserver",
+    opnsense: "radnsserver",
+    mvc: "RDNSS",
+};
+
+const DOMAINS: RaField = RaField {
+    pfsense: "radomainsearchlist",
+    opnsense: "radomainsearchlist",
+    mvc: "DNSSL",
+};
+
+/// Settings copied verbatim between layouts.
+const SCALARS: &[RaField] = &[
+    RaField {
+        pfsense: "rapriority",
+        opnsense: "rapriority",
+        mvc: "priority",
+    },
+    RaField {
+        pfsense: "raminrtradvinterval",
+        opnsense: "ramininterval",
+        mvc: "MinRtrAdvInterval",
+    },
+    RaField {
+        pfsense: "ramaxrtradvinterval",
+        opnsense: "ramaxinterval",
+        mvc: "MaxRtrAdvInterval",
+    },
+    RaField {
+        pfsense: "raadvdefaultlifetime",
+        opnsense: "AdvDefaultLifetime",
+        mvc: "AdvDefaultLifetime",
+    },
+];
+
+/// RA source address (a VIP); legacy layouts only.
+const VIP: &str = "rainterface";
+/// Advertise the DHCPv6 DNS servers; legacy layouts only.
+const SAME_DNS: &str = "rasamednsasdhcp6";
+
+/// RA settings of one interface.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct RaSettings {
+    iface: String,
+    /// Platform-neutral mode: `stateless` for pfSense `stateless_dhcp`.
+    mode: Option<String>,
+    /// Values of [`SCALARS`], by index.
+    scalars: Vec<(usize, String)>,
+    dns: Vec<String>,
+    /// Domain search list, `;`-separated as in the legacy layouts.
+    domains: Option<String>,
+    vip: Option<String>,
+    same_dns: bool,
+    /// DHCPv6 DNS servers of the interface, for [`SAME_DNS`].
+    dhcp6_dns: Vec<String>,
+}
+
+/// Carry pfSense RA settings to OPNsense.
+///
+/// Writes `<OPNsense><radvd>` entries when `target` has the model, and the
+/// OPNsense legacy fields of `<dhcpdv6>` otherwise.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let settings = read_legacy(source, "pfsense");
+    strip_legacy(out);
+    if settings.is_empty() {
+        return;
+    }
+    let has_model = target
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("radvd"))
+        .is_some();
+    if has_model {
+        write_mvc(out, &settings, diagnostics);
+    } else {
+        write_legacy(out, &settings, "opnsense");
+    }
+}
+
+/// Carry OPNsense RA settings (MVC model or legacy fields) to pfSense.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _target: &XmlNode,
+    _diagnostics: &mut Diagnostics,
+) {
+    let mut settings = read_mvc(source);
+    for legacy in read_legacy(source, "opnsense") {
+        if !settings.iter().any(|s| s.iface == legacy.iface) {
+            settings.push(legacy);
+        }
+    }
+    strip_legacy(out);
+    write_legacy(out, &settings, "pfsense");
+}
+
+/// Reduce `<dhcpdv6>`/`<dhcpd6>` to their RA fields for a Kea output.
+///
+/// Interface blocks without RA fields, and sections left empty, are removed.
+pub fn retain_ra_only(root: &mut XmlNode) {
+    for section in root
+        .children
+        .iter_mut()
+        .filter(|c| c.tag == "dhcpdv6" || c.tag == "dhcpd6")
+    {
+        for iface in &mut section.children {
+            iface.children.retain(|c| is_ra_tag(&c.tag));
+        }
+        section.children.retain(|iface| !iface.children.is_empty());
+    }
+    root.children
+        .retain(|c| !(c.tag == "dhcpdv6" || c.tag == "dhcpd6") || !c.children.is_empty());
+}
+
+fn is_ra_tag(tag: &str) -> bool {
+    [&MODE, &DNS, &DOMAINS]
+        .into_iter()
+        .chain(SCALARS)
+        .any(|f| f.pfsense == tag || f.opnsense == tag)
+        || tag == VIP
+        || tag == SAME_DNS
+}
+
+/// Read the legacy RA fields of every `<dhcpdv6>`/`<dhcpd6>` interface.
+fn read_legacy(source: &XmlNode, platform: &str) -> Vec<RaSettings> {
+    let mut out: Vec<RaSettings> = Vec::new();
+    let sections = ["dhcpdv6", "dhcpd6"]
+        .into_iter()
+        .filter_map(|tag| source.get_child(tag));
+    for section in sections {
+        for iface in &section.children {
+            if out.iter().any(|s| s.iface == iface.tag) {
+                continue;
+            }
+            let text = |tag: &str| {
+                iface
+                    .get_text(&[tag])
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string)
+            };
+            let settings = RaSettings {
+                iface: iface.tag.clone(),
+                mode: text(MODE.legacy(platform)).map(|m| neutral_mode(&m)),
+                scalars: SCALARS
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, f)| Some((idx, text(f.legacy(platform))?)))
+                    .collect(),
+                dns: texts(iface, DNS.legacy(platform)),
+                domains: text(DOMAINS.legacy(platform)),
+                vip: text(VIP),
+                same_dns: flag_set(iface, SAME_DNS),
+                dhcp6_dns: texts(iface, "dnsserver"),
+            };
+            if settings.mode.is_some()
+                || !settings.scalars.is_empty()
+                || !settings.dns.is_empty()
+                || settings.domains.is_some()
+            {
+                out.push(settings);
+            }
+        }
+    }
+    out
+}
+
+/// Read `<OPNsense><radvd><entries><entry>`; disabled entries read as mode
+/// `disabled`.
+fn read_mvc(source: &XmlNode) -> Vec<RaSettings> {
+    let Some(entries) = source
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("radvd"))
+        .and_then(|r| r.get_child("entries"))
+    else {
+        return Vec::new();
+    };
+    entries
+        .get_children("entry")
+        .into_iter()
+        .filter_map(|entry| {
+            let text = |tag: &str| {
+                entry
+                    .get_text(&[tag])
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string)
+            };
+            let iface = text("interface")?;
+            let enabled = !entry.get_text(&["enabled"]).is_some_and(is_falsy);
+            Some(RaSettings {
+                iface,
+                mode: if enabled {
+                    text(MODE.mvc).map(|m| neutral_mode(&m))
+                } else {
+                    Some("disabled".to_string())
+                },
+                scalars: SCALARS
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, f)| Some((idx, text(f.mvc)?)))
+                    .collect(),
+                dns: split_list(text(DNS.mvc).as_deref().unwrap_or("")),
+                domains: text(DOMAINS.mvc).map(|d| split_list(&d).join(";")),
+                ..RaSettings::default()
+            })
+        })
+        .collect()
+}
+
+/// Write legacy RA fields into `<dhcpdv6><iface>` for `platform`.
+fn write_legacy(out: &mut XmlNode, settings: &[RaSettings], platform: &str) {
+    if settings.is_empty() {
+        return;
+    }
+    let section = ensure_child_mut(out, "dhcpdv6");
+    for ra in settings {
+        let iface = ensure_child_mut(section, &ra.iface);
+        if let Some(mode) = &ra.mode {
+            push_text(iface, MODE.legacy(platform), &platform_mode(mode, platform));
+        }
+        for (idx, value) in &ra.scalars {
+            push_text(iface, SCALARS[*idx].legacy(platform), value);
+        }
+        for server in &ra.dns {
+            push_text(iface, DNS.legacy(platform), server);
+        }
+        if let Some(domains) = &ra.domains {
+            push_text(iface, DOMAINS.legacy(platform), domains);
+        }
+        if let Some(vip) = &ra.vip {
+            push_text(iface, VIP, vip);
+        }
+        if ra.same_dns {
+            set_flag(iface, SAME_DNS, true, platform);
+        }
+    }
+}
+
+/// Write `<OPNsense><radvd><entries><entry>` nodes, replacing any entry for
+/// the same interface.
+fn write_mvc(out: &mut XmlNode, settings: &[RaSettings], diagnostics: &mut Diagnostics) {
+    let opn = ensure_child_mut(out, "OPNsense");
+    let radvd = ensure_child_mut(opn, "radvd");
+    if !radvd.attributes.contains_key("version") {
+        radvd
+            .attributes
+            .insert("version".to_string(), "1.0.0".to_string());
+    }
+    let entries = ensure_child_mut(radvd, "entries");
+    for ra in settings {
+        entries
+            .children
+            .retain(|e| e.get_text(&["interface"]).map(str::trim) != Some(ra.iface.as_str()));
+        if ra.mode.as_deref() == Some("disabled") {
+            continue;
+        }
+        if let Some(vip) = &ra.vip {
+            diagnostics.warn(
+                "dhcp",
+                format!(
+                    "{}: router advertisement source address {vip} has no OPNsense radvd equivalent; the interface address is used",
+                    ra.iface
+                ),
+            );
+        }
+        let dns = if ra.dns.is_empty() && ra.same_dns {
+            &ra.dhcp6_dns
+        } else {
+            &ra.dns
+        };
+        let mut entry = XmlNode::new("entry");
+        let idx = entries.get_children("entry").len();
+        entry.attributes.insert(
+            "uuid".to_string(),
+            format!("{:08x}-0000-4000-8000-726164766400", idx + 1),
+        );
+        push_text(&mut entry, "enabled", "1");
+        push_text(&mut entry, "interface", &ra.iface);
+        push_text(&mut entry, MODE.mvc, ra.mode.as_deref().unwrap_or(""));
+        for (idx, field) in SCALARS.iter().enumerate() {
+            let value = ra
+                .scalars
+                .iter()
+                .find(|(i, _)| *i == idx)
+                .map(|(_, v)| v.as_str())
+                .unwrap_or("");
+            push_text(&mut entry, field.mvc, value);
+        }
+        push_text(&mut entry, DNS.mvc, &dns.join(","));
+        let domains = ra.domains.as_deref().map(split_list).unwrap_or_default();
+        push_text(&mut entry, DOMAINS.mvc, &domains.join(","));
+        push_text(&mut entry, "description", "");
+        entries.children.push(entry);
+    }
+}
+
+/// Remove the RA fields the merge copied into `<dhcpdv6>`/`<dhcpd6>`.
+fn strip_legacy(out: &mut XmlNode) {
+    for section in out
+        .children
+        .iter_mut()
+        .filter(|c| c.tag == "dhcpdv6" || c.tag == "dhcpd6")
+    {
+        for iface in &mut section.children {
+            iface.children.retain(|c| !is_ra_tag(&c.tag));
+        }
+        section.children.retain(|iface| !iface.children.is_empty());
+    }
+}
+
+fn neutral_mode(mode: &str) -> String {
+    match mode {
+        "stateless_dhcp" => "stateless".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn platform_mode(mode: &str, platform: &str) -> String {
+    match (mode, platform) {
+        ("stateless", "pfsense") => "stateless_dhcp".to_string(),
+        (other, _) => other.to_string(),
+    }
+}
+
+fn texts(node: &XmlNode, tag: &str) -> Vec<String> {
+    node.get_children(tag)
+        .into_iter()
+        .filter_map(|c| c.text.as_deref().map(str::trim))
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split([',', ';', ' '])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn push_text(node: &mut XmlNode, tag: &str, value: &str) {
+    let mut child = XmlNode::new(tag);
+    if !value.is_empty() {
+        child.text = Some(value.to_string());
+    }
+    node.children.push(child);
+}
+
+fn ensure_child_mut<'a>(parent: &'a mut XmlNode, tag: &str) -> &'a mut XmlNode {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == tag) {
+        return &mut parent.children[idx];
+    }
+    parent.children.push(XmlNode::new(tag));
+    let last = parent.children.len() - 1;
+    &mut parent.children[last]
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{retain_ra_only, to_opnsense, to_pfsense};
+    use crate::diagnostics::{Diagnostics, Severity};
+
+    const PF_SOURCE: &[u8] = br#"<pfsense><dhcpdv6><lan><enable/><range><from>::100</from><to>::200</to></range><dnsserver>fd00::53</dnsserver><ramode>stateless_dhcp</ramode><rapriority>high</rapriority><raminrtradvinterval>200</raminrtradvinterval><raadvdefaultlifetime>1800</raadvdefaultlifetime><radomainsearchlist>example.com;lab.example.com</radomainsearchlist><rasamednsasdhcp6/><rainterface>_vip5f1</rainterface></lan><opt1><ramode>disabled</ramode></opt1></dhcpdv6></pfsense>"#;
+
+    #[test]
+    fn writes_radvd_entries_when_target_has_the_model() {
+        let source = parse(PF_SOURCE).expect("parse");
+        let target =
+            parse(br#"<opnsense><OPNsense><radvd version="1.0.0"/></OPNsense></opnsense>"#)
+                .expect("parse");
+        let mut out = source.clone();
+        out.children.push(target.children[0].clone());
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &target, &mut diagnostics);
+
+        let entries = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("radvd"))
+            .and_then(|r| r.get_child("entries"))
+            .expect("entries");
+        assert_eq!(entries.children.len(), 1);
+        let entry = &entries.children[0];
+        assert_eq!(entry.get_text(&["interface"]), Some("lan"));
+        assert_eq!(entry.get_text(&["mode"]), Some("stateless"));
+        assert_eq!(entry.get_text(&["priority"]), Some("high"));
+        assert_eq!(entry.get_text(&["MinRtrAdvInterval"]), Some("200"));
+        assert_eq!(entry.get_text(&["AdvDefaultLifetime"]), Some("1800"));
+        assert_eq!(entry.get_text(&["RDNSS"]), Some("fd00::53"));
+        assert_eq!(
+            entry.get_text(&["DNSSL"]),
+            Some("example.com,lab.example.com")
+        );
+        let lan = out
+            .get_child("dhcpdv6")
+            .and_then(|d| d.get_child("lan"))
+            .expect("lan");
+        assert!(lan.get_child("ramode").is_none());
+        assert!(lan.get_child("range").is_some());
+        assert!(out
+            .get_child("dhcpdv6")
+            .and_then(|d| d.get_child("opt1"))
+            .is_none());
+        assert_eq!(diagnostics.count(Severity::Warning), 1);
+    }
+
+    #[test]
+    fn renames_legacy_fields_for_older_opnsense() {
+        let source = parse(PF_SOURCE).expect("parse");
+        let target = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = source.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &target, &mut diagnostics);
+
+        let lan = out
+            .get_child("dhcpdv6")
+            .and_then(|d| d.get_child("lan"))
+            .expect("lan");
+        assert_eq!(lan.get_text(&["ramode"]), Some("stateless"));
+        assert_eq!(lan.get_text(&["ramininterval"]), Some("200"));
+        assert_eq!(lan.get_text(&["AdvDefaultLifetime"]), Some("1800"));
+        assert_eq!(lan.get_text(&["rasamednsasdhcp6"]), Some("1"));
+        assert_eq!(lan.get_text(&["rainterface"]), Some("_vip5f1"));
+        assert!(lan.get_child("raminrtradvinterval").is_none());
+        assert_eq!(
+            out.get_text(&["dhcpdv6", "opt1", "ramode"]),
+            Some("disabled")
+        );
+    }
+
+    #[test]
+    fn reads_radvd_entries_for_pfsense() {
+        let source = parse(
+            br#"<opnsense><OPNsense><radvd version="1.0.0"><entries><entry uuid="a"><enabled>1</enabled><interface>lan</interface><mode>stateless</mode><priority>low</priority><MaxRtrAdvInterval>600</MaxRtrAdvInterval><RDNSS>fd00::1,fd00::2</RDNSS><DNSSL>example.com</DNSSL></entry><entry uuid="b"><enabled>0</enabled><interface>opt1</interface><mode>router</mode></entry></entries></radvd></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(br#"<pfsense/>"#).expect("parse");
+        let mut diagnostics = Diagnostics::default();
+
+        to_pfsense(&mut out, &source, &source, &mut diagnostics);
+
+        let lan = out
+            .get_child("dhcpdv6")
+            .and_then(|d| d.get_child("lan"))
+            .expect("lan");
+        assert_eq!(lan.get_text(&["ramode"]), Some("stateless_dhcp"));
+        assert_eq!(lan.get_text(&["rapriority"]), Some("low"));
+        assert_eq!(lan.get_text(&["ramaxrtradvinterval"]), Some("600"));
+        assert_eq!(lan.get_children("radnsserver").len(), 2);
+        assert_eq!(lan.get_text(&["radomainsearchlist"]), Some("example.com"));
+        assert_eq!(
+            out.get_text(&["dhcpdv6", "opt1", "ramode"]),
+            Some("disabled")
+        );
+    }
+
+    #[test]
+    fn keeps_only_ra_fields_for_kea() {
+        let mut root = parse(PF_SOURCE).expect("parse");
+        root.children.push(
+            parse(br#"<dhcpd6><opt2><range><from>::1</from><to>::2</to></range></opt2></dhcpd6>"#)
+                .expect("parse"),
+        );
+
+        retain_ra_only(&mut root);
+
+        let lan = root
+            .get_child("dhcpdv6")
+            .and_then(|d| d.get_child("lan"))
+            .expect("lan");
+        assert!(lan.get_child("range").is_none());
+        assert!(lan.get_child("enable").is_none());
+        assert_eq!(lan.get_text(&["ramode"]), Some("stateless_dhcp"));
+        assert!(root.get_child("dhcpd6").is_none());
+    }
+}
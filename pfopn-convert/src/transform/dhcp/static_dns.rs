@@ -0,0 +1,452 @@
+//! DHCP static mappings as Unbound host overrides.
+//!
+//! pfSense resolves static mapping hostnames through Unbound when
+//! "Register DHCP static mappings" is on; OPNsense only does that for ISC
+//! DHCP, and a Kea migration loses it. This stage makes each static mapping
+//! with a hostname an explicit host override so internal names keep
+//! resolving, or reports the ones that have none.
+//!
+//! | | Static mappings | Host overrides |
+//! |---|---|---|
+//! | pfSense | `<dhcpd>` / `<dhcpdv6>` `<{iface}><staticmap>` | `<unbound><hosts>` |
+//! | OPNsense | the same ISC layout, or `<OPNsense><Kea>` reservations | `<OPNsense><unboundplus><hosts><host>` |
+//!
+//! A mapping counts as covered when an override with the same hostname and
+//! domain resolves to its address. An override for the same name pointing
+//! elsewhere is left alone and reported.
+
+use std::net::{IpAddr, Ipv6Addr};
+
+use xml_diff_core::XmlNode;
+
+use super::kea::isc_iface_enabled;
+use crate::diagnostics::Diagnostics;
+use crate::uuid_registry::UuidRegistry;
+
+/// How DHCP static mapping hostnames are reconciled with Unbound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StaticMapDns {
+    /// Leave host overrides as converted.
+    #[default]
+    Off,
+    /// Add a host override for every static mapping that lacks one.
+    Materialize,
+    /// Warn about static mappings without a matching host override.
+    Verify,
+}
+
+/// One static mapping hostname and the address it should resolve to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StaticHost {
+    host: String,
+    domain: String,
+    ip: IpAddr,
+    descr: String,
+}
+
+impl StaticHost {
+    fn fqdn(&self) -> String {
+        if self.domain.is_empty() {
+            self.host.clone()
+        } else {
+            format!("{}.{}", self.host, self.domain)
+        }
+    }
+}
+
+/// Reconcile static mappings from `source` with host overrides in `out`.
+///
+/// # Arguments
+///
+/// * `out` - Converted configuration to check or extend
+/// * `source` - Original source configuration holding the static mappings
+/// * `mode` - Whether to add missing overrides or only report them
+/// * `to` - Target platform (`pfsense` or `opnsense`)
+/// * `diagnostics` - Receives a summary plus conflicts and missing entries
+pub fn apply(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    mode: StaticMapDns,
+    to: &str,
+    diagnostics: &mut Diagnostics,
+) {
+    if mode == StaticMapDns::Off {
+        return;
+    }
+    let wanted = static_hosts(source);
+    if wanted.is_empty() {
+        return;
+    }
+
+    let existing = host_overrides(out, to);
+    let mut missing = Vec::new();
+    for entry in wanted {
+        let same_name: Vec<&StaticHost> = existing
+            .iter()
+            .filter(|o| {
+                o.host.eq_ignore_ascii_case(&entry.host)
+                    && o.domain.eq_ignore_ascii_case(&entry.domain)
+            })
+            .collect();
+        if same_name.iter().any(|o| o.ip == entry.ip) {
+            continue;
+        }
+        if let Some(other) = same_name
+            .iter()
+            .find(|o| o.ip.is_ipv4() == entry.ip.is_ipv4())
+        {
+            diagnostics.warn(
+                "dns",
+                format!(
+                    "static mapping {} -> {} conflicts with host override -> {}; override kept",
+                    entry.fqdn(),
+                    entry.ip,
+                    other.ip
+                ),
+            );
+            continue;
+        }
+        if !missing.contains(&entry) {
+            missing.push(entry);
+        }
+    }
+
+    if mode == StaticMapDns::Verify {
+        for entry in &missing {
+            diagnostics.warn(
+                "dns",
+                format!(
+                    "static mapping {} -> {} has no Unbound host override",
+                    entry.fqdn(),
+                    entry.ip
+                ),
+            );
+        }
+        return;
+    }
+    if missing.is_empty() {
+        return;
+    }
+
+    let added = missing.len();
+    let written = if to == "opnsense" {
+        add_opnsense_overrides(out, missing)
+    } else {
+        add_pfsense_overrides(out, missing)
+    };
+    if written {
+        diagnostics.info(
+            "dns",
+            format!("added {added} Unbound host override(s) for DHCP static mappings"),
+        );
+    } else {
+        diagnostics.warn(
+            "dns",
+            format!(
+                "target has no Unbound configuration; {added} DHCP static mapping host override(s) not added"
+            ),
+        );
+    }
+}
+
+/// Collect static mapping hostnames from ISC DHCP and Kea reservations.
+fn static_hosts(source: &XmlNode) -> Vec<StaticHost> {
+    let system_domain = source
+        .get_text(&["system", "domain"])
+        .map(str::trim)
+        .unwrap_or("");
+    let mut out = Vec::new();
+
+    for (section, ip_field) in [("dhcpd", "ipaddr"), ("dhcpdv6", "ipaddrv6")] {
+        let Some(dhcpd) = source.get_child(section) else {
+            continue;
+        };
+        for iface in dhcpd.children.iter().filter(|c| isc_iface_enabled(c)) {
+            let iface_domain = iface
+                .get_text(&["domain"])
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .unwrap_or(system_domain);
+            for map in iface.get_children("staticmap") {
+                let domain = map
+                    .get_text(&["domain"])
+                    .map(str::trim)
+                    .filter(|d| !d.is_empty())
+                    .unwrap_or(iface_domain);
+                out.extend(static_host(map, "hostname", ip_field, "descr", domain));
+            }
+        }
+    }
+
+    if let Some(kea) = source
+        .get_child("OPNsense")
+        .and_then(|opn| opn.get_child("Kea"))
+    {
+        for family in ["dhcp4", "dhcp6"] {
+            let Some(reservations) = kea
+                .get_child(family)
+                .and_then(|f| f.get_child("reservations"))
+            else {
+                continue;
+            };
+            for reservation in reservations.get_children("reservation") {
+                out.extend(static_host(
+                    reservation,
+                    "hostname",
+                    "ip_address",
+                    "description",
+                    system_domain,
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Build a [`StaticHost`] from one mapping, splitting a dotted hostname into
+/// host and domain.
+///
+/// IPv6 suffixes such as `::10`, which depend on a tracked prefix, have no
+/// fixed address to publish and are skipped.
+fn static_host(
+    node: &XmlNode,
+    host_field: &str,
+    ip_field: &str,
+    descr_field: &str,
+    domain: &str,
+) -> Option<StaticHost> {
+    let hostname = node
+        .get_text(&[host_field])
+        .map(str::trim)
+        .filter(|h| !h.is_empty())?;
+    let ip: IpAddr = node.get_text(&[ip_field])?.trim().parse().ok()?;
+    if let IpAddr::V6(v6) = ip {
+        if is_suffix_only(v6) {
+            return None;
+        }
+    }
+    let (host, domain) = match hostname.split_once('.') {
+        Some((host, rest)) => (host, rest),
+        None => (hostname, domain),
+    };
+    let descr = node
+        .get_text(&[descr_field])
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .unwrap_or("DHCP static mapping");
+    Some(StaticHost {
+        host: host.to_string(),
+        domain: domain.to_string(),
+        ip,
+        descr: descr.to_string(),
+    })
+}
+
+fn is_suffix_only(addr: Ipv6Addr) -> bool {
+    addr.segments()[0] == 0
+}
+
+/// Host overrides already configured in the target's Unbound section.
+fn host_overrides(out: &XmlNode, to: &str) -> Vec<StaticHost> {
+    let (entries, host_field, ip_field) = if to == "opnsense" {
+        let hosts = out
+            .get_child("OPNsense")
+            .and_then(|opn| opn.get_child("unboundplus"))
+            .and_then(|u| u.get_child("hosts"))
+            .map(|h| h.get_children("host"));
+        (hosts, "hostname", "server")
+    } else {
+        let hosts = out.get_child("unbound").map(|u| u.get_children("hosts"));
+        (hosts, "host", "ip")
+    };
+    entries
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| {
+            let host = entry.get_text(&[host_field]).map(str::trim)?;
+            let ip = entry.get_text(&[ip_field])?.trim().parse().ok()?;
+            Some(StaticHost {
+                host: host.to_string(),
+                domain: entry
+                    .get_text(&["domain"])
+                    .map(str::trim)
+                    .unwrap_or("")
+                    .to_string(),
+                ip,
+                descr: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Append `<host>` items to `OPNsense/unboundplus/hosts`.
+///
+/// Returns `false` when the target has no `unboundplus` model to extend.
+fn add_opnsense_overrides(out: &mut XmlNode, entries: Vec<StaticHost>) -> bool {
+    let mut registry = UuidRegistry::from_config(out);
+    let Some(unbound) = out
+        .children
+        .iter_mut()
+        .find(|c| c.tag == "OPNsense")
+        .and_then(|opn| opn.children.iter_mut().find(|c| c.tag == "unboundplus"))
+    else {
+        return false;
+    };
+    let hosts = match unbound.children.iter().position(|c| c.tag == "hosts") {
+        Some(idx) => &mut unbound.children[idx],
+        None => {
+            unbound.children.push(XmlNode::new("hosts"));
+            unbound.children.last_mut().expect("just pushed")
+        }
+    };
+    for entry in entries {
+        let uuid = registry.uuid_for("unbound-host", &format!("{}|{}", entry.fqdn(), entry.ip));
+        let rr = if entry.ip.is_ipv4() { "A" } else { "AAAA" };
+        let mut host = XmlNode::new("host");
+        host.attributes.insert("uuid".to_string(), uuid);
+        for (tag, value) in [
+            ("enabled", "1".to_string()),
+            ("hostname", entry.host),
+            ("domain", entry.domain),
+            ("rr", rr.to_string()),
+            ("mxprio", String::new()),
+            ("mx", String::new()),
+            ("server", entry.ip.to_string()),
+            ("description", entry.descr),
+        ] {
+            host.children.push(leaf(tag, value));
+        }
+        hosts.children.push(host);
+    }
+    true
+}
+
+/// Append `<hosts>` entries to the pfSense `<unbound>` section.
+///
+/// Returns `false` when the target has no `<unbound>` section.
+fn add_pfsense_overrides(out: &mut XmlNode, entries: Vec<StaticHost>) -> bool {
+    let Some(unbound) = out.children.iter_mut().find(|c| c.tag == "unbound") else {
+        return false;
+    };
+    for entry in entries {
+        let mut hosts = XmlNode::new("hosts");
+        for (tag, value) in [
+            ("host", entry.host),
+            ("domain", entry.domain),
+            ("ip", entry.ip.to_string()),
+            ("descr", entry.descr),
+            ("aliases", String::new()),
+        ] {
+            hosts.children.push(leaf(tag, value));
+        }
+        unbound.children.push(hosts);
+    }
+    true
+}
+
+fn leaf(tag: &str, value: String) -> XmlNode {
+    let mut node = XmlNode::new(tag);
+    if !value.is_empty() {
+        node.text = Some(value);
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{apply, StaticMapDns};
+    use crate::diagnostics::Diagnostics;
+
+    const PF_SOURCE: &[u8] = br#"<pfsense><system><domain>home.arpa</domain></system><dhcpd><lan><enable/><staticmap><mac>aa</mac><ipaddr>192.168.1.10</ipaddr><hostname>nas</hostname><descr>NAS</descr></staticmap><staticmap><mac>bb</mac><ipaddr>192.168.1.11</ipaddr><hostname>printer.office.lan</hostname></staticmap><staticmap><mac>cc</mac><ipaddr>192.168.1.12</ipaddr></staticmap></lan></dhcpd><dhcpdv6><lan><staticmap><duid>1</duid><ipaddrv6>::10</ipaddrv6><hostname>nas</hostname></staticmap></lan></dhcpdv6></pfsense>"#;
+
+    #[test]
+    fn materializes_missing_overrides_for_opnsense() {
+        let source = parse(PF_SOURCE).expect("parse");
+        let mut out = parse(
+            br#"<opnsense><OPNsense><unboundplus version="1.0.13"><hosts><host uuid="h1"><enabled>1</enabled><hostname>nas</hostname><domain>home.arpa</domain><rr>A</rr><server>192.168.1.10</server></host></hosts></unboundplus></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        apply(
+            &mut out,
+            &source,
+            StaticMapDns::Materialize,
+            "opnsense",
+            &mut diagnostics,
+        );
+
+        let hosts = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("unboundplus"))
+            .and_then(|u| u.get_child("hosts"))
+            .expect("hosts");
+        let added = hosts.get_children("host");
+        assert_eq!(added.len(), 2);
+        assert_eq!(added[1].get_text(&["hostname"]), Some("printer"));
+        assert_eq!(added[1].get_text(&["domain"]), Some("office.lan"));
+        assert_eq!(added[1].get_text(&["server"]), Some("192.168.1.11"));
+        assert_eq!(added[1].get_text(&["rr"]), Some("A"));
+        assert!(added[1].attributes.contains_key("uuid"));
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn verify_reports_missing_and_conflicting_overrides() {
+        let source = parse(PF_SOURCE).expect("parse");
+        let mut out = parse(
+            br#"<pfsense><unbound><hosts><host>nas</host><domain>home.arpa</domain><ip>192.168.1.99</ip></hosts></unbound></pfsense>"#,
+        )
+        .expect("parse");
+        let before = out.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        apply(
+            &mut out,
+            &source,
+            StaticMapDns::Verify,
+            "pfsense",
+            &mut diagnostics,
+        );
+
+        assert_eq!(out, before);
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages.iter().any(|m| m.contains("conflicts")));
+        assert!(
+            messages
+                .iter()
+                .any(|m| m
+                    .contains("printer.office.lan -> 192.168.1.11 has no Unbound host override"))
+        );
+    }
+
+    #[test]
+    fn materializes_pfsense_overrides_from_kea_reservations() {
+        let source = parse(
+            br#"<opnsense><system><domain>lan.example</domain></system><OPNsense><Kea><dhcp4><reservations><reservation uuid="r1"><ip_address>10.0.0.5</ip_address><hostname>cam</hostname></reservation></reservations></dhcp4></Kea></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(br#"<pfsense><unbound><enable/></unbound></pfsense>"#).expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        apply(
+            &mut out,
+            &source,
+            StaticMapDns::Materialize,
+            "pfsense",
+            &mut diagnostics,
+        );
+
+        let hosts = out
+            .get_child("unbound")
+            .map(|u| u.get_children("hosts"))
+            .expect("unbound");
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].get_text(&["host"]), Some("cam"));
+        assert_eq!(hosts[0].get_text(&["domain"]), Some("lan.example"));
+        assert_eq!(hosts[0].get_text(&["ip"]), Some("10.0.0.5"));
+    }
+}
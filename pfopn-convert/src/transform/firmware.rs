@@ -0,0 +1,313 @@
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+
+/// Directory holding the pfSense repository configurations.
+const PFSENSE_REPO_DIR: &str = "/usr/local/etc/pfSense/pkg/repos/";
+
+/// Repository file name prefix; the suffix selects the branch.
+const PFSENSE_REPO_PREFIX: &str = "pfSense-repo";
+
+/// pfSense update branch, from `<system><pkg_repo_conf_path>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateBranch {
+    /// `pfSense-repo.conf`: latest stable release.
+    Stable,
+    /// `pfSense-repo-<version>.conf`: stable, pinned to one release.
+    Pinned(String),
+    /// `pfSense-repo-previous.conf`: previous stable release.
+    Previous,
+    /// `pfSense-repo-devel.conf`: development snapshots.
+    Development,
+    /// A repository file outside the stock set.
+    Custom(String),
+}
+
+/// Classify a pfSense `pkg_repo_conf_path`.
+pub fn parse_update_branch(path: &str) -> UpdateBranch {
+    let custom = || UpdateBranch::Custom(path.to_string());
+    let Some(file) = path.strip_prefix(PFSENSE_REPO_DIR) else {
+        return custom();
+    };
+    let Some(suffix) = file
+        .strip_prefix(PFSENSE_REPO_PREFIX)
+        .and_then(|rest| rest.strip_suffix(".conf"))
+    else {
+        return custom();
+    };
+    match suffix {
+        "" => UpdateBranch::Stable,
+        "-devel" => UpdateBranch::Development,
+        "-previous" => UpdateBranch::Previous,
+        _ => match suffix.strip_prefix('-') {
+            Some(version) if version.starts_with(|c: char| c.is_ascii_digit()) => {
+                UpdateBranch::Pinned(version.to_string())
+            }
+            _ => custom(),
+        },
+    }
+}
+
+/// Translate update settings for OPNsense output.
+///
+/// pfSense has no `<firmware>` block, so the destination baseline's is
+/// carried over unchanged — mirror, flavour, subscription, and the plugin
+/// list that backend detection reads — except that a pfSense development
+/// branch selects the OPNsense development type. pfSense's
+/// `<pkg_repo_conf_path>` is removed; a previous-release or custom
+/// repository is reported since OPNsense has no equivalent.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(system) = child_mut(out, "system") else {
+        return;
+    };
+    system.children.retain(|c| c.tag != "pkg_repo_conf_path");
+
+    let mut firmware = destination_baseline
+        .get_child("system")
+        .and_then(|s| s.get_child("firmware"))
+        .cloned();
+    let branch = source
+        .get_child("system")
+        .and_then(|s| text(s, "pkg_repo_conf_path"))
+        .map(parse_update_branch);
+    match branch {
+        Some(UpdateBranch::Development) => {
+            let firmware = firmware.get_or_insert_with(|| XmlNode::new("firmware"));
+            set_text(firmware, "type", "devel");
+            diagnostics.info(
+                "firmware",
+                "pfSense development branch mapped to the OPNsense development release type",
+            );
+        }
+        Some(UpdateBranch::Previous) => diagnostics.warn(
+            "firmware",
+            "pfSense 'previous' update branch has no OPNsense equivalent; OPNsense follows the current production series",
+        ),
+        Some(UpdateBranch::Custom(path)) => diagnostics.warn(
+            "firmware",
+            format!(
+                "pfSense custom package repository '{path}' is pfSense-specific and was not carried; select an OPNsense mirror under System > Firmware > Settings"
+            ),
+        ),
+        Some(UpdateBranch::Stable | UpdateBranch::Pinned(_)) | None => {}
+    }
+
+    system.children.retain(|c| c.tag != "firmware");
+    system.children.extend(firmware);
+}
+
+/// Translate update settings for pfSense output.
+///
+/// The OPNsense `<firmware>` block is removed. Its development type selects
+/// the pfSense development branch; otherwise the baseline's repository is
+/// kept. A custom mirror, a non-default flavour, or a business subscription
+/// only apply to OPNsense and are reported.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(system) = child_mut(out, "system") else {
+        return;
+    };
+    system
+        .children
+        .retain(|c| c.tag != "firmware" && c.tag != "pkg_repo_conf_path");
+
+    let firmware = source
+        .get_child("system")
+        .and_then(|s| s.get_child("firmware"));
+    let repo = if firmware.and_then(|f| text(f, "type")) == Some("devel") {
+        diagnostics.info(
+            "firmware",
+            "OPNsense development release type mapped to the pfSense development branch",
+        );
+        Some(format!(
+            "{PFSENSE_REPO_DIR}{PFSENSE_REPO_PREFIX}-devel.conf"
+        ))
+    } else {
+        destination_baseline
+            .get_child("system")
+            .and_then(|s| text(s, "pkg_repo_conf_path"))
+            .map(ToOwned::to_owned)
+    };
+    if let Some(repo) = repo {
+        set_text(system, "pkg_repo_conf_path", &repo);
+    }
+
+    let Some(firmware) = firmware else {
+        return;
+    };
+    if let Some(mirror) = text(firmware, "mirror") {
+        diagnostics.warn(
+            "firmware",
+            format!(
+                "OPNsense mirror '{mirror}' is OPNsense-specific and was not carried; pfSense updates come from the Netgate repositories"
+            ),
+        );
+    }
+    if let Some(flavour) = text(firmware, "flavour") {
+        diagnostics.warn(
+            "firmware",
+            format!("OPNsense firmware flavour '{flavour}' has no pfSense equivalent"),
+        );
+    }
+    match text(firmware, "type") {
+        None | Some("devel") => {}
+        Some(kind) => diagnostics.warn(
+            "firmware",
+            format!("OPNsense '{kind}' release type has no pfSense equivalent; using the baseline branch"),
+        ),
+    }
+    if text(firmware, "subscription").is_some() {
+        diagnostics.warn(
+            "firmware",
+            "OPNsense business subscription key was not carried",
+        );
+    }
+}
+
+fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
+    node.children.iter_mut().find(|c| c.tag == tag)
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = child_mut(node, tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{parse_update_branch, to_opnsense, to_pfsense, UpdateBranch};
+    use crate::diagnostics::{Diagnostics, Severity};
+    use crate::plugin_detect::collect_opnsense_declared_plugins;
+
+    #[test]
+    fn classifies_pfsense_repositories() {
+        let dir = "/usr/local/etc/pfSense/pkg/repos";
+        assert_eq!(
+            parse_update_branch(&format!("{dir}/pfSense-repo.conf")),
+            UpdateBranch::Stable
+        );
+        assert_eq!(
+            parse_update_branch(&format!("{dir}/pfSense-repo-2.7.2.conf")),
+            UpdateBranch::Pinned("2.7.2".to_string())
+        );
+        assert_eq!(
+            parse_update_branch(&format!("{dir}/pfSense-repo-devel.conf")),
+            UpdateBranch::Development
+        );
+        assert_eq!(
+            parse_update_branch(&format!("{dir}/pfSense-repo-previous.conf")),
+            UpdateBranch::Previous
+        );
+        assert!(matches!(
+            parse_update_branch("/root/my-repo.conf"),
+            UpdateBranch::Custom(_)
+        ));
+    }
+
+    #[test]
+    fn keeps_baseline_firmware_and_plugins_for_opnsense() {
+        let source = parse(
+            br#"<pfsense><system><hostname>fw</hostname><pkg_repo_conf_path>/usr/local/etc/pfSense/pkg/repos/pfSense-repo-devel.conf</pkg_repo_conf_path></system></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(
+            br#"<opnsense><system><firmware version="1.0.1"><mirror>https://mirror.example</mirror><plugins>os-isc-dhcp os-wireguard</plugins><type/></firmware></system></opnsense>"#,
+        )
+        .expect("parse");
+        // The merge copies <system> verbatim from the source.
+        let mut out = parse(b"<opnsense/>").expect("parse");
+        out.children
+            .push(source.get_child("system").expect("system").clone());
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let firmware = out
+            .get_child("system")
+            .and_then(|s| s.get_child("firmware"))
+            .expect("firmware");
+        assert_eq!(
+            firmware.attributes.get("version").map(String::as_str),
+            Some("1.0.1")
+        );
+        assert_eq!(
+            firmware.get_text(&["mirror"]),
+            Some("https://mirror.example")
+        );
+        assert_eq!(firmware.get_text(&["type"]), Some("devel"));
+        assert_eq!(
+            collect_opnsense_declared_plugins(&out),
+            vec!["os-isc-dhcp", "os-wireguard"]
+        );
+        assert!(out.get_text(&["system", "pkg_repo_conf_path"]).is_none());
+        assert_eq!(diagnostics.count(Severity::Warning), 0);
+    }
+
+    #[test]
+    fn warns_about_custom_pfsense_repository() {
+        let source = parse(
+            br#"<pfsense><system><pkg_repo_conf_path>/root/custom.conf</pkg_repo_conf_path></system></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = source.clone();
+        out.tag = "opnsense".to_string();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(
+            &mut out,
+            &source,
+            &parse(b"<opnsense/>").expect("parse"),
+            &mut diagnostics,
+        );
+
+        assert_eq!(diagnostics.count(Severity::Warning), 1);
+    }
+
+    #[test]
+    fn drops_opnsense_firmware_for_pfsense() {
+        let source = parse(
+            br#"<opnsense><system><firmware><mirror>https://pkg.opnsense.org</mirror><flavour>libressl</flavour><plugins>os-wireguard</plugins><type/><subscription>secret</subscription></firmware></system></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(
+            br#"<pfsense><system><pkg_repo_conf_path>/usr/local/etc/pfSense/pkg/repos/pfSense-repo.conf</pkg_repo_conf_path></system></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = source.clone();
+        out.tag = "pfsense".to_string();
+        let mut diagnostics = Diagnostics::default();
+
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let system = out.get_child("system").expect("system");
+        assert!(system.get_child("firmware").is_none());
+        assert_eq!(
+            system.get_text(&["pkg_repo_conf_path"]),
+            Some("/usr/local/etc/pfSense/pkg/repos/pfSense-repo.conf")
+        );
+        assert_eq!(diagnostics.count(Severity::Warning), 3);
+        assert!(diagnostics.iter().all(|d| !d.message.contains("secret")));
+    }
+}
@@ -0,0 +1,548 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::is_falsy;
+use crate::transform::units::{self, GATEWAY_MONITOR};
+
+/// Longest gateway or gateway group name either platform accepts.
+const MAX_GATEWAY_NAME_LEN: usize = 32;
+
+/// Gateway monitoring fields stored under different names: (pfSense, OPNsense).
+///
+/// The latency and loss thresholds (`latencylow`, `latencyhigh`, `losslow`,
+/// `losshigh`), the alternative monitor address (`monitor`), and
+/// `monitor_disable`/`force_down` share their names and carry over as-is.
+const MONITOR_RENAMES: &[(&str, &str)] = &[
+    ("data_payload", "data_length"),
+    ("dpinger_dont_add_static_route", "monitor_noroute"),
+    ("nonlocalgateway", "fargw"),
+];
+
+/// OPNsense's default gateway priority; any other value is user-set.
+const DEFAULT_PRIORITY: &str = "255";
+
+/// Convert gateway monitoring timings and gateway groups for OPNsense output.
+///
+/// See [`apply`], [`normalize_group_names`], and [`default_group_to_opnsense`].
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    _source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(out, "opnsense", diagnostics);
+    normalize_group_names(out, diagnostics);
+    default_group_to_opnsense(out, diagnostics);
+}
+
+/// Convert gateway monitoring timings and gateway groups for pfSense output.
+///
+/// See [`apply`], [`normalize_group_names`], and [`gateway_switching_to_pfsense`].
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    _source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(out, "pfsense", diagnostics);
+    normalize_group_names(out, diagnostics);
+    gateway_switching_to_pfsense(out, diagnostics);
+}
+
+/// Translate the dpinger settings of every `<gateways><gateway_item>`.
+///
+/// The section is copied wholesale from the source, so its probe interval,
+/// loss interval, averaging period, and alert interval are still in the
+/// source platform's units (milliseconds on pfSense, seconds on OPNsense),
+/// and fields in [`MONITOR_RENAMES`] still carry the source's names. Settings
+/// the target has no per-gateway field for are dropped with a warning:
+/// pfSense's `action_disable` and `gw_down_kill_states`, and a non-default
+/// OPNsense `priority`.
+fn apply(out: &mut XmlNode, to: &str, diagnostics: &mut Diagnostics) {
+    let Some(gateways) = out.children.iter_mut().find(|c| c.tag == "gateways") else {
+        return;
+    };
+    for item in gateways
+        .children
+        .iter_mut()
+        .filter(|c| c.tag == "gateway_item")
+    {
+        let label = format!(
+            "gateway {}",
+            item.get_text(&["name"]).map(str::trim).unwrap_or("?")
+        );
+        units::convert_children(item, GATEWAY_MONITOR, to, "gateways", &label, diagnostics);
+        rename_monitor_fields(item, to);
+        drop_unsupported_monitor_fields(item, to, &label, diagnostics);
+    }
+}
+
+/// Rename the [`MONITOR_RENAMES`] fields of one gateway to `to`'s names.
+fn rename_monitor_fields(item: &mut XmlNode, to: &str) {
+    for (pf, opn) in MONITOR_RENAMES {
+        let (from, target) = if to == "opnsense" {
+            (pf, opn)
+        } else {
+            (opn, pf)
+        };
+        if item.get_child(target).is_some() {
+            item.children.retain(|c| c.tag != *from);
+            continue;
+        }
+        if let Some(child) = item.children.iter_mut().find(|c| c.tag == *from) {
+            child.tag = (*target).to_string();
+        }
+    }
+}
+
+/// Remove monitoring fields `to` has no per-gateway equivalent for, warning
+/// when they held a setting.
+fn drop_unsupported_monitor_fields(
+    item: &mut XmlNode,
+    to: &str,
+    label: &str,
+    diagnostics: &mut Diagnostics,
+) {
+    let fields: &[(&str, &str)] = if to == "opnsense" {
+        &[
+            (
+                "action_disable",
+                "monitoring action is disabled (gateway is never marked down); OPNsense has no equivalent, disable monitoring or leave the gateway out of groups instead",
+            ),
+            (
+                "gw_down_kill_states",
+                "kills states when the gateway goes down; use the global 'Kill states' gateway option in OPNsense",
+            ),
+        ]
+    } else {
+        &[(
+            "priority",
+            "has a default route priority; pfSense picks the default gateway explicitly, so select it or a gateway group as the default gateway",
+        )]
+    };
+    for (field, reason) in fields {
+        let Some(idx) = item.children.iter().position(|c| c.tag == *field) else {
+            continue;
+        };
+        let value = item.children[idx]
+            .text
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string();
+        item.children.remove(idx);
+        let set = match *field {
+            "priority" => !value.is_empty() && value != DEFAULT_PRIORITY,
+            _ => !is_falsy(&value),
+        };
+        if set {
+            diagnostics.warn("gateways", format!("{label} {reason}"));
+        }
+    }
+}
+
+/// Rename gateway groups the target would reject and follow the rename in
+/// every policy routing rule.
+///
+/// Group names may only hold letters, digits, and underscores, are capped
+/// at [`MAX_GATEWAY_NAME_LEN`] characters, and must not match a gateway or
+/// another group. Hand-edited or imported configs can break any of those;
+/// such a group is renamed, and filter rules whose `<gateway>` named it and
+/// `defaultgw4`/`defaultgw6` selections are rewritten to the new name.
+fn normalize_group_names(out: &mut XmlNode, diagnostics: &mut Diagnostics) {
+    let Some(gateways) = out.children.iter_mut().find(|c| c.tag == "gateways") else {
+        return;
+    };
+    let mut taken: BTreeSet<String> = gateways
+        .children
+        .iter()
+        .filter(|c| c.tag == "gateway_item")
+        .filter_map(|c| c.get_text(&["name"]))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect();
+    let mut renames = BTreeMap::new();
+    for group in gateways
+        .children
+        .iter_mut()
+        .filter(|c| c.tag == "gateway_group")
+    {
+        let Some(name) = group.get_text(&["name"]).map(str::to_string) else {
+            continue;
+        };
+        let valid = unique_name(&valid_name(&name), &taken);
+        taken.insert(valid.to_ascii_lowercase());
+        if valid != name {
+            set_text(group, "name", &valid);
+            diagnostics.info(
+                "gateways",
+                format!("gateway group '{name}' renamed to '{valid}' to fit gateway naming rules"),
+            );
+            renames.insert(name, valid);
+        }
+    }
+    if renames.is_empty() {
+        return;
+    }
+
+    for child in &mut gateways.children {
+        if matches!(child.tag.as_str(), "defaultgw4" | "defaultgw6") {
+            rename_text(child, &renames);
+        }
+    }
+    if let Some(filter) = out.children.iter_mut().find(|c| c.tag == "filter") {
+        for rule in filter.children.iter_mut().filter(|c| c.tag == "rule") {
+            if let Some(gateway) = rule.children.iter_mut().find(|c| c.tag == "gateway") {
+                rename_text(gateway, &renames);
+            }
+        }
+    }
+}
+
+/// Replace characters outside `[A-Za-z0-9_]` and cap the length.
+fn valid_name(name: &str) -> String {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_GATEWAY_NAME_LEN)
+        .collect();
+    if cleaned.is_empty() {
+        "GWGROUP".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Append `_2`, `_3`, ... until `name` is not taken (case-insensitively).
+fn unique_name(name: &str, taken: &BTreeSet<String>) -> String {
+    if !taken.contains(&name.to_ascii_lowercase()) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| {
+            let suffix = format!("_{n}");
+            let stem: String = name
+                .chars()
+                .take(MAX_GATEWAY_NAME_LEN - suffix.len())
+                .collect();
+            format!("{stem}{suffix}")
+        })
+        .find(|candidate| !taken.contains(&candidate.to_ascii_lowercase()))
+        .expect("unbounded suffixes")
+}
+
+fn rename_text(node: &mut XmlNode, renames: &BTreeMap<String, String>) {
+    if let Some(renamed) = node.text.as_deref().and_then(|t| renames.get(t.trim())) {
+        node.text = Some(renamed.clone());
+    }
+}
+
+/// Replace a default gateway group with OPNsense default gateway switching.
+///
+/// pfSense fails the default route over by selecting a gateway group as
+/// `defaultgw4`/`defaultgw6`. OPNsense cannot use a group there; it marks
+/// one `gateway_item` as the default and, with `<system><gw_switch_default>`
+/// enabled, moves the default route to the next gateway by priority when it
+/// goes down. The group's lowest-tier member becomes the default gateway and
+/// switching is turned on. Policy routing rules keep using the group.
+fn default_group_to_opnsense(out: &mut XmlNode, diagnostics: &mut Diagnostics) {
+    let Some(gateways) = out.children.iter_mut().find(|c| c.tag == "gateways") else {
+        return;
+    };
+    let mut switched = false;
+    for (tag, protocol) in [("defaultgw4", "inet"), ("defaultgw6", "inet6")] {
+        let Some(selected) = gateways.get_text(&[tag]).map(|t| t.trim().to_string()) else {
+            continue;
+        };
+        let Some(group) = gateways
+            .children
+            .iter()
+            .find(|c| c.tag == "gateway_group" && c.get_text(&["name"]) == Some(&selected))
+        else {
+            continue;
+        };
+        let Some(member) = first_tier_member(group) else {
+            diagnostics.warn(
+                "gateways",
+                format!("default gateway group '{selected}' has no members; OPNsense will have no default gateway"),
+            );
+            continue;
+        };
+        for item in gateways
+            .children
+            .iter_mut()
+            .filter(|c| c.tag == "gateway_item")
+        {
+            let name = item.get_text(&["name"]).map(str::trim);
+            if name == Some(member.as_str()) {
+                set_text(item, "defaultgw", "1");
+            } else if item.get_text(&["ipprotocol"]).unwrap_or("inet") == protocol {
+                item.children.retain(|c| c.tag != "defaultgw");
+            }
+        }
+        set_text(gateways, tag, &member);
+        diagnostics.info(
+            "gateways",
+            format!(
+                "default gateway group '{selected}' is not supported on OPNsense; made '{member}' the default gateway and enabled default gateway switching"
+            ),
+        );
+        switched = true;
+    }
+    if switched {
+        set_text(ensure_child_mut(out, "system"), "gw_switch_default", "1");
+    }
+}
+
+/// Name of the group member with the lowest tier (`NAME|tier|vip` items).
+fn first_tier_member(group: &XmlNode) -> Option<String> {
+    group
+        .children
+        .iter()
+        .filter(|c| c.tag == "item")
+        .filter_map(|item| {
+            let mut parts = item.text.as_deref()?.split('|');
+            let name = parts.next()?.trim();
+            let tier = parts
+                .next()
+                .and_then(|t| t.trim().parse::<u32>().ok())
+                .unwrap_or(u32::MAX);
+            (!name.is_empty()).then(|| (tier, name.to_string()))
+        })
+        .min_by_key(|(tier, _)| *tier)
+        .map(|(_, name)| name)
+}
+
+/// Drop OPNsense default gateway switching, which pfSense does not have.
+///
+/// pfSense fails the default route over by selecting a gateway group as the
+/// default gateway instead, which needs the user to choose the tiers.
+fn gateway_switching_to_pfsense(out: &mut XmlNode, diagnostics: &mut Diagnostics) {
+    let Some(system) = out.children.iter_mut().find(|c| c.tag == "system") else {
+        return;
+    };
+    let Some(idx) = system
+        .children
+        .iter()
+        .position(|c| c.tag == "gw_switch_default")
+    else {
+        return;
+    };
+    let enabled = system.children[idx]
+        .text
+        .as_deref()
+        .is_some_and(|t| !matches!(t.trim(), "" | "0"));
+    system.children.remove(idx);
+    if enabled {
+        diagnostics.warn(
+            "gateways",
+            "default gateway switching has no pfSense equivalent; create a gateway group of the failover gateways and select it as the default gateway",
+        );
+    }
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    let child = ensure_child_mut(node, tag);
+    child.text = (!value.is_empty()).then(|| value.to_string());
+}
+
+/// Get a mutable reference to a child, creating it if it doesn't exist.
+fn ensure_child_mut<'a>(parent: &'a mut XmlNode, tag: &str) -> &'a mut XmlNode {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == tag) {
+        return &mut parent.children[idx];
+    }
+    parent.children.push(XmlNode::new(tag));
+    let last = parent.children.len() - 1;
+    &mut parent.children[last]
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::{Diagnostics, Severity};
+
+    #[test]
+    fn converts_pfsense_milliseconds_to_opnsense_seconds() {
+        let source = parse(
+            br#"<pfsense><gateways><gateway_item><name>WAN_DHCP</name><interval>1000</interval><loss_interval>2000</loss_interval><time_period>60000</time_period><alert_interval>1000</alert_interval></gateway_item><defaultgw4>WAN_DHCP</defaultgw4></gateways></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = source.clone();
+        out.tag = "opnsense".to_string();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &source, &mut diagnostics);
+
+        let item = out
+            .get_child("gateways")
+            .and_then(|g| g.get_child("gateway_item"))
+            .expect("gateway");
+        assert_eq!(item.get_text(&["interval"]), Some("1"));
+        assert_eq!(item.get_text(&["loss_interval"]), Some("2"));
+        assert_eq!(item.get_text(&["time_period"]), Some("60"));
+        assert_eq!(item.get_text(&["alert_interval"]), Some("1"));
+        assert_eq!(diagnostics.count(Severity::Warning), 0);
+    }
+
+    #[test]
+    fn converts_opnsense_seconds_to_pfsense_milliseconds() {
+        let source = parse(
+            br#"<opnsense><gateways><gateway_item><name>GW1</name><interval>1</interval><loss_interval>4</loss_interval><time_period/></gateway_item></gateways></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = source.clone();
+        out.tag = "pfsense".to_string();
+        let mut diagnostics = Diagnostics::default();
+
+        to_pfsense(&mut out, &source, &source, &mut diagnostics);
+
+        assert_eq!(
+            out.get_text(&["gateways", "gateway_item", "interval"]),
+            Some("1000")
+        );
+        assert_eq!(
+            out.get_text(&["gateways", "gateway_item", "loss_interval"]),
+            Some("4000")
+        );
+        assert_eq!(
+            out.get_text(&["gateways", "gateway_item", "time_period"]),
+            None
+        );
+    }
+
+    #[test]
+    fn maps_monitor_settings_to_opnsense() {
+        let source = parse(br#"<pfsense/>"#).expect("parse");
+        let mut out = parse(
+            br#"<pfsense><gateways><gateway_item><name>WAN_GW</name><monitor>9.9.9.9</monitor><latencylow>100</latencylow><latencyhigh>300</latencyhigh><losslow>5</losslow><losshigh>15</losshigh><data_payload>64</data_payload><dpinger_dont_add_static_route/><nonlocalgateway/><action_disable/><gw_down_kill_states/></gateway_item></gateways></pfsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &source, &mut diagnostics);
+        let item = out
+            .get_child("gateways")
+            .and_then(|g| g.get_child("gateway_item"))
+            .expect("gateway_item");
+        assert_eq!(item.get_text(&["monitor"]), Some("9.9.9.9"));
+        assert_eq!(item.get_text(&["latencylow"]), Some("100"));
+        assert_eq!(item.get_text(&["losshigh"]), Some("15"));
+        assert_eq!(item.get_text(&["data_length"]), Some("64"));
+        assert!(item.get_child("monitor_noroute").is_some());
+        assert!(item.get_child("fargw").is_some());
+        for gone in [
+            "data_payload",
+            "dpinger_dont_add_static_route",
+            "nonlocalgateway",
+            "action_disable",
+            "gw_down_kill_states",
+        ] {
+            assert!(item.get_child(gone).is_none(), "{gone}");
+        }
+        let warnings: Vec<&str> = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("gateway WAN_GW monitoring action is disabled"));
+    }
+
+    #[test]
+    fn maps_monitor_settings_to_pfsense() {
+        let source = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = parse(
+            br#"<opnsense><gateways><gateway_item><name>WAN_GW</name><data_length>32</data_length><monitor_noroute>1</monitor_noroute><fargw>0</fargw><priority>255</priority></gateway_item><gateway_item><name>WAN2_GW</name><priority>10</priority></gateway_item></gateways></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::default();
+
+        to_pfsense(&mut out, &source, &source, &mut diagnostics);
+        let items = out
+            .get_child("gateways")
+            .map(|g| g.get_children("gateway_item"))
+            .expect("gateways");
+        assert_eq!(items[0].get_text(&["data_payload"]), Some("32"));
+        assert_eq!(
+            items[0].get_text(&["dpinger_dont_add_static_route"]),
+            Some("1")
+        );
+        assert_eq!(items[0].get_text(&["nonlocalgateway"]), Some("0"));
+        assert!(items[0].get_child("priority").is_none());
+        let warnings: Vec<&str> = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("gateway WAN2_GW has a default route priority"));
+    }
+
+    #[test]
+    fn renames_invalid_groups_and_their_policy_rules() {
+        let source = parse(
+            br#"<pfsense><gateways><gateway_item><name>WAN_DHCP</name></gateway_item><gateway_group><name>Multi-WAN</name><item>WAN_DHCP|1|address</item></gateway_group><gateway_group><name>wan_dhcp</name><item>WAN_DHCP|1|address</item></gateway_group></gateways><filter><rule><gateway>Multi-WAN</gateway></rule><rule><gateway>wan_dhcp</gateway></rule><rule><gateway>WAN_DHCP</gateway></rule></filter></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = source.clone();
+        out.tag = "opnsense".to_string();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &source, &mut diagnostics);
+
+        let names: Vec<&str> = out
+            .get_child("gateways")
+            .expect("gateways")
+            .get_children("gateway_group")
+            .into_iter()
+            .filter_map(|g| g.get_text(&["name"]))
+            .collect();
+        assert_eq!(names, ["Multi_WAN", "wan_dhcp_2"]);
+        let rule_gateways: Vec<&str> = out
+            .get_child("filter")
+            .expect("filter")
+            .get_children("rule")
+            .into_iter()
+            .filter_map(|r| r.get_text(&["gateway"]))
+            .collect();
+        assert_eq!(rule_gateways, ["Multi_WAN", "wan_dhcp_2", "WAN_DHCP"]);
+        assert_eq!(diagnostics.count(Severity::Info), 2);
+    }
+
+    #[test]
+    fn default_gateway_group_becomes_opnsense_gateway_switching() {
+        let source = parse(
+            br#"<pfsense><system/><gateways><gateway_item><name>WAN1</name><ipprotocol>inet</ipprotocol><defaultgw>1</defaultgw></gateway_item><gateway_item><name>WAN2</name><ipprotocol>inet</ipprotocol></gateway_item><gateway_group><name>FAILOVER</name><item>WAN1|2|address</item><item>WAN2|1|address</item></gateway_group><defaultgw4>FAILOVER</defaultgw4></gateways></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = source.clone();
+        out.tag = "opnsense".to_string();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &source, &mut diagnostics);
+
+        let gateways = out.get_child("gateways").expect("gateways");
+        assert_eq!(gateways.get_text(&["defaultgw4"]), Some("WAN2"));
+        let items = gateways.get_children("gateway_item");
+        assert_eq!(items[0].get_text(&["defaultgw"]), None);
+        assert_eq!(items[1].get_text(&["defaultgw"]), Some("1"));
+        assert_eq!(out.get_text(&["system", "gw_switch_default"]), Some("1"));
+
+        let mut back = out.clone();
+        back.tag = "pfsense".to_string();
+        let mut diagnostics = Diagnostics::default();
+        to_pfsense(&mut back, &out, &out, &mut diagnostics);
+        assert!(back.get_text(&["system", "gw_switch_default"]).is_none());
+        assert_eq!(diagnostics.count(Severity::Warning), 1);
+    }
+}
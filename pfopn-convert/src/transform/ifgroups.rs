@@ -64,12 +64,16 @@ fn rewrite_group_tokens(root: &mut XmlNode, from: &str, to: &str) {
 
 /// Recursively walk the tree and rewrite group name tokens in relevant elements.
 ///
-/// Only elements whose tag is `interface`, `members`, or `interfaces` have
-/// their text content inspected -- these are the fields where interface group
-/// names appear as comma/space-separated token lists in both pfSense and
-/// OPNsense configs.
+/// Only elements whose tag is `interface`, `members`, `interfaces`, or
+/// `ifname` have their text content inspected -- these are the fields where
+/// interface group names appear as comma/space-separated token lists in both
+/// pfSense and OPNsense configs, plus the group definition itself, so rules
+/// and the group they name keep the same casing.
 fn rewrite_node(node: &mut XmlNode, from: &str, to: &str) {
-    if node.tag == "interface" || node.tag == "members" || node.tag == "interfaces" {
+    if matches!(
+        node.tag.as_str(),
+        "interface" | "members" | "interfaces" | "ifname"
+    ) {
         if let Some(text) = node.text.clone() {
             let rewritten = rewrite_token_list(&text, from, to);
             if rewritten != text {
@@ -159,7 +163,7 @@ mod tests {
     #[test]
     fn pfsense_rewrites_wireguard_group_token_back() {
         let mut root = parse(
-            br#"<pfsense><filter><rule><interface>wireGuard</interface></rule></filter></pfsense>"#,
+            br#"<pfsense><ifgroups><ifgroupentry><ifname>wireGuard</ifname><members>opt3</members></ifgroupentry></ifgroups><filter><rule><interface>wireGuard</interface></rule></filter></pfsense>"#,
         )
         .expect("parse");
         normalize_for_pfsense(&mut root);
@@ -167,5 +171,9 @@ mod tests {
             root.get_text(&["filter", "rule", "interface"]),
             Some("WireGuard")
         );
+        assert_eq!(
+            root.get_text(&["ifgroups", "ifgroupentry", "ifname"]),
+            Some("WireGuard")
+        );
     }
 }
@@ -19,24 +19,27 @@ use xml_diff_core::XmlNode;
 /// 3. Clone the full source interface node (all settings), rename its tag to
 ///    the mapped name, then overwrite `<if>` with the target baseline's
 ///    device name.
-/// 4. Upsert the merged node into the output tree.
+/// 4. Carry the link settings (`mtu`, `mss`, `media`, `mediaopt`). Forced
+///    media names are driver specific, so they are only kept when the source
+///    and target NICs use the same driver; otherwise the target autoselects.
+/// 5. Upsert the merged node into the output tree.
+///
+/// Returns one note per interface whose forced media setting was dropped.
 pub fn apply(
     out: &mut XmlNode,
     source: &XmlNode,
     target: &XmlNode,
     interface_map_from: Option<&BTreeMap<String, String>>,
-) {
-    let src_interfaces = match source.get_child("interfaces") {
-        Some(n) => n,
-        None => return,
+) -> Vec<String> {
+    let mut notes = Vec::new();
+    let Some(src_interfaces) = source.get_child("interfaces") else {
+        return notes;
     };
-    let target_interfaces = match target.get_child("interfaces") {
-        Some(n) => n,
-        None => return,
+    let Some(target_interfaces) = target.get_child("interfaces") else {
+        return notes;
     };
-    let out_interfaces = match child_mut(out, "interfaces") {
-        Some(n) => n,
-        None => return,
+    let Some(out_interfaces) = child_mut(out, "interfaces") else {
+        return notes;
     };
 
     for src_iface in &src_interfaces.children {
@@ -57,9 +60,45 @@ pub fn apply(
         // Overwrite the device binding with the target's physical NIC name.
         if let Some(dst_if) = target_iface.get_text(&["if"]).map(str::trim) {
             set_or_insert_text_child(&mut merged_iface, "if", dst_if);
+            let src_if = src_iface.get_text(&["if"]).map(str::trim).unwrap_or("");
+            if let Some(media) = forced_media(src_iface) {
+                if device_driver(src_if) != device_driver(dst_if) {
+                    merged_iface
+                        .children
+                        .retain(|c| c.tag != "media" && c.tag != "mediaopt");
+                    notes.push(format!(
+                        "interface {mapped}: dropped forced media '{media}' ({src_if} -> {dst_if} changes driver); target will autoselect"
+                    ));
+                }
+            }
         }
         upsert_child(out_interfaces, merged_iface);
     }
+    notes
+}
+
+/// Forced media of an interface (`media` plus `mediaopt`), if any.
+fn forced_media(iface: &XmlNode) -> Option<String> {
+    let media = iface
+        .get_text(&["media"])
+        .map(str::trim)
+        .filter(|v| !v.is_empty() && !v.eq_ignore_ascii_case("autoselect"))?;
+    match iface
+        .get_text(&["mediaopt"])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        Some(opt) => Some(format!("{media} {opt}")),
+        None => Some(media.to_string()),
+    }
+}
+
+/// Driver part of a device name: `igb0` -> `igb`, `vtnet1.100` -> `vtnet`.
+pub fn device_driver(device: &str) -> &str {
+    let end = device
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(device.len());
+    &device[..end]
 }
 
 /// Return a mutable reference to the first child with the given tag.
@@ -189,4 +228,55 @@ mod tests {
         assert_eq!(out.get_text(&["interfaces", "lan", "ipaddrv6"]), None);
         assert_eq!(out.get_text(&["interfaces", "lan", "subnetv6"]), None);
     }
+
+    #[test]
+    fn carries_mtu_mss_and_media_on_same_driver() {
+        let source = parse(
+            br#"<pfsense><interfaces><wan><if>igb0</if><mtu>9000</mtu><mss>1460</mss><media>1000baseT</media><mediaopt>full-duplex</mediaopt></wan></interfaces></pfsense>"#,
+        )
+        .expect("parse");
+        let target =
+            parse(br#"<opnsense><interfaces><wan><if>igb1</if></wan></interfaces></opnsense>"#)
+                .expect("parse");
+        let mut out = target.clone();
+
+        let notes = apply(&mut out, &source, &target, None);
+        assert!(notes.is_empty());
+        assert_eq!(out.get_text(&["interfaces", "wan", "mtu"]), Some("9000"));
+        assert_eq!(out.get_text(&["interfaces", "wan", "mss"]), Some("1460"));
+        assert_eq!(
+            out.get_text(&["interfaces", "wan", "media"]),
+            Some("1000baseT")
+        );
+        assert_eq!(
+            out.get_text(&["interfaces", "wan", "mediaopt"]),
+            Some("full-duplex")
+        );
+    }
+
+    #[test]
+    fn drops_forced_media_when_driver_changes() {
+        let source = parse(
+            br#"<pfsense><interfaces><wan><if>igb0</if><mtu>1492</mtu><media>100baseTX</media><mediaopt>full-duplex</mediaopt></wan></interfaces></pfsense>"#,
+        )
+        .expect("parse");
+        let target =
+            parse(br#"<opnsense><interfaces><wan><if>vtnet0</if></wan></interfaces></opnsense>"#)
+                .expect("parse");
+        let mut out = target.clone();
+
+        let notes = apply(&mut out, &source, &target, None);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains("100baseTX full-duplex"));
+        assert_eq!(out.get_text(&["interfaces", "wan", "mtu"]), Some("1492"));
+        assert_eq!(out.get_text(&["interfaces", "wan", "media"]), None);
+        assert_eq!(out.get_text(&["interfaces", "wan", "mediaopt"]), None);
+    }
+
+    #[test]
+    fn device_driver_strips_unit_and_vlan() {
+        assert_eq!(super::device_driver("igb0"), "igb");
+        assert_eq!(super::device_driver("vtnet1.100"), "vtnet");
+        assert_eq!(super::device_driver("lagg0_vlan20"), "lagg");
+    }
 }
@@ -1,4 +1,5 @@
 use crate::transform::ipsec_pf_to_opn;
+use crate::uuid_registry::UuidRegistry;
 use xml_diff_core::XmlNode;
 
 /// Transform IPsec configuration for OPNsense output.
@@ -28,7 +29,9 @@ pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, target: &XmlNode) {
         if looks_like_pfsense_ipsec(top) {
             // pfSense phase1/phase2 layout -- translate into OPNsense's
             // Swanctl connection model and IPsec pre-shared-key store.
-            let (mapped_ipsec, mapped_swanctl) = ipsec_pf_to_opn::map_pf_ipsec_to_opnsense(top);
+            let mut registry = UuidRegistry::from_config(out);
+            let (mapped_ipsec, mapped_swanctl) =
+                ipsec_pf_to_opn::map_pf_ipsec_to_opnsense(top, &mut registry);
             upsert_nested_opnsense_node("IPsec", out, &mapped_ipsec);
             upsert_nested_opnsense_node("Swanctl", out, &mapped_swanctl);
         } else {
@@ -254,6 +257,9 @@ mod tests {
                   <mobike>off</mobike>
                   <dpd_delay>10</dpd_delay>
                   <dpd_maxfail>5</dpd_maxfail>
+                  <lifetime>28800</lifetime>
+                  <rekey_time>25920</rekey_time>
+                  <reauth_time/>
                   <startaction>none</startaction>
                 </phase1>
                 <phase2>
@@ -281,6 +287,16 @@ mod tests {
             ]),
             Some("198.51.100.10")
         );
+        let conn =
+            |field| out.get_text(&["OPNsense", "Swanctl", "Connections", "Connection", field]);
+        assert_eq!(conn("rekey_time"), Some("25920"));
+        assert_eq!(conn("reauth_time"), Some(""));
+        assert_eq!(conn("over_time"), Some("2880"));
+        assert_eq!(conn("dpd_timeout"), Some("60"));
+        assert_eq!(
+            out.get_text(&["OPNsense", "Swanctl", "children", "child", "rekey_time"]),
+            Some("3600")
+        );
         assert_eq!(
             out.get_text(&["OPNsense", "Swanctl", "children", "child", "remote_ts"]),
             Some("192.168.10.0/24")
@@ -303,6 +319,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn builds_swanctl_proposals_from_phase_algorithms() {
+        let source = parse(
+            br#"<pfsense><ipsec>
+                <phase1><ikeid>1</ikeid><encryption><item><encryption-algorithm><name>aes</name><keylen>256</keylen></encryption-algorithm><hash-algorithm>sha256</hash-algorithm><dhgroup>14</dhgroup></item></encryption></phase1>
+                <phase2><ikeid>1</ikeid><encryption-algorithm-option><name>aes128gcm</name><keylen>128</keylen></encryption-algorithm-option><hash-algorithm-option>hmac_sha256</hash-algorithm-option><pfsgroup>0</pfsgroup></phase2>
+                <phase1><ikeid>2</ikeid></phase1>
+              </ipsec></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(br#"<opnsense><system/></opnsense>"#).expect("target parse");
+        let mut out = target.clone();
+
+        to_opnsense(&mut out, &source, &target);
+        let connections: Vec<Option<&str>> = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("Swanctl"))
+            .and_then(|s| s.get_child("Connections"))
+            .expect("connections")
+            .get_children("Connection")
+            .into_iter()
+            .map(|c| c.get_text(&["proposals"]))
+            .collect();
+        assert_eq!(
+            connections,
+            vec![Some("aes256-sha256-modp2048"), Some("default")]
+        );
+        assert_eq!(
+            out.get_text(&["OPNsense", "Swanctl", "children", "child", "esp_proposals"]),
+            Some("aes128gcm16")
+        );
+    }
+
     #[test]
     fn to_pfsense_prefers_existing_top_level_ipsec_over_nested_copy() {
         let source = parse(
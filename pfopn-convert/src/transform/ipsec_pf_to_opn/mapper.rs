@@ -1,10 +1,13 @@
 use xml_diff_core::XmlNode;
 
 use super::base::{base_opnsense_ipsec, base_swanctl, push_to_ipsec_psk, push_to_swanctl};
+use super::proposals::{phase1_proposals, phase2_proposals};
 use super::util::{
-    enabled_from_disabled, on_off_to_bool, p1_auth_to_swanctl, p1_local_id, p1_remote_id,
-    p2_local_ts, p2_remote_ts, p2_start_action, push_text_child, stable_uuid, text_or,
+    enabled_from_disabled, on_off_to_bool, p1_auth_to_swanctl, p1_dpd_timeout, p1_local_id,
+    p1_remote_id, p1_sa_times, p2_local_ts, p2_rekey_time, p2_remote_ts, p2_start_action,
+    push_text_child, seconds, text_or,
 };
+use crate::uuid_registry::UuidRegistry;
 
 /// Map pfSense IPsec phase1/phase2 configuration to OPNsense IPsec/Swanctl format.
 ///
@@ -14,21 +17,26 @@ use super::util::{
 /// # Mapping Strategy
 ///
 /// For each pfSense `<phase1>` (IKE SA):
-/// 1. Create an OPNsense `<Connection>` with IKE parameters
+/// 1. Create an OPNsense `<Connection>` with IKE parameters and proposals
 /// 2. Create a `<local>` entry for local endpoint authentication
 /// 3. Create a `<remote>` entry for remote endpoint authentication
 /// 4. Extract pre-shared key into `<IPsec><preSharedKeys>`
-/// 5. All elements are linked by deterministic UUIDs
+/// 5. All elements are linked by deterministic UUIDs issued by `registry`,
+///    keyed by `ikeid`
 ///
 /// For each pfSense `<phase2>` (ESP child SA):
 /// 1. Match to parent phase1 via `ikeid`
 /// 2. Create an OPNsense `<child>` entry linked to the parent Connection
 /// 3. Convert traffic selectors (local/remote networks)
+/// 4. Build ESP proposals from the encryption/hash options and PFS group
 ///
 /// # Returns
 ///
 /// A tuple of `(IPsec, Swanctl)` nodes to be inserted under `<OPNsense>`.
-pub(super) fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlNode) {
+pub(super) fn map_pf_ipsec_to_opnsense(
+    source_ipsec: &XmlNode,
+    registry: &mut UuidRegistry,
+) -> (XmlNode, XmlNode) {
     let mut ipsec = base_opnsense_ipsec();
     let mut swanctl = base_swanctl();
 
@@ -57,16 +65,20 @@ pub(super) fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlN
 
         // Generate deterministic UUIDs for Connection, local, and remote entries
         // These UUIDs link the elements together in OPNsense's data model
-        let conn_uuid = stable_uuid("conn", idx, &ikeid);
-        let local_uuid = stable_uuid("local", idx, &ikeid);
-        let remote_uuid = stable_uuid("remote", idx, &ikeid);
+        let conn_uuid = registry.uuid_for("swanctl-connection", &ikeid);
+        let local_uuid = registry.uuid_for("swanctl-local", &ikeid);
+        let remote_uuid = registry.uuid_for("swanctl-remote", &ikeid);
 
         // Create the Connection entry (IKE SA configuration)
         let mut conn = XmlNode::new("Connection");
         conn.attributes
             .insert("uuid".to_string(), conn_uuid.clone());
         push_text_child(&mut conn, "enabled", enabled_from_disabled(p1));
-        push_text_child(&mut conn, "proposals", "default"); // Use OPNsense's default IKE proposals
+        push_text_child(
+            &mut conn,
+            "proposals",
+            &proposals_or_default(phase1_proposals(p1)),
+        ); // IKE proposals
         push_text_child(&mut conn, "unique", "no"); // Allow multiple SAs with same peer
         push_text_child(&mut conn, "aggressive", "0"); // Main mode (not aggressive)
         push_text_child(&mut conn, "version", "0"); // Auto-detect IKEv1/IKEv2
@@ -84,11 +96,16 @@ pub(super) fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlN
             "encap",
             on_off_to_bool(text_or(p1, "nat_traversal", "off")), // NAT-T (UDP encapsulation)
         );
-        push_text_child(&mut conn, "reauth_time", ""); // Use default reauth time
-        push_text_child(&mut conn, "rekey_time", ""); // Use default rekey time
-        push_text_child(&mut conn, "over_time", ""); // Use default overtime
-        push_text_child(&mut conn, "dpd_delay", text_or(p1, "dpd_delay", "")); // Dead Peer Detection delay
-        push_text_child(&mut conn, "dpd_timeout", text_or(p1, "dpd_maxfail", "")); // DPD timeout (pfSense calls it maxfail)
+        let (rekey_time, reauth_time, over_time) = p1_sa_times(p1);
+        push_text_child(&mut conn, "reauth_time", &reauth_time); // Empty = default reauth time
+        push_text_child(&mut conn, "rekey_time", &rekey_time); // Empty = default rekey time
+        push_text_child(&mut conn, "over_time", &over_time); // Empty = default overtime
+        push_text_child(
+            &mut conn,
+            "dpd_delay",
+            &seconds(p1, "dpd_delay").map_or_else(String::new, |v| v.to_string()),
+        ); // Dead Peer Detection delay
+        push_text_child(&mut conn, "dpd_timeout", &p1_dpd_timeout(p1)); // delay * (maxfail + 1)
         push_text_child(&mut conn, "pools", "radius"); // Virtual IP pool (unused for site-to-site)
         push_text_child(&mut conn, "send_certreq", "1"); // Request peer's certificate
         push_text_child(&mut conn, "send_cert", "");
@@ -134,7 +151,7 @@ pub(super) fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlN
         // In pfSense, PSK is embedded in phase1. In OPNsense, it's stored separately.
         let mut psk = XmlNode::new("preSharedKey");
         psk.attributes
-            .insert("uuid".to_string(), stable_uuid("psk", idx, &ikeid));
+            .insert("uuid".to_string(), registry.uuid_for("ipsec-psk", &ikeid));
         push_text_child(&mut psk, "ident", &p1_local_id(p1)); // Local identity for PSK lookup
         push_text_child(&mut psk, "remote_ident", &p1_remote_id(p1)); // Remote identity for PSK lookup
         push_text_child(&mut psk, "keyType", "PSK");
@@ -150,13 +167,18 @@ pub(super) fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlN
             .enumerate()
         {
             let mut child = XmlNode::new("child");
-            child
-                .attributes
-                .insert("uuid".to_string(), stable_uuid("child", cidx, &ikeid));
+            child.attributes.insert(
+                "uuid".to_string(),
+                registry.uuid_for("swanctl-child", &format!("{ikeid}|{cidx}")),
+            );
             push_text_child(&mut child, "enabled", "1"); // Always enabled (pfSense doesn't disable individual phase2s)
             push_text_child(&mut child, "connection", &conn_uuid); // Link to parent Connection
             push_text_child(&mut child, "reqid", text_or(p2, "reqid", "")); // IPsec policy ID
-            push_text_child(&mut child, "esp_proposals", "default"); // Use OPNsense's default ESP proposals
+            push_text_child(
+                &mut child,
+                "esp_proposals",
+                &proposals_or_default(phase2_proposals(p2)),
+            ); // ESP proposals
             push_text_child(&mut child, "sha256_96", "0"); // Use full SHA256 (not truncated)
             push_text_child(&mut child, "start_action", p2_start_action(p1)); // Initiation policy
             push_text_child(&mut child, "close_action", "none"); // Don't close on inactivity
@@ -165,7 +187,7 @@ pub(super) fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlN
             push_text_child(&mut child, "policies", "1"); // Install IPsec policies
             push_text_child(&mut child, "local_ts", &p2_local_ts(p2)); // Local traffic selector
             push_text_child(&mut child, "remote_ts", &p2_remote_ts(p2)); // Remote traffic selector
-            push_text_child(&mut child, "rekey_time", text_or(p2, "lifetime", "")); // SA lifetime
+            push_text_child(&mut child, "rekey_time", &p2_rekey_time(p2)); // SA rekey interval
             push_text_child(&mut child, "description", text_or(p2, "descr", ""));
             push_to_swanctl(&mut swanctl, "children", child);
         }
@@ -173,3 +195,13 @@ pub(super) fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlN
 
     (ipsec, swanctl)
 }
+
+/// Join proposals into swanctl's comma-separated list, or `default` when the
+/// phase had none that translate.
+fn proposals_or_default(proposals: Vec<String>) -> String {
+    if proposals.is_empty() {
+        "default".to_string()
+    } else {
+        proposals.join(",")
+    }
+}
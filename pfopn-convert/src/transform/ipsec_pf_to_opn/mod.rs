@@ -31,15 +31,24 @@
 //! - Creates an OPNsense `<Swanctl><child>` (ESP child SA)
 //! - Links it to the parent Connection via UUID
 //!
+//! Phase1 `<encryption>` items and phase2 algorithm options become swanctl
+//! `proposals` / `esp_proposals` strings (`aes256-sha256-modp2048`); a phase
+//! whose algorithms cannot be translated keeps OPNsense's `default`.
+//!
 //! All elements are assigned deterministic UUIDs based on their ikeid and index
 //! to ensure stable identifiers across repeated conversions.
 
 use xml_diff_core::XmlNode;
 
+use crate::uuid_registry::UuidRegistry;
+
 mod base;
 mod mapper;
+mod proposals;
 mod util;
 
+pub(crate) use proposals::{phase1_proposals, phase2_proposals};
+
 /// Convert pfSense IPsec configuration to OPNsense IPsec and Swanctl format.
 ///
 /// Returns a tuple of `(IPsec, Swanctl)` XML nodes:
@@ -90,6 +99,9 @@ mod util;
 ///   </Swanctl>
 /// </OPNsense>
 /// ```
-pub fn map_pf_ipsec_to_opnsense(source_ipsec: &XmlNode) -> (XmlNode, XmlNode) {
-    mapper::map_pf_ipsec_to_opnsense(source_ipsec)
+pub fn map_pf_ipsec_to_opnsense(
+    source_ipsec: &XmlNode,
+    registry: &mut UuidRegistry,
+) -> (XmlNode, XmlNode) {
+    mapper::map_pf_ipsec_to_opnsense(source_ipsec, registry)
 }
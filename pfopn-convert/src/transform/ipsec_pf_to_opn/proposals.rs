@@ -0,0 +1,231 @@
+use xml_diff_core::XmlNode;
+
+use super::util::text_or;
+
+/// strongSwan key exchange keyword for an IKE DH group number.
+fn dh_group(group: &str) -> Option<&'static str> {
+    Some(match group.trim() {
+        "1" => "modp768",
+        "2" => "modp1024",
+        "5" => "modp1536",
+        "14" => "modp2048",
+        "15" => "modp3072",
+        "16" => "modp4096",
+        "17" => "modp6144",
+        "18" => "modp8192",
+        "19" => "ecp256",
+        "20" => "ecp384",
+        "21" => "ecp521",
+        "22" => "modp1024s160",
+        "23" => "modp2048s224",
+        "24" => "modp2048s256",
+        "25" => "ecp192",
+        "26" => "ecp224",
+        "27" => "ecp224bp",
+        "28" => "ecp256bp",
+        "29" => "ecp384bp",
+        "30" => "ecp512bp",
+        "31" => "curve25519",
+        "32" => "curve448",
+        _ => return None,
+    })
+}
+
+/// strongSwan keywords for a pfSense `<encryption-algorithm>` node, and
+/// whether the cipher is AEAD (carries its own integrity check).
+///
+/// pfSense names GCM by ICV size (`aes128gcm` is a 16-byte ICV) and keeps the
+/// key size in `<keylen>`; `auto` expands to every key size pfSense offers.
+fn cipher(node: &XmlNode) -> Vec<(String, bool)> {
+    let name = text_or(node, "name", "").trim().to_ascii_lowercase();
+    let keylen = text_or(node, "keylen", "").trim().to_ascii_lowercase();
+    let key_sizes: Vec<&str> = match keylen.as_str() {
+        "" | "auto" => vec!["128", "192", "256"],
+        len => vec![len],
+    };
+    let gcm_icv = match name.as_str() {
+        "aes64gcm" => Some("8"),
+        "aes96gcm" => Some("12"),
+        "aes128gcm" => Some("16"),
+        _ => None,
+    };
+    if let Some(icv) = gcm_icv {
+        return key_sizes
+            .iter()
+            .map(|len| (format!("aes{len}gcm{icv}"), true))
+            .collect();
+    }
+    match name.as_str() {
+        "aes" | "camellia" | "blowfish" => key_sizes
+            .iter()
+            .map(|len| (format!("{name}{len}"), false))
+            .collect(),
+        "chacha20poly1305" => vec![(name, true)],
+        "3des" | "cast128" | "des" => vec![(name, false)],
+        _ => Vec::new(),
+    }
+}
+
+/// strongSwan integrity keyword for a pfSense hash name (`sha256`,
+/// `hmac_sha256`, `aesxcbc`).
+fn integrity(hash: &str) -> Option<String> {
+    let hash = hash.trim().to_ascii_lowercase();
+    let hash = hash.strip_prefix("hmac_").unwrap_or(&hash);
+    matches!(
+        hash,
+        "md5" | "sha1" | "sha256" | "sha384" | "sha512" | "aesxcbc"
+    )
+    .then(|| hash.to_string())
+}
+
+/// IKE proposals for a pfSense phase1, one per `<encryption><item>`.
+///
+/// pfSense before 2.5 kept a single algorithm set directly on the phase1;
+/// that form is read when there are no items. Each proposal is
+/// `cipher-integrity[-prf]-dhgroup`; AEAD ciphers keep the hash, which
+/// strongSwan reads as the PRF for IKE. Items with an unknown cipher or DH
+/// group are skipped, and an empty result means "use the defaults".
+pub(crate) fn phase1_proposals(p1: &XmlNode) -> Vec<String> {
+    let items = p1
+        .get_child("encryption")
+        .map(|e| e.get_children("item"))
+        .unwrap_or_default();
+    let items = if items.is_empty() { vec![p1] } else { items };
+    let prf_select = p1
+        .get_child("prfselect_enable")
+        .is_some_and(|n| n.text.as_deref().map(str::trim) != Some("0"));
+    let mut out = Vec::new();
+    for item in items {
+        let Some(algorithm) = item.get_child("encryption-algorithm") else {
+            continue;
+        };
+        let Some(dh) = dh_group(text_or(item, "dhgroup", "")) else {
+            continue;
+        };
+        let hash = integrity(text_or(item, "hash-algorithm", ""));
+        let prf = integrity(text_or(item, "prf-algorithm", ""))
+            .filter(|_| prf_select)
+            .map(|p| format!("prf{p}"));
+        for (enc, _) in cipher(algorithm) {
+            let parts: Vec<&str> = [
+                Some(enc.as_str()),
+                hash.as_deref(),
+                prf.as_deref(),
+                Some(dh),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            push_unique(&mut out, parts.join("-"));
+        }
+    }
+    out
+}
+
+/// ESP proposals for a pfSense phase2.
+///
+/// Every `<encryption-algorithm-option>` is combined with every
+/// `<hash-algorithm-option>` (AEAD ciphers take none) and the PFS group,
+/// when one is set.
+pub(crate) fn phase2_proposals(p2: &XmlNode) -> Vec<String> {
+    let hashes: Vec<String> = p2
+        .get_children("hash-algorithm-option")
+        .into_iter()
+        .filter_map(|h| h.text.as_deref().and_then(integrity))
+        .collect();
+    let pfs = dh_group(text_or(p2, "pfsgroup", ""));
+    let mut out = Vec::new();
+    for option in p2.get_children("encryption-algorithm-option") {
+        for (enc, aead) in cipher(option) {
+            let integrities: Vec<Option<&str>> = if aead || hashes.is_empty() {
+                vec![None]
+            } else {
+                hashes.iter().map(|h| Some(h.as_str())).collect()
+            };
+            for hash in integrities {
+                let parts: Vec<&str> = [Some(enc.as_str()), hash, pfs]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                push_unique(&mut out, parts.join("-"));
+            }
+        }
+    }
+    out
+}
+
+fn push_unique(out: &mut Vec<String>, proposal: String) {
+    if !out.contains(&proposal) {
+        out.push(proposal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{phase1_proposals, phase2_proposals};
+
+    #[test]
+    fn builds_ike_proposals_from_encryption_items() {
+        let p1 = parse(
+            br#"<phase1><prfselect_enable>yes</prfselect_enable><encryption>
+                <item><encryption-algorithm><name>aes128gcm</name><keylen>256</keylen></encryption-algorithm><hash-algorithm>sha256</hash-algorithm><prf-algorithm>sha384</prf-algorithm><dhgroup>31</dhgroup></item>
+                <item><encryption-algorithm><name>3des</name></encryption-algorithm><hash-algorithm>md5</hash-algorithm><dhgroup>2</dhgroup></item>
+                <item><encryption-algorithm><name>aes</name><keylen>128</keylen></encryption-algorithm><hash-algorithm>sha1</hash-algorithm><dhgroup>99</dhgroup></item>
+            </encryption></phase1>"#,
+        )
+        .expect("parse");
+        assert_eq!(
+            phase1_proposals(&p1),
+            vec![
+                "aes256gcm16-sha256-prfsha384-curve25519",
+                "3des-md5-modp1024"
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_pre_2_5_single_algorithm_phase1() {
+        let p1 = parse(
+            br#"<phase1><encryption-algorithm><name>aes</name><keylen>256</keylen></encryption-algorithm><hash-algorithm>sha256</hash-algorithm><dhgroup>14</dhgroup></phase1>"#,
+        )
+        .expect("parse");
+        assert_eq!(phase1_proposals(&p1), vec!["aes256-sha256-modp2048"]);
+    }
+
+    #[test]
+    fn combines_esp_ciphers_hashes_and_pfs_group() {
+        let p2 = parse(
+            br#"<phase2>
+                <encryption-algorithm-option><name>aes</name><keylen>auto</keylen></encryption-algorithm-option>
+                <encryption-algorithm-option><name>aes128gcm</name><keylen>128</keylen></encryption-algorithm-option>
+                <hash-algorithm-option>hmac_sha256</hash-algorithm-option>
+                <hash-algorithm-option>hmac_sha1</hash-algorithm-option>
+                <pfsgroup>14</pfsgroup>
+            </phase2>"#,
+        )
+        .expect("parse");
+        assert_eq!(
+            phase2_proposals(&p2),
+            vec![
+                "aes128-sha256-modp2048",
+                "aes128-sha1-modp2048",
+                "aes192-sha256-modp2048",
+                "aes192-sha1-modp2048",
+                "aes256-sha256-modp2048",
+                "aes256-sha1-modp2048",
+                "aes128gcm16-modp2048",
+            ]
+        );
+    }
+
+    #[test]
+    fn omits_pfs_when_disabled() {
+        let p2 = parse(
+            br#"<phase2><encryption-algorithm-option><name>chacha20poly1305</name></encryption-algorithm-option><pfsgroup>0</pfsgroup></phase2>"#,
+        )
+        .expect("parse");
+        assert_eq!(phase2_proposals(&p2), vec!["chacha20poly1305"]);
+    }
+}
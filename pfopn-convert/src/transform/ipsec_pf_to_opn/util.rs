@@ -1,18 +1,13 @@
 use xml_diff_core::XmlNode;
 
+use crate::transform::{booleans, units};
+
 /// Convert pfSense's `<disabled>` field to OPNsense's `<enabled>` field.
 ///
 /// pfSense uses presence of `<disabled>` to indicate disabled state.
 /// OPNsense uses `<enabled>` with "1" for enabled, "0" for disabled.
-///
-/// Returns "1" if the node has no `<disabled>` child (enabled in pfSense).
-/// Returns "0" if the node has a `<disabled>` child (disabled in pfSense).
 pub(super) fn enabled_from_disabled(node: &XmlNode) -> &'static str {
-    if text_or(node, "disabled", "").is_empty() {
-        "1"
-    } else {
-        "0"
-    }
+    booleans::numeric(!booleans::flag_set(node, "disabled"))
 }
 
 /// Convert pfSense phase1 authentication method to Swanctl auth type.
@@ -91,16 +86,73 @@ pub(super) fn p2_start_action(p1: &XmlNode) -> &'static str {
     }
 }
 
+/// Read a pfSense lifetime or delay field as whole seconds for swanctl.
+///
+/// Units come from [`units::IPSEC_LIFETIMES`]; values that are not whole
+/// numbers are dropped so swanctl falls back to its default.
+pub(super) fn seconds(node: &XmlNode, field: &str) -> Option<u64> {
+    let unit = units::field_unit(units::IPSEC_LIFETIMES, field)?;
+    let raw = text_or(node, field, "");
+    units::convert(raw, unit.pfsense, unit.opnsense)?
+        .value
+        .parse()
+        .ok()
+}
+
+/// Determine Swanctl IKE SA timers `(rekey_time, reauth_time, over_time)`
+/// from a pfSense phase1.
+///
+/// pfSense 2.5+ keeps rekey and reauth times next to a hard `lifetime`;
+/// strongSwan's over_time is the margin between that lifetime and the later
+/// of the two. Older configs only have `lifetime`, which was the rekey
+/// interval. Unset timers are empty so OPNsense uses its defaults.
+pub(super) fn p1_sa_times(p1: &XmlNode) -> (String, String, String) {
+    let as_text = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let lifetime = seconds(p1, "lifetime");
+    let rekey = seconds(p1, "rekey_time");
+    let reauth = seconds(p1, "reauth_time");
+    if rekey.is_none() && reauth.is_none() {
+        return (as_text(lifetime), String::new(), String::new());
+    }
+    let latest = rekey.max(reauth).unwrap_or_default();
+    let over = lifetime
+        .and_then(|l| l.checked_sub(latest))
+        .filter(|v| *v > 0);
+    (as_text(rekey), as_text(reauth), as_text(over))
+}
+
+/// Determine Swanctl `dpd_timeout` (seconds) from a pfSense phase1.
+///
+/// pfSense stores a DPD delay and a retry count (`dpd_maxfail`) and derives
+/// the timeout as `delay * (maxfail + 1)`; swanctl takes the timeout itself.
+pub(super) fn p1_dpd_timeout(p1: &XmlNode) -> String {
+    let Some(delay) = seconds(p1, "dpd_delay") else {
+        return String::new();
+    };
+    let Ok(maxfail) = text_or(p1, "dpd_maxfail", "").parse::<u64>() else {
+        return String::new();
+    };
+    delay
+        .checked_mul(maxfail + 1)
+        .map(|v| v.to_string())
+        .unwrap_or_default()
+}
+
+/// Determine Swanctl child `rekey_time` from a pfSense phase2: its own rekey
+/// time when set, otherwise its lifetime.
+pub(super) fn p2_rekey_time(p2: &XmlNode) -> String {
+    seconds(p2, "rekey_time")
+        .or_else(|| seconds(p2, "lifetime"))
+        .map(|v| v.to_string())
+        .unwrap_or_default()
+}
+
 /// Convert pfSense "on"/"off" strings to OPNsense "1"/"0" strings.
 ///
 /// pfSense uses "on" and "off" for boolean toggles in IPsec config.
 /// OPNsense uses "1" and "0" in Swanctl configuration.
 pub(super) fn on_off_to_bool(v: &str) -> &'static str {
-    if v.eq_ignore_ascii_case("on") {
-        "1"
-    } else {
-        "0"
-    }
+    booleans::numeric(booleans::is_truthy(v))
 }
 
 /// Extract trimmed text from a child element, or return a default value.
@@ -119,62 +171,6 @@ pub(super) fn push_text_child(parent: &mut XmlNode, tag: &str, value: &str) {
     parent.children.push(child);
 }
 
-/// Generate a deterministic UUID v4 from a prefix, index, and seed string.
-///
-/// Creates stable UUIDs for IPsec config elements so that the same pfSense
-/// configuration always produces the same OPNsense UUIDs. This is important
-/// for:
-/// - Idempotent conversions (converting twice produces identical output)
-/// - Linking related elements (Connection → local/remote/child via UUID references)
-/// - Diff stability (unchanged tunnels keep the same UUIDs)
-///
-/// The algorithm:
-/// 1. Mix prefix and seed bytes into a 16-byte accumulator with position-dependent rotation
-/// 2. Fold in the index to ensure different elements with the same seed get unique UUIDs
-/// 3. Set UUID version 4 bits and RFC 4122 variant bits for format compliance
-///
-/// # Example
-/// ```ignore
-/// stable_uuid("conn", 0, "1") // -> "abc12345-6789-4...-8...-..."
-/// stable_uuid("conn", 0, "1") // -> same UUID (deterministic)
-/// stable_uuid("conn", 1, "1") // -> different UUID (different index)
-/// stable_uuid("local", 0, "1") // -> different UUID (different prefix)
-/// ```
-pub(super) fn stable_uuid(prefix: &str, idx: usize, seed: &str) -> String {
-    let mut bytes = [0u8; 16];
-    // Mix prefix and seed bytes into the accumulator with position-dependent rotation
-    for (i, b) in prefix.bytes().chain(seed.bytes()).enumerate() {
-        bytes[i % 16] = bytes[i % 16].wrapping_add(b).rotate_left((i % 7) as u32);
-    }
-    // Fold in the index so that elements with identical seeds but different positions
-    // still produce distinct UUIDs
-    for (i, b) in bytes.iter_mut().enumerate() {
-        *b = b.wrapping_add(((idx + i) as u8).rotate_left((idx % 5) as u32));
-    }
-    // Set UUID version 4 nibble (0x40) and RFC 4122 variant bits (0x80-0xBF)
-    bytes[6] = (bytes[6] & 0x0f) | 0x40; // version 4
-    bytes[8] = (bytes[8] & 0x3f) | 0x80; // variant 10xx
-    format!(
-        "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-        bytes[0],
-        bytes[1],
-        bytes[2],
-        bytes[3],
-        bytes[4],
-        bytes[5],
-        bytes[6],
-        bytes[7],
-        bytes[8],
-        bytes[9],
-        bytes[10],
-        bytes[11],
-        bytes[12],
-        bytes[13],
-        bytes[14],
-        bytes[15]
-    )
-}
-
 /// Convert a pfSense traffic selector to Swanctl traffic selector format.
 ///
 /// pfSense phase2 `<localid>` and `<remoteid>` elements describe which IP
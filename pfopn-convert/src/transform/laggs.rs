@@ -0,0 +1,537 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::uuid_registry::UuidRegistry;
+
+/// Aggregation protocols both platforms accept.
+const SHARED_PROTOS: &[&str] = &["none", "lacp", "failover", "loadbalance", "roundrobin"];
+
+/// Convert pfSense `<laggs><lagg>` entries into OPNsense's shape.
+///
+/// Protocol, members, hash layers, and description carry over unchanged;
+/// pfSense's `lacptimeout` and `failovermaster` become `lacp_fast_timeout`
+/// and `primary_member`. Each entry gets a stable `uuid`, and the container
+/// keeps the baseline's model attributes. Member device names are rewritten
+/// later by [`super::device_refs::apply`] and checked against the target
+/// hardware by [`reconcile`].
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(items) = source_laggs(source) else {
+        return;
+    };
+    let mut registry = UuidRegistry::from_config(out);
+    let mut laggs = XmlNode::new("laggs");
+    if let Some(base) = destination_baseline.get_child("laggs") {
+        laggs.attributes = base.attributes.clone();
+    }
+
+    for (idx, lagg) in items.iter().enumerate() {
+        let laggif = text_of(lagg, "laggif").unwrap_or_default();
+        let proto = text_of(lagg, "proto").unwrap_or_else(|| "none".to_string());
+        if !SHARED_PROTOS.contains(&proto.as_str()) {
+            diagnostics.warn(
+                "lagg",
+                format!("{laggif}: unknown protocol '{proto}' carried over unchanged"),
+            );
+        }
+        let fast = text_of(lagg, "lacptimeout").as_deref() == Some("fast");
+        let primary = text_of(lagg, "failovermaster").filter(|m| m != "auto");
+
+        let mut node = XmlNode::new("lagg");
+        node.attributes.insert(
+            "uuid".to_string(),
+            registry.uuid_for("lagg", &format!("{laggif}|{idx}")),
+        );
+        for (field, value) in [
+            ("laggif", laggif.as_str()),
+            ("members", &text_of(lagg, "members").unwrap_or_default()),
+            ("primary_member", &primary.unwrap_or_default()),
+            ("proto", &proto),
+            ("lacp_fast_timeout", if fast { "1" } else { "0" }),
+            ("use_flowid", ""),
+            ("lagghash", &text_of(lagg, "lagghash").unwrap_or_default()),
+            ("lacp_strict", ""),
+            ("mtu", ""),
+            ("descr", &text_of(lagg, "descr").unwrap_or_default()),
+        ] {
+            set_text(&mut node, field, value);
+        }
+        laggs.children.push(node);
+    }
+    upsert_top_child(out, laggs);
+}
+
+/// Convert OPNsense `<laggs><lagg>` entries into pfSense's shape.
+///
+/// The reverse of [`to_opnsense`]: `uuid` and the OPNsense-only flags are
+/// dropped, and the FEC protocol (not offered by pfSense) falls back to
+/// static `loadbalance`. A per-LAGG MTU is reported because pfSense sets it
+/// on the assigned interface instead.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let _ = destination_baseline;
+    let Some(items) = source_laggs(source) else {
+        return;
+    };
+    let mut laggs = XmlNode::new("laggs");
+
+    for lagg in items {
+        let laggif = text_of(lagg, "laggif").unwrap_or_default();
+        let mut proto = text_of(lagg, "proto").unwrap_or_else(|| "none".to_string());
+        if proto == "fec" {
+            diagnostics.warn(
+                "lagg",
+                format!("{laggif}: FEC is not supported by pfSense; using loadbalance"),
+            );
+            proto = "loadbalance".to_string();
+        } else if !SHARED_PROTOS.contains(&proto.as_str()) {
+            diagnostics.warn(
+                "lagg",
+                format!("{laggif}: unknown protocol '{proto}' carried over unchanged"),
+            );
+        }
+        if let Some(mtu) = text_of(lagg, "mtu") {
+            diagnostics.warn(
+                "lagg",
+                format!("{laggif}: MTU {mtu} dropped; set it on the assigned interface"),
+            );
+        }
+        let fast = text_of(lagg, "lacp_fast_timeout").as_deref() == Some("1");
+
+        let mut node = XmlNode::new("lagg");
+        for (field, value) in [
+            ("members", text_of(lagg, "members").unwrap_or_default()),
+            ("descr", text_of(lagg, "descr").unwrap_or_default()),
+            ("laggif", laggif.clone()),
+            ("proto", proto),
+            (
+                "lacptimeout",
+                if fast { "fast" } else { "slow" }.to_string(),
+            ),
+            ("lagghash", text_of(lagg, "lagghash").unwrap_or_default()),
+            (
+                "failovermaster",
+                text_of(lagg, "primary_member").unwrap_or_else(|| "auto".to_string()),
+            ),
+        ] {
+            set_text(&mut node, field, &value);
+        }
+        laggs.children.push(node);
+    }
+    upsert_top_child(out, laggs);
+}
+
+/// Reconcile converted LAGGs with the ones already defined on the target.
+///
+/// Runs after device references have been rewritten, so member lists use
+/// target device names where the interface mapping knew them. For each
+/// converted LAGG:
+/// 1. A target LAGG with the same member set is reused under its own name.
+/// 2. A target LAGG with the same name keeps its members (the target's
+///    hardware wins, mirroring how interface `<if>` bindings are kept) and
+///    takes the converted settings.
+/// 3. Otherwise the LAGG is added, renumbered to the next free `laggN` if its
+///    name is already taken.
+///
+/// Target LAGGs that nothing claimed are kept. Interface assignments and VLAN
+/// parents that referenced a renamed LAGG (including dotted `lagg0.10`
+/// devices) are updated, and every member that isn't a known physical device
+/// on the target is reported.
+pub fn reconcile(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let baseline: Vec<XmlNode> = target
+        .get_child("laggs")
+        .map(|l| {
+            l.get_children("lagg")
+                .into_iter()
+                .filter(|g| text_of(g, "laggif").is_some())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if source_laggs(source).is_none() {
+        // Nothing converted: undo any device rewriting applied to the
+        // baseline entries that came through the merge untouched.
+        if let Some(base) = target.get_child("laggs") {
+            upsert_top_child(out, base.clone());
+        }
+        return;
+    }
+    let Some(out_laggs) = child_mut(out, "laggs") else {
+        return;
+    };
+    let converted: Vec<XmlNode> = out_laggs
+        .children
+        .iter()
+        .filter(|c| c.tag == "lagg")
+        .cloned()
+        .collect();
+
+    let mut claimed: BTreeSet<usize> = BTreeSet::new();
+    let mut used: BTreeSet<String> = baseline
+        .iter()
+        .filter_map(|g| text_of(g, "laggif"))
+        .collect();
+    let mut renames = BTreeMap::new();
+    let mut merged = Vec::new();
+
+    for mut lagg in converted {
+        let name = text_of(&lagg, "laggif").unwrap_or_default();
+        let members = member_set(&lagg);
+        let by_members = baseline
+            .iter()
+            .position(|b| !members.is_empty() && member_set(b) == members);
+        let by_name = baseline
+            .iter()
+            .position(|b| text_of(b, "laggif").as_deref() == Some(name.as_str()));
+
+        let new_name = match (by_members, by_name) {
+            (Some(idx), _) if !claimed.contains(&idx) => {
+                claimed.insert(idx);
+                text_of(&baseline[idx], "laggif").unwrap_or_default()
+            }
+            (_, Some(idx)) if !claimed.contains(&idx) => {
+                claimed.insert(idx);
+                let hw = text_of(&baseline[idx], "members").unwrap_or_default();
+                diagnostics.info("lagg", format!("{name}: keeping target members '{hw}'"));
+                set_text(&mut lagg, "members", &hw);
+                name.clone()
+            }
+            _ if name.is_empty() || used.contains(&name) => next_name(&used),
+            _ => name.clone(),
+        };
+        if let Some(uuid) = baseline
+            .iter()
+            .find(|b| text_of(b, "laggif").as_deref() == Some(new_name.as_str()))
+            .and_then(|b| b.attributes.get("uuid"))
+        {
+            lagg.attributes.insert("uuid".to_string(), uuid.clone());
+        }
+        if new_name != name {
+            diagnostics.info("lagg", format!("{name} renamed to {new_name}"));
+            renames.insert(name, new_name.clone());
+        }
+        used.insert(new_name.clone());
+        set_text(&mut lagg, "laggif", &new_name);
+        merged.push(lagg);
+    }
+
+    let mut children: Vec<XmlNode> = baseline
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !claimed.contains(idx))
+        .map(|(_, b)| b.clone())
+        .collect();
+    children.extend(merged);
+    out_laggs.children.retain(|c| c.tag != "lagg");
+    out_laggs.children.extend(children);
+
+    let physical = physical_devices(target);
+    for lagg in out_laggs.get_children("lagg") {
+        let name = text_of(lagg, "laggif").unwrap_or_default();
+        for member in member_set(lagg) {
+            if !physical.contains(&member) {
+                diagnostics.warn(
+                    "lagg",
+                    format!("{name}: member {member} is not a physical device on the target"),
+                );
+            }
+        }
+    }
+
+    rewrite_lagg_refs(out, &renames);
+}
+
+/// Return the source LAGG entries that name a device, if there are any.
+fn source_laggs(source: &XmlNode) -> Option<Vec<&XmlNode>> {
+    let items: Vec<&XmlNode> = source
+        .get_child("laggs")?
+        .get_children("lagg")
+        .into_iter()
+        .filter(|g| text_of(g, "laggif").is_some())
+        .collect();
+    (!items.is_empty()).then_some(items)
+}
+
+/// Devices the target baseline knows to be hardware: non-virtual interface
+/// bindings, VLAN parents, and the members of its own LAGGs.
+fn physical_devices(target: &XmlNode) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    if let Some(interfaces) = target.get_child("interfaces") {
+        out.extend(interfaces.children.iter().filter_map(|i| text_of(i, "if")));
+    }
+    if let Some(vlans) = target.get_child("vlans") {
+        out.extend(
+            vlans
+                .get_children("vlan")
+                .into_iter()
+                .filter_map(|v| text_of(v, "if")),
+        );
+    }
+    if let Some(laggs) = target.get_child("laggs") {
+        for lagg in laggs.get_children("lagg") {
+            out.extend(member_set(lagg));
+        }
+    }
+    out.retain(|d| !is_virtual_device(d));
+    out
+}
+
+fn is_virtual_device(name: &str) -> bool {
+    name.contains('.')
+        || [
+            "lagg", "vlan", "qinq", "bridge", "ovpn", "wg", "tun", "gif", "gre", "ppp", "lo",
+        ]
+        .iter()
+        .any(|p| name.starts_with(p))
+}
+
+fn member_set(lagg: &XmlNode) -> BTreeSet<String> {
+    text_of(lagg, "members")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Point interface assignments and VLAN parents at renamed LAGG devices.
+fn rewrite_lagg_refs(out: &mut XmlNode, renames: &BTreeMap<String, String>) {
+    if renames.is_empty() {
+        return;
+    }
+    let rename = |dev: &str| -> Option<String> {
+        let (base, suffix) = dev
+            .split_once('.')
+            .map_or((dev, None), |(b, s)| (b, Some(s)));
+        let new = renames.get(base)?;
+        Some(suffix.map_or_else(|| new.clone(), |s| format!("{new}.{s}")))
+    };
+    for (section, item, fields) in [
+        ("interfaces", None, &["if"][..]),
+        ("vlans", Some("vlan"), &["if", "vlanif"][..]),
+        ("qinqs", Some("qinqentry"), &["if", "vlanif"][..]),
+    ] {
+        let Some(container) = child_mut(out, section) else {
+            continue;
+        };
+        for node in &mut container.children {
+            if item.is_some_and(|t| node.tag != t) {
+                continue;
+            }
+            for field in fields {
+                if let Some(new) = text_of(node, field).and_then(|d| rename(&d)) {
+                    set_text(node, field, &new);
+                }
+            }
+        }
+    }
+}
+
+fn next_name(used: &BTreeSet<String>) -> String {
+    (0..1000u16)
+        .map(|i| format!("lagg{i}"))
+        .find(|name| !used.contains(name))
+        .unwrap_or_else(|| "lagg999".to_string())
+}
+
+fn text_of(node: &XmlNode, child: &str) -> Option<String> {
+    node.get_text(&[child])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
+    let idx = node.children.iter().position(|c| c.tag == tag)?;
+    Some(&mut node.children[idx])
+}
+
+fn upsert_top_child(root: &mut XmlNode, node: XmlNode) {
+    if let Some(idx) = root.children.iter().position(|c| c.tag == node.tag) {
+        root.children[idx] = node;
+    } else {
+        root.children.push(node);
+    }
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{reconcile, to_opnsense, to_pfsense};
+    use crate::diagnostics::{Diagnostics, Severity};
+
+    #[test]
+    fn converts_pfsense_lagg_to_opnsense_shape() {
+        let source = parse(
+            br#"<pfsense><laggs><lagg><members>igb2,igb3</members><descr>uplink</descr><laggif>lagg0</laggif><proto>lacp</proto><lacptimeout>fast</lacptimeout><lagghash>l2,l3</lagghash><failovermaster>auto</failovermaster></lagg></laggs></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<opnsense><laggs version="1.0.0"><lagg/></laggs></opnsense>"#)
+            .expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let laggs = out.get_child("laggs").expect("laggs");
+        assert_eq!(
+            laggs.attributes.get("version").map(String::as_str),
+            Some("1.0.0")
+        );
+        assert_eq!(laggs.children.len(), 1);
+        let lagg = &laggs.children[0];
+        assert!(lagg.attributes.contains_key("uuid"));
+        assert_eq!(lagg.get_text(&["members"]), Some("igb2,igb3"));
+        assert_eq!(lagg.get_text(&["proto"]), Some("lacp"));
+        assert_eq!(lagg.get_text(&["lacp_fast_timeout"]), Some("1"));
+        assert_eq!(lagg.get_text(&["primary_member"]), Some(""));
+        assert_eq!(lagg.get_text(&["lagghash"]), Some("l2,l3"));
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn converts_opnsense_lagg_to_pfsense_shape() {
+        let source = parse(
+            br#"<opnsense><laggs version="1.0.0"><lagg uuid="x"><laggif>lagg1</laggif><members>ix0,ix1</members><primary_member>ix1</primary_member><proto>fec</proto><lacp_fast_timeout>0</lacp_fast_timeout><mtu>9000</mtu><descr/></lagg></laggs></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(b"<pfsense/>").expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let lagg = out
+            .get_child("laggs")
+            .and_then(|l| l.get_child("lagg"))
+            .expect("lagg");
+        assert!(lagg.attributes.is_empty());
+        assert_eq!(lagg.get_text(&["proto"]), Some("loadbalance"));
+        assert_eq!(lagg.get_text(&["lacptimeout"]), Some("slow"));
+        assert_eq!(lagg.get_text(&["failovermaster"]), Some("ix1"));
+        assert!(lagg.get_child("mtu").is_none());
+        assert_eq!(diagnostics.count(Severity::Warning), 2);
+    }
+
+    #[test]
+    fn reuses_target_lagg_with_same_members_and_renames_refs() {
+        let source = parse(
+            br#"<pfsense><laggs><lagg><laggif>lagg2</laggif><members>igb0,igb1</members></lagg></laggs></pfsense>"#,
+        )
+        .expect("parse");
+        let target = parse(
+            br#"<opnsense><interfaces><lan><if>lagg0</if></lan><wan><if>vtnet0</if></wan></interfaces><laggs><lagg uuid="t0"><laggif>lagg0</laggif><members>vtnet1,vtnet2</members></lagg></laggs></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(
+            br#"<opnsense><interfaces><opt1><if>lagg2.10</if></opt1></interfaces><vlans><vlan><if>lagg2</if><tag>10</tag><vlanif>lagg2.10</vlanif></vlan></vlans><laggs><lagg><laggif>lagg2</laggif><members>vtnet1,vtnet2</members></lagg></laggs></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::default();
+
+        reconcile(&mut out, &source, &target, &mut diagnostics);
+
+        let laggs = out.get_child("laggs").expect("laggs");
+        assert_eq!(laggs.children.len(), 1);
+        assert_eq!(laggs.children[0].get_text(&["laggif"]), Some("lagg0"));
+        assert_eq!(
+            laggs.children[0].attributes.get("uuid").map(String::as_str),
+            Some("t0")
+        );
+        assert_eq!(
+            out.get_text(&["interfaces", "opt1", "if"]),
+            Some("lagg0.10")
+        );
+        assert_eq!(out.get_text(&["vlans", "vlan", "if"]), Some("lagg0"));
+        assert_eq!(out.get_text(&["vlans", "vlan", "vlanif"]), Some("lagg0.10"));
+        assert_eq!(diagnostics.count(Severity::Warning), 0);
+    }
+
+    #[test]
+    fn renumbers_colliding_lagg_and_flags_unknown_members() {
+        let source = parse(
+            br#"<pfsense><laggs><lagg><laggif>lagg0</laggif><members>igb4,igb5</members></lagg><lagg><laggif>lagg1</laggif><members>igb6</members></lagg></laggs></pfsense>"#,
+        )
+        .expect("parse");
+        let target = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet1</if></lan></interfaces><laggs><lagg><laggif>lagg1</laggif><members>vtnet2,vtnet3</members></lagg></laggs></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(
+            br#"<opnsense><interfaces><opt1><if>lagg0</if></opt1><opt2><if>lagg1</if></opt2></interfaces><laggs><lagg><laggif>lagg0</laggif><members>vtnet2,vtnet3</members></lagg><lagg><laggif>lagg1</laggif><members>igb6</members></lagg></laggs></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::default();
+
+        reconcile(&mut out, &source, &target, &mut diagnostics);
+
+        let laggs = out.get_child("laggs").expect("laggs");
+        let names: Vec<_> = laggs
+            .children
+            .iter()
+            .map(|l| {
+                (
+                    l.get_text(&["laggif"]).unwrap(),
+                    l.get_text(&["members"]).unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(names, vec![("lagg1", "vtnet2,vtnet3"), ("lagg0", "igb6")]);
+        assert_eq!(out.get_text(&["interfaces", "opt1", "if"]), Some("lagg1"));
+        assert_eq!(out.get_text(&["interfaces", "opt2", "if"]), Some("lagg0"));
+        let warnings: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            warnings,
+            vec!["lagg0: member igb6 is not a physical device on the target"]
+        );
+    }
+
+    #[test]
+    fn restores_baseline_laggs_when_source_has_none() {
+        let source = parse(b"<pfsense><interfaces/></pfsense>").expect("parse");
+        let target = parse(
+            br#"<opnsense><laggs><lagg><laggif>lagg0</laggif><members>vtnet1</members></lagg></laggs></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(
+            br#"<opnsense><laggs><lagg><laggif>lagg0</laggif><members>vtnet9</members></lagg></laggs></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::default();
+
+        reconcile(&mut out, &source, &target, &mut diagnostics);
+        assert_eq!(out.get_text(&["laggs", "lagg", "members"]), Some("vtnet1"));
+    }
+}
@@ -3,6 +3,52 @@ use std::net::Ipv4Addr;
 use anyhow::{bail, Result};
 use xml_diff_core::XmlNode;
 
+use crate::transform::booleans::nodes_at_mut;
+use crate::verify_values::nodes_at;
+
+/// Fields holding literal addresses a renumbered LAN can leave stale.
+///
+/// Alias `address` lists are space-separated and model alias `content` is
+/// newline-separated; each entry is checked on its own.
+const LITERAL_FIELDS: &[&[&str]] = &[
+    &["filter", "rule", "source", "address"],
+    &["filter", "rule", "destination", "address"],
+    &["nat", "rule", "target"],
+    &["nat", "rule", "source", "address"],
+    &["nat", "rule", "destination", "address"],
+    &["nat", "outbound", "rule", "source", "network"],
+    &["nat", "outbound", "rule", "destination", "address"],
+    &["nat", "outbound", "rule", "target"],
+    &["nat", "onetoone", "external"],
+    &["nat", "onetoone", "source", "address"],
+    &["nat", "onetoone", "destination", "address"],
+    &["aliases", "alias", "address"],
+    &[
+        "OPNsense", "Firewall", "Alias", "aliases", "alias", "content",
+    ],
+];
+
+/// What to do with literal addresses from the old LAN subnet in rules, NAT,
+/// and aliases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LanLiterals {
+    /// Move them into the new subnet, keeping host bits like the DHCP remap.
+    #[default]
+    Rewrite,
+    /// Leave them and report each one for review.
+    Review,
+}
+
+/// A literal address from the old LAN subnet found outside `<dhcpd><lan>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanLiteral {
+    /// Field holding it (`filter/rule[3]/destination/address`).
+    pub path: String,
+    pub old: String,
+    /// The rewritten value; `None` when it was left for review.
+    pub new: Option<String>,
+}
+
 /// Rewrite the LAN interface IP address and update every reference to it
 /// throughout the config tree.
 ///
@@ -19,11 +65,21 @@ use xml_diff_core::XmlNode;
 /// 3. **`replace_exact_ip_text`** -- sweep the entire tree for text nodes
 ///    that exactly match the old LAN IP and replace them (catches gateways,
 ///    static routes, and other stray references).
+/// 4. **`renumber_literals`** -- find addresses, ranges, and networks from
+///    the old subnet in rule, NAT, and alias fields ([`LITERAL_FIELDS`]) and
+///    rewrite or report them per `literals`. A network wider than the LAN
+///    that contains it cannot be moved consistently and is always reported.
+///
+/// Returns every literal found, rewritten or not.
 ///
 /// Before making changes, `ensure_no_conflict` checks that no other interface
 /// already uses the requested IP. If the old and new IPs are identical, the
 /// function is a no-op.
-pub fn apply(root: &mut XmlNode, new_lan_ip: &str) -> Result<()> {
+pub fn apply(
+    root: &mut XmlNode,
+    new_lan_ip: &str,
+    literals: LanLiterals,
+) -> Result<Vec<LanLiteral>> {
     let new_ip: Ipv4Addr = new_lan_ip
         .parse()
         .map_err(|_| anyhow::anyhow!("invalid --lan-ip value: {new_lan_ip}"))?;
@@ -43,7 +99,7 @@ pub fn apply(root: &mut XmlNode, new_lan_ip: &str) -> Result<()> {
         .parse()
         .map_err(|_| anyhow::anyhow!("interfaces.lan.ipaddr is not IPv4: {old_ip_str}"))?;
     if old_ip == new_ip {
-        return Ok(());
+        return Ok(Vec::new());
     }
     let prefix = lan
         .get_text(&["subnet"])
@@ -58,7 +114,7 @@ pub fn apply(root: &mut XmlNode, new_lan_ip: &str) -> Result<()> {
         old_ip.to_string().as_str(),
         new_ip.to_string().as_str(),
     );
-    Ok(())
+    Ok(renumber_literals(root, old_ip, new_ip, prefix, literals))
 }
 
 /// Bail if any non-LAN interface already uses `new_ip` as its `<ipaddr>`.
@@ -163,15 +219,105 @@ fn replace_exact_ip_text(node: &mut XmlNode, old_ip: &str, new_ip: &str) {
     }
 }
 
+/// Rewrite or collect old-subnet literals in each of [`LITERAL_FIELDS`].
+fn renumber_literals(
+    root: &mut XmlNode,
+    old_ip: Ipv4Addr,
+    new_ip: Ipv4Addr,
+    prefix: u8,
+    mode: LanLiterals,
+) -> Vec<LanLiteral> {
+    let snapshot = root.clone();
+    let mut out = Vec::new();
+    for path in LITERAL_FIELDS {
+        let mut labelled = Vec::new();
+        nodes_at(&snapshot, path, String::new(), &mut labelled);
+        for ((label, _), node) in labelled.into_iter().zip(nodes_at_mut(root, path)) {
+            let Some(text) = node.text.clone() else {
+                continue;
+            };
+            let rewritten = map_tokens(&text, |token| {
+                let new = match renumber_token(token, old_ip, new_ip, prefix)? {
+                    Some(new) if mode == LanLiterals::Rewrite => Some(new),
+                    _ => None,
+                };
+                out.push(LanLiteral {
+                    path: label.clone(),
+                    old: token.to_string(),
+                    new: new.clone(),
+                });
+                new
+            });
+            if rewritten != text {
+                node.text = Some(rewritten);
+            }
+        }
+    }
+    out
+}
+
+/// Rebuild `text` with each whitespace- or comma-separated token passed
+/// through `f`; tokens it returns `None` for are kept, as are separators.
+pub(crate) fn map_tokens(text: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut token = String::new();
+    for ch in text.chars() {
+        if ch.is_whitespace() || ch == ',' {
+            out.push_str(&f(&token).unwrap_or_else(|| token.clone()));
+            out.push(ch);
+            token.clear();
+        } else {
+            token.push(ch);
+        }
+    }
+    out.push_str(&f(&token).unwrap_or(token));
+    out
+}
+
+/// Classify one address, `a-b` range, or `a/len` network.
+///
+/// `None` when it does not touch the old subnet, `Some(Some(new))` when it
+/// can be moved into the new subnet, and `Some(None)` when it touches the
+/// old subnet but cannot be moved consistently (a range leaving the subnet,
+/// or a network wider than it).
+pub(crate) fn renumber_token(
+    token: &str,
+    old_ip: Ipv4Addr,
+    new_ip: Ipv4Addr,
+    prefix: u8,
+) -> Option<Option<String>> {
+    if let Some((from, to)) = token.split_once('-') {
+        let (from, to) = (from.parse::<Ipv4Addr>().ok()?, to.parse::<Ipv4Addr>().ok()?);
+        let from_new = remap_if_in_old_subnet(&from.to_string(), old_ip, new_ip, prefix);
+        let to_new = remap_if_in_old_subnet(&to.to_string(), old_ip, new_ip, prefix);
+        return match (from_new, to_new) {
+            (None, None) => None,
+            (Some(from), Some(to)) => Some(Some(format!("{from}-{to}"))),
+            _ => Some(None),
+        };
+    }
+    if let Some((addr, len)) = token.split_once('/') {
+        let addr: Ipv4Addr = addr.parse().ok()?;
+        let len: u8 = len.parse().ok()?;
+        if len >= prefix {
+            let moved = remap_if_in_old_subnet(&addr.to_string(), old_ip, new_ip, prefix)?;
+            return Some(Some(format!("{moved}/{len}")));
+        }
+        let wide = network(addr, len)?;
+        return (network(old_ip, len)? == wide).then_some(None);
+    }
+    remap_if_in_old_subnet(token, old_ip, new_ip, prefix).map(Some)
+}
+
 /// Compute the network address (host bits zeroed) for the given IP and prefix.
-fn network(ip: Ipv4Addr, prefix: u8) -> Option<u32> {
+pub(crate) fn network(ip: Ipv4Addr, prefix: u8) -> Option<u32> {
     Some(u32::from(ip) & mask(prefix)?)
 }
 
 /// Return the subnet mask as a `u32` for a CIDR prefix length (0..=32).
 ///
 /// Returns `None` for invalid prefix lengths (> 32).
-fn mask(prefix: u8) -> Option<u32> {
+pub(crate) fn mask(prefix: u8) -> Option<u32> {
     if prefix > 32 {
         return None;
     }
@@ -200,7 +346,7 @@ fn set_or_insert_text_child(node: &mut XmlNode, tag: &str, value: &str) {
 
 #[cfg(test)]
 mod tests {
-    use super::apply;
+    use super::{apply, LanLiterals};
     use xml_diff_core::parse;
 
     #[test]
@@ -214,7 +360,7 @@ mod tests {
         )
         .expect("parse");
 
-        apply(&mut root, "192.168.1.1").expect("apply");
+        apply(&mut root, "192.168.1.1", LanLiterals::Rewrite).expect("apply");
 
         assert_eq!(
             root.get_text(&["interfaces", "lan", "ipaddr"]),
@@ -237,4 +383,46 @@ mod tests {
             Some("192.168.1.1")
         );
     }
+
+    #[test]
+    fn rewrites_or_reports_old_subnet_literals() {
+        let source = br#"<pfsense>
+                <interfaces><lan><ipaddr>10.1.10.1</ipaddr><subnet>24</subnet></lan></interfaces>
+                <filter><rule><destination><address>10.1.10.5</address></destination></rule>
+                  <rule><source><address>10.0.0.0/8</address></source></rule></filter>
+                <nat><rule><target>10.1.10.20</target></rule></nat>
+                <aliases><alias><address>10.1.10.0/25 10.1.10.30-10.1.10.40 172.16.0.1</address></alias></aliases>
+            </pfsense>"#;
+
+        let mut root = parse(source).expect("parse");
+        let literals = apply(&mut root, "192.168.1.1", LanLiterals::Rewrite).expect("apply");
+        assert_eq!(
+            root.get_text(&["filter", "rule", "destination", "address"]),
+            Some("192.168.1.5")
+        );
+        assert_eq!(
+            root.get_text(&["nat", "rule", "target"]),
+            Some("192.168.1.20")
+        );
+        assert_eq!(
+            root.get_text(&["aliases", "alias", "address"]),
+            Some("192.168.1.0/25 192.168.1.30-192.168.1.40 172.16.0.1")
+        );
+        let unmoved: Vec<(&str, &str)> = literals
+            .iter()
+            .filter(|l| l.new.is_none())
+            .map(|l| (l.path.as_str(), l.old.as_str()))
+            .collect();
+        assert_eq!(unmoved, [("filter/rule[2]/source/address", "10.0.0.0/8")]);
+        assert_eq!(literals.len(), 5);
+
+        let mut root = parse(source).expect("parse");
+        let literals = apply(&mut root, "192.168.1.1", LanLiterals::Review).expect("apply");
+        assert_eq!(
+            root.get_text(&["nat", "rule", "target"]),
+            Some("10.1.10.20")
+        );
+        assert_eq!(literals.len(), 5);
+        assert!(literals.iter().all(|l| l.new.is_none()));
+    }
 }
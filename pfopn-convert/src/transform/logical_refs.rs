@@ -29,7 +29,11 @@ fn rewrite_node(node: &mut XmlNode, logical_map: &BTreeMap<String, String>) {
         // <members> and <interfaces> can hold space-separated lists of
         // logical interface names, e.g. "lan opt1 opt2".
         "members" | "interfaces" => rewrite_token_list(node, logical_map),
-        // <interface> holds a single logical name, e.g. "opt2".
+        // <interface> usually holds a single logical name, e.g. "opt2"; floating
+        // rules and NTP bindings use comma-separated lists such as "lan,opt2".
+        "interface" if node.text.as_deref().is_some_and(|t| t.contains(',')) => {
+            rewrite_token_list(node, logical_map)
+        }
         "interface" => rewrite_single(node, logical_map),
         _ => {}
     }
@@ -137,4 +141,35 @@ mod tests {
             Some("opt1")
         );
     }
+
+    #[test]
+    fn rewrites_interface_group_members() {
+        let mut root = parse(
+            br#"<opnsense><ifgroups><ifgroupentry><ifname>Trusted</ifname><members>lan opt2</members></ifgroupentry></ifgroups></opnsense>"#,
+        )
+        .expect("parse");
+        let mut map = BTreeMap::new();
+        map.insert("opt2".to_string(), "opt1".to_string());
+
+        apply(&mut root, Some(&map));
+        assert_eq!(
+            root.get_text(&["ifgroups", "ifgroupentry", "members"]),
+            Some("lan opt1")
+        );
+        assert_eq!(
+            root.get_text(&["ifgroups", "ifgroupentry", "ifname"]),
+            Some("Trusted")
+        );
+    }
+
+    #[test]
+    fn rewrites_comma_separated_interface_lists() {
+        let mut root = parse(br#"<pfsense><ntpd><interface>lan,opt2</interface></ntpd></pfsense>"#)
+            .expect("parse");
+        let mut map = BTreeMap::new();
+        map.insert("opt2".to_string(), "opt1".to_string());
+
+        apply(&mut root, Some(&map));
+        assert_eq!(root.get_text(&["ntpd", "interface"]), Some("lan,opt1"));
+    }
 }
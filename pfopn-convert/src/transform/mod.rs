@@ -1,24 +1,42 @@
+pub mod acme;
 pub mod aliases;
+pub mod booleans;
 pub mod bridges;
 pub mod certs;
 pub mod device_refs;
 pub mod dhcp;
+pub mod firmware;
+pub mod gateways;
 pub mod ifgroups;
 pub mod interface_presence;
 pub mod interface_settings;
 pub mod ipsec;
 pub mod ipsec_pf_to_opn;
+pub mod laggs;
 pub mod lan_ip;
 pub mod logical_refs;
+pub mod monitoring_agents;
+pub mod mvc_snapshot;
+pub mod nat;
+pub mod ntpd;
 pub mod openvpn;
 pub mod opnsense_assignments;
 pub mod pfblocker;
+pub mod ppp_servers;
 pub mod ppps;
+pub mod renumber;
 pub mod section_sync;
+pub mod small_packages;
 pub mod staticroutes;
-pub mod system_identity;
+pub mod system_groups;
+pub mod system_settings;
 pub mod system_users;
 pub mod tailscale;
+pub mod tunables;
+pub mod units;
 pub mod users;
 pub mod vlan_ifnames;
+pub mod vlans;
+pub mod webgui;
 pub mod wireguard;
+pub mod wol;
@@ -0,0 +1,438 @@
+//! pfSense monitoring agent packages to their OPNsense plugins.
+//!
+//! | pfSense package | `<installedpackages>` child | OPNsense plugin | Model |
+//! |-----------------|-----------------------------|-----------------|-------|
+//! | Zabbix Agent | `zabbixagentlts` / `zabbixagent` | os-zabbix-agent | `<OPNsense><ZabbixAgent>` |
+//! | Telegraf | `telegraf` | os-telegraf | `<OPNsense><telegraf>` |
+//!
+//! Both packages keep a single `<config>` entry. Settings with a direct
+//! counterpart (server addresses, hostname, ports, PSK encryption, the
+//! InfluxDB output) are written into the plugin model; the rest are reported
+//! as `monitoring` warnings. Zabbix user parameters, stored base64-encoded
+//! on pfSense, become `<userparameter>` items.
+
+use base64::Engine;
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::is_truthy;
+use crate::uuid_registry::UuidRegistry;
+
+/// `<installedpackages>` children holding Zabbix agent settings.
+pub const ZABBIX_TAGS: &[&str] = &["zabbixagentlts", "zabbixagent"];
+
+/// How a pfSense value is written to the model.
+#[derive(Debug, Clone, Copy)]
+enum Value {
+    Text,
+    /// Any truthy spelling becomes `1`, anything else `0`.
+    Flag,
+    /// Space- or comma-separated list, written comma-separated.
+    List,
+}
+
+/// pfSense field, model path, and conversion.
+type FieldMap = (&'static str, &'static [&'static str], Value);
+
+const ZABBIX_FIELDS: &[FieldMap] = &[
+    (
+        "agentenabled",
+        &["settings", "main", "enabled"],
+        Value::Flag,
+    ),
+    ("hostname", &["settings", "main", "hostname"], Value::Text),
+    ("server", &["settings", "main", "serverList"], Value::List),
+    ("listenip", &["settings", "main", "listenIP"], Value::Text),
+    (
+        "listenport",
+        &["settings", "main", "listenPort"],
+        Value::Text,
+    ),
+    (
+        "serveractive",
+        &["settings", "features", "activeCheckServers"],
+        Value::List,
+    ),
+    (
+        "refreshactchecks",
+        &["settings", "features", "refreshActiveChecks"],
+        Value::Text,
+    ),
+    ("timeout", &["settings", "tuning", "timeout"], Value::Text),
+    (
+        "startagents",
+        &["settings", "tuning", "startAgents"],
+        Value::Text,
+    ),
+    (
+        "buffersend",
+        &["settings", "tuning", "bufferSend"],
+        Value::Text,
+    ),
+    (
+        "buffersize",
+        &["settings", "tuning", "bufferSize"],
+        Value::Text,
+    ),
+    (
+        "tlspskidentity",
+        &["settings", "features", "encryptionidentity"],
+        Value::Text,
+    ),
+    (
+        "tlspsk",
+        &["settings", "features", "encryptionpsk"],
+        Value::Text,
+    ),
+];
+
+/// Zabbix certificate-based TLS settings, which refer to files on disk.
+const ZABBIX_TLS_FILES: &[&str] = &["tlscafile", "tlscertfile", "tlskeyfile", "tlscrlfile"];
+
+const TELEGRAF_FIELDS: &[FieldMap] = &[
+    ("enable", &["general", "enabled"], Value::Flag),
+    ("interval", &["general", "interval"], Value::Text),
+];
+
+/// Telegraf InfluxDB output settings; only written when the output is InfluxDB.
+const TELEGRAF_INFLUX_FIELDS: &[FieldMap] = &[
+    ("influx_server", &["output", "influx_url"], Value::Text),
+    ("influx_db", &["output", "influx_database"], Value::Text),
+    ("influx_user", &["output", "influx_username"], Value::Text),
+    ("influx_pass", &["output", "influx_password"], Value::Text),
+    (
+        "insecure_skip_verify",
+        &["output", "influx_skip_ssl_verify"],
+        Value::Flag,
+    ),
+];
+
+/// Convert the Zabbix agent and Telegraf package settings in `source` into
+/// their OPNsense plugin models in `out`.
+///
+/// An existing model in the output keeps the fields the package does not
+/// set. The package configs themselves are removed with the rest of
+/// `<installedpackages>` when the output is pruned.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(installed) = source.get_child("installedpackages") else {
+        return;
+    };
+    if let Some(config) = ZABBIX_TAGS
+        .iter()
+        .find_map(|tag| installed.get_child(tag))
+        .and_then(package_config)
+    {
+        zabbix(out, config, diagnostics);
+    }
+    if let Some(config) = installed.get_child("telegraf").and_then(package_config) {
+        telegraf(out, config, diagnostics);
+    }
+}
+
+fn zabbix(out: &mut XmlNode, config: &XmlNode, diagnostics: &mut Diagnostics) {
+    let mut registry = UuidRegistry::from_config(out);
+    let mut model = take_model(out, "ZabbixAgent");
+    apply_fields(&mut model, config, ZABBIX_FIELDS);
+    let active = !field(config, "serveractive").is_empty();
+    set_path(
+        &mut model,
+        &["settings", "features", "enableActiveChecks"],
+        flag(active),
+    );
+    let psk = !field(config, "tlspsk").is_empty();
+    set_path(
+        &mut model,
+        &["settings", "features", "encryption"],
+        flag(psk),
+    );
+
+    let cert_files: Vec<&str> = ZABBIX_TLS_FILES
+        .iter()
+        .copied()
+        .filter(|tag| !field(config, tag).is_empty())
+        .collect();
+    if !cert_files.is_empty() {
+        diagnostics.warn(
+            "monitoring",
+            format!(
+                "Zabbix Agent -> os-zabbix-agent: certificate-based TLS ({}) is not carried; copy the files and configure it by hand",
+                cert_files.join(", ")
+            ),
+        );
+    }
+
+    let params = user_parameters(field(config, "userparams"));
+    if !params.is_empty() {
+        let list = ensure_child_mut(&mut model, "userparameters");
+        for (key, command, accept_params) in params {
+            if list
+                .get_children("userparameter")
+                .iter()
+                .any(|p| field(p, "key") == key)
+            {
+                continue;
+            }
+            let mut node = XmlNode::new("userparameter");
+            node.attributes.insert(
+                "uuid".to_string(),
+                registry.uuid_for("zabbix-userparameter", &key),
+            );
+            set_path(&mut node, &["enabled"], "1");
+            set_path(&mut node, &["key"], &key);
+            set_path(&mut node, &["command"], &command);
+            set_path(&mut node, &["acceptParams"], flag(accept_params));
+            list.children.push(node);
+        }
+    }
+
+    put_model(out, model);
+    diagnostics.info(
+        "monitoring",
+        "Zabbix Agent settings converted; install os-zabbix-agent on the target before restore",
+    );
+}
+
+fn telegraf(out: &mut XmlNode, config: &XmlNode, diagnostics: &mut Diagnostics) {
+    let mut model = take_model(out, "telegraf");
+    apply_fields(&mut model, config, TELEGRAF_FIELDS);
+    match field(config, "telegraf_output") {
+        "" | "influxdb" => {
+            apply_fields(&mut model, config, TELEGRAF_INFLUX_FIELDS);
+            set_path(&mut model, &["output", "influx_enable"], "1");
+        }
+        other => diagnostics.warn(
+            "monitoring",
+            format!("Telegraf -> os-telegraf: '{other}' output is not carried; configure it under Services > Telegraf > Output"),
+        ),
+    }
+    if !field(config, "telegraf_raw_config").is_empty() {
+        diagnostics.warn(
+            "monitoring",
+            "Telegraf -> os-telegraf: the additional raw configuration is not carried; review it against os-telegraf's input and output settings",
+        );
+    }
+
+    put_model(out, model);
+    diagnostics.info(
+        "monitoring",
+        "Telegraf settings converted; install os-telegraf on the target before restore",
+    );
+}
+
+/// Parse base64 `UserParameter=key,command` lines into `(key, command,
+/// accepts parameters)`.
+fn user_parameters(encoded: &str) -> Vec<(String, String, bool)> {
+    let compact: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    let Ok(decoded) = base64::engine::general_purpose::STANDARD.decode(compact) else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&decoded)
+        .lines()
+        .filter_map(|line| {
+            let (key, command) = line
+                .trim()
+                .strip_prefix("UserParameter=")?
+                .split_once(',')?;
+            let (key, accept_params) = match key.strip_suffix("[*]") {
+                Some(base) => (base, true),
+                None => (key, false),
+            };
+            Some((
+                key.trim().to_string(),
+                command.trim().to_string(),
+                accept_params,
+            ))
+        })
+        .collect()
+}
+
+fn apply_fields(model: &mut XmlNode, config: &XmlNode, fields: &[FieldMap]) {
+    for (tag, path, kind) in fields {
+        let Some(raw) = config.get_text(&[tag]).map(str::trim) else {
+            continue;
+        };
+        let value = match kind {
+            Value::Text => raw.to_string(),
+            Value::Flag => flag(is_truthy(raw)).to_string(),
+            Value::List => raw
+                .split([' ', ','])
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(","),
+        };
+        set_path(model, path, &value);
+    }
+}
+
+/// The package's single `<config>` entry, or the package node itself when
+/// the fields sit directly under it.
+fn package_config(package: &XmlNode) -> Option<&XmlNode> {
+    let config = package.get_child("config").unwrap_or(package);
+    (!config.children.is_empty()).then_some(config)
+}
+
+/// Remove `<OPNsense><{tag}>` from `out`, or start a new model.
+fn take_model(out: &mut XmlNode, tag: &str) -> XmlNode {
+    let opn = ensure_child_mut(out, "OPNsense");
+    match opn.children.iter().position(|c| c.tag == tag) {
+        Some(idx) => opn.children.remove(idx),
+        None => XmlNode::new(tag),
+    }
+}
+
+fn put_model(out: &mut XmlNode, model: XmlNode) {
+    ensure_child_mut(out, "OPNsense").children.push(model);
+}
+
+fn flag(on: bool) -> &'static str {
+    if on {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+fn field<'a>(node: &'a XmlNode, tag: &str) -> &'a str {
+    node.get_text(&[tag]).map(str::trim).unwrap_or("")
+}
+
+fn set_path(node: &mut XmlNode, path: &[&str], value: &str) {
+    let mut current = node;
+    for part in path {
+        current = ensure_child_mut(current, part);
+    }
+    current.text = Some(value.to_string());
+}
+
+/// Get a mutable reference to a child, creating it if it doesn't exist.
+fn ensure_child_mut<'a>(parent: &'a mut XmlNode, tag: &str) -> &'a mut XmlNode {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == tag) {
+        return &mut parent.children[idx];
+    }
+    parent.children.push(XmlNode::new(tag));
+    let last = parent.children.len() - 1;
+    &mut parent.children[last]
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use xml_diff_core::parse;
+
+    use super::to_opnsense;
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn converts_zabbix_agent_settings_and_user_parameters() {
+        let params = base64::engine::general_purpose::STANDARD.encode(
+            "UserParameter=pf.states,pfctl -si | grep current\nUserParameter=disk.free[*],df $1\n",
+        );
+        let source = parse(
+            format!(
+                "<pfsense><installedpackages><zabbixagentlts><config>\
+                 <agentenabled>on</agentenabled><server>10.0.0.5 10.0.0.6</server>\
+                 <serveractive>10.0.0.5</serveractive><hostname>fw1</hostname>\
+                 <listenport>10050</listenport><tlspskidentity>fw1</tlspskidentity><tlspsk>abcd</tlspsk>\
+                 <tlscafile>/root/ca.pem</tlscafile><userparams>{params}</userparams>\
+                 </config></zabbixagentlts></installedpackages></pfsense>"
+            )
+            .as_bytes(),
+        )
+        .expect("parse");
+        let baseline = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let model = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("ZabbixAgent"))
+            .expect("model");
+        assert_eq!(model.get_text(&["settings", "main", "enabled"]), Some("1"));
+        assert_eq!(
+            model.get_text(&["settings", "main", "serverList"]),
+            Some("10.0.0.5,10.0.0.6")
+        );
+        assert_eq!(
+            model.get_text(&["settings", "main", "hostname"]),
+            Some("fw1")
+        );
+        assert_eq!(
+            model.get_text(&["settings", "features", "enableActiveChecks"]),
+            Some("1")
+        );
+        assert_eq!(
+            model.get_text(&["settings", "features", "encryption"]),
+            Some("1")
+        );
+        let params = model
+            .get_child("userparameters")
+            .expect("userparameters")
+            .get_children("userparameter");
+        assert_eq!(params.len(), 2);
+        assert_eq!(
+            params[0].get_text(&["command"]),
+            Some("pfctl -si | grep current")
+        );
+        assert_eq!(params[1].get_text(&["key"]), Some("disk.free"));
+        assert_eq!(params[1].get_text(&["acceptParams"]), Some("1"));
+
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("certificate-based TLS (tlscafile)")));
+    }
+
+    #[test]
+    fn converts_telegraf_influx_output_and_keeps_other_model_fields() {
+        let source = parse(
+            br#"<pfsense><installedpackages><telegraf><config>
+                <enable>on</enable><interval>30</interval><telegraf_output>influxdb</telegraf_output>
+                <influx_server>https://influx.example:8086</influx_server><influx_db>fw</influx_db>
+                <influx_user>writer</influx_user><influx_pass>secret</influx_pass>
+                <telegraf_raw_config>W1tpbnB1dHMuY3B1XV0=</telegraf_raw_config>
+            </config></telegraf></installedpackages></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = parse(
+            br#"<opnsense><OPNsense><telegraf version="1.12.0"><input><cpu>1</cpu></input></telegraf></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let model = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("telegraf"))
+            .expect("model");
+        assert_eq!(
+            model.attributes.get("version").map(String::as_str),
+            Some("1.12.0")
+        );
+        assert_eq!(model.get_text(&["input", "cpu"]), Some("1"));
+        assert_eq!(model.get_text(&["general", "enabled"]), Some("1"));
+        assert_eq!(model.get_text(&["general", "interval"]), Some("30"));
+        assert_eq!(model.get_text(&["output", "influx_enable"]), Some("1"));
+        assert_eq!(
+            model.get_text(&["output", "influx_url"]),
+            Some("https://influx.example:8086")
+        );
+        assert_eq!(
+            model.get_text(&["output", "influx_password"]),
+            Some("secret")
+        );
+
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("additional raw configuration is not carried")));
+    }
+}
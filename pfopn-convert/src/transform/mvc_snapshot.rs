@@ -0,0 +1,264 @@
+//! Round-trip snapshots of OPNsense MVC sections.
+//!
+//! OPNsense models (OpenVPN instances, Swanctl, Kea, firewall aliases,
+//! WireGuard) carry fields pfSense has no place for, so an OPNsense → pfSense
+//! → OPNsense hop would lose them. When converting to pfSense, each model
+//! present in the source is copied into a single hidden container:
+//!
+//! ```xml
+//! <pfopn_snapshots>
+//!   <snapshot path="OPNsense/OpenVPN" digest="...">
+//!     <OpenVPN version="1.0.0">...</OpenVPN>
+//!   </snapshot>
+//! </pfopn_snapshots>
+//! ```
+//!
+//! `digest` fingerprints the pfSense sections generated from that model.
+//! When converting back to OPNsense, a snapshot is restored only if those
+//! pfSense sections are unchanged; otherwise the edits made on pfSense win and
+//! the section is converted normally.
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+
+/// Top-level tag of the hidden snapshot container in pfSense output.
+pub const CONTAINER_TAG: &str = "pfopn_snapshots";
+
+/// An OPNsense model and the pfSense sections it is converted into.
+struct SnapshotSection {
+    path: &'static [&'static str],
+    pfsense: &'static [&'static [&'static str]],
+}
+
+const SECTIONS: &[SnapshotSection] = &[
+    SnapshotSection {
+        path: &["OPNsense", "OpenVPN"],
+        pfsense: &[&["openvpn"]],
+    },
+    SnapshotSection {
+        path: &["OPNsense", "Swanctl"],
+        pfsense: &[&["ipsec"]],
+    },
+    SnapshotSection {
+        path: &["OPNsense", "IPsec"],
+        pfsense: &[&["ipsec"]],
+    },
+    SnapshotSection {
+        path: &["OPNsense", "Kea"],
+        pfsense: &[&["dhcpd"], &["kea"]],
+    },
+    SnapshotSection {
+        path: &["OPNsense", "Firewall", "Alias"],
+        pfsense: &[&["aliases"]],
+    },
+    SnapshotSection {
+        path: &["OPNsense", "wireguard"],
+        pfsense: &[&["installedpackages", "wireguard"], &["wireguard"]],
+    },
+];
+
+/// Store the source's OPNsense models in the pfSense output.
+///
+/// Call after every other transform so the digest matches what is written.
+/// Replaces any existing container in `out`.
+pub fn capture(out: &mut XmlNode, source: &XmlNode) {
+    out.children.retain(|c| c.tag != CONTAINER_TAG);
+    let mut container = XmlNode::new(CONTAINER_TAG);
+    for section in SECTIONS {
+        let Some(model) = find_path(source, section.path) else {
+            continue;
+        };
+        let mut snapshot = XmlNode::new("snapshot");
+        snapshot
+            .attributes
+            .insert("path".to_string(), section.path.join("/"));
+        snapshot
+            .attributes
+            .insert("digest".to_string(), pfsense_digest(out, section));
+        snapshot.children.push(model.clone());
+        container.children.push(snapshot);
+    }
+    if !container.children.is_empty() {
+        out.children.push(container);
+    }
+}
+
+/// Restore OPNsense models from a pfSense source's snapshot container.
+///
+/// Snapshots whose path is listed in `skip` are left alone (for example Kea
+/// when the output uses the ISC backend).
+pub fn restore(out: &mut XmlNode, source: &XmlNode, skip: &[&str], diagnostics: &mut Diagnostics) {
+    let Some(container) = source.get_child(CONTAINER_TAG) else {
+        return;
+    };
+    for snapshot in container.get_children("snapshot") {
+        let Some(path) = snapshot.attributes.get("path") else {
+            continue;
+        };
+        let Some(section) = SECTIONS.iter().find(|s| s.path.join("/") == *path) else {
+            diagnostics.warn(
+                "snapshot",
+                format!("unknown round-trip snapshot '{path}' ignored"),
+            );
+            continue;
+        };
+        if skip.contains(&path.as_str()) {
+            continue;
+        }
+        let Some(model) = snapshot.children.first() else {
+            continue;
+        };
+        if snapshot.attributes.get("digest") != Some(&pfsense_digest(source, section)) {
+            diagnostics.warn(
+                "snapshot",
+                format!(
+                    "{path} was edited on pfSense since the round-trip snapshot; converted from pfSense data instead"
+                ),
+            );
+            continue;
+        }
+
+        let (leaf, parents) = section.path.split_last().expect("non-empty path");
+        let parent = ensure_path_mut(out, parents);
+        match parent.children.iter_mut().find(|c| c.tag == *leaf) {
+            Some(existing) => *existing = model.clone(),
+            None => parent.children.push(model.clone()),
+        }
+        diagnostics.info(
+            "snapshot",
+            format!("{path} restored from round-trip snapshot"),
+        );
+    }
+}
+
+/// Fingerprint the pfSense sections generated from `section`.
+fn pfsense_digest(root: &XmlNode, section: &SnapshotSection) -> String {
+    let mut hash = FNV_OFFSET;
+    for path in section.pfsense {
+        hash = fnv(hash, path.join("/").as_bytes());
+        if let Some(node) = find_path(root, path) {
+            hash = hash_node(hash, node);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+fn fnv(mut hash: u64, bytes: &[u8]) -> u64 {
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    // Separator so adjacent fields cannot run together.
+    hash ^= 0xff;
+    hash.wrapping_mul(FNV_PRIME)
+}
+
+/// Hash a subtree, ignoring formatting details that do not survive a write
+/// and re-parse (surrounding whitespace, CDATA, comments).
+fn hash_node(mut hash: u64, node: &XmlNode) -> u64 {
+    hash = fnv(hash, node.tag.as_bytes());
+    for (key, value) in &node.attributes {
+        hash = fnv(hash, key.as_bytes());
+        hash = fnv(hash, value.as_bytes());
+    }
+    hash = fnv(hash, node.text.as_deref().unwrap_or("").trim().as_bytes());
+    for child in &node.children {
+        hash = hash_node(hash, child);
+    }
+    fnv(hash, b"/")
+}
+
+fn find_path<'a>(root: &'a XmlNode, path: &[&str]) -> Option<&'a XmlNode> {
+    path.iter().try_fold(root, |node, tag| node.get_child(tag))
+}
+
+fn ensure_path_mut<'a>(root: &'a mut XmlNode, path: &[&str]) -> &'a mut XmlNode {
+    let mut node = root;
+    for tag in path {
+        let idx = match node.children.iter().position(|c| c.tag == *tag) {
+            Some(idx) => idx,
+            None => {
+                node.children.push(XmlNode::new(*tag));
+                node.children.len() - 1
+            }
+        };
+        node = &mut node.children[idx];
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::{parse, write};
+
+    use super::{capture, restore, CONTAINER_TAG};
+    use crate::diagnostics::Diagnostics;
+
+    fn opn_source() -> xml_diff_core::XmlNode {
+        parse(
+            br#"<opnsense><OPNsense><OpenVPN version="1.0.0"><Instances><Instance uuid="i1"><role>server</role><verify_client_cert>require</verify_client_cert></Instance></Instances></OpenVPN><Firewall><Alias><aliases><alias uuid="a1"><name>lan_hosts</name><counters>1</counters></alias></aliases></Alias></Firewall></OPNsense></opnsense>"#,
+        )
+        .expect("parse")
+    }
+
+    #[test]
+    fn restores_unchanged_sections_after_write_and_reparse() {
+        let mut pf = parse(
+            br#"<pfsense><openvpn><openvpn-server><vpnid>1</vpnid><description>  road warrior </description></openvpn-server></openvpn><aliases><alias><name>lan_hosts</name></alias></aliases></pfsense>"#,
+        )
+        .expect("parse");
+        capture(&mut pf, &opn_source());
+        let container = pf.get_child(CONTAINER_TAG).expect("container");
+        assert_eq!(container.get_children("snapshot").len(), 2);
+
+        let reparsed = parse(&write(&pf).expect("write")).expect("reparse");
+        let mut opn =
+            parse(br#"<opnsense><OPNsense><OpenVPN/></OPNsense></opnsense>"#).expect("parse");
+        let mut diagnostics = Diagnostics::new();
+        restore(&mut opn, &reparsed, &[], &mut diagnostics);
+
+        assert_eq!(
+            opn.get_text(&[
+                "OPNsense",
+                "OpenVPN",
+                "Instances",
+                "Instance",
+                "verify_client_cert"
+            ]),
+            Some("require")
+        );
+        assert_eq!(
+            opn.get_text(&["OPNsense", "Firewall", "Alias", "aliases", "alias", "counters"]),
+            Some("1")
+        );
+        assert_eq!(diagnostics.iter().count(), 2);
+    }
+
+    #[test]
+    fn skips_sections_edited_on_pfsense() {
+        let mut pf = parse(
+            br#"<pfsense><openvpn><openvpn-server><vpnid>1</vpnid></openvpn-server></openvpn></pfsense>"#,
+        )
+        .expect("parse");
+        capture(&mut pf, &opn_source());
+        pf.children[0].children[0].children[0].text = Some("2".to_string());
+
+        let mut opn = parse(br#"<opnsense/>"#).expect("parse");
+        let mut diagnostics = Diagnostics::new();
+        restore(
+            &mut opn,
+            &pf,
+            &["OPNsense/Firewall/Alias"],
+            &mut diagnostics,
+        );
+
+        assert!(opn.get_child("OPNsense").is_none());
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].starts_with("OPNsense/OpenVPN was edited on pfSense"));
+    }
+}
@@ -0,0 +1,293 @@
+//! Outbound NAT mode and mapping conversion.
+//!
+//! Both platforms keep outbound NAT in `<nat><outbound>`, and the section
+//! sync copies it from the source, but a few details differ:
+//!
+//! | Setting | pfSense | OPNsense |
+//! |---------|---------|----------|
+//! | interface address target | `<target>wanip</target>` | empty `<target/>` |
+//! | subnet target | `<target>` + `<target_subnet>` | `<target>other-subnet</target>` + `<targetip>` + `<targetip_subnet>` |
+//! | source hash key | `source_hash_key` | `poolopts_sourcehashkey` |
+//!
+//! The outbound mode always follows the source (`manual` is stored as
+//! `advanced` on both platforms). A missing mode means automatic, so it is
+//! written explicitly to keep a target baseline default from applying.
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+
+/// Convert outbound NAT mode and mappings for an OPNsense target.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    reconcile(out, source, target, "opnsense", diagnostics);
+}
+
+/// Convert outbound NAT mode and mappings for a pfSense target.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    reconcile(out, source, target, "pfsense", diagnostics);
+}
+
+fn reconcile(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    target: &XmlNode,
+    platform: &str,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(source_nat) = source.get_child("nat") else {
+        return;
+    };
+    let source_outbound = source_nat.get_child("outbound");
+    let raw_mode = source_outbound
+        .and_then(|o| o.get_text(&["mode"]))
+        .map(str::trim)
+        .unwrap_or("");
+    let mode = normalize_mode(raw_mode);
+    if mode.is_none() {
+        diagnostics.warn(
+            "nat",
+            format!("outbound NAT mode '{raw_mode}' is not recognized; copied as-is"),
+        );
+    }
+    let mode = mode.unwrap_or(raw_mode);
+
+    let baseline_mode = target
+        .get_child("nat")
+        .and_then(|n| n.get_child("outbound"))
+        .and_then(|o| o.get_text(&["mode"]))
+        .and_then(|m| normalize_mode(m.trim()));
+    if let Some(baseline_mode) = baseline_mode.filter(|b| *b != mode) {
+        diagnostics.info(
+            "nat",
+            format!("outbound NAT mode set to '{mode}' from source (target baseline used '{baseline_mode}')"),
+        );
+    }
+
+    let nat = ensure_child_mut(out, "nat");
+    let outbound = ensure_child_mut(nat, "outbound");
+    set_text(outbound, "mode", mode);
+
+    let mut converted = 0usize;
+    for rule in outbound.children.iter_mut().filter(|c| c.tag == "rule") {
+        if platform == "opnsense" {
+            rule_to_opnsense(rule);
+        } else {
+            rule_to_pfsense(rule);
+        }
+        converted += 1;
+    }
+    if converted > 0 && matches!(mode, "automatic" | "disabled") {
+        diagnostics.warn(
+            "nat",
+            format!(
+                "{converted} outbound NAT mapping(s) kept but inactive because outbound mode is '{mode}'"
+            ),
+        );
+    }
+}
+
+/// Map a stored outbound mode onto the spelling both platforms write.
+///
+/// Returns `None` for values neither platform understands.
+fn normalize_mode(raw: &str) -> Option<&'static str> {
+    match raw.to_ascii_lowercase().as_str() {
+        "" | "automatic" => Some("automatic"),
+        "hybrid" => Some("hybrid"),
+        "advanced" | "manual" => Some("advanced"),
+        "disabled" | "disable" => Some("disabled"),
+        _ => None,
+    }
+}
+
+fn rule_to_opnsense(rule: &mut XmlNode) {
+    rename_child(rule, "source_hash_key", "poolopts_sourcehashkey");
+
+    let iface = rule
+        .get_text(&["interface"])
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let target_value = rule.get_text(&["target"]).unwrap_or("").trim().to_string();
+    let bits = rule
+        .get_text(&["target_subnet"])
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    rule.children.retain(|c| c.tag != "target_subnet");
+
+    if !iface.is_empty() && target_value.eq_ignore_ascii_case(&format!("{iface}ip")) {
+        set_text(rule, "target", "");
+    } else if is_ip(&target_value) && !bits.is_empty() && !is_host_bits(&target_value, &bits) {
+        set_text(rule, "target", "other-subnet");
+        set_text(rule, "targetip", &target_value);
+        set_text(rule, "targetip_subnet", &bits);
+    }
+}
+
+fn rule_to_pfsense(rule: &mut XmlNode) {
+    rename_child(rule, "poolopts_sourcehashkey", "source_hash_key");
+
+    let iface = rule
+        .get_text(&["interface"])
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let target_value = rule.get_text(&["target"]).unwrap_or("").trim().to_string();
+    if target_value.eq_ignore_ascii_case("other-subnet") {
+        let ip = rule
+            .get_text(&["targetip"])
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let bits = rule
+            .get_text(&["targetip_subnet"])
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        rule.children
+            .retain(|c| c.tag != "targetip" && c.tag != "targetip_subnet");
+        set_text(rule, "target", &ip);
+        set_text(rule, "target_subnet", &bits);
+    } else if target_value.is_empty() && !iface.is_empty() && !iface.contains(',') {
+        set_text(rule, "target", &format!("{iface}ip"));
+    }
+}
+
+fn is_ip(value: &str) -> bool {
+    value.parse::<std::net::IpAddr>().is_ok()
+}
+
+/// A prefix length that covers a single address needs no subnet target.
+fn is_host_bits(ip: &str, bits: &str) -> bool {
+    let host = if ip.contains(':') { "128" } else { "32" };
+    bits == host
+}
+
+fn rename_child(node: &mut XmlNode, from: &str, to: &str) {
+    if node.get_child(to).is_some() {
+        node.children.retain(|c| c.tag != from);
+        return;
+    }
+    for child in node.children.iter_mut().filter(|c| c.tag == from) {
+        child.tag = to.to_string();
+    }
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    let child = ensure_child_mut(node, tag);
+    child.text = (!value.is_empty()).then(|| value.to_string());
+}
+
+/// Get a mutable reference to a child, creating it if it doesn't exist.
+fn ensure_child_mut<'a>(parent: &'a mut XmlNode, tag: &str) -> &'a mut XmlNode {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == tag) {
+        return &mut parent.children[idx];
+    }
+    parent.children.push(XmlNode::new(tag));
+    let last = parent.children.len() - 1;
+    &mut parent.children[last]
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::{Diagnostics, Severity};
+
+    #[test]
+    fn keeps_source_mode_and_converts_pfsense_mappings() {
+        let source = parse(
+            br#"<pfsense><nat><outbound><mode>hybrid</mode><rule><interface>wan</interface><target>wanip</target><target_subnet/><source_hash_key>0xabc</source_hash_key></rule><rule><interface>wan</interface><target>198.51.100.8</target><target_subnet>29</target_subnet></rule></outbound></nat></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(
+            br#"<opnsense><nat><outbound><mode>automatic</mode></outbound></nat></opnsense>"#,
+        )
+        .expect("target parse");
+        let mut out = parse(
+            br#"<opnsense><nat><outbound><mode>hybrid</mode><rule><interface>wan</interface><target>wanip</target><target_subnet/><source_hash_key>0xabc</source_hash_key></rule><rule><interface>wan</interface><target>198.51.100.8</target><target_subnet>29</target_subnet></rule></outbound></nat></opnsense>"#,
+        )
+        .expect("out parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &target, &mut diagnostics);
+
+        let outbound = out
+            .get_child("nat")
+            .and_then(|n| n.get_child("outbound"))
+            .expect("outbound");
+        assert_eq!(outbound.get_text(&["mode"]), Some("hybrid"));
+        let rules = outbound.get_children("rule");
+        assert_eq!(rules[0].get_text(&["target"]), None);
+        assert_eq!(
+            rules[0].get_text(&["poolopts_sourcehashkey"]),
+            Some("0xabc")
+        );
+        assert!(rules[0].get_child("source_hash_key").is_none());
+        assert_eq!(rules[1].get_text(&["target"]), Some("other-subnet"));
+        assert_eq!(rules[1].get_text(&["targetip"]), Some("198.51.100.8"));
+        assert_eq!(rules[1].get_text(&["targetip_subnet"]), Some("29"));
+        assert!(rules[1].get_child("target_subnet").is_none());
+        assert_eq!(diagnostics.count(Severity::Info), 1);
+    }
+
+    #[test]
+    fn converts_opnsense_mappings_to_pfsense() {
+        let source = parse(
+            br#"<opnsense><nat><outbound><mode>advanced</mode><rule><interface>wan</interface><target/></rule><rule><interface>opt1</interface><target>other-subnet</target><targetip>203.0.113.0</targetip><targetip_subnet>28</targetip_subnet><poolopts_sourcehashkey>k</poolopts_sourcehashkey></rule></outbound></nat></opnsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(br#"<pfsense><nat/></pfsense>"#).expect("target parse");
+        let mut out = source.clone();
+        out.tag = "pfsense".to_string();
+        let mut diagnostics = Diagnostics::new();
+
+        to_pfsense(&mut out, &source, &target, &mut diagnostics);
+
+        let rules = out
+            .get_child("nat")
+            .and_then(|n| n.get_child("outbound"))
+            .map(|o| o.get_children("rule"))
+            .expect("rules");
+        assert_eq!(rules[0].get_text(&["target"]), Some("wanip"));
+        assert_eq!(rules[1].get_text(&["target"]), Some("203.0.113.0"));
+        assert_eq!(rules[1].get_text(&["target_subnet"]), Some("28"));
+        assert_eq!(rules[1].get_text(&["source_hash_key"]), Some("k"));
+        assert!(rules[1].get_child("targetip").is_none());
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn writes_explicit_mode_and_flags_inactive_mappings() {
+        let source =
+            parse(br#"<pfsense><nat><outbound><rule><interface>wan</interface></rule></outbound></nat></pfsense>"#)
+                .expect("source parse");
+        let target =
+            parse(br#"<opnsense><nat><outbound><mode>hybrid</mode></outbound></nat></opnsense>"#)
+                .expect("target parse");
+        let mut out = parse(
+            br#"<opnsense><nat><outbound><rule><interface>wan</interface></rule></outbound></nat></opnsense>"#,
+        )
+        .expect("out parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &target, &mut diagnostics);
+
+        assert_eq!(
+            out.get_text(&["nat", "outbound", "mode"]),
+            Some("automatic")
+        );
+        assert_eq!(diagnostics.count(Severity::Warning), 1);
+    }
+}
@@ -0,0 +1,235 @@
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+
+/// `<ntpd>` fields both platforms understand with the same meaning.
+///
+/// Covers interface bindings (comma-separated logical names, rewritten later
+/// by the logical interface mapping), orphan stratum, server flags, logging,
+/// global access restrictions, and the GPS/PPS reference clocks.
+const SHARED_FIELDS: &[&str] = &[
+    "interface",
+    "orphan",
+    "prefer",
+    "noselect",
+    "logpeer",
+    "logsys",
+    "clockstats",
+    "loopstats",
+    "peerstats",
+    "statsgraph",
+    "leapsec",
+    "kod",
+    "nomodify",
+    "noquery",
+    "noserve",
+    "nopeer",
+    "notrap",
+    "gps",
+    "pps",
+];
+
+/// Marker OPNsense uses to decide whether a time server is a pool.
+const POOL_MARKER: &str = "pool.ntp.org";
+
+/// Convert pfSense NTP server settings to OPNsense.
+///
+/// - Shared fields are copied as-is.
+/// - pfSense marks pools explicitly (`<ispool>`); OPNsense treats any host
+///   containing `pool.ntp.org` as a pool, so other pool hosts become plain
+///   servers and are reported.
+/// - pfSense uses `iburst` for every server; OPNsense needs it listed per
+///   server in `<iburst>`.
+/// - Per-network access restrictions (`<restrictions>`) have no OPNsense
+///   equivalent and are dropped with a warning.
+/// - A disabled pfSense NTP service is reported, since OPNsense has no
+///   matching switch.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(src) = source.get_child("ntpd") else {
+        return;
+    };
+    let mut ntpd = copy_shared(src);
+
+    for host in tokens(src.get_text(&["ispool"])) {
+        if !host.contains(POOL_MARKER) {
+            diagnostics.warn(
+                "ntpd",
+                format!("{host} is a pool on pfSense but OPNsense will use it as a single server"),
+            );
+        }
+    }
+    let servers = tokens(source.get_text(&["system", "timeservers"]));
+    if !servers.is_empty() {
+        push_text(&mut ntpd, "iburst", &servers.join(" "));
+    }
+
+    let acl_rows = src
+        .get_child("restrictions")
+        .map_or(0, |r| r.get_children("row").len());
+    if acl_rows > 0 {
+        diagnostics.warn(
+            "ntpd",
+            format!(
+                "{acl_rows} per-network NTP access restriction(s) have no OPNsense equivalent; dropped"
+            ),
+        );
+    }
+    if src.get_text(&["enable"]).map(str::trim) == Some("disabled") {
+        diagnostics.warn(
+            "ntpd",
+            "NTP server is disabled on pfSense but OPNsense always runs ntpd; review interface bindings",
+        );
+    }
+    warn_dropped(src, &["enable", "ispool", "restrictions"], diagnostics);
+
+    replace_ntpd(out, ntpd);
+}
+
+/// Convert OPNsense NTP server settings to pfSense.
+///
+/// Shared fields are copied as-is and `<ispool>` is derived from the
+/// `pool.ntp.org` time servers. `<iburst>` is dropped because pfSense always
+/// uses iburst.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(src) = source.get_child("ntpd") else {
+        return;
+    };
+    let mut ntpd = copy_shared(src);
+
+    let pools: Vec<_> = tokens(source.get_text(&["system", "timeservers"]))
+        .into_iter()
+        .filter(|host| host.contains(POOL_MARKER))
+        .collect();
+    if !pools.is_empty() {
+        push_text(&mut ntpd, "ispool", &pools.join(" "));
+    }
+    warn_dropped(src, &["iburst"], diagnostics);
+
+    replace_ntpd(out, ntpd);
+}
+
+/// Copy the shared fields of a source `<ntpd>` into a new node.
+fn copy_shared(src: &XmlNode) -> XmlNode {
+    let mut ntpd = XmlNode::new("ntpd");
+    ntpd.children = src
+        .children
+        .iter()
+        .filter(|c| SHARED_FIELDS.contains(&c.tag.as_str()))
+        .cloned()
+        .collect();
+    ntpd
+}
+
+/// Report non-empty source fields that were neither shared nor handled.
+fn warn_dropped(src: &XmlNode, handled: &[&str], diagnostics: &mut Diagnostics) {
+    let dropped: Vec<_> = src
+        .children
+        .iter()
+        .filter(|c| !SHARED_FIELDS.contains(&c.tag.as_str()) && !handled.contains(&c.tag.as_str()))
+        .filter(|c| {
+            !c.children.is_empty() || c.text.as_deref().is_some_and(|t| !t.trim().is_empty())
+        })
+        .map(|c| c.tag.as_str())
+        .collect();
+    if !dropped.is_empty() {
+        diagnostics.warn(
+            "ntpd",
+            format!(
+                "NTP settings without a target equivalent dropped: {}",
+                dropped.join(", ")
+            ),
+        );
+    }
+}
+
+/// Replace the output `<ntpd>` in place, or append it if missing.
+fn replace_ntpd(out: &mut XmlNode, ntpd: XmlNode) {
+    if let Some(existing) = out.children.iter_mut().find(|c| c.tag == "ntpd") {
+        *existing = ntpd;
+    } else {
+        out.children.push(ntpd);
+    }
+}
+
+fn push_text(node: &mut XmlNode, tag: &str, value: &str) {
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+/// Split a space-separated host list.
+fn tokens(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or("")
+        .split_whitespace()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn converts_pfsense_ntpd_to_opnsense() {
+        let source = parse(
+            br#"<pfsense><system><timeservers>0.pfsense.pool.ntp.org time.example.com</timeservers></system><ntpd><enable>enabled</enable><interface>lan,opt1</interface><orphan>12</orphan><ispool>0.pfsense.pool.ntp.org time.example.com</ispool><kod>on</kod><gps><type>Generic</type><port>cuau0</port></gps><restrictions><row><acl_network>10.0.0.0</acl_network><mask>8</mask></row></restrictions></ntpd></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline =
+            parse(br#"<opnsense><ntpd><prefer>0.opnsense.pool.ntp.org</prefer></ntpd></opnsense>"#)
+                .expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let ntpd = out.get_child("ntpd").expect("ntpd");
+        assert_eq!(ntpd.get_text(&["interface"]), Some("lan,opt1"));
+        assert_eq!(ntpd.get_text(&["orphan"]), Some("12"));
+        assert_eq!(ntpd.get_text(&["gps", "port"]), Some("cuau0"));
+        assert_eq!(
+            ntpd.get_text(&["iburst"]),
+            Some("0.pfsense.pool.ntp.org time.example.com")
+        );
+        assert!(ntpd.get_child("prefer").is_none());
+        assert!(ntpd.get_child("restrictions").is_none());
+
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("time.example.com is a pool"));
+        assert!(messages[1].starts_with("1 per-network"));
+    }
+
+    #[test]
+    fn derives_pfsense_pools_from_opnsense_timeservers() {
+        let source = parse(
+            br#"<opnsense><system><timeservers>0.opnsense.pool.ntp.org ntp.example.com</timeservers></system><ntpd><interface>lan</interface><iburst>ntp.example.com</iburst><pps><port>cuau1</port></pps></ntpd></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let ntpd = out.get_child("ntpd").expect("ntpd");
+        assert_eq!(ntpd.get_text(&["ispool"]), Some("0.opnsense.pool.ntp.org"));
+        assert_eq!(ntpd.get_text(&["pps", "port"]), Some("cuau1"));
+        assert!(ntpd.get_child("iburst").is_none());
+        assert!(diagnostics.is_empty());
+    }
+}
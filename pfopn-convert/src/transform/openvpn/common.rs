@@ -165,33 +165,6 @@ pub(super) fn source_assigned_ovpns_units(source: &XmlNode) -> Vec<String> {
     out
 }
 
-/// Generate a deterministic UUID for an OpenVPN instance.
-///
-/// Creates stable UUIDs based on the pfSense `<vpnid>` field so that the same
-/// pfSense configuration always produces the same OPNsense UUIDs. This ensures:
-/// - Idempotent conversions (converting twice produces identical output)
-/// - Diff stability (unchanged instances keep the same UUIDs)
-///
-/// The UUID format is v4-like (with version bits set appropriately) and incorporates
-/// the numeric portion of the vpnid. Falls back to using the index if vpnid has no digits.
-///
-/// # Arguments
-///
-/// * `vpnid` - The pfSense vpnid (e.g., "1", "2", "10")
-/// * `index` - Fallback index if vpnid parsing fails
-///
-/// # Returns
-///
-/// A UUID string like "00000000-0000-4000-8000-000000000001"
-pub(super) fn synthetic_uuid_for_id(vpnid: &str, index: usize) -> String {
-    let digits: String = vpnid.chars().filter(|c| c.is_ascii_digit()).collect();
-    let id_num = digits.parse::<u64>().unwrap_or((index + 1) as u64);
-    format!(
-        "00000000-0000-4000-8000-{id_num:012x}",
-        id_num = id_num % 0x1_0000_0000_0000
-    )
-}
-
 /// Create and append a text-only child element to a parent node.
 ///
 /// Helper to reduce boilerplate when building XML structures.
@@ -234,25 +207,3 @@ pub(super) fn text_or<'a>(node: &'a XmlNode, path: &[&str], fallback: &'a str) -
         .unwrap_or(fallback)
         .to_string()
 }
-
-/// Check if a string value represents a boolean true.
-///
-/// Recognizes multiple boolean-like strings: "1", "yes", "true", "enabled", "on".
-/// Case-insensitive.
-pub(super) fn is_truthy(value: String) -> bool {
-    matches!(
-        value.trim().to_ascii_lowercase().as_str(),
-        "1" | "yes" | "true" | "enabled" | "on"
-    )
-}
-
-/// Convert a boolean value to "1" or "0" string representation.
-///
-/// Used when setting boolean fields in XML config elements.
-pub(super) fn bool_to_01(v: bool) -> &'static str {
-    if v {
-        "1"
-    } else {
-        "0"
-    }
-}
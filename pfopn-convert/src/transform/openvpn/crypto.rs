@@ -0,0 +1,159 @@
+use base64::Engine;
+use xml_diff_core::XmlNode;
+
+use super::common::{push_text_child, set_or_insert_text_child, text_or};
+use crate::transform::booleans::{is_truthy, numeric};
+use crate::uuid_registry::UuidRegistry;
+
+/// Scope for OPNsense `<StaticKey>` UUIDs, keyed by the instance's vpnid.
+const STATIC_KEY_SCOPE: &str = "openvpn-statickey";
+
+/// OpenVPN directive a pfSense `<compression>` value turns on, if it
+/// compresses anything.
+///
+/// `lz4`, `lz4-v2`, and `lzo` use `compress <algo>`; `yes`, `no`, `adaptive`,
+/// and `noadapt` are the legacy `comp-lzo` forms. `stub`, `stub-v2`, and
+/// `none` only frame packets and stay accepted by modern OpenVPN.
+pub(crate) fn compression_directive(value: &str) -> Option<&'static str> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "lz4" | "lz4-v2" | "lzo" => Some("compress"),
+        "yes" | "no" | "adaptive" | "noadapt" => Some("comp-lzo"),
+        _ => None,
+    }
+}
+
+/// Set cipher, digest, compression, and TLS key fields on an OPNsense
+/// instance from a pfSense server.
+///
+/// - `data_ciphers` (or pre-2.5 `ncp-ciphers`) → `data-ciphers`
+/// - `data_ciphers_fallback` (or pre-2.5 `crypto`) → `data-ciphers-fallback`
+/// - `digest` → `auth`
+/// - compression → `compress_migrate`, which accepts compressing clients
+///   without compressing
+/// - `tls` / `tls_type` → a `<StaticKey>` pushed to `static_keys` and
+///   referenced by `tls_key`
+///
+/// Every field is written, so values from the target's template instance
+/// never leak into a converted server.
+pub(super) fn pf_to_opn(
+    server: &XmlNode,
+    instance: &mut XmlNode,
+    vpnid: &str,
+    registry: &mut UuidRegistry,
+    static_keys: &mut Vec<XmlNode>,
+) {
+    let ciphers = [
+        text_or(server, &["data_ciphers"], ""),
+        text_or(server, &["ncp-ciphers"], ""),
+    ]
+    .into_iter()
+    .find(|v| !v.is_empty())
+    .unwrap_or_default();
+    let fallback = [
+        text_or(server, &["data_ciphers_fallback"], ""),
+        text_or(server, &["crypto"], ""),
+    ]
+    .into_iter()
+    .find(|v| !v.is_empty())
+    .unwrap_or_default();
+    set_or_insert_text_child(instance, "data-ciphers", ciphers);
+    set_or_insert_text_child(instance, "data-ciphers-fallback", fallback);
+    set_or_insert_text_child(instance, "auth", text_or(server, &["digest"], ""));
+    let compresses = compression_directive(&text_or(server, &["compression"], "")).is_some();
+    set_or_insert_text_child(instance, "compress_migrate", numeric(compresses));
+
+    let key = static_key_text(&text_or(server, &["tls"], ""));
+    if key.is_empty() {
+        set_or_insert_text_child(instance, "tls_key", "");
+        return;
+    }
+    let uuid = registry.uuid_for(STATIC_KEY_SCOPE, vpnid);
+    let mode = match text_or(server, &["tls_type"], "auth").as_str() {
+        "crypt" => "crypt",
+        _ => "auth",
+    };
+    let mut static_key = XmlNode::new("StaticKey");
+    static_key
+        .attributes
+        .insert("uuid".to_string(), uuid.clone());
+    push_text_child(&mut static_key, "mode", mode);
+    push_text_child(&mut static_key, "key", key);
+    push_text_child(
+        &mut static_key,
+        "description",
+        text_or(server, &["description"], ""),
+    );
+    static_keys.push(static_key);
+    set_or_insert_text_child(instance, "tls_key", uuid);
+}
+
+/// Add cipher, digest, compression, and TLS key fields to a pfSense server
+/// from an OPNsense instance; the inverse of [`pf_to_opn`].
+///
+/// `compress_migrate` becomes `allow_compression=asym` (accept compressed
+/// packets, never send them), and the referenced `<StaticKey>` is looked up
+/// in `source` and stored base64-encoded as pfSense expects.
+pub(super) fn opn_to_pf(instance: &XmlNode, server: &mut XmlNode, source: &XmlNode) {
+    let ciphers = text_or(instance, &["data-ciphers"], "");
+    if !ciphers.is_empty() {
+        push_text_child(server, "ncp_enable", "enabled");
+        push_text_child(server, "data_ciphers", ciphers);
+    }
+    let fallback = text_or(instance, &["data-ciphers-fallback"], "");
+    if !fallback.is_empty() {
+        push_text_child(server, "data_ciphers_fallback", fallback);
+    }
+    let digest = text_or(instance, &["auth"], "");
+    if !digest.is_empty() {
+        push_text_child(server, "digest", digest);
+    }
+    if is_truthy(&text_or(instance, &["compress_migrate"], "0")) {
+        push_text_child(server, "allow_compression", "asym");
+    }
+
+    let key_ref = text_or(instance, &["tls_key"], "");
+    let Some(static_key) = source
+        .get_child("OPNsense")
+        .and_then(|n| n.get_child("OpenVPN"))
+        .and_then(|n| n.get_child("StaticKeys"))
+        .and_then(|keys| {
+            keys.get_children("StaticKey")
+                .into_iter()
+                .find(|k| !key_ref.is_empty() && k.attributes.get("uuid") == Some(&key_ref))
+        })
+    else {
+        return;
+    };
+    let key = text_or(static_key, &["key"], "");
+    if key.is_empty() {
+        return;
+    }
+    push_text_child(
+        server,
+        "tls",
+        base64::engine::general_purpose::STANDARD.encode(format!("{key}\n")),
+    );
+    push_text_child(
+        server,
+        "tls_type",
+        match text_or(static_key, &["mode"], "auth").as_str() {
+            "crypt" => "crypt",
+            _ => "auth",
+        },
+    );
+}
+
+/// Static key text from a pfSense `<tls>` value, which is base64 of the key
+/// file; values that are already plain text pass through.
+fn static_key_text(raw: &str) -> String {
+    let compact: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return String::new();
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(&compact)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .filter(|text| text.contains("OpenVPN Static key"))
+        .unwrap_or_else(|| raw.trim().to_string())
+}
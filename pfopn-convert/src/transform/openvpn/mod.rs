@@ -22,6 +22,20 @@
 //! - More structured with nested containers
 //! - Interface references also use "ovpnsN" naming
 //!
+//! ## Cipher, Compression, and TLS Key Mapping
+//!
+//! - pfSense `data_ciphers` / `data_ciphers_fallback` (or the pre-2.5
+//!   `ncp-ciphers` / `crypto`) ↔ OPNsense `data-ciphers` /
+//!   `data-ciphers-fallback`; `digest` ↔ `auth`
+//! - pfSense compression is never carried over as compression: OPNsense
+//!   gets `compress_migrate`, which accepts compressing peers without
+//!   compressing, and that maps back to `allow_compression=asym`
+//! - pfSense's inline base64 `tls` key with `tls_type` (auth/crypt) ↔ an
+//!   OPNsense `<StaticKeys><StaticKey>` referenced by the instance's `tls_key`
+//!
+//! `verify` checks the result against the target version's OpenVPN; see
+//! `verify_openvpn`.
+//!
 //! ## Round-Trip Preservation
 //!
 //! To support lossless pfSense → OPNsense → pfSense conversions:
@@ -39,13 +53,18 @@
 
 use xml_diff_core::XmlNode;
 
+use crate::uuid_registry::UuidRegistry;
+
 mod common;
+mod crypto;
 mod opn_to_pf;
 mod pf_to_opn;
 
 #[cfg(test)]
 mod tests;
 
+pub(crate) use crypto::compression_directive;
+
 /// Convert OpenVPN configuration to OPNsense format.
 ///
 /// Handles two input cases:
@@ -70,16 +89,29 @@ mod tests;
 pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, target: &XmlNode) {
     // Get OPNsense instances — either from source if already OPNsense format,
     // or by mapping pfSense servers/clients to instances
-    let instances = common::source_opnsense_instances(source)
-        .unwrap_or_else(|| pf_to_opn::map_pfsense_servers_to_opnsense_instances(source, target));
+    let mut registry = UuidRegistry::from_config(out);
+    let mut static_keys = Vec::new();
+    let instances = common::source_opnsense_instances(source).unwrap_or_else(|| {
+        pf_to_opn::map_pfsense_servers_to_opnsense_instances(
+            source,
+            target,
+            &mut registry,
+            &mut static_keys,
+        )
+    });
     if instances.children.is_empty() {
         return;
     }
 
-    // Insert instances into the OPNsense nested structure
+    // Insert instances (and the TLS keys they reference) into the OPNsense nested structure
     let opn = common::ensure_child_mut(out, "OPNsense");
     let openvpn = common::ensure_child_mut(opn, "OpenVPN");
     common::upsert_child(openvpn, instances);
+    if !static_keys.is_empty() {
+        let mut keys = XmlNode::new("StaticKeys");
+        keys.children = static_keys;
+        common::upsert_child(openvpn, keys);
+    }
 
     // Handle top-level <openvpn> for pfSense compatibility
     if let Some(source_pf_openvpn) = common::source_pfsense_servers(source) {
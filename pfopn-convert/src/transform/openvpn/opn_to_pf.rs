@@ -1,6 +1,8 @@
 use xml_diff_core::XmlNode;
 
-use super::common::{is_truthy, push_text_child, text_or};
+use super::common::{push_text_child, text_or};
+use super::crypto;
+use crate::transform::booleans::is_truthy;
 
 /// Map OPNsense OpenVPN instances to pfSense servers and clients.
 ///
@@ -76,7 +78,7 @@ pub(super) fn map_opnsense_instances_to_pfsense(source: &XmlNode) -> XmlNode {
         }
         push_text_child(&mut server, "vpnid", text_or(instance, &["vpnid"], "1"));
         // OPNsense uses <enabled>1</enabled>, pfSense uses <disable/> (empty element = disabled)
-        if !is_truthy(text_or(instance, &["enabled"], "1")) {
+        if !is_truthy(&text_or(instance, &["enabled"], "1")) {
             server.children.push(XmlNode::new("disable"));
         }
         push_text_child(&mut server, "mode", "server_tls");
@@ -119,6 +121,7 @@ pub(super) fn map_opnsense_instances_to_pfsense(source: &XmlNode) -> XmlNode {
             "topology",
             text_or(instance, &["topology"], "subnet"),
         );
+        crypto::opn_to_pf(instance, &mut server, source);
 
         // DNS domain and servers: OPNsense uses comma-separated, pfSense uses numbered fields
         if let Some(domain) = instance
@@ -161,10 +164,10 @@ pub(super) fn map_opnsense_instances_to_pfsense(source: &XmlNode) -> XmlNode {
         {
             push_text_child(&mut server, "username", username);
         }
-        if is_truthy(text_or(instance, &["username_as_common_name"], "0")) {
+        if is_truthy(&text_or(instance, &["username_as_common_name"], "0")) {
             push_text_child(&mut server, "username_as_common_name", "enabled");
         }
-        if is_truthy(text_or(instance, &["strictusercn"], "0")) {
+        if is_truthy(&text_or(instance, &["strictusercn"], "0")) {
             push_text_child(&mut server, "strictusercn", "1");
         }
 
@@ -174,7 +177,7 @@ pub(super) fn map_opnsense_instances_to_pfsense(source: &XmlNode) -> XmlNode {
             push_text_child(&mut server, "push_blockoutsidedns", "yes");
         }
         if flag_present(&push_flags, "register-dns")
-            || is_truthy(text_or(instance, &["register_dns"], "0"))
+            || is_truthy(&text_or(instance, &["register_dns"], "0"))
         {
             push_text_child(&mut server, "push_register_dns", "yes");
         }
@@ -183,7 +186,7 @@ pub(super) fn map_opnsense_instances_to_pfsense(source: &XmlNode) -> XmlNode {
         }
 
         // NetBIOS
-        if is_truthy(text_or(instance, &["netbios_enable"], "0")) {
+        if is_truthy(&text_or(instance, &["netbios_enable"], "0")) {
             push_text_child(&mut server, "netbios_enable", "yes");
         }
         if let Some(netbios_ntype) = instance
@@ -1,9 +1,11 @@
 use xml_diff_core::XmlNode;
 
 use super::common::{
-    bool_to_01, is_truthy, opnsense_instance_template, set_or_insert_text_child,
-    source_assigned_ovpns_units, synthetic_uuid_for_id, text_or,
+    opnsense_instance_template, set_or_insert_text_child, source_assigned_ovpns_units, text_or,
 };
+use super::crypto;
+use crate::transform::booleans::{flag_set, is_truthy, numeric};
+use crate::uuid_registry::UuidRegistry;
 
 /// Map pfSense OpenVPN servers and clients to OPNsense instances.
 ///
@@ -14,13 +16,14 @@ use super::common::{
 /// ## Mapping Strategy
 ///
 /// - Uses the target's existing instance as a template to ensure correct structure
-/// - Assigns deterministic UUIDs based on vpnid for idempotent conversions
+/// - Issues deterministic UUIDs keyed by vpnid for idempotent conversions
 /// - Maps interface assignments when possible (matching count of servers to interfaces)
 /// - Converts field names and structures between formats:
 ///   - `<disable>` (pfSense) → `<enabled>` (OPNsense, inverted logic)
 ///   - `<dev_mode>` → `<dev_type>`
 ///   - DNS/NTP servers: separate fields → comma-separated lists
 ///   - Push flags: separate boolean fields → comma-separated flag list
+///   - Ciphers, digest, compression, and TLS keys (see `crypto::pf_to_opn`)
 ///
 /// ## Round-Trip Preservation
 ///
@@ -32,6 +35,8 @@ use super::common::{
 ///
 /// * `source` - The pfSense configuration containing `<openvpn>` with servers/clients
 /// * `target` - The OPNsense target template (used for default instance structure)
+/// * `registry` - UUIDs issued for this conversion
+/// * `static_keys` - Receives the `<StaticKey>` for each server's TLS key
 ///
 /// # Returns
 ///
@@ -39,6 +44,8 @@ use super::common::{
 pub(super) fn map_pfsense_servers_to_opnsense_instances(
     source: &XmlNode,
     target: &XmlNode,
+    registry: &mut UuidRegistry,
+    static_keys: &mut Vec<XmlNode>,
 ) -> XmlNode {
     let mut instances = XmlNode::new("Instances");
     let Some(openvpn) = source.get_child("openvpn") else {
@@ -75,20 +82,19 @@ pub(super) fn map_pfsense_servers_to_opnsense_instances(
 
         // Preserve UUIDs from round-trip conversions, or generate deterministic ones
         let uuid = text_or(server, &["opnsense_instance_uuid"], "");
-        instance.attributes.insert(
-            "uuid".to_string(),
-            if uuid.is_empty() {
-                synthetic_uuid_for_id(vpnid.as_str(), instances.children.len())
-            } else {
-                uuid
-            },
-        );
-        set_or_insert_text_child(&mut instance, "vpnid", vpnid);
+        let uuid = if uuid.is_empty() {
+            registry.uuid_for("openvpn-instance", &vpnid)
+        } else {
+            registry.register("openvpn-instance", &vpnid, &uuid);
+            uuid
+        };
+        instance.attributes.insert("uuid".to_string(), uuid);
+        set_or_insert_text_child(&mut instance, "vpnid", vpnid.clone());
         // pfSense uses <disable> (presence = disabled), OPNsense uses <enabled> (1 = enabled)
         set_or_insert_text_child(
             &mut instance,
             "enabled",
-            bool_to_01(server.get_text(&["disable"]).is_none()),
+            numeric(!flag_set(server, "disable")),
         );
         set_or_insert_text_child(
             &mut instance,
@@ -122,8 +128,9 @@ pub(super) fn map_pfsense_servers_to_opnsense_instances(
         set_or_insert_text_child(
             &mut instance,
             "topology",
-            text_or(server, &["topology"], "subnet"),
+            text_or(server, &["topology"], "subnet").to_ascii_lowercase(),
         );
+        crypto::pf_to_opn(server, &mut instance, &vpnid, registry, static_keys);
         set_or_insert_text_child(
             &mut instance,
             "description",
@@ -160,9 +167,9 @@ pub(super) fn map_pfsense_servers_to_opnsense_instances(
         append_push_flag(
             &mut push_flags,
             "block-outside-dns",
-            is_truthy(text_or(server, &["push_blockoutsidedns"], "0")),
+            is_truthy(&text_or(server, &["push_blockoutsidedns"], "0")),
         );
-        let wants_register_dns = is_truthy(text_or(server, &["push_register_dns"], "0"));
+        let wants_register_dns = is_truthy(&text_or(server, &["push_register_dns"], "0"));
         append_push_flag(&mut push_flags, "register-dns", wants_register_dns);
         if wants_register_dns {
             set_or_insert_text_child(&mut instance, "register_dns", "1");
@@ -181,15 +188,15 @@ pub(super) fn map_pfsense_servers_to_opnsense_instances(
         {
             set_or_insert_text_child(&mut instance, "username", username);
         }
-        if is_truthy(text_or(server, &["username_as_common_name"], "0")) {
+        if is_truthy(&text_or(server, &["username_as_common_name"], "0")) {
             set_or_insert_text_child(&mut instance, "username_as_common_name", "1");
         }
-        if is_truthy(text_or(server, &["strictusercn"], "0")) {
+        if is_truthy(&text_or(server, &["strictusercn"], "0")) {
             set_or_insert_text_child(&mut instance, "strictusercn", "1");
         }
 
         // NetBIOS
-        if is_truthy(text_or(server, &["netbios_enable"], "0")) {
+        if is_truthy(&text_or(server, &["netbios_enable"], "0")) {
             set_or_insert_text_child(&mut instance, "netbios_enable", "1");
         }
         if let Some(netbios_ntype) = server
@@ -258,3 +258,79 @@ fn does_not_preserve_top_level_openvpn_servers_for_opnsense_origin() {
     to_opnsense(&mut out, &source, &target);
     assert_eq!(out.get_child("openvpn").map(|n| n.children.len()), Some(0));
 }
+
+#[test]
+fn maps_ciphers_compression_and_tls_key_to_opnsense() {
+    let source = parse(
+        br#"<pfsense><openvpn><openvpn-server><vpnid>3</vpnid><description>Remote</description><ncp-ciphers>AES-256-GCM:AES-128-GCM</ncp-ciphers><crypto>AES-256-CBC</crypto><digest>SHA256</digest><compression>lz4-v2</compression><topology>SUBNET</topology><tls>LS0tLS1CRUdJTiBPcGVuVlBOIFN0YXRpYyBrZXkgVjEtLS0tLQphYmNkCi0tLS0tRU5EIE9wZW5WUE4gU3RhdGljIGtleSBWMS0tLS0tCg==</tls><tls_type>crypt</tls_type></openvpn-server></openvpn></pfsense>"#,
+    )
+    .expect("source parse");
+    let target = parse(
+        br#"<opnsense><OPNsense><OpenVPN><Instances><Instance><data-ciphers>CHACHA20-POLY1305</data-ciphers><tls_key>stale</tls_key></Instance></Instances></OpenVPN></OPNsense></opnsense>"#,
+    )
+    .expect("target parse");
+    let mut out = target.clone();
+
+    to_opnsense(&mut out, &source, &target);
+    let openvpn = out
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("OpenVPN"))
+        .expect("OpenVPN");
+    let inst = openvpn
+        .get_child("Instances")
+        .and_then(|i| i.get_child("Instance"))
+        .expect("instance");
+    assert_eq!(
+        inst.get_text(&["data-ciphers"]),
+        Some("AES-256-GCM:AES-128-GCM")
+    );
+    assert_eq!(
+        inst.get_text(&["data-ciphers-fallback"]),
+        Some("AES-256-CBC")
+    );
+    assert_eq!(inst.get_text(&["auth"]), Some("SHA256"));
+    assert_eq!(inst.get_text(&["compress_migrate"]), Some("1"));
+    assert_eq!(inst.get_text(&["topology"]), Some("subnet"));
+
+    let key = openvpn
+        .get_child("StaticKeys")
+        .and_then(|k| k.get_child("StaticKey"))
+        .expect("static key");
+    assert_eq!(
+        inst.get_text(&["tls_key"]),
+        key.attributes.get("uuid").map(String::as_str)
+    );
+    assert_eq!(key.get_text(&["mode"]), Some("crypt"));
+    assert!(key
+        .get_text(&["key"])
+        .is_some_and(|k| k.starts_with("-----BEGIN OpenVPN Static key V1-----")));
+}
+
+#[test]
+fn maps_opnsense_ciphers_and_static_key_to_pfsense() {
+    let source = parse(
+        br#"<opnsense><OPNsense><OpenVPN><Instances><Instance><vpnid>1</vpnid><data-ciphers>AES-256-GCM</data-ciphers><data-ciphers-fallback>AES-256-CBC</data-ciphers-fallback><auth>SHA512</auth><compress_migrate>1</compress_migrate><tls_key>k1</tls_key></Instance></Instances><StaticKeys><StaticKey uuid="k1"><mode>auth</mode><key>-----BEGIN OpenVPN Static key V1-----
+abcd
+-----END OpenVPN Static key V1-----
+</key></StaticKey></StaticKeys></OpenVPN></OPNsense></opnsense>"#,
+    )
+    .expect("source parse");
+    let target = parse(br#"<pfsense><openvpn/></pfsense>"#).expect("target parse");
+    let mut out = target.clone();
+
+    to_pfsense(&mut out, &source, &target);
+    let server = out
+        .get_child("openvpn")
+        .and_then(|o| o.get_child("openvpn-server"))
+        .expect("server");
+    assert_eq!(server.get_text(&["ncp_enable"]), Some("enabled"));
+    assert_eq!(server.get_text(&["data_ciphers"]), Some("AES-256-GCM"));
+    assert_eq!(
+        server.get_text(&["data_ciphers_fallback"]),
+        Some("AES-256-CBC")
+    );
+    assert_eq!(server.get_text(&["digest"]), Some("SHA512"));
+    assert_eq!(server.get_text(&["allow_compression"]), Some("asym"));
+    assert_eq!(server.get_text(&["tls_type"]), Some("auth"));
+    assert_eq!(server.get_text(&["tls"]), Some("LS0tLS1CRUdJTiBPcGVuVlBOIFN0YXRpYyBrZXkgVjEtLS0tLQphYmNkCi0tLS0tRU5EIE9wZW5WUE4gU3RhdGljIGtleSBWMS0tLS0tCg=="));
+}
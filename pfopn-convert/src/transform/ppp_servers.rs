@@ -0,0 +1,362 @@
+use std::net::Ipv4Addr;
+
+use base64::Engine;
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::plugin_detect::collect_opnsense_declared_plugins;
+
+/// Convert PPPoE server (`<pppoes>`) and L2TP server (`<l2tp>`) settings for
+/// OPNsense output.
+///
+/// Both platforms inherit these sections from the same legacy code, so the
+/// server address, remote pool, DNS, and RADIUS settings carry over as-is.
+/// The one structural difference is the PPPoE user list: pfSense packs it
+/// into a single `<username>` string of `name:base64(password):ip` entries,
+/// while OPNsense stores one `<user>` element per account with a plain
+/// password. OPNsense only serves these protocols through the `os-pppoe` and
+/// `os-l2tp` plugins; when the target baseline doesn't declare them, the
+/// section is still written but a warning says what to install.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    remove_missing_sections(out, source);
+    let plugins = collect_opnsense_declared_plugins(destination_baseline);
+    let has_plugin = |name: &str| plugins.iter().any(|p| p.eq_ignore_ascii_case(name));
+
+    if let Some(pppoes) = source.get_child("pppoes") {
+        let mut converted = pppoes.clone();
+        for server in converted.children.iter_mut().filter(|c| c.tag == "pppoe") {
+            let label = server_label(server);
+            let users = server
+                .get_text(&["username"])
+                .map(|raw| parse_pfsense_users(raw, &label, diagnostics))
+                .unwrap_or_default();
+            server.children.retain(|c| c.tag != "username");
+            server.children.extend(users);
+            check_server(server, "n_pppoe_units", &label, diagnostics);
+        }
+        if any_enabled(&converted, "pppoe") && !has_plugin("os-pppoe") {
+            diagnostics.warn(
+                "ppp_servers",
+                "PPPoE server needs the os-pppoe plugin on OPNsense; install it on the target before restoring",
+            );
+        }
+        upsert_top_child(out, converted);
+    }
+
+    if let Some(l2tp) = source.get_child("l2tp") {
+        let converted = l2tp.clone();
+        check_server(&converted, "n_l2tp_units", "L2TP server", diagnostics);
+        if is_enabled(&converted) && !has_plugin("os-l2tp") {
+            diagnostics.warn(
+                "ppp_servers",
+                "L2TP server needs the os-l2tp plugin on OPNsense; install it on the target before restoring",
+            );
+        }
+        upsert_top_child(out, converted);
+    }
+}
+
+/// Convert PPPoE server and L2TP server settings for pfSense output.
+///
+/// pfSense ships both servers natively, so the only rewrite is packing
+/// OPNsense's `<user>` elements back into pfSense's `<username>` string.
+/// See [`to_opnsense`] for the shared layout.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let _ = destination_baseline;
+    remove_missing_sections(out, source);
+    if let Some(pppoes) = source.get_child("pppoes") {
+        let mut converted = pppoes.clone();
+        for server in converted.children.iter_mut().filter(|c| c.tag == "pppoe") {
+            let label = server_label(server);
+            let packed = pack_pfsense_users(server);
+            server.children.retain(|c| c.tag != "user");
+            if !packed.is_empty() {
+                set_text(server, "username", &packed);
+            }
+            check_server(server, "n_pppoe_units", &label, diagnostics);
+        }
+        upsert_top_child(out, converted);
+    }
+
+    if let Some(l2tp) = source.get_child("l2tp") {
+        check_server(l2tp, "n_l2tp_units", "L2TP server", diagnostics);
+        upsert_top_child(out, l2tp.clone());
+    }
+}
+
+/// Split pfSense's `name:base64(password):ip` list into `<user>` elements.
+fn parse_pfsense_users(raw: &str, label: &str, diagnostics: &mut Diagnostics) -> Vec<XmlNode> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let mut out = Vec::new();
+    for entry in raw.split_whitespace() {
+        let mut parts = entry.splitn(3, ':');
+        let name = parts.next().unwrap_or_default();
+        let encoded = parts.next().unwrap_or_default();
+        let ip = parts.next().unwrap_or_default();
+        let password = match engine
+            .decode(encoded)
+            .ok()
+            .and_then(|b| String::from_utf8(b).ok())
+        {
+            Some(password) => password,
+            None => {
+                diagnostics.warn(
+                    "ppp_servers",
+                    format!("{label}: password for user '{name}' could not be decoded; reset it on the target"),
+                );
+                String::new()
+            }
+        };
+        let mut user = XmlNode::new("user");
+        set_text(&mut user, "name", name);
+        set_text(&mut user, "password", &password);
+        set_text(&mut user, "ip", ip);
+        out.push(user);
+    }
+    out
+}
+
+/// Pack OPNsense `<user>` elements into pfSense's `<username>` string.
+fn pack_pfsense_users(server: &XmlNode) -> String {
+    let engine = base64::engine::general_purpose::STANDARD;
+    server
+        .get_children("user")
+        .into_iter()
+        .filter_map(|user| {
+            let name = text_of(user, "name")?;
+            let password = user.get_text(&["password"]).unwrap_or_default();
+            let ip = text_of(user, "ip").unwrap_or_default();
+            Some(format!("{name}:{}:{ip}", engine.encode(password)))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Check the remote address pool of an enabled server and report RADIUS use.
+///
+/// The pool starts at `<remoteip>` and spans `units` addresses; it must fit
+/// in IPv4 space and must not contain the server's own `<localip>`.
+fn check_server(server: &XmlNode, units_tag: &str, label: &str, diagnostics: &mut Diagnostics) {
+    if !is_enabled(server) {
+        return;
+    }
+    let start = text_of(server, "remoteip").and_then(|v| v.parse::<Ipv4Addr>().ok());
+    let units = text_of(server, units_tag).and_then(|v| v.parse::<u32>().ok());
+    match (start, units) {
+        (Some(start), Some(units)) if units > 0 => {
+            let Some(end) = u32::from(start).checked_add(units - 1).map(Ipv4Addr::from) else {
+                diagnostics.warn(
+                    "ppp_servers",
+                    format!("{label}: remote pool starting at {start} overflows IPv4 space"),
+                );
+                return;
+            };
+            diagnostics.info(
+                "ppp_servers",
+                format!("{label}: remote pool {start}-{end} ({units} address(es))"),
+            );
+            let local = text_of(server, "localip").and_then(|v| v.parse::<Ipv4Addr>().ok());
+            if local.is_some_and(|l| l >= start && l <= end) {
+                diagnostics.warn(
+                    "ppp_servers",
+                    format!("{label}: server address is inside the remote pool {start}-{end}"),
+                );
+            }
+        }
+        _ => diagnostics.warn(
+            "ppp_servers",
+            format!("{label}: remote address pool is missing or invalid; set it on the target"),
+        ),
+    }
+    if server
+        .get_child("radius")
+        .and_then(|r| r.get_child("server"))
+        .is_some_and(|s| !s.children.is_empty() || text_of_node(s).is_some())
+    {
+        diagnostics.warn(
+            "ppp_servers",
+            format!("{label}: RADIUS authentication carried over; confirm the RADIUS server accepts the target as a client"),
+        );
+    }
+}
+
+/// Drop baseline server sections the source doesn't configure.
+fn remove_missing_sections(out: &mut XmlNode, source: &XmlNode) {
+    for tag in ["pppoes", "l2tp"] {
+        if source.get_child(tag).is_none() {
+            out.children.retain(|c| c.tag != tag);
+        }
+    }
+}
+
+fn any_enabled(container: &XmlNode, item: &str) -> bool {
+    container.get_children(item).into_iter().any(is_enabled)
+}
+
+fn is_enabled(server: &XmlNode) -> bool {
+    text_of(server, "mode").as_deref() == Some("server")
+}
+
+fn server_label(server: &XmlNode) -> String {
+    match text_of(server, "descr").or_else(|| text_of(server, "interface")) {
+        Some(name) => format!("PPPoE server {name}"),
+        None => "PPPoE server".to_string(),
+    }
+}
+
+fn text_of_node(node: &XmlNode) -> Option<&str> {
+    node.text
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn text_of(node: &XmlNode, child: &str) -> Option<String> {
+    node.get_text(&[child])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+fn upsert_top_child(root: &mut XmlNode, node: XmlNode) {
+    if let Some(idx) = root.children.iter().position(|c| c.tag == node.tag) {
+        root.children[idx] = node;
+    } else {
+        root.children.push(node);
+    }
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::{Diagnostics, Severity};
+
+    const PF_PPPOE: &[u8] = br#"<pfsense><pppoes><pppoe><mode>server</mode><interface>lan</interface><localip>10.9.0.1</localip><remoteip>10.9.0.10</remoteip><n_pppoe_units>5</n_pppoe_units><descr>dorm</descr><username>alice:c2VjcmV0:10.9.0.11 bob:aHVudGVyMg==:</username></pppoe></pppoes></pfsense>"#;
+
+    fn warnings(diagnostics: &Diagnostics) -> Vec<String> {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .map(|d| d.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn unpacks_pppoe_users_for_opnsense() {
+        let source = parse(PF_PPPOE).expect("parse");
+        let baseline = parse(
+            br#"<opnsense><system><firmware><plugins>os-pppoe</plugins></firmware></system></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let server = out
+            .get_child("pppoes")
+            .and_then(|p| p.get_child("pppoe"))
+            .expect("pppoe");
+        assert!(server.get_child("username").is_none());
+        let users = server.get_children("user");
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].get_text(&["name"]), Some("alice"));
+        assert_eq!(users[0].get_text(&["password"]), Some("secret"));
+        assert_eq!(users[0].get_text(&["ip"]), Some("10.9.0.11"));
+        assert_eq!(users[1].get_text(&["password"]), Some("hunter2"));
+        assert!(warnings(&diagnostics).is_empty());
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message
+                == "PPPoE server dorm: remote pool 10.9.0.10-10.9.0.14 (5 address(es))"));
+    }
+
+    #[test]
+    fn warns_when_opnsense_target_lacks_plugins() {
+        let source = parse(
+            br#"<pfsense><l2tp><mode>server</mode><localip>10.8.0.1</localip><remoteip>10.8.0.1</remoteip><n_l2tp_units>4</n_l2tp_units><user><name>carol</name><password>pw</password><ip/></user></l2tp></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(b"<opnsense><system/></opnsense>").expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        assert_eq!(out.get_text(&["l2tp", "user", "name"]), Some("carol"));
+        assert_eq!(
+            warnings(&diagnostics),
+            vec![
+                "L2TP server: server address is inside the remote pool 10.8.0.1-10.8.0.4".to_string(),
+                "L2TP server needs the os-l2tp plugin on OPNsense; install it on the target before restoring".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn pppoe_users_round_trip_through_opnsense() {
+        let source = parse(PF_PPPOE).expect("parse");
+        let opn_base = parse(b"<opnsense/>").expect("parse");
+        let mut opn = opn_base.clone();
+        to_opnsense(&mut opn, &source, &opn_base, &mut Diagnostics::default());
+
+        let pf_base = parse(b"<pfsense/>").expect("parse");
+        let mut back = pf_base.clone();
+        to_pfsense(&mut back, &opn, &pf_base, &mut Diagnostics::default());
+
+        assert_eq!(
+            back.get_text(&["pppoes", "pppoe", "username"]),
+            Some("alice:c2VjcmV0:10.9.0.11 bob:aHVudGVyMg==:")
+        );
+        assert!(back
+            .get_child("pppoes")
+            .and_then(|p| p.get_child("pppoe"))
+            .is_some_and(|s| s.get_child("user").is_none()));
+    }
+
+    #[test]
+    fn reports_invalid_pool_and_undecodable_password() {
+        let source = parse(
+            br#"<pfsense><pppoes><pppoe><mode>server</mode><remoteip>bogus</remoteip><username>dave:***:</username></pppoe></pppoes></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(
+            br#"<opnsense><system><firmware><plugins>os-pppoe</plugins></firmware></system></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        assert_eq!(
+            warnings(&diagnostics),
+            vec![
+                "PPPoE server: password for user 'dave' could not be decoded; reset it on the target".to_string(),
+                "PPPoE server: remote address pool is missing or invalid; set it on the target".to_string(),
+            ]
+        );
+    }
+}
@@ -1,41 +1,205 @@
 use xml_diff_core::XmlNode;
 
+use crate::diagnostics::Diagnostics;
+
+/// Per-link `<ppp>` fields: comma-separated lists with one value per port.
+const PER_LINK_FIELDS: &[&str] = &["mtu", "mru", "mrru", "bandwidth"];
+
+/// Fields that only take effect on a multilink (MLPPP) bundle.
+const MULTILINK_FIELDS: &[&str] = &["mrru", "shortseq"];
+
+/// pfSense-only `<ppp>` fields that OPNsense never reads: persistent uptime
+/// logging and the PPPoE periodic reset schedule.
+const PFSENSE_ONLY_FIELDS: &[&str] = &[
+    "uptime",
+    "pppoe-reset-type",
+    "pppoe_resethour",
+    "pppoe_resetminute",
+    "pppoe_resetdate",
+    "pppoe_pr_type",
+    "pppoe_pr_preset_val",
+];
+
+/// How a `<ppp>` option the target ignores should be fixed up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IgnoredFix {
+    /// Remove the field.
+    Drop,
+    /// Keep only the first `n` comma-separated values.
+    Truncate(usize),
+}
+
+/// A `<ppp>` option the target platform will not apply as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoredOption {
+    /// Field tag inside `<ppp>`.
+    pub field: &'static str,
+    /// Why the target ignores it, phrased as a suggested action.
+    pub reason: String,
+    /// How conversion fixes it up.
+    pub fix: IgnoredFix,
+}
+
 /// Transfer PPP (Point-to-Point Protocol) settings to OPNsense output.
 ///
 /// Replaces the entire `<ppps>` section in the output with the one from the
-/// source config. This preserves PPPoE, PPTP, and other PPP configurations.
-pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, _target: &XmlNode) {
-    sync_ppps(out, source);
+/// source config, then drops or trims the options OPNsense would ignore
+/// (see [`ignored_options`]).
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    sync_ppps(out, source, "opnsense", diagnostics);
 }
 
 /// Transfer PPP (Point-to-Point Protocol) settings to pfSense output.
 ///
 /// Replaces the entire `<ppps>` section in the output with the one from the
-/// source config. This preserves PPPoE, PPTP, and other PPP configurations.
-pub fn to_pfsense(out: &mut XmlNode, source: &XmlNode, _target: &XmlNode) {
-    sync_ppps(out, source);
+/// source config, then drops or trims the options pfSense would ignore.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _target: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    sync_ppps(out, source, "pfsense", diagnostics);
+}
+
+/// List the options in one `<ppp>` entry that `platform` will not apply.
+///
+/// Both platforms share the `<ppp>` layout, so most fields carry over as-is.
+/// The exceptions are:
+/// - per-link lists (`mtu`, `mru`, `mrru`, `bandwidth`) with more values than
+///   there are ports — the extra values are never read;
+/// - multilink-only options (`mrru`, `shortseq`) on a single-link PPP;
+/// - an idle timeout without dial-on-demand, which both platforms ignore;
+/// - pfSense-only uptime logging and periodic reset settings on OPNsense.
+pub fn ignored_options(ppp: &XmlNode, platform: &str) -> Vec<IgnoredOption> {
+    let links = link_count(ppp);
+    let mut out = Vec::new();
+
+    for field in PER_LINK_FIELDS {
+        let count = list_values(ppp, field).len();
+        if count > links && !(links == 1 && MULTILINK_FIELDS.contains(field)) {
+            out.push(IgnoredOption {
+                field,
+                reason: format!("{count} values for {links} link(s); only the first {links} apply"),
+                fix: IgnoredFix::Truncate(links),
+            });
+        }
+    }
+    if links == 1 {
+        for field in MULTILINK_FIELDS {
+            if has_field(ppp, field) {
+                out.push(IgnoredOption {
+                    field,
+                    reason: "only applies to multilink PPP; add a second port to use it"
+                        .to_string(),
+                    fix: IgnoredFix::Drop,
+                });
+            }
+        }
+    }
+    let idle = ppp.get_text(&["idletimeout"]).map(str::trim).unwrap_or("0");
+    if !matches!(idle, "" | "0") && !has_field(ppp, "ondemand") {
+        out.push(IgnoredOption {
+            field: "idletimeout",
+            reason: "only applies with dial-on-demand; enable ondemand to use it".to_string(),
+            fix: IgnoredFix::Drop,
+        });
+    }
+    if platform == "opnsense" {
+        for field in PFSENSE_ONLY_FIELDS {
+            if has_field(ppp, field) {
+                let reason = if *field == "uptime" {
+                    "uptime logging is not available on OPNsense".to_string()
+                } else {
+                    "periodic reset is not available on OPNsense; schedule a cron job to reconnect instead".to_string()
+                };
+                out.push(IgnoredOption {
+                    field,
+                    reason,
+                    fix: IgnoredFix::Drop,
+                });
+            }
+        }
+    }
+    out
 }
 
 /// Replace the `<ppps>` section in `out` with the one from `source`.
 ///
-/// PPP config structure is identical between pfSense and OPNsense, so this
-/// is a straight copy. If the source has no `<ppps>` section, the output's
-/// `<ppps>` section (if any) is removed.
-fn sync_ppps(out: &mut XmlNode, source: &XmlNode) {
+/// If the source has no `<ppps>` section, the output's `<ppps>` section (if
+/// any) is removed. Each `<ppp>` is then cleaned up for `platform`, with one
+/// warning per dropped or trimmed option.
+fn sync_ppps(out: &mut XmlNode, source: &XmlNode, platform: &str, diagnostics: &mut Diagnostics) {
     // Remove any existing <ppps> section
     out.children.retain(|c| c.tag != "ppps");
 
     // Copy the source's <ppps> section if it exists
-    if let Some(ppps) = source.get_child("ppps") {
-        out.children.push(ppps.clone());
+    let Some(ppps) = source.get_child("ppps") else {
+        return;
+    };
+    let mut ppps = ppps.clone();
+    for ppp in ppps.children.iter_mut().filter(|c| c.tag == "ppp") {
+        let label = ppp
+            .get_text(&["if"])
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or("ppp")
+            .to_string();
+        for ignored in ignored_options(ppp, platform) {
+            match ignored.fix {
+                IgnoredFix::Drop => ppp.children.retain(|c| c.tag != ignored.field),
+                IgnoredFix::Truncate(n) => {
+                    let kept = list_values(ppp, ignored.field)[..n].join(",");
+                    if let Some(child) = ppp.children.iter_mut().find(|c| c.tag == ignored.field) {
+                        child.text = Some(kept);
+                    }
+                }
+            }
+            diagnostics.warn(
+                "ppps",
+                format!("{label}: {} {}", ignored.field, ignored.reason),
+            );
+        }
+    }
+    out.children.push(ppps);
+}
+
+fn link_count(ppp: &XmlNode) -> usize {
+    list_values(ppp, "ports").len().max(1)
+}
+
+fn list_values(ppp: &XmlNode, field: &str) -> Vec<String> {
+    ppp.get_text(&[field])
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Fields that are switched on by their mere presence (`<ondemand/>`).
+const FLAG_FIELDS: &[&str] = &["uptime", "ondemand", "shortseq"];
+
+/// Flag fields count as set when present; valued fields need text.
+fn has_field(ppp: &XmlNode, field: &str) -> bool {
+    if FLAG_FIELDS.contains(&field) {
+        return ppp.get_child(field).is_some();
     }
+    ppp.get_text(&[field]).is_some_and(|v| !v.trim().is_empty())
 }
 
 #[cfg(test)]
 mod tests {
     use xml_diff_core::parse;
 
-    use super::to_opnsense;
+    use super::{ignored_options, to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
 
     #[test]
     fn replaces_target_ppps_with_source_ppps() {
@@ -48,8 +212,68 @@ mod tests {
         )
         .expect("parse");
         let mut out = target.clone();
-        to_opnsense(&mut out, &source, &target);
+        let mut diagnostics = Diagnostics::default();
+        to_opnsense(&mut out, &source, &target, &mut diagnostics);
         assert_eq!(out.get_text(&["ppps", "ppp", "if"]), Some("pppoe0"));
         assert_eq!(out.get_text(&["ppps", "ppp", "ports"]), Some("igb0"));
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn drops_pfsense_only_and_inert_options_for_opnsense() {
+        let source = parse(
+            br#"<pfsense><ppps><ppp><if>pppoe0</if><ports>igb0</ports><mtu>1492,1492</mtu><mrru>1600</mrru><idletimeout>300</idletimeout><uptime/><pppoe-reset-type>preset</pppoe-reset-type><pppoe_pr_type>daily</pppoe_pr_type></ppp></ppps></pfsense>"#,
+        )
+        .expect("parse");
+        let target = parse(b"<opnsense/>").expect("parse");
+        let mut out = target.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_opnsense(&mut out, &source, &target, &mut diagnostics);
+
+        let ppp = out
+            .get_child("ppps")
+            .and_then(|p| p.get_child("ppp"))
+            .expect("ppp");
+        assert_eq!(ppp.get_text(&["mtu"]), Some("1492"));
+        for dropped in [
+            "mrru",
+            "idletimeout",
+            "uptime",
+            "pppoe-reset-type",
+            "pppoe_pr_type",
+        ] {
+            assert!(ppp.get_child(dropped).is_none(), "{dropped} kept");
+        }
+        assert_eq!(diagnostics.len(), 6);
+    }
+
+    #[test]
+    fn keeps_multilink_and_dial_on_demand_options() {
+        let ppp = parse(
+            br#"<ppp><if>pppoe0</if><ports>igb0,igb1</ports><mtu>1492,1480</mtu><mrru>1600,1600</mrru><shortseq/><ondemand/><idletimeout>300</idletimeout></ppp>"#,
+        )
+        .expect("parse");
+        assert!(ignored_options(&ppp, "opnsense").is_empty());
+        assert!(ignored_options(&ppp, "pfsense").is_empty());
+    }
+
+    #[test]
+    fn keeps_periodic_reset_for_pfsense() {
+        let source = parse(
+            br#"<opnsense><ppps><ppp><if>pppoe0</if><ports>igb0</ports><pppoe-reset-type>custom</pppoe-reset-type></ppp></ppps></opnsense>"#,
+        )
+        .expect("parse");
+        let target = parse(b"<pfsense/>").expect("parse");
+        let mut out = target.clone();
+        let mut diagnostics = Diagnostics::default();
+
+        to_pfsense(&mut out, &source, &target, &mut diagnostics);
+
+        assert_eq!(
+            out.get_text(&["ppps", "ppp", "pppoe-reset-type"]),
+            Some("custom")
+        );
+        assert!(diagnostics.is_empty());
     }
 }
@@ -0,0 +1,289 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use xml_diff_core::XmlNode;
+
+use crate::transform::lan_ip::{map_tokens, network, renumber_token};
+use crate::verify_uuid_refs::child_labels;
+
+/// Free-text fields left alone, so a note quoting an old address keeps it.
+const FREE_TEXT: &[&str] = &["descr", "description", "comment", "notes"];
+
+/// One `--renumber OLD=NEW` subnet mapping, both sides with the same prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubnetMapping {
+    pub old: Ipv4Addr,
+    pub new: Ipv4Addr,
+    pub prefix: u8,
+}
+
+impl SubnetMapping {
+    /// Whether `addr` falls inside the old subnet.
+    fn covers_old(&self, addr: Ipv4Addr) -> bool {
+        network(addr, self.prefix) == network(self.old, self.prefix)
+    }
+}
+
+impl fmt::Display for SubnetMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}={}/{}",
+            self.old, self.prefix, self.new, self.prefix
+        )
+    }
+}
+
+impl FromStr for SubnetMapping {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (old, new) = value
+            .split_once('=')
+            .ok_or_else(|| format!("invalid --renumber '{value}'; expected OLD-CIDR=NEW-CIDR"))?;
+        let (old, old_prefix) = parse_cidr(old)?;
+        let (new, new_prefix) = parse_cidr(new)?;
+        if old_prefix != new_prefix {
+            return Err(format!(
+                "invalid --renumber '{value}'; both subnets need the same prefix length"
+            ));
+        }
+        let to_net = |ip: Ipv4Addr| Ipv4Addr::from(network(ip, old_prefix).unwrap_or(0));
+        Ok(Self {
+            old: to_net(old),
+            new: to_net(new),
+            prefix: old_prefix,
+        })
+    }
+}
+
+fn parse_cidr(raw: &str) -> Result<(Ipv4Addr, u8), String> {
+    let (addr, prefix) = raw
+        .trim()
+        .split_once('/')
+        .ok_or_else(|| format!("'{raw}' is not an IPv4 CIDR"))?;
+    let addr: Ipv4Addr = addr
+        .parse()
+        .map_err(|_| format!("'{raw}' is not an IPv4 CIDR"))?;
+    let prefix: u8 = prefix
+        .parse()
+        .ok()
+        .filter(|p| *p <= 32)
+        .ok_or_else(|| format!("'{raw}' is not an IPv4 CIDR"))?;
+    Ok((addr, prefix))
+}
+
+/// An address, range, or network from a renumbered subnet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenumberedValue {
+    /// Field holding it (`staticroutes/route[2]/gateway`).
+    pub path: String,
+    pub old: String,
+    /// The rewritten value; `None` when it cannot be moved consistently.
+    pub new: Option<String>,
+}
+
+/// Move every address in the old subnets of `mappings` into the new ones.
+///
+/// This generalizes `--lan-ip` to any number of subnets. Every text value in
+/// the config is checked token by token, so interface addresses, DHCP ranges
+/// and reservations, aliases, rules, NAT, VPN local and remote networks, and
+/// static routes all move together and host bits are kept (`10.1.10.37`
+/// becomes `192.168.1.37`). Free-text fields ([`FREE_TEXT`]) are skipped.
+///
+/// Before changing anything, `ensure_no_collision` rejects mappings whose
+/// old or new subnets overlap each other, and new subnets that overlap an
+/// interface that is not itself being renumbered.
+///
+/// Returns every value found in an old subnet. A range leaving the subnet,
+/// or a network wider than it, is returned without a new value and left as
+/// it was.
+pub fn apply(root: &mut XmlNode, mappings: &[SubnetMapping]) -> Result<Vec<RenumberedValue>> {
+    let mappings: Vec<SubnetMapping> = mappings
+        .iter()
+        .copied()
+        .filter(|m| m.old != m.new)
+        .collect();
+    if mappings.is_empty() {
+        return Ok(Vec::new());
+    }
+    ensure_no_collision(root, &mappings)?;
+    let mut out = Vec::new();
+    renumber_subtree(root, "", &mappings, &mut out);
+    Ok(out)
+}
+
+/// Bail on mappings that would merge two subnets into one.
+fn ensure_no_collision(root: &XmlNode, mappings: &[SubnetMapping]) -> Result<()> {
+    for (idx, a) in mappings.iter().enumerate() {
+        for b in &mappings[idx + 1..] {
+            if overlaps(a.old, a.prefix, b.old, b.prefix) {
+                bail!("--renumber {a} and {b} map overlapping subnets");
+            }
+            if overlaps(a.new, a.prefix, b.new, b.prefix) {
+                bail!("--renumber {a} and {b} map onto overlapping subnets");
+            }
+        }
+    }
+    let Some(interfaces) = root.get_child("interfaces") else {
+        return Ok(());
+    };
+    for iface in &interfaces.children {
+        let Some(ip) = iface
+            .get_text(&["ipaddr"])
+            .and_then(|ip| ip.trim().parse::<Ipv4Addr>().ok())
+        else {
+            continue;
+        };
+        if mappings.iter().any(|m| m.covers_old(ip)) {
+            continue;
+        }
+        let prefix = iface
+            .get_text(&["subnet"])
+            .and_then(|s| s.trim().parse::<u8>().ok())
+            .unwrap_or(32);
+        if let Some(m) = mappings
+            .iter()
+            .find(|m| overlaps(m.new, m.prefix, ip, prefix))
+        {
+            bail!(
+                "--renumber {m} conflicts with existing interface {}.ipaddr={ip}/{prefix}",
+                iface.tag
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether two networks share any address.
+fn overlaps(a: Ipv4Addr, a_prefix: u8, b: Ipv4Addr, b_prefix: u8) -> bool {
+    let shorter = a_prefix.min(b_prefix);
+    network(a, shorter) == network(b, shorter)
+}
+
+fn renumber_subtree(
+    node: &mut XmlNode,
+    label: &str,
+    mappings: &[SubnetMapping],
+    out: &mut Vec<RenumberedValue>,
+) {
+    if let Some(text) = node.text.clone() {
+        let rewritten = map_tokens(&text, |token| {
+            let new = mappings
+                .iter()
+                .find_map(|m| renumber_token(token, m.old, m.new, m.prefix))?;
+            out.push(RenumberedValue {
+                path: label.to_string(),
+                old: token.to_string(),
+                new: new.clone(),
+            });
+            new
+        });
+        if rewritten != text {
+            node.text = Some(rewritten);
+        }
+    }
+    let labels = child_labels(node, label);
+    for (child, child_label) in node.children.iter_mut().zip(labels) {
+        if FREE_TEXT.contains(&child.tag.as_str()) {
+            continue;
+        }
+        renumber_subtree(child, &child_label, mappings, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{apply, SubnetMapping};
+
+    fn mapping(raw: &str) -> SubnetMapping {
+        raw.parse().expect("mapping")
+    }
+
+    #[test]
+    fn parses_mappings_to_network_addresses() {
+        let m = mapping("10.1.10.1/24=192.168.1.0/24");
+        assert_eq!(m.to_string(), "10.1.10.0/24=192.168.1.0/24");
+        assert!("10.1.10.0/24=192.168.0.0/23"
+            .parse::<SubnetMapping>()
+            .is_err());
+        assert!("10.1.10.0/24".parse::<SubnetMapping>().is_err());
+    }
+
+    #[test]
+    fn renumbers_every_subnet_consistently() {
+        let mut root = parse(
+            br#"<pfsense>
+                <interfaces>
+                  <lan><ipaddr>10.1.10.1</ipaddr><subnet>24</subnet></lan>
+                  <opt1><ipaddr>10.2.0.1</ipaddr><subnet>24</subnet></opt1>
+                </interfaces>
+                <dhcpd><opt1><range><from>10.2.0.100</from><to>10.2.0.199</to></range>
+                  <staticmap><ipaddr>10.2.0.50</ipaddr><descr>was 10.2.0.50</descr></staticmap></opt1></dhcpd>
+                <openvpn><openvpn-server><local_network>10.1.10.0/24,10.2.0.0/24</local_network></openvpn-server></openvpn>
+                <staticroutes><route><network>172.16.0.0/16</network><gateway>10.2.0.254</gateway></route></staticroutes>
+            </pfsense>"#,
+        )
+        .expect("parse");
+
+        let changed = apply(
+            &mut root,
+            &[
+                mapping("10.1.10.0/24=192.168.1.0/24"),
+                mapping("10.2.0.0/24=192.168.2.0/24"),
+            ],
+        )
+        .expect("apply");
+
+        assert_eq!(
+            root.get_text(&["interfaces", "opt1", "ipaddr"]),
+            Some("192.168.2.1")
+        );
+        assert_eq!(
+            root.get_text(&["dhcpd", "opt1", "range", "to"]),
+            Some("192.168.2.199")
+        );
+        assert_eq!(
+            root.get_text(&["dhcpd", "opt1", "staticmap", "descr"]),
+            Some("was 10.2.0.50")
+        );
+        assert_eq!(
+            root.get_text(&["openvpn", "openvpn-server", "local_network"]),
+            Some("192.168.1.0/24,192.168.2.0/24")
+        );
+        assert_eq!(
+            root.get_text(&["staticroutes", "route", "gateway"]),
+            Some("192.168.2.254")
+        );
+        assert_eq!(changed.len(), 8);
+        assert!(changed
+            .iter()
+            .any(|c| c.path == "dhcpd/opt1/staticmap/ipaddr"));
+    }
+
+    #[test]
+    fn rejects_collisions() {
+        let source = br#"<pfsense><interfaces>
+              <lan><ipaddr>10.1.10.1</ipaddr><subnet>24</subnet></lan>
+              <opt1><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></opt1>
+            </interfaces></pfsense>"#;
+        let mut root = parse(source).expect("parse");
+        let err = apply(&mut root, &[mapping("10.1.10.0/24=192.168.1.0/24")])
+            .expect_err("interface collision");
+        assert!(err.to_string().contains("existing interface opt1"));
+
+        let err = apply(
+            &mut root,
+            &[
+                mapping("10.1.10.0/24=172.16.1.0/24"),
+                mapping("10.9.0.0/24=172.16.1.0/24"),
+            ],
+        )
+        .expect_err("mapping collision");
+        assert!(err.to_string().contains("overlapping subnets"));
+    }
+}
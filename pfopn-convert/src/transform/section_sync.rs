@@ -37,14 +37,20 @@ const SYNCED_TOP_LEVEL_SECTIONS: &[&str] = &[
 /// If a section exists in the source, it replaces (or adds) that section in `out`.
 /// If a section doesn't exist in the source, it's removed from `out`.
 pub fn sync_shared_top_level_sections(out: &mut XmlNode, source: &XmlNode) {
+    let sections = source.index();
     for tag in SYNCED_TOP_LEVEL_SECTIONS {
-        match source.get_child(tag).cloned() {
+        match sections.get_child(tag).cloned() {
             Some(src_child) => upsert_top_child(out, src_child),
             None => remove_top_children(out, tag),
         }
     }
 }
 
+/// Whether `tag` is one of the sections copied wholesale from the source.
+pub fn is_synced_section(tag: &str) -> bool {
+    SYNCED_TOP_LEVEL_SECTIONS.contains(&tag)
+}
+
 /// Insert or replace a top-level child node in the root.
 fn upsert_top_child(root: &mut XmlNode, node: XmlNode) {
     if let Some(idx) = root.children.iter().position(|c| c.tag == node.tag) {
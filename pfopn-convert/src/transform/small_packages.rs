@@ -0,0 +1,265 @@
+//! Small pfSense packages with an OPNsense counterpart.
+//!
+//! Packages such as Service Watchdog or Shellcmd keep a short list of items
+//! under `<installedpackages>`. OPNsense has no package of the same shape;
+//! the same job is done by a core feature (Monit, configd boot hooks,
+//! remote backups) that is configured differently. Rather than letting the
+//! blanket `installedpackages` prune drop them silently, each package in
+//! [`SMALL_PACKAGES`] turns its items into manual steps for that
+//! counterpart, reported as `packages` warnings.
+//!
+//! Adding a package means adding a [`SmallPackage`] entry with a handler
+//! that lists its steps, and its tags to [`SECTIONS`].
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::is_truthy;
+
+/// A pfSense package and how its items carry over to OPNsense.
+#[derive(Debug, Clone, Copy)]
+pub struct SmallPackage {
+    /// Package name as shown in the pfSense package manager.
+    pub name: &'static str,
+    /// `<installedpackages>` child holding the package config.
+    pub tag: &'static str,
+    /// OPNsense feature or plugin that replaces it.
+    pub counterpart: &'static str,
+    /// Manual steps for the package config.
+    steps: fn(&XmlNode) -> Vec<String>,
+}
+
+/// Every small package with a known counterpart.
+pub const SMALL_PACKAGES: &[SmallPackage] = &[
+    SmallPackage {
+        name: "Service Watchdog",
+        tag: "servicewatchdog",
+        counterpart: "Monit",
+        steps: service_watchdog_steps,
+    },
+    SmallPackage {
+        name: "Shellcmd",
+        tag: "shellcmdsettings",
+        counterpart: "configd boot hooks",
+        steps: shellcmd_steps,
+    },
+    SmallPackage {
+        name: "Filer",
+        tag: "filer",
+        counterpart: "files managed outside the config",
+        steps: filer_steps,
+    },
+    SmallPackage {
+        name: "System Patches",
+        tag: "patches",
+        counterpart: "opnsense-patch",
+        steps: patches_steps,
+    },
+    SmallPackage {
+        name: "AutoConfigBackup",
+        tag: "autoconfigbackup",
+        counterpart: "os-api-backup or remote backups",
+        steps: autoconfigbackup_steps,
+    },
+];
+
+/// Capability sections for the packages in [`SMALL_PACKAGES`].
+pub const SECTIONS: &[&str] = &[
+    "installedpackages/servicewatchdog",
+    "installedpackages/shellcmdsettings",
+    "installedpackages/filer",
+    "installedpackages/patches",
+    "installedpackages/autoconfigbackup",
+];
+
+/// The handler for an `<installedpackages>` child, if there is one.
+pub fn find(tag: &str) -> Option<&'static SmallPackage> {
+    SMALL_PACKAGES.iter().find(|p| p.tag == tag)
+}
+
+/// Report manual steps for every small package configured in `source`.
+///
+/// The package configs themselves are removed with the rest of
+/// `<installedpackages>` when the output is pruned.
+pub fn to_opnsense(
+    _out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(installed) = source.get_child("installedpackages") else {
+        return;
+    };
+    for package in SMALL_PACKAGES {
+        let Some(config) = installed.get_child(package.tag) else {
+            continue;
+        };
+        for step in (package.steps)(config) {
+            diagnostics.warn(
+                "packages",
+                format!("{} -> {}: {step}", package.name, package.counterpart),
+            );
+        }
+    }
+}
+
+fn field<'a>(item: &'a XmlNode, tag: &str) -> &'a str {
+    item.get_text(&[tag]).map(str::trim).unwrap_or("")
+}
+
+fn labelled(value: &str, descr: &str) -> String {
+    if descr.is_empty() {
+        format!("'{value}'")
+    } else {
+        format!("'{value}' ({descr})")
+    }
+}
+
+fn service_watchdog_steps(config: &XmlNode) -> Vec<String> {
+    config
+        .get_children("item")
+        .into_iter()
+        .filter(|item| !field(item, "name").is_empty())
+        .map(|item| {
+            let mut step = format!(
+                "add a Monit process check for {}",
+                labelled(field(item, "name"), field(item, "description"))
+            );
+            if is_truthy(field(item, "notify")) {
+                step.push_str(" with an alert");
+            }
+            step
+        })
+        .collect()
+}
+
+fn shellcmd_steps(config: &XmlNode) -> Vec<String> {
+    config.get_children("config")
+        .into_iter()
+        .filter(|item| !field(item, "cmd").is_empty())
+        .filter_map(|item| {
+            let cmd = labelled(field(item, "cmd"), field(item, "description"));
+            match field(item, "cmdtype") {
+                "disabled" => None,
+                "earlyshellcmd" => Some(format!(
+                    "run {cmd} from a script in /usr/local/etc/rc.syshook.d/early/"
+                )),
+                "afterfilterchangeshellcmd" => Some(format!(
+                    "run {cmd} from a configd action after filter reloads; there is no boot hook for it"
+                )),
+                _ => Some(format!(
+                    "run {cmd} from a script in /usr/local/etc/rc.syshook.d/start/"
+                )),
+            }
+        })
+        .collect()
+}
+
+fn filer_steps(config: &XmlNode) -> Vec<String> {
+    config
+        .get_children("config")
+        .into_iter()
+        .filter(|item| !field(item, "fullfile").is_empty())
+        .map(|item| {
+            let mut step = format!(
+                "recreate {}",
+                labelled(field(item, "fullfile"), field(item, "description"))
+            );
+            let perm = field(item, "fileperm");
+            if !perm.is_empty() {
+                step.push_str(&format!(" with mode {perm}"));
+            }
+            let command = field(item, "command");
+            if !command.is_empty() {
+                step.push_str(&format!(", then run '{command}'"));
+            }
+            step
+        })
+        .collect()
+}
+
+fn patches_steps(config: &XmlNode) -> Vec<String> {
+    config.get_children("item")
+        .into_iter()
+        .map(|item| {
+            let name = [field(item, "descr"), field(item, "location")]
+                .into_iter()
+                .find(|v| !v.is_empty())
+                .unwrap_or("unnamed patch");
+            format!(
+                "review patch '{name}'; pfSense patches do not apply to OPNsense, use opnsense-patch for an upstream fix"
+            )
+        })
+        .collect()
+}
+
+fn autoconfigbackup_steps(config: &XmlNode) -> Vec<String> {
+    let settings = config.get_child("config").unwrap_or(config);
+    if !settings.children.is_empty() && !is_truthy(field(settings, "enable_acb")) {
+        return Vec::new();
+    }
+    vec![
+        "set up remote backups under System > Configuration > Backups or install os-api-backup; the backup encryption password is not carried".to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{find, to_opnsense, SECTIONS, SMALL_PACKAGES};
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn sections_match_the_package_table() {
+        let tags: Vec<String> = SMALL_PACKAGES
+            .iter()
+            .map(|p| format!("installedpackages/{}", p.tag))
+            .collect();
+        assert_eq!(tags, SECTIONS);
+        assert_eq!(find("shellcmdsettings").map(|p| p.name), Some("Shellcmd"));
+        assert!(find("pfblockerng").is_none());
+    }
+
+    #[test]
+    fn reports_manual_steps_per_item() {
+        let source = parse(
+            br#"<pfsense><installedpackages>
+                <servicewatchdog><item><name>unbound</name><description>DNS Resolver</description><notify>on</notify></item></servicewatchdog>
+                <shellcmdsettings>
+                    <config><cmd>ifconfig igb0 mtu 9000</cmd><cmdtype>earlyshellcmd</cmdtype></config>
+                    <config><cmd>/root/start.sh</cmd><cmdtype>shellcmd</cmdtype><description>Start</description></config>
+                    <config><cmd>/root/off.sh</cmd><cmdtype>disabled</cmdtype></config>
+                </shellcmdsettings>
+                <filer><config><fullfile>/root/notes.txt</fullfile><fileperm>0644</fileperm><command>touch /tmp/x</command></config></filer>
+                <patches><item><descr>Fix GUI</descr></item></patches>
+                <nrpe/>
+            </installedpackages></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(messages.len(), 5, "{messages:?}");
+        assert!(messages.iter().any(|m| m.contains(
+            "Service Watchdog -> Monit: add a Monit process check for 'unbound' (DNS Resolver) with an alert"
+        )));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("'ifconfig igb0 mtu 9000'") && m.contains("rc.syshook.d/early/")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("'/root/start.sh' (Start)") && m.contains("rc.syshook.d/start/")));
+        assert!(messages
+            .iter()
+            .any(|m| m
+                .contains("recreate '/root/notes.txt' with mode 0644, then run 'touch /tmp/x'")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("review patch 'Fix GUI'")));
+    }
+}
@@ -1,5 +1,17 @@
+use std::collections::BTreeMap;
+
 use xml_diff_core::XmlNode;
 
+use crate::transform::booleans::normalize_flag;
+use crate::uuid_registry::UuidRegistry;
+
+/// Suffixes both platforms append to an uppercased logical interface name when
+/// creating dynamic gateways (e.g. `WAN_DHCP`, `OPT2_VPNV4`).
+const DYNAMIC_GATEWAY_SUFFIXES: &[&str] = &[
+    "DHCP", "DHCP6", "PPPOE", "PPTP", "L2TP", "SLAAC", "TRACK6", "6RD", "6TO4", "VPNV4", "VPNV6",
+    "TUNNELV4", "TUNNELV6", "VTIV4", "VTIV6",
+];
+
 /// Normalize static routes for OPNsense format.
 ///
 /// OPNsense static routes require two additional fields that pfSense doesn't have:
@@ -13,6 +25,7 @@ pub fn to_opnsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline:
         return;
     };
     let mut normalized = routes;
+    let mut registry = UuidRegistry::from_config(out);
 
     for (idx, route) in normalized
         .children
@@ -20,10 +33,9 @@ pub fn to_opnsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline:
         .filter(|n| n.tag == "route")
         .enumerate()
     {
-        // Add a uuid attribute if missing
+        // Key the UUID by route properties so it is stable across runs
         if !route.attributes.contains_key("uuid") {
-            // Generate a deterministic UUID from route properties
-            let seed = format!(
+            let key = format!(
                 "{}|{}|{}|{}",
                 route.get_text(&["network"]).unwrap_or_default(),
                 route.get_text(&["gateway"]).unwrap_or_default(),
@@ -32,10 +44,11 @@ pub fn to_opnsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline:
             );
             route
                 .attributes
-                .insert("uuid".to_string(), stable_uuid(seed.as_bytes(), idx));
+                .insert("uuid".to_string(), registry.uuid_for("staticroute", &key));
         }
 
         // Add <disabled>0</disabled> if missing (OPNsense expects this field)
+        normalize_flag(route, "disabled", "opnsense");
         if route.get_child("disabled").is_none() {
             let mut disabled = XmlNode::new("disabled");
             disabled.text = Some("0".to_string());
@@ -47,11 +60,10 @@ pub fn to_opnsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline:
 
 /// Normalize static routes for pfSense format.
 ///
-/// pfSense static routes do NOT have:
-/// 1. A `uuid` attribute (OPNsense-only requirement)
-/// 2. A `<disabled>` child element (OPNsense uses this; pfSense just omits the route)
-///
-/// This function removes those OPNsense-specific fields to produce clean pfSense XML.
+/// pfSense static routes do NOT have a `uuid` attribute (OPNsense-only
+/// requirement), and pfSense reads `<disabled>` as a presence tag: the uuid
+/// is removed, `<disabled>0</disabled>` is dropped, and a disabled route keeps
+/// an empty `<disabled/>`.
 pub fn to_pfsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline: &XmlNode) {
     let Some(routes) = out.get_child("staticroutes").cloned() else {
         return;
@@ -62,57 +74,138 @@ pub fn to_pfsense(out: &mut XmlNode, _source: &XmlNode, _destination_baseline: &
         // Remove OPNsense-specific uuid attribute
         route.attributes.remove("uuid");
 
-        // Remove OPNsense-specific <disabled> element
-        if let Some(idx) = route.children.iter().position(|c| c.tag == "disabled") {
-            route.children.remove(idx);
-        }
+        // <disabled>0</disabled> would read as disabled on pfSense
+        normalize_flag(route, "disabled", "pfsense");
     }
     upsert_child(out, normalized);
 }
 
-/// Replace or insert a child node into the parent by tag name.
-fn upsert_child(parent: &mut XmlNode, child: XmlNode) {
-    if let Some(idx) = parent.children.iter().position(|c| c.tag == child.tag) {
-        parent.children[idx] = child;
+/// Rewrite gateway names derived from renamed logical interfaces.
+///
+/// Dynamic gateways are named after their interface (`OPT2_DHCP` belongs to
+/// `opt2`), so when interfaces are renumbered or virtual assignments are
+/// normalized those names change too. This updates gateway item names,
+/// default gateway selections, gateway group members, and the `<gateway>` of
+/// every static route and policy routing filter rule so they keep pointing at
+/// the gateway that moved with its interface.
+///
+/// `logical_map` is the same old -> new logical name map passed to
+/// `logical_refs::apply`.
+pub fn rewrite_gateway_refs(out: &mut XmlNode, logical_map: Option<&BTreeMap<String, String>>) {
+    let Some(logical_map) = logical_map.filter(|m| !m.is_empty()) else {
         return;
+    };
+
+    for gateways in gateway_sections_mut(out) {
+        for child in &mut gateways.children {
+            match child.tag.as_str() {
+                "gateway_item" => rewrite_text_child(child, "name", logical_map),
+                "defaultgw4" | "defaultgw6" => rewrite_gateway_text(child, logical_map),
+                "gateway_group" => {
+                    for item in child.children.iter_mut().filter(|c| c.tag == "item") {
+                        rewrite_group_item(item, logical_map);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(routes) = out.children.iter_mut().find(|c| c.tag == "staticroutes") {
+        for route in routes.children.iter_mut().filter(|n| n.tag == "route") {
+            rewrite_text_child(route, "gateway", logical_map);
+        }
+    }
+    if let Some(filter) = out.children.iter_mut().find(|c| c.tag == "filter") {
+        for rule in filter.children.iter_mut().filter(|n| n.tag == "rule") {
+            rewrite_text_child(rule, "gateway", logical_map);
+        }
     }
-    parent.children.push(child);
 }
 
-/// Generate a stable UUID from a seed string and index.
+/// Return the logical interface a dynamic gateway name belongs to.
 ///
-/// Uses CRC32 of the seed XORed with the index to create a deterministic but
-/// unique-looking UUID. This isn't a real UUID v4, but it's good enough for
-/// config file identification and ensures the same route always gets the same UUID.
-fn stable_uuid(seed: &[u8], idx: usize) -> String {
-    format!(
-        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
-        crc32(seed) ^ (idx as u32),
-        0,
-        0,
-        0,
-        (idx as u64) + 1
-    )
+/// `WAN_DHCP6` -> `Some("wan")`, `OPT3_VPNV4` -> `Some("opt3")`. Names that
+/// do not end in a known dynamic suffix return `None`.
+pub fn dynamic_gateway_interface(name: &str) -> Option<String> {
+    let (iface, suffix) = name.trim().rsplit_once('_')?;
+    if iface.is_empty() || !DYNAMIC_GATEWAY_SUFFIXES.contains(&suffix.to_ascii_uppercase().as_str())
+    {
+        return None;
+    }
+    Some(iface.to_ascii_lowercase())
 }
 
-/// CRC32 hash using the standard polynomial (IEEE 802.3).
-fn crc32(input: &[u8]) -> u32 {
-    let mut crc = 0xffff_ffffu32;
-    for b in input {
-        crc ^= *b as u32;
-        for _ in 0..8 {
-            let mask = (crc & 1).wrapping_neg();
-            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+/// Map a gateway name through `logical_map` if it is a dynamic gateway.
+fn map_gateway_name(name: &str, logical_map: &BTreeMap<String, String>) -> Option<String> {
+    let iface = dynamic_gateway_interface(name)?;
+    let mapped = logical_map.get(&iface)?;
+    let (_, suffix) = name.trim().rsplit_once('_')?;
+    Some(format!("{}_{suffix}", mapped.to_ascii_uppercase()))
+}
+
+/// `<gateways>` (both platforms) and OPNsense MVC `<OPNsense><Gateways>`.
+fn gateway_sections_mut(out: &mut XmlNode) -> Vec<&mut XmlNode> {
+    let mut sections = Vec::new();
+    for child in &mut out.children {
+        match child.tag.as_str() {
+            "gateways" => sections.push(child),
+            "OPNsense" => {
+                sections.extend(child.children.iter_mut().filter(|c| c.tag == "Gateways"))
+            }
+            _ => {}
         }
     }
-    !crc
+    sections
+}
+
+fn rewrite_text_child(node: &mut XmlNode, tag: &str, logical_map: &BTreeMap<String, String>) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        rewrite_gateway_text(child, logical_map);
+    }
+}
+
+fn rewrite_gateway_text(node: &mut XmlNode, logical_map: &BTreeMap<String, String>) {
+    let Some(current) = node.text.as_deref() else {
+        return;
+    };
+    if let Some(mapped) = map_gateway_name(current, logical_map) {
+        node.text = Some(mapped);
+    }
+}
+
+/// Gateway group items are `NAME|tier|vip`; only the name is rewritten.
+fn rewrite_group_item(item: &mut XmlNode, logical_map: &BTreeMap<String, String>) {
+    let Some(current) = item.text.as_deref() else {
+        return;
+    };
+    let (name, rest) = current.split_once('|').unwrap_or((current, ""));
+    let Some(mapped) = map_gateway_name(name, logical_map) else {
+        return;
+    };
+    item.text = Some(if current.contains('|') {
+        format!("{mapped}|{rest}")
+    } else {
+        mapped
+    });
+}
+
+/// Replace or insert a child node into the parent by tag name.
+fn upsert_child(parent: &mut XmlNode, child: XmlNode) {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == child.tag) {
+        parent.children[idx] = child;
+        return;
+    }
+    parent.children.push(child);
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use xml_diff_core::parse;
 
-    use super::{to_opnsense, to_pfsense};
+    use super::{dynamic_gateway_interface, rewrite_gateway_refs, to_opnsense, to_pfsense};
 
     #[test]
     fn adds_uuid_and_disabled_for_opnsense_routes() {
@@ -149,4 +242,76 @@ mod tests {
         assert!(!route.attributes.contains_key("uuid"));
         assert!(route.get_child("disabled").is_none());
     }
+
+    #[test]
+    fn keeps_disabled_opnsense_routes_disabled_for_pfsense() {
+        let source = parse(br#"<opnsense><staticroutes/></opnsense>"#).expect("parse");
+        let mut out = parse(
+            br#"<pfsense><staticroutes><route uuid="abc"><network>10.9.9.0/24</network><disabled>1</disabled></route></staticroutes></pfsense>"#,
+        )
+        .expect("parse");
+
+        to_pfsense(&mut out, &source, &source);
+        let disabled = out
+            .get_child("staticroutes")
+            .and_then(|s| s.get_child("route"))
+            .and_then(|r| r.get_child("disabled"))
+            .expect("disabled flag");
+        assert!(disabled.text.is_none());
+    }
+
+    #[test]
+    fn rewrites_dynamic_gateway_names_for_renamed_interfaces() {
+        let mut out = parse(
+            br#"<opnsense><gateways><gateway_item><interface>opt2</interface><name>OVPNS1_VPNV4</name></gateway_item><gateway_group><item>OVPNS1_VPNV4|1|address</item><item>WAN_DHCP|2|address</item></gateway_group><defaultgw4>OVPNS1_VPNV4</defaultgw4></gateways><staticroutes><route><network>10.9.9.0/24</network><gateway>OVPNS1_VPNV4</gateway></route><route><network>10.8.8.0/24</network><gateway>STATIC_GW1</gateway></route></staticroutes><filter><rule><gateway>OVPNS1_VPNV4</gateway></rule></filter></opnsense>"#,
+        )
+        .expect("parse");
+        let mut map = BTreeMap::new();
+        map.insert("ovpns1".to_string(), "opt2".to_string());
+
+        rewrite_gateway_refs(&mut out, Some(&map));
+        assert_eq!(
+            out.get_text(&["gateways", "gateway_item", "name"]),
+            Some("OPT2_VPNV4")
+        );
+        assert_eq!(
+            out.get_text(&["gateways", "defaultgw4"]),
+            Some("OPT2_VPNV4")
+        );
+        let groups = out
+            .get_child("gateways")
+            .and_then(|g| g.get_child("gateway_group"))
+            .expect("group");
+        let items: Vec<_> = groups
+            .children
+            .iter()
+            .filter_map(|i| i.text.as_deref())
+            .collect();
+        assert_eq!(items, vec!["OPT2_VPNV4|1|address", "WAN_DHCP|2|address"]);
+        let routes = out.get_child("staticroutes").expect("routes");
+        let gateways: Vec<_> = routes
+            .children
+            .iter()
+            .filter_map(|r| r.get_text(&["gateway"]))
+            .collect();
+        assert_eq!(gateways, vec!["OPT2_VPNV4", "STATIC_GW1"]);
+        assert_eq!(
+            out.get_text(&["filter", "rule", "gateway"]),
+            Some("OPT2_VPNV4")
+        );
+    }
+
+    #[test]
+    fn dynamic_gateway_interface_requires_known_suffix() {
+        assert_eq!(
+            dynamic_gateway_interface("WAN_DHCP6"),
+            Some("wan".to_string())
+        );
+        assert_eq!(
+            dynamic_gateway_interface("OPT3_VPNV4"),
+            Some("opt3".to_string())
+        );
+        assert_eq!(dynamic_gateway_interface("HOME_ROUTER"), None);
+        assert_eq!(dynamic_gateway_interface("GW1"), None);
+    }
 }
@@ -0,0 +1,245 @@
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::privilege_map::{default_privilege_map, PrivilegeMap};
+
+/// Fields copied from a source `<group>` into the target.
+const GROUP_FIELDS: &[&str] = &["name", "description", "scope", "gid", "member", "priv"];
+
+/// Transfer local groups from pfSense to OPNsense.
+///
+/// Group privileges are translated with the privilege map; entries without an
+/// OPNsense equivalent are dropped and reported to `diagnostics`.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    transfer_groups(out, source, "opnsense", diagnostics);
+}
+
+/// Transfer local groups from OPNsense to pfSense.
+///
+/// Works like [`to_opnsense`], and additionally strips the OPNsense `uuid`
+/// attribute from transferred groups.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    transfer_groups(out, source, "pfsense", diagnostics);
+}
+
+/// Copy or merge every source group into the output `<system>`.
+///
+/// - The implicit "all" group is skipped (both platforms manage it).
+/// - Groups already present in the target (such as "admins") keep their own
+///   privileges and members; translated source privileges and source members
+///   are added to them.
+/// - Other groups are inserted after the last existing target group.
+fn transfer_groups(out: &mut XmlNode, source: &XmlNode, to: &str, diagnostics: &mut Diagnostics) {
+    let Some(source_system) = source.get_child("system") else {
+        return;
+    };
+    let Some(system_out) = out.children.iter_mut().find(|n| n.tag == "system") else {
+        return;
+    };
+    let map = default_privilege_map();
+
+    for group in source_system.get_children("group") {
+        let Some(name) = group_name(group) else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("all") {
+            continue;
+        }
+
+        let translated = translate_group(group, &name, to, &map, diagnostics);
+        if let Some(existing) = find_group_mut(system_out, &name) {
+            merge_repeated(existing, &translated, "member");
+            merge_repeated(existing, &translated, "priv");
+            continue;
+        }
+
+        warn_gid_collision(system_out, &translated, &name, diagnostics);
+        let insert_at = system_out
+            .children
+            .iter()
+            .rposition(|n| n.tag == "group")
+            .map_or(system_out.children.len(), |idx| idx + 1);
+        system_out.children.insert(insert_at, translated);
+    }
+}
+
+/// Build a sanitized copy of `group` with privileges translated for `to`.
+fn translate_group(
+    group: &XmlNode,
+    name: &str,
+    to: &str,
+    map: &PrivilegeMap,
+    diagnostics: &mut Diagnostics,
+) -> XmlNode {
+    let mut out = XmlNode::new("group");
+    if to == "opnsense" {
+        out.attributes = group.attributes.clone();
+    }
+
+    for child in &group.children {
+        if !GROUP_FIELDS.contains(&child.tag.as_str()) {
+            continue;
+        }
+        if child.tag != "priv" {
+            out.children.push(child.clone());
+            continue;
+        }
+        let Some(privilege) = child
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        else {
+            continue;
+        };
+        match map.translate(privilege, to) {
+            Some(mapped) => {
+                let mut node = XmlNode::new("priv");
+                node.text = Some(mapped.to_string());
+                out.children.push(node);
+            }
+            None => diagnostics.warn(
+                "system_groups",
+                format!("group {name}: privilege {privilege} has no {to} equivalent; dropped"),
+            ),
+        }
+    }
+    out
+}
+
+/// Append `tag` children from `source` that `dest` does not already have.
+fn merge_repeated(dest: &mut XmlNode, source: &XmlNode, tag: &str) {
+    for node in source.get_children(tag) {
+        let value = node.text.as_deref().map(str::trim);
+        let present = dest
+            .get_children(tag)
+            .iter()
+            .any(|existing| existing.text.as_deref().map(str::trim) == value);
+        if !present {
+            dest.children.push(node.clone());
+        }
+    }
+}
+
+/// Report when a new group's gid is already used by a different target group.
+fn warn_gid_collision(
+    system: &XmlNode,
+    group: &XmlNode,
+    name: &str,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(gid) = group.get_text(&["gid"]).map(str::trim) else {
+        return;
+    };
+    let collides = system
+        .get_children("group")
+        .iter()
+        .any(|existing| existing.get_text(&["gid"]).map(str::trim) == Some(gid));
+    if collides {
+        diagnostics.warn(
+            "system_groups",
+            format!("gid collision for group {name} (gid {gid}); review group IDs after restore"),
+        );
+    }
+}
+
+fn group_name(group: &XmlNode) -> Option<String> {
+    group
+        .get_text(&["name"])
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(ToString::to_string)
+}
+
+fn find_group_mut<'a>(system: &'a mut XmlNode, name: &str) -> Option<&'a mut XmlNode> {
+    system.children.iter_mut().find(|child| {
+        child.tag == "group"
+            && child
+                .get_text(&["name"])
+                .is_some_and(|v| v.trim().eq_ignore_ascii_case(name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn transfers_group_with_translated_privileges() {
+        let source = parse(
+            br#"<pfsense><system><group><name>all</name></group><group><name>admins</name><member>0</member><member>2001</member><priv>page-all</priv></group><group><name>dns-ops</name><gid>2005</gid><member>2001</member><priv>page-services-dnsresolver</priv><priv>page-pfblockerng</priv></group></system></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<opnsense><system/></opnsense>"#).expect("parse");
+        let mut out = parse(
+            br#"<opnsense><system><group><name>admins</name><priv>page-all</priv><member>0</member></group><user><name>root</name></user></system></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let system = out.get_child("system").expect("system");
+        let tags: Vec<_> = system.children.iter().map(|c| c.tag.as_str()).collect();
+        assert_eq!(tags, vec!["group", "group", "user"]);
+
+        let admins = &system.children[0];
+        let members: Vec<_> = admins
+            .get_children("member")
+            .iter()
+            .filter_map(|m| m.text.as_deref())
+            .collect();
+        assert_eq!(members, vec!["0", "2001"]);
+        assert_eq!(admins.get_children("priv").len(), 1);
+
+        let dns = &system.children[1];
+        assert_eq!(dns.get_text(&["name"]), Some("dns-ops"));
+        let privs: Vec<_> = dns
+            .get_children("priv")
+            .iter()
+            .filter_map(|p| p.text.as_deref())
+            .collect();
+        assert_eq!(privs, vec!["page-services-unbound"]);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.source == "system_groups" && d.message.contains("page-pfblockerng")));
+    }
+
+    #[test]
+    fn strips_uuid_and_translates_for_pfsense() {
+        let source = parse(
+            br#"<opnsense><system><group uuid="abc"><name>logs</name><priv>page-diagnostics-logs-firewall</priv><source_networks/></group></system></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
+        let mut out = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let group = out
+            .get_child("system")
+            .and_then(|s| s.get_child("group"))
+            .expect("group");
+        assert!(group.attributes.is_empty());
+        assert!(group.get_child("source_networks").is_none());
+        assert_eq!(
+            group.get_text(&["priv"]),
+            Some("page-status-systemlogs-firewall")
+        );
+        assert!(diagnostics.is_empty());
+    }
+}
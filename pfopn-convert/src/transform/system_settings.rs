@@ -0,0 +1,612 @@
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::{flag_set, set_flag};
+
+/// How one `<system>` leaf is carried from source to output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    /// Same tag and value format on both platforms; every occurrence is copied.
+    Copy,
+    /// On/off switch: pfSense marks it with an empty element, OPNsense with `1`.
+    Flag,
+    /// Only the destination has it: the baseline value is kept.
+    Baseline,
+    /// Only the source has it: dropped, with a warning naming the replacement.
+    Drop(&'static str),
+    /// Zone name; legacy UTC aliases are normalized to `Etc/UTC`.
+    Timezone,
+    /// GUI language; locale codes differ (see the language tables).
+    Language,
+    /// Console selection: pfSense `enableserial` + `primaryconsole`,
+    /// OPNsense `primaryconsole` + `secondaryconsole`.
+    Console,
+    /// RAM disks: pfSense `use_mfs_tmpvar`, OPNsense `use_mfs_tmp` + `use_mfs_var`.
+    RamDisks,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SystemField {
+    tag: &'static str,
+    kind: FieldKind,
+}
+
+const fn field(tag: &'static str, kind: FieldKind) -> SystemField {
+    SystemField { tag, kind }
+}
+
+/// Fields handled the same way in both directions.
+const SHARED_FIELDS: &[SystemField] = &[
+    // Identity and DNS
+    field("hostname", FieldKind::Copy),
+    field("domain", FieldKind::Copy),
+    field("timeservers", FieldKind::Copy),
+    field("dnsserver", FieldKind::Copy),
+    field("dnsallowoverride", FieldKind::Flag),
+    field("dnsallowoverride_exclude", FieldKind::Copy),
+    field("dns1gw", FieldKind::Copy),
+    field("dns2gw", FieldKind::Copy),
+    field("dns3gw", FieldKind::Copy),
+    field("dns4gw", FieldKind::Copy),
+    field("dns5gw", FieldKind::Copy),
+    field("dns6gw", FieldKind::Copy),
+    field("dns7gw", FieldKind::Copy),
+    field("dns8gw", FieldKind::Copy),
+    // Locale
+    field("timezone", FieldKind::Timezone),
+    field("language", FieldKind::Language),
+    // Console
+    field("primaryconsole", FieldKind::Console),
+    field("serialspeed", FieldKind::Copy),
+    field("disableconsolemenu", FieldKind::Flag),
+    // Power management and sensors
+    field("powerd_enable", FieldKind::Flag),
+    field("powerd_ac_mode", FieldKind::Copy),
+    field("powerd_battery_mode", FieldKind::Copy),
+    field("powerd_normal_mode", FieldKind::Copy),
+    field("thermal_hardware", FieldKind::Copy),
+    // Boot-time network and disk settings
+    field("ipv6allow", FieldKind::Flag),
+    field("disablechecksumoffloading", FieldKind::Flag),
+    field("disablesegmentationoffloading", FieldKind::Flag),
+    field("disablelargereceiveoffloading", FieldKind::Flag),
+    field("use_mfs_tmpvar", FieldKind::RamDisks),
+];
+
+/// Extra fields when converting pfSense -> OPNsense.
+const TO_OPNSENSE_FIELDS: &[SystemField] = &[
+    field("usevirtualterminal", FieldKind::Baseline),
+    field("serialusb", FieldKind::Baseline),
+    field(
+        "use_mfs_tmp_size",
+        FieldKind::Drop("OPNsense sizes RAM disks automatically"),
+    ),
+    field(
+        "use_mfs_var_size",
+        FieldKind::Drop("OPNsense sizes RAM disks automatically"),
+    ),
+    field(
+        "earlyshellcmd",
+        FieldKind::Drop(
+            "add it as a script under /usr/local/etc/rc.syshook.d/early after a security review",
+        ),
+    ),
+    field(
+        "shellcmd",
+        FieldKind::Drop(
+            "add it as a script under /usr/local/etc/rc.syshook.d/start after a security review",
+        ),
+    ),
+];
+
+/// Extra fields when converting OPNsense -> pfSense.
+const TO_PFSENSE_FIELDS: &[SystemField] = &[
+    field(
+        "usevirtualterminal",
+        FieldKind::Drop("pfSense always uses the virtual terminal"),
+    ),
+    // Legacy OPNsense configs may still carry boot commands; pfSense runs them
+    field("earlyshellcmd", FieldKind::Copy),
+    field("shellcmd", FieldKind::Copy),
+];
+
+/// pfSense locale -> OPNsense locale.
+const PFSENSE_TO_OPNSENSE_LANGUAGE: &[(&str, &str)] = &[
+    ("en_US", "en_US"),
+    ("de_DE", "de_DE"),
+    ("es", "es_ES"),
+    ("es_AR", "es_ES"),
+    ("fr", "fr_FR"),
+    ("it", "it_IT"),
+    ("ja", "ja_JP"),
+    ("ko", "ko_KR"),
+    ("nb", "no_NO"),
+    ("nl", "nl_NL"),
+    ("pl", "pl_PL"),
+    ("pt_BR", "pt_BR"),
+    ("pt_PT", "pt_PT"),
+    ("ru", "ru_RU"),
+    ("tr", "tr_TR"),
+    ("zh_CN", "zh_CN"),
+    ("zh_Hans_CN", "zh_CN"),
+    ("zh_HK", "zh_TW"),
+    ("zh_TW", "zh_TW"),
+];
+
+/// OPNsense locale -> pfSense locale.
+const OPNSENSE_TO_PFSENSE_LANGUAGE: &[(&str, &str)] = &[
+    ("en_US", "en_US"),
+    ("de_DE", "de_DE"),
+    ("es_ES", "es"),
+    ("fr_FR", "fr"),
+    ("it_IT", "it"),
+    ("ja_JP", "ja"),
+    ("ko_KR", "ko"),
+    ("no_NO", "nb"),
+    ("nl_NL", "nl"),
+    ("pl_PL", "pl"),
+    ("pt_BR", "pt_BR"),
+    ("pt_PT", "pt_PT"),
+    ("ru_RU", "ru"),
+    ("tr_TR", "tr"),
+    ("zh_CN", "zh_CN"),
+    ("zh_TW", "zh_TW"),
+];
+
+/// Zone names both platforms accept but list only as `Etc/UTC`.
+const UTC_ALIASES: &[&str] = &[
+    "UTC",
+    "UCT",
+    "GMT",
+    "GMT0",
+    "Greenwich",
+    "Universal",
+    "Zulu",
+    "Etc/UCT",
+    "Etc/GMT",
+    "Etc/GMT0",
+    "Etc/Universal",
+    "Etc/Zulu",
+];
+
+/// Convert `<system>` leaf settings for OPNsense output.
+///
+/// The `<system>` section arrives as a verbatim copy of the source; this pass
+/// rewrites the fields in [`SHARED_FIELDS`] and [`TO_OPNSENSE_FIELDS`] into
+/// OPNsense form. Users, groups, `<webgui>`, and `<ssh>` have their own
+/// transforms; other leaves are left as copied.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(
+        out,
+        source,
+        destination_baseline,
+        "opnsense",
+        TO_OPNSENSE_FIELDS,
+        diagnostics,
+    );
+}
+
+/// Convert `<system>` leaf settings for pfSense output.
+///
+/// Counterpart of [`to_opnsense`] using [`TO_PFSENSE_FIELDS`].
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(
+        out,
+        source,
+        destination_baseline,
+        "pfsense",
+        TO_PFSENSE_FIELDS,
+        diagnostics,
+    );
+}
+
+fn apply(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    platform: &str,
+    extra: &[SystemField],
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(src) = source.get_child("system") else {
+        return;
+    };
+    let Some(dst) = out.children.iter_mut().find(|n| n.tag == "system") else {
+        return;
+    };
+    let empty = XmlNode::new("system");
+    let baseline = destination_baseline.get_child("system").unwrap_or(&empty);
+
+    for field in SHARED_FIELDS.iter().chain(extra) {
+        match field.kind {
+            FieldKind::Copy => sync_all_children_by_tag(dst, src, field.tag),
+            FieldKind::Flag => set_flag(dst, field.tag, flag_set(src, field.tag), platform),
+            FieldKind::Baseline => sync_all_children_by_tag(dst, baseline, field.tag),
+            FieldKind::Drop(replacement) => {
+                let dropped: Vec<String> = src
+                    .get_children(field.tag)
+                    .into_iter()
+                    .filter_map(|c| c.text.as_deref().map(str::trim))
+                    .filter(|v| !v.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect();
+                dst.children.retain(|c| c.tag != field.tag);
+                if !dropped.is_empty() {
+                    diagnostics.warn(
+                        "system_settings",
+                        format!(
+                            "system {} '{}' not supported on {platform}; {replacement}",
+                            field.tag,
+                            dropped.join("', '")
+                        ),
+                    );
+                }
+            }
+            FieldKind::Timezone => {
+                let zone = text(src, "timezone")
+                    .or_else(|| text(baseline, "timezone"))
+                    .map(normalize_timezone);
+                set_text(dst, "timezone", zone.as_deref());
+            }
+            FieldKind::Language => {
+                let language = convert_language(src, baseline, platform, diagnostics);
+                set_text(dst, "language", language.as_deref());
+            }
+            FieldKind::Console => convert_console(dst, src, platform),
+            FieldKind::RamDisks => convert_ram_disks(dst, src, platform, diagnostics),
+        }
+    }
+}
+
+fn normalize_timezone(zone: &str) -> String {
+    if UTC_ALIASES.contains(&zone) {
+        "Etc/UTC".to_string()
+    } else {
+        zone.to_string()
+    }
+}
+
+/// Map the source language through the direction's table; unknown locales
+/// fall back to the baseline language with a warning.
+fn convert_language(
+    src: &XmlNode,
+    baseline: &XmlNode,
+    platform: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<String> {
+    let table = if platform == "opnsense" {
+        PFSENSE_TO_OPNSENSE_LANGUAGE
+    } else {
+        OPNSENSE_TO_PFSENSE_LANGUAGE
+    };
+    let fallback = text(baseline, "language").map(ToOwned::to_owned);
+    let language = text(src, "language")?;
+    if let Some((_, mapped)) = table.iter().find(|(from, _)| *from == language) {
+        return Some((*mapped).to_string());
+    }
+    diagnostics.warn(
+        "system_settings",
+        format!(
+            "system language '{language}' has no {platform} equivalent; using '{}'",
+            fallback.as_deref().unwrap_or("en_US")
+        ),
+    );
+    fallback.or_else(|| Some("en_US".to_string()))
+}
+
+/// Rebuild the console selection for `platform`.
+///
+/// pfSense enables the serial console with `<enableserial/>` and picks the
+/// preferred one with `<primaryconsole>` (`serial` or `video`). OPNsense
+/// lists a primary and an optional secondary console; `efi` counts as video
+/// on pfSense.
+fn convert_console(dst: &mut XmlNode, src: &XmlNode, platform: &str) {
+    dst.children.retain(|c| {
+        !matches!(
+            c.tag.as_str(),
+            "enableserial" | "primaryconsole" | "secondaryconsole"
+        )
+    });
+    if platform == "opnsense" {
+        let serial = flag_set(src, "enableserial");
+        let primary = match (serial, text(src, "primaryconsole")) {
+            (true, Some("video")) => "video",
+            (true, _) => "serial",
+            (false, _) => "video",
+        };
+        set_text(dst, "primaryconsole", Some(primary));
+        if serial {
+            let secondary = if primary == "serial" {
+                "video"
+            } else {
+                "serial"
+            };
+            set_text(dst, "secondaryconsole", Some(secondary));
+        }
+    } else {
+        dst.children.retain(|c| c.tag != "serialusb");
+        let primary = text(src, "primaryconsole").unwrap_or("video");
+        let secondary = text(src, "secondaryconsole").unwrap_or("");
+        if primary == "serial" || secondary == "serial" {
+            set_flag(dst, "enableserial", true, platform);
+            let preferred = if primary == "serial" {
+                "serial"
+            } else {
+                "video"
+            };
+            set_text(dst, "primaryconsole", Some(preferred));
+        }
+    }
+}
+
+/// Convert the `/tmp` and `/var` RAM disk switches.
+fn convert_ram_disks(
+    dst: &mut XmlNode,
+    src: &XmlNode,
+    platform: &str,
+    diagnostics: &mut Diagnostics,
+) {
+    dst.children.retain(|c| {
+        !matches!(
+            c.tag.as_str(),
+            "use_mfs_tmpvar" | "use_mfs_tmp" | "use_mfs_var"
+        )
+    });
+    if platform == "opnsense" {
+        if flag_set(src, "use_mfs_tmpvar") {
+            set_flag(dst, "use_mfs_tmp", true, platform);
+            set_flag(dst, "use_mfs_var", true, platform);
+        }
+        return;
+    }
+    let tmp = flag_set(src, "use_mfs_tmp");
+    let var = flag_set(src, "use_mfs_var");
+    if tmp || var {
+        set_flag(dst, "use_mfs_tmpvar", true, platform);
+    }
+    if tmp != var {
+        diagnostics.warn(
+            "system_settings",
+            "pfSense keeps /tmp and /var in RAM together; both are now RAM disks",
+        );
+    }
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Set `tag` to `value`, or remove it when `value` is `None`.
+fn set_text(node: &mut XmlNode, tag: &str, value: Option<&str>) {
+    let Some(value) = value else {
+        node.children.retain(|c| c.tag != tag);
+        return;
+    };
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+/// Copy all children with a given tag from src to dst, replacing any existing ones.
+///
+/// Used for fields that can appear multiple times (like `<dnsserver>`) as well
+/// as single-valued ones, so the destination exactly matches the source.
+fn sync_all_children_by_tag(dst: &mut XmlNode, src: &XmlNode, tag: &str) {
+    dst.children.retain(|c| c.tag != tag);
+    for child in &src.children {
+        if child.tag == tag {
+            dst.children.push(child.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::{parse, XmlNode};
+
+    use super::{
+        to_opnsense, to_pfsense, FieldKind, SystemField, SHARED_FIELDS, TO_OPNSENSE_FIELDS,
+        TO_PFSENSE_FIELDS,
+    };
+    use crate::diagnostics::Diagnostics;
+
+    fn convert(source: &str, baseline: &str) -> (XmlNode, Diagnostics) {
+        let source = parse(source.as_bytes()).expect("parse");
+        let baseline = parse(baseline.as_bytes()).expect("parse");
+        // The merge copies <system> verbatim from the source before this pass.
+        let mut out = baseline.clone();
+        out.children.retain(|c| c.tag != "system");
+        out.children.extend(source.get_child("system").cloned());
+        let mut diagnostics = Diagnostics::default();
+        if baseline.tag == "opnsense" {
+            to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+        } else {
+            to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+        }
+        (out, diagnostics)
+    }
+
+    fn system_values(out: &XmlNode, tag: &str) -> Vec<String> {
+        out.get_child("system")
+            .expect("system")
+            .get_children(tag)
+            .into_iter()
+            .map(|c| c.text.clone().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn copies_identity_and_dns_settings() {
+        let (out, diagnostics) = convert(
+            r#"<pfsense><system>
+                <hostname>gw-a</hostname><domain>example.net</domain>
+                <timeservers>1.1.1.1 2.2.2.2</timeservers>
+                <dnsallowoverride/>
+                <dnsserver>1.1.1.1</dnsserver><dnsserver>8.8.8.8</dnsserver>
+                <dns1gw>none</dns1gw><dns2gw>wan</dns2gw>
+            </system></pfsense>"#,
+            r#"<opnsense><system><hostname>dst</hostname><dnsallowoverride>0</dnsallowoverride><dnsserver>9.9.9.9</dnsserver></system></opnsense>"#,
+        );
+
+        assert_eq!(out.get_text(&["system", "hostname"]), Some("gw-a"));
+        assert_eq!(out.get_text(&["system", "domain"]), Some("example.net"));
+        assert_eq!(
+            out.get_text(&["system", "timeservers"]),
+            Some("1.1.1.1 2.2.2.2")
+        );
+        assert_eq!(out.get_text(&["system", "dnsallowoverride"]), Some("1"));
+        assert_eq!(out.get_text(&["system", "dns2gw"]), Some("wan"));
+        assert_eq!(system_values(&out, "dnsserver"), vec!["1.1.1.1", "8.8.8.8"]);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn flags_use_empty_elements_on_pfsense() {
+        let (out, _) = convert(
+            r#"<opnsense><system><dnsallowoverride>1</dnsallowoverride><powerd_enable>1</powerd_enable><disableconsolemenu>0</disableconsolemenu></system></opnsense>"#,
+            r#"<pfsense><system><disableconsolemenu/></system></pfsense>"#,
+        );
+        let system = out.get_child("system").expect("system");
+
+        assert_eq!(
+            system.get_child("dnsallowoverride").map(|c| &c.text),
+            Some(&None)
+        );
+        assert_eq!(
+            system.get_child("powerd_enable").map(|c| &c.text),
+            Some(&None)
+        );
+        assert!(system.get_child("disableconsolemenu").is_none());
+    }
+
+    #[test]
+    fn converts_serial_console_both_ways() {
+        let (out, _) = convert(
+            r#"<pfsense><system><enableserial/><primaryconsole>serial</primaryconsole><serialspeed>9600</serialspeed></system></pfsense>"#,
+            r#"<opnsense><system><primaryconsole>video</primaryconsole><serialusb>1</serialusb></system></opnsense>"#,
+        );
+        assert_eq!(out.get_text(&["system", "primaryconsole"]), Some("serial"));
+        assert_eq!(out.get_text(&["system", "secondaryconsole"]), Some("video"));
+        assert_eq!(out.get_text(&["system", "serialspeed"]), Some("9600"));
+        assert_eq!(out.get_text(&["system", "serialusb"]), Some("1"));
+        assert!(out
+            .get_child("system")
+            .and_then(|s| s.get_child("enableserial"))
+            .is_none());
+
+        let (out, _) = convert(
+            r#"<opnsense><system><primaryconsole>efi</primaryconsole><secondaryconsole>serial</secondaryconsole><serialusb>1</serialusb></system></opnsense>"#,
+            r#"<pfsense><system/></pfsense>"#,
+        );
+        let system = out.get_child("system").expect("system");
+        assert!(system.get_child("enableserial").is_some());
+        assert_eq!(system.get_text(&["primaryconsole"]), Some("video"));
+        assert!(system.get_child("secondaryconsole").is_none());
+        assert!(system.get_child("serialusb").is_none());
+    }
+
+    #[test]
+    fn maps_language_and_timezone() {
+        let (out, diagnostics) = convert(
+            r#"<pfsense><system><language>zh_Hans_CN</language><timezone>UTC</timezone></system></pfsense>"#,
+            r#"<opnsense><system><language>en_US</language></system></opnsense>"#,
+        );
+        assert_eq!(out.get_text(&["system", "language"]), Some("zh_CN"));
+        assert_eq!(out.get_text(&["system", "timezone"]), Some("Etc/UTC"));
+        assert!(diagnostics.is_empty());
+
+        let (out, diagnostics) = convert(
+            r#"<opnsense><system><language>uk_UA</language></system></opnsense>"#,
+            r#"<pfsense><system><language>en_US</language><timezone>Europe/Berlin</timezone></system></pfsense>"#,
+        );
+        assert_eq!(out.get_text(&["system", "language"]), Some("en_US"));
+        assert_eq!(out.get_text(&["system", "timezone"]), Some("Europe/Berlin"));
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn converts_ram_disks_and_drops_boot_commands() {
+        let (out, diagnostics) = convert(
+            r#"<pfsense><system><use_mfs_tmpvar/><use_mfs_tmp_size>512</use_mfs_tmp_size><earlyshellcmd>echo a</earlyshellcmd><earlyshellcmd>echo b</earlyshellcmd></system></pfsense>"#,
+            r#"<opnsense><system/></opnsense>"#,
+        );
+        let system = out.get_child("system").expect("system");
+        assert_eq!(system.get_text(&["use_mfs_tmp"]), Some("1"));
+        assert_eq!(system.get_text(&["use_mfs_var"]), Some("1"));
+        for dropped in ["use_mfs_tmpvar", "use_mfs_tmp_size", "earlyshellcmd"] {
+            assert!(system.get_child(dropped).is_none(), "{dropped} kept");
+        }
+        assert_eq!(diagnostics.len(), 2);
+
+        let (out, diagnostics) = convert(
+            r#"<opnsense><system><use_mfs_var>1</use_mfs_var></system></opnsense>"#,
+            r#"<pfsense><system/></pfsense>"#,
+        );
+        assert!(out
+            .get_child("system")
+            .and_then(|s| s.get_child("use_mfs_tmpvar"))
+            .is_some());
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    /// Every table entry must be exercised by a conversion in its direction.
+    #[test]
+    fn every_table_field_is_converted() {
+        fn check(fields: &[SystemField], source_root: &str, target_root: &str) {
+            for field in fields {
+                let value = match field.kind {
+                    FieldKind::Language => "en_US",
+                    FieldKind::Timezone => "Europe/Paris",
+                    FieldKind::Console => "serial",
+                    _ => "1",
+                };
+                // OPNsense spells the RAM disk switch per directory.
+                let source_tag = match field.kind {
+                    FieldKind::RamDisks if source_root == "opnsense" => "use_mfs_tmp",
+                    _ => field.tag,
+                };
+                let source = format!(
+                    "<{source_root}><system><{source_tag}>{value}</{source_tag}></system></{source_root}>"
+                );
+                let baseline = format!(
+                    "<{target_root}><system><{tag}>{value}</{tag}></system></{target_root}>",
+                    tag = field.tag
+                );
+                let (out, diagnostics) = convert(&source, &baseline);
+                let system = out.get_child("system").expect("system");
+                let kept = system.get_child(field.tag).is_some();
+                match field.kind {
+                    FieldKind::Drop(_) => {
+                        assert!(!kept, "{} kept", field.tag);
+                        assert_eq!(diagnostics.len(), 1, "{} not reported", field.tag);
+                    }
+                    FieldKind::RamDisks if target_root == "opnsense" => {
+                        assert!(system.get_child("use_mfs_tmp").is_some());
+                    }
+                    _ => assert!(kept, "{} lost", field.tag),
+                }
+            }
+        }
+
+        check(SHARED_FIELDS, "pfsense", "opnsense");
+        check(TO_OPNSENSE_FIELDS, "pfsense", "opnsense");
+        check(SHARED_FIELDS, "opnsense", "pfsense");
+        check(TO_PFSENSE_FIELDS, "opnsense", "pfsense");
+    }
+}
@@ -1,5 +1,8 @@
 use xml_diff_core::XmlNode;
 
+use crate::diagnostics::Diagnostics;
+use crate::transform::booleans::flag_set;
+
 /// Convert user accounts from pfSense to OPNsense format.
 ///
 /// Key differences:
@@ -12,9 +15,17 @@ use xml_diff_core::XmlNode;
 /// 1. Maps the source "admin" user to target "root" user with password conversion
 /// 2. Copies all other GUI users (users with page-* privileges)
 /// 3. Removes the old "admin" user to avoid duplicates
-pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, _destination_baseline: &XmlNode) {
+///
+/// UID collisions between source and destination users are reported to
+/// `diagnostics`.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
     map_login_user(out, source, "admin", "root", "password");
-    preserve_gui_users(out, source, "password");
+    preserve_gui_users(out, source, "password", diagnostics);
     remove_user_by_name(out, "admin");
 }
 
@@ -30,9 +41,17 @@ pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, _destination_baseline: &
 /// 1. Maps the source "root" user to target "admin" user with password conversion
 /// 2. Copies all other GUI users (users with page-* privileges)
 /// 3. Removes the old "root" user to avoid duplicates
-pub fn to_pfsense(out: &mut XmlNode, source: &XmlNode, _destination_baseline: &XmlNode) {
+///
+/// UID collisions between source and destination users are reported to
+/// `diagnostics`.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    _destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
     map_login_user(out, source, "root", "admin", "bcrypt-hash");
-    preserve_gui_users(out, source, "bcrypt-hash");
+    preserve_gui_users(out, source, "bcrypt-hash", diagnostics);
     remove_user_by_name(out, "root");
 }
 
@@ -253,7 +272,12 @@ struct GuiUser {
 /// 2. Matches them to existing users in the output by UID or username
 /// 3. Updates or creates users as needed
 /// 4. Converts credential tags to match the target platform
-fn preserve_gui_users(out: &mut XmlNode, source: &XmlNode, target_credential_tag: &str) {
+fn preserve_gui_users(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    target_credential_tag: &str,
+    diagnostics: &mut Diagnostics,
+) {
     let gui_users = collect_gui_users(source);
     if gui_users.is_empty() {
         return;
@@ -263,7 +287,7 @@ fn preserve_gui_users(out: &mut XmlNode, source: &XmlNode, target_credential_tag
     };
 
     for gui_user in gui_users {
-        apply_gui_user(system_out, &gui_user, target_credential_tag);
+        apply_gui_user(system_out, &gui_user, target_credential_tag, diagnostics);
     }
 }
 
@@ -271,7 +295,7 @@ fn preserve_gui_users(out: &mut XmlNode, source: &XmlNode, target_credential_tag
 ///
 /// A GUI user is one that:
 /// - Has UID != 0 (not the root admin)
-/// - Is enabled (no <disabled>1</disabled>)
+/// - Is enabled (no set `<disabled>` flag)
 /// - Has GUI privileges (page-* privileges or is in the admins group)
 ///
 /// Returns a sanitized version of each user with only fields safe to transfer.
@@ -326,16 +350,24 @@ fn collect_gui_users(root: &XmlNode) -> Vec<GuiUser> {
 /// 1. Try to match by UID (most reliable, since UIDs should be stable)
 /// 2. Fall back to matching by username
 /// 3. If no match, create a new user
-fn apply_gui_user(system_out: &mut XmlNode, gui_user: &GuiUser, target_credential_tag: &str) {
+fn apply_gui_user(
+    system_out: &mut XmlNode,
+    gui_user: &GuiUser,
+    target_credential_tag: &str,
+    diagnostics: &mut Diagnostics,
+) {
     // Try matching by UID first (most reliable)
     if let Some(uid) = gui_user.uid.as_deref() {
         if uid != "0" {
             if let Some(dest_user) = find_user_by_uid_mut(system_out, uid) {
                 // Found a UID match. If the name differs, warn about collision.
                 if !names_equal(dest_user, &gui_user.name) {
-                    eprintln!(
-                        "warning: UID collision for GUI user {} (uid {}); falling back to name match",
-                        gui_user.name, uid
+                    diagnostics.warn(
+                        "system_users",
+                        format!(
+                            "UID collision for GUI user {} (uid {}); falling back to name match",
+                            gui_user.name, uid
+                        ),
                     );
                 }
                 update_gui_user(dest_user, gui_user, target_credential_tag);
@@ -407,11 +439,10 @@ fn copy_field(dest: &mut XmlNode, source: &XmlNode, tag: &str) {
 
 /// Check if a user is enabled (not disabled).
 ///
-/// Users are enabled by default unless they have <disabled>1</disabled>.
+/// Users are enabled unless they carry a set `<disabled>` flag (pfSense
+/// presence tag or OPNsense `1`).
 fn is_enabled(user: &XmlNode) -> bool {
-    user.get_text(&["disabled"])
-        .map(|v| !matches!(v.trim(), "1"))
-        .unwrap_or(true) // Default to enabled if no disabled field
+    !flag_set(user, "disabled")
 }
 
 /// Check if a user has GUI/web interface privileges.
@@ -504,6 +535,7 @@ fn names_equal(user: &XmlNode, name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
     use xml_diff_core::parse;
 
     #[test]
@@ -518,7 +550,7 @@ mod tests {
         .expect("parse");
         let baseline = out.clone();
 
-        to_opnsense(&mut out, &source, &baseline);
+        to_opnsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let root_user = out
             .get_child("system")
             .and_then(|s| {
@@ -548,7 +580,7 @@ mod tests {
         .expect("parse");
         let baseline = out.clone();
 
-        to_pfsense(&mut out, &source, &baseline);
+        to_pfsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let admin_user = out
             .get_child("system")
             .and_then(|s| {
@@ -578,7 +610,7 @@ mod tests {
         .expect("parse");
         let baseline = out.clone();
 
-        to_opnsense(&mut out, &source, &baseline);
+        to_opnsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let root_user = out
             .get_child("system")
             .and_then(|s| {
@@ -602,7 +634,7 @@ mod tests {
         .expect("parse");
         let baseline = out.clone();
 
-        to_opnsense(&mut out, &source, &baseline);
+        to_opnsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let user = out
             .get_child("system")
             .and_then(|s| s.children.iter().find(|u| u.tag == "user"))
@@ -622,7 +654,7 @@ mod tests {
         .expect("parse");
         let baseline = out.clone();
 
-        to_opnsense(&mut out, &source, &baseline);
+        to_opnsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let root_user = out
             .get_child("system")
             .and_then(|s| {
@@ -645,7 +677,7 @@ mod tests {
                 .expect("out parse");
         let baseline = out.clone();
 
-        to_opnsense(&mut out, &source, &baseline);
+        to_opnsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let system = out.get_child("system").expect("system");
         let gui_user = system
             .children
@@ -670,7 +702,7 @@ mod tests {
                 .expect("out parse");
         let baseline = out.clone();
 
-        to_opnsense(&mut out, &source, &baseline);
+        to_opnsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let system = out.get_child("system").expect("system");
         assert!(system
             .children
@@ -690,7 +722,7 @@ mod tests {
         .expect("out parse");
         let baseline = out.clone();
 
-        to_opnsense(&mut out, &source, &baseline);
+        to_opnsense(&mut out, &source, &baseline, &mut Diagnostics::new());
         let system = out.get_child("system").expect("system");
         let gui_user = system
             .children
@@ -707,4 +739,25 @@ mod tests {
             1
         );
     }
+
+    #[test]
+    fn reports_uid_collision_diagnostic() {
+        let source = parse(
+            br#"<pfsense><system><user><name>webuser</name><uid>5</uid><priv>page-all</priv><password>GUI_PASS</password></user></system></pfsense>"#,
+        )
+        .expect("source parse");
+        let mut out = parse(
+            br#"<opnsense><system><user><name>other</name><uid>5</uid><password>OLD</password></user></system></opnsense>"#,
+        )
+        .expect("out parse");
+        let baseline = out.clone();
+
+        let mut diagnostics = Diagnostics::new();
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["UID collision for GUI user webuser (uid 5); falling back to name match"]
+        );
+    }
 }
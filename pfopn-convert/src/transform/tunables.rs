@@ -0,0 +1,254 @@
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+use crate::tunable_map::{default_tunable_map, TunableCompat};
+use crate::uuid_registry::UuidRegistry;
+
+/// Convert system tunables (`<sysctl>`) for OPNsense output.
+///
+/// OPNsense stores tunables as an MVC model, so every item gets a stable
+/// `uuid` attribute. See [`apply`] for the merge rules.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(out, source, destination_baseline, "opnsense", diagnostics);
+    let mut registry = UuidRegistry::from_config(out);
+    if let Some(sysctl) = child_mut(out, "sysctl") {
+        registry.assign_missing(sysctl, "item", "tunable", |_, item| {
+            item.get_text(&["tunable"]).unwrap_or("").to_string()
+        });
+    }
+}
+
+/// Convert system tunables (`<sysctl>`) for pfSense output.
+///
+/// pfSense items carry no `uuid` attributes. See [`apply`] for the merge rules.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(out, source, destination_baseline, "pfsense", diagnostics);
+    if let Some(sysctl) = child_mut(out, "sysctl") {
+        sysctl.attributes.clear();
+        for item in sysctl.children.iter_mut().filter(|c| c.tag == "item") {
+            item.attributes.remove("uuid");
+        }
+    }
+}
+
+/// Merge source tunables into the target `<sysctl>` section.
+///
+/// Starts from the destination baseline's tunables (the platform defaults)
+/// and looks each source tunable up in the compatibility table:
+/// - Supported: the value overrides the target item with the (possibly
+///   renamed) tunable, or a new item is appended
+/// - Unsupported: dropped with a warning
+/// - Unknown: copied unchanged with a warning to verify it on the target
+fn apply(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    target: &str,
+    diagnostics: &mut Diagnostics,
+) {
+    let source_items = tunable_items(source.get_child("sysctl"));
+    if source_items.is_empty() {
+        return;
+    }
+    let map = default_tunable_map();
+
+    let mut sysctl = destination_baseline
+        .get_child("sysctl")
+        .cloned()
+        .unwrap_or_else(|| XmlNode::new("sysctl"));
+    sysctl
+        .children
+        .retain(|c| c.tag != "item" || item_tunable(c).is_some());
+
+    for item in source_items {
+        let Some(name) = item_tunable(item) else {
+            continue;
+        };
+        let target_name = match map.translate(name, target) {
+            TunableCompat::Supported(target_name) => {
+                if target_name != name {
+                    diagnostics.info("tunables", format!("{name} renamed to {target_name}"));
+                }
+                target_name
+            }
+            TunableCompat::Unsupported(note) => {
+                let reason = note.map_or_else(String::new, |n| format!(" ({n})"));
+                diagnostics.warn(
+                    "tunables",
+                    format!("{name} does not exist on {target}{reason}; dropped"),
+                );
+                continue;
+            }
+            TunableCompat::Unknown => {
+                diagnostics.warn(
+                    "tunables",
+                    format!(
+                        "{name} is not in the tunable compatibility table; copied unchanged, verify it exists on {target}"
+                    ),
+                );
+                name
+            }
+        };
+
+        let existing = sysctl
+            .children
+            .iter_mut()
+            .find(|c| c.tag == "item" && item_tunable(c) == Some(target_name));
+        match existing {
+            Some(existing) => {
+                set_text(existing, "value", item.get_text(&["value"]).unwrap_or(""));
+                if let Some(descr) = item.get_text(&["descr"]).filter(|d| !d.trim().is_empty()) {
+                    set_text(existing, "descr", descr);
+                }
+            }
+            None => {
+                let mut new_item = XmlNode::new("item");
+                for field in ["tunable", "value", "descr"] {
+                    let mut child = XmlNode::new(field);
+                    child.text = match field {
+                        "tunable" => Some(target_name.to_string()),
+                        _ => item.get_text(&[field]).map(ToString::to_string),
+                    };
+                    new_item.children.push(child);
+                }
+                sysctl.children.push(new_item);
+            }
+        }
+    }
+
+    match child_mut(out, "sysctl") {
+        Some(existing) => *existing = sysctl,
+        None => {
+            let insert_at = out
+                .children
+                .iter()
+                .position(|c| c.tag == "system")
+                .map_or(out.children.len(), |idx| idx + 1);
+            out.children.insert(insert_at, sysctl);
+        }
+    }
+}
+
+/// Non-empty `<item>` children of a `<sysctl>` section.
+fn tunable_items(sysctl: Option<&XmlNode>) -> Vec<&XmlNode> {
+    sysctl
+        .map(|s| s.get_children("item"))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| item_tunable(item).is_some())
+        .collect()
+}
+
+fn item_tunable(item: &XmlNode) -> Option<&str> {
+    item.get_text(&["tunable"])
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
+    let idx = node.children.iter().position(|c| c.tag == tag)?;
+    Some(&mut node.children[idx])
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn merges_pfsense_tunables_into_opnsense_defaults() {
+        let source = parse(
+            br#"<pfsense><sysctl><item><tunable>net.inet.tcp.tso</tunable><value>0</value><descr>Disable TSO</descr></item><item><tunable>hw.syscons.kbd_reboot</tunable><value>1</value><descr/></item><item><tunable>net.inet.ip.fastforwarding</tunable><value>1</value><descr/></item><item><tunable>dev.example.custom</tunable><value>7</value><descr/></item></sysctl></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(
+            br#"<opnsense><system/><sysctl version="1.0.2"><item uuid="keep"><tunable>net.inet.tcp.tso</tunable><value>default</value><descr>TSO</descr></item><item/></sysctl></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let sysctl = out.get_child("sysctl").expect("sysctl");
+        assert_eq!(
+            sysctl.attributes.get("version").map(String::as_str),
+            Some("1.0.2")
+        );
+        let items = sysctl.get_children("item");
+        let names: Vec<_> = items
+            .iter()
+            .map(|i| i.get_text(&["tunable"]).unwrap_or(""))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "net.inet.tcp.tso",
+                "kern.vt.kbd_reboot",
+                "dev.example.custom"
+            ]
+        );
+        assert_eq!(items[0].get_text(&["value"]), Some("0"));
+        assert_eq!(items[0].get_text(&["descr"]), Some("Disable TSO"));
+        assert_eq!(
+            items[0].attributes.get("uuid").map(String::as_str),
+            Some("keep")
+        );
+        assert!(items.iter().all(|i| i.attributes.contains_key("uuid")));
+
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("hw.syscons.kbd_reboot renamed")));
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("net.inet.ip.fastforwarding does not exist on opnsense")));
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("dev.example.custom is not in the tunable")));
+    }
+
+    #[test]
+    fn converts_opnsense_tunables_to_pfsense_without_uuid() {
+        let source = parse(
+            br#"<opnsense><sysctl version="1.0.2"><item uuid="a"><tunable>net.pf.share_forward</tunable><value>1</value><descr/></item><item uuid="b"><tunable>kern.ipc.maxsockbuf</tunable><value>4262144</value><descr/></item></sysctl></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<pfsense><system/><interfaces/></pfsense>"#).expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        assert_eq!(out.children[1].tag, "sysctl");
+        let sysctl = &out.children[1];
+        assert!(sysctl.attributes.is_empty());
+        let items = sysctl.get_children("item");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].get_text(&["tunable"]), Some("kern.ipc.maxsockbuf"));
+        assert!(items[0].attributes.is_empty());
+        assert_eq!(diagnostics.iter().count(), 1);
+    }
+}
@@ -0,0 +1,307 @@
+use std::fmt;
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+
+/// Unit a numeric config value is stored in.
+///
+/// Rates are per second; shaper bandwidth uses decimal prefixes on both
+/// platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+    Bits,
+    Kilobits,
+    Megabits,
+    Gigabits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Time,
+    Rate,
+}
+
+impl Unit {
+    fn dimension(self) -> Dimension {
+        match self {
+            Unit::Milliseconds | Unit::Seconds | Unit::Minutes | Unit::Hours => Dimension::Time,
+            Unit::Bits | Unit::Kilobits | Unit::Megabits | Unit::Gigabits => Dimension::Rate,
+        }
+    }
+
+    /// Size of one unit in milliseconds (time) or bit/s (rate).
+    fn scale(self) -> u64 {
+        match self {
+            Unit::Milliseconds | Unit::Bits => 1,
+            Unit::Seconds | Unit::Kilobits => 1_000,
+            Unit::Minutes => 60_000,
+            Unit::Hours => 3_600_000,
+            Unit::Megabits => 1_000_000,
+            Unit::Gigabits => 1_000_000_000,
+        }
+    }
+
+    /// Parse a shaper bandwidth unit: pfSense `bandwidthtype` (`Kb`) or
+    /// OPNsense `bandwidthMetric` (`Kbit`).
+    pub fn parse_rate(token: &str) -> Option<Unit> {
+        match token.trim() {
+            "b" | "bit" => Some(Unit::Bits),
+            "Kb" | "Kbit" => Some(Unit::Kilobits),
+            "Mb" | "Mbit" => Some(Unit::Megabits),
+            "Gb" | "Gbit" => Some(Unit::Gigabits),
+            _ => None,
+        }
+    }
+
+    /// Shaper bandwidth unit token as written by `platform`.
+    pub fn rate_token(self, platform: &str) -> Option<&'static str> {
+        let opnsense = platform == "opnsense";
+        match self {
+            Unit::Bits => Some(if opnsense { "bit" } else { "b" }),
+            Unit::Kilobits => Some(if opnsense { "Kbit" } else { "Kb" }),
+            Unit::Megabits => Some(if opnsense { "Mbit" } else { "Mb" }),
+            Unit::Gigabits => Some(if opnsense { "Gbit" } else { "Gb" }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Unit::Milliseconds => "ms",
+            Unit::Seconds => "s",
+            Unit::Minutes => "min",
+            Unit::Hours => "h",
+            Unit::Bits => "bit/s",
+            Unit::Kilobits => "Kbit/s",
+            Unit::Megabits => "Mbit/s",
+            Unit::Gigabits => "Gbit/s",
+        })
+    }
+}
+
+/// Units of one field on each platform.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldUnit {
+    pub field: &'static str,
+    pub pfsense: Unit,
+    pub opnsense: Unit,
+}
+
+impl FieldUnit {
+    const fn new(field: &'static str, pfsense: Unit, opnsense: Unit) -> Self {
+        Self {
+            field,
+            pfsense,
+            opnsense,
+        }
+    }
+
+    /// Unit the field is stored in on `platform`.
+    pub fn unit(&self, platform: &str) -> Unit {
+        if platform == "opnsense" {
+            self.opnsense
+        } else {
+            self.pfsense
+        }
+    }
+}
+
+/// Gateway monitoring (dpinger) timings in `<gateway_item>`: pfSense stores
+/// milliseconds, OPNsense seconds.
+pub const GATEWAY_MONITOR: &[FieldUnit] = &[
+    FieldUnit::new("interval", Unit::Milliseconds, Unit::Seconds),
+    FieldUnit::new("loss_interval", Unit::Milliseconds, Unit::Seconds),
+    FieldUnit::new("time_period", Unit::Milliseconds, Unit::Seconds),
+    FieldUnit::new("alert_interval", Unit::Milliseconds, Unit::Seconds),
+];
+
+/// IPsec SA lifetimes and DPD delay: seconds in pfSense phase1/phase2 and in
+/// swanctl connections and children.
+pub const IPSEC_LIFETIMES: &[FieldUnit] = &[
+    FieldUnit::new("lifetime", Unit::Seconds, Unit::Seconds),
+    FieldUnit::new("rekey_time", Unit::Seconds, Unit::Seconds),
+    FieldUnit::new("reauth_time", Unit::Seconds, Unit::Seconds),
+    FieldUnit::new("over_time", Unit::Seconds, Unit::Seconds),
+    FieldUnit::new("dpd_delay", Unit::Seconds, Unit::Seconds),
+];
+
+/// Look up a field in a unit table.
+pub fn field_unit(table: &[FieldUnit], field: &str) -> Option<FieldUnit> {
+    table.iter().find(|f| f.field == field).copied()
+}
+
+/// A converted value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converted {
+    pub value: String,
+    /// False when the value was rounded to fit the target unit.
+    pub exact: bool,
+}
+
+/// Convert a whole-number `value` from one unit to another.
+///
+/// Results are rounded to the nearest whole target unit; a positive value
+/// never rounds down to zero, which both platforms read as "use the
+/// default". Returns `None` for non-numeric values, overflow, or units of
+/// different dimensions.
+pub fn convert(value: &str, from: Unit, to: Unit) -> Option<Converted> {
+    if from.dimension() != to.dimension() {
+        return None;
+    }
+    let amount: u64 = value.trim().parse().ok()?;
+    let base = amount.checked_mul(from.scale())?;
+    let (quotient, remainder) = (base / to.scale(), base % to.scale());
+    let mut converted = quotient + u64::from(remainder * 2 >= to.scale());
+    if converted == 0 && amount > 0 {
+        converted = 1;
+    }
+    Some(Converted {
+        value: converted.to_string(),
+        exact: remainder == 0,
+    })
+}
+
+/// Convert the `table` fields of `node` in place from the other platform's
+/// units into `to`'s.
+///
+/// Empty fields are left alone. Rounded values and values that are not whole
+/// numbers are reported under `section`, with `label` naming the object.
+pub fn convert_children(
+    node: &mut XmlNode,
+    table: &[FieldUnit],
+    to: &str,
+    section: &str,
+    label: &str,
+    diagnostics: &mut Diagnostics,
+) {
+    let from = if to == "opnsense" {
+        "pfsense"
+    } else {
+        "opnsense"
+    };
+    for child in &mut node.children {
+        let Some(field) = field_unit(table, &child.tag) else {
+            continue;
+        };
+        let Some(raw) = child
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        else {
+            continue;
+        };
+        let (src_unit, dst_unit) = (field.unit(from), field.unit(to));
+        match convert(raw, src_unit, dst_unit) {
+            Some(converted) => {
+                if !converted.exact {
+                    diagnostics.warn(
+                        section,
+                        format!(
+                            "{label}: {} {raw} {src_unit} rounded to {} {dst_unit}",
+                            field.field, converted.value
+                        ),
+                    );
+                }
+                child.text = Some(converted.value);
+            }
+            None => diagnostics.warn(
+                section,
+                format!(
+                    "{label}: {} '{raw}' is not a whole number of {src_unit}; copied unchanged",
+                    field.field
+                ),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{convert, convert_children, Unit, GATEWAY_MONITOR};
+    use crate::diagnostics::{Diagnostics, Severity};
+
+    #[test]
+    fn converts_between_time_and_rate_units() {
+        let seconds = convert("2000", Unit::Milliseconds, Unit::Seconds).expect("convert");
+        assert_eq!(seconds.value, "2");
+        assert!(seconds.exact);
+        assert_eq!(
+            convert("4", Unit::Seconds, Unit::Milliseconds)
+                .expect("convert")
+                .value,
+            "4000"
+        );
+        assert_eq!(
+            convert("90", Unit::Minutes, Unit::Hours)
+                .expect("convert")
+                .value,
+            "2"
+        );
+        assert_eq!(
+            convert("1500", Unit::Kilobits, Unit::Megabits)
+                .expect("convert")
+                .value,
+            "2"
+        );
+        assert!(convert("10", Unit::Seconds, Unit::Megabits).is_none());
+        assert!(convert("fast", Unit::Seconds, Unit::Minutes).is_none());
+    }
+
+    #[test]
+    fn positive_values_never_round_to_zero() {
+        let converted = convert("250", Unit::Milliseconds, Unit::Seconds).expect("convert");
+        assert_eq!(converted.value, "1");
+        assert!(!converted.exact);
+        assert_eq!(
+            convert("0", Unit::Milliseconds, Unit::Seconds)
+                .expect("convert")
+                .value,
+            "0"
+        );
+    }
+
+    #[test]
+    fn parses_and_writes_shaper_rate_tokens() {
+        assert_eq!(Unit::parse_rate("Mb"), Some(Unit::Megabits));
+        assert_eq!(Unit::parse_rate("Mbit"), Some(Unit::Megabits));
+        assert_eq!(Unit::Kilobits.rate_token("opnsense"), Some("Kbit"));
+        assert_eq!(Unit::Kilobits.rate_token("pfsense"), Some("Kb"));
+        assert_eq!(Unit::Seconds.rate_token("pfsense"), None);
+    }
+
+    #[test]
+    fn converts_table_fields_and_reports_rounding() {
+        let mut item = parse(
+            br#"<gateway_item><name>WAN_DHCP</name><interval>500</interval><time_period>60000</time_period><loss_interval/><weight>1</weight></gateway_item>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::default();
+
+        convert_children(
+            &mut item,
+            GATEWAY_MONITOR,
+            "opnsense",
+            "gateways",
+            "gateway WAN_DHCP",
+            &mut diagnostics,
+        );
+
+        assert_eq!(item.get_text(&["interval"]), Some("1"));
+        assert_eq!(item.get_text(&["time_period"]), Some("60"));
+        assert_eq!(item.get_text(&["weight"]), Some("1"));
+        assert_eq!(diagnostics.count(Severity::Warning), 1);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message.contains("interval 500 ms rounded to 1 s")));
+    }
+}
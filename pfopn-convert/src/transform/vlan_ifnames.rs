@@ -2,6 +2,8 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use xml_diff_core::XmlNode;
 
+use crate::uuid_registry::UuidRegistry;
+
 /// Normalize VLAN interface device names for OPNsense.
 ///
 /// **Problem:** pfSense and OPNsense handle VLAN naming differently:
@@ -30,6 +32,7 @@ use xml_diff_core::XmlNode;
 /// <vlans><vlan uuid="..."><if>vtnet0</if><tag>50</tag><vlanif>vlan01</vlanif><pcp>0</pcp><proto/><descr/></vlan></vlans>
 /// ```
 pub fn normalize_opnsense_vlan_ifnames(root: &mut XmlNode) {
+    let mut registry = UuidRegistry::from_config(root);
     let Some(vlans) = child_mut(root, "vlans") else {
         return;
     };
@@ -50,7 +53,9 @@ pub fn normalize_opnsense_vlan_ifnames(root: &mut XmlNode) {
 
         // Check if this VLAN already has a valid vlanif name
         let current_vlanif = text_of(vlan, "vlanif").unwrap_or_default();
-        let vlanif = if current_vlanif.starts_with("vlan") && current_vlanif.len() >= 5 {
+        let vlanif = if (current_vlanif.starts_with("vlan") || current_vlanif.starts_with("qinq"))
+            && current_vlanif.len() >= 5
+        {
             current_vlanif // Keep existing vlanif name
         } else {
             next_vlanif_name(&used) // Generate a new one
@@ -60,7 +65,8 @@ pub fn normalize_opnsense_vlan_ifnames(root: &mut XmlNode) {
         set_or_insert_text_child(vlan, "vlanif", &vlanif);
 
         // Add OPNsense-specific metadata (uuid, pcp, proto, descr)
-        ensure_vlan_opnsense_shape(vlan, idx_seed(&vlanif, &parent, &tag));
+        let uuid = registry.uuid_for("vlan", &format!("{vlanif}|{parent}|{tag}"));
+        ensure_vlan_opnsense_shape(vlan, uuid);
 
         used.insert(vlanif.clone());
         dotted_to_vlanif.insert(dotted, vlanif);
@@ -100,7 +106,7 @@ fn collect_used_vlanif(vlans: &XmlNode) -> BTreeSet<String> {
         .iter()
         .filter(|n| n.tag == "vlan")
         .filter_map(|v| text_of(v, "vlanif"))
-        .filter(|name| name.starts_with("vlan"))
+        .filter(|name| name.starts_with("vlan") || name.starts_with("qinq"))
         .collect()
 }
 
@@ -144,15 +150,18 @@ fn set_or_insert_text_child(node: &mut XmlNode, tag: &str, value: &str) {
 ///
 /// OPNsense expects:
 /// - uuid attribute (for tracking in the UI)
-/// - <pcp> (Priority Code Point, usually 0)
+/// - <pcp> (Priority Code Point; kept when set, otherwise 0)
 /// - <proto> (protocol/ethertype, usually empty)
 /// - <descr> (description, usually empty)
-fn ensure_vlan_opnsense_shape(vlan: &mut XmlNode, seed: usize) {
-    if !vlan.attributes.contains_key("uuid") {
-        vlan.attributes
-            .insert("uuid".to_string(), stable_uuid(seed));
-    }
+fn ensure_vlan_opnsense_shape(vlan: &mut XmlNode, uuid: String) {
+    vlan.attributes.entry("uuid".to_string()).or_insert(uuid);
     ensure_child(vlan, "pcp", "0");
+    // pfSense leaves PCP empty when unset; OPNsense requires a priority.
+    if let Some(pcp) = vlan.children.iter_mut().find(|c| c.tag == "pcp") {
+        if pcp.text.as_deref().map(str::trim).unwrap_or("").is_empty() {
+            pcp.text = Some("0".to_string());
+        }
+    }
     ensure_child(vlan, "proto", "");
     ensure_child(vlan, "descr", "");
 }
@@ -167,56 +176,6 @@ fn ensure_child(node: &mut XmlNode, tag: &str, default_value: &str) {
     node.children.push(child);
 }
 
-/// Generate a hash seed from VLAN properties for deterministic UUID generation.
-fn idx_seed(vlanif: &str, parent: &str, tag: &str) -> usize {
-    let mut s: usize = 0;
-    for b in vlanif.bytes().chain(parent.bytes()).chain(tag.bytes()) {
-        s = s.wrapping_mul(131).wrapping_add(b as usize);
-    }
-    s
-}
-
-/// Generate a stable UUID v4 from a seed value.
-///
-/// Uses a simple LCG (Linear Congruential Generator) to produce deterministic
-/// but pseudo-random-looking UUIDs. The same seed always produces the same UUID.
-fn stable_uuid(seed: usize) -> String {
-    let mut acc = [0u8; 16];
-    let mut x = seed as u64;
-
-    // Fill 16 bytes using LCG
-    for (i, a) in acc.iter_mut().enumerate() {
-        x = x
-            .wrapping_mul(6364136223846793005)
-            .wrapping_add(1 + i as u64);
-        *a = (x >> ((i % 8) * 8)) as u8;
-    }
-
-    // Set UUID version 4 bits (0x40 in byte 6, 0x80-0xBF in byte 8)
-    acc[6] = (acc[6] & 0x0f) | 0x40;
-    acc[8] = (acc[8] & 0x3f) | 0x80;
-
-    format!(
-        "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-        acc[0],
-        acc[1],
-        acc[2],
-        acc[3],
-        acc[4],
-        acc[5],
-        acc[6],
-        acc[7],
-        acc[8],
-        acc[9],
-        acc[10],
-        acc[11],
-        acc[12],
-        acc[13],
-        acc[14],
-        acc[15]
-    )
-}
-
 #[cfg(test)]
 mod tests {
     use xml_diff_core::parse;
@@ -258,6 +217,26 @@ mod tests {
         assert_eq!(root.get_text(&["interfaces", "opt3", "if"]), Some("vlan07"));
     }
 
+    #[test]
+    fn keeps_pcp_and_qinq_device_names() {
+        let mut root = parse(
+            br#"<opnsense>
+                <vlans>
+                    <vlan><if>vtnet0</if><tag>50</tag><pcp>5</pcp><vlanif>vtnet0.50</vlanif></vlan>
+                    <vlan><if>vtnet0</if><tag>60</tag><pcp></pcp></vlan>
+                    <vlan><if>vtnet0</if><tag>100</tag><proto>802.1ad</proto><vlanif>qinq01</vlanif></vlan>
+                </vlans>
+            </opnsense>"#,
+        )
+        .expect("parse");
+
+        normalize_opnsense_vlan_ifnames(&mut root);
+        let vlans = root.get_child("vlans").expect("vlans").get_children("vlan");
+        assert_eq!(vlans[0].get_text(&["pcp"]), Some("5"));
+        assert_eq!(vlans[1].get_text(&["pcp"]), Some("0"));
+        assert_eq!(vlans[2].get_text(&["vlanif"]), Some("qinq01"));
+    }
+
     #[test]
     fn adds_opnsense_vlan_uuid_and_defaults() {
         let mut root = parse(
@@ -0,0 +1,366 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+
+/// Ethertype OPNsense uses for QinQ (service) VLANs.
+const PROTO_QINQ: &str = "802.1ad";
+
+/// Convert pfSense QinQ entries into OPNsense VLANs.
+///
+/// pfSense keeps QinQ in `<qinqs><qinqentry>` (parent, outer tag, and a list
+/// of inner member tags). OPNsense models the same thing as plain VLANs: an
+/// outer VLAN with `<proto>802.1ad</proto>` and a `qinqNN` device, plus one
+/// 802.1Q VLAN per member stacked on that device. Interface assignments that
+/// used pfSense's dotted QinQ devices (`igb1.100`, `igb1.100.20`) are pointed
+/// at the new devices. PCP values on existing VLANs are carried as-is; the
+/// remaining OPNsense shape is filled in by
+/// [`super::vlan_ifnames::normalize_opnsense_vlan_ifnames`].
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let _ = destination_baseline;
+    let Some(qinqs) = source.get_child("qinqs") else {
+        return;
+    };
+    let entries = qinqs.get_children("qinqentry");
+    if entries.is_empty() {
+        return;
+    }
+
+    let vlans = ensure_child_mut(out, "vlans");
+    let mut used: BTreeSet<String> = vlans
+        .children
+        .iter()
+        .filter_map(|v| text_of(v, "vlanif"))
+        .collect();
+    let mut devices = BTreeMap::new();
+
+    for entry in entries {
+        let (Some(parent), Some(tag)) = (text_of(entry, "if"), text_of(entry, "tag")) else {
+            diagnostics.warn("qinq", "QinQ entry without parent or tag skipped");
+            continue;
+        };
+        let descr = text_of(entry, "descr").unwrap_or_default();
+        let outer = next_name("qinq", &used);
+        used.insert(outer.clone());
+        vlans
+            .children
+            .push(vlan_node(&parent, &tag, "0", PROTO_QINQ, &descr, &outer));
+        devices.insert(format!("{parent}.{tag}"), outer.clone());
+
+        let raw_members = text_of(entry, "members").unwrap_or_default();
+        let Some(members) = expand_members(&raw_members) else {
+            diagnostics.warn(
+                "qinq",
+                format!("{parent}.{tag}: member list '{raw_members}' is not valid; inner VLANs not created"),
+            );
+            continue;
+        };
+        for member in &members {
+            let inner = next_name("vlan", &used);
+            used.insert(inner.clone());
+            vlans
+                .children
+                .push(vlan_node(&outer, member, "0", "", &descr, &inner));
+            devices.insert(format!("{parent}.{tag}.{member}"), inner);
+        }
+        diagnostics.info(
+            "qinq",
+            format!(
+                "{parent}.{tag} converted to 802.1ad VLAN {outer} with {} inner VLAN(s)",
+                members.len()
+            ),
+        );
+    }
+
+    rewrite_interface_devices(out, &devices);
+}
+
+/// Convert OPNsense VLANs into pfSense VLANs and QinQ entries.
+///
+/// 802.1ad VLANs and the VLANs stacked on them become `<qinqs><qinqentry>`
+/// items; every other VLAN keeps its parent, tag, PCP, and description but
+/// takes pfSense's dotted device name (`igb1.10`) and loses the
+/// OPNsense-only `uuid` and `proto`. Interface assignments follow the
+/// renamed devices.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let _ = (source, destination_baseline);
+    let Some(vlans) = child_mut(out, "vlans") else {
+        return;
+    };
+    vlans.attributes.clear();
+    let items: Vec<XmlNode> = vlans
+        .children
+        .iter()
+        .filter(|c| c.tag == "vlan")
+        .cloned()
+        .collect();
+
+    let outer: BTreeMap<String, &XmlNode> = items
+        .iter()
+        .filter(|v| text_of(v, "proto").as_deref() == Some(PROTO_QINQ))
+        .filter_map(|v| Some((text_of(v, "vlanif")?, v)))
+        .collect();
+
+    let mut devices = BTreeMap::new();
+    let mut kept = Vec::new();
+    let mut members: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for vlan in &items {
+        let parent = text_of(vlan, "if").unwrap_or_default();
+        let tag = text_of(vlan, "tag").unwrap_or_default();
+        let vlanif = text_of(vlan, "vlanif");
+        if let Some(service) = outer.get(&parent) {
+            let dotted = format!(
+                "{}.{}.{tag}",
+                text_of(service, "if").unwrap_or_default(),
+                text_of(service, "tag").unwrap_or_default()
+            );
+            if let Some(vlanif) = vlanif {
+                devices.insert(vlanif, dotted);
+            }
+            members.entry(parent).or_default().push(tag);
+            continue;
+        }
+        if vlanif.as_ref().is_some_and(|v| outer.contains_key(v)) {
+            continue;
+        }
+
+        let dotted = format!("{parent}.{tag}");
+        let mut pf = vlan.clone();
+        pf.attributes.remove("uuid");
+        pf.children.retain(|c| c.tag != "proto");
+        set_text(&mut pf, "vlanif", &dotted);
+        if let Some(vlanif) = vlanif.filter(|v| *v != dotted) {
+            devices.insert(vlanif, dotted);
+        }
+        kept.push(pf);
+    }
+
+    vlans.children.retain(|c| c.tag != "vlan");
+    vlans.children.extend(kept);
+
+    if !outer.is_empty() {
+        let mut qinqs = XmlNode::new("qinqs");
+        for (vlanif, service) in &outer {
+            let parent = text_of(service, "if").unwrap_or_default();
+            let tag = text_of(service, "tag").unwrap_or_default();
+            let dotted = format!("{parent}.{tag}");
+            let inner = members.remove(vlanif).unwrap_or_default();
+
+            let mut entry = XmlNode::new("qinqentry");
+            for (field, value) in [
+                ("if", parent.as_str()),
+                ("tag", tag.as_str()),
+                ("members", &inner.join(" ")),
+                ("descr", &text_of(service, "descr").unwrap_or_default()),
+                ("vlanif", &dotted),
+            ] {
+                set_text(&mut entry, field, value);
+            }
+            qinqs.children.push(entry);
+            diagnostics.info(
+                "qinq",
+                format!(
+                    "802.1ad VLAN {vlanif} converted to QinQ entry {dotted} with {} member(s)",
+                    inner.len()
+                ),
+            );
+            devices.insert(vlanif.clone(), dotted);
+        }
+        out.children.retain(|c| c.tag != "qinqs");
+        let at = out
+            .children
+            .iter()
+            .position(|c| c.tag == "vlans")
+            .map_or(out.children.len(), |i| i + 1);
+        out.children.insert(at, qinqs);
+    }
+
+    rewrite_interface_devices(out, &devices);
+}
+
+/// Expand a pfSense member list (`20 30 40-42`) into individual tags.
+fn expand_members(raw: &str) -> Option<Vec<String>> {
+    let mut out = Vec::new();
+    for part in raw.split_whitespace() {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (start.parse::<u16>().ok()?, end.parse::<u16>().ok()?);
+                if start > end || end > 4094 {
+                    return None;
+                }
+                out.extend((start..=end).map(|t| t.to_string()));
+            }
+            None => {
+                let tag = part.parse::<u16>().ok()?;
+                if tag == 0 || tag > 4094 {
+                    return None;
+                }
+                out.push(tag.to_string());
+            }
+        }
+    }
+    Some(out)
+}
+
+fn vlan_node(
+    parent: &str,
+    tag: &str,
+    pcp: &str,
+    proto: &str,
+    descr: &str,
+    vlanif: &str,
+) -> XmlNode {
+    let mut vlan = XmlNode::new("vlan");
+    for (field, value) in [
+        ("if", parent),
+        ("tag", tag),
+        ("pcp", pcp),
+        ("proto", proto),
+        ("descr", descr),
+        ("vlanif", vlanif),
+    ] {
+        set_text(&mut vlan, field, value);
+    }
+    vlan
+}
+
+/// Point interface assignments at renamed VLAN devices.
+fn rewrite_interface_devices(out: &mut XmlNode, devices: &BTreeMap<String, String>) {
+    if devices.is_empty() {
+        return;
+    }
+    let Some(interfaces) = child_mut(out, "interfaces") else {
+        return;
+    };
+    for iface in &mut interfaces.children {
+        if let Some(mapped) = text_of(iface, "if").and_then(|d| devices.get(&d)) {
+            set_text(iface, "if", mapped);
+        }
+    }
+}
+
+/// Next free `<prefix>NN` device name.
+fn next_name(prefix: &str, used: &BTreeSet<String>) -> String {
+    (1..1000u16)
+        .map(|i| format!("{prefix}{i:02}"))
+        .find(|name| !used.contains(name))
+        .unwrap_or_else(|| format!("{prefix}999"))
+}
+
+fn text_of(node: &XmlNode, child: &str) -> Option<String> {
+    node.get_text(&[child])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+fn child_mut<'a>(node: &'a mut XmlNode, tag: &str) -> Option<&'a mut XmlNode> {
+    let idx = node.children.iter().position(|c| c.tag == tag)?;
+    Some(&mut node.children[idx])
+}
+
+fn ensure_child_mut<'a>(parent: &'a mut XmlNode, tag: &str) -> &'a mut XmlNode {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == tag) {
+        return &mut parent.children[idx];
+    }
+    parent.children.push(XmlNode::new(tag));
+    let idx = parent.children.len() - 1;
+    &mut parent.children[idx]
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
+
+    #[test]
+    fn pfsense_qinq_becomes_stacked_opnsense_vlans() {
+        let source = parse(
+            br#"<pfsense><qinqs><qinqentry><if>igb1</if><tag>100</tag><members>20 30-31</members><descr>metro</descr><vlanif>igb1.100</vlanif></qinqentry></qinqs></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(
+            br#"<opnsense><interfaces><opt2><if>igb1.100.30</if></opt2></interfaces><vlans><vlan><if>igb1</if><tag>10</tag><pcp>5</pcp></vlan></vlans></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<opnsense/>"#).expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let vlans = out.get_child("vlans").expect("vlans").get_children("vlan");
+        assert_eq!(vlans.len(), 5);
+        assert_eq!(vlans[0].get_text(&["pcp"]), Some("5"));
+        assert_eq!(vlans[1].get_text(&["proto"]), Some("802.1ad"));
+        assert_eq!(vlans[1].get_text(&["vlanif"]), Some("qinq01"));
+        assert_eq!(vlans[3].get_text(&["if"]), Some("qinq01"));
+        assert_eq!(vlans[3].get_text(&["tag"]), Some("30"));
+        assert_eq!(
+            out.get_text(&["interfaces", "opt2", "if"]),
+            vlans[3].get_text(&["vlanif"])
+        );
+        assert_eq!(diagnostics.iter().count(), 1);
+    }
+
+    #[test]
+    fn opnsense_vlans_become_pfsense_vlans_and_qinq_entries() {
+        let source = parse(br#"<opnsense/>"#).expect("parse");
+        let mut out = parse(
+            br#"<pfsense>
+                <interfaces><opt1><if>vlan01</if></opt1><opt2><if>vlan03</if></opt2></interfaces>
+                <vlans version="1.0.0">
+                    <vlan uuid="a"><if>igb1</if><tag>10</tag><pcp>3</pcp><proto/><descr>iot</descr><vlanif>vlan01</vlanif></vlan>
+                    <vlan uuid="b"><if>igb1</if><tag>100</tag><pcp>0</pcp><proto>802.1ad</proto><descr>metro</descr><vlanif>qinq01</vlanif></vlan>
+                    <vlan uuid="c"><if>qinq01</if><tag>20</tag><pcp>0</pcp><proto/><descr/><vlanif>vlan03</vlanif></vlan>
+                </vlans>
+            </pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(br#"<pfsense/>"#).expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let vlans = out.get_child("vlans").expect("vlans");
+        assert!(vlans.attributes.is_empty());
+        let vlan = vlans.get_child("vlan").expect("vlan");
+        assert_eq!(vlans.get_children("vlan").len(), 1);
+        assert!(!vlan.attributes.contains_key("uuid"));
+        assert!(vlan.get_child("proto").is_none());
+        assert_eq!(vlan.get_text(&["pcp"]), Some("3"));
+        assert_eq!(vlan.get_text(&["vlanif"]), Some("igb1.10"));
+        assert_eq!(out.get_text(&["qinqs", "qinqentry", "members"]), Some("20"));
+        assert_eq!(
+            out.get_text(&["qinqs", "qinqentry", "vlanif"]),
+            Some("igb1.100")
+        );
+        assert_eq!(out.get_text(&["interfaces", "opt1", "if"]), Some("igb1.10"));
+        assert_eq!(
+            out.get_text(&["interfaces", "opt2", "if"]),
+            Some("igb1.100.20")
+        );
+    }
+}
@@ -0,0 +1,403 @@
+use xml_diff_core::XmlNode;
+
+use crate::diagnostics::Diagnostics;
+
+/// `<webgui>` fields with the same meaning on both platforms.
+///
+/// Fields missing from the source are removed from the output so baseline
+/// values (for example a disabled anti-lockout rule) do not leak through.
+const SHARED_FIELDS: &[&str] = &[
+    "protocol",
+    "port",
+    "ssl-certref",
+    "max_procs",
+    "noantilockout",
+    "nodnsrebindcheck",
+    "nohttpreferercheck",
+    "althostnames",
+    "loginautocomplete",
+    "disablehttpredirect",
+    "session_timeout",
+];
+
+/// pfSense `<webgui>` fields that only change how the GUI looks.
+///
+/// OPNsense has no equivalent, so they are reported as cosmetic-only drops.
+const PFSENSE_COSMETIC_FIELDS: &[&str] = &[
+    "dashboardcolumns",
+    "logincss",
+    "loginshowhost",
+    "webguihostnamemenu",
+    "webguifixedmenu",
+    "webguileftcolumnhyper",
+    "disablealiaspopupdetail",
+    "dashboardavailablewidgetspanel",
+    "systemlogsfilterpanel",
+    "systemlogsmanagelogpanel",
+    "statusmonitoringsettingspanel",
+    "interfacessort",
+    "requirestatefilter",
+    "pagenamefirst",
+    "roworderdragging",
+];
+
+/// GUI themes with a counterpart on the other platform: pfSense
+/// `<webgui><webguicss>` and the top-level OPNsense `<theme>`.
+const THEMES: &[(&str, &str)] = &[
+    ("pfSense.css", "opnsense"),
+    ("pfSense-dark.css", "opnsense-dark"),
+];
+
+/// Convert web GUI settings for OPNsense output.
+///
+/// See [`apply`]. The pfSense theme becomes the OPNsense `<theme>` when
+/// [`THEMES`] has a match; pfSense-only GUI preferences are reported as
+/// cosmetic-only drops.
+pub fn to_opnsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(out, source, destination_baseline, diagnostics);
+    theme_to_opnsense(out, source, diagnostics);
+    report_cosmetic_fields(source, diagnostics);
+}
+
+/// Convert web GUI settings for pfSense output.
+///
+/// See [`apply`]. The OPNsense `<theme>` becomes the pfSense `webguicss`
+/// when [`THEMES`] has a match.
+pub fn to_pfsense(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    apply(out, source, destination_baseline, diagnostics);
+    theme_to_pfsense(out, source, diagnostics);
+}
+
+/// Rebuild `<system><webgui>` from the baseline plus the source's shared fields.
+///
+/// Platform-specific fields (pfSense themes and dashboard layout, OPNsense
+/// cipher and listen-interface settings) come from the destination baseline.
+/// Login protection thresholds live directly under `<system>` and are carried
+/// with the rest of that section.
+///
+/// The certificate reference is resolved against the output certificates:
+/// 1. Kept when a `<cert>` with the same refid exists
+/// 2. Rewritten to the refid of an output cert with the identical `<crt>`
+/// 3. Otherwise the source cert is copied into the output
+/// 4. If the source has no such cert, the baseline reference is kept and a
+///    warning is recorded
+fn apply(
+    out: &mut XmlNode,
+    source: &XmlNode,
+    destination_baseline: &XmlNode,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some(src_webgui) = source
+        .get_child("system")
+        .and_then(|s| s.get_child("webgui"))
+    else {
+        return;
+    };
+    let baseline_webgui = destination_baseline
+        .get_child("system")
+        .and_then(|s| s.get_child("webgui"));
+
+    // Keep the baseline field order; shared fields take the source value in place.
+    let mut webgui = XmlNode::new("webgui");
+    for child in baseline_webgui.map_or(&[][..], |w| &w.children[..]) {
+        if !SHARED_FIELDS.contains(&child.tag.as_str()) {
+            webgui.children.push(child.clone());
+        } else if let Some(src_child) = src_webgui.get_child(&child.tag) {
+            webgui.children.push(src_child.clone());
+        }
+    }
+    for field in SHARED_FIELDS {
+        if webgui.get_child(field).is_none() {
+            if let Some(child) = src_webgui.get_child(field) {
+                webgui.children.push(child.clone());
+            }
+        }
+    }
+
+    let certref = src_webgui
+        .get_text(&["ssl-certref"])
+        .map(str::trim)
+        .filter(|r| !r.is_empty());
+    if let Some(certref) = certref {
+        let fallback = baseline_webgui
+            .and_then(|w| w.get_text(&["ssl-certref"]))
+            .map(str::trim)
+            .filter(|r| !r.is_empty());
+        match resolve_certref(out, source, certref) {
+            Some(resolved) => set_text(&mut webgui, "ssl-certref", &resolved),
+            None => {
+                diagnostics.warn(
+                    "webgui",
+                    format!(
+                        "web GUI certificate '{certref}' not found in source; keeping target certificate reference"
+                    ),
+                );
+                match fallback {
+                    Some(fallback) => set_text(&mut webgui, "ssl-certref", fallback),
+                    None => webgui.children.retain(|c| c.tag != "ssl-certref"),
+                }
+            }
+        }
+    }
+
+    let Some(system) = out.children.iter_mut().find(|c| c.tag == "system") else {
+        return;
+    };
+    match system.children.iter_mut().find(|c| c.tag == "webgui") {
+        Some(existing) => *existing = webgui,
+        None => system.children.push(webgui),
+    }
+}
+
+/// Set the top-level OPNsense `<theme>` from the pfSense `webguicss`.
+fn theme_to_opnsense(out: &mut XmlNode, source: &XmlNode, diagnostics: &mut Diagnostics) {
+    let Some(css) = source
+        .get_text(&["system", "webgui", "webguicss"])
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    else {
+        return;
+    };
+    match THEMES.iter().find(|(pf, _)| *pf == css) {
+        Some((_, theme)) => {
+            set_text(out, "theme", theme);
+            diagnostics.info("webgui", format!("GUI theme '{css}' carried as '{theme}'"));
+        }
+        None => diagnostics.info(
+            "webgui",
+            format!(
+                "GUI theme '{css}' has no OPNsense equivalent; keeping the target theme (cosmetic only)"
+            ),
+        ),
+    }
+}
+
+/// Set the pfSense `webguicss` from the top-level OPNsense `<theme>`.
+fn theme_to_pfsense(out: &mut XmlNode, source: &XmlNode, diagnostics: &mut Diagnostics) {
+    let Some(theme) = source
+        .get_text(&["theme"])
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    else {
+        return;
+    };
+    let Some((css, _)) = THEMES.iter().find(|(_, opn)| *opn == theme) else {
+        diagnostics.info(
+            "webgui",
+            format!(
+                "GUI theme '{theme}' has no pfSense equivalent; keeping the target theme (cosmetic only)"
+            ),
+        );
+        return;
+    };
+    let Some(webgui) = out
+        .children
+        .iter_mut()
+        .find(|c| c.tag == "system")
+        .and_then(|s| s.children.iter_mut().find(|c| c.tag == "webgui"))
+    else {
+        return;
+    };
+    set_text(webgui, "webguicss", css);
+    diagnostics.info("webgui", format!("GUI theme '{theme}' carried as '{css}'"));
+}
+
+/// Report pfSense-only GUI preferences set in the source as cosmetic drops.
+fn report_cosmetic_fields(source: &XmlNode, diagnostics: &mut Diagnostics) {
+    let Some(src_webgui) = source
+        .get_child("system")
+        .and_then(|s| s.get_child("webgui"))
+    else {
+        return;
+    };
+    let dropped: Vec<&str> = PFSENSE_COSMETIC_FIELDS
+        .iter()
+        .copied()
+        .filter(|field| src_webgui.get_child(field).is_some())
+        .collect();
+    if !dropped.is_empty() {
+        diagnostics.info(
+            "webgui",
+            format!(
+                "GUI preferences with no OPNsense equivalent not carried (cosmetic only, nothing functional): {}",
+                dropped.join(", ")
+            ),
+        );
+    }
+}
+
+/// Find or transfer the certificate for `refid`, returning the output refid.
+fn resolve_certref(out: &mut XmlNode, source: &XmlNode, refid: &str) -> Option<String> {
+    if find_cert(out, |c| cert_refid(c) == Some(refid)).is_some() {
+        return Some(refid.to_string());
+    }
+    let src_cert = find_cert(source, |c| cert_refid(c) == Some(refid))?;
+
+    if let Some(crt) = cert_blob(src_cert) {
+        if let Some(existing) = find_cert(out, |c| cert_blob(c) == Some(crt)) {
+            return cert_refid(existing).map(ToString::to_string);
+        }
+    }
+
+    let insert_at = out
+        .children
+        .iter()
+        .rposition(|c| c.tag == "cert")
+        .map_or(out.children.len(), |idx| idx + 1);
+    out.children.insert(insert_at, src_cert.clone());
+    Some(refid.to_string())
+}
+
+fn find_cert(root: &XmlNode, pred: impl Fn(&XmlNode) -> bool) -> Option<&XmlNode> {
+    root.children.iter().find(|c| c.tag == "cert" && pred(c))
+}
+
+fn cert_refid(cert: &XmlNode) -> Option<&str> {
+    cert.get_text(&["refid"]).map(str::trim)
+}
+
+/// The `<crt>` blob, ignoring sanitized `REDACTED` placeholders.
+fn cert_blob(cert: &XmlNode) -> Option<&str> {
+    cert.get_text(&["crt"])
+        .map(str::trim)
+        .filter(|c| !c.is_empty() && !c.eq_ignore_ascii_case("redacted"))
+}
+
+fn set_text(node: &mut XmlNode, tag: &str, value: &str) {
+    if let Some(child) = node.children.iter_mut().find(|c| c.tag == tag) {
+        child.text = Some(value.to_string());
+        return;
+    }
+    let mut child = XmlNode::new(tag);
+    child.text = Some(value.to_string());
+    node.children.push(child);
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::{Diagnostics, Severity};
+
+    #[test]
+    fn carries_shared_fields_and_rewrites_certref_to_matching_cert() {
+        let source = parse(
+            br#"<pfsense><system><webgui><protocol>https</protocol><port>8443</port><ssl-certref>src1</ssl-certref><max_procs>4</max_procs><webguicss>pfSense-dark.css</webguicss></webgui></system><cert><refid>src1</refid><crt>BLOB</crt></cert></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(
+            br#"<opnsense><system><webgui><protocol>http</protocol><ssl-certref>dst1</ssl-certref><ssl-ciphers/><noantilockout>1</noantilockout></webgui></system><cert><refid>dst1</refid><crt>OTHER</crt></cert></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(
+            br#"<opnsense><system><webgui><webguicss>pfSense-dark.css</webguicss></webgui></system><cert><refid>dst1</refid><crt>OTHER</crt></cert><cert><refid>dst2</refid><crt>BLOB</crt></cert></opnsense>"#,
+        )
+        .expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+
+        let webgui = out
+            .get_child("system")
+            .and_then(|s| s.get_child("webgui"))
+            .expect("webgui");
+        assert_eq!(webgui.get_text(&["protocol"]), Some("https"));
+        assert_eq!(webgui.get_text(&["port"]), Some("8443"));
+        assert_eq!(webgui.get_text(&["max_procs"]), Some("4"));
+        assert_eq!(webgui.get_text(&["ssl-certref"]), Some("dst2"));
+        assert!(webgui.get_child("ssl-ciphers").is_some());
+        assert!(webgui.get_child("webguicss").is_none());
+        assert!(webgui.get_child("noantilockout").is_none());
+        assert_eq!(out.get_text(&["theme"]), Some("opnsense-dark"));
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Info));
+    }
+
+    #[test]
+    fn maps_themes_and_reports_cosmetic_only_drops() {
+        let source = parse(
+            br#"<pfsense><system><webgui><webguicss>Compact-RED.css</webguicss><dashboardcolumns>2</dashboardcolumns><logincss>1e3f75;</logincss></webgui></system></pfsense>"#,
+        )
+        .expect("parse");
+        let baseline =
+            parse(br#"<opnsense><theme>opnsense</theme><system><webgui/></system></opnsense>"#)
+                .expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+
+        to_opnsense(&mut out, &source, &baseline, &mut diagnostics);
+        assert_eq!(out.get_text(&["theme"]), Some("opnsense"));
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "GUI theme 'Compact-RED.css' has no OPNsense equivalent; keeping the target theme (cosmetic only)",
+                "GUI preferences with no OPNsense equivalent not carried (cosmetic only, nothing functional): dashboardcolumns, logincss",
+            ]
+        );
+
+        let source = parse(
+            br#"<opnsense><theme>opnsense-dark</theme><system><webgui><protocol>https</protocol></webgui></system></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(
+            br#"<pfsense><system><webgui><webguicss>pfSense.css</webguicss></webgui></system></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = baseline.clone();
+        let mut diagnostics = Diagnostics::new();
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+        assert_eq!(
+            out.get_text(&["system", "webgui", "webguicss"]),
+            Some("pfSense-dark.css")
+        );
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message == "GUI theme 'opnsense-dark' carried as 'pfSense-dark.css'"));
+    }
+
+    #[test]
+    fn transfers_missing_gui_cert_and_warns_when_unresolvable() {
+        let source = parse(
+            br#"<opnsense><system><webgui><ssl-certref>gui</ssl-certref></webgui></system><cert><refid>gui</refid><crt>BLOB</crt></cert></opnsense>"#,
+        )
+        .expect("parse");
+        let baseline = parse(
+            br#"<pfsense><system><webgui><ssl-certref>dst</ssl-certref></webgui></system></pfsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
+        let mut diagnostics = Diagnostics::new();
+
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+        assert_eq!(
+            out.get_text(&["system", "webgui", "ssl-certref"]),
+            Some("gui")
+        );
+        assert_eq!(out.get_text(&["cert", "refid"]), Some("gui"));
+
+        let source = parse(
+            br#"<opnsense><system><webgui><ssl-certref>gone</ssl-certref></webgui></system></opnsense>"#,
+        )
+        .expect("parse");
+        let mut out = parse(br#"<pfsense><system/></pfsense>"#).expect("parse");
+        to_pfsense(&mut out, &source, &baseline, &mut diagnostics);
+        assert_eq!(
+            out.get_text(&["system", "webgui", "ssl-certref"]),
+            Some("dst")
+        );
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.source == "webgui" && d.message.contains("'gone'")));
+    }
+}
@@ -30,14 +30,15 @@
 //!
 //! ## Round-Trip Preservation
 //!
-//! To support lossless pfSense → OPNsense → pfSense round-trips, when converting
-//! from OPNsense to pfSense, the original OPNsense config is stored as
-//! `<opnsense_wireguard_snapshot>` within the pfSense structure. When converting
-//! back to OPNsense, this snapshot is restored if present, preserving all
-//! OPNsense-specific fields that don't exist in pfSense.
+//! OPNsense → pfSense → OPNsense round-trips are handled by the shared
+//! [`crate::transform::mvc_snapshot`] framework. Configs written by older
+//! versions carry `<opnsense_wireguard_snapshot>` inside the pfSense structure;
+//! that snapshot is still restored when converting back to OPNsense.
 
 use xml_diff_core::XmlNode;
 
+use crate::uuid_registry::UuidRegistry;
+
 mod common;
 mod opn_to_pf;
 mod pf_to_opn;
@@ -58,7 +59,8 @@ pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, target: &XmlNode) {
         common::upsert_nested_wireguard(out, source_nested.clone());
     } else if let Some(source_top) = source_pfsense_wireguard(source) {
         // Source has pfSense-style WireGuard config — map it to OPNsense format
-        let mapped = pf_to_opn::map_pfsense_wireguard(source_top);
+        let mut registry = UuidRegistry::from_config(out);
+        let mapped = pf_to_opn::map_pfsense_wireguard(source_top, &mut registry);
         common::upsert_nested_wireguard(out, mapped);
     }
 
@@ -129,6 +131,9 @@ mod tests {
     use xml_diff_core::parse;
 
     use super::{to_opnsense, to_pfsense};
+    use crate::diagnostics::Diagnostics;
+    use crate::transform::mvc_snapshot;
+    use crate::verify_uuid_refs::uuid_reference_findings;
 
     #[test]
     fn maps_pfsense_wireguard_to_opnsense_instances_and_peers() {
@@ -185,6 +190,26 @@ mod tests {
             ]),
             Some("peer1")
         );
+        let peer_uuid = out
+            .get_child("OPNsense")
+            .and_then(|o| o.get_child("wireguard"))
+            .and_then(|w| w.get_child("client"))
+            .and_then(|c| c.get_child("clients"))
+            .and_then(|c| c.get_child("client"))
+            .and_then(|c| c.attributes.get("uuid"))
+            .cloned();
+        assert_eq!(
+            out.get_text(&[
+                "OPNsense",
+                "wireguard",
+                "server",
+                "servers",
+                "server",
+                "peers"
+            ]),
+            peer_uuid.as_deref()
+        );
+        assert!(uuid_reference_findings(&out).is_empty());
     }
 
     #[test]
@@ -239,11 +264,13 @@ mod tests {
             .expect("pf target parse");
         let mut pf = pf_target.clone();
         to_pfsense(&mut pf, &opn_source, &pf_target);
+        mvc_snapshot::capture(&mut pf, &opn_source);
 
         let opn_target = parse(br#"<opnsense><interfaces><lan/></interfaces></opnsense>"#)
             .expect("opn target parse");
         let mut opn = opn_target.clone();
         to_opnsense(&mut opn, &pf, &opn_target);
+        mvc_snapshot::restore(&mut opn, &pf, &[], &mut Diagnostics::new());
 
         assert_eq!(
             opn.get_text(&[
@@ -277,4 +304,21 @@ mod tests {
             .map(String::as_str);
         assert_eq!(peer_uuid, Some("peer-1"));
     }
+
+    #[test]
+    fn restores_legacy_wireguard_snapshot() {
+        let source = parse(
+            br#"<pfsense><installedpackages><wireguard><tunnels/><opnsense_wireguard_snapshot><general><enabled>1</enabled></general></opnsense_wireguard_snapshot></wireguard></installedpackages></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(br#"<opnsense><interfaces><lan/></interfaces></opnsense>"#)
+            .expect("target parse");
+        let mut out = target.clone();
+
+        to_opnsense(&mut out, &source, &target);
+        assert_eq!(
+            out.get_text(&["OPNsense", "wireguard", "general", "enabled"]),
+            Some("1")
+        );
+    }
 }
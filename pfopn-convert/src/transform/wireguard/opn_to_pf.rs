@@ -26,9 +26,8 @@ use super::common::{is_truthy, push_text_child, text_of};
 ///
 /// ## Round-Trip Preservation
 ///
-/// The full OPNsense config is preserved as `<opnsense_wireguard_snapshot>` so that
-/// a pfSense → OPNsense → pfSense conversion can restore all OPNsense-specific fields
-/// that don't exist in pfSense's simpler model (like DNS settings, advanced options, etc.).
+/// OPNsense-specific fields (DNS settings, advanced options, etc.) are kept by
+/// the pipeline-level round-trip snapshot in [`crate::transform::mvc_snapshot`].
 ///
 /// ## Mapping Details
 ///
@@ -194,11 +193,6 @@ pub fn map_opnsense_wireguard(source: &XmlNode) -> XmlNode {
     push_text_child(&mut config, "hide_peers", "yes");
     out.children.push(config);
 
-    // Preserve full OPNsense schema for round-trip restoration.
-    let mut snapshot = source.clone();
-    snapshot.tag = "opnsense_wireguard_snapshot".to_string();
-    out.children.push(snapshot);
-
     out
 }
 
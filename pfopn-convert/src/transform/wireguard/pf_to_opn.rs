@@ -3,6 +3,9 @@ use std::collections::BTreeMap;
 use xml_diff_core::XmlNode;
 
 use super::common::{as_bool_text, push_text_child, text_of};
+use crate::model::WireguardPeer;
+use crate::transform::booleans::numeric;
+use crate::uuid_registry::UuidRegistry;
 
 /// Map pfSense WireGuard configuration to OPNsense format.
 ///
@@ -26,18 +29,19 @@ use super::common::{as_bool_text, push_text_child, text_of};
 ///
 /// ## Round-Trip Support
 ///
-/// If an `<opnsense_wireguard_snapshot>` exists in the source (from a previous
-/// OPNsense → pfSense conversion), it is restored directly to preserve all
-/// OPNsense-specific fields that don't exist in pfSense's simpler model.
+/// If an `<opnsense_wireguard_snapshot>` exists in the source (written inside
+/// `<wireguard>` by older OPNsense → pfSense conversions), it is restored
+/// directly. Newer outputs use [`crate::transform::mvc_snapshot`] instead.
 ///
 /// ## Mapping Details
 ///
 /// - Tunnels → Servers: Each `<tunnels><item>` becomes a `<server>`
 /// - Peers → Clients: Each `<peers><item>` becomes a `<client>`
 /// - Peer-to-tunnel association: pfSense uses `<tun>` field; OPNsense links via `<peers>` UUID list
+/// - UUIDs: issued by `registry`, keyed by peer position and tunnel name
 /// - Instance numbers: Extracted from tunnel names (e.g., "tun_wg0" → instance "0")
 /// - Tunnel addresses: pfSense's `<allowedips><row>` becomes OPNsense's comma-separated CIDRs
-pub fn map_pfsense_wireguard(source: &XmlNode) -> XmlNode {
+pub fn map_pfsense_wireguard(source: &XmlNode, registry: &mut UuidRegistry) -> XmlNode {
     // If we have a snapshot from a previous OPNsense → pfSense → OPNsense round-trip,
     // restore it to preserve all OPNsense-specific fields
     if let Some(snapshot) = source.get_child("opnsense_wireguard_snapshot") {
@@ -47,8 +51,7 @@ pub fn map_pfsense_wireguard(source: &XmlNode) -> XmlNode {
     }
 
     let mut out = XmlNode::new("wireguard");
-    let mut uuid_by_peer_idx: BTreeMap<usize, String> = BTreeMap::new();
-    // Build a map of tunnel name → list of peer UUIDs for that tunnel
+    // Build a map of tunnel name → list of peer keys for that tunnel
     // OPNsense servers reference their clients via a comma-separated UUID list
     let mut peers_by_tun: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
@@ -57,55 +60,36 @@ pub fn map_pfsense_wireguard(source: &XmlNode) -> XmlNode {
     let mut clients = XmlNode::new("clients");
     if let Some(peers) = source.get_child("peers") {
         for (idx, peer) in peers.get_children("item").into_iter().enumerate() {
-            let uuid = stable_uuid("pf-peer", idx);
+            let Ok(peer) = WireguardPeer::from_node(peer) else {
+                continue;
+            };
+            let peer_key = idx.to_string();
+            let uuid = registry.uuid_for("wireguard-peer", &peer_key);
             let mut client = XmlNode::new("client");
             client.attributes.insert("uuid".to_string(), uuid.clone());
-            push_text_child(
-                &mut client,
-                "enabled",
-                as_bool_text(text_of(peer, &["enabled"]).unwrap_or("0")),
-            );
+            push_text_child(&mut client, "enabled", numeric(peer.enabled));
             // pfSense uses <descr> for peer names; generate a default if missing
-            let name = text_of(peer, &["descr"])
-                .map(ToString::to_string)
-                .unwrap_or_else(|| format!("wg_peer_{}", idx + 1));
+            let name = peer.name.unwrap_or_else(|| format!("wg_peer_{}", idx + 1));
             push_text_child(&mut client, "name", name);
-            push_text_child(
-                &mut client,
-                "pubkey",
-                text_of(peer, &["publickey"]).unwrap_or_default(),
-            );
-            push_text_child(
-                &mut client,
-                "psk",
-                text_of(peer, &["presharedkey"]).unwrap_or_default(),
-            );
-            // Convert pfSense's <allowedips><row> structure to comma-separated CIDRs
-            let tunnel_address = peer_tunnel_address(peer);
-            push_text_child(&mut client, "tunneladdress", tunnel_address);
+            push_text_child(&mut client, "pubkey", peer.public_key.unwrap_or_default());
+            push_text_child(&mut client, "psk", peer.preshared_key.unwrap_or_default());
+            // pfSense's <allowedips><row> structure becomes comma-separated CIDRs
+            push_text_child(&mut client, "tunneladdress", peer.allowed_ips.join(","));
             push_text_child(
                 &mut client,
                 "serveraddress",
-                text_of(peer, &["endpoint", "address"]).unwrap_or_default(),
+                peer.endpoint.unwrap_or_default(),
             );
             push_text_child(
                 &mut client,
                 "serverport",
-                text_of(peer, &["endpoint", "port"]).unwrap_or_default(),
-            );
-            push_text_child(
-                &mut client,
-                "keepalive",
-                text_of(peer, &["persistentkeepalive"]).unwrap_or_default(),
+                peer.endpoint_port.unwrap_or_default(),
             );
+            push_text_child(&mut client, "keepalive", peer.keepalive.unwrap_or_default());
             // Associate this peer with its parent tunnel (via <tun> field in pfSense)
-            if let Some(tun) = text_of(peer, &["tun"]) {
-                peers_by_tun
-                    .entry(tun.to_string())
-                    .or_default()
-                    .push(uuid.clone());
+            if let Some(tun) = peer.tunnel {
+                peers_by_tun.entry(tun).or_default().push(peer_key);
             }
-            uuid_by_peer_idx.insert(idx, uuid);
             clients.children.push(client);
         }
     }
@@ -134,9 +118,10 @@ pub fn map_pfsense_wireguard(source: &XmlNode) -> XmlNode {
                 .map(ToString::to_string)
                 .unwrap_or_else(|| format!("tun_wg{idx}"));
             let mut server = XmlNode::new("server");
-            server
-                .attributes
-                .insert("uuid".to_string(), stable_uuid("pf-tunnel", idx));
+            server.attributes.insert(
+                "uuid".to_string(),
+                registry.uuid_for("wireguard-server", &tun_name),
+            );
             push_text_child(
                 &mut server,
                 "enabled",
@@ -174,8 +159,12 @@ pub fn map_pfsense_wireguard(source: &XmlNode) -> XmlNode {
             push_text_child(&mut server, "gateway", ""); // Not set by default
             push_text_child(&mut server, "carp_depend_on", ""); // CARP dependency (unused)
                                                                 // Link this server to its clients via comma-separated UUID list
-            let peer_list = peers_by_tun.get(&tun_name).cloned().unwrap_or_default();
-            push_text_child(&mut server, "peers", peer_list.join(","));
+            let peer_keys = peers_by_tun.get(&tun_name).cloned().unwrap_or_default();
+            push_text_child(
+                &mut server,
+                "peers",
+                registry.rewrite_refs("wireguard-peer", &peer_keys.join(",")),
+            );
             push_text_child(&mut server, "debug", "0"); // Debug mode off
             push_text_child(&mut server, "endpoint", ""); // Not used for servers
             push_text_child(&mut server, "peer_dns", ""); // DNS servers pushed to clients
@@ -185,31 +174,9 @@ pub fn map_pfsense_wireguard(source: &XmlNode) -> XmlNode {
     server_wrap.children.push(servers);
     out.children.push(server_wrap);
 
-    let _ = uuid_by_peer_idx;
     out
 }
 
-/// Extract tunnel addresses from pfSense peer's allowedips structure.
-///
-/// pfSense stores allowed IPs as `<allowedips><row><address>` and `<mask>`.
-/// OPNsense expects a comma-separated list of CIDRs (e.g., "10.0.0.2/32,fd00::2/128").
-///
-/// Returns comma-separated CIDR list, or empty string if no allowed IPs.
-fn peer_tunnel_address(peer: &XmlNode) -> String {
-    let Some(allowed) = peer.get_child("allowedips") else {
-        return String::new();
-    };
-    let mut cidrs = Vec::new();
-    for row in allowed.get_children("row") {
-        let Some(addr) = text_of(row, &["address"]) else {
-            continue;
-        };
-        let mask = text_of(row, &["mask"]).unwrap_or("32"); // Default to /32 for single IPs
-        cidrs.push(format!("{addr}/{mask}"));
-    }
-    cidrs.join(",")
-}
-
 /// Extract WireGuard instance number from pfSense tunnel name.
 ///
 /// pfSense uses names like "tun_wg0", "tun_wg1", etc.
@@ -229,36 +196,3 @@ fn extract_instance_id(tun_name: &str) -> String {
         digits
     }
 }
-
-/// Generate a deterministic UUID for WireGuard config elements.
-///
-/// Creates stable UUIDs so the same pfSense configuration always produces
-/// the same OPNsense UUIDs. This ensures:
-/// - Idempotent conversions (converting twice produces identical output)
-/// - Server-client linking via UUID references works correctly
-/// - Diff stability (unchanged configs keep the same UUIDs)
-///
-/// Uses CRC32 of the prefix XORed with the index to ensure uniqueness.
-fn stable_uuid(prefix: &str, idx: usize) -> String {
-    format!(
-        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
-        crc32(prefix.as_bytes()) ^ (idx as u32),
-        0,
-        0,
-        0,
-        (idx as u64) + 1
-    )
-}
-
-/// Compute CRC32 checksum (ISO 3309 / ITU-T V.42) using standard polynomial 0xEDB88320.
-fn crc32(input: &[u8]) -> u32 {
-    let mut crc = 0xffff_ffffu32;
-    for b in input {
-        crc ^= *b as u32;
-        for _ in 0..8 {
-            let mask = (crc & 1).wrapping_neg(); // All-ones if LSB set, else all-zeros
-            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
-        }
-    }
-    !crc // Final inversion
-}
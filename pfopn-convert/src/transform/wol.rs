@@ -0,0 +1,171 @@
+use xml_diff_core::XmlNode;
+
+use crate::uuid_registry::UuidRegistry;
+
+/// Fields copied for each Wake-on-LAN device.
+const WOL_FIELDS: &[&str] = &["interface", "mac", "descr"];
+
+/// Transfer Wake-on-LAN devices from pfSense to OPNsense.
+///
+/// WOL devices live in different locations:
+/// - pfSense: `<wol><wolentry>` (legacy top-level section)
+/// - OPNsense: `<OPNsense><wol><wolentry uuid="...">` (MVC model)
+///
+/// Entries already present in the target (same interface and MAC) are kept
+/// as-is; new entries get a deterministic UUID keyed by interface and MAC.
+pub fn to_opnsense(out: &mut XmlNode, source: &XmlNode, target: &XmlNode) {
+    let entries = source_entries(source);
+    // The OPNsense model replaces the legacy section; drop anything the merge copied over.
+    out.children.retain(|c| c.tag != "wol");
+    if entries.is_empty() {
+        return;
+    }
+
+    let mut registry = UuidRegistry::from_config(out);
+    let opn = ensure_child_mut(out, "OPNsense");
+    let wol = ensure_child_mut(opn, "wol");
+    if !wol.attributes.contains_key("version") {
+        wol.attributes
+            .insert("version".to_string(), "1.0.0".to_string());
+    }
+    for entry in entries {
+        if contains_entry(wol, &entry) {
+            continue;
+        }
+        let mut entry = entry;
+        let key =
+            entry_key(&entry).map_or_else(String::new, |(iface, mac)| format!("{iface}|{mac}"));
+        let uuid = registry.uuid_for("wol-entry", &key);
+        entry.attributes.insert("uuid".to_string(), uuid);
+        wol.children.push(entry);
+    }
+
+    let _ = target;
+}
+
+/// Transfer Wake-on-LAN devices from OPNsense to pfSense.
+///
+/// Reads the OPNsense MVC model (falling back to a legacy top-level `<wol>`)
+/// and writes a top-level `<wol>` section without `uuid` attributes.
+pub fn to_pfsense(out: &mut XmlNode, source: &XmlNode, target: &XmlNode) {
+    let entries = source_entries(source);
+    if entries.is_empty() {
+        return;
+    }
+
+    let wol = ensure_child_mut(out, "wol");
+    for entry in entries {
+        if !contains_entry(wol, &entry) {
+            wol.children.push(entry);
+        }
+    }
+
+    let _ = target;
+}
+
+/// Collect sanitized `<wolentry>` nodes from either platform's layout.
+fn source_entries(source: &XmlNode) -> Vec<XmlNode> {
+    let section = source
+        .get_child("OPNsense")
+        .and_then(|opn| opn.get_child("wol"))
+        .or_else(|| source.get_child("wol"));
+    let Some(section) = section else {
+        return Vec::new();
+    };
+
+    section
+        .get_children("wolentry")
+        .into_iter()
+        .filter(|entry| entry_key(entry).is_some())
+        .map(|entry| {
+            let mut out = XmlNode::new("wolentry");
+            out.children = entry
+                .children
+                .iter()
+                .filter(|c| WOL_FIELDS.contains(&c.tag.as_str()))
+                .cloned()
+                .collect();
+            out
+        })
+        .collect()
+}
+
+/// Identify an entry by `(interface, lowercase MAC)`; entries without a MAC are skipped.
+fn entry_key(entry: &XmlNode) -> Option<(String, String)> {
+    let mac = entry
+        .get_text(&["mac"])
+        .map(str::trim)
+        .filter(|m| !m.is_empty())?;
+    let iface = entry.get_text(&["interface"]).map(str::trim).unwrap_or("");
+    Some((iface.to_string(), mac.to_ascii_lowercase()))
+}
+
+fn contains_entry(section: &XmlNode, entry: &XmlNode) -> bool {
+    let key = entry_key(entry);
+    section
+        .get_children("wolentry")
+        .iter()
+        .any(|existing| entry_key(existing) == key)
+}
+
+/// Get a mutable reference to a child, creating it if it doesn't exist.
+fn ensure_child_mut<'a>(parent: &'a mut XmlNode, tag: &str) -> &'a mut XmlNode {
+    if let Some(idx) = parent.children.iter().position(|c| c.tag == tag) {
+        return &mut parent.children[idx];
+    }
+    parent.children.push(XmlNode::new(tag));
+    let last = parent.children.len() - 1;
+    &mut parent.children[last]
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{to_opnsense, to_pfsense};
+    use crate::uuid_registry::stable_uuid;
+
+    #[test]
+    fn moves_pfsense_wol_into_opnsense_model() {
+        let source = parse(
+            br#"<pfsense><wol><wolentry><interface>lan</interface><mac>00:11:22:AA:BB:CC</mac><descr>nas</descr></wolentry><wolentry><interface>lan</interface><mac/></wolentry></wol></pfsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(br#"<opnsense><system/></opnsense>"#).expect("target parse");
+        let mut out = parse(br#"<opnsense><system/><wol/></opnsense>"#).expect("out parse");
+
+        to_opnsense(&mut out, &source, &target);
+
+        assert!(out.get_child("wol").is_none());
+        let wol = out
+            .get_child("OPNsense")
+            .and_then(|opn| opn.get_child("wol"))
+            .expect("wol model");
+        let entries = wol.get_children("wolentry");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].attributes.get("uuid").map(String::as_str),
+            Some(stable_uuid("wol-entry", "lan|00:11:22:aa:bb:cc").as_str())
+        );
+        assert_eq!(entries[0].get_text(&["descr"]), Some("nas"));
+    }
+
+    #[test]
+    fn moves_opnsense_model_to_pfsense_without_uuid() {
+        let source = parse(
+            br#"<opnsense><OPNsense><wol version="1.0.0"><wolentry uuid="x"><interface>opt1</interface><mac>00:11:22:33:44:55</mac><descr/></wolentry></wol></OPNsense></opnsense>"#,
+        )
+        .expect("source parse");
+        let target = parse(br#"<pfsense><wol/></pfsense>"#).expect("target parse");
+        let mut out = target.clone();
+
+        to_pfsense(&mut out, &source, &target);
+
+        let entry = out
+            .get_child("wol")
+            .and_then(|wol| wol.get_child("wolentry"))
+            .expect("entry");
+        assert!(entry.attributes.is_empty());
+        assert_eq!(entry.get_text(&["interface"]), Some("opt1"));
+    }
+}
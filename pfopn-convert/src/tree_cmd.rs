@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use pfopn_convert::canonical;
+use pfopn_convert::export::{export_tree, import_tree, TreeFormat};
+use xml_diff_core::{parse_file, write_file_with_options, ChildOrder};
+
+use crate::cli::{self, ExportArgs, ImportArgs};
+use crate::path_guard::ensure_output_not_same;
+
+/// Write one config as a JSON or YAML tree document.
+pub fn run_export(args: ExportArgs) -> Result<()> {
+    if let Some(output) = &args.output {
+        ensure_output_not_same(output, &[&args.file])?;
+    }
+    let root = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let doc = export_tree(&root, tree_format(args.format))?;
+    match &args.output {
+        Some(output) => {
+            std::fs::write(output, &doc)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            println!("exported {} to {}", args.file.display(), output.display());
+        }
+        None => print!("{doc}"),
+    }
+    Ok(())
+}
+
+/// Rebuild config.xml from a tree document.
+///
+/// Sections are written in document order rather than the canonical
+/// platform order, so an exported config comes back unchanged.
+pub fn run_import(args: ImportArgs) -> Result<()> {
+    ensure_output_not_same(&args.output, &[&args.file])?;
+    let format = args
+        .format
+        .map(tree_format)
+        .or_else(|| TreeFormat::from_extension(&args.file))
+        .unwrap_or(TreeFormat::Yaml);
+    let input = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("failed to read {}", args.file.display()))?;
+    let root = import_tree(&input, format)
+        .with_context(|| format!("failed to import {}", args.file.display()))?;
+    let mut opts = canonical::write_options(&root.tag);
+    opts.child_order = ChildOrder::new();
+    write_file_with_options(&root, &args.output, &opts)
+        .with_context(|| format!("failed to write output XML {}", args.output.display()))?;
+    println!(
+        "imported {} to {}",
+        args.file.display(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn tree_format(format: cli::TreeFormat) -> TreeFormat {
+    match format {
+        cli::TreeFormat::Json => TreeFormat::Json,
+        cli::TreeFormat::Yaml => TreeFormat::Yaml,
+    }
+}
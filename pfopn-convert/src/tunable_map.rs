@@ -0,0 +1,154 @@
+//! System tunable (sysctl) compatibility between pfSense and OPNsense.
+//!
+//! Tunables are FreeBSD sysctls, so most names are shared, but some were
+//! renamed across FreeBSD releases or only exist on one platform. The table
+//! lives in `mappings/tunables.toml` and is embedded at build time.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// One tunable with its name on each platform.
+///
+/// A missing side means the tunable does not exist on that platform.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TunableMapEntry {
+    pub pfsense: Option<String>,
+    pub opnsense: Option<String>,
+    /// Why the tunable is renamed or unavailable.
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TunableMap {
+    pub entries: Vec<TunableMapEntry>,
+}
+
+/// How a source tunable carries over to the target platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunableCompat<'a> {
+    /// Exists on the target under this name (possibly renamed).
+    Supported(&'a str),
+    /// Known not to exist on the target; carries the table note if any.
+    Unsupported(Option<&'a str>),
+    /// Not in the table.
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct TunableMapFile {
+    tunable: Vec<TunableMapEntry>,
+}
+
+#[derive(Debug, Error)]
+pub enum TunableMapLoadError {
+    #[error("failed to read tunable map {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("failed to parse tunable map {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+impl TunableMap {
+    /// Look up how `tunable` maps onto platform `to`.
+    pub fn translate(&self, tunable: &str, to: &str) -> TunableCompat<'_> {
+        let tunable = tunable.trim();
+        let entry = self.entries.iter().find(|entry| {
+            let from = match to {
+                "opnsense" => entry.pfsense.as_deref(),
+                "pfsense" => entry.opnsense.as_deref(),
+                _ => None,
+            };
+            from == Some(tunable)
+        });
+        let Some(entry) = entry else {
+            return TunableCompat::Unknown;
+        };
+        let target = match to {
+            "opnsense" => entry.opnsense.as_deref(),
+            _ => entry.pfsense.as_deref(),
+        };
+        match target {
+            Some(name) => TunableCompat::Supported(name),
+            None => TunableCompat::Unsupported(entry.note.as_deref()),
+        }
+    }
+}
+
+pub fn load_tunable_map(path: &Path) -> Result<TunableMap, TunableMapLoadError> {
+    let raw = fs::read_to_string(path).map_err(|source| TunableMapLoadError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    parse_tunable_map(&raw, path.display().to_string())
+}
+
+pub fn default_tunable_map() -> TunableMap {
+    let embedded = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/mappings/tunables.toml"
+    ));
+    match parse_tunable_map(embedded, "embedded tunable map".to_string()) {
+        Ok(map) if !map.entries.is_empty() => map,
+        _ => fallback_tunable_map(),
+    }
+}
+
+fn parse_tunable_map(raw: &str, path: String) -> Result<TunableMap, TunableMapLoadError> {
+    let parsed: TunableMapFile =
+        toml::from_str(raw).map_err(|source| TunableMapLoadError::Parse { path, source })?;
+    Ok(TunableMap {
+        entries: parsed.tunable,
+    })
+}
+
+fn fallback_tunable_map() -> TunableMap {
+    TunableMap {
+        entries: vec![TunableMapEntry {
+            pfsense: Some("net.inet.ip.portrange.first".to_string()),
+            opnsense: Some("net.inet.ip.portrange.first".to_string()),
+            note: None,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_tunable_map, TunableCompat};
+
+    #[test]
+    fn translates_shared_renamed_and_unsupported() {
+        let map = default_tunable_map();
+        assert_eq!(
+            map.translate("net.inet.tcp.tso", "opnsense"),
+            TunableCompat::Supported("net.inet.tcp.tso")
+        );
+        assert_eq!(
+            map.translate("hw.syscons.kbd_reboot", "opnsense"),
+            TunableCompat::Supported("kern.vt.kbd_reboot")
+        );
+        assert_eq!(
+            map.translate("kern.vt.kbd_reboot", "pfsense"),
+            TunableCompat::Supported("hw.syscons.kbd_reboot")
+        );
+        assert!(matches!(
+            map.translate("net.inet.ip.fastforwarding", "opnsense"),
+            TunableCompat::Unsupported(Some(_))
+        ));
+        assert!(matches!(
+            map.translate("net.pf.share_forward", "pfsense"),
+            TunableCompat::Unsupported(Some(_))
+        ));
+        assert_eq!(
+            map.translate("dev.example.custom", "opnsense"),
+            TunableCompat::Unknown
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Deterministic UUIDs for OPNsense MVC items.
+//!
+//! OPNsense models identify items by a `uuid` attribute and link them by
+//! storing that UUID in another item's field (a WireGuard server's `peers`,
+//! a Swanctl child's `connection`, a Kea reservation's `subnet`). Every
+//! to-OPNsense transform issues UUIDs through a [`UuidRegistry`] so that:
+//!
+//! - the same source always produces the same UUIDs
+//! - an item and the references to it agree, because both look the UUID up
+//!   by the same `(scope, key)` pair
+//! - a new UUID never collides with one already in the output
+//!
+//! References are checked after conversion by
+//! [`crate::verify_uuid_refs`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use xml_diff_core::XmlNode;
+
+use crate::fingerprint::sha256;
+
+/// UUIDs issued for one conversion, keyed by `(scope, key)`.
+///
+/// A scope names the kind of item (`"wireguard-peer"`, `"kea-subnet4"`); the
+/// key identifies the item within it using source data (a tunnel id, a
+/// subnet CIDR), so the UUID does not depend on output order.
+#[derive(Debug, Clone, Default)]
+pub struct UuidRegistry {
+    issued: BTreeMap<(String, String), String>,
+    taken: BTreeSet<String>,
+}
+
+impl UuidRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a registry that avoids every `uuid` attribute already in `root`.
+    pub fn from_config(root: &XmlNode) -> Self {
+        let mut registry = Self::new();
+        collect_uuids(root, &mut registry.taken);
+        registry
+    }
+
+    /// The UUID for `key` in `scope`, issuing one on first use.
+    pub fn uuid_for(&mut self, scope: &str, key: &str) -> String {
+        let slot = (scope.to_string(), key.to_string());
+        if let Some(uuid) = self.issued.get(&slot) {
+            return uuid.clone();
+        }
+        let mut uuid = stable_uuid(scope, key);
+        let mut attempt = 1usize;
+        while self.taken.contains(&uuid) {
+            uuid = stable_uuid(scope, &format!("{key}#{attempt}"));
+            attempt += 1;
+        }
+        self.taken.insert(uuid.clone());
+        self.issued.insert(slot, uuid.clone());
+        uuid
+    }
+
+    /// Record a UUID kept from the source so references to `key` resolve to it.
+    pub fn register(&mut self, scope: &str, key: &str, uuid: &str) {
+        self.taken.insert(uuid.to_string());
+        self.issued
+            .insert((scope.to_string(), key.to_string()), uuid.to_string());
+    }
+
+    /// The UUID already issued or registered for `key`, if any.
+    pub fn get(&self, scope: &str, key: &str) -> Option<&str> {
+        self.issued
+            .get(&(scope.to_string(), key.to_string()))
+            .map(String::as_str)
+    }
+
+    /// Rewrite a comma-separated list of keys into the UUIDs issued for them.
+    ///
+    /// Entries with no UUID in `scope` are kept as they are.
+    pub fn rewrite_refs(&self, scope: &str, keys: &str) -> String {
+        keys.split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(|k| self.get(scope, k).unwrap_or(k))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Give every `tag` child of `parent` without a `uuid` attribute one keyed
+    /// by `key(position, node)`.
+    ///
+    /// Items whose key was already issued are told apart by their position.
+    pub fn assign_missing(
+        &mut self,
+        parent: &mut XmlNode,
+        tag: &str,
+        scope: &str,
+        key: impl Fn(usize, &XmlNode) -> String,
+    ) {
+        for (idx, node) in parent
+            .children
+            .iter_mut()
+            .filter(|c| c.tag == tag)
+            .enumerate()
+        {
+            if node.attributes.contains_key("uuid") {
+                continue;
+            }
+            let mut item_key = key(idx, node);
+            if self.get(scope, &item_key).is_some() {
+                item_key = format!("{item_key}@{idx}");
+            }
+            let uuid = self.uuid_for(scope, &item_key);
+            node.attributes.insert("uuid".to_string(), uuid);
+        }
+    }
+}
+
+/// A version-4 shaped UUID derived from `scope` and `key` alone.
+pub fn stable_uuid(scope: &str, key: &str) -> String {
+    let mut seed = Vec::with_capacity(scope.len() + key.len() + 1);
+    seed.extend_from_slice(scope.as_bytes());
+    seed.push(0);
+    seed.extend_from_slice(key.as_bytes());
+    let digest = sha256(&seed);
+
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Whether `value` has the 8-4-4-4-12 hex layout of a UUID.
+pub fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(g, len)| g.len() == len && g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn collect_uuids(node: &XmlNode, out: &mut BTreeSet<String>) {
+    if let Some(uuid) = node.attributes.get("uuid") {
+        out.insert(uuid.clone());
+    }
+    for child in &node.children {
+        collect_uuids(child, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{is_uuid, stable_uuid, UuidRegistry};
+
+    #[test]
+    fn issues_stable_uuids_per_key() {
+        let mut registry = UuidRegistry::new();
+        let peer = registry.uuid_for("wireguard-peer", "0");
+        assert!(is_uuid(&peer));
+        assert_eq!(&peer[14..15], "4");
+        assert_eq!(registry.uuid_for("wireguard-peer", "0"), peer);
+        assert_eq!(peer, stable_uuid("wireguard-peer", "0"));
+        assert_ne!(registry.uuid_for("wireguard-peer", "1"), peer);
+        assert_ne!(registry.uuid_for("wireguard-server", "0"), peer);
+    }
+
+    #[test]
+    fn avoids_uuids_already_in_the_config() {
+        let taken = stable_uuid("kea-subnet4", "10.0.0.0/24");
+        let xml = format!(r#"<opnsense><item uuid="{taken}"/></opnsense>"#);
+        let root = parse(xml.as_bytes()).expect("parse");
+
+        let mut registry = UuidRegistry::from_config(&root);
+        let issued = registry.uuid_for("kea-subnet4", "10.0.0.0/24");
+        assert_ne!(issued, taken);
+        assert!(is_uuid(&issued));
+    }
+
+    #[test]
+    fn rewrites_reference_lists() {
+        let mut registry = UuidRegistry::new();
+        let a = registry.uuid_for("peer", "a");
+        registry.register("peer", "b", "kept-b");
+        assert_eq!(
+            registry.rewrite_refs("peer", "a, b,c"),
+            format!("{a},kept-b,c")
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::{bail, Context, Result};
+use pfopn_convert::baseline_check::{render_baseline_text, validate_baseline};
+use xml_diff_core::parse_file;
+
+use crate::cli::{OutputFormat, ScanTarget, ValidateBaselineArgs};
+use crate::convert::{parse_interface_map, requested_backend};
+
+/// Check a target baseline; fails on errors (and warnings with `--strict`).
+pub fn run_validate_baseline(args: ValidateBaselineArgs) -> Result<()> {
+    let target = parse_file(&args.file)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let source = args
+        .source
+        .as_ref()
+        .map(|path| parse_file(path).with_context(|| format!("failed to parse {}", path.display())))
+        .transpose()?;
+    let interface_map = parse_interface_map(&args.map_interface)?;
+    let to = args.to.map(|to| match to {
+        ScanTarget::Pfsense => "pfsense",
+        ScanTarget::Opnsense => "opnsense",
+    });
+
+    let report = validate_baseline(
+        &target,
+        to,
+        source.as_ref(),
+        requested_backend(args.backend),
+        &interface_map,
+    );
+    match args.format {
+        OutputFormat::Text => println!("{}", render_baseline_text(&report)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    if report.errors > 0 {
+        bail!("validate-baseline failed: {} errors", report.errors);
+    }
+    if args.strict && report.warnings > 0 {
+        bail!(
+            "validate-baseline failed in strict mode: {} warnings",
+            report.warnings
+        );
+    }
+    Ok(())
+}
@@ -1,33 +1,78 @@
+use std::collections::BTreeMap;
+
 use serde::Serialize;
-use xml_diff_core::XmlNode;
+use xml_diff_core::{ParseWarning, XmlNode};
 
 use crate::backend_detect::detect_dhcp_backend;
-use crate::detect::{detect_config, detect_version_info, ConfigFlavor};
+use crate::detect::{
+    detect_config, detect_opnsense_edition, detect_pfsense_edition, detect_version_info,
+    ConfigFlavor, OpnSenseEdition, PfSenseEdition,
+};
 use crate::ipsec_dependencies::compare_ipsec_dependencies;
 use crate::openvpn_dependencies::compare_openvpn_dependencies;
-use crate::profile::load_profile_with_source;
+use crate::orphans::find_orphans;
+use crate::profile::{load_profile_with_source, resolve_baseline_version};
 use crate::scan::{build_scan_report_with_version, ScanReport};
-use crate::verify_bridges::bridge_findings;
+use crate::site_map::SiteMap;
+use crate::verify_bridges::{bridge_findings, bridge_findings_against};
+use crate::verify_certs::cert_findings;
+use crate::verify_codes::FindingCode;
+use crate::verify_dhcp::dhcp_findings;
+use crate::verify_dhcp_relay::relay_findings;
+use crate::verify_exposure::{exposure_findings, exposure_findings_against};
+use crate::verify_fix::FixReport;
+use crate::verify_identity::identity_findings;
 use crate::verify_interfaces::{
     interface_reference_findings, FindingSeverity, VerifyFinding as RefFinding,
 };
+use crate::verify_ipsec_algorithms::ipsec_algorithm_findings;
+use crate::verify_link_settings::link_setting_findings;
 use crate::verify_nat::nat_findings;
+use crate::verify_netboot::netboot_findings_against;
+use crate::verify_openvpn_export::export_readiness;
+use crate::verify_ppps::ppp_findings;
 use crate::verify_profile::profile_findings;
+use crate::verify_routes::route_findings;
 use crate::verify_rule_dupes::rule_duplicate_findings;
 use crate::verify_rule_refs::rule_reference_findings;
+use crate::verify_shellcmds::shellcmd_findings;
+use crate::verify_suppress::SuppressionReport;
+use crate::verify_tunnel_pairs::tunnel_pair_findings;
+use crate::verify_uuid_refs::uuid_reference_findings;
+use crate::verify_values::value_findings;
 use crate::verify_wireguard::wireguard_findings;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum VerifySeverity {
     Error,
     Warning,
+    /// Expected given how the config was produced; not counted.
+    Info,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct VerifyIssue {
     pub severity: VerifySeverity,
-    pub code: String,
+    pub code: FindingCode,
+    /// Config path the issue is about (`filter/rule[3]`), when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
     pub message: String,
+    /// Structured details, such as the name a reference failed to resolve.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub data: BTreeMap<String, String>,
+}
+
+impl VerifyIssue {
+    pub fn new(severity: VerifySeverity, code: FindingCode, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code,
+            path: None,
+            message: message.into(),
+            data: BTreeMap::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -39,6 +84,26 @@ pub struct VerifyReport {
     pub errors: usize,
     pub warnings: usize,
     pub issues: Vec<VerifyIssue>,
+    /// Set by `verify --fix`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixes: Option<FixReport>,
+    /// Set by `verify --suppressions`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppressions: Option<SuppressionReport>,
+}
+
+impl VerifyReport {
+    /// Drop every issue whose code is in `codes` and recount.
+    pub fn suppress(&mut self, codes: &[FindingCode]) {
+        self.issues.retain(|issue| !codes.contains(&issue.code));
+        self.recount();
+    }
+
+    /// Recompute `errors` and `warnings` from `issues`.
+    pub(crate) fn recount(&mut self) {
+        self.errors = count_severity(&self.issues, VerifySeverity::Error);
+        self.warnings = count_severity(&self.issues, VerifySeverity::Warning);
+    }
 }
 
 pub fn build_verify_report(root: &XmlNode, target: Option<&str>) -> VerifyReport {
@@ -62,40 +127,46 @@ pub fn build_verify_report_with_version(
     let version = target_version.unwrap_or(&detected_version).to_string();
     let scan = build_scan_report_with_version(root, target, None, None);
     let profile_platform = target.unwrap_or(&platform);
+    let profile_version = profile_version(root, profile_platform, target_version, &version);
     let (profile, profiles_source) =
-        load_profile_with_source(profile_platform, &version, profiles_dir)
+        load_profile_with_source(profile_platform, &profile_version, profiles_dir)
             .map_or((None, None), |(p, s)| (Some(p), Some(s)));
 
     let mut issues = Vec::new();
     if flavor == ConfigFlavor::Unknown {
         issues.push(err(
-            "unknown_platform",
+            FindingCode::UnknownPlatform,
             "root tag is not recognized as pfsense/opnsense",
         ));
     }
     issues.extend(required_section_issues(root, &platform));
     issues.extend(plugin_issues(&scan));
     issues.extend(interface_issues(root));
+    issues.extend(link_setting_issues(root));
     issues.extend(bridge_issues(root));
     issues.extend(nat_issues(root));
+    issues.extend(route_issues(root));
+    issues.extend(value_issues(root));
+    issues.extend(dhcp_pool_issues(root));
     issues.extend(rule_reference_issues(root));
     issues.extend(rule_duplicate_issues(root));
+    issues.extend(identity_issues(root));
     issues.extend(wireguard_issues(root));
-    issues.extend(dhcp_issues(root, &platform));
+    issues.extend(uuid_reference_issues(root));
+    issues.extend(ppp_issues(root, target.unwrap_or(&platform)));
+    issues.extend(dhcp_issues(root, &platform, &version));
+    issues.extend(relay_issues(root));
     if let Some(profile) = profile.as_ref() {
         issues.extend(profile_findings(root, profile).into_iter().map(map_finding));
     }
     issues.extend(openvpn_issues(root));
     issues.extend(ipsec_issues(root));
+    issues.extend(ipsec_algorithm_issues(root));
+    issues.extend(cert_issues(root));
+    issues.extend(shellcmd_issues(root));
 
-    let errors = issues
-        .iter()
-        .filter(|i| i.severity == VerifySeverity::Error)
-        .count();
-    let warnings = issues
-        .iter()
-        .filter(|i| i.severity == VerifySeverity::Warning)
-        .count();
+    let errors = count_severity(&issues, VerifySeverity::Error);
+    let warnings = count_severity(&issues, VerifySeverity::Warning);
 
     VerifyReport {
         platform,
@@ -105,9 +176,193 @@ pub fn build_verify_report_with_version(
         errors,
         warnings,
         issues,
+        fixes: None,
+        suppressions: None,
+    }
+}
+
+/// Profile version to check against.
+///
+/// pfSense Plus and CE share config version numbers, so a Plus release
+/// given as `--target-version` (`24.11`), or a pfSense config detected as
+/// Plus, selects the `plus` profile. Likewise a Business Edition release
+/// (`25.4`, `25.10`) or an OPNsense config detected as Business Edition
+/// selects the `business` profile. A bare major (`99`) keeps its own
+/// profile lookup.
+fn profile_version(
+    root: &XmlNode,
+    platform: &str,
+    target_version: Option<&str>,
+    version: &str,
+) -> String {
+    if platform == "opnsense" {
+        let business = match target_version {
+            Some(requested) => is_business_release(requested),
+            None => detect_opnsense_edition(root)
+                .is_some_and(|e| e.edition == OpnSenseEdition::Business),
+        };
+        return if business {
+            "business".to_string()
+        } else {
+            version.to_string()
+        };
+    }
+    if platform != "pfsense" {
+        return version.to_string();
+    }
+    let plus = match target_version {
+        Some(requested) => {
+            requested.contains('.') && resolve_baseline_version(platform, requested) == Some("plus")
+                || requested.trim().eq_ignore_ascii_case("plus")
+        }
+        None => detect_pfsense_edition(root).is_some_and(|e| e.edition == PfSenseEdition::Plus),
+    };
+    if plus {
+        "plus".to_string()
+    } else {
+        version.to_string()
+    }
+}
+
+/// Business Edition releases are numbered `.4` and `.10`; community
+/// releases `.1` and `.7`.
+fn is_business_release(version: &str) -> bool {
+    let mut parts = version.trim().split('.');
+    parts.next().is_some_and(|m| m.parse::<u32>().is_ok())
+        && parts.next().is_some_and(|m| m == "4" || m == "10")
+}
+
+/// Add one `orphan_object` warning per object nothing refers to.
+///
+/// Opt-in (`verify --orphans`): orphans are harmless on restore, but listing
+/// them helps spot dependencies that were renamed or dropped in transfer.
+pub fn add_orphan_issues(report: &mut VerifyReport, root: &XmlNode) {
+    for orphan in find_orphans(root) {
+        let mut issue = VerifyIssue::new(
+            VerifySeverity::Warning,
+            FindingCode::OrphanObject,
+            format!(
+                "{} '{}' ({}) is not referenced by anything",
+                orphan.kind, orphan.name, orphan.label
+            ),
+        );
+        issue.data.insert("kind".to_string(), orphan.kind);
+        issue.data.insert("name".to_string(), orphan.name);
+        report.issues.push(issue);
+        report.warnings += 1;
+    }
+}
+
+/// Add the WAN exposure audit (`verify --exposure`).
+///
+/// With `source`, exposures the source config did not have are errors
+/// (`wan_exposure_added`); the rest are `wan_exposure` warnings.
+pub fn add_exposure_issues(report: &mut VerifyReport, root: &XmlNode, source: Option<&XmlNode>) {
+    let findings = match source {
+        Some(source) => exposure_findings_against(root, source),
+        None => exposure_findings(root),
+    };
+    for issue in findings.into_iter().map(map_finding) {
+        match issue.severity {
+            VerifySeverity::Error => report.errors += 1,
+            VerifySeverity::Warning => report.warnings += 1,
+            VerifySeverity::Info => {}
+        }
+        report.issues.push(issue);
+    }
+}
+
+/// Add the netboot comparison (`verify --netboot-source`): PXE settings
+/// `source` served that `root` does not are `netboot_dropped` errors.
+pub fn add_netboot_issues(report: &mut VerifyReport, root: &XmlNode, source: &XmlNode) {
+    for issue in netboot_findings_against(root, source)
+        .into_iter()
+        .map(map_finding)
+    {
+        match issue.severity {
+            VerifySeverity::Error => report.errors += 1,
+            VerifySeverity::Warning => report.warnings += 1,
+            VerifySeverity::Info => {}
+        }
+        report.issues.push(issue);
+    }
+}
+
+/// Compare both ends of IPsec tunnels between devices converted together
+/// (`batch` with a site map): `tunnel_pair_*` issues naming both devices.
+pub fn tunnel_pair_issues(devices: &[(&str, &XmlNode)], sites: &SiteMap) -> Vec<VerifyIssue> {
+    tunnel_pair_findings(devices, sites)
+        .into_iter()
+        .map(map_finding)
+        .collect()
+}
+
+/// Re-run the bridge checks against the pre-conversion config
+/// (`verify --bridge-source`): members whose interface was in `source` but
+/// is gone from `root` become `bridge_member_pruned` errors instead of
+/// `missing_bridge_member`.
+pub fn add_bridge_issues(report: &mut VerifyReport, root: &XmlNode, source: &XmlNode) {
+    let before = bridge_issues(root);
+    report.issues.retain(|issue| !before.contains(issue));
+    report.issues.extend(
+        bridge_findings_against(root, source)
+            .into_iter()
+            .map(map_finding),
+    );
+    report.errors = count_severity(&report.issues, VerifySeverity::Error);
+    report.warnings = count_severity(&report.issues, VerifySeverity::Warning);
+}
+
+/// Downgrade `cert_missing_key` warnings to info for configs written with
+/// `convert --strip-private-keys`, whose keys are provisioned on the target
+/// separately (`verify --keys-stripped`).
+pub fn downgrade_missing_keys(report: &mut VerifyReport) {
+    for issue in &mut report.issues {
+        if issue.code == FindingCode::CertMissingKey && issue.severity == VerifySeverity::Warning {
+            issue.severity = VerifySeverity::Info;
+            report.warnings -= 1;
+        }
     }
 }
 
+/// Add the OpenVPN client export readiness check (`verify --openvpn-export`):
+/// an `openvpn_export_missing` warning per item a server lacks, and an
+/// `openvpn_export_ready` info line per server with nothing missing.
+pub fn add_openvpn_export_issues(report: &mut VerifyReport, root: &XmlNode) {
+    for readiness in export_readiness(root) {
+        if readiness.is_ready() {
+            report.issues.push(VerifyIssue::new(
+                VerifySeverity::Info,
+                FindingCode::OpenvpnExportReady,
+                format!("{}: ready for client export", readiness.server),
+            ));
+            continue;
+        }
+        for item in &readiness.missing {
+            report.issues.push(VerifyIssue::new(
+                VerifySeverity::Warning,
+                FindingCode::OpenvpnExportMissing,
+                format!("{}: {item}", readiness.server),
+            ));
+            report.warnings += 1;
+        }
+    }
+}
+
+/// Add one `parse_recovered` warning per problem lenient parsing recovered
+/// from, ahead of the findings for the recovered tree.
+pub fn add_parse_warnings(report: &mut VerifyReport, warnings: &[ParseWarning]) {
+    let issues = warnings.iter().map(|warning| {
+        VerifyIssue::new(
+            VerifySeverity::Warning,
+            FindingCode::ParseRecovered,
+            warning.to_string(),
+        )
+    });
+    report.issues.splice(0..0, issues);
+    report.warnings += warnings.len();
+}
+
 pub fn render_verify_text(report: &VerifyReport, verbose: bool) -> String {
     let mut out = Vec::new();
     out.push(format!(
@@ -124,6 +379,12 @@ pub fn render_verify_text(report: &VerifyReport, verbose: bool) -> String {
         "result errors={} warnings={}",
         report.errors, report.warnings
     ));
+    if let Some(fixes) = &report.fixes {
+        out.extend(render_fixes(fixes));
+    }
+    if let Some(suppressions) = &report.suppressions {
+        out.extend(render_suppressions(suppressions));
+    }
     if report.issues.is_empty() {
         out.push("issues".to_string());
         out.push("- none".to_string());
@@ -134,12 +395,41 @@ pub fn render_verify_text(report: &VerifyReport, verbose: bool) -> String {
         let sev = match issue.severity {
             VerifySeverity::Error => "error",
             VerifySeverity::Warning => "warning",
+            VerifySeverity::Info => "info",
         };
         out.push(format!("- [{sev}] {}: {}", issue.code, issue.message));
     }
     out.join("\n")
 }
 
+fn render_suppressions(report: &SuppressionReport) -> Vec<String> {
+    let verb = if report.updated {
+        "baseline updated"
+    } else {
+        "suppressed"
+    };
+    let mut out = vec![format!("{verb}={} by {}", report.suppressed, report.file)];
+    for entry in &report.unused {
+        out.push(format!("- unused suppression: {entry}"));
+    }
+    out
+}
+
+fn render_fixes(report: &FixReport) -> Vec<String> {
+    let mut out = vec![format!(
+        "fixes {}={}",
+        if report.dry_run { "planned" } else { "applied" },
+        report.fixes.len()
+    )];
+    if let Some(output) = &report.output {
+        out.push(format!("fixed config: {output}"));
+    }
+    for fix in &report.fixes {
+        out.push(format!("- [{}] {}: {}", fix.class, fix.path, fix.message));
+    }
+    out
+}
+
 fn required_section_issues(root: &XmlNode, platform: &str) -> Vec<VerifyIssue> {
     let required: &[&str] = match platform {
         "pfsense" | "opnsense" => &["system", "interfaces"],
@@ -149,7 +439,7 @@ fn required_section_issues(root: &XmlNode, platform: &str) -> Vec<VerifyIssue> {
     for section in required {
         if root.get_child(section).is_none() {
             out.push(err(
-                "missing_required_section",
+                FindingCode::MissingRequiredSection,
                 &format!("required section '{section}' is missing"),
             ));
         }
@@ -161,13 +451,13 @@ fn plugin_issues(scan: &ScanReport) -> Vec<VerifyIssue> {
     let mut out = Vec::new();
     for plugin in &scan.unsupported_plugins {
         out.push(warn(
-            "unsupported_plugin",
+            FindingCode::UnsupportedPlugin,
             &format!("unsupported plugin detected: {plugin}"),
         ));
     }
     for plugin in &scan.missing_target_compat {
         out.push(warn(
-            "target_plugin_compat",
+            FindingCode::TargetPluginCompat,
             &format!("plugin not marked compatible with target: {plugin}"),
         ));
     }
@@ -181,6 +471,13 @@ fn interface_issues(root: &XmlNode) -> Vec<VerifyIssue> {
         .collect()
 }
 
+fn link_setting_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    link_setting_findings(root)
+        .into_iter()
+        .map(map_finding)
+        .collect()
+}
+
 fn bridge_issues(root: &XmlNode) -> Vec<VerifyIssue> {
     bridge_findings(root).into_iter().map(map_finding).collect()
 }
@@ -189,6 +486,18 @@ fn nat_issues(root: &XmlNode) -> Vec<VerifyIssue> {
     nat_findings(root).into_iter().map(map_finding).collect()
 }
 
+fn route_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    route_findings(root).into_iter().map(map_finding).collect()
+}
+
+fn dhcp_pool_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    dhcp_findings(root).into_iter().map(map_finding).collect()
+}
+
+fn value_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    value_findings(root).into_iter().map(map_finding).collect()
+}
+
 fn rule_reference_issues(root: &XmlNode) -> Vec<VerifyIssue> {
     rule_reference_findings(root)
         .into_iter()
@@ -203,6 +512,13 @@ fn rule_duplicate_issues(root: &XmlNode) -> Vec<VerifyIssue> {
         .collect()
 }
 
+fn identity_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    identity_findings(root)
+        .into_iter()
+        .map(map_finding)
+        .collect()
+}
+
 fn wireguard_issues(root: &XmlNode) -> Vec<VerifyIssue> {
     wireguard_findings(root)
         .into_iter()
@@ -210,7 +526,35 @@ fn wireguard_issues(root: &XmlNode) -> Vec<VerifyIssue> {
         .collect()
 }
 
-fn dhcp_issues(root: &XmlNode, platform: &str) -> Vec<VerifyIssue> {
+fn uuid_reference_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    uuid_reference_findings(root)
+        .into_iter()
+        .map(map_finding)
+        .collect()
+}
+
+fn shellcmd_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    shellcmd_findings(root)
+        .into_iter()
+        .map(map_finding)
+        .collect()
+}
+
+fn ipsec_algorithm_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    ipsec_algorithm_findings(root)
+        .into_iter()
+        .map(map_finding)
+        .collect()
+}
+
+fn ppp_issues(root: &XmlNode, platform: &str) -> Vec<VerifyIssue> {
+    ppp_findings(root, platform)
+        .into_iter()
+        .map(map_finding)
+        .collect()
+}
+
+fn dhcp_issues(root: &XmlNode, platform: &str, version: &str) -> Vec<VerifyIssue> {
     let mut out = Vec::new();
     let has_legacy = root.get_child("dhcpd").is_some()
         || root.get_child("dhcpdv6").is_some()
@@ -230,19 +574,19 @@ fn dhcp_issues(root: &XmlNode, platform: &str) -> Vec<VerifyIssue> {
             .to_ascii_lowercase();
         if backend == "isc" && !has_legacy {
             out.push(err(
-                "dhcp_backend_inconsistent",
+                FindingCode::DhcpBackendInconsistent,
                 "pfSense backend is ISC but legacy DHCP sections are missing (dhcpd/dhcpdv6/dhcpd6)",
             ));
         }
         if backend == "isc" && has_pfsense_kea {
             out.push(err(
-                "dhcp_backend_inconsistent",
+                FindingCode::DhcpBackendInconsistent,
                 "pfSense backend is ISC but Kea section is still present",
             ));
         }
         if backend == "kea" && !has_pfsense_kea {
             out.push(warn(
-                "dhcp_backend_advisory",
+                FindingCode::DhcpBackendAdvisory,
                 "pfSense backend is Kea but top-level <kea> section is missing; verify DHCP backend state on target",
             ));
         }
@@ -252,22 +596,22 @@ fn dhcp_issues(root: &XmlNode, platform: &str) -> Vec<VerifyIssue> {
     if platform == "opnsense" {
         let backend = detect_dhcp_backend(root).mode;
         if backend == "isc" {
-            if !opnsense_has_declared_plugin(root, "os-isc-dhcp") {
+            if !isc_dhcp_in_core(version) && !opnsense_has_declared_plugin(root, "os-isc-dhcp") {
                 out.push(err(
-                    "dhcp_backend_inconsistent",
+                    FindingCode::DhcpBackendInconsistent,
                     "OPNsense appears to use ISC DHCP but os-isc-dhcp is not declared in system.firmware.plugins",
                 ));
             }
             if !has_legacy {
                 out.push(err(
-                    "dhcp_backend_inconsistent",
+                    FindingCode::DhcpBackendInconsistent,
                     "OPNsense appears to use ISC DHCP but legacy DHCP sections are missing (dhcpd/dhcpdv6/dhcpd6)",
                 ));
             }
         }
         if backend == "kea" && !has_opnsense_kea {
             out.push(err(
-                "dhcp_backend_inconsistent",
+                FindingCode::DhcpBackendInconsistent,
                 "OPNsense appears to use Kea but OPNsense.Kea section is missing",
             ));
         }
@@ -276,24 +620,28 @@ fn dhcp_issues(root: &XmlNode, platform: &str) -> Vec<VerifyIssue> {
     out
 }
 
+fn relay_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    relay_findings(root).into_iter().map(map_finding).collect()
+}
+
 fn openvpn_issues(root: &XmlNode) -> Vec<VerifyIssue> {
     let report = compare_openvpn_dependencies(root, root);
     let mut out = Vec::new();
     for ca in report.left_to_right.missing_ca_ids {
         out.push(err(
-            "openvpn_missing_ca",
+            FindingCode::OpenvpnMissingCa,
             &format!("OpenVPN references missing CA '{ca}'"),
         ));
     }
     for cert in report.left_to_right.missing_cert_ids {
         out.push(err(
-            "openvpn_missing_cert",
+            FindingCode::OpenvpnMissingCert,
             &format!("OpenVPN references missing cert '{cert}'"),
         ));
     }
     for user in report.left_to_right.missing_usernames {
         out.push(err(
-            "openvpn_missing_user",
+            FindingCode::OpenvpnMissingUser,
             &format!("OpenVPN references missing user '{user}'"),
         ));
     }
@@ -305,39 +653,39 @@ fn ipsec_issues(root: &XmlNode) -> Vec<VerifyIssue> {
     let mut out = Vec::new();
     for ca in report.left_to_right.missing_ca_ids {
         out.push(err(
-            "ipsec_missing_ca",
+            FindingCode::IpsecMissingCa,
             &format!("IPsec references missing CA '{ca}'"),
         ));
     }
     for cert in report.left_to_right.missing_cert_ids {
         out.push(err(
-            "ipsec_missing_cert",
+            FindingCode::IpsecMissingCert,
             &format!("IPsec references missing cert '{cert}'"),
         ));
     }
     for iface in report.left_to_right.missing_interfaces {
         out.push(err(
-            "ipsec_missing_interface",
+            FindingCode::IpsecMissingInterface,
             &format!("IPsec references missing interface '{iface}'"),
         ));
     }
     out
 }
 
-fn err(code: &str, message: &str) -> VerifyIssue {
-    VerifyIssue {
-        severity: VerifySeverity::Error,
-        code: code.to_string(),
-        message: message.to_string(),
-    }
+fn cert_issues(root: &XmlNode) -> Vec<VerifyIssue> {
+    cert_findings(root).into_iter().map(map_finding).collect()
 }
 
-fn warn(code: &str, message: &str) -> VerifyIssue {
-    VerifyIssue {
-        severity: VerifySeverity::Warning,
-        code: code.to_string(),
-        message: message.to_string(),
-    }
+fn count_severity(issues: &[VerifyIssue], severity: VerifySeverity) -> usize {
+    issues.iter().filter(|i| i.severity == severity).count()
+}
+
+fn err(code: FindingCode, message: &str) -> VerifyIssue {
+    VerifyIssue::new(VerifySeverity::Error, code, message)
+}
+
+fn warn(code: FindingCode, message: &str) -> VerifyIssue {
+    VerifyIssue::new(VerifySeverity::Warning, code, message)
 }
 
 fn map_finding(finding: RefFinding) -> VerifyIssue {
@@ -347,10 +695,23 @@ fn map_finding(finding: RefFinding) -> VerifyIssue {
             FindingSeverity::Warning => VerifySeverity::Warning,
         },
         code: finding.code,
+        path: finding.path,
         message: finding.message,
+        data: finding.data,
     }
 }
 
+/// OPNsense releases before 26.1 ship ISC DHCP in core: community 15.1
+/// through 25.7 and Business Edition through 25.10. Anything else, including
+/// an unknown version, needs the `os-isc-dhcp` plugin.
+fn isc_dhcp_in_core(version: &str) -> bool {
+    version
+        .split('.')
+        .next()
+        .and_then(|m| m.trim().parse::<u32>().ok())
+        .is_some_and(|major| (15..26).contains(&major))
+}
+
 fn opnsense_has_declared_plugin(root: &XmlNode, plugin: &str) -> bool {
     let plugins = root
         .get_child("system")
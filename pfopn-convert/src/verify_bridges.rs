@@ -13,11 +13,20 @@
 //!
 //! - Bridges must have at least one member
 //! - All member interfaces must exist
+//! - Member options (`stp`, `edge`, `ifpriority`, ...) must name bridge members
 //! - Bridge interface names (if specified) should follow convention
+//!
+//! Against the pre-conversion config ([`bridge_findings_against`]), members
+//! that were interfaces in the source but are gone from the output are
+//! reported as pruned during conversion.
+
+use std::collections::BTreeSet;
 
 use xml_diff_core::XmlNode;
 
-use crate::verify_interfaces::{collect_defined_interface_names, FindingSeverity, VerifyFinding};
+use crate::transform::bridges::{is_member_option, member_option_interfaces};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::{collect_defined_interface_names, VerifyFinding};
 
 /// Find all bridge configuration problems.
 ///
@@ -34,6 +43,30 @@ use crate::verify_interfaces::{collect_defined_interface_names, FindingSeverity,
 ///
 /// Vector of findings (errors and warnings). Empty if no problems found.
 pub fn bridge_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    findings(root, &BTreeSet::new())
+}
+
+/// Find bridge problems, comparing against the pre-conversion config.
+///
+/// Same checks as [`bridge_findings`], except a missing member that was an
+/// interface in `source` is a `bridge_member_pruned` error: conversion
+/// dropped the interface (usually because the target baseline does not
+/// assign its port) while the bridge still lists it.
+pub fn bridge_findings_against(root: &XmlNode, source: &XmlNode) -> Vec<VerifyFinding> {
+    let source_ifaces = source
+        .get_child("interfaces")
+        .map(|ifaces| {
+            ifaces
+                .children
+                .iter()
+                .map(|c| c.tag.to_ascii_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    findings(root, &source_ifaces)
+}
+
+fn findings(root: &XmlNode, source_ifaces: &BTreeSet<String>) -> Vec<VerifyFinding> {
     let Some(bridges) = root.get_child("bridges") else {
         return Vec::new();
     };
@@ -60,31 +93,49 @@ pub fn bridge_findings(root: &XmlNode) -> Vec<VerifyFinding> {
             .to_ascii_lowercase();
 
         if members.is_empty() && bridgeif.is_empty() {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Error,
-                code: "empty_bridge_members".to_string(),
-                message: format!("bridge #{idx} has no members"),
-            });
+            out.push(VerifyFinding::error(
+                FindingCode::EmptyBridgeMembers,
+                format!("bridge #{idx} has no members"),
+            ));
             continue;
         }
 
-        for member in members {
-            if !defined.contains(&member) {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Error,
-                    code: "missing_bridge_member".to_string(),
-                    message: format!("bridge #{idx} references missing member '{member}'"),
-                });
+        for member in &members {
+            if defined.contains(member) {
+                continue;
+            }
+            if source_ifaces.contains(member) {
+                out.push(VerifyFinding::error(
+                    FindingCode::BridgeMemberPruned,
+                    format!(
+                        "bridge #{idx} member '{member}' was pruned during conversion; assign its port in the target baseline or remove it from the bridge"
+                    ),
+                ));
+            } else {
+                out.push(VerifyFinding::error(
+                    FindingCode::MissingBridgeMember,
+                    format!("bridge #{idx} references missing member '{member}'"),
+                ));
+            }
+        }
+        for option in bridged.children.iter().filter(|c| is_member_option(&c.tag)) {
+            for name in member_option_interfaces(option) {
+                if !members.contains(&name) {
+                    out.push(VerifyFinding::warning(
+                        FindingCode::BridgeOptionNotMember,
+                        format!(
+                            "bridge #{idx} {} names '{name}', which is not a bridge member",
+                            option.tag
+                        ),
+                    ));
+                }
             }
         }
         if !bridgeif.is_empty() && !defined.contains(&bridgeif) && !is_bridge_token(&bridgeif) {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Warning,
-                code: "missing_bridge_interface".to_string(),
-                message: format!(
-                    "bridge #{idx} bridgeif references missing interface '{bridgeif}'"
-                ),
-            });
+            out.push(VerifyFinding::warning(
+                FindingCode::MissingBridgeInterface,
+                format!("bridge #{idx} bridgeif references missing interface '{bridgeif}'"),
+            ));
         }
     }
 
@@ -132,7 +183,7 @@ fn is_bridge_token(token: &str) -> bool {
 mod tests {
     use xml_diff_core::parse;
 
-    use super::bridge_findings;
+    use super::{bridge_findings, bridge_findings_against};
 
     #[test]
     fn detects_empty_bridge_members() {
@@ -143,4 +194,30 @@ mod tests {
         let findings = bridge_findings(&root);
         assert!(findings.iter().any(|f| f.code == "empty_bridge_members"));
     }
+
+    #[test]
+    fn flags_member_options_for_non_members() {
+        let root = parse(
+            br#"<opnsense><interfaces><lan/><opt1/></interfaces><bridges><bridged><members>lan,opt1</members><stp>lan,opt2</stp><ifpriority>opt1:64</ifpriority></bridged></bridges></opnsense>"#,
+        )
+        .expect("parse");
+        let findings = bridge_findings(&root);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "bridge_option_not_member");
+        assert!(findings[0].message.contains("stp names 'opt2'"));
+    }
+
+    #[test]
+    fn reports_members_pruned_during_conversion() {
+        let root = parse(
+            br#"<opnsense><interfaces><lan/></interfaces><bridges><bridged><members>lan,opt1,opt9</members></bridged></bridges></opnsense>"#,
+        )
+        .expect("parse");
+        let source =
+            parse(br#"<pfsense><interfaces><lan/><opt1/></interfaces></pfsense>"#).expect("parse");
+        let findings = bridge_findings_against(&root, &source);
+        let codes: Vec<&str> = findings.iter().map(|f| f.code.as_str()).collect();
+        assert_eq!(codes, ["bridge_member_pruned", "missing_bridge_member"]);
+        assert!(findings[0].message.contains("'opt1'"));
+    }
 }
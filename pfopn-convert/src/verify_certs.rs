@@ -0,0 +1,593 @@
+//! Certificate and CA integrity validation.
+//!
+//! Decodes the base64 PEM blobs stored in `<ca>` and `<cert>` entries and
+//! checks that a converted config still carries a usable certificate store.
+//!
+//! ## Checks Performed
+//!
+//! 1. **Decoding** — `<crt>` and `<prv>` blobs decode to PEM certificates/keys
+//! 2. **Key pairs** — A certificate's private key matches its public key
+//!    (RSA modulus or EC point comparison), and a certificate a service
+//!    presents has a private key (`cert_missing_key`)
+//! 3. **Chains** — A `<caref>` names an existing CA whose subject matches the
+//!    certificate issuer, and that CA is marked as a CA
+//! 4. **Expiry** — Certificates and CAs are currently valid
+//! 5. **Service references** — Captive portal zones reference existing
+//!    certificates; an unresolved web GUI reference is a warning (OpenVPN and
+//!    IPsec are covered by their own dependency checks)
+//!
+//! Duplicate refids are reported by [`crate::verify_identity`].
+//!
+//! Sanitized exports replace blobs with a `REDACTED` placeholder; those blobs
+//! are skipped rather than reported as corrupt.
+//!
+//! ## Service Usage
+//!
+//! [`cert_usage`] lists which services reference each refid (OpenVPN, IPsec,
+//! web GUI, captive portal, user certificates). Findings name those services
+//! so the impact of a broken certificate is visible in the report.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use base64::Engine;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::pem::parse_x509_pem;
+use x509_parser::prelude::FromDer;
+use x509_parser::public_key::PublicKey;
+use xml_diff_core::XmlNode;
+
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::{FindingSeverity, VerifyFinding};
+
+/// DER-encoded OID body for `rsaEncryption` (1.2.840.113549.1.1.1).
+const OID_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+/// DER-encoded OID body for `id-ecPublicKey` (1.2.840.10045.2.1).
+const OID_EC: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+/// A `<ca>` or `<cert>` entry with its decoded blobs.
+struct StoreEntry<'a> {
+    kind: &'static str,
+    node: &'a XmlNode,
+    refid: String,
+    descr: String,
+    crt: Blob,
+    prv: Blob,
+}
+
+/// Decoding state of a `<crt>` or `<prv>` blob.
+enum Blob {
+    Missing,
+    Redacted,
+    Invalid(String),
+    Der(Vec<u8>),
+}
+
+/// Public key material used to match a private key against a certificate.
+#[derive(Debug, PartialEq, Eq)]
+enum KeyMaterial {
+    Rsa(Vec<u8>),
+    Ec(Vec<u8>),
+}
+
+/// Find all certificate store problems.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to validate
+///
+/// # Returns
+///
+/// Vector of findings (errors and warnings). Empty if no problems found.
+pub fn cert_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let entries = collect_entries(root);
+    let usage = cert_usage(root);
+    let mut out = Vec::new();
+
+    let cas: BTreeMap<&str, &StoreEntry> = entries
+        .iter()
+        .filter(|e| e.kind == "CA")
+        .map(|e| (e.refid.as_str(), e))
+        .collect();
+    for entry in &entries {
+        out.extend(entry_findings(entry, &cas, &usage));
+    }
+
+    let known: BTreeSet<&str> = entries.iter().map(|e| e.refid.as_str()).collect();
+    for (refid, services) in &usage {
+        if known.contains(refid.as_str()) {
+            continue;
+        }
+        for service in services {
+            if service == "web GUI" {
+                // The firewall regenerates a self-signed GUI certificate, so
+                // this is survivable but still worth fixing.
+                out.push(finding(
+                    FindingSeverity::Warning,
+                    FindingCode::WebguiMissingCert,
+                    format!("web GUI certificate reference '{refid}' cannot be resolved"),
+                ));
+            } else if service.starts_with("captive portal") {
+                out.push(finding(
+                    FindingSeverity::Error,
+                    FindingCode::CertMissingReference,
+                    format!("{service} references missing certificate '{refid}'"),
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Map each referenced refid to the services that use it.
+///
+/// Covers OpenVPN (legacy and OPNsense instances), IPsec phase 1, the web
+/// GUI, captive portal zones (both platforms), and user certificates.
+pub fn cert_usage(root: &XmlNode) -> BTreeMap<String, Vec<String>> {
+    let mut usage: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut add = |refid: Option<&str>, service: String| {
+        let Some(refid) = refid.map(str::trim).filter(|r| !r.is_empty()) else {
+            return;
+        };
+        let services = usage.entry(refid.to_string()).or_default();
+        if !services.contains(&service) {
+            services.push(service);
+        }
+    };
+
+    if let Some(openvpn) = root.get_child("openvpn") {
+        for instance in &openvpn.children {
+            let kind = instance.tag.trim_start_matches("openvpn-");
+            let id = text(instance, "vpnid").unwrap_or("?");
+            for tag in ["certref", "caref"] {
+                add(instance.get_text(&[tag]), format!("OpenVPN {kind} {id}"));
+            }
+        }
+    }
+    let opn = root.get_child("OPNsense");
+    if let Some(instances) = opn
+        .and_then(|o| o.get_child("OpenVPN"))
+        .and_then(|o| o.get_child("Instances"))
+    {
+        for instance in instances.get_children("Instance") {
+            let name = text(instance, "description").unwrap_or("?");
+            for tag in ["cert", "ca"] {
+                add(
+                    instance.get_text(&[tag]),
+                    format!("OpenVPN instance {name}"),
+                );
+            }
+        }
+    }
+    if let Some(ipsec) = root.get_child("ipsec") {
+        for phase1 in ipsec.get_children("phase1") {
+            let id = text(phase1, "ikeid").unwrap_or("?");
+            for tag in ["certref", "caref"] {
+                add(phase1.get_text(&[tag]), format!("IPsec phase1 {id}"));
+            }
+        }
+    }
+    add(
+        root.get_text(&["system", "webgui", "ssl-certref"]),
+        "web GUI".to_string(),
+    );
+    if let Some(portal) = root.get_child("captiveportal") {
+        for zone in &portal.children {
+            add(
+                zone.get_text(&["certref"]),
+                format!("captive portal {}", zone.tag),
+            );
+        }
+    }
+    if let Some(zones) = opn
+        .and_then(|o| o.get_child("captiveportal"))
+        .and_then(|c| c.get_child("zones"))
+    {
+        for zone in zones.get_children("zone") {
+            let name = text(zone, "description").unwrap_or("?");
+            add(
+                zone.get_text(&["certificate"]),
+                format!("captive portal {name}"),
+            );
+        }
+    }
+    if let Some(system) = root.get_child("system") {
+        for user in system.get_children("user") {
+            let name = text(user, "name").unwrap_or("?");
+            for cert in user.get_children("cert") {
+                add(cert.text.as_deref(), format!("user {name}"));
+            }
+        }
+    }
+    usage
+}
+
+/// Decoding, key-pair, chain, and expiry checks for one entry.
+fn entry_findings(
+    entry: &StoreEntry,
+    cas: &BTreeMap<&str, &StoreEntry>,
+    usage: &BTreeMap<String, Vec<String>>,
+) -> Vec<VerifyFinding> {
+    let label = describe(entry, usage);
+    let mut out = Vec::new();
+
+    let cert = match &entry.crt {
+        Blob::Der(der) => match X509Certificate::from_der(der) {
+            Ok((_, cert)) => Some(cert),
+            Err(e) => {
+                out.push(decode_error(
+                    FindingCode::CertDecodeFailed,
+                    &label,
+                    &e.to_string(),
+                ));
+                None
+            }
+        },
+        Blob::Invalid(reason) => {
+            out.push(decode_error(FindingCode::CertDecodeFailed, &label, reason));
+            None
+        }
+        Blob::Missing | Blob::Redacted => None,
+    };
+
+    match &entry.prv {
+        Blob::Der(der) => match (private_key_material(der), &cert) {
+            (None, _) => out.push(decode_error(
+                FindingCode::CertKeyDecodeFailed,
+                &label,
+                "unsupported private key encoding",
+            )),
+            (Some(key), Some(cert)) => {
+                if cert_key_material(cert).is_some_and(|public| public != key) {
+                    out.push(finding(
+                        FindingSeverity::Error,
+                        FindingCode::CertKeyMismatch,
+                        format!("{label}: private key does not match the certificate"),
+                    ));
+                }
+            }
+            (Some(_), None) => {}
+        },
+        Blob::Invalid(reason) => out.push(decode_error(
+            FindingCode::CertKeyDecodeFailed,
+            &label,
+            reason,
+        )),
+        Blob::Missing if entry.kind == "certificate" && needs_key(entry, usage) => {
+            out.push(finding(
+                FindingSeverity::Warning,
+                FindingCode::CertMissingKey,
+                format!("{label} has no private key"),
+            ));
+        }
+        Blob::Missing | Blob::Redacted => {}
+    }
+
+    if let Some(caref) = text(entry.node, "caref") {
+        match cas.get(caref) {
+            None => out.push(finding(
+                FindingSeverity::Error,
+                FindingCode::CertMissingCa,
+                format!("{label} references missing CA '{caref}'"),
+            )),
+            Some(ca) => out.extend(chain_findings(&label, cert.as_ref(), ca)),
+        }
+    }
+
+    if let Some(cert) = &cert {
+        if !cert.validity().is_valid() {
+            out.push(finding(
+                FindingSeverity::Warning,
+                FindingCode::CertExpired,
+                format!(
+                    "{label} is not currently valid (valid {} to {})",
+                    cert.validity().not_before,
+                    cert.validity().not_after
+                ),
+            ));
+        }
+    }
+    out
+}
+
+/// Check that `cert` was issued by the CA entry it references.
+fn chain_findings(
+    label: &str,
+    cert: Option<&X509Certificate>,
+    ca: &StoreEntry,
+) -> Vec<VerifyFinding> {
+    let Blob::Der(der) = &ca.crt else {
+        return Vec::new();
+    };
+    let Ok((_, ca_cert)) = X509Certificate::from_der(der) else {
+        // Reported as cert_decode_failed on the CA itself.
+        return Vec::new();
+    };
+
+    let mut out = Vec::new();
+    if !ca_cert.is_ca() {
+        out.push(finding(
+            FindingSeverity::Warning,
+            FindingCode::CertCaNotCa,
+            format!(
+                "{label} chains to '{}' which is not marked as a CA",
+                ca.refid
+            ),
+        ));
+    }
+    if let Some(cert) = cert {
+        if cert.issuer().as_raw() != ca_cert.subject().as_raw() {
+            out.push(finding(
+                FindingSeverity::Error,
+                FindingCode::CertChainMismatch,
+                format!(
+                    "{label} was issued by '{}' but references CA '{}' ('{}')",
+                    cert.issuer(),
+                    ca.refid,
+                    ca_cert.subject()
+                ),
+            ));
+        }
+    }
+    out
+}
+
+fn collect_entries(root: &XmlNode) -> Vec<StoreEntry<'_>> {
+    let mut out = Vec::new();
+    for (tag, kind) in [("ca", "CA"), ("cert", "certificate")] {
+        for node in root.get_children(tag) {
+            out.push(StoreEntry {
+                kind,
+                node,
+                refid: text(node, "refid").unwrap_or("").to_string(),
+                descr: text(node, "descr").unwrap_or("").to_string(),
+                crt: decode_blob(node.get_text(&["crt"])),
+                prv: decode_blob(node.get_text(&["prv"])),
+            });
+        }
+    }
+    out
+}
+
+/// DER bytes of a `<ca>` or `<cert>` entry's certificate, if it decodes.
+pub(crate) fn certificate_der(node: &XmlNode) -> Option<Vec<u8>> {
+    match decode_blob(node.get_text(&["crt"])) {
+        Blob::Der(der) => Some(der),
+        _ => None,
+    }
+}
+
+/// Decode a base64-wrapped PEM blob into DER.
+fn decode_blob(raw: Option<&str>) -> Blob {
+    let Some(raw) = raw.map(str::trim).filter(|r| !r.is_empty()) else {
+        return Blob::Missing;
+    };
+    if raw.eq_ignore_ascii_case("redacted") {
+        return Blob::Redacted;
+    }
+    let compact: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+    let pem = match base64::engine::general_purpose::STANDARD.decode(compact) {
+        Ok(pem) => pem,
+        Err(e) => return Blob::Invalid(format!("invalid base64 ({e})")),
+    };
+    match parse_x509_pem(&pem) {
+        Ok((_, pem)) => Blob::Der(pem.contents),
+        Err(e) => Blob::Invalid(format!("invalid PEM ({e})")),
+    }
+}
+
+/// Extract the public half of a PKCS#8, PKCS#1 RSA, or SEC1 EC private key.
+fn private_key_material(der: &[u8]) -> Option<KeyMaterial> {
+    let (_, body, _) = der_tlv(der)?;
+    let (version_tag, _, rest) = der_tlv(body)?;
+    if version_tag != 0x02 {
+        return None;
+    }
+    let (next_tag, next, after) = der_tlv(rest)?;
+    match next_tag {
+        // PKCS#8: AlgorithmIdentifier, then the wrapped key in an OCTET STRING.
+        0x30 => {
+            let (_, oid, _) = der_tlv(next)?;
+            let (_, inner, _) = der_tlv(after)?;
+            if oid == OID_RSA {
+                private_key_material(inner)
+            } else if oid == OID_EC {
+                ec_public_point(inner)
+            } else {
+                None
+            }
+        }
+        // PKCS#1 RSAPrivateKey: version, modulus, ...
+        0x02 => Some(KeyMaterial::Rsa(strip_leading_zeros(next).to_vec())),
+        // SEC1 ECPrivateKey: version, privateKey OCTET STRING, ...
+        0x04 => ec_public_point(der),
+        _ => None,
+    }
+}
+
+/// Read the optional `[1] publicKey` field of a SEC1 ECPrivateKey.
+fn ec_public_point(der: &[u8]) -> Option<KeyMaterial> {
+    let (_, mut body, _) = der_tlv(der)?;
+    while !body.is_empty() {
+        let (tag, value, rest) = der_tlv(body)?;
+        if tag == 0xa1 {
+            let (_, bits, _) = der_tlv(value)?;
+            // Skip the BIT STRING unused-bits byte.
+            return Some(KeyMaterial::Ec(bits.get(1..)?.to_vec()));
+        }
+        body = rest;
+    }
+    None
+}
+
+fn cert_key_material(cert: &X509Certificate) -> Option<KeyMaterial> {
+    match cert.public_key().parsed().ok()? {
+        PublicKey::RSA(rsa) => Some(KeyMaterial::Rsa(strip_leading_zeros(rsa.modulus).to_vec())),
+        PublicKey::EC(point) => Some(KeyMaterial::Ec(point.data().to_vec())),
+        _ => None,
+    }
+}
+
+/// Split one DER TLV off `input`, returning `(tag, value, rest)`.
+fn der_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first & 0x80 == 0 {
+        (usize::from(first), rest)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let len = rest[..count]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+        (len, &rest[count..])
+    };
+    if rest.len() < len {
+        return None;
+    }
+    Some((tag, &rest[..len], &rest[len..]))
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Human-readable entry label including the services that use it.
+fn describe(entry: &StoreEntry, usage: &BTreeMap<String, Vec<String>>) -> String {
+    let name = if entry.descr.is_empty() {
+        &entry.refid
+    } else {
+        &entry.descr
+    };
+    let mut label = format!("{} '{}' ({})", entry.kind, name, entry.refid);
+    if let Some(services) = usage.get(&entry.refid) {
+        label.push_str(&format!(" used by {}", services.join(", ")));
+    }
+    label
+}
+
+/// Whether a service presents `entry` and so needs its private key. User
+/// certificates are often issued without keeping the key on the firewall.
+fn needs_key(entry: &StoreEntry, usage: &BTreeMap<String, Vec<String>>) -> bool {
+    !matches!(entry.crt, Blob::Missing)
+        && usage
+            .get(&entry.refid)
+            .is_some_and(|services| services.iter().any(|s| !s.starts_with("user ")))
+}
+
+fn decode_error(code: FindingCode, label: &str, reason: &str) -> VerifyFinding {
+    finding(
+        FindingSeverity::Error,
+        code,
+        format!("{label} could not be decoded: {reason}"),
+    )
+}
+
+fn finding(severity: FindingSeverity, code: FindingCode, message: String) -> VerifyFinding {
+    VerifyFinding::new(severity, code, message)
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use base64::Engine;
+    use xml_diff_core::parse;
+
+    use super::{cert_findings, cert_usage};
+
+    fn blob(name: &str) -> String {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../fixtures/certs")
+            .join(name);
+        let pem = std::fs::read(&path).expect("read fixture");
+        base64::engine::general_purpose::STANDARD.encode(pem)
+    }
+
+    fn codes(xml: &str) -> Vec<String> {
+        let root = parse(xml.as_bytes()).expect("parse");
+        cert_findings(&root)
+            .into_iter()
+            .map(|f| f.code.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn accepts_matching_chain_and_keys() {
+        let xml = format!(
+            "<pfsense><system><webgui><ssl-certref>c1</ssl-certref></webgui></system>\
+             <ca><refid>ca1</refid><crt>{}</crt><prv>{}</prv></ca>\
+             <cert><refid>c1</refid><caref>ca1</caref><crt>{}</crt><prv>{}</prv></cert>\
+             <cert><refid>c2</refid><crt>REDACTED</crt><prv>REDACTED</prv></cert></pfsense>",
+            blob("ca.crt"),
+            blob("ca.key"),
+            blob("leaf.crt"),
+            blob("leaf.key")
+        );
+        assert!(codes(&xml).is_empty());
+    }
+
+    #[test]
+    fn flags_mismatched_key_chain_expiry_and_duplicates() {
+        let xml = format!(
+            "<pfsense><system><webgui><ssl-certref>gone</ssl-certref></webgui></system>\
+             <ca><refid>ca1</refid><crt>{}</crt></ca>\
+             <cert><refid>c1</refid><caref>ca1</caref><crt>{}</crt><prv>{}</prv></cert>\
+             <cert><refid>c1</refid><caref>ca9</caref><crt>not base64!</crt></cert></pfsense>",
+            blob("ca.crt"),
+            blob("expired.crt"),
+            blob("leaf.key")
+        );
+        assert_eq!(
+            codes(&xml),
+            vec![
+                "cert_key_mismatch",
+                "cert_chain_mismatch",
+                "cert_expired",
+                "cert_decode_failed",
+                "cert_missing_ca",
+                "webgui_missing_cert",
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_presented_certificate_without_key() {
+        let xml = format!(
+            "<pfsense><system><webgui><ssl-certref>c1</ssl-certref></webgui>\
+             <user><name>alice</name><cert>c2</cert></user></system>\
+             <cert><refid>c1</refid><crt>{leaf}</crt></cert>\
+             <cert><refid>c2</refid><crt>{leaf}</crt></cert></pfsense>",
+            leaf = blob("leaf.crt")
+        );
+        assert_eq!(codes(&xml), vec!["cert_missing_key"]);
+    }
+
+    #[test]
+    fn lists_services_per_certificate() {
+        let root = parse(
+            br#"<opnsense><system><webgui><ssl-certref>c1</ssl-certref></webgui><user><name>alice</name><cert>c2</cert></user></system><openvpn><openvpn-server><vpnid>1</vpnid><certref>c1</certref><caref>ca1</caref></openvpn-server></openvpn><ipsec><phase1><ikeid>3</ikeid><certref>c1</certref></phase1></ipsec><OPNsense><captiveportal><zones><zone><description>guest</description><certificate>c1</certificate></zone></zones></captiveportal></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let usage = cert_usage(&root);
+        assert_eq!(
+            usage["c1"],
+            vec![
+                "OpenVPN server 1",
+                "IPsec phase1 3",
+                "web GUI",
+                "captive portal guest"
+            ]
+        );
+        assert_eq!(usage["ca1"], vec!["OpenVPN server 1"]);
+        assert_eq!(usage["c2"], vec!["user alice"]);
+    }
+}
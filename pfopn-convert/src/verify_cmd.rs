@@ -1,23 +1,95 @@
+use std::fs;
+
 use anyhow::{bail, Context, Result};
-use pfopn_convert::verify::{build_verify_report_with_version, render_verify_text};
-use xml_diff_core::parse_file;
+use pfopn_convert::canonical;
+use pfopn_convert::report_template::render_template_file;
+use pfopn_convert::verify::{
+    add_bridge_issues, add_exposure_issues, add_netboot_issues, add_openvpn_export_issues,
+    add_orphan_issues, add_parse_warnings, build_verify_report_with_version,
+    downgrade_missing_keys, render_verify_text,
+};
+use pfopn_convert::verify_fix::{apply_fixes, FixReport};
+use pfopn_convert::verify_suppress::Suppressions;
+use xml_diff_core::{
+    parse_file, parse_file_with_options, write_file_with_options, ParseOptions, XmlNode,
+};
 
 use crate::cli::{OutputFormat, ScanTarget, VerifyArgs};
+use crate::path_guard::ensure_output_not_same;
 
 pub fn run_verify(args: VerifyArgs) -> Result<()> {
-    let node = parse_file(&args.file)
-        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let parsed = parse_file_with_options(
+        &args.file,
+        &ParseOptions {
+            lenient: true,
+            ..ParseOptions::default()
+        },
+    )
+    .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let mut node = parsed.root;
+    let fixes = if args.fix.is_empty() {
+        None
+    } else {
+        Some(run_fixes(&args, &mut node)?)
+    };
     let to = args.to.map(scan_target_name);
-    let report = build_verify_report_with_version(
+    let mut report = build_verify_report_with_version(
         &node,
         to,
         args.target_version.as_deref(),
         args.profiles_dir.as_deref(),
     );
+    add_parse_warnings(&mut report, &parsed.warnings);
+    report.fixes = fixes;
+    if args.orphans {
+        add_orphan_issues(&mut report, &node);
+    }
+    if let Some(path) = &args.exposure_source {
+        let source =
+            parse_file(path).with_context(|| format!("failed to parse {}", path.display()))?;
+        add_exposure_issues(&mut report, &node, Some(&source));
+    } else if args.exposure {
+        add_exposure_issues(&mut report, &node, None);
+    }
+    if let Some(path) = &args.netboot_source {
+        let source =
+            parse_file(path).with_context(|| format!("failed to parse {}", path.display()))?;
+        add_netboot_issues(&mut report, &node, &source);
+    }
+    if let Some(path) = &args.bridge_source {
+        let source =
+            parse_file(path).with_context(|| format!("failed to parse {}", path.display()))?;
+        add_bridge_issues(&mut report, &node, &source);
+    }
+    if args.openvpn_export {
+        add_openvpn_export_issues(&mut report, &node);
+    }
+    if args.keys_stripped {
+        downgrade_missing_keys(&mut report);
+    }
+    if let Some(path) = &args.suppressions {
+        let suppressions = if args.update_baseline {
+            let previous = if path.exists() {
+                Suppressions::load(path)?
+            } else {
+                Suppressions::default()
+            };
+            let baseline = Suppressions::baseline(&report, &previous);
+            fs::write(path, baseline.to_toml())
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            baseline
+        } else {
+            Suppressions::load(path)?
+        };
+        let mut applied = suppressions.apply(&mut report, &path.display().to_string());
+        applied.updated = args.update_baseline;
+        report.suppressions = Some(applied);
+    }
 
-    match args.format {
-        OutputFormat::Text => println!("{}", render_verify_text(&report, args.verbose)),
-        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    match (&args.template, args.format) {
+        (Some(template), _) => print!("{}", render_template_file(template, &report)?),
+        (None, OutputFormat::Text) => println!("{}", render_verify_text(&report, args.verbose)),
+        (None, OutputFormat::Json) => println!("{}", serde_json::to_string_pretty(&report)?),
     }
 
     if report.errors > 0 {
@@ -29,6 +101,32 @@ pub fn run_verify(args: VerifyArgs) -> Result<()> {
     Ok(())
 }
 
+/// Apply `--fix` to `node` and write it, or list the fixes on `--dry-run`.
+///
+/// A dry run leaves `node` untouched, so the report describes the input.
+fn run_fixes(args: &VerifyArgs, node: &mut XmlNode) -> Result<FixReport> {
+    if args.dry_run {
+        let mut fixed = node.clone();
+        return Ok(FixReport {
+            dry_run: true,
+            output: None,
+            fixes: apply_fixes(&mut fixed, &args.fix),
+        });
+    }
+    let Some(output) = &args.fix_output else {
+        bail!("--fix needs --fix-output <FILE> (or --dry-run to only list fixes)");
+    };
+    ensure_output_not_same(output, &[&args.file])?;
+    let fixes = apply_fixes(node, &args.fix);
+    write_file_with_options(node, output, &canonical::write_options(&node.tag))
+        .with_context(|| format!("failed to write fixed config {}", output.display()))?;
+    Ok(FixReport {
+        dry_run: false,
+        output: Some(output.display().to_string()),
+        fixes,
+    })
+}
+
 fn scan_target_name(target: ScanTarget) -> &'static str {
     match target {
         ScanTarget::Pfsense => "pfsense",
@@ -0,0 +1,473 @@
+//! Stable machine-readable codes for verify findings.
+//!
+//! Every check in the `verify_*` modules reports a [`FindingCode`], so
+//! tooling can filter or suppress findings by code instead of matching on
+//! message text. Codes serialize as the snake_case names shown by
+//! `verify` (`missing_interface_reference`); those names are stable, while
+//! messages may be reworded.
+//!
+//! Related codes share a prefix (`cert_`, `dhcp_`, `openvpn_`, `profile_`,
+//! ...), which [`FindingCode::as_str`] exposes for grouping.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The kind of problem a verify finding reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FindingCode {
+    BackendNotReady,
+    BackendSourceConflict,
+    BridgeMemberPruned,
+    BridgeOptionNotMember,
+    BusinessOnlyPlugin,
+    CertCaNotCa,
+    CertChainMismatch,
+    CertDecodeFailed,
+    CertExpired,
+    CertKeyDecodeFailed,
+    CertKeyMismatch,
+    CertMissingCa,
+    CertMissingKey,
+    CertMissingReference,
+    CidrHostBits,
+    DefaultRuleOverlap,
+    DhcpBackendAdvisory,
+    DhcpBackendInconsistent,
+    DhcpPoolInvalid,
+    DhcpRangeOutsideSubnet,
+    DhcpRangeOverlap,
+    DhcpRangeReversed,
+    DhcpReservationConflict,
+    DhcpReservationInRange,
+    DhcpReservationOutsideSubnet,
+    DhcpSubnetOverlap,
+    DuplicateAliasName,
+    DuplicateCertRefid,
+    DuplicateFirewallRule,
+    DuplicateGatewayName,
+    DuplicateInterface,
+    DuplicateInterfaceAssignment,
+    DuplicateObject,
+    DuplicateStaticMac,
+    DuplicateUserName,
+    DuplicateUserUid,
+    EmptyBridgeMembers,
+    EmptyIfgroupReference,
+    HostnameHasDomain,
+    IfgroupCaseMismatch,
+    InterfaceForcedMedia,
+    InterfaceJumboMtu,
+    InvalidValue,
+    IpsecMissingCa,
+    IpsecMissingCert,
+    IpsecMissingInterface,
+    IpsecWeakAlgorithm,
+    MissingAliasReference,
+    MissingBridgeInterface,
+    MissingBridgeMember,
+    MissingContainer,
+    MissingGatewayGroupMember,
+    MissingGatewayInterface,
+    MissingGatewayReference,
+    MissingIfgroupMember,
+    MissingInterface,
+    MissingInterfaceReference,
+    MissingRequiredSection,
+    MissingRouteGateway,
+    MissingRouteInterface,
+    MissingScheduleReference,
+    NatInvalidOutboundMode,
+    NatMissingAlias,
+    NatMissingAssociatedRule,
+    NatMissingInterface,
+    NetbootDropped,
+    NoInterfaces,
+    OpenvpnCipherRejected,
+    OpenvpnExportMissing,
+    OpenvpnExportReady,
+    OpenvpnMissingCa,
+    OpenvpnMissingCert,
+    OpenvpnMissingUser,
+    OpenvpnOptionRejected,
+    OpenvpnTlsModeUnsupported,
+    OpenvpnTopologyUnsupported,
+    OrphanObject,
+    ParseRecovered,
+    PlatformMismatch,
+    PlusOnlyFeature,
+    PppIgnoredOption,
+    ProfileBridgeMissingMembers,
+    ProfileDeprecatedSectionPresent,
+    ProfileGatewayMissingRequiredField,
+    ProfileMissingRequiredSection,
+    ProfileRouteMissingAnyRequiredField,
+    ProfileRouteMissingRequiredField,
+    ProfileRuleDuplicateOrderKey,
+    ProfileRuleMissingOrderKey,
+    ProfileRuleMissingRequiredField,
+    RelayServerOnRelayInterface,
+    RelayServerUnreachable,
+    RelayUnknownInterface,
+    RouteMissingGateway,
+    RouteOverlapsInterfaceSubnet,
+    SamePlatform,
+    SharedDevice,
+    ShellcmdReview,
+    TargetPluginCompat,
+    TunnelPairMissing,
+    TunnelPairNetworkMismatch,
+    TunnelPairPskMismatch,
+    TunnelPairUnreadable,
+    UnknownPlatform,
+    UnknownVersion,
+    UnsupportedPlugin,
+    UuidDuplicate,
+    UuidMissing,
+    UuidRefUnresolved,
+    WanExposure,
+    WanExposureAdded,
+    WebguiMissingCert,
+    WireguardMissingInterfaceAssignment,
+}
+
+impl FindingCode {
+    pub const ALL: &'static [FindingCode] = &[
+        FindingCode::BackendNotReady,
+        FindingCode::BackendSourceConflict,
+        FindingCode::BridgeMemberPruned,
+        FindingCode::BridgeOptionNotMember,
+        FindingCode::BusinessOnlyPlugin,
+        FindingCode::CertCaNotCa,
+        FindingCode::CertChainMismatch,
+        FindingCode::CertDecodeFailed,
+        FindingCode::CertExpired,
+        FindingCode::CertKeyDecodeFailed,
+        FindingCode::CertKeyMismatch,
+        FindingCode::CertMissingCa,
+        FindingCode::CertMissingKey,
+        FindingCode::CertMissingReference,
+        FindingCode::CidrHostBits,
+        FindingCode::DefaultRuleOverlap,
+        FindingCode::DhcpBackendAdvisory,
+        FindingCode::DhcpBackendInconsistent,
+        FindingCode::DhcpPoolInvalid,
+        FindingCode::DhcpRangeOutsideSubnet,
+        FindingCode::DhcpRangeOverlap,
+        FindingCode::DhcpRangeReversed,
+        FindingCode::DhcpReservationConflict,
+        FindingCode::DhcpReservationInRange,
+        FindingCode::DhcpReservationOutsideSubnet,
+        FindingCode::DhcpSubnetOverlap,
+        FindingCode::DuplicateAliasName,
+        FindingCode::DuplicateCertRefid,
+        FindingCode::DuplicateFirewallRule,
+        FindingCode::DuplicateGatewayName,
+        FindingCode::DuplicateInterface,
+        FindingCode::DuplicateInterfaceAssignment,
+        FindingCode::DuplicateObject,
+        FindingCode::DuplicateStaticMac,
+        FindingCode::DuplicateUserName,
+        FindingCode::DuplicateUserUid,
+        FindingCode::EmptyBridgeMembers,
+        FindingCode::EmptyIfgroupReference,
+        FindingCode::HostnameHasDomain,
+        FindingCode::IfgroupCaseMismatch,
+        FindingCode::InterfaceForcedMedia,
+        FindingCode::InterfaceJumboMtu,
+        FindingCode::InvalidValue,
+        FindingCode::IpsecMissingCa,
+        FindingCode::IpsecMissingCert,
+        FindingCode::IpsecMissingInterface,
+        FindingCode::IpsecWeakAlgorithm,
+        FindingCode::MissingAliasReference,
+        FindingCode::MissingBridgeInterface,
+        FindingCode::MissingBridgeMember,
+        FindingCode::MissingContainer,
+        FindingCode::MissingGatewayGroupMember,
+        FindingCode::MissingGatewayInterface,
+        FindingCode::MissingGatewayReference,
+        FindingCode::MissingIfgroupMember,
+        FindingCode::MissingInterface,
+        FindingCode::MissingInterfaceReference,
+        FindingCode::MissingRequiredSection,
+        FindingCode::MissingRouteGateway,
+        FindingCode::MissingRouteInterface,
+        FindingCode::MissingScheduleReference,
+        FindingCode::NatInvalidOutboundMode,
+        FindingCode::NatMissingAlias,
+        FindingCode::NatMissingAssociatedRule,
+        FindingCode::NatMissingInterface,
+        FindingCode::NetbootDropped,
+        FindingCode::NoInterfaces,
+        FindingCode::OpenvpnCipherRejected,
+        FindingCode::OpenvpnExportMissing,
+        FindingCode::OpenvpnExportReady,
+        FindingCode::OpenvpnMissingCa,
+        FindingCode::OpenvpnMissingCert,
+        FindingCode::OpenvpnMissingUser,
+        FindingCode::OpenvpnOptionRejected,
+        FindingCode::OpenvpnTlsModeUnsupported,
+        FindingCode::OpenvpnTopologyUnsupported,
+        FindingCode::OrphanObject,
+        FindingCode::ParseRecovered,
+        FindingCode::PlatformMismatch,
+        FindingCode::PlusOnlyFeature,
+        FindingCode::PppIgnoredOption,
+        FindingCode::ProfileBridgeMissingMembers,
+        FindingCode::ProfileDeprecatedSectionPresent,
+        FindingCode::ProfileGatewayMissingRequiredField,
+        FindingCode::ProfileMissingRequiredSection,
+        FindingCode::ProfileRouteMissingAnyRequiredField,
+        FindingCode::ProfileRouteMissingRequiredField,
+        FindingCode::ProfileRuleDuplicateOrderKey,
+        FindingCode::ProfileRuleMissingOrderKey,
+        FindingCode::ProfileRuleMissingRequiredField,
+        FindingCode::RelayServerOnRelayInterface,
+        FindingCode::RelayServerUnreachable,
+        FindingCode::RelayUnknownInterface,
+        FindingCode::RouteMissingGateway,
+        FindingCode::RouteOverlapsInterfaceSubnet,
+        FindingCode::SamePlatform,
+        FindingCode::SharedDevice,
+        FindingCode::ShellcmdReview,
+        FindingCode::TargetPluginCompat,
+        FindingCode::TunnelPairMissing,
+        FindingCode::TunnelPairNetworkMismatch,
+        FindingCode::TunnelPairPskMismatch,
+        FindingCode::TunnelPairUnreadable,
+        FindingCode::UnknownPlatform,
+        FindingCode::UnknownVersion,
+        FindingCode::UnsupportedPlugin,
+        FindingCode::UuidDuplicate,
+        FindingCode::UuidMissing,
+        FindingCode::UuidRefUnresolved,
+        FindingCode::WanExposure,
+        FindingCode::WanExposureAdded,
+        FindingCode::WebguiMissingCert,
+        FindingCode::WireguardMissingInterfaceAssignment,
+    ];
+
+    /// Code as shown in reports and JSON output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FindingCode::BackendNotReady => "backend_not_ready",
+            FindingCode::BackendSourceConflict => "backend_source_conflict",
+            FindingCode::BridgeMemberPruned => "bridge_member_pruned",
+            FindingCode::BridgeOptionNotMember => "bridge_option_not_member",
+            FindingCode::BusinessOnlyPlugin => "business_only_plugin",
+            FindingCode::CertCaNotCa => "cert_ca_not_ca",
+            FindingCode::CertChainMismatch => "cert_chain_mismatch",
+            FindingCode::CertDecodeFailed => "cert_decode_failed",
+            FindingCode::CertExpired => "cert_expired",
+            FindingCode::CertKeyDecodeFailed => "cert_key_decode_failed",
+            FindingCode::CertKeyMismatch => "cert_key_mismatch",
+            FindingCode::CertMissingCa => "cert_missing_ca",
+            FindingCode::CertMissingKey => "cert_missing_key",
+            FindingCode::CertMissingReference => "cert_missing_reference",
+            FindingCode::CidrHostBits => "cidr_host_bits",
+            FindingCode::DefaultRuleOverlap => "default_rule_overlap",
+            FindingCode::DhcpBackendAdvisory => "dhcp_backend_advisory",
+            FindingCode::DhcpBackendInconsistent => "dhcp_backend_inconsistent",
+            FindingCode::DhcpPoolInvalid => "dhcp_pool_invalid",
+            FindingCode::DhcpRangeOutsideSubnet => "dhcp_range_outside_subnet",
+            FindingCode::DhcpRangeOverlap => "dhcp_range_overlap",
+            FindingCode::DhcpRangeReversed => "dhcp_range_reversed",
+            FindingCode::DhcpReservationConflict => "dhcp_reservation_conflict",
+            FindingCode::DhcpReservationInRange => "dhcp_reservation_in_range",
+            FindingCode::DhcpReservationOutsideSubnet => "dhcp_reservation_outside_subnet",
+            FindingCode::DhcpSubnetOverlap => "dhcp_subnet_overlap",
+            FindingCode::DuplicateAliasName => "duplicate_alias_name",
+            FindingCode::DuplicateCertRefid => "duplicate_cert_refid",
+            FindingCode::DuplicateFirewallRule => "duplicate_firewall_rule",
+            FindingCode::DuplicateGatewayName => "duplicate_gateway_name",
+            FindingCode::DuplicateInterface => "duplicate_interface",
+            FindingCode::DuplicateInterfaceAssignment => "duplicate_interface_assignment",
+            FindingCode::DuplicateObject => "duplicate_object",
+            FindingCode::DuplicateStaticMac => "duplicate_static_mac",
+            FindingCode::DuplicateUserName => "duplicate_user_name",
+            FindingCode::DuplicateUserUid => "duplicate_user_uid",
+            FindingCode::EmptyBridgeMembers => "empty_bridge_members",
+            FindingCode::EmptyIfgroupReference => "empty_ifgroup_reference",
+            FindingCode::HostnameHasDomain => "hostname_has_domain",
+            FindingCode::IfgroupCaseMismatch => "ifgroup_case_mismatch",
+            FindingCode::InterfaceForcedMedia => "interface_forced_media",
+            FindingCode::InterfaceJumboMtu => "interface_jumbo_mtu",
+            FindingCode::InvalidValue => "invalid_value",
+            FindingCode::IpsecMissingCa => "ipsec_missing_ca",
+            FindingCode::IpsecMissingCert => "ipsec_missing_cert",
+            FindingCode::IpsecMissingInterface => "ipsec_missing_interface",
+            FindingCode::IpsecWeakAlgorithm => "ipsec_weak_algorithm",
+            FindingCode::MissingAliasReference => "missing_alias_reference",
+            FindingCode::MissingBridgeInterface => "missing_bridge_interface",
+            FindingCode::MissingBridgeMember => "missing_bridge_member",
+            FindingCode::MissingContainer => "missing_container",
+            FindingCode::MissingGatewayGroupMember => "missing_gateway_group_member",
+            FindingCode::MissingGatewayInterface => "missing_gateway_interface",
+            FindingCode::MissingGatewayReference => "missing_gateway_reference",
+            FindingCode::MissingIfgroupMember => "missing_ifgroup_member",
+            FindingCode::MissingInterface => "missing_interface",
+            FindingCode::MissingInterfaceReference => "missing_interface_reference",
+            FindingCode::MissingRequiredSection => "missing_required_section",
+            FindingCode::MissingRouteGateway => "missing_route_gateway",
+            FindingCode::MissingRouteInterface => "missing_route_interface",
+            FindingCode::MissingScheduleReference => "missing_schedule_reference",
+            FindingCode::NatInvalidOutboundMode => "nat_invalid_outbound_mode",
+            FindingCode::NatMissingAlias => "nat_missing_alias",
+            FindingCode::NatMissingAssociatedRule => "nat_missing_associated_rule",
+            FindingCode::NatMissingInterface => "nat_missing_interface",
+            FindingCode::NetbootDropped => "netboot_dropped",
+            FindingCode::NoInterfaces => "no_interfaces",
+            FindingCode::OpenvpnCipherRejected => "openvpn_cipher_rejected",
+            FindingCode::OpenvpnExportMissing => "openvpn_export_missing",
+            FindingCode::OpenvpnExportReady => "openvpn_export_ready",
+            FindingCode::OpenvpnMissingCa => "openvpn_missing_ca",
+            FindingCode::OpenvpnMissingCert => "openvpn_missing_cert",
+            FindingCode::OpenvpnMissingUser => "openvpn_missing_user",
+            FindingCode::OpenvpnOptionRejected => "openvpn_option_rejected",
+            FindingCode::OpenvpnTlsModeUnsupported => "openvpn_tls_mode_unsupported",
+            FindingCode::OpenvpnTopologyUnsupported => "openvpn_topology_unsupported",
+            FindingCode::OrphanObject => "orphan_object",
+            FindingCode::ParseRecovered => "parse_recovered",
+            FindingCode::PlatformMismatch => "platform_mismatch",
+            FindingCode::PlusOnlyFeature => "plus_only_feature",
+            FindingCode::PppIgnoredOption => "ppp_ignored_option",
+            FindingCode::ProfileBridgeMissingMembers => "profile_bridge_missing_members",
+            FindingCode::ProfileDeprecatedSectionPresent => "profile_deprecated_section_present",
+            FindingCode::ProfileGatewayMissingRequiredField => {
+                "profile_gateway_missing_required_field"
+            }
+            FindingCode::ProfileMissingRequiredSection => "profile_missing_required_section",
+            FindingCode::ProfileRouteMissingAnyRequiredField => {
+                "profile_route_missing_any_required_field"
+            }
+            FindingCode::ProfileRouteMissingRequiredField => "profile_route_missing_required_field",
+            FindingCode::ProfileRuleDuplicateOrderKey => "profile_rule_duplicate_order_key",
+            FindingCode::ProfileRuleMissingOrderKey => "profile_rule_missing_order_key",
+            FindingCode::ProfileRuleMissingRequiredField => "profile_rule_missing_required_field",
+            FindingCode::RelayServerOnRelayInterface => "relay_server_on_relay_interface",
+            FindingCode::RelayServerUnreachable => "relay_server_unreachable",
+            FindingCode::RelayUnknownInterface => "relay_unknown_interface",
+            FindingCode::RouteMissingGateway => "route_missing_gateway",
+            FindingCode::RouteOverlapsInterfaceSubnet => "route_overlaps_interface_subnet",
+            FindingCode::SamePlatform => "same_platform",
+            FindingCode::SharedDevice => "shared_device",
+            FindingCode::ShellcmdReview => "shellcmd_review",
+            FindingCode::TargetPluginCompat => "target_plugin_compat",
+            FindingCode::TunnelPairMissing => "tunnel_pair_missing",
+            FindingCode::TunnelPairNetworkMismatch => "tunnel_pair_network_mismatch",
+            FindingCode::TunnelPairPskMismatch => "tunnel_pair_psk_mismatch",
+            FindingCode::TunnelPairUnreadable => "tunnel_pair_unreadable",
+            FindingCode::UnknownPlatform => "unknown_platform",
+            FindingCode::UnknownVersion => "unknown_version",
+            FindingCode::UnsupportedPlugin => "unsupported_plugin",
+            FindingCode::UuidDuplicate => "uuid_duplicate",
+            FindingCode::UuidMissing => "uuid_missing",
+            FindingCode::UuidRefUnresolved => "uuid_ref_unresolved",
+            FindingCode::WanExposure => "wan_exposure",
+            FindingCode::WanExposureAdded => "wan_exposure_added",
+            FindingCode::WebguiMissingCert => "webgui_missing_cert",
+            FindingCode::WireguardMissingInterfaceAssignment => {
+                "wireguard_missing_interface_assignment"
+            }
+        }
+    }
+}
+
+impl fmt::Display for FindingCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FindingCode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        FindingCode::ALL
+            .iter()
+            .copied()
+            .find(|code| code.as_str() == value)
+            .ok_or_else(|| format!("unknown finding code '{value}'"))
+    }
+}
+
+impl PartialEq<str> for FindingCode {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for FindingCode {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl Serialize for FindingCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FindingCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use xml_diff_core::parse;
+
+    use super::FindingCode;
+    use crate::verify::build_verify_report;
+
+    #[test]
+    fn codes_are_unique_and_round_trip() {
+        let names: BTreeSet<&str> = FindingCode::ALL.iter().map(|c| c.as_str()).collect();
+        assert_eq!(names.len(), FindingCode::ALL.len());
+        for code in FindingCode::ALL {
+            assert_eq!(code.as_str().parse::<FindingCode>(), Ok(*code));
+            assert_eq!(
+                serde_json::to_string(code).expect("json"),
+                format!("\"{code}\"")
+            );
+        }
+        assert!("not_a_code".parse::<FindingCode>().is_err());
+    }
+
+    #[test]
+    fn reports_carry_typed_codes_and_details() {
+        let root = parse(
+            b"<pfsense><system/><interfaces><lan/></interfaces><filter>\
+              <rule><interface>opt7</interface></rule></filter></pfsense>",
+        )
+        .expect("parse");
+        let mut report = build_verify_report(&root, None);
+        let issue = report
+            .issues
+            .iter()
+            .find(|i| i.code == FindingCode::MissingInterfaceReference)
+            .expect("missing interface");
+        assert_eq!(
+            issue.data.get("interface").map(String::as_str),
+            Some("opt7")
+        );
+        let json = serde_json::to_value(issue).expect("json");
+        assert_eq!(json["code"], "missing_interface_reference");
+        assert_eq!(json["data"]["interface"], "opt7");
+
+        let errors = report.errors;
+        report.suppress(&[FindingCode::MissingInterfaceReference]);
+        assert_eq!(report.errors, errors - 1);
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| i.code != FindingCode::MissingInterfaceReference));
+    }
+}
@@ -0,0 +1,430 @@
+//! DHCPv4 pool, reservation, and subnet consistency.
+//!
+//! Checks both representations of a DHCPv4 server: ISC (`<dhcpd><iface>`,
+//! scoped by the interface address) and Kea (`<OPNsense><Kea><dhcp4>`
+//! subnets, pools, and reservations).
+//!
+//! ## Checks Performed
+//!
+//! 1. **Ranges** — Each dynamic range runs low to high, lies inside its
+//!    subnet, and does not overlap another range of the same scope
+//! 2. **Reservations** — Each reserved address lies inside its subnet and is
+//!    not reserved for two different clients; reservations inside a dynamic
+//!    range are warnings
+//! 3. **Subnets** — No two served scopes share address space
+//!
+//! [`range_problems`] is shared with the Kea migration, which drops ranges
+//! Kea would refuse to load instead of writing them out.
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use xml_diff_core::XmlNode;
+
+use crate::transform::booleans::flag_set;
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+use crate::verify_routes::{parse_cidr, same_network};
+
+/// An IPv4 subnet as `(network, prefix)`.
+pub type Subnet4 = (Ipv4Addr, u8);
+
+/// Why a dynamic range cannot be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeProblem {
+    /// The range ends before it starts.
+    Reversed,
+    /// An end of the range lies outside the subnet.
+    OutsideSubnet,
+    /// The range overlaps the earlier range at this index.
+    Overlaps(usize),
+}
+
+/// Find the unusable ranges of one scope, as `(index, problem)`.
+///
+/// Each range reports at most one problem. Overlaps are only checked
+/// against earlier ranges without problems, so dropping every reported range
+/// leaves a valid pool list.
+pub fn range_problems(
+    subnet: Option<Subnet4>,
+    ranges: &[(Ipv4Addr, Ipv4Addr)],
+) -> Vec<(usize, RangeProblem)> {
+    let mut problems = Vec::new();
+    let mut kept: Vec<usize> = Vec::new();
+    for (idx, (from, to)) in ranges.iter().enumerate() {
+        let problem = if from > to {
+            Some(RangeProblem::Reversed)
+        } else if subnet.is_some_and(|s| !contains(s, *from) || !contains(s, *to)) {
+            Some(RangeProblem::OutsideSubnet)
+        } else {
+            kept.iter()
+                .find(|&&k| ranges[k].0 <= *to && *from <= ranges[k].1)
+                .map(|&k| RangeProblem::Overlaps(k))
+        };
+        match problem {
+            Some(problem) => problems.push((idx, problem)),
+            None => kept.push(idx),
+        }
+    }
+    problems
+}
+
+/// Describe a [`RangeProblem`] for a message ending in the range itself.
+pub fn describe_range_problem(
+    problem: RangeProblem,
+    subnet: Option<Subnet4>,
+    ranges: &[(Ipv4Addr, Ipv4Addr)],
+) -> String {
+    match problem {
+        RangeProblem::Reversed => "ends before it starts".to_string(),
+        RangeProblem::OutsideSubnet => match subnet {
+            Some((network, prefix)) => format!("is outside the subnet {network}/{prefix}"),
+            None => "is outside the subnet".to_string(),
+        },
+        RangeProblem::Overlaps(other) => {
+            let (from, to) = ranges[other];
+            format!("overlaps range {from} - {to}")
+        }
+    }
+}
+
+/// True when `ip` lies inside `subnet`.
+pub fn contains(subnet: Subnet4, ip: Ipv4Addr) -> bool {
+    same_network(IpAddr::V4(subnet.0), IpAddr::V4(ip), subnet.1)
+}
+
+/// One DHCPv4 scope: an ISC interface block or a Kea `<subnet4>`.
+#[derive(Debug, Default)]
+struct Scope {
+    label: String,
+    subnet: Option<Subnet4>,
+    ranges: Vec<(Ipv4Addr, Ipv4Addr)>,
+    /// `(address, client)` where client is the MAC address.
+    reservations: Vec<(Ipv4Addr, String)>,
+    served: bool,
+}
+
+/// Find all DHCPv4 pool, reservation, and subnet problems.
+pub fn dhcp_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let mut out = Vec::new();
+    let isc = isc_scopes(root);
+    let kea = kea_scopes(root, &mut out);
+    for scope in isc.iter().chain(&kea) {
+        out.extend(scope_findings(scope));
+    }
+    out.extend(subnet_overlap_findings(&isc));
+    out.extend(subnet_overlap_findings(&kea));
+    out
+}
+
+fn scope_findings(scope: &Scope) -> Vec<VerifyFinding> {
+    let label = &scope.label;
+    let mut out = Vec::new();
+    for (idx, problem) in range_problems(scope.subnet, &scope.ranges) {
+        let (from, to) = scope.ranges[idx];
+        let code = match problem {
+            RangeProblem::Reversed => FindingCode::DhcpRangeReversed,
+            RangeProblem::OutsideSubnet => FindingCode::DhcpRangeOutsideSubnet,
+            RangeProblem::Overlaps(_) => FindingCode::DhcpRangeOverlap,
+        };
+        out.push(error(
+            code,
+            format!(
+                "{label}: range {from} - {to} {}",
+                describe_range_problem(problem, scope.subnet, &scope.ranges)
+            ),
+        ));
+    }
+    for (idx, (ip, client)) in scope.reservations.iter().enumerate() {
+        if let Some((network, prefix)) = scope.subnet.filter(|s| !contains(*s, *ip)) {
+            out.push(error(
+                FindingCode::DhcpReservationOutsideSubnet,
+                format!(
+                    "{label}: reservation {ip} ({client}) is outside the subnet {network}/{prefix}"
+                ),
+            ));
+        }
+        let taken = scope.reservations[..idx]
+            .iter()
+            .find(|(other_ip, other)| other_ip == ip && !other.eq_ignore_ascii_case(client));
+        if let Some((_, other)) = taken {
+            out.push(error(
+                FindingCode::DhcpReservationConflict,
+                format!("{label}: {ip} is reserved for both {other} and {client}"),
+            ));
+        }
+        if scope
+            .ranges
+            .iter()
+            .any(|(from, to)| from <= to && (*from..=*to).contains(ip))
+        {
+            out.push(VerifyFinding::warning(
+                FindingCode::DhcpReservationInRange,
+                format!(
+                    "{label}: reservation {ip} ({client}) lies inside a dynamic range; it can be leased to another client"
+                ),
+            ));
+        }
+    }
+    out
+}
+
+fn subnet_overlap_findings(scopes: &[Scope]) -> Vec<VerifyFinding> {
+    let served: Vec<(&str, Subnet4)> = scopes
+        .iter()
+        .filter(|s| s.served)
+        .filter_map(|s| Some((s.label.as_str(), s.subnet?)))
+        .collect();
+    let mut out = Vec::new();
+    for (i, (a_label, a)) in served.iter().enumerate() {
+        for (b_label, b) in &served[i + 1..] {
+            if same_network(IpAddr::V4(a.0), IpAddr::V4(b.0), a.1.min(b.1)) {
+                out.push(error(
+                    FindingCode::DhcpSubnetOverlap,
+                    format!(
+                        "{a_label} ({}/{}) and {b_label} ({}/{}) serve overlapping subnets",
+                        a.0, a.1, b.0, b.1
+                    ),
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// ISC scopes from `<dhcpd>`, with the subnet of the matching interface.
+fn isc_scopes(root: &XmlNode) -> Vec<Scope> {
+    let Some(dhcpd) = root.get_child("dhcpd") else {
+        return Vec::new();
+    };
+    let interfaces = root.get_child("interfaces");
+    dhcpd
+        .children
+        .iter()
+        .map(|iface| {
+            let subnet = interfaces
+                .and_then(|i| i.get_child(&iface.tag))
+                .and_then(interface_subnet);
+            let pool_ranges = iface
+                .get_children("pool")
+                .into_iter()
+                .flat_map(|p| p.get_children("range"));
+            let ranges = iface
+                .get_children("range")
+                .into_iter()
+                .chain(pool_ranges)
+                .filter_map(|r| Some((ipv4_text(r, "from")?, ipv4_text(r, "to")?)))
+                .collect();
+            let reservations = iface
+                .get_children("staticmap")
+                .into_iter()
+                .filter_map(|m| Some((ipv4_text(m, "ipaddr")?, client_text(m, "mac"))))
+                .collect();
+            Scope {
+                label: format!("dhcpd/{}", iface.tag),
+                subnet,
+                ranges,
+                reservations,
+                served: flag_set(iface, "enable"),
+            }
+        })
+        .collect()
+}
+
+/// Kea scopes from `<OPNsense><Kea><dhcp4>`; unreadable pool entries are
+/// reported into `out`.
+fn kea_scopes(root: &XmlNode, out: &mut Vec<VerifyFinding>) -> Vec<Scope> {
+    let Some(dhcp4) = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("Kea"))
+        .and_then(|k| k.get_child("dhcp4"))
+    else {
+        return Vec::new();
+    };
+    let Some(subnets) = dhcp4.get_child("subnets") else {
+        return Vec::new();
+    };
+    let reservations = dhcp4
+        .get_child("reservations")
+        .map(|r| r.get_children("reservation"))
+        .unwrap_or_default();
+    subnets
+        .get_children("subnet4")
+        .into_iter()
+        .filter_map(|subnet4| {
+            let cidr = subnet4.get_text(&["subnet"])?.trim();
+            let subnet = parse_subnet4(cidr)?;
+            let label = format!("Kea subnet {cidr}");
+            let mut ranges = Vec::new();
+            for entry in subnet4
+                .get_text(&["pools"])
+                .unwrap_or("")
+                .split([',', '\n'])
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+            {
+                match parse_pool(entry) {
+                    Some(range) => ranges.push(range),
+                    None => out.push(error(
+                        FindingCode::DhcpPoolInvalid,
+                        format!("{label}: pool '{entry}' is not a range or CIDR"),
+                    )),
+                }
+            }
+            let uuid = subnet4.attributes.get("uuid").map(String::as_str);
+            let reservations = reservations
+                .iter()
+                .filter(|r| uuid.is_some() && r.get_text(&["subnet"]).map(str::trim) == uuid)
+                .filter_map(|r| Some((ipv4_text(r, "ip_address")?, client_text(r, "hw_address"))))
+                .collect();
+            Some(Scope {
+                label,
+                subnet: Some(subnet),
+                ranges,
+                reservations,
+                served: true,
+            })
+        })
+        .collect()
+}
+
+/// Parse a Kea pool entry: `from-to` or a CIDR.
+fn parse_pool(entry: &str) -> Option<(Ipv4Addr, Ipv4Addr)> {
+    if let Some((from, to)) = entry.split_once('-') {
+        return Some((from.trim().parse().ok()?, to.trim().parse().ok()?));
+    }
+    let (IpAddr::V4(addr), prefix) = parse_cidr(entry)? else {
+        return None;
+    };
+    if prefix > 32 {
+        return None;
+    }
+    let (network, prefix) = network_of(addr, prefix);
+    let last = u32::from(network) | u32::MAX.checked_shr(u32::from(prefix)).unwrap_or(0);
+    Some((network, Ipv4Addr::from(last)))
+}
+
+/// Parse an IPv4 CIDR such as a Kea `<subnet>` into its network.
+pub(crate) fn parse_subnet4(cidr: &str) -> Option<Subnet4> {
+    match parse_cidr(cidr)? {
+        (IpAddr::V4(addr), prefix) if prefix <= 32 => Some(network_of(addr, prefix)),
+        _ => None,
+    }
+}
+
+/// Network of an `<interfaces>` entry's static IPv4 address.
+pub(crate) fn interface_subnet(iface: &XmlNode) -> Option<Subnet4> {
+    let addr = ipv4_text(iface, "ipaddr")?;
+    let prefix = iface.get_text(&["subnet"])?.trim().parse::<u8>().ok()?;
+    (prefix <= 32).then(|| network_of(addr, prefix))
+}
+
+fn network_of(addr: Ipv4Addr, prefix: u8) -> Subnet4 {
+    let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+    (Ipv4Addr::from(u32::from(addr) & mask), prefix)
+}
+
+fn ipv4_text(node: &XmlNode, tag: &str) -> Option<Ipv4Addr> {
+    node.get_text(&[tag])?.trim().parse().ok()
+}
+
+fn client_text(node: &XmlNode, tag: &str) -> String {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .unwrap_or("no MAC")
+        .to_string()
+}
+
+fn error(code: FindingCode, message: String) -> VerifyFinding {
+    VerifyFinding::error(code, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use xml_diff_core::parse;
+
+    use super::{dhcp_findings, range_problems, RangeProblem};
+    use crate::verify_interfaces::FindingSeverity;
+
+    fn codes(xml: &[u8]) -> Vec<String> {
+        let root = parse(xml).expect("parse");
+        dhcp_findings(&root)
+            .into_iter()
+            .map(|f| f.code.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn reports_one_problem_per_range_against_kept_ranges() {
+        let ip = |s: &str| s.parse::<Ipv4Addr>().expect("ip");
+        let ranges = [
+            (ip("192.168.1.100"), ip("192.168.1.200")),
+            (ip("192.168.2.10"), ip("192.168.2.20")),
+            (ip("192.168.1.150"), ip("192.168.1.250")),
+            (ip("192.168.1.90"), ip("192.168.1.80")),
+            (ip("192.168.1.201"), ip("192.168.1.210")),
+        ];
+        assert_eq!(
+            range_problems(Some((ip("192.168.1.0"), 24)), &ranges),
+            vec![
+                (1, RangeProblem::OutsideSubnet),
+                (2, RangeProblem::Overlaps(0)),
+                (3, RangeProblem::Reversed),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_isc_ranges_reservations_and_subnets() {
+        let root = parse(
+            br#"<pfsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan><opt1><ipaddr>192.168.1.129</ipaddr><subnet>25</subnet></opt1></interfaces><dhcpd><lan><enable/><range><from>192.168.1.100</from><to>192.168.1.200</to></range><pool><range><from>192.168.1.150</from><to>192.168.1.250</to></range></pool><staticmap><mac>00:11:22:33:44:55</mac><ipaddr>192.168.1.120</ipaddr></staticmap><staticmap><mac>00:11:22:33:44:56</mac><ipaddr>192.168.2.10</ipaddr></staticmap><staticmap><mac>00:11:22:33:44:57</mac><ipaddr>192.168.1.10</ipaddr></staticmap><staticmap><mac>00:11:22:33:44:58</mac><ipaddr>192.168.1.10</ipaddr></staticmap></lan><opt1><enable/></opt1></dhcpd></pfsense>"#,
+        )
+        .expect("parse");
+        let findings = dhcp_findings(&root);
+        let codes: Vec<&str> = findings.iter().map(|f| f.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "dhcp_range_overlap",
+                "dhcp_reservation_in_range",
+                "dhcp_reservation_outside_subnet",
+                "dhcp_reservation_conflict",
+                "dhcp_subnet_overlap",
+            ]
+        );
+        assert_eq!(findings[1].severity, FindingSeverity::Warning);
+        assert!(findings[4]
+            .message
+            .contains("dhcpd/lan (192.168.1.0/24) and dhcpd/opt1 (192.168.1.128/25)"));
+    }
+
+    #[test]
+    fn disabled_isc_scopes_do_not_overlap() {
+        let found = codes(
+            br#"<pfsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan><opt1><ipaddr>192.168.1.129</ipaddr><subnet>25</subnet></opt1></interfaces><dhcpd><lan><enable/></lan><opt1/></dhcpd></pfsense>"#,
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn checks_kea_pools_and_reservations() {
+        let found = codes(
+            br#"<opnsense><OPNsense><Kea><dhcp4><subnets><subnet4 uuid="s1"><subnet>10.0.0.0/24</subnet><pools>10.0.0.100-10.0.0.200
+10.0.0.128/26
+10.0.1.10-10.0.1.20
+bogus</pools></subnet4><subnet4 uuid="s2"><subnet>10.0.0.0/16</subnet></subnet4></subnets><reservations><reservation><subnet>s1</subnet><hw_address>aa:bb:cc:dd:ee:01</hw_address><ip_address>10.0.0.150</ip_address></reservation><reservation><subnet>s2</subnet><hw_address>aa:bb:cc:dd:ee:02</hw_address><ip_address>10.0.0.5</ip_address></reservation></reservations></dhcp4></Kea></OPNsense></opnsense>"#,
+        );
+        assert_eq!(
+            found,
+            vec![
+                "dhcp_pool_invalid",
+                "dhcp_range_overlap",
+                "dhcp_range_outside_subnet",
+                "dhcp_reservation_in_range",
+                "dhcp_subnet_overlap",
+            ]
+        );
+    }
+}
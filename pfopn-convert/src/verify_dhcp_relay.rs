@@ -0,0 +1,209 @@
+//! DHCP relay validation.
+//!
+//! Checks that every enabled relay can actually forward requests after a
+//! conversion renamed interfaces or dropped routes.
+//!
+//! ## Checks Performed
+//!
+//! 1. **Relay interface** — Each relay listens on a defined interface
+//! 2. **Destination reachability** — Each destination server sits on a
+//!    directly connected subnet or inside an enabled static route; anything
+//!    else would only be reached through the default gateway
+//! 3. **Destination placement** — A destination is not on the subnet of an
+//!    interface it relays for (the server would already see those requests)
+//!
+//! ## Relay Sources
+//!
+//! - `<dhcrelay>`, `<dhcrelay6>`, `<dhcp6relay>` (legacy, both platforms)
+//! - `<OPNsense><DHCRelay><relays>` with their `<destinations>` (OPNsense model)
+
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+use xml_diff_core::XmlNode;
+
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::{collect_defined_interface_names, VerifyFinding};
+use crate::verify_routes::{collect_interface_subnets, parse_cidr, same_network};
+
+/// One enabled relay interface and the servers it forwards to.
+struct Relay {
+    interface: String,
+    servers: Vec<String>,
+}
+
+/// Find DHCP relay problems.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to validate
+///
+/// # Returns
+///
+/// Vector of findings. Empty if no relay is enabled or all relays check out.
+pub fn relay_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let relays = collect_relays(root);
+    if relays.is_empty() {
+        return Vec::new();
+    }
+    let interfaces = collect_defined_interface_names(root);
+    let subnets = collect_interface_subnets(root);
+    let routes = collect_route_networks(root);
+
+    let mut out = Vec::new();
+    let mut reported = BTreeSet::new();
+    for relay in &relays {
+        if !interfaces.contains(&relay.interface.to_ascii_lowercase()) {
+            out.push(VerifyFinding::warning(
+                FindingCode::RelayUnknownInterface,
+                format!(
+                    "DHCP relay listens on undefined interface '{}'",
+                    relay.interface
+                ),
+            ));
+        }
+        for server in &relay.servers {
+            let Ok(addr) = server.parse::<IpAddr>() else {
+                continue;
+            };
+            let own_subnet = subnets.iter().find(|(iface, if_addr, prefix)| {
+                iface.eq_ignore_ascii_case(&relay.interface)
+                    && same_network(addr, *if_addr, *prefix)
+            });
+            if own_subnet.is_some() {
+                out.push(VerifyFinding::warning(
+                    FindingCode::RelayServerOnRelayInterface,
+                    format!(
+                        "DHCP relay destination {server} is on the subnet of relay interface '{}'",
+                        relay.interface
+                    ),
+                ));
+            }
+            let connected = subnets
+                .iter()
+                .any(|(_, if_addr, prefix)| same_network(addr, *if_addr, *prefix));
+            let routed = routes
+                .iter()
+                .any(|(net, prefix)| same_network(addr, *net, *prefix));
+            if !connected && !routed && reported.insert(server.clone()) {
+                out.push(VerifyFinding::warning(
+                    FindingCode::RelayServerUnreachable,
+                    format!(
+                        "DHCP relay destination {server} is not on a connected subnet or static route; it would only be reached via the default gateway"
+                    ),
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Collect enabled relays from the legacy sections and the OPNsense model.
+fn collect_relays(root: &XmlNode) -> Vec<Relay> {
+    let mut out = Vec::new();
+    for tag in ["dhcrelay", "dhcrelay6", "dhcp6relay"] {
+        let Some(section) = root.get_child(tag) else {
+            continue;
+        };
+        let enabled = section
+            .get_child("enable")
+            .is_some_and(|e| e.text.as_deref().is_none_or(|t| t.trim() != "0"));
+        if !enabled {
+            continue;
+        }
+        let servers = split_list(section.get_text(&["server"]));
+        for interface in split_list(section.get_text(&["interface"])) {
+            out.push(Relay {
+                interface,
+                servers: servers.clone(),
+            });
+        }
+    }
+
+    let Some(dhc) = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("DHCRelay"))
+    else {
+        return out;
+    };
+    let destinations = dhc.get_children("destinations");
+    for relay in dhc.get_children("relays") {
+        if relay.get_text(&["enabled"]).map(str::trim) != Some("1") {
+            continue;
+        }
+        let Some(interface) = relay
+            .get_text(&["interface"])
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        else {
+            continue;
+        };
+        let dest = relay.get_text(&["destination"]).unwrap_or("").trim();
+        let servers = destinations
+            .iter()
+            .find(|d| d.attributes.get("uuid").map(String::as_str) == Some(dest))
+            .map(|d| split_list(d.get_text(&["server"])))
+            .unwrap_or_default();
+        out.push(Relay {
+            interface: interface.to_string(),
+            servers,
+        });
+    }
+    out
+}
+
+/// Collect the networks of enabled static routes.
+fn collect_route_networks(root: &XmlNode) -> Vec<(IpAddr, u8)> {
+    let Some(routes) = root.get_child("staticroutes") else {
+        return Vec::new();
+    };
+    routes
+        .children
+        .iter()
+        .filter(|r| r.tag == "route" && r.get_child("disabled").is_none())
+        .filter_map(|r| r.get_text(&["network"]))
+        .filter_map(|n| parse_cidr(n.trim()))
+        .collect()
+}
+
+fn split_list(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::relay_findings;
+
+    #[test]
+    fn accepts_connected_and_routed_destinations() {
+        let root = parse(
+            br#"<pfsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan><opt1><ipaddr>10.0.0.1</ipaddr><subnet>24</subnet></opt1></interfaces><staticroutes><route><network>172.16.0.0/16</network><gateway>CORE</gateway></route></staticroutes><dhcrelay><enable/><interface>lan</interface><server>10.0.0.5,172.16.4.4</server></dhcrelay></pfsense>"#,
+        )
+        .expect("parse");
+        assert!(relay_findings(&root).is_empty());
+    }
+
+    #[test]
+    fn flags_unreachable_and_misplaced_destinations() {
+        let root = parse(
+            br#"<opnsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces><staticroutes><route><network>172.16.0.0/16</network><disabled/></route></staticroutes><OPNsense><DHCRelay><destinations uuid="d1"><server>192.168.1.9,172.16.4.4</server></destinations><relays uuid="r1"><enabled>1</enabled><interface>lan</interface><destination>d1</destination></relays><relays uuid="r2"><enabled>1</enabled><interface>opt9</interface><destination>d1</destination></relays></DHCRelay></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let codes: Vec<_> = relay_findings(&root).into_iter().map(|f| f.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "relay_server_on_relay_interface",
+                "relay_server_unreachable",
+                "relay_unknown_interface",
+            ]
+        );
+    }
+}
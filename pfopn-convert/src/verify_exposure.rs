@@ -0,0 +1,401 @@
+//! WAN exposure audit.
+//!
+//! Lists what a config makes reachable from the internet, so a conversion
+//! that widens the attack surface is caught before restore. Opt-in
+//! (`verify --exposure`): most configs expose something on purpose, so each
+//! exposure is a warning to review rather than an error.
+//!
+//! ## What Counts as Exposed
+//!
+//! - **Services** — the web GUI, SSH, SNMP, NTP, and DNS (Unbound or
+//!   dnsmasq) when enabled and bound to a WAN interface by name, or bound to
+//!   all interfaces while a WAN rule lets anyone reach the service's port
+//! - **Rules** — enabled pass rules on a WAN interface whose source is `any`
+//!
+//! WAN interfaces are `wan` plus any interface with an upstream gateway or
+//! a dynamic address (DHCP, PPPoE, ...), so multi-WAN setups are covered.
+//!
+//! ## Before and After
+//!
+//! [`exposure_findings_against`] compares a converted config with its
+//! source. Every exposure is keyed by what it opens, not by rule number or
+//! interface name, so renumbered rules and remapped WANs still match, and an
+//! exposure the source did not have is an error.
+
+use std::collections::BTreeSet;
+
+use xml_diff_core::XmlNode;
+
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// Interface address modes that take their address from upstream.
+const DYNAMIC_ADDRESS_MODES: &[&str] = &["dhcp", "dhcp6", "pppoe", "pptp", "l2tp", "ppp"];
+
+/// Rule destination networks that mean the firewall itself.
+const SELF_NETWORKS: &[&str] = &["(self)", "self", "wanip", "this firewall"];
+
+/// One thing reachable from a WAN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exposure {
+    /// What is opened, independent of rule order and interface names.
+    pub key: String,
+    pub message: String,
+}
+
+/// A listening service and where it binds.
+struct Service {
+    name: &'static str,
+    protocol: &'static str,
+    port: u16,
+    /// Logical interfaces it binds to; empty means all interfaces.
+    interfaces: Vec<String>,
+}
+
+/// An enabled pass rule on a WAN with `any` as source.
+struct OpenRule {
+    number: usize,
+    descr: String,
+    interface: String,
+    protocol: String,
+    destination: String,
+    port: String,
+    to_firewall: bool,
+}
+
+/// Warn about every WAN exposure in `root`.
+pub fn exposure_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    wan_exposures(root)
+        .into_iter()
+        .map(|exposure| VerifyFinding::warning(FindingCode::WanExposure, exposure.message))
+        .collect()
+}
+
+/// Like [`exposure_findings`], but an exposure `source` does not have is an
+/// error (`wan_exposure_added`).
+pub fn exposure_findings_against(root: &XmlNode, source: &XmlNode) -> Vec<VerifyFinding> {
+    let before: BTreeSet<String> = wan_exposures(source).into_iter().map(|e| e.key).collect();
+    wan_exposures(root)
+        .into_iter()
+        .map(|exposure| {
+            if before.contains(&exposure.key) {
+                VerifyFinding::warning(FindingCode::WanExposure, exposure.message)
+            } else {
+                VerifyFinding::error(
+                    FindingCode::WanExposureAdded,
+                    format!(
+                        "{}; the source config did not expose this",
+                        exposure.message
+                    ),
+                )
+            }
+        })
+        .collect()
+}
+
+/// Every service and rule `root` exposes on a WAN, in a stable order.
+pub fn wan_exposures(root: &XmlNode) -> Vec<Exposure> {
+    let wans = wan_interfaces(root);
+    let rules = open_rules(root, &wans);
+    let mut out = Vec::new();
+    for service in services(root) {
+        let bound: Vec<&String> = service
+            .interfaces
+            .iter()
+            .filter(|iface| wans.contains(iface.as_str()))
+            .collect();
+        let label = format!("{} ({}/{})", service.name, service.protocol, service.port);
+        if let Some(iface) = bound.first() {
+            out.push(Exposure {
+                key: format!("service:{}", service.name),
+                message: format!("{label} listens on WAN interface {iface}"),
+            });
+        } else if service.interfaces.is_empty() {
+            if let Some(rule) = rules.iter().find(|rule| rule.reaches(&service)) {
+                out.push(Exposure {
+                    key: format!("service:{}", service.name),
+                    message: format!(
+                        "{label} listens on all interfaces and filter rule #{} ({}) lets any source reach it on {}",
+                        rule.number, rule.descr, rule.interface
+                    ),
+                });
+            }
+        }
+    }
+    for rule in &rules {
+        let port = if rule.port.is_empty() {
+            "any port".to_string()
+        } else {
+            format!("port {}", rule.port)
+        };
+        out.push(Exposure {
+            key: format!("rule:{}:{}:{}", rule.protocol, rule.destination, rule.port),
+            message: format!(
+                "filter rule #{} ({}) passes {} from any to {} {port} on WAN interface {}",
+                rule.number,
+                rule.descr,
+                if rule.protocol.is_empty() {
+                    "all protocols"
+                } else {
+                    &rule.protocol
+                },
+                rule.destination,
+                rule.interface
+            ),
+        });
+    }
+    out
+}
+
+impl OpenRule {
+    /// Whether this rule lets traffic reach `service` on the firewall.
+    fn reaches(&self, service: &Service) -> bool {
+        let protocol_ok = self.protocol.is_empty()
+            || self.protocol == "any"
+            || self.protocol.split('/').any(|p| p == service.protocol);
+        let port_ok = self.port.is_empty() || port_in_range(&self.port, service.port);
+        (self.to_firewall || self.destination == "any") && protocol_ok && port_ok
+    }
+}
+
+/// `wan` plus interfaces with a gateway or a dynamic address.
+fn wan_interfaces(root: &XmlNode) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    let Some(interfaces) = root.get_child("interfaces") else {
+        return out;
+    };
+    for iface in &interfaces.children {
+        let dynamic = ["ipaddr", "ipaddrv6"].iter().any(|tag| {
+            text(iface, tag).is_some_and(|mode| {
+                DYNAMIC_ADDRESS_MODES.contains(&mode.to_ascii_lowercase().as_str())
+            })
+        });
+        if iface.tag == "wan"
+            || dynamic
+            || text(iface, "gateway").is_some()
+            || text(iface, "gatewayv6").is_some()
+        {
+            out.insert(iface.tag.clone());
+        }
+    }
+    out
+}
+
+/// Enabled listeners of the audited services.
+fn services(root: &XmlNode) -> Vec<Service> {
+    let mut out = Vec::new();
+    let system = root.get_child("system");
+
+    if let Some(webgui) = system.and_then(|s| s.get_child("webgui")) {
+        let https = text(webgui, "protocol") != Some("http");
+        let port = text(webgui, "port")
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(if https { 443 } else { 80 });
+        out.push(Service {
+            name: "web GUI",
+            protocol: "tcp",
+            port,
+            interfaces: split_list(text(webgui, "interfaces")),
+        });
+    }
+
+    if let Some(ssh) = system.and_then(|s| s.get_child("ssh")) {
+        let enabled = ["enable", "enabled"]
+            .iter()
+            .any(|tag| text(ssh, tag).is_some_and(|v| v == "enabled" || v == "1"));
+        if enabled {
+            out.push(Service {
+                name: "SSH",
+                protocol: "tcp",
+                port: text(ssh, "port").and_then(|p| p.parse().ok()).unwrap_or(22),
+                interfaces: split_list(text(ssh, "interfaces")),
+            });
+        }
+    }
+
+    if let Some(snmp) = root.get_child("snmpd") {
+        if is_enabled(snmp) {
+            out.push(Service {
+                name: "SNMP",
+                protocol: "udp",
+                port: text(snmp, "pollport")
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(161),
+                interfaces: split_list(text(snmp, "bindip").filter(|b| *b != "all")),
+            });
+        }
+    }
+
+    if let Some(ntpd) = root.get_child("ntpd") {
+        if text(ntpd, "enable") != Some("disabled") {
+            out.push(Service {
+                name: "NTP",
+                protocol: "udp",
+                port: 123,
+                interfaces: split_list(text(ntpd, "interface")),
+            });
+        }
+    }
+
+    for (section, tag) in [("unbound", "active_interface"), ("dnsmasq", "interface")] {
+        let Some(dns) = root.get_child(section) else {
+            continue;
+        };
+        if is_enabled(dns) {
+            out.push(Service {
+                name: "DNS",
+                protocol: "udp",
+                port: text(dns, "port").and_then(|p| p.parse().ok()).unwrap_or(53),
+                interfaces: split_list(text(dns, tag).filter(|i| *i != "all")),
+            });
+        }
+    }
+    out
+}
+
+/// Pass rules on a WAN interface that accept any source.
+fn open_rules(root: &XmlNode, wans: &BTreeSet<String>) -> Vec<OpenRule> {
+    let Some(filter) = root.get_child("filter") else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for (idx, rule) in filter.get_children("rule").into_iter().enumerate() {
+        let number = idx + 1;
+        if text(rule, "type").is_some_and(|t| t != "pass")
+            || rule
+                .get_child("disabled")
+                .is_some_and(|d| d.text.as_deref() != Some("0"))
+            || text(rule, "direction") == Some("out")
+        {
+            continue;
+        }
+        let Some(interface) = split_list(text(rule, "interface"))
+            .into_iter()
+            .find(|iface| wans.contains(iface))
+        else {
+            continue;
+        };
+        let Some(source) = rule.get_child("source") else {
+            continue;
+        };
+        if source.get_child("any").is_none() || source.get_child("not").is_some() {
+            continue;
+        }
+        let destination = rule.get_child("destination");
+        let target = destination.and_then(|d| text(d, "network").or_else(|| text(d, "address")));
+        let (destination_label, to_firewall) = match target {
+            Some(target) => (
+                target.to_string(),
+                SELF_NETWORKS.contains(&target.to_ascii_lowercase().as_str()),
+            ),
+            None => ("any".to_string(), false),
+        };
+        out.push(OpenRule {
+            number,
+            descr: text(rule, "descr").unwrap_or("no description").to_string(),
+            interface,
+            protocol: text(rule, "protocol")
+                .unwrap_or_default()
+                .to_ascii_lowercase(),
+            destination: destination_label,
+            port: destination
+                .and_then(|d| text(d, "port"))
+                .unwrap_or_default()
+                .to_string(),
+            to_firewall,
+        });
+    }
+    out
+}
+
+/// Whether a rule port (`22`, `1000-2000`, `1000:2000`) covers `port`;
+/// alias names never match.
+fn port_in_range(raw: &str, port: u16) -> bool {
+    let (low, high) = raw.split_once(['-', ':']).unwrap_or((raw, raw));
+    match (low.trim().parse::<u16>(), high.trim().parse::<u16>()) {
+        (Ok(low), Ok(high)) => (low..=high).contains(&port),
+        _ => false,
+    }
+}
+
+/// Presence-style `<enable>` flag, as pfSense writes it.
+fn is_enabled(section: &XmlNode) -> bool {
+    section
+        .get_child("enable")
+        .is_some_and(|e| !matches!(e.text.as_deref().map(str::trim), Some("0" | "no")))
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn split_list(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{exposure_findings, exposure_findings_against, wan_exposures};
+    use crate::verify_interfaces::FindingSeverity;
+
+    const LAN_ONLY: &[u8] = br#"<pfsense><system><webgui><protocol>https</protocol></webgui><ssh><enable>enabled</enable></ssh></system><interfaces><wan><ipaddr>dhcp</ipaddr></wan><lan><ipaddr>192.168.1.1</ipaddr></lan></interfaces><unbound><enable/><active_interface>lan</active_interface></unbound><filter><rule><type>pass</type><interface>lan</interface><source><any/></source><destination><any/></destination></rule><rule><type>pass</type><interface>wan</interface><source><address>203.0.113.5</address></source><destination><network>wanip</network><port>22</port></destination></rule></filter></pfsense>"#;
+
+    #[test]
+    fn lan_only_services_and_restricted_wan_rules_are_not_exposed() {
+        let root = parse(LAN_ONLY).expect("parse");
+        assert!(wan_exposures(&root).is_empty());
+    }
+
+    #[test]
+    fn flags_wan_bound_services_and_open_wan_rules() {
+        let root = parse(
+            br#"<opnsense><system><webgui><protocol>https</protocol><port>8443</port><interfaces>lan,wan</interfaces></webgui><ssh><enabled>enabled</enabled></ssh></system><interfaces><wan><ipaddr>dhcp</ipaddr></wan><opt1><ipaddr>198.51.100.2</ipaddr><gateway>WAN2_GW</gateway></opt1><lan><ipaddr>192.168.1.1</ipaddr></lan></interfaces><snmpd><enable/><bindip>opt1</bindip></snmpd><ntpd><interface>lan</interface></ntpd><filter><rule><type>pass</type><interface>opt1</interface><protocol>tcp</protocol><source><any/></source><destination><network>(self)</network><port>20-25</port></destination><descr>admin</descr></rule><rule><type>pass</type><interface>wan</interface><protocol>tcp</protocol><source><any/></source><destination><address>10.0.0.5</address><port>443</port></destination><disabled/></rule></filter></opnsense>"#,
+        )
+        .expect("parse");
+
+        let messages: Vec<String> = exposure_findings(&root)
+            .into_iter()
+            .map(|f| f.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "web GUI (tcp/8443) listens on WAN interface wan",
+                "SSH (tcp/22) listens on all interfaces and filter rule #1 (admin) lets any source reach it on opt1",
+                "SNMP (udp/161) listens on WAN interface opt1",
+                "filter rule #1 (admin) passes tcp from any to (self) port 20-25 on WAN interface opt1",
+            ]
+        );
+    }
+
+    #[test]
+    fn exposure_missing_from_source_is_an_error() {
+        let source = parse(LAN_ONLY).expect("parse");
+        let converted = parse(
+            br#"<opnsense><system><ssh><enabled>enabled</enabled></ssh></system><interfaces><wan><ipaddr>dhcp</ipaddr></wan></interfaces><filter><rule><interface>wan</interface><protocol>tcp</protocol><source><any/></source><destination><network>wanip</network><port>22</port></destination></rule></filter></opnsense>"#,
+        )
+        .expect("parse");
+
+        let findings = exposure_findings_against(&converted, &source);
+        assert_eq!(findings.len(), 2);
+        assert!(findings
+            .iter()
+            .all(|f| f.severity == FindingSeverity::Error && f.code == "wan_exposure_added"));
+        assert!(findings[0]
+            .message
+            .ends_with("; the source config did not expose this"));
+
+        assert!(exposure_findings_against(&converted, &converted)
+            .iter()
+            .all(|f| f.severity == FindingSeverity::Warning));
+    }
+}
@@ -0,0 +1,344 @@
+//! Mechanical fixes for safe classes of verify findings.
+//!
+//! Some findings have exactly one correct repair that does not need a human
+//! decision, so `verify --fix` can apply it instead of leaving the config
+//! broken. Each [`FixClass`] covers one kind:
+//!
+//! - **schedules** — Remove a rule's reference to a schedule that does not
+//!   exist; the rule then applies at all times, as the firewall already
+//!   treats it (`missing_schedule_reference`)
+//! - **interfaces** — Disable a rule bound to an interface that does not
+//!   exist rather than let it load against nothing
+//!   (`missing_interface_reference`)
+//! - **booleans** — Re-encode legacy boolean flags in the platform's own
+//!   spelling (see [`crate::transform::booleans`])
+//! - **uuids** — Issue a UUID to each model list item missing one
+//!   (`uuid_missing`)
+//!
+//! Fixes run in [`FixClass::ALL`] order whatever order they were asked for,
+//! and every change is returned as an [`AppliedFix`] so a dry run can list
+//! them without writing anything.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+use xml_diff_core::XmlNode;
+
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::transform::booleans::{
+    canonicalize_flag, flag_set_on, nodes_at_mut, normalize_flag, set_flag, LEGACY_FLAGS,
+};
+use crate::uuid_registry::UuidRegistry;
+use crate::verify_interfaces::{
+    collect_defined_interface_names, collect_ifgroup_names, is_interface_token_known, split_tokens,
+};
+use crate::verify_rule_refs::collect_schedule_names;
+use crate::verify_uuid_refs::{child_labels, lacks_list_uuid};
+use crate::verify_values::nodes_at;
+
+/// UUID registry scope for items given a UUID by [`FixClass::Uuids`].
+const UUID_SCOPE: &str = "verify-fix";
+
+/// A class of finding `verify --fix` can repair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixClass {
+    Schedules,
+    Interfaces,
+    Booleans,
+    Uuids,
+}
+
+impl FixClass {
+    pub const ALL: &'static [FixClass] = &[
+        FixClass::Schedules,
+        FixClass::Interfaces,
+        FixClass::Booleans,
+        FixClass::Uuids,
+    ];
+
+    /// Name used on the command line and in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            FixClass::Schedules => "schedules",
+            FixClass::Interfaces => "interfaces",
+            FixClass::Booleans => "booleans",
+            FixClass::Uuids => "uuids",
+        }
+    }
+}
+
+impl fmt::Display for FixClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FixClass {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        FixClass::ALL
+            .iter()
+            .copied()
+            .find(|class| class.name().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                let names: Vec<&str> = FixClass::ALL.iter().map(|c| c.name()).collect();
+                format!(
+                    "unknown fix class '{value}'; expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// One change made (or, in a dry run, that would be made).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppliedFix {
+    pub class: FixClass,
+    /// Path of the changed item (`filter/rule[3]`).
+    pub path: String,
+    pub message: String,
+}
+
+/// Fixes attached to a verify report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FixReport {
+    /// Listed only; nothing was written.
+    pub dry_run: bool,
+    /// Where the fixed config was written.
+    pub output: Option<String>,
+    pub fixes: Vec<AppliedFix>,
+}
+
+/// Apply the fixes in `classes` to `root` and list each change.
+pub fn apply_fixes(root: &mut XmlNode, classes: &[FixClass]) -> Vec<AppliedFix> {
+    let platform = match detect_config(root) {
+        ConfigFlavor::OpnSense => "opnsense",
+        _ => "pfsense",
+    };
+    let mut out = Vec::new();
+    for class in FixClass::ALL.iter().filter(|c| classes.contains(c)) {
+        match class {
+            FixClass::Schedules => fix_schedules(root, &mut out),
+            FixClass::Interfaces => fix_interfaces(root, platform, &mut out),
+            FixClass::Booleans => fix_booleans(root, platform, &mut out),
+            FixClass::Uuids => fix_uuids(root, &mut out),
+        }
+    }
+    out
+}
+
+/// Drop `<sched>`/`<schedule>` from rules naming an undefined schedule.
+fn fix_schedules(root: &mut XmlNode, out: &mut Vec<AppliedFix>) {
+    let schedules = collect_schedule_names(root);
+    let Some(filter) = root.children.iter_mut().find(|c| c.tag == "filter") else {
+        return;
+    };
+    let labels = child_labels(filter, "filter");
+    for (rule, path) in filter.children.iter_mut().zip(labels) {
+        if rule.tag != "rule" {
+            continue;
+        }
+        for tag in ["sched", "schedule"] {
+            let Some(name) = rule.get_text(&[tag]).map(str::trim) else {
+                continue;
+            };
+            if name.is_empty() || schedules.contains(&name.to_ascii_lowercase()) {
+                continue;
+            }
+            let message = format!("removed reference to missing schedule '{name}'");
+            rule.children.retain(|c| c.tag != tag);
+            out.push(AppliedFix {
+                class: FixClass::Schedules,
+                path: path.clone(),
+                message,
+            });
+        }
+    }
+}
+
+/// Disable enabled rules bound to an interface that is not defined.
+fn fix_interfaces(root: &mut XmlNode, platform: &str, out: &mut Vec<AppliedFix>) {
+    let defined = collect_defined_interface_names(root);
+    let groups = collect_ifgroup_names(root);
+    let Some(filter) = root.children.iter_mut().find(|c| c.tag == "filter") else {
+        return;
+    };
+    let labels = child_labels(filter, "filter");
+    for (rule, path) in filter.children.iter_mut().zip(labels) {
+        if rule.tag != "rule" || flag_set_on(rule, "disabled", platform) {
+            continue;
+        }
+        let missing: Vec<String> = split_tokens(rule.get_text(&["interface"]).unwrap_or(""))
+            .into_iter()
+            .filter(|t| !is_interface_token_known(t, &defined) && !groups.contains(t))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        set_flag(rule, "disabled", true, platform);
+        out.push(AppliedFix {
+            class: FixClass::Interfaces,
+            path,
+            message: format!(
+                "disabled rule referencing missing interface '{}'",
+                missing.join("', '")
+            ),
+        });
+    }
+}
+
+/// Re-encode [`LEGACY_FLAGS`] fields for `platform`.
+fn fix_booleans(root: &mut XmlNode, platform: &str, out: &mut Vec<AppliedFix>) {
+    let snapshot = root.clone();
+    for entry in LEGACY_FLAGS {
+        let mut labelled = Vec::new();
+        nodes_at(&snapshot, entry.path, String::new(), &mut labelled);
+        let nodes = nodes_at_mut(root, entry.path);
+        for ((path, _), node) in labelled.into_iter().zip(nodes) {
+            for field in entry.fields {
+                let before = node.get_child(field).map(describe_flag);
+                canonicalize_flag(node, field, platform);
+                normalize_flag(node, field, platform);
+                let after = node.get_child(field).map(describe_flag);
+                if before == after {
+                    continue;
+                }
+                out.push(AppliedFix {
+                    class: FixClass::Booleans,
+                    path: path.clone(),
+                    message: format!(
+                        "{field} re-encoded for {platform}: {} -> {}",
+                        before.as_deref().unwrap_or("absent"),
+                        after.as_deref().unwrap_or("absent")
+                    ),
+                });
+            }
+        }
+    }
+}
+
+fn describe_flag(node: &XmlNode) -> String {
+    match node.text.as_deref().map(str::trim) {
+        Some(text) if !text.is_empty() => format!("'{text}'"),
+        _ => "empty".to_string(),
+    }
+}
+
+/// Give each model list item without a `uuid` one, avoiding every UUID in use.
+fn fix_uuids(root: &mut XmlNode, out: &mut Vec<AppliedFix>) {
+    let mut registry = UuidRegistry::from_config(root);
+    assign_uuids(root, "", &mut registry, out);
+}
+
+fn assign_uuids(
+    node: &mut XmlNode,
+    label: &str,
+    registry: &mut UuidRegistry,
+    out: &mut Vec<AppliedFix>,
+) {
+    let labels = child_labels(node, label);
+    let missing: Vec<usize> = (0..node.children.len())
+        .filter(|idx| lacks_list_uuid(node, *idx))
+        .collect();
+    for (idx, (child, path)) in node.children.iter_mut().zip(labels).enumerate() {
+        if missing.contains(&idx) {
+            let uuid = registry.uuid_for(UUID_SCOPE, &path);
+            child.attributes.insert("uuid".to_string(), uuid.clone());
+            out.push(AppliedFix {
+                class: FixClass::Uuids,
+                path: path.clone(),
+                message: format!("assigned uuid {uuid}"),
+            });
+        }
+        assign_uuids(child, &path, registry, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{apply_fixes, FixClass};
+    use crate::transform::booleans::flag_set;
+    use crate::verify::build_verify_report;
+
+    #[test]
+    fn removes_dangling_schedules_and_disables_orphaned_rules() {
+        let mut root = parse(
+            b"<pfsense><system/><interfaces><lan/></interfaces>\
+              <schedules><schedule><name>office</name></schedule></schedules><filter>\
+              <rule><interface>lan</interface><sched>office</sched></rule>\
+              <rule><interface>lan</interface><sched>gone</sched></rule>\
+              <rule><interface>opt9</interface></rule>\
+              </filter></pfsense>",
+        )
+        .expect("parse");
+        let fixes = apply_fixes(&mut root, &[FixClass::Interfaces, FixClass::Schedules]);
+        let listed: Vec<(FixClass, &str)> =
+            fixes.iter().map(|f| (f.class, f.path.as_str())).collect();
+        assert_eq!(
+            listed,
+            [
+                (FixClass::Schedules, "filter/rule[2]"),
+                (FixClass::Interfaces, "filter/rule[3]")
+            ]
+        );
+        let rules = root
+            .get_child("filter")
+            .expect("filter")
+            .get_children("rule");
+        assert_eq!(rules[0].get_text(&["sched"]), Some("office"));
+        assert!(rules[1].get_child("sched").is_none());
+        assert!(flag_set(rules[2], "disabled"));
+
+        let report = build_verify_report(&root, None);
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| i.code != "missing_schedule_reference"));
+        assert_eq!(report.errors, 0);
+    }
+
+    #[test]
+    fn normalizes_booleans_and_assigns_missing_uuids() {
+        let mut root = parse(
+            br#"<opnsense><filter><rule><disabled/></rule></filter>
+              <OPNsense><Kea><dhcp4><subnets><subnet4 uuid="s1"/><subnet4/></subnets></dhcp4></Kea></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let fixes = apply_fixes(&mut root, &[FixClass::Uuids, FixClass::Booleans]);
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(fixes[0].class, FixClass::Booleans);
+        assert_eq!(fixes[0].path, "filter/rule");
+        assert_eq!(
+            fixes[0].message,
+            "disabled re-encoded for opnsense: empty -> '1'"
+        );
+        assert_eq!(fixes[1].path, "OPNsense/Kea/dhcp4/subnets/subnet4[2]");
+        assert_eq!(root.get_text(&["filter", "rule", "disabled"]), Some("1"));
+
+        assert!(apply_fixes(&mut root, FixClass::ALL).is_empty());
+    }
+
+    #[test]
+    fn keeps_pfsense_flags_with_off_values_on() {
+        let mut root = parse(
+            br#"<pfsense><filter><rule><interface>lan</interface><disabled>0</disabled></rule></filter></pfsense>"#,
+        )
+        .expect("parse");
+        let fixes = apply_fixes(&mut root, &[FixClass::Booleans]);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(
+            fixes[0].message,
+            "disabled re-encoded for pfsense: '0' -> empty"
+        );
+        let rule = root
+            .get_child("filter")
+            .and_then(|f| f.get_child("rule"))
+            .expect("rule");
+        assert!(rule.get_child("disabled").is_some_and(|d| d.text.is_none()));
+    }
+}
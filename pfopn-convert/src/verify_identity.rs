@@ -0,0 +1,270 @@
+//! Duplicate identity field detection.
+//!
+//! Objects are looked up by name or id on import, so two entries sharing an
+//! identity make the importer keep one and silently drop or misattach the
+//! other. Each duplicate is reported once, with the path of every
+//! occurrence (`system/user[2]`, `system/user[5]`).
+//!
+//! ## Checks Performed
+//!
+//! 1. **Certificates** — No two CAs/certificates share a `<refid>`
+//! 2. **Aliases** — Alias names are unique across legacy `<aliases>` and the
+//!    OPNsense `Firewall/Alias` model; OPNsense compares them
+//!    case-insensitively
+//! 3. **Users** — User names and uids are unique
+//! 4. **Gateways** — Gateway and gateway group names are unique (they share
+//!    one namespace for rules and routes)
+//! 5. **DHCP static mappings** — A MAC address has at most one ISC static
+//!    mapping per interface, and one Kea reservation per subnet
+
+use std::collections::BTreeMap;
+
+use xml_diff_core::XmlNode;
+
+use crate::detect::{detect_config, ConfigFlavor};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+use crate::verify_values::nodes_at;
+
+/// Find identity fields used by more than one object.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to scan
+///
+/// # Returns
+///
+/// One error per duplicated identity, listing every occurrence.
+pub fn identity_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let opnsense = detect_config(root) == ConfigFlavor::OpnSense;
+    let mut out = Vec::new();
+
+    let certs = collect(root, &[&["ca"], &["cert"]], "refid", |v| v.to_string());
+    report(
+        &mut out,
+        FindingCode::DuplicateCertRefid,
+        "certificate refid",
+        certs,
+    );
+
+    let aliases = collect(
+        root,
+        &[
+            &["aliases", "alias"],
+            &["OPNsense", "Firewall", "Alias", "aliases", "alias"],
+        ],
+        "name",
+        |v| {
+            if opnsense {
+                v.to_ascii_lowercase()
+            } else {
+                v.to_string()
+            }
+        },
+    );
+    report(
+        &mut out,
+        FindingCode::DuplicateAliasName,
+        "alias name",
+        aliases,
+    );
+
+    let users = collect(root, &[&["system", "user"]], "name", |v| v.to_string());
+    report(&mut out, FindingCode::DuplicateUserName, "user name", users);
+    let uids = collect(root, &[&["system", "user"]], "uid", |v| v.to_string());
+    report(&mut out, FindingCode::DuplicateUserUid, "user uid", uids);
+
+    let gateways = collect(
+        root,
+        &[
+            &["gateways", "gateway_item"],
+            &["gateways", "gateway_group"],
+            &["OPNsense", "Gateways", "gateway_item"],
+        ],
+        "name",
+        |v| v.to_string(),
+    );
+    report(
+        &mut out,
+        FindingCode::DuplicateGatewayName,
+        "gateway name",
+        gateways,
+    );
+
+    report(
+        &mut out,
+        FindingCode::DuplicateStaticMac,
+        "static mapping MAC",
+        static_macs(root),
+    );
+    out
+}
+
+/// Group the nodes at `paths` by the normalized text of their `field`.
+fn collect(
+    root: &XmlNode,
+    paths: &[&[&str]],
+    field: &str,
+    key: impl Fn(&str) -> String,
+) -> BTreeMap<String, Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in paths {
+        let mut nodes = Vec::new();
+        nodes_at(root, path, String::new(), &mut nodes);
+        for (label, node) in nodes {
+            if let Some(value) = text(node, field) {
+                groups.entry(key(value)).or_default().push(label);
+            }
+        }
+    }
+    groups
+}
+
+/// Static mapping MACs keyed by interface (ISC) or subnet (Kea) and MAC.
+fn static_macs(root: &XmlNode) -> BTreeMap<String, Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut maps = Vec::new();
+    nodes_at(root, &["dhcpd", "*", "staticmap"], String::new(), &mut maps);
+    for (label, node) in maps {
+        let Some(mac) = text(node, "mac") else {
+            continue;
+        };
+        let scope = label.split('/').nth(1).unwrap_or_default();
+        groups
+            .entry(format!("{} on {scope}", normalize_mac(mac)))
+            .or_default()
+            .push(label);
+    }
+    let mut reservations = Vec::new();
+    nodes_at(
+        root,
+        &["OPNsense", "Kea", "dhcp4", "reservations", "reservation"],
+        String::new(),
+        &mut reservations,
+    );
+    for (label, node) in reservations {
+        let Some(mac) = text(node, "hw_address") else {
+            continue;
+        };
+        let subnet = text(node, "subnet").unwrap_or_default();
+        groups
+            .entry(format!("{} in subnet {subnet}", normalize_mac(mac)))
+            .or_default()
+            .push(label);
+    }
+    groups
+}
+
+fn normalize_mac(mac: &str) -> String {
+    mac.to_ascii_lowercase().replace('-', ":")
+}
+
+fn report(
+    out: &mut Vec<VerifyFinding>,
+    code: FindingCode,
+    what: &str,
+    groups: BTreeMap<String, Vec<String>>,
+) {
+    for (value, paths) in groups {
+        if paths.len() < 2 {
+            continue;
+        }
+        out.push(VerifyFinding::error(
+            code,
+            format!(
+                "{what} '{value}' is used more than once ({})",
+                paths.join(", ")
+            ),
+        ));
+    }
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::identity_findings;
+
+    fn findings(xml: &[u8]) -> Vec<(String, String)> {
+        let root = parse(xml).expect("parse");
+        identity_findings(&root)
+            .into_iter()
+            .map(|f| (f.code.to_string(), f.message))
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_occurrence_of_duplicated_identities() {
+        let found = findings(
+            br#"<pfsense>
+              <system>
+                <user><name>admin</name><uid>0</uid></user>
+                <user><name>ops</name><uid>2000</uid></user>
+                <user><name>ops</name><uid>2000</uid></user>
+              </system>
+              <ca><refid>r1</refid></ca>
+              <cert><refid>r1</refid></cert>
+              <aliases><alias><name>Web</name></alias><alias><name>web</name></alias></aliases>
+              <gateways>
+                <gateway_item><name>WAN_GW</name></gateway_item>
+                <gateway_group><name>WAN_GW</name></gateway_group>
+              </gateways>
+              <dhcpd>
+                <lan>
+                  <staticmap><mac>AA:BB:CC:00:00:01</mac></staticmap>
+                  <staticmap><mac>aa-bb-cc-00-00-01</mac></staticmap>
+                </lan>
+                <opt1><staticmap><mac>aa:bb:cc:00:00:01</mac></staticmap></opt1>
+              </dhcpd>
+            </pfsense>"#,
+        );
+        let codes: Vec<&str> = found.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "duplicate_cert_refid",
+                "duplicate_user_name",
+                "duplicate_user_uid",
+                "duplicate_gateway_name",
+                "duplicate_static_mac",
+            ]
+        );
+        assert_eq!(
+            found[0].1,
+            "certificate refid 'r1' is used more than once (ca, cert)"
+        );
+        assert_eq!(
+            found[1].1,
+            "user name 'ops' is used more than once (system/user[2], system/user[3])"
+        );
+        assert!(found[4].1.contains("'aa:bb:cc:00:00:01 on lan'"));
+        assert!(found[4]
+            .1
+            .contains("dhcpd/lan/staticmap[1], dhcpd/lan/staticmap[2]"));
+    }
+
+    #[test]
+    fn compares_opnsense_alias_names_case_insensitively() {
+        let found = findings(
+            br#"<opnsense>
+              <OPNsense><Firewall><Alias><aliases>
+                <alias uuid="a"><name>Web</name></alias>
+                <alias uuid="b"><name>WEB</name></alias>
+              </aliases></Alias></Firewall>
+              <Kea><dhcp4><reservations>
+                <reservation><subnet>s1</subnet><hw_address>00:11:22:33:44:55</hw_address></reservation>
+                <reservation><subnet>s2</subnet><hw_address>00:11:22:33:44:55</hw_address></reservation>
+              </reservations></dhcp4></Kea></OPNsense>
+            </opnsense>"#,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "duplicate_alias_name");
+        assert!(found[0].1.contains("'web'"));
+    }
+}
@@ -23,6 +23,9 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use xml_diff_core::XmlNode;
 
+use crate::transform::booleans::flag_set;
+use crate::verify_codes::FindingCode;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FindingSeverity {
     Error,
@@ -32,8 +35,44 @@ pub enum FindingSeverity {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VerifyFinding {
     pub severity: FindingSeverity,
-    pub code: String,
+    pub code: FindingCode,
+    /// Config path the finding is about (`filter/rule[3]`), when known.
+    pub path: Option<String>,
     pub message: String,
+    /// Structured details, such as the name a reference failed to resolve.
+    pub data: BTreeMap<String, String>,
+}
+
+impl VerifyFinding {
+    pub fn new(severity: FindingSeverity, code: FindingCode, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code,
+            path: None,
+            message: message.into(),
+            data: BTreeMap::new(),
+        }
+    }
+
+    pub fn error(code: FindingCode, message: impl Into<String>) -> Self {
+        Self::new(FindingSeverity::Error, code, message)
+    }
+
+    pub fn warning(code: FindingCode, message: impl Into<String>) -> Self {
+        Self::new(FindingSeverity::Warning, code, message)
+    }
+
+    /// Attach the config path the finding is about.
+    pub fn at(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Attach one structured detail.
+    pub fn with(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.data.insert(key.to_string(), value.into());
+        self
+    }
 }
 
 /// Find all interface reference problems in a configuration.
@@ -51,8 +90,9 @@ pub struct VerifyFinding {
 pub fn interface_reference_findings(root: &XmlNode) -> Vec<VerifyFinding> {
     let mut out = Vec::new();
     let defined = collect_defined_interface_names(root);
+    let groups = collect_ifgroup_names(root);
     out.extend(duplicate_interface_findings(root));
-    out.extend(rule_interface_findings(root, &defined));
+    out.extend(rule_interface_findings(root, &defined, &groups));
     out.extend(gateway_interface_findings(root, &defined));
     out.extend(route_interface_findings(root, &defined));
     out
@@ -107,6 +147,21 @@ pub fn collect_defined_interface_names(root: &XmlNode) -> BTreeSet<String> {
     out
 }
 
+/// Collect interface group names from `<ifgroups><ifgroupentry><ifname>`.
+///
+/// Names are normalized to lowercase; group resolution and membership are
+/// checked in [`crate::verify_rule_refs`].
+pub fn collect_ifgroup_names(root: &XmlNode) -> BTreeSet<String> {
+    root.get_child("ifgroups")
+        .map(|g| g.get_children("ifgroupentry"))
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| entry.get_text(&["ifname"]))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
 /// Find duplicate interface assignments.
 ///
 /// Checks if the same logical interface name (e.g., "lan") is assigned
@@ -132,10 +187,12 @@ fn duplicate_interface_findings(root: &XmlNode) -> Vec<VerifyFinding> {
     counts
         .into_iter()
         .filter(|(_, count)| *count > 1)
-        .map(|(name, count)| VerifyFinding {
-            severity: FindingSeverity::Error,
-            code: "duplicate_interface_assignment".to_string(),
-            message: format!("interface '{name}' assigned {count} times"),
+        .map(|(name, count)| {
+            VerifyFinding::error(
+                FindingCode::DuplicateInterfaceAssignment,
+                format!("interface '{name}' assigned {count} times"),
+            )
+            .with("interface", name.to_string())
         })
         .collect()
 }
@@ -144,17 +201,23 @@ fn duplicate_interface_findings(root: &XmlNode) -> Vec<VerifyFinding> {
 ///
 /// Validates that each `<rule><interface>` value refers to an interface
 /// that actually exists. Interface values can be comma or space-separated
-/// for multi-interface rules.
+/// for multi-interface rules. Tokens naming an interface group are skipped.
+/// Disabled rules are reported as warnings rather than errors.
 ///
 /// # Arguments
 ///
 /// * `root` - Configuration root to check
 /// * `defined` - Set of defined interface names
+/// * `groups` - Set of interface group names
 ///
 /// # Returns
 ///
-/// Vector of error findings for each missing interface reference
-fn rule_interface_findings(root: &XmlNode, defined: &BTreeSet<String>) -> Vec<VerifyFinding> {
+/// Vector of findings for each missing interface reference
+fn rule_interface_findings(
+    root: &XmlNode,
+    defined: &BTreeSet<String>,
+    groups: &BTreeSet<String>,
+) -> Vec<VerifyFinding> {
     let mut out = Vec::new();
     let Some(filter) = root.get_child("filter") else {
         return out;
@@ -168,13 +231,23 @@ fn rule_interface_findings(root: &XmlNode, defined: &BTreeSet<String>) -> Vec<Ve
         let Some(interface) = rule.get_text(&["interface"]) else {
             continue;
         };
+        // A disabled rule is never loaded, so its dangling interface only
+        // needs cleaning up (`verify --fix interfaces` disables such rules).
+        let (severity, note) = if flag_set(rule, "disabled") {
+            (FindingSeverity::Warning, " (rule is disabled)")
+        } else {
+            (FindingSeverity::Error, "")
+        };
         for token in split_tokens(interface) {
-            if !is_interface_token_known(&token, defined) {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Error,
-                    code: "missing_interface_reference".to_string(),
-                    message: format!("filter rule #{idx} references missing interface '{token}'"),
-                });
+            if !is_interface_token_known(&token, defined) && !groups.contains(&token) {
+                out.push(
+                    VerifyFinding::new(
+                        severity,
+                        FindingCode::MissingInterfaceReference,
+                        format!("filter rule #{idx} references missing interface '{token}'{note}"),
+                    )
+                    .with("interface", token),
+                );
             }
         }
     }
@@ -205,11 +278,13 @@ fn gateway_interface_findings(root: &XmlNode, defined: &BTreeSet<String>) -> Vec
         };
         for token in split_tokens(interface) {
             if !is_interface_token_known(&token, defined) {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Error,
-                    code: "missing_gateway_interface".to_string(),
-                    message: format!("gateway references missing interface '{token}'"),
-                });
+                out.push(
+                    VerifyFinding::error(
+                        FindingCode::MissingGatewayInterface,
+                        format!("gateway references missing interface '{token}'"),
+                    )
+                    .with("interface", token),
+                );
             }
         }
     }
@@ -240,11 +315,13 @@ fn route_interface_findings(root: &XmlNode, defined: &BTreeSet<String>) -> Vec<V
         };
         for token in split_tokens(interface) {
             if !is_interface_token_known(&token, defined) {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Error,
-                    code: "missing_route_interface".to_string(),
-                    message: format!("static route references missing interface '{token}'"),
-                });
+                out.push(
+                    VerifyFinding::error(
+                        FindingCode::MissingRouteInterface,
+                        format!("static route references missing interface '{token}'"),
+                    )
+                    .with("interface", token),
+                );
             }
         }
     }
@@ -263,7 +340,7 @@ fn route_interface_findings(root: &XmlNode, defined: &BTreeSet<String>) -> Vec<V
 /// # Returns
 ///
 /// Vector of normalized (lowercase, trimmed) interface tokens
-fn split_tokens(raw: &str) -> Vec<String> {
+pub(crate) fn split_tokens(raw: &str) -> Vec<String> {
     raw.split([',', ' ', '\t', '\n'])
         .map(str::trim)
         .filter(|s| !s.is_empty())
@@ -286,7 +363,7 @@ fn split_tokens(raw: &str) -> Vec<String> {
 /// # Returns
 ///
 /// True if interface is valid, false otherwise
-fn is_interface_token_known(token: &str, defined: &BTreeSet<String>) -> bool {
+pub(crate) fn is_interface_token_known(token: &str, defined: &BTreeSet<String>) -> bool {
     if defined.contains(token) {
         return true;
     }
@@ -0,0 +1,188 @@
+//! Weak or deprecated IPsec algorithms.
+//!
+//! Conversion carries IPsec proposals over unchanged, including algorithms
+//! that were acceptable when a tunnel was set up but are not any more.
+//! [`ipsec_algorithm_findings`] lists them per phase as warnings
+//! (`ipsec_weak_algorithm`) so the operator can review them with the peer
+//! before the config goes live:
+//!
+//! - ciphers: DES, 3DES, Blowfish, CAST-128
+//! - integrity: MD5
+//! - DH groups: 1 (`modp768`), 2 (`modp1024`), 22 (`modp1024s160`)
+//!
+//! Swanctl connections and children are read when the config has any;
+//! otherwise pfSense `<ipsec>` phase1/phase2 entries are translated to
+//! proposals the same way conversion does.
+
+use xml_diff_core::XmlNode;
+
+use crate::transform::ipsec_pf_to_opn::{phase1_proposals, phase2_proposals};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// Algorithm keywords to flag, with how each is reported.
+const WEAK: &[(&str, &str)] = &[
+    ("des", "DES"),
+    ("3des", "3DES"),
+    ("blowfish128", "Blowfish"),
+    ("blowfish192", "Blowfish"),
+    ("blowfish256", "Blowfish"),
+    ("cast128", "CAST-128"),
+    ("md5", "MD5"),
+    ("prfmd5", "MD5"),
+    ("modp768", "DH group 1 (modp768)"),
+    ("modp1024", "DH group 2 (modp1024)"),
+    ("modp1024s160", "DH group 22 (modp1024s160)"),
+];
+
+/// Warnings for each IPsec phase whose proposals use weak algorithms.
+pub fn ipsec_algorithm_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    phases(root)
+        .into_iter()
+        .filter_map(|(label, proposals)| {
+            let weak = weak_algorithms(&proposals);
+            (!weak.is_empty()).then(|| {
+                VerifyFinding::warning(
+                    FindingCode::IpsecWeakAlgorithm,
+                    format!(
+                        "{label} uses weak or deprecated algorithms: {} (proposals {})",
+                        weak.join(", "),
+                        proposals.join(",")
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Each phase's label and proposals.
+fn phases(root: &XmlNode) -> Vec<(String, Vec<String>)> {
+    let swanctl = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("Swanctl"));
+    let connections: Vec<&XmlNode> = swanctl
+        .and_then(|s| s.get_child("Connections"))
+        .map(|c| c.get_children("Connection"))
+        .unwrap_or_default();
+    if let Some(swanctl) = swanctl.filter(|_| !connections.is_empty()) {
+        let mut out: Vec<(String, Vec<String>)> = connections
+            .into_iter()
+            .map(|conn| {
+                (
+                    format!("IPsec connection {}", name(conn, "description")),
+                    split(conn.get_text(&["proposals"])),
+                )
+            })
+            .collect();
+        out.extend(
+            swanctl
+                .get_child("children")
+                .map(|c| c.get_children("child"))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|child| {
+                    (
+                        format!("IPsec child {}", name(child, "description")),
+                        split(child.get_text(&["esp_proposals"])),
+                    )
+                }),
+        );
+        return out;
+    }
+
+    let Some(ipsec) = root.get_child("ipsec") else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for phase in &ipsec.children {
+        let ikeid = phase.get_text(&["ikeid"]).map(str::trim).unwrap_or("");
+        let proposals = match phase.tag.as_str() {
+            "phase1" => phase1_proposals(phase),
+            "phase2" => phase2_proposals(phase),
+            _ => continue,
+        };
+        out.push((
+            format!(
+                "IPsec {} {} (ikeid {ikeid})",
+                phase.tag,
+                name(phase, "descr")
+            ),
+            proposals,
+        ));
+    }
+    out
+}
+
+/// Weak algorithm names used by `proposals`, in [`WEAK`] order.
+fn weak_algorithms(proposals: &[String]) -> Vec<&'static str> {
+    let mut out = Vec::new();
+    for (keyword, label) in WEAK {
+        let used = proposals
+            .iter()
+            .any(|p| p.split('-').any(|part| part.eq_ignore_ascii_case(keyword)));
+        if used && !out.contains(label) {
+            out.push(*label);
+        }
+    }
+    out
+}
+
+fn split(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty() && *p != "default")
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn name(node: &XmlNode, tag: &str) -> String {
+    let descr = node.get_text(&[tag]).map(str::trim).unwrap_or("");
+    if descr.is_empty() {
+        node.attributes
+            .get("uuid")
+            .map_or_else(|| "(unnamed)".to_string(), |uuid| uuid.clone())
+    } else {
+        format!("'{descr}'")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::ipsec_algorithm_findings;
+
+    fn messages(xml: &[u8]) -> Vec<String> {
+        let root = parse(xml).expect("parse");
+        ipsec_algorithm_findings(&root)
+            .into_iter()
+            .map(|f| f.message)
+            .collect()
+    }
+
+    #[test]
+    fn lists_weak_algorithms_in_pfsense_phases() {
+        let xml = br#"<pfsense><ipsec>
+            <phase1><ikeid>1</ikeid><descr>Watford</descr><encryption><item><encryption-algorithm><name>3des</name></encryption-algorithm><hash-algorithm>md5</hash-algorithm><dhgroup>2</dhgroup></item></encryption></phase1>
+            <phase2><ikeid>1</ikeid><descr>LAN</descr><encryption-algorithm-option><name>aes128gcm</name><keylen>256</keylen></encryption-algorithm-option><pfsgroup>14</pfsgroup></phase2>
+            </ipsec></pfsense>"#;
+        assert_eq!(
+            messages(xml),
+            vec!["IPsec phase1 'Watford' (ikeid 1) uses weak or deprecated algorithms: 3DES, MD5, DH group 2 (modp1024) (proposals 3des-md5-modp1024)"]
+        );
+    }
+
+    #[test]
+    fn reads_swanctl_proposals_and_ignores_default() {
+        let xml = br#"<opnsense><OPNsense><Swanctl>
+            <Connections><Connection uuid="c1"><description>HQ</description><proposals>default</proposals></Connection></Connections>
+            <children><child uuid="ch1"><description/><esp_proposals>aes256-sha256-modp2048,aes128-sha1-modp1024</esp_proposals></child></children>
+            </Swanctl></OPNsense></opnsense>"#;
+        assert_eq!(
+            messages(xml),
+            vec!["IPsec child ch1 uses weak or deprecated algorithms: DH group 2 (modp1024) (proposals aes256-sha256-modp2048,aes128-sha1-modp1024)"]
+        );
+    }
+}
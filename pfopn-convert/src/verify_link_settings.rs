@@ -0,0 +1,120 @@
+//! Interface link setting validation.
+//!
+//! MTU and forced media settings are carried over verbatim, but they only
+//! work if the target NIC supports them. A jumbo MTU on a paravirtual NIC
+//! whose host does not allow it, or a forced speed on a NIC without media
+//! selection, takes the link down after import. These checks flag the
+//! device drivers where that commonly happens.
+
+use xml_diff_core::XmlNode;
+
+use crate::transform::interface_settings::device_driver;
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// Largest MTU that every Ethernet driver accepts.
+const STANDARD_MTU: u32 = 1500;
+
+/// Drivers where jumbo frames often fail: paravirtual NICs that depend on
+/// host support (`vtnet`, `hn`, `xn`) and USB or low-end NICs with limited
+/// or no jumbo frame support.
+const LIMITED_JUMBO_DRIVERS: &[&str] = &["vtnet", "hn", "xn", "ue", "axe", "axge", "ure", "re"];
+
+/// Paravirtual drivers with no media selection; a forced speed or duplex
+/// fails to apply.
+const NO_MEDIA_DRIVERS: &[&str] = &["vtnet", "hn", "xn", "vmx"];
+
+/// Find interface MTU and media settings the assigned device likely rejects.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to validate
+///
+/// # Returns
+///
+/// One warning per risky setting. Empty if no interface sets one.
+pub fn link_setting_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let Some(interfaces) = root.get_child("interfaces") else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for iface in &interfaces.children {
+        let Some(device) = iface
+            .get_text(&["if"])
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        else {
+            continue;
+        };
+        let driver = device_driver(device);
+        let mtu = iface
+            .get_text(&["mtu"])
+            .and_then(|v| v.trim().parse::<u32>().ok());
+        if let Some(mtu) = mtu.filter(|m| *m > STANDARD_MTU) {
+            if LIMITED_JUMBO_DRIVERS.contains(&driver) {
+                out.push(VerifyFinding::warning(
+                    FindingCode::InterfaceJumboMtu,
+                    format!(
+                        "interface {} sets MTU {mtu} on {device}; {driver} devices often do not support jumbo frames (for vtnet the host must allow it), so the link may stop passing traffic",
+                        iface.tag
+                    ),
+                ));
+            }
+        }
+        let media = iface
+            .get_text(&["media"])
+            .map(str::trim)
+            .filter(|v| !v.is_empty() && !v.eq_ignore_ascii_case("autoselect"));
+        if let Some(media) = media {
+            if NO_MEDIA_DRIVERS.contains(&driver) {
+                out.push(VerifyFinding::warning(
+                    FindingCode::InterfaceForcedMedia,
+                    format!(
+                        "interface {} forces media '{media}' on {device}; {driver} devices have no media selection, so the setting will not apply",
+                        iface.tag
+                    ),
+                ));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::link_setting_findings;
+
+    #[test]
+    fn warns_on_jumbo_mtu_for_vtnet() {
+        let root = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet1</if><mtu>9000</mtu></lan><wan><if>igb0</if><mtu>9000</mtu></wan></interfaces></opnsense>"#,
+        )
+        .expect("parse");
+        let findings = link_setting_findings(&root);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "interface_jumbo_mtu");
+        assert!(findings[0].message.contains("interface lan"));
+    }
+
+    #[test]
+    fn ignores_standard_mtu_and_autoselect() {
+        let root = parse(
+            br#"<opnsense><interfaces><lan><if>vtnet1</if><mtu>1500</mtu><media>autoselect</media></lan></interfaces></opnsense>"#,
+        )
+        .expect("parse");
+        assert!(link_setting_findings(&root).is_empty());
+    }
+
+    #[test]
+    fn warns_on_forced_media_for_virtual_nic() {
+        let root = parse(
+            br#"<opnsense><interfaces><wan><if>vmx0</if><media>1000baseT</media><mediaopt>full-duplex</mediaopt></wan></interfaces></opnsense>"#,
+        )
+        .expect("parse");
+        let findings = link_setting_findings(&root);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "interface_forced_media");
+    }
+}
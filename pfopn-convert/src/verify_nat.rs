@@ -8,6 +8,8 @@
 //! 1. **Outbound mode validation** — Ensures outbound NAT mode is recognized
 //! 2. **Interface references** — NAT rules reference valid interfaces
 //! 3. **Associated rule IDs** — Port forwards reference valid filter rules
+//! 4. **Outbound references** — Outbound mappings reference defined aliases
+//!    or interfaces in their source, destination, and translation target
 //!
 //! ## NAT Rule Structure
 //!
@@ -24,7 +26,9 @@ use std::collections::BTreeSet;
 
 use xml_diff_core::XmlNode;
 
-use crate::verify_interfaces::{collect_defined_interface_names, FindingSeverity, VerifyFinding};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::{collect_defined_interface_names, VerifyFinding};
+use crate::verify_rule_refs::collect_alias_names;
 
 /// Find all NAT configuration problems.
 ///
@@ -32,6 +36,7 @@ use crate::verify_interfaces::{collect_defined_interface_names, FindingSeverity,
 /// - Valid outbound mode setting
 /// - Interface references that exist
 /// - Associated rule IDs that exist in filter rules
+/// - Outbound mapping aliases and targets that exist
 ///
 /// # Arguments
 ///
@@ -48,12 +53,14 @@ pub fn nat_findings(root: &XmlNode) -> Vec<VerifyFinding> {
     // Collect context for validation
     let interfaces = collect_defined_interface_names(root);
     let associated_ids = collect_filter_associated_ids(root);
+    let aliases = collect_alias_names(root);
 
     // Run all NAT validation checks
     let mut out = Vec::new();
     out.extend(outbound_mode_findings(nat));
     out.extend(nat_interface_findings(nat, &interfaces));
     out.extend(nat_association_findings(nat, &associated_ids));
+    out.extend(outbound_reference_findings(nat, &interfaces, &aliases));
     out
 }
 
@@ -95,11 +102,10 @@ fn outbound_mode_findings(nat: &XmlNode) -> Vec<VerifyFinding> {
     if valid.iter().any(|v| mode.eq_ignore_ascii_case(v)) {
         return Vec::new();
     }
-    vec![VerifyFinding {
-        severity: FindingSeverity::Warning,
-        code: "nat_invalid_outbound_mode".to_string(),
-        message: format!("NAT outbound mode '{mode}' is not recognized"),
-    }]
+    vec![VerifyFinding::warning(
+        FindingCode::NatInvalidOutboundMode,
+        format!("NAT outbound mode '{mode}' is not recognized"),
+    )]
 }
 
 /// Find NAT rules that reference undefined interfaces.
@@ -125,11 +131,10 @@ fn nat_interface_findings(nat: &XmlNode, interfaces: &BTreeSet<String>) -> Vec<V
             if is_builtin_nat_interface(&token) || interfaces.contains(&token) {
                 continue;
             }
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Error,
-                code: "nat_missing_interface".to_string(),
-                message: format!("NAT rule #{idx} references missing interface '{token}'"),
-            });
+            out.push(VerifyFinding::error(
+                FindingCode::NatMissingInterface,
+                format!("NAT rule #{idx} references missing interface '{token}'"),
+            ));
         }
     }
     out
@@ -164,15 +169,87 @@ fn nat_association_findings(
         if associated_ids.contains(assoc) {
             continue;
         }
-        out.push(VerifyFinding {
-            severity: FindingSeverity::Warning,
-            code: "nat_missing_associated_rule".to_string(),
-            message: format!("NAT rule #{idx} associated-rule-id '{assoc}' not found in filter"),
-        });
+        out.push(VerifyFinding::warning(
+            FindingCode::NatMissingAssociatedRule,
+            format!("NAT rule #{idx} associated-rule-id '{assoc}' not found in filter"),
+        ));
     }
     out
 }
 
+/// Find outbound NAT mappings that reference undefined aliases or interfaces.
+///
+/// Checks the `network`/`address` of `<source>` and `<destination>` and the
+/// translation `<target>`. Accepted values are literals (`any`, `(self)`,
+/// IPs, CIDRs), interface names, interface address macros (`wanip`), and
+/// alias names. A leading `!` negation is ignored.
+///
+/// # Arguments
+///
+/// * `nat` - NAT configuration node
+/// * `interfaces` - Set of defined interface names
+/// * `aliases` - Set of defined alias names (lowercase)
+///
+/// # Returns
+///
+/// Vector of error findings for each unresolved reference
+fn outbound_reference_findings(
+    nat: &XmlNode,
+    interfaces: &BTreeSet<String>,
+    aliases: &BTreeSet<String>,
+) -> Vec<VerifyFinding> {
+    let Some(outbound) = nat.get_child("outbound") else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for (idx, rule) in outbound.get_children("rule").into_iter().enumerate() {
+        let mut refs = Vec::new();
+        for side in ["source", "destination"] {
+            for field in ["network", "address"] {
+                if let Some(value) = rule.get_text(&[side, field]) {
+                    refs.push((format!("{side} {field}"), value));
+                }
+            }
+        }
+        if let Some(value) = rule.get_text(&["target"]) {
+            refs.push(("target".to_string(), value));
+        }
+        for (field, value) in refs {
+            let value = value.trim().trim_start_matches('!').to_ascii_lowercase();
+            if value.is_empty() || resolves_outbound_ref(&value, interfaces, aliases) {
+                continue;
+            }
+            out.push(VerifyFinding::error(
+                FindingCode::NatMissingAlias,
+                format!(
+                    "outbound NAT rule #{idx} {field} references missing alias or interface '{value}'"
+                ),
+            ));
+        }
+    }
+    out
+}
+
+/// Check whether an outbound mapping value is a literal or a defined name.
+fn resolves_outbound_ref(
+    value: &str,
+    interfaces: &BTreeSet<String>,
+    aliases: &BTreeSet<String>,
+) -> bool {
+    if matches!(value, "any" | "(self)" | "self" | "other-subnet") {
+        return true;
+    }
+    let addr = value.split_once('/').map_or(value, |(ip, _)| ip);
+    if addr.parse::<std::net::IpAddr>().is_ok() {
+        return true;
+    }
+    if aliases.contains(value) {
+        return true;
+    }
+    let iface = value.strip_suffix("ip").unwrap_or(value);
+    interfaces.contains(value) || interfaces.contains(iface) || is_builtin_nat_interface(iface)
+}
+
 /// Collect all NAT rules from both port forward and outbound sections.
 ///
 /// NAT rules exist in:
@@ -288,6 +365,22 @@ mod tests {
         assert!(findings.iter().any(|f| f.code == "nat_missing_interface"));
     }
 
+    #[test]
+    fn errors_on_missing_outbound_alias() {
+        let root = parse(
+            br#"<pfsense><interfaces><wan/><lan/></interfaces><aliases><alias><name>Voip</name></alias></aliases><nat><outbound><rule><interface>wan</interface><source><network>voip</network></source><destination><any/></destination><target>wanip</target></rule><rule><interface>wan</interface><source><network>lan</network></source><destination><network>!Gone</network></destination><target>Pool</target></rule></outbound></nat></pfsense>"#,
+        )
+        .expect("parse");
+        let findings = nat_findings(&root);
+        let missing: Vec<_> = findings
+            .iter()
+            .filter(|f| f.code == "nat_missing_alias")
+            .collect();
+        assert_eq!(missing.len(), 2);
+        assert!(missing[0].message.contains("'gone'"));
+        assert!(missing[1].message.contains("target"));
+    }
+
     #[test]
     fn warns_on_missing_associated_rule() {
         let root = parse(
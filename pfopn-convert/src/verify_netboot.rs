@@ -0,0 +1,246 @@
+//! PXE / netboot settings, before vs after conversion.
+//!
+//! A DHCP scope that hands out a next server, TFTP server, or boot file is
+//! what lets lab machines network-boot; losing one in conversion does not
+//! show up until a client fails to boot. [`netboot_findings_against`]
+//! compares the scopes of a converted config with its source and reports
+//! every setting the output no longer serves as an error (`netboot_dropped`).
+//!
+//! ## Sources Read
+//!
+//! - **ISC** — enabled `<dhcpd><iface>` blocks: `<nextserver>`, `<tftp>` or
+//!   numbered option 66, and `<filename>` or numbered option 67
+//! - **Kea** — `<OPNsense><Kea><dhcp4>` subnets: `<next_server>` and the
+//!   `tftp_server_name` / `boot_file_name` option data
+//!
+//! Scopes match by subnet, so ISC and Kea scopes compare with each other
+//! and remapped interface names still match; an ISC scope whose interface
+//! has no static address falls back to its interface name.
+
+use std::collections::BTreeMap;
+
+use xml_diff_core::XmlNode;
+
+use crate::transform::dhcp::kea::{isc_iface_enabled, option_value};
+use crate::verify_codes::FindingCode;
+use crate::verify_dhcp::{interface_subnet, parse_subnet4, Subnet4};
+use crate::verify_interfaces::VerifyFinding;
+
+/// One DHCPv4 scope with the netboot settings it serves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetbootScope {
+    /// `dhcpd/<iface>` or `Kea subnet <cidr>`.
+    pub label: String,
+    /// ISC interface name, for scopes without a known subnet.
+    pub iface: Option<String>,
+    pub subnet: Option<Subnet4>,
+    /// Setting name (`next server`, `TFTP server`, `boot file`) to value.
+    pub settings: BTreeMap<&'static str, String>,
+}
+
+/// Every served DHCPv4 scope in `root` that hands out netboot settings.
+pub fn netboot_scopes(root: &XmlNode) -> Vec<NetbootScope> {
+    let mut out = isc_scopes(root);
+    out.extend(kea_scopes(root));
+    out.retain(|scope| !scope.settings.is_empty());
+    out
+}
+
+/// Netboot settings `source` serves that `root` no longer does.
+pub fn netboot_findings_against(root: &XmlNode, source: &XmlNode) -> Vec<VerifyFinding> {
+    let after = netboot_scopes(root);
+    let mut out = Vec::new();
+    for before in netboot_scopes(source) {
+        let matches: Vec<&NetbootScope> = after.iter().filter(|a| same_scope(&before, a)).collect();
+        if matches.is_empty() {
+            let listed: Vec<String> = before
+                .settings
+                .iter()
+                .map(|(name, value)| format!("{name} {value}"))
+                .collect();
+            out.push(dropped(format!(
+                "{}: source served {} but the output serves no netboot settings for {}",
+                before.label,
+                listed.join(", "),
+                scope_target(&before)
+            )));
+            continue;
+        }
+        for (name, value) in &before.settings {
+            let served: Vec<&str> = matches
+                .iter()
+                .filter_map(|a| a.settings.get(name).map(String::as_str))
+                .collect();
+            if served.contains(&value.as_str()) {
+                continue;
+            }
+            let message = match served.first() {
+                Some(other) => format!(
+                    "{}: {name} changed from '{value}' to '{other}' in {}",
+                    before.label, matches[0].label
+                ),
+                None => format!(
+                    "{}: {name} '{value}' was dropped from {}",
+                    before.label, matches[0].label
+                ),
+            };
+            out.push(dropped(message));
+        }
+    }
+    out
+}
+
+fn same_scope(before: &NetbootScope, after: &NetbootScope) -> bool {
+    match (before.subnet, after.subnet) {
+        (Some(a), Some(b)) => a == b,
+        _ => before.iface.is_some() && before.iface == after.iface,
+    }
+}
+
+fn scope_target(scope: &NetbootScope) -> String {
+    match (scope.subnet, &scope.iface) {
+        (Some((network, prefix)), _) => format!("{network}/{prefix}"),
+        (None, Some(iface)) => format!("interface {iface}"),
+        (None, None) => scope.label.clone(),
+    }
+}
+
+fn isc_scopes(root: &XmlNode) -> Vec<NetbootScope> {
+    let Some(dhcpd) = root.get_child("dhcpd") else {
+        return Vec::new();
+    };
+    let interfaces = root.get_child("interfaces");
+    dhcpd
+        .children
+        .iter()
+        .filter(|iface| isc_iface_enabled(iface))
+        .map(|iface| {
+            let mut settings = BTreeMap::new();
+            for (name, tag, option) in [
+                ("next server", "nextserver", None),
+                ("TFTP server", "tftp", Some("66")),
+                ("boot file", "filename", Some("67")),
+            ] {
+                let numbered = option.and_then(|code| numbered_option(iface, code));
+                if let Some(value) = text(iface, &[tag]).or(numbered) {
+                    settings.insert(name, value);
+                }
+            }
+            NetbootScope {
+                label: format!("dhcpd/{}", iface.tag),
+                iface: Some(iface.tag.clone()),
+                subnet: interfaces
+                    .and_then(|i| i.get_child(&iface.tag))
+                    .and_then(interface_subnet),
+                settings,
+            }
+        })
+        .collect()
+}
+
+fn kea_scopes(root: &XmlNode) -> Vec<NetbootScope> {
+    let Some(subnets) = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("Kea"))
+        .and_then(|k| k.get_child("dhcp4"))
+        .and_then(|d| d.get_child("subnets"))
+    else {
+        return Vec::new();
+    };
+    subnets
+        .get_children("subnet4")
+        .into_iter()
+        .filter_map(|subnet4| {
+            let cidr = subnet4.get_text(&["subnet"])?.trim();
+            let mut settings = BTreeMap::new();
+            for (name, path) in [
+                ("next server", &["next_server"][..]),
+                ("TFTP server", &["option_data", "tftp_server_name"]),
+                ("boot file", &["option_data", "boot_file_name"]),
+            ] {
+                if let Some(value) = text(subnet4, path) {
+                    settings.insert(name, value);
+                }
+            }
+            Some(NetbootScope {
+                label: format!("Kea subnet {cidr}"),
+                iface: None,
+                subnet: parse_subnet4(cidr),
+                settings,
+            })
+        })
+        .collect()
+}
+
+/// Value of `<numberoptions>` item `code`, decoded.
+fn numbered_option(iface: &XmlNode, code: &str) -> Option<String> {
+    iface
+        .get_child("numberoptions")?
+        .get_children("item")
+        .into_iter()
+        .find(|item| item.get_text(&["number"]).map(str::trim) == Some(code))
+        .map(|item| option_value(item.get_text(&["value"]).unwrap_or("")))
+        .filter(|v| !v.is_empty())
+}
+
+fn text(node: &XmlNode, path: &[&str]) -> Option<String> {
+    node.get_text(path)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+}
+
+fn dropped(message: String) -> VerifyFinding {
+    VerifyFinding::error(FindingCode::NetbootDropped, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::netboot_findings_against;
+
+    const SOURCE: &[u8] = br#"<pfsense>
+        <interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces>
+        <dhcpd><lan><enable/><nextserver>192.168.1.5</nextserver><tftp>192.168.1.5</tftp>
+          <numberoptions><item><number>67</number><type>text</type><value>InB4ZWxpbnV4LjAi</value></item></numberoptions>
+        </lan></dhcpd></pfsense>"#;
+
+    fn messages(output: &[u8]) -> Vec<String> {
+        let source = parse(SOURCE).expect("parse");
+        let root = parse(output).expect("parse");
+        netboot_findings_against(&root, &source)
+            .into_iter()
+            .map(|f| f.message)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_equivalent_kea_subnet() {
+        let output = br#"<opnsense><OPNsense><Kea><dhcp4><subnets><subnet4><subnet>192.168.1.0/24</subnet><next_server>192.168.1.5</next_server><option_data><tftp_server_name>192.168.1.5</tftp_server_name><boot_file_name>pxelinux.0</boot_file_name></option_data></subnet4></subnets></dhcp4></Kea></OPNsense></opnsense>"#;
+        assert!(messages(output).is_empty());
+    }
+
+    #[test]
+    fn reports_dropped_and_changed_settings() {
+        let output = br#"<opnsense><OPNsense><Kea><dhcp4><subnets><subnet4><subnet>192.168.1.0/24</subnet><next_server/><option_data><tftp_server_name>192.168.1.6</tftp_server_name><boot_file_name>pxelinux.0</boot_file_name></option_data></subnet4></subnets></dhcp4></Kea></OPNsense></opnsense>"#;
+        assert_eq!(
+            messages(output),
+            vec![
+                "dhcpd/lan: TFTP server changed from '192.168.1.5' to '192.168.1.6' in Kea subnet 192.168.1.0/24",
+                "dhcpd/lan: next server '192.168.1.5' was dropped from Kea subnet 192.168.1.0/24",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_scope_without_netboot_settings() {
+        let output = br#"<opnsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces><dhcpd><lan><enable>1</enable></lan></dhcpd></opnsense>"#;
+        assert_eq!(
+            messages(output),
+            vec![
+                "dhcpd/lan: source served TFTP server 192.168.1.5, boot file pxelinux.0, next server 192.168.1.5 but the output serves no netboot settings for 192.168.1.0/24"
+            ]
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! OpenVPN settings the target version's OpenVPN rejects.
+//!
+//! Compression, cipher negotiation, topology, and TLS key handling changed
+//! across OpenVPN 2.4–2.6, and a converted instance can carry a setting the
+//! target no longer starts with (`comp-lzo` on OpenVPN 2.6, `BF-CBC` without
+//! a legacy provider). [`openvpn_findings`] checks each server and client
+//! against the target profile's `openvpn_*` lists and reports warnings:
+//!
+//! - `openvpn_option_rejected` — compression setting or custom option
+//! - `openvpn_cipher_rejected` — data cipher, fallback, or legacy cipher
+//! - `openvpn_topology_unsupported`
+//! - `openvpn_tls_mode_unsupported`
+//!
+//! ## Instances Read
+//!
+//! - **OPNsense** — `<OPNsense><OpenVPN><Instances>`, with TLS keys from
+//!   `<StaticKeys>`; the legacy `<openvpn>` section only when there are no
+//!   instances, since conversion keeps a pfSense-format copy there
+//! - **pfSense** — `<openvpn><openvpn-server>` and `<openvpn-client>`
+
+use std::collections::BTreeSet;
+
+use xml_diff_core::XmlNode;
+
+use crate::profile::ExpectedProfile;
+use crate::transform::openvpn::compression_directive;
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// Effective OpenVPN settings of one server or client.
+#[derive(Debug, Default)]
+struct OpenVpnSettings {
+    label: String,
+    /// Directives in use, with where each came from.
+    options: Vec<(String, String)>,
+    /// Ciphers in use, with the field each came from.
+    ciphers: Vec<(String, &'static str)>,
+    topology: Option<String>,
+    tls_mode: Option<String>,
+}
+
+/// Warnings for OpenVPN settings the profile's OpenVPN rejects.
+pub fn openvpn_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyFinding> {
+    let mut out = Vec::new();
+    for settings in instances(root) {
+        let label = &settings.label;
+        for (option, origin) in &settings.options {
+            if contains(&profile.openvpn_rejected_options, option) {
+                out.push(warning(
+                    FindingCode::OpenvpnOptionRejected,
+                    format!("{label}: {origin} uses '{option}', which the target OpenVPN rejects"),
+                ));
+            }
+        }
+        for (cipher, field) in &settings.ciphers {
+            if contains(&profile.openvpn_rejected_ciphers, cipher) {
+                out.push(warning(
+                    FindingCode::OpenvpnCipherRejected,
+                    format!(
+                        "{label}: {field} cipher '{cipher}' is not supported by the target OpenVPN"
+                    ),
+                ));
+            }
+        }
+        if let Some(topology) = &settings.topology {
+            if !profile.openvpn_topologies.is_empty()
+                && !contains(&profile.openvpn_topologies, topology)
+            {
+                out.push(warning(
+                    FindingCode::OpenvpnTopologyUnsupported,
+                    format!(
+                        "{label}: topology '{topology}' is not available on the target (expected one of {})",
+                        profile.openvpn_topologies.join(", ")
+                    ),
+                ));
+            }
+        }
+        if let Some(mode) = &settings.tls_mode {
+            if !profile.openvpn_tls_modes.is_empty() && !contains(&profile.openvpn_tls_modes, mode)
+            {
+                out.push(warning(
+                    FindingCode::OpenvpnTlsModeUnsupported,
+                    format!("{label}: TLS key mode 'tls-{mode}' is not supported by the target"),
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn instances(root: &XmlNode) -> Vec<OpenVpnSettings> {
+    let opnsense_instances = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("OpenVPN"))
+        .filter(|_| root.tag == "opnsense");
+    if let Some(openvpn) = opnsense_instances {
+        let found: Vec<OpenVpnSettings> = openvpn
+            .get_child("Instances")
+            .map(|i| i.get_children("Instance"))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|instance| opnsense_instance(instance, openvpn))
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    let Some(openvpn) = root.get_child("openvpn") else {
+        return Vec::new();
+    };
+    openvpn
+        .children
+        .iter()
+        .filter(|c| c.tag == "openvpn-server" || c.tag == "openvpn-client")
+        .map(pfsense_instance)
+        .collect()
+}
+
+fn opnsense_instance(instance: &XmlNode, openvpn: &XmlNode) -> OpenVpnSettings {
+    let role = text(instance, "role").unwrap_or_else(|| "server".to_string());
+    let name = text(instance, "description")
+        .or_else(|| text(instance, "vpnid"))
+        .or_else(|| instance.attributes.get("uuid").cloned())
+        .unwrap_or_default();
+    let mut settings = OpenVpnSettings {
+        label: format!("OpenVPN {role} '{name}'"),
+        topology: text(instance, "topology"),
+        ..OpenVpnSettings::default()
+    };
+    for (field, name) in [
+        ("data-ciphers", "data"),
+        ("data-ciphers-fallback", "fallback"),
+    ] {
+        push_ciphers(&mut settings, instance, field, name);
+    }
+    push_custom_options(&mut settings, instance);
+    let key_ref = text(instance, "tls_key");
+    settings.tls_mode = openvpn
+        .get_child("StaticKeys")
+        .map(|k| k.get_children("StaticKey"))
+        .unwrap_or_default()
+        .into_iter()
+        .find(|k| key_ref.is_some() && k.attributes.get("uuid") == key_ref.as_ref())
+        .and_then(|k| text(k, "mode"));
+    settings
+}
+
+fn pfsense_instance(node: &XmlNode) -> OpenVpnSettings {
+    let name = text(node, "description")
+        .or_else(|| text(node, "vpnid"))
+        .unwrap_or_default();
+    let mut settings = OpenVpnSettings {
+        label: format!("{} '{name}'", node.tag),
+        topology: text(node, "topology"),
+        tls_mode: text(node, "tls")
+            .map(|_| text(node, "tls_type").unwrap_or_else(|| "auth".to_string())),
+        ..OpenVpnSettings::default()
+    };
+    if let Some(value) = text(node, "compression") {
+        if let Some(directive) = compression_directive(&value) {
+            settings
+                .options
+                .push((directive.to_string(), format!("compression '{value}'")));
+        }
+    }
+    for (field, name) in [
+        ("data_ciphers", "data"),
+        ("ncp-ciphers", "data"),
+        ("data_ciphers_fallback", "fallback"),
+        ("crypto", "fallback"),
+    ] {
+        push_ciphers(&mut settings, node, field, name);
+    }
+    push_custom_options(&mut settings, node);
+    settings
+}
+
+/// Add the ciphers of a comma- or colon-separated cipher field.
+fn push_ciphers(settings: &mut OpenVpnSettings, node: &XmlNode, field: &str, name: &'static str) {
+    let Some(value) = text(node, field) else {
+        return;
+    };
+    for cipher in value.split([',', ':']).map(str::trim) {
+        if !cipher.is_empty() && !settings.ciphers.iter().any(|(c, _)| c == cipher) {
+            settings.ciphers.push((cipher.to_string(), name));
+        }
+    }
+}
+
+/// Add the directives of `<custom_options>`, one per line or `;`-separated
+/// entry; `push "<directive> ..."` counts as the pushed directive.
+fn push_custom_options(settings: &mut OpenVpnSettings, node: &XmlNode) {
+    let Some(value) = text(node, "custom_options") else {
+        return;
+    };
+    let mut seen = BTreeSet::new();
+    for entry in value.split(['\n', ';']).map(str::trim) {
+        let mut words = entry.split_whitespace();
+        let Some(first) = words.next() else {
+            continue;
+        };
+        let directive = if first == "push" {
+            words.next().map(|w| w.trim_start_matches('"'))
+        } else {
+            Some(first)
+        };
+        let Some(directive) = directive
+            .map(|d| d.trim_start_matches("--").to_ascii_lowercase())
+            .filter(|d| !d.is_empty() && !d.starts_with('#'))
+        else {
+            continue;
+        };
+        if seen.insert(directive.clone()) {
+            settings
+                .options
+                .push((directive, "custom options".to_string()));
+        }
+    }
+}
+
+fn contains(list: &[String], value: &str) -> bool {
+    list.iter().any(|item| item.eq_ignore_ascii_case(value))
+}
+
+fn text(node: &XmlNode, tag: &str) -> Option<String> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+}
+
+fn warning(code: FindingCode, message: String) -> VerifyFinding {
+    VerifyFinding::warning(code, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::openvpn_findings;
+    use crate::profile::load_profile;
+
+    fn codes_and_messages(xml: &[u8], platform: &str, version: &str) -> Vec<(String, String)> {
+        let root = parse(xml).expect("parse");
+        let profile = load_profile(platform, version).expect("profile");
+        openvpn_findings(&root, &profile)
+            .into_iter()
+            .map(|f| (f.code.to_string(), f.message))
+            .collect()
+    }
+
+    const PFSENSE: &[u8] = br#"<pfsense><openvpn><openvpn-server>
+        <vpnid>1</vpnid><description>Remote</description><compression>lzo</compression>
+        <data_ciphers>AES-256-GCM,BF-CBC</data_ciphers><data_ciphers_fallback>BF-CBC</data_ciphers_fallback>
+        <topology>subnet</topology><tls>a2V5</tls><tls_type>crypt</tls_type>
+        <custom_options>push "comp-lzo yes";tls-remote vpn.example.com</custom_options>
+        </openvpn-server></openvpn></pfsense>"#;
+
+    #[test]
+    fn reports_settings_modern_openvpn_rejects() {
+        assert_eq!(
+            codes_and_messages(PFSENSE, "pfsense", "2.8.0"),
+            vec![
+                (
+                    "openvpn_option_rejected".to_string(),
+                    "openvpn-server 'Remote': compression 'lzo' uses 'compress', which the target OpenVPN rejects".to_string()
+                ),
+                (
+                    "openvpn_option_rejected".to_string(),
+                    "openvpn-server 'Remote': custom options uses 'comp-lzo', which the target OpenVPN rejects".to_string()
+                ),
+                (
+                    "openvpn_option_rejected".to_string(),
+                    "openvpn-server 'Remote': custom options uses 'tls-remote', which the target OpenVPN rejects".to_string()
+                ),
+                (
+                    "openvpn_cipher_rejected".to_string(),
+                    "openvpn-server 'Remote': data cipher 'BF-CBC' is not supported by the target OpenVPN".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn older_target_profile_accepts_compression_and_legacy_ciphers() {
+        assert_eq!(
+            codes_and_messages(PFSENSE, "pfsense", "2.6.0"),
+            vec![(
+                "openvpn_option_rejected".to_string(),
+                "openvpn-server 'Remote': custom options uses 'tls-remote', which the target OpenVPN rejects".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn reads_opnsense_instances_and_static_keys() {
+        let xml = br#"<opnsense><openvpn><openvpn-server><compression>lzo</compression></openvpn-server></openvpn>
+            <OPNsense><OpenVPN>
+              <Instances><Instance uuid="i1"><role>server</role><description>Site</description>
+                <topology>p2p</topology><data-ciphers>AES-256-GCM</data-ciphers>
+                <data-ciphers-fallback>DES-EDE3-CBC</data-ciphers-fallback>
+                <compress_migrate>1</compress_migrate><tls_key>k1</tls_key></Instance></Instances>
+              <StaticKeys><StaticKey uuid="k1"><mode>crypt</mode><key>x</key></StaticKey></StaticKeys>
+            </OpenVPN></OPNsense></opnsense>"#;
+        let root = parse(xml).expect("parse");
+        let mut profile = load_profile("opnsense", "26.1").expect("profile");
+        profile.openvpn_topologies = vec!["subnet".to_string()];
+        profile.openvpn_tls_modes = vec!["auth".to_string()];
+        let messages: Vec<String> = openvpn_findings(&root, &profile)
+            .into_iter()
+            .map(|f| f.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "OpenVPN server 'Site': fallback cipher 'DES-EDE3-CBC' is not supported by the target OpenVPN",
+                "OpenVPN server 'Site': topology 'p2p' is not available on the target (expected one of subnet)",
+                "OpenVPN server 'Site': TLS key mode 'tls-crypt' is not supported by the target",
+            ]
+        );
+    }
+}
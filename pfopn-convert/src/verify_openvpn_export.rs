@@ -0,0 +1,376 @@
+//! OpenVPN client export readiness.
+//!
+//! After conversion, remote users need new client profiles from the target's
+//! client export. [`export_readiness`] checks that each OpenVPN server has
+//! what an exported profile needs and lists what is missing per server:
+//!
+//! - **Server certificate** — the referenced certificate exists and carries
+//!   the TLS Web Server Authentication EKU; exported profiles use
+//!   `remote-cert-tls server`, so clients reject a certificate without it
+//! - **CA** — the referenced CA exists
+//! - **Key exchange** — pfSense servers have a DH parameter length, or
+//!   `none` for ECDH only; OPNsense instances use ECDH defaults. A TLS key
+//!   mode must come with its key.
+//! - **Public address** — the client export's hostname setting, or else the
+//!   bind address or interface address, is a hostname or public IP address
+//!   that clients can reach
+//!
+//! Shared-key (`p2p_shared_key`) servers have no client export and are
+//! skipped.
+//!
+//! ## Instances Read
+//!
+//! - **OPNsense** — `<OPNsense><OpenVPN><Instances>` with role `server`;
+//!   export hostnames from `<OPNsense><OpenVPNExport><servers>`
+//! - **pfSense** — `<openvpn><openvpn-server>`; export addresses from
+//!   `<installedpackages><vpn_openvpn_export><serverconfig>`
+
+use std::net::IpAddr;
+
+use serde::Serialize;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::prelude::FromDer;
+use xml_diff_core::XmlNode;
+
+use crate::verify_certs::certificate_der;
+
+/// What one OpenVPN server still needs for client export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportReadiness {
+    /// Server label, e.g. `OpenVPN server 'Remote access' (vpnid 1)`.
+    pub server: String,
+    /// Missing or unusable items; empty when the server is ready.
+    pub missing: Vec<String>,
+}
+
+impl ExportReadiness {
+    pub fn is_ready(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Settings of one server that client export depends on.
+struct ExportServer<'a> {
+    label: String,
+    vpnid: String,
+    certref: Option<&'a str>,
+    caref: Option<&'a str>,
+    /// `None` when the platform has no DH setting.
+    dh_length: Option<Option<&'a str>>,
+    /// A TLS key mode is set but its key is missing.
+    tls_key_missing: bool,
+    bind_address: Option<&'a str>,
+    interface: &'a str,
+}
+
+/// Check every OpenVPN server in `root` for client export readiness.
+pub fn export_readiness(root: &XmlNode) -> Vec<ExportReadiness> {
+    servers(root)
+        .into_iter()
+        .map(|server| ExportReadiness {
+            missing: missing_items(root, &server),
+            server: server.label,
+        })
+        .collect()
+}
+
+fn missing_items(root: &XmlNode, server: &ExportServer) -> Vec<String> {
+    let mut missing = Vec::new();
+
+    match server.certref {
+        None => missing.push("no server certificate selected".to_string()),
+        Some(refid) => match store_entry(root, "cert", refid) {
+            None => missing.push(format!("server certificate '{refid}' is not in the config")),
+            Some(cert) => {
+                if let Some(false) = has_server_auth(cert) {
+                    missing.push(format!(
+                        "server certificate '{refid}' lacks the TLS Web Server Authentication extended key usage"
+                    ));
+                }
+            }
+        },
+    }
+    match server.caref {
+        None => missing.push("no CA selected".to_string()),
+        Some(refid) if store_entry(root, "ca", refid).is_none() => {
+            missing.push(format!("CA '{refid}' is not in the config"));
+        }
+        Some(_) => {}
+    }
+
+    if server.dh_length == Some(None) {
+        missing.push("no DH parameter length; pick one, or 'none' for ECDH only".to_string());
+    }
+    if server.tls_key_missing {
+        missing.push("a TLS key mode is set but the key is missing".to_string());
+    }
+
+    if let Some(reason) = address_problem(root, server) {
+        missing.push(reason);
+    }
+    missing
+}
+
+/// Why clients may not reach the address the export writes into profiles.
+fn address_problem(root: &XmlNode, server: &ExportServer) -> Option<String> {
+    if export_hostname(root, &server.vpnid).is_some() {
+        return None;
+    }
+    let (address, origin) = match server.bind_address {
+        Some(address) => (Some(address), "bind address".to_string()),
+        None => (
+            interface_address(root, server.interface),
+            format!("interface '{}'", server.interface),
+        ),
+    };
+    let hint = "set a hostname in the client export settings";
+    match address.map(str::parse::<IpAddr>) {
+        None => Some(format!("{origin} has no static address; {hint}")),
+        Some(Err(_)) => Some(format!(
+            "{origin} has a dynamic address ({}); {hint}",
+            address.unwrap_or_default()
+        )),
+        Some(Ok(ip)) if !is_public(ip) => {
+            Some(format!("{origin} address {ip} is not public; {hint}"))
+        }
+        Some(Ok(_)) => None,
+    }
+}
+
+/// Hostname or address the client export is configured to use for a server.
+fn export_hostname<'a>(root: &'a XmlNode, vpnid: &str) -> Option<&'a str> {
+    let opnsense = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("OpenVPNExport"))
+        .and_then(|e| e.get_child("servers"))
+        .map(|s| s.get_children("server"))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| text(s, "vpnid") == Some(vpnid))
+        .find_map(|s| text(s, "hostname"));
+    let pfsense = || {
+        root.get_child("installedpackages")
+            .and_then(|ip| ip.get_child("vpn_openvpn_export"))
+            .and_then(|e| e.get_child("serverconfig"))
+            .map(|s| s.get_children("item"))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|item| text(item, "server") == Some(vpnid))
+            .find_map(|item| match text(item, "useaddr") {
+                // The firewall's own hostname, assumed to resolve publicly.
+                Some(mode @ ("serverhostname" | "servermagichost")) => Some(mode),
+                Some("other") => text(item, "useaddr_hostname"),
+                _ => None,
+            })
+    };
+    opnsense.or_else(pfsense)
+}
+
+fn interface_address<'a>(root: &'a XmlNode, interface: &str) -> Option<&'a str> {
+    root.get_child("interfaces")
+        .and_then(|i| i.get_child(interface))
+        .and_then(|i| text(i, "ipaddr"))
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            let shared = a == 100 && (64..128).contains(&b);
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || shared)
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Whether a certificate has the serverAuth EKU; `None` when it cannot be
+/// decoded (sanitized exports).
+fn has_server_auth(cert: &XmlNode) -> Option<bool> {
+    let der = certificate_der(cert)?;
+    let (_, parsed) = X509Certificate::from_der(&der).ok()?;
+    Some(
+        parsed
+            .extended_key_usage()
+            .ok()
+            .flatten()
+            .is_some_and(|eku| eku.value.server_auth || eku.value.any),
+    )
+}
+
+fn store_entry<'a>(root: &'a XmlNode, tag: &str, refid: &str) -> Option<&'a XmlNode> {
+    root.get_children(tag)
+        .into_iter()
+        .find(|n| text(n, "refid") == Some(refid))
+}
+
+fn servers(root: &XmlNode) -> Vec<ExportServer<'_>> {
+    let openvpn = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("OpenVPN"))
+        .filter(|_| root.tag == "opnsense");
+    if let Some(openvpn) = openvpn {
+        let found: Vec<ExportServer> = openvpn
+            .get_child("Instances")
+            .map(|i| i.get_children("Instance"))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|i| text(i, "role").unwrap_or("server") == "server")
+            .map(|instance| opnsense_server(instance, openvpn))
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    root.get_child("openvpn")
+        .map(|o| o.get_children("openvpn-server"))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| text(s, "mode") != Some("p2p_shared_key"))
+        .map(pfsense_server)
+        .collect()
+}
+
+fn opnsense_server<'a>(instance: &'a XmlNode, openvpn: &'a XmlNode) -> ExportServer<'a> {
+    let vpnid = text(instance, "vpnid").unwrap_or_default().to_string();
+    let key_ref = text(instance, "tls_key");
+    let key_found = openvpn
+        .get_child("StaticKeys")
+        .map(|k| k.get_children("StaticKey"))
+        .unwrap_or_default()
+        .into_iter()
+        .any(|k| k.attributes.get("uuid").map(String::as_str) == key_ref);
+    ExportServer {
+        label: label(text(instance, "description"), &vpnid),
+        vpnid,
+        certref: text(instance, "cert"),
+        caref: text(instance, "ca"),
+        dh_length: None,
+        tls_key_missing: key_ref.is_some() && !key_found,
+        bind_address: text(instance, "local"),
+        interface: "wan",
+    }
+}
+
+fn pfsense_server(server: &XmlNode) -> ExportServer<'_> {
+    let vpnid = text(server, "vpnid").unwrap_or_default().to_string();
+    ExportServer {
+        label: label(text(server, "description"), &vpnid),
+        vpnid,
+        certref: text(server, "certref"),
+        caref: text(server, "caref"),
+        dh_length: Some(text(server, "dh_length")),
+        tls_key_missing: text(server, "tls_type").is_some() && text(server, "tls").is_none(),
+        bind_address: text(server, "ipaddr"),
+        interface: text(server, "interface").unwrap_or("wan"),
+    }
+}
+
+fn label(description: Option<&str>, vpnid: &str) -> String {
+    match description {
+        Some(descr) => format!("OpenVPN server '{descr}' (vpnid {vpnid})"),
+        None => format!("OpenVPN server vpnid {vpnid}"),
+    }
+}
+
+fn text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use base64::Engine;
+    use xml_diff_core::parse;
+
+    use super::export_readiness;
+
+    fn blob(name: &str) -> String {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../fixtures/certs")
+            .join(name);
+        let pem = std::fs::read(&path).expect("read fixture");
+        base64::engine::general_purpose::STANDARD.encode(pem)
+    }
+
+    #[test]
+    fn pfsense_server_with_everything_is_ready() {
+        let xml = format!(
+            "<pfsense><interfaces><wan><ipaddr>dhcp</ipaddr></wan></interfaces>\
+             <ca><refid>ca1</refid><crt>{}</crt></ca><cert><refid>c1</refid><crt>{}</crt></cert>\
+             <openvpn><openvpn-server><vpnid>1</vpnid><description>RA</description><mode>server_tls_user</mode>\
+             <certref>c1</certref><caref>ca1</caref><dh_length>none</dh_length><interface>wan</interface></openvpn-server>\
+             <openvpn-server><vpnid>2</vpnid><mode>p2p_shared_key</mode></openvpn-server></openvpn>\
+             <installedpackages><vpn_openvpn_export><serverconfig><item><server>1</server>\
+             <useaddr>other</useaddr><useaddr_hostname>vpn.example.com</useaddr_hostname></item>\
+             </serverconfig></vpn_openvpn_export></installedpackages></pfsense>",
+            blob("ca.crt"),
+            blob("server.crt")
+        );
+        let root = parse(xml.as_bytes()).expect("parse");
+        let readiness = export_readiness(&root);
+        assert_eq!(readiness.len(), 1, "shared-key servers are skipped");
+        assert!(readiness[0].is_ready(), "{:?}", readiness[0].missing);
+    }
+
+    #[test]
+    fn lists_missing_items_per_server() {
+        let xml = format!(
+            "<pfsense><interfaces><wan><ipaddr>192.168.1.2</ipaddr></wan></interfaces>\
+             <cert><refid>c1</refid><crt>{}</crt></cert>\
+             <openvpn><openvpn-server><vpnid>1</vpnid><certref>c1</certref><caref>ca9</caref>\
+             <tls_type>auth</tls_type><interface>wan</interface></openvpn-server></openvpn></pfsense>",
+            blob("leaf.crt")
+        );
+        let root = parse(xml.as_bytes()).expect("parse");
+        let readiness = export_readiness(&root);
+        assert_eq!(readiness[0].server, "OpenVPN server vpnid 1");
+        assert_eq!(
+            readiness[0].missing,
+            vec![
+                "server certificate 'c1' lacks the TLS Web Server Authentication extended key usage",
+                "CA 'ca9' is not in the config",
+                "no DH parameter length; pick one, or 'none' for ECDH only",
+                "a TLS key mode is set but the key is missing",
+                "interface 'wan' address 192.168.1.2 is not public; set a hostname in the client export settings",
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_opnsense_instances_and_export_hostnames() {
+        let root = parse(
+            br#"<opnsense><interfaces><wan><ipaddr>203.0.113.5</ipaddr></wan></interfaces>
+            <OPNsense>
+              <OpenVPN><Instances>
+                <Instance uuid="i1"><vpnid>1</vpnid><role>server</role><cert>c1</cert><ca>ca1</ca><tls_key>k9</tls_key></Instance>
+                <Instance uuid="i2"><vpnid>2</vpnid><role>client</role></Instance>
+                <Instance uuid="i3"><vpnid>3</vpnid><role>server</role><cert>c1</cert><ca>ca1</ca><local>10.0.0.1</local></Instance>
+              </Instances></OpenVPN>
+              <OpenVPNExport><servers><server><vpnid>3</vpnid><hostname>vpn.example.com</hostname></server></servers></OpenVPNExport>
+            </OPNsense>
+            <ca><refid>ca1</refid><crt>REDACTED</crt></ca><cert><refid>c1</refid><crt>REDACTED</crt></cert>
+            </opnsense>"#,
+        )
+        .expect("parse");
+        let readiness = export_readiness(&root);
+        assert_eq!(readiness.len(), 2);
+        assert_eq!(
+            readiness[0].missing,
+            vec!["a TLS key mode is set but the key is missing"]
+        );
+        assert!(readiness[1].is_ready());
+    }
+}
@@ -0,0 +1,66 @@
+//! PPP link (PPPoE/PPTP/L2TP client) validation.
+//!
+//! Reports `<ppps><ppp>` options the platform will not apply as written, so
+//! a converted config doesn't silently lose an MTU, idle timeout, or reset
+//! schedule. The rules live in [`crate::transform::ppps::ignored_options`]
+//! and are shared with conversion, which drops or trims the same options.
+
+use xml_diff_core::XmlNode;
+
+use crate::transform::ppps::ignored_options;
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// Find PPP options that `platform` will ignore.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to validate
+/// * `platform` - Platform the config is meant for (`pfsense` or `opnsense`)
+///
+/// # Returns
+///
+/// One warning per ignored option. Empty if there are no PPP links.
+pub fn ppp_findings(root: &XmlNode, platform: &str) -> Vec<VerifyFinding> {
+    let Some(ppps) = root.get_child("ppps") else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for ppp in ppps.children.iter().filter(|c| c.tag == "ppp") {
+        let name = ppp
+            .get_text(&["if"])
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or("ppp");
+        for ignored in ignored_options(ppp, platform) {
+            out.push(VerifyFinding::warning(
+                FindingCode::PppIgnoredOption,
+                format!(
+                    "PPP link {name} option '{}' will be ignored: {}",
+                    ignored.field, ignored.reason
+                ),
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::ppp_findings;
+
+    #[test]
+    fn reports_pfsense_only_options_for_opnsense() {
+        let root = parse(
+            br#"<pfsense><ppps><ppp><if>pppoe0</if><ports>igb0</ports><uptime/></ppp></ppps></pfsense>"#,
+        )
+        .expect("parse");
+        let findings = ppp_findings(&root, "opnsense");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "ppp_ignored_option");
+        assert!(findings[0].message.contains("'uptime'"));
+        assert!(ppp_findings(&root, "pfsense").is_empty());
+    }
+}
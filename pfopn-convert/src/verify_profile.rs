@@ -2,8 +2,12 @@ use std::collections::BTreeSet;
 
 use xml_diff_core::XmlNode;
 
+use crate::opnsense_editions::business_plugin_uses;
+use crate::plus_features::plus_feature_uses;
 use crate::profile::ExpectedProfile;
-use crate::verify_interfaces::{FindingSeverity, VerifyFinding};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+use crate::verify_openvpn::openvpn_findings;
 
 pub fn profile_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyFinding> {
     let mut out = Vec::new();
@@ -14,6 +18,9 @@ pub fn profile_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<Verify
     out.extend(gateway_field_findings(root, profile));
     out.extend(route_field_findings(root, profile));
     out.extend(bridge_findings(root, profile));
+    out.extend(openvpn_findings(root, profile));
+    out.extend(plus_feature_findings(root, profile));
+    out.extend(business_plugin_findings(root, profile));
     out
 }
 
@@ -22,10 +29,11 @@ fn required_section_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<V
         .required_sections
         .iter()
         .filter(|section| root.get_child(section.as_str()).is_none())
-        .map(|section| VerifyFinding {
-            severity: FindingSeverity::Warning,
-            code: "profile_missing_required_section".to_string(),
-            message: format!("expected section '{section}' is missing"),
+        .map(|section| {
+            VerifyFinding::warning(
+                FindingCode::ProfileMissingRequiredSection,
+                format!("expected section '{section}' is missing"),
+            )
         })
         .collect()
 }
@@ -35,14 +43,51 @@ fn deprecated_section_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec
         .deprecated_sections
         .iter()
         .filter(|section| root.get_child(section.as_str()).is_some())
-        .map(|section| VerifyFinding {
-            severity: FindingSeverity::Warning,
-            code: "profile_deprecated_section_present".to_string(),
-            message: format!("deprecated section '{section}' is present"),
+        .map(|section| {
+            VerifyFinding::warning(
+                FindingCode::ProfileDeprecatedSectionPresent,
+                format!("deprecated section '{section}' is present"),
+            )
         })
         .collect()
 }
 
+fn plus_feature_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyFinding> {
+    if profile.plus_features {
+        return Vec::new();
+    }
+    plus_feature_uses(root)
+        .into_iter()
+        .map(|used| {
+            VerifyFinding::warning(
+                FindingCode::PlusOnlyFeature,
+                format!(
+                    "{} uses {}, a pfSense Plus feature this target lacks; {}",
+                    used.path, used.description, used.action
+                ),
+            )
+        })
+        .collect()
+}
+
+fn business_plugin_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyFinding> {
+    if profile.business_plugins {
+        return Vec::new();
+    }
+    business_plugin_uses(root)
+        .into_iter()
+        .map(|used| VerifyFinding::warning(
+            FindingCode::BusinessOnlyPlugin,
+            format!(
+                "{} ({}, found in {}) is only available on OPNsense Business Edition; a community target drops it",
+                used.description,
+                used.package,
+                used.evidence
+            ),
+        ))
+        .collect()
+}
+
 fn rule_field_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyFinding> {
     let Some(filter) = root.get_child("filter") else {
         return Vec::new();
@@ -60,11 +105,10 @@ fn rule_field_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyF
                 .map(|v| !v.trim().is_empty())
                 .unwrap_or(false);
             if !ok {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Warning,
-                    code: "profile_rule_missing_required_field".to_string(),
-                    message: format!("filter rule #{idx} is missing required field '{field}'"),
-                });
+                out.push(VerifyFinding::warning(
+                    FindingCode::ProfileRuleMissingRequiredField,
+                    format!("filter rule #{idx} is missing required field '{field}'"),
+                ));
             }
         }
     }
@@ -93,28 +137,25 @@ fn rule_order_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyF
     let mut out = Vec::new();
     for (idx, rule) in rules.into_iter().enumerate() {
         let Some(value) = rule.get_text(&[order_key.as_str()]) else {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Warning,
-                code: "profile_rule_missing_order_key".to_string(),
-                message: format!("filter rule #{idx} is missing order key '{order_key}'"),
-            });
+            out.push(VerifyFinding::warning(
+                FindingCode::ProfileRuleMissingOrderKey,
+                format!("filter rule #{idx} is missing order key '{order_key}'"),
+            ));
             continue;
         };
         let value = value.trim().to_string();
         if value.is_empty() {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Warning,
-                code: "profile_rule_missing_order_key".to_string(),
-                message: format!("filter rule #{idx} has empty order key '{order_key}'"),
-            });
+            out.push(VerifyFinding::warning(
+                FindingCode::ProfileRuleMissingOrderKey,
+                format!("filter rule #{idx} has empty order key '{order_key}'"),
+            ));
             continue;
         }
         if !seen.insert(value.clone()) {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Warning,
-                code: "profile_rule_duplicate_order_key".to_string(),
-                message: format!("duplicate firewall order key '{value}'"),
-            });
+            out.push(VerifyFinding::warning(
+                FindingCode::ProfileRuleDuplicateOrderKey,
+                format!("duplicate firewall order key '{value}'"),
+            ));
         }
     }
     out
@@ -135,11 +176,10 @@ fn gateway_field_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<Veri
                 .map(|v| !v.trim().is_empty())
                 .unwrap_or(false);
             if !ok {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Warning,
-                    code: "profile_gateway_missing_required_field".to_string(),
-                    message: format!("gateway #{idx} is missing required field '{field}'"),
-                });
+                out.push(VerifyFinding::warning(
+                    FindingCode::ProfileGatewayMissingRequiredField,
+                    format!("gateway #{idx} is missing required field '{field}'"),
+                ));
             }
         }
     }
@@ -158,11 +198,10 @@ fn route_field_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<Verify
                 .map(|v| !v.trim().is_empty())
                 .unwrap_or(false);
             if !ok {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Warning,
-                    code: "profile_route_missing_required_field".to_string(),
-                    message: format!("static route #{idx} is missing required field '{field}'"),
-                });
+                out.push(VerifyFinding::warning(
+                    FindingCode::ProfileRouteMissingRequiredField,
+                    format!("static route #{idx} is missing required field '{field}'"),
+                ));
             }
         }
         if !profile.route_required_any_fields.is_empty() {
@@ -173,14 +212,13 @@ fn route_field_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<Verify
                     .unwrap_or(false)
             });
             if !has_any {
-                out.push(VerifyFinding {
-                    severity: FindingSeverity::Warning,
-                    code: "profile_route_missing_any_required_field".to_string(),
-                    message: format!(
+                out.push(VerifyFinding::warning(
+                    FindingCode::ProfileRouteMissingAnyRequiredField,
+                    format!(
                         "static route #{idx} is missing one of [{}]",
                         profile.route_required_any_fields.join(", ")
                     ),
-                });
+                ));
             }
         }
     }
@@ -210,11 +248,10 @@ fn bridge_findings(root: &XmlNode, profile: &ExpectedProfile) -> Vec<VerifyFindi
             .map(|v| !v.trim().is_empty())
             .unwrap_or(false);
         if !members && !bridgeif {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Warning,
-                code: "profile_bridge_missing_members".to_string(),
-                message: format!("bridge #{idx} has no members according to profile"),
-            });
+            out.push(VerifyFinding::warning(
+                FindingCode::ProfileBridgeMissingMembers,
+                format!("bridge #{idx} has no members according to profile"),
+            ));
         }
     }
     out
@@ -0,0 +1,212 @@
+//! Static route validation.
+//!
+//! Validates that static routes still point at something routable after a
+//! conversion renamed or pruned interfaces and gateways.
+//!
+//! ## Checks Performed
+//!
+//! 1. **Gateway presence** — Every route with a network names a gateway
+//! 2. **Gateway resolution** — The gateway is a defined gateway item, a
+//!    gateway group, or a dynamic gateway (`WAN_DHCP`) of a defined interface
+//! 3. **Subnet overlap** — The route network does not fall inside a subnet
+//!    that is already directly connected on an interface
+//!
+//! ## Gateway Sources
+//!
+//! Gateways are discovered from:
+//! - `<gateways><gateway_item><name>` and `<gateways><gateway_group><name>`
+//! - `<OPNsense><Gateways><gateway_item><name>` (OPNsense MVC model)
+
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+use xml_diff_core::XmlNode;
+
+use crate::transform::staticroutes::dynamic_gateway_interface;
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::{collect_defined_interface_names, VerifyFinding};
+
+/// Find all static route problems.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to validate
+///
+/// # Returns
+///
+/// Vector of findings (errors and warnings). Empty if no problems found.
+pub fn route_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let Some(routes) = root.get_child("staticroutes") else {
+        return Vec::new();
+    };
+
+    let interfaces = collect_defined_interface_names(root);
+    let gateways = collect_gateway_names(root);
+    let subnets = collect_interface_subnets(root);
+
+    let mut out = Vec::new();
+    for route in routes.children.iter().filter(|n| n.tag == "route") {
+        let Some(network) = non_empty_text(route, "network") else {
+            // Empty template `<route/>` entries carry nothing to validate.
+            continue;
+        };
+        out.extend(gateway_findings(route, network, &gateways, &interfaces));
+        out.extend(overlap_findings(network, &subnets));
+    }
+    out
+}
+
+/// Check that a route's gateway is set and resolves.
+fn gateway_findings(
+    route: &XmlNode,
+    network: &str,
+    gateways: &BTreeSet<String>,
+    interfaces: &BTreeSet<String>,
+) -> Vec<VerifyFinding> {
+    let Some(gateway) = non_empty_text(route, "gateway") else {
+        return vec![VerifyFinding::error(
+            FindingCode::RouteMissingGateway,
+            format!("static route {network} has no gateway"),
+        )];
+    };
+    if gateway_resolves(gateway, gateways, interfaces) {
+        return Vec::new();
+    }
+    vec![VerifyFinding::error(
+        FindingCode::MissingRouteGateway,
+        format!("static route {network} references missing gateway '{gateway}'"),
+    )]
+}
+
+/// A gateway resolves if it is defined, is a dynamic gateway of a defined
+/// interface, or is a literal address.
+pub(crate) fn gateway_resolves(
+    gateway: &str,
+    gateways: &BTreeSet<String>,
+    interfaces: &BTreeSet<String>,
+) -> bool {
+    if gateways.contains(&gateway.to_ascii_lowercase()) || gateway.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+    dynamic_gateway_interface(gateway).is_some_and(|iface| interfaces.contains(&iface))
+}
+
+/// Warn when a route targets a network that is already directly connected.
+fn overlap_findings(network: &str, subnets: &[(String, IpAddr, u8)]) -> Vec<VerifyFinding> {
+    let Some((addr, prefix)) = parse_cidr(network) else {
+        return Vec::new();
+    };
+    subnets
+        .iter()
+        .filter(|(_, if_addr, if_prefix)| {
+            prefix >= *if_prefix && same_network(addr, *if_addr, *if_prefix)
+        })
+        .map(|(iface, if_addr, if_prefix)| {
+            VerifyFinding::warning(
+                FindingCode::RouteOverlapsInterfaceSubnet,
+                format!("static route {network} falls inside {iface} subnet {if_addr}/{if_prefix}"),
+            )
+        })
+        .collect()
+}
+
+/// Collect lowercase gateway and gateway group names.
+fn collect_gateway_names(root: &XmlNode) -> BTreeSet<String> {
+    let mut sections: Vec<&XmlNode> = root.get_children("gateways");
+    if let Some(opn) = root.get_child("OPNsense") {
+        sections.extend(opn.get_children("Gateways"));
+    }
+
+    sections
+        .into_iter()
+        .flat_map(|section| &section.children)
+        .filter(|child| matches!(child.tag.as_str(), "gateway_item" | "gateway_group"))
+        .filter_map(|child| non_empty_text(child, "name"))
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+/// Collect statically addressed interface subnets as `(name, address, prefix)`.
+pub(crate) fn collect_interface_subnets(root: &XmlNode) -> Vec<(String, IpAddr, u8)> {
+    let Some(interfaces) = root.get_child("interfaces") else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for iface in &interfaces.children {
+        for (addr_tag, prefix_tag) in [("ipaddr", "subnet"), ("ipaddrv6", "subnetv6")] {
+            let addr = non_empty_text(iface, addr_tag).and_then(|a| a.parse::<IpAddr>().ok());
+            let prefix = non_empty_text(iface, prefix_tag).and_then(|p| p.parse::<u8>().ok());
+            if let (Some(addr), Some(prefix)) = (addr, prefix) {
+                out.push((iface.tag.clone(), addr, prefix));
+            }
+        }
+    }
+    out
+}
+
+/// Parse `addr/prefix`; a bare address is treated as a host route.
+pub(crate) fn parse_cidr(raw: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match raw.split_once('/') {
+        Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, prefix.parse::<u8>().ok()?),
+        None => {
+            let addr = raw.parse::<IpAddr>().ok()?;
+            (addr, if addr.is_ipv4() { 32 } else { 128 })
+        }
+    };
+    Some((addr, prefix))
+}
+
+/// Whether `a` and `b` share the first `prefix` bits (same address family only).
+pub(crate) fn same_network(a: IpAddr, b: IpAddr, prefix: u8) -> bool {
+    let (a, b, width) = match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            (u128::from(u32::from(a)), u128::from(u32::from(b)), 32u8)
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => (u128::from(a), u128::from(b), 128),
+        _ => return false,
+    };
+    if prefix == 0 || prefix > width {
+        return prefix == 0;
+    }
+    let shift = width - prefix;
+    (a >> shift) == (b >> shift)
+}
+
+fn non_empty_text<'a>(node: &'a XmlNode, tag: &str) -> Option<&'a str> {
+    node.get_text(&[tag])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::route_findings;
+
+    #[test]
+    fn accepts_defined_and_dynamic_gateways() {
+        let root = parse(
+            br#"<pfsense><interfaces><wan><ipaddr>dhcp</ipaddr></wan><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces><gateways><gateway_item><name>CORE_GW</name></gateway_item></gateways><staticroutes><route><network>10.1.0.0/16</network><gateway>CORE_GW</gateway></route><route><network>10.2.0.0/16</network><gateway>WAN_DHCP</gateway></route><route/></staticroutes></pfsense>"#,
+        )
+        .expect("parse");
+        assert!(route_findings(&root).is_empty());
+    }
+
+    #[test]
+    fn flags_dangling_gateway_and_connected_overlap() {
+        let root = parse(
+            br#"<opnsense><interfaces><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces><staticroutes><route><network>10.1.0.0/16</network><gateway>OPT4_DHCP</gateway></route><route><network>192.168.1.128/25</network><gateway>10.0.0.1</gateway></route><route><network>10.3.0.0/16</network><gateway/></route></staticroutes></opnsense>"#,
+        )
+        .expect("parse");
+        let codes: Vec<_> = route_findings(&root).into_iter().map(|f| f.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "missing_route_gateway",
+                "route_overlaps_interface_subnet",
+                "route_missing_gateway"
+            ]
+        );
+    }
+}
@@ -28,7 +28,8 @@ use std::collections::BTreeMap;
 
 use xml_diff_core::XmlNode;
 
-use crate::verify_interfaces::{FindingSeverity, VerifyFinding};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
 
 /// Find duplicate firewall rules.
 ///
@@ -72,26 +73,24 @@ pub fn rule_duplicate_findings(root: &XmlNode) -> Vec<VerifyFinding> {
         let has_default = rows.iter().any(|r| is_default_descr(&r.descr));
         let has_non_default = rows.iter().any(|r| !is_default_descr(&r.descr));
         if has_default && has_non_default {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Warning,
-                code: "default_rule_overlap".to_string(),
-                message: format!(
+            out.push(VerifyFinding::warning(
+                FindingCode::DefaultRuleOverlap,
+                format!(
                     "default rule overlaps custom rule signatures (trackers: {})",
                     trackers(rows)
                 ),
-            });
+            ));
             continue;
         }
 
         // True duplicate (all default or all custom)
-        out.push(VerifyFinding {
-            severity: FindingSeverity::Warning,
-            code: "duplicate_firewall_rule".to_string(),
-            message: format!(
+        out.push(VerifyFinding::warning(
+            FindingCode::DuplicateFirewallRule,
+            format!(
                 "duplicate firewall rule signature detected (trackers: {})",
                 trackers(rows)
             ),
-        });
+        ));
     }
     out
 }
@@ -101,7 +100,7 @@ pub fn rule_duplicate_findings(root: &XmlNode) -> Vec<VerifyFinding> {
 /// Includes all fields that affect rule matching behavior. Rules with
 /// identical fingerprints will match the same traffic.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct RuleFingerprint {
+pub(crate) struct RuleFingerprint {
     interface: String,
     action: String,
     ipprotocol: String,
@@ -126,7 +125,7 @@ struct RuleMeta {
 }
 
 /// Compute a rule's fingerprint from all matching-relevant fields.
-fn fingerprint(rule: &XmlNode) -> RuleFingerprint {
+pub(crate) fn fingerprint(rule: &XmlNode) -> RuleFingerprint {
     RuleFingerprint {
         interface: text(rule, "interface").to_ascii_lowercase(),
         action: text(rule, "type").to_ascii_lowercase(),
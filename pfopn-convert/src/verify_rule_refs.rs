@@ -2,13 +2,18 @@
 //!
 //! Validates that firewall rules and static routes reference valid resources:
 //! - **Aliases** — Firewall address aliases must exist
-//! - **Gateways** — Gateways referenced in rules and routes must be defined
+//! - **Gateways** — Gateways referenced in rules and routes must be defined;
+//!   a gateway group used for policy routing must list only defined gateways
 //! - **Schedules** — Time-based schedules must exist
+//! - **Interface groups** — A rule interface naming a group must match the
+//!   group's exact name, and the group's members must be defined interfaces
 //!
 //! ## Reference Types
 //!
 //! - Filter rule addresses can reference aliases (e.g., "TRUSTED_HOSTS")
-//! - Filter rules and static routes can specify gateways for routing
+//! - Filter rules and static routes can specify gateways for routing; a rule
+//!   gateway may be a gateway, a gateway group, or the dynamic gateway of a
+//!   defined interface (`OPT2_DHCP`)
 //! - Filter rules can have schedules for time-based activation
 //!
 //! ## Built-in References
@@ -20,15 +25,19 @@ use std::collections::BTreeSet;
 
 use xml_diff_core::XmlNode;
 
-use crate::verify_interfaces::{FindingSeverity, VerifyFinding};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::{collect_defined_interface_names, VerifyFinding};
+use crate::verify_routes::gateway_resolves;
 
 /// Find all reference validation problems in firewall rules and routes.
 ///
 /// Validates that:
 /// - Rule addresses reference defined aliases
-/// - Rule gateways reference defined gateways
+/// - Rule gateways reference defined gateways or groups, and those groups
+///   only list defined gateways
 /// - Route gateways reference defined gateways
 /// - Rule schedules reference defined schedules
+/// - Rule interface groups resolve to an `<ifgroups>` entry whose members exist
 ///
 /// # Arguments
 ///
@@ -47,8 +56,10 @@ pub fn rule_reference_findings(root: &XmlNode) -> Vec<VerifyFinding> {
     // Validate references in filter rules and static routes
     out.extend(filter_rule_alias_findings(root, &aliases));
     out.extend(filter_rule_gateway_findings(root, &gateways));
+    out.extend(gateway_group_member_findings(root, &gateways));
     out.extend(static_route_gateway_findings(root, &gateways));
     out.extend(filter_rule_schedule_findings(root, &schedules));
+    out.extend(filter_rule_ifgroup_findings(root));
     out
 }
 
@@ -73,13 +84,12 @@ fn filter_rule_alias_findings(root: &XmlNode, aliases: &BTreeSet<String>) -> Vec
                     continue;
                 }
                 if !aliases.contains(&token.to_ascii_lowercase()) {
-                    out.push(VerifyFinding {
-                        severity: FindingSeverity::Error,
-                        code: "missing_alias_reference".to_string(),
-                        message: format!(
+                    out.push(VerifyFinding::error(
+                        FindingCode::MissingAliasReference,
+                        format!(
                             "filter rule #{idx} {side} references alias '{token}' that does not exist"
                         ),
-                    });
+                    ).with("alias", token.to_string()));
                 }
             }
         }
@@ -91,6 +101,7 @@ fn filter_rule_gateway_findings(root: &XmlNode, gateways: &BTreeSet<String>) ->
     let Some(filter) = root.get_child("filter") else {
         return Vec::new();
     };
+    let interfaces = collect_defined_interface_names(root);
     let mut out = Vec::new();
     for (idx, rule) in filter
         .children
@@ -102,17 +113,70 @@ fn filter_rule_gateway_findings(root: &XmlNode, gateways: &BTreeSet<String>) ->
             continue;
         };
         let gateway = gateway.trim();
-        if gateway.is_empty() || is_builtin_or_literal(gateway) {
+        if gateway.is_empty() {
             continue;
         }
-        if !gateways.contains(&gateway.to_ascii_lowercase()) {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Error,
-                code: "missing_gateway_reference".to_string(),
-                message: format!(
-                    "filter rule #{idx} references gateway '{gateway}' that does not exist"
+        if !gateway_resolves(gateway, gateways, &interfaces) {
+            out.push(
+                VerifyFinding::error(
+                    FindingCode::MissingGatewayReference,
+                    format!(
+                        "filter rule #{idx} references gateway '{gateway}' that does not exist"
+                    ),
+                )
+                .with("gateway", gateway.to_string()),
+            );
+        }
+    }
+    out
+}
+
+/// Find gateway groups used by filter rules whose members do not exist.
+///
+/// A policy routing rule that names a group only routes through the members
+/// that still resolve, so a member lost in conversion silently drops a WAN.
+fn gateway_group_member_findings(
+    root: &XmlNode,
+    gateways: &BTreeSet<String>,
+) -> Vec<VerifyFinding> {
+    let Some(filter) = root.get_child("filter") else {
+        return Vec::new();
+    };
+    let used: BTreeSet<String> = filter
+        .get_children("rule")
+        .into_iter()
+        .filter_map(|rule| rule.get_text(&["gateway"]))
+        .map(|gateway| gateway.trim().to_ascii_lowercase())
+        .collect();
+    let interfaces = collect_defined_interface_names(root);
+    let mut out = Vec::new();
+    for group in root
+        .get_child("gateways")
+        .map(|g| g.get_children("gateway_group"))
+        .unwrap_or_default()
+    {
+        let Some(name) = group.get_text(&["name"]).map(str::trim) else {
+            continue;
+        };
+        if !used.contains(&name.to_ascii_lowercase()) {
+            continue;
+        }
+        for item in group.get_children("item") {
+            let member = item
+                .text
+                .as_deref()
+                .and_then(|t| t.split('|').next())
+                .map(str::trim)
+                .unwrap_or_default();
+            if member.is_empty() || gateway_resolves(member, gateways, &interfaces) {
+                continue;
+            }
+            out.push(VerifyFinding::error(
+                FindingCode::MissingGatewayGroupMember,
+                format!(
+                    "gateway group '{name}' used by filter rules lists gateway '{member}' that does not exist"
                 ),
-            });
+            ).with("gateway_group", name.to_string()).with("gateway", member.to_string()));
         }
     }
     out
@@ -135,13 +199,15 @@ fn static_route_gateway_findings(
             continue;
         }
         if !gateways.contains(&gateway.to_ascii_lowercase()) {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Error,
-                code: "missing_route_gateway".to_string(),
-                message: format!(
-                    "static route #{idx} references gateway '{gateway}' that does not exist"
-                ),
-            });
+            out.push(
+                VerifyFinding::error(
+                    FindingCode::MissingRouteGateway,
+                    format!(
+                        "static route #{idx} references gateway '{gateway}' that does not exist"
+                    ),
+                )
+                .with("gateway", gateway.to_string()),
+            );
         }
     }
     out
@@ -172,19 +238,102 @@ fn filter_rule_schedule_findings(
             continue;
         }
         if !schedules.contains(&sched.to_ascii_lowercase()) {
-            out.push(VerifyFinding {
-                severity: FindingSeverity::Warning,
-                code: "missing_schedule_reference".to_string(),
-                message: format!(
-                    "filter rule #{idx} references schedule '{sched}' that does not exist"
+            out.push(
+                VerifyFinding::warning(
+                    FindingCode::MissingScheduleReference,
+                    format!("filter rule #{idx} references schedule '{sched}' that does not exist"),
+                )
+                .with("schedule", sched.to_string()),
+            );
+        }
+    }
+    out
+}
+
+/// Find rule interface groups that do not resolve in the output.
+///
+/// Group names are case sensitive on both platforms, so a rule on `Admins`
+/// does not match a group named `admins`. A group used by rules must also
+/// list only defined interfaces; a member lost in conversion (or renamed
+/// without the interface map) silently removes the rules from that port.
+fn filter_rule_ifgroup_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let Some(filter) = root.get_child("filter") else {
+        return Vec::new();
+    };
+    let groups: Vec<(&str, &str)> = root
+        .get_child("ifgroups")
+        .map(|g| g.get_children("ifgroupentry"))
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| {
+            let name = entry.get_text(&["ifname"]).map(str::trim)?;
+            Some((name, entry.get_text(&["members"]).unwrap_or_default()))
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .collect();
+    if groups.is_empty() {
+        return Vec::new();
+    }
+    let interfaces = collect_defined_interface_names(root);
+    let mut used = BTreeSet::new();
+    let mut out = Vec::new();
+    for (idx, rule) in filter
+        .children
+        .iter()
+        .filter(|c| c.tag == "rule")
+        .enumerate()
+    {
+        let Some(interface) = rule.get_text(&["interface"]) else {
+            continue;
+        };
+        for token in interface.split([',', ' ']).map(str::trim) {
+            if groups.iter().any(|(name, _)| *name == token) {
+                used.insert(token);
+                continue;
+            }
+            let Some((name, _)) = groups
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(token))
+            else {
+                continue;
+            };
+            out.push(VerifyFinding::error(
+                FindingCode::IfgroupCaseMismatch,
+                format!(
+                    "filter rule #{idx} references interface group '{token}' but the group is named '{name}'"
                 ),
-            });
+            ).with("group", name.to_string()));
+        }
+    }
+    for (name, members) in groups.iter().filter(|(name, _)| used.contains(name)) {
+        let members: Vec<&str> = members
+            .split([',', ' '])
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .collect();
+        if members.is_empty() {
+            out.push(VerifyFinding::warning(
+                FindingCode::EmptyIfgroupReference,
+                format!(
+                    "interface group '{name}' used by filter rules has no members; its rules match nothing"
+                ),
+            ).with("group", name.to_string()));
+        }
+        for member in members {
+            if !interfaces.contains(&member.to_ascii_lowercase()) {
+                out.push(VerifyFinding::error(
+                    FindingCode::MissingIfgroupMember,
+                    format!(
+                        "interface group '{name}' used by filter rules lists interface '{member}' that does not exist"
+                    ),
+                ).with("group", name.to_string()).with("interface", member.to_string()));
+            }
         }
     }
     out
 }
 
-fn collect_alias_names(root: &XmlNode) -> BTreeSet<String> {
+pub(crate) fn collect_alias_names(root: &XmlNode) -> BTreeSet<String> {
     let mut out = BTreeSet::new();
     if let Some(aliases) = root.get_child("aliases") {
         for alias in aliases.children.iter().filter(|c| c.tag == "alias") {
@@ -242,7 +391,7 @@ fn collect_gateway_names(root: &XmlNode) -> BTreeSet<String> {
     out
 }
 
-fn collect_schedule_names(root: &XmlNode) -> BTreeSet<String> {
+pub(crate) fn collect_schedule_names(root: &XmlNode) -> BTreeSet<String> {
     let mut out = BTreeSet::new();
     if let Some(schedules) = root.get_child("schedules") {
         for s in schedules.children.iter().filter(|c| c.tag == "schedule") {
@@ -334,6 +483,41 @@ mod tests {
             .any(|f| f.code == "missing_gateway_reference"));
     }
 
+    #[test]
+    fn checks_policy_routing_groups_and_dynamic_gateways() {
+        let root = parse(
+            br#"<opnsense><interfaces><wan/><opt1/></interfaces><gateways><gateway_item><name>WAN_GW</name></gateway_item><gateway_group><name>MULTIWAN</name><item>WAN_GW|1|address</item><item>OPT1_DHCP|1|address</item><item>WAN2_GW|2|address</item></gateway_group></gateways><filter><rule><gateway>MULTIWAN</gateway></rule><rule><gateway>OPT1_DHCP</gateway></rule><rule><gateway>OPT2_DHCP</gateway></rule></filter></opnsense>"#,
+        )
+        .expect("parse");
+        let findings = rule_reference_findings(&root);
+        let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "filter rule #2 references gateway 'OPT2_DHCP' that does not exist",
+                "gateway group 'MULTIWAN' used by filter rules lists gateway 'WAN2_GW' that does not exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_rule_interface_groups_resolve() {
+        let root = parse(
+            br#"<opnsense><interfaces><lan/><opt1/></interfaces><ifgroups><ifgroupentry><ifname>Trusted</ifname><members>lan opt1 opt4</members></ifgroupentry><ifgroupentry><ifname>Empty</ifname><members/></ifgroupentry></ifgroups><filter><rule><interface>Trusted</interface></rule><rule><interface>trusted</interface></rule><rule><interface>Empty</interface></rule><rule><interface>lan</interface></rule></filter></opnsense>"#,
+        )
+        .expect("parse");
+        let findings = rule_reference_findings(&root);
+        let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "filter rule #1 references interface group 'trusted' but the group is named 'Trusted'",
+                "interface group 'Trusted' used by filter rules lists interface 'opt4' that does not exist",
+                "interface group 'Empty' used by filter rules has no members; its rules match nothing",
+            ]
+        );
+    }
+
     #[test]
     fn warns_on_missing_schedule_reference() {
         let root =
@@ -0,0 +1,156 @@
+//! Boot-time shell command review.
+//!
+//! pfSense runs `<system><earlyshellcmd>` and `<system><shellcmd>` entries
+//! as root at every boot, and the Shellcmd package adds commands that run
+//! after filter reloads. They often carry interface or routing workarounds
+//! the rest of the config depends on, and nothing else in the config says
+//! what they do, so each one is listed for a security and behaviour review.
+//!
+//! ## Command Sources
+//!
+//! - `<system><earlyshellcmd>` and `<system><shellcmd>`
+//! - `<installedpackages><shellcmdsettings><config>` (`cmdtype` other than
+//!   `disabled`); the package mirrors its boot commands into `<system>`, so
+//!   each command is listed once
+
+use xml_diff_core::XmlNode;
+
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// One command the platform runs from the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellCommand {
+    /// When it runs: `earlyshellcmd`, `shellcmd`, or `afterfilterchangeshellcmd`.
+    pub kind: String,
+    pub command: String,
+    /// Package description, when the command came from Shellcmd.
+    pub description: Option<String>,
+}
+
+impl std::fmt::Display for ShellCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} '{}'", self.kind, self.command)?;
+        if let Some(description) = &self.description {
+            write!(f, " ({description})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every boot-time shell command in `root`, in config order.
+pub fn shell_commands(root: &XmlNode) -> Vec<ShellCommand> {
+    let mut out: Vec<ShellCommand> = Vec::new();
+    if let Some(system) = root.get_child("system") {
+        for kind in ["earlyshellcmd", "shellcmd"] {
+            for node in system.get_children(kind) {
+                let command = node.text.as_deref().map(str::trim).unwrap_or("");
+                if !command.is_empty() {
+                    out.push(ShellCommand {
+                        kind: kind.to_string(),
+                        command: command.to_string(),
+                        description: None,
+                    });
+                }
+            }
+        }
+    }
+
+    let package = root
+        .get_child("installedpackages")
+        .and_then(|p| p.get_child("shellcmdsettings"));
+    for entry in package
+        .map(|p| p.get_children("config"))
+        .unwrap_or_default()
+    {
+        let command = entry.get_text(&["cmd"]).map(str::trim).unwrap_or("");
+        let kind = entry
+            .get_text(&["cmdtype"])
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .unwrap_or("shellcmd");
+        if command.is_empty() || kind == "disabled" {
+            continue;
+        }
+        let description = entry
+            .get_text(&["description"])
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(ToOwned::to_owned);
+        match out
+            .iter_mut()
+            .find(|c| c.kind == kind && c.command == command)
+        {
+            Some(existing) => existing.description = existing.description.take().or(description),
+            None => out.push(ShellCommand {
+                kind: kind.to_string(),
+                command: command.to_string(),
+                description,
+            }),
+        }
+    }
+    out
+}
+
+/// One `shellcmd_review` warning per boot-time shell command.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to validate
+///
+/// # Returns
+///
+/// Vector of warnings. Empty if the config runs no shell commands.
+pub fn shellcmd_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    shell_commands(root)
+        .into_iter()
+        .map(|command| {
+            VerifyFinding::warning(
+                FindingCode::ShellcmdReview,
+                format!("security review: {command} runs as root"),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{shell_commands, shellcmd_findings};
+
+    #[test]
+    fn lists_system_and_package_commands_once() {
+        let root = parse(
+            br#"<pfsense><system><earlyshellcmd>ifconfig igb0 -txcsum</earlyshellcmd><shellcmd>/root/routes.sh</shellcmd><shellcmd/></system><installedpackages><shellcmdsettings>
+                <config><cmd>ifconfig igb0 -txcsum</cmd><cmdtype>earlyshellcmd</cmdtype><description>NIC fix</description></config>
+                <config><cmd>pfctl -F states</cmd><cmdtype>afterfilterchangeshellcmd</cmdtype></config>
+                <config><cmd>/root/off.sh</cmd><cmdtype>disabled</cmdtype></config>
+            </shellcmdsettings></installedpackages></pfsense>"#,
+        )
+        .expect("parse");
+
+        let listed: Vec<String> = shell_commands(&root)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                "earlyshellcmd 'ifconfig igb0 -txcsum' (NIC fix)",
+                "shellcmd '/root/routes.sh'",
+                "afterfilterchangeshellcmd 'pfctl -F states'",
+            ]
+        );
+        let findings = shellcmd_findings(&root);
+        assert_eq!(findings.len(), 3);
+        assert!(findings.iter().all(|f| f.code == "shellcmd_review"));
+    }
+
+    #[test]
+    fn no_findings_without_commands() {
+        let root = parse(br#"<opnsense><system><hostname>fw</hostname></system></opnsense>"#)
+            .expect("parse");
+        assert!(shellcmd_findings(&root).is_empty());
+    }
+}
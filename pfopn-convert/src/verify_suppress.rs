@@ -0,0 +1,338 @@
+//! Accepted verify findings.
+//!
+//! A suppressions file lists findings that have been reviewed and accepted,
+//! so they stop failing `verify` in CI while anything new still does:
+//!
+//! ```toml
+//! [[suppress]]
+//! code = "missing_schedule_reference"
+//! path = "filter/rule*"
+//! justification = "schedules are recreated by hand after restore"
+//!
+//! [[suppress]]
+//! code = "shellcmd_review"
+//! message = "shellcmd: /usr/local/bin/fan-control.sh (runs at boot)"
+//! justification = "reviewed with the site owner"
+//! ```
+//!
+//! `path` is a glob (`*` matches any run of characters, `?` one character)
+//! over the finding's config path; `message` matches the finding's message
+//! exactly, for findings without a path. An entry with neither covers every
+//! finding with its code. Every entry needs a `justification`.
+//!
+//! [`Suppressions::baseline`] turns a report into entries accepting all of
+//! its findings (`verify --update-baseline`), keeping the justification of
+//! entries that already covered them.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::verify::{VerifyIssue, VerifyReport, VerifySeverity};
+use crate::verify_codes::FindingCode;
+
+/// Justification written for entries new to a baseline.
+pub const BASELINE_JUSTIFICATION: &str = "accepted by verify --update-baseline";
+
+/// One accepted finding, or a family of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    pub code: FindingCode,
+    /// Glob over the finding's config path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Exact message, for findings without a path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub justification: String,
+}
+
+impl Suppression {
+    /// Whether this entry covers `issue`.
+    pub fn matches(&self, issue: &VerifyIssue) -> bool {
+        if issue.code != self.code {
+            return false;
+        }
+        if let Some(pattern) = &self.path {
+            if !issue
+                .path
+                .as_deref()
+                .is_some_and(|path| glob_match(pattern, path))
+            {
+                return false;
+            }
+        }
+        self.message
+            .as_deref()
+            .is_none_or(|message| message == issue.message)
+    }
+
+    /// Short description for reports (`code path` or `code 'message'`).
+    pub fn describe(&self) -> String {
+        match (&self.path, &self.message) {
+            (Some(path), _) => format!("{} {path}", self.code),
+            (None, Some(message)) => format!("{} '{message}'", self.code),
+            (None, None) => self.code.to_string(),
+        }
+    }
+}
+
+/// A parsed suppressions file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppressions {
+    #[serde(default, rename = "suppress")]
+    pub entries: Vec<Suppression>,
+}
+
+#[derive(Debug, Error)]
+pub enum SuppressionsError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("invalid suppressions file {path}: {source}")]
+    Parse {
+        path: String,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("{path}: suppression for {entry} has no justification")]
+    MissingJustification { path: String, entry: String },
+}
+
+/// Outcome of applying a suppressions file, attached to the verify report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SuppressionReport {
+    pub file: String,
+    /// Findings dropped from the report.
+    pub suppressed: usize,
+    /// Entries that matched nothing and can be removed.
+    pub unused: Vec<String>,
+    /// Whether the file was rewritten from this run (`--update-baseline`).
+    pub updated: bool,
+}
+
+impl Suppressions {
+    /// Read a suppressions file.
+    pub fn load(path: &Path) -> Result<Self, SuppressionsError> {
+        let raw = fs::read_to_string(path).map_err(|source| SuppressionsError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::parse(&raw, path.display().to_string())
+    }
+
+    /// Parse suppressions TOML.
+    pub fn parse(raw: &str, path: String) -> Result<Self, SuppressionsError> {
+        let parsed: Suppressions =
+            toml::from_str(raw).map_err(|source| SuppressionsError::Parse {
+                path: path.clone(),
+                source,
+            })?;
+        if let Some(entry) = parsed
+            .entries
+            .iter()
+            .find(|e| e.justification.trim().is_empty())
+        {
+            return Err(SuppressionsError::MissingJustification {
+                path,
+                entry: entry.describe(),
+            });
+        }
+        Ok(parsed)
+    }
+
+    /// Entries accepting every counted finding in `report`.
+    ///
+    /// Findings with a path are keyed by path, others by message. An entry of
+    /// `previous` that already covered a finding keeps its justification.
+    pub fn baseline(report: &VerifyReport, previous: &Suppressions) -> Self {
+        let mut entries: Vec<Suppression> = Vec::new();
+        for issue in &report.issues {
+            if issue.severity == VerifySeverity::Info {
+                continue;
+            }
+            let justification = previous
+                .entries
+                .iter()
+                .find(|e| e.matches(issue))
+                .map_or(BASELINE_JUSTIFICATION, |e| e.justification.as_str());
+            let entry = Suppression {
+                code: issue.code,
+                message: issue.path.is_none().then(|| issue.message.clone()),
+                path: issue.path.clone(),
+                justification: justification.to_string(),
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        Self { entries }
+    }
+
+    /// Render as TOML for writing back to disk.
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("suppressions serialize to TOML")
+    }
+
+    /// Drop every issue an entry covers and recount `report`.
+    pub fn apply(&self, report: &mut VerifyReport, file: &str) -> SuppressionReport {
+        let mut used = vec![false; self.entries.len()];
+        let before = report.issues.len();
+        report.issues.retain(
+            |issue| match self.entries.iter().position(|e| e.matches(issue)) {
+                Some(idx) => {
+                    used[idx] = true;
+                    false
+                }
+                None => true,
+            },
+        );
+        let suppressed = before - report.issues.len();
+        report.recount();
+        SuppressionReport {
+            file: file.to_string(),
+            suppressed,
+            unused: self
+                .entries
+                .iter()
+                .zip(used)
+                .filter(|(_, used)| !used)
+                .map(|(entry, _)| entry.describe())
+                .collect(),
+            updated: false,
+        }
+    }
+}
+
+/// Match `text` against a glob of `*` and `?` wildcards.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::{glob_match, Suppressions, SuppressionsError, BASELINE_JUSTIFICATION};
+    use crate::verify::build_verify_report;
+
+    const CONFIG: &[u8] = b"<pfsense><system/><interfaces><lan/></interfaces><filter>\
+        <rule><interface>opt7</interface></rule></filter>\
+        <OPNsense><Kea><dhcp4><subnets><subnet4 uuid=\"s1\"/><subnet4/></subnets></dhcp4></Kea></OPNsense>\
+        </pfsense>";
+
+    #[test]
+    fn globs_match_paths() {
+        assert!(glob_match("filter/rule*", "filter/rule[3]"));
+        assert!(glob_match(
+            "OPNsense/*/subnet4[?]",
+            "OPNsense/Kea/dhcp4/subnets/subnet4[2]"
+        ));
+        assert!(!glob_match("filter/rule", "filter/rule[3]"));
+    }
+
+    #[test]
+    fn suppresses_by_path_or_message_and_lists_unused_entries() {
+        let root = parse(CONFIG).expect("parse");
+        let mut report = build_verify_report(&root, None);
+        let errors = report.errors;
+        let suppressions = Suppressions::parse(
+            r#"
+            [[suppress]]
+            code = "uuid_missing"
+            path = "OPNsense/Kea/*"
+            justification = "assigned on import"
+
+            [[suppress]]
+            code = "missing_interface_reference"
+            message = "filter rule #0 references missing interface 'opt7'"
+            justification = "opt7 is added after restore"
+
+            [[suppress]]
+            code = "missing_alias_reference"
+            justification = "stale"
+            "#,
+            "accepted.toml".to_string(),
+        )
+        .expect("parse");
+
+        let applied = suppressions.apply(&mut report, "accepted.toml");
+        assert_eq!(applied.suppressed, 2);
+        assert_eq!(applied.unused, ["missing_alias_reference"]);
+        assert_eq!(report.errors, errors - 1);
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| i.code != "uuid_missing" && i.code != "missing_interface_reference"));
+    }
+
+    #[test]
+    fn baseline_accepts_everything_and_keeps_justifications() {
+        let root = parse(CONFIG).expect("parse");
+        let report = build_verify_report(&root, None);
+        let previous = Suppressions::parse(
+            "[[suppress]]\ncode = \"uuid_missing\"\njustification = \"assigned on import\"\n",
+            "accepted.toml".to_string(),
+        )
+        .expect("parse");
+
+        let baseline = Suppressions::baseline(&report, &previous);
+        let reparsed =
+            Suppressions::parse(&baseline.to_toml(), "accepted.toml".to_string()).expect("reparse");
+        assert_eq!(reparsed, baseline);
+        let uuid = baseline
+            .entries
+            .iter()
+            .find(|e| e.code == "uuid_missing")
+            .expect("uuid entry");
+        assert_eq!(
+            uuid.path.as_deref(),
+            Some("OPNsense/Kea/dhcp4/subnets/subnet4[2]")
+        );
+        assert_eq!(uuid.justification, "assigned on import");
+        assert!(baseline
+            .entries
+            .iter()
+            .any(|e| e.justification == BASELINE_JUSTIFICATION));
+
+        let mut again = build_verify_report(&root, None);
+        baseline.apply(&mut again, "accepted.toml");
+        assert_eq!((again.errors, again.warnings), (0, 0));
+
+        let err = Suppressions::parse(
+            "[[suppress]]\ncode = \"uuid_missing\"\njustification = \" \"\n",
+            "accepted.toml".to_string(),
+        )
+        .expect_err("no justification");
+        assert!(matches!(
+            err,
+            SuppressionsError::MissingJustification { .. }
+        ));
+    }
+}
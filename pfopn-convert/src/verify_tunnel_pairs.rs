@@ -0,0 +1,268 @@
+//! Cross-device IPsec tunnel checks for batch conversions.
+//!
+//! A site-to-site tunnel only comes up when both firewalls agree on it, and
+//! each side is usually converted on its own. When a batch converts both
+//! ends, the [site map](crate::site_map) ties each phase1 `remote-gateway`
+//! to a device, so the two halves can be compared.
+//!
+//! ## Checks Performed
+//!
+//! 1. **Missing return tunnel** — A device whose phase1 points at another
+//!    device in the batch has a phase1 pointing back (warning)
+//! 2. **Pre-shared keys** — Both ends of a PSK tunnel use the same key
+//! 3. **Networks** — Each end's phase2 local networks are the other end's
+//!    remote networks
+//!
+//! Tunnels are read from the `<ipsec>` phase1/phase2 layout, which both
+//! platforms' converted output carries. Disabled phase1 entries are skipped.
+
+use std::collections::BTreeSet;
+use std::net::Ipv4Addr;
+
+use xml_diff_core::XmlNode;
+
+use crate::site_map::SiteMap;
+use crate::transform::booleans::flag_set;
+use crate::transform::lan_ip::network;
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// One end of a tunnel as configured on a device.
+struct TunnelEnd<'a> {
+    device: &'a str,
+    descr: String,
+    peer: String,
+    psk: Option<&'a str>,
+    /// `(local, remote)` network pairs of the phase2 entries.
+    networks: BTreeSet<(String, String)>,
+}
+
+/// Compare the two ends of every tunnel between devices in `devices`.
+///
+/// # Arguments
+///
+/// * `devices` - Batch device names with their converted configs
+/// * `sites` - Site map naming each device's public addresses
+///
+/// # Returns
+///
+/// Vector of findings naming both devices. Empty without a site map.
+pub fn tunnel_pair_findings(devices: &[(&str, &XmlNode)], sites: &SiteMap) -> Vec<VerifyFinding> {
+    let ends: Vec<TunnelEnd> = devices
+        .iter()
+        .flat_map(|(name, root)| tunnel_ends(name, root, sites))
+        .collect();
+    let in_batch: BTreeSet<&str> = devices.iter().map(|(name, _)| *name).collect();
+    let mut out = Vec::new();
+    for end in &ends {
+        if !in_batch.contains(end.peer.as_str()) {
+            continue;
+        }
+        let Some(back) = ends
+            .iter()
+            .find(|other| other.device == end.peer && other.peer == end.device)
+        else {
+            out.push(VerifyFinding::warning(
+                FindingCode::TunnelPairMissing,
+                format!(
+                    "{} IPsec tunnel '{}' points at {}, which has no tunnel back to {}",
+                    end.device, end.descr, end.peer, end.device
+                ),
+            ));
+            continue;
+        };
+        // Each pair is compared once, from the device that sorts first.
+        if end.device > back.device {
+            continue;
+        }
+        if let (Some(a), Some(b)) = (end.psk, back.psk) {
+            if a != b {
+                out.push(VerifyFinding::error(
+                    FindingCode::TunnelPairPskMismatch,
+                    format!(
+                        "{} tunnel '{}' and {} tunnel '{}' use different pre-shared keys",
+                        end.device, end.descr, back.device, back.descr
+                    ),
+                ));
+            }
+        }
+        let mirrored: BTreeSet<(String, String)> = back
+            .networks
+            .iter()
+            .map(|(local, remote)| (remote.clone(), local.clone()))
+            .collect();
+        if end.networks != mirrored {
+            out.push(VerifyFinding::error(
+                FindingCode::TunnelPairNetworkMismatch,
+                format!(
+                    "{} tunnel '{}' carries {} but {} tunnel '{}' expects {}",
+                    end.device,
+                    end.descr,
+                    render_networks(&end.networks),
+                    back.device,
+                    back.descr,
+                    render_networks(&mirrored)
+                ),
+            ));
+        }
+    }
+    out
+}
+
+fn tunnel_ends<'a>(device: &'a str, root: &'a XmlNode, sites: &SiteMap) -> Vec<TunnelEnd<'a>> {
+    let Some(ipsec) = root.get_child("ipsec") else {
+        return Vec::new();
+    };
+    let phase2s = ipsec.get_children("phase2");
+    let mut out = Vec::new();
+    for phase1 in ipsec.get_children("phase1") {
+        if flag_set(phase1, "disabled") {
+            continue;
+        }
+        let Some(peer) = phase1
+            .get_text(&["remote-gateway"])
+            .and_then(|gateway| sites.site_for(gateway))
+        else {
+            continue;
+        };
+        let ikeid = phase1.get_text(&["ikeid"]).unwrap_or_default();
+        let networks = phase2s
+            .iter()
+            .filter(|p2| p2.get_text(&["ikeid"]).unwrap_or_default() == ikeid)
+            .filter(|p2| !flag_set(p2, "disabled"))
+            .map(|p2| {
+                (
+                    selector(root, p2, "localid"),
+                    selector(root, p2, "remoteid"),
+                )
+            })
+            .collect();
+        let psk = phase1
+            .get_text(&["pre-shared-key"])
+            .filter(|_| {
+                phase1
+                    .get_text(&["authentication_method"])
+                    .is_none_or(|m| m.contains("pre_shared_key") || m == "psk")
+            })
+            .filter(|key| !key.is_empty());
+        out.push(TunnelEnd {
+            device,
+            descr: phase1
+                .get_text(&["descr"])
+                .filter(|d| !d.is_empty())
+                .map_or_else(|| format!("ikeid {ikeid}"), str::to_string),
+            peer: peer.name.clone(),
+            psk,
+            networks,
+        });
+    }
+    out
+}
+
+/// A phase2 selector as a CIDR; interface selectors (`lan`) resolve to the
+/// interface's subnet.
+fn selector(root: &XmlNode, phase2: &XmlNode, side: &str) -> String {
+    let Some(id) = phase2.get_child(side) else {
+        return String::new();
+    };
+    let kind = id.get_text(&["type"]).unwrap_or_default();
+    let address = id.get_text(&["address"]).unwrap_or_default();
+    match kind {
+        "network" => format!(
+            "{address}/{}",
+            id.get_text(&["netbits"]).unwrap_or_default()
+        ),
+        "address" => format!("{address}/32"),
+        iface => root
+            .get_child("interfaces")
+            .and_then(|i| i.get_child(iface))
+            .and_then(interface_network)
+            .unwrap_or_else(|| iface.to_string()),
+    }
+}
+
+fn interface_network(iface: &XmlNode) -> Option<String> {
+    let ip: Ipv4Addr = iface.get_text(&["ipaddr"])?.trim().parse().ok()?;
+    let prefix: u8 = iface.get_text(&["subnet"])?.trim().parse().ok()?;
+    Some(format!("{}/{prefix}", Ipv4Addr::from(network(ip, prefix)?)))
+}
+
+fn render_networks(networks: &BTreeSet<(String, String)>) -> String {
+    if networks.is_empty() {
+        return "no networks".to_string();
+    }
+    networks
+        .iter()
+        .map(|(local, remote)| format!("{local} <-> {remote}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::tunnel_pair_findings;
+    use crate::site_map::SiteMap;
+
+    fn sites() -> SiteMap {
+        SiteMap::parse(
+            r#"
+            [[site]]
+            name = "hq"
+            addresses = ["203.0.113.10"]
+            [[site]]
+            name = "branch"
+            addresses = ["203.0.113.20"]
+            hostname = "branch.example.net"
+            "#,
+            "sites.toml".to_string(),
+        )
+        .expect("sites")
+    }
+
+    fn tunnel(peer: &str, key: &str, local: &str, remote: &str) -> String {
+        format!(
+            "<pfsense><interfaces><lan><ipaddr>{local}.1</ipaddr><subnet>24</subnet></lan></interfaces><ipsec>\
+             <phase1><ikeid>1</ikeid><descr>to peer</descr><remote-gateway>{peer}</remote-gateway>\
+             <authentication_method>pre_shared_key</authentication_method><pre-shared-key>{key}</pre-shared-key></phase1>\
+             <phase2><ikeid>1</ikeid><localid><type>lan</type></localid>\
+             <remoteid><type>network</type><address>{remote}.0</address><netbits>24</netbits></remoteid></phase2>\
+             </ipsec></pfsense>"
+        )
+    }
+
+    #[test]
+    fn matching_ends_pass() {
+        let hq = parse(tunnel("branch.example.net", "s3cret", "10.0.0", "10.1.0").as_bytes())
+            .expect("hq");
+        let branch =
+            parse(tunnel("203.0.113.10", "s3cret", "10.1.0", "10.0.0").as_bytes()).expect("branch");
+        assert!(tunnel_pair_findings(&[("hq", &hq), ("branch", &branch)], &sites()).is_empty());
+    }
+
+    #[test]
+    fn reports_key_and_network_mismatches_and_missing_returns() {
+        let hq =
+            parse(tunnel("203.0.113.20", "s3cret", "10.0.0", "10.1.0").as_bytes()).expect("hq");
+        let branch =
+            parse(tunnel("203.0.113.10", "other", "10.1.0", "10.9.0").as_bytes()).expect("branch");
+        let codes: Vec<String> =
+            tunnel_pair_findings(&[("hq", &hq), ("branch", &branch)], &sites())
+                .into_iter()
+                .map(|f| f.code.to_string())
+                .collect();
+        assert_eq!(
+            codes,
+            ["tunnel_pair_psk_mismatch", "tunnel_pair_network_mismatch"]
+        );
+
+        let lone = parse(b"<pfsense/>").expect("lone");
+        let findings = tunnel_pair_findings(&[("hq", &hq), ("branch", &lone)], &sites());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            findings[0].message,
+            "hq IPsec tunnel 'to peer' points at branch, which has no tunnel back to hq"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! OPNsense MVC UUID reference validation.
+//!
+//! OPNsense links model items by UUID; a reference to a UUID that is not
+//! in the config leaves the item orphaned in the GUI and is silently skipped
+//! when the service config is generated.
+//!
+//! ## Checks Performed
+//!
+//! 1. **Unresolved references** — Each known reference field names a UUID
+//!    of an item in its target list
+//! 2. **Duplicate UUIDs** — No two items anywhere in the config share a UUID
+//! 3. **Missing UUIDs** — An item in a model list (one whose siblings of the
+//!    same tag carry a `uuid`) has one too; `verify --fix uuids` issues them
+//!
+//! ## Reference Fields
+//!
+//! | Field | Refers to |
+//! |-------|-----------|
+//! | WireGuard `server/peers` | `client` items |
+//! | Swanctl `local`, `remote`, `child` `connection` | `Connection` items |
+//! | Kea `reservation/subnet` (v4, v6) | `subnet4` / `subnet6` items |
+//! | DHCRelay `relays/destination` | `destinations` items |
+//! | ACME `certificate/account`, `certificate/validationMethod` | `account` / `validation` items |
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use xml_diff_core::XmlNode;
+
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+
+/// One field holding UUIDs of items in another list.
+struct UuidRef {
+    /// Human-readable name of the referencing item.
+    label: &'static str,
+    /// Path from the root to the container holding referencing items.
+    container: &'static [&'static str],
+    /// Tags of referencing items within the container.
+    items: &'static [&'static str],
+    /// Field holding one or more comma-separated UUIDs.
+    field: &'static str,
+    /// Path from the root to the container holding referenced items.
+    targets: &'static [&'static str],
+    /// Tag of referenced items within the target container.
+    target_tag: &'static str,
+}
+
+const UUID_REFS: &[UuidRef] = &[
+    UuidRef {
+        label: "WireGuard instance",
+        container: &["OPNsense", "wireguard", "server", "servers"],
+        items: &["server"],
+        field: "peers",
+        targets: &["OPNsense", "wireguard", "client", "clients"],
+        target_tag: "client",
+    },
+    UuidRef {
+        label: "Swanctl",
+        container: &["OPNsense", "Swanctl", "locals"],
+        items: &["local"],
+        field: "connection",
+        targets: &["OPNsense", "Swanctl", "Connections"],
+        target_tag: "Connection",
+    },
+    UuidRef {
+        label: "Swanctl",
+        container: &["OPNsense", "Swanctl", "remotes"],
+        items: &["remote"],
+        field: "connection",
+        targets: &["OPNsense", "Swanctl", "Connections"],
+        target_tag: "Connection",
+    },
+    UuidRef {
+        label: "Swanctl",
+        container: &["OPNsense", "Swanctl", "children"],
+        items: &["child"],
+        field: "connection",
+        targets: &["OPNsense", "Swanctl", "Connections"],
+        target_tag: "Connection",
+    },
+    UuidRef {
+        label: "Kea DHCPv4",
+        container: &["OPNsense", "Kea", "dhcp4", "reservations"],
+        items: &["reservation"],
+        field: "subnet",
+        targets: &["OPNsense", "Kea", "dhcp4", "subnets"],
+        target_tag: "subnet4",
+    },
+    UuidRef {
+        label: "Kea DHCPv6",
+        container: &["OPNsense", "Kea", "dhcp6", "reservations"],
+        items: &["reservation"],
+        field: "subnet",
+        targets: &["OPNsense", "Kea", "dhcp6", "subnets"],
+        target_tag: "subnet6",
+    },
+    UuidRef {
+        label: "DHCP relay",
+        container: &["OPNsense", "DHCRelay"],
+        items: &["relays"],
+        field: "destination",
+        targets: &["OPNsense", "DHCRelay"],
+        target_tag: "destinations",
+    },
+    UuidRef {
+        label: "ACME certificate",
+        container: &["OPNsense", "AcmeClient", "certificates"],
+        items: &["certificate"],
+        field: "account",
+        targets: &["OPNsense", "AcmeClient", "accounts"],
+        target_tag: "account",
+    },
+    UuidRef {
+        label: "ACME certificate",
+        container: &["OPNsense", "AcmeClient", "certificates"],
+        items: &["certificate"],
+        field: "validationMethod",
+        targets: &["OPNsense", "AcmeClient", "validations"],
+        target_tag: "validation",
+    },
+];
+
+/// Find unresolved UUID references and duplicate UUIDs.
+///
+/// # Arguments
+///
+/// * `root` - Configuration root to validate
+///
+/// # Returns
+///
+/// Vector of findings. Empty for pfSense configs, which carry no model UUIDs.
+pub fn uuid_reference_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let mut out = Vec::new();
+    for reference in UUID_REFS {
+        let Some(container) = find_path(root, reference.container) else {
+            continue;
+        };
+        let known: BTreeSet<&str> = find_path(root, reference.targets)
+            .map(|t| {
+                t.children
+                    .iter()
+                    .filter(|c| c.tag == reference.target_tag)
+                    .filter_map(|c| c.attributes.get("uuid").map(String::as_str))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for item in container
+            .children
+            .iter()
+            .filter(|c| reference.items.contains(&c.tag.as_str()))
+        {
+            let value = item.get_text(&[reference.field]).unwrap_or("");
+            for uuid in value.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                if known.contains(uuid) {
+                    continue;
+                }
+                out.push(
+                    VerifyFinding::error(
+                        FindingCode::UuidRefUnresolved,
+                        format!(
+                            "{} {} '{}' {} references missing {} '{uuid}'",
+                            reference.label,
+                            item.tag,
+                            item_name(item),
+                            reference.field,
+                            reference.target_tag
+                        ),
+                    )
+                    .with("uuid", uuid.to_string()),
+                );
+            }
+        }
+    }
+
+    let mut seen: BTreeMap<&str, usize> = BTreeMap::new();
+    count_uuids(root, &mut seen);
+    for (uuid, count) in seen.into_iter().filter(|(_, count)| *count > 1) {
+        out.push(
+            VerifyFinding::error(
+                FindingCode::UuidDuplicate,
+                format!("UUID '{uuid}' is used by {count} items"),
+            )
+            .with("uuid", uuid.to_string()),
+        );
+    }
+    let mut missing = Vec::new();
+    missing_uuid_paths(root, "", &mut missing);
+    for path in missing {
+        out.push(
+            VerifyFinding::warning(
+                FindingCode::UuidMissing,
+                format!("{path} has no uuid while other items in its list do"),
+            )
+            .at(path.clone()),
+        );
+    }
+    out
+}
+
+/// Whether `parent.children[idx]` lacks the `uuid` a sibling of the same tag has.
+pub(crate) fn lacks_list_uuid(parent: &XmlNode, idx: usize) -> bool {
+    let item = &parent.children[idx];
+    !item.attributes.contains_key("uuid")
+        && parent
+            .children
+            .iter()
+            .any(|c| c.tag == item.tag && c.attributes.contains_key("uuid"))
+}
+
+/// Slash-joined labels for each child of `node` below `label`, with a 1-based
+/// `[n]` on repeated tags (`OPNsense/Kea/dhcp4/subnets/subnet4[2]`).
+pub(crate) fn child_labels(node: &XmlNode, label: &str) -> Vec<String> {
+    let mut seen: BTreeMap<&str, usize> = BTreeMap::new();
+    node.children
+        .iter()
+        .map(|child| {
+            let total = node.children.iter().filter(|c| c.tag == child.tag).count();
+            let position = seen.entry(child.tag.as_str()).or_default();
+            *position += 1;
+            let segment = if total > 1 {
+                format!("{}[{position}]", child.tag)
+            } else {
+                child.tag.clone()
+            };
+            if label.is_empty() {
+                segment
+            } else {
+                format!("{label}/{segment}")
+            }
+        })
+        .collect()
+}
+
+fn missing_uuid_paths(node: &XmlNode, label: &str, out: &mut Vec<String>) {
+    for (idx, (child, child_label)) in node
+        .children
+        .iter()
+        .zip(child_labels(node, label))
+        .enumerate()
+    {
+        if lacks_list_uuid(node, idx) {
+            out.push(child_label.clone());
+        }
+        missing_uuid_paths(child, &child_label, out);
+    }
+}
+
+/// A name to identify an item in messages: its uuid, else its first named field.
+fn item_name(item: &XmlNode) -> &str {
+    item.attributes
+        .get("uuid")
+        .map(String::as_str)
+        .or_else(|| {
+            ["name", "description", "descr", "interface"]
+                .iter()
+                .find_map(|f| item.get_text(&[f]))
+        })
+        .unwrap_or("?")
+}
+
+fn count_uuids<'a>(node: &'a XmlNode, seen: &mut BTreeMap<&'a str, usize>) {
+    if let Some(uuid) = node.attributes.get("uuid") {
+        *seen.entry(uuid.as_str()).or_default() += 1;
+    }
+    for child in &node.children {
+        count_uuids(child, seen);
+    }
+}
+
+fn find_path<'a>(root: &'a XmlNode, path: &[&str]) -> Option<&'a XmlNode> {
+    path.iter().try_fold(root, |node, tag| node.get_child(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::uuid_reference_findings;
+
+    #[test]
+    fn accepts_resolved_references() {
+        let root = parse(
+            br#"<opnsense><OPNsense><Swanctl><Connections><Connection uuid="c1"/></Connections><children><child uuid="k1"><connection>c1</connection></child></children></Swanctl><wireguard><client><clients><client uuid="p1"/><client uuid="p2"/></clients></client><server><servers><server uuid="s1"><peers>p1,p2</peers></server></servers></server></wireguard></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        assert!(uuid_reference_findings(&root).is_empty());
+    }
+
+    #[test]
+    fn reports_unresolved_references_and_duplicates() {
+        let root = parse(
+            br#"<opnsense><OPNsense><Kea><dhcp4><subnets><subnet4 uuid="s1"/></subnets><reservations><reservation uuid="r1"><subnet>gone</subnet></reservation></reservations></dhcp4></Kea><DHCRelay><destinations uuid="s1"/><relays uuid="x"><destination>d9</destination></relays></DHCRelay></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+
+        let codes: Vec<_> = uuid_reference_findings(&root)
+            .into_iter()
+            .map(|f| (f.code, f.message))
+            .collect();
+        assert_eq!(codes.len(), 3);
+        assert!(codes
+            .iter()
+            .any(|(c, m)| c == "uuid_ref_unresolved" && m.contains("'gone'")));
+        assert!(codes
+            .iter()
+            .any(|(c, m)| c == "uuid_ref_unresolved" && m.contains("'d9'")));
+        assert!(codes
+            .iter()
+            .any(|(c, m)| c == "uuid_duplicate" && m.contains("'s1'")));
+    }
+
+    #[test]
+    fn warns_on_list_items_without_uuid() {
+        let root = parse(
+            br#"<opnsense><OPNsense><Kea><dhcp4><subnets><subnet4 uuid="s1"/><subnet4/></subnets></dhcp4></Kea></OPNsense></opnsense>"#,
+        )
+        .expect("parse");
+        let findings = uuid_reference_findings(&root);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "uuid_missing");
+        assert!(findings[0]
+            .message
+            .starts_with("OPNsense/Kea/dhcp4/subnets/subnet4[2] "));
+    }
+}
@@ -0,0 +1,507 @@
+//! Leaf value format validation.
+//!
+//! Both firewalls reject a config (or silently break the service) when a
+//! typed field holds a malformed value, so typos are caught here before
+//! restore.
+//!
+//! ## Checks Performed
+//!
+//! 1. **Formats** — Every field in [`FIELDS`] holds its expected type: IPv4
+//!    or IPv6 address, CIDR, prefix length, MAC address, port or port range,
+//!    hostname, or domain. Errors include a hint for common typos
+//!    (`192.168.1.256`, `192,168.1.1`, `aa-bb-cc-dd-ee-ff`, `90-80`)
+//! 2. **Network addresses** — A CIDR with host bits set is reported with
+//!    the network it most likely meant
+//!
+//! DHCP pool consistency is checked separately by [`crate::verify_dhcp`].
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use xml_diff_core::XmlNode;
+
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
+use crate::verify_routes::parse_cidr;
+
+/// Format a field value must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Ipv4,
+    Ipv6,
+    /// IPv4 or IPv6 address.
+    Ip,
+    /// `address/prefix`.
+    Cidr,
+    /// Prefix length up to the given width (32 or 128).
+    Prefix(u8),
+    /// Colon-separated MAC address.
+    Mac,
+    /// A single port, 1–65535.
+    Port,
+    /// A port or `low-high`/`low:high` range; values that do not start with
+    /// a digit are alias names and are not checked.
+    PortOrAlias,
+    /// A single DNS label (no domain part).
+    Hostname,
+    /// Dot-separated DNS labels.
+    Domain,
+}
+
+/// One typed field: the nodes at `path` (`*` matches any tag) and the child
+/// holding the value. `keywords` are non-format values the field accepts.
+#[derive(Debug, Clone, Copy)]
+pub struct ValueField {
+    pub path: &'static [&'static str],
+    pub field: &'static str,
+    pub kind: ValueKind,
+    pub keywords: &'static [&'static str],
+}
+
+impl ValueField {
+    const fn new(
+        path: &'static [&'static str],
+        field: &'static str,
+        kind: ValueKind,
+        keywords: &'static [&'static str],
+    ) -> Self {
+        Self {
+            path,
+            field,
+            kind,
+            keywords,
+        }
+    }
+}
+
+const IPV4_MODES: &[&str] = &["dhcp", "pppoe", "pptp", "l2tp", "ppp"];
+const IPV6_MODES: &[&str] = &["dhcp6", "slaac", "track6", "6rd", "6to4"];
+
+/// Fields checked by [`value_findings`], on both platforms.
+pub const FIELDS: &[ValueField] = &[
+    ValueField::new(&["system"], "hostname", ValueKind::Hostname, &[]),
+    ValueField::new(&["system"], "domain", ValueKind::Domain, &[]),
+    ValueField::new(&["system"], "dnsserver", ValueKind::Ip, &[]),
+    ValueField::new(&["system", "webgui"], "port", ValueKind::Port, &[]),
+    ValueField::new(&["system", "ssh"], "port", ValueKind::Port, &[]),
+    ValueField::new(&["interfaces", "*"], "ipaddr", ValueKind::Ipv4, IPV4_MODES),
+    ValueField::new(&["interfaces", "*"], "subnet", ValueKind::Prefix(32), &[]),
+    ValueField::new(
+        &["interfaces", "*"],
+        "ipaddrv6",
+        ValueKind::Ipv6,
+        IPV6_MODES,
+    ),
+    ValueField::new(
+        &["interfaces", "*"],
+        "subnetv6",
+        ValueKind::Prefix(128),
+        &[],
+    ),
+    ValueField::new(&["interfaces", "*"], "spoofmac", ValueKind::Mac, &[]),
+    ValueField::new(&["dhcpd", "*", "range"], "from", ValueKind::Ipv4, &[]),
+    ValueField::new(&["dhcpd", "*", "range"], "to", ValueKind::Ipv4, &[]),
+    ValueField::new(
+        &["dhcpd", "*", "pool", "range"],
+        "from",
+        ValueKind::Ipv4,
+        &[],
+    ),
+    ValueField::new(&["dhcpd", "*", "pool", "range"], "to", ValueKind::Ipv4, &[]),
+    ValueField::new(&["dhcpd", "*"], "gateway", ValueKind::Ipv4, &["none"]),
+    ValueField::new(&["dhcpd", "*"], "dnsserver", ValueKind::Ipv4, &[]),
+    ValueField::new(&["dhcpd", "*"], "domain", ValueKind::Domain, &[]),
+    ValueField::new(&["dhcpd", "*", "staticmap"], "mac", ValueKind::Mac, &[]),
+    ValueField::new(&["dhcpd", "*", "staticmap"], "ipaddr", ValueKind::Ipv4, &[]),
+    ValueField::new(
+        &["dhcpd", "*", "staticmap"],
+        "hostname",
+        ValueKind::Hostname,
+        &[],
+    ),
+    ValueField::new(&["dhcpdv6", "*", "range"], "from", ValueKind::Ipv6, &[]),
+    ValueField::new(&["dhcpdv6", "*", "range"], "to", ValueKind::Ipv6, &[]),
+    ValueField::new(
+        &["dhcpdv6", "*", "staticmap"],
+        "ipaddrv6",
+        ValueKind::Ipv6,
+        &[],
+    ),
+    ValueField::new(&["dhcpd6", "*", "range"], "from", ValueKind::Ipv6, &[]),
+    ValueField::new(&["dhcpd6", "*", "range"], "to", ValueKind::Ipv6, &[]),
+    ValueField::new(
+        &["dhcpd6", "*", "staticmap"],
+        "ipaddrv6",
+        ValueKind::Ipv6,
+        &[],
+    ),
+    ValueField::new(&["staticroutes", "route"], "network", ValueKind::Cidr, &[]),
+    ValueField::new(
+        &["gateways", "gateway_item"],
+        "gateway",
+        ValueKind::Ip,
+        &["dynamic"],
+    ),
+    ValueField::new(&["gateways", "gateway_item"], "monitor", ValueKind::Ip, &[]),
+    ValueField::new(&["virtualip", "vip"], "subnet", ValueKind::Ip, &[]),
+    ValueField::new(
+        &["virtualip", "vip"],
+        "subnet_bits",
+        ValueKind::Prefix(128),
+        &[],
+    ),
+    ValueField::new(
+        &["filter", "rule", "source"],
+        "port",
+        ValueKind::PortOrAlias,
+        &[],
+    ),
+    ValueField::new(
+        &["filter", "rule", "destination"],
+        "port",
+        ValueKind::PortOrAlias,
+        &[],
+    ),
+    ValueField::new(
+        &["nat", "rule", "destination"],
+        "port",
+        ValueKind::PortOrAlias,
+        &[],
+    ),
+    ValueField::new(&["nat", "rule"], "local-port", ValueKind::PortOrAlias, &[]),
+    ValueField::new(
+        &["OPNsense", "Kea", "dhcp4", "subnets", "subnet4"],
+        "subnet",
+        ValueKind::Cidr,
+        &[],
+    ),
+    ValueField::new(
+        &["OPNsense", "Kea", "dhcp4", "reservations", "reservation"],
+        "hw_address",
+        ValueKind::Mac,
+        &[],
+    ),
+    ValueField::new(
+        &["OPNsense", "Kea", "dhcp4", "reservations", "reservation"],
+        "ip_address",
+        ValueKind::Ipv4,
+        &[],
+    ),
+];
+
+/// Find malformed values in the [`FIELDS`] table.
+pub fn value_findings(root: &XmlNode) -> Vec<VerifyFinding> {
+    let mut out = Vec::new();
+    for field in FIELDS {
+        let mut nodes = Vec::new();
+        nodes_at(root, field.path, String::new(), &mut nodes);
+        for (label, node) in nodes {
+            for value_node in node.children.iter().filter(|c| c.tag == field.field) {
+                let Some(value) = value_node
+                    .text
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                else {
+                    continue;
+                };
+                if field.keywords.contains(&value) {
+                    continue;
+                }
+                let location = format!("{label}/{}", field.field);
+                if let Some(finding) = check_value(&location, value, field.kind) {
+                    out.push(finding);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Check one value; `None` when it is well formed.
+fn check_value(location: &str, value: &str, kind: ValueKind) -> Option<VerifyFinding> {
+    let problem = match kind {
+        ValueKind::Ipv4 => ipv4_problem(value),
+        ValueKind::Ipv6 => value
+            .parse::<Ipv6Addr>()
+            .err()
+            .map(|_| "is not a valid IPv6 address".to_string()),
+        ValueKind::Ip => {
+            if value.contains(':') {
+                value
+                    .parse::<Ipv6Addr>()
+                    .err()
+                    .map(|_| "is not a valid IPv6 address".to_string())
+            } else {
+                ipv4_problem(value)
+            }
+        }
+        ValueKind::Cidr => return cidr_finding(location, value),
+        ValueKind::Prefix(max) => prefix_problem(value, max),
+        ValueKind::Mac => mac_problem(value),
+        ValueKind::Port => port_problem(value),
+        ValueKind::PortOrAlias => {
+            if !value.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            port_range_problem(value)
+        }
+        ValueKind::Hostname if value.contains('.') && domain_problem(value).is_none() => {
+            return Some(VerifyFinding::warning(
+                FindingCode::HostnameHasDomain,
+                format!(
+                    "{location}: '{value}' includes a domain; the GUI expects the hostname label only"
+                ),
+            ));
+        }
+        ValueKind::Hostname => label_problem(value),
+        ValueKind::Domain => domain_problem(value),
+    }?;
+    Some(error(
+        FindingCode::InvalidValue,
+        format!("{location}: '{value}' {problem}"),
+    ))
+}
+
+/// Explain why `value` is not an IPv4 address, with a typo hint.
+fn ipv4_problem(value: &str) -> Option<String> {
+    if value.parse::<Ipv4Addr>().is_ok() {
+        return None;
+    }
+    let base = "is not a valid IPv4 address";
+    if value.contains(',') {
+        let fixed = value.replace(',', ".");
+        if fixed.parse::<Ipv4Addr>().is_ok() {
+            return Some(format!("{base}; did you mean '{fixed}'?"));
+        }
+    }
+    let octets: Vec<&str> = value.split('.').collect();
+    if octets.len() != 4 {
+        return Some(format!(
+            "{base} (expected 4 octets, found {})",
+            octets.len()
+        ));
+    }
+    for octet in octets {
+        match octet.parse::<u32>() {
+            Ok(n) if n > 255 => {
+                return Some(format!("{base} (octet {n} is out of range 0-255)"));
+            }
+            Ok(_) if octet.len() > 1 && octet.starts_with('0') => {
+                return Some(format!("{base} (octet '{octet}' has a leading zero)"));
+            }
+            Ok(_) => {}
+            Err(_) => return Some(format!("{base} (octet '{octet}' is not a number)")),
+        }
+    }
+    Some(base.to_string())
+}
+
+/// Check a CIDR, including host bits set inside the prefix.
+fn cidr_finding(location: &str, value: &str) -> Option<VerifyFinding> {
+    let Some((addr, prefix)) = value.split_once('/') else {
+        return Some(error(
+            FindingCode::InvalidValue,
+            format!("{location}: '{value}' is not a CIDR (expected address/prefix)"),
+        ));
+    };
+    let width = if addr.contains(':') { 128 } else { 32 };
+    let problem = if width == 32 {
+        ipv4_problem(addr)
+    } else {
+        addr.parse::<Ipv6Addr>()
+            .err()
+            .map(|_| "is not a valid IPv6 address".to_string())
+    }
+    .map(|p| format!("address '{addr}' {p}"))
+    .or_else(|| prefix_problem(prefix, width).map(|p| format!("prefix '{prefix}' {p}")));
+    if let Some(problem) = problem {
+        return Some(error(
+            FindingCode::InvalidValue,
+            format!("{location}: '{value}' {problem}"),
+        ));
+    }
+    let (addr, prefix) = parse_cidr(value)?;
+    let network = network_address(addr, prefix);
+    (network != addr).then(|| {
+        VerifyFinding::warning(
+            FindingCode::CidrHostBits,
+            format!("{location}: '{value}' has host bits set; did you mean '{network}/{prefix}'?"),
+        )
+    })
+}
+
+fn prefix_problem(value: &str, max: u8) -> Option<String> {
+    match value.parse::<u8>() {
+        Ok(n) if n <= max => None,
+        _ => Some(format!("is not a prefix length (0-{max})")),
+    }
+}
+
+fn mac_problem(value: &str) -> Option<String> {
+    let valid = |sep: char| {
+        let groups: Vec<&str> = value.split(sep).collect();
+        groups.len() == 6
+            && groups
+                .iter()
+                .all(|g| (1..=2).contains(&g.len()) && g.chars().all(|c| c.is_ascii_hexdigit()))
+    };
+    if valid(':') {
+        return None;
+    }
+    if valid('-') {
+        return Some(format!(
+            "is not a valid MAC address; use ':' separators ('{}')",
+            value.replace('-', ":")
+        ));
+    }
+    Some("is not a valid MAC address (expected six hex pairs like aa:bb:cc:dd:ee:ff)".into())
+}
+
+fn port_number(value: &str) -> Option<u16> {
+    value.parse::<u16>().ok().filter(|p| *p > 0)
+}
+
+fn port_problem(value: &str) -> Option<String> {
+    port_number(value)
+        .is_none()
+        .then(|| "is not a port (1-65535)".to_string())
+}
+
+fn port_range_problem(value: &str) -> Option<String> {
+    let Some((low, high)) = value.split_once(['-', ':']) else {
+        return port_problem(value);
+    };
+    match (port_number(low), port_number(high)) {
+        (Some(low), Some(high)) if low > high => Some(format!(
+            "is a reversed port range; did you mean '{high}-{low}'?"
+        )),
+        (Some(_), Some(_)) => None,
+        _ => Some("is not a port range (1-65535)".to_string()),
+    }
+}
+
+fn label_problem(label: &str) -> Option<String> {
+    let valid = (1..=63).contains(&label.len())
+        && label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !label.starts_with('-')
+        && !label.ends_with('-');
+    (!valid).then(|| "is not a valid hostname".to_string())
+}
+
+fn domain_problem(value: &str) -> Option<String> {
+    let valid = value.len() <= 253 && value.split('.').all(|l| label_problem(l).is_none());
+    (!valid).then(|| "is not a valid domain name".to_string())
+}
+
+fn network_address(addr: IpAddr, prefix: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(v4) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(v4) & mask))
+        }
+        IpAddr::V6(v6) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(v6) & mask))
+        }
+    }
+}
+
+/// Collect `(label, node)` for the nodes at `path`; `*` matches any tag.
+///
+/// Labels use the real tags, with a 1-based index for repeated siblings
+/// (`filter/rule[3]/destination`).
+pub(crate) fn nodes_at<'a>(
+    node: &'a XmlNode,
+    path: &[&str],
+    label: String,
+    out: &mut Vec<(String, &'a XmlNode)>,
+) {
+    let Some((first, rest)) = path.split_first() else {
+        out.push((label, node));
+        return;
+    };
+    let matches: Vec<&XmlNode> = node
+        .children
+        .iter()
+        .filter(|c| *first == "*" || c.tag == *first)
+        .collect();
+    let repeated = *first != "*" && matches.len() > 1;
+    for (idx, child) in matches.into_iter().enumerate() {
+        let segment = if repeated {
+            format!("{}[{}]", child.tag, idx + 1)
+        } else {
+            child.tag.clone()
+        };
+        let label = if label.is_empty() {
+            segment
+        } else {
+            format!("{label}/{segment}")
+        };
+        nodes_at(child, rest, label, out);
+    }
+}
+
+fn error(code: FindingCode, message: String) -> VerifyFinding {
+    VerifyFinding::error(code, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use xml_diff_core::parse;
+
+    use super::value_findings;
+
+    fn messages(xml: &[u8]) -> Vec<String> {
+        let root = parse(xml).expect("parse");
+        value_findings(&root)
+            .into_iter()
+            .map(|f| format!("{}: {}", f.code, f.message))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_well_formed_values_and_keywords() {
+        let found = messages(
+            br#"<pfsense><system><hostname>fw1</hostname><domain>example.com</domain><dnsserver>2001:db8::53</dnsserver><webgui><port>8443</port></webgui></system><interfaces><wan><ipaddr>dhcp</ipaddr><ipaddrv6>track6</ipaddrv6><spoofmac/></wan><lan><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet><spoofmac>00:11:22:aa:bb:cc</spoofmac></lan></interfaces><filter><rule><destination><port>web_ports</port></destination></rule><rule><destination><port>8000-8080</port></destination></rule></filter><staticroutes><route><network>10.1.0.0/16</network></route></staticroutes></pfsense>"#,
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn flags_malformed_values_with_hints() {
+        let found = messages(
+            br#"<pfsense><system><hostname>fw1.example.com</hostname></system><interfaces><lan><ipaddr>192.168.1.256</ipaddr><subnet>33</subnet><spoofmac>00-11-22-aa-bb-cc</spoofmac></lan><opt1><ipaddr>10,0,0,1</ipaddr></opt1></interfaces><filter><rule><destination><port>90-80</port></destination></rule></filter><staticroutes><route><network>10.1.2.3/16</network></route></staticroutes></pfsense>"#,
+        );
+        let expected = [
+            "hostname_has_domain: system/hostname: 'fw1.example.com' includes a domain",
+            "interfaces/lan/ipaddr: '192.168.1.256' is not a valid IPv4 address (octet 256 is out of range 0-255)",
+            "interfaces/opt1/ipaddr: '10,0,0,1' is not a valid IPv4 address; did you mean '10.0.0.1'?",
+            "interfaces/lan/subnet: '33' is not a prefix length (0-32)",
+            "use ':' separators ('00:11:22:aa:bb:cc')",
+            "filter/rule/destination/port: '90-80' is a reversed port range; did you mean '80-90'?",
+            "cidr_host_bits: staticroutes/route/network: '10.1.2.3/16' has host bits set; did you mean '10.1.0.0/16'?",
+        ];
+        for needle in expected {
+            assert!(
+                found.iter().any(|m| m.contains(needle)),
+                "missing {needle}: {found:?}"
+            );
+        }
+        assert_eq!(found.len(), expected.len(), "{found:?}");
+    }
+
+    #[test]
+    fn labels_repeated_siblings_by_index() {
+        let found = messages(
+            br#"<opnsense><staticroutes><route><network>10.0.0.0/8</network></route><route><network>10.0.0/8</network></route></staticroutes></opnsense>"#,
+        );
+        assert_eq!(
+            found,
+            vec![
+                "invalid_value: staticroutes/route[2]/network: '10.0.0/8' address '10.0.0' is not a valid IPv4 address (expected 4 octets, found 3)"
+            ]
+        );
+    }
+}
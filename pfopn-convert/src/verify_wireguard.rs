@@ -15,7 +15,8 @@
 //! - Explicit wireguard interface tag
 //! - Physical interface containing "wg" (tun_wg0, tun_wg1, etc.)
 
-use crate::verify_interfaces::{FindingSeverity, VerifyFinding};
+use crate::verify_codes::FindingCode;
+use crate::verify_interfaces::VerifyFinding;
 use xml_diff_core::XmlNode;
 
 /// Find WireGuard configuration problems.
@@ -44,13 +45,11 @@ pub fn wireguard_findings(root: &XmlNode) -> Vec<VerifyFinding> {
     }
 
     // Warning: WireGuard is enabled but has no interface assignment
-    vec![VerifyFinding {
-        severity: FindingSeverity::Warning,
-        code: "wireguard_missing_interface_assignment".to_string(),
-        message:
-            "WireGuard appears enabled but no wireguard/tun_wg* interface assignment was found"
-                .to_string(),
-    }]
+    vec![VerifyFinding::warning(
+        FindingCode::WireguardMissingInterfaceAssignment,
+        "WireGuard appears enabled but no wireguard/tun_wg* interface assignment was found"
+            .to_string(),
+    )]
 }
 
 /// Check if WireGuard configuration exists.
@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn batch_converts_manifest_devices_and_reports_failures() {
+    let dir = tempdir().expect("tempdir");
+    fs::copy(
+        fixture("fixtures/pfsense-base.xml"),
+        dir.path().join("branch.xml"),
+    )
+    .expect("copy");
+    fs::write(dir.path().join("broken.xml"), "<pfsense>").expect("write");
+    let manifest = dir.path().join("batch.toml");
+    fs::write(
+        &manifest,
+        format!(
+            r#"
+[defaults]
+to = "opnsense"
+target_file = "{}"
+output_dir = "out"
+
+[[device]]
+source = "branch.xml"
+lan_ip = "10.9.0.1"
+
+[[device]]
+name = "bad"
+source = "broken.xml"
+"#,
+            fixture("fixtures/opnsense-base.xml").display()
+        ),
+    )
+    .expect("write manifest");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .args(["batch", "--manifest"])
+        .arg(&manifest)
+        .args(["--jobs", "2"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "batch: 2 device(s), 1 succeeded, 1 failed",
+        ))
+        .stdout(predicate::str::contains("FAILED bad"))
+        .stderr(predicate::str::contains("1 of 2 device(s)"));
+
+    let converted = fs::read_to_string(dir.path().join("out/branch.xml")).expect("output");
+    assert!(converted.contains("<opnsense>"));
+    assert!(converted.contains("10.9.0.1"));
+}
+
+#[test]
+fn batch_converts_directory_as_json() {
+    let dir = tempdir().expect("tempdir");
+    let sources = dir.path().join("configs");
+    fs::create_dir(&sources).expect("mkdir");
+    fs::copy(fixture("fixtures/pfsense-base.xml"), sources.join("a.xml")).expect("copy");
+    fs::copy(
+        fixture("fixtures/pfsense-base-kea.xml"),
+        sources.join("b.xml"),
+    )
+    .expect("copy");
+    let out = dir.path().join("out");
+
+    let assert = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .args(["batch", "--dir"])
+        .arg(&sources)
+        .args(["--to", "opnsense", "--target-file"])
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--output-dir")
+        .arg(&out)
+        .args(["--format", "json"])
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(json["succeeded"], 2);
+    assert_eq!(json["entries"][0]["name"], "a");
+    assert_eq!(json["entries"][1]["name"], "b");
+    assert!(out.join("a.xml").exists());
+    assert!(out.join("b.xml").exists());
+}
+
+#[test]
+fn batch_requires_an_input() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .args(["batch", "--to", "opnsense"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--manifest"));
+}
+
+fn with_tunnel(peer: &str, key: &str, local: &str, remote: &str) -> String {
+    let base = fs::read_to_string(fixture("fixtures/pfsense-base.xml")).expect("fixture");
+    let tunnel = format!(
+        "<phase1><ikeid>1</ikeid><descr>site link</descr><remote-gateway>{peer}</remote-gateway>\
+         <authentication_method>pre_shared_key</authentication_method><pre-shared-key>{key}</pre-shared-key></phase1>\
+         <phase2><ikeid>1</ikeid><localid><type>network</type><address>{local}</address><netbits>24</netbits></localid>\
+         <remoteid><type>network</type><address>{remote}</address><netbits>24</netbits></remoteid></phase2>\
+         <vtimaps></vtimaps>"
+    );
+    base.replacen("<vtimaps></vtimaps>", &tunnel, 1)
+}
+
+#[test]
+fn batch_site_map_rewrites_peers_and_cross_checks_tunnels() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("hq.xml"),
+        with_tunnel("203.0.113.20", "s3cret", "10.0.0.0", "10.1.0.0"),
+    )
+    .expect("write");
+    fs::write(
+        dir.path().join("branch.xml"),
+        with_tunnel("hq.example.net", "other", "10.1.0.0", "10.0.0.0"),
+    )
+    .expect("write");
+    let manifest = dir.path().join("batch.toml");
+    fs::write(
+        &manifest,
+        format!(
+            r#"
+[defaults]
+to = "opnsense"
+target_file = "{}"
+output_dir = "out"
+
+[[device]]
+source = "hq.xml"
+
+[[device]]
+source = "branch.xml"
+"#,
+            fixture("fixtures/opnsense-base.xml").display()
+        ),
+    )
+    .expect("write manifest");
+    let sites = dir.path().join("sites.toml");
+    fs::write(
+        &sites,
+        r#"
+[[site]]
+name = "hq"
+addresses = ["203.0.113.10"]
+hostname = "hq.example.net"
+
+[[site]]
+name = "branch"
+addresses = ["203.0.113.20"]
+new_address = "198.51.100.20"
+"#,
+    )
+    .expect("write sites");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .args(["batch", "--manifest"])
+        .arg(&manifest)
+        .arg("--site-map")
+        .arg(&sites)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("2 succeeded"))
+        .stdout(predicate::str::contains(
+            "- [error] tunnel_pair_psk_mismatch: branch tunnel 'site link' and hq tunnel 'site link' use different pre-shared keys",
+        ))
+        .stdout(predicate::str::contains("tunnel_pair_network_mismatch").not())
+        .stderr(predicate::str::contains("1 tunnel cross-check error(s)"));
+
+    let hq = fs::read_to_string(dir.path().join("out/hq.xml")).expect("output");
+    assert!(hq.contains("<remote-gateway>198.51.100.20</remote-gateway>"));
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn capabilities_lists_transforms_as_json() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .args(["capabilities", "--from", "pfsense", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""id": "nat""#))
+        .stdout(predicate::str::contains(r#""direction": "both""#))
+        .stdout(predicate::str::contains("mvc-snapshots").not());
+}
+
+#[test]
+fn capabilities_classifies_config_sections() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("capabilities")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--to", "opnsense"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("out of scope for opnsense"))
+        .stdout(predicate::str::is_match(r"(?m)^filter\s+full\s+filter$").unwrap())
+        .stdout(predicate::str::is_match(r"(?m)^installedpackages/squid\s+out-of-scope$").unwrap());
+}
+
+#[test]
+fn capabilities_config_requires_target() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("capabilities")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .assert()
+        .failure();
+}
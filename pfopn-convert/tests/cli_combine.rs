@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+use xml_diff_core::parse_file;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn combine_with_itself_adds_nothing() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("combined.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("combine")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("aliases: added=0 merged=0"))
+        .stdout(predicate::str::contains("rules: added=0"))
+        .stdout(predicate::str::contains("collisions=0"));
+
+    let source = parse_file(&fixture("fixtures/pfsense-base.xml")).expect("parse source");
+    let combined = parse_file(&output).expect("parse output");
+    assert_eq!(
+        combined.get_child("filter").expect("filter").children.len(),
+        source.get_child("filter").expect("filter").children.len()
+    );
+}
+
+#[test]
+fn combine_reports_alias_collision_as_json() {
+    let dir = tempdir().expect("tempdir");
+    let base = dir.path().join("a.xml");
+    let other = dir.path().join("b.xml");
+    let output = dir.path().join("out.xml");
+    fs::write(
+        &base,
+        r#"<pfsense><aliases><alias><name>lab</name><type>host</type><address>10.0.0.1</address></alias></aliases></pfsense>"#,
+    )
+    .expect("write base");
+    fs::write(
+        &other,
+        r#"<pfsense><aliases><alias><name>lab</name><type>network</type><address>10.0.0.0/24</address></alias></aliases></pfsense>"#,
+    )
+    .expect("write other");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("combine")
+        .arg(&base)
+        .arg(&other)
+        .arg("--output")
+        .arg(&output)
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"collisions\": 1"))
+        .stdout(predicate::str::contains("alias 'lab'"));
+}
+
+#[test]
+fn combine_rejects_mixed_platforms() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("out.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("combine")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("both must be the same platform"));
+}
+
+#[test]
+fn combine_places_rules_after_anchor_description() {
+    let dir = tempdir().expect("tempdir");
+    let base = dir.path().join("a.xml");
+    let other = dir.path().join("b.xml");
+    let output = dir.path().join("out.xml");
+    fs::write(
+        &base,
+        r#"<pfsense><filter><rule><type>block</type><interface>lan</interface><descr>Block bogons</descr></rule><rule><type>pass</type><interface>lan</interface><descr>Default allow</descr></rule></filter></pfsense>"#,
+    )
+    .expect("write base");
+    fs::write(
+        &other,
+        r#"<pfsense><filter><rule><type>block</type><interface>lan</interface><protocol>tcp</protocol><descr>Block telnet</descr></rule></filter></pfsense>"#,
+    )
+    .expect("write other");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("combine")
+        .arg(&base)
+        .arg(&other)
+        .arg("--output")
+        .arg(&output)
+        .arg("--rule-placement")
+        .arg("after:Block bogons")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rules: added=1"));
+
+    let combined = parse_file(&output).expect("parse output");
+    let descrs: Vec<&str> = combined
+        .get_child("filter")
+        .expect("filter")
+        .get_children("rule")
+        .into_iter()
+        .filter_map(|rule| rule.get_text(&["descr"]))
+        .collect();
+    assert_eq!(descrs, ["Block bogons", "Block telnet", "Default allow"]);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("combine")
+        .arg(&base)
+        .arg(&other)
+        .arg("--output")
+        .arg(&output)
+        .arg("--rule-placement")
+        .arg("between")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid rule placement"));
+}
@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn compare_rules_accepts_fixture_conversion() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("converted.xml");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--to", "opnsense", "--target-file"])
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("compare-rules")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("equivalent=true"))
+        .stdout(predicate::str::contains("missing=0"));
+}
+
+#[test]
+fn compare_rules_fails_on_lost_rule() {
+    let dir = tempdir().expect("tempdir");
+    let source = dir.path().join("source.xml");
+    let converted = dir.path().join("converted.xml");
+    fs::write(
+        &source,
+        r#"<pfsense><filter>
+<rule><type>pass</type><interface>opt2</interface><tracker>7</tracker><descr>iot</descr><source><any/></source><destination><any/></destination></rule>
+<rule><type>pass</type><interface>lan</interface><source><any/></source><destination><any/></destination></rule>
+</filter></pfsense>"#,
+    )
+    .expect("write source");
+    fs::write(
+        &converted,
+        r#"<opnsense><filter>
+<rule><type>pass</type><interface>lan</interface><source><any/></source><destination><any/></destination></rule>
+</filter></opnsense>"#,
+    )
+    .expect("write converted");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("compare-rules")
+        .arg(&source)
+        .arg(&converted)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("missing in converted:"))
+        .stdout(predicate::str::contains("- #1 (tracker 7) \"iot\""))
+        .stderr(predicate::str::contains(
+            "1 source rule(s) have no equivalent",
+        ));
+}
@@ -92,6 +92,56 @@ fn convert_allows_minimal_template_with_explicit_flag() {
         .stderr(predicate::str::contains("interface preflight failed"));
 }
 
+#[test]
+fn convert_generates_baseline_from_target_version() {
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("converted.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-version")
+        .arg("26.1.2")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "generated opnsense 26.1 baseline; interface devices taken from source",
+        ));
+
+    let converted = parse(&fs::read(&output_path).expect("read output")).expect("parse output");
+    assert_eq!(
+        converted.get_text(&["interfaces", "wan", "if"]),
+        Some("igb2")
+    );
+    assert!(converted
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("Kea"))
+        .is_some());
+}
+
+#[test]
+fn convert_rejects_unknown_target_version() {
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("converted.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("pfsense")
+        .arg("--target-version")
+        .arg("2.6.0")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("available: 2.7, 2.8, plus"));
+}
+
 #[test]
 fn convert_rejects_output_overwriting_input() {
     let input = fixture("fixtures/pfsense-base.xml");
@@ -111,3 +161,778 @@ fn convert_rejects_output_overwriting_input() {
             "refusing to overwrite source file",
         ));
 }
+
+#[test]
+fn convert_prune_orphans_lists_removed_objects() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense>
+            <interfaces><lan><if>em0</if></lan></interfaces>
+            <aliases>
+              <alias><name>USED</name><address>10.0.0.1</address></alias>
+              <alias><name>STALE</name><address>10.0.0.2</address></alias>
+            </aliases>
+            <filter><rule><interface>lan</interface><destination><address>USED</address></destination></rule></filter>
+        </pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--prune-orphans")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pruned_orphans=1"))
+        .stdout(predicate::str::contains("- alias STALE"));
+
+    let converted = fs::read_to_string(&output_path).expect("converted file");
+    assert!(converted.contains("USED"));
+    assert!(!converted.contains("STALE"));
+}
+
+#[test]
+fn convert_progress_reports_stage_timings() {
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("converted.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--progress")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("progress: parse source"))
+        .stderr(predicate::str::contains("progress: diff"))
+        .stderr(predicate::str::contains("progress: merge/aliases"))
+        .stderr(predicate::str::contains("progress: dhcp migration"))
+        .stderr(predicate::str::contains("progress: write"))
+        .stderr(predicate::str::contains("progress: done in"));
+}
+
+#[test]
+fn convert_strict_fails_on_untranslated_sections_without_writing() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em0</if></lan></interfaces><hasync><pfsyncenabled>on</pfsyncenabled></hasync><unbound><enable/></unbound></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("strict: 2 untranslated item(s)"))
+        .stderr(predicate::str::contains(
+            "section 'unbound' dropped: opnsense cannot hold it",
+        ))
+        .stderr(predicate::str::contains(
+            "section 'hasync' copied verbatim without translation",
+        ));
+
+    assert!(!output_path.exists());
+}
+
+#[test]
+fn convert_strict_passes_complete_conversion() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em0</if></lan></interfaces></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--strict")
+        .assert()
+        .success();
+
+    assert!(output_path.exists());
+}
+
+#[test]
+fn convert_reports_small_package_manual_steps_once_in_strict_mode() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em0</if></lan></interfaces><installedpackages><shellcmdsettings><config><cmd>/root/boot.sh</cmd><cmdtype>shellcmd</cmdtype></config></shellcmdsettings></installedpackages></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("strict: 1 untranslated item(s)"))
+        .stderr(predicate::str::contains(
+            "Shellcmd -> configd boot hooks: run '/root/boot.sh' from a script in /usr/local/etc/rc.syshook.d/start/",
+        ))
+        .stderr(predicate::str::contains("package 'shellcmdsettings' dropped").not());
+}
+
+#[test]
+fn convert_renders_summary_through_user_template() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+    let template = dir.path().join("summary.j2");
+
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em0</if></lan></interfaces></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+    fs::write(
+        &template,
+        "{{ report.from }} -> {{ report.to }}: {{ report.summary.interfaces }} interface(s)\n",
+    )
+    .expect("template write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--template")
+        .arg(path_as_str(&template))
+        .assert()
+        .success()
+        .stdout("pfsense -> opnsense: 1 interface(s)\n");
+    assert!(output_path.exists());
+}
+
+#[test]
+fn convert_repairs_latin1_and_double_encoded_descriptions() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+
+    fs::write(
+        &input,
+        b"<pfsense><interfaces><lan><if>em0</if><descr>B\xfcro</descr></lan></interfaces><filter><rule><interface>lan</interface><descr>R&amp;amp;D</descr></rule></filter></pfsense>",
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("encoding_repairs=2"))
+        .stdout(predicate::str::contains(
+            "- pfsense.interfaces[1].lan[1].descr[1]: text has 1 byte(s) that are not valid UTF-8; read them as Windows-1252",
+        ))
+        .stdout(predicate::str::contains(
+            "- pfsense.filter[1].rule[1].descr[1]: decoded double-encoded entities in 'R&amp;D'",
+        ));
+
+    let output = fs::read_to_string(&output_path).expect("output is UTF-8");
+    assert!(output.contains("<descr>B\u{fc}ro</descr>"));
+    assert!(output.contains("<descr>R&amp;D</descr>"));
+    assert!(!output.contains("&amp;amp;"));
+}
+
+#[test]
+fn convert_keeps_multi_wan_policy_routing_consistent() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><system/><interfaces><wan><if>em0</if><ipaddr>dhcp</ipaddr></wan><lan><if>em1</if></lan><opt1><if>em2</if><ipaddr>dhcp</ipaddr></opt1></interfaces><gateways><gateway_item><interface>opt1</interface><name>WAN2_GW</name><gateway>198.51.100.1</gateway><ipprotocol>inet</ipprotocol></gateway_item><gateway_group><name>WAN-Failover</name><item>WAN_DHCP|1|address</item><item>WAN2_GW|2|address</item><trigger>down</trigger></gateway_group><defaultgw4>WAN-Failover</defaultgw4></gateways><filter><rule><type>pass</type><interface>lan</interface><gateway>WAN-Failover</gateway><descr>Policy route LAN</descr></rule></filter></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><system/><interfaces><wan><if>em0</if></wan><lan><if>em1</if></lan><opt1><if>em2</if></opt1></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .assert()
+        .success();
+
+    let output = parse(&fs::read(&output_path).expect("read output")).expect("parse output");
+    assert_eq!(
+        output.get_text(&["gateways", "gateway_group", "name"]),
+        Some("WAN_Failover")
+    );
+    assert_eq!(
+        output.get_text(&["filter", "rule", "gateway"]),
+        Some("WAN_Failover")
+    );
+    assert_eq!(
+        output.get_text(&["gateways", "defaultgw4"]),
+        Some("WAN_DHCP")
+    );
+    assert_eq!(output.get_text(&["system", "gw_switch_default"]), Some("1"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&output_path))
+        .assert()
+        .stdout(predicate::str::contains("missing_gateway").not());
+}
+
+#[test]
+fn convert_folds_duplicate_ca_onto_target_copy() {
+    use base64::Engine;
+
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+    let pem = fs::read(fixture("fixtures/certs/ca.crt")).expect("read ca");
+    let ca = base64::engine::general_purpose::STANDARD.encode(pem);
+
+    fs::write(
+        &input,
+        format!(
+            "<pfsense><interfaces><lan><if>em0</if></lan></interfaces>\
+             <ca><refid>ca-src</refid><descr>Home CA</descr><crt>{ca}</crt></ca>\
+             <openvpn><openvpn-server><vpnid>1</vpnid><caref>ca-src</caref></openvpn-server></openvpn></pfsense>"
+        ),
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        format!(
+            "<opnsense><interfaces><lan><if>em0</if></lan></interfaces>\
+             <ca><refid>ca-dst</refid><descr>Home CA</descr><crt>{ca}</crt></ca></opnsense>"
+        ),
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cert_remaps=1"))
+        .stdout(predicate::str::contains("- ca ca-src -> ca-dst (Home CA)"));
+
+    let output = fs::read_to_string(&output_path).expect("read output");
+    assert!(!output.contains("ca-src"));
+    assert_eq!(output.matches("<refid>ca-dst</refid>").count(), 1);
+}
+
+#[test]
+fn convert_lists_pruned_nodes_and_keep_unknown_preserves_them() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em0</if></lan></interfaces>
+            <installedpackages><package><name>snort</name></package><snortglobal><rule>KEEPME</rule></snortglobal></installedpackages>
+        </pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let convert = |keep_unknown: bool| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+        cmd.arg("convert")
+            .arg(path_as_str(&input))
+            .arg("--output")
+            .arg(path_as_str(&output_path))
+            .arg("--to")
+            .arg("opnsense")
+            .arg("--target-file")
+            .arg(path_as_str(&target));
+        if keep_unknown {
+            cmd.arg("--keep-unknown");
+        }
+        cmd
+    };
+
+    convert(false)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pruned=2"))
+        .stdout(predicate::str::contains(
+            "- installedpackages.package [carried]",
+        ))
+        .stdout(predicate::str::contains(
+            "- installedpackages.snortglobal [dropped]: opnsense cannot hold this package config",
+        ));
+    let converted = fs::read_to_string(&output_path).expect("converted file");
+    assert!(!converted.contains("KEEPME"));
+
+    convert(true)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "a copy is kept under pfopn_preserved.installedpackages.snortglobal",
+        ));
+    let converted = parse(&fs::read(&output_path).expect("converted file")).expect("parse");
+    assert_eq!(
+        converted.get_text(&[
+            "pfopn_preserved",
+            "installedpackages",
+            "snortglobal",
+            "rule"
+        ]),
+        Some("KEEPME")
+    );
+    assert!(converted.get_child("installedpackages").is_none());
+}
+
+#[test]
+fn convert_strips_private_keys_and_verify_accepts_them_missing() {
+    use base64::Engine;
+
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+    let encode = |name: &str| {
+        let pem = fs::read(fixture(&format!("fixtures/certs/{name}"))).expect("read fixture");
+        base64::engine::general_purpose::STANDARD.encode(pem)
+    };
+    let (crt, prv) = (encode("leaf.crt"), encode("leaf.key"));
+
+    fs::write(
+        &input,
+        format!(
+            "<pfsense><interfaces><lan><if>em0</if></lan></interfaces>\
+             <cert><refid>c1</refid><descr>VPN</descr><crt>{crt}</crt><prv>{prv}</prv></cert>\
+             <openvpn><openvpn-server><vpnid>1</vpnid><certref>c1</certref></openvpn-server></openvpn></pfsense>"
+        ),
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--strip-private-keys")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "removed private keys from 1 certificate(s)/CA(s)",
+        ));
+
+    let output = parse(&fs::read(&output_path).expect("read output")).expect("parse");
+    let cert = output.get_child("cert").expect("cert kept");
+    assert!(cert.get_child("prv").is_none());
+    assert_eq!(cert.get_text(&["crt"]), Some(crt.as_str()));
+
+    let mut verify = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    verify
+        .arg("verify")
+        .arg(path_as_str(&output_path))
+        .assert()
+        .stdout(predicate::str::contains("[warning] cert_missing_key"));
+
+    let mut verify = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    verify
+        .arg("verify")
+        .arg(path_as_str(&output_path))
+        .arg("--keys-stripped")
+        .assert()
+        .stdout(predicate::str::contains("[info] cert_missing_key"))
+        .stdout(predicate::str::contains("[warning] cert_missing_key").not());
+}
+
+#[test]
+fn convert_maps_acme_package_to_acme_client() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em0</if></lan></interfaces>
+            <system><webgui><protocol>https</protocol><ssl-certref>acme1</ssl-certref></webgui></system>
+            <cert><refid>acme1</refid><descr>gui</descr><crt>C</crt></cert>
+            <installedpackages><acme>
+                <accountkeys><item><name>LE</name><acmeserver>letsencrypt-staging-2</acmeserver></item></accountkeys>
+                <certificates><item><name>gui</name><status>active</status><acmeaccount>LE</acmeaccount>
+                    <a_domainlist><item><name>fw.example.com</name><method>standalone</method></item></a_domainlist>
+                </item></certificates>
+            </acme></installedpackages>
+        </pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "- installedpackages.acme [carried]",
+        ))
+        .stderr(predicate::str::contains("install os-acme-client"));
+
+    let output = parse(&fs::read(&output_path).expect("read output")).expect("parse");
+    let model = output
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("AcmeClient"))
+        .expect("acme model");
+    assert_eq!(
+        model.get_text(&["accounts", "account", "ca"]),
+        Some("letsencrypt_test")
+    );
+    assert_eq!(
+        model.get_text(&["certificates", "certificate", "certRefId"]),
+        Some("acme1")
+    );
+    assert_eq!(
+        output.get_text(&["system", "webgui", "ssl-certref"]),
+        Some("acme1")
+    );
+
+    let mut verify = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    verify
+        .arg("verify")
+        .arg(path_as_str(&output_path))
+        .assert()
+        .stdout(predicate::str::contains("uuid_ref").not());
+}
+
+#[test]
+fn convert_stamps_revision_and_optional_provenance_marker() {
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("converted.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.env("SOURCE_DATE_EPOCH", "1767225600")
+        .arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--backend")
+        .arg("isc")
+        .arg("--revision-user")
+        .arg("migration@example.com")
+        .arg("--provenance")
+        .assert()
+        .success();
+
+    let converted = fs::read(&output_path).expect("converted file");
+    let root = parse(&converted).expect("parse output");
+    assert_eq!(root.get_children("revision").len(), 1);
+    assert_eq!(root.get_text(&["revision", "time"]), Some("1767225600"));
+    assert!(root
+        .get_text(&["revision", "description"])
+        .is_some_and(
+            |d| d.starts_with("converted by pfopn-convert v") && d.contains("from pfsense")
+        ));
+    assert_eq!(
+        root.get_text(&["revision", "username"]),
+        Some("migration@example.com")
+    );
+    let flags: Vec<&str> = root
+        .get_child("pfopn_provenance")
+        .expect("marker")
+        .get_children("flag")
+        .into_iter()
+        .filter_map(|f| f.text.as_deref())
+        .collect();
+    assert_eq!(
+        flags,
+        [
+            "--to opnsense",
+            "--target-file opnsense-base.xml",
+            "--backend isc"
+        ]
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--no-revision")
+        .assert()
+        .success();
+    let root = parse(&fs::read(&output_path).expect("converted file")).expect("parse output");
+    assert_eq!(
+        root.get_text(&["revision", "description"]),
+        Some("/api/routes/routes/addroute/ made changes")
+    );
+    assert!(root.get_child("pfopn_provenance").is_none());
+}
+
+#[test]
+fn convert_upgrades_m0n0wall_config_before_converting() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("m0n0wall.xml");
+    let output_path = dir.path().join("converted.xml");
+    fs::write(
+        &input,
+        r#"<m0n0wall>
+  <version>1.8</version>
+  <system><hostname>fw</hostname><domain>example.lan</domain></system>
+  <interfaces>
+    <wan><if>igb0</if><ipaddr>203.0.113.2</ipaddr><subnet>29</subnet><gateway>203.0.113.1</gateway></wan>
+    <lan><if>igb1</if><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan>
+  </interfaces>
+  <nat>
+    <rule><interface>wan</interface><protocol>tcp</protocol><external-port>443</external-port><target>192.168.1.10</target><local-port>443</local-port></rule>
+  </nat>
+</m0n0wall>"#,
+    )
+    .expect("src write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "legacy_upgrades=3 from_version=1.8",
+        ))
+        .stdout(predicate::str::contains(
+            "- interfaces/wan/gateway: address 203.0.113.1 became gateway WANGW",
+        ));
+
+    let converted = parse(&fs::read(&output_path).expect("read output")).expect("parse output");
+    assert_eq!(converted.tag, "opnsense");
+    assert_eq!(
+        converted.get_text(&["nat", "rule", "destination", "port"]),
+        Some("443")
+    );
+}
+
+#[test]
+fn convert_rejects_config_too_old_to_upgrade() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("m0n0wall.xml");
+    fs::write(&input, "<m0n0wall><version>1.2</version></m0n0wall>").expect("src write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&dir.path().join("converted.xml")))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "config version 1.2 is too old to convert",
+        ));
+}
+
+#[test]
+fn convert_prints_section_deltas_against_source_and_baseline() {
+    let dir = tempdir().expect("tempdir");
+    let output_path = dir.path().join("converted.xml");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("section_deltas"))
+        .stdout(predicate::str::contains(
+            "- aliases:           source    6  baseline    0 -> output    6 (unchanged)",
+        ))
+        .stdout(predicate::str::contains(
+            "- certificates:      source    6  baseline    2 -> output    7 (1 added)",
+        ))
+        .stdout(predicate::str::contains("dhcp static maps:"));
+}
+
+#[test]
+fn convert_keeps_pfsense_flags_with_zero_values_on() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output_path = dir.path().join("converted.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em0</if></lan></interfaces><filter><rule><interface>lan</interface><descr>off</descr><disabled>0</disabled></rule><rule><interface>lan</interface><descr>on</descr></rule></filter></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><if>em0</if></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output_path))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .assert()
+        .success();
+
+    let output = parse(&fs::read(&output_path).expect("output")).expect("parse output");
+    let rules = output
+        .get_child("filter")
+        .map(|f| f.get_children("rule"))
+        .expect("rules");
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].get_text(&["disabled"]), Some("1"));
+    assert!(rules[1].get_child("disabled").is_none());
+}
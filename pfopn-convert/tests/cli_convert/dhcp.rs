@@ -142,6 +142,111 @@ fn convert_lan_ip_allows_lan_subnet_mismatch_without_global_override() {
         .success();
 }
 
+#[test]
+fn convert_lan_ip_rewrites_or_reports_old_subnet_literals() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output = dir.path().join("out.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><ipaddr>10.1.10.1</ipaddr><subnet>24</subnet></lan></interfaces><filter><rule><type>pass</type><interface>lan</interface><destination><address>10.1.10.5</address></destination></rule></filter></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><ipaddr>10.1.10.1</ipaddr><subnet>24</subnet></lan></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let convert = |literals: &str| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+        cmd.arg("convert")
+            .arg(path_as_str(&input))
+            .arg("--output")
+            .arg(path_as_str(&output))
+            .arg("--to")
+            .arg("opnsense")
+            .arg("--target-file")
+            .arg(path_as_str(&target))
+            .arg("--lan-ip")
+            .arg("192.168.1.1")
+            .arg("--lan-literals")
+            .arg(literals)
+            .assert()
+            .success()
+    };
+
+    convert("rewrite").stderr(predicate::str::contains(
+        "filter/rule/destination/address: renumbered 10.1.10.5 to 192.168.1.5",
+    ));
+    let out = fs::read_to_string(&output).expect("read output");
+    assert!(out.contains("<address>192.168.1.5</address>"));
+
+    convert("review").stderr(predicate::str::contains(
+        "warning: filter/rule/destination/address: '10.1.10.5' overlaps the old LAN subnet",
+    ));
+    let out = fs::read_to_string(&output).expect("read output");
+    assert!(out.contains("<address>10.1.10.5</address>"));
+}
+
+#[test]
+fn convert_renumber_moves_subnets_and_rejects_collisions() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output = dir.path().join("out.xml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><ipaddr>10.1.10.1</ipaddr><subnet>24</subnet></lan><opt1><ipaddr>10.2.0.1</ipaddr><subnet>24</subnet></opt1></interfaces><staticroutes><route><network>172.16.0.0/16</network><gateway>10.2.0.254</gateway></route></staticroutes></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><interfaces><lan><ipaddr>10.1.10.1</ipaddr><subnet>24</subnet></lan><opt1/></interfaces></opnsense>"#,
+    )
+    .expect("dst write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--renumber")
+        .arg("10.2.0.0/24=192.168.2.0/24")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "staticroutes/route/gateway: renumbered 10.2.0.254 to 192.168.2.254",
+        ));
+    let out = fs::read_to_string(&output).expect("read output");
+    assert!(out.contains("<gateway>192.168.2.254</gateway>"));
+    assert!(out.contains("<ipaddr>10.1.10.1</ipaddr>"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--renumber")
+        .arg("10.2.0.0/24=10.1.10.0/24")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--renumber 10.2.0.0/24=10.1.10.0/24 conflicts with existing interface lan",
+        ));
+}
+
 #[test]
 fn convert_lan_ip_fails_on_interface_conflict() {
     let dir = tempdir().expect("tempdir");
@@ -207,7 +312,7 @@ fn convert_auto_backend_prefers_kea_for_opnsense_26_and_migrates_reservation() {
         .success();
 
     let out = fs::read_to_string(&output).expect("read out");
-    assert!(out.contains("<reservation>"));
+    assert!(out.contains("<reservation uuid=\""));
     assert!(out.contains("<ip_address>192.168.1.25</ip_address>"));
     // v4 ISC section should be removed after Kea migration
     assert!(
@@ -602,3 +707,62 @@ fn convert_kea_pfsense_removes_isc_sections() {
         "dhcpbackend should be set to kea"
     );
 }
+
+#[test]
+fn convert_static_map_dns_materializes_unbound_host_overrides() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("out.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--static-map-dns")
+        .arg("materialize")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Unbound host override(s)"));
+
+    let root = parse(fs::read(&output).expect("read out").as_slice()).expect("parse out");
+    let hosts = root
+        .get_child("OPNsense")
+        .and_then(|o| o.get_child("unboundplus"))
+        .and_then(|u| u.get_child("hosts"))
+        .expect("unbound hosts");
+    assert!(hosts.get_children("host").iter().any(|h| {
+        h.get_text(&["hostname"]) == Some("host")
+            && h.get_text(&["domain"]) == Some("example.com")
+            && h.get_text(&["server"]) == Some("192.168.1.248")
+    }));
+}
+
+#[test]
+fn convert_static_map_dns_verify_leaves_output_unchanged() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("out.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(path_as_str(&output))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--static-map-dns")
+        .arg("verify")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "static mapping host.example.com -> 192.168.1.248 has no Unbound host override",
+        ));
+
+    let out = fs::read_to_string(&output).expect("read out");
+    assert!(!out.contains("<server>192.168.1.248</server>"));
+}
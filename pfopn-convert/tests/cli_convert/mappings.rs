@@ -579,3 +579,71 @@ fn convert_testdata_pfsense_to_opnsense_maps_openvpn_instances() {
     assert!(!out.contains("<ovpns1>"));
     assert!(out.contains("<openvpn-server>"));
 }
+
+#[test]
+fn convert_policy_keeps_target_filter_rules() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output = dir.path().join("out.xml");
+    let policy = dir.path().join("policy.toml");
+
+    fs::write(
+        &input,
+        r#"<pfsense><system/><interfaces><lan/></interfaces><filter><rule><descr>from source</descr></rule></filter></pfsense>"#,
+    )
+    .expect("src write");
+    fs::write(
+        &target,
+        r#"<opnsense><system/><interfaces><lan/></interfaces><filter><rule><descr>target default</descr></rule></filter></opnsense>"#,
+    )
+    .expect("dst write");
+    fs::write(&policy, "[sections]\nfilter = \"force-target-default\"\n").expect("policy write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--policy")
+        .arg(path_as_str(&policy))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("<filter> taken from target baseline"));
+
+    let out = fs::read_to_string(&output).expect("read out");
+    assert!(out.contains("target default"));
+    assert!(!out.contains("from source"));
+}
+
+#[test]
+fn convert_rejects_invalid_policy_file() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("src.xml");
+    let target = dir.path().join("dst.xml");
+    let output = dir.path().join("out.xml");
+    let policy = dir.path().join("policy.toml");
+
+    fs::write(&input, r#"<pfsense><system/></pfsense>"#).expect("src write");
+    fs::write(&target, r#"<opnsense><system/></opnsense>"#).expect("dst write");
+    fs::write(&policy, "[sections]\nfilter = \"merge\"\n").expect("policy write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&input))
+        .arg("--output")
+        .arg(path_as_str(&output))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(path_as_str(&target))
+        .arg("--policy")
+        .arg(path_as_str(&policy))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to parse conversion policy"));
+}
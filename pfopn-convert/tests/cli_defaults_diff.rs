@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::Value;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn defaults_diff_summarizes_changes_per_section() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("defaults-diff")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "defaults_diff platform=pfsense baseline=2.7",
+        ))
+        .stdout(predicate::str::contains(
+            "baseline_reason: config version 23.3 matches the 2.7 template",
+        ))
+        .stdout(predicate::str::contains("- filter added="))
+        .stdout(predicate::str::contains("revision").not())
+        .stdout(predicate::str::contains("pfsense.system[1].hostname[1]").not());
+}
+
+#[test]
+fn defaults_diff_json_filters_to_one_section() {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("defaults-diff")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .args([
+            "--section",
+            "system",
+            "--release",
+            "25.7",
+            "--format",
+            "json",
+        ])
+        .output()
+        .expect("defaults-diff output");
+    assert!(output.status.success(), "defaults-diff should succeed");
+
+    let report: Value = serde_json::from_slice(&output.stdout).expect("json parse");
+    assert_eq!(report["baseline"], "25.7");
+    let sections = report["sections"].as_array().expect("sections");
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0]["section"], "system");
+    assert!(!report["entries"].as_array().expect("entries").is_empty());
+}
+
+#[test]
+fn defaults_diff_rejects_unknown_release() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("defaults-diff")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--release", "2.4"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no embedded pfsense defaults for release '2.4'; expected one of 2.7, 2.8, plus",
+        ));
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn dhcp_plan_lists_each_interface_per_backend() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("dhcp-plan")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-file")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "--backend kea (uses kea):\n  lan v4: migrated\n  lan v6: legacy\n    - DHCPv6 range on lan but unable to determine IPv6 prefix",
+        ))
+        .stdout(predicate::str::contains(
+            "--backend isc (uses isc):\n  lan v4: legacy\n  lan v6: legacy",
+        ));
+}
+
+#[test]
+fn dhcp_plan_reports_unavailable_backends() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    let output = cmd
+        .arg("dhcp-plan")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-version")
+        .arg("26.1")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let plan: serde_json::Value = serde_json::from_slice(&output).expect("json");
+    let isc = &plan["backends"][2];
+    assert_eq!(isc["requested"], "isc");
+    assert!(isc["error"]
+        .as_str()
+        .is_some_and(|e| e.contains("os-isc-dhcp")));
+    assert_eq!(plan["backends"][1]["scopes"][0]["outcome"], "migrated");
+}
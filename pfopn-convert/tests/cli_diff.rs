@@ -201,3 +201,372 @@ fn diff_rejects_output_overwriting_input() {
 fn path_as_str(path: &Path) -> &str {
     path.to_str().expect("path should be valid utf-8")
 }
+
+#[test]
+fn diff_ignore_accepts_path_query() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("diff")
+        .arg(fixture("fixtures/simple_a.xml"))
+        .arg(fixture("fixtures/simple_b.xml"))
+        .arg("--ignore")
+        .arg("settings")
+        .arg("--ignore")
+        .arg("items.item[value='Beta']")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("item[2]").not())
+        .stdout(predicate::str::contains("item[3]"));
+}
+
+#[test]
+fn diff_ignore_falls_back_to_literal_when_not_a_query() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("diff")
+        .arg(fixture("fixtures/simple_a.xml"))
+        .arg(fixture("fixtures/simple_b.xml"))
+        .arg("--ignore")
+        .arg("items/item[")
+        .arg("--ignore")
+        .arg("settings")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("settings").not())
+        .stdout(predicate::str::contains("item[3]"));
+}
+
+#[test]
+fn diff_ignore_category_hides_revision_metadata() {
+    let dir = tempdir().expect("tempdir");
+    let left = dir.path().join("left.xml");
+    let right = dir.path().join("right.xml");
+    fs::write(
+        &left,
+        "<pfsense><revision><time>1</time><username>admin</username></revision><filter><rule><tracker>1</tracker><descr>a</descr><updated><time>1</time><username>admin</username></updated></rule></filter><system><hostname>fw</hostname></system></pfsense>",
+    )
+    .expect("left write");
+    fs::write(
+        &right,
+        "<pfsense><revision><time>2</time><username>ops</username></revision><filter><rule><tracker>1</tracker><descr>a</descr><updated><time>2</time><username>ops</username></updated></rule></filter><system><hostname>fw2</hostname></system></pfsense>",
+    )
+    .expect("right write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .arg("--ignore-category")
+        .arg("metadata,dynamic-state")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hostname"))
+        .stdout(predicate::str::contains("revision").not())
+        .stdout(predicate::str::contains("updated").not());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .arg("--ignore-category")
+        .arg("timestamps")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown ignore category 'timestamps'; expected one of metadata, change-user",
+        ));
+}
+
+#[test]
+fn diff_ignore_category_change_user_hides_only_usernames() {
+    let dir = tempdir().expect("tempdir");
+    let left = dir.path().join("left.xml");
+    let right = dir.path().join("right.xml");
+    fs::write(
+        &left,
+        "<pfsense><revision><time>1</time><username>admin</username></revision><filter><rule><tracker>1</tracker><updated><time>1</time><username>admin</username></updated></rule></filter><system><user><name>admin</name></user></system></pfsense>",
+    )
+    .expect("left write");
+    fs::write(
+        &right,
+        "<pfsense><revision><time>2</time><username>ops</username></revision><filter><rule><tracker>1</tracker><updated><time>2</time><username>ops</username></updated></rule></filter><system><user><name>ops</name></user></system></pfsense>",
+    )
+    .expect("right write");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .args(["--ignore-category", "change-user"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("~ pfsense.revision[1].time[1]"))
+        .stdout(predicate::str::contains(
+            "~ pfsense.filter[1].rule[1].updated[1].time[1]",
+        ))
+        .stdout(predicate::str::contains(
+            "~ pfsense.system[1].user[1].name[1]",
+        ))
+        .stdout(predicate::str::contains("username").not());
+}
+
+#[test]
+fn diff_reports_model_version_bumps_as_ignorable_attribute_changes() {
+    let dir = tempdir().expect("tempdir");
+    let left = dir.path().join("left.xml");
+    let right = dir.path().join("right.xml");
+    fs::write(
+        &left,
+        r#"<opnsense><OPNsense><Kea><dhcp4 version="1.0.4"><general><enabled>0</enabled></general></dhcp4></Kea></OPNsense></opnsense>"#,
+    )
+    .expect("left write");
+    fs::write(
+        &right,
+        r#"<opnsense><OPNsense><Kea><dhcp4 version="1.0.5"><general><enabled>1</enabled></general></dhcp4></Kea></OPNsense></opnsense>"#,
+    )
+    .expect("right write");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"@ opnsense.OPNsense[1].Kea[1].dhcp4[1]@version: "1.0.4" -> "1.0.5""#,
+        ))
+        .stdout(predicate::str::contains(
+            "~ opnsense.OPNsense[1].Kea[1].dhcp4[1].general[1].enabled[1]",
+        ))
+        .stdout(predicate::str::contains("~ opnsense.OPNsense[1].Kea[1].dhcp4[1]\n").not());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .args(["--ignore-category", "model-versions", "--summary"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("modified=1"))
+        .stdout(predicate::str::contains("attributes=0"));
+}
+
+#[test]
+fn diff_strict_confidence_ignores_low_confidence_conflicts() {
+    let dir = tempdir().expect("tempdir");
+    let left = dir.path().join("left.xml");
+    let right = dir.path().join("right.xml");
+    let plan = dir.path().join("plan.json");
+    fs::write(
+        &left,
+        "<pfsense><system><hostname>fw</hostname></system><filter><rule><descr>old</descr></rule></filter></pfsense>",
+    )
+    .expect("left write");
+    fs::write(
+        &right,
+        "<pfsense><system><hostname>fw</hostname></system><filter><rule><descr>new</descr></rule></filter></pfsense>",
+    )
+    .expect("right write");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("manual conflicts detected"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .args(["--strict", "--strict-confidence", "50", "--plan"])
+        .arg(path_as_str(&plan))
+        .assert()
+        .success();
+    let plan: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&plan).expect("plan")).expect("plan json");
+    assert_eq!(plan[0]["confidence"], 30);
+    assert_eq!(
+        plan[0]["rationale"],
+        serde_json::json!(["value_differs", "descriptive_field"])
+    );
+}
+
+#[test]
+fn diff_reports_relocated_blocks_once() {
+    let dir = tempdir().expect("tempdir");
+    let left = dir.path().join("left.xml");
+    let right = dir.path().join("right.xml");
+    fs::write(
+        &left,
+        "<opnsense><cron><job><command>backup</command><minutes>5</minutes></job></cron><OPNsense/></opnsense>",
+    )
+    .expect("left write");
+    fs::write(
+        &right,
+        "<opnsense><cron/><OPNsense><cron><jobs><job><command>backup</command><minutes>5</minutes></job></jobs></cron></OPNsense></opnsense>",
+    )
+    .expect("right write");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "> opnsense.OPNsense[1].cron[1].jobs[1].job[1]: relocated from opnsense.cron[1].job[1]",
+        ))
+        .stdout(predicate::str::contains("- opnsense.cron[1].job[1]").not());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("diff")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("relocated=1"))
+        .stdout(predicate::str::contains("conflict_manual=0"));
+}
+
+#[test]
+fn diff_reports_moved_rules_separately_from_changes() {
+    let dir = tempdir().expect("tempdir");
+    let left_path = dir.path().join("left.xml");
+    let right_path = dir.path().join("right.xml");
+    let rules = |trackers: &[&str]| {
+        let body: String = trackers
+            .iter()
+            .map(|t| format!("<rule><tracker>{t}</tracker><descr>rule {t}</descr></rule>"))
+            .collect();
+        format!("<pfsense><filter>{body}</filter></pfsense>")
+    };
+    fs::write(&left_path, rules(&["1", "2", "3", "4", "5", "6"])).expect("left write");
+    fs::write(&right_path, rules(&["1", "2", "3", "4", "6", "5"])).expect("right write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("diff")
+        .arg(path_as_str(&left_path))
+        .arg(path_as_str(&right_path))
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "modified=0 only_left=0 only_right=0 structural=0 moved=1",
+        ));
+
+    let mut text = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    text.arg("diff")
+        .arg(path_as_str(&left_path))
+        .arg(path_as_str(&right_path))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "> pfsense.filter[1].rule[5]: moved 5 -> 6",
+        ))
+        .stdout(predicate::str::contains(
+            "reason=order changed: position 5 -> 6",
+        ));
+}
+
+#[test]
+fn diff_uses_key_fields_from_mappings_file() {
+    let dir = tempdir().expect("tempdir");
+    let left_path = dir.path().join("left.xml");
+    let right_path = dir.path().join("right.xml");
+    let mappings = dir.path().join("keys.toml");
+    let maps = |macs: &[&str]| {
+        let body: String = macs
+            .iter()
+            .map(|m| format!("<staticmap><mac>{m}</mac><descr>host {m}</descr></staticmap>"))
+            .collect();
+        format!("<pfsense><dhcpd><lan>{body}</lan></dhcpd></pfsense>")
+    };
+    fs::write(&left_path, maps(&["aa", "bb"])).expect("left write");
+    fs::write(&right_path, maps(&["bb", "aa"])).expect("right write");
+    fs::write(&mappings, "[key_fields]\nstaticmap = \"mac\"\n").expect("mappings write");
+
+    let mut positional = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    positional
+        .arg("diff")
+        .arg(path_as_str(&left_path))
+        .arg(path_as_str(&right_path))
+        .arg("--mappings-dir")
+        .arg(path_as_str(dir.path()))
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("modified=4"));
+
+    let mut keyed = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    keyed
+        .arg("diff")
+        .arg(path_as_str(&left_path))
+        .arg(path_as_str(&right_path))
+        .arg("--mappings-dir")
+        .arg(path_as_str(dir.path()))
+        .arg("--mappings-file")
+        .arg(path_as_str(&mappings))
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "modified=0 only_left=0 only_right=0",
+        ))
+        .stderr(predicate::str::contains("warning").not());
+
+    fs::write(&mappings, "[key_fields]\nstaticmap = \"ipaddr\"\n").expect("mappings write");
+    let mut missing = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    missing
+        .arg("diff")
+        .arg(path_as_str(&left_path))
+        .arg(path_as_str(&right_path))
+        .arg("--mappings-dir")
+        .arg(path_as_str(dir.path()))
+        .arg("--mappings-file")
+        .arg(path_as_str(&mappings))
+        .arg("--summary")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "key field 'ipaddr' is missing from 4 of 4 sampled <staticmap> entries",
+        ));
+}
+
+#[test]
+fn diff_compares_alias_members_as_sets() {
+    let dir = tempdir().expect("tempdir");
+    let left_path = dir.path().join("left.xml");
+    let right_path = dir.path().join("right.xml");
+    let alias = |address: &str, detail: &str| {
+        format!(
+            "<pfsense><aliases><alias><name>web</name><type>host</type>\
+             <address>{address}</address><detail>{detail}</detail></alias></aliases></pfsense>"
+        )
+    };
+    fs::write(&left_path, alias("10.0.0.1 10.0.0.2", "one||two")).expect("left write");
+    fs::write(&right_path, alias("10.0.0.2 10.0.0.1", "two||one")).expect("right write");
+
+    let mut reordered = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    reordered
+        .arg("diff")
+        .arg(path_as_str(&left_path))
+        .arg(path_as_str(&right_path))
+        .arg("--summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("modified=0"))
+        .stdout(predicate::str::contains("alias_member_changes").not());
+
+    fs::write(&right_path, alias("10.0.0.2 10.0.0.3", "two||three")).expect("right write");
+    let mut changed = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    changed
+        .arg("diff")
+        .arg(path_as_str(&left_path))
+        .arg(path_as_str(&right_path))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Alias Members"))
+        .stdout(predicate::str::contains("~ alias web: +10.0.0.3 -10.0.0.1"));
+}
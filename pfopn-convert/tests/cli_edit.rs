@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+use xml_diff_core::parse_file;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn edit_applies_set_add_delete_and_verifies() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("edited.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("edit")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(&output)
+        .arg("--set")
+        .arg("system.hostname")
+        .arg("edited-fw")
+        .arg("--add")
+        .arg("system")
+        .arg("<edit_marker>yes</edit_marker>")
+        .arg("--delete")
+        .arg("filter.rule[1]")
+        .arg("--verify")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("edited 3 node(s) with 3 op(s)"))
+        .stdout(predicate::str::contains("result errors=0"));
+
+    let source = parse_file(&fixture("fixtures/pfsense-base.xml")).expect("parse source");
+    let edited = parse_file(&output).expect("parse output");
+    assert_eq!(edited.get_text(&["system", "hostname"]), Some("edited-fw"));
+    assert_eq!(edited.get_text(&["system", "edit_marker"]), Some("yes"));
+    assert_eq!(
+        edited.get_child("filter").expect("filter").children.len() + 1,
+        source.get_child("filter").expect("filter").children.len()
+    );
+}
+
+#[test]
+fn edit_fails_when_path_matches_nothing() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("edited.xml");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("edit")
+        .arg(fixture("fixtures/simple_a.xml"))
+        .arg("--output")
+        .arg(&output)
+        .arg("--delete")
+        .arg("settings.missing")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("path not found: settings.missing"));
+    assert!(!output.exists());
+}
@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use xml_diff_core::parse_file;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+fn path_as_str(path: &Path) -> &str {
+    path.to_str().expect("utf-8 path")
+}
+
+#[test]
+fn export_then_import_returns_the_same_tree() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for (name, format) in [("config.yaml", "yaml"), ("config.json", "json")] {
+        for source in ["fixtures/pfsense-base.xml", "fixtures/opnsense-base.xml"] {
+            let doc = dir.path().join(name);
+            let xml = dir.path().join("config.xml");
+            Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+                .arg("export")
+                .arg(fixture(source))
+                .args(["--format", format, "-o", path_as_str(&doc)])
+                .assert()
+                .success();
+            Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+                .arg("import")
+                .arg(&doc)
+                .args(["-o", path_as_str(&xml)])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("imported"));
+
+            let original = parse_file(&fixture(source)).expect("parse source");
+            let restored = parse_file(&xml).expect("parse restored");
+            assert_eq!(restored, original, "{source} via {format}");
+        }
+    }
+}
+
+#[test]
+fn export_prints_yaml_to_stdout_by_default() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("export")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "pfopn_tree: 1\nroot:\n  tag: pfsense\n",
+        ));
+}
+
+#[test]
+fn import_rejects_unknown_fields() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let doc = dir.path().join("bad.json");
+    std::fs::write(
+        &doc,
+        r#"{"pfopn_tree": 1, "root": {"tag": "pfsense", "childs": []}}"#,
+    )
+    .expect("write");
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("import")
+        .arg(&doc)
+        .args(["-o", path_as_str(&dir.path().join("out.xml"))])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid JSON tree document"));
+}
@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+use xml_diff_core::parse_file;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn extract_openvpn_slice_passes_verify() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("openvpn.xml");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("extract")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--feature")
+        .arg("openvpn")
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("features: openvpn"))
+        .stdout(predicate::str::contains("missing: 0"));
+
+    let extracted = parse_file(&output).expect("parse output");
+    assert!(extracted.get_child("openvpn").is_some());
+    assert!(extracted.get_child("aliases").is_none());
+    assert!(extracted
+        .get_child("filter")
+        .expect("filter")
+        .children
+        .is_empty());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("verify")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("errors=0"));
+}
+
+#[test]
+fn extract_reports_missing_references_as_json() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("config.xml");
+    let output = dir.path().join("rules.xml");
+    fs::write(
+        &input,
+        r#"<pfsense><interfaces><lan><if>em1</if></lan></interfaces><filter><rule><interface>lan</interface><sched>office</sched></rule></filter></pfsense>"#,
+    )
+    .expect("write input");
+
+    let assert = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("extract")
+        .arg(&input)
+        .arg("--feature")
+        .arg("rules")
+        .arg("--output")
+        .arg(&output)
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success();
+    let report: serde_json::Value =
+        serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(report["features"], serde_json::json!(["rules"]));
+    assert_eq!(report["kept"]["interface"], 1);
+    assert_eq!(report["missing"], serde_json::json!(["schedule:office"]));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("extract")
+        .arg(&input)
+        .arg("--feature")
+        .arg("wireguard")
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown feature 'wireguard'"));
+}
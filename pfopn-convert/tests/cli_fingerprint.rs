@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn fingerprint_prints_section_hashes_as_json() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("fingerprint")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""platform": "pfsense""#))
+        .stdout(predicate::str::contains(r#""filter": ""#))
+        .stdout(predicate::str::contains(r#""revision""#).not());
+}
+
+#[test]
+fn diff_fingerprints_reports_changed_sections() {
+    let dir = tempdir().expect("tempdir");
+    let approved = dir.path().join("approved.json");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("fingerprint")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--output")
+        .arg(&approved)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("wrote"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .args(["diff", "--fingerprints"])
+        .arg(&approved)
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "fingerprint drift: 0 changed, 0 added, 0 removed",
+        ));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .args(["diff", "--fingerprints", "--strict"])
+        .arg(&approved)
+        .arg(fixture("fixtures/pfsense-base-kea.xml"))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("changed: filter"))
+        .stderr(predicate::str::contains("fingerprints differ"));
+}
@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn graph_prints_dot_by_default() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("graph")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("digraph config {"))
+        .stdout(predicate::str::contains(
+            r#""gateway:IPV4WAN" -> "interface:wan" [label="interface"];"#,
+        ));
+}
+
+#[test]
+fn graph_writes_json_to_output_file() {
+    let dir = tempdir().expect("tempdir");
+    let output = dir.path().join("graph.json");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("graph")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--format", "json", "--output"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("wrote"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output).expect("read")).expect("json");
+    let nodes = json["nodes"].as_array().expect("nodes");
+    assert!(nodes.iter().any(|n| n["id"] == "interface:lan"));
+    assert!(json["edges"].as_array().is_some_and(|e| !e.is_empty()));
+}
@@ -36,7 +36,8 @@ fn inspect_detect_identifies_platform() {
         .stdout(predicate::str::contains("version="))
         .stdout(predicate::str::contains("version_source="))
         .stdout(predicate::str::contains("version_confidence="))
-        .stdout(predicate::str::contains("dhcp_backend="));
+        .stdout(predicate::str::contains("dhcp_backend="))
+        .stdout(predicate::str::contains("edition=ce"));
 }
 
 #[test]
@@ -69,3 +70,46 @@ fn inspect_plugins_detects_tailscale_on_opnsense_fixture() {
             "- tailscale declared=false configured=true enabled=true",
         ));
 }
+
+#[test]
+fn inspect_section_accepts_path_query() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("inspect")
+        .arg(fixture("fixtures/simple_a.xml"))
+        .arg("--section")
+        .arg("items.item[@id='2']")
+        .arg("--depth")
+        .arg("1")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("item\n  value\n"))
+        .stdout(predicate::str::contains("settings").not());
+}
+
+#[test]
+fn inspect_grep_prints_matching_paths() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("inspect")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--grep")
+        .arg("lan")
+        .arg("--grep-section")
+        .arg("interfaces")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("interfaces.lan <lan>"))
+        .stdout(predicate::str::contains("matches="));
+}
+
+#[test]
+fn inspect_grep_rejects_invalid_regex() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("inspect")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--grep")
+        .arg("(unclosed")
+        .arg("--regex")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --grep pattern"));
+}
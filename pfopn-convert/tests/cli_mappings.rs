@@ -0,0 +1,114 @@
+use std::{fs, path::Path};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+fn path_as_str(path: &Path) -> &str {
+    path.to_str().expect("utf-8 path")
+}
+
+#[test]
+fn mappings_lint_passes_for_embedded_defaults() {
+    let dir = tempdir().expect("tempdir");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.env("PFOPN_CONVERT_MAPPINGS_DIR", path_as_str(dir.path()))
+        .arg("mappings")
+        .arg("lint")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from embedded; 0 finding(s)"));
+}
+
+#[test]
+fn mappings_lint_reports_unknown_sections_across_layers() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join("sections.toml"),
+        r#"
+[[mapping]]
+left = "gateways"
+right = ["Gateways", "gatewayz"]
+category = "network"
+note = "system override with a typo"
+"#,
+    )
+    .expect("write system mappings");
+    let user = dir.path().join("user.toml");
+    fs::write(
+        &user,
+        r#"
+[[mapping]]
+left = "legacy_thing"
+right = ["OPNsense"]
+category = "test"
+note = "user addition"
+"#,
+    )
+    .expect("write user mappings");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("mappings")
+        .arg("lint")
+        .arg("--mappings-dir")
+        .arg(path_as_str(dir.path()))
+        .arg("--mappings-file")
+        .arg(path_as_str(&user))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "[unknown_section] 'gateways' maps to 'gatewayz', which is not a known section",
+        ))
+        .stdout(predicate::str::contains(
+            "[unknown_section] left section 'legacy_thing' is not a known section",
+        ))
+        .stdout(predicate::str::contains("2 finding(s)"));
+}
+
+#[test]
+fn sections_layers_user_file_over_mappings_dir() {
+    let dir = tempdir().expect("tempdir");
+    let left = dir.path().join("left.xml");
+    let right = dir.path().join("right.xml");
+    fs::write(&left, "<pfsense><foo/><qux/></pfsense>").expect("left write");
+    fs::write(&right, "<opnsense><bar/><quux/></opnsense>").expect("right write");
+    fs::write(
+        dir.path().join("sections.toml"),
+        r#"
+[[mapping]]
+left = "foo"
+right = ["bar"]
+category = "test"
+note = "from system dir"
+"#,
+    )
+    .expect("write system mappings");
+    let user = dir.path().join("user.toml");
+    fs::write(
+        &user,
+        r#"
+[[mapping]]
+left = "qux"
+right = ["quux"]
+category = "test"
+note = "from user file"
+"#,
+    )
+    .expect("write user mappings");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("sections")
+        .arg(path_as_str(&left))
+        .arg(path_as_str(&right))
+        .arg("--format")
+        .arg("json")
+        .arg("--mappings-dir")
+        .arg(path_as_str(dir.path()))
+        .arg("--mappings-file")
+        .arg(path_as_str(&user))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from system dir"))
+        .stdout(predicate::str::contains("from user file"))
+        .stdout(predicate::str::contains("+file:"));
+}
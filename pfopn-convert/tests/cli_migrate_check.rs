@@ -99,3 +99,26 @@ fn migrate_check_fails_on_dhcp_backend_inconsistency() {
         .stdout(predicate::str::contains("[FAIL] dhcp_integrity"))
         .stderr(predicate::str::contains("migrate-check failed"));
 }
+
+#[test]
+fn migrate_check_markdown_includes_readiness_matrix() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("cfg.xml");
+    fs::write(
+        &input,
+        r#"<pfsense><system/><interfaces><lan/></interfaces><filter/><shaper><queue><name>qWAN</name></queue></shaper></pfsense>"#,
+    )
+    .expect("write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("migrate-check")
+        .arg(path_as_str(&input))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--format")
+        .arg("markdown")
+        .assert()
+        .stdout(predicate::str::contains("## Feature readiness"))
+        .stdout(predicate::str::contains("**Readiness score:** 0/100"))
+        .stdout(predicate::str::contains("| shaper | unsupported | 5 |"));
+}
@@ -146,3 +146,66 @@ fn roundtrip_pfsense_to_opnsense_back_has_no_modified_or_structural_diff() {
     assert!(summary.contains("modified=0"), "{summary}");
     assert!(summary.contains("structural=0"), "{summary}");
 }
+
+#[test]
+fn roundtrip_opnsense_via_pfsense_restores_mvc_snapshot() {
+    let dir = tempdir().expect("tempdir");
+    let opn_src = dir.path().join("opn-src.xml");
+    let pf_base = dir.path().join("pf-base.xml");
+    let to_pf = dir.path().join("opn-to-pf.xml");
+    let clean_pf = dir.path().join("opn-to-pf-clean.xml");
+    let back_to_opn = dir.path().join("opn-back.xml");
+
+    fs::write(
+        &opn_src,
+        r#"<opnsense>
+<system/>
+<interfaces><lan><if>igc1</if></lan></interfaces>
+<OPNsense><Firewall><Alias><aliases><alias uuid="a1"><name>lab</name><type>host</type><content>10.0.0.1</content><counters>1</counters></alias></aliases></Alias></Firewall></OPNsense>
+</opnsense>"#,
+    )
+    .expect("write opn src");
+    fs::write(
+        &pf_base,
+        r#"<pfsense><system/><interfaces><lan><if>igc1</if></lan></interfaces></pfsense>"#,
+    )
+    .expect("write pf base");
+
+    let convert = |input: &std::path::Path,
+                   to: &str,
+                   target: &std::path::Path,
+                   out: &std::path::Path,
+                   extra: &[&str]| {
+        let mut args = vec![
+            "convert",
+            input.to_str().expect("utf8 path"),
+            "--to",
+            to,
+            "--target-file",
+            target.to_str().expect("utf8 path"),
+            "--output",
+            out.to_str().expect("utf8 path"),
+        ];
+        args.extend_from_slice(extra);
+        run_success(&args);
+    };
+    convert(&opn_src, "pfsense", &pf_base, &to_pf, &[]);
+    convert(
+        &opn_src,
+        "pfsense",
+        &pf_base,
+        &clean_pf,
+        &["--no-snapshots"],
+    );
+    convert(&to_pf, "opnsense", &opn_src, &back_to_opn, &[]);
+
+    assert!(fs::read_to_string(&to_pf)
+        .expect("read pf")
+        .contains("<pfopn_snapshots>"));
+    assert!(!fs::read_to_string(&clean_pf)
+        .expect("read clean pf")
+        .contains("pfopn_snapshots"));
+    let back = fs::read_to_string(&back_to_opn).expect("read back");
+    assert!(back.contains("<counters>1</counters>"), "{back}");
+    assert!(!back.contains("pfopn_snapshots"));
+}
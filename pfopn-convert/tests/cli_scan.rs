@@ -227,6 +227,177 @@ note = "test"
         .stdout(predicate::str::contains("Using mappings: file:"));
 }
 
+#[test]
+fn scan_lists_boot_shell_commands_for_security_review() {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("scan")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("scan output");
+    assert!(output.status.success(), "scan should succeed");
+
+    let report: Value = serde_json::from_slice(&output.stdout).expect("json parse");
+    let review: Vec<&str> = report["security_review"]
+        .as_array()
+        .expect("security_review array")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert_eq!(review.len(), 3);
+    assert!(
+        review.contains(&"earlyshellcmd '/usr/local/pkg/pfblockerng/pfblockerng.sh aliastables'")
+    );
+}
+
+#[test]
+fn scan_flags_stale_package_configs_and_unconfigured_packages() {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("scan")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("scan output");
+    assert!(output.status.success(), "scan should succeed");
+
+    let report: Value = serde_json::from_slice(&output.stdout).expect("json parse");
+    let stale: Vec<&str> = report["stale_package_configs"]
+        .as_array()
+        .expect("stale_package_configs array")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert!(stale.contains(&"snort (snortglobal)"));
+    assert!(stale.contains(&"nrpe (nrpe)"));
+    assert!(!stale.iter().any(|s| s.starts_with("tailscale")));
+    assert_eq!(
+        report["unconfigured_packages"],
+        serde_json::json!(["System Patches"])
+    );
+    assert!(report["recommendations"]
+        .as_array()
+        .expect("recommendations")
+        .iter()
+        .any(|r| r
+            .as_str()
+            .is_some_and(|r| r.contains("stale_package_configs"))));
+}
+
+#[test]
+fn scan_renders_user_template() {
+    let dir = tempdir().expect("tempdir");
+    let template = dir.path().join("report.j2");
+    fs::write(
+        &template,
+        "Plattform: {{ report.platform }}\nPrüfen: {{ report.security_review | length }}\n",
+    )
+    .expect("write template");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("scan")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--template")
+        .arg(path_as_str(&template))
+        .assert()
+        .success()
+        .stdout("Plattform: pfsense\nPrüfen: 3\n");
+}
+
+#[test]
+fn scan_template_reports_undefined_fields() {
+    let dir = tempdir().expect("tempdir");
+    let template = dir.path().join("report.j2");
+    fs::write(&template, "{{ report.platfrom }}").expect("write template");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("scan")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .arg("--template")
+        .arg(path_as_str(&template))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to render template"));
+}
+
+#[test]
+fn scan_lists_parse_warnings_for_truncated_export() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("truncated.xml");
+    let full = fs::read(fixture("fixtures/pfsense-base.xml")).expect("read fixture");
+    fs::write(&input, &full[..full.len() / 2]).expect("write src");
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("scan")
+        .arg(path_as_str(&input))
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("scan output");
+    assert!(output.status.success(), "scan should succeed");
+
+    let report: Value = serde_json::from_slice(&output.stdout).expect("json parse");
+    assert_eq!(report["platform"], "pfsense");
+    let warnings = report["parse_warnings"]
+        .as_array()
+        .expect("parse_warnings array");
+    assert!(warnings
+        .iter()
+        .filter_map(Value::as_str)
+        .any(|w| w.contains("input ends inside <pfsense>")));
+    assert!(report["recommendations"][0]
+        .as_str()
+        .expect("recommendation")
+        .starts_with("the file is damaged and was read leniently"));
+}
+
 fn path_as_str(path: &Path) -> &str {
     path.to_str().expect("path should be utf8")
 }
+
+#[test]
+fn scan_reports_metrics_and_flags_large_configs() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("large.xml");
+    let rules: String = (1..=1200)
+        .map(|t| format!("<rule><tracker>{t}</tracker><type>pass</type></rule>"))
+        .collect();
+    fs::write(
+        &input,
+        format!(
+            "<pfsense><version>23.3</version><filter>{rules}</filter><aliases><alias><name>a</name></alias></aliases></pfsense>"
+        ),
+    )
+    .expect("write input");
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("scan")
+        .arg(&input)
+        .arg("--format")
+        .arg("json")
+        .output()
+        .expect("scan output");
+    assert!(output.status.success(), "scan should succeed");
+    let report: Value = serde_json::from_slice(&output.stdout).expect("json parse");
+    let metrics = &report["metrics"];
+    assert_eq!(metrics["filter_rules"].as_u64(), Some(1200));
+    assert_eq!(metrics["aliases"].as_u64(), Some(1));
+    assert_eq!(metrics["complexity"].as_str(), Some("large"));
+    assert!(metrics["estimated_output_bytes"].as_u64().unwrap_or(0) > 0);
+    let recommendations = report["recommendations"]
+        .as_array()
+        .expect("recommendations");
+    assert!(recommendations
+        .iter()
+        .filter_map(Value::as_str)
+        .any(|r| r.starts_with("large config:")));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("scan")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("metrics filter_rules="))
+        .stdout(predicate::str::contains("complexity=small"));
+}
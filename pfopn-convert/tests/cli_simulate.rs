@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn simulate_reports_matching_rule_and_outbound_nat() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("simulate")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--in", "lan", "--src", "192.168.1.50", "--dst", "8.8.8.8"])
+        .args(["--dst-port", "443"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"Default allow LAN to any rule\" action=pass",
+        ))
+        .stdout(predicate::str::contains("egress: wan"))
+        .stdout(predicate::str::contains("result: pass"));
+}
+
+#[test]
+fn simulate_rejects_unassigned_interface() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("simulate")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--in", "opt99", "--src", "192.168.1.50", "--dst", "8.8.8.8"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'opt99' is not assigned"));
+}
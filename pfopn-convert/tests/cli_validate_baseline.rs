@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+#[test]
+fn validate_baseline_accepts_matching_baseline() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("validate-baseline")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .args(["--to", "opnsense", "--source"])
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("result errors=0"));
+}
+
+#[test]
+fn validate_baseline_rejects_wrong_platform() {
+    Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("validate-baseline")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--to", "opnsense"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("platform_mismatch"))
+        .stderr(predicate::str::contains("validate-baseline failed"));
+}
+
+#[test]
+fn validate_baseline_reports_missing_interfaces_as_json() {
+    let assert = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"))
+        .arg("validate-baseline")
+        .arg(fixture("fixtures/opnsense-base.xml"))
+        .arg("--source")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .args(["--map-interface", "lan=opt9", "--format", "json"])
+        .assert()
+        .failure();
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    let codes: Vec<&str> = json["issues"]
+        .as_array()
+        .expect("issues")
+        .iter()
+        .filter_map(|issue| issue["code"].as_str())
+        .collect();
+    assert!(codes.contains(&"missing_interface"), "{codes:?}");
+}
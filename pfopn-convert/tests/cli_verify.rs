@@ -21,6 +21,18 @@ fn verify_passes_for_real_fixture() {
         .stdout(predicate::str::contains("result errors=0"));
 }
 
+#[test]
+fn verify_flags_boot_shell_commands_for_security_review() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(fixture("fixtures/pfsense-base.xml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "shellcmd_review: security review: earlyshellcmd 'service wireguardd start' (WireGuard earlyshellcmd (DO NOT EDIT/DELETE!)) runs as root",
+        ));
+}
+
 #[test]
 fn verify_fails_on_missing_required_section() {
     let dir = tempdir().expect("tempdir");
@@ -160,6 +172,427 @@ fn verify_fails_on_pfsense_dhcp_backend_inconsistency() {
         .stdout(predicate::str::contains("dhcp_backend_inconsistent"));
 }
 
+#[test]
+fn verify_orphans_warns_about_unreferenced_objects() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("orphans.xml");
+    fs::write(
+        &input,
+        r#"<pfsense>
+            <system/>
+            <interfaces><lan/></interfaces>
+            <cert><refid>old</refid><descr>Old VPN</descr></cert>
+            <schedules><schedule><name>never</name></schedule></schedules>
+        </pfsense>"#,
+    )
+    .expect("write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .assert()
+        .stdout(predicate::str::contains("orphan_object").not());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--orphans")
+        .assert()
+        .stdout(predicate::str::contains(
+            "orphan_object: cert 'old' (Old VPN) is not referenced by anything",
+        ))
+        .stdout(predicate::str::contains("schedule 'never'"));
+}
+
+#[test]
+fn verify_exposure_fails_when_conversion_opens_wan() {
+    let dir = tempdir().expect("tempdir");
+    let source = dir.path().join("source.xml");
+    let converted = dir.path().join("converted.xml");
+    fs::write(
+        &source,
+        r#"<pfsense><system><ssh><enable>enabled</enable></ssh></system><interfaces><wan><ipaddr>dhcp</ipaddr></wan><lan><ipaddr>192.168.1.1</ipaddr></lan></interfaces><filter><rule><type>pass</type><interface>wan</interface><protocol>tcp</protocol><source><any/></source><destination><address>192.168.1.10</address><port>443</port></destination><descr>web server</descr></rule></filter></pfsense>"#,
+    )
+    .expect("write source");
+    fs::write(
+        &converted,
+        r#"<opnsense><system><ssh><enabled>enabled</enabled><interfaces>wan</interfaces></ssh></system><interfaces><wan><ipaddr>dhcp</ipaddr></wan><lan><ipaddr>192.168.1.1</ipaddr></lan></interfaces><filter><rule><type>pass</type><interface>wan</interface><protocol>tcp</protocol><source><any/></source><destination><address>192.168.1.10</address><port>443</port></destination><descr>web server</descr></rule></filter></opnsense>"#,
+    )
+    .expect("write converted");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&source))
+        .arg("--exposure")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[warning] wan_exposure: filter rule #1 (web server) passes tcp from any to 192.168.1.10 port 443 on WAN interface wan",
+        ))
+        .stdout(predicate::str::contains("SSH").not());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&converted))
+        .arg("--exposure-source")
+        .arg(path_as_str(&source))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "[error] wan_exposure_added: SSH (tcp/22) listens on WAN interface wan; the source config did not expose this",
+        ))
+        .stdout(predicate::str::contains("[warning] wan_exposure: filter rule #1"));
+}
+
+#[test]
+fn verify_netboot_source_checks_pxe_settings_survive_kea_migration() {
+    let dir = tempdir().expect("tempdir");
+    let source = dir.path().join("source.xml");
+    let converted = dir.path().join("converted.xml");
+    let broken = dir.path().join("broken.xml");
+    fs::write(
+        &source,
+        r#"<pfsense><interfaces><wan><if>em0</if><ipaddr>dhcp</ipaddr></wan><lan><if>em1</if><ipaddr>192.168.1.1</ipaddr><subnet>24</subnet></lan></interfaces><dhcpd><lan><enable/><range><from>192.168.1.100</from><to>192.168.1.200</to></range><nextserver>192.168.1.5</nextserver><tftp>192.168.1.5</tftp><filename>pxelinux.0</filename></lan></dhcpd></pfsense>"#,
+    )
+    .expect("write source");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("convert")
+        .arg(path_as_str(&source))
+        .arg("--output")
+        .arg(path_as_str(&converted))
+        .arg("--to")
+        .arg("opnsense")
+        .arg("--target-version")
+        .arg("26.1")
+        .arg("--backend")
+        .arg("kea")
+        .assert()
+        .success();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&converted))
+        .arg("--netboot-source")
+        .arg(path_as_str(&source))
+        .assert()
+        .stdout(predicate::str::contains("netboot_dropped").not());
+
+    let output = fs::read_to_string(&converted).expect("read converted");
+    fs::write(
+        &broken,
+        output.replace(
+            "<boot_file_name>pxelinux.0</boot_file_name>",
+            "<boot_file_name/>",
+        ),
+    )
+    .expect("write broken");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&broken))
+        .arg("--netboot-source")
+        .arg(path_as_str(&source))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "[error] netboot_dropped: dhcpd/lan: boot file 'pxelinux.0' was dropped from Kea subnet 192.168.1.0/24",
+        ));
+}
+
+#[test]
+fn verify_bridge_source_reports_pruned_bridge_members() {
+    let dir = tempdir().expect("tempdir");
+    let source = dir.path().join("source.xml");
+    let converted = dir.path().join("converted.xml");
+    fs::write(
+        &source,
+        r#"<pfsense><interfaces><lan><if>em1</if></lan><opt1><if>em2</if></opt1></interfaces></pfsense>"#,
+    )
+    .expect("write source");
+    fs::write(
+        &converted,
+        r#"<opnsense><interfaces><lan><if>vtnet1</if></lan></interfaces><bridges><bridged uuid="b1"><members>lan,opt1</members><bridgeif>bridge0</bridgeif></bridged></bridges></opnsense>"#,
+    )
+    .expect("write converted");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&converted))
+        .arg("--bridge-source")
+        .arg(path_as_str(&source))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "[error] bridge_member_pruned: bridge #0 member 'opt1' was pruned during conversion",
+        ))
+        .stdout(predicate::str::contains("missing_bridge_member").not());
+}
+
+#[test]
+fn verify_reads_damaged_export_leniently_and_reports_recoveries() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("damaged.xml");
+    fs::write(
+        &input,
+        b"<pfsense><system><hostname>fw</hostname></system><interfaces><lan><if>em0</if><descr>Caf\xe9</descr></lan></interfaces><filter><rule><interface>lan</interface><descr>R&D</descr></rule><rule><des",
+    )
+    .expect("write src");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "parse_recovered: byte 88: 1 byte(s) are not valid UTF-8; read them as Windows-1252",
+        ))
+        .stdout(predicate::str::contains(
+            "kept the reference as literal text",
+        ))
+        .stdout(predicate::str::contains(
+            "input ends inside <pfsense> <filter> <rule>; closed them",
+        ));
+
+    let mut strict = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    strict
+        .arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--strict")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn verify_openvpn_export_lists_missing_items_per_server() {
+    let dir = tempdir().expect("tempdir");
+    let config = dir.path().join("config.xml");
+    fs::write(
+        &config,
+        r#"<opnsense><system/><interfaces><wan><ipaddr>dhcp</ipaddr></wan></interfaces>
+            <OPNsense><OpenVPN><Instances>
+              <Instance uuid="i1"><vpnid>1</vpnid><role>server</role><description>RA</description><cert>gone</cert><ca>ca1</ca></Instance>
+              <Instance uuid="i2"><vpnid>2</vpnid><role>server</role><description>Site</description><cert>c1</cert><ca>ca1</ca><local>198.51.100.7</local></Instance>
+            </Instances></OpenVPN></OPNsense>
+            <ca><refid>ca1</refid><crt>REDACTED</crt></ca><cert><refid>c1</refid><crt>REDACTED</crt></cert>
+        </opnsense>"#,
+    )
+    .expect("config write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&config))
+        .arg("--openvpn-export")
+        .assert()
+        .stdout(predicate::str::contains(
+            "[warning] openvpn_export_missing: OpenVPN server 'RA' (vpnid 1): server certificate 'gone' is not in the config",
+        ))
+        .stdout(predicate::str::contains(
+            "OpenVPN server 'RA' (vpnid 1): interface 'wan' has a dynamic address (dhcp)",
+        ))
+        .stdout(predicate::str::contains(
+            "[info] openvpn_export_ready: OpenVPN server 'Site' (vpnid 2): ready for client export",
+        ));
+}
+
 fn path_as_str(path: &Path) -> &str {
     path.to_str().expect("utf8 path")
 }
+
+#[test]
+fn verify_warns_on_plus_only_features_for_ce_and_opnsense_targets() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("plus.xml");
+    fs::write(
+        &input,
+        r#"<pfsense>
+            <version>23.3</version>
+            <system/>
+            <interfaces><lan/></interfaces>
+            <filter/>
+            <openvpn><openvpn-server><vpnid>1</vpnid><dco>enabled</dco></openvpn-server></openvpn>
+        </pfsense>"#,
+    )
+    .expect("write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plus_only_feature").not());
+
+    for version in ["2.7.2", "25.1"] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+        let assert = cmd
+            .arg("verify")
+            .arg(path_as_str(&input))
+            .arg("--target-version")
+            .arg(version)
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+        assert_eq!(
+            stdout.contains(
+                "plus_only_feature: openvpn/openvpn-server uses OpenVPN Data Channel Offload"
+            ),
+            version == "2.7.2",
+            "{version}: {stdout}"
+        );
+    }
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--to")
+        .arg("opnsense")
+        .assert()
+        .stdout(predicate::str::contains("plus_only_feature"));
+}
+
+#[test]
+fn verify_uses_business_profile_for_business_edition_configs() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("be.xml");
+    fs::write(
+        &input,
+        r#"<opnsense>
+            <version>25.10</version>
+            <system><firmware><type>-business</type><plugins>os-OPNWAF</plugins></firmware></system>
+            <interfaces><lan/></interfaces>
+            <filter/>
+            <dhcpd><lan><enable>1</enable></lan></dhcpd>
+            <OPNsense><OPNWAF><general/></OPNWAF></OPNsense>
+        </opnsense>"#,
+    )
+    .expect("write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("business_only_plugin").not())
+        .stdout(predicate::str::contains("dhcp_backend_inconsistent").not());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--target-version")
+        .arg("25.7")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "business_only_plugin: OPNWAF web application firewall (os-OPNWAF, found in firmware.plugins)",
+        ));
+}
+
+#[test]
+fn verify_fix_lists_on_dry_run_and_writes_fixed_config() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("broken.xml");
+    let output = dir.path().join("fixed.xml");
+    fs::write(
+        &input,
+        r#"<pfsense>
+            <system/>
+            <interfaces><lan/></interfaces>
+            <filter>
+                <rule><interface>lan</interface><sched>gone</sched></rule>
+                <rule><interface>opt7</interface></rule>
+            </filter>
+        </pfsense>"#,
+    )
+    .expect("write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--fix")
+        .arg("schedules,interfaces")
+        .arg("--dry-run")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("fixes planned=2"))
+        .stdout(predicate::str::contains(
+            "- [schedules] filter/rule[1]: removed reference to missing schedule 'gone'",
+        ))
+        .stdout(predicate::str::contains(
+            "- [interfaces] filter/rule[2]: disabled rule referencing missing interface 'opt7'",
+        ));
+    assert!(!output.exists());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--fix")
+        .arg("schedules,interfaces")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--fix needs --fix-output"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--fix")
+        .arg("schedules,interfaces")
+        .arg("--fix-output")
+        .arg(path_as_str(&output))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fixes applied=2"))
+        .stdout(predicate::str::contains("result errors=0"));
+    let fixed = fs::read_to_string(&output).expect("read fixed");
+    assert!(!fixed.contains("<sched>"));
+    assert!(fixed.contains("<disabled"));
+}
+
+#[test]
+fn verify_suppressions_baseline_accepts_known_findings_only() {
+    let dir = tempdir().expect("tempdir");
+    let input = dir.path().join("known.xml");
+    let accepted = dir.path().join("accepted.toml");
+    let config = |rules: &str| {
+        format!(
+            "<pfsense><system/><interfaces><lan/></interfaces><filter>{rules}</filter></pfsense>"
+        )
+    };
+    fs::write(&input, config("<rule><interface>opt7</interface></rule>")).expect("write");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--suppressions")
+        .arg(path_as_str(&accepted))
+        .arg("--update-baseline")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("result errors=0"))
+        .stdout(predicate::str::contains("baseline updated=1 by"));
+    let baseline = fs::read_to_string(&accepted).expect("baseline");
+    assert!(baseline.contains("code = \"missing_interface_reference\""));
+    assert!(baseline.contains("justification = \"accepted by verify --update-baseline\""));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--suppressions")
+        .arg(path_as_str(&accepted))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("suppressed=1 by"));
+
+    fs::write(
+        &input,
+        config("<rule><interface>opt7</interface></rule><rule><interface>opt8</interface></rule>"),
+    )
+    .expect("write");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("pfopn-convert"));
+    cmd.arg("verify")
+        .arg(path_as_str(&input))
+        .arg("--suppressions")
+        .arg(path_as_str(&accepted))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("result errors=1"))
+        .stdout(predicate::str::contains("missing interface 'opt8'"));
+}
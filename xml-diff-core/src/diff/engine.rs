@@ -1,6 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 
 use crate::diff::result::DiffEntry;
+use crate::query::PathQuery;
 use crate::XmlNode;
 
 /// Configures tree diff behavior.
@@ -12,8 +15,25 @@ pub struct DiffOptions {
     pub max_depth: i32,
     /// Optional map from tag -> child tag used as key for repeated-element matching.
     pub key_fields: HashMap<String, String>,
-    /// Paths or tag names to ignore.
+    /// Paths or tag names to ignore. An entry starting with `@` ignores that
+    /// attribute on every node (`@version`).
     pub ignore_paths: Vec<String>,
+    /// Path queries whose matches (in either tree) are ignored.
+    pub ignore_queries: Vec<PathQuery>,
+    /// Report differing comments as modifications. Comments are ignored by default.
+    pub compare_comments: bool,
+    /// Tags whose repeated elements are order-sensitive (firewall rules are
+    /// evaluated top to bottom). Elements of these tags matched through
+    /// [`key_fields`](Self::key_fields) whose relative order changed are
+    /// reported as [`DiffEntry::Moved`]; without a key field, position is
+    /// the identity and a reorder shows up as modifications.
+    pub ordered_tags: HashSet<String>,
+    /// Pair nodes removed in one place and added with identical content in
+    /// another into [`DiffEntry::Relocated`]. Content is compared by tag,
+    /// attributes (minus ignored `@` names), trimmed text and children;
+    /// comments are not compared. Leaf nodes are never paired, since an empty
+    /// or one-value element turning up elsewhere says nothing about a move.
+    pub detect_relocations: bool,
 }
 
 impl Default for DiffOptions {
@@ -23,6 +43,10 @@ impl Default for DiffOptions {
             max_depth: -1,
             key_fields: HashMap::new(),
             ignore_paths: Vec::new(),
+            ignore_queries: Vec::new(),
+            compare_comments: false,
+            ordered_tags: HashSet::new(),
+            detect_relocations: false,
         }
     }
 }
@@ -36,19 +60,68 @@ pub fn diff(left: &XmlNode, right: &XmlNode) -> Vec<DiffEntry> {
 pub fn diff_with_options(left: &XmlNode, right: &XmlNode, opts: &DiffOptions) -> Vec<DiffEntry> {
     let mut out = Vec::new();
     let root_path = left.tag.clone();
-    diff_node(left, right, &root_path, 0, opts, &mut out);
+    let scope = DiffScope::new(opts, left, right);
+    diff_node(left, right, &root_path, 0, &scope, &mut out);
+    if opts.detect_relocations {
+        out = pair_relocations(out, opts);
+    }
     out
 }
 
+/// Options plus the nodes selected by [`DiffOptions::ignore_queries`].
+pub(crate) struct DiffScope<'a> {
+    pub(crate) opts: &'a DiffOptions,
+    ignored: HashSet<usize>,
+}
+
+impl<'a> DiffScope<'a> {
+    pub(crate) fn new(opts: &'a DiffOptions, left: &XmlNode, right: &XmlNode) -> Self {
+        let ignored = opts
+            .ignore_queries
+            .iter()
+            .flat_map(|query| query.select(left).into_iter().chain(query.select(right)))
+            .map(node_id)
+            .collect();
+        Self { opts, ignored }
+    }
+
+    fn ignores(&self, node: &XmlNode) -> bool {
+        self.ignored.contains(&node_id(node))
+    }
+}
+
+/// Node identity by address; stored as `usize` so the set stays `Sync`.
+fn node_id(node: &XmlNode) -> usize {
+    node as *const XmlNode as usize
+}
+
+/// Signature shared by the sequential and parallel child differs.
+pub(crate) type ChildDiffer =
+    fn(&XmlNode, &XmlNode, &str, i32, &DiffScope<'_>, &mut Vec<DiffEntry>);
+
 fn diff_node(
     left: &XmlNode,
     right: &XmlNode,
     path: &str,
     depth: i32,
-    opts: &DiffOptions,
+    scope: &DiffScope<'_>,
     out: &mut Vec<DiffEntry>,
 ) {
-    if should_ignore(path, opts) {
+    diff_node_with(left, right, path, depth, scope, out, diff_children);
+}
+
+/// Diff a single node, delegating its children to `children`.
+pub(crate) fn diff_node_with(
+    left: &XmlNode,
+    right: &XmlNode,
+    path: &str,
+    depth: i32,
+    scope: &DiffScope<'_>,
+    out: &mut Vec<DiffEntry>,
+    children: ChildDiffer,
+) {
+    let opts = scope.opts;
+    if should_ignore(path, opts) || scope.ignores(left) || scope.ignores(right) {
         return;
     }
 
@@ -63,21 +136,30 @@ fn diff_node(
             path: path.to_string(),
             description: format!("tag mismatch: left='{}' right='{}'", left.tag, right.tag),
         });
-        diff_children(left, right, path, depth, opts, out);
+        children(left, right, path, depth, scope, out);
         return;
     }
 
-    if left.attributes != right.attributes
-        || normalize_text(&left.text) != normalize_text(&right.text)
-    {
+    diff_attributes(left, right, path, opts, out);
+    if normalize_text(&left.text) != normalize_text(&right.text) {
         out.push(DiffEntry::Modified {
             path: path.to_string(),
             left: local_signature(left),
             right: local_signature(right),
+            left_text: normalize_text(&left.text).map(str::to_string),
+            right_text: normalize_text(&right.text).map(str::to_string),
+        });
+    } else if opts.compare_comments && !left.comments().eq(right.comments()) {
+        out.push(DiffEntry::Modified {
+            path: path.to_string(),
+            left: format!("comments={:?}", left.comments().collect::<Vec<_>>()),
+            right: format!("comments={:?}", right.comments().collect::<Vec<_>>()),
+            left_text: normalize_text(&left.text).map(str::to_string),
+            right_text: normalize_text(&right.text).map(str::to_string),
         });
     }
 
-    diff_children(left, right, path, depth, opts, out);
+    children(left, right, path, depth, scope, out);
 
     if opts.include_identical && out.len() == start_len {
         out.push(DiffEntry::Identical {
@@ -86,10 +168,44 @@ fn diff_node(
     }
 }
 
+/// Push one [`DiffEntry::AttributeChanged`] per differing attribute, by name.
+fn diff_attributes(
+    left: &XmlNode,
+    right: &XmlNode,
+    path: &str,
+    opts: &DiffOptions,
+    out: &mut Vec<DiffEntry>,
+) {
+    if left.attributes == right.attributes {
+        return;
+    }
+    let names: BTreeSet<&String> = left
+        .attributes
+        .keys()
+        .chain(right.attributes.keys())
+        .collect();
+    for name in names {
+        let (l, r) = (left.attributes.get(name), right.attributes.get(name));
+        if l == r {
+            continue;
+        }
+        let attr_path = format!("{path}@{name}");
+        if should_ignore(&attr_path, opts) {
+            continue;
+        }
+        out.push(DiffEntry::AttributeChanged {
+            path: attr_path,
+            name: name.clone(),
+            left: l.cloned(),
+            right: r.cloned(),
+        });
+    }
+}
+
 struct MatchContext<'a, 'b> {
     parent_path: &'a str,
     depth: i32,
-    opts: &'a DiffOptions,
+    scope: &'a DiffScope<'a>,
     out: &'b mut Vec<DiffEntry>,
 }
 
@@ -103,16 +219,16 @@ fn match_by_index(
     for i in 0..max {
         let child_path = format!("{}.{tag}[{}]", ctx.parent_path, i + 1);
         match (left_nodes.get(i), right_nodes.get(i)) {
-            (Some(l), Some(r)) => diff_node(l, r, &child_path, ctx.depth + 1, ctx.opts, ctx.out),
-            (Some(l), None) => ctx.out.push(DiffEntry::OnlyLeft {
+            (Some(l), Some(r)) => diff_node(l, r, &child_path, ctx.depth + 1, ctx.scope, ctx.out),
+            (Some(l), None) if !ctx.scope.ignores(l) => ctx.out.push(DiffEntry::OnlyLeft {
                 path: child_path,
                 node: (*l).clone(),
             }),
-            (None, Some(r)) => ctx.out.push(DiffEntry::OnlyRight {
+            (None, Some(r)) if !ctx.scope.ignores(r) => ctx.out.push(DiffEntry::OnlyRight {
                 path: child_path,
                 node: (*r).clone(),
             }),
-            (None, None) => {}
+            _ => {}
         }
     }
 }
@@ -124,75 +240,101 @@ fn match_by_key(
     right_nodes: Vec<&XmlNode>,
     ctx: &mut MatchContext<'_, '_>,
 ) {
-    let right_keys: Vec<Option<String>> = right_nodes
+    // Right-side positions per key, so each left node finds its match
+    // without rescanning the whole group.
+    let mut right_by_key: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for (idx, node) in right_nodes.iter().enumerate() {
+        if let Some(key) = node.get_text(&[key_field]) {
+            right_by_key.entry(key).or_default().push_back(idx);
+        }
+    }
+
+    // Nodes with different keys are different elements, so only a node
+    // missing its key falls back to the node at the same position, and only
+    // when that node's key has no match of its own on the left.
+    let left_keys: HashSet<&str> = left_nodes
         .iter()
-        .map(|n| n.get_text(&[key_field]).map(ToString::to_string))
+        .filter_map(|node| node.get_text(&[key_field]))
         .collect();
+    let positional_ok = |left: &XmlNode, right_idx: usize| match (
+        left.get_text(&[key_field]),
+        right_nodes[right_idx].get_text(&[key_field]),
+    ) {
+        (Some(_), Some(_)) => false,
+        (_, right_key) => right_key.is_none_or(|key| !left_keys.contains(key)),
+    };
 
+    // Pair every left node first: moves can only be told apart from shifts
+    // once all key matches are known.
     let mut used_right = HashSet::new();
-
+    let mut matches = Vec::with_capacity(left_nodes.len());
     for (left_idx, left_node) in left_nodes.iter().enumerate() {
-        let left_key = left_node.get_text(&[key_field]).map(ToString::to_string);
-        let child_path = if let Some(key) = &left_key {
-            format!("{}.{tag}[{key}]", ctx.parent_path)
-        } else {
-            format!("{}.{tag}[{}]", ctx.parent_path, left_idx + 1)
-        };
-
-        let matched_right = if let Some(left_key_val) = &left_key {
-            right_keys.iter().enumerate().find_map(|(idx, right_key)| {
-                if used_right.contains(&idx) {
-                    return None;
-                }
-                if right_key.as_ref() == Some(left_key_val) {
-                    Some(idx)
-                } else {
-                    None
-                }
-            })
-        } else {
-            None
+        let keyed = left_node
+            .get_text(&[key_field])
+            .and_then(|key| right_by_key.get_mut(key))
+            .and_then(|candidates| {
+                // Entries taken by a positional match stay queued; skip them.
+                std::iter::from_fn(|| candidates.pop_front()).find(|idx| !used_right.contains(idx))
+            });
+        let matched = match keyed {
+            Some(right_idx) => Some((right_idx, true)),
+            None if left_idx < right_nodes.len()
+                && !used_right.contains(&left_idx)
+                && positional_ok(left_node, left_idx) =>
+            {
+                Some((left_idx, false))
+            }
+            None => None,
         };
-
-        if let Some(right_idx) = matched_right {
+        if let Some((right_idx, _)) = matched {
             used_right.insert(right_idx);
-            diff_node(
-                left_node,
-                right_nodes[right_idx],
-                &child_path,
-                ctx.depth + 1,
-                ctx.opts,
-                ctx.out,
-            );
-            continue;
         }
+        matches.push(matched);
+    }
 
-        let positional = if left_idx < right_nodes.len() && !used_right.contains(&left_idx) {
-            Some(left_idx)
-        } else {
-            None
+    let moved = if ctx.scope.opts.ordered_tags.contains(tag) {
+        moved_matches(&matches)
+    } else {
+        HashSet::new()
+    };
+
+    for (left_idx, (left_node, matched)) in left_nodes.iter().zip(&matches).enumerate() {
+        let child_path = match left_node.get_text(&[key_field]) {
+            Some(key) => format!("{}.{tag}[{key}]", ctx.parent_path),
+            None => format!("{}.{tag}[{}]", ctx.parent_path, left_idx + 1),
         };
 
-        if let Some(right_idx) = positional {
-            used_right.insert(right_idx);
-            diff_node(
-                left_node,
-                right_nodes[right_idx],
-                &child_path,
-                ctx.depth + 1,
-                ctx.opts,
-                ctx.out,
-            );
-        } else {
-            ctx.out.push(DiffEntry::OnlyLeft {
+        match matched {
+            Some((right_idx, _)) => {
+                if moved.contains(&left_idx)
+                    && !ctx.scope.ignores(left_node)
+                    && !should_ignore(&child_path, ctx.scope.opts)
+                {
+                    ctx.out.push(DiffEntry::Moved {
+                        path: child_path.clone(),
+                        from: left_idx + 1,
+                        to: right_idx + 1,
+                    });
+                }
+                diff_node(
+                    left_node,
+                    right_nodes[*right_idx],
+                    &child_path,
+                    ctx.depth + 1,
+                    ctx.scope,
+                    ctx.out,
+                );
+            }
+            None if !ctx.scope.ignores(left_node) => ctx.out.push(DiffEntry::OnlyLeft {
                 path: child_path,
                 node: (*left_node).clone(),
-            });
+            }),
+            None => {}
         }
     }
 
     for (right_idx, right_node) in right_nodes.iter().enumerate() {
-        if used_right.contains(&right_idx) {
+        if used_right.contains(&right_idx) || ctx.scope.ignores(right_node) {
             continue;
         }
         let right_key = right_node.get_text(&[key_field]).map(ToString::to_string);
@@ -208,47 +350,292 @@ fn match_by_key(
     }
 }
 
+/// Left positions of key-matched nodes whose relative order changed.
+///
+/// The longest run of key matches that keeps its order on the right stays
+/// put; every other key match moved. Insertions and removals around an
+/// element shift it without moving it.
+fn moved_matches(matches: &[Option<(usize, bool)>]) -> HashSet<usize> {
+    let keyed: Vec<(usize, usize)> = matches
+        .iter()
+        .enumerate()
+        .filter_map(|(left_idx, matched)| match matched {
+            Some((right_idx, true)) => Some((left_idx, *right_idx)),
+            _ => None,
+        })
+        .collect();
+
+    // Longest increasing subsequence of right positions (patience sorting).
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = vec![None; keyed.len()];
+    for (i, (_, right_idx)) in keyed.iter().enumerate() {
+        let slot = tails.partition_point(|&t| keyed[t].1 < *right_idx);
+        if slot > 0 {
+            prev[i] = Some(tails[slot - 1]);
+        }
+        if slot == tails.len() {
+            tails.push(i);
+        } else {
+            tails[slot] = i;
+        }
+    }
+    let mut kept = HashSet::new();
+    let mut cursor = tails.last().copied();
+    while let Some(i) = cursor {
+        kept.insert(i);
+        cursor = prev[i];
+    }
+
+    keyed
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !kept.contains(i))
+        .map(|(_, (left_idx, _))| *left_idx)
+        .collect()
+}
+
+/// Children of one tag from each side, in document order.
+pub(crate) type ChildGroup<'a> = (String, Vec<&'a XmlNode>, Vec<&'a XmlNode>);
+
+/// Group children by tag in first-seen order (left side first, then right).
+pub(crate) fn child_groups<'a>(left: &'a XmlNode, right: &'a XmlNode) -> Vec<ChildGroup<'a>> {
+    let left_index = left.index();
+    let right_index = right.index();
+    let right_only = right_index
+        .tags()
+        .iter()
+        .filter(|tag| left_index.positions(tag).is_empty());
+
+    left_index
+        .tags()
+        .iter()
+        .chain(right_only)
+        .map(|tag| {
+            (
+                tag.to_string(),
+                left_index.get_children(tag),
+                right_index.get_children(tag),
+            )
+        })
+        .collect()
+}
+
+/// Diff one tag group below `path`, appending entries to `out`.
+pub(crate) fn diff_group(
+    group: ChildGroup<'_>,
+    path: &str,
+    depth: i32,
+    scope: &DiffScope<'_>,
+    out: &mut Vec<DiffEntry>,
+) {
+    let (tag, left_nodes, right_nodes) = group;
+    let mut ctx = MatchContext {
+        parent_path: path,
+        depth,
+        scope,
+        out,
+    };
+
+    if let Some(key_field) = scope.opts.key_fields.get(&tag) {
+        match_by_key(&tag, key_field, left_nodes, right_nodes, &mut ctx);
+    } else {
+        match_by_index(&tag, left_nodes, right_nodes, &mut ctx);
+    }
+}
+
 fn diff_children(
     left: &XmlNode,
     right: &XmlNode,
     path: &str,
     depth: i32,
-    opts: &DiffOptions,
+    scope: &DiffScope<'_>,
     out: &mut Vec<DiffEntry>,
 ) {
-    let mut tags = Vec::new();
-    for child in &left.children {
-        if !tags.iter().any(|t| t == &child.tag) {
-            tags.push(child.tag.clone());
-        }
+    for group in child_groups(left, right) {
+        diff_group(group, path, depth, scope, out);
     }
-    for child in &right.children {
-        if !tags.iter().any(|t| t == &child.tag) {
-            tags.push(child.tag.clone());
+}
+
+/// A removed or added subtree, or an element inside one.
+struct Candidate<'a> {
+    /// Index of the `OnlyLeft`/`OnlyRight` entry holding the node.
+    entry: usize,
+    path: String,
+    node: &'a XmlNode,
+    hash: u64,
+}
+
+/// Pair removed and added nodes with equal content into
+/// [`DiffEntry::Relocated`], placed at the removal.
+///
+/// Elements inside a removed or added subtree are candidates too, so a node
+/// moved into a new container still pairs; the entry for the enclosing
+/// subtree is kept and only whole-entry matches replace their entries.
+/// Candidates are compared by content hash, confirmed with a full
+/// comparison, and paired first come first served.
+pub(crate) fn pair_relocations(entries: Vec<DiffEntry>, opts: &DiffOptions) -> Vec<DiffEntry> {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        match entry {
+            DiffEntry::OnlyLeft { path, node } => {
+                collect_candidates(idx, path, node, opts, &mut removed)
+            }
+            DiffEntry::OnlyRight { path, node } => {
+                collect_candidates(idx, path, node, opts, &mut added)
+            }
+            _ => {}
         }
     }
+    if removed.is_empty() || added.is_empty() {
+        return entries;
+    }
 
-    for tag in tags {
-        let left_nodes: Vec<&XmlNode> = left.children.iter().filter(|n| n.tag == tag).collect();
-        let right_nodes: Vec<&XmlNode> = right.children.iter().filter(|n| n.tag == tag).collect();
-        let mut ctx = MatchContext {
-            parent_path: path,
-            depth,
-            opts,
-            out,
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (idx, candidate) in added.iter().enumerate() {
+        buckets.entry(candidate.hash).or_default().push(idx);
+    }
+    let mut used_removed: Vec<&str> = Vec::new();
+    let mut used_added: Vec<&str> = Vec::new();
+    let mut pairs: Vec<(&Candidate<'_>, &Candidate<'_>)> = Vec::new();
+    for left in &removed {
+        if used_removed.iter().any(|used| overlaps(used, &left.path)) {
+            continue;
+        }
+        let Some(bucket) = buckets.get(&left.hash) else {
+            continue;
         };
+        let matched = bucket.iter().map(|&idx| &added[idx]).find(|right| {
+            !used_added.iter().any(|used| overlaps(used, &right.path))
+                && same_content(left.node, right.node, opts)
+        });
+        if let Some(right) = matched {
+            used_removed.push(&left.path);
+            used_added.push(&right.path);
+            pairs.push((left, right));
+        }
+    }
+    if pairs.is_empty() {
+        return entries;
+    }
 
-        if let Some(key_field) = opts.key_fields.get(&tag) {
-            match_by_key(&tag, key_field, left_nodes, right_nodes, &mut ctx);
-        } else {
-            match_by_index(&tag, left_nodes, right_nodes, &mut ctx);
+    let whole = |candidate: &Candidate<'_>, entries: &[DiffEntry]| match &entries[candidate.entry] {
+        DiffEntry::OnlyLeft { path, .. } | DiffEntry::OnlyRight { path, .. } => {
+            *path == candidate.path
         }
+        _ => false,
+    };
+    let dropped: HashSet<usize> = pairs
+        .iter()
+        .flat_map(|(left, right)| {
+            [left, right]
+                .into_iter()
+                .filter(|c| whole(c, &entries))
+                .map(|c| c.entry)
+        })
+        .collect();
+    let mut relocations: HashMap<usize, Vec<DiffEntry>> = HashMap::new();
+    for (left, right) in &pairs {
+        relocations
+            .entry(left.entry)
+            .or_default()
+            .push(DiffEntry::Relocated {
+                from: left.path.clone(),
+                to: right.path.clone(),
+            });
     }
+
+    let mut out = Vec::with_capacity(entries.len());
+    for (idx, entry) in entries.iter().enumerate() {
+        if !dropped.contains(&idx) {
+            out.push(entry.clone());
+        }
+        if let Some(moved) = relocations.remove(&idx) {
+            out.extend(moved);
+        }
+    }
+    out
+}
+
+/// Push `node` and its non-leaf descendants, in document order.
+fn collect_candidates<'a>(
+    entry: usize,
+    path: &str,
+    node: &'a XmlNode,
+    opts: &DiffOptions,
+    out: &mut Vec<Candidate<'a>>,
+) {
+    if node.children.is_empty() {
+        return;
+    }
+    out.push(Candidate {
+        entry,
+        path: path.to_string(),
+        node,
+        hash: content_hash(node, opts),
+    });
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for child in &node.children {
+        let position = seen.entry(child.tag.as_str()).or_insert(0);
+        *position += 1;
+        let child_path = format!("{path}.{}[{position}]", child.tag);
+        collect_candidates(entry, &child_path, child, opts, out);
+    }
+}
+
+/// Whether one path is the other or inside it.
+fn overlaps(a: &str, b: &str) -> bool {
+    let inside = |outer: &str, inner: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    };
+    inside(a, b) || inside(b, a)
+}
+
+fn compared_attributes<'a>(
+    node: &'a XmlNode,
+    opts: &'a DiffOptions,
+) -> impl Iterator<Item = (&'a String, &'a String)> {
+    node.attributes.iter().filter(|(name, _)| {
+        !opts
+            .ignore_paths
+            .iter()
+            .any(|ignore| ignore.strip_prefix('@') == Some(name.as_str()))
+    })
+}
+
+fn content_hash(node: &XmlNode, opts: &DiffOptions) -> u64 {
+    fn feed(node: &XmlNode, opts: &DiffOptions, hasher: &mut DefaultHasher) {
+        node.tag.hash(hasher);
+        compared_attributes(node, opts).for_each(|attr| attr.hash(hasher));
+        normalize_text(&node.text).hash(hasher);
+        node.children.len().hash(hasher);
+        node.children
+            .iter()
+            .for_each(|child| feed(child, opts, hasher));
+    }
+    let mut hasher = DefaultHasher::new();
+    feed(node, opts, &mut hasher);
+    hasher.finish()
+}
+
+fn same_content(left: &XmlNode, right: &XmlNode, opts: &DiffOptions) -> bool {
+    left.tag == right.tag
+        && compared_attributes(left, opts).eq(compared_attributes(right, opts))
+        && normalize_text(&left.text) == normalize_text(&right.text)
+        && left.children.len() == right.children.len()
+        && left
+            .children
+            .iter()
+            .zip(&right.children)
+            .all(|(l, r)| same_content(l, r, opts))
 }
 
 fn should_ignore(path: &str, opts: &DiffOptions) -> bool {
     opts.ignore_paths.iter().any(|ignore| {
         path == ignore
+            || (ignore.starts_with('@') && path.ends_with(ignore.as_str()))
             || path.ends_with(&format!(".{ignore}"))
             || path.contains(&format!(".{ignore}["))
             || path == format!("{ignore}[1]")
@@ -1,7 +1,11 @@
 //! Core XML tree diffing.
 
 pub mod engine;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 pub mod result;
 
 pub use engine::{diff, diff_with_options, DiffOptions};
+#[cfg(feature = "parallel")]
+pub use parallel::diff_parallel;
 pub use result::DiffEntry;
@@ -0,0 +1,46 @@
+//! Rayon-backed diffing of top-level sections.
+
+use rayon::prelude::*;
+
+use crate::diff::engine::{
+    child_groups, diff_group, diff_node_with, pair_relocations, DiffOptions, DiffScope,
+};
+use crate::diff::result::DiffEntry;
+use crate::XmlNode;
+
+/// Diff two XML trees, processing the root's child sections concurrently.
+///
+/// Each tag group under the root is diffed on the rayon pool and the results
+/// are concatenated in the same order [`diff_with_options`] would produce, so
+/// output is identical to the sequential engine.
+///
+/// [`diff_with_options`]: crate::diff::diff_with_options
+pub fn diff_parallel(left: &XmlNode, right: &XmlNode, opts: &DiffOptions) -> Vec<DiffEntry> {
+    let mut out = Vec::new();
+    let root_path = left.tag.clone();
+    let scope = DiffScope::new(opts, left, right);
+    diff_node_with(left, right, &root_path, 0, &scope, &mut out, diff_sections);
+    if opts.detect_relocations {
+        out = pair_relocations(out, opts);
+    }
+    out
+}
+
+fn diff_sections(
+    left: &XmlNode,
+    right: &XmlNode,
+    path: &str,
+    depth: i32,
+    scope: &DiffScope<'_>,
+    out: &mut Vec<DiffEntry>,
+) {
+    let sections: Vec<Vec<DiffEntry>> = child_groups(left, right)
+        .into_par_iter()
+        .map(|group| {
+            let mut section_out = Vec::new();
+            diff_group(group, path, depth, scope, &mut section_out);
+            section_out
+        })
+        .collect();
+    out.extend(sections.into_iter().flatten());
+}
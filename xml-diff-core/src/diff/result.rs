@@ -8,11 +8,29 @@ use crate::XmlNode;
 pub enum DiffEntry {
     /// Node exists in both with identical content.
     Identical { path: String },
-    /// Node exists in both but text/attributes differ.
+    /// Node exists in both but its text (or, with `compare_comments`, its
+    /// comments) differs. Attribute differences are reported separately as
+    /// [`DiffEntry::AttributeChanged`].
     Modified {
         path: String,
+        /// Display form of the left side.
         left: String,
+        /// Display form of the right side.
         right: String,
+        /// Trimmed left text; `None` when empty or absent.
+        left_text: Option<String>,
+        /// Trimmed right text; `None` when empty or absent.
+        right_text: Option<String>,
+    },
+    /// Attribute `name` of a node present in both differs, or exists on one
+    /// side only (`None` on the other). `path` is the node path followed by
+    /// `@name`, so `ignore_paths` can name one attribute everywhere
+    /// (`@version`) or at one node.
+    AttributeChanged {
+        path: String,
+        name: String,
+        left: Option<String>,
+        right: Option<String>,
     },
     /// Node only in the left input.
     OnlyLeft { path: String, node: XmlNode },
@@ -20,4 +38,23 @@ pub enum DiffEntry {
     OnlyRight { path: String, node: XmlNode },
     /// Structural mismatch (for example, node tag mismatch).
     Structural { path: String, description: String },
+    /// Node of an ordered tag (see [`DiffOptions::ordered_tags`]) exists in
+    /// both, but its position among same-tag siblings changed. Positions are
+    /// 1-based. Content differences are reported separately.
+    ///
+    /// [`DiffOptions::ordered_tags`]: crate::DiffOptions::ordered_tags
+    Moved {
+        path: String,
+        from: usize,
+        to: usize,
+    },
+    /// Node removed at `from` and added with the same content at `to`, under
+    /// a different parent. Only reported with
+    /// [`DiffOptions::detect_relocations`]; it replaces the
+    /// [`DiffEntry::OnlyLeft`]/[`DiffEntry::OnlyRight`] entries that cover
+    /// exactly the moved node, while entries for an enclosing removed or
+    /// added container are kept.
+    ///
+    /// [`DiffOptions::detect_relocations`]: crate::DiffOptions::detect_relocations
+    Relocated { from: String, to: String },
 }
@@ -6,16 +6,33 @@ pub fn format_text(entries: &[DiffEntry]) -> String {
     for entry in entries {
         match entry {
             DiffEntry::Identical { path } => lines.push(format!("= {path}")),
-            DiffEntry::Modified { path, left, right } => {
+            DiffEntry::Modified {
+                path, left, right, ..
+            } => {
                 lines.push(format!("~ {path}"));
                 lines.push(format!("  left:  {left}"));
                 lines.push(format!("  right: {right}"));
             }
+            DiffEntry::AttributeChanged {
+                path, left, right, ..
+            } => {
+                let show = |v: &Option<String>| {
+                    v.as_deref()
+                        .map_or_else(|| "(absent)".to_string(), |v| format!("{v:?}"))
+                };
+                lines.push(format!("@ {path}: {} -> {}", show(left), show(right)));
+            }
             DiffEntry::OnlyLeft { path, .. } => lines.push(format!("- {path}")),
             DiffEntry::OnlyRight { path, .. } => lines.push(format!("+ {path}")),
             DiffEntry::Structural { path, description } => {
                 lines.push(format!("! {path}: {description}"));
             }
+            DiffEntry::Moved { path, from, to } => {
+                lines.push(format!("> {path}: moved {from} -> {to}"));
+            }
+            DiffEntry::Relocated { from, to } => {
+                lines.push(format!("> {to}: relocated from {from}"));
+            }
         }
     }
     lines.join("\n")
@@ -28,6 +45,9 @@ pub fn format_summary(entries: &[DiffEntry]) -> String {
     let mut only_left = 0;
     let mut only_right = 0;
     let mut structural = 0;
+    let mut moved = 0;
+    let mut attributes = 0;
+    let mut relocated = 0;
 
     for entry in entries {
         match entry {
@@ -36,10 +56,13 @@ pub fn format_summary(entries: &[DiffEntry]) -> String {
             DiffEntry::OnlyLeft { .. } => only_left += 1,
             DiffEntry::OnlyRight { .. } => only_right += 1,
             DiffEntry::Structural { .. } => structural += 1,
+            DiffEntry::Moved { .. } => moved += 1,
+            DiffEntry::AttributeChanged { .. } => attributes += 1,
+            DiffEntry::Relocated { .. } => relocated += 1,
         }
     }
 
     format!(
-        "identical={identical} modified={modified} only_left={only_left} only_right={only_right} structural={structural}"
+        "identical={identical} modified={modified} only_left={only_left} only_right={only_right} structural={structural} moved={moved} attributes={attributes} relocated={relocated}"
     )
 }
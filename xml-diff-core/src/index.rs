@@ -0,0 +1,119 @@
+//! Tag index over a node's children.
+//!
+//! [`XmlNode::get_child`] and [`XmlNode::get_children`] scan every child on
+//! each call, which adds up when a caller looks up many tags or keys under a
+//! large parent (a `<filter>` with thousands of rules, the document root).
+//! [`ChildIndex`] scans once and answers later lookups from a map.
+//!
+//! The index borrows the node, so the tree cannot change while it is alive
+//! and the positions it holds never go stale. Build it after the last
+//! mutation and drop it before the next one.
+
+use std::collections::HashMap;
+
+use crate::XmlNode;
+
+/// Read-only view of a node's children grouped by tag.
+#[derive(Debug, Clone)]
+pub struct ChildIndex<'a> {
+    node: &'a XmlNode,
+    tags: Vec<&'a str>,
+    positions: HashMap<&'a str, Vec<usize>>,
+}
+
+impl<'a> ChildIndex<'a> {
+    /// Index the children of `node`.
+    pub fn new(node: &'a XmlNode) -> Self {
+        let mut tags = Vec::new();
+        let mut positions: HashMap<&'a str, Vec<usize>> = HashMap::new();
+        for (idx, child) in node.children.iter().enumerate() {
+            let slots = positions.entry(child.tag.as_str()).or_default();
+            if slots.is_empty() {
+                tags.push(child.tag.as_str());
+            }
+            slots.push(idx);
+        }
+        Self {
+            node,
+            tags,
+            positions,
+        }
+    }
+
+    /// The indexed node.
+    pub fn node(&self) -> &'a XmlNode {
+        self.node
+    }
+
+    /// Distinct child tags in first-seen order.
+    pub fn tags(&self) -> &[&'a str] {
+        &self.tags
+    }
+
+    /// Positions in `children` of the children with `tag`, in document order.
+    pub fn positions(&self, tag: &str) -> &[usize] {
+        self.positions.get(tag).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Return the first child with the provided tag.
+    pub fn get_child(&self, tag: &str) -> Option<&'a XmlNode> {
+        let idx = *self.positions(tag).first()?;
+        Some(&self.node.children[idx])
+    }
+
+    /// Return all children with the provided tag.
+    pub fn get_children(&self, tag: &str) -> Vec<&'a XmlNode> {
+        self.positions(tag)
+            .iter()
+            .map(|idx| &self.node.children[*idx])
+            .collect()
+    }
+
+    /// Group the children with `tag` by the text of their `key` child.
+    ///
+    /// Children without the key are left out; positions under one key keep
+    /// document order.
+    pub fn keyed(&self, tag: &str, key: &str) -> HashMap<&'a str, Vec<usize>> {
+        let mut out: HashMap<&'a str, Vec<usize>> = HashMap::new();
+        for idx in self.positions(tag) {
+            if let Some(value) = self.node.children[*idx].get_text(&[key]) {
+                out.entry(value).or_default().push(*idx);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    #[test]
+    fn answers_lookups_like_the_node() {
+        let root = parse(
+            br#"<filter><rule><tracker>1</tracker></rule><separator/><rule><tracker>2</tracker></rule><rule/></filter>"#,
+        )
+        .expect("parse");
+        let index = root.index();
+
+        assert_eq!(index.tags(), ["rule", "separator"]);
+        assert_eq!(index.positions("rule"), [0, 2, 3]);
+        assert!(index.positions("missing").is_empty());
+        assert_eq!(index.get_child("separator"), root.get_child("separator"));
+        assert_eq!(index.get_children("rule"), root.get_children("rule"));
+        assert!(index.get_child("missing").is_none());
+    }
+
+    #[test]
+    fn groups_children_by_key_text() {
+        let root = parse(
+            br#"<aliases><alias><name>a</name></alias><alias><name>b</name></alias><alias><name>a</name></alias><alias/></aliases>"#,
+        )
+        .expect("parse");
+
+        let keyed = root.index().keyed("alias", "name");
+        assert_eq!(keyed.len(), 2);
+        assert_eq!(keyed["a"], [0, 2]);
+        assert_eq!(keyed["b"], [1]);
+    }
+}
@@ -2,12 +2,24 @@
 
 pub mod diff;
 pub mod format;
+pub mod index;
 pub mod parser;
+pub mod query;
 pub mod tree;
 pub mod writer;
 
+#[cfg(feature = "parallel")]
+pub use diff::diff_parallel;
 pub use diff::{diff, diff_with_options, DiffEntry, DiffOptions};
 pub use format::{format_json, format_summary, format_text};
-pub use parser::{parse, parse_file, ParseError};
-pub use tree::XmlNode;
-pub use writer::{write, write_file, WriteError};
+pub use index::ChildIndex;
+pub use parser::{
+    parse, parse_file, parse_file_with_options, parse_with_options, ParseError, ParseOptions,
+    ParseOutcome, ParseWarning,
+};
+pub use query::{PathQuery, QueryError};
+pub use tree::{MiscKind, XmlMisc, XmlNode};
+pub use writer::{
+    write, write_file, write_file_with_options, write_with_options, ChildOrder, EmptyElementStyle,
+    WriteError, WriteOptions,
+};
@@ -1,12 +1,16 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
 use std::fs;
 use std::path::Path;
 
-use quick_xml::events::Event;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::name::QName;
 use quick_xml::Reader;
+use serde::Serialize;
 use thiserror::Error;
 
-use crate::tree::XmlNode;
+use crate::tree::{XmlMisc, XmlNode};
 
 /// Errors that can occur while parsing XML into an [`XmlNode`] tree.
 #[derive(Debug, Error)]
@@ -28,83 +32,161 @@ pub enum ParseError {
     Malformed(String),
 }
 
+/// Parser behaviour for [`parse_with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Recover from damage that leaves the tree usable instead of failing,
+    /// recording each recovery as a [`ParseWarning`]:
+    ///
+    /// - UTF-16 input is converted, and bytes that are not valid UTF-8 are
+    ///   read as Windows-1252 (common in hand-edited descriptions)
+    /// - unknown entities and bare `&` are kept as literal text
+    /// - a start tag missing its end tag is closed at its parent's end tag,
+    ///   and end tags matching nothing are ignored
+    /// - truncated input is cut at the last complete markup and open
+    ///   elements are closed
+    /// - elements after the root element are dropped
+    ///
+    /// Input with no root element still fails.
+    pub lenient: bool,
+    /// Convert non-UTF-8 input the way `lenient` does, without the other
+    /// recoveries. Implied by `lenient`.
+    pub repair_encoding: bool,
+}
+
+/// A problem lenient parsing recovered from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseWarning {
+    /// Byte offset in the (UTF-8) input near the problem.
+    pub position: u64,
+    /// What was wrong and how it was handled.
+    pub message: String,
+    /// Element the problem was in, as `root.tag[n]` segments (1-based per
+    /// tag, like diff paths), when it is tied to one element.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "byte {} ({path}): {}", self.position, self.message),
+            None => write!(f, "byte {}: {}", self.position, self.message),
+        }
+    }
+}
+
+/// Parsed tree plus the problems recovered from while building it.
+#[derive(Debug, Clone)]
+pub struct ParseOutcome {
+    /// Document root.
+    pub root: XmlNode,
+    /// Recovered problems in document order; always empty in strict mode.
+    pub warnings: Vec<ParseWarning>,
+}
+
 /// Parse XML bytes into an [`XmlNode`] tree.
 pub fn parse(xml: &[u8]) -> Result<XmlNode, ParseError> {
-    let mut reader = Reader::from_reader(xml);
+    parse_with_options(xml, &ParseOptions::default()).map(|outcome| outcome.root)
+}
+
+/// Parse XML bytes with custom options.
+pub fn parse_with_options(xml: &[u8], opts: &ParseOptions) -> Result<ParseOutcome, ParseError> {
+    let mut warnings = Vec::new();
+    let mut repaired = Vec::new();
+    let input = if opts.lenient || opts.repair_encoding {
+        decode_lenient(xml, &mut warnings, &mut repaired)
+    } else {
+        Cow::Borrowed(xml)
+    };
+
+    let mut reader = Reader::from_reader(input.as_ref());
     reader.config_mut().trim_text(false);
+    if opts.lenient {
+        reader.config_mut().check_end_names = false;
+        reader.config_mut().allow_unmatched_ends = true;
+    }
 
+    let mut builder = TreeBuilder {
+        lenient: opts.lenient,
+        stack: Vec::new(),
+        root: None,
+        prolog: Vec::new(),
+        warnings,
+        repaired,
+    };
     let mut buf = Vec::new();
-    let mut stack: Vec<XmlNode> = Vec::new();
-    let mut root: Option<XmlNode> = None;
 
     loop {
-        match reader.read_event_into(&mut buf)? {
+        let start = reader.buffer_position();
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(event) => event,
+            // Every syntax error means the input ended inside markup.
+            Err(quick_xml::Error::Syntax(err)) if opts.lenient => {
+                builder.warn(
+                    reader.error_position(),
+                    format!("input is truncated ({err}); the incomplete markup was dropped"),
+                );
+                break;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let position = reader.buffer_position();
+        match event {
             Event::Start(e) => {
-                let node = build_node_start(&e, &reader)?;
-                stack.push(node);
+                let node = builder.node_start(&e, &reader, position)?;
+                builder.note_repairs(start, position, Some(&node.tag), "start tag");
+                builder.stack.push(node);
             }
             Event::Empty(e) => {
-                let node = build_node_start(&e, &reader)?;
-                if let Some(parent) = stack.last_mut() {
-                    parent.children.push(node);
-                } else if root.is_none() {
-                    root = Some(node);
-                } else {
-                    return Err(ParseError::Malformed(
-                        "multiple top-level elements found".to_string(),
-                    ));
-                }
+                let node = builder.node_start(&e, &reader, position)?;
+                builder.note_repairs(start, position, Some(&node.tag), "start tag");
+                builder.attach(node, position)?;
             }
             Event::Text(e) => {
-                if let Some(current) = stack.last_mut() {
-                    let text = e.unescape()?.into_owned();
-                    if !text.trim().is_empty() {
-                        match &mut current.text {
-                            Some(existing) => existing.push_str(&text),
-                            None => current.text = Some(text),
+                if !builder.stack.is_empty() {
+                    let text = match e.unescape() {
+                        Ok(text) => text.into_owned(),
+                        Err(err) if opts.lenient => {
+                            let raw = std::str::from_utf8(e.as_ref())?;
+                            builder.warn(
+                                position,
+                                format!("{err}; kept the reference as literal text"),
+                            );
+                            unescape_lenient(raw)
                         }
-                    }
+                        Err(err) => return Err(err.into()),
+                    };
+                    builder.note_repairs(start, position, None, "text");
+                    builder.push_text(text, false);
                 }
             }
             Event::CData(e) => {
-                if let Some(current) = stack.last_mut() {
+                if !builder.stack.is_empty() {
                     let text = std::str::from_utf8(e.as_ref())?.to_string();
-                    if !text.trim().is_empty() {
-                        match &mut current.text {
-                            Some(existing) => existing.push_str(&text),
-                            None => current.text = Some(text),
-                        }
-                    }
+                    builder.note_repairs(start, position, None, "text");
+                    builder.push_text(text, true);
                 }
             }
-            Event::End(_) => {
-                let node = stack.pop().ok_or_else(|| {
-                    ParseError::Malformed("encountered closing tag without open tag".to_string())
-                })?;
-
-                if let Some(parent) = stack.last_mut() {
-                    parent.children.push(node);
-                } else if root.is_none() {
-                    root = Some(node);
-                } else {
-                    return Err(ParseError::Malformed(
-                        "multiple top-level elements found".to_string(),
-                    ));
-                }
+            Event::End(e) => {
+                let name = qname_to_string(e.name())?;
+                builder.close(&name, position)?;
+            }
+            Event::Comment(e) => {
+                let content = std::str::from_utf8(e.as_ref())?;
+                builder.push_misc(XmlMisc::comment(content, 0));
+            }
+            Event::PI(e) => {
+                let content = std::str::from_utf8(e.as_ref())?;
+                builder.push_misc(XmlMisc::processing_instruction(content, 0));
             }
             Event::Eof => break,
-            Event::Decl(_) | Event::PI(_) | Event::DocType(_) | Event::Comment(_) => {}
+            Event::Decl(_) | Event::DocType(_) => {}
         }
         buf.clear();
     }
 
-    if !stack.is_empty() {
-        return Err(ParseError::Malformed(
-            "unclosed element(s) at end of document".to_string(),
-        ));
-    }
-
-    root.ok_or_else(|| ParseError::Malformed("no root element found".to_string()))
+    builder.finish(reader.buffer_position())
 }
 
 /// Parse an XML file into an [`XmlNode`] tree.
@@ -113,23 +195,345 @@ pub fn parse_file(path: &Path) -> Result<XmlNode, ParseError> {
     parse(&bytes)
 }
 
-fn build_node_start(
-    e: &quick_xml::events::BytesStart<'_>,
-    reader: &Reader<&[u8]>,
-) -> Result<XmlNode, ParseError> {
-    let tag = qname_to_string(e.name())?;
-    let mut node = XmlNode::new(tag);
+/// Parse an XML file with custom options.
+pub fn parse_file_with_options(
+    path: &Path,
+    opts: &ParseOptions,
+) -> Result<ParseOutcome, ParseError> {
+    let bytes = fs::read(path)?;
+    parse_with_options(&bytes, opts)
+}
+
+/// Element stack and results while events are read.
+struct TreeBuilder {
+    lenient: bool,
+    stack: Vec<XmlNode>,
+    root: Option<XmlNode>,
+    prolog: Vec<XmlMisc>,
+    warnings: Vec<ParseWarning>,
+    /// Sorted input offsets of characters read as Windows-1252.
+    repaired: Vec<usize>,
+}
+
+impl TreeBuilder {
+    fn warn(&mut self, position: u64, message: String) {
+        self.warnings.push(ParseWarning {
+            position,
+            message,
+            path: None,
+        });
+    }
+
+    /// Warn about Windows-1252 characters in `start..end`, naming the open
+    /// element, or its new child `tag`.
+    fn note_repairs(&mut self, start: u64, end: u64, tag: Option<&str>, what: &str) {
+        let count = |offset: u64| {
+            let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+            self.repaired.partition_point(|r| *r < offset)
+        };
+        let repaired = count(end) - count(start);
+        if repaired == 0 {
+            return;
+        }
+        let path = match tag {
+            Some(tag) => self.child_path(tag),
+            None => self.open_path(),
+        };
+        self.warnings.push(ParseWarning {
+            position: start,
+            message: format!(
+                "{what} has {repaired} byte(s) that are not valid UTF-8; read them as Windows-1252"
+            ),
+            path: Some(path),
+        });
+    }
+
+    /// Path of the innermost open element.
+    fn open_path(&self) -> String {
+        let mut path = String::new();
+        for (depth, node) in self.stack.iter().enumerate() {
+            match depth.checked_sub(1) {
+                Some(parent) => {
+                    let nth = same_tag_count(&self.stack[parent], &node.tag) + 1;
+                    path.push_str(&format!(".{}[{nth}]", node.tag));
+                }
+                None => path.push_str(&node.tag),
+            }
+        }
+        path
+    }
 
-    for attr in e.attributes() {
-        let attr = attr.map_err(quick_xml::Error::from)?;
-        let key = qname_to_string(attr.key)?;
-        let value = attr
-            .decode_and_unescape_value(reader.decoder())?
-            .into_owned();
-        node.attributes.insert(key, value);
+    /// Path of a new element `tag` under the innermost open element.
+    fn child_path(&self, tag: &str) -> String {
+        match self.stack.last() {
+            Some(parent) => {
+                let nth = same_tag_count(parent, tag) + 1;
+                format!("{}.{tag}[{nth}]", self.open_path())
+            }
+            None => tag.to_string(),
+        }
     }
 
-    Ok(node)
+    /// Add a finished element to the open element, or make it the root.
+    fn attach(&mut self, node: XmlNode, position: u64) -> Result<(), ParseError> {
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children.push(node);
+        } else if self.root.is_none() {
+            self.root = Some(node);
+        } else if self.lenient {
+            self.warn(
+                position,
+                format!("dropped <{}> after the root element", node.tag),
+            );
+        } else {
+            return Err(ParseError::Malformed(
+                "multiple top-level elements found".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Close the element an end tag names.
+    ///
+    /// Strict parsing relies on the reader to reject mismatched end tags;
+    /// lenient parsing closes any elements left open inside it.
+    fn close(&mut self, name: &str, position: u64) -> Result<(), ParseError> {
+        let open = if self.lenient {
+            self.stack.iter().rposition(|node| node.tag == name)
+        } else {
+            self.stack.len().checked_sub(1)
+        };
+        let Some(open) = open else {
+            if self.lenient {
+                self.warn(
+                    position,
+                    format!("ignored </{name}> with no matching start tag"),
+                );
+                return Ok(());
+            }
+            return Err(ParseError::Malformed(
+                "encountered closing tag without open tag".to_string(),
+            ));
+        };
+        while self.stack.len() > open + 1 {
+            let node = self.stack.pop().expect("stack is longer than open");
+            self.warn(
+                position,
+                format!("<{}> has no end tag; closed it at </{name}>", node.tag),
+            );
+            self.attach(node, position)?;
+        }
+        let node = self.stack.pop().expect("open is on the stack");
+        self.attach(node, position)
+    }
+
+    fn push_text(&mut self, text: String, cdata: bool) {
+        let Some(current) = self.stack.last_mut() else {
+            return;
+        };
+        if text.trim().is_empty() {
+            return;
+        }
+        // Only text read entirely from CDATA sections is written back as CDATA
+        match &mut current.text {
+            Some(existing) => {
+                existing.push_str(&text);
+                current.cdata &= cdata;
+            }
+            None => {
+                current.text = Some(text);
+                current.cdata = cdata;
+            }
+        }
+    }
+
+    /// Attach a comment/PI to the open element, or to the prolog before the root.
+    ///
+    /// Markup after the root element closes is dropped.
+    fn push_misc(&mut self, mut misc: XmlMisc) {
+        if let Some(current) = self.stack.last_mut() {
+            misc.position = current.children.len();
+            current.misc.push(misc);
+        } else if self.root.is_none() {
+            self.prolog.push(misc);
+        }
+    }
+
+    fn node_start(
+        &mut self,
+        e: &BytesStart<'_>,
+        reader: &Reader<&[u8]>,
+        position: u64,
+    ) -> Result<XmlNode, ParseError> {
+        let tag = qname_to_string(e.name())?;
+        let mut node = XmlNode::new(tag);
+
+        for attr in e.attributes() {
+            let attr = match attr {
+                Ok(attr) => attr,
+                Err(err) if self.lenient => {
+                    self.warn(
+                        position,
+                        format!("skipped malformed attribute on <{}>: {err}", node.tag),
+                    );
+                    continue;
+                }
+                Err(err) => return Err(quick_xml::Error::from(err).into()),
+            };
+            let key = qname_to_string(attr.key)?;
+            let value = match attr.decode_and_unescape_value(reader.decoder()) {
+                Ok(value) => value.into_owned(),
+                Err(err) if self.lenient => {
+                    self.warn(
+                        position,
+                        format!(
+                            "attribute {key} on <{}>: {err}; kept the reference as literal text",
+                            node.tag
+                        ),
+                    );
+                    unescape_lenient(std::str::from_utf8(&attr.value)?)
+                }
+                Err(err) => return Err(err.into()),
+            };
+            node.attributes.insert(key, value);
+        }
+
+        Ok(node)
+    }
+
+    fn finish(mut self, position: u64) -> Result<ParseOutcome, ParseError> {
+        if !self.stack.is_empty() {
+            if !self.lenient {
+                return Err(ParseError::Malformed(
+                    "unclosed element(s) at end of document".to_string(),
+                ));
+            }
+            let open: Vec<String> = self.stack.iter().map(|n| format!("<{}>", n.tag)).collect();
+            self.warn(
+                position,
+                format!("input ends inside {}; closed them", open.join(" ")),
+            );
+            while let Some(node) = self.stack.pop() {
+                self.attach(node, position)?;
+            }
+        }
+
+        let mut root = self
+            .root
+            .ok_or_else(|| ParseError::Malformed("no root element found".to_string()))?;
+        root.prolog = self.prolog;
+        Ok(ParseOutcome {
+            root,
+            warnings: self.warnings,
+        })
+    }
+}
+
+fn same_tag_count(parent: &XmlNode, tag: &str) -> usize {
+    parent.children.iter().filter(|c| c.tag == tag).count()
+}
+
+/// Convert input that is not UTF-8 for lenient parsing.
+///
+/// A UTF-16 byte order mark converts the whole input; otherwise invalid
+/// bytes are read as Windows-1252, the usual encoding of text pasted into
+/// descriptions from other tools. The output offset of each such character
+/// is pushed to `repaired`.
+fn decode_lenient<'a>(
+    xml: &'a [u8],
+    warnings: &mut Vec<ParseWarning>,
+    repaired: &mut Vec<usize>,
+) -> Cow<'a, [u8]> {
+    let utf16 = match xml {
+        [0xFF, 0xFE, rest @ ..] => Some((rest, u16::from_le_bytes as fn([u8; 2]) -> u16)),
+        [0xFE, 0xFF, rest @ ..] => Some((rest, u16::from_be_bytes as fn([u8; 2]) -> u16)),
+        _ => None,
+    };
+    if let Some((rest, unit)) = utf16 {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|pair| unit([pair[0], pair[1]]))
+            .collect();
+        warnings.push(ParseWarning {
+            position: 0,
+            message: "input is UTF-16; converted to UTF-8".to_string(),
+            path: None,
+        });
+        return Cow::Owned(String::from_utf16_lossy(&units).into_bytes());
+    }
+    if std::str::from_utf8(xml).is_ok() {
+        return Cow::Borrowed(xml);
+    }
+
+    let mut out = String::with_capacity(xml.len());
+    for chunk in xml.utf8_chunks() {
+        out.push_str(chunk.valid());
+        for byte in chunk.invalid() {
+            repaired.push(out.len());
+            out.push(windows_1252(*byte));
+        }
+    }
+    warnings.push(ParseWarning {
+        position: repaired.first().copied().unwrap_or_default() as u64,
+        message: format!(
+            "{} byte(s) are not valid UTF-8; read them as Windows-1252",
+            repaired.len()
+        ),
+        path: None,
+    });
+    Cow::Owned(out.into_bytes())
+}
+
+/// Windows-1252 character for a byte that is not valid UTF-8.
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '\u{20AC}', '\u{FFFD}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{FFFD}',
+        '\u{017D}', '\u{FFFD}', '\u{FFFD}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}',
+        '\u{2022}', '\u{2013}', '\u{2014}', '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}',
+        '\u{0153}', '\u{FFFD}', '\u{017E}', '\u{0178}',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+/// Unescape `raw`, keeping unknown or unterminated references as written.
+fn unescape_lenient(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let resolved = rest[1..].find(';').and_then(|end| {
+            let entity = &rest[1..=end];
+            resolve_entity(entity).map(|value| (value, end + 2))
+        });
+        match resolved {
+            Some((value, len)) => {
+                out.push_str(&value);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn resolve_entity(entity: &str) -> Option<String> {
+    if let Some(value) = resolve_predefined_entity(entity) {
+        return Some(value.to_string());
+    }
+    let code = match entity.strip_prefix("#x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => entity.strip_prefix('#')?.parse().ok()?,
+    };
+    char::from_u32(code).map(String::from)
 }
 
 fn qname_to_string(name: QName<'_>) -> Result<String, ParseError> {
@@ -0,0 +1,407 @@
+//! A small path query language for selecting nested nodes.
+//!
+//! Queries are evaluated relative to a root node and are made of `.`-separated
+//! steps, e.g. `interfaces.lan.if`. A step written after `//` matches at any
+//! depth below the current node, e.g. `//staticmap` or `dhcpd//staticmap`.
+//! Each step may carry predicates in brackets:
+//!
+//! - `[5]` selects the fifth matching sibling (1-based)
+//! - `[mac='aa:bb:cc:dd:ee:ff']` requires a child element with that text
+//! - `[@name='value']` requires an attribute with that value
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::tree::XmlNode;
+
+/// Errors that can occur while parsing a [`PathQuery`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum QueryError {
+    /// Query string was empty.
+    #[error("empty path query")]
+    Empty,
+    /// A step had no tag name.
+    #[error("missing tag name at offset {0}")]
+    MissingTag(usize),
+    /// A `[` predicate was not closed.
+    #[error("unterminated predicate starting at offset {0}")]
+    UnterminatedPredicate(usize),
+    /// Predicate content could not be understood.
+    #[error("invalid predicate '[{0}]'")]
+    InvalidPredicate(String),
+}
+
+/// Filter applied to the nodes matched by a step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// Keep only the nth match among siblings (1-based).
+    Index(usize),
+    /// Keep nodes whose named child has exactly this (trimmed) text.
+    ChildText {
+        /// Child tag name.
+        tag: String,
+        /// Expected text.
+        value: String,
+    },
+    /// Keep nodes whose attribute has exactly this value.
+    Attribute {
+        /// Attribute name.
+        name: String,
+        /// Expected value.
+        value: String,
+    },
+}
+
+/// One step of a [`PathQuery`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// Match at any depth below the context node instead of direct children.
+    pub descendant: bool,
+    /// Tag name to match.
+    pub tag: String,
+    /// Predicates applied in order.
+    pub predicates: Vec<Predicate>,
+}
+
+/// A parsed path query such as `filter.rule[5]` or `//staticmap[mac='..']`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathQuery {
+    steps: Vec<Step>,
+}
+
+impl PathQuery {
+    /// Parse a query string.
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(QueryError::Empty);
+        }
+
+        let bytes = input.as_bytes();
+        let mut steps = Vec::new();
+        let mut pos = 0;
+        let mut descendant = false;
+        if input.starts_with("//") {
+            descendant = true;
+            pos = 2;
+        }
+
+        loop {
+            let tag_start = pos;
+            while pos < bytes.len() && !matches!(bytes[pos], b'.' | b'[' | b']' | b'/') {
+                pos += 1;
+            }
+            let tag = input[tag_start..pos].trim();
+            if tag.is_empty() {
+                return Err(QueryError::MissingTag(tag_start));
+            }
+
+            let mut predicates = Vec::new();
+            while pos < bytes.len() && bytes[pos] == b'[' {
+                let close =
+                    find_predicate_end(input, pos).ok_or(QueryError::UnterminatedPredicate(pos))?;
+                predicates.push(parse_predicate(&input[pos + 1..close])?);
+                pos = close + 1;
+            }
+
+            steps.push(Step {
+                descendant,
+                tag: tag.to_string(),
+                predicates,
+            });
+
+            if pos >= bytes.len() {
+                break;
+            }
+            if input[pos..].starts_with("//") {
+                descendant = true;
+                pos += 2;
+            } else if bytes[pos] == b'.' {
+                descendant = false;
+                pos += 1;
+            } else {
+                return Err(QueryError::MissingTag(pos));
+            }
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Steps in evaluation order.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Return all nodes below `root` matched by this query, in document order.
+    pub fn select<'a>(&self, root: &'a XmlNode) -> Vec<&'a XmlNode> {
+        self.matches(root)
+            .into_iter()
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    /// Return the first node matched by this query.
+    pub fn select_first<'a>(&self, root: &'a XmlNode) -> Option<&'a XmlNode> {
+        self.select(root).into_iter().next()
+    }
+
+    /// Return child-index paths from `root` to each matched node.
+    ///
+    /// Paths resolve with [`XmlNode::descendant`] and [`XmlNode::descendant_mut`],
+    /// which lets callers mutate matches without holding borrows into the tree.
+    pub fn locate(&self, root: &XmlNode) -> Vec<Vec<usize>> {
+        self.matches(root)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    fn matches<'a>(&self, root: &'a XmlNode) -> Vec<Located<'a>> {
+        let mut current = vec![(Vec::new(), root)];
+        for step in &self.steps {
+            let mut next = Vec::new();
+            let mut seen = HashSet::new();
+            for (path, node) in current {
+                apply_step(step, node, &path, &mut next, &mut seen);
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+impl FromStr for PathQuery {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for PathQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (idx, step) in self.steps.iter().enumerate() {
+            if step.descendant {
+                write!(f, "//")?;
+            } else if idx > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", step.tag)?;
+            for predicate in &step.predicates {
+                match predicate {
+                    Predicate::Index(n) => write!(f, "[{n}]")?,
+                    Predicate::ChildText { tag, value } => write!(f, "[{tag}='{value}']")?,
+                    Predicate::Attribute { name, value } => write!(f, "[@{name}='{value}']")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find_predicate_end(input: &str, open: usize) -> Option<usize> {
+    let mut quote = None;
+    for (offset, ch) in input[open + 1..].char_indices() {
+        match (quote, ch) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, ']') => return Some(open + 1 + offset),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+fn parse_predicate(body: &str) -> Result<Predicate, QueryError> {
+    let invalid = || QueryError::InvalidPredicate(body.to_string());
+    let trimmed = body.trim();
+
+    if let Ok(index) = trimmed.parse::<usize>() {
+        return if index == 0 {
+            Err(invalid())
+        } else {
+            Ok(Predicate::Index(index))
+        };
+    }
+
+    let (name, value) = trimmed.split_once('=').ok_or_else(invalid)?;
+    let name = name.trim();
+    let value = unquote(value.trim()).ok_or_else(invalid)?;
+    match name.strip_prefix('@') {
+        Some(attr) if !attr.is_empty() => Ok(Predicate::Attribute {
+            name: attr.to_string(),
+            value,
+        }),
+        None if !name.is_empty() => Ok(Predicate::ChildText {
+            tag: name.to_string(),
+            value,
+        }),
+        _ => Err(invalid()),
+    }
+}
+
+fn unquote(raw: &str) -> Option<String> {
+    let quote = raw.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let inner = raw.strip_prefix(quote)?.strip_suffix(quote)?;
+    Some(inner.to_string())
+}
+
+/// A matched node with its child-index path from the query root.
+type Located<'a> = (Vec<usize>, &'a XmlNode);
+
+fn apply_step<'a>(
+    step: &Step,
+    parent: &'a XmlNode,
+    parent_path: &[usize],
+    out: &mut Vec<Located<'a>>,
+    seen: &mut HashSet<Vec<usize>>,
+) {
+    let mut matched: Vec<(usize, &XmlNode)> = parent
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| child.tag == step.tag)
+        .collect();
+    for predicate in &step.predicates {
+        matched = filter_predicate(predicate, matched);
+    }
+    for (idx, node) in matched {
+        let mut path = parent_path.to_vec();
+        path.push(idx);
+        if seen.insert(path.clone()) {
+            out.push((path, node));
+        }
+    }
+
+    if step.descendant {
+        for (idx, child) in parent.children.iter().enumerate() {
+            let mut path = parent_path.to_vec();
+            path.push(idx);
+            apply_step(step, child, &path, out, seen);
+        }
+    }
+}
+
+fn filter_predicate<'a>(
+    predicate: &Predicate,
+    nodes: Vec<(usize, &'a XmlNode)>,
+) -> Vec<(usize, &'a XmlNode)> {
+    match predicate {
+        Predicate::Index(n) => nodes.get(n - 1).copied().into_iter().collect(),
+        Predicate::ChildText { tag, value } => nodes
+            .into_iter()
+            .filter(|(_, node)| {
+                node.get_text(&[tag.as_str()])
+                    .is_some_and(|text| text.trim() == value)
+            })
+            .collect(),
+        Predicate::Attribute { name, value } => nodes
+            .into_iter()
+            .filter(|(_, node)| node.attributes.get(name) == Some(value))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathQuery, Predicate, QueryError};
+    use crate::parse;
+
+    const XML: &[u8] = br#"<pfsense>
+  <interfaces><lan><if>em1</if></lan><wan><if>em0</if></wan></interfaces>
+  <filter><rule><tracker>1</tracker></rule><rule><tracker>2</tracker></rule></filter>
+  <dhcpd>
+    <lan><staticmap><mac>aa:bb:cc:00:00:01</mac></staticmap></lan>
+    <opt1><staticmap><mac>aa:bb:cc:00:00:02</mac></staticmap></opt1>
+  </dhcpd>
+  <cert uuid="c1"><descr>one</descr></cert>
+</pfsense>"#;
+
+    #[test]
+    fn selects_nested_child_path() {
+        let root = parse(XML).expect("parse");
+        let query = PathQuery::parse("interfaces.lan.if").expect("query");
+        let nodes = query.select(&root);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].text.as_deref(), Some("em1"));
+    }
+
+    #[test]
+    fn index_predicate_is_one_based() {
+        let root = parse(XML).expect("parse");
+        let query = PathQuery::parse("filter.rule[2]").expect("query");
+        let node = query.select_first(&root).expect("rule");
+        assert_eq!(node.get_text(&["tracker"]), Some("2"));
+    }
+
+    #[test]
+    fn descendant_step_with_child_text_predicate() {
+        let root = parse(XML).expect("parse");
+        let all = PathQuery::parse("//staticmap").expect("query");
+        assert_eq!(all.select(&root).len(), 2);
+
+        let one = PathQuery::parse("dhcpd//staticmap[mac='aa:bb:cc:00:00:02']").expect("query");
+        let nodes = one.select(&root);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].get_text(&["mac"]), Some("aa:bb:cc:00:00:02"));
+    }
+
+    #[test]
+    fn attribute_predicate_matches() {
+        let root = parse(XML).expect("parse");
+        let query = PathQuery::parse("cert[@uuid=\"c1\"]").expect("query");
+        assert_eq!(
+            query
+                .select_first(&root)
+                .and_then(|n| n.get_text(&["descr"])),
+            Some("one")
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let raw = "//staticmap[mac='a.b'][1]";
+        let query = PathQuery::parse(raw).expect("query");
+        assert_eq!(query.to_string(), raw);
+        assert_eq!(
+            query.steps()[0].predicates[0],
+            Predicate::ChildText {
+                tag: "mac".to_string(),
+                value: "a.b".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn locate_returns_child_index_paths() {
+        let root = parse(XML).expect("parse");
+        let query = PathQuery::parse("filter.rule[2].tracker").expect("query");
+        let paths = query.locate(&root);
+        assert_eq!(paths, vec![vec![1, 1, 0]]);
+        assert_eq!(
+            root.descendant(&paths[0]).and_then(|n| n.text.as_deref()),
+            Some("2")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        assert_eq!(PathQuery::parse(""), Err(QueryError::Empty));
+        assert!(matches!(
+            PathQuery::parse("a..b"),
+            Err(QueryError::MissingTag(_))
+        ));
+        assert!(matches!(
+            PathQuery::parse("rule[1"),
+            Err(QueryError::UnterminatedPredicate(_))
+        ));
+        assert!(matches!(
+            PathQuery::parse("rule[0]"),
+            Err(QueryError::InvalidPredicate(_))
+        ));
+    }
+}
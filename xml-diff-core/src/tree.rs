@@ -3,6 +3,52 @@ use std::fmt::{self, Display, Formatter};
 
 use serde::Serialize;
 
+use crate::index::ChildIndex;
+
+/// Kind of non-element markup preserved alongside element children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MiscKind {
+    /// `<!-- ... -->`
+    Comment,
+    /// `<?target ...?>`
+    ProcessingInstruction,
+}
+
+/// A comment or processing instruction preserved inside an element.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct XmlMisc {
+    /// Markup kind.
+    pub kind: MiscKind,
+    /// Raw content between the delimiters, without `<!--`/`-->` or `<?`/`?>`.
+    pub content: String,
+    /// Number of element children that precede this item.
+    ///
+    /// The writer emits the item just before the child at this index, or after
+    /// the last child when the index is past the end.
+    pub position: usize,
+}
+
+impl XmlMisc {
+    /// Create a comment positioned before the child at `position`.
+    pub fn comment(content: impl Into<String>, position: usize) -> Self {
+        Self {
+            kind: MiscKind::Comment,
+            content: content.into(),
+            position,
+        }
+    }
+
+    /// Create a processing instruction positioned before the child at `position`.
+    pub fn processing_instruction(content: impl Into<String>, position: usize) -> Self {
+        Self {
+            kind: MiscKind::ProcessingInstruction,
+            content: content.into(),
+            position,
+        }
+    }
+}
+
 /// A generic XML tree node.
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct XmlNode {
@@ -14,6 +60,17 @@ pub struct XmlNode {
     pub children: Vec<XmlNode>,
     /// Optional text content.
     pub text: Option<String>,
+    /// Whether `text` was read from (and is written as) a CDATA section.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cdata: bool,
+    /// Comments and processing instructions found inside this element.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub misc: Vec<XmlMisc>,
+    /// Comments and processing instructions before the root element.
+    ///
+    /// Only populated on the document root. The XML declaration is not kept.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub prolog: Vec<XmlMisc>,
 }
 
 impl XmlNode {
@@ -24,9 +81,20 @@ impl XmlNode {
             attributes: BTreeMap::new(),
             children: Vec::new(),
             text: None,
+            cdata: false,
+            misc: Vec::new(),
+            prolog: Vec::new(),
         }
     }
 
+    /// Iterate comments inside this element in document order.
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.misc
+            .iter()
+            .filter(|m| m.kind == MiscKind::Comment)
+            .map(|m| m.content.as_str())
+    }
+
     /// Return the first child with the provided tag.
     pub fn get_child(&self, tag: &str) -> Option<&XmlNode> {
         self.children.iter().find(|child| child.tag == tag)
@@ -40,6 +108,26 @@ impl XmlNode {
             .collect()
     }
 
+    /// Build a [`ChildIndex`] for repeated lookups among this node's children.
+    pub fn index(&self) -> ChildIndex<'_> {
+        ChildIndex::new(self)
+    }
+
+    /// Follow child indices from this node, as returned by
+    /// [`PathQuery::locate`](crate::query::PathQuery::locate).
+    pub fn descendant(&self, indices: &[usize]) -> Option<&XmlNode> {
+        indices
+            .iter()
+            .try_fold(self, |node, idx| node.children.get(*idx))
+    }
+
+    /// Mutable variant of [`XmlNode::descendant`].
+    pub fn descendant_mut(&mut self, indices: &[usize]) -> Option<&mut XmlNode> {
+        indices
+            .iter()
+            .try_fold(self, |node, idx| node.children.get_mut(*idx))
+    }
+
     /// Walk a nested child path and return terminal node text if found.
     pub fn get_text<'a>(&'a self, path: &[&str]) -> Option<&'a str> {
         if path.is_empty() {
@@ -1,11 +1,12 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesPI, BytesStart, BytesText, Event};
 use quick_xml::Writer;
 use thiserror::Error;
 
-use crate::tree::XmlNode;
+use crate::tree::{MiscKind, XmlMisc, XmlNode};
 
 /// Errors that can occur while writing XML from an [`XmlNode`] tree.
 #[derive(Debug, Error)]
@@ -18,42 +19,325 @@ pub enum WriteError {
     Io(#[from] std::io::Error),
 }
 
+/// How to serialize elements with no children and no text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyElementStyle {
+    /// `<tag/>`
+    #[default]
+    SelfClosing,
+    /// `<tag></tag>`
+    Expanded,
+}
+
+/// Child ordering rules keyed by element path.
+///
+/// Paths are dot-joined tag names from the root without indices, for example
+/// `opnsense` for top-level sections or `opnsense.system` for system settings.
+/// Children of a parent with a rule are stably sorted: listed tags first in
+/// rule order, then unlisted tags alphabetically. Repeated siblings with the
+/// same tag always keep their relative order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChildOrder {
+    rules: HashMap<String, Vec<String>>,
+}
+
+impl ChildOrder {
+    /// Create an empty rule set (no reordering).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the ordering rule for children of `path`.
+    pub fn with_rule<I, S>(mut self, path: impl Into<String>, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rules
+            .insert(path.into(), tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Return the ordering rule for children of `path`, if any.
+    pub fn rule(&self, path: &str) -> Option<&[String]> {
+        self.rules.get(path).map(Vec::as_slice)
+    }
+
+    /// Whether no rules are configured.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// Serialization options for [`write_with_options`].
+///
+/// Attributes are always written in name order, since [`XmlNode`] stores
+/// them in a sorted map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Indentation character (typically `b' '` or `b'\t'`).
+    pub indent_char: u8,
+    /// Indentation width per nesting level. `0` writes the document on one line.
+    pub indent_size: usize,
+    /// Serialization of empty elements.
+    pub empty_elements: EmptyElementStyle,
+    /// Child ordering rules applied while writing.
+    pub child_order: ChildOrder,
+    /// Emit an `<?xml version="1.0"?>` declaration before the root element.
+    pub xml_declaration: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            indent_char: b' ',
+            indent_size: 2,
+            empty_elements: EmptyElementStyle::SelfClosing,
+            child_order: ChildOrder::new(),
+            xml_declaration: false,
+        }
+    }
+}
+
 /// Serialize an [`XmlNode`] tree into XML bytes.
 pub fn write(node: &XmlNode) -> Result<Vec<u8>, WriteError> {
-    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
-    write_node(&mut writer, node)?;
+    write_with_options(node, &WriteOptions::default())
+}
+
+/// Serialize an [`XmlNode`] tree into XML bytes using `opts`.
+pub fn write_with_options(node: &XmlNode, opts: &WriteOptions) -> Result<Vec<u8>, WriteError> {
+    let mut writer = if opts.indent_size == 0 {
+        Writer::new(Vec::new())
+    } else {
+        Writer::new_with_indent(Vec::new(), opts.indent_char, opts.indent_size)
+    };
+    if opts.xml_declaration {
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", None, None)))?;
+    }
+    for misc in &node.prolog {
+        write_misc(&mut writer, misc)?;
+    }
+    write_node(&mut writer, node, &node.tag, opts)?;
     Ok(writer.into_inner())
 }
 
 /// Serialize an [`XmlNode`] tree and write it to `path`.
 pub fn write_file(node: &XmlNode, path: &Path) -> Result<(), WriteError> {
-    let bytes = write(node)?;
+    write_file_with_options(node, path, &WriteOptions::default())
+}
+
+/// Serialize an [`XmlNode`] tree using `opts` and write it to `path`.
+pub fn write_file_with_options(
+    node: &XmlNode,
+    path: &Path,
+    opts: &WriteOptions,
+) -> Result<(), WriteError> {
+    let bytes = write_with_options(node, opts)?;
     fs::write(path, bytes)?;
     Ok(())
 }
 
-fn write_node(writer: &mut Writer<Vec<u8>>, node: &XmlNode) -> Result<(), quick_xml::Error> {
+fn write_node(
+    writer: &mut Writer<Vec<u8>>,
+    node: &XmlNode,
+    path: &str,
+    opts: &WriteOptions,
+) -> Result<(), quick_xml::Error> {
     let mut start = BytesStart::new(node.tag.as_str());
 
     for (key, value) in &node.attributes {
         start.push_attribute((key.as_str(), value.as_str()));
     }
 
-    if node.children.is_empty() && node.text.is_none() {
-        writer.write_event(Event::Empty(start))?;
+    if node.children.is_empty() && node.text.is_none() && node.misc.is_empty() {
+        match opts.empty_elements {
+            EmptyElementStyle::SelfClosing => {
+                writer.write_event(Event::Empty(start))?;
+            }
+            EmptyElementStyle::Expanded => {
+                writer.write_event(Event::Start(start))?;
+                // An empty text event keeps the end tag on the same line.
+                writer.write_event(Event::Text(BytesText::new("")))?;
+                writer.write_event(Event::End(BytesEnd::new(node.tag.as_str())))?;
+            }
+        }
         return Ok(());
     }
 
     writer.write_event(Event::Start(start))?;
 
     if let Some(text) = &node.text {
-        writer.write_event(Event::Text(BytesText::new(text)))?;
+        if node.cdata {
+            write_cdata(writer, text)?;
+        } else {
+            writer.write_event(Event::Text(BytesText::new(text)))?;
+        }
     }
 
-    for child in &node.children {
-        write_node(writer, child)?;
+    let (groups, trailing) = ordered_children(node, path, &opts.child_order);
+    for (leading, child) in groups {
+        for misc in leading {
+            write_misc(writer, misc)?;
+        }
+        let child_path = format!("{path}.{}", child.tag);
+        write_node(writer, child, &child_path, opts)?;
+    }
+    for misc in trailing {
+        write_misc(writer, misc)?;
     }
 
     writer.write_event(Event::End(BytesEnd::new(node.tag.as_str())))?;
     Ok(())
 }
+
+/// Children paired with the comments/PIs that precede them.
+type ChildGroup<'a> = (Vec<&'a XmlMisc>, &'a XmlNode);
+
+/// Return children in write order plus misc items that follow the last child.
+///
+/// Comments and PIs travel with the child they precede, so reordering keeps a
+/// comment next to the element it describes.
+fn ordered_children<'a>(
+    node: &'a XmlNode,
+    path: &str,
+    order: &ChildOrder,
+) -> (Vec<ChildGroup<'a>>, Vec<&'a XmlMisc>) {
+    let mut groups: Vec<ChildGroup<'a>> = node
+        .children
+        .iter()
+        .map(|child| (Vec::new(), child))
+        .collect();
+    let mut trailing = Vec::new();
+    for misc in &node.misc {
+        match groups.get_mut(misc.position) {
+            Some((leading, _)) => leading.push(misc),
+            None => trailing.push(misc),
+        }
+    }
+
+    if let Some(rule) = order.rule(path) {
+        groups.sort_by_key(
+            |(_, child)| match rule.iter().position(|tag| tag == &child.tag) {
+                Some(rank) => (rank, ""),
+                None => (rule.len(), child.tag.as_str()),
+            },
+        );
+    }
+    (groups, trailing)
+}
+
+fn write_misc(writer: &mut Writer<Vec<u8>>, misc: &XmlMisc) -> Result<(), quick_xml::Error> {
+    match misc.kind {
+        MiscKind::Comment => writer.write_event(Event::Comment(BytesText::from_escaped(
+            misc.content.as_str(),
+        ))),
+        MiscKind::ProcessingInstruction => {
+            writer.write_event(Event::PI(BytesPI::new(misc.content.as_str())))
+        }
+    }
+}
+
+/// Write `text` as CDATA, splitting around any `]]>` so the output stays valid.
+fn write_cdata(writer: &mut Writer<Vec<u8>>, text: &str) -> Result<(), quick_xml::Error> {
+    let mut rest = text;
+    while let Some(idx) = rest.find("]]>") {
+        let (head, tail) = rest.split_at(idx + 2);
+        writer.write_event(Event::CData(BytesCData::new(head)))?;
+        rest = tail;
+    }
+    writer.write_event(Event::CData(BytesCData::new(rest)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write, write_with_options, ChildOrder, EmptyElementStyle, WriteOptions};
+    use crate::parse;
+
+    #[test]
+    fn child_order_sorts_listed_then_unlisted_and_keeps_repeats_stable() {
+        let node = parse(
+            br#"<root><zeta/><rule><id>1</id></rule><system/><alpha/><rule><id>2</id></rule></root>"#,
+        )
+        .expect("parse");
+        let opts = WriteOptions {
+            indent_size: 0,
+            child_order: ChildOrder::new().with_rule("root", ["system", "rule"]),
+            ..WriteOptions::default()
+        };
+
+        let out =
+            String::from_utf8(write_with_options(&node, &opts).expect("write")).expect("utf8");
+        assert_eq!(
+            out,
+            "<root><system/><rule><id>1</id></rule><rule><id>2</id></rule><alpha/><zeta/></root>"
+        );
+    }
+
+    #[test]
+    fn expanded_empty_elements_and_declaration() {
+        let node = parse(br#"<root><empty/><item>x</item></root>"#).expect("parse");
+        let opts = WriteOptions {
+            indent_char: b'\t',
+            indent_size: 1,
+            empty_elements: EmptyElementStyle::Expanded,
+            xml_declaration: true,
+            ..WriteOptions::default()
+        };
+
+        let out =
+            String::from_utf8(write_with_options(&node, &opts).expect("write")).expect("utf8");
+        assert_eq!(
+            out,
+            "<?xml version=\"1.0\"?>\n<root>\n\t<empty></empty>\n\t<item>x</item>\n</root>"
+        );
+    }
+
+    #[test]
+    fn comments_cdata_and_pis_round_trip() {
+        let xml = br#"<?xml version="1.0"?><?xml-stylesheet href="a.xsl"?><!-- head --><root><!-- first --><a><![CDATA[x < y ]]]]><![CDATA[> z]]></a><?php echo 1; ?><b/><!-- tail --></root>"#;
+        let node = parse(xml).expect("parse");
+        assert_eq!(node.prolog.len(), 2);
+        assert_eq!(
+            node.comments().collect::<Vec<_>>(),
+            vec![" first ", " tail "]
+        );
+        let a = node.get_child("a").expect("a");
+        assert!(a.cdata);
+        assert_eq!(a.text.as_deref(), Some("x < y ]]> z"));
+
+        let out = write(&node).expect("write");
+        let text = String::from_utf8(out.clone()).expect("utf8");
+        assert!(text.contains("<![CDATA[x < y ]]]]><![CDATA[> z]]>"));
+        assert!(text.contains("<?php echo 1; ?>"));
+        assert_eq!(parse(&out).expect("reparse"), node);
+    }
+
+    #[test]
+    fn mixed_text_and_cdata_is_written_as_plain_text() {
+        let node = parse(br#"<root><a>x &amp; <![CDATA[<y>]]></a><b><![CDATA[1]]> 2</b></root>"#)
+            .expect("parse");
+        for tag in ["a", "b"] {
+            assert!(!node.get_child(tag).expect("child").cdata);
+        }
+        assert_eq!(node.get_text(&["a"]), Some("x & <y>"));
+
+        let out = String::from_utf8(write(&node).expect("write")).expect("utf8");
+        assert!(!out.contains("CDATA"));
+        assert!(out.contains("<a>x &amp; &lt;y&gt;</a>"));
+        assert_eq!(parse(out.as_bytes()).expect("reparse"), node);
+    }
+
+    #[test]
+    fn comments_follow_their_element_when_reordered() {
+        let node = parse(br#"<root><!-- about b --><b/><a/></root>"#).expect("parse");
+        let opts = WriteOptions {
+            indent_size: 0,
+            child_order: ChildOrder::new().with_rule("root", ["a", "b"]),
+            ..WriteOptions::default()
+        };
+
+        let out =
+            String::from_utf8(write_with_options(&node, &opts).expect("write")).expect("utf8");
+        assert_eq!(out, "<root><a/><!-- about b --><b/></root>");
+    }
+}
@@ -1,8 +1,8 @@
 use std::path::PathBuf;
 
 use xml_diff_core::{
-    diff, diff_with_options, format_json, format_summary, format_text, parse_file, DiffEntry,
-    DiffOptions,
+    diff, diff_with_options, format_json, format_summary, format_text, parse, parse_file,
+    DiffEntry, DiffOptions, XmlNode,
 };
 
 fn fixture(path: &str) -> PathBuf {
@@ -50,3 +50,115 @@ fn ignore_paths_skips_version_differences() {
         _ => false,
     }));
 }
+
+#[test]
+fn comments_are_ignored_unless_requested() {
+    let left = parse(b"<root><!-- old --><a>1</a></root>").expect("left parse");
+    let right = parse(b"<root><!-- new --><a>1</a></root>").expect("right parse");
+
+    assert!(diff(&left, &right).is_empty());
+
+    let opts = DiffOptions {
+        compare_comments: true,
+        ..DiffOptions::default()
+    };
+    let entries = diff_with_options(&left, &right, &opts);
+    assert!(matches!(
+        entries.as_slice(),
+        [DiffEntry::Modified { path, .. }] if path == "root"
+    ));
+}
+
+#[test]
+fn attribute_changes_are_reported_per_attribute_and_ignorable() {
+    let left =
+        parse(br#"<root><Kea version="1.0.0" uuid="a"><x>1</x></Kea></root>"#).expect("left parse");
+    let right = parse(br#"<root><Kea version="1.0.1" extra="y"><x>1</x></Kea></root>"#)
+        .expect("right parse");
+
+    let entries = diff(&left, &right);
+    assert_eq!(
+        entries,
+        vec![
+            DiffEntry::AttributeChanged {
+                path: "root.Kea[1]@extra".to_string(),
+                name: "extra".to_string(),
+                left: None,
+                right: Some("y".to_string()),
+            },
+            DiffEntry::AttributeChanged {
+                path: "root.Kea[1]@uuid".to_string(),
+                name: "uuid".to_string(),
+                left: Some("a".to_string()),
+                right: None,
+            },
+            DiffEntry::AttributeChanged {
+                path: "root.Kea[1]@version".to_string(),
+                name: "version".to_string(),
+                left: Some("1.0.0".to_string()),
+                right: Some("1.0.1".to_string()),
+            },
+        ]
+    );
+    assert!(format_text(&entries).contains(r#"@ root.Kea[1]@version: "1.0.0" -> "1.0.1""#));
+    assert!(format_text(&entries).contains("@ root.Kea[1]@extra: (absent) -> \"y\""));
+    assert!(format_summary(&entries).ends_with("attributes=3 relocated=0"));
+
+    let opts = DiffOptions {
+        ignore_paths: vec!["@version".to_string(), "root.Kea[1]@uuid".to_string()],
+        ..DiffOptions::default()
+    };
+    let entries = diff_with_options(&left, &right, &opts);
+    assert!(matches!(
+        entries.as_slice(),
+        [DiffEntry::AttributeChanged { name, .. }] if name == "extra"
+    ));
+}
+
+#[test]
+fn relocated_nodes_pair_removal_and_addition_when_enabled() {
+    let left = parse(
+        br#"<root><aliases><alias><name>web</name><address>10.0.0.1</address></alias><alias><name>gone</name></alias></aliases><OPNsense/></root>"#,
+    )
+    .expect("left parse");
+    let right = parse(
+        br#"<root><aliases/><OPNsense><Firewall><alias><name>web</name><address>10.0.0.1</address></alias></Firewall><enable/></OPNsense></root>"#,
+    )
+    .expect("right parse");
+
+    let plain = diff(&left, &right);
+    assert!(!plain
+        .iter()
+        .any(|e| matches!(e, DiffEntry::Relocated { .. })));
+
+    let opts = DiffOptions {
+        detect_relocations: true,
+        ..DiffOptions::default()
+    };
+    let entries = diff_with_options(&left, &right, &opts);
+    assert_eq!(
+        entries,
+        vec![
+            DiffEntry::Relocated {
+                from: "root.aliases[1].alias[1]".to_string(),
+                to: "root.OPNsense[1].Firewall[1].alias[1]".to_string(),
+            },
+            DiffEntry::OnlyLeft {
+                path: "root.aliases[1].alias[2]".to_string(),
+                node: left.children[0].children[1].clone(),
+            },
+            DiffEntry::OnlyRight {
+                path: "root.OPNsense[1].Firewall[1]".to_string(),
+                node: right.children[1].children[0].clone(),
+            },
+            DiffEntry::OnlyRight {
+                path: "root.OPNsense[1].enable[1]".to_string(),
+                node: XmlNode::new("enable"),
+            },
+        ]
+    );
+    assert!(format_text(&entries).contains(
+        "> root.OPNsense[1].Firewall[1].alias[1]: relocated from root.aliases[1].alias[1]"
+    ));
+    assert!(format_summary(&entries).ends_with("relocated=1"));
+}
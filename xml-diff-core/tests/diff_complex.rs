@@ -50,3 +50,70 @@ fn key_field_matching_handles_reordered_repeated_elements() {
         .iter()
         .any(|e| matches!(e, DiffEntry::OnlyLeft { .. } | DiffEntry::OnlyRight { .. })));
 }
+
+#[test]
+fn ordered_tags_report_moves_separately_from_shifts() {
+    let rules = |trackers: &[&str]| {
+        let body: String = trackers
+            .iter()
+            .map(|t| format!("<rule><tracker>{t}</tracker><descr>r{t}</descr></rule>"))
+            .collect();
+        parse(format!("<root><filter>{body}</filter></root>").as_bytes()).expect("parse")
+    };
+    // 5 moves from the 5th slot to the last; 0 is inserted at the top,
+    // which shifts everything without moving it.
+    let left = rules(&["1", "2", "3", "4", "5", "6", "7"]);
+    let right = rules(&["0", "1", "2", "3", "4", "6", "7", "5"]);
+
+    let mut opts = DiffOptions {
+        key_fields: HashMap::from([("rule".to_string(), "tracker".to_string())]),
+        ..DiffOptions::default()
+    };
+    assert!(!diff_with_options(&left, &right, &opts)
+        .iter()
+        .any(|e| matches!(e, DiffEntry::Moved { .. })));
+
+    opts.ordered_tags.insert("rule".to_string());
+    let entries = diff_with_options(&left, &right, &opts);
+    assert_eq!(
+        entries,
+        vec![
+            DiffEntry::Moved {
+                path: "root.filter[1].rule[5]".to_string(),
+                from: 5,
+                to: 8,
+            },
+            DiffEntry::OnlyRight {
+                path: "root.filter[1].rule[0]".to_string(),
+                node: right.get_child("filter").expect("filter").children[0].clone(),
+            },
+        ]
+    );
+    assert_eq!(
+        xml_diff_core::format_text(&entries).lines().next(),
+        Some("> root.filter[1].rule[5]: moved 5 -> 8")
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_diff_matches_sequential_order() {
+    let base = |name: &str| {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("fixtures")
+            .join(name);
+        xml_diff_core::parse_file(&path).expect("fixture parse")
+    };
+    let left = base("pfsense-base.xml");
+    let right = base("opnsense-base.xml");
+    let opts = DiffOptions {
+        include_identical: true,
+        ..DiffOptions::default()
+    };
+
+    let sequential = diff_with_options(&left, &right, &opts);
+    let parallel = xml_diff_core::diff_parallel(&left, &right, &opts);
+    assert!(!sequential.is_empty());
+    assert_eq!(parallel, sequential);
+}
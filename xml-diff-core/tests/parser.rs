@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use xml_diff_core::parse_file;
+use xml_diff_core::{parse, parse_file, parse_with_options, ParseOptions, ParseOutcome};
 
 fn fixture(path: &str) -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -30,3 +30,161 @@ fn parses_real_world_roots() {
     assert_eq!(pf.tag, "pfsense");
     assert_eq!(opn.tag, "opnsense");
 }
+
+fn lenient(xml: &[u8]) -> ParseOutcome {
+    parse_with_options(
+        xml,
+        &ParseOptions {
+            lenient: true,
+            ..ParseOptions::default()
+        },
+    )
+    .expect("lenient parse")
+}
+
+#[test]
+fn strips_utf8_bom_in_both_modes() {
+    let xml = b"\xEF\xBB\xBF<?xml version=\"1.0\"?><root><a>1</a></root>";
+    assert_eq!(
+        parse(xml).expect("strict parse").get_text(&["a"]),
+        Some("1")
+    );
+    assert!(lenient(xml).warnings.is_empty());
+}
+
+#[test]
+fn lenient_reads_windows_1252_and_utf16() {
+    let cp1252 = b"<root><descr>\x93Guest\x94 Wi\xADFi</descr></root>";
+    assert!(parse(cp1252).is_err());
+    let outcome = lenient(cp1252);
+    assert_eq!(
+        outcome.root.get_text(&["descr"]),
+        Some("\u{201C}Guest\u{201D} Wi\u{AD}Fi")
+    );
+    assert_eq!(outcome.warnings.len(), 2);
+    assert!(outcome.warnings[0]
+        .message
+        .starts_with("3 byte(s) are not valid UTF-8"));
+    assert_eq!(outcome.warnings[0].path, None);
+    assert_eq!(outcome.warnings[1].path.as_deref(), Some("root.descr[1]"));
+
+    let mut utf16 = vec![0xFF, 0xFE];
+    for unit in "<root><a>ü</a></root>".encode_utf16() {
+        utf16.extend_from_slice(&unit.to_le_bytes());
+    }
+    let outcome = lenient(&utf16);
+    assert_eq!(outcome.root.get_text(&["a"]), Some("ü"));
+    assert_eq!(
+        outcome.warnings[0].message,
+        "input is UTF-16; converted to UTF-8"
+    );
+}
+
+#[test]
+fn repair_encoding_names_each_repaired_element_and_stays_strict() {
+    let opts = ParseOptions {
+        repair_encoding: true,
+        ..ParseOptions::default()
+    };
+    let xml = b"<pfsense><filter><rule><descr>a</descr></rule><rule><descr>Caf\xe9</descr></rule></filter><aliases><alias name=\"\x80\"/></aliases></pfsense>";
+    let outcome = parse_with_options(xml, &opts).expect("repairing parse");
+    assert_eq!(
+        outcome.root.get_children("filter")[0].get_children("rule")[1].get_text(&["descr"]),
+        Some("Caf\u{e9}")
+    );
+    let paths: Vec<Option<&str>> = outcome.warnings.iter().map(|w| w.path.as_deref()).collect();
+    assert_eq!(
+        paths,
+        [
+            None,
+            Some("pfsense.filter[1].rule[2].descr[1]"),
+            Some("pfsense.aliases[1].alias[1]"),
+        ]
+    );
+    assert!(outcome.warnings[2]
+        .message
+        .starts_with("start tag has 1 byte(s)"));
+    assert!(outcome.warnings[1]
+        .to_string()
+        .contains("(pfsense.filter[1].rule[2].descr[1]): text has 1 byte(s)"));
+
+    let truncated = b"<pfsense><descr>\xe9</descr>";
+    assert!(parse_with_options(truncated, &opts).is_err());
+    assert!(parse_with_options(b"<a>&bogus;</a>", &opts).is_err());
+}
+
+#[test]
+fn lenient_keeps_invalid_entities_as_text() {
+    let xml = br#"<root><descr>R&D &nbsp; &lt;lab&gt; &#233;</descr><a name="x&y"/></root>"#;
+    assert!(parse(xml).is_err());
+    let outcome = lenient(xml);
+    assert_eq!(
+        outcome.root.get_text(&["descr"]),
+        Some("R&D &nbsp; <lab> \u{e9}")
+    );
+    let a = outcome.root.get_child("a").expect("a");
+    assert_eq!(a.attributes.get("name").map(String::as_str), Some("x&y"));
+    assert_eq!(outcome.warnings.len(), 2);
+}
+
+#[test]
+fn lenient_closes_truncated_and_mismatched_elements() {
+    let truncated = b"<root><filter><rule><descr>a</descr></rule><rule><des";
+    assert!(parse(truncated).is_err());
+    let outcome = lenient(truncated);
+    let filter = outcome.root.get_child("filter").expect("filter");
+    assert_eq!(filter.get_children("rule").len(), 2);
+    assert_eq!(outcome.warnings.len(), 2, "{:?}", outcome.warnings);
+    assert!(outcome.warnings[0]
+        .message
+        .starts_with("input is truncated"));
+    assert_eq!(
+        outcome.warnings[1].message,
+        "input ends inside <root> <filter> <rule>; closed them"
+    );
+
+    let mismatched = b"<root><a><b>1</a><c/></x></root><extra/>";
+    assert!(parse(mismatched).is_err());
+    let outcome = lenient(mismatched);
+    let messages: Vec<String> = outcome.warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(outcome.root.get_text(&["a", "b"]), Some("1"));
+    assert!(outcome.root.get_child("c").is_some());
+    assert_eq!(messages.len(), 3, "{messages:?}");
+    assert!(messages[0].ends_with("<b> has no end tag; closed it at </a>"));
+    assert!(messages[1].ends_with("ignored </x> with no matching start tag"));
+    assert!(messages[2].ends_with("dropped <extra> after the root element"));
+}
+
+#[test]
+fn lenient_still_fails_without_a_root_element() {
+    let err = parse_with_options(
+        b"garbage only",
+        &ParseOptions {
+            lenient: true,
+            ..ParseOptions::default()
+        },
+    )
+    .expect_err("no root");
+    assert_eq!(err.to_string(), "malformed XML: no root element found");
+}
+
+#[test]
+fn lenient_recovers_every_truncation_of_a_real_config() {
+    let xml = std::fs::read(fixture("fixtures/simple_a.xml")).expect("read fixture");
+    let root_start = xml
+        .windows(7)
+        .position(|w| w == b"<config")
+        .expect("root start")
+        + 8;
+    for len in root_start..xml.len() {
+        let outcome = parse_with_options(
+            &xml[..len],
+            &ParseOptions {
+                lenient: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap_or_else(|err| panic!("prefix of {len} bytes: {err}"));
+        assert_eq!(outcome.root.tag, "config");
+    }
+}